@@ -0,0 +1,449 @@
+//! Casing contract checks
+//!
+//! Services call these from their tests so a camelCase field cannot slip into
+//! a DTO: [`scan_dir`] reads the sources of every `Serialize` struct and enum
+//! variant and reports the fields whose wire name is not snake_case, and
+//! [`assert_casing`] checks the JSON a value actually serializes to.
+//!
+//! ```ignore
+//! #[test]
+//! fn every_dto_is_snake_case() {
+//!     let violations = api_casing::contract::scan_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+//!     assert!(violations.is_empty(), "{:#?}", violations);
+//! }
+//! ```
+//!
+//! The scan is line based: it understands `#[derive(..Serialize..)]`,
+//! `#[serde(rename = "..")]`, `flatten`, `skip` and container `rename_all`,
+//! which is all the DTOs of this repository use. Names the conversion leaves
+//! alone (MongoDB's `_id`, `@type`) are not reported.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{is_snake, Casing, Policy};
+
+/// A field whose wire name breaks the casing contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub file: String,
+    pub line: usize,
+    /// Struct or enum the field belongs to
+    pub item: String,
+    /// Wire name of the field, or the offending `rename_all`
+    pub field: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} {}.{} is not snake_case",
+            self.file, self.line, self.item, self.field
+        )
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    skipped: bool,
+}
+
+struct Item {
+    name: String,
+    /// Set when the container renames its fields to a casing it may not
+    renames_fields: bool,
+    depth: usize,
+    opened: bool,
+}
+
+/// Violations in every `.rs` file under `dir`
+pub fn scan_dir(dir: impl AsRef<Path>) -> Vec<Violation> {
+    let mut files = Vec::new();
+    collect_sources(dir.as_ref(), &mut files);
+    files.sort();
+
+    files
+        .iter()
+        .flat_map(|path| {
+            let source = std::fs::read_to_string(path)
+                .unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err));
+            let mut violations = scan_source(&source);
+            for violation in &mut violations {
+                violation.file = path.display().to_string();
+            }
+            violations
+        })
+        .collect()
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    let entries = std::fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", dir.display(), err));
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Violations in one source file (`file` is left empty)
+pub fn scan_source(source: &str) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut serializes = false;
+    let mut rename_all: Option<String> = None;
+    let mut item: Option<Item> = None;
+    let mut attrs = FieldAttrs::default();
+
+    for (index, raw) in source.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        let Some(current) = item.as_mut() else {
+            if line.starts_with("#[derive(") {
+                serializes |= derives_serialize(line);
+            } else if line.starts_with("#[serde(") {
+                if let Some(value) = attr_value(line, "rename_all") {
+                    rename_all = Some(value);
+                }
+            } else if line.starts_with('#') {
+                // Other attributes sit between the derive and the item
+            } else if serializes && (line.contains("struct ") || line.contains("enum ")) {
+                let is_struct = line.contains("struct ");
+                let keyword = if is_struct { "struct " } else { "enum " };
+                let name: String = line[line.find(keyword).unwrap() + keyword.len()..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                let renames_fields = is_struct
+                    && rename_all
+                        .as_deref()
+                        .is_some_and(|casing| !matches!(casing, "snake_case" | "lowercase"));
+
+                if renames_fields {
+                    violations.push(Violation {
+                        file: String::new(),
+                        line: index + 1,
+                        item: name.clone(),
+                        field: format!("rename_all = \"{}\"", rename_all.as_deref().unwrap()),
+                    });
+                }
+
+                // Tuple and unit structs have no named fields
+                let is_tuple = line
+                    .find('(')
+                    .is_some_and(|paren| line.find('{').is_none_or(|brace| paren < brace));
+                if !(line.ends_with(';') || is_tuple) {
+                    let mut opened = Item {
+                        name,
+                        renames_fields,
+                        depth: 0,
+                        opened: false,
+                    };
+                    track_braces(&mut opened, line);
+                    item = (!opened.opened || opened.depth > 0).then_some(opened);
+                }
+                serializes = false;
+                rename_all = None;
+            } else {
+                serializes = false;
+                rename_all = None;
+            }
+            continue;
+        };
+
+        if line.starts_with("#[") {
+            if line.starts_with("#[serde(") {
+                if let Some(rename) = attr_value(line, "rename") {
+                    attrs.rename = Some(rename);
+                }
+                attrs.skipped |= [
+                    "flatten",
+                    "skip)",
+                    "skip,",
+                    "skip_serializing)",
+                    "skip_serializing,",
+                ]
+                .iter()
+                .any(|flag| line.contains(flag));
+            }
+            continue;
+        }
+
+        if current.opened && current.depth > 0 && !current.renames_fields {
+            let mut wire_names = Vec::new();
+            if let Some(field) = field_name(line) {
+                if !attrs.skipped {
+                    wire_names.push(attrs.rename.take().unwrap_or(field));
+                }
+            } else if let (Some(open), Some(close)) = (line.find('{'), line.rfind('}')) {
+                // One-line enum variant: `Joined { room_id: String, user_id: i64 },`
+                if open < close {
+                    wire_names.extend(line[open + 1..close].split(',').filter_map(field_name));
+                }
+            }
+
+            for wire in wire_names {
+                if !conforms(&wire) {
+                    violations.push(Violation {
+                        file: String::new(),
+                        line: index + 1,
+                        item: current.name.clone(),
+                        field: wire,
+                    });
+                }
+            }
+        }
+        attrs = FieldAttrs::default();
+
+        track_braces(current, line);
+        if current.opened && current.depth == 0 {
+            item = None;
+        }
+    }
+
+    violations
+}
+
+/// Snake case, or not an identifier the conversion would touch (`_id`)
+fn conforms(wire: &str) -> bool {
+    is_snake(wire) || !wire.starts_with(|c: char| c.is_ascii_lowercase())
+}
+
+fn derives_serialize(line: &str) -> bool {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .any(|word| word == "Serialize" || word == "serde::Serialize")
+}
+
+/// The string value of `name = "..."` in an attribute line
+fn attr_value(line: &str, name: &str) -> Option<String> {
+    let pattern = format!("{} = \"", name);
+    let mut rest = line;
+    while let Some(at) = rest.find(&pattern) {
+        // `rename` must not match inside `rename_all`
+        let preceded = rest[..at].chars().last();
+        if preceded.is_none_or(|c| !(c.is_alphanumeric() || c == '_')) {
+            let value = &rest[at + pattern.len()..];
+            return value.find('"').map(|end| value[..end].to_string());
+        }
+        rest = &rest[at + pattern.len()..];
+    }
+    None
+}
+
+/// `pub user_id: i64,` -> `user_id`; `r#type` -> `type`
+fn field_name(line: &str) -> Option<String> {
+    let mut rest = line.trim();
+    for visibility in ["pub(crate) ", "pub(super) ", "pub "] {
+        if let Some(stripped) = rest.strip_prefix(visibility) {
+            rest = stripped;
+            break;
+        }
+    }
+    let rest = rest.strip_prefix("r#").unwrap_or(rest);
+
+    let name: String = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    let after = rest[name.len()..].trim_start();
+    let is_field = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_uppercase())
+        && after.starts_with(':')
+        && !after.starts_with("::");
+    is_field.then_some(name)
+}
+
+fn track_braces(item: &mut Item, line: &str) {
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => {
+                item.depth += 1;
+                item.opened = true;
+            }
+            '}' if !in_string => item.depth = item.depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+}
+
+/// JSON paths of the keys of `value` not in `casing`, skipping the values of
+/// opaque fields and keys that are not identifiers (map keys such as ids)
+pub fn violations(value: &Value, casing: Casing, opaque: &[String]) -> Vec<String> {
+    let mut found = Vec::new();
+    walk(value, casing, opaque, "$", &mut found);
+    found
+}
+
+fn walk(value: &Value, casing: Casing, opaque: &[String], path: &str, found: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = format!("{}.{}", path, key);
+                let is_identifier = key.starts_with(|c: char| c.is_ascii_lowercase())
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if is_identifier && !casing.matches(key) {
+                    found.push(child_path.clone());
+                }
+                if !opaque.iter().any(|o| *o == crate::to_snake(key)) {
+                    walk(child, casing, opaque, &child_path, found);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                walk(item, casing, opaque, &format!("{}[{}]", path, i), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Panics unless `value` serializes with every key in `casing`, and converts
+/// to the other casing and back unchanged
+pub fn assert_casing<T: Serialize>(value: &T, casing: Casing) {
+    let opaque = &Policy::default().opaque;
+    let json = serde_json::to_value(value).expect("DTO serializes");
+
+    let found = violations(&json, casing, opaque);
+    assert!(
+        found.is_empty(),
+        "keys not in {} case: {:?}",
+        casing.as_str(),
+        found
+    );
+
+    let other = match casing {
+        Casing::Snake => Casing::Camel,
+        Casing::Camel => Casing::Snake,
+    };
+    let mut round_trip = json.clone();
+    crate::convert(&mut round_trip, other, opaque);
+    assert!(
+        violations(&round_trip, other, opaque).is_empty(),
+        "keys that do not convert to {} case: {:?}",
+        other.as_str(),
+        violations(&round_trip, other, opaque)
+    );
+    crate::convert(&mut round_trip, casing, opaque);
+    assert_eq!(round_trip, json, "keys do not convert back unchanged");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+/// A response
+#[derive(Debug, Serialize)]
+pub struct OrderResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub order_id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub r#ref: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub totalCents: i64,
+}
+
+#[derive(Deserialize)]
+pub struct Request {
+    pub userId: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Legacy {
+    pub user_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct Wrapper(pub String);
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    #[serde(rename = "system.welcome")]
+    Welcome { user_id: i64, serverTime: String },
+    Kicked {
+        #[serde(rename = "byUser")]
+        by_user: i64,
+    },
+    Ping,
+}
+"#;
+
+    #[test]
+    fn scan_reports_the_wire_names_of_serialized_fields() {
+        let found: Vec<(String, String)> = scan_source(SOURCE)
+            .into_iter()
+            .map(|v| (v.item, v.field))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                ("OrderResponse".to_string(), "createdAt".to_string()),
+                ("OrderResponse".to_string(), "totalCents".to_string()),
+                (
+                    "Legacy".to_string(),
+                    "rename_all = \"camelCase\"".to_string()
+                ),
+                ("Message".to_string(), "serverTime".to_string()),
+                ("Message".to_string(), "byUser".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn serialized_values_are_checked_below_opaque_fields_only() {
+        let value = serde_json::json!({
+            "user_id": 1,
+            "rooms": { "Room-1": { "seatCount": 2 } },
+            "metadata": { "orderRef": "x" },
+        });
+        let opaque = Policy::default().opaque;
+
+        assert_eq!(
+            violations(&value, Casing::Snake, &opaque),
+            vec!["$.rooms.Room-1.seatCount".to_string()]
+        );
+    }
+
+    #[test]
+    fn snake_case_dtos_pass_the_contract() {
+        #[derive(Serialize)]
+        struct Payout {
+            payout_id: String,
+            address_line_1: String,
+            metadata: Value,
+        }
+
+        assert_casing(
+            &Payout {
+                payout_id: "p1".to_string(),
+                address_line_1: "Main St".to_string(),
+                metadata: serde_json::json!({ "anyKey": 1 }),
+            },
+            Casing::Snake,
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Casing middleware for actix-web
+//!
+//! Applies the [`Policy::global`] to a service: camelCase JSON request bodies
+//! reach the handlers in snake_case while `API_CASING_ACCEPT_BOTH` is on, and
+//! JSON responses are rewritten to the casing the request asked for with
+//! [`HEADER`](crate::HEADER) (or the policy's default). The response echoes
+//! the casing it used. Other bodies (multipart uploads, HTML, streams of
+//! anything but JSON) pass through untouched.
+
+use actix_web::body::{self, BoxBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::{Error, HttpMessage};
+use futures_core::Stream;
+use serde_json::Value;
+use std::future::poll_fn;
+use std::pin::Pin;
+
+use crate::{convert, Casing, Policy, HEADER};
+
+/// Largest request body read for rewriting, `JsonConfig`'s default limit
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Casing middleware; wrap it outside any middleware that reads request bodies
+/// (idempotency fingerprints) or edits JSON responses (localization)
+pub async fn apply_policy<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, Error>
+where
+    B: MessageBody + 'static,
+{
+    apply(Policy::global(), request, next).await
+}
+
+/// [`apply_policy`] with an explicit policy
+pub async fn apply<B>(
+    policy: &Policy,
+    mut request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, Error>
+where
+    B: MessageBody + 'static,
+{
+    let casing = policy.output_for(request.headers().get(HEADER).and_then(|v| v.to_str().ok()));
+
+    if policy.accept_both && is_json(request.headers()) {
+        let bytes = read_body(&mut request).await?;
+        let bytes = match rewrite(&bytes, Casing::Snake, &policy.opaque) {
+            Some(rewritten) => rewritten.into(),
+            None => bytes,
+        };
+        request.set_payload(Payload::from(bytes));
+    }
+
+    let response = next.call(request).await?.map_into_boxed_body();
+    let (http_request, mut http_response) = response.into_parts();
+    http_response.headers_mut().insert(
+        HeaderName::from_static("x-api-casing"),
+        HeaderValue::from_static(casing.as_str()),
+    );
+
+    // Handlers write snake_case already
+    if casing == Casing::Snake || !is_json(http_response.headers()) {
+        return Ok(ServiceResponse::new(http_request, http_response));
+    }
+
+    let (http_response, response_body) = http_response.into_parts();
+    let bytes = body::to_bytes(response_body)
+        .await
+        .map_err(|_| actix_web::error::ErrorInternalServerError("Failed to read response body"))?;
+    let bytes = match rewrite(&bytes, casing, &policy.opaque) {
+        Some(rewritten) => rewritten.into(),
+        None => bytes,
+    };
+
+    Ok(ServiceResponse::new(
+        http_request,
+        http_response.set_body(BoxBody::new(bytes)),
+    ))
+}
+
+/// The whole request body, up to the `JsonConfig` default limit
+async fn read_body(request: &mut ServiceRequest) -> Result<Bytes, Error> {
+    let mut payload = request.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = poll_fn(|cx| Pin::new(&mut payload).poll_next(cx)).await {
+        body.extend_from_slice(&chunk?);
+        if body.len() > MAX_BODY_BYTES {
+            return Err(actix_web::error::ErrorPayloadTooLarge(
+                "JSON payload is too large",
+            ));
+        }
+    }
+    Ok(body.freeze())
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"))
+}
+
+/// The body with its keys in `casing`; None when it is not JSON or already
+/// cased, so signed bodies (Stripe webhooks) keep their exact bytes
+fn rewrite(bytes: &[u8], casing: Casing, opaque: &[String]) -> Option<Vec<u8>> {
+    if bytes.is_empty() {
+        return None;
+    }
+    let original: Value = serde_json::from_slice(bytes).ok()?;
+    let mut value = original.clone();
+    convert(&mut value, casing, opaque);
+    if value == original {
+        return None;
+    }
+    serde_json::to_vec(&value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, web, App, HttpResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Order {
+        order_id: String,
+        unit_price: i64,
+    }
+
+    async fn echo(body: web::Json<Order>) -> HttpResponse {
+        HttpResponse::Ok().json(body.into_inner())
+    }
+
+    #[actix_web::test]
+    async fn bodies_are_accepted_and_returned_in_either_casing() {
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(apply_policy))
+                .route("/orders", web::post().to(echo)),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/orders")
+            .insert_header((HEADER, "camel"))
+            .set_json(serde_json::json!({ "orderId": "o1", "unitPrice": 5 }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert!(response.status().is_success());
+        assert_eq!(response.headers().get(HEADER).unwrap(), "camel");
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body, serde_json::json!({ "orderId": "o1", "unitPrice": 5 }));
+
+        let request = test::TestRequest::post()
+            .uri("/orders")
+            .set_json(serde_json::json!({ "order_id": "o2", "unitPrice": 7 }))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, request).await;
+        assert_eq!(
+            body,
+            serde_json::json!({ "order_id": "o2", "unit_price": 7 })
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! API field casing
+//!
+//! Every DTO of blazing_sun, checkout and the gateway protocol is written in
+//! snake_case, which is the casing on the wire unless a [`Policy`] says
+//! otherwise. Clients that want camelCase ask for it with `API_CASING=camel`
+//! (every response of a service) or per request with the [`HEADER`]:
+//!
+//! ```text
+//! API_CASING=snake                  # snake | camel, the default output
+//! API_CASING_ACCEPT_BOTH=true       # also accept camelCase request bodies
+//! API_CASING_OPAQUE_FIELDS=metadata,payload
+//! ```
+//!
+//! While clients migrate, `API_CASING_ACCEPT_BOTH` rewrites camelCase keys of
+//! JSON request bodies to snake_case so handlers keep one shape. The values of
+//! opaque fields (user metadata, theme variables, stored JSON documents) are
+//! data rather than DTO fields and are never rewritten.
+//!
+//! [`contract`] checks that DTO sources stay snake_case, so the conversion
+//! has one well-defined input. The `actix` feature adds the middleware
+//! ([`http::apply_policy`]).
+
+pub mod contract;
+#[cfg(feature = "actix")]
+pub mod http;
+
+use std::sync::OnceLock;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Request header choosing the response casing (`snake` or `camel`); the
+/// response echoes the casing it used
+pub const HEADER: &str = "X-Api-Casing";
+
+const CASING_VAR: &str = "API_CASING";
+const ACCEPT_BOTH_VAR: &str = "API_CASING_ACCEPT_BOTH";
+const OPAQUE_VAR: &str = "API_CASING_OPAQUE_FIELDS";
+
+/// Fields whose values are free-form documents rather than DTOs
+pub const DEFAULT_OPAQUE_FIELDS: &[&str] = &[
+    "metadata",
+    "payload",
+    "preferences",
+    "translations",
+    "schema_data",
+    "structured_data",
+    "custom_meta",
+    "theme_light",
+    "theme_dark",
+    "effective_light",
+    "effective_dark",
+    "scss_variables",
+];
+
+/// Casing of JSON object keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Casing {
+    #[default]
+    Snake,
+    Camel,
+}
+
+impl Casing {
+    /// `snake`/`snake_case` or `camel`/`camelCase`, case-insensitively
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "snake" | "snake_case" => Some(Self::Snake),
+            "camel" | "camelcase" => Some(Self::Camel),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Snake => "snake",
+            Self::Camel => "camel",
+        }
+    }
+
+    /// `key` in this casing; keys that are not identifiers pass through
+    pub fn key(self, key: &str) -> String {
+        match self {
+            Self::Snake => to_snake(key),
+            Self::Camel => to_camel(key),
+        }
+    }
+
+    /// Whether `key` is already in this casing
+    pub fn matches(self, key: &str) -> bool {
+        match self {
+            Self::Snake => is_snake(key),
+            Self::Camel => is_camel(key),
+        }
+    }
+}
+
+/// `user_id` -> `userId`. Only snake_case identifiers change; a segment
+/// starting with a digit keeps its underscore (`line_1` -> `line_1`) so the
+/// key converts back unchanged.
+pub fn to_camel(key: &str) -> String {
+    if !is_snake(key) || !key.contains('_') {
+        return key.to_string();
+    }
+
+    let mut out = String::with_capacity(key.len());
+    for (i, segment) in key.split('_').enumerate() {
+        let mut chars = segment.chars();
+        match chars.next() {
+            Some(first) if i > 0 && first.is_ascii_digit() => {
+                out.push('_');
+                out.push_str(segment);
+            }
+            Some(first) if i > 0 => {
+                out.push(first.to_ascii_uppercase());
+                out.extend(chars);
+            }
+            _ => out.push_str(segment),
+        }
+    }
+    out
+}
+
+/// `userId` -> `user_id`, `avatarURL` -> `avatar_url`. Only identifiers
+/// starting with a lowercase letter change; everything else (`@type`,
+/// `Content-Type`, numeric map keys) passes through.
+pub fn to_snake(key: &str) -> String {
+    let mut chars = key.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid || !key.bytes().any(|b| b.is_ascii_uppercase()) {
+        return key.to_string();
+    }
+
+    let chars: Vec<char> = key.chars().collect();
+    let mut out = String::with_capacity(key.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            let starts_word = prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower);
+            if starts_word {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// `user_id`, `p99_ms`, `line_1`
+pub fn is_snake(key: &str) -> bool {
+    !key.is_empty()
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key.split('_').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        })
+}
+
+/// `userId`, `p99Ms`, `id`, `addressLine_1`
+pub fn is_camel(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key.split('_').enumerate().all(|(i, segment)| {
+            segment.bytes().all(|b| b.is_ascii_alphanumeric())
+                && (i == 0 || segment.starts_with(|c: char| c.is_ascii_digit()))
+        })
+}
+
+/// Rewrite the keys of every object in `value`, leaving the values of
+/// `opaque` fields (named in snake_case) as they are. A converted key never
+/// replaces one the object already has.
+pub fn convert<S: AsRef<str>>(value: &mut Value, casing: Casing, opaque: &[S]) {
+    match value {
+        Value::Object(map) => {
+            // Keys already in the casing go first so they win over converted ones
+            let (keep, rename): (Vec<_>, Vec<_>) = std::mem::take(map)
+                .into_iter()
+                .partition(|(key, _)| casing.key(key) == *key);
+
+            for (key, mut child) in keep.into_iter().chain(rename) {
+                let snake = to_snake(&key);
+                if !opaque.iter().any(|o| o.as_ref() == snake) {
+                    convert(&mut child, casing, opaque);
+                }
+                let converted = casing.key(&key);
+                if !map.contains_key(&converted) {
+                    map.insert(converted, child);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                convert(item, casing, opaque);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// How a service cases its JSON
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// Response casing when the request does not ask for one
+    pub output: Casing,
+    /// Rewrite camelCase request bodies to snake_case
+    pub accept_both: bool,
+    /// Fields whose values are never rewritten
+    pub opaque: Vec<String>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            output: Casing::Snake,
+            accept_both: true,
+            opaque: DEFAULT_OPAQUE_FIELDS
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Policy {
+    /// `API_CASING`, `API_CASING_ACCEPT_BOTH` and `API_CASING_OPAQUE_FIELDS`,
+    /// falling back to the defaults for unset or unparsable values
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Some(casing) = std::env::var(CASING_VAR)
+            .ok()
+            .as_deref()
+            .and_then(Casing::parse)
+        {
+            policy.output = casing;
+        }
+        if let Ok(accept_both) = std::env::var(ACCEPT_BOTH_VAR) {
+            policy.accept_both = !matches!(
+                accept_both.trim().to_ascii_lowercase().as_str(),
+                "0" | "false" | "off" | "no"
+            );
+        }
+        if let Ok(fields) = std::env::var(OPAQUE_VAR) {
+            policy.opaque = fields
+                .split(',')
+                .map(|f| to_snake(f.trim()))
+                .filter(|f| !f.is_empty())
+                .collect();
+        }
+        policy
+    }
+
+    /// The policy from the environment, read once
+    pub fn global() -> &'static Policy {
+        static POLICY: OnceLock<Policy> = OnceLock::new();
+        POLICY.get_or_init(Self::from_env)
+    }
+
+    /// Casing of a response, given the request's [`HEADER`]
+    pub fn output_for(&self, requested: Option<&str>) -> Casing {
+        requested.and_then(Casing::parse).unwrap_or(self.output)
+    }
+}
+
+/// Deserializes `T` from snake_case or camelCase input, for bodies read
+/// outside the middleware (queue payloads, webhooks of other services)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnyCase<T>(pub T);
+
+impl<T> AnyCase<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for AnyCase<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = Value::deserialize(deserializer)?;
+        convert(&mut value, Casing::Snake, &Policy::global().opaque);
+        serde_json::from_value(value)
+            .map(AnyCase)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes `T` in a chosen casing, for JSON written outside the middleware
+pub struct Cased<'a, T>(pub &'a T, pub Casing);
+
+impl<T: Serialize> Serialize for Cased<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serde_json::to_value(self.0).map_err(serde::ser::Error::custom)?;
+        if self.1 != Casing::Snake {
+            convert(&mut value, self.1, &Policy::global().opaque);
+        }
+        value.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keys_convert_both_ways() {
+        for (snake, camel) in [
+            ("user_id", "userId"),
+            ("id", "id"),
+            ("p99_latency_ms", "p99LatencyMs"),
+            ("address_line_1", "addressLine_1"),
+            ("sha256", "sha256"),
+        ] {
+            assert_eq!(to_camel(snake), camel);
+            assert_eq!(to_snake(camel), snake);
+        }
+
+        assert_eq!(to_snake("avatarURL"), "avatar_url");
+        assert_eq!(to_snake("htmlURLValue"), "html_url_value");
+        for untouched in ["@type", "Content-Type", "42", "en-US", "a.b", ""] {
+            assert_eq!(to_snake(untouched), untouched);
+            assert_eq!(to_camel(untouched), untouched);
+        }
+    }
+
+    #[test]
+    fn conversion_skips_opaque_values_and_existing_keys() {
+        let mut value = json!({
+            "user_id": 1,
+            "items": [{ "unit_price": 5 }],
+            "metadata": { "order_ref": "x" },
+        });
+        convert(&mut value, Casing::Camel, DEFAULT_OPAQUE_FIELDS);
+        assert_eq!(
+            value,
+            json!({
+                "userId": 1,
+                "items": [{ "unitPrice": 5 }],
+                "metadata": { "order_ref": "x" },
+            })
+        );
+
+        let mut mixed = json!({ "userId": 1, "user_id": 2, "customMeta": { "pageTitle": "t" } });
+        convert(&mut mixed, Casing::Snake, DEFAULT_OPAQUE_FIELDS);
+        assert_eq!(
+            mixed,
+            json!({ "user_id": 2, "custom_meta": { "pageTitle": "t" } })
+        );
+    }
+
+    #[test]
+    fn requests_choose_their_casing() {
+        let policy = Policy::default();
+        assert_eq!(policy.output_for(None), Casing::Snake);
+        assert_eq!(policy.output_for(Some("camelCase")), Casing::Camel);
+        assert_eq!(policy.output_for(Some("kebab")), Casing::Snake);
+    }
+
+    #[test]
+    fn wrappers_read_and_write_either_casing() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Refund {
+            order_id: String,
+            amount_cents: i64,
+        }
+
+        let AnyCase(refund) =
+            serde_json::from_str::<AnyCase<Refund>>(r#"{"orderId":"o1","amount_cents":5}"#)
+                .unwrap();
+        assert_eq!(
+            refund,
+            Refund {
+                order_id: "o1".to_string(),
+                amount_cents: 5
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(Cased(&refund, Casing::Camel)).unwrap(),
+            json!({ "orderId": "o1", "amountCents": 5 })
+        );
+    }
+}
@@ -0,0 +1,38 @@
+-- Create processed_events and balance_ledger tables
+-- processed_events deduplicates Kafka deliveries so a redelivered event
+-- (consumer rebalance, retry after crash) is applied exactly once.
+-- balance_ledger records every balance change with its source event.
+
+CREATE TABLE IF NOT EXISTS processed_events (
+    id BIGSERIAL PRIMARY KEY,
+    topic VARCHAR(255) NOT NULL,
+    request_id VARCHAR(255) NOT NULL,
+    processed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT unique_processed_event UNIQUE (topic, request_id)
+);
+
+CREATE INDEX idx_processed_events_processed_at ON processed_events(processed_at);
+
+CREATE TABLE IF NOT EXISTS balance_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    amount_cents BIGINT NOT NULL,
+    balance_after BIGINT NOT NULL,
+    source VARCHAR(64) NOT NULL,
+    reference_id VARCHAR(255),
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_balance_ledger_user FOREIGN KEY (user_id)
+        REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_balance_ledger_user_created ON balance_ledger(user_id, created_at DESC);
+CREATE INDEX idx_balance_ledger_reference ON balance_ledger(reference_id);
+
+COMMENT ON TABLE processed_events IS 'Kafka events already applied (exactly-once processing)';
+COMMENT ON COLUMN processed_events.request_id IS 'Idempotency key of the event (e.g. checkout request_id)';
+COMMENT ON TABLE balance_ledger IS 'Append-only log of user balance changes';
+COMMENT ON COLUMN balance_ledger.balance_after IS 'User balance (cents) after this entry was applied';
+COMMENT ON COLUMN balance_ledger.source IS 'Origin of the change (checkout, bigger_dice, roulette, admin, ...)';
@@ -0,0 +1,79 @@
+-- Create game_room_tombstones table
+-- When a room is deleted or deactivated (finished/abandoned) a short-lived
+-- tombstone is kept so late commands get a definitive "room_gone" answer
+-- instead of a generic "room not found". Expired tombstones are purged by the
+-- game_room_retention cron job.
+
+CREATE TABLE IF NOT EXISTS game_room_tombstones (
+    room_id VARCHAR(64) PRIMARY KEY,
+    room_name VARCHAR(255) NOT NULL,
+    game_type VARCHAR(50) NOT NULL,
+    reason VARCHAR(32) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL DEFAULT NOW() + INTERVAL '15 minutes',
+
+    CONSTRAINT check_tombstone_reason CHECK (reason IN ('deleted', 'finished', 'deactivated'))
+);
+
+CREATE INDEX idx_game_room_tombstones_expires ON game_room_tombstones(expires_at);
+
+-- Record a tombstone whenever a room goes away
+CREATE OR REPLACE FUNCTION trigger_game_room_tombstone()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO game_room_tombstones (room_id, room_name, game_type, reason)
+        VALUES (OLD.room_id, OLD.room_name, OLD.game_type, 'deleted')
+        ON CONFLICT (room_id) DO UPDATE SET
+            reason = 'deleted',
+            created_at = NOW(),
+            expires_at = NOW() + INTERVAL '15 minutes';
+        RETURN OLD;
+    END IF;
+
+    IF OLD.is_active AND NOT NEW.is_active THEN
+        INSERT INTO game_room_tombstones (room_id, room_name, game_type, reason)
+        VALUES (
+            NEW.room_id,
+            NEW.room_name,
+            NEW.game_type,
+            CASE WHEN NEW.status = 'finished' THEN 'finished' ELSE 'deactivated' END
+        )
+        ON CONFLICT (room_id) DO UPDATE SET
+            reason = EXCLUDED.reason,
+            created_at = NOW(),
+            expires_at = NOW() + INTERVAL '15 minutes';
+    ELSIF NOT OLD.is_active AND NEW.is_active THEN
+        -- Room was reactivated; it is no longer gone
+        DELETE FROM game_room_tombstones WHERE room_id = NEW.room_id;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER game_room_tombstone_on_delete
+    AFTER DELETE ON game_rooms
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_game_room_tombstone();
+
+CREATE TRIGGER game_room_tombstone_on_deactivate
+    AFTER UPDATE OF is_active ON game_rooms
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_game_room_tombstone();
+
+-- Purge expired tombstones (for retention cron job)
+CREATE OR REPLACE FUNCTION sp_purge_expired_game_room_tombstones()
+RETURNS INTEGER AS $$
+DECLARE
+    v_deleted INTEGER;
+BEGIN
+    DELETE FROM game_room_tombstones WHERE expires_at < NOW();
+
+    GET DIAGNOSTICS v_deleted = ROW_COUNT;
+    RETURN v_deleted;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON TABLE game_room_tombstones IS 'Short-lived markers for deleted/finished rooms (late command handling)';
+COMMENT ON COLUMN game_room_tombstones.reason IS 'deleted, finished or deactivated';
@@ -0,0 +1,41 @@
+-- Create feature_flags table
+-- Runtime toggles for gradual rollouts (new games, Kafka checkout flow).
+-- Evaluation order: disabled -> off; user in allowlist -> on;
+-- otherwise on when the user's stable bucket (0-99) < rollout_percentage.
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+    id BIGSERIAL PRIMARY KEY,
+    key VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    rollout_percentage SMALLINT NOT NULL DEFAULT 0,
+    allowlist BIGINT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT check_rollout_percentage CHECK (rollout_percentage BETWEEN 0 AND 100),
+    CONSTRAINT check_feature_flag_key CHECK (key ~ '^[a-z0-9_.]+$')
+);
+
+CREATE OR REPLACE FUNCTION trigger_update_feature_flag_timestamp()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_feature_flag_timestamp
+    BEFORE UPDATE ON feature_flags
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_update_feature_flag_timestamp();
+
+-- Seed flags used by the application (disabled by default)
+INSERT INTO feature_flags (key, description, enabled, rollout_percentage)
+VALUES ('checkout_kafka_flow', 'Create balance checkout sessions through the Kafka checkout flow', FALSE, 0)
+ON CONFLICT (key) DO NOTHING;
+
+COMMENT ON TABLE feature_flags IS 'Runtime feature toggles with percentage rollouts and user allowlists';
+COMMENT ON COLUMN feature_flags.key IS 'Flag identifier, e.g. checkout_kafka_flow or game.tic_tac_toe';
+COMMENT ON COLUMN feature_flags.rollout_percentage IS 'Share of users (0-100) that get the feature';
+COMMENT ON COLUMN feature_flags.allowlist IS 'User IDs that always get the feature while enabled';
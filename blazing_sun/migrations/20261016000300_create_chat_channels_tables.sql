@@ -0,0 +1,51 @@
+-- Create chat_channels and chat_channel_members tables
+-- Persistent lobby/global chat channels that exist independently of game rooms.
+-- Channel definitions and membership live in PostgreSQL; messages are stored
+-- in the MongoDB `channel_messages` collection.
+
+CREATE TABLE IF NOT EXISTS chat_channels (
+    id BIGSERIAL PRIMARY KEY,
+    slug VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT check_chat_channel_slug CHECK (slug ~ '^[a-z0-9_-]+$')
+);
+
+CREATE TABLE IF NOT EXISTS chat_channel_members (
+    channel_id BIGINT NOT NULL REFERENCES chat_channels(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_read_message_id VARCHAR(24),
+    last_read_at TIMESTAMPTZ,
+
+    PRIMARY KEY (channel_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_channel_members_user ON chat_channel_members(user_id);
+
+CREATE OR REPLACE FUNCTION trigger_update_chat_channel_timestamp()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_chat_channel_timestamp
+    BEFORE UPDATE ON chat_channels
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_update_chat_channel_timestamp();
+
+-- Seed the default global channel
+INSERT INTO chat_channels (slug, name, description)
+VALUES ('general', 'General', 'Global chat for everyone')
+ON CONFLICT (slug) DO NOTHING;
+
+COMMENT ON TABLE chat_channels IS 'Persistent lobby/global chat channels (messages stored in MongoDB)';
+COMMENT ON TABLE chat_channel_members IS 'Channel membership with per-user read markers';
+COMMENT ON COLUMN chat_channel_members.last_read_message_id IS 'MongoDB ObjectId (hex) of the last message the user has read';
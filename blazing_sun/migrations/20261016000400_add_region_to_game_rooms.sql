@@ -0,0 +1,30 @@
+-- Add region routing to game_rooms
+-- In multi-region deployments every room is owned by one region: its commands
+-- go to that region's games topics and its players connect to that region's
+-- gateway. Single-region deployments keep everything in 'default'.
+
+ALTER TABLE game_rooms
+    ADD COLUMN IF NOT EXISTS region VARCHAR(32) NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_game_rooms_region_status
+    ON game_rooms(region, status)
+    WHERE is_active = TRUE;
+
+-- History of waiting rooms moved between regions
+CREATE TABLE IF NOT EXISTS game_room_region_migrations (
+    id BIGSERIAL PRIMARY KEY,
+    room_id VARCHAR(64) NOT NULL,
+    from_region VARCHAR(32) NOT NULL,
+    to_region VARCHAR(32) NOT NULL,
+    trigger VARCHAR(16) NOT NULL,
+    requested_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT check_region_migration_trigger CHECK (trigger IN ('admin', 'drain'))
+);
+
+CREATE INDEX idx_game_room_region_migrations_room ON game_room_region_migrations(room_id);
+
+COMMENT ON COLUMN game_rooms.region IS 'Region that owns the room (topic set and gateway)';
+COMMENT ON TABLE game_room_region_migrations IS 'Audit trail of waiting rooms moved to another region';
+COMMENT ON COLUMN game_room_region_migrations.trigger IS 'admin (manual) or drain (region being drained)';
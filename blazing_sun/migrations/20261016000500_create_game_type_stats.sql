@@ -0,0 +1,20 @@
+-- Create game_type_stats table
+-- Public per-game-type statistics, recomputed hourly by the game_type_stats cron job
+-- from MongoDB game history (games, durations, dice rolls) and game_rooms
+-- (finished / abandoned rooms, disconnect forfeits).
+
+CREATE TABLE IF NOT EXISTS game_type_stats (
+    game_type VARCHAR(50) PRIMARY KEY,
+    total_games BIGINT NOT NULL DEFAULT 0,
+    avg_duration_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+    finished_rooms BIGINT NOT NULL DEFAULT 0,
+    disconnect_forfeits BIGINT NOT NULL DEFAULT 0,
+    abandoned_rooms BIGINT NOT NULL DEFAULT 0,
+    dice_distribution JSONB,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE game_type_stats IS 'Aggregated public statistics per game type';
+COMMENT ON COLUMN game_type_stats.total_games IS 'Games recorded in MongoDB game history';
+COMMENT ON COLUMN game_type_stats.disconnect_forfeits IS 'Finished rooms where a player was removed after a disconnect timeout';
+COMMENT ON COLUMN game_type_stats.dice_distribution IS 'Observed die roll distribution with chi-square fairness check (dice games only)';
@@ -0,0 +1,20 @@
+-- Create tenant_themes table
+-- Per-tenant overrides of the whitelisted theme CSS custom properties.
+-- Tenants without a row (or with empty overrides) use the platform default theme.
+
+CREATE TABLE IF NOT EXISTS tenant_themes (
+    tenant_key VARCHAR(64) PRIMARY KEY,
+    theme_light JSONB NOT NULL DEFAULT '{}'::jsonb,
+    theme_dark JSONB NOT NULL DEFAULT '{}'::jsonb,
+    version INTEGER NOT NULL DEFAULT 1,
+    stylesheet VARCHAR(255) NOT NULL,
+    updated_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE tenant_themes IS 'Tenant-scoped theme variable overrides';
+COMMENT ON COLUMN tenant_themes.theme_light IS 'Light theme CSS custom property overrides (whitelisted names only)';
+COMMENT ON COLUMN tenant_themes.theme_dark IS 'Dark theme CSS custom property overrides (whitelisted names only)';
+COMMENT ON COLUMN tenant_themes.version IS 'Incremented on every change to the overrides';
+COMMENT ON COLUMN tenant_themes.stylesheet IS 'Content-hashed file name of the built tenant bundle';
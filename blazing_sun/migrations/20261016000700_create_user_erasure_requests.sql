@@ -0,0 +1,34 @@
+-- Create user_erasure_requests table
+-- GDPR erasure workflow: a user requests deletion of their account, which stays
+-- soft-deleted for a grace period (cancellable). When the grace period ends the
+-- user_erasure cron queues an erase_user MQ job that anonymizes the user's PII
+-- in Postgres and MongoDB and publishes user.deleted.
+--
+-- Status flow: pending -> queued -> completed | failed
+--              pending -> cancelled
+
+CREATE TABLE IF NOT EXISTS user_erasure_requests (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'queued', 'completed', 'failed', 'cancelled')),
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    scheduled_for TIMESTAMPTZ NOT NULL,
+    cancelled_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    error TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- At most one open request per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_erasure_requests_open
+    ON user_erasure_requests(user_id)
+    WHERE status IN ('pending', 'queued');
+
+CREATE INDEX IF NOT EXISTS idx_user_erasure_requests_due
+    ON user_erasure_requests(scheduled_for)
+    WHERE status = 'pending';
+
+COMMENT ON TABLE user_erasure_requests IS 'Account deletion (GDPR erasure) requests';
+COMMENT ON COLUMN user_erasure_requests.scheduled_for IS 'End of the grace period; erasure runs after this';
+COMMENT ON COLUMN user_erasure_requests.error IS 'Last failure reported by the erase_user job';
@@ -0,0 +1,45 @@
+-- Game room configuration presets
+-- Named room setups per game type, offered by GET /api/v1/games/{game_type}/room-presets
+-- and accepted by the create_room command ("preset"). Values are validated
+-- against the game type's room constraints (app/games/room_config.rs) when a
+-- room is created, so a preset can never produce an invalid room.
+
+CREATE TABLE IF NOT EXISTS game_room_presets (
+    id BIGSERIAL PRIMARY KEY,
+    game_type VARCHAR(50) NOT NULL,
+    preset_key VARCHAR(50) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    player_count INTEGER NOT NULL,
+    allow_spectators BOOLEAN NOT NULL DEFAULT TRUE,
+    max_spectators INTEGER NOT NULL DEFAULT 10,
+    turn_timer_seconds INTEGER,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT unique_game_room_preset UNIQUE (game_type, preset_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_game_room_presets_game_type
+    ON game_room_presets(game_type, sort_order)
+    WHERE is_active = TRUE;
+
+-- Per-room turn timer (NULL = game default, or no timer for games without one)
+ALTER TABLE game_rooms ADD COLUMN IF NOT EXISTS turn_timer_seconds INTEGER;
+
+INSERT INTO game_room_presets
+    (game_type, preset_key, name, description, player_count, allow_spectators, max_spectators, turn_timer_seconds, sort_order)
+VALUES
+    ('bigger_dice', 'duel', 'Duel', 'One on one, spectators welcome', 2, TRUE, 10, NULL, 1),
+    ('bigger_dice', 'party', 'Party', 'Six players rolling for the lead', 6, TRUE, 10, NULL, 2),
+    ('bigger_dice', 'showdown', 'Showdown', 'Full table of ten, no spectators', 10, FALSE, 0, NULL, 3),
+    ('tic_tac_toe', 'classic', 'Classic', 'Standard 60 second turns', 2, TRUE, 10, 60, 1),
+    ('tic_tac_toe', 'blitz', 'Blitz', 'Fast 15 second turns', 2, TRUE, 10, 15, 2),
+    ('tic_tac_toe', 'relaxed', 'Relaxed', 'Three minutes per move, private match', 2, FALSE, 0, 180, 3)
+ON CONFLICT (game_type, preset_key) DO NOTHING;
+
+COMMENT ON TABLE game_room_presets IS 'Named room configurations per game type';
+COMMENT ON COLUMN game_room_presets.turn_timer_seconds IS 'Seconds per move; NULL for games without a turn timer';
+COMMENT ON COLUMN game_rooms.turn_timer_seconds IS 'Seconds per move chosen at creation; NULL = game default';
@@ -0,0 +1,5 @@
+-- Room lists page with keyset cursors on (created_at, id) within a game type;
+-- the partial index covers only the rooms a list can ever show
+CREATE INDEX IF NOT EXISTS idx_game_rooms_active_type_created_id
+    ON game_rooms(game_type, created_at DESC, id DESC)
+    WHERE is_active = TRUE AND status IN ('waiting', 'in_progress');
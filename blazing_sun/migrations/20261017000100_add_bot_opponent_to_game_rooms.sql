@@ -0,0 +1,16 @@
+-- Practice rooms against a bot opponent
+-- bot_difficulty is set only for vs_bot rooms; the bot plays as the account
+-- configured in GAME_BOT_USER_ID
+
+ALTER TABLE game_rooms
+    ADD COLUMN IF NOT EXISTS vs_bot BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS bot_difficulty VARCHAR(16);
+
+ALTER TABLE game_rooms
+    ADD CONSTRAINT check_game_rooms_bot_difficulty CHECK (
+        (vs_bot = FALSE AND bot_difficulty IS NULL)
+        OR (vs_bot = TRUE AND bot_difficulty IN ('easy', 'medium', 'hard'))
+    );
+
+COMMENT ON COLUMN game_rooms.vs_bot IS 'Practice room where a bot takes the second seat';
+COMMENT ON COLUMN game_rooms.bot_difficulty IS 'easy, medium or hard (vs_bot rooms only)';
@@ -0,0 +1,13 @@
+-- Create user_preferences table
+-- Per-user settings that are not part of the core users row. `locale` selects
+-- the language of API and WebSocket messages; it is carried in the JWT so
+-- services can localize responses without a lookup.
+
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    locale VARCHAR(10),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE user_preferences IS 'Per-user settings (message locale, ...)';
+COMMENT ON COLUMN user_preferences.locale IS 'Preferred message locale code (en, sr); NULL follows Accept-Language';
@@ -0,0 +1,29 @@
+-- Create game_predictions table
+-- Spectator predictions on running games. The stake is debited when the
+-- prediction is made and stays `escrowed` until the game ends; settlement
+-- moves it to won/lost/refunded and records the payout. Every debit and
+-- credit also lands in balance_ledger (reference `prediction:<id>`).
+
+CREATE TABLE IF NOT EXISTS game_predictions (
+    id BIGSERIAL PRIMARY KEY,
+    room_id VARCHAR(64) NOT NULL,
+    game_type VARCHAR(50) NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    predicted_winner_id BIGINT NOT NULL,
+    stake_cents BIGINT NOT NULL CHECK (stake_cents > 0),
+    status VARCHAR(16) NOT NULL DEFAULT 'escrowed'
+        CHECK (status IN ('escrowed', 'won', 'lost', 'refunded')),
+    payout_cents BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    settled_at TIMESTAMPTZ,
+
+    CONSTRAINT unique_game_prediction UNIQUE (room_id, user_id)
+);
+
+CREATE INDEX idx_game_predictions_escrowed ON game_predictions(room_id, created_at)
+    WHERE status = 'escrowed';
+CREATE INDEX idx_game_predictions_user ON game_predictions(user_id, created_at DESC);
+
+COMMENT ON TABLE game_predictions IS 'Spectator winner predictions with escrowed coin stakes';
+COMMENT ON COLUMN game_predictions.status IS 'escrowed until the game ends, then won, lost or refunded';
+COMMENT ON COLUMN game_predictions.payout_cents IS 'Amount credited back on settlement (stake included)';
@@ -0,0 +1,43 @@
+-- Create game_webhooks and game_webhook_deliveries tables
+-- Admin-registered URLs notified about room lifecycle events (room_created,
+-- game_started, game_over). Each notification is a delivery row that the
+-- game_webhooks cron hands to the MQ; failed attempts are rescheduled with
+-- exponential backoff through next_attempt_at.
+
+CREATE TABLE IF NOT EXISTS game_webhooks (
+    id BIGSERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret VARCHAR(128) NOT NULL,
+    events TEXT[] NOT NULL DEFAULT ARRAY['room_created', 'game_started', 'game_over'],
+    description VARCHAR(255),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS game_webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id BIGINT NOT NULL REFERENCES game_webhooks(id) ON DELETE CASCADE,
+    event_type VARCHAR(32) NOT NULL,
+    room_id VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'queued', 'retrying', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_game_webhook_deliveries_due ON game_webhook_deliveries(next_attempt_at)
+    WHERE status IN ('pending', 'queued', 'retrying');
+CREATE INDEX idx_game_webhook_deliveries_webhook ON game_webhook_deliveries(webhook_id, created_at DESC);
+
+COMMENT ON TABLE game_webhooks IS 'Outgoing webhooks for game room lifecycle events';
+COMMENT ON COLUMN game_webhooks.secret IS 'HMAC-SHA256 key used to sign deliveries (X-Webhook-Signature)';
+COMMENT ON TABLE game_webhook_deliveries IS 'Delivery log of game webhooks, one row per event and webhook';
+COMMENT ON COLUMN game_webhook_deliveries.status IS 'pending -> queued -> delivered, or retrying until failed';
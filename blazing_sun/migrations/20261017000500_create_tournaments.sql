@@ -0,0 +1,62 @@
+-- Create tournament tables
+-- Single-elimination tournaments. Entry fees are debited on registration and
+-- collected in prize_pool_cents; when the bracket is full the tournament
+-- starts and every match gets its own game room. Entry fees, refunds and
+-- prizes all land in balance_ledger (reference `tournament:<id>`).
+
+CREATE TABLE IF NOT EXISTS tournaments (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    game_type VARCHAR(50) NOT NULL,
+    bracket_size INTEGER NOT NULL CHECK (bracket_size IN (2, 4, 8, 16, 32, 64)),
+    entry_fee_cents BIGINT NOT NULL DEFAULT 0 CHECK (entry_fee_cents >= 0),
+    prize_pool_cents BIGINT NOT NULL DEFAULT 0,
+    status VARCHAR(16) NOT NULL DEFAULT 'registering'
+        CHECK (status IN ('registering', 'running', 'finished', 'cancelled')),
+    current_round INTEGER NOT NULL DEFAULT 0,
+    winner_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_tournaments_status ON tournaments(status, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS tournament_entries (
+    tournament_id BIGINT NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entry_fee_cents BIGINT NOT NULL,
+    eliminated_round INTEGER,
+    place INTEGER,
+    prize_cents BIGINT NOT NULL DEFAULT 0,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (tournament_id, user_id)
+);
+
+CREATE INDEX idx_tournament_entries_user ON tournament_entries(user_id, registered_at DESC);
+
+CREATE TABLE IF NOT EXISTS tournament_matches (
+    id BIGSERIAL PRIMARY KEY,
+    tournament_id BIGINT NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    round INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    player1_id BIGINT NOT NULL,
+    player2_id BIGINT NOT NULL,
+    room_id VARCHAR(64) UNIQUE,
+    winner_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+
+    CONSTRAINT unique_tournament_match UNIQUE (tournament_id, round, position)
+);
+
+CREATE INDEX idx_tournament_matches_without_room ON tournament_matches(tournament_id)
+    WHERE room_id IS NULL;
+
+COMMENT ON TABLE tournaments IS 'Single-elimination game tournaments';
+COMMENT ON COLUMN tournaments.current_round IS '0 while registering, then the round being played';
+COMMENT ON COLUMN tournament_entries.place IS '1 champion, 2 runner-up, 3 semi-final losers, 5 quarter-final losers, ...';
+COMMENT ON COLUMN tournament_matches.room_id IS 'Game room of the match; NULL until the room is created';
@@ -0,0 +1,32 @@
+-- Create balance_adjustments table
+-- Manual coin grants (positive amount) and revocations (negative amount) made
+-- by support. An adjustment is requested by one super admin and only applied
+-- to the balance once a different super admin approves it; the approval
+-- writes the balance_ledger entry (source 'admin_adjustment').
+
+CREATE TABLE IF NOT EXISTS balance_adjustments (
+    id BIGSERIAL PRIMARY KEY,
+    batch_id UUID NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    amount_cents BIGINT NOT NULL CHECK (amount_cents <> 0),
+    reason TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'executed', 'rejected')),
+    requested_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    review_note TEXT,
+    balance_after BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reviewed_at TIMESTAMPTZ,
+    CONSTRAINT balance_adjustments_second_reviewer
+        CHECK (status <> 'executed' OR reviewed_by IS DISTINCT FROM requested_by)
+);
+
+CREATE INDEX idx_balance_adjustments_keyset ON balance_adjustments(created_at DESC, id DESC);
+CREATE INDEX idx_balance_adjustments_user ON balance_adjustments(user_id, created_at DESC);
+CREATE INDEX idx_balance_adjustments_pending ON balance_adjustments(created_at)
+    WHERE status = 'pending';
+
+COMMENT ON TABLE balance_adjustments IS 'Admin balance grants and revocations with two-person approval';
+COMMENT ON COLUMN balance_adjustments.batch_id IS 'Adjustments requested together share a batch';
+COMMENT ON COLUMN balance_adjustments.status IS 'pending -> executed (approved) or rejected';
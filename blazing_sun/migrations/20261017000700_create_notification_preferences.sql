@@ -0,0 +1,18 @@
+-- Create notification_preferences table
+-- How each user wants to hear about each notification category. A missing
+-- row means the category's default channel (websocket), so only changed
+-- categories are stored.
+
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    category VARCHAR(32) NOT NULL
+        CHECK (category IN ('payments', 'game_invites', 'chat_mentions')),
+    channel VARCHAR(16) NOT NULL
+        CHECK (channel IN ('websocket', 'email', 'none')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, category)
+);
+
+COMMENT ON TABLE notification_preferences IS 'Per-user delivery channel for each notification category';
+COMMENT ON COLUMN notification_preferences.category IS 'payments, game_invites or chat_mentions';
+COMMENT ON COLUMN notification_preferences.channel IS 'websocket, email or none (muted)';
@@ -0,0 +1,22 @@
+-- Create balance_transfers table
+-- Coins sent from one user to another. A transfer debits the sender and
+-- credits the recipient in one transaction, writing a balance_ledger entry
+-- (source 'transfer') for each side.
+
+CREATE TABLE IF NOT EXISTS balance_transfers (
+    id BIGSERIAL PRIMARY KEY,
+    sender_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    recipient_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    amount_cents BIGINT NOT NULL CHECK (amount_cents > 0),
+    memo TEXT,
+    sender_balance_after BIGINT NOT NULL,
+    recipient_balance_after BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT balance_transfers_distinct_parties CHECK (sender_id <> recipient_id)
+);
+
+CREATE INDEX idx_balance_transfers_sender ON balance_transfers(sender_id, created_at DESC, id DESC);
+CREATE INDEX idx_balance_transfers_recipient ON balance_transfers(recipient_id, created_at DESC, id DESC);
+
+COMMENT ON TABLE balance_transfers IS 'User-to-user coin transfers';
+COMMENT ON COLUMN balance_transfers.memo IS 'Optional note from the sender, shown to both parties';
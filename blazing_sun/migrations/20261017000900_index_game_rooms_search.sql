@@ -0,0 +1,8 @@
+-- Room search matches free text anywhere in a room name; a trigram index lets
+-- ILIKE '%text%' use an index. Like the keyset index it only covers the rooms
+-- a list can ever show.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_game_rooms_active_name_trgm
+    ON game_rooms USING GIN (room_name gin_trgm_ops)
+    WHERE is_active = TRUE AND status IN ('waiting', 'in_progress');
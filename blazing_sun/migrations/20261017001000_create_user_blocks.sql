@@ -0,0 +1,68 @@
+-- Create user_blocks table
+-- One row per user a user has blocked. Blocks used to be 'blocked' rows of
+-- the friends table; they move here and the friend functions read from here.
+
+CREATE TABLE IF NOT EXISTS user_blocks (
+    blocker_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (blocker_id, blocked_id),
+    CHECK (blocker_id <> blocked_id)
+);
+
+-- Who blocked a user (presence and chat filtering)
+CREATE INDEX IF NOT EXISTS idx_user_blocks_blocked ON user_blocks(blocked_id);
+
+INSERT INTO user_blocks (blocker_id, blocked_id, created_at)
+SELECT user_id, friend_id, created_at FROM friends WHERE status = 'blocked'
+ON CONFLICT DO NOTHING;
+
+DELETE FROM friends WHERE status = 'blocked';
+
+-- Block a user: ends any friendship or pending request between the two
+CREATE OR REPLACE FUNCTION block_user(
+    p_user_id BIGINT,
+    p_blocked_id BIGINT
+) RETURNS BOOLEAN AS $$
+BEGIN
+    DELETE FROM friends
+    WHERE (user_id = p_user_id AND friend_id = p_blocked_id)
+       OR (user_id = p_blocked_id AND friend_id = p_user_id);
+
+    INSERT INTO user_blocks (blocker_id, blocked_id)
+    VALUES (p_user_id, p_blocked_id)
+    ON CONFLICT DO NOTHING;
+
+    RETURN TRUE;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Unblock a user
+CREATE OR REPLACE FUNCTION unblock_user(
+    p_user_id BIGINT,
+    p_blocked_id BIGINT
+) RETURNS BOOLEAN AS $$
+BEGIN
+    DELETE FROM user_blocks
+    WHERE blocker_id = p_user_id
+    AND blocked_id = p_blocked_id;
+
+    RETURN FOUND;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Check if a user is blocked by another
+CREATE OR REPLACE FUNCTION is_blocked(
+    p_blocker_id BIGINT,
+    p_blocked_id BIGINT
+) RETURNS BOOLEAN AS $$
+BEGIN
+    RETURN EXISTS (
+        SELECT 1 FROM user_blocks
+        WHERE blocker_id = p_blocker_id
+        AND blocked_id = p_blocked_id
+    );
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON TABLE user_blocks IS 'Users a user has blocked from chat, direct messages and presence';
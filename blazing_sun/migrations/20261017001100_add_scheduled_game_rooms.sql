@@ -0,0 +1,48 @@
+-- Add scheduled game rooms
+-- A host can create a room that opens later. Until scheduled_start_at the room
+-- is 'scheduled': it is not listed in the lobby and cannot be joined, but
+-- users can register for it. The games scheduler reminds registered users
+-- before the start and moves the room to 'waiting' when it is due.
+
+ALTER TABLE game_rooms
+    ADD COLUMN IF NOT EXISTS scheduled_start_at TIMESTAMPTZ;
+
+ALTER TABLE game_rooms DROP CONSTRAINT IF EXISTS valid_status;
+ALTER TABLE game_rooms
+    ADD CONSTRAINT valid_status
+    CHECK (status IN ('scheduled', 'waiting', 'in_progress', 'finished', 'abandoned'));
+
+-- Upcoming listing and the scheduler's due check
+CREATE INDEX IF NOT EXISTS idx_game_rooms_scheduled
+    ON game_rooms(scheduled_start_at)
+    WHERE status = 'scheduled';
+
+-- Users registered for a scheduled room (the host is registered on creation)
+CREATE TABLE IF NOT EXISTS game_room_registrations (
+    room_id VARCHAR(64) NOT NULL REFERENCES game_rooms(room_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (room_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_game_room_registrations_user
+    ON game_room_registrations(user_id);
+
+-- Reminders already sent, so each one goes out once across restarts and instances
+CREATE TABLE IF NOT EXISTS game_room_schedule_reminders (
+    room_id VARCHAR(64) NOT NULL REFERENCES game_rooms(room_id) ON DELETE CASCADE,
+    minutes_before INTEGER NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (room_id, minutes_before)
+);
+
+-- Registered users hear about scheduled rooms by email unless they pick another channel
+ALTER TABLE notification_preferences DROP CONSTRAINT IF EXISTS notification_preferences_category_check;
+ALTER TABLE notification_preferences
+    ADD CONSTRAINT notification_preferences_category_check
+    CHECK (category IN ('payments', 'game_invites', 'chat_mentions', 'game_reminders'));
+
+COMMENT ON COLUMN game_rooms.scheduled_start_at IS 'When a scheduled room opens (NULL for rooms opened on creation)';
+COMMENT ON TABLE game_room_registrations IS 'Users registered for a scheduled game room';
+COMMENT ON TABLE game_room_schedule_reminders IS 'T-minus reminders sent for scheduled game rooms';
+COMMENT ON COLUMN notification_preferences.category IS 'payments, game_invites, chat_mentions or game_reminders';
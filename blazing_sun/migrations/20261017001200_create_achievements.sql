@@ -0,0 +1,47 @@
+-- Create achievements tables
+-- Achievement definitions, the progress counted towards them and the
+-- achievements users unlocked. Progress rows are keyed by the event they came
+-- from, so a redelivered event is counted once.
+
+CREATE TABLE IF NOT EXISTS achievements (
+    id BIGSERIAL PRIMARY KEY,
+    code VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(128) NOT NULL,
+    description TEXT NOT NULL,
+    -- games_played, games_won or amount_spent_cents
+    metric VARCHAR(32) NOT NULL CHECK (metric IN ('games_played', 'games_won', 'amount_spent_cents')),
+    threshold BIGINT NOT NULL CHECK (threshold > 0),
+    -- Badge icon shown by the frontend
+    badge VARCHAR(64) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_achievements_metric ON achievements(metric) WHERE is_active;
+
+CREATE TABLE IF NOT EXISTS user_achievement_progress (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    metric VARCHAR(32) NOT NULL,
+    -- Event the progress came from (game over event ID, checkout request ID)
+    source_id VARCHAR(128) NOT NULL,
+    amount BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, metric, source_id)
+);
+
+CREATE TABLE IF NOT EXISTS user_achievements (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement_id BIGINT NOT NULL REFERENCES achievements(id) ON DELETE CASCADE,
+    unlocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, achievement_id)
+);
+
+INSERT INTO achievements (code, name, description, metric, threshold, badge) VALUES
+    ('first_win', 'First Win', 'Win your first game', 'games_won', 1, 'trophy'),
+    ('ten_games', 'Regular', 'Play 10 games', 'games_played', 10, 'dice'),
+    ('big_spender', 'Big Spender', 'Spend 100.00 on balance top-ups', 'amount_spent_cents', 10000, 'diamond')
+ON CONFLICT (code) DO NOTHING;
+
+COMMENT ON TABLE achievements IS 'Achievement definitions: a threshold on one metric';
+COMMENT ON TABLE user_achievement_progress IS 'Progress events counted towards achievement metrics, once per source event';
+COMMENT ON TABLE user_achievements IS 'Achievements users unlocked';
@@ -0,0 +1,23 @@
+-- Create balance_daily_snapshots table
+-- End-of-day balance of every user with ledger activity that day, written by
+-- the balance_snapshots cron. Historical balance queries start from the latest
+-- snapshot before the requested time and only sum the ledger entries after it,
+-- instead of replaying a user's whole ledger.
+
+CREATE TABLE IF NOT EXISTS balance_daily_snapshots (
+    user_id BIGINT NOT NULL,
+    snapshot_date DATE NOT NULL,
+    balance_cents BIGINT NOT NULL,
+    last_ledger_id BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, snapshot_date),
+    CONSTRAINT fk_balance_daily_snapshots_user FOREIGN KEY (user_id)
+        REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_balance_daily_snapshots_date ON balance_daily_snapshots(snapshot_date);
+
+COMMENT ON TABLE balance_daily_snapshots IS 'End-of-day (UTC) user balances derived from balance_ledger';
+COMMENT ON COLUMN balance_daily_snapshots.balance_cents IS 'balance_after of the last ledger entry of the day';
+COMMENT ON COLUMN balance_daily_snapshots.last_ledger_id IS 'Last balance_ledger entry included in the snapshot';
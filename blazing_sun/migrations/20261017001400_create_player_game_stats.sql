@@ -0,0 +1,39 @@
+-- Create player game stats tables
+-- Per-player results of finished rooms and the totals derived from them per
+-- game type. Results are keyed by user and room, so a redelivered game over
+-- event or a backfill over rooms already recorded counts once.
+
+CREATE TABLE IF NOT EXISTS player_game_results (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    room_id VARCHAR(64) NOT NULL,
+    game_type VARCHAR(50) NOT NULL,
+    outcome VARCHAR(8) NOT NULL CHECK (outcome IN ('win', 'loss', 'draw')),
+    -- NULL when the room's start time is unknown
+    match_seconds BIGINT,
+    finished_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, room_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_game_results_history
+    ON player_game_results(user_id, game_type, finished_at);
+
+CREATE TABLE IF NOT EXISTS player_game_stats (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    game_type VARCHAR(50) NOT NULL,
+    games_played BIGINT NOT NULL DEFAULT 0,
+    wins BIGINT NOT NULL DEFAULT 0,
+    losses BIGINT NOT NULL DEFAULT 0,
+    draws BIGINT NOT NULL DEFAULT 0,
+    -- Wins in a row when positive, losses in a row when negative
+    current_streak INTEGER NOT NULL DEFAULT 0,
+    best_win_streak INTEGER NOT NULL DEFAULT 0,
+    timed_games BIGINT NOT NULL DEFAULT 0,
+    total_match_seconds BIGINT NOT NULL DEFAULT 0,
+    last_played_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, game_type)
+);
+
+COMMENT ON TABLE player_game_results IS 'Outcome of each finished room for each of its players';
+COMMENT ON TABLE player_game_stats IS 'Per-player totals per game type, folded from player_game_results';
+COMMENT ON COLUMN player_game_stats.timed_games IS 'Games with a known duration; the average match time is total_match_seconds / timed_games';
@@ -0,0 +1,9 @@
+-- Per-room Bigger Dice rules
+-- Chosen at room creation and validated against the allowed ranges in
+-- app::games::bigger_dice; NULL for other games and for rooms created before
+-- rules were configurable, which play with the default rules.
+
+ALTER TABLE game_rooms
+    ADD COLUMN IF NOT EXISTS dice_rules JSONB;
+
+COMMENT ON COLUMN game_rooms.dice_rules IS 'Bigger Dice rules (rounds_to_win, dice_count, dice_sides, tiebreaker, tiebreaker_roll_offs); NULL = defaults';
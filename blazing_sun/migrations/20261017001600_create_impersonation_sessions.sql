@@ -0,0 +1,22 @@
+-- Create impersonation_sessions table
+-- An admin acting as a user to see what they see. The session id is carried in
+-- the impersonation JWT; the token is refused once the session has ended or
+-- expired, and the rows are the audit trail of who impersonated whom and why.
+
+CREATE TABLE IF NOT EXISTS impersonation_sessions (
+    id UUID PRIMARY KEY,
+    admin_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    ended_by BIGINT REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_impersonation_sessions_user ON impersonation_sessions(user_id, started_at DESC);
+CREATE INDEX idx_impersonation_sessions_admin ON impersonation_sessions(admin_id, started_at DESC);
+
+COMMENT ON TABLE impersonation_sessions IS 'Time-boxed admin impersonation sessions (audit trail)';
+COMMENT ON COLUMN impersonation_sessions.expires_at IS 'Expiry of the impersonation JWT';
+COMMENT ON COLUMN impersonation_sessions.ended_at IS 'Set when an admin ends the session before it expires';
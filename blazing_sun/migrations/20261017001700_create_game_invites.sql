@@ -0,0 +1,26 @@
+-- Create game_invites table
+-- A room member inviting another user into the room. The invitee accepts
+-- (joining the lobby without the room password) or declines; invites nobody
+-- answers within GAME_ROOM_INVITE_TTL_SECONDS are expired by a sweep that
+-- tells the inviter.
+
+CREATE TABLE IF NOT EXISTS game_invites (
+    id UUID PRIMARY KEY,
+    room_id VARCHAR(64) NOT NULL REFERENCES game_rooms(room_id) ON DELETE CASCADE,
+    inviter_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    invitee_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined', 'expired')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    responded_at TIMESTAMPTZ
+);
+
+-- One open invite per user and room; inviting again refreshes it
+CREATE UNIQUE INDEX idx_game_invites_pending
+    ON game_invites(room_id, invitee_id) WHERE status = 'pending';
+CREATE INDEX idx_game_invites_expiry ON game_invites(expires_at) WHERE status = 'pending';
+CREATE INDEX idx_game_invites_invitee ON game_invites(invitee_id, created_at DESC);
+
+COMMENT ON TABLE game_invites IS 'Invitations into game rooms';
+COMMENT ON COLUMN game_invites.responded_at IS 'When the invite was accepted, declined or expired';
@@ -0,0 +1,30 @@
+-- Create balance_holds table
+-- Coins frozen while a Stripe dispute (chargeback) of the checkout that
+-- credited them is open. Placing a hold debits the balance (balance_ledger
+-- source 'dispute_hold'); a won dispute gives the coins back
+-- ('dispute_release'), a lost one keeps them and deducts whatever the hold
+-- could not cover ('dispute_deduction').
+
+CREATE TABLE IF NOT EXISTS balance_holds (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source VARCHAR(32) NOT NULL,
+    reference_id VARCHAR(255) NOT NULL,
+    request_id VARCHAR(255),
+    requested_cents BIGINT NOT NULL CHECK (requested_cents >= 0),
+    held_cents BIGINT NOT NULL CHECK (held_cents >= 0 AND held_cents <= requested_cents),
+    deducted_cents BIGINT NOT NULL DEFAULT 0 CHECK (deducted_cents >= 0),
+    status VARCHAR(16) NOT NULL DEFAULT 'held'
+        CHECK (status IN ('held', 'released', 'deducted')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CONSTRAINT unique_balance_hold UNIQUE (source, reference_id)
+);
+
+CREATE INDEX idx_balance_holds_user ON balance_holds(user_id, created_at DESC);
+CREATE INDEX idx_balance_holds_open ON balance_holds(created_at) WHERE status = 'held';
+
+COMMENT ON TABLE balance_holds IS 'Coins frozen while a payment dispute is open';
+COMMENT ON COLUMN balance_holds.reference_id IS 'What the hold is for (the Stripe dispute id)';
+COMMENT ON COLUMN balance_holds.requested_cents IS 'Coins the dispute covers; held_cents is what the balance could freeze';
+COMMENT ON COLUMN balance_holds.status IS 'held -> released (dispute won) or deducted (dispute lost)';
@@ -0,0 +1,28 @@
+-- Create auth_sessions table
+-- One row per signed-in device. The session id is carried in every access
+-- token (`sid`) and linked from the device's refresh token; revoking the
+-- session revokes the refresh token, denylists the id in Redis for the rest
+-- of the access token lifetime and closes the device's WebSocket connections.
+
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    id UUID PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    device_info TEXT,
+    ip_address VARCHAR(45),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_auth_sessions_user ON auth_sessions(user_id, last_seen_at DESC);
+
+ALTER TABLE session_refresh_tokens
+    ADD COLUMN IF NOT EXISTS session_id UUID REFERENCES auth_sessions(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_session_refresh_tokens_session_id ON session_refresh_tokens(session_id);
+
+COMMENT ON TABLE auth_sessions IS 'Signed-in devices of a user, listed and revoked under /api/v1/auth/sessions';
+COMMENT ON COLUMN auth_sessions.device_info IS 'User agent at sign-in';
+COMMENT ON COLUMN auth_sessions.last_seen_at IS 'Sign-in or last access token refresh';
+COMMENT ON COLUMN auth_sessions.expires_at IS 'Expiry of the refresh token, or of the access token without one';
@@ -0,0 +1,21 @@
+-- Create game_chat_mutes table
+-- Chat mutes set by the host or admin spectator of a room: the user cannot
+-- send to the channel ('all' = every channel) until expires_at (NULL = until
+-- unmuted). Expired mutes are deleted by a sweep that announces them to the
+-- room.
+
+CREATE TABLE IF NOT EXISTS game_chat_mutes (
+    room_id VARCHAR(64) NOT NULL REFERENCES game_rooms(room_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(16) NOT NULL
+        CHECK (channel IN ('all', 'lobby', 'players', 'spectators')),
+    muted_by BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    PRIMARY KEY (room_id, user_id, channel)
+);
+
+CREATE INDEX idx_game_chat_mutes_expiry ON game_chat_mutes(expires_at) WHERE expires_at IS NOT NULL;
+
+COMMENT ON TABLE game_chat_mutes IS 'Moderator chat mutes per room, user and channel';
+COMMENT ON COLUMN game_chat_mutes.expires_at IS 'When the mute ends; NULL = until unmuted';
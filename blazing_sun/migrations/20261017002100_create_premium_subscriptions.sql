@@ -0,0 +1,23 @@
+-- Create premium_subscriptions table
+-- Premium memberships as the checkout service reports them on
+-- checkout.subscriptions: SubscriptionActivated sets active (and the paid
+-- period), SubscriptionLapsed clears it. A user has premium while one of
+-- their subscriptions is active. changed_at is the checkout timestamp of the
+-- last event applied, so a redelivered older event changes nothing.
+
+CREATE TABLE IF NOT EXISTS premium_subscriptions (
+    subscription_id VARCHAR(255) PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(32) NOT NULL,
+    active BOOLEAN NOT NULL,
+    current_period_end TIMESTAMPTZ,
+    cancel_at_period_end BOOLEAN NOT NULL DEFAULT FALSE,
+    changed_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_premium_subscriptions_user ON premium_subscriptions(user_id) WHERE active;
+
+COMMENT ON TABLE premium_subscriptions IS 'Stripe premium memberships, kept in step by checkout.subscriptions';
+COMMENT ON COLUMN premium_subscriptions.status IS 'Stripe subscription status (active, trialing, past_due, canceled, unpaid, ...)';
+COMMENT ON COLUMN premium_subscriptions.active IS 'Whether the subscription grants premium';
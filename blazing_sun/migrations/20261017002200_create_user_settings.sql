@@ -0,0 +1,18 @@
+-- Create user_settings table
+-- Per-account display settings that follow the user between devices (theme,
+-- notification sounds). Keys and value types are defined by the settings
+-- schema in blazing_sun (app::user_settings); a missing row means the key's
+-- default, so only changed keys are stored. The language setting is kept in
+-- user_preferences.locale, which tokens and message localization already use.
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key VARCHAR(64) NOT NULL,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, key)
+);
+
+COMMENT ON TABLE user_settings IS 'Per-user display settings, one row per changed key';
+COMMENT ON COLUMN user_settings.key IS 'Settings schema key (theme, notification_sounds)';
+COMMENT ON COLUMN user_settings.value IS 'JSON value, validated against the key''s type before it is stored';
@@ -0,0 +1,50 @@
+-- Create two-factor authentication tables
+-- user_two_factor holds a user's TOTP secret; the row is pending until the
+-- first code is confirmed (enabled_at set), and users.two_factor mirrors
+-- whether it is enabled. last_used_step rejects a code used twice.
+-- Recovery codes are stored hashed and can each be used once.
+-- two_factor_challenges are sign-ins whose password was accepted and that wait
+-- for a code (or for enrollment, when the user's role requires two-factor);
+-- the client holds the pre-auth token, only its hash is stored.
+
+CREATE TABLE IF NOT EXISTS user_two_factor (
+    user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    enabled_at TIMESTAMPTZ,
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS two_factor_recovery_codes (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_two_factor_recovery_codes_user
+    ON two_factor_recovery_codes (user_id);
+
+CREATE TABLE IF NOT EXISTS two_factor_challenges (
+    id UUID PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    purpose VARCHAR(16) NOT NULL CHECK (purpose IN ('verify', 'enroll')),
+    remember_me BOOLEAN NOT NULL DEFAULT FALSE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_two_factor_challenges_expires_at
+    ON two_factor_challenges (expires_at);
+
+COMMENT ON TABLE user_two_factor IS 'TOTP secret of each user that enrolled in two-factor authentication';
+COMMENT ON COLUMN user_two_factor.secret IS 'Base32 TOTP secret (SHA-1, 6 digits, 30 second steps)';
+COMMENT ON COLUMN user_two_factor.enabled_at IS 'When the first code was confirmed; NULL while enrollment is pending';
+COMMENT ON COLUMN user_two_factor.last_used_step IS 'Time step of the last accepted code, so a code works only once';
+COMMENT ON TABLE two_factor_recovery_codes IS 'Single-use recovery codes (SHA-256 hashed) for users without their authenticator';
+COMMENT ON TABLE two_factor_challenges IS 'Sign-ins waiting for a two-factor code, addressed by a hashed pre-auth token';
+COMMENT ON COLUMN two_factor_challenges.purpose IS 'verify (enter a code) or enroll (role requires two-factor, set it up first)';
@@ -0,0 +1,48 @@
+-- Create user_data_exports table
+-- Self-serve data portability: a user asks for a copy of their data (for
+-- instance before deleting their account). The export_user_data MQ job
+-- assembles a zip of JSON and CSV files, stores it privately and marks the
+-- export ready; the download link is signed and expires with the export. The
+-- data_exports cron deletes the archives of expired exports.
+--
+-- Status flow: pending -> processing -> ready -> expired
+--              pending | processing -> failed
+
+CREATE TABLE IF NOT EXISTS user_data_exports (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'ready', 'failed', 'expired')),
+    storage_path TEXT,
+    size_bytes BIGINT,
+    checksum VARCHAR(64),
+    error TEXT,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- At most one export being built per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_data_exports_open
+    ON user_data_exports(user_id)
+    WHERE status IN ('pending', 'processing');
+
+CREATE INDEX IF NOT EXISTS idx_user_data_exports_user
+    ON user_data_exports(user_id, requested_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_user_data_exports_expiry
+    ON user_data_exports(expires_at)
+    WHERE status = 'ready';
+
+-- Users hear about finished exports by email unless they pick another channel
+ALTER TABLE notification_preferences DROP CONSTRAINT IF EXISTS notification_preferences_category_check;
+ALTER TABLE notification_preferences
+    ADD CONSTRAINT notification_preferences_category_check
+    CHECK (category IN ('payments', 'game_invites', 'chat_mentions', 'game_reminders', 'data_exports'));
+
+COMMENT ON TABLE user_data_exports IS 'Self-serve exports of a user''s data (data portability)';
+COMMENT ON COLUMN user_data_exports.storage_path IS 'Private storage path of the zip archive (NULL until ready and once expired)';
+COMMENT ON COLUMN user_data_exports.expires_at IS 'End of the download window; the archive is deleted after this';
+COMMENT ON COLUMN user_data_exports.error IS 'Last failure reported by the export_user_data job';
+COMMENT ON COLUMN notification_preferences.category IS 'payments, game_invites, chat_mentions, game_reminders or data_exports';
@@ -0,0 +1,238 @@
+//! Achievements
+//!
+//! Achievements ("first win", "10 games played", "big spender") are defined in
+//! the `achievements` table, each as a threshold on one [`Metric`]. Bus events
+//! become [`Progress`] rows: a finished game counts as played for every player
+//! and as won for its winner, a successful checkout adds its amount to what the
+//! user spent. Progress rows are keyed by the event they came from, so a
+//! redelivered event is counted once, and unlocking is idempotent, so every
+//! achievement is awarded (and announced) at most once per user.
+//!
+//! The achievement handler (`events::handlers::AchievementHandler`) records
+//! the progress and pushes an `achievement.event.unlocked` envelope to the
+//! user for every achievement that was unlocked.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::app::chat::types::{Actor, Audience, EventEnvelope as GatewayEnvelope};
+use crate::app::checkout::CheckoutFinishedEvent;
+use crate::app::games::tournament::FINISHED_EVENTS;
+use crate::app::games::types::EventEnvelope as GameEnvelope;
+
+/// Event type of the gateway push (and of the client's server message)
+pub const ACHIEVEMENT_UNLOCKED_EVENT: &str = "achievement.event.unlocked";
+
+/// What an achievement's threshold is measured on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Finished games the user played in
+    GamesPlayed,
+    /// Finished games the user won
+    GamesWon,
+    /// Cents paid through successful checkouts
+    AmountSpentCents,
+}
+
+impl Metric {
+    /// Name stored in `achievements.metric`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Metric::GamesPlayed => "games_played",
+            Metric::GamesWon => "games_won",
+            Metric::AmountSpentCents => "amount_spent_cents",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Metric::GamesPlayed, Metric::GamesWon, Metric::AmountSpentCents]
+            .into_iter()
+            .find(|metric| metric.as_str() == value)
+    }
+}
+
+/// One event's contribution to a user's metric
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub user_id: i64,
+    pub metric: Metric,
+    /// The event it came from (counted once)
+    pub source_id: String,
+    pub amount: i64,
+}
+
+/// An achievement a user just unlocked
+#[derive(Debug, Clone, Serialize)]
+pub struct UnlockedAchievement {
+    pub achievement_id: i64,
+    pub code: String,
+    pub name: String,
+    pub description: String,
+    pub badge: String,
+    pub unlocked_at: DateTime<Utc>,
+}
+
+/// Played (every player) and won (the winner) progress of a finished game
+pub fn from_game_envelope(envelope: &GameEnvelope) -> Vec<Progress> {
+    let payload = &envelope.payload;
+    let finished = payload
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|event_type| FINISHED_EVENTS.contains(&event_type));
+    if !finished {
+        return Vec::new();
+    }
+
+    let source_id = &envelope.event_id;
+    let mut progress: Vec<Progress> = players(payload)
+        .into_iter()
+        .map(|user_id| Progress {
+            user_id,
+            metric: Metric::GamesPlayed,
+            source_id: source_id.clone(),
+            amount: 1,
+        })
+        .collect();
+
+    if let Some(winner_id) = payload.get("winner_id").and_then(user_id) {
+        progress.push(Progress {
+            user_id: winner_id,
+            metric: Metric::GamesWon,
+            source_id: source_id.clone(),
+            amount: 1,
+        });
+    }
+
+    progress
+}
+
+/// Spending progress of a successful checkout
+pub fn from_checkout(event: &CheckoutFinishedEvent) -> Option<Progress> {
+    if !event.is_success() || event.amount_cents <= 0 {
+        return None;
+    }
+
+    Some(Progress {
+        user_id: event.user_id,
+        metric: Metric::AmountSpentCents,
+        source_id: event.request_id.clone(),
+        amount: event.amount_cents,
+    })
+}
+
+/// Players listed in `final_scores` as `[user_id, username, score]`
+fn players(payload: &Value) -> Vec<i64> {
+    let mut players: Vec<i64> = payload
+        .get("final_scores")
+        .and_then(Value::as_array)
+        .map(|scores| {
+            scores
+                .iter()
+                .filter_map(|score| score.get(0).and_then(user_id))
+                .collect()
+        })
+        .unwrap_or_default();
+    players.sort_unstable();
+    players.dedup();
+    players
+}
+
+fn user_id(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Gateway envelope announcing an unlocked achievement to its user
+pub fn unlocked_envelope(user_id: i64, achievement: &UnlockedAchievement) -> GatewayEnvelope {
+    GatewayEnvelope {
+        event_id: Uuid::new_v4().to_string(),
+        event_type: ACHIEVEMENT_UNLOCKED_EVENT.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        correlation_id: logging::request_id::current(),
+        producer: "blazing_sun".to_string(),
+        actor: Actor {
+            user_id: 0, // System
+            username: "system".to_string(),
+            socket_id: String::new(),
+            roles: vec![],
+        },
+        audience: Audience::user(user_id),
+        payload: serde_json::json!({
+            "achievement_id": achievement.achievement_id,
+            "code": achievement.code,
+            "name": achievement.name,
+            "description": achievement.description,
+            "badge": achievement.badge,
+            "unlocked_at": achievement.unlocked_at.to_rfc3339(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{Actor as GameActor, Audience as GameAudience};
+    use serde_json::json;
+
+    fn envelope(payload: Value) -> GameEnvelope {
+        GameEnvelope {
+            event_id: "e-1".to_string(),
+            event_type: "games.event.bigger_dice.game_over".to_string(),
+            timestamp: "2026-10-17T12:00:00Z".to_string(),
+            correlation_id: None,
+            producer: "blazing_sun".to_string(),
+            actor: GameActor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: GameAudience::room("r-1"),
+            payload,
+        }
+    }
+
+    #[test]
+    fn finished_game_counts_for_players_and_winner() {
+        let progress = from_game_envelope(&envelope(json!({
+            "type": "bigger_dice.game_over",
+            "room_id": "r-1",
+            "winner_id": 7,
+            "final_scores": [[7, "ana", 10], ["9", "bob", 4]],
+        })));
+
+        let played: Vec<i64> = progress
+            .iter()
+            .filter(|p| p.metric == Metric::GamesPlayed)
+            .map(|p| p.user_id)
+            .collect();
+        assert_eq!(played, vec![7, 9]);
+        assert!(progress
+            .iter()
+            .any(|p| p.metric == Metric::GamesWon && p.user_id == 7));
+        assert!(progress.iter().all(|p| p.source_id == "e-1"));
+    }
+
+    #[test]
+    fn other_game_events_make_no_progress() {
+        let progress = from_game_envelope(&envelope(json!({
+            "type": "bigger_dice.rolled",
+            "room_id": "r-1",
+        })));
+        assert!(progress.is_empty());
+    }
+
+    #[test]
+    fn metrics_round_trip_through_their_names() {
+        for metric in [Metric::GamesPlayed, Metric::GamesWon, Metric::AmountSpentCents] {
+            assert_eq!(Metric::parse(metric.as_str()), Some(metric));
+        }
+        assert_eq!(Metric::parse("logins"), None);
+    }
+}
@@ -0,0 +1,12 @@
+//! Analytics module
+//!
+//! Admin reporting built from Kafka events:
+//! - `projection`: turns game and checkout events into facts
+//! - `mongodb_analytics`: folds facts into MongoDB documents (one per room / checkout)
+//!   and aggregates them into daily funnels
+//!
+//! Facts are written with `$min` upserts keyed by room or request id, so
+//! redelivered or out-of-order events never double count.
+
+pub mod mongodb_analytics;
+pub mod projection;
@@ -0,0 +1,313 @@
+//! MongoDB analytics projections
+//!
+//! `analytics_game_rooms` holds one document per room with the first time it
+//! was created/started/finished/abandoned; `analytics_checkouts` holds one
+//! document per checkout request. Both carry a `day` bucket (the earliest
+//! event's UTC date) so daily funnels are a single `$group`.
+
+use super::projection::{day_of, CheckoutFact, CheckoutStep, GameFact, RoomStep};
+use mongodb::bson::{doc, Bson, DateTime as BsonDateTime, Document};
+use mongodb::options::{IndexOptions, UpdateOptions};
+use mongodb::{Collection, Database, IndexModel};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Collection name for per-room funnel documents
+const COLLECTION_GAME_ROOMS: &str = "analytics_game_rooms";
+
+/// Collection name for per-checkout documents
+const COLLECTION_CHECKOUTS: &str = "analytics_checkouts";
+
+/// Games funnel for one day
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GameDay {
+    pub day: String,
+    pub rooms_created: i64,
+    pub games_started: i64,
+    pub games_finished: i64,
+    pub rooms_abandoned: i64,
+    /// Finished games with a known start, i.e. the ones averaged below
+    #[serde(skip)]
+    pub timed_games: i64,
+    pub avg_match_duration_seconds: Option<f64>,
+}
+
+/// Checkout counts for one day
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckoutDay {
+    pub day: String,
+    pub sessions_created: i64,
+    pub succeeded: i64,
+    pub failed: i64,
+    pub revenue_cents: i64,
+}
+
+/// MongoDB analytics client
+pub struct MongoAnalyticsClient {
+    db: Arc<Database>,
+}
+
+impl MongoAnalyticsClient {
+    /// Create a new MongoDB analytics client
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    fn game_rooms(&self) -> Collection<Document> {
+        self.db.collection(COLLECTION_GAME_ROOMS)
+    }
+
+    fn checkouts(&self) -> Collection<Document> {
+        self.db.collection(COLLECTION_CHECKOUTS)
+    }
+
+    /// Initialize indexes for the analytics collections
+    pub async fn init_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let room_index = IndexModel::builder()
+            .keys(doc! { "room_id": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("analytics_room_idx".to_string())
+                    .unique(true)
+                    .build(),
+            )
+            .build();
+
+        let room_day_index = IndexModel::builder()
+            .keys(doc! { "day": 1, "game_type": 1 })
+            .options(IndexOptions::builder().name("analytics_room_day_idx".to_string()).build())
+            .build();
+
+        self.game_rooms()
+            .create_indexes([room_index, room_day_index])
+            .await?;
+
+        let checkout_index = IndexModel::builder()
+            .keys(doc! { "request_id": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("analytics_checkout_idx".to_string())
+                    .unique(true)
+                    .build(),
+            )
+            .build();
+
+        let checkout_day_index = IndexModel::builder()
+            .keys(doc! { "day": 1 })
+            .options(IndexOptions::builder().name("analytics_checkout_day_idx".to_string()).build())
+            .build();
+
+        self.checkouts()
+            .create_indexes([checkout_index, checkout_day_index])
+            .await?;
+
+        info!("MongoDB analytics indexes initialized");
+        Ok(())
+    }
+
+    /// Record a funnel step of a room (idempotent)
+    pub async fn record_game_fact(&self, fact: &GameFact) -> Result<(), mongodb::error::Error> {
+        let at = BsonDateTime::from_millis(fact.at.timestamp_millis());
+
+        let mut min = doc! { "day": day_of(&fact.at) };
+        let mut set = Document::new();
+        match &fact.step {
+            RoomStep::Created { game_type } => {
+                min.insert("created_at", at);
+                set.insert("game_type", game_type);
+            }
+            RoomStep::Started => {
+                min.insert("started_at", at);
+            }
+            RoomStep::Finished => {
+                min.insert("finished_at", at);
+            }
+            RoomStep::Abandoned => {
+                min.insert("abandoned_at", at);
+            }
+        }
+
+        let mut update = doc! { "$min": min };
+        if !set.is_empty() {
+            update.insert("$set", set);
+        } else if let Some(game_type) = &fact.game_type {
+            update.insert("$setOnInsert", doc! { "game_type": game_type });
+        }
+
+        self.game_rooms()
+            .update_one(doc! { "room_id": &fact.room_id }, update)
+            .with_options(UpdateOptions::builder().upsert(true).build())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record a status change of a checkout (idempotent)
+    pub async fn record_checkout_fact(&self, fact: &CheckoutFact) -> Result<(), mongodb::error::Error> {
+        let at = BsonDateTime::from_millis(fact.at.timestamp_millis());
+        let field = match fact.step {
+            CheckoutStep::SessionCreated => "session_created_at",
+            CheckoutStep::Succeeded => "succeeded_at",
+            CheckoutStep::Failed => "failed_at",
+        };
+
+        let mut min = doc! { "day": day_of(&fact.at) };
+        min.insert(field, at);
+
+        let mut set = doc! {
+            "user_id": fact.user_id,
+            "amount_cents": fact.amount_cents,
+            "currency": &fact.currency,
+            "purpose": &fact.purpose,
+        };
+        if let Some(coupon_code) = &fact.coupon_code {
+            set.insert("coupon_code", coupon_code);
+            set.insert("discount_cents", fact.discount_cents);
+        }
+
+        let update = doc! { "$min": min, "$set": set };
+
+        self.checkouts()
+            .update_one(doc! { "request_id": &fact.request_id }, update)
+            .with_options(UpdateOptions::builder().upsert(true).build())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Daily games funnel between two days (inclusive, `YYYY-MM-DD`)
+    pub async fn game_days(
+        &self,
+        from: &str,
+        to: &str,
+        game_type: Option<&str>,
+    ) -> Result<Vec<GameDay>, mongodb::error::Error> {
+        let mut filter = doc! { "day": { "$gte": from, "$lte": to } };
+        if let Some(game_type) = game_type {
+            filter.insert("game_type", game_type);
+        }
+
+        let has = |field: &str| doc! { "$cond": [{ "$gt": [format!("${}", field), Bson::Null] }, 1, 0] };
+        let timed = doc! { "$and": [
+            { "$gt": ["$started_at", Bson::Null] },
+            { "$gt": ["$finished_at", Bson::Null] },
+        ] };
+
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! {
+                "$group": {
+                    "_id": "$day",
+                    "rooms_created": { "$sum": has("created_at") },
+                    "games_started": { "$sum": has("started_at") },
+                    "games_finished": { "$sum": has("finished_at") },
+                    "rooms_abandoned": { "$sum": { "$cond": [
+                        { "$and": [
+                            { "$gt": ["$abandoned_at", Bson::Null] },
+                            { "$lte": ["$finished_at", Bson::Null] },
+                        ] },
+                        1,
+                        0,
+                    ] } },
+                    "timed_games": { "$sum": { "$cond": [timed.clone(), 1, 0] } },
+                    "avg_duration": { "$avg": { "$cond": [
+                        timed,
+                        { "$divide": [{ "$subtract": ["$finished_at", "$started_at"] }, 1000] },
+                        Bson::Null,
+                    ] } },
+                }
+            },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+
+        let mut cursor = self.game_rooms().aggregate(pipeline).await?;
+        let mut days = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(doc) = cursor.next().await {
+            match doc {
+                Ok(doc) => {
+                    let Ok(day) = doc.get_str("_id") else {
+                        continue;
+                    };
+                    let count = |name: &str| bson_number(doc.get(name)).unwrap_or(0.0) as i64;
+                    days.push(GameDay {
+                        day: day.to_string(),
+                        rooms_created: count("rooms_created"),
+                        games_started: count("games_started"),
+                        games_finished: count("games_finished"),
+                        rooms_abandoned: count("rooms_abandoned"),
+                        timed_games: count("timed_games"),
+                        avg_match_duration_seconds: bson_number(doc.get("avg_duration")),
+                    });
+                }
+                Err(e) => error!("Error reading game analytics day: {}", e),
+            }
+        }
+
+        Ok(days)
+    }
+
+    /// Daily checkout counts between two days (inclusive, `YYYY-MM-DD`)
+    pub async fn checkout_days(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<CheckoutDay>, mongodb::error::Error> {
+        let has = |field: &str| doc! { "$cond": [{ "$gt": [format!("${}", field), Bson::Null] }, 1, 0] };
+
+        let pipeline = vec![
+            doc! { "$match": { "day": { "$gte": from, "$lte": to } } },
+            doc! {
+                "$group": {
+                    "_id": "$day",
+                    "sessions_created": { "$sum": has("session_created_at") },
+                    "succeeded": { "$sum": has("succeeded_at") },
+                    "failed": { "$sum": has("failed_at") },
+                    "revenue_cents": { "$sum": { "$cond": [
+                        { "$gt": ["$succeeded_at", Bson::Null] },
+                        "$amount_cents",
+                        0,
+                    ] } },
+                }
+            },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+
+        let mut cursor = self.checkouts().aggregate(pipeline).await?;
+        let mut days = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(doc) = cursor.next().await {
+            match doc {
+                Ok(doc) => {
+                    let Ok(day) = doc.get_str("_id") else {
+                        continue;
+                    };
+                    let count = |name: &str| bson_number(doc.get(name)).unwrap_or(0.0) as i64;
+                    days.push(CheckoutDay {
+                        day: day.to_string(),
+                        sessions_created: count("sessions_created"),
+                        succeeded: count("succeeded"),
+                        failed: count("failed"),
+                        revenue_cents: count("revenue_cents"),
+                    });
+                }
+                Err(e) => error!("Error reading checkout analytics day: {}", e),
+            }
+        }
+
+        Ok(days)
+    }
+}
+
+/// Read a numeric aggregation result regardless of its BSON width
+fn bson_number(value: Option<&Bson>) -> Option<f64> {
+    match value? {
+        Bson::Int32(v) => Some(*v as f64),
+        Bson::Int64(v) => Some(*v as f64),
+        Bson::Double(v) => Some(*v),
+        _ => None,
+    }
+}
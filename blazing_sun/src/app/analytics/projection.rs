@@ -0,0 +1,199 @@
+//! Event projections
+//!
+//! Reduces the events the analytics handler sees to the few facts the funnels
+//! need. Game events arrive as `EventEnvelope`s on the games events topic (the
+//! `GameEvent` is the envelope payload, tagged by `type`); checkout events are
+//! `CheckoutFinishedEvent`s.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::app::checkout::CheckoutFinishedEvent;
+use crate::app::games::types::EventEnvelope;
+
+/// Step a room reached in the rooms created -> started -> finished funnel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoomStep {
+    /// Carries the game type from the `room_created` payload
+    Created { game_type: String },
+    Started,
+    Finished,
+    /// Removed or cancelled before a result (only counted when never finished)
+    Abandoned,
+}
+
+/// One funnel step of one room
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameFact {
+    pub room_id: String,
+    /// Game type taken from the event type prefix, when present
+    pub game_type: Option<String>,
+    pub step: RoomStep,
+    pub at: DateTime<Utc>,
+}
+
+impl GameFact {
+    /// Funnel fact carried by a game event, if any
+    pub fn from_envelope(envelope: &EventEnvelope) -> Option<Self> {
+        let payload = &envelope.payload;
+        let room_id = payload.get("room_id")?.as_str()?.to_string();
+
+        let step = match payload.get("type")?.as_str()? {
+            "room_created" => RoomStep::Created {
+                game_type: payload.get("game_type")?.as_str()?.to_string(),
+            },
+            "game_started" => RoomStep::Started,
+            "game_ended" | "bigger_dice.game_over" | "tic_tac_toe.match_ended" => RoomStep::Finished,
+            "tic_tac_toe.match_cancelled" => RoomStep::Abandoned,
+            "room_removed" if payload.get("reason").and_then(Value::as_str) != Some("game_finished") => {
+                RoomStep::Abandoned
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            room_id,
+            game_type: game_type_of(&envelope.event_type),
+            step,
+            at: parse_timestamp(&envelope.timestamp),
+        })
+    }
+}
+
+/// Status a checkout reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckoutStep {
+    SessionCreated,
+    Succeeded,
+    Failed,
+}
+
+/// One status change of one checkout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckoutFact {
+    pub request_id: String,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub purpose: String,
+    /// Promo code the checkout was opened with
+    pub coupon_code: Option<String>,
+    pub discount_cents: i64,
+    pub step: CheckoutStep,
+    pub at: DateTime<Utc>,
+}
+
+impl CheckoutFact {
+    pub fn from_event(event: &CheckoutFinishedEvent) -> Option<Self> {
+        let step = match event.status.as_str() {
+            "session_created" => CheckoutStep::SessionCreated,
+            "success" => CheckoutStep::Succeeded,
+            "failed" => CheckoutStep::Failed,
+            _ => return None,
+        };
+
+        Some(Self {
+            request_id: event.request_id.clone(),
+            user_id: event.user_id,
+            amount_cents: event.amount_cents,
+            currency: event.currency.clone(),
+            purpose: event.purpose.clone(),
+            coupon_code: event.coupon.as_ref().map(|coupon| coupon.code.clone()),
+            discount_cents: event.coupon.as_ref().map_or(0, |coupon| coupon.discount_cents),
+            step,
+            at: parse_timestamp(&event.timestamp),
+        })
+    }
+}
+
+/// UTC day bucket (`YYYY-MM-DD`, sorts lexicographically)
+pub fn day_of(at: &DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d").to_string()
+}
+
+/// `bigger_dice` from `games.event.bigger_dice.game_started`
+fn game_type_of(event_type: &str) -> Option<String> {
+    let rest = event_type.strip_prefix("games.event.")?;
+    let (game_type, _) = rest.split_once('.')?;
+    Some(game_type.to_string())
+}
+
+/// Event timestamps are RFC 3339; fall back to the processing time
+fn parse_timestamp(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{Actor, Audience};
+    use serde_json::json;
+
+    fn envelope(event_type: &str, payload: Value) -> EventEnvelope {
+        EventEnvelope {
+            event_id: "e-1".to_string(),
+            event_type: event_type.to_string(),
+            timestamp: "2026-10-17T10:00:00Z".to_string(),
+            correlation_id: None,
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::room("r-1"),
+            payload,
+        }
+    }
+
+    #[test]
+    fn room_created_carries_game_type_and_day() {
+        let fact = GameFact::from_envelope(&envelope(
+            "games.event.tic_tac_toe.room_created",
+            json!({ "type": "room_created", "room_id": "r-1", "game_type": "tic_tac_toe" }),
+        ))
+        .expect("fact");
+
+        assert_eq!(fact.step, RoomStep::Created { game_type: "tic_tac_toe".to_string() });
+        assert_eq!(fact.game_type.as_deref(), Some("tic_tac_toe"));
+        assert_eq!(day_of(&fact.at), "2026-10-17");
+    }
+
+    #[test]
+    fn game_specific_endings_finish_the_room() {
+        let fact = GameFact::from_envelope(&envelope(
+            "games.event.bigger_dice.game_over",
+            json!({ "type": "bigger_dice.game_over", "room_id": "r-1" }),
+        ))
+        .expect("fact");
+
+        assert_eq!(fact.step, RoomStep::Finished);
+        assert_eq!(fact.game_type.as_deref(), Some("bigger_dice"));
+    }
+
+    #[test]
+    fn only_unfinished_removals_count_as_abandoned() {
+        let removed = |reason: &str| {
+            GameFact::from_envelope(&envelope(
+                "games.event.room_removed",
+                json!({ "type": "room_removed", "room_id": "r-1", "reason": reason }),
+            ))
+        };
+
+        assert_eq!(removed("host_left").map(|f| f.step), Some(RoomStep::Abandoned));
+        assert_eq!(removed("game_finished"), None);
+    }
+
+    #[test]
+    fn other_events_are_ignored() {
+        let fact = GameFact::from_envelope(&envelope(
+            "games.event.bigger_dice.rolled",
+            json!({ "type": "bigger_dice.rolled", "room_id": "r-1" }),
+        ));
+
+        assert_eq!(fact, None);
+    }
+}
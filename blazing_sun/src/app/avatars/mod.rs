@@ -0,0 +1,317 @@
+//! Avatars (profile pictures)
+//!
+//! Uploads are checked on their content, not their filename: the bytes must
+//! sniff as JPEG, PNG or WebP, stay under AVATAR_MAX_FILE_SIZE and declare at
+//! most AVATAR_MAX_DIMENSION pixels per side (read from the header, before
+//! anything is decoded). The original is stored through the configured
+//! storage driver (local disk or S3-compatible) under
+//! `private/profile-pictures/`, and the `process_avatar` MQ worker renders the
+//! square [`SIZES`] from it and records them as image variants.
+//!
+//! An avatar upload never changes once stored (a new picture is a new upload),
+//! so its ETags only depend on the upload, the variant and its size.
+
+use image::imageops::FilterType;
+use image::{ImageFormat, ImageReader};
+use std::io::Cursor;
+
+use crate::bootstrap::includes::controllers::uploads;
+use crate::bootstrap::includes::storage::{self, StorageError};
+use crate::config::UploadConfig;
+
+/// Storage subfolder of profile pictures (below `private/`)
+pub const SUBFOLDER: &str = "profile-pictures";
+
+/// `uploads.description` marking an upload as a profile picture
+pub const UPLOAD_DESCRIPTION: &str = "profile-picture";
+
+/// Standard square variants: name and edge length in pixels
+pub const SIZES: [(&str, u32); 4] = [
+    ("thumb", 64),
+    ("small", 128),
+    ("medium", 256),
+    ("large", 512),
+];
+
+/// Accepted image formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvatarFormat {
+    Jpeg,
+    Png,
+    WebP,
+}
+
+impl AvatarFormat {
+    fn from_image_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Jpeg => Some(AvatarFormat::Jpeg),
+            ImageFormat::Png => Some(AvatarFormat::Png),
+            ImageFormat::WebP => Some(AvatarFormat::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AvatarFormat::Jpeg => "jpg",
+            AvatarFormat::Png => "png",
+            AvatarFormat::WebP => "webp",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AvatarFormat::Jpeg => "image/jpeg",
+            AvatarFormat::Png => "image/png",
+            AvatarFormat::WebP => "image/webp",
+        }
+    }
+}
+
+/// Why an avatar was rejected or could not be processed
+#[derive(Debug)]
+pub enum AvatarError {
+    Empty,
+    TooLarge { max_size: u64, actual_size: u64 },
+    UnsupportedFormat,
+    DimensionsTooLarge { width: u32, height: u32, max: u32 },
+    Decode(String),
+    Encode(String),
+}
+
+impl AvatarError {
+    /// Client-facing message (translated through the catalog)
+    pub fn message(&self) -> &'static str {
+        match self {
+            AvatarError::Empty => "No file data received",
+            AvatarError::TooLarge { .. } => "Profile picture is too large",
+            AvatarError::UnsupportedFormat => "Profile picture must be a JPEG, PNG or WebP image",
+            AvatarError::DimensionsTooLarge { .. } => "Profile picture dimensions are too large",
+            AvatarError::Decode(_) | AvatarError::Encode(_) => "Profile picture could not be read",
+        }
+    }
+}
+
+impl std::fmt::Display for AvatarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AvatarError::TooLarge {
+                max_size,
+                actual_size,
+            } => write!(
+                f,
+                "Avatar too large: {} bytes (max: {} bytes)",
+                actual_size, max_size
+            ),
+            AvatarError::DimensionsTooLarge { width, height, max } => write!(
+                f,
+                "Avatar dimensions too large: {}x{} (max: {} per side)",
+                width, height, max
+            ),
+            AvatarError::Decode(e) => write!(f, "Failed to decode avatar: {}", e),
+            AvatarError::Encode(e) => write!(f, "Failed to encode avatar variant: {}", e),
+            other => write!(f, "{}", other.message()),
+        }
+    }
+}
+
+impl std::error::Error for AvatarError {}
+
+/// A rendered square variant
+#[derive(Debug, Clone)]
+pub struct RenderedVariant {
+    pub name: &'static str,
+    pub size: u32,
+    pub format: AvatarFormat,
+    pub data: Vec<u8>,
+}
+
+/// Validate an upload against the configured limits
+pub fn validate(data: &[u8]) -> Result<AvatarFormat, AvatarError> {
+    validate_with(
+        data,
+        UploadConfig::avatar_max_file_size(),
+        UploadConfig::avatar_max_dimension(),
+    )
+}
+
+fn validate_with(
+    data: &[u8],
+    max_size: u64,
+    max_dimension: u32,
+) -> Result<AvatarFormat, AvatarError> {
+    if data.is_empty() {
+        return Err(AvatarError::Empty);
+    }
+    if data.len() as u64 > max_size {
+        return Err(AvatarError::TooLarge {
+            max_size,
+            actual_size: data.len() as u64,
+        });
+    }
+
+    let format = image::guess_format(data)
+        .ok()
+        .and_then(AvatarFormat::from_image_format)
+        .ok_or(AvatarError::UnsupportedFormat)?;
+
+    let (width, height) = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| AvatarError::Decode(e.to_string()))?
+        .into_dimensions()
+        .map_err(|e| AvatarError::Decode(e.to_string()))?;
+    if width > max_dimension || height > max_dimension {
+        return Err(AvatarError::DimensionsTooLarge {
+            width,
+            height,
+            max: max_dimension,
+        });
+    }
+
+    Ok(format)
+}
+
+/// Render all [`SIZES`] as center-cropped squares.
+///
+/// JPEG sources stay JPEG, everything else becomes PNG (keeps transparency).
+/// CPU bound: call it from a blocking thread.
+pub fn render_variants(data: &[u8]) -> Result<Vec<RenderedVariant>, AvatarError> {
+    let source = image::load_from_memory(data).map_err(|e| AvatarError::Decode(e.to_string()))?;
+    let format = match image::guess_format(data) {
+        Ok(ImageFormat::Jpeg) => AvatarFormat::Jpeg,
+        _ => AvatarFormat::Png,
+    };
+
+    SIZES
+        .iter()
+        .map(|&(name, size)| {
+            let resized = source.resize_to_fill(size, size, FilterType::Lanczos3);
+            let mut out = Cursor::new(Vec::new());
+            let result = match format {
+                AvatarFormat::Jpeg => image::DynamicImage::ImageRgb8(resized.to_rgb8())
+                    .write_to(&mut out, ImageFormat::Jpeg),
+                _ => resized.write_to(&mut out, ImageFormat::Png),
+            };
+            result.map_err(|e| AvatarError::Encode(e.to_string()))?;
+
+            Ok(RenderedVariant {
+                name,
+                size,
+                format,
+                data: out.into_inner(),
+            })
+        })
+        .collect()
+}
+
+/// ETag of a stored avatar file (`variant` is "original" for the upload itself)
+pub fn etag(upload_uuid: &uuid::Uuid, variant: &str, size_bytes: i64) -> String {
+    format!("\"{}-{}-{}\"", upload_uuid, variant, size_bytes)
+}
+
+/// Whether an `If-None-Match` header matches the ETag (weak comparison)
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Read an avatar file through the storage driver, falling back to the
+/// legacy local upload locations for pictures stored before the driver
+pub async fn read(storage_path: &str) -> Result<Vec<u8>, StorageError> {
+    let driver_result = match storage::get_storage() {
+        Ok(storage) => storage.get(storage_path).await,
+        Err(e) => Err(e),
+    };
+
+    match driver_result {
+        Ok(data) => Ok(data),
+        Err(e) => uploads::read_file(storage_path).await.map_err(|_| e),
+    }
+}
+
+/// Delete an avatar file through the storage driver (and its legacy location)
+pub async fn delete(storage_path: &str) -> Result<bool, StorageError> {
+    let storage = storage::get_storage()?;
+    if storage.delete(storage_path).await? {
+        return Ok(true);
+    }
+    uploads::delete_file(storage_path)
+        .await
+        .map_err(|e| StorageError::IoError(std::io::Error::other(e.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let img = RgbaImage::from_pixel(width, height, Rgba([200, 40, 40, 255]));
+        let mut out = Cursor::new(Vec::new());
+        img.write_to(&mut out, ImageFormat::Png).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn sniffs_the_format_from_content() {
+        assert_eq!(
+            validate_with(&png(10, 10), 1 << 20, 100).unwrap(),
+            AvatarFormat::Png
+        );
+        assert!(matches!(
+            validate_with(b"GIF89a not really", 1 << 20, 100),
+            Err(AvatarError::UnsupportedFormat)
+        ));
+        assert!(matches!(
+            validate_with(b"<svg></svg>", 1 << 20, 100),
+            Err(AvatarError::UnsupportedFormat)
+        ));
+    }
+
+    #[test]
+    fn rejects_oversized_files_and_dimensions() {
+        let data = png(120, 40);
+        assert!(matches!(
+            validate_with(&data, 10, 1000),
+            Err(AvatarError::TooLarge { max_size: 10, .. })
+        ));
+        assert!(matches!(
+            validate_with(&data, 1 << 20, 100),
+            Err(AvatarError::DimensionsTooLarge {
+                width: 120,
+                height: 40,
+                max: 100
+            })
+        ));
+        assert!(matches!(
+            validate_with(&[], 10, 10),
+            Err(AvatarError::Empty)
+        ));
+    }
+
+    #[test]
+    fn renders_every_standard_size_as_a_square() {
+        let variants = render_variants(&png(300, 120)).unwrap();
+
+        assert_eq!(variants.len(), SIZES.len());
+        for (variant, (name, size)) in variants.iter().zip(SIZES) {
+            let img = image::load_from_memory(&variant.data).unwrap();
+            assert_eq!(variant.name, name);
+            assert_eq!((img.width(), img.height()), (size, size));
+            assert_eq!(variant.format, AvatarFormat::Png);
+        }
+    }
+
+    #[test]
+    fn matches_strong_weak_and_wildcard_etags() {
+        let uuid = uuid::Uuid::nil();
+        let tag = etag(&uuid, "small", 42);
+
+        assert!(etag_matches(&tag, &tag));
+        assert!(etag_matches(&format!("\"other\", W/{}", tag), &tag));
+        assert!(etag_matches("*", &tag));
+        assert!(!etag_matches(&etag(&uuid, "large", 42), &tag));
+    }
+}
@@ -0,0 +1,95 @@
+//! Client cache invalidation hints
+//!
+//! Frontends keep what they fetched over REST. When a mutation changes it, a
+//! `cache.invalidate` envelope naming the entity is published on
+//! `system.events`; the gateway turns it into a `cache.invalidate` server
+//! message for the affected users, who re-fetch only that entity instead of
+//! polling.
+//!
+//! Hints carry no data and are best effort: a client that misses one (it was
+//! offline, Kafka was down) re-fetches on its next page load anyway.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::app::chat::types::{Actor, Audience, EventEnvelope};
+
+/// Event type of the gateway push (and of the client's server message)
+pub const CACHE_INVALIDATE_EVENT: &str = "cache.invalidate";
+
+/// What a client may have cached, named as in the hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheEntity {
+    /// `GET /api/v1/user`: name, avatar, role
+    UserProfile,
+    /// The balance shown in the header and on the balance page
+    Balance,
+    /// `GET /api/v1/payments/history` and `GET /api/v1/balance/transfers`
+    Transactions,
+}
+
+impl CacheEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserProfile => "user_profile",
+            Self::Balance => "balance",
+            Self::Transactions => "transactions",
+        }
+    }
+}
+
+/// One entity a client should re-fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheHint {
+    pub entity: CacheEntity,
+    /// ID of the entity, e.g. the user ID for a profile
+    pub id: String,
+}
+
+impl CacheHint {
+    pub fn new(entity: CacheEntity, id: impl ToString) -> Self {
+        Self {
+            entity,
+            id: id.to_string(),
+        }
+    }
+
+    /// Gateway envelope pushing this hint to `user_ids`
+    pub fn envelope(&self, user_ids: Vec<i64>) -> EventEnvelope {
+        EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: CACHE_INVALIDATE_EVENT.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0, // System
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::users(user_ids),
+            payload: serde_json::json!({
+                "entity": self.entity.as_str(),
+                "id": self.id,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_names_the_entity_for_its_users() {
+        let envelope = CacheHint::new(CacheEntity::Balance, 7).envelope(vec![7]);
+
+        assert_eq!(envelope.event_type, CACHE_INVALIDATE_EVENT);
+        assert_eq!(envelope.audience.user_ids, vec!["7"]);
+        assert_eq!(envelope.payload["entity"], "balance");
+        assert_eq!(envelope.payload["id"], "7");
+    }
+}
@@ -0,0 +1,281 @@
+//! Two-tier cache
+//!
+//! Values are looked up in an in-process [`moka`] cache first, then in Redis,
+//! and only then loaded from the source of truth; a load fills both tiers.
+//!
+//! ```rust,ignore
+//! let profile = cache
+//!     .get_or_load(&UserProfileKey(user_id), || load_profile(&db, user_id))
+//!     .await?;
+//! ```
+//!
+//! Each cached type implements [`CacheKey`], which names its namespace and
+//! value type, so a key can only ever produce the value it was stored with.
+//! The in-process tier lives in a [`LocalTier`] that is created once per
+//! namespace and shared by every [`TwoTierCache`] of that namespace, so an
+//! invalidation made by an HTTP controller is seen by the event handlers of
+//! the same process. Other instances only drop their copy when it expires,
+//! which is why the local TTL is kept much shorter than the Redis one.
+//!
+//! Like the other Redis-backed caches, Redis errors are logged and treated as
+//! a miss (fails open).
+
+pub mod invalidation;
+pub mod user_profile;
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use moka::sync::Cache;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::database::SharedRedis;
+
+pub use user_profile::{UserProfile, UserProfileCache, UserProfileKey};
+
+/// Typed key of a cached value
+pub trait CacheKey {
+    type Value: Serialize + DeserializeOwned + Clone + Send + Sync + 'static;
+
+    /// Namespace shared by all keys of this type, e.g. `user_profile`
+    const NAMESPACE: &'static str;
+
+    /// Identifier of this key within its namespace
+    fn id(&self) -> String;
+
+    /// Redis key of the shared tier
+    fn redis_key(&self) -> String {
+        format!("cache:{}:{}", Self::NAMESPACE, self.id())
+    }
+}
+
+/// Lookup counters of one namespace
+#[derive(Debug, Default)]
+pub struct CacheMetrics {
+    local_hits: AtomicU64,
+    redis_hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+/// Point-in-time view of a namespace's counters
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub namespace: &'static str,
+    pub local_hits: u64,
+    pub redis_hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub local_entries: u64,
+    /// Share of lookups answered by either tier (0 before the first lookup)
+    pub hit_ratio: f64,
+}
+
+impl CacheMetrics {
+    fn snapshot(&self, namespace: &'static str, local_entries: u64) -> CacheStats {
+        let local_hits = self.local_hits.load(Ordering::Relaxed);
+        let redis_hits = self.redis_hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = local_hits + redis_hits + misses;
+
+        CacheStats {
+            namespace,
+            local_hits,
+            redis_hits,
+            misses,
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            local_entries,
+            hit_ratio: if lookups == 0 {
+                0.0
+            } else {
+                (local_hits + redis_hits) as f64 / lookups as f64
+            },
+        }
+    }
+}
+
+/// In-process tier of a namespace and its counters; cloning shares both
+pub struct LocalTier<K: CacheKey> {
+    entries: Cache<String, K::Value>,
+    metrics: Arc<CacheMetrics>,
+}
+
+impl<K: CacheKey> Clone for LocalTier<K> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+impl<K: CacheKey> LocalTier<K> {
+    pub fn new(max_entries: u64, ttl: Duration) -> Self {
+        Self {
+            entries: Cache::builder().max_capacity(max_entries).time_to_live(ttl).build(),
+            metrics: Arc::new(CacheMetrics::default()),
+        }
+    }
+}
+
+/// In-process cache backed by a shared Redis tier
+pub struct TwoTierCache<K: CacheKey> {
+    local: LocalTier<K>,
+    redis: Option<SharedRedis>,
+    redis_ttl_seconds: u64,
+}
+
+impl<K: CacheKey> Clone for TwoTierCache<K> {
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            redis: self.redis.clone(),
+            redis_ttl_seconds: self.redis_ttl_seconds,
+        }
+    }
+}
+
+impl<K: CacheKey> TwoTierCache<K> {
+    pub fn new(local: LocalTier<K>, redis: Option<SharedRedis>, redis_ttl_seconds: u64) -> Self {
+        Self {
+            local,
+            redis,
+            redis_ttl_seconds,
+        }
+    }
+
+    /// Cached value of `key`, calling `load` only when neither tier has it
+    pub async fn get_or_load<F, Fut, E>(&self, key: &K, load: F) -> Result<K::Value, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<K::Value, E>>,
+    {
+        let id = key.id();
+        let metrics = &self.local.metrics;
+
+        if let Some(value) = self.local.entries.get(&id) {
+            metrics.local_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        if let Some(value) = self.get_shared(key).await {
+            metrics.redis_hits.fetch_add(1, Ordering::Relaxed);
+            self.local.entries.insert(id, value.clone());
+            return Ok(value);
+        }
+
+        metrics.misses.fetch_add(1, Ordering::Relaxed);
+        let value = load().await?;
+        self.set_shared(key, &value).await;
+        self.local.entries.insert(id, value.clone());
+
+        Ok(value)
+    }
+
+    /// Drop `key` from both tiers
+    pub async fn invalidate(&self, key: &K) {
+        self.local.entries.invalidate(&key.id());
+        self.local.metrics.invalidations.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(mut redis) = self.redis.clone() {
+            let result: Result<(), redis::RedisError> = redis.del(key.redis_key()).await;
+            if let Err(e) = result {
+                warn!(key = %key.redis_key(), error = %e, "Failed to invalidate cache entry");
+            }
+        }
+    }
+
+    /// Counters of this namespace since the process started
+    pub fn stats(&self) -> CacheStats {
+        self.local
+            .metrics
+            .snapshot(K::NAMESPACE, self.local.entries.entry_count())
+    }
+
+    async fn get_shared(&self, key: &K) -> Option<K::Value> {
+        let mut redis = self.redis.clone()?;
+
+        let cached: Option<String> = match redis.get(key.redis_key()).await {
+            Ok(cached) => cached,
+            Err(e) => {
+                warn!(key = %key.redis_key(), error = %e, "Failed to read cache entry");
+                return None;
+            }
+        };
+
+        serde_json::from_str(&cached?).ok()
+    }
+
+    async fn set_shared(&self, key: &K, value: &K::Value) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let Ok(json) = serde_json::to_string(value) else {
+            return;
+        };
+
+        let result: Result<(), redis::RedisError> =
+            redis.set_ex(key.redis_key(), json, self.redis_ttl_seconds).await;
+        if let Err(e) = result {
+            warn!(key = %key.redis_key(), error = %e, "Failed to write cache entry");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestKey(i64);
+
+    impl CacheKey for TestKey {
+        type Value = String;
+        const NAMESPACE: &'static str = "test";
+
+        fn id(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    fn cache() -> TwoTierCache<TestKey> {
+        TwoTierCache::new(LocalTier::new(100, Duration::from_secs(60)), None, 60)
+    }
+
+    #[test]
+    fn redis_keys_are_namespaced() {
+        assert_eq!(TestKey(42).redis_key(), "cache:test:42");
+    }
+
+    #[tokio::test]
+    async fn loads_once_until_invalidated() {
+        let cache = cache();
+        let load = |value: &'static str| move || async move { Ok::<_, ()>(value.to_string()) };
+
+        assert_eq!(cache.get_or_load(&TestKey(1), load("first")).await, Ok("first".to_string()));
+        assert_eq!(cache.get_or_load(&TestKey(1), load("second")).await, Ok("first".to_string()));
+
+        cache.invalidate(&TestKey(1)).await;
+        assert_eq!(cache.get_or_load(&TestKey(1), load("third")).await, Ok("third".to_string()));
+
+        let stats = cache.stats();
+        assert_eq!((stats.local_hits, stats.redis_hits, stats.misses), (1, 0, 2));
+        assert_eq!(stats.invalidations, 1);
+        assert!((stats.hit_ratio - 1.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn failed_loads_are_not_cached() {
+        let cache = cache();
+
+        assert_eq!(cache.get_or_load(&TestKey(2), || async { Err::<String, _>("down") }).await, Err("down"));
+        assert_eq!(
+            cache.get_or_load(&TestKey(2), || async { Ok::<_, &str>("up".to_string()) }).await,
+            Ok("up".to_string())
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! User profile cache
+//!
+//! Game and chat handlers show the name and avatar of the user behind every
+//! command. [`UserProfileCache`] answers those lookups from memory or Redis;
+//! entries are dropped when a `user.updated`, `user.profile_updated`,
+//! `user.deactivated` or `user.deleted` event is consumed, and by the
+//! controllers that change a profile.
+
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use super::{CacheKey, CacheStats, LocalTier, TwoTierCache};
+use crate::app::db_query::read::user;
+use crate::config::CacheConfig;
+use crate::database::SharedRedis;
+
+/// Public profile fields of a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_id: Option<i64>,
+    pub avatar_uuid: Option<Uuid>,
+}
+
+impl UserProfile {
+    /// Full name as shown to other players
+    pub fn display_name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+}
+
+impl From<user::User> for UserProfile {
+    fn from(user: user::User) -> Self {
+        Self {
+            id: user.id,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            avatar_id: user.avatar_id,
+            avatar_uuid: user.avatar_uuid,
+        }
+    }
+}
+
+/// Cache key of a user's profile
+pub struct UserProfileKey(pub i64);
+
+impl CacheKey for UserProfileKey {
+    type Value = UserProfile;
+    const NAMESPACE: &'static str = "user_profile";
+
+    fn id(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// In-process tier shared by every `UserProfileCache` of this process
+static LOCAL: Lazy<LocalTier<UserProfileKey>> = Lazy::new(|| {
+    LocalTier::new(
+        CacheConfig::user_profile_max_entries(),
+        Duration::from_secs(CacheConfig::user_profile_local_ttl_seconds()),
+    )
+});
+
+/// Cached user profile lookups
+#[derive(Clone)]
+pub struct UserProfileCache {
+    cache: TwoTierCache<UserProfileKey>,
+}
+
+impl UserProfileCache {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self {
+            cache: TwoTierCache::new(LOCAL.clone(), redis, CacheConfig::user_profile_redis_ttl_seconds()),
+        }
+    }
+
+    /// Profile of `user_id`, loaded from Postgres on a miss
+    pub async fn get(&self, db: &Pool<Postgres>, user_id: i64) -> Result<UserProfile, sqlx::Error> {
+        self.cache
+            .get_or_load(&UserProfileKey(user_id), || async {
+                user::get_by_id(db, user_id).await.map(UserProfile::from)
+            })
+            .await
+    }
+
+    /// Drop the cached profile of `user_id` (call after changing it)
+    pub async fn invalidate(&self, user_id: i64) {
+        self.cache.invalidate(&UserProfileKey(user_id)).await;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+}
@@ -0,0 +1,191 @@
+//! Chat block mirror
+//!
+//! Blocks are stored in PostgreSQL (`user_blocks`) and mirrored to Redis so the
+//! WebSocket gateway can filter without a database:
+//!
+//! - `chat:blocks:{user_id}`: users `user_id` blocked; the gateway drops direct
+//!   messages and typing indicators from them
+//! - `chat:blocked_by:{user_id}`: users who blocked `user_id`; the gateway
+//!   hides `user_id`'s presence and live channel messages from them
+//!
+//! The whole mirror is rebuilt from PostgreSQL at startup, which also picks up
+//! blocks migrated from the friends table and changes made while Redis was
+//! down. A user's entries are rewritten again whenever they list their blocks.
+//! Without Redis the mirror is disabled and only the checks in blazing_sun
+//! apply.
+
+use std::collections::HashSet;
+
+use sqlx::{Pool, Postgres};
+use tracing::{info, warn};
+
+use crate::app::db_query::read::user_blocks as user_blocks_read;
+use crate::database::SharedRedis;
+
+/// Keys returned per SCAN call while rebuilding
+const SCAN_COUNT: usize = 500;
+
+fn blocks_key(user_id: i64) -> String {
+    format!("chat:blocks:{}", user_id)
+}
+
+fn blocked_by_key(user_id: i64) -> String {
+    format!("chat:blocked_by:{}", user_id)
+}
+
+#[derive(Clone)]
+pub struct ChatBlocks {
+    redis: Option<SharedRedis>,
+}
+
+impl ChatBlocks {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    /// Mirror a new block
+    pub async fn block(&self, blocker_id: i64, blocked_id: i64) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let result: Result<(), redis::RedisError> = redis::pipe()
+            .atomic()
+            .sadd(blocks_key(blocker_id), blocked_id)
+            .ignore()
+            .sadd(blocked_by_key(blocked_id), blocker_id)
+            .ignore()
+            .query_async(&mut redis)
+            .await;
+
+        if let Err(e) = result {
+            warn!(blocker_id, blocked_id, error = %e, "Failed to mirror block to Redis");
+        }
+    }
+
+    /// Mirror a removed block
+    pub async fn unblock(&self, blocker_id: i64, blocked_id: i64) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let result: Result<(), redis::RedisError> = redis::pipe()
+            .atomic()
+            .srem(blocks_key(blocker_id), blocked_id)
+            .ignore()
+            .srem(blocked_by_key(blocked_id), blocker_id)
+            .ignore()
+            .query_async(&mut redis)
+            .await;
+
+        if let Err(e) = result {
+            warn!(blocker_id, blocked_id, error = %e, "Failed to mirror unblock to Redis");
+        }
+    }
+
+    /// Rewrite a user's block list from the users they blocked in PostgreSQL,
+    /// including the `blocked_by` entries of users no longer on it
+    pub async fn sync(&self, blocker_id: i64, blocked_ids: &[i64]) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let mirrored: Vec<i64> = match redis::cmd("SMEMBERS")
+            .arg(blocks_key(blocker_id))
+            .query_async(&mut redis)
+            .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                warn!(blocker_id, error = %e, "Failed to read mirrored blocks from Redis");
+                return;
+            }
+        };
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(blocks_key(blocker_id)).ignore();
+        for stale_id in mirrored.iter().filter(|id| !blocked_ids.contains(id)) {
+            pipe.srem(blocked_by_key(*stale_id), blocker_id).ignore();
+        }
+        for blocked_id in blocked_ids {
+            pipe.sadd(blocks_key(blocker_id), *blocked_id)
+                .ignore()
+                .sadd(blocked_by_key(*blocked_id), blocker_id)
+                .ignore();
+        }
+
+        let result: Result<(), redis::RedisError> = pipe.query_async(&mut redis).await;
+        if let Err(e) = result {
+            warn!(blocker_id, error = %e, "Failed to sync blocks to Redis");
+        }
+    }
+
+    /// Rewrite the whole mirror from PostgreSQL (at startup)
+    pub async fn rebuild_all(&self, db: &Pool<Postgres>) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let pairs = match user_blocks_read::all(db).await {
+            Ok(pairs) => pairs,
+            Err(e) => {
+                warn!(error = %e, "Failed to load blocks for the Redis mirror");
+                return;
+            }
+        };
+
+        let mut stale = HashSet::new();
+        for pattern in ["chat:blocks:*", "chat:blocked_by:*"] {
+            match scan_keys(&mut redis, pattern).await {
+                Ok(keys) => stale.extend(keys),
+                Err(e) => {
+                    warn!(error = %e, "Failed to scan the block mirror in Redis");
+                    return;
+                }
+            }
+        }
+
+        // One transaction, so the gateway never sees a half-written mirror
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for key in &stale {
+            pipe.del(key).ignore();
+        }
+        for (blocker_id, blocked_id) in &pairs {
+            pipe.sadd(blocks_key(*blocker_id), *blocked_id)
+                .ignore()
+                .sadd(blocked_by_key(*blocked_id), *blocker_id)
+                .ignore();
+        }
+
+        let result: Result<(), redis::RedisError> = pipe.query_async(&mut redis).await;
+        match result {
+            Ok(()) => info!(blocks = pairs.len(), "Chat block mirror rebuilt"),
+            Err(e) => warn!(error = %e, "Failed to rebuild the block mirror in Redis"),
+        }
+    }
+}
+
+/// Every key matching `pattern`, read with SCAN so Redis is never blocked
+async fn scan_keys(
+    redis: &mut SharedRedis,
+    pattern: &str,
+) -> Result<Vec<String>, redis::RedisError> {
+    let mut keys = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query_async(redis)
+            .await?;
+        keys.extend(batch);
+        if next == 0 {
+            return Ok(keys);
+        }
+        cursor = next;
+    }
+}
@@ -3,7 +3,12 @@
 //! This module handles chat functionality including:
 //! - Private messages (stored in MongoDB)
 //! - Public lobby messages (stored in PostgreSQL)
+//! - Lobby/global channel messages (stored in MongoDB, channels in PostgreSQL)
 //! - Kafka command handlers for WebSocket gateway
+//! - Redis mirrors of user blocks and unread direct messages for the WebSocket gateway
 
+pub mod blocks;
+pub mod mongodb_channel;
 pub mod mongodb_chat;
 pub mod types;
+pub mod unread;
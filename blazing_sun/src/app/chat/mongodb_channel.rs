@@ -0,0 +1,204 @@
+//! MongoDB channel chat operations
+//!
+//! Handles message storage and paginated history for lobby/global chat channels.
+//! Channel definitions and membership live in PostgreSQL (`chat_channels`).
+
+use super::types::{ChannelMessage, MessageType};
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::options::{FindOptions, IndexOptions};
+use mongodb::{Collection, Database, IndexModel};
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Collection name for channel messages
+const COLLECTION_CHANNEL_MESSAGES: &str = "channel_messages";
+
+/// MongoDB channel chat client
+pub struct MongoChannelClient {
+    db: Arc<Database>,
+}
+
+impl MongoChannelClient {
+    /// Create a new MongoDB channel chat client
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Get the channel messages collection
+    fn messages(&self) -> Collection<ChannelMessage> {
+        self.db.collection(COLLECTION_CHANNEL_MESSAGES)
+    }
+
+    /// Initialize indexes for the channel messages collection
+    pub async fn init_indexes(&self) -> Result<(), mongodb::error::Error> {
+        // History and unread counts both walk a channel by _id
+        let channel_index = IndexModel::builder()
+            .keys(doc! { "channel_id": 1, "_id": -1 })
+            .options(IndexOptions::builder().name("channel_messages_idx".to_string()).build())
+            .build();
+
+        // Index for querying messages by user (for moderation)
+        let sender_index = IndexModel::builder()
+            .keys(doc! { "sender_id": 1, "_id": -1 })
+            .options(IndexOptions::builder().name("sender_messages_idx".to_string()).build())
+            .build();
+
+        self.messages()
+            .create_indexes([channel_index, sender_index])
+            .await?;
+
+        info!("MongoDB channel chat indexes initialized");
+        Ok(())
+    }
+
+    /// Store a message in a channel
+    pub async fn send_message(
+        &self,
+        channel_id: i64,
+        sender_id: i64,
+        sender_username: &str,
+        sender_avatar_id: Option<i64>,
+        content: &str,
+    ) -> Result<ChannelMessage, mongodb::error::Error> {
+        let mut message = ChannelMessage {
+            id: None,
+            channel_id,
+            sender_id,
+            sender_username: sender_username.to_string(),
+            sender_avatar_id,
+            content: content.to_string(),
+            message_type: MessageType::Text,
+            created_at: Utc::now(),
+        };
+
+        let result = self.messages().insert_one(&message).await?;
+        message.id = result.inserted_id.as_object_id();
+
+        Ok(message)
+    }
+
+    /// Get a page of channel history, newest page first but returned in chronological order.
+    /// Messages of `hidden_senders` (users the reader blocked) are left out.
+    ///
+    /// `before` is the ObjectId of the oldest message the client already has.
+    pub async fn get_messages(
+        &self,
+        channel_id: i64,
+        limit: i64,
+        before: Option<ObjectId>,
+        hidden_senders: &[i64],
+    ) -> Result<Vec<ChannelMessage>, mongodb::error::Error> {
+        let mut filter = doc! { "channel_id": channel_id };
+
+        if let Some(before_id) = before {
+            filter.insert("_id", doc! { "$lt": before_id });
+        }
+
+        if !hidden_senders.is_empty() {
+            filter.insert("sender_id", doc! { "$nin": hidden_senders });
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "_id": -1 })
+            .limit(limit)
+            .build();
+
+        let mut cursor = self.messages().find(filter).with_options(options).await?;
+        let mut messages = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(msg) = cursor.next().await {
+            match msg {
+                Ok(m) => messages.push(m),
+                Err(e) => error!("Error reading channel message: {}", e),
+            }
+        }
+
+        // Reverse to get chronological order
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// Count messages posted after the user's read marker (excluding their own)
+    pub async fn count_unread(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+        last_read: Option<ObjectId>,
+    ) -> Result<u64, mongodb::error::Error> {
+        let mut filter = doc! {
+            "channel_id": channel_id,
+            "sender_id": { "$ne": user_id }
+        };
+
+        if let Some(last_read_id) = last_read {
+            filter.insert("_id", doc! { "$gt": last_read_id });
+        }
+
+        self.messages().count_documents(filter).await
+    }
+
+    /// Erase the sender name, avatar and content of a user's messages (GDPR erasure)
+    pub async fn anonymize_sender(
+        &self,
+        user_id: i64,
+        username: &str,
+        placeholder: &str,
+    ) -> Result<u64, mongodb::error::Error> {
+        let result = self
+            .messages()
+            .update_many(
+                doc! { "sender_id": user_id },
+                doc! { "$set": {
+                    "sender_username": username,
+                    "sender_avatar_id": null,
+                    "content": placeholder,
+                } },
+            )
+            .await?;
+
+        Ok(result.modified_count)
+    }
+
+    /// A page of the messages a user posted in any channel, oldest first (data exports)
+    pub async fn get_sent_messages(
+        &self,
+        user_id: i64,
+        limit: i64,
+        skip: u64,
+    ) -> Result<Vec<ChannelMessage>, mongodb::error::Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "_id": 1 })
+            .limit(limit)
+            .skip(skip)
+            .build();
+
+        let mut cursor = self
+            .messages()
+            .find(doc! { "sender_id": user_id })
+            .with_options(options)
+            .await?;
+        let mut messages = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(msg) = cursor.next().await {
+            match msg {
+                Ok(m) => messages.push(m),
+                Err(e) => error!("Error reading channel message: {}", e),
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Delete all messages of a channel (used when the channel is deleted)
+    pub async fn delete_channel_messages(&self, channel_id: i64) -> Result<u64, mongodb::error::Error> {
+        let result = self
+            .messages()
+            .delete_many(doc! { "channel_id": channel_id })
+            .await?;
+
+        Ok(result.deleted_count)
+    }
+}
@@ -2,12 +2,17 @@
 //!
 //! Handles private message storage and retrieval in MongoDB.
 //! Private chats are stored indefinitely (unlike public lobby messages which are capped).
+//!
+//! Messages are threaded by `conversation_id` and carry delivered/read
+//! receipts: `delivered_at` is set when one of the recipient's clients
+//! acknowledges them, `read`/`read_at` when the recipient reads them.
 
-use super::types::{ConversationSummary, MessageType, PrivateMessage};
+use super::types::{conversation_id, MessageType, PrivateMessage};
 use chrono::Utc;
 use mongodb::bson::{doc, oid::ObjectId, Document};
-use mongodb::options::{FindOptions, IndexOptions, UpdateOptions};
+use mongodb::options::{FindOptions, IndexOptions};
 use mongodb::{Collection, Database, IndexModel};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tracing::{error, info};
 
@@ -58,8 +63,19 @@ impl MongoChatClient {
             .options(IndexOptions::builder().name("created_at_idx".to_string()).build())
             .build();
 
+        // Index for paging through a conversation thread
+        let thread_index = IndexModel::builder()
+            .keys(doc! { "conversation_id": 1, "_id": -1 })
+            .options(IndexOptions::builder().name("thread_idx".to_string()).build())
+            .build();
+
         collection
-            .create_indexes([conversation_index, user_messages_index, created_at_index])
+            .create_indexes([
+                conversation_index,
+                user_messages_index,
+                created_at_index,
+                thread_index,
+            ])
             .await?;
 
         info!("MongoDB chat indexes initialized");
@@ -76,10 +92,12 @@ impl MongoChatClient {
     ) -> Result<PrivateMessage, mongodb::error::Error> {
         let message = PrivateMessage {
             id: None,
+            conversation_id: Some(conversation_id(sender_id, recipient_id)),
             sender_id,
             recipient_id,
             content: content.to_string(),
             message_type,
+            delivered_at: None,
             read: false,
             read_at: None,
             created_at: Utc::now(),
@@ -96,7 +114,10 @@ impl MongoChatClient {
         })
     }
 
-    /// Get messages between two users (conversation)
+    /// Get a page of the conversation between two users, in chronological order.
+    ///
+    /// `before` is the ObjectId of the oldest message the client already has;
+    /// messages the user deleted are left out.
     pub async fn get_conversation(
         &self,
         user_id: i64,
@@ -130,10 +151,23 @@ impl MongoChatClient {
         }
 
         let options = FindOptions::builder()
-            .sort(doc! { "created_at": -1 })
+            .sort(doc! { "_id": -1 })
             .limit(limit)
             .build();
 
+        let mut messages = self.find_messages(filter, Some(options)).await?;
+
+        // Reverse to get chronological order
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// Collect the messages matching a filter
+    async fn find_messages(
+        &self,
+        filter: Document,
+        options: Option<FindOptions>,
+    ) -> Result<Vec<PrivateMessage>, mongodb::error::Error> {
         let mut cursor = self.messages().find(filter).with_options(options).await?;
         let mut messages = Vec::new();
 
@@ -148,7 +182,69 @@ impl MongoChatClient {
         Ok(messages)
     }
 
-    /// Mark messages as read
+    /// Record that messages reached one of the recipient's clients.
+    ///
+    /// Only messages sent to `user_id` that were not delivered yet are
+    /// updated; they are returned so the senders can be told.
+    pub async fn mark_delivered(
+        &self,
+        user_id: i64,
+        message_ids: &[ObjectId],
+    ) -> Result<Vec<PrivateMessage>, mongodb::error::Error> {
+        let filter = doc! {
+            "_id": { "$in": message_ids },
+            "recipient_id": user_id,
+            "delivered_at": null
+        };
+
+        let messages = self.find_messages(filter.clone(), None).await?;
+        if messages.is_empty() {
+            return Ok(messages);
+        }
+
+        let update = doc! { "$set": { "delivered_at": Utc::now().to_rfc3339() } };
+        self.messages_raw().update_many(filter, update).await?;
+
+        Ok(messages)
+    }
+
+    /// Mark specific messages sent to `user_id` as read (and delivered, if no
+    /// delivered receipt arrived first), returning the ones that were unread
+    pub async fn mark_read(
+        &self,
+        user_id: i64,
+        message_ids: &[ObjectId],
+    ) -> Result<Vec<PrivateMessage>, mongodb::error::Error> {
+        let filter = doc! {
+            "_id": { "$in": message_ids },
+            "recipient_id": user_id,
+            "read": false
+        };
+
+        let messages = self.find_messages(filter.clone(), None).await?;
+        if messages.is_empty() {
+            return Ok(messages);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        self.messages_raw()
+            .update_many(
+                doc! {
+                    "_id": { "$in": message_ids },
+                    "recipient_id": user_id,
+                    "delivered_at": null
+                },
+                doc! { "$set": { "delivered_at": &now } },
+            )
+            .await?;
+        self.messages_raw()
+            .update_many(filter, doc! { "$set": { "read": true, "read_at": &now } })
+            .await?;
+
+        Ok(messages)
+    }
+
+    /// Mark every message from `sender_id` to `user_id` as read
     pub async fn mark_messages_read(
         &self,
         user_id: i64,
@@ -160,13 +256,26 @@ impl MongoChatClient {
             "read": false
         };
 
+        let now = Utc::now().to_rfc3339();
         let update = doc! {
             "$set": {
                 "read": true,
-                "read_at": Utc::now().to_rfc3339()
+                "read_at": &now
             }
         };
 
+        self.messages_raw()
+            .update_many(
+                doc! {
+                    "sender_id": sender_id,
+                    "recipient_id": user_id,
+                    "read": false,
+                    "delivered_at": null
+                },
+                doc! { "$set": { "delivered_at": &now } },
+            )
+            .await?;
+
         let result = self.messages_raw().update_many(filter, update).await?;
         Ok(result.modified_count)
     }
@@ -196,6 +305,35 @@ impl MongoChatClient {
         self.messages().count_documents(filter).await
     }
 
+    /// Unread messages sent to a user, per conversation id
+    pub async fn count_unread_by_conversation(
+        &self,
+        user_id: i64,
+    ) -> Result<BTreeMap<String, u32>, mongodb::error::Error> {
+        let pipeline = vec![
+            doc! { "$match": { "recipient_id": user_id, "read": false } },
+            doc! { "$group": { "_id": "$sender_id", "count": { "$sum": 1 } } },
+        ];
+
+        let mut cursor = self.messages_raw().aggregate(pipeline).await?;
+        let mut counts = BTreeMap::new();
+
+        use futures::StreamExt;
+        while let Some(doc) = cursor.next().await {
+            match doc {
+                Ok(d) => {
+                    let (Ok(sender_id), Ok(count)) = (d.get_i64("_id"), d.get_i32("count")) else {
+                        continue;
+                    };
+                    counts.insert(conversation_id(user_id, sender_id), count.max(0) as u32);
+                }
+                Err(e) => error!("Error reading unread count: {}", e),
+            }
+        }
+
+        Ok(counts)
+    }
+
     /// Get conversation list for a user (recent conversations with last message)
     pub async fn get_conversations(
         &self,
@@ -298,6 +436,50 @@ impl MongoChatClient {
         self.messages().find_one(filter).await
     }
 
+    /// Erase the content of every message a user has sent (GDPR erasure)
+    pub async fn anonymize_sender(
+        &self,
+        user_id: i64,
+        placeholder: &str,
+    ) -> Result<u64, mongodb::error::Error> {
+        let result = self
+            .messages_raw()
+            .update_many(
+                doc! { "sender_id": user_id },
+                doc! { "$set": { "content": placeholder } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
+    /// A page of every message a user sent or received, oldest first (data exports)
+    pub async fn get_user_messages(
+        &self,
+        user_id: i64,
+        limit: i64,
+        skip: u64,
+    ) -> Result<Vec<PrivateMessage>, mongodb::error::Error> {
+        let filter = doc! { "$or": [{ "sender_id": user_id }, { "recipient_id": user_id }] };
+        let options = FindOptions::builder()
+            .sort(doc! { "_id": 1 })
+            .limit(limit)
+            .skip(skip)
+            .build();
+
+        let mut cursor = self.messages().find(filter).with_options(options).await?;
+        let mut messages = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(msg) = cursor.next().await {
+            match msg {
+                Ok(m) => messages.push(m),
+                Err(e) => error!("Error reading private message: {}", e),
+            }
+        }
+
+        Ok(messages)
+    }
+
     /// Check if user can access a message (is sender or recipient)
     pub async fn can_access_message(
         &self,
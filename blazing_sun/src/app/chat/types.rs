@@ -31,15 +31,26 @@ where
     serializer.serialize_str(&value.to_string())
 }
 
+/// Thread id of the direct messages between two users: `dm:{lower id}:{higher id}`
+pub fn conversation_id(user_id: i64, other_user_id: i64) -> String {
+    format!("dm:{}:{}", user_id.min(other_user_id), user_id.max(other_user_id))
+}
+
 /// Private message structure (stored in MongoDB)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivateMessage {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<mongodb::bson::oid::ObjectId>,
+    /// See [`conversation_id`]; missing on messages stored before threads
+    #[serde(default)]
+    pub conversation_id: Option<String>,
     pub sender_id: i64,
     pub recipient_id: i64,
     pub content: String,
     pub message_type: MessageType,
+    /// When one of the recipient's clients acknowledged the message
+    #[serde(default)]
+    pub delivered_at: Option<DateTime<Utc>>,
     pub read: bool,
     pub read_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -67,6 +78,7 @@ impl Default for MessageType {
 /// Conversation summary for listing chats
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationSummary {
+    pub conversation_id: String,
     pub user_id: i64,
     pub username: String,
     pub avatar_id: Option<i64>,
@@ -113,6 +125,7 @@ pub enum ChatEvent {
     #[serde(rename = "message_received")]
     MessageReceived {
         message_id: String,
+        conversation_id: String,
         sender_id: i64,
         sender_username: String,
         sender_avatar_id: Option<i64>,
@@ -132,8 +145,19 @@ pub enum ChatEvent {
         message_type: String,
         created_at: String,
     },
+    #[serde(rename = "message_delivered")]
+    MessageDelivered {
+        conversation_id: String,
+        message_ids: Vec<String>,
+        recipient_id: i64,
+        sender_id: i64,
+        delivered_at: String,
+    },
     #[serde(rename = "message_read")]
     MessageRead {
+        conversation_id: String,
+        /// Empty when a legacy `mark_read` marked the whole conversation
+        message_ids: Vec<String>,
         reader_id: i64,
         sender_id: i64,
         read_at: String,
@@ -144,6 +168,29 @@ pub enum ChatEvent {
         recipient_id: i64,
         is_typing: bool,
     },
+    #[serde(rename = "channel_joined")]
+    ChannelJoined {
+        channel_id: i64,
+        channel_name: String,
+        user_id: i64,
+        last_read_message_id: Option<String>,
+    },
+    #[serde(rename = "channel_left")]
+    ChannelLeft {
+        channel_id: i64,
+        user_id: i64,
+    },
+    #[serde(rename = "channel_message")]
+    ChannelMessageReceived {
+        message_id: String,
+        channel_id: i64,
+        sender_id: i64,
+        sender_username: String,
+        sender_avatar_id: Option<i64>,
+        content: String,
+        message_type: String,
+        created_at: String,
+    },
     #[serde(rename = "error")]
     Error {
         code: String,
@@ -152,6 +199,28 @@ pub enum ChatEvent {
     },
 }
 
+impl ChatEvent {
+    /// Event name used in the `chat.event.*` envelope type
+    pub fn event_type_name(&self) -> &'static str {
+        match self {
+            ChatEvent::MessageReceived { .. } => "message_received",
+            ChatEvent::LobbyMessageReceived { .. } => "lobby_message_received",
+            ChatEvent::MessageDelivered { .. } => "message_delivered",
+            ChatEvent::MessageRead { .. } => "message_read",
+            ChatEvent::TypingIndicator { .. } => "typing_indicator",
+            ChatEvent::ChannelJoined { .. } => "channel_joined",
+            ChatEvent::ChannelLeft { .. } => "channel_left",
+            ChatEvent::ChannelMessageReceived { .. } => "channel_message",
+            ChatEvent::Error { .. } => "error",
+        }
+    }
+}
+
+/// Gateway room that the connections of a channel's members are registered in
+pub fn channel_room(channel_id: i64) -> String {
+    format!("channel:{}", channel_id)
+}
+
 /// Event envelope for Kafka messages (matches ws_gateway protocol)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventEnvelope {
@@ -252,3 +321,17 @@ impl Audience {
         }
     }
 }
+
+/// Message posted to a lobby/global chat channel (stored in MongoDB)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMessage {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<mongodb::bson::oid::ObjectId>,
+    pub channel_id: i64,
+    pub sender_id: i64,
+    pub sender_username: String,
+    pub sender_avatar_id: Option<i64>,
+    pub content: String,
+    pub message_type: MessageType,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,74 @@
+//! Unread direct-message mirror
+//!
+//! Unread counts live in MongoDB (`read: false` on private messages) and are
+//! mirrored to Redis so the WebSocket gateway can include them in
+//! `system.state_snapshot` without a database:
+//!
+//! - `chat:unread:{user_id}`: hash of conversation id to the number of
+//!   messages `user_id` has not read in it
+//!
+//! Every write sets the exact count read back from MongoDB, so a missed update
+//! is corrected by the next message or receipt in the same conversation, and
+//! listing conversations rewrites the whole hash. Without Redis the mirror is
+//! disabled and snapshots report no unread messages.
+
+use std::collections::BTreeMap;
+
+use tracing::warn;
+
+use crate::database::SharedRedis;
+
+fn unread_key(user_id: i64) -> String {
+    format!("chat:unread:{}", user_id)
+}
+
+#[derive(Clone)]
+pub struct ChatUnread {
+    redis: Option<SharedRedis>,
+}
+
+impl ChatUnread {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    /// Mirror the unread count of one conversation; zero removes the entry
+    pub async fn set(&self, user_id: i64, conversation_id: &str, count: u64) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let mut cmd = if count == 0 {
+            redis::cmd("HDEL")
+        } else {
+            redis::cmd("HSET")
+        };
+        cmd.arg(unread_key(user_id)).arg(conversation_id);
+        if count > 0 {
+            cmd.arg(count);
+        }
+
+        let result: Result<(), redis::RedisError> = cmd.query_async(&mut redis).await;
+        if let Err(e) = result {
+            warn!(user_id, conversation_id, error = %e, "Failed to mirror unread count to Redis");
+        }
+    }
+
+    /// Rewrite a user's unread counts
+    pub async fn sync(&self, user_id: i64, counts: &BTreeMap<String, u32>) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(unread_key(user_id)).ignore();
+        for (conversation_id, count) in counts.iter().filter(|(_, count)| **count > 0) {
+            pipe.hset(unread_key(user_id), conversation_id, *count).ignore();
+        }
+
+        let result: Result<(), redis::RedisError> = pipe.query_async(&mut redis).await;
+        if let Err(e) = result {
+            warn!(user_id, error = %e, "Failed to sync unread counts to Redis");
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Checkout Service HTTP Client
+//!
+//! Reads data owned by the checkout service through its internal API
+//! (`/internal/...`), authenticated with short-lived service tokens signed
+//! with `SERVICE_AUTH_KEYS`. The calls themselves live in the shared
+//! `checkout_client` crate; this module configures it for the app.
+
+use checkout_client::{CheckoutApi, ClientConfig, HttpCheckoutClient};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use service_auth::{KeyRing, Signer};
+use std::time::Duration;
+
+use crate::config::{AppConfig, ServiceAuthConfig};
+
+pub use checkout_client::{CheckoutClientError, CheckoutTransaction};
+
+/// Internal calls are on the request path of user-facing endpoints, keep them short
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Issuer this app signs as
+const ISSUER: &str = "blazing_sun";
+
+/// Service tokens are None when SERVICE_AUTH_KEYS is missing or invalid
+static CLIENT: Lazy<HttpCheckoutClient> = Lazy::new(|| {
+    let signer = match KeyRing::parse(ServiceAuthConfig::keys()) {
+        Ok(keys) => {
+            Some(Signer::new(keys, ISSUER).with_ttl(ServiceAuthConfig::token_ttl_seconds()))
+        }
+        Err(e) => {
+            tracing::warn!("Checkout client disabled: {}", e);
+            None
+        }
+    };
+
+    HttpCheckoutClient::new(
+        ClientConfig::new(AppConfig::checkout_service_url()).with_timeout(REQUEST_TIMEOUT),
+    )
+    .with_signer(signer)
+});
+
+/// Client for the checkout service
+pub fn client() -> &'static dyn CheckoutApi {
+    &*CLIENT
+}
+
+/// Fetch a user's checkout transactions created before `before` (newest first)
+pub async fn fetch_user_transactions(
+    user_id: i64,
+    before: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<CheckoutTransaction>, CheckoutClientError> {
+    client().user_transactions(user_id, before, limit).await
+}
@@ -1,7 +1,19 @@
+pub mod client;
+
+use chrono::Utc;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::sync::{oneshot, Mutex};
+use uuid::Uuid;
+
+use crate::app::chat::types::{Actor, Audience, EventEnvelope as GatewayEnvelope};
+
+/// Gateway push carrying the Stripe URL of a Kafka checkout whose API request
+/// is no longer waiting (timed out, or answered by another instance)
+pub const SESSION_READY_EVENT: &str = "checkout.event.session_ready";
+/// Gateway push for a Kafka checkout whose session could not be opened
+pub const SESSION_FAILED_EVENT: &str = "checkout.event.session_failed";
 
 // ============================================================================
 // Checkout Flow Types (checkout and checkout_finished topics)
@@ -51,8 +63,9 @@ impl CheckoutKafkaRequest {
 }
 
 /// Event received from "checkout_finished" topic
-/// Received in three scenarios:
+/// Received in four scenarios:
 /// - status="session_created": Stripe session created (includes session_url for redirect)
+/// - status="session_failed": No session could be opened for the request
 /// - status="success": Payment succeeded (update user balance)
 /// - status="failed": Payment failed (log warning)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,18 +80,37 @@ pub struct CheckoutFinishedEvent {
     pub currency: String,
     /// Purpose of the checkout
     pub purpose: String,
-    /// Payment status: "session_created", "success", or "failed"
+    /// Payment status: "session_created", "session_failed", "success", or "failed"
     pub status: String,
     /// Stripe session ID (if available)
     pub session_id: Option<String>,
     /// Stripe session URL for redirect (only for status="session_created")
     pub session_url: Option<String>,
+    /// RFC 3339 time the session stops accepting payment (only for status="session_created")
+    #[serde(default)]
+    pub expires_at: Option<String>,
     /// Stripe payment intent ID (if available)
     pub payment_intent_id: Option<String>,
     /// Error message (if failed)
     pub error_message: Option<String>,
+    /// Stable cause of a status="session_failed", e.g. "stripe_api" or
+    /// "daily_volume_exceeded"
+    #[serde(default)]
+    pub error_code: Option<String>,
     /// ISO 8601 timestamp when checkout finished
     pub timestamp: String,
+    /// Promo code used for the checkout (if any)
+    #[serde(default)]
+    pub coupon: Option<CheckoutCoupon>,
+}
+
+/// Promo code applied to a checkout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckoutCoupon {
+    pub coupon_id: i64,
+    pub code: String,
+    /// Taken off `amount_cents`; the user paid `amount_cents - discount_cents`
+    pub discount_cents: i64,
 }
 
 impl CheckoutFinishedEvent {
@@ -87,6 +119,11 @@ impl CheckoutFinishedEvent {
         self.status == "session_created"
     }
 
+    /// Check if no session could be opened for the request
+    pub fn is_session_failed(&self) -> bool {
+        self.status == "session_failed"
+    }
+
     /// Check if the payment was successful
     pub fn is_success(&self) -> bool {
         self.status == "success"
@@ -98,18 +135,76 @@ impl CheckoutFinishedEvent {
     }
 }
 
+/// Event received from the "checkout.disputes" topic
+/// - status="opened": A chargeback was opened; freeze `hold_cents` coins
+/// - status="won": Closed in our favour; release the frozen coins
+/// - status="lost": Closed against us; the frozen coins are deducted for good
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutDisputeEvent {
+    /// Stripe dispute ID
+    pub dispute_id: String,
+    /// Checkout the disputed coins were credited by
+    pub request_id: String,
+    pub user_id: i64,
+    /// "opened", "won" or "lost"
+    pub status: String,
+    /// Disputed amount, in the payment's currency
+    pub amount_cents: i64,
+    pub currency: String,
+    /// Coins to freeze
+    pub hold_cents: i64,
+    /// Stripe dispute reason, e.g. "fraudulent"
+    pub reason: String,
+    #[serde(default)]
+    pub payment_intent_id: Option<String>,
+    /// ISO 8601 timestamp of the change
+    pub timestamp: String,
+}
+
+/// Incoming event from checkout on the "checkout.subscriptions" topic, keyed by user_id
+/// - SubscriptionActivated: Premium granted (or its paid period changed)
+/// - SubscriptionLapsed: Premium taken away (canceled, unpaid)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CheckoutSubscriptionEvent {
+    #[serde(rename = "checkout.subscription.activated")]
+    SubscriptionActivated {
+        /// Stripe subscription ID
+        subscription_id: String,
+        user_id: i64,
+        /// Stripe subscription status, e.g. "active"
+        status: String,
+        /// ISO 8601 end of the paid period
+        #[serde(default)]
+        current_period_end: Option<String>,
+        #[serde(default)]
+        cancel_at_period_end: bool,
+        /// ISO 8601 timestamp of the change
+        timestamp: String,
+    },
+    #[serde(rename = "checkout.subscription.lapsed")]
+    SubscriptionLapsed {
+        subscription_id: String,
+        user_id: i64,
+        status: String,
+        timestamp: String,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct CheckoutSessionResult {
     pub session_id: Option<String>,
     pub session_url: Option<String>,
+    pub expires_at: Option<String>,
     pub error: Option<String>,
 }
 
 impl CheckoutSessionResult {
-    pub fn success(session_id: String, session_url: String) -> Self {
+    pub fn success(session_id: String, session_url: String, expires_at: Option<String>) -> Self {
         Self {
             session_id: Some(session_id),
             session_url: Some(session_url),
+            expires_at,
             error: None,
         }
     }
@@ -118,9 +213,74 @@ impl CheckoutSessionResult {
         Self {
             session_id: None,
             session_url: None,
+            expires_at: None,
             error: Some(error),
         }
     }
+
+    /// Result carried by a `session_created` or `session_failed` event
+    pub fn from_event(event: &CheckoutFinishedEvent) -> Self {
+        if event.is_session_failed() {
+            return Self::failure(
+                event
+                    .error_message
+                    .clone()
+                    .unwrap_or_else(|| "Checkout failed".to_string()),
+            );
+        }
+
+        match (&event.session_id, &event.session_url) {
+            (Some(session_id), Some(session_url)) => Self::success(
+                session_id.clone(),
+                session_url.clone(),
+                event.expires_at.clone(),
+            ),
+            _ => Self::failure("Checkout session created without a URL".to_string()),
+        }
+    }
+}
+
+/// Gateway envelope delivering a checkout result to the user's connections
+/// when the API request that asked for it has stopped waiting
+pub fn session_envelope(
+    user_id: i64,
+    request_id: &str,
+    result: &CheckoutSessionResult,
+) -> GatewayEnvelope {
+    let (event_type, payload) = match &result.error {
+        Some(error) => (
+            SESSION_FAILED_EVENT,
+            serde_json::json!({
+                "request_id": request_id,
+                "message": error,
+            }),
+        ),
+        None => (
+            SESSION_READY_EVENT,
+            serde_json::json!({
+                "request_id": request_id,
+                "session_id": result.session_id,
+                "url": result.session_url,
+                "expires_at": result.expires_at,
+            }),
+        ),
+    };
+
+    GatewayEnvelope {
+        event_id: Uuid::new_v4().to_string(),
+        event_type: event_type.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        correlation_id: Some(request_id.to_string()),
+        producer: "blazing_sun".to_string(),
+        actor: Actor {
+            user_id: 0, // System
+            username: "system".to_string(),
+            socket_id: String::new(),
+            roles: vec![],
+        },
+        audience: Audience::user(user_id),
+        payload,
+    }
 }
 
 static PENDING_CHECKOUTS: Lazy<Mutex<HashMap<String, oneshot::Sender<CheckoutSessionResult>>>> =
@@ -167,7 +327,10 @@ pub fn euros_to_cents(amount_eur: i64) -> Result<i64, &'static str> {
 
 #[cfg(test)]
 mod tests {
-    use super::{CheckoutFinishedEvent, CheckoutKafkaRequest, euros_to_cents};
+    use super::{
+        euros_to_cents, session_envelope, CheckoutFinishedEvent, CheckoutKafkaRequest,
+        CheckoutSessionResult, SESSION_FAILED_EVENT, SESSION_READY_EVENT,
+    };
 
     #[test]
     fn checkout_request_serializes_correctly() {
@@ -226,6 +389,71 @@ mod tests {
         assert!(!event.is_failed());
     }
 
+    #[test]
+    fn session_created_and_failed_events_become_session_results() {
+        let created: CheckoutFinishedEvent = serde_json::from_value(serde_json::json!({
+            "request_id": "req_abc",
+            "user_id": 7,
+            "amount_cents": 500,
+            "currency": "eur",
+            "purpose": "balance_topup",
+            "status": "session_created",
+            "session_id": "cs_test_123",
+            "session_url": "https://stripe.test/checkout",
+            "expires_at": "2026-10-18T10:30:00+00:00",
+            "payment_intent_id": null,
+            "error_message": null,
+            "timestamp": "2026-10-17T10:30:00Z"
+        }))
+        .expect("deserialize session_created");
+        let result = CheckoutSessionResult::from_event(&created);
+        assert_eq!(result.session_url.as_deref(), Some("https://stripe.test/checkout"));
+        assert_eq!(result.expires_at.as_deref(), Some("2026-10-18T10:30:00+00:00"));
+        assert!(result.error.is_none());
+
+        let failed: CheckoutFinishedEvent = serde_json::from_value(serde_json::json!({
+            "request_id": "req_def",
+            "user_id": 7,
+            "amount_cents": 500,
+            "currency": "eur",
+            "purpose": "balance_topup",
+            "status": "session_failed",
+            "session_id": null,
+            "session_url": null,
+            "payment_intent_id": null,
+            "error_message": "Daily payment limit reached",
+            "error_code": "daily_volume_exceeded",
+            "timestamp": "2026-10-17T10:30:00Z"
+        }))
+        .expect("deserialize session_failed");
+        assert!(failed.is_session_failed());
+        assert_eq!(failed.error_code.as_deref(), Some("daily_volume_exceeded"));
+        assert!(!failed.is_failed());
+        let result = CheckoutSessionResult::from_event(&failed);
+        assert_eq!(result.error.as_deref(), Some("Daily payment limit reached"));
+        assert!(result.session_url.is_none());
+    }
+
+    #[test]
+    fn session_envelopes_reach_only_the_requesting_user() {
+        let ready = CheckoutSessionResult::success(
+            "cs_test_123".to_string(),
+            "https://stripe.test/checkout".to_string(),
+            None,
+        );
+        let envelope = session_envelope(7, "req_abc", &ready);
+        assert_eq!(envelope.event_type, SESSION_READY_EVENT);
+        assert_eq!(envelope.correlation_id.as_deref(), Some("req_abc"));
+        assert_eq!(envelope.payload["url"], "https://stripe.test/checkout");
+        let audience = serde_json::to_value(&envelope.audience).expect("serialize audience");
+        assert_eq!(audience["user_ids"], serde_json::json!(["7"]));
+
+        let failed = CheckoutSessionResult::failure("Checkout failed".to_string());
+        let envelope = session_envelope(7, "req_abc", &failed);
+        assert_eq!(envelope.event_type, SESSION_FAILED_EVENT);
+        assert_eq!(envelope.payload["message"], "Checkout failed");
+    }
+
     #[test]
     fn checkout_finished_deserializes_success() {
         let payload = serde_json::json!({
@@ -248,6 +476,32 @@ mod tests {
         assert!(event.is_success());
         assert!(!event.is_session_created());
         assert!(!event.is_failed());
+        assert!(event.coupon.is_none());
+    }
+
+    #[test]
+    fn checkout_finished_deserializes_coupon() {
+        let payload = serde_json::json!({
+            "request_id": "req_ghi",
+            "user_id": 8,
+            "amount_cents": 1000,
+            "currency": "eur",
+            "purpose": "balance_topup",
+            "status": "success",
+            "session_id": "cs_test_789",
+            "session_url": null,
+            "payment_intent_id": "pi_test_012",
+            "error_message": null,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "coupon": { "coupon_id": 3, "code": "SPRING20", "discount_cents": 200 }
+        });
+
+        let event: CheckoutFinishedEvent =
+            serde_json::from_value(payload).expect("deserialize coupon");
+
+        let coupon = event.coupon.expect("coupon");
+        assert_eq!(coupon.code, "SPRING20");
+        assert_eq!(coupon.discount_cents, 200);
     }
 
     #[test]
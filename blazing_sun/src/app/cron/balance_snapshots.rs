@@ -0,0 +1,22 @@
+//! Balance Snapshots Cron Job
+//!
+//! Writes the end-of-day balance of every user with ledger activity into
+//! balance_daily_snapshots for each finished (UTC) day not covered yet, so
+//! `GET /api/v1/admin/users/{id}/balance?at=` only replays the ledger entries
+//! after the latest snapshot. Runs daily, shortly after midnight.
+
+use crate::app::db_query::mutations::balance_ledger as db_ledger_mutations;
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Run the balance snapshots job
+pub async fn run(db: Pool<Postgres>) {
+    let today = Utc::now().date_naive();
+
+    match db_ledger_mutations::snapshot_days(&db, today).await {
+        Ok(0) => {}
+        Ok(written) => info!("Wrote {} balance snapshot(s)", written),
+        Err(e) => error!("Balance snapshots: failed to write snapshots: {}", e),
+    }
+}
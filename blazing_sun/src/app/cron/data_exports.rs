@@ -0,0 +1,63 @@
+//! Data Export Cleanup Cron Job
+//!
+//! Deletes the archives of data exports whose download window has closed
+//! (`DATA_EXPORT_TTL_HOURS` after they were ready) and marks them expired.
+//! An archive that could not be deleted keeps its export ready, so it is
+//! tried again on the next run. Runs hourly.
+
+use crate::app::db_query::mutations::user_data_exports as db_mutations;
+use crate::app::db_query::read::user_data_exports as db_read;
+use crate::bootstrap::includes::storage::{self, StorageError};
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Most exports expired per run
+const BATCH_SIZE: i64 = 500;
+
+/// Run the data export cleanup job
+pub async fn run(db: Pool<Postgres>) {
+    let expired = match db_read::get_expired(&db, BATCH_SIZE).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            error!("Data exports: failed to load expired exports: {}", e);
+            return;
+        }
+    };
+
+    if expired.is_empty() {
+        return;
+    }
+
+    let storage = match storage::get_storage() {
+        Ok(storage) => storage,
+        Err(e) => {
+            error!("Data exports: storage unavailable: {}", e);
+            return;
+        }
+    };
+
+    let mut removed = 0;
+    for export in expired {
+        if let Some(path) = &export.storage_path {
+            match storage.delete(path).await {
+                Ok(_) | Err(StorageError::NotFound) => {}
+                Err(e) => {
+                    error!(
+                        "Data exports: failed to delete archive of export {}: {}",
+                        export.id, e
+                    );
+                    continue;
+                }
+            }
+        }
+
+        match db_mutations::mark_expired(&db, export.id).await {
+            Ok(()) => removed += 1,
+            Err(e) => error!("Data exports: failed to expire export {}: {}", export.id, e),
+        }
+    }
+
+    if removed > 0 {
+        info!("Expired {} data export(s)", removed);
+    }
+}
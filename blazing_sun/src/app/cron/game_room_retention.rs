@@ -0,0 +1,17 @@
+//! Game Room Retention Cron Job
+//!
+//! Purges expired room tombstones left behind by deleted/finished rooms.
+//! Runs every 5 minutes.
+
+use crate::database::mutations::game_room;
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Run the game room retention job
+pub async fn run(db: Pool<Postgres>) {
+    match game_room::purge_expired_tombstones(&db).await {
+        Ok(0) => {}
+        Ok(count) => info!("Purged {} expired game room tombstone(s)", count),
+        Err(e) => error!("Failed to purge game room tombstones: {}", e),
+    }
+}
@@ -0,0 +1,102 @@
+//! Game Type Stats Cron Job
+//!
+//! Recomputes the public per-game-type statistics served by
+//! `GET /api/v1/games/stats`: game counts and average duration from MongoDB
+//! game history, dice roll distribution for dice games, and finished /
+//! abandoned / disconnect-forfeit room counts from game_rooms.
+//! Runs hourly.
+
+use crate::app::games::fairness::DiceDistribution;
+use crate::app::games::mongodb_games::MongoGameClient;
+use crate::app::games::types::GameType;
+use crate::database::create_mongodb;
+use crate::database::mutations::game_type_stats::{self as db_stats_mutations, UpsertGameTypeStatsParams};
+use crate::database::read::game_type_stats as db_stats_read;
+use sqlx::{Pool, Postgres};
+use std::collections::BTreeMap;
+use tracing::{error, info};
+
+/// Run the game type stats job
+pub async fn run(db: Pool<Postgres>) {
+    let mongodb = match create_mongodb().await {
+        Ok(mongodb) => mongodb,
+        Err(e) => {
+            error!("Game type stats: failed to connect to MongoDB: {}", e);
+            return;
+        }
+    };
+    let games = MongoGameClient::new(mongodb);
+
+    let summaries = match games.get_game_type_summaries().await {
+        Ok(summaries) => summaries,
+        Err(e) => {
+            error!("Game type stats: failed to aggregate game history: {}", e);
+            return;
+        }
+    };
+
+    let outcomes = match db_stats_read::count_room_outcomes(&db).await {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            error!("Game type stats: failed to count room outcomes: {}", e);
+            return;
+        }
+    };
+
+    let mut stats: BTreeMap<String, UpsertGameTypeStatsParams> = BTreeMap::new();
+
+    for summary in summaries {
+        let params = entry(&mut stats, &summary.game_type);
+        params.total_games = summary.total_games;
+        params.avg_duration_seconds = summary.avg_duration_seconds;
+    }
+
+    for outcome in outcomes {
+        let params = entry(&mut stats, &outcome.game_type);
+        params.finished_rooms = outcome.finished_rooms;
+        params.disconnect_forfeits = outcome.disconnect_forfeits;
+        params.abandoned_rooms = outcome.abandoned_rooms;
+    }
+
+    // Only Bigger Dice records die rolls
+    let dice = GameType::BiggerDice;
+    match games.get_roll_counts(dice.clone()).await {
+        Ok(counts) => {
+            let distribution = DiceDistribution::from_counts(&counts);
+            entry(&mut stats, dice.as_str()).dice_distribution =
+                serde_json::to_value(distribution).ok();
+        }
+        Err(e) => error!("Game type stats: failed to count dice rolls: {}", e),
+    }
+
+    let mut updated = 0;
+    for params in stats.values() {
+        match db_stats_mutations::upsert(&db, params).await {
+            Ok(()) => updated += 1,
+            Err(e) => error!(
+                "Game type stats: failed to store stats for {}: {}",
+                params.game_type, e
+            ),
+        }
+    }
+
+    info!("Recomputed stats for {} game type(s)", updated);
+}
+
+/// Stats being collected for a game type, created empty on first use
+fn entry<'a>(
+    stats: &'a mut BTreeMap<String, UpsertGameTypeStatsParams>,
+    game_type: &str,
+) -> &'a mut UpsertGameTypeStatsParams {
+    stats
+        .entry(game_type.to_string())
+        .or_insert_with(|| UpsertGameTypeStatsParams {
+            game_type: game_type.to_string(),
+            total_games: 0,
+            avg_duration_seconds: 0.0,
+            finished_rooms: 0,
+            disconnect_forfeits: 0,
+            abandoned_rooms: 0,
+            dice_distribution: None,
+        })
+}
@@ -0,0 +1,77 @@
+//! Game Webhooks Cron Job
+//!
+//! Queues a `deliver_game_webhook` MQ job for every webhook delivery that is
+//! due: new deliveries, failed attempts whose backoff has elapsed, and
+//! deliveries whose job was lost while queued. Deliveries are claimed
+//! (-> `queued`) before being enqueued; one that could not be enqueued is
+//! released and picked up on the next run. Runs every 10 seconds.
+
+use crate::app::db_query::mutations::game_webhooks as db_mutations;
+use crate::app::db_query::read::game_webhooks as db_read;
+use crate::app::mq::jobs::DeliverGameWebhookParams;
+use crate::mq::{JobOptions, MessageQueue, QueuedJob};
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Deliveries queued per run
+const BATCH_SIZE: i64 = 100;
+
+/// Queued deliveries untouched for this long are queued again
+const STUCK_AFTER_MINUTES: i64 = 10;
+
+/// Run the game webhooks job
+pub async fn run(db: Pool<Postgres>) {
+    let due = match db_read::get_due_ids(&db, STUCK_AFTER_MINUTES, BATCH_SIZE).await {
+        Ok(due) => due,
+        Err(e) => {
+            error!("Game webhooks: failed to load due deliveries: {}", e);
+            return;
+        }
+    };
+
+    if due.is_empty() {
+        return;
+    }
+
+    let mq = match MessageQueue::new(db.clone()).await {
+        Ok(mq) => mq,
+        Err(e) => {
+            error!("Game webhooks: failed to connect to the message queue: {}", e);
+            return;
+        }
+    };
+
+    let mut queued = 0;
+    for delivery_id in due {
+        match db_mutations::mark_queued(&db, delivery_id).await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                error!("Game webhooks: failed to claim delivery {}: {}", delivery_id, e);
+                continue;
+            }
+        }
+
+        let payload = match serde_json::to_string(&DeliverGameWebhookParams { delivery_id }) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Game webhooks: failed to serialize delivery {}: {}", delivery_id, e);
+                continue;
+            }
+        };
+
+        if let Err(e) = mq
+            .enqueue(QueuedJob::new("deliver_game_webhook", payload, JobOptions::new()))
+            .await
+        {
+            error!("Game webhooks: failed to enqueue delivery {}: {}", delivery_id, e);
+            if let Err(e) = db_mutations::release(&db, delivery_id).await {
+                error!("Game webhooks: failed to release delivery {}: {}", delivery_id, e);
+            }
+            continue;
+        }
+        queued += 1;
+    }
+
+    info!("Game webhooks: queued {} deliver_game_webhook job(s)", queued);
+}
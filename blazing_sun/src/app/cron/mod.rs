@@ -9,5 +9,13 @@
 //! 2. Export it here
 //! 3. Register it in `crons/mod.rs` using Schedule API
 
+pub mod balance_snapshots;
+pub mod data_exports;
+pub mod game_room_retention;
+pub mod game_webhooks;
+pub mod game_type_stats;
 pub mod list_user_emails;
+pub mod prediction_refunds;
+pub mod theme_previews;
 pub mod user_counter;
+pub mod user_erasure;
@@ -0,0 +1,49 @@
+//! Prediction Refunds Cron Job
+//!
+//! Refunds spectator predictions still in escrow for games that will never
+//! settle them: rooms that were abandoned, closed or lost to a restart before
+//! the game ended. Stakes are only touched once they are older than
+//! `STALE_AFTER_MINUTES` and their room is no longer in progress.
+//! Runs every 5 minutes.
+
+use crate::app::db_query::mutations::game_predictions as db_mutations;
+use crate::app::db_query::read::game_predictions as db_read;
+use crate::app::games::predictions;
+use crate::database::replica;
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Escrowed stakes younger than this are left for the game to settle
+const STALE_AFTER_MINUTES: i64 = 10;
+
+/// Run the prediction refunds job
+pub async fn run(db: Pool<Postgres>) {
+    let room_ids = match db_read::orphaned_room_ids(&db, STALE_AFTER_MINUTES).await {
+        Ok(room_ids) => room_ids,
+        Err(e) => {
+            error!("Prediction refunds: failed to load orphaned predictions: {}", e);
+            return;
+        }
+    };
+
+    for room_id in room_ids {
+        let stakes = match db_read::escrowed_for_room(&db, &room_id).await {
+            Ok(stakes) => stakes,
+            Err(e) => {
+                error!("Prediction refunds: failed to load stakes of room {}: {}", room_id, e);
+                continue;
+            }
+        };
+
+        match db_mutations::settle(&db, &room_id, &predictions::settle(&stakes, None)).await {
+            Ok(refunded) if !refunded.is_empty() => {
+                info!("Refunded {} prediction(s) of room {}", refunded.len(), room_id);
+                for payout in &refunded {
+                    replica::mark_written(payout.user_id).await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => error!("Prediction refunds: failed to refund room {}: {}", room_id, e),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Theme Preview Cleanup Cron Job
+//!
+//! Removes theme previews that were neither promoted nor discarded within
+//! `THEME_PREVIEW_TTL`. Runs hourly.
+
+use crate::bootstrap::includes::ThemeService;
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Run the theme preview cleanup job
+pub async fn run(_db: Pool<Postgres>) {
+    match ThemeService::remove_stale_previews() {
+        Ok(0) => {}
+        Ok(count) => info!("Removed {} stale theme preview(s)", count),
+        Err(e) => error!("Failed to remove stale theme previews: {}", e),
+    }
+}
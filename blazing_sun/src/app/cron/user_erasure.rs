@@ -0,0 +1,95 @@
+//! User Erasure Cron Job
+//!
+//! Queues an `erase_user` MQ job for every account deletion request whose
+//! grace period has ended. Requests are claimed (pending -> queued) before
+//! being enqueued so a request is never erased twice; a request that could
+//! not be enqueued goes back to pending and is picked up on the next run.
+//! Runs hourly.
+
+use crate::app::db_query::mutations::user_erasure as db_mutations;
+use crate::app::db_query::read::user_erasure as db_read;
+use crate::app::mq::jobs::EraseUserParams;
+use crate::mq::{JobOptions, MessageQueue, QueuedJob};
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+/// Run the user erasure job
+pub async fn run(db: Pool<Postgres>) {
+    let due = match db_read::get_due(&db).await {
+        Ok(due) => due,
+        Err(e) => {
+            error!("User erasure: failed to load due requests: {}", e);
+            return;
+        }
+    };
+
+    if due.is_empty() {
+        return;
+    }
+
+    let mq = match MessageQueue::new(db.clone()).await {
+        Ok(mq) => mq,
+        Err(e) => {
+            error!(
+                "User erasure: failed to connect to the message queue: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    let mut queued = 0;
+    for request in due {
+        match db_mutations::mark_queued(&db, request.id).await {
+            Ok(true) => {}
+            // Cancelled since it was loaded
+            Ok(false) => continue,
+            Err(e) => {
+                error!(
+                    "User erasure: failed to claim request {}: {}",
+                    request.id, e
+                );
+                continue;
+            }
+        }
+
+        let params = EraseUserParams {
+            request_id: request.id,
+            user_id: request.user_id,
+        };
+        let payload = match serde_json::to_string(&params) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!(
+                    "User erasure: failed to serialize request {}: {}",
+                    request.id, e
+                );
+                continue;
+            }
+        };
+
+        if let Err(e) = mq
+            .enqueue(QueuedJob::new(
+                "erase_user",
+                payload,
+                JobOptions::new().owner(request.user_id),
+            ))
+            .await
+        {
+            error!(
+                "User erasure: failed to enqueue request {}: {}",
+                request.id, e
+            );
+            if let Err(e) = db_mutations::requeue(&db, request.id).await {
+                error!(
+                    "User erasure: failed to release request {}: {}",
+                    request.id, e
+                );
+            }
+            continue;
+        }
+        queued += 1;
+    }
+
+    info!("User erasure: queued {} erase_user job(s)", queued);
+}
@@ -0,0 +1,103 @@
+//! Data exports (data portability)
+//!
+//! `POST /api/v1/me/export` opens a `user_data_exports` row and queues the
+//! `export_user_data` job, which gathers the user's profile, checkout
+//! transactions, balance ledger, game history and chat messages into a zip
+//! of JSON and CSV files and stores it privately. When it is ready the user
+//! gets a `data_exports` notification carrying the download link.
+//!
+//! Download links need no JWT (they are emailed), so they are signed: the
+//! `signature` query parameter is a hex HMAC-SHA256, keyed with JWT_SECRET, of
+//! the export id and the `expires` unix timestamp, which is the end of the
+//! export's download window (`DATA_EXPORT_TTL_HOURS`). The `data_exports`
+//! cron deletes archives once their window has closed.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::{AppConfig, JwtConfig};
+
+/// Route of the signed download (outside the JWT protected `/me` scope)
+pub const DOWNLOAD_PATH: &str = "/api/v1/data-exports";
+
+fn mac(secret: &str, export_id: i64, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("data_export.{}.{}", export_id, expires).as_bytes());
+    mac
+}
+
+/// Hex signature of a download link
+fn signature(secret: &str, export_id: i64, expires: i64) -> String {
+    hex::encode(mac(secret, export_id, expires).finalize().into_bytes())
+}
+
+/// Whether `signature` was issued for the export and `expires` has not passed
+fn is_valid(
+    secret: &str,
+    export_id: i64,
+    expires: i64,
+    signature: &str,
+    now: DateTime<Utc>,
+) -> bool {
+    if expires <= now.timestamp() {
+        return false;
+    }
+    match hex::decode(signature) {
+        Ok(bytes) => mac(secret, export_id, expires).verify_slice(&bytes).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Absolute, signed link downloading an export until `expires_at`
+pub fn download_url(export_id: i64, expires_at: DateTime<Utc>) -> String {
+    let expires = expires_at.timestamp();
+    format!(
+        "{}{}/{}/download?expires={}&signature={}",
+        AppConfig::app_url().trim_end_matches('/'),
+        DOWNLOAD_PATH,
+        export_id,
+        expires,
+        signature(JwtConfig::secret(), export_id, expires)
+    )
+}
+
+/// Check the `expires` and `signature` of a download link
+pub fn verify_download(export_id: i64, expires: i64, signature: &str) -> bool {
+    is_valid(
+        JwtConfig::secret(),
+        export_id,
+        expires,
+        signature,
+        Utc::now(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn signatures_bind_the_export_and_its_expiry() {
+        let now = Utc::now();
+        let expires = (now + Duration::hours(1)).timestamp();
+        let sig = signature("secret", 7, expires);
+
+        assert!(is_valid("secret", 7, expires, &sig, now));
+        assert!(!is_valid("secret", 8, expires, &sig, now));
+        assert!(!is_valid("secret", 7, expires + 1, &sig, now));
+        assert!(!is_valid("other", 7, expires, &sig, now));
+        assert!(!is_valid("secret", 7, expires, "not-hex", now));
+    }
+
+    #[test]
+    fn links_stop_working_when_they_expire() {
+        let now = Utc::now();
+        let expires = (now - Duration::seconds(1)).timestamp();
+        let sig = signature("secret", 7, expires);
+
+        assert!(!is_valid("secret", 7, expires, &sig, now));
+    }
+}
@@ -0,0 +1,83 @@
+//! Achievements Mutation Queries
+//!
+//! Write operations for the user_achievement_progress and user_achievements
+//! tables.
+
+use sqlx::{Pool, Postgres, Row};
+
+use crate::app::achievements::{Progress, UnlockedAchievement};
+
+/// Count a progress event (once per source) and unlock every active
+/// achievement of its metric the user's total now reaches
+///
+/// Returns only the achievements unlocked by this call, so a redelivered
+/// event unlocks (and announces) nothing new. Unlocking does not depend on
+/// the progress row being new: an event whose earlier handling stopped
+/// between the two steps still unlocks on redelivery.
+pub async fn record_progress(
+    db: &Pool<Postgres>,
+    progress: &Progress,
+) -> Result<Vec<UnlockedAchievement>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_achievement_progress (user_id, metric, source_id, amount)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, metric, source_id) DO NOTHING
+        "#,
+    )
+    .bind(progress.user_id)
+    .bind(progress.metric.as_str())
+    .bind(&progress.source_id)
+    .bind(progress.amount)
+    .execute(&mut *tx)
+    .await?;
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(amount), 0)::BIGINT
+        FROM user_achievement_progress
+        WHERE user_id = $1 AND metric = $2
+        "#,
+    )
+    .bind(progress.user_id)
+    .bind(progress.metric.as_str())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let rows = sqlx::query(
+        r#"
+        WITH unlocked AS (
+            INSERT INTO user_achievements (user_id, achievement_id)
+            SELECT $1, id FROM achievements
+            WHERE metric = $2 AND threshold <= $3 AND is_active
+            ON CONFLICT (user_id, achievement_id) DO NOTHING
+            RETURNING achievement_id, unlocked_at
+        )
+        SELECT a.id, a.code, a.name, a.description, a.badge, u.unlocked_at
+        FROM unlocked u
+        JOIN achievements a ON a.id = u.achievement_id
+        ORDER BY a.threshold
+        "#,
+    )
+    .bind(progress.user_id)
+    .bind(progress.metric.as_str())
+    .bind(total)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| UnlockedAchievement {
+            achievement_id: r.get("id"),
+            code: r.get("code"),
+            name: r.get("name"),
+            description: r.get("description"),
+            badge: r.get("badge"),
+            unlocked_at: r.get("unlocked_at"),
+        })
+        .collect())
+}
@@ -0,0 +1,126 @@
+//! Auth Sessions Mutation Queries
+//!
+//! Write operations for the auth_sessions table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::app::db_query::read::auth_sessions::{map_session, AuthSession, COLUMNS};
+
+/// Record a signed-in device
+pub async fn create(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    device_info: Option<&str>,
+    ip_address: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<AuthSession, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        INSERT INTO auth_sessions (id, user_id, device_info, ip_address, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(device_info)
+    .bind(ip_address)
+    .bind(expires_at)
+    .fetch_one(db)
+    .await?;
+
+    Ok(map_session(row))
+}
+
+/// Link a refresh token to the session it keeps alive
+pub async fn attach_refresh_token(
+    db: &Pool<Postgres>,
+    id: Uuid,
+    token_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE session_refresh_tokens
+        SET session_id = $1
+        WHERE token_hash = $2
+        "#,
+    )
+    .bind(id)
+    .bind(token_hash)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark a session as used by a token refresh
+pub async fn touch(db: &Pool<Postgres>, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE auth_sessions SET last_seen_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Revoke one of a user's active sessions and its refresh tokens; None if the
+/// user has no such session or it already ended
+pub async fn revoke(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    id: Uuid,
+) -> Result<Option<AuthSession>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE auth_sessions
+        SET revoked_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if row.is_some() {
+        sqlx::query(
+            r#"
+            UPDATE session_refresh_tokens
+            SET is_revoked = TRUE, revoked_at = NOW()
+            WHERE session_id = $1 AND is_revoked = FALSE
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(row.map(map_session))
+}
+
+/// Revoke every active session of a user (sign out everywhere); the refresh
+/// tokens are revoked separately
+pub async fn revoke_all_for_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Vec<AuthSession>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        UPDATE auth_sessions
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_session).collect())
+}
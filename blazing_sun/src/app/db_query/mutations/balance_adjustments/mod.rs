@@ -0,0 +1,203 @@
+//! Balance Adjustments Mutation Queries
+//!
+//! Write operations for the balance_adjustments table. Approving an adjustment
+//! changes the user's balance and appends its balance_ledger entry in the same
+//! transaction as the status change, so an adjustment is applied exactly once.
+
+use serde_json::json;
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+use super::balance_ledger::{self, LedgerChange};
+use crate::app::db_query::read::balance_adjustments::{map_adjustment, BalanceAdjustment};
+
+/// Ledger source label of executed adjustments
+pub const LEDGER_SOURCE: &str = "admin_adjustment";
+
+/// One requested adjustment
+pub struct NewAdjustment {
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub reason: String,
+}
+
+/// Result of approving an adjustment
+#[derive(Debug)]
+pub enum ApproveOutcome {
+    /// Balance changed and ledger entry written
+    Executed(BalanceAdjustment),
+    NotFound,
+    /// Already executed or rejected
+    NotPending,
+    /// The requester cannot approve their own adjustment
+    SameReviewer,
+    /// A revocation larger than the user's balance; the adjustment stays pending
+    InsufficientBalance { current_balance: i64 },
+}
+
+/// Result of rejecting an adjustment
+#[derive(Debug)]
+pub enum RejectOutcome {
+    Rejected(BalanceAdjustment),
+    NotFound,
+    NotPending,
+}
+
+/// Request a batch of adjustments, returning their ids in request order
+pub async fn create_batch(
+    db: &Pool<Postgres>,
+    requested_by: i64,
+    adjustments: &[NewAdjustment],
+) -> Result<(Uuid, Vec<i64>), sqlx::Error> {
+    let batch_id = Uuid::new_v4();
+    let mut tx = db.begin().await?;
+    let mut ids = Vec::with_capacity(adjustments.len());
+
+    for adjustment in adjustments {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO balance_adjustments (batch_id, user_id, amount_cents, reason, requested_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(batch_id)
+        .bind(adjustment.user_id)
+        .bind(adjustment.amount_cents)
+        .bind(&adjustment.reason)
+        .bind(requested_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ids.push(row.get("id"));
+    }
+
+    tx.commit().await?;
+
+    Ok((batch_id, ids))
+}
+
+/// Approve a pending adjustment and apply it to the user's balance
+pub async fn approve(
+    db: &Pool<Postgres>,
+    id: i64,
+    reviewed_by: i64,
+) -> Result<ApproveOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let row = sqlx::query(
+        r#"
+        SELECT user_id, amount_cents, reason, status, requested_by
+        FROM balance_adjustments
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        tx.rollback().await?;
+        return Ok(ApproveOutcome::NotFound);
+    };
+
+    let user_id: i64 = row.get("user_id");
+    let amount_cents: i64 = row.get("amount_cents");
+    let reason: String = row.get("reason");
+    let status: String = row.get("status");
+    let requested_by: Option<i64> = row.get("requested_by");
+
+    if status != "pending" {
+        tx.rollback().await?;
+        return Ok(ApproveOutcome::NotPending);
+    }
+    if requested_by == Some(reviewed_by) {
+        tx.rollback().await?;
+        return Ok(ApproveOutcome::SameReviewer);
+    }
+
+    // Revocations never take a balance below zero
+    let current_balance = balance_ledger::lock_balance(&mut tx, user_id)
+        .await?
+        .unwrap_or(0);
+    if current_balance + amount_cents < 0 {
+        tx.rollback().await?;
+        return Ok(ApproveOutcome::InsufficientBalance { current_balance });
+    }
+
+    let reference_id = format!("adjustment:{}", id);
+    let balance_after = balance_ledger::record(
+        &mut tx,
+        user_id,
+        &LedgerChange {
+            amount_cents,
+            source: LEDGER_SOURCE,
+            reference_id: Some(&reference_id),
+            metadata: json!({
+                "reason": reason,
+                "requested_by": requested_by,
+                "approved_by": reviewed_by,
+            }),
+        },
+    )
+    .await?;
+
+    let adjustment = sqlx::query(
+        r#"
+        UPDATE balance_adjustments
+        SET status = 'executed', reviewed_by = $2, reviewed_at = NOW(), balance_after = $3
+        WHERE id = $1
+        RETURNING id, batch_id, user_id, amount_cents, reason, status, requested_by,
+                  reviewed_by, review_note, balance_after, created_at, reviewed_at
+        "#,
+    )
+    .bind(id)
+    .bind(reviewed_by)
+    .bind(balance_after)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ApproveOutcome::Executed(map_adjustment(adjustment)))
+}
+
+/// Reject a pending adjustment; the balance is left untouched
+pub async fn reject(
+    db: &Pool<Postgres>,
+    id: i64,
+    reviewed_by: i64,
+    note: Option<&str>,
+) -> Result<RejectOutcome, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        UPDATE balance_adjustments
+        SET status = 'rejected', reviewed_by = $2, reviewed_at = NOW(), review_note = $3
+        WHERE id = $1 AND status = 'pending'
+        RETURNING id, batch_id, user_id, amount_cents, reason, status, requested_by,
+                  reviewed_by, review_note, balance_after, created_at, reviewed_at
+        "#,
+    )
+    .bind(id)
+    .bind(reviewed_by)
+    .bind(note)
+    .fetch_optional(db)
+    .await?;
+
+    if let Some(row) = row {
+        return Ok(RejectOutcome::Rejected(map_adjustment(row)));
+    }
+
+    let exists = sqlx::query("SELECT 1 FROM balance_adjustments WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .is_some();
+
+    Ok(if exists {
+        RejectOutcome::NotPending
+    } else {
+        RejectOutcome::NotFound
+    })
+}
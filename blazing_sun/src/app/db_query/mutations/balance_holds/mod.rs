@@ -0,0 +1,242 @@
+//! Balance Holds Mutation Queries
+//!
+//! Write operations for the balance_holds table. Every change to a hold moves
+//! coins in the same transaction as its balance update and balance_ledger
+//! entry, so a hold is placed once and resolved once.
+
+use serde_json::Value;
+use sqlx::{Pool, Postgres, Row, Transaction};
+
+use super::balance_ledger::{self, lock_balance, LedgerChange};
+
+/// `balance_holds.source` of holds placed for Stripe disputes
+pub const SOURCE_DISPUTE: &str = "dispute";
+
+/// Ledger source label of frozen coins
+pub const LEDGER_HOLD: &str = "dispute_hold";
+/// Ledger source label of coins given back after a won dispute
+pub const LEDGER_RELEASE: &str = "dispute_release";
+/// Ledger source label of coins taken after a lost dispute the hold did not cover
+pub const LEDGER_DEDUCTION: &str = "dispute_deduction";
+
+/// A hold to place
+pub struct NewHold<'a> {
+    pub user_id: i64,
+    /// The Stripe dispute id
+    pub reference_id: &'a str,
+    /// Checkout request the disputed coins were credited by
+    pub request_id: &'a str,
+    /// Coins to freeze
+    pub amount_cents: i64,
+    pub metadata: Value,
+}
+
+/// A balance change made by a hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub user_id: i64,
+    /// Coins added (positive) or taken (negative); 0 when nothing moved
+    pub change: i64,
+    pub balance: i64,
+}
+
+/// Result of placing a hold
+#[derive(Debug)]
+pub enum HoldOutcome {
+    Placed(BalanceChange),
+    /// The hold was already placed (duplicate delivery)
+    Duplicate,
+    UnknownUser,
+}
+
+/// How a dispute ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Give the held coins back
+    Release,
+    /// Keep the held coins, and take what the hold fell short of
+    Deduct,
+}
+
+/// Result of resolving a hold
+#[derive(Debug)]
+pub enum ResolveOutcome {
+    Resolved(BalanceChange),
+    NotFound,
+    /// Already released or deducted (duplicate delivery)
+    AlreadyResolved,
+}
+
+/// Freeze up to `amount_cents` of the user's coins, once per dispute. A
+/// balance that has been spent since is frozen as far as it goes; the rest is
+/// settled if the dispute is lost.
+pub async fn hold(db: &Pool<Postgres>, hold: &NewHold<'_>) -> Result<HoldOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let Some(balance) = lock_balance(&mut tx, hold.user_id).await? else {
+        tx.rollback().await?;
+        return Ok(HoldOutcome::UnknownUser);
+    };
+    let held = held_cents(balance, hold.amount_cents);
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO balance_holds (user_id, source, reference_id, request_id, requested_cents, held_cents)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (source, reference_id) DO NOTHING
+        "#,
+    )
+    .bind(hold.user_id)
+    .bind(SOURCE_DISPUTE)
+    .bind(hold.reference_id)
+    .bind(hold.request_id)
+    .bind(hold.amount_cents.max(0))
+    .bind(held)
+    .execute(&mut *tx)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(HoldOutcome::Duplicate);
+    }
+
+    let balance = apply(
+        &mut tx,
+        hold.user_id,
+        -held,
+        LEDGER_HOLD,
+        hold.reference_id,
+        &hold.metadata,
+    )
+    .await?
+    .unwrap_or(balance);
+
+    tx.commit().await?;
+
+    Ok(HoldOutcome::Placed(BalanceChange {
+        user_id: hold.user_id,
+        change: -held,
+        balance,
+    }))
+}
+
+/// Settle the hold of a closed dispute
+pub async fn resolve(
+    db: &Pool<Postgres>,
+    reference_id: &str,
+    resolution: Resolution,
+    metadata: &Value,
+) -> Result<ResolveOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let row = sqlx::query(
+        r#"
+        SELECT user_id, requested_cents, held_cents, status
+        FROM balance_holds
+        WHERE source = $1 AND reference_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(SOURCE_DISPUTE)
+    .bind(reference_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        tx.rollback().await?;
+        return Ok(ResolveOutcome::NotFound);
+    };
+
+    let user_id: i64 = row.get("user_id");
+    let requested: i64 = row.get("requested_cents");
+    let held: i64 = row.get("held_cents");
+    let status: String = row.get("status");
+
+    if status != "held" {
+        tx.rollback().await?;
+        return Ok(ResolveOutcome::AlreadyResolved);
+    }
+
+    let balance = lock_balance(&mut tx, user_id).await?.unwrap_or(0);
+    let (change, source, status, deducted) = match resolution {
+        Resolution::Release => (held, LEDGER_RELEASE, "released", 0),
+        Resolution::Deduct => {
+            let shortfall = held_cents(balance, requested - held);
+            (-shortfall, LEDGER_DEDUCTION, "deducted", held + shortfall)
+        }
+    };
+
+    let balance = apply(&mut tx, user_id, change, source, reference_id, metadata)
+        .await?
+        .unwrap_or(balance);
+
+    sqlx::query(
+        r#"
+        UPDATE balance_holds
+        SET status = $2, deducted_cents = $3, resolved_at = NOW()
+        WHERE source = $1 AND reference_id = $4
+        "#,
+    )
+    .bind(SOURCE_DISPUTE)
+    .bind(status)
+    .bind(deducted)
+    .bind(reference_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ResolveOutcome::Resolved(BalanceChange {
+        user_id,
+        change,
+        balance,
+    }))
+}
+
+/// Coins a balance can give up towards `wanted`
+fn held_cents(balance: i64, wanted: i64) -> i64 {
+    wanted.clamp(0, balance.max(0))
+}
+
+/// Change the balance and append its ledger entry; None (nothing written)
+/// when `change` is 0
+async fn apply(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    change: i64,
+    source: &str,
+    reference_id: &str,
+    metadata: &Value,
+) -> Result<Option<i64>, sqlx::Error> {
+    if change == 0 {
+        return Ok(None);
+    }
+
+    let balance_after = balance_ledger::record(
+        tx,
+        user_id,
+        &LedgerChange {
+            amount_cents: change,
+            source,
+            reference_id: Some(reference_id),
+            metadata: metadata.clone(),
+        },
+    )
+    .await?;
+
+    Ok(Some(balance_after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holds_never_take_a_balance_below_zero() {
+        assert_eq!(held_cents(1000, 400), 400);
+        assert_eq!(held_cents(250, 400), 250);
+        assert_eq!(held_cents(0, 400), 0);
+        assert_eq!(held_cents(-10, 400), 0);
+        assert_eq!(held_cents(1000, -5), 0);
+    }
+}
@@ -0,0 +1,236 @@
+//! Balance Ledger Mutation Queries
+//!
+//! Write operations for the balance_ledger, processed_events and
+//! balance_daily_snapshots tables.
+//!
+//! Every change to `users.balance` goes through a ledger-writing mutation, so
+//! the ledger can rebuild any user's balance at any point in time.
+
+use chrono::NaiveDate;
+use serde_json::Value;
+use sqlx::{Pool, Postgres, Row, Transaction};
+
+/// Ledger source label of Bigger Dice participation fees
+pub const SOURCE_GAME_FEE: &str = "game_fee";
+/// Ledger source label of participation fees given back when a selection fails
+pub const SOURCE_GAME_FEE_REFUND: &str = "game_fee_refund";
+/// Ledger source label of Bigger Dice prizes
+pub const SOURCE_GAME_PRIZE: &str = "game_prize";
+/// Ledger source label of roulette stakes
+pub const SOURCE_ROULETTE_STAKE: &str = "roulette_stake";
+/// Ledger source label of roulette winnings
+pub const SOURCE_ROULETTE_PAYOUT: &str = "roulette_payout";
+
+/// One balance change to record
+pub struct LedgerChange<'a> {
+    /// Coins added (positive) or taken (negative)
+    pub amount_cents: i64,
+    pub source: &'a str,
+    pub reference_id: Option<&'a str>,
+    pub metadata: Value,
+}
+
+/// Result of applying balance changes
+#[derive(Debug)]
+pub enum ApplyOutcome {
+    Applied {
+        balance_after: i64,
+    },
+    /// A debit would have taken the balance below zero; nothing was applied
+    InsufficientBalance {
+        current_balance: i64,
+    },
+    UnknownUser,
+}
+
+/// Apply `changes` to a user's balance in order, with a ledger entry each, in
+/// one transaction.
+///
+/// Debits never take the balance below zero: if one would, none of the
+/// changes is applied.
+pub async fn apply(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    changes: &[LedgerChange<'_>],
+) -> Result<ApplyOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let Some(current_balance) = lock_balance(&mut tx, user_id).await? else {
+        tx.rollback().await?;
+        return Ok(ApplyOutcome::UnknownUser);
+    };
+
+    let mut balance_after = current_balance;
+    for change in changes {
+        if change.amount_cents < 0 && balance_after + change.amount_cents < 0 {
+            tx.rollback().await?;
+            return Ok(ApplyOutcome::InsufficientBalance { current_balance });
+        }
+        balance_after = record(&mut tx, user_id, change).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(ApplyOutcome::Applied { balance_after })
+}
+
+/// Lock a user's row for the rest of the transaction and read their balance.
+///
+/// Returns `Ok(None)` when the user does not exist.
+pub async fn lock_balance(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query("SELECT balance FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    Ok(row.map(|r| r.get("balance")))
+}
+
+/// Move a user's balance by `change` and append its ledger entry, inside the
+/// caller's transaction. A change of 0 writes no entry.
+///
+/// Every balance_ledger row is written here, so each entry's `balance_after`
+/// is the balance its own update returned.
+///
+/// Returns the balance after the change.
+pub async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    change: &LedgerChange<'_>,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        UPDATE users
+        SET balance = balance + $1, updated_at = NOW()
+        WHERE id = $2
+        RETURNING balance
+        "#,
+    )
+    .bind(change.amount_cents)
+    .bind(user_id)
+    .fetch_one(&mut **tx)
+    .await?;
+    let balance_after: i64 = row.get("balance");
+
+    if change.amount_cents == 0 {
+        return Ok(balance_after);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO balance_ledger (user_id, amount_cents, balance_after, source, reference_id, metadata)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(user_id)
+    .bind(change.amount_cents)
+    .bind(balance_after)
+    .bind(change.source)
+    .bind(change.reference_id)
+    .bind(&change.metadata)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(balance_after)
+}
+
+/// Parameters for crediting a balance exactly once per source event
+pub struct CreditOnceParams<'a> {
+    /// Kafka topic the event was consumed from
+    pub topic: &'a str,
+    /// Idempotency key of the event (e.g. checkout request_id)
+    pub request_id: &'a str,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    /// Ledger source label (e.g. "checkout")
+    pub source: &'a str,
+    pub metadata: Value,
+}
+
+/// Credit a user's balance and append a ledger entry, exactly once per (topic, request_id).
+///
+/// The dedup insert, balance update and ledger insert share one transaction,
+/// so either all three are applied or none is.
+///
+/// Returns `Ok(Some(new_balance))` when applied, `Ok(None)` when the event was
+/// already processed (duplicate delivery).
+pub async fn credit_once(
+    db: &Pool<Postgres>,
+    params: &CreditOnceParams<'_>,
+) -> Result<Option<i64>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO processed_events (topic, request_id)
+        VALUES ($1, $2)
+        ON CONFLICT (topic, request_id) DO NOTHING
+        "#,
+    )
+    .bind(params.topic)
+    .bind(params.request_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    let balance_after = record(
+        &mut tx,
+        params.user_id,
+        &LedgerChange {
+            amount_cents: params.amount_cents,
+            source: params.source,
+            reference_id: Some(params.request_id),
+            metadata: params.metadata.clone(),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(balance_after))
+}
+
+/// Write the end-of-day balance snapshots of every day before `until` (UTC)
+/// that has ledger activity and was not snapshotted yet.
+///
+/// Snapshots resume from the day after the latest existing one, so the first
+/// run backfills the whole ledger and later runs only cover the new days.
+/// Re-running a day overwrites its snapshots.
+///
+/// Returns the number of snapshots written.
+pub async fn snapshot_days(db: &Pool<Postgres>, until: NaiveDate) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO balance_daily_snapshots (user_id, snapshot_date, balance_cents, last_ledger_id)
+        SELECT DISTINCT ON (user_id, day) user_id, day, balance_after, id
+        FROM (
+            SELECT id, user_id, balance_after, created_at,
+                   (created_at AT TIME ZONE 'UTC')::date AS day
+            FROM balance_ledger
+            WHERE created_at < $1::date::timestamp AT TIME ZONE 'UTC'
+              AND created_at >= COALESCE(
+                  (SELECT (MAX(snapshot_date) + 1)::timestamp AT TIME ZONE 'UTC'
+                   FROM balance_daily_snapshots),
+                  '-infinity'::timestamptz
+              )
+        ) entries
+        ORDER BY user_id, day, created_at DESC, id DESC
+        ON CONFLICT (user_id, snapshot_date) DO UPDATE
+        SET balance_cents = EXCLUDED.balance_cents,
+            last_ledger_id = EXCLUDED.last_ledger_id,
+            created_at = NOW()
+        "#,
+    )
+    .bind(until)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,164 @@
+//! Balance Transfers Mutation Queries
+//!
+//! Write operations for the balance_transfers table. A transfer debits the
+//! sender, credits the recipient, records the transfer and appends a
+//! balance_ledger entry for each side in one transaction.
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{Pool, Postgres, Row};
+
+use super::balance_ledger::{self, LedgerChange};
+
+/// Ledger source label of both sides of a transfer
+pub const LEDGER_SOURCE: &str = "transfer";
+
+/// One requested transfer
+pub struct NewTransfer {
+    pub sender_id: i64,
+    pub recipient_id: i64,
+    pub amount_cents: i64,
+    pub memo: Option<String>,
+}
+
+/// An executed transfer with both parties' new balances
+#[derive(Debug, Clone)]
+pub struct CompletedTransfer {
+    pub id: i64,
+    pub sender_id: i64,
+    pub recipient_id: i64,
+    pub amount_cents: i64,
+    pub memo: Option<String>,
+    pub sender_balance_after: i64,
+    pub recipient_balance_after: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Result of a transfer
+#[derive(Debug)]
+pub enum TransferOutcome {
+    Completed(CompletedTransfer),
+    RecipientNotFound,
+    InsufficientBalance { current_balance: i64 },
+    /// The sender already sent close to `daily_limit_cents` in the last 24 hours
+    DailyLimitExceeded { remaining_cents: i64 },
+}
+
+/// Move `amount_cents` from the sender to the recipient
+///
+/// Both users are locked in id order, so concurrent transfers between the same
+/// two users cannot deadlock and a sender's daily total cannot be raced past
+/// the limit.
+pub async fn transfer(
+    db: &Pool<Postgres>,
+    new: &NewTransfer,
+    daily_limit_cents: i64,
+) -> Result<TransferOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, balance
+        FROM users
+        WHERE id = ANY($1)
+        ORDER BY id
+        FOR UPDATE
+        "#,
+    )
+    .bind([new.sender_id, new.recipient_id].as_slice())
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let balance_of = |user_id: i64| {
+        rows.iter()
+            .find(|r| r.get::<i64, _>("id") == user_id)
+            .map(|r| r.get::<i64, _>("balance"))
+    };
+    if balance_of(new.recipient_id).is_none() {
+        tx.rollback().await?;
+        return Ok(TransferOutcome::RecipientNotFound);
+    }
+    let current_balance = balance_of(new.sender_id).ok_or(sqlx::Error::RowNotFound)?;
+
+    let sent_today: i64 = sqlx::query(
+        r#"
+        SELECT COALESCE(SUM(amount_cents), 0)::BIGINT AS sent
+        FROM balance_transfers
+        WHERE sender_id = $1 AND created_at > NOW() - INTERVAL '1 day'
+        "#,
+    )
+    .bind(new.sender_id)
+    .fetch_one(&mut *tx)
+    .await?
+    .get("sent");
+
+    if sent_today + new.amount_cents > daily_limit_cents {
+        tx.rollback().await?;
+        return Ok(TransferOutcome::DailyLimitExceeded {
+            remaining_cents: (daily_limit_cents - sent_today).max(0),
+        });
+    }
+    if current_balance < new.amount_cents {
+        tx.rollback().await?;
+        return Ok(TransferOutcome::InsufficientBalance { current_balance });
+    }
+
+    // Both rows are locked, so the balances after the transfer are known up front
+    let sender_balance_after = current_balance - new.amount_cents;
+    let recipient_balance_after =
+        balance_of(new.recipient_id).ok_or(sqlx::Error::RowNotFound)? + new.amount_cents;
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO balance_transfers
+            (sender_id, recipient_id, amount_cents, memo, sender_balance_after, recipient_balance_after)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, created_at
+        "#,
+    )
+    .bind(new.sender_id)
+    .bind(new.recipient_id)
+    .bind(new.amount_cents)
+    .bind(&new.memo)
+    .bind(sender_balance_after)
+    .bind(recipient_balance_after)
+    .fetch_one(&mut *tx)
+    .await?;
+    let id: i64 = row.get("id");
+    let created_at: DateTime<Utc> = row.get("created_at");
+
+    let reference_id = format!("transfer:{}", id);
+    let sides = [
+        (new.sender_id, -new.amount_cents, new.recipient_id),
+        (new.recipient_id, new.amount_cents, new.sender_id),
+    ];
+    for (user_id, amount_cents, counterparty_id) in sides {
+        balance_ledger::record(
+            &mut tx,
+            user_id,
+            &LedgerChange {
+                amount_cents,
+                source: LEDGER_SOURCE,
+                reference_id: Some(&reference_id),
+                metadata: json!({
+                    "counterparty_id": counterparty_id,
+                    "memo": new.memo,
+                }),
+            },
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(TransferOutcome::Completed(CompletedTransfer {
+        id,
+        sender_id: new.sender_id,
+        recipient_id: new.recipient_id,
+        amount_cents: new.amount_cents,
+        memo: new.memo.clone(),
+        sender_balance_after,
+        recipient_balance_after,
+        created_at,
+    }))
+}
@@ -0,0 +1,134 @@
+//! Chat Channel Mutation Queries
+//!
+//! Write operations for the chat_channels and chat_channel_members tables.
+
+use sqlx::{Pool, Postgres};
+
+/// Parameters for creating a channel
+pub struct CreateChatChannelParams {
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: i64,
+}
+
+/// Parameters for updating a channel (None = keep current value)
+pub struct UpdateChatChannelParams {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Create a channel, returns its ID
+pub async fn create(
+    db: &Pool<Postgres>,
+    params: &CreateChatChannelParams,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO chat_channels (slug, name, description, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(&params.slug)
+    .bind(&params.name)
+    .bind(&params.description)
+    .bind(params.created_by)
+    .fetch_one(db)
+    .await
+}
+
+/// Update a channel, returns true if a row was updated
+pub async fn update(
+    db: &Pool<Postgres>,
+    id: i64,
+    params: &UpdateChatChannelParams,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE chat_channels
+        SET name = COALESCE($2, name),
+            description = COALESCE($3, description),
+            is_active = COALESCE($4, is_active)
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&params.name)
+    .bind(&params.description)
+    .bind(params.is_active)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a channel (memberships cascade), returns true if a row was deleted
+pub async fn delete(db: &Pool<Postgres>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM chat_channels WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add a user to a channel, returns true if the user was not a member yet
+pub async fn join(db: &Pool<Postgres>, channel_id: i64, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO chat_channel_members (channel_id, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT (channel_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove a user from a channel, returns true if the user was a member
+pub async fn leave(db: &Pool<Postgres>, channel_id: i64, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM chat_channel_members WHERE channel_id = $1 AND user_id = $2",
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Move a member's read marker forward, returns true if the marker changed
+///
+/// ObjectIds are time-ordered hex strings of equal length, so the marker only
+/// advances and never moves back to an older message.
+pub async fn mark_read(
+    db: &Pool<Postgres>,
+    channel_id: i64,
+    user_id: i64,
+    message_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE chat_channel_members
+        SET last_read_message_id = $3,
+            last_read_at = NOW()
+        WHERE channel_id = $1
+          AND user_id = $2
+          AND (last_read_message_id IS NULL OR last_read_message_id < $3)
+        "#,
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .bind(message_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
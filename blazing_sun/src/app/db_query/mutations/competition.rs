@@ -3,8 +3,14 @@
 //! Write operations for competitions, entries, and votes.
 
 use chrono::{DateTime, Utc};
+use serde_json::json;
 use sqlx::{Pool, Postgres};
 
+use super::balance_ledger::{self, LedgerChange};
+
+/// Ledger source label of competition prizes
+pub const LEDGER_PRIZE: &str = "competition_prize";
+
 /// Parameters for creating a competition
 pub struct CreateCompetitionParams {
     pub title: String,
@@ -97,13 +103,17 @@ pub async fn add_admin_vote(
     Ok(result.id)
 }
 
-/// Set competition winner and award timestamp
+/// Set competition winner and award timestamp, and pay the winner's prize
+/// with its ledger entry in the same transaction
 pub async fn set_winner(
     db: &Pool<Postgres>,
     competition_id: i64,
     winner_gallery_id: i64,
     winner_user_id: i64,
+    prize_cents: i64,
 ) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
     sqlx::query!(
         r#"
         UPDATE competitions
@@ -114,8 +124,23 @@ pub async fn set_winner(
         winner_user_id,
         competition_id
     )
-    .execute(db)
+    .execute(&mut *tx)
+    .await?;
+
+    let reference_id = competition_id.to_string();
+    balance_ledger::record(
+        &mut tx,
+        winner_user_id,
+        &LedgerChange {
+            amount_cents: prize_cents,
+            source: LEDGER_PRIZE,
+            reference_id: Some(&reference_id),
+            metadata: json!({ "gallery_id": winner_gallery_id }),
+        },
+    )
     .await?;
 
+    tx.commit().await?;
+
     Ok(())
 }
@@ -0,0 +1,80 @@
+//! Feature Flag Mutation Queries
+//!
+//! Write operations for the feature_flags table.
+
+use sqlx::{Pool, Postgres};
+
+/// Parameters for creating a feature flag
+pub struct CreateFeatureFlagParams {
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub rollout_percentage: i16,
+    pub allowlist: Vec<i64>,
+}
+
+/// Parameters for updating a feature flag (None = keep current value)
+pub struct UpdateFeatureFlagParams {
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    pub rollout_percentage: Option<i16>,
+    pub allowlist: Option<Vec<i64>>,
+}
+
+/// Create a feature flag, returns its ID
+pub async fn create(
+    db: &Pool<Postgres>,
+    params: &CreateFeatureFlagParams,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO feature_flags (key, description, enabled, rollout_percentage, allowlist)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(&params.key)
+    .bind(&params.description)
+    .bind(params.enabled)
+    .bind(params.rollout_percentage)
+    .bind(&params.allowlist)
+    .fetch_one(db)
+    .await
+}
+
+/// Update a feature flag by key, returns true if a row was updated
+pub async fn update(
+    db: &Pool<Postgres>,
+    key: &str,
+    params: &UpdateFeatureFlagParams,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE feature_flags
+        SET description = COALESCE($2, description),
+            enabled = COALESCE($3, enabled),
+            rollout_percentage = COALESCE($4, rollout_percentage),
+            allowlist = COALESCE($5, allowlist)
+        WHERE key = $1
+        "#,
+    )
+    .bind(key)
+    .bind(&params.description)
+    .bind(params.enabled)
+    .bind(params.rollout_percentage)
+    .bind(&params.allowlist)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a feature flag by key, returns true if a row was deleted
+pub async fn delete(db: &Pool<Postgres>, key: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM feature_flags WHERE key = $1")
+        .bind(key)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
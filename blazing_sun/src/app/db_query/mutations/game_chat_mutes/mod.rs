@@ -0,0 +1,78 @@
+//! Game Chat Mutes Mutation Queries
+//!
+//! Write operations for the game_chat_mutes table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::app::db_query::read::game_chat_mutes::{map_mute, GameChatMute, COLUMNS};
+
+/// Mute a user in a channel of a room; an existing mute of the same channel
+/// is replaced
+pub async fn upsert(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    user_id: i64,
+    channel: &str,
+    muted_by: i64,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<GameChatMute, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        INSERT INTO game_chat_mutes (room_id, user_id, channel, muted_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (room_id, user_id, channel)
+        DO UPDATE SET muted_by = EXCLUDED.muted_by,
+                      created_at = NOW(),
+                      expires_at = EXCLUDED.expires_at
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(room_id)
+    .bind(user_id)
+    .bind(channel)
+    .bind(muted_by)
+    .bind(expires_at)
+    .fetch_one(db)
+    .await?;
+
+    Ok(map_mute(row))
+}
+
+/// Lift a mute; false if there was none
+pub async fn delete(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    user_id: i64,
+    channel: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM game_chat_mutes
+        WHERE room_id = $1 AND user_id = $2 AND channel = $3
+        "#,
+    )
+    .bind(room_id)
+    .bind(user_id)
+    .bind(channel)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete every mute that ran out and return them; each mute is returned to
+/// exactly one caller, so only one instance announces it
+pub async fn delete_expired(db: &Pool<Postgres>) -> Result<Vec<GameChatMute>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        DELETE FROM game_chat_mutes
+        WHERE expires_at IS NOT NULL AND expires_at <= NOW()
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_mute).collect())
+}
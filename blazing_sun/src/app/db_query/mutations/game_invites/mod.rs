@@ -0,0 +1,82 @@
+//! Game Invites Mutation Queries
+//!
+//! Write operations for the game_invites table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::app::db_query::read::game_invites::{map_invite, GameInvite, COLUMNS};
+
+/// Invite a user into a room; an open invite of the same user into the same
+/// room is handed to the new inviter and its expiry refreshed
+pub async fn create(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    inviter_id: i64,
+    invitee_id: i64,
+    expires_at: DateTime<Utc>,
+) -> Result<GameInvite, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        INSERT INTO game_invites (id, room_id, inviter_id, invitee_id, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (room_id, invitee_id) WHERE status = 'pending'
+        DO UPDATE SET inviter_id = EXCLUDED.inviter_id,
+                      created_at = NOW(),
+                      expires_at = EXCLUDED.expires_at
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(Uuid::new_v4())
+    .bind(room_id)
+    .bind(inviter_id)
+    .bind(invitee_id)
+    .bind(expires_at)
+    .fetch_one(db)
+    .await?;
+
+    Ok(map_invite(row))
+}
+
+/// Answer an open invite of `invitee_id` with "accepted" or "declined"; None
+/// if it does not exist, is not theirs, was already answered or has expired
+pub async fn respond(
+    db: &Pool<Postgres>,
+    id: Uuid,
+    invitee_id: i64,
+    status: &str,
+) -> Result<Option<GameInvite>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE game_invites
+        SET status = $3, responded_at = NOW()
+        WHERE id = $1 AND invitee_id = $2 AND status = 'pending' AND expires_at > NOW()
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(invitee_id)
+    .bind(status)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_invite))
+}
+
+/// Expire every open invite past its expiry and return them; each invite is
+/// returned to exactly one caller, so only one instance notifies the inviter
+pub async fn expire_due(db: &Pool<Postgres>) -> Result<Vec<GameInvite>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        UPDATE game_invites
+        SET status = 'expired', responded_at = NOW()
+        WHERE status = 'pending' AND expires_at <= NOW()
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_invite).collect())
+}
@@ -0,0 +1,156 @@
+//! Game Predictions Mutation Queries
+//!
+//! Write operations for the game_predictions table. Stakes and payouts move
+//! coins, so every change to `users.balance` here is paired with a
+//! balance_ledger entry in the same transaction.
+
+use serde_json::json;
+use sqlx::{Pool, Postgres, Row};
+
+use super::balance_ledger::{self, LedgerChange};
+use crate::app::games::predictions::{PredictionOutcome, PredictionPayout};
+
+/// Parameters for placing a prediction
+pub struct PlacePredictionParams<'a> {
+    pub room_id: &'a str,
+    pub game_type: &'a str,
+    pub user_id: i64,
+    pub predicted_winner_id: i64,
+    pub stake_cents: i64,
+}
+
+/// Result of placing a prediction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlacePredictionOutcome {
+    /// Stake debited and escrowed
+    Placed { prediction_id: i64, balance_after: i64 },
+    /// Balance too low for the stake
+    InsufficientBalance { current_balance: i64 },
+    /// The user already has a prediction for this room
+    AlreadyPlaced,
+}
+
+/// Record a prediction and move its stake from the balance into escrow.
+///
+/// The prediction insert, balance debit and ledger entry share one
+/// transaction, so a refused debit leaves no prediction behind.
+pub async fn place(
+    db: &Pool<Postgres>,
+    params: &PlacePredictionParams<'_>,
+) -> Result<PlacePredictionOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO game_predictions (room_id, game_type, user_id, predicted_winner_id, stake_cents)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (room_id, user_id) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(params.room_id)
+    .bind(params.game_type)
+    .bind(params.user_id)
+    .bind(params.predicted_winner_id)
+    .bind(params.stake_cents)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(inserted) = inserted else {
+        tx.rollback().await?;
+        return Ok(PlacePredictionOutcome::AlreadyPlaced);
+    };
+    let prediction_id: i64 = inserted.get("id");
+
+    let current_balance = balance_ledger::lock_balance(&mut tx, params.user_id)
+        .await?
+        .unwrap_or(0);
+    if current_balance < params.stake_cents {
+        tx.rollback().await?;
+        return Ok(PlacePredictionOutcome::InsufficientBalance { current_balance });
+    }
+
+    let reference_id = format!("prediction:{}", prediction_id);
+    let balance_after = balance_ledger::record(
+        &mut tx,
+        params.user_id,
+        &LedgerChange {
+            amount_cents: -params.stake_cents,
+            source: "prediction_stake",
+            reference_id: Some(&reference_id),
+            metadata: json!({
+                "room_id": params.room_id,
+                "game_type": params.game_type,
+                "predicted_winner_id": params.predicted_winner_id,
+            }),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(PlacePredictionOutcome::Placed {
+        prediction_id,
+        balance_after,
+    })
+}
+
+/// Apply settled payouts: mark each prediction won/lost/refunded and credit
+/// its payout.
+///
+/// Rows are only touched while still `escrowed`, so settling the same room
+/// twice (game end racing the refund cron) pays out once. Returns the payouts
+/// that were applied.
+pub async fn settle(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    payouts: &[PredictionPayout],
+) -> Result<Vec<PredictionPayout>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let mut applied = Vec::with_capacity(payouts.len());
+
+    for payout in payouts {
+        let updated = sqlx::query(
+            r#"
+            UPDATE game_predictions
+            SET status = $1, payout_cents = $2, settled_at = NOW()
+            WHERE id = $3 AND status = 'escrowed'
+            "#,
+        )
+        .bind(payout.outcome.as_str())
+        .bind(payout.payout_cents)
+        .bind(payout.prediction_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            continue;
+        }
+
+        if payout.payout_cents > 0 {
+            let source = match payout.outcome {
+                PredictionOutcome::Refunded => "prediction_refund",
+                _ => "prediction_payout",
+            };
+
+            let reference_id = format!("prediction:{}", payout.prediction_id);
+            balance_ledger::record(
+                &mut tx,
+                payout.user_id,
+                &LedgerChange {
+                    amount_cents: payout.payout_cents,
+                    source,
+                    reference_id: Some(&reference_id),
+                    metadata: json!({ "room_id": room_id, "stake_cents": payout.stake_cents }),
+                },
+            )
+            .await?;
+        }
+
+        applied.push(payout.clone());
+    }
+
+    tx.commit().await?;
+
+    Ok(applied)
+}
@@ -2,8 +2,11 @@
 //!
 //! Write operations for the game_rooms table.
 
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres};
 
+use crate::app::games::bigger_dice::BiggerDiceRules;
+
 /// Parameters for creating a new game room
 pub struct CreateRoomParams {
     pub room_id: String,
@@ -407,6 +410,225 @@ pub async fn cleanup_abandoned(
     Ok(result.into())
 }
 
+/// Purge expired room tombstones (for retention cron job)
+pub async fn purge_expired_tombstones(db: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+    let count: i32 = sqlx::query_scalar("SELECT sp_purge_expired_game_room_tombstones()")
+        .fetch_one(db)
+        .await?;
+
+    Ok(count.into())
+}
+
+/// Assign the owning region of a newly created room
+pub async fn assign_region(db: &Pool<Postgres>, room_id: &str, region: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE game_rooms SET region = $2, updated_at = NOW() WHERE room_id = $1")
+        .bind(room_id)
+        .bind(region)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Store the validated room settings the create stored procedure does not take
+pub async fn apply_config(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    max_spectators: i32,
+    turn_timer_seconds: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE game_rooms SET max_spectators = $2, turn_timer_seconds = $3, updated_at = NOW() WHERE room_id = $1",
+    )
+    .bind(room_id)
+    .bind(max_spectators)
+    .bind(turn_timer_seconds.map(|seconds| seconds as i32))
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Flag a room as a practice room against a bot of `difficulty`
+pub async fn set_bot_opponent(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    difficulty: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE game_rooms SET vs_bot = TRUE, bot_difficulty = $2, updated_at = NOW() WHERE room_id = $1",
+    )
+    .bind(room_id)
+    .bind(difficulty)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Store the Bigger Dice rules a room was created with
+pub async fn set_dice_rules(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    rules: &BiggerDiceRules,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE game_rooms SET dice_rules = $2, updated_at = NOW() WHERE room_id = $1")
+        .bind(room_id)
+        .bind(serde_json::json!(rules))
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Hold a new room until `start_at`; the host stays registered for it
+pub async fn schedule(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    host_id: i64,
+    start_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE game_rooms SET status = 'scheduled', scheduled_start_at = $2, updated_at = NOW() WHERE room_id = $1",
+    )
+    .bind(room_id)
+    .bind(start_at)
+    .execute(db)
+    .await?;
+
+    register(db, room_id, host_id).await?;
+    Ok(())
+}
+
+/// Register a user for a scheduled room; false when the room is not
+/// scheduled (any more)
+pub async fn register(db: &Pool<Postgres>, room_id: &str, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO game_room_registrations (room_id, user_id)
+        SELECT room_id, $2 FROM game_rooms
+        WHERE room_id = $1 AND status = 'scheduled' AND is_active = TRUE
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(room_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove a user's registration; false when they were not registered
+pub async fn unregister(db: &Pool<Postgres>, room_id: &str, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM game_room_registrations WHERE room_id = $1 AND user_id = $2")
+        .bind(room_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the reminders of a scheduled room as sent and return the ones that
+/// were not sent yet (another instance may have claimed the rest)
+pub async fn claim_schedule_reminders(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    minutes_before: &[i64],
+) -> Result<Vec<i64>, sqlx::Error> {
+    let minutes: Vec<i32> = minutes_before.iter().map(|m| *m as i32).collect();
+    let claimed: Vec<i32> = sqlx::query_scalar(
+        r#"
+        INSERT INTO game_room_schedule_reminders (room_id, minutes_before)
+        SELECT $1, UNNEST($2::INTEGER[])
+        ON CONFLICT DO NOTHING
+        RETURNING minutes_before
+        "#,
+    )
+    .bind(room_id)
+    .bind(&minutes)
+    .fetch_all(db)
+    .await?;
+
+    Ok(claimed.into_iter().map(i64::from).collect())
+}
+
+/// Open a scheduled room for joining; false when it was already opened or closed
+pub async fn open_scheduled(db: &Pool<Postgres>, room_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE game_rooms SET status = 'waiting', updated_at = NOW() WHERE room_id = $1 AND status = 'scheduled'",
+    )
+    .bind(room_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace a room's password hash (upgrading legacy bcrypt hashes to argon2id)
+pub async fn update_password_hash(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    password_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE game_rooms SET password_hash = $2, updated_at = NOW() WHERE room_id = $1")
+        .bind(room_id)
+        .bind(password_hash)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Move a waiting room from one region to another and record the migration.
+///
+/// Returns false when the room is no longer waiting or already moved.
+pub async fn migrate_region(
+    db: &Pool<Postgres>,
+    room_id: &str,
+    from_region: &str,
+    to_region: &str,
+    trigger: &str,
+    requested_by: Option<i64>,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE game_rooms
+        SET region = $3, updated_at = NOW()
+        WHERE room_id = $1 AND region = $2 AND status = 'waiting' AND is_active = TRUE
+        "#,
+    )
+    .bind(room_id)
+    .bind(from_region)
+    .bind(to_region)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO game_room_region_migrations (room_id, from_region, to_region, trigger, requested_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(room_id)
+    .bind(from_region)
+    .bind(to_region)
+    .bind(trigger)
+    .bind(requested_by)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
 // =============================================================================
 // Enhanced Game Room Functions
 // =============================================================================
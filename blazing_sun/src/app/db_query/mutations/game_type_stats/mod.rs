@@ -0,0 +1,51 @@
+//! Game Type Stats Mutation Queries
+//!
+//! Write operations for the game_type_stats table.
+
+use sqlx::{Pool, Postgres};
+
+/// Parameters for storing freshly computed stats
+pub struct UpsertGameTypeStatsParams {
+    pub game_type: String,
+    pub total_games: i64,
+    pub avg_duration_seconds: f64,
+    pub finished_rooms: i64,
+    pub disconnect_forfeits: i64,
+    pub abandoned_rooms: i64,
+    pub dice_distribution: Option<serde_json::Value>,
+}
+
+/// Insert or replace the stats of a game type
+pub async fn upsert(
+    db: &Pool<Postgres>,
+    params: &UpsertGameTypeStatsParams,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO game_type_stats (
+            game_type, total_games, avg_duration_seconds, finished_rooms,
+            disconnect_forfeits, abandoned_rooms, dice_distribution, computed_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+        ON CONFLICT (game_type) DO UPDATE SET
+            total_games = EXCLUDED.total_games,
+            avg_duration_seconds = EXCLUDED.avg_duration_seconds,
+            finished_rooms = EXCLUDED.finished_rooms,
+            disconnect_forfeits = EXCLUDED.disconnect_forfeits,
+            abandoned_rooms = EXCLUDED.abandoned_rooms,
+            dice_distribution = EXCLUDED.dice_distribution,
+            computed_at = NOW()
+        "#,
+    )
+    .bind(&params.game_type)
+    .bind(params.total_games)
+    .bind(params.avg_duration_seconds)
+    .bind(params.finished_rooms)
+    .bind(params.disconnect_forfeits)
+    .bind(params.abandoned_rooms)
+    .bind(&params.dice_distribution)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Game Webhooks Mutation Queries
+//!
+//! Write operations for the game_webhooks and game_webhook_deliveries tables.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{Pool, Postgres, Row};
+
+/// Parameters for registering a webhook
+pub struct CreateGameWebhookParams {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+    pub created_by: i64,
+}
+
+/// Parameters for updating a webhook (None fields are left unchanged)
+pub struct UpdateGameWebhookParams {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Register a webhook, returning its id
+pub async fn create(db: &Pool<Postgres>, params: &CreateGameWebhookParams) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO game_webhooks (url, secret, events, description, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(&params.url)
+    .bind(&params.secret)
+    .bind(&params.events)
+    .bind(&params.description)
+    .bind(params.created_by)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.get("id"))
+}
+
+/// Update a webhook; returns false when it does not exist
+pub async fn update(
+    db: &Pool<Postgres>,
+    id: i64,
+    params: &UpdateGameWebhookParams,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE game_webhooks
+        SET url = COALESCE($2, url),
+            events = COALESCE($3, events),
+            description = COALESCE($4, description),
+            is_active = COALESCE($5, is_active),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&params.url)
+    .bind(&params.events)
+    .bind(&params.description)
+    .bind(params.is_active)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace the signing secret of a webhook
+pub async fn rotate_secret(db: &Pool<Postgres>, id: i64, secret: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE game_webhooks SET secret = $2, updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(secret)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a webhook and its delivery log
+pub async fn delete(db: &Pool<Postgres>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM game_webhooks WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Create a pending delivery for every active webhook subscribed to
+/// `event_type`. Returns how many were created.
+pub async fn enqueue_deliveries(
+    db: &Pool<Postgres>,
+    event_type: &str,
+    room_id: &str,
+    payload: &Value,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO game_webhook_deliveries (webhook_id, event_type, room_id, payload)
+        SELECT id, $1, $2, $3
+        FROM game_webhooks
+        WHERE is_active AND $1 = ANY(events)
+        "#,
+    )
+    .bind(event_type)
+    .bind(room_id)
+    .bind(payload)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Claim a due delivery for an MQ job (-> queued). Returns false when it is
+/// no longer due (delivered, failed or claimed by another run).
+pub async fn mark_queued(db: &Pool<Postgres>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE game_webhook_deliveries
+        SET status = 'queued', updated_at = NOW()
+        WHERE id = $1 AND status IN ('pending', 'retrying', 'queued')
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Hand a claimed delivery back when its job could not be enqueued
+pub async fn release(db: &Pool<Postgres>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE game_webhook_deliveries
+        SET status = 'retrying', updated_at = NOW()
+        WHERE id = $1 AND status = 'queued'
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a successful attempt
+pub async fn mark_delivered(db: &Pool<Postgres>, id: i64, response_status: i32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE game_webhook_deliveries
+        SET status = 'delivered', attempts = attempts + 1, response_status = $2,
+            last_error = NULL, delivered_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(response_status)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a failed attempt. With `next_attempt_at` the delivery is retried
+/// then; without it the delivery is given up on.
+pub async fn mark_attempt_failed(
+    db: &Pool<Postgres>,
+    id: i64,
+    response_status: Option<i32>,
+    error: &str,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE game_webhook_deliveries
+        SET status = CASE WHEN $4::timestamptz IS NULL THEN 'failed' ELSE 'retrying' END,
+            attempts = attempts + 1,
+            response_status = $2,
+            last_error = $3,
+            next_attempt_at = COALESCE($4, next_attempt_at),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(response_status)
+    .bind(error)
+    .bind(next_attempt_at)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Schedule a delivered or failed delivery again (admin redelivery).
+/// Attempts start over. Returns false when the delivery does not exist.
+pub async fn redeliver(db: &Pool<Postgres>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE game_webhook_deliveries
+        SET status = 'pending', attempts = 0, next_attempt_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,59 @@
+//! Impersonation Sessions Mutation Queries
+//!
+//! Write operations for the impersonation_sessions table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::app::db_query::read::impersonation_sessions::{
+    map_session, ImpersonationSession, COLUMNS,
+};
+
+/// Open a session for `admin_id` acting as `user_id`
+pub async fn create(
+    db: &Pool<Postgres>,
+    admin_id: i64,
+    user_id: i64,
+    reason: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<ImpersonationSession, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        INSERT INTO impersonation_sessions (id, admin_id, user_id, reason, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(Uuid::new_v4())
+    .bind(admin_id)
+    .bind(user_id)
+    .bind(reason)
+    .bind(expires_at)
+    .fetch_one(db)
+    .await?;
+
+    Ok(map_session(row))
+}
+
+/// End an active session; None if it does not exist or already ended or expired
+pub async fn end(
+    db: &Pool<Postgres>,
+    id: Uuid,
+    ended_by: i64,
+) -> Result<Option<ImpersonationSession>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE impersonation_sessions
+        SET ended_at = NOW(), ended_by = $2
+        WHERE id = $1 AND ended_at IS NULL AND expires_at > NOW()
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(ended_by)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_session))
+}
@@ -1,18 +1,33 @@
+pub mod achievements;
 pub mod activation_hash;
 pub mod asset;
+pub mod auth_sessions;
+pub mod balance_adjustments;
+pub mod balance_holds;
+pub mod balance_ledger;
+pub mod balance_transfers;
+pub mod chat_channel;
+pub mod feature_flag;
 pub mod friend;
 pub mod gallery;
 pub mod gallery_like;
 pub mod game_chat_config;
+pub mod game_chat_mutes;
+pub mod game_invites;
 pub mod game_player_disconnects;
+pub mod game_predictions;
+pub mod game_webhooks;
 pub mod game_room;
+pub mod game_type_stats;
 pub mod game_user_mutes;
 pub mod image_variant;
 pub mod competition;
 pub mod geo_place;
 pub mod geo_place_image;
+pub mod impersonation_sessions;
 pub mod lobby;
 pub mod localization;
+pub mod notification_preferences;
 pub mod oauth_authorization;
 pub mod oauth_client;
 pub mod oauth_scope;
@@ -20,8 +35,18 @@ pub mod page_hreflang;
 pub mod page_schema;
 pub mod page_seo;
 pub mod picture;
+pub mod player_game_stats;
+pub mod premium_subscriptions;
 pub mod schema_entity;
+pub mod seed;
 pub mod session_refresh_token;
 pub mod site_config;
+pub mod tenant_theme;
+pub mod tournaments;
+pub mod two_factor;
 pub mod upload;
 pub mod user;
+pub mod user_data_exports;
+pub mod user_erasure;
+pub mod user_preferences;
+pub mod user_settings;
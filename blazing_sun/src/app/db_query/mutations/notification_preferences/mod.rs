@@ -0,0 +1,33 @@
+//! Notification Preferences Mutation Queries
+//!
+//! Write operations for the notification_preferences table.
+
+use sqlx::{Pool, Postgres};
+
+use crate::app::notifications::{NotificationCategory, NotificationChannel};
+
+/// Set the user's channel for the given categories in one transaction
+pub async fn set_channels(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    channels: &[(NotificationCategory, NotificationChannel)],
+) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    for (category, channel) in channels {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, category, channel)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, category) DO UPDATE SET channel = EXCLUDED.channel, updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(category.as_str())
+        .bind(channel.as_str())
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
@@ -0,0 +1,169 @@
+//! Player Game Stats Mutation Queries
+//!
+//! Write operations for the player_game_results and player_game_stats tables.
+
+use sqlx::{Pool, Postgres, Transaction};
+
+use crate::app::db_query::read::player_game_stats::{self as read_stats, stats_from_row};
+use crate::app::games::player_stats::{MatchResult, PlayerGameStats};
+
+/// Store a result unless the player's room is already recorded
+///
+/// Results of deleted users are dropped. Returns whether the row is new.
+async fn insert_result(
+    tx: &mut Transaction<'_, Postgres>,
+    result: &MatchResult,
+) -> Result<bool, sqlx::Error> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO player_game_results
+            (user_id, room_id, game_type, outcome, match_seconds, finished_at)
+        SELECT $1, $2, $3, $4, $5, $6
+        WHERE EXISTS (SELECT 1 FROM users WHERE id = $1)
+        ON CONFLICT (user_id, room_id) DO NOTHING
+        "#,
+    )
+    .bind(result.user_id)
+    .bind(&result.room_id)
+    .bind(&result.game_type)
+    .bind(result.outcome.as_str())
+    .bind(result.match_seconds)
+    .bind(result.finished_at)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    Ok(inserted > 0)
+}
+
+/// Lock a player's totals row (created empty if missing) for the transaction,
+/// so concurrent updates of the same player apply one after the other
+async fn lock_stats(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    game_type: &str,
+) -> Result<PlayerGameStats, sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO player_game_stats (user_id, game_type)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id, game_type) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(game_type)
+    .execute(&mut **tx)
+    .await?;
+
+    let row = sqlx::query(
+        "SELECT * FROM player_game_stats WHERE user_id = $1 AND game_type = $2 FOR UPDATE",
+    )
+    .bind(user_id)
+    .bind(game_type)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(stats_from_row(&row))
+}
+
+async fn upsert_stats(
+    tx: &mut Transaction<'_, Postgres>,
+    stats: &PlayerGameStats,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO player_game_stats
+            (user_id, game_type, games_played, wins, losses, draws, current_streak,
+             best_win_streak, timed_games, total_match_seconds, last_played_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
+        ON CONFLICT (user_id, game_type) DO UPDATE SET
+            games_played = EXCLUDED.games_played,
+            wins = EXCLUDED.wins,
+            losses = EXCLUDED.losses,
+            draws = EXCLUDED.draws,
+            current_streak = EXCLUDED.current_streak,
+            best_win_streak = EXCLUDED.best_win_streak,
+            timed_games = EXCLUDED.timed_games,
+            total_match_seconds = EXCLUDED.total_match_seconds,
+            last_played_at = EXCLUDED.last_played_at,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(stats.user_id)
+    .bind(&stats.game_type)
+    .bind(stats.games_played)
+    .bind(stats.wins)
+    .bind(stats.losses)
+    .bind(stats.draws)
+    .bind(stats.current_streak)
+    .bind(stats.best_win_streak)
+    .bind(stats.timed_games)
+    .bind(stats.total_match_seconds)
+    .bind(stats.last_played_at)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a result and add it to the player's totals
+///
+/// Returns the updated totals, or `None` when the result was already recorded
+/// (a redelivered event) and nothing changed.
+pub async fn record_result(
+    db: &Pool<Postgres>,
+    result: &MatchResult,
+) -> Result<Option<PlayerGameStats>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    if !insert_result(&mut tx, result).await? {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    let mut stats = lock_stats(&mut tx, result.user_id, &result.game_type).await?;
+    stats.record(result.outcome, result.match_seconds, result.finished_at);
+
+    upsert_stats(&mut tx, &stats).await?;
+    tx.commit().await?;
+
+    Ok(Some(stats))
+}
+
+/// Store results without touching the totals (see [`rebuild`])
+///
+/// Returns the results that were new.
+pub async fn insert_results(
+    db: &Pool<Postgres>,
+    results: &[MatchResult],
+) -> Result<Vec<MatchResult>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let mut inserted = Vec::new();
+
+    for result in results {
+        if insert_result(&mut tx, result).await? {
+            inserted.push(result.clone());
+        }
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Recompute a player's totals for one game type from their full result history
+pub async fn rebuild(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    game_type: &str,
+) -> Result<PlayerGameStats, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    lock_stats(&mut tx, user_id, game_type).await?;
+
+    let history = read_stats::results(&mut *tx, user_id, game_type).await?;
+    let stats = PlayerGameStats::from_results(user_id, game_type, &history);
+
+    upsert_stats(&mut tx, &stats).await?;
+    tx.commit().await?;
+
+    Ok(stats)
+}
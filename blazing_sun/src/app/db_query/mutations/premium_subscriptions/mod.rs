@@ -0,0 +1,79 @@
+//! Premium Subscriptions Mutation Queries
+//!
+//! Write operations for the premium_subscriptions table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+/// State of a subscription reported by checkout
+#[derive(Debug, Clone)]
+pub struct SubscriptionChange<'a> {
+    pub subscription_id: &'a str,
+    pub user_id: i64,
+    pub status: &'a str,
+    pub active: bool,
+    /// None keeps the stored period (lapses carry none)
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub cancel_at_period_end: bool,
+    /// Checkout timestamp of the event
+    pub changed_at: DateTime<Utc>,
+}
+
+/// How a reported change was stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOutcome {
+    Applied,
+    /// A later change was stored already (redelivered or reordered event)
+    Stale,
+    UnknownUser,
+}
+
+/// Store a subscription's reported state unless a later one is stored
+pub async fn apply(
+    db: &Pool<Postgres>,
+    change: &SubscriptionChange<'_>,
+) -> Result<ChangeOutcome, sqlx::Error> {
+    let user_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(change.user_id)
+        .fetch_one(db)
+        .await?;
+    if !user_exists {
+        return Ok(ChangeOutcome::UnknownUser);
+    }
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO premium_subscriptions (
+            subscription_id, user_id, status, active,
+            current_period_end, cancel_at_period_end, changed_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (subscription_id)
+        DO UPDATE SET user_id = EXCLUDED.user_id,
+                      status = EXCLUDED.status,
+                      active = EXCLUDED.active,
+                      current_period_end = COALESCE(
+                          EXCLUDED.current_period_end,
+                          premium_subscriptions.current_period_end
+                      ),
+                      cancel_at_period_end = EXCLUDED.cancel_at_period_end,
+                      changed_at = EXCLUDED.changed_at
+        WHERE premium_subscriptions.changed_at <= EXCLUDED.changed_at
+        "#,
+    )
+    .bind(change.subscription_id)
+    .bind(change.user_id)
+    .bind(change.status)
+    .bind(change.active)
+    .bind(change.current_period_end)
+    .bind(change.cancel_at_period_end)
+    .bind(change.changed_at)
+    .execute(db)
+    .await?;
+
+    Ok(if result.rows_affected() > 0 {
+        ChangeOutcome::Applied
+    } else {
+        ChangeOutcome::Stale
+    })
+}
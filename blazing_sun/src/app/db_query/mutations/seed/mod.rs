@@ -0,0 +1,284 @@
+//! Seed Mutation Queries
+//!
+//! Upserts of the local development fixtures (see `app::seed`) and their
+//! removal. Every write is keyed by a natural key (user email, room_id), so
+//! running the seed again updates the fixtures in place.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Row};
+
+/// A demo user, created or brought back to its fixture state
+pub struct SeedUserParams<'a> {
+    pub email: &'a str,
+    /// bcrypt hash of the demo password
+    pub password_hash: &'a str,
+    pub first_name: &'a str,
+    pub last_name: &'a str,
+    pub permissions: i16,
+}
+
+/// Create or update a demo user (activated, verified, no forced password
+/// change) and return its id. The balance is left alone: it only moves
+/// through ledger entries.
+pub async fn upsert_user(
+    db: &Pool<Postgres>,
+    params: &SeedUserParams<'_>,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO users (email, password, first_name, last_name, permissions, activated, verified)
+        VALUES ($1, $2, $3, $4, $5, 1, 1)
+        ON CONFLICT (email) DO UPDATE
+        SET password = EXCLUDED.password,
+            first_name = EXCLUDED.first_name,
+            last_name = EXCLUDED.last_name,
+            permissions = EXCLUDED.permissions,
+            activated = 1,
+            verified = 1,
+            user_must_set_password = 0,
+            updated_at = NOW()
+        RETURNING id
+        "#,
+    )
+    .bind(params.email)
+    .bind(params.password_hash)
+    .bind(params.first_name)
+    .bind(params.last_name)
+    .bind(params.permissions)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.get("id"))
+}
+
+/// A demo game room in a given state
+pub struct SeedRoomParams<'a> {
+    pub room_id: &'a str,
+    pub room_name: &'a str,
+    pub game_type: &'a str,
+    pub status: &'a str,
+    pub host_id: i64,
+    /// Seated players, in seat order
+    pub player_ids: &'a [i64],
+    /// Users waiting in the lobby
+    pub lobby_ids: &'a [i64],
+    pub password_hash: Option<&'a str>,
+    pub current_turn: Option<i64>,
+    pub turn_number: i32,
+    pub winner_id: Option<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub scheduled_start_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+}
+
+/// Create or reset a demo room. Player and lobby entries are built from the
+/// users table like `sp_create_game_room` does; bans, spectators and
+/// selections are cleared.
+pub async fn upsert_room(
+    db: &Pool<Postgres>,
+    params: &SeedRoomParams<'_>,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        WITH members AS (
+            SELECT u.id, jsonb_build_object(
+                'user_id', u.id,
+                'username', u.first_name,
+                'avatar_id', u.avatar_id,
+                'score', 0,
+                'is_ready', FALSE,
+                'joined_at', NOW()
+            ) AS player
+            FROM users u
+        )
+        INSERT INTO game_rooms (
+            room_id, room_name, game_type, status, host_id, players, lobby,
+            password_hash, is_password_protected, current_turn, turn_number, winner_id,
+            started_at, finished_at, scheduled_start_at, recorded_players, is_active
+        )
+        VALUES (
+            $1, $2, $3, $4, $5,
+            (SELECT COALESCE(jsonb_agg(m.player ORDER BY array_position($6::BIGINT[], m.id)), '[]'::JSONB)
+             FROM members m WHERE m.id = ANY($6)),
+            (SELECT COALESCE(jsonb_agg(m.player ORDER BY array_position($7::BIGINT[], m.id)), '[]'::JSONB)
+             FROM members m WHERE m.id = ANY($7)),
+            $8, $8 IS NOT NULL, $9, $10, $11,
+            $12, $13, $14,
+            CASE WHEN $12::TIMESTAMPTZ IS NULL THEN '{}'::BIGINT[] ELSE $6 END,
+            $15
+        )
+        ON CONFLICT (room_id) DO UPDATE
+        SET room_name = EXCLUDED.room_name,
+            game_type = EXCLUDED.game_type,
+            status = EXCLUDED.status,
+            host_id = EXCLUDED.host_id,
+            players = EXCLUDED.players,
+            lobby = EXCLUDED.lobby,
+            password_hash = EXCLUDED.password_hash,
+            is_password_protected = EXCLUDED.is_password_protected,
+            current_turn = EXCLUDED.current_turn,
+            turn_number = EXCLUDED.turn_number,
+            winner_id = EXCLUDED.winner_id,
+            started_at = EXCLUDED.started_at,
+            finished_at = EXCLUDED.finished_at,
+            scheduled_start_at = EXCLUDED.scheduled_start_at,
+            recorded_players = EXCLUDED.recorded_players,
+            is_active = EXCLUDED.is_active,
+            banned_users = '{}',
+            spectators = '{}',
+            spectators_data = '[]'::JSONB,
+            selected_players = '{}',
+            auto_players = '{}'
+        RETURNING id
+        "#,
+    )
+    .bind(params.room_id)
+    .bind(params.room_name)
+    .bind(params.game_type)
+    .bind(params.status)
+    .bind(params.host_id)
+    .bind(params.player_ids)
+    .bind(params.lobby_ids)
+    .bind(params.password_hash)
+    .bind(params.current_turn)
+    .bind(params.turn_number)
+    .bind(params.winner_id)
+    .bind(params.started_at)
+    .bind(params.finished_at)
+    .bind(params.scheduled_start_at)
+    .bind(params.is_active)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.get("id"))
+}
+
+/// Rows removed by `delete_seeded`
+#[derive(Debug, Default)]
+pub struct SeedDeletion {
+    pub users: u64,
+    pub rooms: u64,
+    pub processed_events: u64,
+    /// In the checkout service's database, see `delete_checkout_transactions`
+    pub checkout_transactions: u64,
+}
+
+/// Delete the fixtures: rooms whose room_id starts with `room_prefix`, users
+/// whose email ends with `email_suffix` (with everything that cascades from
+/// them: ledger entries, sessions, ...) and the `processed_events` rows of
+/// `topic`, so their ledger entries are applied again by the next seed.
+pub async fn delete_seeded(
+    db: &Pool<Postgres>,
+    email_suffix: &str,
+    room_prefix: &str,
+    topic: &str,
+) -> Result<SeedDeletion, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let rooms = sqlx::query("DELETE FROM game_rooms WHERE starts_with(room_id, $1)")
+        .bind(room_prefix)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let users = sqlx::query("DELETE FROM users WHERE right(email, length($1)) = $1")
+        .bind(email_suffix)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let processed_events = sqlx::query("DELETE FROM processed_events WHERE topic = $1")
+        .bind(topic)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(SeedDeletion {
+        users,
+        rooms,
+        processed_events,
+        ..SeedDeletion::default()
+    })
+}
+
+/// A settled demo payment in the checkout service's database
+pub struct SeedCheckoutParams<'a> {
+    /// Also the ledger entry's request_id
+    pub request_id: &'a str,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub currency: &'a str,
+    pub purpose: &'a str,
+    pub session_id: &'a str,
+    pub payment_intent_id: &'a str,
+    pub metadata: serde_json::Value,
+}
+
+/// Create or reset a demo `checkout_transactions` row as a succeeded
+/// payment. `checkout_db` is the checkout service's pool, not the app's.
+pub async fn upsert_checkout_transaction(
+    checkout_db: &Pool<Postgres>,
+    params: &SeedCheckoutParams<'_>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO checkout_transactions (
+            request_id, user_id, amount_cents, currency, purpose,
+            stripe_session_id, payment_intent_id, status, metadata, completed_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'payment_succeeded', $8, NOW())
+        ON CONFLICT (request_id) DO UPDATE
+        SET user_id = EXCLUDED.user_id,
+            amount_cents = EXCLUDED.amount_cents,
+            currency = EXCLUDED.currency,
+            purpose = EXCLUDED.purpose,
+            stripe_session_id = EXCLUDED.stripe_session_id,
+            stripe_session_url = NULL,
+            payment_intent_id = EXCLUDED.payment_intent_id,
+            status = 'payment_succeeded',
+            error_message = NULL,
+            metadata = EXCLUDED.metadata,
+            expires_at = NULL,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(params.request_id)
+    .bind(params.user_id)
+    .bind(params.amount_cents)
+    .bind(params.currency)
+    .bind(params.purpose)
+    .bind(params.session_id)
+    .bind(params.payment_intent_id)
+    .bind(&params.metadata)
+    .execute(checkout_db)
+    .await?;
+
+    Ok(())
+}
+
+/// Delete the demo `checkout_transactions` rows (and any dispute opened on
+/// them) from the checkout service's database
+pub async fn delete_checkout_transactions(
+    checkout_db: &Pool<Postgres>,
+    request_ids: &[&str],
+) -> Result<u64, sqlx::Error> {
+    let mut tx = checkout_db.begin().await?;
+
+    sqlx::query("DELETE FROM checkout_disputes WHERE request_id = ANY($1)")
+        .bind(request_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let deleted = sqlx::query("DELETE FROM checkout_transactions WHERE request_id = ANY($1)")
+        .bind(request_ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(deleted)
+}
@@ -0,0 +1,54 @@
+//! Tenant Theme Mutation Queries
+//!
+//! Write operations for the tenant_themes table.
+
+use sqlx::{Pool, Postgres, Row};
+
+/// Parameters for storing a tenant's overrides
+pub struct UpsertTenantThemeParams {
+    pub tenant_key: String,
+    pub theme_light: serde_json::Value,
+    pub theme_dark: serde_json::Value,
+    pub stylesheet: String,
+    pub updated_by: Option<i64>,
+}
+
+/// Insert or replace a tenant's overrides, returning the new version
+pub async fn upsert(
+    db: &Pool<Postgres>,
+    params: &UpsertTenantThemeParams,
+) -> Result<i32, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO tenant_themes (tenant_key, theme_light, theme_dark, stylesheet, updated_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (tenant_key) DO UPDATE SET
+            theme_light = EXCLUDED.theme_light,
+            theme_dark = EXCLUDED.theme_dark,
+            stylesheet = EXCLUDED.stylesheet,
+            updated_by = EXCLUDED.updated_by,
+            version = tenant_themes.version + 1,
+            updated_at = NOW()
+        RETURNING version
+        "#,
+    )
+    .bind(&params.tenant_key)
+    .bind(&params.theme_light)
+    .bind(&params.theme_dark)
+    .bind(&params.stylesheet)
+    .bind(params.updated_by)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.get("version"))
+}
+
+/// Remove a tenant's overrides so it falls back to the default theme
+pub async fn delete(db: &Pool<Postgres>, tenant_key: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM tenant_themes WHERE tenant_key = $1")
+        .bind(tenant_key)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,567 @@
+//! Tournaments Mutation Queries
+//!
+//! Write operations for the tournaments, tournament_entries and
+//! tournament_matches tables. Entry fees, refunds and prizes move coins, so
+//! every change to `users.balance` here is paired with a balance_ledger entry
+//! in the same transaction. Changes that depend on the tournament's state
+//! lock its row first, so concurrent registrations and results are applied
+//! one at a time.
+
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use serde_json::json;
+use sqlx::{Pool, Postgres, Row, Transaction};
+
+use super::balance_ledger::{self, LedgerChange};
+use crate::app::games::tournament::{self, TournamentPayout};
+
+/// Parameters for creating a tournament
+pub struct CreateTournamentParams {
+    pub name: String,
+    pub game_type: String,
+    pub bracket_size: i32,
+    pub entry_fee_cents: i64,
+    pub created_by: i64,
+}
+
+/// Result of a registration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterOutcome {
+    /// Entry fee paid; `started` when this entry filled the bracket
+    Registered { balance_after: i64, started: bool },
+    NotFound,
+    /// Registration is closed
+    NotOpen,
+    AlreadyRegistered,
+    InsufficientBalance { current_balance: i64 },
+}
+
+/// Result of withdrawing a registration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnregisterOutcome {
+    Unregistered { refund_cents: i64 },
+    NotRegistered,
+    /// The tournament already started (or is over)
+    NotOpen,
+}
+
+/// What recording a match result did to the tournament
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordResultOutcome {
+    /// The match was already decided, the winner isn't one of its players or
+    /// the tournament is no longer running
+    Ignored,
+    /// Other matches of the round are still being played
+    Recorded,
+    /// The round is complete; the matches of `round` were created
+    RoundAdvanced { round: i32 },
+    /// The final was played and the prizes paid
+    Finished { champion_id: i64, payouts: Vec<TournamentPayout> },
+}
+
+/// Create a tournament open for registration
+pub async fn create(db: &Pool<Postgres>, params: &CreateTournamentParams) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO tournaments (name, game_type, bracket_size, entry_fee_cents, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(&params.name)
+    .bind(&params.game_type)
+    .bind(params.bracket_size)
+    .bind(params.entry_fee_cents)
+    .bind(params.created_by)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.get("id"))
+}
+
+/// Register a player and move the entry fee into the prize pool.
+///
+/// The entry that fills the bracket also starts the tournament: entrants are
+/// shuffled into the first-round matches (without rooms yet).
+pub async fn register(
+    db: &Pool<Postgres>,
+    tournament_id: i64,
+    user_id: i64,
+) -> Result<RegisterOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let Some(row) = sqlx::query(
+        "SELECT bracket_size, entry_fee_cents, status FROM tournaments WHERE id = $1 FOR UPDATE",
+    )
+    .bind(tournament_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        tx.rollback().await?;
+        return Ok(RegisterOutcome::NotFound);
+    };
+
+    let bracket_size: i32 = row.get("bracket_size");
+    let entry_fee_cents: i64 = row.get("entry_fee_cents");
+    let status: String = row.get("status");
+
+    let entries: i64 = sqlx::query("SELECT COUNT(*) AS entries FROM tournament_entries WHERE tournament_id = $1")
+        .bind(tournament_id)
+        .fetch_one(&mut *tx)
+        .await?
+        .get("entries");
+
+    if status != "registering" || entries >= bracket_size as i64 {
+        tx.rollback().await?;
+        return Ok(RegisterOutcome::NotOpen);
+    }
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO tournament_entries (tournament_id, user_id, entry_fee_cents)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tournament_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(user_id)
+    .bind(entry_fee_cents)
+    .execute(&mut *tx)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(RegisterOutcome::AlreadyRegistered);
+    }
+
+    let current_balance = balance_ledger::lock_balance(&mut tx, user_id)
+        .await?
+        .unwrap_or(0);
+    if current_balance < entry_fee_cents {
+        tx.rollback().await?;
+        return Ok(RegisterOutcome::InsufficientBalance { current_balance });
+    }
+
+    let balance_after = record(
+        &mut tx,
+        tournament_id,
+        user_id,
+        -entry_fee_cents,
+        "tournament_entry",
+        json!({}),
+    )
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE tournaments
+        SET prize_pool_cents = prize_pool_cents + $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(entry_fee_cents)
+    .execute(&mut *tx)
+    .await?;
+
+    let started = entries + 1 == bracket_size as i64;
+    if started {
+        let mut players: Vec<i64> = sqlx::query("SELECT user_id FROM tournament_entries WHERE tournament_id = $1")
+            .bind(tournament_id)
+            .fetch_all(&mut *tx)
+            .await?
+            .iter()
+            .map(|r| r.get("user_id"))
+            .collect();
+        players.shuffle(&mut rand::thread_rng());
+
+        insert_round(&mut tx, tournament_id, 1, &players).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE tournaments
+            SET status = 'running', current_round = 1, started_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(RegisterOutcome::Registered { balance_after, started })
+}
+
+/// Withdraw a registration and refund the entry fee (only while registering)
+pub async fn unregister(
+    db: &Pool<Postgres>,
+    tournament_id: i64,
+    user_id: i64,
+) -> Result<UnregisterOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let status: Option<String> = sqlx::query("SELECT status FROM tournaments WHERE id = $1 FOR UPDATE")
+        .bind(tournament_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|r| r.get("status"));
+
+    match status.as_deref() {
+        None => {
+            tx.rollback().await?;
+            return Ok(UnregisterOutcome::NotRegistered);
+        }
+        Some("registering") => {}
+        Some(_) => {
+            tx.rollback().await?;
+            return Ok(UnregisterOutcome::NotOpen);
+        }
+    }
+
+    let Some(deleted) = sqlx::query(
+        r#"
+        DELETE FROM tournament_entries
+        WHERE tournament_id = $1 AND user_id = $2
+        RETURNING entry_fee_cents
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        tx.rollback().await?;
+        return Ok(UnregisterOutcome::NotRegistered);
+    };
+    let refund_cents: i64 = deleted.get("entry_fee_cents");
+
+    refund(&mut tx, tournament_id, user_id, refund_cents).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE tournaments
+        SET prize_pool_cents = prize_pool_cents - $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(refund_cents)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(UnregisterOutcome::Unregistered { refund_cents })
+}
+
+/// Link a match to the game room it is played in
+pub async fn set_match_room(db: &Pool<Postgres>, match_id: i64, room_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE tournament_matches SET room_id = $2 WHERE id = $1 AND room_id IS NULL")
+        .bind(match_id)
+        .bind(room_id)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the winner of a match and move the tournament on.
+///
+/// Completing a round creates the next round's matches (without rooms);
+/// completing the final splits the prize pool per `prize_split` and credits
+/// the prizes. Results for decided matches are ignored, so a redelivered
+/// game over event has no effect.
+pub async fn record_result(
+    db: &Pool<Postgres>,
+    match_id: i64,
+    winner_id: i64,
+    prize_split: &[u32],
+) -> Result<RecordResultOutcome, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let tournament = sqlx::query(
+        r#"
+        SELECT t.id, t.status, t.prize_pool_cents
+        FROM tournaments t
+        JOIN tournament_matches m ON m.tournament_id = t.id
+        WHERE m.id = $1
+        FOR UPDATE OF t
+        "#,
+    )
+    .bind(match_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(tournament) = tournament.filter(|t| t.get::<String, _>("status") == "running") else {
+        tx.rollback().await?;
+        return Ok(RecordResultOutcome::Ignored);
+    };
+    let tournament_id: i64 = tournament.get("id");
+    let prize_pool_cents: i64 = tournament.get("prize_pool_cents");
+
+    let Some(decided) = sqlx::query(
+        r#"
+        UPDATE tournament_matches
+        SET winner_id = $2, finished_at = NOW()
+        WHERE id = $1 AND winner_id IS NULL AND $2 IN (player1_id, player2_id)
+        RETURNING round, player1_id, player2_id
+        "#,
+    )
+    .bind(match_id)
+    .bind(winner_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        tx.rollback().await?;
+        return Ok(RecordResultOutcome::Ignored);
+    };
+
+    let round: i32 = decided.get("round");
+    let player1_id: i64 = decided.get("player1_id");
+    let loser_id = if player1_id == winner_id {
+        decided.get("player2_id")
+    } else {
+        player1_id
+    };
+
+    sqlx::query(
+        "UPDATE tournament_entries SET eliminated_round = $3 WHERE tournament_id = $1 AND user_id = $2",
+    )
+    .bind(tournament_id)
+    .bind(loser_id)
+    .bind(round)
+    .execute(&mut *tx)
+    .await?;
+
+    let winners: Vec<Option<i64>> = sqlx::query(
+        "SELECT winner_id FROM tournament_matches WHERE tournament_id = $1 AND round = $2 ORDER BY position",
+    )
+    .bind(tournament_id)
+    .bind(round)
+    .fetch_all(&mut *tx)
+    .await?
+    .iter()
+    .map(|r| r.get("winner_id"))
+    .collect();
+
+    let Some(winners) = winners.into_iter().collect::<Option<Vec<i64>>>() else {
+        tx.commit().await?;
+        return Ok(RecordResultOutcome::Recorded);
+    };
+
+    if winners.len() > 1 {
+        insert_round(&mut tx, tournament_id, round + 1, &winners).await?;
+
+        sqlx::query("UPDATE tournaments SET current_round = $2, updated_at = NOW() WHERE id = $1")
+            .bind(tournament_id)
+            .bind(round + 1)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        return Ok(RecordResultOutcome::RoundAdvanced { round: round + 1 });
+    }
+
+    // The final: rank everyone by the round they went out in, then pay out
+    let mut losers_by_round: BTreeMap<i32, Vec<i64>> = BTreeMap::new();
+    for row in sqlx::query(
+        r#"
+        SELECT user_id, eliminated_round
+        FROM tournament_entries
+        WHERE tournament_id = $1 AND eliminated_round IS NOT NULL
+        ORDER BY user_id
+        "#,
+    )
+    .bind(tournament_id)
+    .fetch_all(&mut *tx)
+    .await?
+    {
+        losers_by_round
+            .entry(row.get("eliminated_round"))
+            .or_default()
+            .push(row.get("user_id"));
+    }
+    let losers_by_round: Vec<Vec<i64>> = (1..=round)
+        .map(|r| losers_by_round.remove(&r).unwrap_or_default())
+        .collect();
+
+    let payouts = tournament::payouts(prize_pool_cents, prize_split, winner_id, &losers_by_round);
+
+    sqlx::query(
+        r#"
+        UPDATE tournament_entries
+        SET place = CASE
+            WHEN user_id = $2 THEN 1
+            ELSE (1 << ($3 - eliminated_round)) + 1
+        END
+        WHERE tournament_id = $1
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(winner_id)
+    .bind(round)
+    .execute(&mut *tx)
+    .await?;
+
+    for payout in &payouts {
+        record(
+            &mut tx,
+            tournament_id,
+            payout.user_id,
+            payout.amount_cents,
+            "tournament_prize",
+            json!({ "place": payout.place }),
+        )
+        .await?;
+
+        sqlx::query(
+            "UPDATE tournament_entries SET prize_cents = $3 WHERE tournament_id = $1 AND user_id = $2",
+        )
+        .bind(tournament_id)
+        .bind(payout.user_id)
+        .bind(payout.amount_cents)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE tournaments
+        SET status = 'finished', winner_id = $2, finished_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(winner_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(RecordResultOutcome::Finished {
+        champion_id: winner_id,
+        payouts,
+    })
+}
+
+/// Cancel a tournament that hasn't finished and refund every entry fee.
+///
+/// Returns the refunded players, or None when the tournament doesn't exist
+/// or is already over.
+pub async fn cancel(db: &Pool<Postgres>, tournament_id: i64) -> Result<Option<Vec<i64>>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let status: Option<String> = sqlx::query("SELECT status FROM tournaments WHERE id = $1 FOR UPDATE")
+        .bind(tournament_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|r| r.get("status"));
+
+    if !matches!(status.as_deref(), Some("registering" | "running")) {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    let entries = sqlx::query("SELECT user_id, entry_fee_cents FROM tournament_entries WHERE tournament_id = $1")
+        .bind(tournament_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    let mut refunded = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let user_id: i64 = entry.get("user_id");
+        refund(&mut tx, tournament_id, user_id, entry.get("entry_fee_cents")).await?;
+        refunded.push(user_id);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE tournaments
+        SET status = 'cancelled', prize_pool_cents = 0, finished_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(tournament_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(refunded))
+}
+
+/// Insert the matches of a round, pairing `players` in order
+async fn insert_round(
+    tx: &mut Transaction<'_, Postgres>,
+    tournament_id: i64,
+    round: i32,
+    players: &[i64],
+) -> Result<(), sqlx::Error> {
+    for (index, (player1_id, player2_id)) in tournament::pair_up(players).into_iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO tournament_matches (tournament_id, round, position, player1_id, player2_id)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(tournament_id)
+        .bind(round)
+        .bind(index as i32 + 1)
+        .bind(player1_id)
+        .bind(player2_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Credit an entry fee back
+async fn refund(
+    tx: &mut Transaction<'_, Postgres>,
+    tournament_id: i64,
+    user_id: i64,
+    amount_cents: i64,
+) -> Result<(), sqlx::Error> {
+    if amount_cents == 0 {
+        return Ok(());
+    }
+
+    record(
+        tx,
+        tournament_id,
+        user_id,
+        amount_cents,
+        "tournament_refund",
+        json!({}),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Move a user's balance with a ledger entry referencing the tournament
+async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    tournament_id: i64,
+    user_id: i64,
+    amount_cents: i64,
+    source: &str,
+    metadata: serde_json::Value,
+) -> Result<i64, sqlx::Error> {
+    let reference_id = format!("tournament:{}", tournament_id);
+    balance_ledger::record(
+        tx,
+        user_id,
+        &LedgerChange {
+            amount_cents,
+            source,
+            reference_id: Some(&reference_id),
+            metadata,
+        },
+    )
+    .await
+}
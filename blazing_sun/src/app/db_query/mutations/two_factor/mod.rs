@@ -0,0 +1,214 @@
+//! Two-Factor Mutation Queries
+//!
+//! Write operations for the user_two_factor, two_factor_recovery_codes and
+//! two_factor_challenges tables. `users.two_factor` is kept in step with
+//! whether a secret is enabled.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::app::two_factor::ChallengePurpose;
+
+/// Start (or restart) enrollment with a new pending secret; an enabled
+/// secret is left alone
+pub async fn start_enrollment(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    secret: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO user_two_factor (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, updated_at = NOW()
+            WHERE user_two_factor.enabled_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(secret)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Enable the pending secret after its first code (time step `step`) and
+/// store the recovery codes
+pub async fn enable(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    step: i64,
+    recovery_code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE user_two_factor
+        SET enabled_at = NOW(), last_used_step = $2, updated_at = NOW()
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE users SET two_factor = 1, updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    replace_recovery_codes_in(&mut tx, user_id, recovery_code_hashes).await?;
+
+    tx.commit().await
+}
+
+/// Remove the secret and recovery codes
+pub async fn disable(db: &Pool<Postgres>, user_id: i64) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    for table in ["user_two_factor", "two_factor_recovery_codes"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE user_id = $1", table))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query("UPDATE users SET two_factor = 0, updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+/// Record an accepted code's time step; false when a code of this or a later
+/// step was already used (a replay that raced the check)
+pub async fn use_step(db: &Pool<Postgres>, user_id: i64, step: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_two_factor
+        SET last_used_step = $2, updated_at = NOW()
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Use up a recovery code; false when it does not exist or was used
+pub async fn use_recovery_code(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    code_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE two_factor_recovery_codes
+        SET used_at = NOW()
+        WHERE id = (
+            SELECT id FROM two_factor_recovery_codes
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            LIMIT 1
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hash)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace all of the user's recovery codes
+pub async fn replace_recovery_codes(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    replace_recovery_codes_in(&mut tx, user_id, code_hashes).await?;
+    tx.commit().await
+}
+
+async fn replace_recovery_codes_in(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM two_factor_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    for code_hash in code_hashes {
+        sqlx::query("INSERT INTO two_factor_recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Open a challenge for a sign-in whose password was accepted; expired
+/// challenges are cleared on the way
+pub async fn create_challenge(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    token_hash: &str,
+    purpose: ChallengePurpose,
+    remember_me: bool,
+    expires_at: DateTime<Utc>,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query("DELETE FROM two_factor_challenges WHERE expires_at <= NOW()")
+        .execute(db)
+        .await?;
+
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO two_factor_challenges (id, user_id, token_hash, purpose, remember_me, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(purpose.as_str())
+    .bind(remember_me)
+    .bind(expires_at)
+    .execute(db)
+    .await?;
+
+    Ok(id)
+}
+
+/// Count a wrong code; returns the attempts so far
+pub async fn record_failed_attempt(db: &Pool<Postgres>, id: Uuid) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar(
+        "UPDATE two_factor_challenges SET attempts = attempts + 1 WHERE id = $1 RETURNING attempts",
+    )
+    .bind(id)
+    .fetch_one(db)
+    .await
+}
+
+/// Close a challenge (completed or out of attempts)
+pub async fn delete_challenge(db: &Pool<Postgres>, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM two_factor_challenges WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
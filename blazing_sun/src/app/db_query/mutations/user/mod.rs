@@ -1,6 +1,12 @@
-use sqlx::{Pool, Postgres};
+use serde_json::json;
+use sqlx::{Pool, Postgres, Transaction};
 use uuid::Uuid;
 
+use super::balance_ledger::{self, LedgerChange};
+
+/// Ledger source label of balances set through a profile update
+pub const LEDGER_BALANCE_SET: &str = "balance_set";
+
 pub struct CreateUserParams {
     pub email: String,
     pub password: String,
@@ -69,6 +75,8 @@ pub async fn update_full(
     user_id: i64,
     params: &UpdateUserFullParams,
 ) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
     // Update first_name and last_name
     sqlx::query!(
         "UPDATE users SET first_name = $1, last_name = $2, updated_at = NOW() WHERE id = $3",
@@ -76,18 +84,12 @@ pub async fn update_full(
         &params.last_name,
         user_id
     )
-    .execute(db)
+    .execute(&mut *tx)
     .await?;
 
     // Update balance if provided
     if let Some(balance) = params.balance {
-        sqlx::query!(
-            "UPDATE users SET balance = $1, updated_at = NOW() WHERE id = $2",
-            balance,
-            user_id
-        )
-        .execute(db)
-        .await?;
+        set_balance(&mut tx, user_id, balance).await?;
     }
 
     // Update password if provided (hash it first)
@@ -98,10 +100,12 @@ pub async fn update_full(
             &hashed_password,
             user_id
         )
-        .execute(db)
+        .execute(&mut *tx)
         .await?;
     }
 
+    tx.commit().await?;
+
     Ok(())
 }
 
@@ -119,6 +123,8 @@ pub async fn update_partial(
     user_id: i64,
     params: &UpdateUserPartialParams,
 ) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
     // Update each field individually if present
     // (SQLx requires compile-time checked queries, so we can't build dynamic SQL)
     if let Some(ref first_name) = params.first_name {
@@ -127,7 +133,7 @@ pub async fn update_partial(
             first_name,
             user_id
         )
-        .execute(db)
+        .execute(&mut *tx)
         .await?;
     }
     if let Some(ref last_name) = params.last_name {
@@ -136,17 +142,11 @@ pub async fn update_partial(
             last_name,
             user_id
         )
-        .execute(db)
+        .execute(&mut *tx)
         .await?;
     }
     if let Some(balance) = params.balance {
-        sqlx::query!(
-            "UPDATE users SET balance = $1, updated_at = NOW() WHERE id = $2",
-            balance,
-            user_id
-        )
-        .execute(db)
-        .await?;
+        set_balance(&mut tx, user_id, balance).await?;
     }
     if let Some(ref password) = params.password {
         let hashed_password = bcrypt::hash(password, bcrypt::DEFAULT_COST).unwrap();
@@ -155,10 +155,37 @@ pub async fn update_partial(
             &hashed_password,
             user_id
         )
-        .execute(db)
+        .execute(&mut *tx)
         .await?;
     }
 
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Set a user's balance, recording the difference in the balance ledger
+async fn set_balance(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    balance: i64,
+) -> Result<(), sqlx::Error> {
+    let previous = balance_ledger::lock_balance(tx, user_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    balance_ledger::record(
+        tx,
+        user_id,
+        &LedgerChange {
+            amount_cents: balance - previous,
+            source: LEDGER_BALANCE_SET,
+            reference_id: None,
+            metadata: json!({ "previous_balance": previous }),
+        },
+    )
+    .await?;
+
     Ok(())
 }
 
@@ -285,87 +312,3 @@ pub async fn update_permissions(
     .await?;
     Ok(())
 }
-
-/// Increment user balance by amount (in cents)
-pub async fn add_balance(
-    db: &Pool<Postgres>,
-    user_id: i64,
-    amount_cents: i64,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        "UPDATE users SET balance = balance + $1, updated_at = NOW() WHERE id = $2",
-        amount_cents,
-        user_id
-    )
-    .execute(db)
-    .await?;
-
-    Ok(())
-}
-
-/// Deduct balance from user if they have sufficient funds.
-/// Returns Ok(new_balance) if successful, Err if insufficient funds or user not found.
-/// This is atomic - checks and deducts in a single query.
-pub async fn deduct_balance_if_sufficient(
-    db: &Pool<Postgres>,
-    user_id: i64,
-    amount_cents: i64,
-) -> Result<i64, DeductBalanceError> {
-    // Atomic check-and-deduct using WHERE clause
-    let result = sqlx::query!(
-        r#"
-        UPDATE users
-        SET balance = balance - $1, updated_at = NOW()
-        WHERE id = $2 AND balance >= $1
-        RETURNING balance
-        "#,
-        amount_cents,
-        user_id
-    )
-    .fetch_optional(db)
-    .await
-    .map_err(|e| DeductBalanceError::DatabaseError(e.to_string()))?;
-
-    match result {
-        Some(row) => Ok(row.balance),
-        None => {
-            // Check if user exists and their current balance
-            let user = sqlx::query!(
-                "SELECT balance FROM users WHERE id = $1",
-                user_id
-            )
-            .fetch_optional(db)
-            .await
-            .map_err(|e| DeductBalanceError::DatabaseError(e.to_string()))?;
-
-            match user {
-                Some(u) => Err(DeductBalanceError::InsufficientBalance {
-                    current: u.balance,
-                    required: amount_cents,
-                }),
-                None => Err(DeductBalanceError::UserNotFound),
-            }
-        }
-    }
-}
-
-#[derive(Debug)]
-pub enum DeductBalanceError {
-    InsufficientBalance { current: i64, required: i64 },
-    UserNotFound,
-    DatabaseError(String),
-}
-
-impl std::fmt::Display for DeductBalanceError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            DeductBalanceError::InsufficientBalance { current, required } => {
-                write!(f, "Insufficient balance: has {}, needs {}", current, required)
-            }
-            DeductBalanceError::UserNotFound => write!(f, "User not found"),
-            DeductBalanceError::DatabaseError(e) => write!(f, "Database error: {}", e),
-        }
-    }
-}
-
-impl std::error::Error for DeductBalanceError {}
@@ -0,0 +1,105 @@
+//! User Data Export Mutation Queries
+//!
+//! Write operations for the user_data_exports table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Row};
+
+/// Open an export for a user.
+/// Returns None when the user already has an export being built.
+pub async fn create(db: &Pool<Postgres>, user_id: i64) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO user_data_exports (user_id)
+        VALUES ($1)
+        ON CONFLICT (user_id) WHERE status IN ('pending', 'processing') DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| r.get("id")))
+}
+
+/// Remove an export that never reached the queue
+pub async fn delete(db: &Pool<Postgres>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM user_data_exports WHERE id = $1 AND status = 'pending'")
+        .bind(id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Claim an export for the export_user_data job (a retried job claims it again)
+pub async fn mark_processing(db: &Pool<Postgres>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_data_exports
+        SET status = 'processing', updated_at = NOW()
+        WHERE id = $1 AND status IN ('pending', 'processing')
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn mark_ready(
+    db: &Pool<Postgres>,
+    id: i64,
+    storage_path: &str,
+    size_bytes: i64,
+    checksum: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE user_data_exports
+        SET status = 'ready', storage_path = $2, size_bytes = $3, checksum = $4,
+            expires_at = $5, completed_at = NOW(), error = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(storage_path)
+    .bind(size_bytes)
+    .bind(checksum)
+    .bind(expires_at)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn mark_failed(db: &Pool<Postgres>, id: i64, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE user_data_exports SET status = 'failed', error = $2, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(id)
+    .bind(error)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Close the download window once the archive is deleted
+pub async fn mark_expired(db: &Pool<Postgres>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE user_data_exports
+        SET status = 'expired', storage_path = NULL, updated_at = NOW()
+        WHERE id = $1 AND status = 'ready'
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! User Erasure Mutation Queries
+//!
+//! Write operations for the user_erasure_requests table, and the Postgres
+//! part of erasing a user's personal data.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Row};
+
+/// Text that replaces erased message content
+pub const ERASED_CONTENT: &str = "[erased]";
+
+/// Open an erasure request for a user.
+/// Returns None when the user already has a pending or queued request.
+pub async fn create(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    scheduled_for: DateTime<Utc>,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO user_erasure_requests (user_id, scheduled_for)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) WHERE status IN ('pending', 'queued') DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(scheduled_for)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| r.get("id")))
+}
+
+/// Cancel the user's request while it is still in its grace period
+pub async fn cancel(db: &Pool<Postgres>, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_erasure_requests
+        SET status = 'cancelled', cancelled_at = NOW(), updated_at = NOW()
+        WHERE user_id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim a due request for the erase_user job (pending -> queued)
+pub async fn mark_queued(db: &Pool<Postgres>, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_erasure_requests
+        SET status = 'queued', updated_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Put a queued request back to pending (the job could not be enqueued)
+pub async fn requeue(db: &Pool<Postgres>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE user_erasure_requests SET status = 'pending', updated_at = NOW() WHERE id = $1 AND status = 'queued'",
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn mark_completed(db: &Pool<Postgres>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE user_erasure_requests
+        SET status = 'completed', completed_at = NOW(), error = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn mark_failed(db: &Pool<Postgres>, id: i64, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE user_erasure_requests SET status = 'failed', error = $2, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(id)
+    .bind(error)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Anonymize a user's PII in Postgres.
+///
+/// The users row is kept (balance ledger, transactions and game rooms reference
+/// it) but its identifying fields are replaced and the password made unusable.
+/// Sessions, OAuth grants, activation hashes and friendships are removed and
+/// lobby messages are blanked. Returns the anonymized email.
+pub async fn anonymize_user(db: &Pool<Postgres>, user_id: i64) -> Result<String, sqlx::Error> {
+    let email = format!("deleted-user-{}@erased.invalid", user_id);
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET email = $2,
+            first_name = 'Deleted',
+            last_name = 'User',
+            password = '!erased',
+            avatar_uuid = NULL,
+            avatar_id = NULL,
+            activated = 0,
+            verified = 0,
+            two_factor = 0,
+            user_must_set_password = 0,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(&email)
+    .execute(&mut *tx)
+    .await?;
+
+    for table in [
+        "activation_hashes",
+        "session_refresh_tokens",
+        "oauth_refresh_tokens",
+        "oauth_authorization_codes",
+        "oauth_consent_grants",
+        "chat_channel_members",
+    ] {
+        sqlx::query(&format!("DELETE FROM {} WHERE user_id = $1", table))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query("DELETE FROM friends WHERE user_id = $1 OR friend_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE lobby_messages
+        SET content = $2, is_deleted = TRUE, deleted_at = COALESCE(deleted_at, NOW())
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ERASED_CONTENT)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(email)
+}
@@ -0,0 +1,26 @@
+//! User Preferences Mutation Queries
+//!
+//! Write operations for the user_preferences table.
+
+use sqlx::{Pool, Postgres};
+
+/// Set (or clear with `None`) the user's preferred message locale
+pub async fn set_locale(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    locale: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_preferences (user_id, locale)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET locale = EXCLUDED.locale, updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(locale)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! User Settings Mutation Queries
+//!
+//! Write operations for the user_settings table.
+
+use serde_json::Value;
+use sqlx::{Pool, Postgres};
+
+use crate::app::user_settings::SettingKey;
+
+/// Set the user's value of the given (validated) settings in one transaction;
+/// `language` is stored as the locale in `user_preferences`
+pub async fn set_values(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    values: &[(SettingKey, Value)],
+) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    for (key, value) in values {
+        if *key == SettingKey::Language {
+            sqlx::query(
+                r#"
+                INSERT INTO user_preferences (user_id, locale)
+                VALUES ($1, $2)
+                ON CONFLICT (user_id) DO UPDATE SET locale = EXCLUDED.locale, updated_at = NOW()
+                "#,
+            )
+            .bind(user_id)
+            .bind(value.as_str())
+            .execute(&mut *tx)
+            .await?;
+            continue;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_settings (user_id, key, value)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(key.as_str())
+        .bind(value)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
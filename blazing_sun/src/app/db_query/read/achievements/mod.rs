@@ -0,0 +1,65 @@
+//! Achievements Read Queries
+//!
+//! Read operations for the achievements, user_achievements and
+//! user_achievement_progress tables.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres, Row};
+
+/// An achievement with one user's progress towards it
+#[derive(Debug, Clone, Serialize)]
+pub struct UserAchievement {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    pub description: String,
+    pub metric: String,
+    pub threshold: i64,
+    pub badge: String,
+    /// The user's current value of the metric
+    pub progress: i64,
+    /// When the user unlocked it (`None` while locked)
+    pub unlocked_at: Option<DateTime<Utc>>,
+}
+
+/// Every active achievement with the user's progress, unlocked ones first
+pub async fn list_for_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Vec<UserAchievement>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT a.id, a.code, a.name, a.description, a.metric, a.threshold, a.badge,
+               COALESCE(p.total, 0)::BIGINT AS progress, ua.unlocked_at
+        FROM achievements a
+        LEFT JOIN user_achievements ua ON ua.achievement_id = a.id AND ua.user_id = $1
+        LEFT JOIN (
+            SELECT metric, SUM(amount) AS total
+            FROM user_achievement_progress
+            WHERE user_id = $1
+            GROUP BY metric
+        ) p ON p.metric = a.metric
+        WHERE a.is_active OR ua.unlocked_at IS NOT NULL
+        ORDER BY ua.unlocked_at DESC NULLS LAST, a.metric, a.threshold
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| UserAchievement {
+            id: r.get("id"),
+            code: r.get("code"),
+            name: r.get("name"),
+            description: r.get("description"),
+            metric: r.get("metric"),
+            threshold: r.get("threshold"),
+            badge: r.get("badge"),
+            progress: r.get("progress"),
+            unlocked_at: r.get("unlocked_at"),
+        })
+        .collect())
+}
@@ -0,0 +1,76 @@
+//! Auth Sessions Read Queries
+//!
+//! Read operations for the auth_sessions table.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Signed-in device record from database
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthSession {
+    pub id: Uuid,
+    pub user_id: i64,
+    pub device_info: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+pub(crate) const COLUMNS: &str =
+    "id, user_id, device_info, ip_address, created_at, last_seen_at, expires_at, revoked_at";
+
+pub(crate) fn map_session(r: PgRow) -> AuthSession {
+    AuthSession {
+        id: r.get("id"),
+        user_id: r.get("user_id"),
+        device_info: r.get("device_info"),
+        ip_address: r.get("ip_address"),
+        created_at: r.get("created_at"),
+        last_seen_at: r.get("last_seen_at"),
+        expires_at: r.get("expires_at"),
+        revoked_at: r.get("revoked_at"),
+    }
+}
+
+/// A user's sessions that are neither revoked nor expired, most recently seen first
+pub async fn get_active_by_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Vec<AuthSession>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {COLUMNS} FROM auth_sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        ORDER BY last_seen_at DESC
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_session).collect())
+}
+
+/// Session a refresh token was issued for; None for tokens issued before
+/// sessions were recorded
+pub async fn get_id_by_refresh_token(
+    db: &Pool<Postgres>,
+    token_hash: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT session_id FROM session_refresh_tokens
+        WHERE token_hash = $1
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.and_then(|r| r.get("session_id")))
+}
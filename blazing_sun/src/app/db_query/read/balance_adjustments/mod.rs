@@ -0,0 +1,122 @@
+//! Balance Adjustments Read Queries
+//!
+//! Read operations for the balance_adjustments table.
+
+use chrono::{DateTime, Utc};
+use pagination::{Cursor, Direction};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Balance adjustment record from database
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceAdjustment {
+    pub id: i64,
+    pub batch_id: Uuid,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub reason: String,
+    pub status: String,
+    pub requested_by: Option<i64>,
+    pub reviewed_by: Option<i64>,
+    pub review_note: Option<String>,
+    pub balance_after: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// Filters of the adjustment history
+#[derive(Debug, Default)]
+pub struct AdjustmentFilter<'a> {
+    pub status: Option<&'a str>,
+    pub user_id: Option<i64>,
+}
+
+const COLUMNS: &str = "id, batch_id, user_id, amount_cents, reason, status, requested_by, \
+     reviewed_by, review_note, balance_after, created_at, reviewed_at";
+
+pub(crate) fn map_adjustment(r: PgRow) -> BalanceAdjustment {
+    BalanceAdjustment {
+        id: r.get("id"),
+        batch_id: r.get("batch_id"),
+        user_id: r.get("user_id"),
+        amount_cents: r.get("amount_cents"),
+        reason: r.get("reason"),
+        status: r.get("status"),
+        requested_by: r.get("requested_by"),
+        reviewed_by: r.get("reviewed_by"),
+        review_note: r.get("review_note"),
+        balance_after: r.get("balance_after"),
+        created_at: r.get("created_at"),
+        reviewed_at: r.get("reviewed_at"),
+    }
+}
+
+/// Get an adjustment by id
+pub async fn get_by_id(db: &Pool<Postgres>, id: i64) -> Result<Option<BalanceAdjustment>, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {COLUMNS} FROM balance_adjustments WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(map_adjustment))
+}
+
+/// One page of adjustments, newest first. Keyset pagination on
+/// `(created_at, id)`; returns up to `limit + 1` rows in query order, see
+/// `pagination::paginate`.
+pub async fn get_page(
+    db: &Pool<Postgres>,
+    filter: &AdjustmentFilter<'_>,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<BalanceAdjustment>, sqlx::Error> {
+    let (keyset, order) = match cursor.map(|c| c.direction) {
+        None | Some(Direction::Next) => (
+            "($3::TIMESTAMPTZ IS NULL OR (created_at, id) < ($3, $4))",
+            "created_at DESC, id DESC",
+        ),
+        Some(Direction::Prev) => ("(created_at, id) > ($3, $4)", "created_at ASC, id ASC"),
+    };
+
+    let query = format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM balance_adjustments
+        WHERE ($1::VARCHAR IS NULL OR status = $1)
+        AND ($2::BIGINT IS NULL OR user_id = $2)
+        AND {keyset}
+        ORDER BY {order}
+        LIMIT $5
+        "#
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(filter.status)
+        .bind(filter.user_id)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id).unwrap_or_default())
+        .bind(limit + 1)
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows.into_iter().map(map_adjustment).collect())
+}
+
+/// Ids among `user_ids` that have no user
+pub async fn get_unknown_users(db: &Pool<Postgres>, user_ids: &[i64]) -> Result<Vec<i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT requested.id
+        FROM UNNEST($1::BIGINT[]) AS requested(id)
+        LEFT JOIN users ON users.id = requested.id
+        WHERE users.id IS NULL
+        "#,
+    )
+    .bind(user_ids)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("id")).collect())
+}
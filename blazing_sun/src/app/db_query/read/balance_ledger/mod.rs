@@ -0,0 +1,226 @@
+//! Balance Ledger Read Queries
+//!
+//! Read operations for the balance_ledger and balance_daily_snapshots tables.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Pool, Postgres, Row};
+
+/// Balance ledger entry from database
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceLedgerEntry {
+    pub id: i64,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub balance_after: i64,
+    pub source: String,
+    pub reference_id: Option<String>,
+    pub metadata: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Get a user's ledger entries created strictly before `before` (newest first)
+pub async fn get_by_user_before(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    before: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<BalanceLedgerEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, amount_cents, balance_after, source, reference_id, metadata, created_at
+        FROM balance_ledger
+        WHERE user_id = $1
+          AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(before)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| BalanceLedgerEntry {
+            id: r.get("id"),
+            user_id: r.get("user_id"),
+            amount_cents: r.get("amount_cents"),
+            balance_after: r.get("balance_after"),
+            source: r.get("source"),
+            reference_id: r.get("reference_id"),
+            metadata: r.get("metadata"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// A user's balance at a point in time, rebuilt from the ledger
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoricalBalance {
+    pub user_id: i64,
+    pub balance_cents: i64,
+    /// Daily snapshot the reconstruction started from (None: whole ledger)
+    pub snapshot_date: Option<NaiveDate>,
+    /// Ledger entries applied on top of the starting balance
+    pub entries_replayed: i64,
+    /// The balance was changed without a ledger entry at some point (before
+    /// every writer recorded one), so `balance_cents` cannot be trusted
+    pub untracked_changes: bool,
+}
+
+/// Rebuild a user's balance as of `at` (inclusive).
+///
+/// Starts from the latest daily snapshot taken before `at`'s day (or, without
+/// one, from the balance before the user's first ledger entry) and adds the
+/// ledger entries after it up to `at`. A user without any ledger entries has
+/// always had their current balance.
+///
+/// The ledger is then checked for changes it does not explain (see
+/// [`has_untracked_changes`]); such users get `untracked_changes`.
+///
+/// Returns `Ok(None)` when the user does not exist.
+pub async fn balance_at(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    at: DateTime<Utc>,
+) -> Result<Option<HistoricalBalance>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        WITH snapshot AS (
+            SELECT snapshot_date, balance_cents
+            FROM balance_daily_snapshots
+            WHERE user_id = $1
+              AND snapshot_date < ($2 AT TIME ZONE 'UTC')::date
+            ORDER BY snapshot_date DESC
+            LIMIT 1
+        ),
+        opening AS (
+            SELECT balance_after - amount_cents AS balance_cents
+            FROM balance_ledger
+            WHERE user_id = $1
+            ORDER BY created_at ASC, id ASC
+            LIMIT 1
+        ),
+        replayed AS (
+            SELECT COUNT(*) AS entries, COALESCE(SUM(amount_cents), 0)::BIGINT AS delta
+            FROM balance_ledger
+            WHERE user_id = $1
+              AND created_at <= $2
+              AND created_at >= COALESCE(
+                  (SELECT (snapshot_date + 1)::timestamp AT TIME ZONE 'UTC' FROM snapshot),
+                  '-infinity'::timestamptz
+              )
+        )
+        SELECT
+            u.id,
+            u.balance AS current_balance,
+            (SELECT snapshot_date FROM snapshot) AS snapshot_date,
+            COALESCE(
+                (SELECT balance_cents FROM snapshot),
+                (SELECT balance_cents FROM opening),
+                u.balance
+            ) + replayed.delta AS balance_cents,
+            replayed.entries
+        FROM users u, replayed
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(at)
+    .fetch_optional(db)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let steps: Vec<LedgerStep> = sqlx::query(
+        r#"
+        SELECT amount_cents, balance_after
+        FROM balance_ledger
+        WHERE user_id = $1
+        ORDER BY id ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?
+    .iter()
+    .map(|r| LedgerStep {
+        amount_cents: r.get("amount_cents"),
+        balance_after: r.get("balance_after"),
+    })
+    .collect();
+
+    Ok(Some(HistoricalBalance {
+        user_id: row.get("id"),
+        balance_cents: row.get("balance_cents"),
+        snapshot_date: row.get("snapshot_date"),
+        entries_replayed: row.get("entries"),
+        untracked_changes: has_untracked_changes(&steps, row.get("current_balance")),
+    }))
+}
+
+/// One ledger entry's effect on the balance
+#[derive(Debug, Clone, Copy)]
+struct LedgerStep {
+    amount_cents: i64,
+    balance_after: i64,
+}
+
+/// Whether a user's ledger (in insertion order) leaves part of their current
+/// balance unexplained.
+///
+/// The first entry may start from any balance: whatever the user held before
+/// the ledger existed. Every later entry must start where the previous one
+/// ended, and the current balance must be the last entry's. Without entries,
+/// the current balance is the pre-ledger one.
+fn has_untracked_changes(steps: &[LedgerStep], current_balance: i64) -> bool {
+    let chain_broken = steps
+        .windows(2)
+        .any(|pair| pair[1].balance_after - pair[1].amount_cents != pair[0].balance_after);
+    let tail_differs = steps
+        .last()
+        .is_some_and(|last| last.balance_after != current_balance);
+
+    chain_broken || tail_differs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(amount_cents: i64, balance_after: i64) -> LedgerStep {
+        LedgerStep {
+            amount_cents,
+            balance_after,
+        }
+    }
+
+    #[test]
+    fn pre_ledger_balance_is_trusted() {
+        // Held 5000 before the ledger existed, then won 1000 and spent 300
+        let steps = [step(1000, 6000), step(-300, 5700)];
+        assert!(!has_untracked_changes(&steps, 5700));
+
+        // Held 5000 before the ledger existed and never touched it since
+        assert!(!has_untracked_changes(&[], 5000));
+    }
+
+    #[test]
+    fn gaps_between_entries_are_untracked() {
+        // Something added 200 between the two entries without an entry
+        let steps = [step(1000, 1000), step(-300, 900)];
+        assert!(has_untracked_changes(&steps, 900));
+    }
+
+    #[test]
+    fn changes_after_the_last_entry_are_untracked() {
+        let steps = [step(1000, 1000)];
+        assert!(has_untracked_changes(&steps, 1500));
+    }
+}
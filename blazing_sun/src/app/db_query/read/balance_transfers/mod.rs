@@ -0,0 +1,150 @@
+//! Balance Transfers Read Queries
+//!
+//! Read operations for the balance_transfers table. Transfers are always read
+//! from one party's side: `direction` and `balance_after` are the viewer's.
+
+use chrono::{DateTime, Utc};
+use pagination::{Cursor, Direction};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// A transfer as seen by one of its parties
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceTransfer {
+    pub id: i64,
+    /// `sent` or `received`
+    pub direction: String,
+    pub sender_id: i64,
+    pub sender_name: String,
+    pub recipient_id: i64,
+    pub recipient_name: String,
+    pub amount_cents: i64,
+    pub memo: Option<String>,
+    /// The viewer's balance right after the transfer
+    pub balance_after: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which side of the history to list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Sent,
+    Received,
+}
+
+impl TransferDirection {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sent" => Some(TransferDirection::Sent),
+            "received" => Some(TransferDirection::Received),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferDirection::Sent => "sent",
+            TransferDirection::Received => "received",
+        }
+    }
+}
+
+/// Columns of a transfer seen by the user bound to `$1`
+const COLUMNS: &str = r#"
+    t.id,
+    CASE WHEN t.sender_id = $1 THEN 'sent' ELSE 'received' END AS direction,
+    t.sender_id,
+    CONCAT_WS(' ', s.first_name, s.last_name) AS sender_name,
+    t.recipient_id,
+    CONCAT_WS(' ', r.first_name, r.last_name) AS recipient_name,
+    t.amount_cents,
+    t.memo,
+    CASE WHEN t.sender_id = $1 THEN t.sender_balance_after ELSE t.recipient_balance_after END AS balance_after,
+    t.created_at
+"#;
+
+pub(crate) fn map_transfer(r: PgRow) -> BalanceTransfer {
+    BalanceTransfer {
+        id: r.get("id"),
+        direction: r.get("direction"),
+        sender_id: r.get("sender_id"),
+        sender_name: r.get("sender_name"),
+        recipient_id: r.get("recipient_id"),
+        recipient_name: r.get("recipient_name"),
+        amount_cents: r.get("amount_cents"),
+        memo: r.get("memo"),
+        balance_after: r.get("balance_after"),
+        created_at: r.get("created_at"),
+    }
+}
+
+/// A transfer by id, seen by `user_id`
+pub async fn get_by_id(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    id: i64,
+) -> Result<Option<BalanceTransfer>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM balance_transfers t
+        JOIN users s ON s.id = t.sender_id
+        JOIN users r ON r.id = t.recipient_id
+        WHERE t.id = $2 AND (t.sender_id = $1 OR t.recipient_id = $1)
+        "#
+    ))
+    .bind(user_id)
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_transfer))
+}
+
+/// One page of a user's sent and/or received transfers, newest first. Keyset
+/// pagination on `(created_at, id)`; returns up to `limit + 1` rows in query
+/// order, see `pagination::paginate`.
+pub async fn get_page_for_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    direction: Option<TransferDirection>,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<BalanceTransfer>, sqlx::Error> {
+    let parties = match direction {
+        None => "(t.sender_id = $1 OR t.recipient_id = $1)",
+        Some(TransferDirection::Sent) => "t.sender_id = $1",
+        Some(TransferDirection::Received) => "t.recipient_id = $1",
+    };
+    let (keyset, order) = match cursor.map(|c| c.direction) {
+        None | Some(Direction::Next) => (
+            "($2::TIMESTAMPTZ IS NULL OR (t.created_at, t.id) < ($2, $3))",
+            "t.created_at DESC, t.id DESC",
+        ),
+        Some(Direction::Prev) => ("(t.created_at, t.id) > ($2, $3)", "t.created_at ASC, t.id ASC"),
+    };
+
+    let query = format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM balance_transfers t
+        JOIN users s ON s.id = t.sender_id
+        JOIN users r ON r.id = t.recipient_id
+        WHERE {parties}
+        AND {keyset}
+        ORDER BY {order}
+        LIMIT $4
+        "#
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(user_id)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id).unwrap_or_default())
+        .bind(limit + 1)
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows.into_iter().map(map_transfer).collect())
+}
@@ -0,0 +1,176 @@
+//! Chat Channel Read Queries
+//!
+//! Read operations for the chat_channels and chat_channel_members tables.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Chat channel record from database
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatChannel {
+    pub id: i64,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_by: Option<i64>,
+    pub member_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user's membership in a channel
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatChannelMember {
+    pub channel_id: i64,
+    pub user_id: i64,
+    pub joined_at: DateTime<Utc>,
+    pub last_read_message_id: Option<String>,
+    pub last_read_at: Option<DateTime<Utc>>,
+}
+
+fn map_channel(r: PgRow) -> ChatChannel {
+    ChatChannel {
+        id: r.get("id"),
+        slug: r.get("slug"),
+        name: r.get("name"),
+        description: r.get("description"),
+        is_active: r.get("is_active"),
+        created_by: r.get("created_by"),
+        member_count: r.get("member_count"),
+        created_at: r.get("created_at"),
+        updated_at: r.get("updated_at"),
+    }
+}
+
+const CHANNEL_COLUMNS: &str = r#"
+    c.id, c.slug, c.name, c.description, c.is_active, c.created_by,
+    (SELECT COUNT(*) FROM chat_channel_members m WHERE m.channel_id = c.id) AS member_count,
+    c.created_at, c.updated_at
+"#;
+
+/// Get a channel by ID (active or not)
+pub async fn get_by_id(db: &Pool<Postgres>, id: i64) -> Result<Option<ChatChannel>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM chat_channels c WHERE c.id = $1",
+        CHANNEL_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_channel))
+}
+
+/// Get a channel by slug (active or not)
+pub async fn get_by_slug(
+    db: &Pool<Postgres>,
+    slug: &str,
+) -> Result<Option<ChatChannel>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM chat_channels c WHERE c.slug = $1",
+        CHANNEL_COLUMNS
+    ))
+    .bind(slug)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_channel))
+}
+
+/// Get all channels ordered by name (admins also see inactive ones)
+pub async fn get_all(
+    db: &Pool<Postgres>,
+    include_inactive: bool,
+) -> Result<Vec<ChatChannel>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM chat_channels c WHERE c.is_active OR $1 ORDER BY c.name",
+        CHANNEL_COLUMNS
+    ))
+    .bind(include_inactive)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_channel).collect())
+}
+
+/// Get a user's membership in a channel
+pub async fn get_membership(
+    db: &Pool<Postgres>,
+    channel_id: i64,
+    user_id: i64,
+) -> Result<Option<ChatChannelMember>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT channel_id, user_id, joined_at, last_read_message_id, last_read_at
+        FROM chat_channel_members
+        WHERE channel_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| ChatChannelMember {
+        channel_id: r.get("channel_id"),
+        user_id: r.get("user_id"),
+        joined_at: r.get("joined_at"),
+        last_read_message_id: r.get("last_read_message_id"),
+        last_read_at: r.get("last_read_at"),
+    }))
+}
+
+/// Get all memberships of a user (for unread counts)
+pub async fn get_memberships_for_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Vec<ChatChannelMember>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT m.channel_id, m.user_id, m.joined_at, m.last_read_message_id, m.last_read_at
+        FROM chat_channel_members m
+        JOIN chat_channels c ON c.id = m.channel_id
+        WHERE m.user_id = $1 AND c.is_active
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ChatChannelMember {
+            channel_id: r.get("channel_id"),
+            user_id: r.get("user_id"),
+            joined_at: r.get("joined_at"),
+            last_read_message_id: r.get("last_read_message_id"),
+            last_read_at: r.get("last_read_at"),
+        })
+        .collect())
+}
+
+/// Members of a channel whose first name matches one of `names`
+/// (case-insensitive, `names` lowercased); used to resolve `@mentions`
+pub async fn find_member_ids_by_name(
+    db: &Pool<Postgres>,
+    channel_id: i64,
+    names: &[String],
+) -> Result<Vec<i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT m.user_id
+        FROM chat_channel_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.channel_id = $1 AND LOWER(u.first_name) = ANY($2)
+        "#,
+    )
+    .bind(channel_id)
+    .bind(names)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("user_id")).collect())
+}
@@ -0,0 +1,65 @@
+//! Feature Flag Read Queries
+//!
+//! Read operations for the feature_flags table.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Feature flag record from database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    pub id: i64,
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub rollout_percentage: i16,
+    pub allowlist: Vec<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn map_row(r: PgRow) -> FeatureFlag {
+    FeatureFlag {
+        id: r.get("id"),
+        key: r.get("key"),
+        description: r.get("description"),
+        enabled: r.get("enabled"),
+        rollout_percentage: r.get("rollout_percentage"),
+        allowlist: r.get("allowlist"),
+        created_at: r.get("created_at"),
+        updated_at: r.get("updated_at"),
+    }
+}
+
+/// Get a flag by key
+pub async fn get_by_key(db: &Pool<Postgres>, key: &str) -> Result<Option<FeatureFlag>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, key, description, enabled, rollout_percentage, allowlist, created_at, updated_at
+        FROM feature_flags
+        WHERE key = $1
+        "#,
+    )
+    .bind(key)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_row))
+}
+
+/// Get all flags ordered by key
+pub async fn get_all(db: &Pool<Postgres>) -> Result<Vec<FeatureFlag>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, key, description, enabled, rollout_percentage, allowlist, created_at, updated_at
+        FROM feature_flags
+        ORDER BY key
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_row).collect())
+}
@@ -0,0 +1,51 @@
+//! Game Chat Mutes Read Queries
+//!
+//! Read operations for the game_chat_mutes table.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Moderator chat mute record from database
+#[derive(Debug, Clone)]
+pub struct GameChatMute {
+    pub room_id: String,
+    pub user_id: i64,
+    /// "all", "lobby", "players" or "spectators"
+    pub channel: String,
+    pub muted_by: i64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub(crate) const COLUMNS: &str = "room_id, user_id, channel, muted_by, created_at, expires_at";
+
+pub(crate) fn map_mute(r: PgRow) -> GameChatMute {
+    GameChatMute {
+        room_id: r.get("room_id"),
+        user_id: r.get("user_id"),
+        channel: r.get("channel"),
+        muted_by: r.get("muted_by"),
+        created_at: r.get("created_at"),
+        expires_at: r.get("expires_at"),
+    }
+}
+
+/// Mutes of a room that have not run out
+pub async fn get_active_by_room(
+    db: &Pool<Postgres>,
+    room_id: &str,
+) -> Result<Vec<GameChatMute>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {COLUMNS} FROM game_chat_mutes
+        WHERE room_id = $1 AND (expires_at IS NULL OR expires_at > NOW())
+        ORDER BY created_at
+        "#
+    ))
+    .bind(room_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_mute).collect())
+}
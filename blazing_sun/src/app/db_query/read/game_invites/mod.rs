@@ -0,0 +1,47 @@
+//! Game Invites Read Queries
+//!
+//! Read operations for the game_invites table.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Game invite record from database
+#[derive(Debug, Clone)]
+pub struct GameInvite {
+    pub id: Uuid,
+    pub room_id: String,
+    pub inviter_id: i64,
+    pub invitee_id: i64,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+pub(crate) const COLUMNS: &str =
+    "id, room_id, inviter_id, invitee_id, status, created_at, expires_at, responded_at";
+
+pub(crate) fn map_invite(r: PgRow) -> GameInvite {
+    GameInvite {
+        id: r.get("id"),
+        room_id: r.get("room_id"),
+        inviter_id: r.get("inviter_id"),
+        invitee_id: r.get("invitee_id"),
+        status: r.get("status"),
+        created_at: r.get("created_at"),
+        expires_at: r.get("expires_at"),
+        responded_at: r.get("responded_at"),
+    }
+}
+
+/// Get an invite by id
+pub async fn get_by_id(db: &Pool<Postgres>, id: Uuid) -> Result<Option<GameInvite>, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {COLUMNS} FROM game_invites WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(map_invite))
+}
@@ -0,0 +1,58 @@
+//! Game Predictions Read Queries
+//!
+//! Read operations for the game_predictions table.
+
+use sqlx::{Pool, Postgres, Row};
+
+use crate::app::games::predictions::Stake;
+
+/// Escrowed stakes of a room, oldest first
+pub async fn escrowed_for_room(db: &Pool<Postgres>, room_id: &str) -> Result<Vec<Stake>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, predicted_winner_id, stake_cents
+        FROM game_predictions
+        WHERE room_id = $1 AND status = 'escrowed'
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(room_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(stake_from_row).collect())
+}
+
+/// Rooms holding escrowed stakes older than `older_than_minutes` that no
+/// running game will settle (the room ended, was deleted or never settled)
+pub async fn orphaned_room_ids(
+    db: &Pool<Postgres>,
+    older_than_minutes: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT p.room_id
+        FROM game_predictions p
+        WHERE p.status = 'escrowed'
+          AND p.created_at < NOW() - make_interval(mins => $1::int)
+          AND NOT EXISTS (
+              SELECT 1 FROM game_rooms r
+              WHERE r.room_id = p.room_id AND r.status = 'in_progress' AND r.is_active
+          )
+        "#,
+    )
+    .bind(older_than_minutes)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(|r| r.get("room_id")).collect())
+}
+
+fn stake_from_row(row: &sqlx::postgres::PgRow) -> Stake {
+    Stake {
+        prediction_id: row.get("id"),
+        user_id: row.get("user_id"),
+        predicted_winner_id: row.get("predicted_winner_id"),
+        stake_cents: row.get("stake_cents"),
+    }
+}
@@ -3,8 +3,11 @@
 //! Read operations for the game_rooms table.
 
 use chrono::{DateTime, Utc};
+use pagination::{Cursor, Direction};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, Row};
+
+use crate::app::games::bigger_dice::BiggerDiceRules;
 
 /// Player in a game room (from JSONB)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,7 +21,7 @@ pub struct GamePlayerDb {
 }
 
 /// Game room record from database
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct GameRoomRecord {
     pub id: i64,
     pub room_id: String,
@@ -54,6 +57,36 @@ pub struct GameRoomRecord {
     pub auto_players: Vec<i64>,
 }
 
+/// Tombstone left behind by a deleted or finished room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRoomTombstone {
+    pub room_id: String,
+    pub room_name: String,
+    pub game_type: String,
+    /// "deleted", "finished" or "deactivated"
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Region ownership of an active room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRoomRegion {
+    pub room_id: String,
+    pub room_name: String,
+    pub game_type: String,
+    pub status: String,
+    pub region: String,
+}
+
+/// Active room count per region and status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionRoomCount {
+    pub region: String,
+    pub status: String,
+    pub count: i64,
+}
+
 /// Game room list item (lighter for list display)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameRoomListItem {
@@ -68,6 +101,67 @@ pub struct GameRoomListItem {
     pub created_at: DateTime<Utc>,
 }
 
+/// A room waiting for its scheduled start
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ScheduledRoom {
+    pub room_id: String,
+    pub room_name: String,
+    pub game_type: String,
+    pub host_id: i64,
+    pub host_name: String,
+    pub scheduled_start_at: DateTime<Utc>,
+    pub is_password_protected: bool,
+    pub player_count: i32,
+    pub registered_count: i64,
+}
+
+/// Lobby room search; unset filters match every room
+#[derive(Debug, Clone, Default)]
+pub struct RoomSearch {
+    pub game_type: Option<String>,
+    /// "waiting" or "in_progress"
+    pub status: Option<String>,
+    pub has_password: Option<bool>,
+    pub allow_spectators: Option<bool>,
+    /// Case-insensitive prefix of the host's username
+    pub host_prefix: Option<String>,
+    /// Case-insensitive text anywhere in the room name
+    pub query: Option<String>,
+}
+
+/// Order of room search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoomSort {
+    #[default]
+    Newest,
+    Oldest,
+}
+
+impl RoomSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "newest" => Some(Self::Newest),
+            "oldest" => Some(Self::Oldest),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Newest => "newest",
+            Self::Oldest => "oldest",
+        }
+    }
+}
+
+/// Escape `%`, `_` and `\` so user text matches literally in a LIKE pattern
+fn like_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 /// Get game room by room_id (only returns active rooms)
 pub async fn get_by_room_id(
     db: &Pool<Postgres>,
@@ -301,6 +395,193 @@ pub async fn get_by_host(
     .await
 }
 
+/// Get active rooms (waiting + in-progress) the user is hosting, playing in or watching
+pub async fn get_active_for_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Vec<GameRoomListItem>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            room_id,
+            room_name,
+            game_type,
+            status,
+            host_id,
+            players,
+            jsonb_array_length(players) AS player_count,
+            is_password_protected,
+            created_at
+        FROM game_rooms
+        WHERE is_active = TRUE
+        AND status IN ('waiting', 'in_progress')
+        AND (
+            host_id = $1
+            OR players @> jsonb_build_array(jsonb_build_object('user_id', $1::BIGINT))
+            OR $1 = ANY(spectators)
+        )
+        ORDER BY created_at DESC
+        LIMIT 20
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| GameRoomListItem {
+            room_id: r.get("room_id"),
+            room_name: r.get("room_name"),
+            game_type: r.get("game_type"),
+            status: r.get("status"),
+            host_id: r.get("host_id"),
+            players: r.get("players"),
+            player_count: r.get("player_count"),
+            is_password_protected: r.get("is_password_protected"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// Get the unexpired tombstone for a room, if the room was recently deleted or finished
+pub async fn get_tombstone(db: &Pool<Postgres>, room_id: &str) -> Option<GameRoomTombstone> {
+    let row = sqlx::query(
+        r#"
+        SELECT room_id, room_name, game_type, reason, created_at, expires_at
+        FROM game_room_tombstones
+        WHERE room_id = $1 AND expires_at > NOW()
+        "#,
+    )
+    .bind(room_id)
+    .fetch_optional(db)
+    .await
+    .ok()??;
+
+    Some(GameRoomTombstone {
+        room_id: row.get("room_id"),
+        room_name: row.get("room_name"),
+        game_type: row.get("game_type"),
+        reason: row.get("reason"),
+        created_at: row.get("created_at"),
+        expires_at: row.get("expires_at"),
+    })
+}
+
+fn row_to_room_region(row: &sqlx::postgres::PgRow) -> GameRoomRegion {
+    GameRoomRegion {
+        room_id: row.get("room_id"),
+        room_name: row.get("room_name"),
+        game_type: row.get("game_type"),
+        status: row.get("status"),
+        region: row.get("region"),
+    }
+}
+
+/// Get the owning region of an active room by room_id
+pub async fn get_region(
+    db: &Pool<Postgres>,
+    room_id: &str,
+) -> Result<Option<GameRoomRegion>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT room_id, room_name, game_type, status, region
+        FROM game_rooms
+        WHERE room_id = $1 AND is_active = TRUE
+        "#,
+    )
+    .bind(room_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_room_region))
+}
+
+/// Get the owning region of a waiting room by name
+pub async fn get_region_by_name(
+    db: &Pool<Postgres>,
+    room_name: &str,
+) -> Result<Option<GameRoomRegion>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT room_id, room_name, game_type, status, region
+        FROM game_rooms
+        WHERE room_name = $1 AND status = 'waiting' AND is_active = TRUE
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(room_name)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_room_region))
+}
+
+/// Get waiting rooms owned by a region
+pub async fn get_waiting_rooms_in_region(
+    db: &Pool<Postgres>,
+    region: &str,
+) -> Result<Vec<GameRoomRegion>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT room_id, room_name, game_type, status, region
+        FROM game_rooms
+        WHERE region = $1 AND status = 'waiting' AND is_active = TRUE
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(region)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(row_to_room_region).collect())
+}
+
+/// Last update of each waiting room owned by a region, to seed inactivity
+/// deadlines of rooms this process has not seen a command for
+pub async fn get_waiting_room_activity_in_region(
+    db: &Pool<Postgres>,
+    region: &str,
+) -> Result<Vec<(String, DateTime<Utc>)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT room_id, updated_at
+        FROM game_rooms
+        WHERE region = $1 AND status = 'waiting' AND is_active = TRUE
+        "#,
+    )
+    .bind(region)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(|r| (r.get("room_id"), r.get("updated_at"))).collect())
+}
+
+/// Count active rooms grouped by region and status
+pub async fn count_active_by_region(db: &Pool<Postgres>) -> Result<Vec<RegionRoomCount>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT region, status, COUNT(*) AS count
+        FROM game_rooms
+        WHERE is_active = TRUE AND status IN ('waiting', 'in_progress')
+        GROUP BY region, status
+        ORDER BY region, status
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| RegionRoomCount {
+            region: row.get("region"),
+            status: row.get("status"),
+            count: row.get("count"),
+        })
+        .collect())
+}
+
 /// Count active rooms
 pub async fn count_active(db: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
     let result = sqlx::query_scalar!(
@@ -363,58 +644,240 @@ pub async fn get_waiting_rooms(
     .await
 }
 
-/// Get active rooms (waiting + in-progress) for a game type
-pub async fn get_active_rooms(
+/// One page of active rooms (waiting + in-progress) for a game type, newest first.
+/// Keyset pagination on `(created_at, id)`; returns up to `limit + 1` rows in
+/// query order, see `pagination::paginate`.
+pub async fn get_active_rooms_page(
     db: &Pool<Postgres>,
     game_type: &str,
+    cursor: Option<&Cursor>,
+    limit: i64,
 ) -> Result<Vec<GameRoomRecord>, sqlx::Error> {
-    sqlx::query_as!(
-        GameRoomRecord,
+    let (keyset, order) = match cursor.map(|c| c.direction) {
+        None | Some(Direction::Next) => (
+            "($2::TIMESTAMPTZ IS NULL OR (created_at, id) < ($2, $3))",
+            "created_at DESC, id DESC",
+        ),
+        Some(Direction::Prev) => ("(created_at, id) > ($2, $3)", "created_at ASC, id ASC"),
+    };
+
+    let query = format!(
         r#"
         SELECT
-            id,
-            room_id,
-            room_name,
-            game_type,
-            status,
-            host_id,
-            players,
-            lobby,
-            banned_users,
-            spectators,
-            current_turn,
-            turn_number,
-            winner_id,
-            is_password_protected,
-            password_hash,
-            is_active,
-            created_at,
-            started_at,
-            finished_at,
-            updated_at,
-            player_count,
-            allow_spectators,
-            max_spectators,
-            admin_spectator_id,
-            lobby_chat_enabled,
-            spectators_data,
-            recorded_players,
-            recorded_spectators,
-            selected_players,
-            auto_players
+            id, room_id, room_name, game_type, status, host_id, players, lobby,
+            banned_users, spectators, current_turn, turn_number, winner_id,
+            is_password_protected, password_hash, is_active,
+            created_at, started_at, finished_at, updated_at,
+            player_count, allow_spectators, max_spectators, admin_spectator_id,
+            lobby_chat_enabled, spectators_data, recorded_players, recorded_spectators,
+            selected_players, auto_players
         FROM game_rooms
         WHERE status IN ('waiting', 'in_progress')
         AND game_type = $1
         AND is_active = TRUE
-        ORDER BY created_at DESC
-        LIMIT 50
+        AND {keyset}
+        ORDER BY {order}
+        LIMIT $4
+        "#
+    );
+
+    sqlx::query_as::<_, GameRoomRecord>(&query)
+        .bind(game_type)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id).unwrap_or_default())
+        .bind(limit + 1)
+        .fetch_all(db)
+        .await
+}
+
+/// One page of active rooms (waiting + in-progress) matching a search.
+/// Keyset pagination on `(created_at, id)` in the order `sort` asks for; `Next`
+/// reads further along that order. Returns up to `limit + 1` rows in query
+/// order, see `pagination::paginate`. Only filters that are set become conditions,
+/// so the room name match can use the trigram index.
+pub async fn search_active_rooms_page(
+    db: &Pool<Postgres>,
+    search: &RoomSearch,
+    sort: RoomSort,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<GameRoomRecord>, sqlx::Error> {
+    let mut conditions = vec![
+        "is_active = TRUE".to_string(),
+        "status IN ('waiting', 'in_progress')".to_string(),
+    ];
+    let mut next_param = 0;
+    let mut param = || {
+        next_param += 1;
+        next_param
+    };
+
+    if search.game_type.is_some() {
+        conditions.push(format!("game_type = ${}", param()));
+    }
+    if search.status.is_some() {
+        conditions.push(format!("status = ${}", param()));
+    }
+    if search.has_password.is_some() {
+        conditions.push(format!("is_password_protected = ${}", param()));
+    }
+    if search.allow_spectators.is_some() {
+        conditions.push(format!("allow_spectators = ${}", param()));
+    }
+    if search.host_prefix.is_some() {
+        // The host's username is only stored with their player or lobby entry
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM jsonb_array_elements(players || lobby) AS member \
+             WHERE member->>'user_id' = host_id::TEXT AND member->>'username' ILIKE ${})",
+            param()
+        ));
+    }
+    if search.query.is_some() {
+        conditions.push(format!("room_name ILIKE ${}", param()));
+    }
+
+    let ascending = match (sort, cursor.map(|c| c.direction)) {
+        (RoomSort::Newest, Some(Direction::Prev)) | (RoomSort::Oldest, None | Some(Direction::Next)) => true,
+        (RoomSort::Newest, None | Some(Direction::Next)) | (RoomSort::Oldest, Some(Direction::Prev)) => false,
+    };
+    if cursor.is_some() {
+        let (created_at, id) = (param(), param());
+        let comparison = if ascending { ">" } else { "<" };
+        conditions.push(format!("(created_at, id) {} (${}, ${})", comparison, created_at, id));
+    }
+    let order = if ascending {
+        "created_at ASC, id ASC"
+    } else {
+        "created_at DESC, id DESC"
+    };
+    let limit_param = param();
+
+    let query = format!(
+        r#"
+        SELECT
+            id, room_id, room_name, game_type, status, host_id, players, lobby,
+            banned_users, spectators, current_turn, turn_number, winner_id,
+            is_password_protected, password_hash, is_active,
+            created_at, started_at, finished_at, updated_at,
+            player_count, allow_spectators, max_spectators, admin_spectator_id,
+            lobby_chat_enabled, spectators_data, recorded_players, recorded_spectators,
+            selected_players, auto_players
+        FROM game_rooms
+        WHERE {}
+        ORDER BY {order}
+        LIMIT ${limit_param}
         "#,
-        game_type
+        conditions.join("\n        AND ")
+    );
+
+    // Bind in the order the conditions were numbered
+    let mut rows = sqlx::query_as::<_, GameRoomRecord>(&query);
+    if let Some(game_type) = &search.game_type {
+        rows = rows.bind(game_type);
+    }
+    if let Some(status) = &search.status {
+        rows = rows.bind(status);
+    }
+    if let Some(has_password) = search.has_password {
+        rows = rows.bind(has_password);
+    }
+    if let Some(allow_spectators) = search.allow_spectators {
+        rows = rows.bind(allow_spectators);
+    }
+    if let Some(host_prefix) = &search.host_prefix {
+        rows = rows.bind(format!("{}%", like_escape(host_prefix)));
+    }
+    if let Some(text) = &search.query {
+        rows = rows.bind(format!("%{}%", like_escape(text)));
+    }
+    if let Some(cursor) = cursor {
+        rows = rows.bind(cursor.created_at).bind(cursor.id);
+    }
+
+    rows.bind(limit + 1).fetch_all(db).await
+}
+
+/// Every active room (waiting + in-progress) of a game type, newest first.
+/// Used to rebuild the Redis room list projection.
+pub async fn get_all_active_rooms(
+    db: &Pool<Postgres>,
+    game_type: &str,
+) -> Result<Vec<GameRoomRecord>, sqlx::Error> {
+    sqlx::query_as::<_, GameRoomRecord>(
+        r#"
+        SELECT
+            id, room_id, room_name, game_type, status, host_id, players, lobby,
+            banned_users, spectators, current_turn, turn_number, winner_id,
+            is_password_protected, password_hash, is_active,
+            created_at, started_at, finished_at, updated_at,
+            player_count, allow_spectators, max_spectators, admin_spectator_id,
+            lobby_chat_enabled, spectators_data, recorded_players, recorded_spectators,
+            selected_players, auto_players
+        FROM game_rooms
+        WHERE status IN ('waiting', 'in_progress')
+        AND game_type = $1
+        AND is_active = TRUE
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )
+    .bind(game_type)
+    .fetch_all(db)
+    .await
+}
+
+/// `(room_id, game_type)` of every active room (scheduled, waiting and
+/// in-progress). Used to rebuild the Redis room game type records.
+pub async fn get_active_room_game_types(
+    db: &Pool<Postgres>,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT room_id, game_type FROM game_rooms
+         WHERE status IN ('scheduled', 'waiting', 'in_progress') AND is_active = TRUE",
     )
     .fetch_all(db)
     .await
 }
 
+/// Scheduled rooms opening before `until`, soonest first; `region` limits
+/// them to the rooms one region owns
+pub async fn get_scheduled_rooms(
+    db: &Pool<Postgres>,
+    game_type: Option<&str>,
+    region: Option<&str>,
+    until: DateTime<Utc>,
+) -> Result<Vec<ScheduledRoom>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT r.room_id, r.room_name, r.game_type, r.host_id, u.first_name AS host_name,
+               r.scheduled_start_at, r.is_password_protected, r.player_count,
+               (SELECT COUNT(*) FROM game_room_registrations g WHERE g.room_id = r.room_id) AS registered_count
+        FROM game_rooms r
+        JOIN users u ON u.id = r.host_id
+        WHERE r.status = 'scheduled'
+        AND r.is_active = TRUE
+        AND r.scheduled_start_at <= $3
+        AND ($1::VARCHAR IS NULL OR r.game_type = $1)
+        AND ($2::VARCHAR IS NULL OR r.region = $2)
+        ORDER BY r.scheduled_start_at, r.id
+        "#,
+    )
+    .bind(game_type)
+    .bind(region)
+    .bind(until)
+    .fetch_all(db)
+    .await
+}
+
+/// Users registered for a scheduled room
+pub async fn get_registered_users(db: &Pool<Postgres>, room_id: &str) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT user_id FROM game_room_registrations WHERE room_id = $1 ORDER BY created_at, user_id",
+    )
+    .bind(room_id)
+    .fetch_all(db)
+    .await
+}
+
 // =============================================================================
 // Enhanced Game Room Read Functions
 // =============================================================================
@@ -597,3 +1060,39 @@ pub async fn get_room_settings(
 
     Ok(row.map(|r| (r.player_count, r.allow_spectators, r.max_spectators, r.lobby_chat_enabled)))
 }
+
+/// Turn timer chosen at room creation (None = game default)
+pub async fn get_turn_timer(db: &Pool<Postgres>, room_id: &str) -> Result<Option<i64>, sqlx::Error> {
+    let seconds: Option<Option<i32>> =
+        sqlx::query_scalar("SELECT turn_timer_seconds FROM game_rooms WHERE room_id = $1")
+            .bind(room_id)
+            .fetch_optional(db)
+            .await?;
+
+    Ok(seconds.flatten().map(i64::from))
+}
+
+/// Bigger Dice rules of a room (None = default rules, or not a Bigger Dice room)
+pub async fn get_dice_rules(
+    db: &Pool<Postgres>,
+    room_id: &str,
+) -> Result<Option<BiggerDiceRules>, sqlx::Error> {
+    let rules: Option<Option<serde_json::Value>> =
+        sqlx::query_scalar("SELECT dice_rules FROM game_rooms WHERE room_id = $1")
+            .bind(room_id)
+            .fetch_optional(db)
+            .await?;
+
+    Ok(rules.flatten().and_then(|rules| serde_json::from_value(rules).ok()))
+}
+
+/// Bot difficulty of a practice room (None = not a vs_bot room)
+pub async fn get_bot_difficulty(db: &Pool<Postgres>, room_id: &str) -> Result<Option<String>, sqlx::Error> {
+    let difficulty: Option<Option<String>> =
+        sqlx::query_scalar("SELECT bot_difficulty FROM game_rooms WHERE room_id = $1 AND vs_bot = TRUE")
+            .bind(room_id)
+            .fetch_optional(db)
+            .await?;
+
+    Ok(difficulty.flatten())
+}
@@ -0,0 +1,70 @@
+//! Game Room Preset Read Queries
+//!
+//! Read operations for the game_room_presets table.
+
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// A named room configuration of a game type
+#[derive(Debug, Clone)]
+pub struct GameRoomPreset {
+    pub game_type: String,
+    pub preset_key: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub player_count: i32,
+    pub allow_spectators: bool,
+    pub max_spectators: i32,
+    pub turn_timer_seconds: Option<i32>,
+}
+
+impl GameRoomPreset {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            game_type: row.get("game_type"),
+            preset_key: row.get("preset_key"),
+            name: row.get("name"),
+            description: row.get("description"),
+            player_count: row.get("player_count"),
+            allow_spectators: row.get("allow_spectators"),
+            max_spectators: row.get("max_spectators"),
+            turn_timer_seconds: row.get("turn_timer_seconds"),
+        }
+    }
+}
+
+/// Active presets of a game type, in display order
+pub async fn get_by_game_type(
+    db: &Pool<Postgres>,
+    game_type: &str,
+) -> Result<Vec<GameRoomPreset>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM game_room_presets
+        WHERE game_type = $1 AND is_active = TRUE
+        ORDER BY sort_order, preset_key
+        "#,
+    )
+    .bind(game_type)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(GameRoomPreset::from_row).collect())
+}
+
+/// Get an active preset by key
+pub async fn get_by_key(
+    db: &Pool<Postgres>,
+    game_type: &str,
+    preset_key: &str,
+) -> Result<Option<GameRoomPreset>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT * FROM game_room_presets WHERE game_type = $1 AND preset_key = $2 AND is_active = TRUE",
+    )
+    .bind(game_type)
+    .bind(preset_key)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.as_ref().map(GameRoomPreset::from_row))
+}
@@ -0,0 +1,108 @@
+//! Game Type Stats Read Queries
+//!
+//! Read operations for the game_type_stats table and the game_rooms
+//! aggregates the stats job is built from.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Stored statistics for one game type
+#[derive(Debug, Clone)]
+pub struct GameTypeStats {
+    pub game_type: String,
+    pub total_games: i64,
+    pub avg_duration_seconds: f64,
+    pub finished_rooms: i64,
+    pub disconnect_forfeits: i64,
+    pub abandoned_rooms: i64,
+    pub dice_distribution: Option<serde_json::Value>,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl GameTypeStats {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            game_type: row.get("game_type"),
+            total_games: row.get("total_games"),
+            avg_duration_seconds: row.get("avg_duration_seconds"),
+            finished_rooms: row.get("finished_rooms"),
+            disconnect_forfeits: row.get("disconnect_forfeits"),
+            abandoned_rooms: row.get("abandoned_rooms"),
+            dice_distribution: row.get("dice_distribution"),
+            computed_at: row.get("computed_at"),
+        }
+    }
+}
+
+/// Room outcome counts for one game type
+#[derive(Debug, Clone)]
+pub struct RoomOutcomeCounts {
+    pub game_type: String,
+    pub finished_rooms: i64,
+    pub disconnect_forfeits: i64,
+    pub abandoned_rooms: i64,
+}
+
+/// Get stats for all game types
+pub async fn get_all(db: &Pool<Postgres>) -> Result<Vec<GameTypeStats>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM game_type_stats ORDER BY game_type")
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows.iter().map(GameTypeStats::from_row).collect())
+}
+
+/// Get stats for a single game type
+pub async fn get_by_type(
+    db: &Pool<Postgres>,
+    game_type: &str,
+) -> Result<Option<GameTypeStats>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM game_type_stats WHERE game_type = $1")
+        .bind(game_type)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.as_ref().map(GameTypeStats::from_row))
+}
+
+/// Count finished and abandoned rooms per game type.
+///
+/// A finished room counts as a disconnect forfeit when a player was deselected
+/// after the disconnect timeout or was left under auto-control.
+pub async fn count_room_outcomes(
+    db: &Pool<Postgres>,
+) -> Result<Vec<RoomOutcomeCounts>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            gr.game_type,
+            COUNT(*) FILTER (WHERE gr.status = 'finished') AS finished_rooms,
+            COUNT(*) FILTER (
+                WHERE gr.status = 'finished'
+                AND (
+                    cardinality(gr.auto_players) > 0
+                    OR EXISTS (
+                        SELECT 1 FROM game_player_disconnects d
+                        WHERE d.room_id = gr.room_id AND d.deselected
+                    )
+                )
+            ) AS disconnect_forfeits,
+            COUNT(*) FILTER (WHERE gr.status = 'abandoned') AS abandoned_rooms
+        FROM game_rooms gr
+        GROUP BY gr.game_type
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RoomOutcomeCounts {
+            game_type: row.get("game_type"),
+            finished_rooms: row.get("finished_rooms"),
+            disconnect_forfeits: row.get("disconnect_forfeits"),
+            abandoned_rooms: row.get("abandoned_rooms"),
+        })
+        .collect())
+}
@@ -0,0 +1,175 @@
+//! Game Webhooks Read Queries
+//!
+//! Read operations for the game_webhooks and game_webhook_deliveries tables.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Webhook record from database (the secret is only returned on creation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameWebhook {
+    pub id: i64,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Delivery log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameWebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event_type: String,
+    pub room_id: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn map_webhook(r: PgRow) -> GameWebhook {
+    GameWebhook {
+        id: r.get("id"),
+        url: r.get("url"),
+        secret: r.get("secret"),
+        events: r.get("events"),
+        description: r.get("description"),
+        is_active: r.get("is_active"),
+        created_by: r.get("created_by"),
+        created_at: r.get("created_at"),
+        updated_at: r.get("updated_at"),
+    }
+}
+
+fn map_delivery(r: PgRow) -> GameWebhookDelivery {
+    GameWebhookDelivery {
+        id: r.get("id"),
+        webhook_id: r.get("webhook_id"),
+        event_type: r.get("event_type"),
+        room_id: r.get("room_id"),
+        payload: r.get("payload"),
+        status: r.get("status"),
+        attempts: r.get("attempts"),
+        response_status: r.get("response_status"),
+        last_error: r.get("last_error"),
+        next_attempt_at: r.get("next_attempt_at"),
+        delivered_at: r.get("delivered_at"),
+        created_at: r.get("created_at"),
+    }
+}
+
+/// Get a webhook by id
+pub async fn get_by_id(db: &Pool<Postgres>, id: i64) -> Result<Option<GameWebhook>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, url, secret, events, description, is_active, created_by, created_at, updated_at
+        FROM game_webhooks
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_webhook))
+}
+
+/// Get all webhooks, newest first
+pub async fn get_all(db: &Pool<Postgres>) -> Result<Vec<GameWebhook>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, url, secret, events, description, is_active, created_by, created_at, updated_at
+        FROM game_webhooks
+        ORDER BY id DESC
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_webhook).collect())
+}
+
+/// Get a delivery by id
+pub async fn get_delivery(
+    db: &Pool<Postgres>,
+    id: i64,
+) -> Result<Option<GameWebhookDelivery>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, webhook_id, event_type, room_id, payload, status, attempts, response_status,
+               last_error, next_attempt_at, delivered_at, created_at
+        FROM game_webhook_deliveries
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_delivery))
+}
+
+/// Latest deliveries of a webhook, newest first
+pub async fn get_deliveries(
+    db: &Pool<Postgres>,
+    webhook_id: i64,
+    status: Option<&str>,
+    limit: i64,
+) -> Result<Vec<GameWebhookDelivery>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, webhook_id, event_type, room_id, payload, status, attempts, response_status,
+               last_error, next_attempt_at, delivered_at, created_at
+        FROM game_webhook_deliveries
+        WHERE webhook_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(status)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_delivery).collect())
+}
+
+/// Ids of deliveries whose next attempt is due, oldest first.
+///
+/// Includes deliveries stuck in `queued` for longer than `stuck_after_minutes`
+/// (their job was lost, e.g. the broker restarted before it ran).
+pub async fn get_due_ids(
+    db: &Pool<Postgres>,
+    stuck_after_minutes: i64,
+    limit: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id
+        FROM game_webhook_deliveries
+        WHERE (status IN ('pending', 'retrying') AND next_attempt_at <= NOW())
+           OR (status = 'queued' AND updated_at < NOW() - make_interval(mins => $1::int))
+        ORDER BY next_attempt_at
+        LIMIT $2
+        "#,
+    )
+    .bind(stuck_after_minutes)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(|r| r.get("id")).collect())
+}
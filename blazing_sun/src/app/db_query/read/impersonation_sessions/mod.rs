@@ -0,0 +1,86 @@
+//! Impersonation Sessions Read Queries
+//!
+//! Read operations for the impersonation_sessions table.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Impersonation session record from database
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpersonationSession {
+    pub id: Uuid,
+    pub admin_id: Option<i64>,
+    pub user_id: i64,
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub ended_by: Option<i64>,
+}
+
+/// Filters of the session history
+#[derive(Debug, Default)]
+pub struct SessionFilter {
+    pub user_id: Option<i64>,
+    pub admin_id: Option<i64>,
+}
+
+pub(crate) const COLUMNS: &str =
+    "id, admin_id, user_id, reason, started_at, expires_at, ended_at, ended_by";
+
+pub(crate) fn map_session(r: PgRow) -> ImpersonationSession {
+    ImpersonationSession {
+        id: r.get("id"),
+        admin_id: r.get("admin_id"),
+        user_id: r.get("user_id"),
+        reason: r.get("reason"),
+        started_at: r.get("started_at"),
+        expires_at: r.get("expires_at"),
+        ended_at: r.get("ended_at"),
+        ended_by: r.get("ended_by"),
+    }
+}
+
+/// Whether a session is neither ended nor expired
+pub async fn is_active(db: &Pool<Postgres>, id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM impersonation_sessions
+            WHERE id = $1 AND ended_at IS NULL AND expires_at > NOW()
+        ) AS active
+        "#,
+    )
+    .bind(id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.get("active"))
+}
+
+/// Most recent sessions first
+pub async fn get_recent(
+    db: &Pool<Postgres>,
+    filter: &SessionFilter,
+    limit: i64,
+) -> Result<Vec<ImpersonationSession>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {COLUMNS} FROM impersonation_sessions
+        WHERE ($1::BIGINT IS NULL OR user_id = $1)
+          AND ($2::BIGINT IS NULL OR admin_id = $2)
+        ORDER BY started_at DESC
+        LIMIT $3
+        "#
+    ))
+    .bind(filter.user_id)
+    .bind(filter.admin_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_session).collect())
+}
@@ -0,0 +1,30 @@
+//! Notification Preferences Read Queries
+//!
+//! Read operations for the notification_preferences table.
+
+use sqlx::{Pool, Postgres, Row};
+
+use crate::app::notifications::NotificationPreferences;
+
+/// The user's channel for every notification category (defaults for the
+/// categories they never changed)
+pub async fn get_for_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<NotificationPreferences, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT category, channel FROM notification_preferences WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let rows: Vec<(String, String)> = rows
+        .into_iter()
+        .map(|r| (r.get("category"), r.get("channel")))
+        .collect();
+
+    Ok(NotificationPreferences::from_rows(
+        rows.iter().map(|(category, channel)| (category.as_str(), channel.as_str())),
+    ))
+}
@@ -0,0 +1,156 @@
+//! Player Game Stats Read Queries
+//!
+//! Read operations for the player_game_stats and player_game_results tables
+//! and the finished game_rooms they are recorded from.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgExecutor, Pool, Postgres, Row};
+
+use crate::app::games::player_stats::{MatchResult, Outcome, PlayerGameStats};
+
+/// A finished room as stored in game_rooms
+#[derive(Debug, Clone)]
+pub struct FinishedRoomRecord {
+    pub room_id: String,
+    pub game_type: String,
+    /// Players recorded at game start (the room's players for older rooms)
+    pub players: Vec<i64>,
+    pub winner_id: Option<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+const FINISHED_ROOM_COLUMNS: &str = r#"
+    room_id, game_type, winner_id, started_at, finished_at,
+    CASE WHEN cardinality(recorded_players) > 0 THEN recorded_players
+         ELSE ARRAY(SELECT (p->>'user_id')::BIGINT FROM jsonb_array_elements(players) p)
+    END AS player_ids
+"#;
+
+impl FinishedRoomRecord {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            room_id: row.get("room_id"),
+            game_type: row.get("game_type"),
+            players: row.get("player_ids"),
+            winner_id: row.get("winner_id"),
+            started_at: row.get("started_at"),
+            finished_at: row.get("finished_at"),
+        }
+    }
+}
+
+pub(crate) fn stats_from_row(row: &PgRow) -> PlayerGameStats {
+    PlayerGameStats {
+        user_id: row.get("user_id"),
+        game_type: row.get("game_type"),
+        games_played: row.get("games_played"),
+        wins: row.get("wins"),
+        losses: row.get("losses"),
+        draws: row.get("draws"),
+        current_streak: row.get("current_streak"),
+        best_win_streak: row.get("best_win_streak"),
+        timed_games: row.get("timed_games"),
+        total_match_seconds: row.get("total_match_seconds"),
+        last_played_at: row.get("last_played_at"),
+    }
+}
+
+/// A player's totals for one game type (`None` before their first finished game)
+pub async fn get(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    game_type: &str,
+) -> Result<Option<PlayerGameStats>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM player_game_stats WHERE user_id = $1 AND game_type = $2")
+        .bind(user_id)
+        .bind(game_type)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.as_ref().map(stats_from_row))
+}
+
+/// A player's results for one game type, oldest first
+pub async fn results(
+    db: impl PgExecutor<'_>,
+    user_id: i64,
+    game_type: &str,
+) -> Result<Vec<MatchResult>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT user_id, room_id, game_type, outcome, match_seconds, finished_at
+        FROM player_game_results
+        WHERE user_id = $1 AND game_type = $2
+        ORDER BY finished_at, room_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(game_type)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let outcome: String = row.get("outcome");
+            Some(MatchResult {
+                user_id: row.get("user_id"),
+                room_id: row.get("room_id"),
+                game_type: row.get("game_type"),
+                outcome: Outcome::parse(&outcome)?,
+                match_seconds: row.get("match_seconds"),
+                finished_at: row.get("finished_at"),
+            })
+        })
+        .collect())
+}
+
+/// A room by ID (`None` if it doesn't exist or hasn't finished)
+pub async fn finished_room(
+    db: &Pool<Postgres>,
+    room_id: &str,
+) -> Result<Option<FinishedRoomRecord>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM game_rooms WHERE room_id = $1 AND status = 'finished'",
+        FINISHED_ROOM_COLUMNS
+    ))
+    .bind(room_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.as_ref().map(FinishedRoomRecord::from_row))
+}
+
+/// Finished rooms after a (finished_at, room_id) keyset cursor, oldest first
+pub async fn finished_rooms_after(
+    db: &Pool<Postgres>,
+    after: Option<(DateTime<Utc>, String)>,
+    limit: i64,
+) -> Result<Vec<FinishedRoomRecord>, sqlx::Error> {
+    let (after_at, after_room) = match after {
+        Some((at, room_id)) => (Some(at), room_id),
+        None => (None, String::new()),
+    };
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {}
+        FROM game_rooms
+        WHERE status = 'finished'
+          AND finished_at IS NOT NULL
+          AND ($1::TIMESTAMPTZ IS NULL OR (finished_at, room_id) > ($1, $2))
+        ORDER BY finished_at, room_id
+        LIMIT $3
+        "#,
+        FINISHED_ROOM_COLUMNS
+    ))
+    .bind(after_at)
+    .bind(after_room)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(FinishedRoomRecord::from_row).collect())
+}
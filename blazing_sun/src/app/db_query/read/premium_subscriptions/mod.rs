@@ -0,0 +1,67 @@
+//! Premium Subscriptions Read Queries
+//!
+//! Read operations for the premium_subscriptions table.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Premium membership record from database
+#[derive(Debug, Clone, Serialize)]
+pub struct PremiumSubscription {
+    pub subscription_id: String,
+    pub user_id: i64,
+    /// Stripe subscription status
+    pub status: String,
+    /// Whether the subscription grants premium
+    pub active: bool,
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub cancel_at_period_end: bool,
+    pub changed_at: DateTime<Utc>,
+}
+
+pub(crate) const COLUMNS: &str =
+    "subscription_id, user_id, status, active, current_period_end, cancel_at_period_end, changed_at";
+
+pub(crate) fn map_subscription(r: PgRow) -> PremiumSubscription {
+    PremiumSubscription {
+        subscription_id: r.get("subscription_id"),
+        user_id: r.get("user_id"),
+        status: r.get("status"),
+        active: r.get("active"),
+        current_period_end: r.get("current_period_end"),
+        cancel_at_period_end: r.get("cancel_at_period_end"),
+        changed_at: r.get("changed_at"),
+    }
+}
+
+/// Whether the user has premium; gate premium features on this
+pub async fn is_premium(db: &Pool<Postgres>, user_id: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM premium_subscriptions WHERE user_id = $1 AND active)",
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await
+}
+
+/// The user's subscription that grants premium, else the latest one
+pub async fn get_by_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Option<PremiumSubscription>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        SELECT {COLUMNS} FROM premium_subscriptions
+        WHERE user_id = $1
+        ORDER BY active DESC, changed_at DESC
+        LIMIT 1
+        "#
+    ))
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(map_subscription))
+}
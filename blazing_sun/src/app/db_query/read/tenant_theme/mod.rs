@@ -0,0 +1,57 @@
+//! Tenant Theme Read Queries
+//!
+//! Read operations for the tenant_themes table.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Theme overrides of one tenant
+#[derive(Debug, Clone)]
+pub struct TenantTheme {
+    pub tenant_key: String,
+    pub theme_light: serde_json::Value,
+    pub theme_dark: serde_json::Value,
+    pub version: i32,
+    pub stylesheet: String,
+    pub updated_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TenantTheme {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            tenant_key: row.get("tenant_key"),
+            theme_light: row.get("theme_light"),
+            theme_dark: row.get("theme_dark"),
+            version: row.get("version"),
+            stylesheet: row.get("stylesheet"),
+            updated_by: row.get("updated_by"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+}
+
+/// Get all tenants with theme overrides
+pub async fn get_all(db: &Pool<Postgres>) -> Result<Vec<TenantTheme>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM tenant_themes ORDER BY tenant_key")
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows.iter().map(TenantTheme::from_row).collect())
+}
+
+/// Get the overrides of a tenant (None = tenant uses the default theme)
+pub async fn get_by_key(
+    db: &Pool<Postgres>,
+    tenant_key: &str,
+) -> Result<Option<TenantTheme>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM tenant_themes WHERE tenant_key = $1")
+        .bind(tenant_key)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.as_ref().map(TenantTheme::from_row))
+}
@@ -0,0 +1,221 @@
+//! Tournaments Read Queries
+//!
+//! Read operations for the tournaments, tournament_entries and
+//! tournament_matches tables.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// Tournament record from database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tournament {
+    pub id: i64,
+    pub name: String,
+    pub game_type: String,
+    pub bracket_size: i32,
+    pub entry_fee_cents: i64,
+    pub prize_pool_cents: i64,
+    pub status: String,
+    pub current_round: i32,
+    pub winner_id: Option<i64>,
+    pub created_by: Option<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Registered player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub entry_fee_cents: i64,
+    pub eliminated_round: Option<i32>,
+    pub place: Option<i32>,
+    pub prize_cents: i64,
+    pub registered_at: DateTime<Utc>,
+}
+
+/// One match of the bracket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentMatch {
+    pub id: i64,
+    pub tournament_id: i64,
+    pub round: i32,
+    pub position: i32,
+    pub player1_id: i64,
+    pub player2_id: i64,
+    pub room_id: Option<String>,
+    pub winner_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+const TOURNAMENT_COLUMNS: &str = "id, name, game_type, bracket_size, entry_fee_cents, prize_pool_cents, \
+     status, current_round, winner_id, created_by, started_at, finished_at, created_at, updated_at";
+
+const MATCH_COLUMNS: &str = "id, tournament_id, round, position, player1_id, player2_id, room_id, \
+     winner_id, created_at, finished_at";
+
+pub(crate) fn map_tournament(r: PgRow) -> Tournament {
+    Tournament {
+        id: r.get("id"),
+        name: r.get("name"),
+        game_type: r.get("game_type"),
+        bracket_size: r.get("bracket_size"),
+        entry_fee_cents: r.get("entry_fee_cents"),
+        prize_pool_cents: r.get("prize_pool_cents"),
+        status: r.get("status"),
+        current_round: r.get("current_round"),
+        winner_id: r.get("winner_id"),
+        created_by: r.get("created_by"),
+        started_at: r.get("started_at"),
+        finished_at: r.get("finished_at"),
+        created_at: r.get("created_at"),
+        updated_at: r.get("updated_at"),
+    }
+}
+
+pub(crate) fn map_match(r: PgRow) -> TournamentMatch {
+    TournamentMatch {
+        id: r.get("id"),
+        tournament_id: r.get("tournament_id"),
+        round: r.get("round"),
+        position: r.get("position"),
+        player1_id: r.get("player1_id"),
+        player2_id: r.get("player2_id"),
+        room_id: r.get("room_id"),
+        winner_id: r.get("winner_id"),
+        created_at: r.get("created_at"),
+        finished_at: r.get("finished_at"),
+    }
+}
+
+/// Get a tournament by id
+pub async fn get_by_id(db: &Pool<Postgres>, id: i64) -> Result<Option<Tournament>, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {} FROM tournaments WHERE id = $1", TOURNAMENT_COLUMNS))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(map_tournament))
+}
+
+/// List tournaments, newest first, optionally only those with `status`
+pub async fn get_all(
+    db: &Pool<Postgres>,
+    status: Option<&str>,
+    limit: i64,
+) -> Result<Vec<Tournament>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM tournaments WHERE ($1::text IS NULL OR status = $1) ORDER BY id DESC LIMIT $2",
+        TOURNAMENT_COLUMNS
+    ))
+    .bind(status)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_tournament).collect())
+}
+
+/// Registered players in registration order
+pub async fn get_entries(
+    db: &Pool<Postgres>,
+    tournament_id: i64,
+) -> Result<Vec<TournamentEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT e.user_id, u.first_name AS username, e.entry_fee_cents, e.eliminated_round,
+               e.place, e.prize_cents, e.registered_at
+        FROM tournament_entries e
+        JOIN users u ON u.id = e.user_id
+        WHERE e.tournament_id = $1
+        ORDER BY e.registered_at, e.user_id
+        "#,
+    )
+    .bind(tournament_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TournamentEntry {
+            user_id: r.get("user_id"),
+            username: r.get("username"),
+            entry_fee_cents: r.get("entry_fee_cents"),
+            eliminated_round: r.get("eliminated_round"),
+            place: r.get("place"),
+            prize_cents: r.get("prize_cents"),
+            registered_at: r.get("registered_at"),
+        })
+        .collect())
+}
+
+/// The whole bracket, round by round
+pub async fn get_matches(
+    db: &Pool<Postgres>,
+    tournament_id: i64,
+) -> Result<Vec<TournamentMatch>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM tournament_matches WHERE tournament_id = $1 ORDER BY round, position",
+        MATCH_COLUMNS
+    ))
+    .bind(tournament_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_match).collect())
+}
+
+/// Get a match by id
+pub async fn get_match(db: &Pool<Postgres>, id: i64) -> Result<Option<TournamentMatch>, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {} FROM tournament_matches WHERE id = $1", MATCH_COLUMNS))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(map_match))
+}
+
+/// The match played in a game room, if the room belongs to a tournament
+pub async fn get_match_by_room(
+    db: &Pool<Postgres>,
+    room_id: &str,
+) -> Result<Option<TournamentMatch>, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {} FROM tournament_matches WHERE room_id = $1", MATCH_COLUMNS))
+        .bind(room_id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(map_match))
+}
+
+/// Matches still waiting for their game room
+pub async fn get_matches_without_room(
+    db: &Pool<Postgres>,
+    tournament_id: i64,
+) -> Result<Vec<TournamentMatch>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM tournament_matches WHERE tournament_id = $1 AND room_id IS NULL ORDER BY round, position",
+        MATCH_COLUMNS
+    ))
+    .bind(tournament_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(map_match).collect())
+}
+
+/// Whether a game room hosts a tournament match
+pub async fn is_match_room(db: &Pool<Postgres>, room_id: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM tournament_matches WHERE room_id = $1) AS found")
+        .bind(room_id)
+        .fetch_one(db)
+        .await?;
+
+    Ok(row.get("found"))
+}
@@ -0,0 +1,95 @@
+//! Two-Factor Read Queries
+//!
+//! Read operations for the user_two_factor, two_factor_recovery_codes and
+//! two_factor_challenges tables.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+use crate::app::two_factor::ChallengePurpose;
+
+/// A user's TOTP secret; `enabled_at` is None while enrollment is pending
+#[derive(Debug, Clone)]
+pub struct UserTwoFactor {
+    pub user_id: i64,
+    pub secret: String,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+}
+
+impl UserTwoFactor {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+}
+
+/// A sign-in waiting for a two-factor code
+#[derive(Debug, Clone)]
+pub struct TwoFactorChallenge {
+    pub id: Uuid,
+    pub user_id: i64,
+    pub purpose: ChallengePurpose,
+    pub remember_me: bool,
+    pub attempts: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The user's TOTP secret, enabled or pending
+pub async fn get(db: &Pool<Postgres>, user_id: i64) -> Result<Option<UserTwoFactor>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT user_id, secret, enabled_at, last_used_step FROM user_two_factor WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| UserTwoFactor {
+        user_id: r.get("user_id"),
+        secret: r.get("secret"),
+        enabled_at: r.get("enabled_at"),
+        last_used_step: r.get("last_used_step"),
+    }))
+}
+
+/// Recovery codes the user has not used yet
+pub async fn count_unused_recovery_codes(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM two_factor_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await
+}
+
+/// The unexpired challenge a pre-auth token belongs to
+pub async fn get_challenge(
+    db: &Pool<Postgres>,
+    token_hash: &str,
+) -> Result<Option<TwoFactorChallenge>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, user_id, purpose, remember_me, attempts, expires_at
+        FROM two_factor_challenges
+        WHERE token_hash = $1 AND expires_at > NOW()
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.and_then(|r| {
+        let purpose: String = r.get("purpose");
+        Some(TwoFactorChallenge {
+            id: r.get("id"),
+            user_id: r.get("user_id"),
+            purpose: ChallengePurpose::parse(&purpose)?,
+            remember_me: r.get("remember_me"),
+            attempts: r.get("attempts"),
+            expires_at: r.get("expires_at"),
+        })
+    }))
+}
@@ -0,0 +1,64 @@
+//! User Blocks Read Queries
+//!
+//! Read operations for the user_blocks table.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Row};
+
+/// A user on someone's block list
+#[derive(Debug, Clone)]
+pub struct BlockedUser {
+    pub user_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_id: Option<i64>,
+    pub blocked_at: DateTime<Utc>,
+}
+
+/// Users a user has blocked, most recent first
+pub async fn list(db: &Pool<Postgres>, blocker_id: i64) -> Result<Vec<BlockedUser>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT u.id, u.first_name, u.last_name, u.avatar_id, b.created_at
+         FROM user_blocks b
+         JOIN users u ON u.id = b.blocked_id
+         WHERE b.blocker_id = $1
+         ORDER BY b.created_at DESC",
+    )
+    .bind(blocker_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| BlockedUser {
+            user_id: r.get("id"),
+            first_name: r.get("first_name"),
+            last_name: r.get("last_name"),
+            avatar_id: r.get("avatar_id"),
+            blocked_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// IDs of the users a user has blocked
+pub async fn blocked_ids(db: &Pool<Postgres>, blocker_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT blocked_id FROM user_blocks WHERE blocker_id = $1")
+        .bind(blocker_id)
+        .fetch_all(db)
+        .await
+}
+
+/// IDs of the users who blocked a user
+pub async fn blocker_ids(db: &Pool<Postgres>, blocked_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT blocker_id FROM user_blocks WHERE blocked_id = $1")
+        .bind(blocked_id)
+        .fetch_all(db)
+        .await
+}
+
+/// Every block as `(blocker_id, blocked_id)`
+pub async fn all(db: &Pool<Postgres>) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT blocker_id, blocked_id FROM user_blocks")
+        .fetch_all(db)
+        .await
+}
@@ -0,0 +1,94 @@
+//! User Data Export Read Queries
+//!
+//! Read operations for the user_data_exports table.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// A user's request for a copy of their data
+#[derive(Debug, Clone)]
+pub struct UserDataExport {
+    pub id: i64,
+    pub user_id: i64,
+    pub status: String,
+    pub storage_path: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub checksum: Option<String>,
+    pub error: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl UserDataExport {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            status: row.get("status"),
+            storage_path: row.get("storage_path"),
+            size_bytes: row.get("size_bytes"),
+            checksum: row.get("checksum"),
+            error: row.get("error"),
+            requested_at: row.get("requested_at"),
+            completed_at: row.get("completed_at"),
+            expires_at: row.get("expires_at"),
+        }
+    }
+
+    /// Ready and still inside its download window
+    pub fn is_downloadable(&self, now: DateTime<Utc>) -> bool {
+        self.status == "ready"
+            && self.storage_path.is_some()
+            && self.expires_at.is_some_and(|expires_at| expires_at > now)
+    }
+}
+
+/// Get an export by id
+pub async fn get_by_id(
+    db: &Pool<Postgres>,
+    id: i64,
+) -> Result<Option<UserDataExport>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM user_data_exports WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.as_ref().map(UserDataExport::from_row))
+}
+
+/// Get the user's most recent export (any status)
+pub async fn get_latest_by_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Option<UserDataExport>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT * FROM user_data_exports WHERE user_id = $1 ORDER BY requested_at DESC, id DESC LIMIT 1",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.as_ref().map(UserDataExport::from_row))
+}
+
+/// Ready exports whose download window has closed
+pub async fn get_expired(
+    db: &Pool<Postgres>,
+    limit: i64,
+) -> Result<Vec<UserDataExport>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM user_data_exports
+        WHERE status = 'ready' AND expires_at <= NOW()
+        ORDER BY expires_at
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(UserDataExport::from_row).collect())
+}
@@ -0,0 +1,138 @@
+//! User Erasure Read Queries
+//!
+//! Read operations for the user_erasure_requests table.
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Pool, Postgres, Row};
+
+/// An account deletion request
+#[derive(Debug, Clone)]
+pub struct UserErasureRequest {
+    pub id: i64,
+    pub user_id: i64,
+    pub status: String,
+    pub requested_at: DateTime<Utc>,
+    pub scheduled_for: DateTime<Utc>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+impl UserErasureRequest {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            status: row.get("status"),
+            requested_at: row.get("requested_at"),
+            scheduled_for: row.get("scheduled_for"),
+            cancelled_at: row.get("cancelled_at"),
+            completed_at: row.get("completed_at"),
+            error: row.get("error"),
+        }
+    }
+}
+
+/// Erasure request awaiting processing, with the account it belongs to
+#[derive(Debug, Clone)]
+pub struct PendingErasure {
+    pub request: UserErasureRequest,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+/// Get a request by id
+pub async fn get_by_id(
+    db: &Pool<Postgres>,
+    id: i64,
+) -> Result<Option<UserErasureRequest>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM user_erasure_requests WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.as_ref().map(UserErasureRequest::from_row))
+}
+
+/// Get the user's most recent request (any status)
+pub async fn get_latest_by_user(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Option<UserErasureRequest>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT * FROM user_erasure_requests WHERE user_id = $1 ORDER BY requested_at DESC LIMIT 1",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.as_ref().map(UserErasureRequest::from_row))
+}
+
+/// Whether the user has a pending or queued request (account soft-deleted)
+pub async fn is_pending_erasure(db: &Pool<Postgres>, user_id: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM user_erasure_requests WHERE user_id = $1 AND status IN ('pending', 'queued'))",
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await
+}
+
+/// Pending requests whose grace period has ended
+pub async fn get_due(db: &Pool<Postgres>) -> Result<Vec<UserErasureRequest>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM user_erasure_requests
+        WHERE status = 'pending' AND scheduled_for <= NOW()
+        ORDER BY scheduled_for
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.iter().map(UserErasureRequest::from_row).collect())
+}
+
+/// Requests not yet completed or cancelled (pending, queued and failed), oldest deadline first
+pub async fn get_open_report(
+    db: &Pool<Postgres>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<PendingErasure>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT r.*, u.email, u.first_name, u.last_name
+        FROM user_erasure_requests r
+        JOIN users u ON u.id = r.user_id
+        WHERE r.status IN ('pending', 'queued', 'failed')
+        ORDER BY r.scheduled_for
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| PendingErasure {
+            request: UserErasureRequest::from_row(row),
+            email: row.get("email"),
+            first_name: row.get("first_name"),
+            last_name: row.get("last_name"),
+        })
+        .collect())
+}
+
+/// Count requests included in the open report
+pub async fn count_open(db: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM user_erasure_requests WHERE status IN ('pending', 'queued', 'failed')",
+    )
+    .fetch_one(db)
+    .await
+}
@@ -0,0 +1,15 @@
+//! User Preferences Read Queries
+//!
+//! Read operations for the user_preferences table.
+
+use sqlx::{Pool, Postgres, Row};
+
+/// The user's preferred message locale, if they picked one
+pub async fn get_locale(db: &Pool<Postgres>, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query("SELECT locale FROM user_preferences WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.and_then(|r| r.get("locale")))
+}
@@ -0,0 +1,76 @@
+//! User Settings Read Queries
+//!
+//! Read operations for the user_settings table.
+
+use serde_json::Value;
+use sqlx::{Pool, Postgres, Row};
+use std::collections::BTreeMap;
+
+use crate::app::user_settings::UserSettings;
+
+/// The user's value for every setting (defaults for the keys they never
+/// changed); `language` comes from `user_preferences.locale`
+pub async fn get_for_user(db: &Pool<Postgres>, user_id: i64) -> Result<UserSettings, sqlx::Error> {
+    let rows = sqlx::query("SELECT key, value FROM user_settings WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(db)
+        .await?;
+
+    let rows: Vec<(String, Value)> = rows
+        .into_iter()
+        .map(|r| (r.get("key"), r.get("value")))
+        .collect();
+
+    let locale: Option<String> =
+        sqlx::query_scalar("SELECT locale FROM user_preferences WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?
+            .flatten();
+
+    Ok(UserSettings::from_rows(
+        rows.iter().map(|(key, value)| (key.as_str(), value)),
+        locale.as_deref(),
+    ))
+}
+
+/// Stored rows and locale of one user
+#[derive(Default)]
+struct StoredSettings {
+    rows: Vec<(String, Value)>,
+    locale: Option<String>,
+}
+
+/// Settings of every user who changed a setting or picked a locale (for the
+/// Redis mirror)
+pub async fn all(db: &Pool<Postgres>) -> Result<Vec<(i64, UserSettings)>, sqlx::Error> {
+    let rows = sqlx::query("SELECT user_id, key, value FROM user_settings")
+        .fetch_all(db)
+        .await?;
+    let locales = sqlx::query("SELECT user_id, locale FROM user_preferences WHERE locale IS NOT NULL")
+        .fetch_all(db)
+        .await?;
+
+    let mut users: BTreeMap<i64, StoredSettings> = BTreeMap::new();
+    for r in rows {
+        users
+            .entry(r.get("user_id"))
+            .or_default()
+            .rows
+            .push((r.get("key"), r.get("value")));
+    }
+    for r in locales {
+        users.entry(r.get("user_id")).or_default().locale = r.get("locale");
+    }
+
+    Ok(users
+        .into_iter()
+        .map(|(user_id, stored)| {
+            let settings = UserSettings::from_rows(
+                stored.rows.iter().map(|(key, value)| (key.as_str(), value)),
+                stored.locale.as_deref(),
+            );
+            (user_id, settings)
+        })
+        .collect())
+}
@@ -0,0 +1,201 @@
+//! Feature Flags
+//!
+//! Runtime toggles backed by the `feature_flags` table with a short-lived Redis
+//! cache in front of it.
+//!
+//! ```rust,ignore
+//! let flags = FeatureFlags::new(db.clone(), state.redis());
+//! if flags.is_enabled(flag::CHECKOUT_KAFKA_FLOW, Some(user_id)).await { ... }
+//! ```
+//!
+//! A flag is on for a user when it is enabled and the user is either in the
+//! allowlist or falls into the rollout percentage. Buckets are derived from a
+//! hash of the flag key and user ID, so a user keeps the same answer as the
+//! percentage grows.
+
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+use tracing::warn;
+
+use crate::database::read::feature_flag::{self as db_feature_flag, FeatureFlag};
+use crate::database::SharedRedis;
+
+/// Well-known flag keys
+pub mod flag {
+    /// Balance top-ups go through the Kafka checkout flow
+    pub const CHECKOUT_KAFKA_FLOW: &str = "checkout_kafka_flow";
+
+    /// Per-game availability flag, e.g. `game.tic_tac_toe`
+    pub fn game(game_type: &str) -> String {
+        format!("game.{}", game_type)
+    }
+}
+
+/// How long evaluated flag definitions are cached in Redis
+const CACHE_TTL_SECONDS: u64 = 30;
+
+/// Redis key for a cached flag definition
+fn cache_key(key: &str) -> String {
+    format!("feature_flag:{}", key)
+}
+
+/// Feature flag evaluator
+#[derive(Clone)]
+pub struct FeatureFlags {
+    db: Pool<Postgres>,
+    redis: Option<SharedRedis>,
+}
+
+impl FeatureFlags {
+    pub fn new(db: Pool<Postgres>, redis: Option<SharedRedis>) -> Self {
+        Self { db, redis }
+    }
+
+    /// Whether `key` is on for `user_id`. Unknown flags are off.
+    pub async fn is_enabled(&self, key: &str, user_id: Option<i64>) -> bool {
+        self.is_enabled_or(key, user_id, false).await
+    }
+
+    /// Whether `key` is on for `user_id`, returning `default` when the flag does not exist.
+    ///
+    /// Used for kill switches on features that already ship (e.g. existing games),
+    /// where a missing flag must not turn the feature off.
+    pub async fn is_enabled_or(&self, key: &str, user_id: Option<i64>, default: bool) -> bool {
+        match self.load(key).await {
+            Some(flag) => evaluate(&flag, user_id),
+            None => default,
+        }
+    }
+
+    /// Drop the cached definition of a flag (call after admin changes)
+    pub async fn invalidate(&self, key: &str) {
+        if let Some(mut redis) = self.redis.clone() {
+            let result: Result<(), redis::RedisError> = redis.del(cache_key(key)).await;
+            if let Err(e) = result {
+                warn!("Failed to invalidate feature flag cache for {}: {}", key, e);
+            }
+        }
+    }
+
+    async fn load(&self, key: &str) -> Option<FeatureFlag> {
+        let mut redis = self.redis.clone();
+
+        if let Some(conn) = redis.as_mut() {
+            let cached: Result<Option<String>, redis::RedisError> = conn.get(cache_key(key)).await;
+            if let Ok(Some(json)) = cached {
+                // "null" caches a missing flag so unknown keys don't hit the database
+                if let Ok(flag) = serde_json::from_str::<Option<FeatureFlag>>(&json) {
+                    return flag;
+                }
+            }
+        }
+
+        let flag = match db_feature_flag::get_by_key(&self.db, key).await {
+            Ok(flag) => flag,
+            Err(e) => {
+                warn!("Failed to load feature flag {}: {}", key, e);
+                return None;
+            }
+        };
+
+        if let Some(conn) = redis.as_mut() {
+            if let Ok(json) = serde_json::to_string(&flag) {
+                let result: Result<(), redis::RedisError> =
+                    conn.set_ex(cache_key(key), json, CACHE_TTL_SECONDS).await;
+                if let Err(e) = result {
+                    warn!("Failed to cache feature flag {}: {}", key, e);
+                }
+            }
+        }
+
+        flag
+    }
+}
+
+/// Evaluate a flag definition for a user
+pub fn evaluate(flag: &FeatureFlag, user_id: Option<i64>) -> bool {
+    if !flag.enabled {
+        return false;
+    }
+
+    if flag.rollout_percentage >= 100 {
+        return true;
+    }
+
+    let Some(user_id) = user_id else {
+        return false;
+    };
+
+    if flag.allowlist.contains(&user_id) {
+        return true;
+    }
+
+    i16::from(bucket(&flag.key, user_id)) < flag.rollout_percentage
+}
+
+/// Stable bucket (0-99) for a user within a flag
+fn bucket(key: &str, user_id: i64) -> u8 {
+    let digest = Sha256::digest(format!("{}:{}", key, user_id).as_bytes());
+    let value = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    (value % 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn flag(enabled: bool, rollout_percentage: i16, allowlist: Vec<i64>) -> FeatureFlag {
+        FeatureFlag {
+            id: 1,
+            key: "test_flag".to_string(),
+            description: None,
+            enabled,
+            rollout_percentage,
+            allowlist,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn disabled_flag_is_off_even_for_allowlisted_users() {
+        assert!(!evaluate(&flag(false, 100, vec![7]), Some(7)));
+    }
+
+    #[test]
+    fn allowlist_wins_over_zero_percent() {
+        let f = flag(true, 0, vec![7]);
+        assert!(evaluate(&f, Some(7)));
+        assert!(!evaluate(&f, Some(8)));
+        assert!(!evaluate(&f, None));
+    }
+
+    #[test]
+    fn full_rollout_includes_anonymous_users() {
+        assert!(evaluate(&flag(true, 100, vec![]), None));
+    }
+
+    #[test]
+    fn percentage_rollout_is_stable_and_roughly_proportional() {
+        let f = flag(true, 30, vec![]);
+        let enabled = (1..=10_000).filter(|id| evaluate(&f, Some(*id))).count();
+        assert!((2_500..3_500).contains(&enabled), "enabled = {}", enabled);
+
+        for id in 1..100 {
+            assert_eq!(evaluate(&f, Some(id)), evaluate(&f, Some(id)));
+        }
+    }
+
+    #[test]
+    fn growing_rollout_keeps_existing_users() {
+        let small = flag(true, 10, vec![]);
+        let large = flag(true, 50, vec![]);
+        for id in 1..1_000 {
+            if evaluate(&small, Some(id)) {
+                assert!(evaluate(&large, Some(id)));
+            }
+        }
+    }
+}
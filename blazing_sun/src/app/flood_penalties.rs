@@ -0,0 +1,150 @@
+//! WebSocket flood penalties
+//!
+//! The WebSocket gateway counts users' rate limit violations in Redis and
+//! escalates from a warning to mutes to a disconnect. Each penalized user has
+//! a hash `flood:penalty:{user_id}` with `strikes`, `last_strike_at` and
+//! `muted_until` (unix ms), and the sorted set `flood:penalized` lists them,
+//! scored by when their record expires.
+//!
+//! Admins inspect and clear penalties here. Clearing removes the record; the
+//! gateway re-reads the mute of a muted connection about once a second, so a
+//! cleared mute is lifted right away.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::Serialize;
+
+use crate::database::SharedRedis;
+
+const PENALTY_PREFIX: &str = "flood:penalty:";
+const PENALIZED: &str = "flood:penalized";
+
+fn penalty_key(user_id: i64) -> String {
+    format!("{}{}", PENALTY_PREFIX, user_id)
+}
+
+/// A user's flood record
+#[derive(Debug, Clone, Serialize)]
+pub struct FloodPenalty {
+    pub user_id: i64,
+    /// Strikes within the gateway's strike window
+    pub strikes: u32,
+    pub last_strike_at: Option<DateTime<Utc>>,
+    pub muted_until: Option<DateTime<Utc>>,
+    pub is_muted: bool,
+}
+
+impl FloodPenalty {
+    /// Build from the fields of a penalty hash; `None` for a missing record
+    fn from_fields(user_id: i64, fields: &HashMap<String, String>, now: DateTime<Utc>) -> Option<Self> {
+        let strikes = fields.get("strikes")?.parse().ok()?;
+        let timestamp = |field: &str| {
+            fields
+                .get(field)
+                .and_then(|value| value.parse().ok())
+                .and_then(DateTime::from_timestamp_millis)
+        };
+        let muted_until = timestamp("muted_until");
+
+        Some(Self {
+            user_id,
+            strikes,
+            last_strike_at: timestamp("last_strike_at"),
+            muted_until,
+            is_muted: muted_until.is_some_and(|until| until > now),
+        })
+    }
+}
+
+/// Reads and clears the gateway's flood records
+pub struct FloodPenalties {
+    redis: SharedRedis,
+}
+
+impl FloodPenalties {
+    pub fn new(redis: SharedRedis) -> Self {
+        Self { redis }
+    }
+
+    /// Users with a flood record, most recently penalized first
+    pub async fn list(&self, limit: usize) -> Result<Vec<FloodPenalty>, redis::RedisError> {
+        let mut redis = self.redis.clone();
+        let now = Utc::now();
+
+        // Records expire on their own; drop their index entries with them
+        let _: () = redis
+            .zrembyscore(PENALIZED, "-inf", now.timestamp_millis())
+            .await?;
+        let user_ids: Vec<i64> = redis.zrevrange(PENALIZED, 0, limit as isize - 1).await?;
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for user_id in &user_ids {
+            pipe.hgetall(penalty_key(*user_id));
+        }
+        let records: Vec<HashMap<String, String>> = pipe.query_async(&mut redis).await?;
+
+        Ok(user_ids
+            .into_iter()
+            .zip(records)
+            .filter_map(|(user_id, fields)| FloodPenalty::from_fields(user_id, &fields, now))
+            .collect())
+    }
+
+    /// A user's flood record, if they have one
+    pub async fn get(&self, user_id: i64) -> Result<Option<FloodPenalty>, redis::RedisError> {
+        let mut redis = self.redis.clone();
+        let fields: HashMap<String, String> = redis.hgetall(penalty_key(user_id)).await?;
+        Ok(FloodPenalty::from_fields(user_id, &fields, Utc::now()))
+    }
+
+    /// Forget a user's strikes and lift their mute; false when there was nothing to clear
+    pub async fn clear(&self, user_id: i64) -> Result<bool, redis::RedisError> {
+        let mut redis = self.redis.clone();
+        let (deleted, _): (u32, u32) = redis::pipe()
+            .atomic()
+            .del(penalty_key(user_id))
+            .zrem(PENALIZED, user_id)
+            .query_async(&mut redis)
+            .await?;
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn records_report_whether_the_user_is_still_muted() {
+        let now = DateTime::from_timestamp_millis(1_700_000_000_000).expect("now");
+        let record = fields(&[
+            ("strikes", "3"),
+            ("last_strike_at", "1699999990000"),
+            ("muted_until", "1700000020000"),
+        ]);
+
+        let penalty = FloodPenalty::from_fields(7, &record, now).expect("penalty");
+        assert_eq!(penalty.strikes, 3);
+        assert!(penalty.is_muted);
+
+        let later = now + chrono::Duration::seconds(30);
+        assert!(!FloodPenalty::from_fields(7, &record, later).expect("penalty").is_muted);
+    }
+
+    #[test]
+    fn missing_records_are_none() {
+        assert!(FloodPenalty::from_fields(7, &HashMap::new(), Utc::now()).is_none());
+    }
+}
@@ -1,16 +1,20 @@
 //! Bigger Dice game logic
 //!
-//! Rules:
-//! - N players (2-10) take turns rolling a single die (1-6)
+//! Rules (defaults in brackets, configurable per room with [`BiggerDiceRules`]):
+//! - N players (2-10) take turns rolling their dice [one six-sided die]; a
+//!   roll is the total of the dice
 //! - Each round, all active players roll once
 //! - After all players roll, compare all rolls to find highest
 //! - If ONE player has highest roll, they get 1 point and new round starts
 //! - If MULTIPLE players tie for highest, only those players enter a tiebreaker
-//! - In tiebreaker, only tied players roll again
-//! - Repeat tiebreaker until one clear winner emerges
-//! - Award the point to the tiebreaker winner
-//! - First to reach 10 points wins the game
-
+//! - Sudden death tiebreaker [default]: only tied players roll again, repeated
+//!   until one clear winner emerges, who gets the point
+//! - Best-of tiebreaker: the tied players play roll-offs; a roll-off's single
+//!   highest roller wins it (tied roll-offs are replayed) and the first to win
+//!   the majority of `tiebreaker_roll_offs` gets the point
+//! - First to reach `rounds_to_win` points [10] wins the game
+
+use super::room_config::{RoomConfigError, RoomConstraints};
 use super::types::{GameEvent, GameRoom, GameTurn, RoomStatus};
 use chrono::Utc;
 use rand::Rng;
@@ -18,9 +22,134 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::info;
 
-/// Win score for Bigger Dice
+/// Win score for Bigger Dice rooms with the default rules
 pub const WIN_SCORE: i32 = 10;
 
+/// Allowed range of each configurable rule (inclusive)
+pub const ROUNDS_TO_WIN_RANGE: (i32, i32) = (1, 25);
+pub const DICE_COUNT_RANGE: (i32, i32) = (1, 3);
+pub const DICE_SIDES_RANGE: (i32, i32) = (4, 20);
+pub const TIEBREAKER_ROLL_OFFS_RANGE: (i32, i32) = (3, 7);
+
+/// How tied highest rollers settle a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tiebreaker {
+    /// The tied players reroll until one of them is highest
+    SuddenDeath,
+    /// The tied players play roll-offs until one wins the majority
+    BestOf,
+}
+
+/// Rules a Bigger Dice room is played with, chosen at room creation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BiggerDiceRules {
+    /// Points (won rounds) needed to win the game
+    pub rounds_to_win: i32,
+    /// Dice rolled per turn; a roll is their total
+    pub dice_count: i32,
+    pub dice_sides: i32,
+    pub tiebreaker: Tiebreaker,
+    /// Roll-offs of a best-of tiebreaker (odd; unused for sudden death)
+    pub tiebreaker_roll_offs: i32,
+}
+
+impl Default for BiggerDiceRules {
+    fn default() -> Self {
+        Self {
+            rounds_to_win: WIN_SCORE,
+            dice_count: 1,
+            dice_sides: 6,
+            tiebreaker: Tiebreaker::SuddenDeath,
+            tiebreaker_roll_offs: 3,
+        }
+    }
+}
+
+/// Rules requested for a new room; unset values keep the defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct BiggerDiceRuleSettings {
+    pub rounds_to_win: Option<i32>,
+    pub dice_count: Option<i32>,
+    pub dice_sides: Option<i32>,
+    pub tiebreaker: Option<Tiebreaker>,
+    pub tiebreaker_roll_offs: Option<i32>,
+}
+
+impl BiggerDiceRuleSettings {
+    /// Whether no rule was requested
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Values set here win over `base`
+    pub fn or(self, base: BiggerDiceRuleSettings) -> BiggerDiceRuleSettings {
+        BiggerDiceRuleSettings {
+            rounds_to_win: self.rounds_to_win.or(base.rounds_to_win),
+            dice_count: self.dice_count.or(base.dice_count),
+            dice_sides: self.dice_sides.or(base.dice_sides),
+            tiebreaker: self.tiebreaker.or(base.tiebreaker),
+            tiebreaker_roll_offs: self.tiebreaker_roll_offs.or(base.tiebreaker_roll_offs),
+        }
+    }
+}
+
+impl BiggerDiceRules {
+    /// Fill unset rules with the defaults and validate the result
+    pub fn resolve(settings: BiggerDiceRuleSettings) -> Result<Self, RoomConfigError> {
+        let defaults = Self::default();
+        let rules = Self {
+            rounds_to_win: settings.rounds_to_win.unwrap_or(defaults.rounds_to_win),
+            dice_count: settings.dice_count.unwrap_or(defaults.dice_count),
+            dice_sides: settings.dice_sides.unwrap_or(defaults.dice_sides),
+            tiebreaker: settings.tiebreaker.unwrap_or(defaults.tiebreaker),
+            tiebreaker_roll_offs: settings
+                .tiebreaker_roll_offs
+                .unwrap_or(defaults.tiebreaker_roll_offs),
+        };
+
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn validate(&self) -> Result<(), RoomConfigError> {
+        let checks = [
+            ("rounds_to_win", self.rounds_to_win, ROUNDS_TO_WIN_RANGE),
+            ("dice_count", self.dice_count, DICE_COUNT_RANGE),
+            ("dice_sides", self.dice_sides, DICE_SIDES_RANGE),
+            (
+                "tiebreaker_roll_offs",
+                self.tiebreaker_roll_offs,
+                TIEBREAKER_ROLL_OFFS_RANGE,
+            ),
+        ];
+        for (rule, value, (min, max)) in checks {
+            if !(min..=max).contains(&value) {
+                return Err(RoomConfigError::RuleRange { rule, min, max });
+            }
+        }
+
+        if self.tiebreaker_roll_offs % 2 == 0 {
+            return Err(RoomConfigError::EvenRollOffs);
+        }
+        Ok(())
+    }
+
+    /// Roll-offs a player must win to take a best-of tiebreaker
+    pub fn roll_offs_to_win(&self) -> i32 {
+        self.tiebreaker_roll_offs / 2 + 1
+    }
+}
+
+/// Room settings Bigger Dice can be played with (no turn timer)
+pub const ROOM_CONSTRAINTS: RoomConstraints = RoomConstraints {
+    min_players: 2,
+    max_players: 10,
+    default_players: 2,
+    max_spectators: 10,
+    turn_timer: None,
+};
+
 /// Maximum number of tiebreaker iterations to prevent infinite loops
 const MAX_TIEBREAKER_ITERATIONS: i32 = 100;
 
@@ -41,6 +170,13 @@ pub struct BiggerDiceRoundState {
     pub tiebreaker_iteration: i32,
     /// Last completed round's rolls for display (player_id -> roll)
     pub last_round_rolls: HashMap<i64, i32>,
+    /// Players the current tiebreaker started with (best-of roll-offs are
+    /// replayed with all of them)
+    #[serde(default)]
+    pub tiebreaker_players: Vec<i64>,
+    /// Roll-offs won in the current best-of tiebreaker: player_id -> wins
+    #[serde(default)]
+    pub roll_off_wins: HashMap<i64, i32>,
 }
 
 impl Default for BiggerDiceRoundState {
@@ -53,6 +189,8 @@ impl Default for BiggerDiceRoundState {
             is_tiebreaker: false,
             tiebreaker_iteration: 0,
             last_round_rolls: HashMap::new(),
+            tiebreaker_players: Vec::new(),
+            roll_off_wins: HashMap::new(),
         }
     }
 }
@@ -65,6 +203,8 @@ impl BiggerDiceRoundState {
         self.current_roller_index = 0;
         self.is_tiebreaker = false;
         self.tiebreaker_iteration = 0;
+        self.tiebreaker_players.clear();
+        self.roll_off_wins.clear();
     }
 
     /// Start a new round (after point is awarded)
@@ -76,10 +216,16 @@ impl BiggerDiceRoundState {
         self.current_roller_index = 0;
         self.is_tiebreaker = false;
         self.tiebreaker_iteration = 0;
+        self.tiebreaker_players.clear();
+        self.roll_off_wins.clear();
     }
 
     /// Start a tiebreaker with only the tied players
     pub fn start_tiebreaker(&mut self, tied_players: Vec<i64>) {
+        if !self.is_tiebreaker {
+            self.tiebreaker_players = tied_players.clone();
+            self.roll_off_wins.clear();
+        }
         self.last_round_rolls = self.current_round_rolls.clone();
         self.current_round_rolls.clear();
         self.active_rollers = tied_players;
@@ -104,16 +250,25 @@ impl BiggerDiceRoundState {
         self.current_roller_index >= self.active_rollers.len()
     }
 
+    /// Count a won best-of roll-off; returns the player's roll-off wins
+    pub fn record_roll_off_win(&mut self, player_id: i64) -> i32 {
+        let wins = self.roll_off_wins.entry(player_id).or_insert(0);
+        *wins += 1;
+        *wins
+    }
+
     /// Check if tiebreaker safety limit exceeded
     pub fn tiebreaker_limit_exceeded(&self) -> bool {
         self.tiebreaker_iteration >= MAX_TIEBREAKER_ITERATIONS
     }
 }
 
-/// Roll a die (1-6)
-pub fn roll_die() -> i32 {
+/// Roll the room's dice; returns their total
+pub fn roll_dice(rules: &BiggerDiceRules) -> i32 {
     let mut rng = rand::thread_rng();
-    rng.gen_range(1..=6)
+    (0..rules.dice_count)
+        .map(|_| rng.gen_range(1..=rules.dice_sides))
+        .sum()
 }
 
 /// Find players with the highest roll from a set of rolls
@@ -161,8 +316,8 @@ pub fn process_roll(
         None => return (events, false),
     };
 
-    // Roll the die
-    let roll = roll_die();
+    // Roll the room's dice
+    let roll = roll_dice(&room.dice_rules.unwrap_or_default());
 
     // Record the roll
     round_state.record_roll(player_id, roll);
@@ -205,6 +360,7 @@ pub fn process_roll(
                 room_id: room.room_id.clone(),
                 current_turn: next_roller,
                 turn_number: room.turn_number,
+                state_checksum: None,
             });
         }
     }
@@ -219,6 +375,7 @@ fn evaluate_round(
     round_state: &mut BiggerDiceRoundState,
 ) -> (Vec<GameEvent>, bool) {
     let mut events = Vec::new();
+    let rules = room.dice_rules.unwrap_or_default();
 
     // Find the highest rollers (preserving original roll order for tiebreakers)
     let (highest_roll, highest_players) = find_highest_rollers(
@@ -241,9 +398,19 @@ fn evaluate_round(
         None
     };
 
-    // If there's a single winner, award the point BEFORE creating the event
+    // A best-of roll-off only counts toward the tiebreaker; the point goes to
+    // the first player to win the majority of roll-offs
+    let best_of = round_state.is_tiebreaker && rules.tiebreaker == Tiebreaker::BestOf;
+    let point_winner = match winner_id {
+        Some(winner) if best_of => {
+            (round_state.record_roll_off_win(winner) >= rules.roll_offs_to_win()).then_some(winner)
+        }
+        winner => winner,
+    };
+
+    // If there's a point winner, award the point BEFORE creating the event
     // This ensures the scores in the event reflect the updated state
-    if let Some(winner) = winner_id {
+    if let Some(winner) = point_winner {
         if let Some(p) = room.get_player_mut(winner) {
             p.score += 1;
         }
@@ -273,10 +440,11 @@ fn evaluate_round(
         highest_players = ?highest_players,
         is_tie = %is_tie,
         is_tiebreaker = %round_state.is_tiebreaker,
+        roll_off_wins = ?round_state.roll_off_wins,
         "Bigger Dice: Round evaluated"
     );
 
-    if let Some(winner) = winner_id {
+    if let Some(winner) = point_winner {
         // Point was already awarded before creating the round result event
         let winner_score = room.get_player(winner).map(|p| p.score).unwrap_or(0);
 
@@ -288,7 +456,7 @@ fn evaluate_round(
         );
 
         // Check for game end
-        if winner_score >= WIN_SCORE {
+        if winner_score >= rules.rounds_to_win {
             // Game over
             room.status = RoomStatus::Finished;
             room.winner_id = Some(winner);
@@ -309,6 +477,7 @@ fn evaluate_round(
                 winner_id: winner,
                 winner_username,
                 final_scores,
+                started_at: room.started_at,
             });
 
             return (events, true);
@@ -327,10 +496,12 @@ fn evaluate_round(
                 room_id: room.room_id.clone(),
                 current_turn: first_roller,
                 turn_number: room.turn_number,
+                state_checksum: None,
             });
         }
     } else {
-        // Tie - start tiebreaker with only the tied players
+        // No point yet - tied highest rollers, or a best-of tiebreaker that
+        // is still undecided
         if round_state.tiebreaker_limit_exceeded() {
             // Safety limit exceeded - pick random winner from tied players
             let random_winner = highest_players[0]; // Just pick first one
@@ -356,17 +527,25 @@ fn evaluate_round(
                     room_id: room.room_id.clone(),
                     current_turn: first_roller,
                     turn_number: room.turn_number,
+                    state_checksum: None,
                 });
             }
         } else {
-            // Start tiebreaker
+            // Start tiebreaker; best-of roll-offs are played by everyone the
+            // tiebreaker started with, sudden death only by the tied players
+            let tied_players = if best_of {
+                round_state.tiebreaker_players.clone()
+            } else {
+                highest_players
+            };
+
             events.push(GameEvent::BiggerDiceTiebreakerStarted {
                 room_id: room.room_id.clone(),
-                tied_players: highest_players.clone(),
+                tied_players: tied_players.clone(),
                 tied_roll: highest_roll,
             });
 
-            round_state.start_tiebreaker(highest_players);
+            round_state.start_tiebreaker(tied_players);
 
             // First tied player rolls
             if let Some(first_roller) = round_state.current_roller() {
@@ -377,6 +556,7 @@ fn evaluate_round(
                     room_id: room.room_id.clone(),
                     current_turn: first_roller,
                     turn_number: room.turn_number,
+                    state_checksum: None,
                 });
             }
         }
@@ -423,6 +603,7 @@ pub fn start_game(room: &mut GameRoom) -> (Vec<GameEvent>, BiggerDiceRoundState)
         room_id: room.room_id.clone(),
         current_turn: first_player,
         turn_number: 1,
+        state_checksum: None,
     });
 
     info!(
@@ -453,3 +634,107 @@ pub fn create_turn_record(
         timestamp: Utc::now(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{GamePlayer, GameType};
+
+    fn room(rules: BiggerDiceRules) -> GameRoom {
+        let mut room = GameRoom::new("room-1", "Room 1", GameType::BiggerDice, 1);
+        room.players = [1, 2]
+            .into_iter()
+            .map(|user_id| GamePlayer {
+                user_id,
+                username: format!("user{}", user_id),
+                avatar_id: None,
+                score: 0,
+                is_ready: true,
+                joined_at: Utc::now(),
+                stats: None,
+            })
+            .collect();
+        room.dice_rules = Some(rules);
+        room
+    }
+
+    /// Play out the active rollers' turn with fixed rolls
+    fn play(room: &mut GameRoom, state: &mut BiggerDiceRoundState, rolls: &[(i64, i32)]) -> bool {
+        for &(player_id, roll) in rolls {
+            state.record_roll(player_id, roll);
+        }
+        evaluate_round(room, state).1
+    }
+
+    fn scores(room: &GameRoom) -> Vec<i32> {
+        room.players.iter().map(|p| p.score).collect()
+    }
+
+    #[test]
+    fn test_rolls_use_the_configured_dice() {
+        let rules = BiggerDiceRules {
+            dice_count: 3,
+            dice_sides: 4,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            assert!((3..=12).contains(&roll_dice(&rules)));
+        }
+    }
+
+    #[test]
+    fn test_game_ends_at_the_configured_rounds() {
+        let mut room = room(BiggerDiceRules {
+            rounds_to_win: 2,
+            ..Default::default()
+        });
+        let (_, mut state) = start_game(&mut room);
+
+        assert!(!play(&mut room, &mut state, &[(1, 6), (2, 1)]));
+        assert!(play(&mut room, &mut state, &[(1, 6), (2, 1)]));
+        assert_eq!(room.winner_id, Some(1));
+    }
+
+    #[test]
+    fn test_sudden_death_awards_the_first_clear_tiebreaker() {
+        let mut room = room(BiggerDiceRules::default());
+        let (_, mut state) = start_game(&mut room);
+
+        play(&mut room, &mut state, &[(1, 5), (2, 5)]);
+        assert!(state.is_tiebreaker);
+        play(&mut room, &mut state, &[(1, 2), (2, 3)]);
+        assert_eq!(scores(&room), vec![0, 1]);
+        assert!(!state.is_tiebreaker);
+    }
+
+    #[test]
+    fn test_best_of_tiebreaker_needs_the_majority_of_roll_offs() {
+        let mut room = room(BiggerDiceRules {
+            tiebreaker: Tiebreaker::BestOf,
+            tiebreaker_roll_offs: 3,
+            ..Default::default()
+        });
+        let (_, mut state) = start_game(&mut room);
+
+        play(&mut room, &mut state, &[(1, 5), (2, 5)]);
+        assert_eq!(state.tiebreaker_players, vec![1, 2]);
+
+        // Won roll-off, still undecided
+        play(&mut room, &mut state, &[(1, 6), (2, 2)]);
+        assert_eq!(scores(&room), vec![0, 0]);
+        assert_eq!(state.active_rollers, vec![1, 2]);
+
+        // Tied roll-offs are replayed and don't count
+        play(&mut room, &mut state, &[(1, 3), (2, 3)]);
+        assert_eq!(state.roll_off_wins.get(&1), Some(&1));
+        assert_eq!(state.roll_off_wins.get(&2), None);
+
+        play(&mut room, &mut state, &[(1, 1), (2, 4)]);
+        assert_eq!(scores(&room), vec![0, 0]);
+
+        play(&mut room, &mut state, &[(1, 6), (2, 4)]);
+        assert_eq!(scores(&room), vec![1, 0]);
+        assert!(!state.is_tiebreaker);
+        assert!(state.roll_off_wins.is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Bot orchestrator
+//!
+//! Plays the bot seat of `vs_bot` rooms. After every command the game handler
+//! asks `bots::next_action` what the bot should do and hands the result here;
+//! the action is published as a regular `games.command.*` envelope (actor = the
+//! bot account) to this region's games commands topic after a short "thinking"
+//! delay, so bot moves go through the same validation, persistence and events
+//! as a human's.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::bots::BotAction;
+use super::types::{Actor, Audience, EventEnvelope, GameRoom};
+use crate::config::GamesConfig;
+use crate::events::producer::EventProducer;
+
+/// Account the bot plays as (GAME_BOT_USER_ID / GAME_BOT_USERNAME)
+#[derive(Debug, Clone)]
+pub struct BotIdentity {
+    pub user_id: i64,
+    pub username: String,
+}
+
+pub struct BotOrchestrator {
+    producer: Option<Arc<EventProducer>>,
+    identity: Option<BotIdentity>,
+}
+
+impl BotOrchestrator {
+    pub fn new(producer: Option<Arc<EventProducer>>) -> Self {
+        let identity = GamesConfig::bot_user_id().map(|user_id| BotIdentity {
+            user_id,
+            username: GamesConfig::bot_username().to_string(),
+        });
+
+        Self { producer, identity }
+    }
+
+    /// Whether bot rooms can be created (a bot account is configured)
+    pub fn is_enabled(&self) -> bool {
+        self.identity.is_some() && self.producer.is_some()
+    }
+
+    pub fn bot_user_id(&self) -> Option<i64> {
+        self.identity.as_ref().map(|bot| bot.user_id)
+    }
+
+    /// Publish the bot's next command for `room` after the configured delay
+    pub fn dispatch(&self, room: &GameRoom, action: BotAction) {
+        let (Some(producer), Some(bot)) = (self.producer.clone(), self.identity.clone()) else {
+            return;
+        };
+
+        let (command, payload) = match &action {
+            BotAction::Join => (
+                "join_room",
+                serde_json::json!({ "room_name": room.room_name }),
+            ),
+            BotAction::Ready => ("ready", serde_json::json!({ "room_id": room.room_id })),
+            BotAction::Move(bot_move) => bot_move.command(&room.room_id),
+        };
+
+        let envelope = EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: format!("games.command.{}", command),
+            timestamp: Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: bot.user_id,
+                username: bot.username,
+                socket_id: String::new(),
+                roles: vec!["bot".to_string()],
+            },
+            audience: Audience::room(room.room_id.clone()),
+            payload,
+        };
+
+        let bytes = match serde_json::to_vec(&envelope) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize bot command");
+                return;
+            }
+        };
+
+        let room_id = room.room_id.clone();
+        let game_type = room.game_type.as_str();
+        let delay = Duration::from_millis(GamesConfig::bot_move_delay_ms());
+
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+
+            match producer
+                .send_games_command(Some(game_type), &room_id, &bytes)
+                .await
+            {
+                Ok(()) => info!(room_id = %room_id, command = %command, "Bot command published"),
+                Err(e) => warn!(room_id = %room_id, command = %command, error = %e, "Failed to publish bot command"),
+            }
+        });
+    }
+}
@@ -0,0 +1,339 @@
+//! Bot opponents
+//!
+//! Rooms created with `vs_bot` get a bot player so a single user can practice.
+//! Every game engine has a `BotPlayer` that picks a move from the engine's own
+//! state; `next_action` decides what the bot has to do next in a room (join,
+//! ready up, move). The bot orchestrator turns those actions into ordinary
+//! `games.command.*` envelopes, so the rest of the pipeline treats the bot like
+//! any other player.
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use super::bigger_dice::BiggerDiceRoundState;
+use super::tic_tac_toe::TicTacToeMatchState;
+use super::types::{GameRoom, GameType, RoomStatus};
+
+/// How well a bot plays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotDifficulty {
+    /// Random legal moves
+    Easy,
+    /// Takes wins and blocks losses, otherwise random
+    #[default]
+    Medium,
+    /// Plays perfectly where the game allows it
+    Hard,
+}
+
+impl BotDifficulty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BotDifficulty::Easy => "easy",
+            BotDifficulty::Medium => "medium",
+            BotDifficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "easy" => Some(BotDifficulty::Easy),
+            "medium" => Some(BotDifficulty::Medium),
+            "hard" => Some(BotDifficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// A move a bot submits as a game command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotMove {
+    BiggerDiceRoll,
+    TicTacToeMove { position: u8 },
+}
+
+impl BotMove {
+    /// Command name (without the `games.command.` prefix) and payload
+    pub fn command(&self, room_id: &str) -> (&'static str, serde_json::Value) {
+        match self {
+            BotMove::BiggerDiceRoll => (
+                "bigger_dice.roll",
+                serde_json::json!({ "room_id": room_id }),
+            ),
+            BotMove::TicTacToeMove { position } => (
+                "tic_tac_toe.move",
+                serde_json::json!({ "room_id": room_id, "position": position }),
+            ),
+        }
+    }
+}
+
+/// Move selection for one game engine
+pub trait BotPlayer {
+    /// Engine state the bot decides on
+    type State;
+
+    /// Pick the bot's next move, or None when it is not the bot's turn
+    fn choose_move(&self, state: &Self::State, bot_id: i64) -> Option<BotMove>;
+}
+
+/// Bigger Dice is pure luck: the bot rolls whenever it is its turn
+pub struct BiggerDiceBot;
+
+impl BotPlayer for BiggerDiceBot {
+    type State = BiggerDiceRoundState;
+
+    fn choose_move(&self, state: &Self::State, bot_id: i64) -> Option<BotMove> {
+        (state.current_roller() == Some(bot_id)).then_some(BotMove::BiggerDiceRoll)
+    }
+}
+
+pub struct TicTacToeBot {
+    pub difficulty: BotDifficulty,
+}
+
+impl BotPlayer for TicTacToeBot {
+    type State = TicTacToeMatchState;
+
+    fn choose_move(&self, state: &Self::State, bot_id: i64) -> Option<BotMove> {
+        if state.current_turn != bot_id || state.is_paused {
+            return None;
+        }
+
+        let mark = state.get_player_mark(bot_id)?;
+        let opponent = if mark == 'X' { 'O' } else { 'X' };
+        let board = state.board;
+        let empty: Vec<usize> = (0..9).filter(|&i| board[i].is_none()).collect();
+
+        let position = match self.difficulty {
+            BotDifficulty::Easy => empty.choose(&mut rand::thread_rng()).copied(),
+            BotDifficulty::Medium => winning_cell(&board, mark)
+                .or_else(|| winning_cell(&board, opponent))
+                .or_else(|| empty.choose(&mut rand::thread_rng()).copied()),
+            BotDifficulty::Hard => best_cell(&board, mark, opponent),
+        }?;
+
+        Some(BotMove::TicTacToeMove {
+            position: position as u8,
+        })
+    }
+}
+
+type Board = [Option<char>; 9];
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+fn winner(board: &Board) -> Option<char> {
+    LINES.iter().find_map(|&[a, b, c]| match board[a] {
+        Some(mark) if board[b] == Some(mark) && board[c] == Some(mark) => Some(mark),
+        _ => None,
+    })
+}
+
+/// Empty cell that completes a line for `mark`
+fn winning_cell(board: &Board, mark: char) -> Option<usize> {
+    (0..9).filter(|&i| board[i].is_none()).find(|&i| {
+        let mut next = *board;
+        next[i] = Some(mark);
+        winner(&next) == Some(mark)
+    })
+}
+
+/// Minimax over the remaining cells (at most 9! positions, so no pruning needed)
+fn best_cell(board: &Board, mark: char, opponent: char) -> Option<usize> {
+    fn score(board: &mut Board, to_move: char, me: char, other: char, depth: i32) -> i32 {
+        match winner(board) {
+            Some(w) if w == me => return 10 - depth,
+            Some(_) => return depth - 10,
+            None if board.iter().all(Option::is_some) => return 0,
+            None => {}
+        }
+
+        let next = if to_move == me { other } else { me };
+        let mut best = if to_move == me { i32::MIN } else { i32::MAX };
+        for i in 0..9 {
+            if board[i].is_some() {
+                continue;
+            }
+            board[i] = Some(to_move);
+            let s = score(board, next, me, other, depth + 1);
+            board[i] = None;
+            best = if to_move == me { best.max(s) } else { best.min(s) };
+        }
+        best
+    }
+
+    let mut board = *board;
+    let empty: Vec<usize> = (0..9).filter(|&i| board[i].is_none()).collect();
+    empty
+        .into_iter()
+        .map(|i| {
+            board[i] = Some(mark);
+            let s = score(&mut board, opponent, mark, opponent, 1);
+            board[i] = None;
+            (i, s)
+        })
+        .max_by_key(|&(i, s)| (s, -(i as i32)))
+        .map(|(i, _)| i)
+}
+
+/// What the bot has to do next in a room
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotAction {
+    /// Join the room's lobby
+    Join,
+    /// Ready up after the host selected the bot
+    Ready,
+    Move(BotMove),
+}
+
+/// Engine state of a running game, as far as bots need it
+pub enum BotGameState<'a> {
+    BiggerDice(&'a BiggerDiceRoundState),
+    TicTacToe(&'a TicTacToeMatchState),
+}
+
+/// Decide the bot's next action in a `vs_bot` room (None = nothing to do)
+pub fn next_action(room: &GameRoom, bot_id: i64, state: Option<BotGameState<'_>>) -> Option<BotAction> {
+    let difficulty = room.bot_difficulty?;
+
+    match room.status {
+        RoomStatus::Waiting if room.is_banned(bot_id) => None,
+        RoomStatus::Waiting if !room.is_in_lobby(bot_id) => Some(BotAction::Join),
+        RoomStatus::Waiting => {
+            let ready = room.lobby.iter().any(|p| p.user_id == bot_id && p.is_ready);
+            (room.is_selected_player(bot_id) && !ready).then_some(BotAction::Ready)
+        }
+        RoomStatus::InProgress if room.is_player(bot_id) => {
+            let next = match (&room.game_type, state?) {
+                (GameType::BiggerDice, BotGameState::BiggerDice(state)) => {
+                    BiggerDiceBot.choose_move(state, bot_id)
+                }
+                (GameType::TicTacToe, BotGameState::TicTacToe(state)) => {
+                    TicTacToeBot { difficulty }.choose_move(state, bot_id)
+                }
+                _ => None,
+            };
+            next.map(BotAction::Move)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::GamePlayer;
+    use chrono::Utc;
+
+    const BOT: i64 = 99;
+    const HUMAN: i64 = 1;
+
+    fn ttt_state(board: &str, bot_mark: char) -> TicTacToeMatchState {
+        let (x, o) = if bot_mark == 'X' { (BOT, HUMAN) } else { (HUMAN, BOT) };
+        let mut state = TicTacToeMatchState::initialize(x, o);
+        state.player_x_id = x;
+        state.player_o_id = o;
+        state.current_turn = BOT;
+        for (i, c) in board.chars().enumerate() {
+            state.board[i] = match c {
+                'X' | 'O' => Some(c),
+                _ => None,
+            };
+        }
+        state
+    }
+
+    fn bot_move(difficulty: BotDifficulty, state: &TicTacToeMatchState) -> Option<u8> {
+        match (TicTacToeBot { difficulty }).choose_move(state, BOT) {
+            Some(BotMove::TicTacToeMove { position }) => Some(position),
+            _ => None,
+        }
+    }
+
+    fn player(user_id: i64, is_ready: bool) -> GamePlayer {
+        GamePlayer {
+            user_id,
+            username: format!("user{}", user_id),
+            avatar_id: None,
+            score: 0,
+            is_ready,
+            joined_at: Utc::now(),
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn medium_and_hard_take_the_win_over_blocking() {
+        // Bot is X and can complete the top row; O threatens the middle row
+        let state = ttt_state("XX.OO....", 'X');
+
+        assert_eq!(bot_move(BotDifficulty::Medium, &state), Some(2));
+        assert_eq!(bot_move(BotDifficulty::Hard, &state), Some(2));
+    }
+
+    #[test]
+    fn medium_and_hard_block_a_losing_line() {
+        let state = ttt_state("OO..X....", 'X');
+
+        assert_eq!(bot_move(BotDifficulty::Medium, &state), Some(2));
+        assert_eq!(bot_move(BotDifficulty::Hard, &state), Some(2));
+    }
+
+    #[test]
+    fn easy_plays_a_legal_cell_and_nobody_moves_out_of_turn() {
+        let mut state = ttt_state("XOXOXO...", 'X');
+        let position = bot_move(BotDifficulty::Easy, &state).expect("move");
+        assert!(state.board[position as usize].is_none());
+
+        state.current_turn = HUMAN;
+        assert_eq!(bot_move(BotDifficulty::Hard, &state), None);
+    }
+
+    #[test]
+    fn next_action_walks_the_lobby_flow() {
+        let mut room = GameRoom::new("room-1", "Room 1", GameType::BiggerDice, HUMAN);
+        room.lobby.push(player(HUMAN, false));
+        assert_eq!(next_action(&room, BOT, None), None, "not a vs_bot room");
+
+        room.bot_difficulty = Some(BotDifficulty::Easy);
+        assert_eq!(next_action(&room, BOT, None), Some(BotAction::Join));
+
+        room.lobby.push(player(BOT, false));
+        assert_eq!(next_action(&room, BOT, None), None, "waits to be selected");
+
+        room.selected_players = vec![HUMAN, BOT];
+        assert_eq!(next_action(&room, BOT, None), Some(BotAction::Ready));
+
+        room.lobby[1].is_ready = true;
+        assert_eq!(next_action(&room, BOT, None), None);
+    }
+
+    #[test]
+    fn next_action_rolls_on_the_bots_turn() {
+        let mut room = GameRoom::new("room-1", "Room 1", GameType::BiggerDice, HUMAN);
+        room.bot_difficulty = Some(BotDifficulty::Medium);
+        room.status = RoomStatus::InProgress;
+        room.players = vec![player(HUMAN, true), player(BOT, true)];
+
+        let mut state = BiggerDiceRoundState::default();
+        state.initialize(&[BOT, HUMAN]);
+        assert_eq!(
+            next_action(&room, BOT, Some(BotGameState::BiggerDice(&state))),
+            Some(BotAction::Move(BotMove::BiggerDiceRoll))
+        );
+
+        state.record_roll(BOT, 4);
+        assert_eq!(next_action(&room, BOT, Some(BotGameState::BiggerDice(&state))), None);
+    }
+}
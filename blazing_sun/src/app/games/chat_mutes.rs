@@ -0,0 +1,209 @@
+//! Moderator chat mutes
+//!
+//! The host and the admin spectator of a room can mute a member in one chat
+//! channel (lobby, players or spectators) or in all of them
+//! (`games.command.chat_mute`), for `duration_seconds` or until they lift it
+//! with `chat_unmute`. Mutes are kept on the room (`GameRoom::chat_mutes`, so
+//! `room_state` tells clients whose input to disable) and in `game_chat_mutes`;
+//! `send_chat` refuses a muted sender before the message is saved or
+//! published. A sweep removes timed mutes once they run out and announces
+//! `chat_unmuted` with `expired: true`.
+//!
+//! Unrelated to `mute_user`, which only hides a user's messages from the user
+//! who muted them.
+
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+
+use crate::app::games::invites::is_member;
+use crate::app::games::types::{ChatChannel, GameRoom};
+
+/// Longest timed mute; longer ones are muted until lifted
+pub const MAX_MUTE_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// `game_chat_mutes.channel` of a mute covering every channel
+const ALL_CHANNELS: &str = "all";
+
+/// Why a mute cannot be set or lifted
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ChatMuteError {
+    #[error("Only the host or the admin spectator can mute chat")]
+    NotModerator,
+    #[error("You cannot mute yourself")]
+    SelfMute,
+    #[error("The host and the admin spectator cannot be muted")]
+    ModeratorTarget,
+    #[error("This user is not in the room")]
+    NotMember,
+    #[error("Unknown chat channel")]
+    InvalidChannel,
+    #[error("Mute duration must be between 1 second and 7 days")]
+    InvalidDuration,
+}
+
+impl ChatMuteError {
+    /// Error code sent to the moderator
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChatMuteError::NotModerator => "not_admin",
+            ChatMuteError::SelfMute => "cannot_mute_self",
+            ChatMuteError::ModeratorTarget => "cannot_mute_moderator",
+            ChatMuteError::NotMember => "not_in_room",
+            ChatMuteError::InvalidChannel => "invalid_channel",
+            ChatMuteError::InvalidDuration => "invalid_duration",
+        }
+    }
+}
+
+/// Whether the user moderates the room's chat
+pub fn is_moderator(room: &GameRoom, user_id: i64) -> bool {
+    room.is_admin(user_id) || room.is_admin_spectator(user_id)
+}
+
+/// Channel of a command; missing or `all` mutes every channel
+pub fn parse_channel(channel: Option<&str>) -> Result<Option<ChatChannel>, ChatMuteError> {
+    match channel {
+        None | Some(ALL_CHANNELS) => Ok(None),
+        Some(channel) => channel
+            .parse()
+            .map(Some)
+            .map_err(|_| ChatMuteError::InvalidChannel),
+    }
+}
+
+/// Check that `moderator_id` may mute or unmute `target_id`
+pub fn check_moderation(room: &GameRoom, moderator_id: i64, target_id: i64) -> Result<(), ChatMuteError> {
+    if !is_moderator(room, moderator_id) {
+        return Err(ChatMuteError::NotModerator);
+    }
+    if moderator_id == target_id {
+        return Err(ChatMuteError::SelfMute);
+    }
+    if is_moderator(room, target_id) {
+        return Err(ChatMuteError::ModeratorTarget);
+    }
+    if !is_member(room, target_id) {
+        return Err(ChatMuteError::NotMember);
+    }
+    Ok(())
+}
+
+/// End of a mute of `duration_seconds` from `now`; None mutes until lifted
+pub fn expires_at(
+    duration_seconds: Option<i64>,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, ChatMuteError> {
+    match duration_seconds {
+        None => Ok(None),
+        Some(seconds) if (1..=MAX_MUTE_SECONDS).contains(&seconds) => {
+            Ok(Some(now + Duration::seconds(seconds)))
+        }
+        Some(_) => Err(ChatMuteError::InvalidDuration),
+    }
+}
+
+/// `game_chat_mutes.channel` of a mute
+pub fn stored_channel(channel: Option<&ChatChannel>) -> String {
+    channel.map_or_else(|| ALL_CHANNELS.to_string(), ChatChannel::to_string)
+}
+
+/// Mute channel of a `game_chat_mutes.channel`
+pub fn channel_from_stored(channel: &str) -> Option<ChatChannel> {
+    channel.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{ChatMute, GamePlayer, GameType, RoomStatus};
+
+    fn player(user_id: i64) -> GamePlayer {
+        GamePlayer {
+            user_id,
+            username: format!("user{}", user_id),
+            avatar_id: None,
+            score: 0,
+            is_ready: false,
+            joined_at: Utc::now(),
+            stats: None,
+        }
+    }
+
+    fn room() -> GameRoom {
+        let mut room = GameRoom::new("r-1", "Friday dice", GameType::BiggerDice, 1);
+        room.status = RoomStatus::InProgress;
+        room.players.push(player(2));
+        room.add_spectator(3, "user3", None);
+        room.add_spectator(4, "user4", None);
+        room.admin_spectator_id = Some(4);
+        room
+    }
+
+    #[test]
+    fn host_and_admin_spectator_moderate_members() {
+        let room = room();
+        assert_eq!(check_moderation(&room, 1, 2), Ok(()));
+        assert_eq!(check_moderation(&room, 4, 3), Ok(()));
+        assert_eq!(check_moderation(&room, 2, 3), Err(ChatMuteError::NotModerator));
+        assert_eq!(check_moderation(&room, 1, 1), Err(ChatMuteError::SelfMute));
+        assert_eq!(check_moderation(&room, 4, 1), Err(ChatMuteError::ModeratorTarget));
+        assert_eq!(check_moderation(&room, 1, 9), Err(ChatMuteError::NotMember));
+    }
+
+    #[test]
+    fn channels_and_durations_are_validated() {
+        assert_eq!(parse_channel(None), Ok(None));
+        assert_eq!(parse_channel(Some("all")), Ok(None));
+        assert_eq!(parse_channel(Some("spectators")), Ok(Some(ChatChannel::Spectators)));
+        assert_eq!(parse_channel(Some("team")), Err(ChatMuteError::InvalidChannel));
+
+        let now = Utc::now();
+        assert_eq!(expires_at(None, now), Ok(None));
+        assert_eq!(expires_at(Some(60), now), Ok(Some(now + Duration::seconds(60))));
+        assert_eq!(expires_at(Some(0), now), Err(ChatMuteError::InvalidDuration));
+        assert_eq!(
+            expires_at(Some(MAX_MUTE_SECONDS + 1), now),
+            Err(ChatMuteError::InvalidDuration)
+        );
+
+        assert_eq!(stored_channel(None), "all");
+        assert_eq!(channel_from_stored(&stored_channel(Some(&ChatChannel::Players))), Some(ChatChannel::Players));
+        assert_eq!(channel_from_stored("all"), None);
+    }
+
+    #[test]
+    fn mutes_cover_their_channel_until_they_expire() {
+        let mut room = room();
+        let now = Utc::now();
+        room.set_chat_mute(ChatMute {
+            user_id: 2,
+            channel: Some(ChatChannel::Players),
+            muted_by: 1,
+            expires_at: Some(now + Duration::minutes(5)),
+        });
+        room.set_chat_mute(ChatMute {
+            user_id: 3,
+            channel: None,
+            muted_by: 4,
+            expires_at: None,
+        });
+
+        assert!(room.chat_mute(2, &ChatChannel::Players, now).is_some());
+        assert!(room.chat_mute(2, &ChatChannel::Lobby, now).is_none());
+        assert!(room.chat_mute(2, &ChatChannel::Players, now + Duration::minutes(6)).is_none());
+        assert!(room.chat_mute(3, &ChatChannel::Spectators, now + Duration::days(30)).is_some());
+
+        // Muting the same channel again replaces the mute
+        room.set_chat_mute(ChatMute {
+            user_id: 2,
+            channel: Some(ChatChannel::Players),
+            muted_by: 1,
+            expires_at: None,
+        });
+        assert_eq!(room.chat_mutes.len(), 2);
+
+        assert!(room.remove_chat_mute(3, Some(&ChatChannel::Spectators)).is_none());
+        assert!(room.remove_chat_mute(3, None).is_some());
+        assert!(room.chat_mute(3, &ChatChannel::Spectators, now).is_none());
+    }
+}
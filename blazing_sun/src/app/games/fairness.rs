@@ -0,0 +1,139 @@
+//! Dice fairness summary
+//!
+//! Summarizes observed die rolls so players can check that the dice behave like a
+//! fair six-sided die. A chi-square goodness-of-fit test against the uniform
+//! distribution is used; with fewer than 5 expected rolls per face the test is
+//! not meaningful and no verdict is given.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Number of faces on the die used by Bigger Dice
+pub const DIE_FACES: i32 = 6;
+
+/// Chi-square critical value for 5 degrees of freedom at p = 0.01
+const CHI_SQUARE_CRITICAL: f64 = 15.086;
+
+/// Minimum expected count per face for the chi-square test to apply
+const MIN_EXPECTED_PER_FACE: f64 = 5.0;
+
+/// Observed count for one face
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FaceCount {
+    pub face: i32,
+    pub count: i64,
+    /// Share of all rolls, in percent
+    pub share_pct: f64,
+}
+
+/// Distribution of observed rolls compared to a fair die
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiceDistribution {
+    pub faces: Vec<FaceCount>,
+    pub total_rolls: i64,
+    /// Expected share of each face for a fair die, in percent
+    pub expected_share_pct: f64,
+    /// Largest absolute difference between observed and expected share, in percent points
+    pub max_deviation_pct: f64,
+    pub chi_square: f64,
+    /// `None` while the sample is too small to judge
+    pub consistent_with_fair_die: Option<bool>,
+}
+
+impl DiceDistribution {
+    /// Build the summary from roll counts keyed by face (values outside 1..=6 are ignored)
+    pub fn from_counts(counts: &HashMap<i32, i64>) -> Self {
+        let faces: Vec<(i32, i64)> = (1..=DIE_FACES)
+            .map(|face| (face, counts.get(&face).copied().unwrap_or(0)))
+            .collect();
+        let total_rolls: i64 = faces.iter().map(|(_, count)| count).sum();
+
+        let expected_share_pct = 100.0 / DIE_FACES as f64;
+        let expected = total_rolls as f64 / DIE_FACES as f64;
+
+        let share = |count: i64| {
+            if total_rolls > 0 {
+                count as f64 * 100.0 / total_rolls as f64
+            } else {
+                0.0
+            }
+        };
+
+        let chi_square = if expected > 0.0 {
+            faces
+                .iter()
+                .map(|(_, count)| (*count as f64 - expected).powi(2) / expected)
+                .sum()
+        } else {
+            0.0
+        };
+
+        let max_deviation_pct = if total_rolls > 0 {
+            faces
+                .iter()
+                .map(|(_, count)| (share(*count) - expected_share_pct).abs())
+                .fold(0.0, f64::max)
+        } else {
+            0.0
+        };
+
+        let consistent_with_fair_die =
+            (expected >= MIN_EXPECTED_PER_FACE).then_some(chi_square <= CHI_SQUARE_CRITICAL);
+
+        Self {
+            faces: faces
+                .into_iter()
+                .map(|(face, count)| FaceCount {
+                    face,
+                    count,
+                    share_pct: share(count),
+                })
+                .collect(),
+            total_rolls,
+            expected_share_pct,
+            max_deviation_pct,
+            chi_square,
+            consistent_with_fair_die,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(values: [i64; 6]) -> HashMap<i32, i64> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i as i32 + 1, *count))
+            .collect()
+    }
+
+    #[test]
+    fn test_uniform_rolls_are_fair() {
+        let summary = DiceDistribution::from_counts(&counts([100, 100, 100, 100, 100, 100]));
+        assert_eq!(summary.total_rolls, 600);
+        assert_eq!(summary.chi_square, 0.0);
+        assert_eq!(summary.max_deviation_pct, 0.0);
+        assert_eq!(summary.consistent_with_fair_die, Some(true));
+    }
+
+    #[test]
+    fn test_skewed_rolls_are_flagged() {
+        let summary = DiceDistribution::from_counts(&counts([50, 50, 50, 50, 50, 250]));
+        assert!(summary.chi_square > CHI_SQUARE_CRITICAL);
+        assert_eq!(summary.consistent_with_fair_die, Some(false));
+        assert_eq!(summary.faces[5].count, 250);
+    }
+
+    #[test]
+    fn test_small_samples_have_no_verdict() {
+        let summary = DiceDistribution::from_counts(&counts([1, 2, 0, 3, 1, 2]));
+        assert_eq!(summary.consistent_with_fair_die, None);
+
+        let empty = DiceDistribution::from_counts(&HashMap::new());
+        assert_eq!(empty.total_rolls, 0);
+        assert_eq!(empty.consistent_with_fair_die, None);
+    }
+}
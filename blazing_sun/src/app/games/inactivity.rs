@@ -0,0 +1,198 @@
+//! Room inactivity tracking
+//!
+//! Every command for a room pushes its closing deadline to `now + timeout`.
+//! A periodic sweep asks the tracker what is due: rooms inside the warning
+//! window get one `room_inactivity_warning`, and rooms whose deadline passed
+//! after that warning are closed. A room is never closed without a warning
+//! first; when the sweep only notices a room after its deadline (e.g. after a
+//! restart) the deadline is moved to the end of a fresh warning window.
+//!
+//! The host can extend a room, which adds the extension to its deadline.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// What a sweep has to do for a room
+#[derive(Debug, Clone, PartialEq)]
+pub enum InactivityAction {
+    /// Tell the room it closes at `closes_at` unless someone acts
+    Warn {
+        room_id: String,
+        closes_at: DateTime<Utc>,
+    },
+    /// Close the room
+    Close { room_id: String },
+}
+
+#[derive(Debug, Clone)]
+struct RoomActivity {
+    deadline: DateTime<Utc>,
+    warned: bool,
+}
+
+/// Closing deadlines of the rooms handled by this instance
+#[derive(Debug)]
+pub struct InactivityTracker {
+    timeout: Duration,
+    warning: Duration,
+    extension: Duration,
+    rooms: HashMap<String, RoomActivity>,
+}
+
+impl InactivityTracker {
+    pub fn new(timeout: Duration, warning: Duration, extension: Duration) -> Self {
+        Self {
+            timeout,
+            warning: warning.min(timeout),
+            extension,
+            rooms: HashMap::new(),
+        }
+    }
+
+    /// Record activity in a room
+    pub fn touch(&mut self, room_id: &str, now: DateTime<Utc>) {
+        self.track(room_id, now + self.timeout);
+    }
+
+    /// Start tracking a room whose deadline is already known (e.g. derived
+    /// from its last update); never moves an existing deadline back
+    pub fn track(&mut self, room_id: &str, deadline: DateTime<Utc>) {
+        match self.rooms.get_mut(room_id) {
+            Some(activity) if activity.deadline >= deadline => {}
+            Some(activity) => {
+                activity.deadline = deadline;
+                activity.warned = false;
+            }
+            None => {
+                self.rooms.insert(
+                    room_id.to_string(),
+                    RoomActivity {
+                        deadline,
+                        warned: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Add the extension to a room's deadline, at most `timeout + extension`
+    /// from now; returns the new deadline
+    pub fn extend(&mut self, room_id: &str, now: DateTime<Utc>) -> DateTime<Utc> {
+        let limit = now + self.timeout + self.extension;
+        let activity = self.rooms.entry(room_id.to_string()).or_insert(RoomActivity {
+            deadline: now,
+            warned: false,
+        });
+
+        activity.deadline = (activity.deadline.max(now) + self.extension).min(limit);
+        activity.warned = false;
+        activity.deadline
+    }
+
+    pub fn is_tracked(&self, room_id: &str) -> bool {
+        self.rooms.contains_key(room_id)
+    }
+
+    /// Stop tracking a room that was closed or finished
+    pub fn forget(&mut self, room_id: &str) {
+        self.rooms.remove(room_id);
+    }
+
+    /// Rooms to warn or close at `now`; warned rooms are marked so each
+    /// deadline is announced once
+    pub fn due(&mut self, now: DateTime<Utc>) -> Vec<InactivityAction> {
+        let mut actions = Vec::new();
+
+        for (room_id, activity) in self.rooms.iter_mut() {
+            if activity.warned {
+                if now >= activity.deadline {
+                    actions.push(InactivityAction::Close {
+                        room_id: room_id.clone(),
+                    });
+                }
+            } else if now >= activity.deadline - self.warning {
+                activity.deadline = activity.deadline.max(now + self.warning);
+                activity.warned = true;
+                actions.push(InactivityAction::Warn {
+                    room_id: room_id.clone(),
+                    closes_at: activity.deadline,
+                });
+            }
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> InactivityTracker {
+        InactivityTracker::new(Duration::minutes(30), Duration::minutes(5), Duration::minutes(15))
+    }
+
+    #[test]
+    fn test_warns_once_then_closes() {
+        let mut tracker = tracker();
+        let start = Utc::now();
+        tracker.touch("room-1", start);
+
+        assert!(tracker.due(start + Duration::minutes(24)).is_empty());
+
+        let warned = tracker.due(start + Duration::minutes(25));
+        assert_eq!(
+            warned,
+            vec![InactivityAction::Warn {
+                room_id: "room-1".to_string(),
+                closes_at: start + Duration::minutes(30),
+            }]
+        );
+        assert!(tracker.due(start + Duration::minutes(27)).is_empty());
+
+        let closed = tracker.due(start + Duration::minutes(30));
+        assert_eq!(closed, vec![InactivityAction::Close { room_id: "room-1".to_string() }]);
+    }
+
+    #[test]
+    fn test_activity_after_warning_resets_the_deadline() {
+        let mut tracker = tracker();
+        let start = Utc::now();
+        tracker.touch("room-1", start);
+        tracker.due(start + Duration::minutes(26));
+
+        tracker.touch("room-1", start + Duration::minutes(27));
+        assert!(tracker.due(start + Duration::minutes(31)).is_empty());
+        assert!(matches!(
+            tracker.due(start + Duration::minutes(52)).as_slice(),
+            [InactivityAction::Warn { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_overdue_rooms_get_a_full_warning_window() {
+        let mut tracker = tracker();
+        let now = Utc::now();
+        tracker.track("room-1", now - Duration::hours(2));
+
+        assert_eq!(
+            tracker.due(now),
+            vec![InactivityAction::Warn {
+                room_id: "room-1".to_string(),
+                closes_at: now + Duration::minutes(5),
+            }]
+        );
+        assert!(tracker.due(now + Duration::minutes(4)).is_empty());
+    }
+
+    #[test]
+    fn test_extension_is_capped() {
+        let mut tracker = tracker();
+        let now = Utc::now();
+        tracker.touch("room-1", now);
+
+        assert_eq!(tracker.extend("room-1", now), now + Duration::minutes(45));
+        assert_eq!(tracker.extend("room-1", now), now + Duration::minutes(45));
+    }
+}
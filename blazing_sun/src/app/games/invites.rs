@@ -0,0 +1,146 @@
+//! Game room invitations
+//!
+//! A member of a waiting room can invite another user into it
+//! (`games.command.invite_user`). The invitee gets `invite_received` with the
+//! room details and answers with `accept_invite`, which joins the lobby
+//! without asking for the room password, or `decline_invite`. Either answer is
+//! reported to the inviter with `invite_answered`, as is the expiry of an
+//! invite nobody answered within `GAME_ROOM_INVITE_TTL_SECONDS`. Invites are
+//! stored in Postgres, so the expiry sweep of one instance covers invites
+//! sent through any other.
+
+use thiserror::Error;
+
+use crate::app::games::types::{GameRoom, RoomStatus};
+
+/// Answer of an invite, as stored in `game_invites.status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InviteStatus {
+    Accepted,
+    Declined,
+    Expired,
+}
+
+impl InviteStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InviteStatus::Accepted => "accepted",
+            InviteStatus::Declined => "declined",
+            InviteStatus::Expired => "expired",
+        }
+    }
+}
+
+/// Why an invite cannot be sent
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InviteError {
+    #[error("You cannot invite yourself")]
+    SelfInvite,
+    #[error("Only waiting rooms accept invitations")]
+    RoomNotWaiting,
+    #[error("Only members of the room can invite")]
+    NotMember,
+    #[error("This user is already in the room")]
+    AlreadyInRoom,
+    #[error("This user cannot be invited")]
+    NotAllowed,
+}
+
+impl InviteError {
+    /// Error code sent to the inviter
+    pub fn code(&self) -> &'static str {
+        match self {
+            InviteError::SelfInvite => "invalid_invite",
+            InviteError::RoomNotWaiting => "room_not_open",
+            InviteError::NotMember => "not_in_room",
+            InviteError::AlreadyInRoom => "already_in_room",
+            InviteError::NotAllowed => "invite_not_allowed",
+        }
+    }
+}
+
+/// Whether the host, a player, a lobby member or a spectator
+pub fn is_member(room: &GameRoom, user_id: i64) -> bool {
+    room.is_admin(user_id)
+        || room.is_player(user_id)
+        || room.is_in_lobby(user_id)
+        || room.is_spectator(user_id)
+}
+
+/// Check that `inviter_id` may invite `invitee_id` into the room
+pub fn check_invite(room: &GameRoom, inviter_id: i64, invitee_id: i64) -> Result<(), InviteError> {
+    if inviter_id == invitee_id {
+        return Err(InviteError::SelfInvite);
+    }
+    if room.status != RoomStatus::Waiting {
+        return Err(InviteError::RoomNotWaiting);
+    }
+    if !is_member(room, inviter_id) {
+        return Err(InviteError::NotMember);
+    }
+    if room.is_player(invitee_id) || room.is_in_lobby(invitee_id) {
+        return Err(InviteError::AlreadyInRoom);
+    }
+    if room.is_banned(invitee_id) {
+        return Err(InviteError::NotAllowed);
+    }
+    Ok(())
+}
+
+/// Users already in the lobby or seated, as shown with an invite
+pub fn joined_count(room: &GameRoom) -> usize {
+    room.players.len() + room.lobby.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{BannedPlayer, GamePlayer, GameType};
+    use chrono::Utc;
+
+    fn player(user_id: i64) -> GamePlayer {
+        GamePlayer {
+            user_id,
+            username: format!("user{}", user_id),
+            avatar_id: None,
+            score: 0,
+            is_ready: false,
+            joined_at: Utc::now(),
+            stats: None,
+        }
+    }
+
+    fn room() -> GameRoom {
+        let mut room = GameRoom::new("r-1", "Friday dice", GameType::BiggerDice, 1);
+        room.lobby.push(player(2));
+        room.spectators.push(3);
+        room
+    }
+
+    #[test]
+    fn members_can_invite_outsiders() {
+        let room = room();
+        assert_eq!(check_invite(&room, 1, 9), Ok(()));
+        assert_eq!(check_invite(&room, 2, 9), Ok(()));
+        assert_eq!(check_invite(&room, 3, 9), Ok(()));
+        assert_eq!(check_invite(&room, 8, 9), Err(InviteError::NotMember));
+    }
+
+    #[test]
+    fn invites_need_a_new_user_and_a_waiting_room() {
+        let mut room = room();
+        assert_eq!(check_invite(&room, 1, 1), Err(InviteError::SelfInvite));
+        assert_eq!(check_invite(&room, 1, 2), Err(InviteError::AlreadyInRoom));
+
+        room.banned_users.push(BannedPlayer { user_id: 9, username: "user9".to_string() });
+        assert_eq!(check_invite(&room, 1, 9), Err(InviteError::NotAllowed));
+
+        room.status = RoomStatus::InProgress;
+        assert_eq!(check_invite(&room, 1, 8), Err(InviteError::RoomNotWaiting));
+    }
+
+    #[test]
+    fn spectators_are_not_counted_as_joined() {
+        assert_eq!(joined_count(&room()), 1);
+    }
+}
@@ -0,0 +1,99 @@
+//! Room password throttling
+//!
+//! Wrong room passwords are counted per user and room in Redis
+//! (`games:join_attempts:{room_id}:{user_id}`). The counter expires
+//! `GAME_ROOM_PASSWORD_LOCKOUT_SECONDS` after the first miss; once it reaches
+//! `GAME_ROOM_PASSWORD_MAX_ATTEMPTS` further joins are refused until it
+//! expires. Without Redis the throttle is disabled (fails open), like the
+//! other Redis-backed caches.
+
+use redis::AsyncCommands;
+use tracing::warn;
+
+use crate::config::GamesConfig;
+use crate::database::SharedRedis;
+
+fn attempts_key(room_id: &str, user_id: i64) -> String {
+    format!("games:join_attempts:{}:{}", room_id, user_id)
+}
+
+/// Seconds until the lockout ends, when `attempts` reached `max_attempts`
+pub fn lockout(attempts: u32, max_attempts: u32, ttl_seconds: i64) -> Option<u64> {
+    (max_attempts > 0 && attempts >= max_attempts).then(|| ttl_seconds.max(1) as u64)
+}
+
+#[derive(Clone)]
+pub struct JoinThrottle {
+    redis: Option<SharedRedis>,
+}
+
+impl JoinThrottle {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    /// Seconds the user has to wait before trying this room's password again
+    pub async fn retry_after(&self, room_id: &str, user_id: i64) -> Option<u64> {
+        let mut redis = self.redis.clone()?;
+        let key = attempts_key(room_id, user_id);
+
+        let attempts: Option<u32> = redis.get(&key).await.ok()?;
+        let ttl: i64 = redis.ttl(&key).await.ok()?;
+
+        lockout(attempts?, GamesConfig::room_password_max_attempts(), ttl)
+    }
+
+    /// Count a wrong password; returns the lockout when this attempt triggered it
+    pub async fn record_failure(&self, room_id: &str, user_id: i64) -> Option<u64> {
+        let mut redis = self.redis.clone()?;
+        let key = attempts_key(room_id, user_id);
+        let window = GamesConfig::room_password_lockout_seconds() as i64;
+
+        let attempts: u32 = match redis.incr(&key, 1).await {
+            Ok(attempts) => attempts,
+            Err(e) => {
+                warn!(room_id = %room_id, user_id = user_id, error = %e, "Failed to count room password attempt");
+                return None;
+            }
+        };
+
+        if attempts == 1 {
+            let result: Result<bool, redis::RedisError> = redis.expire(&key, window).await;
+            if let Err(e) = result {
+                warn!(room_id = %room_id, user_id = user_id, error = %e, "Failed to expire room password attempts");
+            }
+        }
+
+        let ttl: i64 = redis.ttl(&key).await.unwrap_or(window);
+        lockout(attempts, GamesConfig::room_password_max_attempts(), ttl)
+    }
+
+    /// Forget the user's misses after a successful join
+    pub async fn clear(&self, room_id: &str, user_id: i64) {
+        if let Some(mut redis) = self.redis.clone() {
+            let result: Result<(), redis::RedisError> = redis.del(attempts_key(room_id, user_id)).await;
+            if let Err(e) = result {
+                warn!(room_id = %room_id, user_id = user_id, error = %e, "Failed to clear room password attempts");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockout_starts_at_the_limit() {
+        assert_eq!(lockout(4, 5, 120), None);
+        assert_eq!(lockout(5, 5, 120), Some(120));
+        assert_eq!(lockout(9, 5, 30), Some(30));
+    }
+
+    #[test]
+    fn lockout_never_reports_zero_seconds_or_triggers_when_disabled() {
+        // TTL of -1/-2 (no expiry / key gone) still means "try again shortly"
+        assert_eq!(lockout(5, 5, -1), Some(1));
+        assert_eq!(lockout(100, 0, 120), None);
+    }
+}
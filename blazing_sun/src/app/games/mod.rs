@@ -7,11 +7,47 @@
 //! - Game chat (stored in MongoDB with channel separation)
 //! - Kafka handlers for game commands from WebSocket gateway
 //! - Roulette game logic and history
+//! - Dice fairness summaries for public stats
+//! - Room occupancy change tracking for lobby lists
+//! - Room configuration constraints per game type
+//! - Bot opponents for practice rooms
+//! - Spectator predictions on running games
+//! - Room lifecycle webhooks for external systems
+//! - Single-elimination tournaments
+//! - Redis projection of lobby room lists
+//! - Inactivity auto-close of waiting rooms
+//! - Spectator waiting lists for full rooms
+//! - Scheduled rooms that open later
+//! - Player statistics per game type
+//! - Invitations into rooms with one-click join
+//! - Moderator chat mutes per channel, with expiry
+//! - Room state diffs against the state each user was sent last
 
 pub mod bigger_dice;
+pub mod bot_orchestrator;
+pub mod bots;
+pub mod chat_mutes;
+pub mod fairness;
+pub mod inactivity;
+pub mod invites;
+pub mod join_throttle;
 pub mod mongodb_game_chat;
 pub mod mongodb_games;
 pub mod mongodb_roulette;
+pub mod occupancy;
+pub mod player_stats;
+pub mod predictions;
+pub mod room_config;
+pub mod room_game_types;
+pub mod room_list;
+pub mod room_password;
+pub mod room_schedule;
 pub mod roulette;
+pub mod spectator_waitlist;
+pub mod state_checksum;
+pub mod state_diff;
 pub mod tic_tac_toe;
+pub mod tournament;
+pub mod tournament_runner;
 pub mod types;
+pub mod webhooks;
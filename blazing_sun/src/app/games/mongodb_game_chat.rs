@@ -172,13 +172,14 @@ impl MongoGameChatClient {
             .await
     }
 
-    /// Get recent messages for a room and channel
+    /// Get recent messages for a room and channel, without those of `hidden_users`
     pub async fn get_messages(
         &self,
         room_id: &str,
         channel: ChatChannel,
         limit: i64,
         before: Option<DateTime<Utc>>,
+        hidden_users: &[i64],
     ) -> Result<Vec<GameChatMessage>, mongodb::error::Error> {
         let mut filter = doc! {
             "room_id": room_id,
@@ -190,6 +191,10 @@ impl MongoGameChatClient {
             filter.insert("created_at", doc! { "$lt": bson_time });
         }
 
+        if !hidden_users.is_empty() {
+            filter.insert("user_id", doc! { "$nin": hidden_users });
+        }
+
         let options = FindOptions::builder()
             .sort(doc! { "created_at": -1 })
             .limit(limit)
@@ -250,6 +255,23 @@ impl MongoGameChatClient {
         Ok(result.deleted_count)
     }
 
+    /// Erase the username, avatar and content of a user's messages (GDPR erasure)
+    pub async fn anonymize_user(
+        &self,
+        user_id: i64,
+        username: &str,
+        placeholder: &str,
+    ) -> Result<u64, mongodb::error::Error> {
+        let result = self
+            .messages()
+            .update_many(
+                doc! { "user_id": user_id, "is_system": false },
+                doc! { "$set": { "username": username, "avatar_id": null, "content": placeholder } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
     /// Get message count for a room
     pub async fn get_message_count(
         &self,
@@ -308,6 +330,34 @@ impl MongoGameChatClient {
         Ok(messages)
     }
 
+    /// A page of the messages a user wrote in game rooms, oldest first (data exports)
+    pub async fn get_sent_messages(
+        &self,
+        user_id: i64,
+        limit: i64,
+        skip: u64,
+    ) -> Result<Vec<GameChatMessage>, mongodb::error::Error> {
+        let filter = doc! { "user_id": user_id, "is_system": false };
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": 1 })
+            .limit(limit)
+            .skip(skip)
+            .build();
+
+        let mut cursor = self.messages().find(filter).with_options(options).await?;
+        let mut messages = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(msg) = cursor.next().await {
+            match msg {
+                Ok(m) => messages.push(m),
+                Err(e) => error!("Error reading user message: {}", e),
+            }
+        }
+
+        Ok(messages)
+    }
+
     /// Get moderated messages for review
     pub async fn get_moderated_messages(
         &self,
@@ -4,9 +4,10 @@
 
 use super::types::{BiggerDicePlayerRoll, BiggerDiceRoundResult, GameHistory, GameHistoryPlayer, GameTurn, GameType};
 use chrono::Utc;
-use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
 use mongodb::options::{FindOptions, IndexOptions};
 use mongodb::{Collection, Database, IndexModel};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{error, info};
 
@@ -360,6 +361,41 @@ impl MongoGameClient {
         }
     }
 
+    /// Replace a user's name in game history and in-progress round results (GDPR erasure)
+    ///
+    /// Scores and winners are kept so the other players' statistics stay intact.
+    pub async fn anonymize_player(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<u64, mongodb::error::Error> {
+        let result = self
+            .history_raw()
+            .update_many(
+                doc! { "players.user_id": user_id },
+                doc! { "$set": { "players.$[player].username": username } },
+            )
+            .array_filters(vec![doc! { "player.user_id": user_id }])
+            .await?;
+
+        let rounds: Collection<Document> = self.db.collection(COLLECTION_ROUND_RESULTS);
+        rounds
+            .update_many(
+                doc! { "rolls.user_id": user_id },
+                doc! { "$set": { "rolls.$[roll].username": username } },
+            )
+            .array_filters(vec![doc! { "roll.user_id": user_id }])
+            .await?;
+        rounds
+            .update_many(
+                doc! { "winner_id": user_id },
+                doc! { "$set": { "winner_username": username } },
+            )
+            .await?;
+
+        Ok(result.modified_count)
+    }
+
     /// Get a specific game by ID
     pub async fn get_game(
         &self,
@@ -391,6 +427,125 @@ impl MongoGameClient {
 
         Ok(games)
     }
+
+    /// Get games in insertion order, after a game ID cursor (for backfills)
+    pub async fn get_games_after(
+        &self,
+        after: Option<ObjectId>,
+        limit: i64,
+    ) -> Result<Vec<GameHistory>, mongodb::error::Error> {
+        let filter = match after {
+            Some(id) => doc! { "_id": { "$gt": id } },
+            None => doc! {},
+        };
+        let options = FindOptions::builder()
+            .sort(doc! { "_id": 1 })
+            .limit(limit)
+            .build();
+
+        let mut cursor = self.history().find(filter).with_options(options).await?;
+        let mut games = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(game) = cursor.next().await {
+            match game {
+                Ok(g) => games.push(g),
+                Err(e) => error!("Error reading game: {}", e),
+            }
+        }
+
+        Ok(games)
+    }
+
+    /// Count finished games and average duration per game type
+    pub async fn get_game_type_summaries(
+        &self,
+    ) -> Result<Vec<GameTypeSummary>, mongodb::error::Error> {
+        let pipeline = vec![doc! {
+            "$group": {
+                "_id": "$game_type",
+                "total_games": { "$sum": 1 },
+                "avg_duration": { "$avg": "$duration_seconds" }
+            }
+        }];
+
+        let mut cursor = self.history_raw().aggregate(pipeline).await?;
+        let mut summaries = Vec::new();
+
+        use futures::StreamExt;
+        while let Some(doc) = cursor.next().await {
+            match doc {
+                Ok(doc) => {
+                    let Ok(game_type) = doc.get_str("_id") else {
+                        continue;
+                    };
+                    summaries.push(GameTypeSummary {
+                        game_type: game_type.to_string(),
+                        total_games: bson_number(doc.get("total_games")).unwrap_or(0.0) as i64,
+                        avg_duration_seconds: bson_number(doc.get("avg_duration")).unwrap_or(0.0),
+                    });
+                }
+                Err(e) => error!("Error reading game type summary: {}", e),
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    /// Count recorded die rolls per face across all finished games of a type
+    pub async fn get_roll_counts(
+        &self,
+        game_type: GameType,
+    ) -> Result<HashMap<i32, i64>, mongodb::error::Error> {
+        let pipeline = vec![
+            doc! { "$match": { "game_type": game_type.as_str() } },
+            doc! { "$unwind": "$turns" },
+            doc! { "$match": { "turns.action.roll": { "$exists": true } } },
+            doc! {
+                "$group": {
+                    "_id": "$turns.action.roll",
+                    "count": { "$sum": 1 }
+                }
+            },
+        ];
+
+        let mut cursor = self.history_raw().aggregate(pipeline).await?;
+        let mut counts = HashMap::new();
+
+        use futures::StreamExt;
+        while let Some(doc) = cursor.next().await {
+            match doc {
+                Ok(doc) => {
+                    if let (Some(face), Some(count)) =
+                        (bson_number(doc.get("_id")), bson_number(doc.get("count")))
+                    {
+                        *counts.entry(face as i32).or_insert(0) += count as i64;
+                    }
+                }
+                Err(e) => error!("Error reading roll count: {}", e),
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+/// Read a numeric aggregation result regardless of its BSON width
+fn bson_number(value: Option<&Bson>) -> Option<f64> {
+    match value? {
+        Bson::Int32(v) => Some(*v as f64),
+        Bson::Int64(v) => Some(*v as f64),
+        Bson::Double(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Finished games of one type, aggregated from game history
+#[derive(Debug, Clone)]
+pub struct GameTypeSummary {
+    pub game_type: String,
+    pub total_games: i64,
+    pub avg_duration_seconds: f64,
 }
 
 /// User game statistics
@@ -253,6 +253,35 @@ impl MongoRouletteClient {
         self.history().find_one(doc! { "_id": spin_id }).await
     }
 
+    /// Get a user's spins with `from <= created_at < to`, oldest first
+    pub async fn get_user_history_between(
+        &self,
+        user_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<RouletteHistory>, mongodb::error::Error> {
+        let options = FindOptions::builder().sort(doc! { "created_at": -1 }).build();
+
+        let mut cursor = self
+            .history()
+            .find(doc! { "user_id": user_id })
+            .with_options(options)
+            .await?;
+        let mut history = Vec::new();
+
+        while let Some(record) = cursor.next().await {
+            match record {
+                Ok(h) if h.created_at < from => break,
+                Ok(h) if h.created_at < to => history.push(h),
+                Ok(_) => {}
+                Err(e) => error!("Error reading roulette history: {}", e),
+            }
+        }
+
+        history.reverse();
+        Ok(history)
+    }
+
     /// Get recent spins (for leaderboard/activity feed)
     pub async fn get_recent_spins(
         &self,
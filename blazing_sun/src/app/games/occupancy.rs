@@ -0,0 +1,177 @@
+//! Room occupancy tracking
+//!
+//! Lobby room lists show how many players and spectators each room has. Every
+//! cached room update records the room's occupancy here; the first change opens a
+//! throttle window and whatever the room looks like when the window closes is
+//! published as a single `room_occupancy_changed` event. Updates that don't change
+//! the counts or status (rolls, ready toggles, ...) publish nothing.
+
+use std::collections::{HashMap, HashSet};
+
+use super::types::{GameEvent, GameRoom, RoomStatus};
+
+/// Counts shown in lobby room lists
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomOccupancy {
+    pub room_id: String,
+    pub room_name: String,
+    pub game_type: String,
+    pub status: RoomStatus,
+    pub player_count: i32,
+    pub max_players: i32,
+    pub spectator_count: i32,
+    pub max_spectators: i32,
+}
+
+impl RoomOccupancy {
+    pub fn from_room(room: &GameRoom) -> Self {
+        // Selected players stay in the lobby until the game starts, count them once
+        let players: HashSet<i64> = room
+            .players
+            .iter()
+            .chain(room.lobby.iter())
+            .map(|p| p.user_id)
+            .collect();
+
+        Self {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            game_type: room.game_type.as_str().to_string(),
+            status: room.status.clone(),
+            player_count: players.len() as i32,
+            max_players: room.player_count,
+            spectator_count: room.spectators_data.len() as i32,
+            max_spectators: room.max_spectators,
+        }
+    }
+
+    pub fn into_event(self) -> GameEvent {
+        GameEvent::RoomOccupancyChanged {
+            room_id: self.room_id,
+            room_name: self.room_name,
+            game_type: self.game_type,
+            status: self.status,
+            player_count: self.player_count,
+            max_players: self.max_players,
+            spectator_count: self.spectator_count,
+            max_spectators: self.max_spectators,
+        }
+    }
+}
+
+/// Coalesces occupancy changes per room
+#[derive(Debug, Default)]
+pub struct OccupancyThrottle {
+    /// Last occupancy published per room
+    published: HashMap<String, RoomOccupancy>,
+    /// Latest occupancy of rooms with an open throttle window
+    pending: HashMap<String, RoomOccupancy>,
+}
+
+impl OccupancyThrottle {
+    /// Record the current state of a room.
+    ///
+    /// Returns true when this opened a new throttle window, i.e. the caller
+    /// must schedule a `flush` for the room.
+    pub fn record(&mut self, room: &GameRoom) -> bool {
+        let occupancy = RoomOccupancy::from_room(room);
+
+        if let Some(pending) = self.pending.get_mut(&occupancy.room_id) {
+            *pending = occupancy;
+            return false;
+        }
+
+        if self.published.get(&occupancy.room_id) == Some(&occupancy) {
+            return false;
+        }
+
+        self.pending.insert(occupancy.room_id.clone(), occupancy);
+        true
+    }
+
+    /// Close the throttle window of a room and return the occupancy to publish,
+    /// if it differs from what was last published
+    pub fn flush(&mut self, room_id: &str) -> Option<RoomOccupancy> {
+        let occupancy = self.pending.remove(room_id)?;
+        if self.published.get(room_id) == Some(&occupancy) {
+            return None;
+        }
+
+        self.published.insert(room_id.to_string(), occupancy.clone());
+        Some(occupancy)
+    }
+
+    /// Forget a room that was removed (its removal is announced separately)
+    pub fn forget(&mut self, room_id: &str) {
+        self.published.remove(room_id);
+        self.pending.remove(room_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{GamePlayer, GameType};
+    use chrono::Utc;
+
+    fn room() -> GameRoom {
+        GameRoom::new("room-1", "Room 1", GameType::BiggerDice, 1)
+    }
+
+    fn player(user_id: i64) -> GamePlayer {
+        GamePlayer {
+            user_id,
+            username: format!("user{}", user_id),
+            avatar_id: None,
+            score: 0,
+            is_ready: false,
+            joined_at: Utc::now(),
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn test_rapid_changes_coalesce() {
+        let mut throttle = OccupancyThrottle::default();
+        let mut room = room();
+
+        assert!(throttle.record(&room));
+        room.lobby.push(player(2));
+        assert!(!throttle.record(&room));
+        room.lobby.push(player(3));
+        assert!(!throttle.record(&room));
+
+        let occupancy = throttle.flush("room-1").unwrap();
+        assert_eq!(occupancy.player_count, 2);
+        assert!(throttle.flush("room-1").is_none());
+    }
+
+    #[test]
+    fn test_unchanged_occupancy_is_not_republished() {
+        let mut throttle = OccupancyThrottle::default();
+        let mut room = room();
+
+        assert!(throttle.record(&room));
+        assert!(throttle.flush("room-1").is_some());
+
+        // e.g. a ready toggle: same counts and status
+        assert!(!throttle.record(&room));
+
+        // Change and revert within one window publishes nothing
+        room.lobby.push(player(2));
+        assert!(throttle.record(&room));
+        room.lobby.pop();
+        throttle.record(&room);
+        assert!(throttle.flush("room-1").is_none());
+    }
+
+    #[test]
+    fn test_players_in_lobby_and_game_are_counted_once() {
+        let mut room = room();
+        room.lobby.push(player(2));
+        room.players.push(player(2));
+        room.players.push(player(3));
+
+        assert_eq!(RoomOccupancy::from_room(&room).player_count, 2);
+    }
+}
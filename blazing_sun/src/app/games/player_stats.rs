@@ -0,0 +1,409 @@
+//! Player statistics per game type
+//!
+//! Every finished room becomes one [`MatchResult`] per player: a win for the
+//! winner, a loss for everyone else, a draw for all players of a room that
+//! finished without a winner. Results are stored in `player_game_results`
+//! keyed by user and room, so a redelivered game over event (or a backfill
+//! run over rooms the handler already saw) counts once, and folded into the
+//! `player_game_stats` totals with [`PlayerGameStats::record`].
+//!
+//! The player stats handler (`events::handlers::PlayerStatsHandler`) records
+//! results from this region's game over events, which carry the room's start
+//! time because the room itself is deleted once it is over. `blazing_admin
+//! stats backfill` records the games played before from the MongoDB game
+//! history and the finished rooms still in game_rooms, then rebuilds the
+//! totals of the players it touched from their full result history.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::tournament::FINISHED_EVENTS;
+use super::types::EventEnvelope;
+
+/// How a finished room ended for one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl Outcome {
+    /// Name stored in `player_game_results.outcome`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Win => "win",
+            Outcome::Loss => "loss",
+            Outcome::Draw => "draw",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Outcome::Win, Outcome::Loss, Outcome::Draw]
+            .into_iter()
+            .find(|outcome| outcome.as_str() == value)
+    }
+}
+
+/// A room reported finished by a game over event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinishedRoom {
+    pub room_id: String,
+    /// None (or 0) when the room ended in a draw
+    pub winner_id: Option<i64>,
+    /// Players listed in `final_scores`
+    pub players: Vec<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the event was produced
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// One player's result of a finished room
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchResult {
+    pub user_id: i64,
+    pub room_id: String,
+    pub game_type: String,
+    pub outcome: Outcome,
+    /// Seconds from game start to finish (None when the start is unknown)
+    pub match_seconds: Option<i64>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// The finished room carried by a games.events envelope
+pub fn finished_room(envelope: &EventEnvelope) -> Option<FinishedRoom> {
+    let payload = &envelope.payload;
+    let event_type = payload.get("type")?.as_str()?;
+    if !FINISHED_EVENTS.contains(&event_type) {
+        return None;
+    }
+
+    let room_id = payload.get("room_id")?.as_str()?.to_string();
+    let winner_id = payload.get("winner_id").and_then(user_id);
+    let started_at = payload
+        .get("started_at")
+        .and_then(Value::as_str)
+        .and_then(parse_time);
+
+    let mut players: Vec<i64> = payload
+        .get("final_scores")
+        .and_then(Value::as_array)
+        .map(|scores| {
+            scores
+                .iter()
+                .filter_map(|score| score.get(0).and_then(user_id))
+                .collect()
+        })
+        .unwrap_or_default();
+    players.sort_unstable();
+    players.dedup();
+
+    Some(FinishedRoom {
+        room_id,
+        winner_id,
+        players,
+        started_at,
+        finished_at: parse_time(&envelope.timestamp),
+    })
+}
+
+/// Game type named by a game specific event type ("games.event.bigger_dice.game_over")
+pub fn game_type_of(event_type: &str) -> Option<&str> {
+    let rest = event_type.strip_prefix("games.event.")?;
+    let (game_type, _) = rest.split_once('.')?;
+    Some(game_type)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+fn user_id(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Results of every player of a finished room
+pub fn results(
+    room_id: &str,
+    game_type: &str,
+    players: &[i64],
+    winner_id: Option<i64>,
+    started_at: Option<DateTime<Utc>>,
+    finished_at: DateTime<Utc>,
+) -> Vec<MatchResult> {
+    let winner_id = winner_id.filter(|id| *id > 0);
+    let match_seconds = started_at.map(|at| (finished_at - at).num_seconds().max(0));
+
+    players
+        .iter()
+        .map(|&user_id| MatchResult {
+            user_id,
+            room_id: room_id.to_string(),
+            game_type: game_type.to_string(),
+            outcome: match winner_id {
+                None => Outcome::Draw,
+                Some(winner) if winner == user_id => Outcome::Win,
+                Some(_) => Outcome::Loss,
+            },
+            match_seconds,
+            finished_at,
+        })
+        .collect()
+}
+
+/// A player's totals for one game type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerGameStats {
+    pub user_id: i64,
+    pub game_type: String,
+    pub games_played: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+    /// Wins in a row when positive, losses in a row when negative (0 after a draw)
+    pub current_streak: i32,
+    pub best_win_streak: i32,
+    /// Games with a known duration (the average is taken over these)
+    pub timed_games: i64,
+    pub total_match_seconds: i64,
+    pub last_played_at: Option<DateTime<Utc>>,
+}
+
+impl PlayerGameStats {
+    /// Totals of a player who has not finished a game yet
+    pub fn new(user_id: i64, game_type: &str) -> Self {
+        Self {
+            user_id,
+            game_type: game_type.to_string(),
+            games_played: 0,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            current_streak: 0,
+            best_win_streak: 0,
+            timed_games: 0,
+            total_match_seconds: 0,
+            last_played_at: None,
+        }
+    }
+
+    /// Totals of a full result history, oldest first
+    pub fn from_results(user_id: i64, game_type: &str, results: &[MatchResult]) -> Self {
+        let mut stats = Self::new(user_id, game_type);
+        for result in results {
+            stats.record(result.outcome, result.match_seconds, result.finished_at);
+        }
+        stats
+    }
+
+    /// Count one more finished game
+    pub fn record(
+        &mut self,
+        outcome: Outcome,
+        match_seconds: Option<i64>,
+        finished_at: DateTime<Utc>,
+    ) {
+        self.games_played += 1;
+        match outcome {
+            Outcome::Win => {
+                self.wins += 1;
+                self.current_streak = self.current_streak.max(0) + 1;
+                self.best_win_streak = self.best_win_streak.max(self.current_streak);
+            }
+            Outcome::Loss => {
+                self.losses += 1;
+                self.current_streak = self.current_streak.min(0) - 1;
+            }
+            Outcome::Draw => {
+                self.draws += 1;
+                self.current_streak = 0;
+            }
+        }
+
+        if let Some(seconds) = match_seconds {
+            self.timed_games += 1;
+            self.total_match_seconds += seconds;
+        }
+        self.last_played_at = Some(
+            self.last_played_at
+                .map_or(finished_at, |at| at.max(finished_at)),
+        );
+    }
+
+    /// Public view of the totals
+    pub fn summary(&self) -> PlayerStatsSummary {
+        PlayerStatsSummary {
+            games_played: self.games_played,
+            wins: self.wins,
+            losses: self.losses,
+            draws: self.draws,
+            win_rate: if self.games_played > 0 {
+                self.wins as f64 / self.games_played as f64
+            } else {
+                0.0
+            },
+            current_streak: self.current_streak,
+            best_win_streak: self.best_win_streak,
+            average_match_seconds: (self.timed_games > 0)
+                .then(|| self.total_match_seconds as f64 / self.timed_games as f64),
+            last_played_at: self.last_played_at,
+        }
+    }
+}
+
+/// Player stats as returned by the API and shown next to lobby players
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStatsSummary {
+    pub games_played: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+    /// Share of games won (0-1)
+    pub win_rate: f64,
+    /// Wins in a row when positive, losses in a row when negative
+    pub current_streak: i32,
+    pub best_win_streak: i32,
+    pub average_match_seconds: Option<f64>,
+    pub last_played_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{Actor, Audience};
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 17, 12, minute, 0).unwrap()
+    }
+
+    fn envelope(event_type: &str, payload: Value) -> EventEnvelope {
+        EventEnvelope {
+            event_id: "e-1".to_string(),
+            event_type: event_type.to_string(),
+            timestamp: "2026-10-17T12:00:00Z".to_string(),
+            correlation_id: None,
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::room("r-1"),
+            payload,
+        }
+    }
+
+    #[test]
+    fn reads_the_room_and_players_of_a_game_over() {
+        let room = finished_room(&envelope(
+            "games.event.bigger_dice.game_over",
+            json!({
+                "type": "bigger_dice.game_over",
+                "room_id": "r-1",
+                "winner_id": "7",
+                "final_scores": [[9, "bob", 4], [7, "ana", 10]],
+                "started_at": "2026-10-17T11:55:00Z",
+            }),
+        ))
+        .unwrap();
+
+        assert_eq!(room.room_id, "r-1");
+        assert_eq!(room.winner_id, Some(7));
+        assert_eq!(room.players, vec![7, 9]);
+        assert_eq!(
+            room.started_at,
+            Some(Utc.with_ymd_and_hms(2026, 10, 17, 11, 55, 0).unwrap())
+        );
+        assert_eq!(room.finished_at, Some(at(0)));
+
+        assert!(finished_room(&envelope(
+            "games.event.bigger_dice.rolled",
+            json!({"type": "bigger_dice.rolled", "room_id": "r-1"}),
+        ))
+        .is_none());
+    }
+
+    #[test]
+    fn names_the_game_type_of_specific_events() {
+        assert_eq!(
+            game_type_of("games.event.tic_tac_toe.match_ended"),
+            Some("tic_tac_toe")
+        );
+        assert_eq!(game_type_of("games.event.game_ended"), None);
+    }
+
+    #[test]
+    fn splits_a_room_into_wins_losses_and_draws() {
+        let won = results("r-1", "bigger_dice", &[7, 9], Some(7), Some(at(0)), at(5));
+        assert_eq!(won[0].outcome, Outcome::Win);
+        assert_eq!(won[1].outcome, Outcome::Loss);
+        assert_eq!(won[0].match_seconds, Some(300));
+
+        let drawn = results("r-2", "tic_tac_toe", &[7, 9], None, None, at(5));
+        assert!(drawn
+            .iter()
+            .all(|r| r.outcome == Outcome::Draw && r.match_seconds.is_none()));
+    }
+
+    #[test]
+    fn tracks_streaks_and_average_match_time() {
+        let mut stats = PlayerGameStats::new(7, "bigger_dice");
+        stats.record(Outcome::Win, Some(60), at(1));
+        stats.record(Outcome::Win, Some(120), at(2));
+        stats.record(Outcome::Win, None, at(3));
+        stats.record(Outcome::Loss, Some(90), at(4));
+        stats.record(Outcome::Loss, None, at(5));
+
+        assert_eq!((stats.wins, stats.losses, stats.draws), (3, 2, 0));
+        assert_eq!(stats.current_streak, -2);
+        assert_eq!(stats.best_win_streak, 3);
+
+        stats.record(Outcome::Draw, None, at(6));
+        let summary = stats.summary();
+        assert_eq!(summary.games_played, 6);
+        assert_eq!(summary.current_streak, 0);
+        assert_eq!(summary.average_match_seconds, Some(90.0));
+        assert_eq!(summary.win_rate, 0.5);
+        assert_eq!(summary.last_played_at, Some(at(6)));
+    }
+
+    #[test]
+    fn rebuilding_from_history_matches_recording_one_by_one() {
+        let history: Vec<MatchResult> = [Some(7), Some(9), Some(7), None]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, winner)| {
+                results(
+                    &format!("r-{}", i),
+                    "tic_tac_toe",
+                    &[7],
+                    winner,
+                    Some(at(0)),
+                    at(i as u32 + 1),
+                )
+            })
+            .collect();
+
+        let mut stats = PlayerGameStats::new(7, "tic_tac_toe");
+        for r in &history {
+            stats.record(r.outcome, r.match_seconds, r.finished_at);
+        }
+
+        assert_eq!(
+            PlayerGameStats::from_results(7, "tic_tac_toe", &history),
+            stats
+        );
+        assert_eq!(stats.summary().win_rate, 0.5);
+    }
+}
@@ -0,0 +1,332 @@
+//! Spectator predictions
+//!
+//! Spectators of a running game can stake coins on which player wins. Stakes
+//! are taken from the balance when the prediction is made and held in escrow
+//! (`game_predictions` rows with status `escrowed`) until the game ends.
+//!
+//! The pool is parimutuel: everything staked is shared by the spectators who
+//! picked the winner, in proportion to their stakes, so a candidate's odds are
+//! `total pool / stakes on that candidate`. There is no house cut. When the
+//! game ends without a winner, or nobody picked the winner, every stake is
+//! refunded.
+//!
+//! Predictions open when the game starts and close after the prediction
+//! window (GAME_PREDICTION_WINDOW_SECONDS) or when the game ends.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::GamePlayer;
+
+/// A player spectators can back, with the pool staked on them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredictionCandidate {
+    pub user_id: i64,
+    pub username: String,
+    pub pool_cents: i64,
+    pub predictions: u32,
+    /// Decimal odds (payout per coin staked); None while nobody backs them
+    pub odds: Option<f64>,
+}
+
+/// Why a prediction was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredictionError {
+    Closed,
+    NotSpectator,
+    AlreadyPredicted,
+    UnknownCandidate,
+    StakeOutOfRange { min: i64, max: i64 },
+}
+
+impl PredictionError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PredictionError::Closed => "predictions_closed",
+            PredictionError::NotSpectator => "not_spectator",
+            PredictionError::AlreadyPredicted => "already_predicted",
+            PredictionError::UnknownCandidate => "unknown_candidate",
+            PredictionError::StakeOutOfRange { .. } => "invalid_stake",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            PredictionError::Closed => "Predictions are closed".to_string(),
+            PredictionError::NotSpectator => "Only spectators can make predictions".to_string(),
+            PredictionError::AlreadyPredicted => {
+                "You already made a prediction for this game".to_string()
+            }
+            PredictionError::UnknownCandidate => "That player is not in this game".to_string(),
+            PredictionError::StakeOutOfRange { min, max } => {
+                format!("Stake must be between {} and {} cents", min, max)
+            }
+        }
+    }
+}
+
+/// One escrowed stake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stake {
+    pub prediction_id: i64,
+    pub user_id: i64,
+    pub predicted_winner_id: i64,
+    pub stake_cents: i64,
+}
+
+/// How a stake was settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictionOutcome {
+    Won,
+    Lost,
+    Refunded,
+}
+
+impl PredictionOutcome {
+    /// Value of `game_predictions.status`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PredictionOutcome::Won => "won",
+            PredictionOutcome::Lost => "lost",
+            PredictionOutcome::Refunded => "refunded",
+        }
+    }
+}
+
+/// Settlement of one stake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PredictionPayout {
+    pub prediction_id: i64,
+    pub user_id: i64,
+    pub stake_cents: i64,
+    pub payout_cents: i64,
+    pub outcome: PredictionOutcome,
+}
+
+/// Split the pool between the stakes on the winner.
+///
+/// Payouts are rounded down; the cents lost to rounding go one each to the
+/// earliest winning stakes so the whole pool is paid out.
+pub fn settle(stakes: &[Stake], winner_id: Option<i64>) -> Vec<PredictionPayout> {
+    let total: i64 = stakes.iter().map(|s| s.stake_cents).sum();
+    let winning_pool: i64 = stakes
+        .iter()
+        .filter(|s| Some(s.predicted_winner_id) == winner_id)
+        .map(|s| s.stake_cents)
+        .sum();
+
+    if winning_pool == 0 {
+        return stakes
+            .iter()
+            .map(|s| payout(s, s.stake_cents, PredictionOutcome::Refunded))
+            .collect();
+    }
+
+    let mut payouts: Vec<PredictionPayout> = stakes
+        .iter()
+        .map(|s| {
+            if Some(s.predicted_winner_id) == winner_id {
+                let share = (s.stake_cents as i128 * total as i128 / winning_pool as i128) as i64;
+                payout(s, share, PredictionOutcome::Won)
+            } else {
+                payout(s, 0, PredictionOutcome::Lost)
+            }
+        })
+        .collect();
+
+    let mut remainder = total - payouts.iter().map(|p| p.payout_cents).sum::<i64>();
+    for p in payouts
+        .iter_mut()
+        .filter(|p| p.outcome == PredictionOutcome::Won)
+    {
+        if remainder == 0 {
+            break;
+        }
+        p.payout_cents += 1;
+        remainder -= 1;
+    }
+
+    payouts
+}
+
+fn payout(stake: &Stake, payout_cents: i64, outcome: PredictionOutcome) -> PredictionPayout {
+    PredictionPayout {
+        prediction_id: stake.prediction_id,
+        user_id: stake.user_id,
+        stake_cents: stake.stake_cents,
+        payout_cents,
+        outcome,
+    }
+}
+
+/// Live prediction pool of one running game
+#[derive(Debug, Clone)]
+pub struct PredictionPool {
+    pub room_id: String,
+    pub closes_at: DateTime<Utc>,
+    /// Set once the Closed event went out
+    pub closed: bool,
+    players: Vec<(i64, String)>,
+    stakes: Vec<Stake>,
+}
+
+impl PredictionPool {
+    pub fn open(room_id: &str, players: &[GamePlayer], now: DateTime<Utc>, window: Duration) -> Self {
+        Self {
+            room_id: room_id.to_string(),
+            closes_at: now + window,
+            closed: false,
+            players: players
+                .iter()
+                .map(|p| (p.user_id, p.username.clone()))
+                .collect(),
+            stakes: Vec::new(),
+        }
+    }
+
+    /// Whether new predictions are accepted
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        !self.closed && now < self.closes_at
+    }
+
+    pub fn total_cents(&self) -> i64 {
+        self.stakes.iter().map(|s| s.stake_cents).sum()
+    }
+
+    /// Check a prediction before its stake is escrowed
+    pub fn validate(
+        &self,
+        user_id: i64,
+        predicted_winner_id: i64,
+        stake_cents: i64,
+        (min, max): (i64, i64),
+        now: DateTime<Utc>,
+    ) -> Result<(), PredictionError> {
+        if !self.is_open(now) {
+            return Err(PredictionError::Closed);
+        }
+        if self.players.iter().any(|(id, _)| *id == user_id) {
+            return Err(PredictionError::NotSpectator);
+        }
+        if !self.players.iter().any(|(id, _)| *id == predicted_winner_id) {
+            return Err(PredictionError::UnknownCandidate);
+        }
+        if self.stakes.iter().any(|s| s.user_id == user_id) {
+            return Err(PredictionError::AlreadyPredicted);
+        }
+        if stake_cents < min || stake_cents > max {
+            return Err(PredictionError::StakeOutOfRange { min, max });
+        }
+        Ok(())
+    }
+
+    /// Record an escrowed stake
+    pub fn add(&mut self, stake: Stake) {
+        self.stakes.push(stake);
+    }
+
+    /// Pool per player, in seat order
+    pub fn candidates(&self) -> Vec<PredictionCandidate> {
+        let total = self.total_cents();
+
+        self.players
+            .iter()
+            .map(|(user_id, username)| {
+                let backing = self.stakes.iter().filter(|s| s.predicted_winner_id == *user_id);
+                let pool_cents: i64 = backing.clone().map(|s| s.stake_cents).sum();
+
+                PredictionCandidate {
+                    user_id: *user_id,
+                    username: username.clone(),
+                    pool_cents,
+                    predictions: backing.count() as u32,
+                    odds: (pool_cents > 0).then(|| total as f64 / pool_cents as f64),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake(prediction_id: i64, user_id: i64, winner: i64, cents: i64) -> Stake {
+        Stake {
+            prediction_id,
+            user_id,
+            predicted_winner_id: winner,
+            stake_cents: cents,
+        }
+    }
+
+    fn player(user_id: i64, username: &str) -> GamePlayer {
+        GamePlayer {
+            user_id,
+            username: username.to_string(),
+            avatar_id: None,
+            score: 0,
+            is_ready: false,
+            joined_at: Utc::now(),
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn winners_share_the_whole_pool_pro_rata() {
+        let stakes = [stake(1, 10, 1, 100), stake(2, 11, 1, 200), stake(3, 12, 2, 101)];
+        let payouts = settle(&stakes, Some(1));
+
+        // 401 * 100 / 300 = 133.67, 401 * 200 / 300 = 267.33; the cent lost to
+        // rounding goes to the earliest winner
+        assert_eq!(payouts[0].payout_cents, 134);
+        assert_eq!(payouts[1].payout_cents, 267);
+        assert_eq!(payouts[2].outcome, PredictionOutcome::Lost);
+        assert_eq!(payouts.iter().map(|p| p.payout_cents).sum::<i64>(), 401);
+    }
+
+    #[test]
+    fn stakes_are_refunded_without_a_backed_winner() {
+        let stakes = [stake(1, 10, 1, 100), stake(2, 11, 1, 50)];
+
+        for winner in [None, Some(2)] {
+            let payouts = settle(&stakes, winner);
+            assert!(payouts.iter().all(|p| p.outcome == PredictionOutcome::Refunded));
+            assert_eq!(payouts[1].payout_cents, 50);
+        }
+    }
+
+    #[test]
+    fn pool_validates_and_reports_odds() {
+        let now = Utc::now();
+        let mut pool = PredictionPool::open(
+            "room",
+            &[player(1, "alice"), player(2, "bob")],
+            now,
+            Duration::seconds(60),
+        );
+        let limits = (10, 1000);
+
+        assert_eq!(pool.validate(1, 2, 100, limits, now), Err(PredictionError::NotSpectator));
+        assert_eq!(pool.validate(10, 3, 100, limits, now), Err(PredictionError::UnknownCandidate));
+        assert_eq!(
+            pool.validate(10, 1, 5, limits, now),
+            Err(PredictionError::StakeOutOfRange { min: 10, max: 1000 })
+        );
+        assert_eq!(pool.validate(10, 1, 100, limits, now), Ok(()));
+
+        pool.add(stake(1, 10, 1, 100));
+        pool.add(stake(2, 11, 2, 300));
+        assert_eq!(pool.validate(10, 2, 100, limits, now), Err(PredictionError::AlreadyPredicted));
+        assert_eq!(
+            pool.validate(12, 1, 100, limits, now + Duration::seconds(60)),
+            Err(PredictionError::Closed)
+        );
+
+        let candidates = pool.candidates();
+        assert_eq!(candidates[0].odds, Some(4.0));
+        assert_eq!(candidates[1].pool_cents, 300);
+        assert_eq!(candidates[1].predictions, 1);
+    }
+}
@@ -0,0 +1,334 @@
+//! Game room configuration
+//!
+//! Every game engine registers its room constraints next to its rules
+//! (`bigger_dice::ROOM_CONSTRAINTS`, `tic_tac_toe::ROOM_CONSTRAINTS`): player
+//! range, spectator cap and, for timed games, the allowed turn timer range.
+//! Room creation resolves the requested settings (optionally starting from a
+//! preset stored in `game_room_presets`) into a `RoomConfig` and rejects
+//! combinations the game cannot be played with. Bigger Dice rooms also carry
+//! their rules (`bigger_dice::BiggerDiceRules`), checked against the allowed
+//! ranges the same way.
+
+use serde::Serialize;
+
+use super::bigger_dice::{BiggerDiceRuleSettings, BiggerDiceRules};
+use super::types::GameType;
+use super::{bigger_dice, tic_tac_toe};
+
+/// Allowed turn timer of a timed game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimerRange {
+    pub min_seconds: i64,
+    pub max_seconds: i64,
+    pub default_seconds: i64,
+}
+
+/// Room settings a game type can be played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RoomConstraints {
+    pub min_players: i32,
+    pub max_players: i32,
+    pub default_players: i32,
+    pub max_spectators: i32,
+    /// None for games without a turn timer
+    pub turn_timer: Option<TimerRange>,
+}
+
+/// Constraints registered by the game's engine
+pub fn constraints(game_type: &GameType) -> &'static RoomConstraints {
+    match game_type {
+        GameType::BiggerDice => &bigger_dice::ROOM_CONSTRAINTS,
+        GameType::TicTacToe => &tic_tac_toe::ROOM_CONSTRAINTS,
+    }
+}
+
+/// Error type for room configuration validation
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RoomConfigError {
+    #[error("{game} rooms need {min} to {max} players")]
+    PlayerCount {
+        game: &'static str,
+        min: i32,
+        max: i32,
+    },
+    #[error("{game} rooms allow at most {max} spectators")]
+    SpectatorCap { game: &'static str, max: i32 },
+    #[error("{game} has no turn timer")]
+    TimerNotSupported { game: &'static str },
+    #[error("{game} turn timer must be between {min} and {max} seconds")]
+    TimerRange {
+        game: &'static str,
+        min: i64,
+        max: i64,
+    },
+    #[error("{game} has no configurable rules")]
+    RulesNotSupported { game: &'static str },
+    #[error("{rule} must be between {min} and {max}")]
+    RuleRange {
+        rule: &'static str,
+        min: i32,
+        max: i32,
+    },
+    #[error("A best-of tiebreaker needs an odd number of roll-offs")]
+    EvenRollOffs,
+    #[error("Unknown room preset: {0}")]
+    UnknownPreset(String),
+}
+
+/// Settings requested for a new room; unset values come from the preset or
+/// the game's defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomSettings {
+    pub player_count: Option<i32>,
+    pub allow_spectators: Option<bool>,
+    pub max_spectators: Option<i32>,
+    pub turn_timer_seconds: Option<i64>,
+    /// Bigger Dice rules
+    pub dice_rules: BiggerDiceRuleSettings,
+}
+
+impl RoomSettings {
+    /// Values set here win over `base`
+    pub fn or(self, base: RoomSettings) -> RoomSettings {
+        RoomSettings {
+            player_count: self.player_count.or(base.player_count),
+            allow_spectators: self.allow_spectators.or(base.allow_spectators),
+            max_spectators: self.max_spectators.or(base.max_spectators),
+            turn_timer_seconds: self.turn_timer_seconds.or(base.turn_timer_seconds),
+            dice_rules: self.dice_rules.or(base.dice_rules),
+        }
+    }
+}
+
+/// Validated configuration of a room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RoomConfig {
+    pub player_count: i32,
+    pub allow_spectators: bool,
+    pub max_spectators: i32,
+    pub turn_timer_seconds: Option<i64>,
+    /// None for games without configurable rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dice_rules: Option<BiggerDiceRules>,
+}
+
+impl RoomConfig {
+    /// Fill unset values with the game's defaults and validate the result
+    pub fn resolve(game_type: &GameType, settings: RoomSettings) -> Result<Self, RoomConfigError> {
+        let limits = constraints(game_type);
+        let allow_spectators = settings.allow_spectators.unwrap_or(true);
+        let dice_rules = match game_type {
+            GameType::BiggerDice => Some(BiggerDiceRules::resolve(settings.dice_rules)?),
+            _ if settings.dice_rules.is_empty() => None,
+            _ => {
+                return Err(RoomConfigError::RulesNotSupported {
+                    game: game_type.as_str(),
+                })
+            }
+        };
+
+        let config = Self {
+            player_count: settings.player_count.unwrap_or(limits.default_players),
+            allow_spectators,
+            max_spectators: settings.max_spectators.unwrap_or(if allow_spectators {
+                limits.max_spectators
+            } else {
+                0
+            }),
+            turn_timer_seconds: settings
+                .turn_timer_seconds
+                .or(limits.turn_timer.map(|timer| timer.default_seconds)),
+            dice_rules,
+        };
+
+        config.validate(game_type)?;
+        Ok(config)
+    }
+
+    pub fn validate(&self, game_type: &GameType) -> Result<(), RoomConfigError> {
+        let limits = constraints(game_type);
+        let game = game_type.as_str();
+
+        if !(limits.min_players..=limits.max_players).contains(&self.player_count) {
+            return Err(RoomConfigError::PlayerCount {
+                game,
+                min: limits.min_players,
+                max: limits.max_players,
+            });
+        }
+
+        if !(0..=limits.max_spectators).contains(&self.max_spectators) {
+            return Err(RoomConfigError::SpectatorCap {
+                game,
+                max: limits.max_spectators,
+            });
+        }
+
+        if let Some(rules) = self.dice_rules {
+            if !matches!(game_type, GameType::BiggerDice) {
+                return Err(RoomConfigError::RulesNotSupported { game });
+            }
+            rules.validate()?;
+        }
+
+        match (limits.turn_timer, self.turn_timer_seconds) {
+            (None, Some(_)) => Err(RoomConfigError::TimerNotSupported { game }),
+            (Some(timer), Some(seconds))
+                if !(timer.min_seconds..=timer.max_seconds).contains(&seconds) =>
+            {
+                Err(RoomConfigError::TimerRange {
+                    game,
+                    min: timer.min_seconds,
+                    max: timer.max_seconds,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid_for_every_game() {
+        for game_type in [GameType::BiggerDice, GameType::TicTacToe] {
+            let config = RoomConfig::resolve(&game_type, RoomSettings::default()).unwrap();
+            assert_eq!(config.player_count, constraints(&game_type).default_players);
+        }
+
+        let config = RoomConfig::resolve(&GameType::TicTacToe, RoomSettings::default()).unwrap();
+        assert_eq!(
+            config.turn_timer_seconds,
+            Some(tic_tac_toe::TURN_TIMER_SECONDS)
+        );
+        let config = RoomConfig::resolve(&GameType::BiggerDice, RoomSettings::default()).unwrap();
+        assert_eq!(config.turn_timer_seconds, None);
+    }
+
+    #[test]
+    fn test_player_count_depends_on_game_type() {
+        let six = RoomSettings {
+            player_count: Some(6),
+            ..Default::default()
+        };
+        assert!(RoomConfig::resolve(&GameType::BiggerDice, six.clone()).is_ok());
+        assert!(matches!(
+            RoomConfig::resolve(&GameType::TicTacToe, six),
+            Err(RoomConfigError::PlayerCount { min: 2, max: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_timer_and_spectator_limits() {
+        let timer = |seconds| RoomSettings {
+            turn_timer_seconds: Some(seconds),
+            ..Default::default()
+        };
+        assert!(RoomConfig::resolve(&GameType::TicTacToe, timer(30)).is_ok());
+        assert!(matches!(
+            RoomConfig::resolve(&GameType::TicTacToe, timer(1)),
+            Err(RoomConfigError::TimerRange { .. })
+        ));
+        assert!(matches!(
+            RoomConfig::resolve(&GameType::BiggerDice, timer(30)),
+            Err(RoomConfigError::TimerNotSupported { .. })
+        ));
+
+        let spectators = RoomSettings {
+            max_spectators: Some(50),
+            ..Default::default()
+        };
+        assert!(matches!(
+            RoomConfig::resolve(&GameType::BiggerDice, spectators),
+            Err(RoomConfigError::SpectatorCap { max: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn test_dice_rules_are_validated_and_only_for_bigger_dice() {
+        let rules = |dice_rules| RoomSettings {
+            dice_rules,
+            ..Default::default()
+        };
+
+        let config = RoomConfig::resolve(&GameType::BiggerDice, RoomSettings::default()).unwrap();
+        assert_eq!(config.dice_rules, Some(BiggerDiceRules::default()));
+
+        let config = RoomConfig::resolve(
+            &GameType::BiggerDice,
+            rules(BiggerDiceRuleSettings {
+                rounds_to_win: Some(3),
+                dice_count: Some(2),
+                tiebreaker: Some(bigger_dice::Tiebreaker::BestOf),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let resolved = config.dice_rules.unwrap();
+        assert_eq!(resolved.rounds_to_win, 3);
+        assert_eq!(resolved.dice_count, 2);
+        assert_eq!(resolved.dice_sides, 6);
+
+        assert!(matches!(
+            RoomConfig::resolve(
+                &GameType::BiggerDice,
+                rules(BiggerDiceRuleSettings {
+                    dice_sides: Some(100),
+                    ..Default::default()
+                })
+            ),
+            Err(RoomConfigError::RuleRange {
+                rule: "dice_sides",
+                ..
+            })
+        ));
+        assert_eq!(
+            RoomConfig::resolve(
+                &GameType::BiggerDice,
+                rules(BiggerDiceRuleSettings {
+                    tiebreaker_roll_offs: Some(4),
+                    ..Default::default()
+                })
+            ),
+            Err(RoomConfigError::EvenRollOffs)
+        );
+        assert!(matches!(
+            RoomConfig::resolve(
+                &GameType::TicTacToe,
+                rules(BiggerDiceRuleSettings {
+                    rounds_to_win: Some(3),
+                    ..Default::default()
+                })
+            ),
+            Err(RoomConfigError::RulesNotSupported { .. })
+        ));
+        assert_eq!(
+            RoomConfig::resolve(&GameType::TicTacToe, RoomSettings::default())
+                .unwrap()
+                .dice_rules,
+            None
+        );
+    }
+
+    #[test]
+    fn test_requested_settings_override_preset() {
+        let preset = RoomSettings {
+            player_count: Some(6),
+            allow_spectators: Some(false),
+            max_spectators: Some(0),
+            turn_timer_seconds: None,
+            ..Default::default()
+        };
+        let requested = RoomSettings {
+            player_count: Some(4),
+            ..Default::default()
+        };
+
+        let config = RoomConfig::resolve(&GameType::BiggerDice, requested.or(preset)).unwrap();
+        assert_eq!(config.player_count, 4);
+        assert!(!config.allow_spectators);
+        assert_eq!(config.max_spectators, 0);
+    }
+}
@@ -0,0 +1,80 @@
+//! Room game type records
+//!
+//! When game commands are partitioned by game (`GAMES_COMMANDS_PARTITIONING`
+//! `composite` or `game_type`) the gateway routes a room's commands by the
+//! room's game type, which most commands do not name. Each room's game type is
+//! written to Redis (`games:room_game_type:{room_id}`) when the room is
+//! created, before anyone learns its id, and rewritten for every open room at
+//! startup. Records expire after a week; rooms are closed by the inactivity
+//! timeout long before that. Without Redis nothing is recorded and the gateway
+//! refuses room commands in those modes rather than misroute them.
+
+use redis::AsyncCommands;
+use sqlx::{Pool, Postgres};
+use tracing::{info, warn};
+
+use crate::app::db_query::read::game_room as game_room_read;
+use crate::database::SharedRedis;
+
+/// Seconds a record is kept
+const RECORD_TTL_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Clone)]
+pub struct RoomGameTypes {
+    redis: Option<SharedRedis>,
+}
+
+impl RoomGameTypes {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    /// Record a new room's game type
+    pub async fn remember(&self, room_id: &str, game_type: &str) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let result: Result<(), redis::RedisError> = redis
+            .set_ex(
+                games_routing::room_game_type_key(room_id),
+                game_type,
+                RECORD_TTL_SECONDS,
+            )
+            .await;
+        if let Err(e) = result {
+            warn!(room_id = %room_id, error = %e, "Failed to record room game type");
+        }
+    }
+
+    /// Rewrite the records of every open room (at startup)
+    pub async fn rebuild_all(&self, db: &Pool<Postgres>) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let rooms = match game_room_read::get_active_room_game_types(db).await {
+            Ok(rooms) => rooms,
+            Err(e) => {
+                warn!(error = %e, "Failed to load rooms for their game type records");
+                return;
+            }
+        };
+
+        let mut pipe = redis::pipe();
+        for (room_id, game_type) in &rooms {
+            pipe.set_ex(
+                games_routing::room_game_type_key(room_id),
+                game_type,
+                RECORD_TTL_SECONDS,
+            )
+            .ignore();
+        }
+
+        let result: Result<(), redis::RedisError> = pipe.query_async(&mut redis).await;
+        match result {
+            Ok(()) => info!(rooms = rooms.len(), "Room game type records rebuilt"),
+            Err(e) => warn!(error = %e, "Failed to rebuild room game type records"),
+        }
+    }
+}
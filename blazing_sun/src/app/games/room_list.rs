@@ -0,0 +1,463 @@
+//! Lobby room list projection
+//!
+//! `list_rooms` is served from a Redis read model instead of Postgres. Every
+//! active room of a game type is stored as JSON in the hash
+//! `games:room_list:{game_type}` (field = room_id). The room list projection
+//! handler refreshes a room's entry from Postgres whenever a room lifecycle
+//! event is published, so lobby refreshes never touch the database.
+//!
+//! A projection is only trusted while its marker `games:room_list:{game_type}:built`
+//! exists. The marker expires after `GAME_ROOM_LIST_PROJECTION_TTL_SECONDS`;
+//! a missing marker is a cache miss and the projection is rebuilt from
+//! Postgres, which also picks up rooms changed by other regions. All
+//! projections are rebuilt at startup. Without Redis, or with a TTL of 0,
+//! room lists are read from Postgres.
+
+use pagination::{Cursor, Direction};
+use redis::AsyncCommands;
+use sqlx::{Pool, Postgres};
+use tracing::{info, warn};
+
+use super::types::{EventEnvelope, GameType};
+use crate::app::db_query::read::game_room::{self as game_room_read, GameRoomRecord};
+use crate::config::GamesConfig;
+use crate::database::SharedRedis;
+
+/// Game types with a lobby room list
+const GAME_TYPES: [GameType; 2] = [GameType::BiggerDice, GameType::TicTacToe];
+
+/// Events after which a room's list entry may be stale
+const LIFECYCLE_EVENTS: &[&str] = &[
+    "room_created",
+    "room_joined",
+    "lobby_joined",
+    "player_left",
+    "player_rejoined",
+    "player_selected",
+    "player_deselected",
+    "selected_players_updated",
+    "player_kicked",
+    "player_banned",
+    "player_unbanned",
+    "user_banned",
+    "removed_from_game",
+    "spectator_joined",
+    "spectator_left",
+    "spectator_kicked",
+    "spectators_updated",
+    "game_started",
+    "game_ended",
+    "bigger_dice.game_over",
+    "tic_tac_toe.match_ended",
+    "tic_tac_toe.match_cancelled",
+    "room_occupancy_changed",
+    "room_migrated",
+    "room_removed",
+];
+
+fn rooms_key(game_type: &str) -> String {
+    format!("games:room_list:{}", game_type)
+}
+
+fn built_key(game_type: &str) -> String {
+    format!("games:room_list:{}:built", game_type)
+}
+
+/// Whether a room belongs in the lobby room list
+pub fn is_listed(record: &GameRoomRecord) -> bool {
+    record.is_active && matches!(record.status.as_str(), "waiting" | "in_progress")
+}
+
+/// Whether a players/lobby JSON array holds the user (ids may be numbers or strings)
+fn json_array_has_user_id(value: &serde_json::Value, user_id: i64) -> bool {
+    value.as_array().is_some_and(|items| {
+        items.iter().any(|item| {
+            let id = item.get("user_id");
+            id.and_then(|id| id.as_i64().or_else(|| id.as_str()?.parse().ok())) == Some(user_id)
+        })
+    })
+}
+
+/// A room as the lobby shows it to a user; `None` when the user may not see it
+/// (banned, or an in-progress room they are not part of). Shared by
+/// `list_rooms`, `search_rooms` and the room search endpoint.
+pub fn list_item_for_user(
+    record: &GameRoomRecord,
+    user_id: i64,
+) -> Option<serde_json::Value> {
+    let is_waiting = record.status == "waiting";
+    let is_in_progress = record.status == "in_progress";
+
+    if record.banned_users.contains(&user_id) {
+        return None;
+    }
+
+    let user_in_room = record.host_id == user_id
+        || record.recorded_players.contains(&user_id)
+        || record.recorded_spectators.contains(&user_id)
+        || record.spectators.contains(&user_id)
+        || json_array_has_user_id(&record.players, user_id)
+        || json_array_has_user_id(&record.lobby, user_id);
+
+    if !is_waiting && !(is_in_progress && user_in_room) {
+        return None;
+    }
+
+    let player_count = record.players.as_array().map(|a| a.len()).unwrap_or(0);
+    let lobby_count = record.lobby.as_array().map(|a| a.len()).unwrap_or(0);
+    let spectator_count = record.spectators.len();
+
+    // Find host name from players or lobby array
+    let host_id = record.host_id;
+    let host_name = record
+        .players
+        .as_array()
+        .and_then(|players| {
+            players.iter().find(|p| {
+                p.get("user_id").and_then(|id| id.as_i64()) == Some(host_id)
+            })
+        })
+        .and_then(|p| p.get("username").and_then(|n| n.as_str()))
+        .or_else(|| {
+            record.lobby.as_array().and_then(|lobby| {
+                lobby.iter().find(|p| {
+                    p.get("user_id").and_then(|id| id.as_i64()) == Some(host_id)
+                })
+            })
+            .and_then(|p| p.get("username").and_then(|n| n.as_str()))
+        })
+        .unwrap_or("Unknown");
+
+    let rejoin_role = if user_in_room {
+        if record.recorded_players.contains(&user_id)
+            || json_array_has_user_id(&record.players, user_id)
+        {
+            Some("player")
+        } else if record.recorded_spectators.contains(&user_id)
+            || record.spectators.contains(&user_id)
+        {
+            Some("spectator")
+        } else if json_array_has_user_id(&record.lobby, user_id) {
+            Some("lobby")
+        } else if record.host_id == user_id {
+            Some("host")
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    Some(serde_json::json!({
+        "room_id": record.room_id,
+        "room_name": record.room_name,
+        "game_type": record.game_type,
+        "host_id": record.host_id,
+        "host_name": host_name,
+        "players": record.players, // Full player array for UI display
+        "lobby": record.lobby,     // Full lobby array
+        "player_count": player_count,
+        "lobby_count": lobby_count,
+        "spectator_count": spectator_count,
+        "max_players": record.player_count, // Use actual player count from room
+        "status": record.status,
+        "is_password_protected": record.is_password_protected,
+        "allow_spectators": record.allow_spectators,
+        "created_at": record.created_at.to_rfc3339(),
+        "can_rejoin": user_in_room,
+        "rejoin_role": rejoin_role,
+    }))
+}
+
+/// The room whose list entry a games.events envelope may have changed
+pub fn affected_room(envelope: &EventEnvelope) -> Option<String> {
+    let event_type = envelope.payload.get("type")?.as_str()?;
+    if !LIFECYCLE_EVENTS.contains(&event_type) {
+        return None;
+    }
+
+    envelope
+        .payload
+        .get("room_id")
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+        .or_else(|| envelope.audience.room_id.clone())
+}
+
+/// Select one page from a projection the way `get_active_rooms_page` does:
+/// keyset on `(created_at, id)`, up to `limit + 1` rooms in query order
+pub fn page_in_query_order(
+    mut rooms: Vec<GameRoomRecord>,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Vec<GameRoomRecord> {
+    let reading_back = matches!(cursor.map(|c| c.direction), Some(Direction::Prev));
+
+    rooms.retain(|room| {
+        let key = (room.created_at, room.id);
+        match cursor {
+            None => true,
+            Some(c) if reading_back => key > (c.created_at, c.id),
+            Some(c) => key < (c.created_at, c.id),
+        }
+    });
+
+    rooms.sort_by_key(|room| (room.created_at, room.id));
+    if !reading_back {
+        rooms.reverse();
+    }
+
+    rooms.truncate(limit.max(0) as usize + 1);
+    rooms
+}
+
+#[derive(Clone)]
+pub struct RoomListProjection {
+    redis: Option<SharedRedis>,
+}
+
+impl RoomListProjection {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    fn connection(&self) -> Option<SharedRedis> {
+        if GamesConfig::room_list_projection_ttl_seconds() == 0 {
+            return None;
+        }
+        self.redis.clone()
+    }
+
+    /// Every listed room of a game type, rebuilding the projection on a miss.
+    /// `None` means there is no projection and Postgres must be asked.
+    pub async fn rooms(
+        &self,
+        db: &Pool<Postgres>,
+        game_type: &str,
+    ) -> Result<Option<Vec<GameRoomRecord>>, sqlx::Error> {
+        let Some(mut redis) = self.connection() else {
+            return Ok(None);
+        };
+
+        let cached: Result<(bool, Vec<String>), redis::RedisError> = redis::pipe()
+            .atomic()
+            .exists(built_key(game_type))
+            .hvals(rooms_key(game_type))
+            .query_async(&mut redis)
+            .await;
+
+        match cached {
+            Ok((true, values)) => Ok(Some(
+                values
+                    .iter()
+                    .filter_map(|value| serde_json::from_str(value).ok())
+                    .collect(),
+            )),
+            Ok((false, _)) => self.rebuild(db, game_type).await.map(Some),
+            Err(e) => {
+                warn!(game_type = %game_type, error = %e, "Failed to read room list projection");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Replace a game type's projection with the rooms in Postgres
+    pub async fn rebuild(
+        &self,
+        db: &Pool<Postgres>,
+        game_type: &str,
+    ) -> Result<Vec<GameRoomRecord>, sqlx::Error> {
+        let rooms = game_room_read::get_all_active_rooms(db, game_type).await?;
+
+        let Some(mut redis) = self.connection() else {
+            return Ok(rooms);
+        };
+
+        let entries: Vec<(String, String)> = rooms
+            .iter()
+            .filter_map(|room| Some((room.room_id.clone(), Self::encode(room)?)))
+            .collect();
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(rooms_key(game_type)).ignore();
+        if !entries.is_empty() {
+            pipe.hset_multiple(rooms_key(game_type), &entries).ignore();
+        }
+        pipe.set_ex(
+            built_key(game_type),
+            1,
+            GamesConfig::room_list_projection_ttl_seconds(),
+        )
+        .ignore();
+
+        let stored: Result<(), redis::RedisError> = pipe.query_async(&mut redis).await;
+        if let Err(e) = stored {
+            warn!(game_type = %game_type, error = %e, "Failed to store room list projection");
+        }
+
+        Ok(rooms)
+    }
+
+    /// Rebuild the projections of every game type (at startup)
+    pub async fn rebuild_all(&self, db: &Pool<Postgres>) {
+        if self.connection().is_none() {
+            return;
+        }
+
+        for game_type in GAME_TYPES {
+            match self.rebuild(db, game_type.as_str()).await {
+                Ok(rooms) => info!(
+                    game_type = %game_type.as_str(),
+                    rooms = rooms.len(),
+                    "Room list projection rebuilt"
+                ),
+                Err(e) => warn!(
+                    game_type = %game_type.as_str(),
+                    error = %e,
+                    "Failed to rebuild room list projection"
+                ),
+            }
+        }
+    }
+
+    /// Re-read one room from Postgres and update or drop its list entry
+    pub async fn refresh_room(&self, db: &Pool<Postgres>, room_id: &str) -> Result<(), sqlx::Error> {
+        let Some(mut redis) = self.connection() else {
+            return Ok(());
+        };
+
+        let room = game_room_read::get_by_room_id(db, room_id).await?;
+
+        let result: Result<(), redis::RedisError> = match room.filter(is_listed) {
+            Some(room) => match Self::encode(&room) {
+                Some(value) => redis.hset(rooms_key(&room.game_type), room_id, value).await,
+                None => Ok(()),
+            },
+            None => {
+                let mut pipe = redis::pipe();
+                for game_type in GAME_TYPES {
+                    pipe.hdel(rooms_key(game_type.as_str()), room_id).ignore();
+                }
+                pipe.query_async(&mut redis).await
+            }
+        };
+
+        if let Err(e) = result {
+            warn!(room_id = %room_id, error = %e, "Failed to update room list projection");
+        }
+
+        Ok(())
+    }
+
+    /// Projection entry of a room; password hashes never leave Postgres
+    fn encode(room: &GameRoomRecord) -> Option<String> {
+        let mut room = room.clone();
+        room.password_hash = None;
+        serde_json::to_string(&room).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).expect("timestamp")
+    }
+
+    fn room(id: i64, created_at: i64) -> GameRoomRecord {
+        serde_json::from_value(json!({
+            "id": id,
+            "room_id": format!("room-{}", id),
+            "room_name": format!("Room {}", id),
+            "game_type": "bigger_dice",
+            "status": "waiting",
+            "host_id": 1,
+            "players": [],
+            "lobby": [],
+            "banned_users": [],
+            "spectators": [],
+            "current_turn": null,
+            "turn_number": 0,
+            "winner_id": null,
+            "is_password_protected": false,
+            "password_hash": null,
+            "is_active": true,
+            "created_at": at(created_at),
+            "started_at": null,
+            "finished_at": null,
+            "updated_at": at(created_at),
+            "player_count": 2,
+            "allow_spectators": true,
+            "max_spectators": 10,
+            "admin_spectator_id": null,
+            "lobby_chat_enabled": true,
+            "spectators_data": [],
+            "recorded_players": [],
+            "recorded_spectators": [],
+            "selected_players": [],
+            "auto_players": []
+        }))
+        .expect("room record")
+    }
+
+    fn ids(rooms: &[GameRoomRecord]) -> Vec<i64> {
+        rooms.iter().map(|r| r.id).collect()
+    }
+
+    #[test]
+    fn pages_match_the_postgres_keyset_order() {
+        let rooms = vec![room(1, 100), room(3, 300), room(2, 200), room(4, 300)];
+
+        // First page: newest first, one extra row to detect the next page
+        assert_eq!(ids(&page_in_query_order(rooms.clone(), None, 2)), vec![4, 3, 2]);
+
+        let older = Cursor { created_at: at(300), id: 3, direction: Direction::Next };
+        assert_eq!(ids(&page_in_query_order(rooms.clone(), Some(&older), 2)), vec![2, 1]);
+
+        // Reading back returns oldest first, like the ASC query
+        let newer = Cursor { created_at: at(200), id: 2, direction: Direction::Prev };
+        assert_eq!(ids(&page_in_query_order(rooms, Some(&newer), 1)), vec![3, 4]);
+    }
+
+    #[test]
+    fn only_lifecycle_events_touch_the_projection() {
+        let envelope = |payload: serde_json::Value| -> EventEnvelope {
+            serde_json::from_value(json!({
+                "event_id": "e1",
+                "event_type": "games.event.x",
+                "timestamp": "2026-01-01T00:00:00Z",
+                "producer": "blazing_sun",
+                "actor": { "user_id": 1, "username": "a", "socket_id": "", "roles": [] },
+                "audience": { "type": "room", "room_id": "from-audience" },
+                "payload": payload
+            }))
+            .expect("envelope")
+        };
+
+        assert_eq!(
+            affected_room(&envelope(json!({ "type": "lobby_joined", "room_id": "r1" }))),
+            Some("r1".to_string())
+        );
+        assert_eq!(
+            affected_room(&envelope(json!({ "type": "spectators_updated" }))),
+            Some("from-audience".to_string())
+        );
+        assert_eq!(affected_room(&envelope(json!({ "type": "bigger_dice.rolled", "room_id": "r1" }))), None);
+    }
+
+    #[test]
+    fn finished_and_inactive_rooms_are_not_listed() {
+        let mut record = room(1, 100);
+        assert!(is_listed(&record));
+
+        record.status = "finished".to_string();
+        assert!(!is_listed(&record));
+
+        record.status = "in_progress".to_string();
+        record.is_active = false;
+        assert!(!is_listed(&record));
+    }
+}
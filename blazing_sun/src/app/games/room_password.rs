@@ -0,0 +1,109 @@
+//! Room passwords
+//!
+//! Room passwords are hashed with argon2id using the crate's default
+//! parameters (19 MiB memory, 2 iterations, 1 lane - the OWASP baseline) and
+//! stored as PHC strings in `game_rooms.password_hash`. Rooms created before
+//! the switch still hold bcrypt hashes; those keep verifying and are reported
+//! as needing a rehash so the join handler can upgrade them on the next
+//! successful join.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params};
+
+/// Result of checking a password against a stored hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    Invalid,
+    Valid,
+    /// Correct password, but the hash is bcrypt or uses outdated parameters
+    ValidNeedsRehash,
+}
+
+impl Verification {
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, Verification::Invalid)
+    }
+}
+
+/// Hash a room password (None for an empty password, i.e. a public room)
+pub fn hash(password: &str) -> Option<String> {
+    if password.is_empty() {
+        return None;
+    }
+
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .ok()
+        .map(|hash| hash.to_string())
+}
+
+pub fn verify(password: &str, stored: &str) -> Verification {
+    if is_bcrypt(stored) {
+        return if bcrypt::verify(password, stored).unwrap_or(false) {
+            Verification::ValidNeedsRehash
+        } else {
+            Verification::Invalid
+        };
+    }
+
+    let Ok(parsed) = PasswordHash::new(stored) else {
+        return Verification::Invalid;
+    };
+
+    if Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_err()
+    {
+        return Verification::Invalid;
+    }
+
+    let defaults = Params::default();
+    let current = parsed.algorithm.as_str() == Algorithm::Argon2id.as_str()
+        && Params::try_from(&parsed).is_ok_and(|params| {
+            params.m_cost() == defaults.m_cost()
+                && params.t_cost() == defaults.t_cost()
+                && params.p_cost() == defaults.p_cost()
+        });
+
+    if current {
+        Verification::Valid
+    } else {
+        Verification::ValidNeedsRehash
+    }
+}
+
+fn is_bcrypt(stored: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| stored.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argon2_hashes_round_trip() {
+        let stored = hash("hunter2").expect("hash");
+
+        assert!(stored.starts_with("$argon2id$"));
+        assert_eq!(verify("hunter2", &stored), Verification::Valid);
+        assert_eq!(verify("hunter3", &stored), Verification::Invalid);
+    }
+
+    #[test]
+    fn empty_password_means_public_room() {
+        assert_eq!(hash(""), None);
+    }
+
+    #[test]
+    fn legacy_bcrypt_hashes_verify_and_ask_for_rehash() {
+        let legacy = bcrypt::hash("hunter2", 4).expect("bcrypt");
+
+        assert_eq!(verify("hunter2", &legacy), Verification::ValidNeedsRehash);
+        assert_eq!(verify("nope", &legacy), Verification::Invalid);
+        assert_eq!(verify("hunter2", "garbage"), Verification::Invalid);
+    }
+}
@@ -0,0 +1,169 @@
+//! Scheduled game rooms
+//!
+//! A host can create a room that opens later (`scheduled_start_at`). Until
+//! then the room is `scheduled`: it is not in the lobby list and cannot be
+//! joined, but users can register for it over REST. A periodic sweep reminds
+//! the registered users `GAME_ROOM_SCHEDULE_REMINDER_MINUTES` before the start
+//! and moves due rooms to `waiting`. Sent reminders are recorded in Postgres,
+//! so each goes out once across restarts and instances; a sweep that finds
+//! several reminders due at once only sends the nearest.
+
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+
+use crate::app::db_query::read::game_room::ScheduledRoom;
+
+/// Why a start time cannot be scheduled
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ScheduleError {
+    #[error("Scheduled rooms are not available")]
+    Disabled,
+    #[error("The start time must be at least {0} minutes from now")]
+    TooSoon(i64),
+    #[error("The start time can be at most {0} days from now")]
+    TooFarAhead(i64),
+}
+
+/// Check a requested start time against the lead time and horizon
+pub fn validate_start(
+    now: DateTime<Utc>,
+    start_at: DateTime<Utc>,
+    min_lead_minutes: i64,
+    max_days: i64,
+) -> Result<(), ScheduleError> {
+    if max_days <= 0 {
+        return Err(ScheduleError::Disabled);
+    }
+    if start_at < now + Duration::minutes(min_lead_minutes) {
+        return Err(ScheduleError::TooSoon(min_lead_minutes));
+    }
+    if start_at > now + Duration::days(max_days) {
+        return Err(ScheduleError::TooFarAhead(max_days));
+    }
+    Ok(())
+}
+
+/// Reminders (minutes before the start) whose time has come, for a room that
+/// has not opened yet
+pub fn due_reminders(
+    now: DateTime<Utc>,
+    start_at: DateTime<Utc>,
+    reminder_minutes: &[i64],
+) -> Vec<i64> {
+    if now >= start_at {
+        return Vec::new();
+    }
+
+    reminder_minutes
+        .iter()
+        .copied()
+        .filter(|minutes| start_at - Duration::minutes(*minutes) <= now)
+        .collect()
+}
+
+/// Escape text for an iCalendar property value
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn ics_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Scheduled rooms as an iCalendar feed, one event per room
+pub fn to_ics(rooms: &[ScheduledRoom], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Blazing Sun//Game Rooms//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for room in rooms {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@game-rooms", room.room_id),
+            format!("DTSTAMP:{}", ics_time(now)),
+            format!("DTSTART:{}", ics_time(room.scheduled_start_at)),
+            format!("SUMMARY:{}", ics_text(&room.room_name)),
+            format!(
+                "DESCRIPTION:{}",
+                ics_text(&format!(
+                    "{} hosted by {} ({} players)",
+                    room.game_type, room.host_name, room.player_count
+                ))
+            ),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    // iCalendar lines end with CRLF
+    lines.join("\r\n") + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_must_fit_the_window() {
+        let now = Utc::now();
+        assert_eq!(validate_start(now, now + Duration::hours(2), 5, 30), Ok(()));
+        assert_eq!(
+            validate_start(now, now + Duration::minutes(2), 5, 30),
+            Err(ScheduleError::TooSoon(5))
+        );
+        assert_eq!(
+            validate_start(now, now + Duration::days(31), 5, 30),
+            Err(ScheduleError::TooFarAhead(30))
+        );
+        assert_eq!(
+            validate_start(now, now + Duration::hours(2), 5, 0),
+            Err(ScheduleError::Disabled)
+        );
+    }
+
+    #[test]
+    fn test_reminders_fall_due_before_the_start() {
+        let start = Utc::now() + Duration::hours(2);
+        let reminders = [60, 10];
+
+        assert!(due_reminders(start - Duration::minutes(61), start, &reminders).is_empty());
+        assert_eq!(
+            due_reminders(start - Duration::minutes(30), start, &reminders),
+            vec![60]
+        );
+        assert_eq!(
+            due_reminders(start - Duration::minutes(5), start, &reminders),
+            vec![60, 10]
+        );
+        assert!(due_reminders(start, start, &reminders).is_empty());
+    }
+
+    #[test]
+    fn test_ics_lists_one_event_per_room() {
+        let start = "2026-10-20T18:30:00Z".parse().unwrap();
+        let room = ScheduledRoom {
+            room_id: "r-1".to_string(),
+            room_name: "Friday, dice night".to_string(),
+            game_type: "bigger_dice".to_string(),
+            host_id: 1,
+            host_name: "Ana".to_string(),
+            scheduled_start_at: start,
+            is_password_protected: false,
+            player_count: 4,
+            registered_count: 2,
+        };
+
+        let ics = to_ics(&[room], start);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART:20261020T183000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Friday\\, dice night\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
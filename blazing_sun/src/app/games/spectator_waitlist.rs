@@ -0,0 +1,324 @@
+//! Spectator waiting list
+//!
+//! When a room's spectator seats are all taken, `join_as_spectator` queues the
+//! user instead of rejecting them. Each room has a sorted set
+//! `games:spectator_waitlist:{room_id}` (member = user_id, score = enqueue
+//! time in ms, i.e. queue order) and a hash
+//! `games:spectator_waitlist:{room_id}:entries` with the JSON entry of every
+//! queued user. A user who asks to spectate again while queued keeps their
+//! place and refreshes their entry.
+//!
+//! Entries expire `GAME_SPECTATOR_WAITLIST_TTL_SECONDS` after the user last
+//! asked to spectate; expired entries are pruned whenever the list is read.
+//! The queue holds at most `GAME_SPECTATOR_WAITLIST_MAX_SIZE` users (0
+//! disables the waitlist). Without Redis the waitlist is disabled and full
+//! rooms reject spectators as before.
+//!
+//! Seats are refilled from the queue after every `spectator_left` event (see
+//! the spectator waitlist handler), and queued users are told their new place.
+
+use chrono::{DateTime, Duration, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::types::EventEnvelope;
+use crate::config::GamesConfig;
+use crate::database::SharedRedis;
+
+fn queue_key(room_id: &str) -> String {
+    format!("games:spectator_waitlist:{}", room_id)
+}
+
+fn entries_key(room_id: &str) -> String {
+    format!("games:spectator_waitlist:{}:entries", room_id)
+}
+
+/// A user waiting for a spectator seat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitlistEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_id: Option<i64>,
+    /// Socket the user asked from (errors are addressed to it)
+    pub socket_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WaitlistEntry {
+    pub fn new(user_id: i64, username: &str, avatar_id: Option<i64>, socket_id: &str) -> Self {
+        Self {
+            user_id,
+            username: username.to_string(),
+            avatar_id,
+            socket_id: socket_id.to_string(),
+            expires_at: Utc::now()
+                + Duration::seconds(GamesConfig::spectator_waitlist_ttl_seconds() as i64),
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Result of asking to join a room's waitlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaitlistJoin {
+    /// Queued (or already queued) at `position` (1 = next) of `size`
+    Queued { position: u32, size: u32 },
+    /// The waitlist already holds `GAME_SPECTATOR_WAITLIST_MAX_SIZE` users
+    Full,
+}
+
+/// Whether a new user fits into a waitlist of `size` users
+pub fn has_room(size: u32, max_size: u32) -> bool {
+    size < max_size
+}
+
+/// Room in which a game event freed a spectator seat
+pub fn freed_seat(envelope: &EventEnvelope) -> Option<String> {
+    if envelope.payload.get("type")?.as_str()? != "spectator_left" {
+        return None;
+    }
+
+    envelope
+        .payload
+        .get("room_id")
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+        .or_else(|| envelope.audience.room_id.clone())
+}
+
+#[derive(Clone)]
+pub struct SpectatorWaitlist {
+    redis: Option<SharedRedis>,
+}
+
+impl SpectatorWaitlist {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    /// Whether full rooms queue spectators at all
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some() && GamesConfig::spectator_waitlist_max_size() > 0
+    }
+
+    /// Queue the user, or refresh their entry when they are already queued.
+    /// `None` when the waitlist is disabled or Redis failed.
+    pub async fn join(&self, room_id: &str, entry: &WaitlistEntry) -> Option<WaitlistJoin> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut redis = self.redis.clone()?;
+
+        let result: Result<Option<WaitlistJoin>, redis::RedisError> = async {
+            let queued: Option<u32> = redis.zrank(queue_key(room_id), entry.user_id).await?;
+            let size: u32 = redis.zcard(queue_key(room_id)).await?;
+            if queued.is_none() && !has_room(size, GamesConfig::spectator_waitlist_max_size()) {
+                return Ok(Some(WaitlistJoin::Full));
+            }
+
+            let ttl = GamesConfig::spectator_waitlist_ttl_seconds() as i64;
+            let json = serde_json::to_string(entry).unwrap_or_default();
+            let _: () = redis::pipe()
+                .atomic()
+                .cmd("ZADD")
+                .arg(queue_key(room_id))
+                .arg("NX")
+                .arg(Utc::now().timestamp_millis())
+                .arg(entry.user_id)
+                .ignore()
+                .hset(entries_key(room_id), entry.user_id, json)
+                .ignore()
+                .expire(queue_key(room_id), ttl)
+                .ignore()
+                .expire(entries_key(room_id), ttl)
+                .ignore()
+                .query_async(&mut redis)
+                .await?;
+
+            let position: Option<u32> = redis.zrank(queue_key(room_id), entry.user_id).await?;
+            let size: u32 = redis.zcard(queue_key(room_id)).await?;
+            Ok(position.map(|rank| WaitlistJoin::Queued {
+                position: rank + 1,
+                size,
+            }))
+        }
+        .await;
+
+        result.unwrap_or_else(|e| {
+            warn!(room_id = %room_id, user_id = entry.user_id, error = %e, "Failed to join spectator waitlist");
+            None
+        })
+    }
+
+    /// Queued users in order, after dropping expired entries (returned second)
+    pub async fn entries(&self, room_id: &str) -> (Vec<WaitlistEntry>, Vec<WaitlistEntry>) {
+        let Some(mut redis) = self.redis.clone() else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let result: Result<(Vec<WaitlistEntry>, Vec<WaitlistEntry>), redis::RedisError> = async {
+            let user_ids: Vec<i64> = redis.zrange(queue_key(room_id), 0, -1).await?;
+            if user_ids.is_empty() {
+                return Ok((Vec::new(), Vec::new()));
+            }
+            let values: Vec<Option<String>> = redis::cmd("HMGET")
+                .arg(entries_key(room_id))
+                .arg(&user_ids)
+                .query_async(&mut redis)
+                .await?;
+
+            let now = Utc::now();
+            let mut queued = Vec::new();
+            let mut expired = Vec::new();
+            let mut stale = Vec::new();
+            for (user_id, value) in user_ids.into_iter().zip(values) {
+                match value.and_then(|v| serde_json::from_str::<WaitlistEntry>(&v).ok()) {
+                    Some(entry) if entry.is_expired(now) => {
+                        stale.push(user_id);
+                        expired.push(entry);
+                    }
+                    Some(entry) => queued.push(entry),
+                    None => stale.push(user_id),
+                }
+            }
+
+            if !stale.is_empty() {
+                let _: () = redis::pipe()
+                    .atomic()
+                    .zrem(queue_key(room_id), &stale)
+                    .ignore()
+                    .hdel(entries_key(room_id), &stale)
+                    .ignore()
+                    .query_async(&mut redis)
+                    .await?;
+            }
+            Ok((queued, expired))
+        }
+        .await;
+
+        result.unwrap_or_else(|e| {
+            warn!(room_id = %room_id, error = %e, "Failed to read spectator waitlist");
+            (Vec::new(), Vec::new())
+        })
+    }
+
+    /// Take the next user off the queue
+    pub async fn pop_next(&self, room_id: &str) -> Option<WaitlistEntry> {
+        let mut redis = self.redis.clone()?;
+
+        loop {
+            let popped: Vec<(i64, f64)> = match redis.zpopmin(queue_key(room_id), 1).await {
+                Ok(popped) => popped,
+                Err(e) => {
+                    warn!(room_id = %room_id, error = %e, "Failed to pop spectator waitlist");
+                    return None;
+                }
+            };
+            let (user_id, _) = popped.into_iter().next()?;
+
+            let value: Option<String> = redis.hget(entries_key(room_id), user_id).await.ok()?;
+            let _: Result<(), redis::RedisError> = redis.hdel(entries_key(room_id), user_id).await;
+
+            match value.and_then(|v| serde_json::from_str::<WaitlistEntry>(&v).ok()) {
+                Some(entry) if !entry.is_expired(Utc::now()) => return Some(entry),
+                // Expired or unreadable entries are skipped
+                _ => continue,
+            }
+        }
+    }
+
+    /// Take a user off the queue (they joined the room another way)
+    pub async fn remove(&self, room_id: &str, user_id: i64) {
+        if let Some(mut redis) = self.redis.clone() {
+            let result: Result<(), redis::RedisError> = redis::pipe()
+                .atomic()
+                .zrem(queue_key(room_id), user_id)
+                .ignore()
+                .hdel(entries_key(room_id), user_id)
+                .ignore()
+                .query_async(&mut redis)
+                .await;
+            if let Err(e) = result {
+                warn!(room_id = %room_id, user_id = user_id, error = %e, "Failed to leave spectator waitlist");
+            }
+        }
+    }
+
+    /// Drop a room's whole queue (the room closed)
+    pub async fn clear(&self, room_id: &str) {
+        if let Some(mut redis) = self.redis.clone() {
+            let result: Result<(), redis::RedisError> =
+                redis.del(&[queue_key(room_id), entries_key(room_id)]).await;
+            if let Err(e) = result {
+                warn!(room_id = %room_id, error = %e, "Failed to clear spectator waitlist");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{Actor, Audience};
+    use serde_json::{json, Value};
+
+    fn entry(expires_at: DateTime<Utc>) -> WaitlistEntry {
+        WaitlistEntry {
+            user_id: 7,
+            username: "alice".to_string(),
+            avatar_id: None,
+            socket_id: "socket-1".to_string(),
+            expires_at,
+        }
+    }
+
+    fn envelope(payload: Value) -> EventEnvelope {
+        EventEnvelope {
+            event_id: "e-1".to_string(),
+            event_type: "games.event.bigger_dice.spectator_left".to_string(),
+            timestamp: "2026-10-17T12:00:00Z".to_string(),
+            correlation_id: None,
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::room("r-1"),
+            payload,
+        }
+    }
+
+    #[test]
+    fn only_departing_spectators_free_a_seat() {
+        let left = envelope(json!({"type": "spectator_left", "room_id": "r-2", "user_id": 7}));
+        assert_eq!(freed_seat(&left), Some("r-2".to_string()));
+
+        let joined = envelope(json!({"type": "spectator_joined", "room_id": "r-2", "user_id": 7}));
+        assert_eq!(freed_seat(&joined), None);
+
+        let no_room = envelope(json!({"type": "spectator_left", "user_id": 7}));
+        assert_eq!(freed_seat(&no_room), Some("r-1".to_string()));
+    }
+
+    #[test]
+    fn entries_expire_at_their_deadline() {
+        let now = Utc::now();
+        assert!(!entry(now + Duration::seconds(1)).is_expired(now));
+        assert!(entry(now).is_expired(now));
+        assert!(entry(now - Duration::seconds(1)).is_expired(now));
+    }
+
+    #[test]
+    fn waitlist_takes_users_until_it_is_full() {
+        assert!(has_room(0, 50));
+        assert!(has_room(49, 50));
+        assert!(!has_room(50, 50));
+        assert!(!has_room(0, 0));
+    }
+}
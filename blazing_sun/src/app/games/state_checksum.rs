@@ -0,0 +1,177 @@
+//! Game state checksums and desync detection
+//!
+//! `room_state` and `turn_changed` events carry a `state_checksum` of the
+//! room's canonical state. Clients compute the same checksum from the state
+//! they rendered and send `report_desync` when the two differ; the server
+//! then resends the authoritative `room_state` to that user.
+//!
+//! The canonical state is the pipe-joined text
+//! `room_id|status|turn_number|current_turn|winner_id|player_ids`, with `-`
+//! for a missing turn or winner and the player IDs sorted ascending and
+//! comma-joined, e.g. `r-1|in_progress|3|42|-|7,42`. The checksum is the
+//! first 16 hex digits of its SHA-256.
+//!
+//! Desyncs are counted per game type in this process so reducers that drift
+//! can be found (`GET /api/v1/admin/games/desyncs`).
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::app::games::types::{GameEvent, GameRoom, RoomStatus};
+
+/// Hex digits of the SHA-256 kept in a checksum
+const CHECKSUM_LEN: usize = 16;
+
+fn status_name(status: &RoomStatus) -> &'static str {
+    match status {
+        RoomStatus::Scheduled => "scheduled",
+        RoomStatus::Waiting => "waiting",
+        RoomStatus::InProgress => "in_progress",
+        RoomStatus::Finished => "finished",
+        RoomStatus::Abandoned => "abandoned",
+    }
+}
+
+/// The text the checksum is taken of
+pub fn canonical_state(room: &GameRoom) -> String {
+    let optional = |id: Option<i64>| id.map_or_else(|| "-".to_string(), |id| id.to_string());
+
+    let mut player_ids: Vec<i64> = room.players.iter().map(|p| p.user_id).collect();
+    player_ids.sort_unstable();
+    let player_ids = player_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        "{}|{}|{}|{}|{}|{}",
+        room.room_id,
+        status_name(&room.status),
+        room.turn_number,
+        optional(room.current_turn),
+        optional(room.winner_id),
+        player_ids
+    )
+}
+
+/// Checksum of the room's canonical state
+pub fn checksum(room: &GameRoom) -> String {
+    let digest = Sha256::digest(canonical_state(room).as_bytes());
+    let mut checksum = hex::encode(digest);
+    checksum.truncate(CHECKSUM_LEN);
+    checksum
+}
+
+/// Stamp the turn events of a batch with the checksum of the room the batch
+/// left behind; a turn change is always the last event of its batch
+pub fn stamp(events: &mut [GameEvent], room: &GameRoom) {
+    let room_checksum = checksum(room);
+    for event in events {
+        if let GameEvent::TurnChanged { state_checksum, .. } = event {
+            *state_checksum = Some(room_checksum.clone());
+        }
+    }
+}
+
+/// Desync reports of one game type
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DesyncStats {
+    pub game_type: String,
+    /// Reports whose checksum differed from the server's; a snapshot was resent
+    pub desyncs: u64,
+    /// Reports that matched the server by the time they arrived
+    pub stale_reports: u64,
+}
+
+static DESYNCS: Lazy<Mutex<HashMap<String, DesyncStats>>> = Lazy::new(Default::default);
+
+/// Count a desync report of a game type
+pub fn record_report(game_type: &str, desynced: bool) {
+    let mut desyncs = DESYNCS.lock().unwrap();
+    let stats = desyncs
+        .entry(game_type.to_string())
+        .or_insert_with(|| DesyncStats {
+            game_type: game_type.to_string(),
+            ..Default::default()
+        });
+    if desynced {
+        stats.desyncs += 1;
+    } else {
+        stats.stale_reports += 1;
+    }
+}
+
+/// Counters of every game type with reports, most desyncs first
+pub fn desync_stats() -> Vec<DesyncStats> {
+    let mut stats: Vec<DesyncStats> = DESYNCS.lock().unwrap().values().cloned().collect();
+    stats.sort_by(|a, b| b.desyncs.cmp(&a.desyncs).then(a.game_type.cmp(&b.game_type)));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{GamePlayer, GameType};
+    use chrono::Utc;
+
+    fn player(user_id: i64) -> GamePlayer {
+        GamePlayer {
+            user_id,
+            username: format!("user{}", user_id),
+            avatar_id: None,
+            score: 0,
+            is_ready: true,
+            joined_at: Utc::now(),
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn canonical_state_ignores_player_order() {
+        let mut room = GameRoom::new("r-1", "Room", GameType::BiggerDice, 42);
+        room.status = RoomStatus::InProgress;
+        room.turn_number = 3;
+        room.current_turn = Some(42);
+        room.players = vec![player(42), player(7)];
+
+        assert_eq!(canonical_state(&room), "r-1|in_progress|3|42|-|7,42");
+
+        let before = checksum(&room);
+        room.players.reverse();
+        assert_eq!(checksum(&room), before);
+        assert_eq!(before.len(), CHECKSUM_LEN);
+
+        room.turn_number += 1;
+        assert_ne!(checksum(&room), before);
+    }
+
+    #[test]
+    fn only_turn_events_are_stamped() {
+        let room = GameRoom::new("r-1", "Room", GameType::TicTacToe, 42);
+        let mut events = vec![
+            GameEvent::TurnChanged {
+                room_id: "r-1".to_string(),
+                current_turn: 42,
+                turn_number: 1,
+                state_checksum: None,
+            },
+            GameEvent::RoomState {
+                room: room.clone(),
+                state_checksum: String::new(),
+                version: 1,
+            },
+        ];
+
+        stamp(&mut events, &room);
+        assert!(matches!(
+            &events[0],
+            GameEvent::TurnChanged { state_checksum: Some(sum), .. } if *sum == checksum(&room)
+        ));
+        assert!(matches!(&events[1], GameEvent::RoomState { state_checksum, .. } if state_checksum.is_empty()));
+    }
+}
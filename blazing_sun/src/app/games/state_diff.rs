@@ -0,0 +1,258 @@
+//! Room state diffs
+//!
+//! A `room_state` carries the whole room. Once a user holds a room state, later
+//! changes are sent to them as `room_state_diff`: JSON-patch style operations
+//! (`add`, `remove`, `replace` with RFC 6901 paths) that turn the state they
+//! hold into the current one.
+//!
+//! Room states are sent per user, so the log keeps the last state each user
+//! was sent. Every state sent to a user gets the next `version` of that user's
+//! copy, and a diff names the `base_version` it applies to. A client whose
+//! version differs from a diff's base missed an update; it drops the diff and
+//! asks for a snapshot with `get_room_state`.
+//!
+//! Arrays are compared element by element while their length is unchanged and
+//! replaced whole otherwise. When the operations would be larger than the
+//! state itself, a snapshot is sent instead.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One change of a JSON document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Operations turning `old` into `new`
+pub fn diff(old: &Value, new: &Value) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    diff_at("", old, new, &mut ops);
+    ops
+}
+
+fn diff_at(path: &str, old: &Value, new: &Value, ops: &mut Vec<PatchOp>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_objects(path, old, new, ops),
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                diff_at(&format!("{}/{}", path, index), old, new, ops);
+            }
+        }
+        _ if old != new => ops.push(PatchOp::Replace {
+            path: path.to_string(),
+            value: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_objects(path: &str, old: &Map<String, Value>, new: &Map<String, Value>, ops: &mut Vec<PatchOp>) {
+    for (key, old_value) in old {
+        let child = format!("{}/{}", path, escape(key));
+        match new.get(key) {
+            Some(new_value) => diff_at(&child, old_value, new_value, ops),
+            None => ops.push(PatchOp::Remove { path: child }),
+        }
+    }
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            ops.push(PatchOp::Add {
+                path: format!("{}/{}", path, escape(key)),
+                value: new_value.clone(),
+            });
+        }
+    }
+}
+
+/// Escape a key as a JSON pointer segment
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// What to send a user for the room's current state
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateUpdate {
+    /// The whole state, as `version` of the user's copy
+    Snapshot { version: u64 },
+    /// Operations from the user's `base_version` to `version`
+    Diff {
+        base_version: u64,
+        version: u64,
+        ops: Vec<PatchOp>,
+    },
+    /// The user already holds this state
+    Unchanged,
+}
+
+/// A state sent to a user
+#[derive(Debug, Clone)]
+struct SentState {
+    version: u64,
+    state: Value,
+}
+
+/// Last room state sent to each user, per room
+#[derive(Debug, Default)]
+pub struct RoomStateLog {
+    rooms: HashMap<String, HashMap<i64, SentState>>,
+}
+
+impl RoomStateLog {
+    /// Record a full state sent to a user; returns its version
+    pub fn snapshot(&mut self, room_id: &str, user_id: i64, state: Value) -> u64 {
+        let users = self.rooms.entry(room_id.to_string()).or_default();
+        let version = users.get(&user_id).map_or(1, |sent| sent.version + 1);
+        users.insert(user_id, SentState { version, state });
+        version
+    }
+
+    /// Record the state a user is about to be sent and say how to send it
+    pub fn update(&mut self, room_id: &str, user_id: i64, state: Value) -> StateUpdate {
+        let Some(sent) = self.rooms.get(room_id).and_then(|users| users.get(&user_id)) else {
+            let version = self.snapshot(room_id, user_id, state);
+            return StateUpdate::Snapshot { version };
+        };
+
+        let ops = diff(&sent.state, &state);
+        if ops.is_empty() {
+            return StateUpdate::Unchanged;
+        }
+
+        let base_version = sent.version;
+        if encoded_len(&ops) >= encoded_len(&state) {
+            let version = self.snapshot(room_id, user_id, state);
+            return StateUpdate::Snapshot { version };
+        }
+
+        let version = self.snapshot(room_id, user_id, state);
+        StateUpdate::Diff {
+            base_version,
+            version,
+            ops,
+        }
+    }
+
+    /// Drop the states of a room that was removed
+    pub fn forget(&mut self, room_id: &str) {
+        self.rooms.remove(room_id);
+    }
+}
+
+fn encoded_len<T: Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map_or(usize::MAX, |bytes| bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Apply operations the way clients do
+    fn apply(doc: &mut Value, ops: &[PatchOp]) {
+        for op in ops {
+            let (path, value) = match op {
+                PatchOp::Add { path, value } | PatchOp::Replace { path, value } => (path, Some(value.clone())),
+                PatchOp::Remove { path } => (path, None),
+            };
+            if path.is_empty() {
+                *doc = value.unwrap();
+                continue;
+            }
+            let (parent, key) = path.rsplit_once('/').unwrap();
+            let key = key.replace("~1", "/").replace("~0", "~");
+            let target = doc.pointer_mut(parent).unwrap();
+            match (target, value) {
+                (Value::Object(map), Some(value)) => {
+                    map.insert(key, value);
+                }
+                (Value::Object(map), None) => {
+                    map.remove(&key);
+                }
+                (Value::Array(items), Some(value)) => items[key.parse::<usize>().unwrap()] = value,
+                _ => panic!("unsupported operation {:?}", op),
+            }
+        }
+    }
+
+    #[test]
+    fn diffs_turn_the_old_state_into_the_new_one() {
+        let old = json!({
+            "status": "waiting",
+            "players": [{"user_id": 1, "is_ready": false}, {"user_id": 2, "is_ready": false}],
+            "spectators": [7],
+            "a/b": 1,
+            "winner_id": null
+        });
+        let new = json!({
+            "status": "in_progress",
+            "players": [{"user_id": 1, "is_ready": true}, {"user_id": 2, "is_ready": false}],
+            "spectators": [7, 8],
+            "turn_number": 1
+        });
+
+        let ops = diff(&old, &new);
+        assert!(ops.contains(&PatchOp::Replace {
+            path: "/players/0/is_ready".to_string(),
+            value: json!(true)
+        }));
+        assert!(ops.contains(&PatchOp::Replace {
+            path: "/spectators".to_string(),
+            value: json!([7, 8])
+        }));
+        assert!(ops.contains(&PatchOp::Remove {
+            path: "/a~1b".to_string()
+        }));
+
+        let mut patched = old.clone();
+        apply(&mut patched, &ops);
+        assert_eq!(patched, new);
+        assert!(diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn versions_are_counted_per_user() {
+        let mut log = RoomStateLog::default();
+        let state = json!({"status": "waiting", "players": [1, 2], "room_name": "A long enough room name"});
+
+        assert_eq!(log.update("r-1", 1, state.clone()), StateUpdate::Snapshot { version: 1 });
+        assert_eq!(log.snapshot("r-1", 2, state.clone()), 1);
+        assert_eq!(log.update("r-1", 1, state.clone()), StateUpdate::Unchanged);
+
+        let started = json!({"status": "in_progress", "players": [1, 2], "room_name": "A long enough room name"});
+        assert_eq!(
+            log.update("r-1", 1, started.clone()),
+            StateUpdate::Diff {
+                base_version: 1,
+                version: 2,
+                ops: vec![PatchOp::Replace {
+                    path: "/status".to_string(),
+                    value: json!("in_progress")
+                }],
+            }
+        );
+        assert!(matches!(
+            log.update("r-1", 2, started),
+            StateUpdate::Diff { base_version: 1, version: 2, .. }
+        ));
+
+        log.forget("r-1");
+        assert_eq!(log.update("r-1", 1, state), StateUpdate::Snapshot { version: 1 });
+    }
+
+    #[test]
+    fn large_changes_are_sent_as_snapshots() {
+        let mut log = RoomStateLog::default();
+        log.snapshot("r-1", 1, json!({"a": 1}));
+
+        assert_eq!(
+            log.update("r-1", 1, json!({"b": 2})),
+            StateUpdate::Snapshot { version: 2 }
+        );
+    }
+}
@@ -5,7 +5,7 @@
 //! - First game: Random player gets X, X always goes first
 //! - After each game (win or draw): Turn order reverses
 //! - Draw: No points awarded, move to next game
-//! - Turn timer: 60 seconds per move
+//! - Turn timer: 60 seconds per move by default (15-300, chosen at room creation)
 //! - Timer expiry: Player forfeits that game (opponent +1 point)
 //! - Entry fee: 1000 coins per player
 //! - Winner prize: 60% of pool (1200 coins)
@@ -16,6 +16,7 @@
 //! - One player disconnected 10+ min: Other player wins
 //! - Both players disconnected 10+ min: Both refunded 990 coins
 
+use super::room_config::{RoomConstraints, TimerRange};
 use super::types::{GameEvent, GameRoom, GameTurn, RoomStatus};
 use chrono::{DateTime, Duration, Utc};
 use rand::Rng;
@@ -26,9 +27,22 @@ use tracing::info;
 /// Win score for Tic Tac Toe match (first to 5)
 pub const WIN_SCORE: i32 = 5;
 
-/// Turn timer in seconds
+/// Default turn timer in seconds
 pub const TURN_TIMER_SECONDS: i64 = 60;
 
+/// Room settings Tic Tac Toe can be played with
+pub const ROOM_CONSTRAINTS: RoomConstraints = RoomConstraints {
+    min_players: 2,
+    max_players: 2,
+    default_players: 2,
+    max_spectators: 10,
+    turn_timer: Some(TimerRange {
+        min_seconds: 15,
+        max_seconds: 300,
+        default_seconds: TURN_TIMER_SECONDS,
+    }),
+};
+
 /// Disconnection timeout in minutes
 pub const DISCONNECT_TIMEOUT_MINUTES: i64 = 10;
 
@@ -67,8 +81,11 @@ pub struct TicTacToeMatchState {
     pub game_number: i32,
     /// Player who went first this game (for tracking reversal)
     pub first_player_this_game: i64,
-    /// Deadline for current move (60 seconds from turn start)
+    /// Deadline for current move (turn timer from turn start)
     pub move_deadline: Option<DateTime<Utc>>,
+    /// Seconds per move, chosen at room creation
+    #[serde(default = "default_turn_timer_seconds")]
+    pub turn_timer_seconds: i64,
     /// Players who are disconnected and when they disconnected
     pub disconnected_at: HashMap<i64, DateTime<Utc>>,
     /// Is the game currently paused due to disconnect?
@@ -86,16 +103,26 @@ impl Default for TicTacToeMatchState {
             game_number: 1,
             first_player_this_game: 0,
             move_deadline: None,
+            turn_timer_seconds: TURN_TIMER_SECONDS,
             disconnected_at: HashMap::new(),
             is_paused: false,
         }
     }
 }
 
+fn default_turn_timer_seconds() -> i64 {
+    TURN_TIMER_SECONDS
+}
+
 impl TicTacToeMatchState {
-    /// Initialize a new match with two players
+    /// Initialize a new match with two players and the default turn timer
     /// Randomly assigns X to one player
     pub fn initialize(player1_id: i64, player2_id: i64) -> Self {
+        Self::initialize_with_timer(player1_id, player2_id, TURN_TIMER_SECONDS)
+    }
+
+    /// Initialize a new match with two players and `turn_timer_seconds` per move
+    pub fn initialize_with_timer(player1_id: i64, player2_id: i64, turn_timer_seconds: i64) -> Self {
         let mut rng = rand::thread_rng();
 
         // Randomly decide who gets X
@@ -110,7 +137,7 @@ impl TicTacToeMatchState {
         scores.insert(player2_id, 0);
 
         let now = Utc::now();
-        let deadline = now + Duration::seconds(TURN_TIMER_SECONDS);
+        let deadline = now + Duration::seconds(turn_timer_seconds);
 
         Self {
             board: [None; 9],
@@ -121,6 +148,7 @@ impl TicTacToeMatchState {
             game_number: 1,
             first_player_this_game: player_x,
             move_deadline: Some(deadline),
+            turn_timer_seconds,
             disconnected_at: HashMap::new(),
             is_paused: false,
         }
@@ -179,7 +207,7 @@ impl TicTacToeMatchState {
         self.current_turn = self.get_opponent(player_id);
 
         // Reset move deadline
-        self.move_deadline = Some(Utc::now() + Duration::seconds(TURN_TIMER_SECONDS));
+        self.move_deadline = Some(Utc::now() + Duration::seconds(self.turn_timer_seconds));
 
         Ok(())
     }
@@ -229,7 +257,7 @@ impl TicTacToeMatchState {
         self.game_number += 1;
 
         // Reset timer
-        self.move_deadline = Some(Utc::now() + Duration::seconds(TURN_TIMER_SECONDS));
+        self.move_deadline = Some(Utc::now() + Duration::seconds(self.turn_timer_seconds));
     }
 
     /// Award a point to a player
@@ -262,7 +290,7 @@ impl TicTacToeMatchState {
         if self.disconnected_at.is_empty() {
             self.is_paused = false;
             // Reset timer for current player
-            self.move_deadline = Some(Utc::now() + Duration::seconds(TURN_TIMER_SECONDS));
+            self.move_deadline = Some(Utc::now() + Duration::seconds(self.turn_timer_seconds));
         }
     }
 
@@ -371,6 +399,7 @@ pub fn process_move(
                 winner_username: match_winner_username,
                 final_scores,
                 prize_amount: prize,
+                started_at: room.started_at,
             });
 
             info!(
@@ -404,6 +433,7 @@ pub fn process_move(
             room_id: room.room_id.clone(),
             current_turn: state.current_turn,
             turn_number: room.turn_number + 1,
+            state_checksum: None,
         });
         room.turn_number += 1;
         room.current_turn = Some(state.current_turn);
@@ -434,6 +464,7 @@ pub fn process_move(
             room_id: room.room_id.clone(),
             current_turn: state.current_turn,
             turn_number: room.turn_number + 1,
+            state_checksum: None,
         });
         room.turn_number += 1;
         room.current_turn = Some(state.current_turn);
@@ -446,6 +477,7 @@ pub fn process_move(
         room_id: room.room_id.clone(),
         current_turn: state.current_turn,
         turn_number: room.turn_number + 1,
+        state_checksum: None,
     });
     room.turn_number += 1;
     room.current_turn = Some(state.current_turn);
@@ -520,6 +552,7 @@ pub fn process_turn_timeout(
             winner_username: match_winner_username,
             final_scores,
             prize_amount: prize,
+            started_at: room.started_at,
         });
 
         return (events, true);
@@ -532,6 +565,7 @@ pub fn process_turn_timeout(
         room_id: room.room_id.clone(),
         current_turn: state.current_turn,
         turn_number: room.turn_number + 1,
+        state_checksum: None,
     });
     room.turn_number += 1;
     room.current_turn = Some(state.current_turn);
@@ -553,7 +587,8 @@ pub fn start_game(room: &mut GameRoom) -> (Vec<GameEvent>, TicTacToeMatchState)
     let player2 = room.players[1].user_id;
 
     // Initialize match state
-    let state = TicTacToeMatchState::initialize(player1, player2);
+    let turn_timer_seconds = room.turn_timer_seconds.unwrap_or(TURN_TIMER_SECONDS);
+    let state = TicTacToeMatchState::initialize_with_timer(player1, player2, turn_timer_seconds);
 
     // Reset room scores
     for player in &mut room.players {
@@ -575,6 +610,7 @@ pub fn start_game(room: &mut GameRoom) -> (Vec<GameEvent>, TicTacToeMatchState)
         room_id: room.room_id.clone(),
         current_turn: state.current_turn,
         turn_number: 1,
+        state_checksum: None,
     });
 
     // Send initial state
@@ -0,0 +1,206 @@
+//! Tournaments
+//!
+//! Single-elimination brackets of 2 to 64 players (a power of two). Players
+//! register until the bracket is full, paying the entry fee into the prize
+//! pool. The entrants are then shuffled into first-round pairs and every match
+//! is played in its own game room. The winner of a room advances when the room
+//! reports its game over. The last remaining player wins the tournament.
+//!
+//! The pool is split per GAME_TOURNAMENT_PRIZE_SPLIT: percentages for the
+//! champion, the runner-up, the semi-final losers, the quarter-final losers
+//! and so on. Players eliminated in the same round share their percentage.
+//! Percentages for rounds the bracket doesn't have go to the champion.
+//! Whatever the split leaves below 100% stays with the house.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::types::EventEnvelope;
+
+/// Smallest and largest bracket
+pub const MIN_BRACKET_SIZE: i32 = 2;
+pub const MAX_BRACKET_SIZE: i32 = 64;
+
+/// Game events that end a room with a winner
+pub const FINISHED_EVENTS: &[&str] = &["game_ended", "bigger_dice.game_over", "tic_tac_toe.match_ended"];
+
+pub fn is_valid_bracket_size(size: i32) -> bool {
+    (MIN_BRACKET_SIZE..=MAX_BRACKET_SIZE).contains(&size) && (size as u32).is_power_of_two()
+}
+
+/// Rounds needed to reduce a full bracket to one player
+pub fn round_count(bracket_size: i32) -> i32 {
+    bracket_size.max(1).trailing_zeros() as i32
+}
+
+/// Pair players in order: (1st, 2nd), (3rd, 4th), ...
+pub fn pair_up(players: &[i64]) -> Vec<(i64, i64)> {
+    players.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Name of the game room a match is played in
+pub fn match_room_name(tournament_id: i64, round: i32, position: i32) -> String {
+    format!("Tournament #{} R{} M{}", tournament_id, round, position)
+}
+
+/// Room and winner of a finished game carried by a games.events envelope
+pub fn finished_match(envelope: &EventEnvelope) -> Option<(String, i64)> {
+    let payload = &envelope.payload;
+    let event_type = payload.get("type")?.as_str()?;
+    if !FINISHED_EVENTS.contains(&event_type) {
+        return None;
+    }
+
+    let room_id = payload.get("room_id")?.as_str()?.to_string();
+    let winner_id = match payload.get("winner_id")? {
+        Value::Number(n) => n.as_i64()?,
+        Value::String(s) => s.parse().ok()?,
+        _ => return None,
+    };
+
+    Some((room_id, winner_id))
+}
+
+/// Match announced when its round starts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TournamentRoundMatch {
+    pub match_id: i64,
+    pub position: i32,
+    pub player1_id: i64,
+    pub player2_id: i64,
+    pub room_id: String,
+    pub room_name: String,
+}
+
+/// Prize paid to one player
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TournamentPayout {
+    pub user_id: i64,
+    /// 1 for the champion, 2 for the runner-up, 3 for the semi-final losers, 5 for the quarter-final losers, ...
+    pub place: i32,
+    pub amount_cents: i64,
+}
+
+/// Split the prize pool.
+///
+/// `losers_by_round[r]` lists the players eliminated in round `r + 1`, so the
+/// last entry holds the runner-up. Payouts of zero are left out.
+pub fn payouts(
+    pool_cents: i64,
+    split: &[u32],
+    champion_id: i64,
+    losers_by_round: &[Vec<i64>],
+) -> Vec<TournamentPayout> {
+    let split_total = split.iter().sum::<u32>().min(100) as i64;
+    let house_cents = pool_cents * (100 - split_total) / 100;
+    let mut result = Vec::new();
+
+    // Tier 1 is the final, tier 2 the semi-finals, ...
+    for (tier, percent) in split.iter().enumerate().skip(1) {
+        let Some(losers) = losers_by_round
+            .len()
+            .checked_sub(tier)
+            .and_then(|round| losers_by_round.get(round))
+            .filter(|losers| !losers.is_empty())
+        else {
+            continue;
+        };
+
+        let tier_cents = pool_cents * *percent as i64 / 100;
+        let each = tier_cents / losers.len() as i64;
+        let mut leftover = tier_cents - each * losers.len() as i64;
+
+        for user_id in losers {
+            let extra = if leftover > 0 { 1 } else { 0 };
+            leftover -= extra;
+            result.push(TournamentPayout {
+                user_id: *user_id,
+                place: (1 << (tier - 1)) + 1,
+                amount_cents: each + extra,
+            });
+        }
+    }
+
+    let paid: i64 = result.iter().map(|p| p.amount_cents).sum();
+    result.insert(
+        0,
+        TournamentPayout {
+            user_id: champion_id,
+            place: 1,
+            amount_cents: pool_cents - house_cents - paid,
+        },
+    );
+
+    result.retain(|p| p.amount_cents > 0);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::games::types::{Actor, Audience};
+    use serde_json::json;
+
+    fn envelope(payload: Value) -> EventEnvelope {
+        EventEnvelope {
+            event_id: "e-1".to_string(),
+            event_type: "games.event.bigger_dice.game_over".to_string(),
+            timestamp: "2026-10-17T12:00:00Z".to_string(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::room("r-1"),
+            payload,
+        }
+    }
+
+    #[test]
+    fn brackets_are_powers_of_two() {
+        assert!(is_valid_bracket_size(2));
+        assert!(is_valid_bracket_size(64));
+        assert!(!is_valid_bracket_size(6));
+        assert!(!is_valid_bracket_size(128));
+        assert_eq!(round_count(2), 1);
+        assert_eq!(round_count(16), 4);
+        assert_eq!(pair_up(&[1, 2, 3, 4]), vec![(1, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn pool_is_split_by_elimination_round() {
+        // 8 players: runner-up 8, semi-final losers 6 and 7
+        let losers = vec![vec![1, 2, 3, 4], vec![6, 7], vec![8]];
+        let result = payouts(8000, &[60, 25, 10], 5, &losers);
+
+        assert_eq!(
+            result,
+            vec![
+                TournamentPayout { user_id: 5, place: 1, amount_cents: 4800 },
+                TournamentPayout { user_id: 8, place: 2, amount_cents: 2000 },
+                TournamentPayout { user_id: 6, place: 3, amount_cents: 400 },
+                TournamentPayout { user_id: 7, place: 3, amount_cents: 400 },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_places_go_to_the_champion() {
+        let result = payouts(1001, &[70, 20, 10], 1, &[vec![2]]);
+
+        assert_eq!(result[0].amount_cents, 801);
+        assert_eq!(result[1], TournamentPayout { user_id: 2, place: 2, amount_cents: 200 });
+    }
+
+    #[test]
+    fn finished_games_report_room_and_winner() {
+        let over = envelope(json!({ "type": "bigger_dice.game_over", "room_id": "r-1", "winner_id": 7 }));
+        let rolled = envelope(json!({ "type": "bigger_dice.rolled", "room_id": "r-1" }));
+
+        assert_eq!(finished_match(&over), Some(("r-1".to_string(), 7)));
+        assert_eq!(finished_match(&rolled), None);
+    }
+}
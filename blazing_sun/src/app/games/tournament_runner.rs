@@ -0,0 +1,218 @@
+//! Tournament runner
+//!
+//! Turns bracket changes into game rooms and events. Every match is played in
+//! a regular two-player room: the first player hosts it and both players are
+//! already in the lobby and selected, so the game starts once both are ready.
+//! Match rooms charge no entry fee and pay no game prize; the tournament's
+//! prize pool is paid out when the final ends.
+//!
+//! Used by the registration endpoint (the entry that fills the bracket opens
+//! round one) and by the tournament event handler (a finished match room
+//! advances its winner).
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::room_game_types::RoomGameTypes;
+use super::tournament::{self, TournamentRoundMatch};
+use super::types::{Actor, Audience, EventEnvelope, GameEvent};
+use crate::app::db_query::mutations::game_room as game_room_mutations;
+use crate::app::db_query::mutations::tournaments::{self as tournament_mutations, RecordResultOutcome};
+use crate::app::db_query::read::tournaments::{self as tournament_read, Tournament, TournamentMatch};
+use crate::config::games::DEFAULT_REGION;
+use crate::config::GamesConfig;
+use crate::database::{replica, SharedRedis};
+use crate::events::producer::EventProducer;
+use crate::events::topic;
+
+pub struct TournamentRunner {
+    db: Pool<Postgres>,
+    producer: Option<Arc<EventProducer>>,
+    room_game_types: RoomGameTypes,
+}
+
+impl TournamentRunner {
+    pub fn new(db: Pool<Postgres>, producer: Option<Arc<EventProducer>>, redis: Option<SharedRedis>) -> Self {
+        Self {
+            db,
+            producer,
+            room_game_types: RoomGameTypes::new(redis),
+        }
+    }
+
+    /// Create rooms for the matches that have none and tell their players
+    pub async fn open_matches(&self, tournament_id: i64) -> Result<(), sqlx::Error> {
+        let pending = tournament_read::get_matches_without_room(&self.db, tournament_id).await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let Some(tournament) = tournament_read::get_by_id(&self.db, tournament_id).await? else {
+            return Ok(());
+        };
+        if tournament.status != "running" {
+            return Ok(());
+        }
+
+        let mut opened: BTreeMap<i32, Vec<TournamentRoundMatch>> = BTreeMap::new();
+        for m in &pending {
+            let round_match = self.open_match(&tournament, m).await?;
+            opened.entry(m.round).or_default().push(round_match);
+        }
+
+        for (round, matches) in opened {
+            let players: Vec<i64> = matches
+                .iter()
+                .flat_map(|m| [m.player1_id, m.player2_id])
+                .collect();
+
+            info!(
+                tournament_id = %tournament.id,
+                round = %round,
+                matches = %matches.len(),
+                "Tournament round started"
+            );
+
+            let event = GameEvent::TournamentRoundStarted {
+                tournament_id: tournament.id,
+                tournament_name: tournament.name.clone(),
+                game_type: tournament.game_type.clone(),
+                round,
+                rounds: tournament::round_count(tournament.bracket_size),
+                matches,
+            };
+            self.publish(event, Audience::users(players)).await;
+        }
+
+        Ok(())
+    }
+
+    /// Record the winner of a match, then open the next round or announce
+    /// the champion
+    pub async fn advance(&self, m: &TournamentMatch, winner_id: i64) -> Result<(), sqlx::Error> {
+        let outcome = tournament_mutations::record_result(
+            &self.db,
+            m.id,
+            winner_id,
+            GamesConfig::tournament_prize_split(),
+        )
+        .await?;
+
+        match outcome {
+            RecordResultOutcome::Finished { champion_id, payouts } => {
+                for payout in &payouts {
+                    replica::mark_written(payout.user_id).await;
+                }
+                let Some(tournament) = tournament_read::get_by_id(&self.db, m.tournament_id).await? else {
+                    return Ok(());
+                };
+                let entrants = tournament_read::get_entries(&self.db, tournament.id)
+                    .await?
+                    .iter()
+                    .map(|e| e.user_id)
+                    .collect();
+
+                info!(
+                    tournament_id = %tournament.id,
+                    winner_id = %champion_id,
+                    prize_pool_cents = %tournament.prize_pool_cents,
+                    "Tournament finished"
+                );
+
+                let event = GameEvent::TournamentFinished {
+                    tournament_id: tournament.id,
+                    tournament_name: tournament.name,
+                    winner_id: champion_id,
+                    prize_pool_cents: tournament.prize_pool_cents,
+                    payouts,
+                };
+                self.publish(event, Audience::users(entrants)).await;
+                Ok(())
+            }
+            // Also retries rooms a previous attempt failed to create
+            _ => self.open_matches(m.tournament_id).await,
+        }
+    }
+
+    async fn open_match(
+        &self,
+        tournament: &Tournament,
+        m: &TournamentMatch,
+    ) -> Result<TournamentRoundMatch, sqlx::Error> {
+        let room_id = Uuid::new_v4().to_string();
+        let room_name = tournament::match_room_name(tournament.id, m.round, m.position);
+
+        let params = game_room_mutations::CreateRoomParams {
+            room_id: room_id.clone(),
+            room_name: room_name.clone(),
+            game_type: tournament.game_type.clone(),
+            host_id: m.player1_id,
+            password_hash: None,
+            player_count: Some(2),
+            allow_spectators: Some(true),
+        };
+        game_room_mutations::create(&self.db, &params).await?;
+        self.room_game_types.remember(&room_id, &tournament.game_type).await;
+
+        if GamesConfig::region() != DEFAULT_REGION {
+            game_room_mutations::assign_region(&self.db, &room_id, GamesConfig::region()).await?;
+        }
+
+        for player_id in [m.player1_id, m.player2_id] {
+            game_room_mutations::add_to_lobby(&self.db, &room_id, player_id).await?;
+            game_room_mutations::select_player_for_game(&self.db, &room_id, m.player1_id, player_id).await?;
+        }
+
+        tournament_mutations::set_match_room(&self.db, m.id, &room_id).await?;
+
+        Ok(TournamentRoundMatch {
+            match_id: m.id,
+            position: m.position,
+            player1_id: m.player1_id,
+            player2_id: m.player2_id,
+            room_id,
+            room_name,
+        })
+    }
+
+    /// Publish a tournament event to this region's games events topic
+    async fn publish(&self, event: GameEvent, audience: Audience) {
+        let Some(producer) = &self.producer else {
+            warn!("No Kafka producer available for tournament events");
+            return;
+        };
+
+        let envelope = EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: format!("games.event.{}", event.event_type_name()),
+            timestamp: Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience,
+            payload: serde_json::to_value(&event).unwrap_or_default(),
+        };
+
+        let bytes = match serde_json::to_vec(&envelope) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize tournament event");
+                return;
+            }
+        };
+
+        if let Err(e) = producer.send_raw(topic::region_games_events(), None, &bytes).await {
+            warn!(error = %e, "Failed to publish tournament event");
+        }
+    }
+}
@@ -3,6 +3,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use super::bigger_dice::BiggerDiceRules;
+use super::bots::BotDifficulty;
+use super::player_stats::PlayerStatsSummary;
+use super::predictions::{PredictionCandidate, PredictionPayout};
+use super::room_password::{self, Verification};
+use super::state_diff::PatchOp;
+use super::tournament::{TournamentPayout, TournamentRoundMatch};
+
 /// Custom deserializer for i64 that accepts both string and integer formats
 /// This handles WebSocket gateway sending user_id as "4" instead of 4
 pub fn deserialize_i64_from_string<'de, D>(deserializer: D) -> Result<i64, D::Error>
@@ -115,6 +123,7 @@ impl GameType {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum RoomStatus {
+    Scheduled,   // Opens at its scheduled start
     Waiting,     // Waiting for players
     InProgress,  // Game is active
     Finished,    // Game has ended
@@ -136,6 +145,9 @@ pub struct GamePlayer {
     pub score: i32,
     pub is_ready: bool,
     pub joined_at: DateTime<Utc>,
+    /// The player's stats for the room's game type, attached when they enter the lobby
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<PlayerStatsSummary>,
 }
 
 /// Banned player info (user_id + username for display)
@@ -195,6 +207,28 @@ impl std::str::FromStr for ChatChannel {
     }
 }
 
+/// A moderator's chat mute: the user cannot send to `channel` (every
+/// channel when None) until `expires_at` (until unmuted when None)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatMute {
+    pub user_id: i64,
+    pub channel: Option<ChatChannel>,
+    pub muted_by: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ChatMute {
+    /// Whether the mute still holds at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+
+    /// Whether the mute applies to `channel`
+    pub fn covers(&self, channel: &ChatChannel) -> bool {
+        self.channel.as_ref().is_none_or(|muted| muted == channel)
+    }
+}
+
 /// Game room structure (stored in Redis via ws_gateway)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameRoom {
@@ -217,7 +251,7 @@ pub struct GameRoom {
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
     pub winner_id: Option<i64>,
-    /// bcrypt hash of the room password (None = public room)
+    /// argon2id (or legacy bcrypt) hash of the room password (None = public room)
     #[serde(skip_serializing)]
     pub password_hash: Option<String>,
     /// Quick flag for UI to show lock icon
@@ -234,6 +268,9 @@ pub struct GameRoom {
     /// Maximum number of spectators allowed
     #[serde(default = "default_max_spectators")]
     pub max_spectators: i32,
+    /// Seconds per move for timed games (None = game default / untimed)
+    #[serde(default)]
+    pub turn_timer_seconds: Option<i64>,
     /// The user designated as admin spectator (when host plays)
     pub admin_spectator_id: Option<i64>,
     /// Whether lobby chat is enabled (disabled during game)
@@ -254,6 +291,16 @@ pub struct GameRoom {
     /// Players that are currently auto-controlled (disconnected + kicked)
     #[serde(default)]
     pub auto_players: Vec<i64>,
+    /// Bot opponent difficulty for practice rooms (None = no bot)
+    #[serde(default)]
+    pub bot_difficulty: Option<BotDifficulty>,
+    /// Bigger Dice rules chosen at creation (None = default rules; other
+    /// games have none)
+    #[serde(default)]
+    pub dice_rules: Option<BiggerDiceRules>,
+    /// Moderator chat mutes, so clients can disable the inputs of muted users
+    #[serde(default)]
+    pub chat_mutes: Vec<ChatMute>,
 }
 
 fn default_player_count() -> i32 {
@@ -296,6 +343,7 @@ impl GameRoom {
             player_count: 2,
             allow_spectators: true,
             max_spectators: 10,
+            turn_timer_seconds: None,
             admin_spectator_id: None,
             lobby_chat_enabled: true,
             spectators_data: Vec::new(),
@@ -303,6 +351,9 @@ impl GameRoom {
             recorded_spectators: Vec::new(),
             selected_players: Vec::new(),
             auto_players: Vec::new(),
+            bot_difficulty: None,
+            dice_rules: None,
+            chat_mutes: Vec::new(),
         }
     }
 
@@ -314,13 +365,7 @@ impl GameRoom {
         host_id: i64,
         password: Option<&str>,
     ) -> Self {
-        let password_hash = password.and_then(|p| {
-            if p.is_empty() {
-                None
-            } else {
-                bcrypt::hash(p, bcrypt::DEFAULT_COST).ok()
-            }
-        });
+        let password_hash = password.and_then(room_password::hash);
         let is_password_protected = password_hash.is_some();
 
         Self {
@@ -345,6 +390,7 @@ impl GameRoom {
             player_count: 2,
             allow_spectators: true,
             max_spectators: 10,
+            turn_timer_seconds: None,
             admin_spectator_id: None,
             lobby_chat_enabled: true,
             spectators_data: Vec::new(),
@@ -352,6 +398,9 @@ impl GameRoom {
             recorded_spectators: Vec::new(),
             selected_players: Vec::new(),
             auto_players: Vec::new(),
+            bot_difficulty: None,
+            dice_rules: None,
+            chat_mutes: Vec::new(),
         }
     }
 
@@ -365,13 +414,7 @@ impl GameRoom {
         player_count: i32,
         allow_spectators: bool,
     ) -> Self {
-        let password_hash = password.and_then(|p| {
-            if p.is_empty() {
-                None
-            } else {
-                bcrypt::hash(p, bcrypt::DEFAULT_COST).ok()
-            }
-        });
+        let password_hash = password.and_then(room_password::hash);
         let is_password_protected = password_hash.is_some();
         // Clamp player_count to valid range
         let player_count = player_count.clamp(2, 10);
@@ -398,6 +441,7 @@ impl GameRoom {
             player_count,
             allow_spectators,
             max_spectators: 10,
+            turn_timer_seconds: None,
             admin_spectator_id: None,
             lobby_chat_enabled: true,
             spectators_data: Vec::new(),
@@ -405,15 +449,18 @@ impl GameRoom {
             recorded_spectators: Vec::new(),
             selected_players: Vec::new(),
             auto_players: Vec::new(),
+            bot_difficulty: None,
+            dice_rules: None,
+            chat_mutes: Vec::new(),
         }
     }
 
     /// Verify a password against the stored hash
-    /// Returns true if room is public (no password) or password matches
-    pub fn verify_password(&self, password: &str) -> bool {
+    /// Valid if room is public (no password) or password matches
+    pub fn verify_password(&self, password: &str) -> Verification {
         match &self.password_hash {
-            Some(hash) => bcrypt::verify(password, hash).unwrap_or(false),
-            None => true, // Public room, no password needed
+            Some(hash) => room_password::verify(password, hash),
+            None => Verification::Valid, // Public room, no password needed
         }
     }
 
@@ -444,6 +491,11 @@ impl GameRoom {
         self.players.iter().any(|p| p.user_id == user_id)
     }
 
+    /// Practice room against a bot (no entry fee, no prize)
+    pub fn is_vs_bot(&self) -> bool {
+        self.bot_difficulty.is_some()
+    }
+
     /// Check if player is auto-controlled
     pub fn is_auto_player(&self, user_id: i64) -> bool {
         self.auto_players.contains(&user_id)
@@ -650,6 +702,29 @@ impl GameRoom {
         self.lobby_chat_enabled = true;
     }
 
+    /// Active mute keeping `user_id` from sending to `channel`, if any
+    pub fn chat_mute(&self, user_id: i64, channel: &ChatChannel, now: DateTime<Utc>) -> Option<&ChatMute> {
+        self.chat_mutes
+            .iter()
+            .find(|mute| mute.user_id == user_id && mute.covers(channel) && mute.is_active(now))
+    }
+
+    /// Add a chat mute, replacing the user's mute of the same channel
+    pub fn set_chat_mute(&mut self, mute: ChatMute) {
+        self.chat_mutes
+            .retain(|m| !(m.user_id == mute.user_id && m.channel == mute.channel));
+        self.chat_mutes.push(mute);
+    }
+
+    /// Remove the user's mute of `channel` (the every-channel mute when None)
+    pub fn remove_chat_mute(&mut self, user_id: i64, channel: Option<&ChatChannel>) -> Option<ChatMute> {
+        let pos = self
+            .chat_mutes
+            .iter()
+            .position(|m| m.user_id == user_id && m.channel.as_ref() == channel)?;
+        Some(self.chat_mutes.remove(pos))
+    }
+
     /// Check if user can send chat to a specific channel
     pub fn can_chat_in_channel(&self, user_id: i64, channel: &ChatChannel) -> bool {
         match channel {
@@ -720,6 +795,24 @@ pub enum GameCommand {
         room_id: String,
         socket_id: String,
     },
+    /// The client's state checksum differs from the one it was sent
+    #[serde(rename = "report_desync")]
+    ReportDesync {
+        user_id: i64,
+        room_id: String,
+        state_checksum: String,
+        socket_id: String,
+    },
+    /// The client asks for a full room state (it missed a state diff)
+    #[serde(rename = "get_room_state")]
+    GetRoomState {
+        user_id: i64,
+        #[serde(default)]
+        room_id: Option<String>,
+        #[serde(default)]
+        room_name: Option<String>,
+        socket_id: String,
+    },
     #[serde(rename = "spectate")]
     Spectate {
         user_id: i64,
@@ -732,6 +825,15 @@ pub enum GameCommand {
         room_id: String,
         socket_id: String,
     },
+    /// Spectator stakes coins on a player winning
+    #[serde(rename = "predict_winner")]
+    PredictWinner {
+        user_id: i64,
+        room_id: String,
+        predicted_winner_id: i64,
+        stake_cents: i64,
+        socket_id: String,
+    },
     /// Admin selects a player from lobby to play against
     #[serde(rename = "select_player")]
     SelectPlayer {
@@ -801,6 +903,29 @@ pub enum GameCommand {
         target_user_id: i64,
         socket_id: String,
     },
+    /// Host or admin spectator mutes a user in one chat channel (every
+    /// channel without one), for `duration_seconds` or until unmuted
+    #[serde(rename = "chat_mute")]
+    ChatMute {
+        user_id: i64,
+        room_id: String,
+        target_user_id: i64,
+        #[serde(default)]
+        channel: Option<String>,
+        #[serde(default)]
+        duration_seconds: Option<i64>,
+        socket_id: String,
+    },
+    /// Host or admin spectator lifts a chat mute
+    #[serde(rename = "chat_unmute")]
+    ChatUnmute {
+        user_id: i64,
+        room_id: String,
+        target_user_id: i64,
+        #[serde(default)]
+        channel: Option<String>,
+        socket_id: String,
+    },
     /// Admin deselects a player (move back from selected to lobby)
     #[serde(rename = "deselect_player")]
     DeselectPlayer {
@@ -929,6 +1054,9 @@ pub enum GameEvent {
         room_id: String,
         current_turn: i64,
         turn_number: i32,
+        /// Checksum of the room after the turn change (see `state_checksum`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
     },
     #[serde(rename = "game_ended")]
     GameEnded {
@@ -949,6 +1077,18 @@ pub enum GameEvent {
         user_id: i64,
         username: String,
     },
+    /// Sent to a user queued for a full room's spectator seats whenever their
+    /// place changes (position 1 = next to be admitted)
+    #[serde(rename = "spectator_waitlist_position")]
+    SpectatorWaitlistPosition {
+        room_id: String,
+        room_name: String,
+        position: u32,
+        waitlist_size: u32,
+        /// The place is given up unless the user asks to spectate again before this
+        expires_at: DateTime<Utc>,
+        socket_id: String,
+    },
     /// Sent to a spectator when they are kicked by admin
     #[serde(rename = "spectator_kicked")]
     SpectatorKicked {
@@ -959,6 +1099,21 @@ pub enum GameEvent {
     #[serde(rename = "room_state")]
     RoomState {
         room: GameRoom,
+        /// Checksum of `room` (see `state_checksum`)
+        state_checksum: String,
+        /// Version of the recipient's copy of the room (see `state_diff`)
+        version: u64,
+    },
+    /// Changes of the room since the state the recipient was sent last
+    #[serde(rename = "room_state_diff")]
+    RoomStateDiff {
+        room_id: String,
+        /// Version the operations apply to; other versions ask for a snapshot
+        base_version: u64,
+        version: u64,
+        ops: Vec<PatchOp>,
+        /// Checksum of the room after the operations
+        state_checksum: String,
     },
     #[serde(rename = "error")]
     Error {
@@ -966,6 +1121,90 @@ pub enum GameEvent {
         message: String,
         socket_id: String,
     },
+    /// Sent when a command targets a room that was recently deleted or finished
+    #[serde(rename = "room_gone")]
+    RoomGone {
+        room_id: String,
+        room_name: String,
+        /// "deleted", "finished" or "deactivated"
+        reason: String,
+        socket_id: String,
+    },
+    /// Sent when a waiting room moved to another region; clients reconnect to
+    /// `gateway_url` and rejoin the room there
+    #[serde(rename = "room_migrated")]
+    RoomMigrated {
+        room_id: String,
+        room_name: String,
+        from_region: String,
+        to_region: String,
+        gateway_url: Option<String>,
+    },
+    /// Broadcast when a room's player/spectator counts or status change, so
+    /// lobby room lists stay current without refetching. Rapid changes are
+    /// coalesced (GAME_OCCUPANCY_THROTTLE_MS).
+    #[serde(rename = "room_occupancy_changed")]
+    RoomOccupancyChanged {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        status: RoomStatus,
+        player_count: i32,
+        max_players: i32,
+        spectator_count: i32,
+        max_spectators: i32,
+    },
+    /// Predictions opened when the game started
+    #[serde(rename = "prediction_opened")]
+    PredictionOpened {
+        room_id: String,
+        closes_at: DateTime<Utc>,
+        min_stake_cents: i64,
+        max_stake_cents: i64,
+        candidates: Vec<PredictionCandidate>,
+    },
+    /// A prediction was accepted; pools and odds changed
+    #[serde(rename = "prediction_pool_updated")]
+    PredictionPoolUpdated {
+        room_id: String,
+        total_pool_cents: i64,
+        candidates: Vec<PredictionCandidate>,
+    },
+    /// The prediction window elapsed; no more predictions this game
+    #[serde(rename = "prediction_closed")]
+    PredictionClosed {
+        room_id: String,
+        total_pool_cents: i64,
+        candidates: Vec<PredictionCandidate>,
+    },
+    /// Payouts applied after the game ended (`winner_id` is None when every
+    /// stake was refunded)
+    #[serde(rename = "prediction_settled")]
+    PredictionSettled {
+        room_id: String,
+        winner_id: Option<i64>,
+        total_pool_cents: i64,
+        payouts: Vec<PredictionPayout>,
+    },
+    /// A tournament round's rooms are ready (sent to the players of the round)
+    #[serde(rename = "tournament_round_started")]
+    TournamentRoundStarted {
+        tournament_id: i64,
+        tournament_name: String,
+        game_type: String,
+        round: i32,
+        rounds: i32,
+        matches: Vec<TournamentRoundMatch>,
+    },
+    /// The final was played (sent to every entrant)
+    #[serde(rename = "tournament_finished")]
+    TournamentFinished {
+        tournament_id: i64,
+        tournament_name: String,
+        winner_id: i64,
+        prize_pool_cents: i64,
+        payouts: Vec<TournamentPayout>,
+    },
     /// Sent when user tries to rejoin a room they're not in
     /// Includes room info so frontend can show "Enter Room" button
     #[serde(rename = "not_in_room")]
@@ -1018,6 +1257,89 @@ pub enum GameEvent {
         room_name: String,
         socket_id: String,
     },
+    /// Join refused because the user entered too many wrong room passwords
+    #[serde(rename = "room_join_denied")]
+    RoomJoinDenied {
+        room_id: String,
+        room_name: String,
+        /// "too_many_attempts"
+        reason: String,
+        /// Seconds until the user may try again
+        retry_after_seconds: u64,
+        socket_id: String,
+    },
+    /// The room closes for inactivity unless someone acts before `closes_at`
+    #[serde(rename = "room_inactivity_warning")]
+    RoomInactivityWarning {
+        room_id: String,
+        room_name: String,
+        closes_at: DateTime<Utc>,
+        closes_in_seconds: i64,
+    },
+    /// The host pushed back the inactivity deadline
+    #[serde(rename = "room_inactivity_extended")]
+    RoomInactivityExtended {
+        room_id: String,
+        closes_at: DateTime<Utc>,
+        extended_by: i64,
+    },
+    /// A room was created to open at `scheduled_start_at` (sent to the host)
+    #[serde(rename = "room_scheduled")]
+    RoomScheduled {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        scheduled_start_at: DateTime<Utc>,
+    },
+    /// A scheduled room the user registered for opens soon
+    #[serde(rename = "scheduled_room_reminder")]
+    ScheduledRoomReminder {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        scheduled_start_at: DateTime<Utc>,
+        minutes_before: i64,
+    },
+    /// A scheduled room the user registered for is open to join
+    #[serde(rename = "scheduled_room_opened")]
+    ScheduledRoomOpened {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+    },
+    /// A room member invited the user; answerable until `expires_at`
+    #[serde(rename = "invite_received")]
+    InviteReceived {
+        invite_id: String,
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        inviter_id: i64,
+        inviter_username: String,
+        is_password_protected: bool,
+        player_count: i32,
+        /// Users already in the lobby or seated
+        joined_count: i32,
+        expires_at: DateTime<Utc>,
+    },
+    /// An invite was accepted, declined or expired (sent to the inviter)
+    #[serde(rename = "invite_answered")]
+    InviteAnswered {
+        invite_id: String,
+        room_id: String,
+        invitee_id: i64,
+        invitee_username: String,
+        /// "accepted", "declined" or "expired"
+        status: String,
+    },
+    /// The room is being closed; sent to its members before `room_removed`
+    #[serde(rename = "room_closing")]
+    RoomClosing {
+        room_id: String,
+        room_name: String,
+        /// "inactive" or "closed_by_admin"
+        reason: String,
+    },
     /// Lobby list updated (for full sync)
     #[serde(rename = "lobby_updated")]
     LobbyUpdated {
@@ -1084,6 +1406,9 @@ pub enum GameEvent {
         winner_id: i64,
         winner_username: String,
         final_scores: Vec<(i64, String, i32)>, // (user_id, username, score)
+        /// When the game started (the room is deleted once it is over)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        started_at: Option<DateTime<Utc>>,
     },
 
     // ========== Tic Tac Toe Events ==========
@@ -1124,6 +1449,9 @@ pub enum GameEvent {
         winner_username: String,
         final_scores: Vec<(i64, String, i32)>,
         prize_amount: i64,
+        /// When the match started (the room is deleted once it is over)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        started_at: Option<DateTime<Utc>>,
     },
     /// Full state sync (for rejoin/spectators)
     #[serde(rename = "tic_tac_toe.state")]
@@ -1174,10 +1502,16 @@ pub enum GameEvent {
         reconnected_player_username: String,
     },
 
-    /// List of available rooms (sent in response to list_rooms command)
+    /// List of available rooms (sent in response to list_rooms and search_rooms commands)
     #[serde(rename = "room_list")]
     RoomList {
         rooms: Vec<serde_json::Value>,
+        /// Cursor for the page of older rooms, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        next_cursor: Option<String>,
+        /// Cursor for the page of newer rooms, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        prev_cursor: Option<String>,
         socket_id: String,
     },
     /// Room was removed/deactivated (host left or game finished)
@@ -1283,6 +1617,25 @@ pub enum GameEvent {
         target_username: String,
         socket_id: String,
     },
+    /// A user was muted in a chat channel (`channel` None = every channel)
+    #[serde(rename = "chat_muted")]
+    ChatMuted {
+        room_id: String,
+        user_id: i64,
+        username: String,
+        channel: Option<ChatChannel>,
+        muted_by: i64,
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// A chat mute was lifted by a moderator, or ran out (`expired`)
+    #[serde(rename = "chat_unmuted")]
+    ChatUnmuted {
+        room_id: String,
+        user_id: i64,
+        username: String,
+        channel: Option<ChatChannel>,
+        expired: bool,
+    },
     /// Player was deselected by admin (moved back to lobby)
     #[serde(rename = "player_deselected")]
     PlayerDeselected {
@@ -1363,9 +1716,20 @@ impl GameEvent {
             GameEvent::GameEnded { .. } => "game_ended",
             GameEvent::SpectatorJoined { .. } => "spectator_joined",
             GameEvent::SpectatorLeft { .. } => "spectator_left",
+            GameEvent::SpectatorWaitlistPosition { .. } => "spectator_waitlist_position",
             GameEvent::SpectatorKicked { .. } => "spectator_kicked",
             GameEvent::RoomState { .. } => "room_state",
+            GameEvent::RoomStateDiff { .. } => "room_state_diff",
             GameEvent::Error { .. } => "error",
+            GameEvent::RoomGone { .. } => "room_gone",
+            GameEvent::RoomMigrated { .. } => "room_migrated",
+            GameEvent::RoomOccupancyChanged { .. } => "room_occupancy_changed",
+            GameEvent::PredictionOpened { .. } => "prediction_opened",
+            GameEvent::PredictionPoolUpdated { .. } => "prediction_pool_updated",
+            GameEvent::PredictionClosed { .. } => "prediction_closed",
+            GameEvent::PredictionSettled { .. } => "prediction_settled",
+            GameEvent::TournamentRoundStarted { .. } => "tournament_round_started",
+            GameEvent::TournamentFinished { .. } => "tournament_finished",
             GameEvent::NotInRoom { .. } => "not_in_room",
             GameEvent::LobbyJoined { .. } => "lobby_joined",
             GameEvent::PlayerSelected { .. } => "player_selected",
@@ -1373,6 +1737,15 @@ impl GameEvent {
             GameEvent::PlayerBanned { .. } => "player_banned",
             GameEvent::PlayerUnbanned { .. } => "player_unbanned",
             GameEvent::UserBanned { .. } => "user_banned",
+            GameEvent::RoomJoinDenied { .. } => "room_join_denied",
+            GameEvent::RoomInactivityWarning { .. } => "room_inactivity_warning",
+            GameEvent::RoomInactivityExtended { .. } => "room_inactivity_extended",
+            GameEvent::RoomScheduled { .. } => "room_scheduled",
+            GameEvent::ScheduledRoomReminder { .. } => "scheduled_room_reminder",
+            GameEvent::ScheduledRoomOpened { .. } => "scheduled_room_opened",
+            GameEvent::InviteReceived { .. } => "invite_received",
+            GameEvent::InviteAnswered { .. } => "invite_answered",
+            GameEvent::RoomClosing { .. } => "room_closing",
             GameEvent::LobbyUpdated { .. } => "lobby_updated",
             GameEvent::BiggerDiceRolled { .. } => "bigger_dice.rolled",
             GameEvent::BiggerDiceRoundResult { .. } => "bigger_dice.round_result",
@@ -1402,6 +1775,8 @@ impl GameEvent {
             GameEvent::BiggerDiceSpectatorChatHistory { .. } => "bigger_dice.spectator_chat_history",
             GameEvent::UserMuted { .. } => "user_muted",
             GameEvent::UserUnmuted { .. } => "user_unmuted",
+            GameEvent::ChatMuted { .. } => "chat_muted",
+            GameEvent::ChatUnmuted { .. } => "chat_unmuted",
             GameEvent::PlayerDeselected { .. } => "player_deselected",
             GameEvent::AdminSpectatorDesignated { .. } => "admin_spectator_designated",
             GameEvent::SpectatorDataJoined { .. } => "spectator_data_joined",
@@ -1523,6 +1898,10 @@ pub enum AudienceType {
     Broadcast,
     Spectators,
     Players,
+    /// Only the room's host (resolved by ws_gateway from room_state)
+    Host,
+    /// Only the room's host and admin spectator
+    Admins,
 }
 
 /// Target audience for the event (matches ws_gateway format)
@@ -1598,4 +1977,25 @@ impl Audience {
             game_id: None,
         }
     }
+
+    /// Create an audience for the room's host only
+    pub fn host(room_id: impl Into<String>) -> Self {
+        Self {
+            audience_type: AudienceType::Host,
+            user_ids: vec![],
+            room_id: Some(room_id.into()),
+            game_id: None,
+        }
+    }
+
+    /// Create an audience for the room's host and admin spectator, for
+    /// events other members must not see (pending lobby joins, ban lists)
+    pub fn admins(room_id: impl Into<String>) -> Self {
+        Self {
+            audience_type: AudienceType::Admins,
+            user_ids: vec![],
+            room_id: Some(room_id.into()),
+            game_id: None,
+        }
+    }
 }
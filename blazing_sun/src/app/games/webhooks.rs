@@ -0,0 +1,145 @@
+//! Game room lifecycle webhooks
+//!
+//! Admins register URLs (with a shared secret) that are notified when rooms
+//! are created, games start and games end. Every notification becomes a row in
+//! `game_webhook_deliveries`; the `game_webhooks` cron hands due deliveries to
+//! the `deliver_game_webhook` MQ job, which POSTs the JSON body and schedules
+//! failed attempts again with exponential backoff until
+//! GAME_WEBHOOK_MAX_ATTEMPTS is reached.
+//!
+//! Deliveries are signed like Stripe webhooks: the `X-Webhook-Signature`
+//! header is `t=<unix timestamp>,v1=<hex HMAC-SHA256 of "<timestamp>.<body>">`
+//! keyed with the webhook secret.
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use super::types::GameRoom;
+
+/// A room was created
+pub const ROOM_CREATED: &str = "room_created";
+/// All players were ready and the game started
+pub const GAME_STARTED: &str = "game_started";
+/// The game finished (with or without a winner)
+pub const GAME_OVER: &str = "game_over";
+
+/// Events a webhook can subscribe to
+pub const EVENTS: &[&str] = &[ROOM_CREATED, GAME_STARTED, GAME_OVER];
+
+/// Header carrying the delivery signature
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// Header carrying the delivery id (stable across retries)
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Delay before the first retry; doubles with every failed attempt
+const BASE_BACKOFF_SECONDS: i64 = 30;
+/// Longest delay between two attempts
+const MAX_BACKOFF_SECONDS: i64 = 6 * 60 * 60;
+
+pub fn is_valid_event(event: &str) -> bool {
+    EVENTS.contains(&event)
+}
+
+/// Only absolute http(s) URLs can receive deliveries
+pub fn is_valid_url(url: &str) -> bool {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some(),
+        Err(_) => false,
+    }
+}
+
+/// New random signing secret (`whsec_` + 64 hex characters)
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Value of the signature header for `body` sent at `timestamp`
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delay before the next attempt after `attempts` failed ones
+pub fn backoff(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(20);
+    let seconds = BASE_BACKOFF_SECONDS.saturating_mul(1 << exponent);
+    Duration::seconds(seconds.min(MAX_BACKOFF_SECONDS))
+}
+
+/// Player as described in webhook payloads
+#[derive(Debug, Clone, Serialize)]
+struct WebhookPlayer<'a> {
+    user_id: i64,
+    username: &'a str,
+    score: i32,
+}
+
+/// Request body of a delivery
+pub fn payload(event: &str, room: &GameRoom, occurred_at: DateTime<Utc>) -> Value {
+    let players: Vec<WebhookPlayer> = room
+        .players
+        .iter()
+        .map(|p| WebhookPlayer {
+            user_id: p.user_id,
+            username: &p.username,
+            score: p.score,
+        })
+        .collect();
+
+    json!({
+        "type": event,
+        "occurred_at": occurred_at,
+        "room": {
+            "room_id": room.room_id,
+            "room_name": room.room_name,
+            "game_type": room.game_type.as_str(),
+            "status": room.status,
+            "host_id": room.host_id,
+            "player_count": room.player_count,
+            "vs_bot": room.is_vs_bot(),
+            "players": players,
+            "winner_id": room.winner_id,
+            "created_at": room.created_at,
+            "started_at": room.started_at,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_hmac_of_timestamp_and_body() {
+        assert_eq!(
+            signature("whsec_room", 1_700_000_000, "{\"type\":\"game_over\"}"),
+            "t=1700000000,v1=46839fdff0ef9c91fd32421f1dcb7ffb90b2392e349f0770e7212ac911dc55ae"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1), Duration::seconds(30));
+        assert_eq!(backoff(2), Duration::seconds(60));
+        assert_eq!(backoff(5), Duration::seconds(480));
+        assert_eq!(backoff(30), Duration::seconds(MAX_BACKOFF_SECONDS));
+    }
+
+    #[test]
+    fn only_http_urls_and_known_events_are_accepted() {
+        assert!(is_valid_url("https://discord.example.com/hooks/1"));
+        assert!(!is_valid_url("ftp://example.com/hook"));
+        assert!(!is_valid_url("not a url"));
+        assert!(is_valid_event(GAME_OVER));
+        assert!(!is_valid_event("room_deleted"));
+    }
+}
@@ -0,0 +1,65 @@
+//!
+//! Achievement Controller
+//!
+//! - GET /api/v1/user/{id}/achievements: A user's achievements with their
+//!   progress towards each and when they were unlocked
+//!
+//! Achievements are unlocked by the achievement event handler as games finish
+//! and payments go through; this endpoint only reads them.
+//!
+
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use tracing::error;
+
+use crate::app::db_query::read::achievements::{self as db_achievements, UserAchievement};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::read::user as db_user;
+use crate::database::AppState;
+
+/// Achievement Controller
+pub struct AchievementController;
+
+/// Achievement list response
+#[derive(Debug, Serialize)]
+pub struct AchievementsResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub user_id: i64,
+    /// How many of the achievements the user unlocked
+    pub unlocked: usize,
+    pub achievements: Vec<UserAchievement>,
+}
+
+impl AchievementController {
+    /// GET /user/{id}/achievements - Unlocked achievements first, then the
+    /// locked ones with the user's progress
+    ///
+    /// # Responses
+    /// - 200: Achievements retrieved
+    /// - 401: Unauthorized
+    /// - 404: User not found
+    /// - 500: Internal server error
+    pub async fn for_user(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let user_id = path.into_inner();
+        let db = state.read_db().await;
+
+        if db_user::get_by_id(&db, user_id).await.is_err() {
+            return HttpResponse::NotFound().json(BaseResponse::error("User not found"));
+        }
+
+        match db_achievements::list_for_user(&db, user_id).await {
+            Ok(achievements) => HttpResponse::Ok().json(AchievementsResponse {
+                base: BaseResponse::success("Achievements retrieved"),
+                user_id,
+                unlocked: achievements.iter().filter(|a| a.unlocked_at.is_some()).count(),
+                achievements,
+            }),
+            Err(e) => {
+                error!("Failed to load achievements of user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load achievements"))
+            }
+        }
+    }
+}
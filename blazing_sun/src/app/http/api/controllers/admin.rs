@@ -4,12 +4,25 @@
 //! - GET /api/v1/admin/uploads - List all uploads (Admin+: permission >= 10)
 //! - GET /api/v1/admin/assets - List all assets (Admin+: permission >= 10)
 //! - GET /api/v1/admin/users - List all users (Super Admin: permission >= 100)
+//! - GET /api/v1/admin/users/erasures - Open account deletion requests (Super Admin)
+//! - GET /api/v1/admin/users/{id}/balance?at= - User's balance at a point in time (Super Admin)
 //! - DELETE /api/v1/admin/users/{id}/avatar - Delete user's avatar (Admin+)
+//! - GET /api/v1/admin/cache/stats - Hit/miss counters of this instance's caches (Admin+)
+//! - GET /api/v1/admin/kafka/consumer - Rebalances and handler drain latency of this instance's consumer (Admin+)
+//! - GET /api/v1/admin/kafka/producer - Payloads of this instance's producer near or over the size limit (Admin+)
+//! - GET /api/v1/admin/kafka/handlers - Calls, errors and dead letters per event handler of this instance (Admin+)
+//! - GET /api/v1/admin/kafka/dead-letters?topic= - Dead letters of a source topic (Admin+)
+//! - POST /api/v1/admin/kafka/dead-letters/replay - Requeue a dead letter for its failed handler (Admin+)
+//! - GET /api/v1/admin/database/pools - Connection pool usage of this instance per subsystem (Admin+)
 
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::app::cache::{CacheStats, UserProfileCache};
+use crate::app::games::state_checksum::{self, DesyncStats};
 use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::http::api::controllers::user::UserController;
 use crate::app::mq::jobs::bulk_delete_uploads::BulkDeleteUploadsParams;
 use crate::app::mq::jobs::bulk_user_action::BulkUserActionParams;
 use crate::app::mq::jobs::delete_user::DeleteUserParams;
@@ -18,9 +31,12 @@ use crate::bootstrap::middleware::controllers::permission::levels;
 use crate::database::mutations::asset as db_asset_mutations;
 use crate::database::mutations::user as db_user_mutations;
 use crate::database::read::asset as db_asset_read;
+use crate::database::read::balance_ledger::{self as db_ledger_read, HistoricalBalance};
 use crate::database::read::upload as db_upload_read;
 use crate::database::read::user as db_user_read;
-use crate::database::AppState;
+use crate::database::read::user_erasure as db_erasure_read;
+use crate::database::{self, AppState, PoolStats, ReplicaStatus};
+use crate::events::{self, DeadLetterQuery, DeadLetterRecord, HandlerStats};
 use crate::mq::{self, JobOptions, JobStatus};
 use uuid::Uuid;
 
@@ -93,6 +109,20 @@ pub struct AdminUserDto {
     pub updated_at: String,
 }
 
+/// Account deletion request DTO for admin view
+#[derive(Serialize)]
+pub struct AdminErasureDto {
+    pub id: i64,
+    pub user_id: i64,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub status: String,
+    pub requested_at: String,
+    pub scheduled_for: String,
+    pub error: Option<String>,
+}
+
 impl AdminController {
     /// GET /api/v1/admin/uploads - List all uploads (Admin+)
     ///
@@ -126,7 +156,7 @@ impl AdminController {
             }
         });
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         let uploads =
             db_upload_read::get_all_filtered(&db, limit, offset, storage_type, search).await;
         let total = db_upload_read::count_filtered(&db, storage_type, search).await;
@@ -170,7 +200,7 @@ impl AdminController {
         let limit = query.limit.unwrap_or(50).min(100); // Max 100
         let offset = query.offset.unwrap_or(0);
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         let assets = db_asset_read::get_all(&db, limit, offset).await;
         let total = db_asset_read::count(&db).await;
 
@@ -213,7 +243,7 @@ impl AdminController {
         let limit = query.limit.unwrap_or(50).min(100); // Max 100
         let offset = query.offset.unwrap_or(0);
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         let users = db_user_read::get_all(&db, limit, offset).await;
         let total = db_user_read::count(&db).await;
 
@@ -243,11 +273,64 @@ impl AdminController {
         }))
     }
 
+    /// GET /api/v1/admin/users/erasures - Open account deletion requests (Super Admin only)
+    ///
+    /// Pending (in grace period), queued and failed requests, soonest erasure first.
+    ///
+    /// Query params:
+    /// - limit: Max number of results (default 50)
+    /// - offset: Number to skip (default 0)
+    pub async fn pending_erasures(
+        state: web::Data<AppState>,
+        query: web::Query<PaginationQuery>,
+    ) -> HttpResponse {
+        let limit = query.limit.unwrap_or(50).min(100); // Max 100
+        let offset = query.offset.unwrap_or(0);
+
+        let db = state.read_db().await;
+        let (requests, total) = match tokio::try_join!(
+            db_erasure_read::get_open_report(&db, limit, offset),
+            db_erasure_read::count_open(&db)
+        ) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to load erasure requests: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve erasure requests"));
+            }
+        };
+
+        let erasure_dtos: Vec<AdminErasureDto> = requests
+            .into_iter()
+            .map(|r| AdminErasureDto {
+                id: r.request.id,
+                user_id: r.request.user_id,
+                email: r.email,
+                first_name: r.first_name,
+                last_name: r.last_name,
+                status: r.request.status,
+                requested_at: r.request.requested_at.to_rfc3339(),
+                scheduled_for: r.request.scheduled_for.to_rfc3339(),
+                error: r.request.error,
+            })
+            .collect();
+
+        HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "erasures": erasure_dtos,
+            "total": total,
+            "limit": limit,
+            "offset": offset
+        }))
+    }
+
     /// DELETE /api/v1/admin/users/{id}/avatar - Delete a user's avatar (Admin+)
     pub async fn delete_user_avatar(
         state: web::Data<AppState>,
+        req: HttpRequest,
         path: web::Path<i64>,
     ) -> HttpResponse {
+        let admin_id = req.extensions().get::<i64>().copied().unwrap_or_default();
         let user_id = path.into_inner();
         let db = state.db.lock().await;
 
@@ -286,9 +369,246 @@ impl AdminController {
                 .json(BaseResponse::error("Failed to clear user avatar"));
         }
 
+        UserController::profile_changed(&state, &user, vec!["avatar"], admin_id).await;
+
         HttpResponse::Ok().json(BaseResponse::success("User avatar deleted successfully"))
     }
 
+    /// GET /api/v1/admin/cache/stats - Cache counters of this instance (Admin+)
+    ///
+    /// Counters are kept per process and reset on restart.
+    pub async fn cache_stats(state: web::Data<AppState>) -> HttpResponse {
+        HttpResponse::Ok().json(CacheStatsResponse {
+            base: BaseResponse::success("Cache stats retrieved"),
+            caches: vec![UserProfileCache::new(state.redis()).stats()],
+        })
+    }
+
+    /// GET /api/v1/admin/kafka/consumer - Event consumer rebalance stats of this instance (Admin+)
+    ///
+    /// Counters are kept per process and reset on restart.
+    pub async fn kafka_consumer() -> HttpResponse {
+        let metrics = events::rebalance_metrics();
+        HttpResponse::Ok().json(KafkaConsumerResponse {
+            base: BaseResponse::success("Kafka consumer stats retrieved"),
+            in_flight_handlers: events::in_flight_handlers(),
+            assignments: metrics.assignments,
+            revocations: metrics.revocations,
+            rebalance_errors: metrics.errors,
+            drain_timeouts: metrics.drain_timeouts,
+            drain_last_ms: metrics.drain_last_ms,
+            drain_max_ms: metrics.drain_max_ms,
+            drain_avg_ms: metrics
+                .drain_total_ms
+                .checked_div(metrics.drain_count)
+                .unwrap_or(0),
+        })
+    }
+
+    /// GET /api/v1/admin/kafka/producer - Event producer payload sizes of this instance (Admin+)
+    ///
+    /// Counters are kept per process and reset on restart.
+    pub async fn kafka_producer(state: web::Data<AppState>) -> HttpResponse {
+        let Some(event_bus) = state.event_bus() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Kafka producer not available"));
+        };
+        let producer = event_bus.producer();
+        let metrics = producer.size_metrics();
+        HttpResponse::Ok().json(KafkaProducerResponse {
+            base: BaseResponse::success("Kafka producer stats retrieved"),
+            max_payload_bytes: producer.size_limits().max_bytes,
+            largest_payload_bytes: metrics.largest_bytes(),
+            near_limit: metrics.near_limit(),
+            segmented: metrics.segmented(),
+            oversized: metrics.rejected(),
+        })
+    }
+
+    /// GET /api/v1/admin/kafka/handlers - Event handler outcomes of this instance (Admin+)
+    ///
+    /// Counters are kept per process and reset on restart.
+    pub async fn kafka_handlers() -> HttpResponse {
+        HttpResponse::Ok().json(KafkaHandlersResponse {
+            base: BaseResponse::success("Kafka handler stats retrieved"),
+            handlers: events::handler_metrics(),
+        })
+    }
+
+    /// GET /api/v1/admin/kafka/dead-letters?topic=&partition=&from=&limit= - Dead letters (Admin+)
+    ///
+    /// Reads `<topic>.dlq`: the latest `limit` (default 20, at most 100) dead
+    /// letters of each partition, or `limit` from offset `from` on.
+    pub async fn kafka_dead_letters(query: web::Query<DeadLetterListQuery>) -> HttpResponse {
+        let query = query.into_inner();
+        if !is_source_topic(&query.topic) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("topic must be a source topic name"));
+        }
+
+        let dead_letter_query = DeadLetterQuery {
+            source_topic: query.topic,
+            partition: query.partition,
+            from_offset: query.from,
+            limit: query
+                .limit
+                .unwrap_or(DEFAULT_DEAD_LETTER_LIMIT)
+                .clamp(1, MAX_DEAD_LETTER_LIMIT),
+        };
+        let browsed = tokio::task::spawn_blocking(move || {
+            events::dead_letter::browse(&dead_letter_query)
+        })
+        .await;
+
+        match browsed {
+            Ok(Ok(dead_letters)) => HttpResponse::Ok().json(DeadLettersResponse {
+                base: BaseResponse::success("Dead letters retrieved"),
+                dead_letters,
+            }),
+            Ok(Err(e)) => {
+                tracing::error!("Failed to read dead letters: {}", e);
+                HttpResponse::ServiceUnavailable()
+                    .json(BaseResponse::error("Failed to read dead letters"))
+            }
+            Err(e) => {
+                tracing::error!("Dead letter browsing task failed: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to read dead letters"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/kafka/dead-letters/replay - Requeue a dead letter (Admin+)
+    ///
+    /// Publishes the original message of the dead letter at `partition` /
+    /// `offset` of `<topic>.dlq` to `topic` again, for the handler that failed
+    /// it only. The dead letter itself stays in place.
+    pub async fn replay_dead_letter(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<ReplayDeadLetterRequest>,
+    ) -> HttpResponse {
+        let body = body.into_inner();
+        if !is_source_topic(&body.topic) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("topic must be a source topic name"));
+        }
+        let Some(event_bus) = state.event_bus() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Kafka producer not available"));
+        };
+
+        let topic = body.topic.clone();
+        let fetched = tokio::task::spawn_blocking(move || {
+            events::dead_letter::fetch(&topic, body.partition, body.offset)
+        })
+        .await;
+        let record = match fetched {
+            Ok(Ok(Some(record))) => record,
+            Ok(Ok(None)) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Dead letter not found"));
+            }
+            Ok(Err(e)) => {
+                tracing::error!("Failed to read dead letter: {}", e);
+                return HttpResponse::ServiceUnavailable()
+                    .json(BaseResponse::error("Failed to read dead letters"));
+            }
+            Err(e) => {
+                tracing::error!("Dead letter lookup task failed: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to read dead letters"));
+            }
+        };
+
+        if let Err(e) =
+            events::dead_letter::requeue(event_bus.producer(), &record.dead_letter).await
+        {
+            tracing::error!("Failed to requeue dead letter: {}", e);
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Failed to requeue dead letter"));
+        }
+
+        tracing::info!(
+            admin_id = ?req.extensions().get::<i64>().copied(),
+            topic = %record.dead_letter.source_topic,
+            partition = record.partition,
+            offset = record.offset,
+            handler = %record.dead_letter.handler,
+            event_id = %record.dead_letter.event_id,
+            "Dead letter requeued"
+        );
+
+        HttpResponse::Ok().json(DeadLetterReplayResponse {
+            base: BaseResponse::success("Dead letter requeued"),
+            dead_letter: record,
+        })
+    }
+
+    /// GET /api/v1/admin/database/pools - Connection pool usage of this instance (Admin+)
+    ///
+    /// One entry per subsystem pool (http, cron, mq, events), and the health
+    /// and lag of each read replica pool. Counters are kept per process and
+    /// reset on restart.
+    pub async fn database_pools() -> HttpResponse {
+        HttpResponse::Ok().json(DatabasePoolsResponse {
+            base: BaseResponse::success("Database pool stats retrieved"),
+            pools: database::pool_stats(),
+            replicas: database::replica_stats(),
+        })
+    }
+
+    /// GET /api/v1/admin/games/desyncs - Desync reports per game type of this instance (Admin+)
+    ///
+    /// Counters are kept per process and reset on restart.
+    pub async fn game_desyncs() -> HttpResponse {
+        HttpResponse::Ok().json(GameDesyncsResponse {
+            base: BaseResponse::success("Desync stats retrieved"),
+            games: state_checksum::desync_stats(),
+        })
+    }
+
+    /// GET /api/v1/admin/users/{id}/balance?at= - User's balance at a point in time (Super Admin only)
+    ///
+    /// `at` is an RFC 3339 timestamp, or a date (`2026-03-03`) meaning the end
+    /// of that day (UTC). The balance is rebuilt from the balance ledger,
+    /// starting at the latest daily snapshot before `at`. Users whose balance
+    /// was changed without ledger entries are refused.
+    pub async fn user_balance_at(
+        state: web::Data<AppState>,
+        path: web::Path<i64>,
+        query: web::Query<BalanceAtQuery>,
+    ) -> HttpResponse {
+        let user_id = path.into_inner();
+
+        let Some(at) = parse_balance_at(&query.at) else {
+            return HttpResponse::BadRequest().json(BaseResponse::error(
+                "at must be an RFC 3339 timestamp or a YYYY-MM-DD date",
+            ));
+        };
+        if at > Utc::now() {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("at must not be in the future"));
+        }
+
+        let db = state.read_db().await;
+        match db_ledger_read::balance_at(&db, user_id, at).await {
+            Ok(Some(balance)) if balance.untracked_changes => HttpResponse::Conflict().json(
+                BaseResponse::error("Balance was changed without ledger entries"),
+            ),
+            Ok(Some(balance)) => HttpResponse::Ok().json(BalanceAtResponse {
+                base: BaseResponse::success("Balance retrieved"),
+                at: at.to_rfc3339(),
+                balance,
+            }),
+            Ok(None) => HttpResponse::NotFound().json(BaseResponse::error("User not found")),
+            Err(e) => {
+                tracing::error!("Failed to rebuild balance of user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load balance"))
+            }
+        }
+    }
+
     /// PATCH /api/v1/admin/users/{id}/permissions - Update user's permissions (Super Admin only)
     pub async fn update_user_permissions(
         state: web::Data<AppState>,
@@ -299,9 +619,9 @@ impl AdminController {
         let new_permissions = body.permissions;
 
         // Validate permission value
-        if ![1, 10, 50, 100].contains(&new_permissions) {
+        if ![1, 5, 10, 50, 100].contains(&new_permissions) {
             return HttpResponse::BadRequest().json(BaseResponse::error(
-                "Invalid permission level. Must be 1, 10, 50, or 100",
+                "Invalid permission level. Must be 1, 5, 10, 50, or 100",
             ));
         }
 
@@ -348,9 +668,9 @@ impl AdminController {
 
         if payload.action == "set_permissions" {
             if let Some(value) = payload.permissions {
-                if ![1, 10, 50, 100].contains(&value) {
+                if ![1, 5, 10, 50, 100].contains(&value) {
                     return HttpResponse::BadRequest().json(BaseResponse::error(
-                        "Invalid permission level. Must be 1, 10, 50, or 100",
+                        "Invalid permission level. Must be 1, 5, 10, 50, or 100",
                     ));
                 }
             } else {
@@ -384,7 +704,7 @@ impl AdminController {
                 .json(BaseResponse::error("Unexpected job status")),
             Err(e) => {
                 tracing::error!("Bulk user action job error: {}", e);
-                HttpResponse::InternalServerError().json(BaseResponse::error("Bulk action failed"))
+                HttpResponse::build(e.status_code()).json(BaseResponse::error("Bulk action failed"))
             }
         }
     }
@@ -816,6 +1136,61 @@ impl AdminController {
     }
 }
 
+/// Historical balance query
+#[derive(Deserialize)]
+pub struct BalanceAtQuery {
+    pub at: String,
+}
+
+/// Parse `at` of a historical balance query: an RFC 3339 timestamp, or a
+/// date standing for the last instant of that day (UTC)
+fn parse_balance_at(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    date.and_hms_micro_opt(23, 59, 59, 999_999)
+        .map(|end_of_day| end_of_day.and_utc())
+}
+
+/// Dead letters listed when no limit is given
+const DEFAULT_DEAD_LETTER_LIMIT: usize = 20;
+
+/// Most dead letters listed per partition
+const MAX_DEAD_LETTER_LIMIT: usize = 100;
+
+/// Dead letter listing query
+#[derive(Deserialize)]
+pub struct DeadLetterListQuery {
+    /// Source topic, e.g. `user.events` (its dead letters are in `user.events.dlq`)
+    pub topic: String,
+    pub partition: Option<i32>,
+    pub from: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// Request to requeue a dead letter
+#[derive(Deserialize)]
+pub struct ReplayDeadLetterRequest {
+    /// Source topic of the dead letter
+    pub topic: String,
+    /// Partition and offset of the dead letter in `<topic>.dlq`
+    pub partition: i32,
+    pub offset: i64,
+}
+
+/// Whether `topic` names a source topic (dead letter topics have no dead letters)
+fn is_source_topic(topic: &str) -> bool {
+    !topic.is_empty()
+        && topic.len() <= 249
+        && !events::topic::is_dead_letter(topic)
+        && topic
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
 /// Request to update user permissions
 #[derive(Deserialize)]
 pub struct UpdatePermissionsRequest {
@@ -841,3 +1216,121 @@ pub struct UpdateUploadMetadataRequest {
     pub description: Option<String>,
     pub storage_type: Option<String>,
 }
+
+/// Cache stats response
+#[derive(Serialize)]
+struct CacheStatsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    caches: Vec<CacheStats>,
+}
+
+/// Kafka consumer rebalance stats response
+#[derive(Serialize)]
+struct KafkaConsumerResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    in_flight_handlers: usize,
+    assignments: u64,
+    revocations: u64,
+    rebalance_errors: u64,
+    drain_timeouts: u64,
+    drain_last_ms: u64,
+    drain_max_ms: u64,
+    drain_avg_ms: u64,
+}
+
+/// Kafka producer payload size stats response
+#[derive(Serialize)]
+struct KafkaProducerResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    max_payload_bytes: usize,
+    largest_payload_bytes: u64,
+    near_limit: u64,
+    segmented: u64,
+    oversized: u64,
+}
+
+/// Event handler stats response
+#[derive(Serialize)]
+struct KafkaHandlersResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    handlers: Vec<HandlerStats>,
+}
+
+/// Dead letter listing response
+#[derive(Serialize)]
+struct DeadLettersResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    dead_letters: Vec<DeadLetterRecord>,
+}
+
+/// Requeued dead letter response
+#[derive(Serialize)]
+struct DeadLetterReplayResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    dead_letter: DeadLetterRecord,
+}
+
+/// Database pool stats response
+#[derive(Serialize)]
+struct DatabasePoolsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    pools: Vec<PoolStats>,
+    /// Read replica pools (empty without DATABASE_READ_URL)
+    replicas: Vec<ReplicaStatus>,
+}
+
+/// Game desync stats response
+#[derive(Serialize)]
+struct GameDesyncsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    games: Vec<DesyncStats>,
+}
+
+/// Historical balance response
+#[derive(Serialize)]
+struct BalanceAtResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    at: String,
+    balance: HistoricalBalance,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance_at_accepts_timestamps() {
+        let at = parse_balance_at("2026-03-03T14:30:00+02:00").unwrap();
+        assert_eq!(at.to_rfc3339(), "2026-03-03T12:30:00+00:00");
+    }
+
+    #[test]
+    fn balance_at_date_means_end_of_day() {
+        let at = parse_balance_at("2026-03-03").unwrap();
+        assert_eq!(at.to_rfc3339(), "2026-03-03T23:59:59.999999+00:00");
+    }
+
+    #[test]
+    fn balance_at_rejects_other_values() {
+        assert!(parse_balance_at("March 3rd").is_none());
+        assert!(parse_balance_at("2026-02-30").is_none());
+    }
+
+    #[test]
+    fn dead_letters_are_listed_by_source_topic() {
+        assert!(is_source_topic("user.events"));
+        assert!(is_source_topic("games.commands.eu-west"));
+        assert!(!is_source_topic("user.events.dlq"));
+        assert!(!is_source_topic(""));
+        assert!(!is_source_topic("user events"));
+    }
+}
@@ -0,0 +1,299 @@
+//!
+//! Analytics Controller
+//!
+//! Admin reports built from the MongoDB analytics projections:
+//! - GET /api/v1/admin/analytics/games: Daily rooms created -> started -> finished funnel
+//! - GET /api/v1/admin/analytics/checkouts: Daily checkout sessions, outcomes and revenue
+//!
+//! Both take `from`/`to` (`YYYY-MM-DD`, UTC, inclusive; default the last 30 days).
+//! Rooms and checkouts are bucketed by the day they were first seen.
+//!
+
+use actix_web::{web, HttpResponse};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::app::analytics::mongodb_analytics::{CheckoutDay, GameDay, MongoAnalyticsClient};
+use crate::app::games::types::GameType;
+use crate::app::http::api::controllers::responses::{BaseResponse, ValidationErrorResponse};
+use crate::app::http::api::validators::FieldError;
+use crate::bootstrap::database::AppState;
+
+/// Default report window in days
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+/// Longest report window in days
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// Analytics Controller
+pub struct AnalyticsController;
+
+/// Report query
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Games report only: restrict to one game type
+    pub game_type: Option<String>,
+}
+
+/// Games funnel for one day (or the whole range)
+#[derive(Debug, Serialize)]
+pub struct GameFunnelItem {
+    #[serde(flatten)]
+    pub counts: GameDay,
+    /// Share of started games that finished (0-1)
+    pub completion_rate: f64,
+}
+
+impl From<GameDay> for GameFunnelItem {
+    fn from(counts: GameDay) -> Self {
+        Self {
+            completion_rate: ratio(counts.games_finished, counts.games_started),
+            counts,
+        }
+    }
+}
+
+/// Games report response
+#[derive(Debug, Serialize)]
+pub struct GameAnalyticsResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub game_type: Option<String>,
+    pub totals: GameFunnelItem,
+    pub days: Vec<GameFunnelItem>,
+}
+
+/// Checkouts report response
+#[derive(Debug, Serialize)]
+pub struct CheckoutAnalyticsResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub totals: CheckoutDay,
+    pub days: Vec<CheckoutDay>,
+}
+
+impl AnalyticsController {
+    /// GET /api/v1/admin/analytics/games - Daily games funnel
+    pub async fn games(
+        state: web::Data<AppState>,
+        query: web::Query<AnalyticsQuery>,
+    ) -> HttpResponse {
+        let (from, to) = match report_range(&query) {
+            Ok(range) => range,
+            Err(response) => return response,
+        };
+
+        if let Some(game_type) = &query.game_type {
+            if GameType::from_str(game_type).is_none() {
+                return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(vec![
+                    FieldError::new("game_type", "invalid", "Unknown game type"),
+                ]));
+            }
+        }
+
+        let Some(client) = analytics_client(&state) else {
+            return unavailable();
+        };
+
+        let days = match client
+            .game_days(&day(from), &day(to), query.game_type.as_deref())
+            .await
+        {
+            Ok(days) => days,
+            Err(e) => {
+                error!("Failed to aggregate game analytics: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve game analytics"));
+            }
+        };
+
+        HttpResponse::Ok().json(GameAnalyticsResponse {
+            base: BaseResponse::success("Game analytics retrieved"),
+            from,
+            to,
+            game_type: query.game_type.clone(),
+            totals: game_totals(&days).into(),
+            days: days.into_iter().map(GameFunnelItem::from).collect(),
+        })
+    }
+
+    /// GET /api/v1/admin/analytics/checkouts - Daily checkout outcomes
+    pub async fn checkouts(
+        state: web::Data<AppState>,
+        query: web::Query<AnalyticsQuery>,
+    ) -> HttpResponse {
+        let (from, to) = match report_range(&query) {
+            Ok(range) => range,
+            Err(response) => return response,
+        };
+
+        let Some(client) = analytics_client(&state) else {
+            return unavailable();
+        };
+
+        let days = match client.checkout_days(&day(from), &day(to)).await {
+            Ok(days) => days,
+            Err(e) => {
+                error!("Failed to aggregate checkout analytics: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve checkout analytics"));
+            }
+        };
+
+        let start = CheckoutDay {
+            day: "total".to_string(),
+            ..CheckoutDay::default()
+        };
+        let totals = days.iter().fold(start, |mut totals, d| {
+            totals.sessions_created += d.sessions_created;
+            totals.succeeded += d.succeeded;
+            totals.failed += d.failed;
+            totals.revenue_cents += d.revenue_cents;
+            totals
+        });
+
+        HttpResponse::Ok().json(CheckoutAnalyticsResponse {
+            base: BaseResponse::success("Checkout analytics retrieved"),
+            from,
+            to,
+            totals,
+            days,
+        })
+    }
+}
+
+fn analytics_client(state: &AppState) -> Option<MongoAnalyticsClient> {
+    state
+        .mongo()
+        .map(|mongodb| MongoAnalyticsClient::new(mongodb.clone()))
+}
+
+fn unavailable() -> HttpResponse {
+    error!("MongoDB not available for analytics");
+    HttpResponse::ServiceUnavailable().json(BaseResponse::error("Analytics service unavailable"))
+}
+
+fn day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Resolve the requested window, defaulting to the last 30 days
+fn report_range(query: &AnalyticsQuery) -> Result<(NaiveDate, NaiveDate), HttpResponse> {
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query
+        .from
+        .unwrap_or_else(|| to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+
+    let error = if from > to {
+        Some(FieldError::new("from", "range", "from must not be after to"))
+    } else if (to - from).num_days() >= MAX_RANGE_DAYS {
+        Some(
+            FieldError::new("from", "range", "Range is too long")
+                .with_constraint(serde_json::json!({ "max_days": MAX_RANGE_DAYS })),
+        )
+    } else {
+        None
+    };
+
+    match error {
+        Some(error) => Err(HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(vec![error]))),
+        None => Ok((from, to)),
+    }
+}
+
+/// Sum daily funnels; the average duration is weighted by timed games per day
+fn game_totals(days: &[GameDay]) -> GameDay {
+    let mut totals = GameDay {
+        day: "total".to_string(),
+        ..GameDay::default()
+    };
+    let mut duration_sum = 0.0;
+
+    for d in days {
+        totals.rooms_created += d.rooms_created;
+        totals.games_started += d.games_started;
+        totals.games_finished += d.games_finished;
+        totals.rooms_abandoned += d.rooms_abandoned;
+        if let Some(avg) = d.avg_match_duration_seconds {
+            totals.timed_games += d.timed_games;
+            duration_sum += avg * d.timed_games as f64;
+        }
+    }
+
+    totals.avg_match_duration_seconds =
+        (totals.timed_games > 0).then(|| duration_sum / totals.timed_games as f64);
+    totals
+}
+
+fn ratio(part: i64, whole: i64) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game_day(day: &str, started: i64, finished: i64, timed: i64, avg: Option<f64>) -> GameDay {
+        GameDay {
+            day: day.to_string(),
+            rooms_created: started + 1,
+            games_started: started,
+            games_finished: finished,
+            rooms_abandoned: 1,
+            timed_games: timed,
+            avg_match_duration_seconds: avg,
+        }
+    }
+
+    #[test]
+    fn totals_weight_average_duration_by_timed_games() {
+        let totals = game_totals(&[
+            game_day("2026-10-16", 4, 3, 3, Some(100.0)),
+            game_day("2026-10-17", 2, 1, 1, Some(200.0)),
+            game_day("2026-10-18", 1, 0, 0, None),
+        ]);
+
+        assert_eq!(totals.games_started, 7);
+        assert_eq!(totals.games_finished, 4);
+        assert_eq!(totals.rooms_abandoned, 3);
+        assert_eq!(totals.avg_match_duration_seconds, Some(125.0));
+
+        let item = GameFunnelItem::from(totals);
+        assert!((item.completion_rate - 4.0 / 7.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn empty_ranges_have_no_average() {
+        let totals = game_totals(&[]);
+
+        assert_eq!(totals.avg_match_duration_seconds, None);
+        assert_eq!(GameFunnelItem::from(totals).completion_rate, 0.0);
+    }
+
+    #[test]
+    fn range_defaults_to_thirty_days_and_rejects_inverted_or_long_windows() {
+        let to = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let query = |from: Option<NaiveDate>| AnalyticsQuery {
+            from,
+            to: Some(to),
+            game_type: None,
+        };
+
+        let (from, _) = report_range(&query(None)).unwrap();
+        assert_eq!((to - from).num_days(), DEFAULT_RANGE_DAYS - 1);
+
+        assert!(report_range(&query(to.succ_opt())).is_err());
+        assert!(report_range(&query(Some(to - Duration::days(MAX_RANGE_DAYS)))).is_err());
+    }
+}
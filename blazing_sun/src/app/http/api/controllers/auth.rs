@@ -2,26 +2,34 @@
 //!
 //! Handles user authentication operations:
 //! - Sign Up: Create a new user account
-//! - Sign In: Authenticate and receive JWT token
+//! - Sign In: Authenticate and receive JWT token (or a pre-auth token when a
+//!   two-factor code is needed, see `TwoFactorController`)
 
 use actix_web::{web, HttpRequest, HttpResponse};
 use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use validator::Validate;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
 
 use crate::app::http::api::controllers::responses::{
     BaseResponse, MissingFieldsResponse, UserDto, ValidationErrorResponse,
 };
+use crate::app::http::api::controllers::two_factor::TwoFactorController;
 use crate::app::http::api::validators::auth::{
     validate_name, validate_password, validate_passwords_match, SigninRequest, SigninRequestRaw,
     SignupRequest, SignupRequestRaw,
 };
+use crate::app::http::api::validators::{validate_request, FieldError};
+use crate::app::sessions;
+use crate::bootstrap::middleware::controllers::rbac::role_of;
 use crate::config::{ActivationConfig, JwtConfig};
 use crate::database::mutations::activation_hash as db_activation_hash;
+use crate::database::mutations::auth_sessions as db_auth_session_mut;
 use crate::database::mutations::session_refresh_token as db_refresh_token_mut;
+use crate::database::read::auth_sessions as db_auth_session;
 use crate::database::read::session_refresh_token as db_refresh_token;
-use crate::database::read::user as db_user;
+use crate::database::read::user::{self as db_user, User};
+use crate::database::read::user_preferences as db_user_preferences;
 use crate::database::AppState;
 use crate::events;
 use crate::mq::jobs::create_user::CreateUserParams;
@@ -32,9 +40,22 @@ use crate::mq::{self, JobOptions, JobStatus};
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Claims {
     pub sub: i64,
+    /// RBAC role derived from `permissions`
     pub role: String,
     pub permissions: i16,
     pub exp: i64,
+    /// Preferred message locale from user_preferences; absent in older tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Admin acting as `sub`; only set on impersonation tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<i64>,
+    /// `impersonation_sessions` row the token belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonation_id: Option<Uuid>,
+    /// `auth_sessions` row of the signed-in device; absent in older tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
 }
 
 /// Sign In Response
@@ -44,6 +65,9 @@ struct SignInResponse {
     base: BaseResponse,
     token: String,
     user: UserDto,
+    /// Recovery codes, when the sign-in also completed two-factor enrollment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recovery_codes: Option<Vec<String>>,
 }
 
 /// Authentication Controller
@@ -110,64 +134,31 @@ impl AuthController {
             last_name: raw.last_name.clone().unwrap(),
         };
 
-        let mut errors: HashMap<String, Vec<String>> = HashMap::new();
-
         // Validate using validator crate
-        if let Err(validation_errors) = user.validate() {
-            for (field, field_errors) in validation_errors.field_errors() {
-                let messages: Vec<String> = field_errors
-                    .iter()
-                    .map(|e| {
-                        e.message
-                            .as_ref()
-                            .map(|m| m.to_string())
-                            .unwrap_or_else(|| e.code.to_string())
-                    })
-                    .collect();
-                errors.insert(field.to_string(), messages);
-            }
-        }
+        let mut errors = validate_request(&user);
 
         // Validate password separately to get all errors
         let password_errors = validate_password(&user.password);
-        if !password_errors.is_empty() {
-            errors
-                .entry("password".to_string())
-                .or_default()
-                .extend(password_errors);
-        }
+        errors.extend(FieldError::each("password", "password_policy", password_errors));
 
         // Validate that password and confirm_password match
         if let Some(mismatch_error) =
             validate_passwords_match(&user.password, &user.confirm_password)
         {
-            errors
-                .entry("confirm_password".to_string())
-                .or_default()
-                .push(mismatch_error);
+            errors.push(FieldError::new("confirm_password", "must_match", mismatch_error));
         }
 
         // Validate first_name (letters only, min 2 chars)
         let first_name_errors = validate_name(&user.first_name, "first_name");
-        if !first_name_errors.is_empty() {
-            errors
-                .entry("first_name".to_string())
-                .or_default()
-                .extend(first_name_errors);
-        }
+        errors.extend(FieldError::each("first_name", "name", first_name_errors));
 
         // Validate last_name (letters only, min 2 chars)
         let last_name_errors = validate_name(&user.last_name, "last_name");
-        if !last_name_errors.is_empty() {
-            errors
-                .entry("last_name".to_string())
-                .or_default()
-                .extend(last_name_errors);
-        }
+        errors.extend(FieldError::each("last_name", "name", last_name_errors));
 
         // If validation errors, return with object format
         if !errors.is_empty() {
-            return HttpResponse::BadRequest().json(ValidationErrorResponse::new(errors));
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
         }
 
         let db = state.db.lock().await;
@@ -294,6 +285,7 @@ impl AuthController {
     /// - 401: Invalid credentials
     pub async fn sign_in(
         state: web::Data<AppState>,
+        req: HttpRequest,
         body: web::Json<SigninRequestRaw>,
     ) -> HttpResponse {
         let raw = body.into_inner();
@@ -319,36 +311,16 @@ impl AuthController {
             remember_me: raw.remember_me,
         };
 
-        let mut errors: HashMap<String, Vec<String>> = HashMap::new();
-
         // Validate using validator crate
-        if let Err(validation_errors) = user_data.validate() {
-            for (field, field_errors) in validation_errors.field_errors() {
-                let messages: Vec<String> = field_errors
-                    .iter()
-                    .map(|e| {
-                        e.message
-                            .as_ref()
-                            .map(|m| m.to_string())
-                            .unwrap_or_else(|| e.code.to_string())
-                    })
-                    .collect();
-                errors.insert(field.to_string(), messages);
-            }
-        }
+        let mut errors = validate_request(&user_data);
 
         // Validate password separately to get all errors
         let password_errors = validate_password(&user_data.password);
-        if !password_errors.is_empty() {
-            errors
-                .entry("password".to_string())
-                .or_default()
-                .extend(password_errors);
-        }
+        errors.extend(FieldError::each("password", "password_policy", password_errors));
 
         // If validation errors, return with object format
         if !errors.is_empty() {
-            return HttpResponse::BadRequest().json(ValidationErrorResponse::new(errors));
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
         }
 
         let db = state.db.lock().await;
@@ -399,11 +371,67 @@ impl AuthController {
             ));
         }
 
+        // With two-factor the JWT waits for a code (or for enrollment when the role requires it)
+        match TwoFactorController::challenge_purpose(&db, &user).await {
+            Ok(Some(purpose)) => {
+                return TwoFactorController::start_challenge(
+                    &db,
+                    &user,
+                    purpose,
+                    user_data.remember_me,
+                )
+                .await;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("Failed to load two-factor state of user {}: {}", user.id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to sign in"));
+            }
+        }
+
+        Self::complete_sign_in(&state, &req, &db, &user, user_data.remember_me, None).await
+    }
+
+    /// Issue the JWT (and the refresh token with `remember_me`) for a user
+    /// whose credentials, and two-factor code if needed, were accepted;
+    /// `recovery_codes` are passed on when the sign-in enrolled two-factor
+    pub(crate) async fn complete_sign_in(
+        state: &AppState,
+        req: &HttpRequest,
+        db: &Pool<Postgres>,
+        user: &User,
+        remember_me: bool,
+        recovery_codes: Option<Vec<String>>,
+    ) -> HttpResponse {
+        // Record the device; without a session the token cannot be revoked on its own
+        let device_info = sessions::device_info(req.headers());
+        let ip_address = sessions::client_ip(&req.connection_info());
+        let session = match db_auth_session_mut::create(
+            db,
+            user.id,
+            device_info.as_deref(),
+            ip_address.as_deref(),
+            sessions::expires_at(remember_me),
+        )
+        .await
+        {
+            Ok(session) => Some(session),
+            Err(e) => {
+                tracing::warn!("Failed to record auth session: {}", e);
+                None
+            }
+        };
+
         let claims = Claims {
             sub: user.id,
-            role: "user".to_string(),
+            role: role_of(user.permissions).as_str().to_string(),
             permissions: user.permissions,
             exp: (Utc::now() + Duration::minutes(JwtConfig::expiration_minutes())).timestamp(),
+            locale: db_user_preferences::get_locale(db, user.id).await.ok().flatten(),
+            impersonator_id: None,
+            impersonation_id: None,
+            sid: session.as_ref().map(|session| session.id),
         };
 
         let token = jsonwebtoken::encode(
@@ -419,8 +447,8 @@ impl AuthController {
                 event_bus,
                 user.id,
                 &user.email,
-                None, // TODO: extract from request
-                None, // TODO: extract from request
+                ip_address.as_deref(),
+                device_info.as_deref(),
             )
             .await
             {
@@ -443,10 +471,12 @@ impl AuthController {
         response.cookie(cookie);
 
         // If remember_me is checked, create a long-lived refresh token
-        if user_data.remember_me {
+        if remember_me {
             let refresh_token = match db_refresh_token_mut::create(
-                &db, user.id, None, // TODO: extract device info from User-Agent
-                None, // TODO: extract IP from request
+                db,
+                user.id,
+                device_info.as_deref(),
+                ip_address.as_deref(),
             )
             .await
             {
@@ -468,10 +498,20 @@ impl AuthController {
                             created_at: user.created_at,
                             updated_at: user.updated_at,
                         },
+                        recovery_codes,
                     });
                 }
             };
 
+            if let Some(session) = &session {
+                let token_hash = db_refresh_token_mut::hash_token(&refresh_token);
+                if let Err(e) =
+                    db_auth_session_mut::attach_refresh_token(db, session.id, &token_hash).await
+                {
+                    tracing::warn!("Failed to link refresh token to session: {}", e);
+                }
+            }
+
             // Set refresh_token cookie (long-lived, HttpOnly, Secure)
             let refresh_cookie = Cookie::build("refresh_token", refresh_token)
                 .path("/api/v1/auth")
@@ -498,6 +538,7 @@ impl AuthController {
                 created_at: user.created_at,
                 updated_at: user.updated_at,
             },
+            recovery_codes,
         })
     }
 
@@ -513,12 +554,44 @@ impl AuthController {
 
         let db = state.db.lock().await;
 
+        // The device's session, from the refresh token or else the access token
+        let mut device_session = None;
+
         // Get refresh token from cookie
         if let Some(refresh_cookie) = req.cookie("refresh_token") {
             let token_hash = db_refresh_token_mut::hash_token(refresh_cookie.value());
             if let Err(e) = db_refresh_token_mut::revoke_by_hash(&db, &token_hash).await {
                 tracing::warn!("Failed to revoke refresh token: {}", e);
             }
+            if let (Ok(record), Ok(Some(session_id))) = (
+                db_refresh_token::get_by_hash(&db, &token_hash).await,
+                db_auth_session::get_id_by_refresh_token(&db, &token_hash).await,
+            ) {
+                device_session = Some((record.user_id, session_id));
+            }
+        }
+
+        if device_session.is_none() {
+            device_session = req
+                .cookie("auth_token")
+                .and_then(|cookie| {
+                    jsonwebtoken::decode::<Claims>(
+                        cookie.value(),
+                        &jsonwebtoken::DecodingKey::from_secret(state.jwt_secret.as_bytes()),
+                        &jsonwebtoken::Validation::default(),
+                    )
+                    .ok()
+                })
+                .and_then(|data| data.claims.sid.map(|sid| (data.claims.sub, sid)));
+        }
+
+        // End the session so it leaves the device list and its sockets close
+        if let Some((user_id, session_id)) = device_session {
+            match db_auth_session_mut::revoke(&db, user_id, session_id).await {
+                Ok(Some(session)) => sessions::enforce_revocation(&state, &[session]).await,
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to revoke auth session: {}", e),
+            }
         }
 
         // Clear both cookies
@@ -595,9 +668,13 @@ impl AuthController {
         // Generate new access token
         let claims = Claims {
             sub: user.id,
-            role: "user".to_string(),
+            role: role_of(user.permissions).as_str().to_string(),
             permissions: user.permissions,
             exp: (Utc::now() + Duration::minutes(JwtConfig::expiration_minutes())).timestamp(),
+            locale: db_user_preferences::get_locale(&db, user.id).await.ok().flatten(),
+            impersonator_id: None,
+            impersonation_id: None,
+            sid: sessions::for_refresh_token(&db, &refresh_record).await,
         };
 
         let token = jsonwebtoken::encode(
@@ -631,6 +708,7 @@ impl AuthController {
                 created_at: user.created_at,
                 updated_at: user.updated_at,
             },
+            recovery_codes: None,
         })
     }
 
@@ -676,6 +754,14 @@ impl AuthController {
             }
         }
 
+        // End every device session, refusing their access tokens as well
+        match db_auth_session_mut::revoke_all_for_user(&db, claims.sub).await {
+            Ok(revoked) => sessions::enforce_revocation(&state, &revoked).await,
+            Err(e) => {
+                tracing::error!("Failed to revoke auth sessions: {}", e);
+            }
+        }
+
         // Clear current session cookies
         let clear_auth = Cookie::build("auth_token", "")
             .path("/")
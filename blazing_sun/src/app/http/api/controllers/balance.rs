@@ -1,26 +1,37 @@
 //! Balance Controller
 //!
 //! Handles balance top-ups via checkout service (Kafka-driven).
+//!
+//! The checkout service answers a `checkout.requests` event with a
+//! `session_created` or `session_failed` event on `checkout.finished`,
+//! correlated by request_id. When that takes longer than the request is
+//! willing to wait, the client gets `202 Accepted` with the request_id and the
+//! result follows over the WebSocket gateway (`checkout.event.session_ready` /
+//! `checkout.event.session_failed`).
 
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use serde::Serialize;
-use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{error, warn};
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::app::checkout::{
     euros_to_cents, register_pending, remove_pending, CheckoutKafkaRequest,
 };
+use crate::app::feature_flags::flag;
 use crate::app::http::api::controllers::responses::{
     BaseResponse, MissingFieldsResponse, ValidationErrorResponse,
 };
-use crate::app::http::api::validators::{BalanceCheckoutRequest, BalanceCheckoutRequestRaw};
+use crate::app::http::api::validators::{
+    validate_request, BalanceCheckoutRequest, BalanceCheckoutRequestRaw,
+};
 use crate::config::AppConfig;
 use crate::database::AppState;
 use crate::events::topic;
 
+/// How long a checkout request waits for the checkout service's answer
+const SESSION_WAIT: Duration = Duration::from_secs(15);
+
 /// Balance Controller
 pub struct BalanceController;
 
@@ -30,6 +41,16 @@ pub struct CheckoutSessionResponse {
     pub base: BaseResponse,
     pub session_id: String,
     pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+/// The session is still being created; its URL arrives over the WebSocket
+#[derive(Debug, Serialize)]
+pub struct CheckoutPendingResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub request_id: String,
 }
 
 impl BalanceController {
@@ -50,7 +71,15 @@ impl BalanceController {
             }
         };
 
-        // 2. Check event bus availability
+        // 2. The Kafka checkout flow is rolled out gradually
+        let flags = state.feature_flags().await;
+        if !flags.is_enabled(flag::CHECKOUT_KAFKA_FLOW, Some(user_id)).await {
+            return HttpResponse::ServiceUnavailable().json(BaseResponse::error(
+                "Kafka checkout is not enabled for this account",
+            ));
+        }
+
+        // 3. Check event bus availability
         let event_bus = match state.event_bus() {
             Some(bus) => bus,
             None => {
@@ -59,7 +88,7 @@ impl BalanceController {
             }
         };
 
-        // 3. Validate request
+        // 4. Validate request
         let raw = body.into_inner();
         let mut missing_fields = Vec::new();
 
@@ -75,25 +104,12 @@ impl BalanceController {
             amount: raw.amount.unwrap(),
         };
 
-        if let Err(validation_errors) = request.validate() {
-            let mut errors: HashMap<String, Vec<String>> = HashMap::new();
-            for (field, field_errors) in validation_errors.field_errors() {
-                let messages: Vec<String> = field_errors
-                    .iter()
-                    .map(|e| {
-                        e.message
-                            .as_ref()
-                            .map(|m| m.to_string())
-                            .unwrap_or_else(|| e.code.to_string())
-                    })
-                    .collect();
-                errors.insert(field.to_string(), messages);
-            }
-
-            return HttpResponse::BadRequest().json(ValidationErrorResponse::new(errors));
+        let errors = validate_request(&request);
+        if !errors.is_empty() {
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
         }
 
-        // 4. Convert amount to cents
+        // 5. Convert amount to cents
         let amount_cents = match euros_to_cents(request.amount) {
             Ok(amount) => amount,
             Err(message) => {
@@ -101,7 +117,7 @@ impl BalanceController {
             }
         };
 
-        // 5. Build URLs for Stripe redirect
+        // 6. Build URLs for Stripe redirect
         let app_url = AppConfig::app_url().trim_end_matches('/');
         let success_url = format!(
             "{}/balance?status=success&session_id={{CHECKOUT_SESSION_ID}}",
@@ -109,11 +125,11 @@ impl BalanceController {
         );
         let cancel_url = format!("{}/balance?status=cancel", app_url);
 
-        // 6. Generate request ID and register pending request
+        // 7. Generate request ID and register pending request
         let request_id = Uuid::new_v4().to_string();
         let receiver = register_pending(request_id.clone()).await;
 
-        // 7. Create the checkout request event for the new topic
+        // 8. Create the checkout request event for the new topic
         let checkout_request = CheckoutKafkaRequest::new(
             request_id.clone(),
             user_id,
@@ -122,7 +138,7 @@ impl BalanceController {
             cancel_url,
         );
 
-        // 8. Serialize and publish to "checkout" topic
+        // 9. Serialize and publish to "checkout" topic
         let payload = match serde_json::to_vec(&checkout_request) {
             Ok(payload) => payload,
             Err(err) => {
@@ -144,21 +160,24 @@ impl BalanceController {
                 .json(BaseResponse::error("Checkout service unavailable"));
         }
 
-        // 9. Wait for response from checkout service (via checkout_finished handler)
-        let response = match tokio::time::timeout(Duration::from_secs(15), receiver).await {
+        // 10. Wait for response from checkout service (via checkout_finished handler)
+        let response = match tokio::time::timeout(SESSION_WAIT, receiver).await {
             Ok(Ok(response)) => response,
             Ok(Err(_)) => {
                 return HttpResponse::BadGateway()
                     .json(BaseResponse::error("Checkout service failed"));
             }
             Err(_) => {
+                // The checkout_finished handler pushes the late result to the user
                 remove_pending(&request_id).await;
-                return HttpResponse::GatewayTimeout()
-                    .json(BaseResponse::error("Checkout timed out"));
+                return HttpResponse::Accepted().json(CheckoutPendingResponse {
+                    base: BaseResponse::success("Checkout session is being created"),
+                    request_id,
+                });
             }
         };
 
-        // 10. Handle response
+        // 11. Handle response
         if let Some(error_message) = response.error {
             warn!("Checkout session failed: {}", error_message);
             return HttpResponse::BadGateway().json(BaseResponse::error("Checkout failed"));
@@ -184,6 +203,7 @@ impl BalanceController {
             base: BaseResponse::success("Checkout session created"),
             session_id,
             url: session_url,
+            expires_at: response.expires_at,
         })
     }
 }
@@ -0,0 +1,422 @@
+//!
+//! Balance Adjustment Controller
+//!
+//! Support grants and revocations of coins with two-person approval:
+//! - GET /api/v1/admin/balance-adjustments: Adjustment history (cursor paginated)
+//! - POST /api/v1/admin/balance-adjustments: Request a batch of adjustments
+//! - POST /api/v1/admin/balance-adjustments/{id}/approve: Approve and apply an adjustment
+//! - POST /api/v1/admin/balance-adjustments/{id}/reject: Reject an adjustment
+//!
+//! An adjustment only reaches the balance once a super admin other than the
+//! requester approves it. The approval writes a balance_ledger entry and
+//! publishes a `user.balance_updated` audit event naming both admins.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use pagination::{paginate, Cursor};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::app::db_query::mutations::balance_adjustments::{
+    self as db_mutations, ApproveOutcome, NewAdjustment, RejectOutcome,
+};
+use crate::app::db_query::read::balance_adjustments::{
+    self as db_read, AdjustmentFilter, BalanceAdjustment,
+};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::{replica, AppState};
+use crate::events;
+use crate::events::types::payloads::BalanceAdjustedPayload;
+
+/// Most adjustments one request may contain
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Largest grant or revocation of a single adjustment (in cents)
+const MAX_ADJUSTMENT_CENTS: i64 = 10_000_000;
+
+/// Reason length bounds
+const MIN_REASON_LENGTH: usize = 3;
+const MAX_REASON_LENGTH: usize = 500;
+
+/// Default and largest page of the history
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+/// Statuses that can be filtered on
+const STATUSES: &[&str] = &["pending", "executed", "rejected"];
+
+/// Balance Adjustment Controller
+pub struct BalanceAdjustmentController;
+
+/// Single adjustment response
+#[derive(Debug, Serialize)]
+pub struct BalanceAdjustmentResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub adjustment: BalanceAdjustment,
+}
+
+/// Created batch response
+#[derive(Debug, Serialize)]
+pub struct BalanceAdjustmentBatchResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub batch_id: Uuid,
+    pub adjustment_ids: Vec<i64>,
+}
+
+/// History page response
+#[derive(Debug, Serialize)]
+pub struct BalanceAdjustmentListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub adjustments: Vec<BalanceAdjustment>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+/// Insufficient balance response for revocations
+#[derive(Debug, Serialize)]
+pub struct AdjustmentBalanceResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub current_balance: i64,
+}
+
+/// One adjustment of a request
+#[derive(Debug, Deserialize)]
+pub struct AdjustmentItem {
+    pub user_id: i64,
+    /// Positive grants coins, negative revokes them
+    pub amount_cents: i64,
+    pub reason: String,
+}
+
+/// Request adjustments
+#[derive(Debug, Deserialize)]
+pub struct CreateAdjustmentsRequest {
+    pub adjustments: Vec<AdjustmentItem>,
+}
+
+/// Reject an adjustment
+#[derive(Debug, Deserialize)]
+pub struct RejectAdjustmentRequest {
+    pub note: Option<String>,
+}
+
+/// History query
+#[derive(Debug, Deserialize)]
+pub struct AdjustmentListQuery {
+    pub status: Option<String>,
+    pub user_id: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Check a batch of adjustments; Err carries the response message
+fn validate_batch(items: &[AdjustmentItem]) -> Result<(), &'static str> {
+    if items.is_empty() {
+        return Err("Add at least one adjustment");
+    }
+    if items.len() > MAX_BATCH_SIZE {
+        return Err("At most 100 adjustments can be requested at once");
+    }
+
+    for item in items {
+        if item.amount_cents == 0 || item.amount_cents.abs() > MAX_ADJUSTMENT_CENTS {
+            return Err("Adjustment amounts must be non-zero and at most 100000 coins");
+        }
+        let reason_length = item.reason.trim().chars().count();
+        if !(MIN_REASON_LENGTH..=MAX_REASON_LENGTH).contains(&reason_length) {
+            return Err("Reasons must be between 3 and 500 characters");
+        }
+    }
+
+    Ok(())
+}
+
+fn admin_id(req: &HttpRequest) -> Option<i64> {
+    req.extensions().get::<i64>().copied()
+}
+
+impl BalanceAdjustmentController {
+    /// GET /api/v1/admin/balance-adjustments - Adjustment history, newest first
+    ///
+    /// # Query
+    /// - status: pending, executed or rejected
+    /// - user_id: adjustments of one user
+    /// - cursor: `next_cursor` / `prev_cursor` of a previous page
+    /// - limit: page size (default 50, max 200)
+    pub async fn list(
+        state: web::Data<AppState>,
+        query: web::Query<AdjustmentListQuery>,
+    ) -> HttpResponse {
+        let query = query.into_inner();
+
+        if let Some(status) = &query.status {
+            if !STATUSES.contains(&status.as_str()) {
+                return HttpResponse::BadRequest().json(BaseResponse::error("Unknown adjustment status"));
+            }
+        }
+
+        let cursor = match query.cursor.as_deref() {
+            Some(value) => match Cursor::decode(value) {
+                Some(cursor) => Some(cursor),
+                None => return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor")),
+            },
+            None => None,
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let filter = AdjustmentFilter {
+            status: query.status.as_deref(),
+            user_id: query.user_id,
+        };
+
+        let db = state.read_db().await;
+        let rows = db_read::get_page(&db, &filter, cursor.as_ref(), limit).await;
+        drop(db);
+
+        match rows {
+            Ok(rows) => {
+                let page = paginate(rows, cursor.as_ref(), limit as usize, |a| (a.created_at, a.id));
+                HttpResponse::Ok().json(BalanceAdjustmentListResponse {
+                    base: BaseResponse::success("Balance adjustments retrieved"),
+                    adjustments: page.items,
+                    next_cursor: page.next_cursor,
+                    prev_cursor: page.prev_cursor,
+                })
+            }
+            Err(e) => {
+                error!("Failed to list balance adjustments: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve balance adjustments"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/balance-adjustments - Request a batch of adjustments
+    ///
+    /// All adjustments of a request share a batch and stay pending until approved.
+    ///
+    /// # Responses
+    /// - 201: Adjustments requested
+    /// - 400: Invalid amounts or reasons, or unknown users
+    pub async fn create(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CreateAdjustmentsRequest>,
+    ) -> HttpResponse {
+        let Some(admin_id) = admin_id(&req) else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let body = body.into_inner();
+
+        if let Err(message) = validate_batch(&body.adjustments) {
+            return HttpResponse::BadRequest().json(BaseResponse::error(message));
+        }
+
+        let adjustments: Vec<NewAdjustment> = body
+            .adjustments
+            .into_iter()
+            .map(|item| NewAdjustment {
+                user_id: item.user_id,
+                amount_cents: item.amount_cents,
+                reason: item.reason.trim().to_string(),
+            })
+            .collect();
+        let user_ids: Vec<i64> = adjustments.iter().map(|a| a.user_id).collect();
+
+        let db = state.db.lock().await;
+
+        match db_read::get_unknown_users(&db, &user_ids).await {
+            Ok(unknown) if unknown.is_empty() => {}
+            Ok(_) => {
+                return HttpResponse::BadRequest().json(BaseResponse::error("User not found"));
+            }
+            Err(e) => {
+                error!("Failed to check users of balance adjustments: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to request balance adjustments"));
+            }
+        }
+
+        let created = db_mutations::create_batch(&db, admin_id, &adjustments).await;
+        drop(db);
+
+        match created {
+            Ok((batch_id, adjustment_ids)) => {
+                info!(
+                    batch_id = %batch_id,
+                    admin_id = %admin_id,
+                    count = adjustment_ids.len(),
+                    "Balance adjustments requested"
+                );
+                HttpResponse::Created().json(BalanceAdjustmentBatchResponse {
+                    base: BaseResponse::success("Balance adjustments requested"),
+                    batch_id,
+                    adjustment_ids,
+                })
+            }
+            Err(e) => {
+                error!("Failed to request balance adjustments: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to request balance adjustments"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/balance-adjustments/{id}/approve - Approve and apply an adjustment
+    ///
+    /// # Responses
+    /// - 200: Adjustment executed
+    /// - 403: The requester tried to approve their own adjustment
+    /// - 404: Adjustment not found
+    /// - 409: Adjustment already reviewed
+    /// - 422: Revocation larger than the user's balance (stays pending)
+    pub async fn approve(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+    ) -> HttpResponse {
+        let Some(admin_id) = admin_id(&req) else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let id = path.into_inner();
+
+        let db = state.db.lock().await;
+        let outcome = db_mutations::approve(&db, id, admin_id).await;
+        drop(db);
+
+        let adjustment = match outcome {
+            Ok(ApproveOutcome::Executed(adjustment)) => adjustment,
+            Ok(ApproveOutcome::NotFound) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Balance adjustment not found"));
+            }
+            Ok(ApproveOutcome::NotPending) => {
+                return HttpResponse::Conflict()
+                    .json(BaseResponse::error("Balance adjustment was already reviewed"));
+            }
+            Ok(ApproveOutcome::SameReviewer) => {
+                return HttpResponse::Forbidden().json(BaseResponse::error(
+                    "Adjustments must be approved by a different admin",
+                ));
+            }
+            Ok(ApproveOutcome::InsufficientBalance { current_balance }) => {
+                return HttpResponse::UnprocessableEntity().json(AdjustmentBalanceResponse {
+                    base: BaseResponse::error("Insufficient balance"),
+                    current_balance,
+                });
+            }
+            Err(e) => {
+                error!("Failed to approve balance adjustment {}: {}", id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to approve balance adjustment"));
+            }
+        };
+
+        info!(
+            adjustment_id = %adjustment.id,
+            user_id = %adjustment.user_id,
+            amount_cents = %adjustment.amount_cents,
+            requested_by = ?adjustment.requested_by,
+            approved_by = %admin_id,
+            "Balance adjustment executed"
+        );
+        replica::mark_written(adjustment.user_id).await;
+
+        if let Some(event_bus) = state.event_bus() {
+            let payload = BalanceAdjustedPayload {
+                balance: adjustment.balance_after.unwrap_or_default(),
+                change: adjustment.amount_cents,
+                source: db_mutations::LEDGER_SOURCE.to_string(),
+                adjustment_id: adjustment.id,
+                reason: adjustment.reason.clone(),
+                requested_by: adjustment.requested_by,
+                approved_by: admin_id,
+            };
+            if let Err(e) = events::publish::balance_adjusted(event_bus, adjustment.user_id, payload).await {
+                warn!("Failed to publish user.balance_updated event: {}", e);
+            }
+        }
+
+        HttpResponse::Ok().json(BalanceAdjustmentResponse {
+            base: BaseResponse::success("Balance adjustment executed"),
+            adjustment,
+        })
+    }
+
+    /// POST /api/v1/admin/balance-adjustments/{id}/reject - Reject an adjustment
+    ///
+    /// # Responses
+    /// - 200: Adjustment rejected
+    /// - 404: Adjustment not found
+    /// - 409: Adjustment already reviewed
+    pub async fn reject(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+        body: Option<web::Json<RejectAdjustmentRequest>>,
+    ) -> HttpResponse {
+        let Some(admin_id) = admin_id(&req) else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let id = path.into_inner();
+        let note = body
+            .and_then(|body| body.into_inner().note)
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+
+        let db = state.db.lock().await;
+        let outcome = db_mutations::reject(&db, id, admin_id, note.as_deref()).await;
+        drop(db);
+
+        match outcome {
+            Ok(RejectOutcome::Rejected(adjustment)) => {
+                info!("Balance adjustment {} rejected by admin {}", id, admin_id);
+                HttpResponse::Ok().json(BalanceAdjustmentResponse {
+                    base: BaseResponse::success("Balance adjustment rejected"),
+                    adjustment,
+                })
+            }
+            Ok(RejectOutcome::NotFound) => {
+                HttpResponse::NotFound().json(BaseResponse::error("Balance adjustment not found"))
+            }
+            Ok(RejectOutcome::NotPending) => HttpResponse::Conflict()
+                .json(BaseResponse::error("Balance adjustment was already reviewed")),
+            Err(e) => {
+                error!("Failed to reject balance adjustment {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to reject balance adjustment"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(amount_cents: i64, reason: &str) -> AdjustmentItem {
+        AdjustmentItem {
+            user_id: 1,
+            amount_cents,
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn batches_need_amounts_and_reasons() {
+        assert!(validate_batch(&[item(500, "Compensation"), item(-200, "Chargeback")]).is_ok());
+
+        assert!(validate_batch(&[]).is_err());
+        assert!(validate_batch(&[item(0, "Nothing")]).is_err());
+        assert!(validate_batch(&[item(MAX_ADJUSTMENT_CENTS + 1, "Too much")]).is_err());
+        assert!(validate_batch(&[item(-MAX_ADJUSTMENT_CENTS - 1, "Too much")]).is_err());
+        assert!(validate_batch(&[item(100, "  ")]).is_err());
+
+        let too_many: Vec<AdjustmentItem> = (0..=MAX_BATCH_SIZE).map(|_| item(1, "Promo")).collect();
+        assert!(validate_batch(&too_many).is_err());
+    }
+}
@@ -0,0 +1,331 @@
+//!
+//! Balance Transfer Controller
+//!
+//! Coins sent from one user to another:
+//! - POST /api/v1/balance/transfers: Send coins to another user
+//! - GET /api/v1/balance/transfers: Sent and received transfers (cursor paginated)
+//!
+//! A transfer debits the sender and credits the recipient atomically, writing
+//! a balance_ledger entry for both. Single transfers are capped at
+//! `TRANSFER_MAX_CENTS` and a sender's transfers over any 24 hours at
+//! `TRANSFER_DAILY_LIMIT_CENTS`. Completed transfers publish
+//! `transaction.transfer_completed` and a `user.balance_updated` per party.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use pagination::{paginate, Cursor};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::app::db_query::mutations::balance_transfers::{
+    self as db_mutations, NewTransfer, TransferOutcome,
+};
+use crate::app::db_query::read::balance_transfers::{
+    self as db_read, BalanceTransfer, TransferDirection,
+};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::config::TransferConfig;
+use crate::database::{replica, AppState};
+use crate::events;
+use crate::events::types::payloads::BalanceTransferredPayload;
+
+/// Longest memo in characters
+const MAX_MEMO_LENGTH: usize = 200;
+
+/// Default and largest page of the history
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+/// Balance Transfer Controller
+pub struct BalanceTransferController;
+
+/// Single transfer response
+#[derive(Debug, Serialize)]
+pub struct BalanceTransferResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub transfer: BalanceTransfer,
+}
+
+/// History page response
+#[derive(Debug, Serialize)]
+pub struct BalanceTransferListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub transfers: Vec<BalanceTransfer>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+/// Insufficient balance response
+#[derive(Debug, Serialize)]
+pub struct TransferBalanceResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub current_balance: i64,
+}
+
+/// Daily limit response
+#[derive(Debug, Serialize)]
+pub struct TransferLimitResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub daily_limit_cents: i64,
+    pub remaining_cents: i64,
+}
+
+/// Send coins
+#[derive(Debug, Deserialize)]
+pub struct CreateTransferRequest {
+    pub recipient_id: i64,
+    pub amount_cents: i64,
+    pub memo: Option<String>,
+}
+
+/// History query
+#[derive(Debug, Deserialize)]
+pub struct TransferListQuery {
+    /// sent or received; both when missing
+    pub direction: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Check a transfer request, returning the trimmed memo; Err carries the
+/// response message
+fn validate_transfer(
+    sender_id: i64,
+    request: &CreateTransferRequest,
+    max_transfer_cents: i64,
+) -> Result<Option<String>, &'static str> {
+    if request.recipient_id == sender_id {
+        return Err("You cannot transfer coins to yourself");
+    }
+    if request.amount_cents <= 0 {
+        return Err("Transfer amount must be positive");
+    }
+    if request.amount_cents > max_transfer_cents {
+        return Err("Transfer amount is above the per-transfer limit");
+    }
+
+    let memo = request
+        .memo
+        .as_deref()
+        .map(str::trim)
+        .filter(|memo| !memo.is_empty());
+    if memo.is_some_and(|memo| memo.chars().count() > MAX_MEMO_LENGTH) {
+        return Err("Memo can be at most 200 characters");
+    }
+
+    Ok(memo.map(str::to_string))
+}
+
+fn user_id(req: &HttpRequest) -> Option<i64> {
+    req.extensions().get::<i64>().copied()
+}
+
+impl BalanceTransferController {
+    /// POST /api/v1/balance/transfers - Send coins to another user
+    ///
+    /// Send an `Idempotency-Key` header to make retries safe.
+    ///
+    /// # Responses
+    /// - 201: Transfer completed
+    /// - 400: Invalid amount, memo or recipient
+    /// - 404: Recipient not found
+    /// - 422: Insufficient balance (`current_balance`) or daily limit reached
+    ///   (`daily_limit_cents`, `remaining_cents`)
+    pub async fn create(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CreateTransferRequest>,
+    ) -> HttpResponse {
+        let Some(sender_id) = user_id(&req) else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let body = body.into_inner();
+
+        let memo = match validate_transfer(sender_id, &body, TransferConfig::max_transfer_cents()) {
+            Ok(memo) => memo,
+            Err(message) => return HttpResponse::BadRequest().json(BaseResponse::error(message)),
+        };
+
+        let new = NewTransfer {
+            sender_id,
+            recipient_id: body.recipient_id,
+            amount_cents: body.amount_cents,
+            memo,
+        };
+        let daily_limit_cents = TransferConfig::daily_limit_cents();
+
+        let db = state.db.lock().await;
+        let outcome = db_mutations::transfer(&db, &new, daily_limit_cents).await;
+        let completed = match outcome {
+            Ok(TransferOutcome::Completed(completed)) => completed,
+            Ok(TransferOutcome::RecipientNotFound) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Recipient not found"));
+            }
+            Ok(TransferOutcome::InsufficientBalance { current_balance }) => {
+                return HttpResponse::UnprocessableEntity().json(TransferBalanceResponse {
+                    base: BaseResponse::error("Insufficient balance"),
+                    current_balance,
+                });
+            }
+            Ok(TransferOutcome::DailyLimitExceeded { remaining_cents }) => {
+                return HttpResponse::UnprocessableEntity().json(TransferLimitResponse {
+                    base: BaseResponse::error("Daily transfer limit reached"),
+                    daily_limit_cents,
+                    remaining_cents,
+                });
+            }
+            Err(e) => {
+                error!("Failed to transfer coins from user {}: {}", sender_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to transfer coins"));
+            }
+        };
+        let transfer = db_read::get_by_id(&db, sender_id, completed.id).await;
+        drop(db);
+
+        // Both users fetch their new balance right away
+        replica::mark_written(completed.sender_id).await;
+        replica::mark_written(completed.recipient_id).await;
+
+        info!(
+            transfer_id = %completed.id,
+            sender_id = %completed.sender_id,
+            recipient_id = %completed.recipient_id,
+            amount_cents = %completed.amount_cents,
+            "Balance transfer completed"
+        );
+
+        let transfer = match transfer {
+            Ok(Some(transfer)) => transfer,
+            Ok(None) | Err(_) => {
+                // The coins moved; only the echo failed
+                error!("Failed to load completed transfer {}", completed.id);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to transfer coins"));
+            }
+        };
+
+        if let Some(event_bus) = state.event_bus() {
+            let payload = BalanceTransferredPayload {
+                transfer_id: completed.id,
+                sender_id: completed.sender_id,
+                sender_name: transfer.sender_name.clone(),
+                recipient_id: completed.recipient_id,
+                recipient_name: transfer.recipient_name.clone(),
+                amount_cents: completed.amount_cents,
+                memo: completed.memo.clone(),
+            };
+            if let Err(e) = events::publish::balance_transferred(
+                event_bus,
+                payload,
+                completed.sender_balance_after,
+                completed.recipient_balance_after,
+            )
+            .await
+            {
+                warn!("Failed to publish transfer events: {}", e);
+            }
+        }
+
+        HttpResponse::Created().json(BalanceTransferResponse {
+            base: BaseResponse::success("Transfer completed"),
+            transfer,
+        })
+    }
+
+    /// GET /api/v1/balance/transfers - Transfers the user sent or received, newest first
+    ///
+    /// # Query
+    /// - direction: sent or received (both by default)
+    /// - cursor: `next_cursor` / `prev_cursor` of a previous page
+    /// - limit: page size (default 50, max 200)
+    pub async fn list(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        query: web::Query<TransferListQuery>,
+    ) -> HttpResponse {
+        let Some(user_id) = user_id(&req) else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let query = query.into_inner();
+
+        let direction = match query.direction.as_deref() {
+            Some(value) => match TransferDirection::parse(value) {
+                Some(direction) => Some(direction),
+                None => {
+                    return HttpResponse::BadRequest()
+                        .json(BaseResponse::error("Direction must be sent or received"));
+                }
+            },
+            None => None,
+        };
+        let cursor = match query.cursor.as_deref() {
+            Some(value) => match Cursor::decode(value) {
+                Some(cursor) => Some(cursor),
+                None => return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor")),
+            },
+            None => None,
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let db = state.read_db_for(user_id).await;
+        let rows = db_read::get_page_for_user(&db, user_id, direction, cursor.as_ref(), limit).await;
+        drop(db);
+
+        match rows {
+            Ok(rows) => {
+                let page = paginate(rows, cursor.as_ref(), limit as usize, |t| (t.created_at, t.id));
+                HttpResponse::Ok().json(BalanceTransferListResponse {
+                    base: BaseResponse::success("Transfers retrieved"),
+                    transfers: page.items,
+                    next_cursor: page.next_cursor,
+                    prev_cursor: page.prev_cursor,
+                })
+            }
+            Err(e) => {
+                error!("Failed to list transfers of user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve transfers"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(recipient_id: i64, amount_cents: i64, memo: Option<&str>) -> CreateTransferRequest {
+        CreateTransferRequest {
+            recipient_id,
+            amount_cents,
+            memo: memo.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn transfers_need_another_recipient_and_a_bounded_amount() {
+        assert_eq!(validate_transfer(1, &request(2, 500, None), 1000), Ok(None));
+        assert_eq!(validate_transfer(1, &request(2, 1000, Some("  ")), 1000), Ok(None));
+
+        assert!(validate_transfer(1, &request(1, 500, None), 1000).is_err());
+        assert!(validate_transfer(1, &request(2, 0, None), 1000).is_err());
+        assert!(validate_transfer(1, &request(2, -5, None), 1000).is_err());
+        assert!(validate_transfer(1, &request(2, 1001, None), 1000).is_err());
+    }
+
+    #[test]
+    fn memos_are_trimmed_and_bounded() {
+        assert_eq!(
+            validate_transfer(1, &request(2, 5, Some(" pizza ")), 1000),
+            Ok(Some("pizza".to_string()))
+        );
+
+        let long = "x".repeat(MAX_MEMO_LENGTH + 1);
+        assert!(validate_transfer(1, &request(2, 5, Some(&long)), 1000).is_err());
+    }
+}
@@ -0,0 +1,580 @@
+//!
+//! Chat Channel Controller
+//!
+//! Lobby/global chat channels that exist independently of game rooms.
+//! Live messaging goes through the WebSocket gateway (`chat.command.channel_*`);
+//! these endpoints cover listing, membership, read markers and history.
+//!
+//! - GET /api/v1/chat/channels: List active channels with membership and unread counts
+//! - POST /api/v1/chat/channels/{id}/join: Join a channel
+//! - POST /api/v1/chat/channels/{id}/leave: Leave a channel
+//! - POST /api/v1/chat/channels/{id}/read: Move the read marker
+//! - GET /api/v1/chat/channels/{id}/messages: Paginated message history
+//! - GET/POST/PATCH/DELETE /api/v1/admin/chat/channels: Admin CRUD
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::app::chat::mongodb_channel::MongoChannelClient;
+use crate::app::chat::types::ChannelMessage;
+use crate::app::db_query::mutations::chat_channel as db_mutations;
+use crate::app::db_query::read::chat_channel::{self as db_read, ChatChannel};
+use crate::app::db_query::read::user_blocks as db_user_blocks;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Default and maximum page size for message history
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 100;
+
+/// Chat Channel Controller
+pub struct ChatChannelController;
+
+/// Channel with the current user's membership state
+#[derive(Debug, Serialize)]
+pub struct ChannelListItem {
+    #[serde(flatten)]
+    pub channel: ChatChannel,
+    pub is_member: bool,
+    pub last_read_message_id: Option<String>,
+    pub unread_count: u64,
+}
+
+/// Channel list response
+#[derive(Debug, Serialize)]
+pub struct ChannelListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub channels: Vec<ChannelListItem>,
+}
+
+/// Admin channel list response
+#[derive(Debug, Serialize)]
+pub struct AdminChannelListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub channels: Vec<ChatChannel>,
+}
+
+/// Single channel response
+#[derive(Debug, Serialize)]
+pub struct ChannelResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub channel: ChatChannel,
+}
+
+/// Channel message as returned by the history endpoint
+#[derive(Debug, Serialize)]
+pub struct ChannelMessageDto {
+    pub message_id: String,
+    pub channel_id: i64,
+    pub sender_id: i64,
+    pub sender_username: String,
+    pub sender_avatar_id: Option<i64>,
+    pub content: String,
+    pub created_at: String,
+}
+
+impl From<ChannelMessage> for ChannelMessageDto {
+    fn from(message: ChannelMessage) -> Self {
+        Self {
+            message_id: message.id.map(|id| id.to_hex()).unwrap_or_default(),
+            channel_id: message.channel_id,
+            sender_id: message.sender_id,
+            sender_username: message.sender_username,
+            sender_avatar_id: message.sender_avatar_id,
+            content: message.content,
+            created_at: message.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Message history response
+#[derive(Debug, Serialize)]
+pub struct ChannelHistoryResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub messages: Vec<ChannelMessageDto>,
+    pub has_more: bool,
+    /// Pass as `before` to load the previous page
+    pub next_before: Option<String>,
+}
+
+/// Query parameters for message history
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub before: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Mark read request
+#[derive(Debug, Deserialize)]
+pub struct MarkReadRequest {
+    pub message_id: String,
+}
+
+/// Create channel request
+#[derive(Debug, Deserialize)]
+pub struct CreateChannelRequest {
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Update channel request (omitted fields are left unchanged)
+#[derive(Debug, Deserialize)]
+pub struct UpdateChannelRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= 64
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty() && name.chars().count() <= 100
+}
+
+impl ChatChannelController {
+    /// GET /api/v1/chat/channels - List active channels
+    pub async fn list(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+
+        let db = state.read_db_for(user_id).await;
+        let channels = db_read::get_all(&db, false).await;
+        let memberships = db_read::get_memberships_for_user(&db, user_id).await;
+        drop(db);
+
+        let (channels, memberships) = match (channels, memberships) {
+            (Ok(channels), Ok(memberships)) => (channels, memberships),
+            (Err(e), _) | (_, Err(e)) => {
+                error!("Failed to list chat channels: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve channels"));
+            }
+        };
+
+        let mongo = state.mongo().map(|db| MongoChannelClient::new(db.clone()));
+        let mut items = Vec::with_capacity(channels.len());
+
+        for channel in channels {
+            let membership = memberships.iter().find(|m| m.channel_id == channel.id);
+            let last_read_message_id = membership.and_then(|m| m.last_read_message_id.clone());
+
+            let unread_count = match (&mongo, membership) {
+                (Some(mongo), Some(_)) => {
+                    let last_read = last_read_message_id
+                        .as_deref()
+                        .and_then(|id| ObjectId::parse_str(id).ok());
+                    mongo
+                        .count_unread(channel.id, user_id, last_read)
+                        .await
+                        .unwrap_or_else(|e| {
+                            warn!("Failed to count unread messages for channel {}: {}", channel.id, e);
+                            0
+                        })
+                }
+                _ => 0,
+            };
+
+            items.push(ChannelListItem {
+                is_member: membership.is_some(),
+                last_read_message_id,
+                unread_count,
+                channel,
+            });
+        }
+
+        HttpResponse::Ok().json(ChannelListResponse {
+            base: BaseResponse::success("Channels retrieved"),
+            channels: items,
+        })
+    }
+
+    /// POST /api/v1/chat/channels/{id}/join - Join a channel
+    ///
+    /// # Responses
+    /// - 200: Joined (or already a member)
+    /// - 404: Channel not found or inactive
+    pub async fn join(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let channel_id = path.into_inner();
+        let db = state.db.lock().await;
+
+        match db_read::get_by_id(&db, channel_id).await {
+            Ok(Some(channel)) if channel.is_active => {}
+            Ok(_) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Channel not found"));
+            }
+            Err(e) => {
+                error!("Failed to load chat channel {}: {}", channel_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to join channel"));
+            }
+        }
+
+        match db_mutations::join(&db, channel_id, user_id).await {
+            Ok(_) => HttpResponse::Ok().json(BaseResponse::success("Joined channel")),
+            Err(e) => {
+                error!("Failed to join chat channel {}: {}", channel_id, e);
+                HttpResponse::InternalServerError().json(BaseResponse::error("Failed to join channel"))
+            }
+        }
+    }
+
+    /// POST /api/v1/chat/channels/{id}/leave - Leave a channel
+    ///
+    /// # Responses
+    /// - 200: Left the channel
+    /// - 404: Not a member
+    pub async fn leave(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let channel_id = path.into_inner();
+        let db = state.db.lock().await;
+
+        match db_mutations::leave(&db, channel_id, user_id).await {
+            Ok(true) => HttpResponse::Ok().json(BaseResponse::success("Left channel")),
+            Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("Not a channel member")),
+            Err(e) => {
+                error!("Failed to leave chat channel {}: {}", channel_id, e);
+                HttpResponse::InternalServerError().json(BaseResponse::error("Failed to leave channel"))
+            }
+        }
+    }
+
+    /// POST /api/v1/chat/channels/{id}/read - Move the read marker
+    ///
+    /// The marker only moves forward; marking an older message is a no-op.
+    ///
+    /// # Responses
+    /// - 200: Marker updated (or already past this message)
+    /// - 400: Invalid message ID
+    /// - 404: Not a channel member
+    pub async fn mark_read(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+        body: web::Json<MarkReadRequest>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let channel_id = path.into_inner();
+
+        let message_id = match ObjectId::parse_str(&body.message_id) {
+            Ok(id) => id.to_hex(),
+            Err(_) => {
+                return HttpResponse::BadRequest().json(BaseResponse::error("Invalid message ID"));
+            }
+        };
+
+        let db = state.db.lock().await;
+
+        match db_read::get_membership(&db, channel_id, user_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Not a channel member"));
+            }
+            Err(e) => {
+                error!("Failed to load chat channel membership: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to update read marker"));
+            }
+        }
+
+        match db_mutations::mark_read(&db, channel_id, user_id, &message_id).await {
+            Ok(_) => HttpResponse::Ok().json(BaseResponse::success("Read marker updated")),
+            Err(e) => {
+                error!("Failed to update read marker for channel {}: {}", channel_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to update read marker"))
+            }
+        }
+    }
+
+    /// GET /api/v1/chat/channels/{id}/messages - Paginated message history
+    ///
+    /// Returns up to `limit` messages older than `before` in chronological order,
+    /// leaving out messages of users the caller blocked.
+    ///
+    /// # Responses
+    /// - 200: Messages
+    /// - 400: Invalid `before` cursor
+    /// - 401: Unauthorized
+    /// - 404: Channel not found or inactive
+    /// - 503: Message storage unavailable
+    pub async fn messages(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+        query: web::Query<HistoryQuery>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let channel_id = path.into_inner();
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT);
+
+        let before = match query.before.as_deref() {
+            Some(before) => match ObjectId::parse_str(before) {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor"));
+                }
+            },
+            None => None,
+        };
+
+        let db = state.read_db_for(user_id).await;
+        let channel = db_read::get_by_id(&db, channel_id).await;
+        let hidden_senders = db_user_blocks::blocked_ids(&db, user_id).await;
+        drop(db);
+
+        let hidden_senders = match hidden_senders {
+            Ok(ids) => ids,
+            Err(e) => {
+                error!("Failed to load blocks of user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve messages"));
+            }
+        };
+
+        match channel {
+            Ok(Some(channel)) if channel.is_active => {}
+            Ok(_) => return HttpResponse::NotFound().json(BaseResponse::error("Channel not found")),
+            Err(e) => {
+                error!("Failed to load chat channel {}: {}", channel_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve messages"));
+            }
+        }
+
+        let Some(mongodb) = state.mongo() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Chat history is unavailable"));
+        };
+
+        // Fetch one extra message to know whether an older page exists
+        let client = MongoChannelClient::new(mongodb.clone());
+        let mut messages = match client.get_messages(channel_id, limit + 1, before, &hidden_senders).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                error!("Failed to load messages for channel {}: {}", channel_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve messages"));
+            }
+        };
+
+        let has_more = messages.len() as i64 > limit;
+        if has_more {
+            messages.remove(0);
+        }
+
+        let messages: Vec<ChannelMessageDto> =
+            messages.into_iter().map(ChannelMessageDto::from).collect();
+        let next_before = if has_more {
+            messages.first().map(|m| m.message_id.clone())
+        } else {
+            None
+        };
+
+        HttpResponse::Ok().json(ChannelHistoryResponse {
+            base: BaseResponse::success("Messages retrieved"),
+            messages,
+            has_more,
+            next_before,
+        })
+    }
+
+    /// GET /api/v1/admin/chat/channels - List all channels, including inactive ones
+    pub async fn admin_list(state: web::Data<AppState>) -> HttpResponse {
+        let db = state.read_db().await;
+
+        match db_read::get_all(&db, true).await {
+            Ok(channels) => HttpResponse::Ok().json(AdminChannelListResponse {
+                base: BaseResponse::success("Channels retrieved"),
+                channels,
+            }),
+            Err(e) => {
+                error!("Failed to list chat channels: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve channels"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/chat/channels - Create a channel
+    ///
+    /// # Responses
+    /// - 201: Channel created
+    /// - 400: Invalid slug or name
+    /// - 409: Slug already in use
+    pub async fn create(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CreateChannelRequest>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let body = body.into_inner();
+
+        if !is_valid_slug(&body.slug) {
+            return HttpResponse::BadRequest().json(BaseResponse::error(
+                "Slug may only contain lowercase letters, digits, '_' and '-'",
+            ));
+        }
+
+        if !is_valid_name(&body.name) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Name must be between 1 and 100 characters"));
+        }
+
+        let db = state.db.lock().await;
+
+        if let Ok(Some(_)) = db_read::get_by_slug(&db, &body.slug).await {
+            return HttpResponse::Conflict().json(BaseResponse::error("Channel already exists"));
+        }
+
+        let params = db_mutations::CreateChatChannelParams {
+            slug: body.slug.clone(),
+            name: body.name.trim().to_string(),
+            description: body.description,
+            created_by: user_id,
+        };
+
+        let id = match db_mutations::create(&db, &params).await {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Failed to create chat channel {}: {}", body.slug, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to create channel"));
+            }
+        };
+
+        info!("Chat channel {} created by user {}", body.slug, user_id);
+
+        match db_read::get_by_id(&db, id).await {
+            Ok(Some(channel)) => HttpResponse::Created().json(ChannelResponse {
+                base: BaseResponse::success("Channel created"),
+                channel,
+            }),
+            _ => HttpResponse::Created().json(BaseResponse::success("Channel created")),
+        }
+    }
+
+    /// PATCH /api/v1/admin/chat/channels/{id} - Update a channel
+    ///
+    /// # Responses
+    /// - 200: Channel updated
+    /// - 400: Invalid name
+    /// - 404: Channel not found
+    pub async fn update(
+        state: web::Data<AppState>,
+        path: web::Path<i64>,
+        body: web::Json<UpdateChannelRequest>,
+    ) -> HttpResponse {
+        let id = path.into_inner();
+        let body = body.into_inner();
+
+        if let Some(name) = &body.name {
+            if !is_valid_name(name) {
+                return HttpResponse::BadRequest()
+                    .json(BaseResponse::error("Name must be between 1 and 100 characters"));
+            }
+        }
+
+        let params = db_mutations::UpdateChatChannelParams {
+            name: body.name.map(|name| name.trim().to_string()),
+            description: body.description,
+            is_active: body.is_active,
+        };
+
+        let db = state.db.lock().await;
+
+        match db_mutations::update(&db, id, &params).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Channel not found"));
+            }
+            Err(e) => {
+                error!("Failed to update chat channel {}: {}", id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to update channel"));
+            }
+        }
+
+        match db_read::get_by_id(&db, id).await {
+            Ok(Some(channel)) => HttpResponse::Ok().json(ChannelResponse {
+                base: BaseResponse::success("Channel updated"),
+                channel,
+            }),
+            _ => HttpResponse::Ok().json(BaseResponse::success("Channel updated")),
+        }
+    }
+
+    /// DELETE /api/v1/admin/chat/channels/{id} - Delete a channel and its messages
+    ///
+    /// # Responses
+    /// - 200: Channel deleted
+    /// - 404: Channel not found
+    pub async fn delete(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let id = path.into_inner();
+        let db = state.db.lock().await;
+        let result = db_mutations::delete(&db, id).await;
+        drop(db);
+
+        match result {
+            Ok(true) => {
+                if let Some(mongodb) = state.mongo() {
+                    let client = MongoChannelClient::new(mongodb.clone());
+                    if let Err(e) = client.delete_channel_messages(id).await {
+                        warn!("Failed to delete messages of chat channel {}: {}", id, e);
+                    }
+                }
+                info!("Chat channel {} deleted", id);
+                HttpResponse::Ok().json(BaseResponse::success("Channel deleted"))
+            }
+            Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("Channel not found")),
+            Err(e) => {
+                error!("Failed to delete chat channel {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to delete channel"))
+            }
+        }
+    }
+}
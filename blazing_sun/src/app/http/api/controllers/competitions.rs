@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::app::db_query::{mutations as db_mutations, read as db_read};
 use crate::bootstrap::database::database::AppState;
+use crate::bootstrap::database::replica;
 
 const PRIZE_CENTS: i64 = 10000;
 
@@ -90,7 +91,7 @@ fn compute_scores(entries: &[db_read::competition::CompetitionEntryWithCounts])
 
 /// GET /api/v1/competitions
 pub async fn list_competitions(state: web::Data<AppState>) -> HttpResponse {
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     match db_read::competition::get_all(&db).await {
         Ok(competitions) => {
@@ -133,7 +134,7 @@ pub async fn get_competition(
     path: web::Path<i64>,
 ) -> HttpResponse {
     let competition_id = path.into_inner();
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     let competition = match db_read::competition::get_by_id(&db, competition_id).await {
         Ok(competition) => competition,
@@ -558,25 +559,19 @@ pub async fn finalize_competition(
         competition_id,
         winner.gallery_id,
         winner.user_id,
+        PRIZE_CENTS,
     )
     .await
     {
         drop(db);
-        eprintln!("Failed to set competition winner: {:?}", e);
+        eprintln!("Failed to finalize competition: {:?}", e);
         return HttpResponse::InternalServerError().json(serde_json::json!({
             "error": "Failed to finalize competition"
         }));
     }
 
-    if let Err(e) = db_mutations::user::add_balance(&db, winner.user_id, PRIZE_CENTS).await {
-        drop(db);
-        eprintln!("Failed to award prize: {:?}", e);
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Failed to award prize"
-        }));
-    }
-
     drop(db);
+    replica::mark_written(winner.user_id).await;
     HttpResponse::Ok().json(serde_json::json!({
         "winner_gallery_id": winner.gallery_id,
         "winner_user_id": winner.user_id,
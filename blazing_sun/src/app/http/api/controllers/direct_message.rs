@@ -0,0 +1,248 @@
+//!
+//! Direct Message Controller
+//!
+//! Persistent one-to-one conversations. Live messaging and receipts go
+//! through the WebSocket gateway (`chat.command.send_message` /
+//! `chat.command.mark_read`); these endpoints cover the inbox and history.
+//!
+//! - GET /api/v1/chat/conversations: Conversations with last message and unread counts
+//! - GET /api/v1/chat/conversations/{user_id}/messages: Paginated conversation history
+//!
+
+use std::collections::BTreeMap;
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::Document;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::app::cache::UserProfileCache;
+use crate::app::chat::mongodb_chat::MongoChatClient;
+use crate::app::chat::types::{conversation_id, ConversationSummary, MessageType, PrivateMessage};
+use crate::app::chat::unread::ChatUnread;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Default and maximum page size for message history
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 100;
+
+/// Maximum conversations returned by the inbox
+const CONVERSATION_LIMIT: i64 = 100;
+
+/// Direct Message Controller
+pub struct DirectMessageController;
+
+/// Conversation list response
+#[derive(Debug, Serialize)]
+pub struct ConversationListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub conversations: Vec<ConversationSummary>,
+}
+
+/// Direct message as returned by the history endpoint
+#[derive(Debug, Serialize)]
+pub struct DirectMessageDto {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub sender_id: i64,
+    pub recipient_id: i64,
+    pub content: String,
+    pub message_type: MessageType,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+    pub read_at: Option<String>,
+}
+
+impl From<PrivateMessage> for DirectMessageDto {
+    fn from(message: PrivateMessage) -> Self {
+        Self {
+            message_id: message.id.map(|id| id.to_hex()).unwrap_or_default(),
+            conversation_id: message
+                .conversation_id
+                .unwrap_or_else(|| conversation_id(message.sender_id, message.recipient_id)),
+            sender_id: message.sender_id,
+            recipient_id: message.recipient_id,
+            content: message.content,
+            message_type: message.message_type,
+            created_at: message.created_at.to_rfc3339(),
+            delivered_at: message.delivered_at.map(|at| at.to_rfc3339()),
+            read_at: message.read_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+/// Conversation history response
+#[derive(Debug, Serialize)]
+pub struct ConversationHistoryResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub conversation_id: String,
+    pub messages: Vec<DirectMessageDto>,
+    pub has_more: bool,
+    /// Pass as `before` to load the previous page
+    pub next_before: Option<String>,
+}
+
+/// Query parameters for message history
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub before: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Read an integer field that MongoDB may return as Int32 or Int64
+fn doc_i64(doc: &Document, key: &str) -> Option<i64> {
+    doc.get_i64(key)
+        .ok()
+        .or_else(|| doc.get_i32(key).ok().map(i64::from))
+}
+
+impl DirectMessageController {
+    /// GET /api/v1/chat/conversations - List the user's conversations
+    ///
+    /// Also rewrites the user's unread mirror in Redis, which the gateway
+    /// reports in `system.state_snapshot`.
+    pub async fn list(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+
+        let Some(mongodb) = state.mongo() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Direct messages are unavailable"));
+        };
+
+        let client = MongoChatClient::new(mongodb.clone());
+        let rows = match client.get_conversations(user_id, CONVERSATION_LIMIT).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Failed to load conversations of user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve conversations"));
+            }
+        };
+
+        let profiles = UserProfileCache::new(state.redis());
+        let db = state.read_db().await;
+        let mut conversations = Vec::with_capacity(rows.len());
+        let mut unread = BTreeMap::new();
+
+        for row in rows {
+            let Some(other_user_id) = doc_i64(&row, "_id") else {
+                continue;
+            };
+            let conversation_id = conversation_id(user_id, other_user_id);
+            let unread_count = doc_i64(&row, "unread_count").unwrap_or(0);
+            if unread_count > 0 {
+                unread.insert(conversation_id.clone(), unread_count as u32);
+            }
+
+            // Deleted accounts keep their conversations, without a profile
+            let (username, avatar_id) = match profiles.get(&db, other_user_id).await {
+                Ok(profile) => (profile.first_name, profile.avatar_id),
+                Err(_) => (String::new(), None),
+            };
+
+            let last_message_at = row
+                .get_str("last_message_at")
+                .ok()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| at.with_timezone(&Utc))
+                .unwrap_or_default();
+
+            conversations.push(ConversationSummary {
+                conversation_id,
+                user_id: other_user_id,
+                username,
+                avatar_id,
+                last_message: row.get_str("last_message").unwrap_or_default().to_string(),
+                last_message_at,
+                unread_count,
+            });
+        }
+        drop(db);
+
+        ChatUnread::new(state.redis()).sync(user_id, &unread).await;
+
+        HttpResponse::Ok().json(ConversationListResponse {
+            base: BaseResponse::success("Conversations retrieved"),
+            conversations,
+        })
+    }
+
+    /// GET /api/v1/chat/conversations/{user_id}/messages - Paginated history
+    pub async fn messages(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+        query: web::Query<HistoryQuery>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let other_user_id = path.into_inner();
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT);
+
+        let before = match query.before.as_deref() {
+            Some(before) => match ObjectId::parse_str(before) {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor"));
+                }
+            },
+            None => None,
+        };
+
+        let Some(mongodb) = state.mongo() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Direct messages are unavailable"));
+        };
+
+        // Fetch one extra message to know whether an older page exists
+        let client = MongoChatClient::new(mongodb.clone());
+        let mut messages = match client
+            .get_conversation(user_id, other_user_id, limit + 1, before)
+            .await
+        {
+            Ok(messages) => messages,
+            Err(e) => {
+                error!(
+                    "Failed to load conversation of users {} and {}: {}",
+                    user_id, other_user_id, e
+                );
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve messages"));
+            }
+        };
+
+        let has_more = messages.len() as i64 > limit;
+        if has_more {
+            messages.remove(0);
+        }
+
+        let messages: Vec<DirectMessageDto> =
+            messages.into_iter().map(DirectMessageDto::from).collect();
+        let next_before = if has_more {
+            messages.first().map(|m| m.message_id.clone())
+        } else {
+            None
+        };
+
+        HttpResponse::Ok().json(ConversationHistoryResponse {
+            base: BaseResponse::success("Messages retrieved"),
+            conversation_id: conversation_id(user_id, other_user_id),
+            messages,
+            has_more,
+            next_before,
+        })
+    }
+}
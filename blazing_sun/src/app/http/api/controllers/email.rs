@@ -12,6 +12,7 @@ use validator::Validate;
 use crate::app::http::api::controllers::responses::{
     BaseResponse, MissingFieldsResponse, ValidationErrorResponse,
 };
+use crate::app::http::api::validators::validate_request;
 use crate::app::mq::jobs::email::{EmailTemplate, SendEmailParams};
 use crate::bootstrap::mq;
 use crate::bootstrap::mq::JobOptions;
@@ -98,19 +99,9 @@ impl EmailController {
             new_email: new_email.clone(),
         };
 
-        if let Err(e) = validated_request.validate() {
-            // Convert ValidationErrors to HashMap<String, Vec<String>>
-            let mut errors = HashMap::new();
-            for (field, field_errors) in e.field_errors() {
-                let messages: Vec<String> = field_errors
-                    .iter()
-                    .filter_map(|err| err.message.as_ref().map(|m| m.to_string()))
-                    .collect();
-                if !messages.is_empty() {
-                    errors.insert(field.to_string(), messages);
-                }
-            }
-            return HttpResponse::BadRequest().json(ValidationErrorResponse::new(errors));
+        let errors = validate_request(&validated_request);
+        if !errors.is_empty() {
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
         }
 
         let db = state.db.lock().await;
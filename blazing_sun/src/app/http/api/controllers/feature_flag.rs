@@ -0,0 +1,232 @@
+//!
+//! Feature Flag Controller
+//!
+//! Admin CRUD for runtime feature flags:
+//! - GET /api/v1/admin/feature-flags: List all flags
+//! - POST /api/v1/admin/feature-flags: Create a flag
+//! - PATCH /api/v1/admin/feature-flags/{key}: Update a flag
+//! - DELETE /api/v1/admin/feature-flags/{key}: Delete a flag
+//!
+
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::app::db_query::mutations::feature_flag as db_mutations;
+use crate::app::db_query::read::feature_flag::{self as db_read, FeatureFlag};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Feature Flag Controller
+pub struct FeatureFlagController;
+
+/// Single flag response
+#[derive(Debug, Serialize)]
+pub struct FeatureFlagResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub flag: FeatureFlag,
+}
+
+/// Flag list response
+#[derive(Debug, Serialize)]
+pub struct FeatureFlagListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub flags: Vec<FeatureFlag>,
+}
+
+/// Create flag request
+#[derive(Debug, Deserialize)]
+pub struct CreateFeatureFlagRequest {
+    pub key: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rollout_percentage: i16,
+    #[serde(default)]
+    pub allowlist: Vec<i64>,
+}
+
+/// Update flag request (omitted fields are left unchanged)
+#[derive(Debug, Deserialize)]
+pub struct UpdateFeatureFlagRequest {
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    pub rollout_percentage: Option<i16>,
+    pub allowlist: Option<Vec<i64>>,
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 100
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+}
+
+fn is_valid_percentage(percentage: i16) -> bool {
+    (0..=100).contains(&percentage)
+}
+
+impl FeatureFlagController {
+    /// GET /api/v1/admin/feature-flags - List all flags
+    pub async fn list(state: web::Data<AppState>) -> HttpResponse {
+        let db = state.read_db().await;
+
+        match db_read::get_all(&db).await {
+            Ok(flags) => HttpResponse::Ok().json(FeatureFlagListResponse {
+                base: BaseResponse::success("Feature flags retrieved"),
+                flags,
+            }),
+            Err(e) => {
+                error!("Failed to list feature flags: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve feature flags"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/feature-flags - Create a flag
+    ///
+    /// # Responses
+    /// - 201: Flag created
+    /// - 400: Invalid key or rollout percentage
+    /// - 409: Flag already exists
+    pub async fn create(
+        state: web::Data<AppState>,
+        body: web::Json<CreateFeatureFlagRequest>,
+    ) -> HttpResponse {
+        let body = body.into_inner();
+
+        if !is_valid_key(&body.key) {
+            return HttpResponse::BadRequest().json(BaseResponse::error(
+                "Key may only contain lowercase letters, digits, '_' and '.'",
+            ));
+        }
+
+        if !is_valid_percentage(body.rollout_percentage) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Rollout percentage must be between 0 and 100"));
+        }
+
+        let db = state.db.lock().await;
+
+        if let Ok(Some(_)) = db_read::get_by_key(&db, &body.key).await {
+            return HttpResponse::Conflict().json(BaseResponse::error("Feature flag already exists"));
+        }
+
+        let params = db_mutations::CreateFeatureFlagParams {
+            key: body.key.clone(),
+            description: body.description,
+            enabled: body.enabled,
+            rollout_percentage: body.rollout_percentage,
+            allowlist: body.allowlist,
+        };
+
+        if let Err(e) = db_mutations::create(&db, &params).await {
+            error!("Failed to create feature flag {}: {}", body.key, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to create feature flag"));
+        }
+
+        let flag = db_read::get_by_key(&db, &body.key).await;
+        drop(db);
+
+        state.feature_flags().await.invalidate(&body.key).await;
+        info!("Feature flag {} created", body.key);
+
+        match flag {
+            Ok(Some(flag)) => HttpResponse::Created().json(FeatureFlagResponse {
+                base: BaseResponse::success("Feature flag created"),
+                flag,
+            }),
+            _ => HttpResponse::Created().json(BaseResponse::success("Feature flag created")),
+        }
+    }
+
+    /// PATCH /api/v1/admin/feature-flags/{key} - Update a flag
+    ///
+    /// # Responses
+    /// - 200: Flag updated
+    /// - 400: Invalid rollout percentage
+    /// - 404: Flag not found
+    pub async fn update(
+        state: web::Data<AppState>,
+        path: web::Path<String>,
+        body: web::Json<UpdateFeatureFlagRequest>,
+    ) -> HttpResponse {
+        let key = path.into_inner();
+        let body = body.into_inner();
+
+        if let Some(percentage) = body.rollout_percentage {
+            if !is_valid_percentage(percentage) {
+                return HttpResponse::BadRequest()
+                    .json(BaseResponse::error("Rollout percentage must be between 0 and 100"));
+            }
+        }
+
+        let params = db_mutations::UpdateFeatureFlagParams {
+            description: body.description,
+            enabled: body.enabled,
+            rollout_percentage: body.rollout_percentage,
+            allowlist: body.allowlist,
+        };
+
+        let db = state.db.lock().await;
+
+        match db_mutations::update(&db, &key, &params).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Feature flag not found"));
+            }
+            Err(e) => {
+                error!("Failed to update feature flag {}: {}", key, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to update feature flag"));
+            }
+        }
+
+        let flag = db_read::get_by_key(&db, &key).await;
+        drop(db);
+
+        state.feature_flags().await.invalidate(&key).await;
+        info!("Feature flag {} updated", key);
+
+        match flag {
+            Ok(Some(flag)) => HttpResponse::Ok().json(FeatureFlagResponse {
+                base: BaseResponse::success("Feature flag updated"),
+                flag,
+            }),
+            _ => HttpResponse::Ok().json(BaseResponse::success("Feature flag updated")),
+        }
+    }
+
+    /// DELETE /api/v1/admin/feature-flags/{key} - Delete a flag
+    ///
+    /// # Responses
+    /// - 200: Flag deleted
+    /// - 404: Flag not found
+    pub async fn delete(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+        let key = path.into_inner();
+        let db = state.db.lock().await;
+
+        let result = db_mutations::delete(&db, &key).await;
+        drop(db);
+
+        match result {
+            Ok(true) => {
+                state.feature_flags().await.invalidate(&key).await;
+                info!("Feature flag {} deleted", key);
+                HttpResponse::Ok().json(BaseResponse::success("Feature flag deleted"))
+            }
+            Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("Feature flag not found")),
+            Err(e) => {
+                error!("Failed to delete feature flag {}: {}", key, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to delete feature flag"))
+            }
+        }
+    }
+}
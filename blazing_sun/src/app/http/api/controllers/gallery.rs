@@ -136,7 +136,7 @@ pub async fn get_user_galleries(req: HttpRequest, state: web::Data<AppState>) ->
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Fetch galleries with counts
     match db_read::gallery::get_by_user_with_counts(&db, user_id).await {
@@ -201,7 +201,7 @@ pub async fn get_gallery(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Fetch gallery
     match db_read::gallery::get_by_id(&db, gallery_id).await {
@@ -269,7 +269,7 @@ pub async fn get_geo_galleries(req: HttpRequest, state: web::Data<AppState>) ->
         }));
     }
 
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     match db_read::gallery::get_geo_for_map(&db).await {
         Ok(galleries) => {
@@ -329,7 +329,7 @@ pub async fn get_geo_gallery(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     match db_read::gallery::get_by_uuid(&db, gallery_uuid).await {
         Ok(gallery) => {
@@ -920,7 +920,7 @@ pub async fn get_user_galleries_oauth(
     }
 
     let user_id = claims.user_id;
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Fetch galleries with counts
     match db_read::gallery::get_by_user_with_counts(&db, user_id).await {
@@ -985,7 +985,7 @@ pub async fn get_gallery_oauth(
     }
 
     let user_id = claims.user_id;
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Check if user owns the gallery
     if !db_read::gallery::user_owns_gallery(&db, gallery_id, user_id).await {
@@ -131,7 +131,7 @@ pub async fn get_like_count(
     path: web::Path<i64>,
 ) -> HttpResponse {
     let gallery_id = path.into_inner();
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     match db_read::gallery_like::count_by_gallery(&db, gallery_id).await {
         Ok(count) => {
@@ -82,7 +82,7 @@ impl GameChatConfigController {
             return HttpResponse::Forbidden().json(BaseResponse::error("Access denied"));
         }
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match db_read::get_config(&db).await {
             Ok(config) => {
@@ -0,0 +1,196 @@
+//!
+//! Game Region Controller
+//!
+//! Admin endpoints for multi-region game deployments:
+//! - GET /api/v1/admin/games/regions: Configured regions and active room counts
+//! - POST /api/v1/admin/games/rooms/{room_id}/migrate: Move a waiting room to another region
+//!
+//! Migration itself is performed by the game handler of the room's current region
+//! (it owns the in-memory room state), so the endpoint publishes a `migrate_room`
+//! command to that region's games topic and returns 202.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::app::db_query::read::game_room::{self as db_read, RegionRoomCount};
+use crate::app::games::types::{Actor, Audience, EventEnvelope};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::config::GamesConfig;
+use crate::database::AppState;
+use crate::events::topic;
+
+/// Game Region Controller
+pub struct GameRegionController;
+
+/// Region as shown to admins
+#[derive(Debug, Serialize)]
+pub struct RegionItem {
+    pub name: String,
+    pub gateway_url: String,
+}
+
+/// Region overview response
+#[derive(Debug, Serialize)]
+pub struct RegionListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub current_region: String,
+    pub drain_target: Option<String>,
+    pub regions: Vec<RegionItem>,
+    pub rooms: Vec<RegionRoomCount>,
+}
+
+/// Migrate room request
+#[derive(Debug, Deserialize)]
+pub struct MigrateRoomRequest {
+    pub region: String,
+}
+
+/// Migrate room response
+#[derive(Debug, Serialize)]
+pub struct MigrateRoomResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub room_id: String,
+    pub from_region: String,
+    pub to_region: String,
+}
+
+impl GameRegionController {
+    /// GET /api/v1/admin/games/regions - Configured regions and active room counts
+    pub async fn list(state: web::Data<AppState>) -> HttpResponse {
+        let db = state.read_db().await;
+
+        match db_read::count_active_by_region(&db).await {
+            Ok(rooms) => HttpResponse::Ok().json(RegionListResponse {
+                base: BaseResponse::success("Regions retrieved"),
+                current_region: GamesConfig::region().to_string(),
+                drain_target: GamesConfig::region_drain_target().map(String::from),
+                regions: GamesConfig::regions()
+                    .iter()
+                    .map(|r| RegionItem {
+                        name: r.name.clone(),
+                        gateway_url: r.gateway_url.clone(),
+                    })
+                    .collect(),
+                rooms,
+            }),
+            Err(e) => {
+                error!("Failed to count rooms by region: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve regions"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/games/rooms/{room_id}/migrate - Move a waiting room
+    ///
+    /// # Responses
+    /// - 202: Migration requested
+    /// - 400: Unknown region or room already in that region
+    /// - 404: Room not found
+    /// - 409: Room is not waiting
+    /// - 503: Event bus unavailable
+    pub async fn migrate_room(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<String>,
+        body: web::Json<MigrateRoomRequest>,
+    ) -> HttpResponse {
+        let admin_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let room_id = path.into_inner();
+        let to_region = body.into_inner().region;
+
+        if GamesConfig::find_region(&to_region).is_none() {
+            return HttpResponse::BadRequest().json(BaseResponse::error("Unknown region"));
+        }
+
+        let db = state.db.lock().await;
+        let room = db_read::get_region(&db, &room_id).await;
+        drop(db);
+
+        let room = match room {
+            Ok(Some(room)) => room,
+            Ok(None) => return HttpResponse::NotFound().json(BaseResponse::error("Room not found")),
+            Err(e) => {
+                error!("Failed to load room {} for migration: {}", room_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to migrate room"));
+            }
+        };
+
+        if room.status != "waiting" {
+            return HttpResponse::Conflict()
+                .json(BaseResponse::error("Only waiting rooms can be migrated"));
+        }
+
+        if room.region == to_region {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Room already belongs to this region"));
+        }
+
+        let Some(event_bus) = state.event_bus() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Game service unavailable"));
+        };
+
+        let envelope = EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: "games.command.migrate_room".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: admin_id,
+                username: "admin".to_string(),
+                socket_id: String::new(),
+                roles: vec!["admin".to_string()],
+            },
+            audience: Audience::room(room_id.clone()),
+            payload: serde_json::json!({
+                "room_id": room_id,
+                "to_region": to_region,
+            }),
+        };
+
+        let payload = match serde_json::to_vec(&envelope) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize migrate_room command: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to migrate room"));
+            }
+        };
+
+        // The command goes to the region that currently owns the room
+        let commands_topic = topic::games_commands_for(&room.region);
+        if let Err(e) = event_bus
+            .producer()
+            .send_raw(&commands_topic, Some(&room_id), &payload)
+            .await
+        {
+            error!("Failed to publish migrate_room for {}: {}", room_id, e);
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Game service unavailable"));
+        }
+
+        info!(
+            "Admin {} requested migration of room {} from {} to {}",
+            admin_id, room_id, room.region, to_region
+        );
+
+        HttpResponse::Accepted().json(MigrateRoomResponse {
+            base: BaseResponse::success("Room migration requested"),
+            room_id,
+            from_region: room.region,
+            to_region,
+        })
+    }
+}
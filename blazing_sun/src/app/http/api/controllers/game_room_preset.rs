@@ -0,0 +1,101 @@
+//!
+//! Game Room Preset Controller
+//!
+//! Room configuration presets and constraints per game type.
+//! GET /api/v1/games/{game_type}/room-presets: Presets and the room constraints
+//! the game registers (player range, spectator cap, turn timer range)
+//!
+//! The create_room command accepts a preset key (`preset`) and applies the
+//! same constraints to whatever the client sends.
+//!
+
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::app::db_query::read::game_room_preset::{self as db_presets, GameRoomPreset};
+use crate::app::games::room_config::{self, RoomConfig, RoomConstraints, RoomSettings};
+use crate::app::games::types::GameType;
+use crate::bootstrap::database::AppState;
+
+/// A preset with its resolved room settings
+#[derive(Debug, Serialize)]
+pub struct RoomPresetItem {
+    pub key: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub config: RoomConfig,
+}
+
+/// Room presets response
+#[derive(Debug, Serialize)]
+pub struct RoomPresetsResponse {
+    pub game_type: String,
+    pub constraints: RoomConstraints,
+    pub presets: Vec<RoomPresetItem>,
+}
+
+/// Resolve a stored preset; presets violating the game's constraints are skipped
+fn to_item(game_type: &GameType, preset: GameRoomPreset) -> Option<RoomPresetItem> {
+    let settings = RoomSettings {
+        player_count: Some(preset.player_count),
+        allow_spectators: Some(preset.allow_spectators),
+        max_spectators: Some(preset.max_spectators),
+        turn_timer_seconds: preset.turn_timer_seconds.map(i64::from),
+        dice_rules: Default::default(),
+    };
+
+    match RoomConfig::resolve(game_type, settings) {
+        Ok(config) => Some(RoomPresetItem {
+            key: preset.preset_key,
+            name: preset.name,
+            description: preset.description,
+            config,
+        }),
+        Err(e) => {
+            warn!(
+                "Skipping invalid {} room preset {}: {}",
+                game_type.as_str(),
+                preset.preset_key,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Get room presets for a game type
+///
+/// GET /api/v1/games/{game_type}/room-presets
+///
+/// This is a public endpoint - no authentication required.
+pub async fn get_presets(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let game_type = match GameType::from_str(&path.into_inner()) {
+        Some(gt) => gt,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid game type"
+            }));
+        }
+    };
+
+    let db = state.read_db().await;
+
+    match db_presets::get_by_game_type(&db, game_type.as_str()).await {
+        Ok(presets) => HttpResponse::Ok().json(RoomPresetsResponse {
+            game_type: game_type.as_str().to_string(),
+            constraints: *room_config::constraints(&game_type),
+            presets: presets
+                .into_iter()
+                .filter_map(|preset| to_item(&game_type, preset))
+                .collect(),
+        }),
+        Err(e) => {
+            error!("Failed to load {} room presets: {}", game_type.as_str(), e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch room presets"
+            }))
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//!
+//! Game Room Schedule Controller
+//!
+//! Rooms scheduled to open later, and registration for them:
+//! GET /api/v1/games/rooms/upcoming: Scheduled rooms, soonest first; JSON, or
+//! an iCalendar feed with `format=ics`
+//! POST /api/v1/games/rooms/{room_id}/registration: Register for a scheduled room
+//! DELETE /api/v1/games/rooms/{room_id}/registration: Withdraw the registration
+//!
+//! Registered users are reminded before the room opens and told when it does
+//! (see `app::games::room_schedule`). Hosts schedule rooms with the
+//! `scheduled_start_at` field of the `create_room` WebSocket command.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::app::db_query::mutations::game_room as db_mutations;
+use crate::app::db_query::read::game_room::{self as db_read, ScheduledRoom};
+use crate::app::games::room_schedule;
+use crate::app::games::types::GameType;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::config::GamesConfig;
+use crate::database::AppState;
+
+/// Days listed when the query does not say
+const DEFAULT_DAYS: i64 = 7;
+
+/// Game Room Schedule Controller
+pub struct GameRoomScheduleController;
+
+/// Upcoming rooms response
+#[derive(Debug, Serialize)]
+pub struct UpcomingRoomsResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub rooms: Vec<ScheduledRoom>,
+}
+
+/// Upcoming rooms query
+#[derive(Debug, Default, Deserialize)]
+pub struct UpcomingQuery {
+    pub game_type: Option<String>,
+    /// How many days ahead to list (default 7, at most GAME_ROOM_SCHEDULE_MAX_DAYS)
+    pub days: Option<i64>,
+    /// json (default) or ics
+    pub format: Option<String>,
+}
+
+impl GameRoomScheduleController {
+    /// GET /api/v1/games/rooms/upcoming - Scheduled rooms, soonest first
+    ///
+    /// # Query
+    /// - game_type: only rooms of this game
+    /// - days: how far ahead to look (default 7)
+    /// - format: `ics` returns a `text/calendar` feed for calendar apps
+    pub async fn upcoming(
+        state: web::Data<AppState>,
+        query: web::Query<UpcomingQuery>,
+    ) -> HttpResponse {
+        let query = query.into_inner();
+
+        let game_type = query.game_type.as_deref().filter(|value| !value.is_empty());
+        if game_type.is_some_and(|value| GameType::from_str(value).is_none()) {
+            return HttpResponse::BadRequest().json(BaseResponse::error("Invalid game type"));
+        }
+        let as_ics = match query.format.as_deref() {
+            None | Some("json") => false,
+            Some("ics") => true,
+            Some(_) => {
+                return HttpResponse::BadRequest()
+                    .json(BaseResponse::error("Format must be json or ics"))
+            }
+        };
+
+        let now = Utc::now();
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_DAYS)
+            .clamp(1, GamesConfig::room_schedule_max_days().max(1));
+
+        let db = state.read_db().await;
+        match db_read::get_scheduled_rooms(&db, game_type, None, now + Duration::days(days)).await {
+            Ok(rooms) if as_ics => HttpResponse::Ok()
+                .content_type("text/calendar; charset=utf-8")
+                .body(room_schedule::to_ics(&rooms, now)),
+            Ok(rooms) => HttpResponse::Ok().json(UpcomingRoomsResponse {
+                base: BaseResponse::success("Upcoming rooms retrieved"),
+                rooms,
+            }),
+            Err(e) => {
+                error!("Failed to load upcoming rooms: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load upcoming rooms"))
+            }
+        }
+    }
+
+    /// POST /api/v1/games/rooms/{room_id}/registration - Register for a scheduled room
+    pub async fn register(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<String>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let room_id = path.into_inner();
+
+        let db = state.db.lock().await.clone();
+        if db_read::is_user_banned(&db, &room_id, user_id).await {
+            return HttpResponse::Forbidden()
+                .json(BaseResponse::error("You are banned from this room"));
+        }
+
+        match db_mutations::register(&db, &room_id, user_id).await {
+            Ok(true) => HttpResponse::Ok().json(BaseResponse::success("Registered for the room")),
+            Ok(false) => match db_read::get_registered_users(&db, &room_id).await {
+                // Registering twice is not an error
+                Ok(users) if users.contains(&user_id) => {
+                    HttpResponse::Ok().json(BaseResponse::success("Registered for the room"))
+                }
+                Ok(_) => {
+                    HttpResponse::NotFound().json(BaseResponse::error("Scheduled room not found"))
+                }
+                Err(e) => {
+                    error!("Failed to load registrations of room {}: {}", room_id, e);
+                    HttpResponse::InternalServerError()
+                        .json(BaseResponse::error("Failed to register for the room"))
+                }
+            },
+            Err(e) => {
+                error!(
+                    "Failed to register user {} for room {}: {}",
+                    user_id, room_id, e
+                );
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to register for the room"))
+            }
+        }
+    }
+
+    /// DELETE /api/v1/games/rooms/{room_id}/registration - Withdraw a registration
+    pub async fn unregister(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<String>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let room_id = path.into_inner();
+
+        let db = state.db.lock().await.clone();
+        match db_mutations::unregister(&db, &room_id, user_id).await {
+            Ok(true) => HttpResponse::Ok().json(BaseResponse::success("Registration withdrawn")),
+            Ok(false) => {
+                HttpResponse::NotFound().json(BaseResponse::error("Registration not found"))
+            }
+            Err(e) => {
+                error!(
+                    "Failed to unregister user {} from room {}: {}",
+                    user_id, room_id, e
+                );
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to withdraw the registration"))
+            }
+        }
+    }
+}
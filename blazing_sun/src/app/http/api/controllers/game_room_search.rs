@@ -0,0 +1,225 @@
+//!
+//! Game Room Search Controller
+//!
+//! Lobby room search beyond the latest rooms `list_rooms` pages through:
+//! GET /api/v1/games/rooms/search: Active rooms matching the filters, cursor
+//! paginated
+//!
+//! Rooms are shown the way the lobby shows them: rooms the user is banned from
+//! are left out, and in-progress rooms only appear to their members. The
+//! `games.command.search_rooms` WebSocket command takes the same filters.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use pagination::{paginate, Cursor};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::app::db_query::read::game_room::{self as db_read, RoomSearch, RoomSort};
+use crate::app::games::room_list;
+use crate::app::games::types::GameType;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Longest host prefix or name search in characters
+const MAX_SEARCH_LENGTH: usize = 64;
+
+/// Default and largest page of results
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 100;
+
+/// Game Room Search Controller
+pub struct GameRoomSearchController;
+
+/// Search results response
+#[derive(Debug, Serialize)]
+pub struct RoomSearchResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub rooms: Vec<serde_json::Value>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+/// Search query
+#[derive(Debug, Default, Deserialize)]
+pub struct RoomSearchQuery {
+    pub game_type: Option<String>,
+    /// waiting or in_progress
+    pub status: Option<String>,
+    pub has_password: Option<bool>,
+    pub allow_spectators: Option<bool>,
+    /// Host username prefix
+    pub host: Option<String>,
+    /// Text anywhere in the room name
+    pub q: Option<String>,
+    /// newest (default) or oldest
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Turn the query into search filters; Err carries the response message
+fn parse_search(query: &RoomSearchQuery) -> Result<(RoomSearch, RoomSort), &'static str> {
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    let game_type = text(&query.game_type);
+    if game_type
+        .as_deref()
+        .is_some_and(|value| GameType::from_str(value).is_none())
+    {
+        return Err("Invalid game type");
+    }
+
+    let status = text(&query.status);
+    if status
+        .as_deref()
+        .is_some_and(|value| !matches!(value, "waiting" | "in_progress"))
+    {
+        return Err("Status must be waiting or in_progress");
+    }
+
+    let sort = match text(&query.sort) {
+        Some(value) => RoomSort::parse(&value).ok_or("Sort must be newest or oldest")?,
+        None => RoomSort::default(),
+    };
+
+    let host_prefix = text(&query.host);
+    let name = text(&query.q);
+    if [&host_prefix, &name].iter().any(|value| {
+        value
+            .as_deref()
+            .is_some_and(|v| v.chars().count() > MAX_SEARCH_LENGTH)
+    }) {
+        return Err("Search text can be at most 64 characters");
+    }
+
+    Ok((
+        RoomSearch {
+            game_type,
+            status,
+            has_password: query.has_password,
+            allow_spectators: query.allow_spectators,
+            host_prefix,
+            query: name,
+        },
+        sort,
+    ))
+}
+
+impl GameRoomSearchController {
+    /// GET /api/v1/games/rooms/search - Active rooms matching the filters
+    ///
+    /// # Query
+    /// - game_type, status (waiting or in_progress), has_password, allow_spectators
+    /// - host: host username prefix; q: text in the room name (both case-insensitive)
+    /// - sort: newest (default) or oldest
+    /// - cursor: `next_cursor` / `prev_cursor` of a previous page of the same search
+    /// - limit: page size (default 50, max 100)
+    pub async fn search(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        query: web::Query<RoomSearchQuery>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let query = query.into_inner();
+
+        let (search, sort) = match parse_search(&query) {
+            Ok(search) => search,
+            Err(message) => return HttpResponse::BadRequest().json(BaseResponse::error(message)),
+        };
+        let cursor = match query.cursor.as_deref() {
+            Some(value) => match Cursor::decode(value) {
+                Some(cursor) => Some(cursor),
+                None => {
+                    return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor"))
+                }
+            },
+            None => None,
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let db = state.read_db().await;
+        match db_read::search_active_rooms_page(&db, &search, sort, cursor.as_ref(), limit).await {
+            Ok(rows) => {
+                let page = paginate(rows, cursor.as_ref(), limit as usize, |r| {
+                    (r.created_at, r.id)
+                });
+                HttpResponse::Ok().json(RoomSearchResponse {
+                    base: BaseResponse::success("Rooms retrieved"),
+                    rooms: page
+                        .items
+                        .iter()
+                        .filter_map(|record| room_list::list_item_for_user(record, user_id))
+                        .collect(),
+                    next_cursor: page.next_cursor,
+                    prev_cursor: page.prev_cursor,
+                })
+            }
+            Err(e) => {
+                error!("Failed to search game rooms for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to search rooms"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_filters_are_left_out() {
+        let query = RoomSearchQuery {
+            host: Some("  ".to_string()),
+            q: Some(" friday ".to_string()),
+            has_password: Some(false),
+            ..Default::default()
+        };
+
+        let (search, sort) = parse_search(&query).expect("valid search");
+        assert_eq!(sort, RoomSort::Newest);
+        assert!(search.host_prefix.is_none() && search.game_type.is_none());
+        assert_eq!(search.query.as_deref(), Some("friday"));
+        assert_eq!(search.has_password, Some(false));
+    }
+
+    #[test]
+    fn unknown_values_are_rejected() {
+        let status = RoomSearchQuery {
+            status: Some("finished".to_string()),
+            ..Default::default()
+        };
+        assert!(parse_search(&status).is_err());
+
+        let sort = RoomSearchQuery {
+            sort: Some("players".to_string()),
+            ..Default::default()
+        };
+        assert!(parse_search(&sort).is_err());
+
+        let long = RoomSearchQuery {
+            q: Some("x".repeat(MAX_SEARCH_LENGTH + 1)),
+            ..Default::default()
+        };
+        assert!(parse_search(&long).is_err());
+
+        let oldest = RoomSearchQuery {
+            sort: Some("oldest".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_search(&oldest).map(|(_, sort)| sort),
+            Ok(RoomSort::Oldest)
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//!
+//! Game Stats Controller
+//!
+//! Public fairness and reliability statistics per game type.
+//! GET /api/v1/games/stats: Stats for all game types
+//! GET /api/v1/games/{game_type}/stats: Stats for one game type
+//! GET /api/v1/games/{game_type}/stats/{user_id}: One player's stats for a game type
+//!
+//! Values are precomputed hourly by the `game_type_stats` cron job;
+//! `computed_at` tells how fresh they are. Player stats are updated as games
+//! finish (see `app::games::player_stats`).
+//!
+
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::error;
+
+use crate::app::db_query::read::game_type_stats::{self as db_stats, GameTypeStats};
+use crate::app::db_query::read::player_game_stats as db_player_stats;
+use crate::app::games::player_stats::{PlayerGameStats, PlayerStatsSummary};
+use crate::app::games::types::GameType;
+use crate::bootstrap::database::AppState;
+
+/// Public stats for one game type
+#[derive(Debug, Serialize)]
+pub struct GameStatsItem {
+    pub game_type: String,
+    pub total_games: i64,
+    pub avg_duration_seconds: f64,
+    pub finished_rooms: i64,
+    pub abandoned_rooms: i64,
+    pub disconnect_forfeits: i64,
+    /// Share of finished rooms decided by a disconnect forfeit (0-1)
+    pub disconnect_forfeit_rate: f64,
+    /// Share of started rooms that reached a result instead of being abandoned (0-1)
+    pub completion_rate: f64,
+    /// Observed roll distribution, only for dice games
+    pub dice_distribution: Option<serde_json::Value>,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl From<GameTypeStats> for GameStatsItem {
+    fn from(stats: GameTypeStats) -> Self {
+        let ratio = |part: i64, whole: i64| {
+            if whole > 0 {
+                part as f64 / whole as f64
+            } else {
+                0.0
+            }
+        };
+
+        Self {
+            disconnect_forfeit_rate: ratio(stats.disconnect_forfeits, stats.finished_rooms),
+            completion_rate: ratio(
+                stats.finished_rooms,
+                stats.finished_rooms + stats.abandoned_rooms,
+            ),
+            game_type: stats.game_type,
+            total_games: stats.total_games,
+            avg_duration_seconds: stats.avg_duration_seconds,
+            finished_rooms: stats.finished_rooms,
+            abandoned_rooms: stats.abandoned_rooms,
+            disconnect_forfeits: stats.disconnect_forfeits,
+            dice_distribution: stats.dice_distribution,
+            computed_at: stats.computed_at,
+        }
+    }
+}
+
+/// Stats list response
+#[derive(Debug, Serialize)]
+pub struct GameStatsListResponse {
+    pub games: Vec<GameStatsItem>,
+}
+
+/// One player's stats for a game type
+#[derive(Debug, Serialize)]
+pub struct PlayerStatsResponse {
+    pub user_id: i64,
+    pub game_type: String,
+    #[serde(flatten)]
+    pub stats: PlayerStatsSummary,
+}
+
+/// Get stats for all game types
+///
+/// GET /api/v1/games/stats
+///
+/// This is a public endpoint - no authentication required.
+pub async fn get_all_stats(state: web::Data<AppState>) -> HttpResponse {
+    let db = state.read_db().await;
+
+    match db_stats::get_all(&db).await {
+        Ok(stats) => HttpResponse::Ok().json(GameStatsListResponse {
+            games: stats.into_iter().map(GameStatsItem::from).collect(),
+        }),
+        Err(e) => {
+            error!("Failed to load game stats: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch game stats"
+            }))
+        }
+    }
+}
+
+/// Get stats for a single game type
+///
+/// GET /api/v1/games/{game_type}/stats
+///
+/// This is a public endpoint - no authentication required.
+pub async fn get_stats(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let game_type = match GameType::from_str(&path.into_inner()) {
+        Some(gt) => gt,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid game type"
+            }));
+        }
+    };
+
+    let db = state.read_db().await;
+
+    match db_stats::get_by_type(&db, game_type.as_str()).await {
+        Ok(Some(stats)) => HttpResponse::Ok().json(GameStatsItem::from(stats)),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No stats computed for this game type yet"
+        })),
+        Err(e) => {
+            error!("Failed to load {} stats: {}", game_type.as_str(), e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch game stats"
+            }))
+        }
+    }
+}
+
+/// Get one player's stats for a game type
+///
+/// GET /api/v1/games/{game_type}/stats/{user_id}
+///
+/// This is a public endpoint - no authentication required. Players without a
+/// finished game of the type get zeroed stats.
+pub async fn get_player_stats(
+    state: web::Data<AppState>,
+    path: web::Path<(String, i64)>,
+) -> HttpResponse {
+    let (game_type, user_id) = path.into_inner();
+    let game_type = match GameType::from_str(&game_type) {
+        Some(gt) => gt,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid game type"
+            }));
+        }
+    };
+
+    let db = state.read_db().await;
+
+    match db_player_stats::get(&db, user_id, game_type.as_str()).await {
+        Ok(stats) => {
+            let stats = stats.unwrap_or_else(|| PlayerGameStats::new(user_id, game_type.as_str()));
+            HttpResponse::Ok().json(PlayerStatsResponse {
+                user_id,
+                game_type: game_type.as_str().to_string(),
+                stats: stats.summary(),
+            })
+        }
+        Err(e) => {
+            error!(
+                "Failed to load {} stats of user {}: {}",
+                game_type.as_str(),
+                user_id,
+                e
+            );
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch player stats"
+            }))
+        }
+    }
+}
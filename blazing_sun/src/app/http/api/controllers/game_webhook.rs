@@ -0,0 +1,386 @@
+//!
+//! Game Webhook Controller
+//!
+//! Admin management of room lifecycle webhooks:
+//! - GET /api/v1/admin/games/webhooks: List webhooks
+//! - POST /api/v1/admin/games/webhooks: Register a webhook (returns its secret once)
+//! - PATCH /api/v1/admin/games/webhooks/{id}: Update URL, events, description or active flag
+//! - DELETE /api/v1/admin/games/webhooks/{id}: Delete a webhook and its delivery log
+//! - POST /api/v1/admin/games/webhooks/{id}/rotate-secret: Replace the signing secret
+//! - GET /api/v1/admin/games/webhooks/{id}/deliveries: Delivery log
+//! - POST /api/v1/admin/games/webhooks/deliveries/{id}/redeliver: Send a delivery again
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::app::db_query::mutations::game_webhooks as db_mutations;
+use crate::app::db_query::read::game_webhooks::{self as db_read, GameWebhook, GameWebhookDelivery};
+use crate::app::games::webhooks;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Default and largest page of the delivery log
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 200;
+
+/// Delivery statuses that can be filtered on
+const DELIVERY_STATUSES: &[&str] = &["pending", "queued", "retrying", "delivered", "failed"];
+
+/// Game Webhook Controller
+pub struct GameWebhookController;
+
+/// Single webhook response
+#[derive(Debug, Serialize)]
+pub struct GameWebhookResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub webhook: GameWebhook,
+}
+
+/// Webhook with its secret (creation and secret rotation only)
+#[derive(Debug, Serialize)]
+pub struct GameWebhookSecretResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub webhook: GameWebhook,
+    pub secret: String,
+}
+
+/// Webhook list response
+#[derive(Debug, Serialize)]
+pub struct GameWebhookListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub webhooks: Vec<GameWebhook>,
+    pub events: &'static [&'static str],
+}
+
+/// Delivery log response
+#[derive(Debug, Serialize)]
+pub struct GameWebhookDeliveriesResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub deliveries: Vec<GameWebhookDelivery>,
+}
+
+/// Register webhook request
+#[derive(Debug, Deserialize)]
+pub struct CreateGameWebhookRequest {
+    pub url: String,
+    /// Defaults to every event
+    pub events: Option<Vec<String>>,
+    pub description: Option<String>,
+}
+
+/// Update webhook request (omitted fields are left unchanged)
+#[derive(Debug, Deserialize)]
+pub struct UpdateGameWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Delivery log query
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Check a subscription list; Err carries the response message
+fn validate_events(events: &[String]) -> Result<(), &'static str> {
+    if events.is_empty() {
+        return Err("Subscribe to at least one event");
+    }
+    if !events.iter().all(|e| webhooks::is_valid_event(e)) {
+        return Err("Events must be room_created, game_started or game_over");
+    }
+    Ok(())
+}
+
+impl GameWebhookController {
+    /// GET /api/v1/admin/games/webhooks - List webhooks
+    pub async fn list(state: web::Data<AppState>) -> HttpResponse {
+        let db = state.read_db().await;
+
+        match db_read::get_all(&db).await {
+            Ok(webhooks) => HttpResponse::Ok().json(GameWebhookListResponse {
+                base: BaseResponse::success("Webhooks retrieved"),
+                webhooks,
+                events: webhooks::EVENTS,
+            }),
+            Err(e) => {
+                error!("Failed to list game webhooks: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve webhooks"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/games/webhooks - Register a webhook
+    ///
+    /// # Responses
+    /// - 201: Webhook created; the response carries the signing secret
+    /// - 400: Invalid URL or events
+    pub async fn create(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CreateGameWebhookRequest>,
+    ) -> HttpResponse {
+        let admin_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let body = body.into_inner();
+
+        if !webhooks::is_valid_url(&body.url) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("URL must be an absolute http or https URL"));
+        }
+
+        let events = body
+            .events
+            .unwrap_or_else(|| webhooks::EVENTS.iter().map(|e| e.to_string()).collect());
+        if let Err(message) = validate_events(&events) {
+            return HttpResponse::BadRequest().json(BaseResponse::error(message));
+        }
+
+        let secret = webhooks::generate_secret();
+        let params = db_mutations::CreateGameWebhookParams {
+            url: body.url,
+            secret: secret.clone(),
+            events,
+            description: body.description,
+            created_by: admin_id,
+        };
+
+        let db = state.db.lock().await;
+        let webhook = match db_mutations::create(&db, &params).await {
+            Ok(id) => db_read::get_by_id(&db, id).await,
+            Err(e) => Err(e),
+        };
+        drop(db);
+
+        match webhook {
+            Ok(Some(webhook)) => {
+                info!("Game webhook {} registered by admin {}", webhook.id, admin_id);
+                HttpResponse::Created().json(GameWebhookSecretResponse {
+                    base: BaseResponse::success("Webhook created"),
+                    webhook,
+                    secret,
+                })
+            }
+            Ok(None) | Err(_) => {
+                error!("Failed to register game webhook for {}", params.url);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to create webhook"))
+            }
+        }
+    }
+
+    /// PATCH /api/v1/admin/games/webhooks/{id} - Update a webhook
+    ///
+    /// # Responses
+    /// - 200: Webhook updated
+    /// - 400: Invalid URL or events
+    /// - 404: Webhook not found
+    pub async fn update(
+        state: web::Data<AppState>,
+        path: web::Path<i64>,
+        body: web::Json<UpdateGameWebhookRequest>,
+    ) -> HttpResponse {
+        let id = path.into_inner();
+        let body = body.into_inner();
+
+        if let Some(url) = &body.url {
+            if !webhooks::is_valid_url(url) {
+                return HttpResponse::BadRequest()
+                    .json(BaseResponse::error("URL must be an absolute http or https URL"));
+            }
+        }
+
+        if let Some(events) = &body.events {
+            if let Err(message) = validate_events(events) {
+                return HttpResponse::BadRequest().json(BaseResponse::error(message));
+            }
+        }
+
+        let params = db_mutations::UpdateGameWebhookParams {
+            url: body.url,
+            events: body.events,
+            description: body.description,
+            is_active: body.is_active,
+        };
+
+        let db = state.db.lock().await;
+
+        match db_mutations::update(&db, id, &params).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Webhook not found"));
+            }
+            Err(e) => {
+                error!("Failed to update game webhook {}: {}", id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to update webhook"));
+            }
+        }
+
+        let webhook = db_read::get_by_id(&db, id).await;
+        drop(db);
+
+        info!("Game webhook {} updated", id);
+
+        match webhook {
+            Ok(Some(webhook)) => HttpResponse::Ok().json(GameWebhookResponse {
+                base: BaseResponse::success("Webhook updated"),
+                webhook,
+            }),
+            _ => HttpResponse::Ok().json(BaseResponse::success("Webhook updated")),
+        }
+    }
+
+    /// DELETE /api/v1/admin/games/webhooks/{id} - Delete a webhook
+    ///
+    /// # Responses
+    /// - 200: Webhook deleted
+    /// - 404: Webhook not found
+    pub async fn delete(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let id = path.into_inner();
+        let db = state.db.lock().await;
+
+        let result = db_mutations::delete(&db, id).await;
+        drop(db);
+
+        match result {
+            Ok(true) => {
+                info!("Game webhook {} deleted", id);
+                HttpResponse::Ok().json(BaseResponse::success("Webhook deleted"))
+            }
+            Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("Webhook not found")),
+            Err(e) => {
+                error!("Failed to delete game webhook {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to delete webhook"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/games/webhooks/{id}/rotate-secret - Replace the signing secret
+    ///
+    /// # Responses
+    /// - 200: Secret rotated; the response carries the new secret
+    /// - 404: Webhook not found
+    pub async fn rotate_secret(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let id = path.into_inner();
+        let secret = webhooks::generate_secret();
+        let db = state.db.lock().await;
+
+        match db_mutations::rotate_secret(&db, id, &secret).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Webhook not found"));
+            }
+            Err(e) => {
+                error!("Failed to rotate secret of game webhook {}: {}", id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to rotate webhook secret"));
+            }
+        }
+
+        let webhook = db_read::get_by_id(&db, id).await;
+        drop(db);
+
+        info!("Game webhook {} secret rotated", id);
+
+        match webhook {
+            Ok(Some(webhook)) => HttpResponse::Ok().json(GameWebhookSecretResponse {
+                base: BaseResponse::success("Webhook secret rotated"),
+                webhook,
+                secret,
+            }),
+            _ => HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to rotate webhook secret")),
+        }
+    }
+
+    /// GET /api/v1/admin/games/webhooks/{id}/deliveries - Delivery log, newest first
+    ///
+    /// # Query
+    /// - status: only deliveries with this status
+    /// - limit: page size (default 50, max 200)
+    pub async fn deliveries(
+        state: web::Data<AppState>,
+        path: web::Path<i64>,
+        query: web::Query<DeliveriesQuery>,
+    ) -> HttpResponse {
+        let id = path.into_inner();
+        let query = query.into_inner();
+
+        if let Some(status) = &query.status {
+            if !DELIVERY_STATUSES.contains(&status.as_str()) {
+                return HttpResponse::BadRequest().json(BaseResponse::error("Unknown delivery status"));
+            }
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+            .clamp(1, MAX_DELIVERY_LIMIT);
+
+        let db = state.read_db().await;
+
+        match db_read::get_by_id(&db, id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return HttpResponse::NotFound().json(BaseResponse::error("Webhook not found")),
+            Err(e) => {
+                error!("Failed to load game webhook {}: {}", id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve deliveries"));
+            }
+        }
+
+        match db_read::get_deliveries(&db, id, query.status.as_deref(), limit).await {
+            Ok(deliveries) => HttpResponse::Ok().json(GameWebhookDeliveriesResponse {
+                base: BaseResponse::success("Deliveries retrieved"),
+                deliveries,
+            }),
+            Err(e) => {
+                error!("Failed to list deliveries of game webhook {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve deliveries"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/games/webhooks/deliveries/{id}/redeliver - Send a delivery again
+    ///
+    /// The delivery goes back to `pending` with its attempts reset and is
+    /// picked up by the next game_webhooks cron run.
+    ///
+    /// # Responses
+    /// - 202: Redelivery scheduled
+    /// - 404: Delivery not found
+    pub async fn redeliver(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let id = path.into_inner();
+        let db = state.db.lock().await;
+
+        let result = db_mutations::redeliver(&db, id).await;
+        drop(db);
+
+        match result {
+            Ok(true) => {
+                info!("Game webhook delivery {} scheduled for redelivery", id);
+                HttpResponse::Accepted().json(BaseResponse::success("Redelivery scheduled"))
+            }
+            Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("Delivery not found")),
+            Err(e) => {
+                error!("Failed to redeliver game webhook delivery {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to schedule redelivery"))
+            }
+        }
+    }
+}
@@ -70,7 +70,7 @@ pub async fn list_public(
     state: web::Data<AppState>,
     query: web::Query<GeoPlacesQuery>,
 ) -> HttpResponse {
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     let places = match query.place_type.as_deref() {
         Some(place_type) => {
@@ -119,7 +119,7 @@ pub async fn list_public(
 
 /// Admin: list all geo places
 pub async fn list_admin(state: web::Data<AppState>) -> HttpResponse {
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     match db_read::geo_place::get_all(&db).await {
         Ok(places) => {
@@ -205,7 +205,7 @@ pub async fn list_place_images(
     path: web::Path<i64>,
 ) -> HttpResponse {
     let place_id = path.into_inner();
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     match db_read::geo_place_image::get_by_place(&db, place_id).await {
         Ok(images) => {
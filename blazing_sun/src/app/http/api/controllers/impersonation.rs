@@ -0,0 +1,297 @@
+//!
+//! Impersonation Controller
+//!
+//! Lets support admins act as a user to see exactly what they see:
+//! - POST /api/v1/admin/impersonate/{user_id}: Start a session and get an impersonation token
+//! - GET /api/v1/admin/impersonate/sessions: Session history (audit trail)
+//! - DELETE /api/v1/admin/impersonate/sessions/{id}: End a session before it expires
+//!
+//! The token is a normal user JWT for the impersonated user that also carries
+//! `impersonator_id` and `impersonation_id`. It is returned in the body only
+//! (no cookie, so the admin's own browser session is untouched) and expires
+//! with the session. Starting and ending sessions publish
+//! `auth.impersonation_started` / `auth.impersonation_ended`, and every event
+//! published while the token is used names the admin (see `app::impersonation`).
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
+use rbac::Role;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::app::db_query::mutations::impersonation_sessions as db_mutations;
+use crate::app::db_query::read::impersonation_sessions::{
+    self as db_read, ImpersonationSession, SessionFilter,
+};
+use crate::app::db_query::read::user as db_user;
+use crate::app::db_query::read::user_preferences as db_user_preferences;
+use crate::app::http::api::controllers::auth::Claims;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::impersonation;
+use crate::bootstrap::middleware::controllers::rbac::role_of;
+use crate::database::AppState;
+use crate::events;
+use crate::events::types::payloads::ImpersonationPayload;
+
+/// Reason length bounds
+const MIN_REASON_LENGTH: usize = 3;
+const MAX_REASON_LENGTH: usize = 500;
+
+/// Default and largest page of the history
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+/// Impersonation Controller
+pub struct ImpersonationController;
+
+/// Started session response
+#[derive(Debug, Serialize)]
+pub struct ImpersonationStartedResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    /// Send as `Authorization: Bearer` (or to the WebSocket gateway) to act as the user
+    pub token: String,
+    pub session: ImpersonationSession,
+}
+
+/// Single session response
+#[derive(Debug, Serialize)]
+pub struct ImpersonationSessionResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub session: ImpersonationSession,
+}
+
+/// Session history response
+#[derive(Debug, Serialize)]
+pub struct ImpersonationSessionListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub sessions: Vec<ImpersonationSession>,
+}
+
+/// Start a session
+#[derive(Debug, Deserialize)]
+pub struct StartImpersonationRequest {
+    pub reason: String,
+    /// Session length; defaults to 15 and is capped by `IMPERSONATION_MAX_MINUTES`
+    pub minutes: Option<i64>,
+}
+
+/// History query
+#[derive(Debug, Deserialize)]
+pub struct SessionListQuery {
+    pub user_id: Option<i64>,
+    pub admin_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+fn payload_of(session: &ImpersonationSession, admin_id: i64) -> ImpersonationPayload {
+    ImpersonationPayload {
+        session_id: session.id,
+        admin_id: session.admin_id.unwrap_or(admin_id),
+        user_id: session.user_id,
+        reason: session.reason.clone(),
+        expires_at: session.expires_at,
+    }
+}
+
+impl ImpersonationController {
+    /// POST /api/v1/admin/impersonate/{user_id} - Start impersonating a user
+    ///
+    /// # Responses
+    /// - 201: Session started, token returned
+    /// - 400: Missing reason, or the admin tried to impersonate themselves
+    /// - 403: The user is an admin
+    /// - 404: User not found
+    pub async fn start(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+        body: web::Json<StartImpersonationRequest>,
+    ) -> HttpResponse {
+        let Some(admin_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let user_id = path.into_inner();
+        let body = body.into_inner();
+
+        let reason = body.reason.trim();
+        if !(MIN_REASON_LENGTH..=MAX_REASON_LENGTH).contains(&reason.chars().count()) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Reason must be between 3 and 500 characters"));
+        }
+        if user_id == admin_id {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("You cannot impersonate yourself"));
+        }
+
+        let db = state.db.lock().await;
+
+        let user = match db_user::get_by_id(&db, user_id).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("User not found"));
+            }
+            Err(e) => {
+                error!("Failed to load user {} to impersonate: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to start impersonation"));
+            }
+        };
+
+        // Acting as another admin would hand out their permissions
+        let role = role_of(user.permissions);
+        if role == Role::Admin {
+            return HttpResponse::Forbidden()
+                .json(BaseResponse::error("Admins cannot be impersonated"));
+        }
+
+        let minutes = impersonation::session_minutes(body.minutes);
+        let expires_at = Utc::now() + Duration::minutes(minutes);
+        let locale = db_user_preferences::get_locale(&db, user.id).await.ok().flatten();
+
+        let session = match db_mutations::create(&db, admin_id, user.id, reason, expires_at).await {
+            Ok(session) => session,
+            Err(e) => {
+                error!("Failed to create impersonation session: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to start impersonation"));
+            }
+        };
+        drop(db);
+
+        let claims = Claims {
+            sub: user.id,
+            role: role.as_str().to_string(),
+            permissions: user.permissions,
+            exp: session.expires_at.timestamp(),
+            locale,
+            impersonator_id: Some(admin_id),
+            impersonation_id: Some(session.id),
+            sid: None,
+        };
+
+        let token = match jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(state.jwt_secret.as_bytes()),
+        ) {
+            Ok(token) => token,
+            Err(e) => {
+                error!("Failed to sign impersonation token: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to start impersonation"));
+            }
+        };
+
+        if let Some(event_bus) = state.event_bus() {
+            if let Err(e) =
+                events::publish::impersonation_started(event_bus, payload_of(&session, admin_id))
+                    .await
+            {
+                warn!("Failed to publish auth.impersonation_started event: {}", e);
+            }
+        }
+
+        info!(
+            session_id = %session.id,
+            admin_id = %admin_id,
+            user_id = %user.id,
+            minutes,
+            "Impersonation started"
+        );
+
+        HttpResponse::Created().json(ImpersonationStartedResponse {
+            base: BaseResponse::success("Impersonation started"),
+            token,
+            session,
+        })
+    }
+
+    /// GET /api/v1/admin/impersonate/sessions - Sessions, newest first
+    ///
+    /// # Query
+    /// - user_id: sessions impersonating one user
+    /// - admin_id: sessions started by one admin
+    /// - limit: number of sessions (default 50, max 200)
+    pub async fn list(
+        state: web::Data<AppState>,
+        query: web::Query<SessionListQuery>,
+    ) -> HttpResponse {
+        let query = query.into_inner();
+        let filter = SessionFilter {
+            user_id: query.user_id,
+            admin_id: query.admin_id,
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let db = state.read_db().await;
+        let sessions = db_read::get_recent(&db, &filter, limit).await;
+        drop(db);
+
+        match sessions {
+            Ok(sessions) => HttpResponse::Ok().json(ImpersonationSessionListResponse {
+                base: BaseResponse::success("Impersonation sessions retrieved"),
+                sessions,
+            }),
+            Err(e) => {
+                error!("Failed to list impersonation sessions: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve impersonation sessions"))
+            }
+        }
+    }
+
+    /// DELETE /api/v1/admin/impersonate/sessions/{id} - End a session now
+    ///
+    /// The session's token is refused from the next request on.
+    pub async fn end(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<Uuid>,
+    ) -> HttpResponse {
+        let Some(admin_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let session_id = path.into_inner();
+
+        let db = state.db.lock().await;
+        let ended = db_mutations::end(&db, session_id, admin_id).await;
+        drop(db);
+
+        let session = match ended {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                return HttpResponse::NotFound()
+                    .json(BaseResponse::error("No active impersonation session with this id"));
+            }
+            Err(e) => {
+                error!("Failed to end impersonation session {}: {}", session_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to end impersonation"));
+            }
+        };
+
+        if let Some(event_bus) = state.event_bus() {
+            if let Err(e) = events::publish::impersonation_ended(
+                event_bus,
+                admin_id,
+                payload_of(&session, admin_id),
+            )
+            .await
+            {
+                warn!("Failed to publish auth.impersonation_ended event: {}", e);
+            }
+        }
+
+        info!(session_id = %session.id, admin_id = %admin_id, "Impersonation ended");
+
+        HttpResponse::Ok().json(ImpersonationSessionResponse {
+            base: BaseResponse::success("Impersonation ended"),
+            session,
+        })
+    }
+}
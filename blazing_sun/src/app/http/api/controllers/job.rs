@@ -0,0 +1,67 @@
+//!
+//! Job Controller
+//!
+//! Status of background jobs for clients without a WebSocket connection:
+//! - GET /api/v1/jobs/{id}: Status and progress of a job the user submitted
+//!
+//! Only jobs enqueued for a user are tracked (see `mq::progress`); snapshots
+//! are kept for a day after the job's last update.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::Serialize;
+use tracing::error;
+
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::bootstrap::middleware::controllers::permission::is_admin;
+use crate::database::AppState;
+use crate::mq::JobSnapshot;
+
+/// Job Controller
+pub struct JobController;
+
+/// Job status response
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub job: JobSnapshot,
+}
+
+impl JobController {
+    /// GET /api/v1/jobs/{id} - Status and progress of a job
+    ///
+    /// Admins may look up any tracked job; other users only their own (a job
+    /// of someone else is reported as not found).
+    pub async fn show(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<String>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let permissions = req.extensions().get::<i16>().copied().unwrap_or_default();
+
+        let Some(redis) = state.redis() else {
+            return HttpResponse::ServiceUnavailable()
+                .json(BaseResponse::error("Redis is not available"));
+        };
+        let job_id = path.into_inner();
+
+        match JobSnapshot::load(&redis, &job_id).await {
+            Ok(Some(job)) if job.owner_id == user_id || is_admin(permissions) => {
+                HttpResponse::Ok().json(JobResponse {
+                    base: BaseResponse::success("Job retrieved"),
+                    job,
+                })
+            }
+            Ok(_) => HttpResponse::NotFound().json(BaseResponse::error("Job not found")),
+            Err(e) => {
+                error!("Failed to load job {}: {}", job_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve job"))
+            }
+        }
+    }
+}
@@ -129,7 +129,7 @@ pub struct UpdateLocalizationKeyRequest {
 
 impl LocalizationController {
     pub async fn list_languages(state: web::Data<AppState>) -> HttpResponse {
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         let languages = match localization_read::get_languages(&db).await {
             Ok(languages) => languages,
@@ -296,7 +296,7 @@ impl LocalizationController {
     }
 
     pub async fn list_locales(state: web::Data<AppState>) -> HttpResponse {
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         let locales = match localization_read::get_locales(&db).await {
             Ok(locales) => locales,
@@ -490,7 +490,7 @@ impl LocalizationController {
     }
 
     pub async fn list_keys(state: web::Data<AppState>) -> HttpResponse {
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         let keys = match localization_read::get_keys(&db).await {
             Ok(keys) => keys,
@@ -0,0 +1,133 @@
+//!
+//! Maintenance Controller
+//!
+//! Admin toggle of the read-only maintenance mode (see `app::maintenance`),
+//! shared through Redis with checkout and the WebSocket gateway:
+//! - GET /api/v1/admin/maintenance: Current state
+//! - PUT /api/v1/admin/maintenance: Turn it on (or change the notice)
+//! - DELETE /api/v1/admin/maintenance: Turn it off
+//!
+
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use maintenance_mode::Notice;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::maintenance;
+
+/// Longest notice shown to users
+const MAX_MESSAGE_LEN: usize = 500;
+
+/// Maintenance Controller
+pub struct MaintenanceController;
+
+/// Maintenance state
+#[derive(Debug, Serialize)]
+pub struct MaintenanceStatus {
+    pub active: bool,
+    /// Forced on by MAINTENANCE_MODE; the toggle cannot turn it off
+    pub forced: bool,
+    pub notice: Option<Notice>,
+}
+
+/// Maintenance state response
+#[derive(Debug, Serialize)]
+pub struct MaintenanceResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub maintenance: MaintenanceStatus,
+}
+
+/// Enable request
+#[derive(Debug, Deserialize)]
+pub struct EnableMaintenanceRequest {
+    /// Shown to users (default: a generic maintenance message)
+    pub message: Option<String>,
+    /// Expected end; also sets `Retry-After` of rejected writes
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+fn unavailable() -> HttpResponse {
+    HttpResponse::ServiceUnavailable().json(BaseResponse::error("Redis is not available"))
+}
+
+async fn status_response(message: &'static str) -> HttpResponse {
+    let forced = maintenance::get().is_some_and(|m| m.is_forced());
+    let notice = maintenance::current().await;
+
+    HttpResponse::Ok().json(MaintenanceResponse {
+        base: BaseResponse::success(message),
+        maintenance: MaintenanceStatus {
+            active: notice.is_some(),
+            forced,
+            notice,
+        },
+    })
+}
+
+impl MaintenanceController {
+    /// GET /api/v1/admin/maintenance - Current state
+    pub async fn status() -> HttpResponse {
+        status_response("Maintenance status retrieved").await
+    }
+
+    /// PUT /api/v1/admin/maintenance - Turn read-only mode on
+    ///
+    /// # Body (`{}` for the defaults)
+    /// - message: notice shown to users (max 500 characters)
+    /// - ends_at: expected end, in the future
+    pub async fn enable(body: web::Json<EnableMaintenanceRequest>) -> HttpResponse {
+        let body = body.into_inner();
+
+        if body
+            .message
+            .as_ref()
+            .is_some_and(|m| m.chars().count() > MAX_MESSAGE_LEN)
+        {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Message may be at most 500 characters"));
+        }
+        if body.ends_at.is_some_and(|ends_at| ends_at <= Utc::now()) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("ends_at must be in the future"));
+        }
+
+        let Some(toggle) = maintenance::get() else {
+            return unavailable();
+        };
+        let notice = Notice::new(body.message, body.ends_at);
+
+        match toggle.enable(&notice).await {
+            Ok(()) => {
+                info!(ends_at = ?notice.ends_at, "Maintenance mode enabled");
+                status_response("Maintenance mode enabled").await
+            }
+            Err(e) => {
+                error!("Failed to enable maintenance mode: {}", e);
+                unavailable()
+            }
+        }
+    }
+
+    /// DELETE /api/v1/admin/maintenance - Turn read-only mode off
+    pub async fn disable() -> HttpResponse {
+        let Some(toggle) = maintenance::get() else {
+            return unavailable();
+        };
+
+        match toggle.disable().await {
+            Ok(true) => {
+                info!("Maintenance mode disabled");
+                status_response("Maintenance mode disabled").await
+            }
+            Ok(false) => HttpResponse::NotFound()
+                .json(BaseResponse::error("Maintenance mode is not enabled")),
+            Err(e) => {
+                error!("Failed to disable maintenance mode: {}", e);
+                unavailable()
+            }
+        }
+    }
+}
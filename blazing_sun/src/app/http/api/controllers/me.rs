@@ -0,0 +1,1236 @@
+//!
+//! Me Controller
+//!
+//! Aggregated endpoints for the authenticated user:
+//! - GET /me/bootstrap: Everything the web app needs on cold start in one call
+//!   (profile, balance, settings, features, unread counts, active rooms)
+//! - GET /me/gaming-activity/export: Annual statement of stakes and winnings
+//!   (CSV or PDF) for self-reporting gaming winnings
+//! - DELETE /me: Request deletion of the account (GDPR erasure after a grace period)
+//! - GET /me/erasure: Status of the latest deletion request
+//! - DELETE /me/erasure: Cancel a deletion request during the grace period
+//! - POST /me/export: Ask for a copy of the user's data (zip of JSON and CSV,
+//!   built by the `export_user_data` MQ job)
+//! - GET /me/export: Status of the latest data export, with its download link
+//! - GET /data-exports/{id}/download: Signed, expiring download of an export
+//!   (no JWT; the link is sent to the user when the export is ready)
+//! - GET /me/locale: Preferred message locale and the supported ones
+//! - PUT /me/locale: Set (or clear) the preferred message locale; it is carried
+//!   in tokens issued from then on (next sign-in or refresh)
+//! - GET /me/notification-preferences: Delivery channel of every notification category
+//! - PUT /me/notification-preferences: Change the channel of some categories
+//! - GET /me/settings: Display settings (theme, language, sounds) and their schema
+//! - PUT /me/settings: Change some settings; they also reach the WebSocket
+//!   gateway, which sends them in `system.authenticated`
+//! - GET /me/blocks: Users the current user blocked
+//! - POST /me/blocks/{user_id}: Block a user from direct messages, typing,
+//!   presence and chat (also ends a friendship between the two)
+//! - DELETE /me/blocks/{user_id}: Unblock a user
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use base64::Engine;
+use chrono::{Datelike, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{error, warn};
+
+use crate::app::chat::blocks::ChatBlocks;
+use crate::app::data_exports;
+use crate::app::http::api::controllers::responses::{
+    BaseResponse, UserDto, ValidationErrorResponse,
+};
+use crate::app::http::api::validators::FieldError;
+use crate::app::mq::jobs::{ExportUserDataParams, GamingActivityExportParams};
+use crate::app::notifications::{NotificationCategory, NotificationChannel, NotificationPreferences};
+use crate::app::user_settings::{self, SettingKey, SettingSchema, SettingsMirror, UserSettings};
+use crate::bootstrap::includes::storage::{self, StorageError};
+use crate::config::{ErasureConfig, GamesConfig};
+use crate::database::mutations::friend as db_friend_mutations;
+use crate::database::mutations::notification_preferences as db_notification_preferences_mutations;
+use crate::database::mutations::user_data_exports as db_export_mutations;
+use crate::database::mutations::user_erasure as db_erasure_mutations;
+use crate::database::mutations::user_preferences as db_preferences_mutations;
+use crate::database::mutations::user_settings as db_settings_mutations;
+use crate::database::read::friend as db_friend;
+use crate::database::read::game_chat_config as db_game_chat_config;
+use crate::database::read::game_room as db_game_room;
+use crate::database::read::notification_preferences as db_notification_preferences;
+use crate::database::read::user as db_user;
+use crate::database::read::user_blocks::{self as db_user_blocks, BlockedUser};
+use crate::database::read::user_data_exports::{self as db_exports, UserDataExport};
+use crate::database::read::user_erasure::{self as db_erasure, UserErasureRequest};
+use crate::database::read::user_preferences as db_preferences;
+use crate::database::read::user_settings as db_settings;
+use crate::database::AppState;
+use crate::mq::{self, JobOptions, JobResult};
+
+/// Cache lifetime (seconds) for each bootstrap section.
+///
+/// Clients should refetch a section only once its `max_age` has elapsed,
+/// or when a live event invalidates it (e.g. `user.balance_updated`).
+mod max_age {
+    pub const PROFILE: u32 = 300;
+    pub const BALANCE: u32 = 0;
+    pub const SETTINGS: u32 = 600;
+    pub const FEATURES: u32 = 3600;
+    pub const UNREAD: u32 = 30;
+    pub const ACTIVE_ROOMS: u32 = 0;
+}
+
+/// Per-section cache control hint
+#[derive(Serialize)]
+pub struct SectionCache {
+    pub max_age: u32,
+    pub scope: &'static str,
+}
+
+impl SectionCache {
+    fn private(max_age: u32) -> Self {
+        Self {
+            max_age,
+            scope: "private",
+        }
+    }
+
+    fn public(max_age: u32) -> Self {
+        Self {
+            max_age,
+            scope: "public",
+        }
+    }
+}
+
+/// A bootstrap section with its cache hint
+#[derive(Serialize)]
+pub struct Section<T: Serialize> {
+    pub data: T,
+    pub cache: SectionCache,
+}
+
+#[derive(Serialize)]
+pub struct BalanceSection {
+    pub balance: i64,
+}
+
+#[derive(Serialize)]
+pub struct SettingsSection {
+    pub chat_max_message_length: i32,
+    pub chat_rate_limit_messages: i32,
+    pub chat_rate_limit_window_seconds: i32,
+    pub chat_global_mute_enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct FeaturesSection {
+    pub bigger_dice_entry_fee_cents: i64,
+    pub bigger_dice_ready_timeout_seconds: i32,
+    pub bigger_dice_winning_percentage: i32,
+}
+
+#[derive(Serialize)]
+pub struct UnreadSection {
+    pub pending_friend_requests: i64,
+}
+
+#[derive(Serialize)]
+pub struct ActiveRoomDto {
+    pub room_id: String,
+    pub room_name: String,
+    pub game_type: String,
+    pub status: String,
+    pub host_id: i64,
+    pub player_count: i32,
+    pub is_password_protected: bool,
+}
+
+/// Response for GET /me/bootstrap
+#[derive(Serialize)]
+pub struct BootstrapResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub profile: Section<UserDto>,
+    pub balance: Section<BalanceSection>,
+    pub settings: Section<Option<SettingsSection>>,
+    pub features: Section<FeaturesSection>,
+    pub unread: Section<UnreadSection>,
+    pub active_rooms: Section<Vec<ActiveRoomDto>>,
+}
+
+/// Query parameters for GET /me/gaming-activity/export
+#[derive(Debug, Deserialize)]
+pub struct GamingActivityExportQuery {
+    pub year: i32,
+    /// "csv" (default) or "pdf"
+    pub format: Option<String>,
+    /// Locale for number/date formatting; defaults to the Accept-Language header
+    pub locale: Option<String>,
+}
+
+/// Earliest year a statement can be requested for
+const FIRST_EXPORT_YEAR: i32 = 2020;
+
+/// First language tag of the Accept-Language header ("sr-RS,sr;q=0.9" -> "sr-RS")
+fn preferred_locale(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("Accept-Language")?
+        .to_str()
+        .ok()?
+        .split(',')
+        .next()
+        .map(|tag| tag.split(';').next().unwrap_or("").trim().to_string())
+        .filter(|tag| !tag.is_empty() && tag != "*")
+}
+
+/// Account deletion request DTO
+#[derive(Debug, Serialize)]
+pub struct ErasureRequestDto {
+    pub id: i64,
+    /// pending, queued, completed, failed or cancelled
+    pub status: String,
+    pub requested_at: String,
+    /// End of the grace period; the account is erased after this
+    pub scheduled_for: String,
+    pub cancelled_at: Option<String>,
+    /// Only pending requests can be cancelled
+    pub cancellable: bool,
+}
+
+impl From<UserErasureRequest> for ErasureRequestDto {
+    fn from(request: UserErasureRequest) -> Self {
+        Self {
+            id: request.id,
+            cancellable: request.status == "pending",
+            status: request.status,
+            requested_at: request.requested_at.to_rfc3339(),
+            scheduled_for: request.scheduled_for.to_rfc3339(),
+            cancelled_at: request.cancelled_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+/// Account deletion response
+#[derive(Debug, Serialize)]
+pub struct ErasureResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub erasure: Option<ErasureRequestDto>,
+}
+
+/// Data export DTO
+#[derive(Debug, Serialize)]
+pub struct DataExportDto {
+    pub id: i64,
+    /// pending, processing, ready, failed or expired
+    pub status: String,
+    pub requested_at: String,
+    pub completed_at: Option<String>,
+    /// End of the download window of a ready export
+    pub expires_at: Option<String>,
+    pub size_bytes: Option<i64>,
+    /// Signed link, only while the export can be downloaded
+    pub download_url: Option<String>,
+}
+
+impl From<UserDataExport> for DataExportDto {
+    fn from(export: UserDataExport) -> Self {
+        let download_url = match export.expires_at {
+            Some(expires_at) if export.is_downloadable(Utc::now()) => {
+                Some(data_exports::download_url(export.id, expires_at))
+            }
+            _ => None,
+        };
+
+        Self {
+            id: export.id,
+            status: export.status,
+            requested_at: export.requested_at.to_rfc3339(),
+            completed_at: export.completed_at.map(|at| at.to_rfc3339()),
+            expires_at: export.expires_at.map(|at| at.to_rfc3339()),
+            size_bytes: export.size_bytes,
+            download_url,
+        }
+    }
+}
+
+/// Data export response
+#[derive(Debug, Serialize)]
+pub struct DataExportResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub export: Option<DataExportDto>,
+    /// Job building the export (see GET /jobs/{id}), when one was queued
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+}
+
+/// Query parameters of a signed data export download link
+#[derive(Debug, Deserialize)]
+pub struct DataExportDownloadQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Request body for PUT /me/locale
+#[derive(Debug, Deserialize)]
+pub struct UpdateLocaleRequest {
+    /// Locale code (`en`, `sr`, ...); null follows Accept-Language again
+    pub locale: Option<String>,
+}
+
+/// Message locale response
+#[derive(Debug, Serialize)]
+pub struct LocaleResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub locale: Option<String>,
+    pub supported: Vec<&'static str>,
+}
+
+impl LocaleResponse {
+    fn new(message: &'static str, locale: Option<String>) -> Self {
+        Self {
+            base: BaseResponse::success(message),
+            locale,
+            supported: i18n::Locale::ALL.iter().map(|l| l.code()).collect(),
+        }
+    }
+}
+
+/// Request body for PUT /me/settings
+#[derive(Debug, Deserialize)]
+pub struct UpdateSettingsRequest {
+    /// Key -> value; keys left out keep their value
+    pub settings: HashMap<String, serde_json::Value>,
+}
+
+/// User settings response
+#[derive(Debug, Serialize)]
+pub struct SettingsResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub settings: UserSettings,
+    pub schema: Vec<SettingSchema>,
+}
+
+impl SettingsResponse {
+    fn new(message: &'static str, settings: UserSettings) -> Self {
+        Self {
+            base: BaseResponse::success(message),
+            settings,
+            schema: user_settings::schema(),
+        }
+    }
+}
+
+/// Request body for PUT /me/notification-preferences
+#[derive(Debug, Deserialize)]
+pub struct UpdateNotificationPreferencesRequest {
+    /// Category -> channel; categories left out keep their channel
+    pub preferences: HashMap<String, String>,
+}
+
+/// Notification preferences response
+#[derive(Debug, Serialize)]
+pub struct NotificationPreferencesResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub preferences: NotificationPreferences,
+    pub channels: Vec<&'static str>,
+}
+
+impl NotificationPreferencesResponse {
+    fn new(message: &'static str, preferences: NotificationPreferences) -> Self {
+        Self {
+            base: BaseResponse::success(message),
+            preferences,
+            channels: vec![
+                NotificationChannel::Websocket.as_str(),
+                NotificationChannel::Email.as_str(),
+                NotificationChannel::Muted.as_str(),
+            ],
+        }
+    }
+}
+
+/// A blocked user
+#[derive(Debug, Serialize)]
+pub struct BlockedUserDto {
+    pub user_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_id: Option<i64>,
+    pub blocked_at: String,
+}
+
+impl From<BlockedUser> for BlockedUserDto {
+    fn from(user: BlockedUser) -> Self {
+        Self {
+            user_id: user.user_id,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            avatar_id: user.avatar_id,
+            blocked_at: user.blocked_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Block list response
+#[derive(Debug, Serialize)]
+pub struct BlocksResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub blocks: Vec<BlockedUserDto>,
+}
+
+/// Me Controller
+pub struct MeController;
+
+impl MeController {
+    /// GET /me/bootstrap - Aggregated startup payload for the current user
+    ///
+    /// Non-critical sections (settings, active rooms) degrade to empty values
+    /// instead of failing the whole request.
+    ///
+    /// # Responses
+    /// - 200: Bootstrap payload
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    /// - 404: User not found
+    /// - 500: Database error
+    pub async fn bootstrap(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+
+        let user = match db_user::get_by_id(&db, user_id).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("User not found"));
+            }
+            Err(e) => {
+                error!("Bootstrap: failed to load user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load bootstrap data"));
+            }
+        };
+
+        let settings = match db_game_chat_config::get_config(&db).await {
+            Ok(config) => Some(SettingsSection {
+                chat_max_message_length: config.max_message_length,
+                chat_rate_limit_messages: config.rate_limit_messages,
+                chat_rate_limit_window_seconds: config.rate_limit_window_seconds,
+                chat_global_mute_enabled: config.global_mute_enabled,
+            }),
+            Err(e) => {
+                warn!("Bootstrap: failed to load chat settings: {}", e);
+                None
+            }
+        };
+
+        let pending_friend_requests = db_friend::count_pending_requests(&db, user_id).await;
+
+        let active_rooms = match db_game_room::get_active_for_user(&db, user_id).await {
+            Ok(rooms) => rooms
+                .into_iter()
+                .map(|room| ActiveRoomDto {
+                    room_id: room.room_id,
+                    room_name: room.room_name,
+                    game_type: room.game_type,
+                    status: room.status,
+                    host_id: room.host_id,
+                    player_count: room.player_count,
+                    is_password_protected: room.is_password_protected,
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Bootstrap: failed to load active rooms for {}: {}", user_id, e);
+                Vec::new()
+            }
+        };
+
+        drop(db);
+
+        let balance = user.balance;
+        let response = BootstrapResponse {
+            base: BaseResponse::success("Bootstrap data retrieved successfully"),
+            profile: Section {
+                data: UserDto {
+                    id: user.id,
+                    email: user.email,
+                    first_name: user.first_name,
+                    last_name: user.last_name,
+                    balance: user.balance,
+                    permissions: user.permissions,
+                    avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
+                    created_at: user.created_at,
+                    updated_at: user.updated_at,
+                },
+                cache: SectionCache::private(max_age::PROFILE),
+            },
+            balance: Section {
+                data: BalanceSection { balance },
+                cache: SectionCache::private(max_age::BALANCE),
+            },
+            settings: Section {
+                data: settings,
+                cache: SectionCache::public(max_age::SETTINGS),
+            },
+            features: Section {
+                data: FeaturesSection {
+                    bigger_dice_entry_fee_cents: GamesConfig::bigger_dice_entry_fee_cents(),
+                    bigger_dice_ready_timeout_seconds:
+                        GamesConfig::bigger_dice_ready_timeout_seconds(),
+                    bigger_dice_winning_percentage: GamesConfig::bigger_dice_winning_percentage(),
+                },
+                cache: SectionCache::public(max_age::FEATURES),
+            },
+            unread: Section {
+                data: UnreadSection {
+                    pending_friend_requests,
+                },
+                cache: SectionCache::private(max_age::UNREAD),
+            },
+            active_rooms: Section {
+                data: active_rooms,
+                cache: SectionCache::private(max_age::ACTIVE_ROOMS),
+            },
+        };
+
+        // The aggregate contains live sections (balance, rooms), so the
+        // response as a whole must never be reused; clients cache per section.
+        HttpResponse::Ok()
+            .insert_header(("Cache-Control", "private, no-store"))
+            .json(response)
+    }
+
+    /// GET /me/gaming-activity/export - Annual gaming activity statement
+    ///
+    /// Stakes, winnings and net results per game for one calendar year, built
+    /// by the `gaming_activity_export` MQ job and returned as a file download.
+    ///
+    /// # Responses
+    /// - 200: CSV or PDF attachment
+    /// - 400: Invalid year or format
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    /// - 500/503: Export could not be generated (e.g. checkout service unavailable)
+    pub async fn gaming_activity_export(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        query: web::Query<GamingActivityExportQuery>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        if !(FIRST_EXPORT_YEAR..=Utc::now().year()).contains(&query.year) {
+            return HttpResponse::BadRequest().json(BaseResponse::error("Invalid year"));
+        }
+
+        let format = query.format.as_deref().unwrap_or("csv").to_ascii_lowercase();
+        if format != "csv" && format != "pdf" {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Invalid format. Must be csv or pdf"));
+        }
+
+        let Some(ref queue) = state.mq else {
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Message queue not available"));
+        };
+
+        let params = GamingActivityExportParams {
+            user_id,
+            year: query.year,
+            format,
+            locale: query
+                .locale
+                .clone()
+                .or_else(|| preferred_locale(&req))
+                .unwrap_or_else(|| "en_US".to_string()),
+        };
+        let options = JobOptions::new().priority(0).fault_tolerance(1).owner(user_id);
+
+        let payload = match mq::enqueue_and_wait_result_dyn(
+            queue,
+            "gaming_activity_export",
+            &params,
+            options,
+            60000,
+        )
+        .await
+        {
+            Ok(JobResult::Success(payload)) => payload,
+            Ok(JobResult::Retry(reason)) | Ok(JobResult::Failed(reason)) => {
+                error!("Gaming activity export for user {} failed: {}", user_id, reason);
+                return HttpResponse::ServiceUnavailable()
+                    .json(BaseResponse::error("Export could not be generated, try again later"));
+            }
+            Err(e) => {
+                error!("Gaming activity export job error: {}", e);
+                return HttpResponse::build(e.status_code())
+                    .json(BaseResponse::error("Export could not be generated"));
+            }
+        };
+
+        let field = |key: &str| payload.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        let content = match base64::engine::general_purpose::STANDARD.decode(field("content_base64")) {
+            Ok(content) => content,
+            Err(e) => {
+                error!("Gaming activity export returned invalid content: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Export could not be generated"));
+            }
+        };
+
+        HttpResponse::Ok()
+            .content_type(field("content_type"))
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", field("filename")),
+            ))
+            .insert_header(("Cache-Control", "private, no-store"))
+            .body(content)
+    }
+
+    /// DELETE /me - Request deletion of the current user's account
+    ///
+    /// The account is soft-deleted: it stays usable (so the request can be
+    /// cancelled) until the grace period ends, after which the `user_erasure`
+    /// cron queues the `erase_user` job that anonymizes the user's data.
+    ///
+    /// # Responses
+    /// - 202: Deletion scheduled
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    /// - 409: A deletion request is already open
+    pub async fn request_erasure(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.db.lock().await;
+        let scheduled_for = Utc::now() + Duration::days(ErasureConfig::grace_period_days());
+
+        match db_erasure_mutations::create(&db, user_id, scheduled_for).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return HttpResponse::Conflict()
+                    .json(BaseResponse::error("Account deletion already requested"));
+            }
+            Err(e) => {
+                error!("Failed to create erasure request for user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to request account deletion"));
+            }
+        }
+
+        match db_erasure::get_latest_by_user(&db, user_id).await {
+            Ok(request) => HttpResponse::Accepted().json(ErasureResponse {
+                base: BaseResponse::success("Account deletion scheduled"),
+                erasure: request.map(ErasureRequestDto::from),
+            }),
+            Err(e) => {
+                error!("Failed to load erasure request for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to request account deletion"))
+            }
+        }
+    }
+
+    /// GET /me/erasure - Latest account deletion request of the current user
+    ///
+    /// # Responses
+    /// - 200: Request (or null when deletion was never requested)
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    pub async fn erasure_status(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+        match db_erasure::get_latest_by_user(&db, user_id).await {
+            Ok(request) => HttpResponse::Ok().json(ErasureResponse {
+                base: BaseResponse::success("Account deletion status retrieved"),
+                erasure: request.map(ErasureRequestDto::from),
+            }),
+            Err(e) => {
+                error!("Failed to load erasure request for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve account deletion status"))
+            }
+        }
+    }
+
+    /// DELETE /me/erasure - Cancel account deletion during the grace period
+    ///
+    /// # Responses
+    /// - 200: Deletion cancelled
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    /// - 409: No cancellable request (none open, or erasure already started)
+    pub async fn cancel_erasure(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.db.lock().await;
+        match db_erasure_mutations::cancel(&db, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::Conflict()
+                    .json(BaseResponse::error("No cancellable account deletion request"));
+            }
+            Err(e) => {
+                error!("Failed to cancel erasure request for user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to cancel account deletion"));
+            }
+        }
+
+        match db_erasure::get_latest_by_user(&db, user_id).await {
+            Ok(request) => HttpResponse::Ok().json(ErasureResponse {
+                base: BaseResponse::success("Account deletion cancelled"),
+                erasure: request.map(ErasureRequestDto::from),
+            }),
+            Err(e) => {
+                warn!("Failed to reload erasure request for user {}: {}", user_id, e);
+                HttpResponse::Ok().json(ErasureResponse {
+                    base: BaseResponse::success("Account deletion cancelled"),
+                    erasure: None,
+                })
+            }
+        }
+    }
+
+    /// POST /me/export - Ask for a copy of the current user's data
+    ///
+    /// Queues the `export_user_data` job, which zips the profile,
+    /// transactions, balance ledger, game history and chat messages as JSON
+    /// and CSV. Its progress is pushed over WebSocket like any owned job, and
+    /// the user is notified with a signed download link once it is ready.
+    ///
+    /// # Responses
+    /// - 202: Export queued
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    /// - 409: An export is already being built
+    /// - 500: Message queue not available
+    pub async fn request_export(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let Some(ref queue) = state.mq else {
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Message queue not available"));
+        };
+
+        let db = state.db.lock().await;
+        let export_id = match db_export_mutations::create(&db, user_id).await {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                return HttpResponse::Conflict()
+                    .json(BaseResponse::error("A data export is already in progress"));
+            }
+            Err(e) => {
+                error!("Failed to create data export for user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to request data export"));
+            }
+        };
+
+        let params = ExportUserDataParams { export_id, user_id };
+        let job_id = match mq::enqueue_job_dyn(
+            queue,
+            "export_user_data",
+            &params,
+            JobOptions::new().owner(user_id),
+        )
+        .await
+        {
+            Ok(job_id) => job_id,
+            Err(e) => {
+                error!("Failed to enqueue data export {}: {}", export_id, e);
+                if let Err(e) = db_export_mutations::delete(&db, export_id).await {
+                    warn!("Failed to remove unqueued data export {}: {}", export_id, e);
+                }
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to request data export"));
+            }
+        };
+
+        match db_exports::get_by_id(&db, export_id).await {
+            Ok(export) => HttpResponse::Accepted().json(DataExportResponse {
+                base: BaseResponse::success("Data export requested"),
+                export: export.map(DataExportDto::from),
+                job_id: Some(job_id),
+            }),
+            Err(e) => {
+                warn!("Failed to reload data export {}: {}", export_id, e);
+                HttpResponse::Accepted().json(DataExportResponse {
+                    base: BaseResponse::success("Data export requested"),
+                    export: None,
+                    job_id: Some(job_id),
+                })
+            }
+        }
+    }
+
+    /// GET /me/export - Latest data export of the current user
+    ///
+    /// # Responses
+    /// - 200: Export (or null when none was ever requested); `download_url`
+    ///   is set while it can be downloaded
+    /// - 401: Unauthorized (no JWT or invalid JWT)
+    pub async fn export_status(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+        match db_exports::get_latest_by_user(&db, user_id).await {
+            Ok(export) => HttpResponse::Ok().json(DataExportResponse {
+                base: BaseResponse::success("Data export status retrieved"),
+                export: export.map(DataExportDto::from),
+                job_id: None,
+            }),
+            Err(e) => {
+                error!("Failed to load data export for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve data export status"))
+            }
+        }
+    }
+
+    /// GET /data-exports/{id}/download - Download a data export archive
+    ///
+    /// Authorized by the link's signature instead of a JWT, so the emailed
+    /// link works on any device until the export expires.
+    ///
+    /// # Responses
+    /// - 200: Zip attachment
+    /// - 403: Invalid or expired link
+    /// - 404: Export not found
+    /// - 410: Export no longer available
+    pub async fn download_export(
+        state: web::Data<AppState>,
+        path: web::Path<i64>,
+        query: web::Query<DataExportDownloadQuery>,
+    ) -> HttpResponse {
+        let export_id = path.into_inner();
+        if !data_exports::verify_download(export_id, query.expires, &query.signature) {
+            return HttpResponse::Forbidden()
+                .json(BaseResponse::error("Invalid or expired download link"));
+        }
+
+        let export = {
+            let db = state.read_db().await;
+            match db_exports::get_by_id(&db, export_id).await {
+                Ok(Some(export)) => export,
+                Ok(None) => {
+                    return HttpResponse::NotFound()
+                        .json(BaseResponse::error("Data export not found"));
+                }
+                Err(e) => {
+                    error!("Failed to load data export {}: {}", export_id, e);
+                    return HttpResponse::InternalServerError()
+                        .json(BaseResponse::error("Failed to download data export"));
+                }
+            }
+        };
+
+        let path = match &export.storage_path {
+            Some(path) if export.is_downloadable(Utc::now()) => path,
+            _ => {
+                return HttpResponse::Gone()
+                    .json(BaseResponse::error("Data export is no longer available"));
+            }
+        };
+
+        let content = match storage::get_storage() {
+            Ok(storage) => storage.get(path).await,
+            Err(e) => Err(e),
+        };
+        match content {
+            Ok(content) => HttpResponse::Ok()
+                .content_type("application/zip")
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"data-export-{}.zip\"", export.id),
+                ))
+                .insert_header(("Cache-Control", "private, no-store"))
+                .body(content),
+            Err(StorageError::NotFound) => HttpResponse::Gone()
+                .json(BaseResponse::error("Data export is no longer available")),
+            Err(e) => {
+                error!("Failed to read data export {}: {}", export_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to download data export"))
+            }
+        }
+    }
+
+    /// GET /me/locale - Preferred message locale
+    pub async fn locale(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+        match db_preferences::get_locale(&db, user_id).await {
+            Ok(locale) => HttpResponse::Ok().json(LocaleResponse::new("Locale retrieved", locale)),
+            Err(e) => {
+                error!("Failed to load locale for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load locale"))
+            }
+        }
+    }
+
+    /// PUT /me/locale - Set the preferred message locale
+    pub async fn update_locale(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<UpdateLocaleRequest>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let locale = match body.locale.as_deref() {
+            None => None,
+            Some(tag) => match i18n::Locale::parse(tag) {
+                Some(locale) => Some(locale.code()),
+                None => {
+                    return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(
+                        vec![FieldError::new("locale", "invalid", "Unsupported locale")],
+                    ));
+                }
+            },
+        };
+
+        let db = state.db.lock().await;
+        if let Err(e) = db_preferences_mutations::set_locale(&db, user_id, locale).await {
+            error!("Failed to update locale for user {}: {}", user_id, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to update locale"));
+        }
+
+        // The locale is also the `language` setting the gateway hydrates clients with
+        match db_settings::get_for_user(&db, user_id).await {
+            Ok(settings) => SettingsMirror::new(state.redis()).store(user_id, &settings).await,
+            Err(e) => warn!("Failed to load settings of user {} for Redis: {}", user_id, e),
+        }
+
+        HttpResponse::Ok().json(LocaleResponse::new(
+            "Locale updated successfully",
+            locale.map(str::to_string),
+        ))
+    }
+
+    /// GET /me/notification-preferences - Channel of every notification category
+    pub async fn notification_preferences(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+        match db_notification_preferences::get_for_user(&db, user_id).await {
+            Ok(preferences) => HttpResponse::Ok().json(NotificationPreferencesResponse::new(
+                "Notification preferences retrieved",
+                preferences,
+            )),
+            Err(e) => {
+                error!("Failed to load notification preferences for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load notification preferences"))
+            }
+        }
+    }
+
+    /// PUT /me/notification-preferences - Change the channel of some categories
+    ///
+    /// # Responses
+    /// - 200: Preferences after the change
+    /// - 400: Unknown category or channel
+    /// - 401: Unauthorized
+    pub async fn update_notification_preferences(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<UpdateNotificationPreferencesRequest>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let mut channels = Vec::with_capacity(body.preferences.len());
+        let mut errors = Vec::new();
+        for (category, channel) in &body.preferences {
+            let field = format!("preferences.{}", category);
+            match (NotificationCategory::parse(category), NotificationChannel::parse(channel)) {
+                (Some(category), Some(channel)) => channels.push((category, channel)),
+                (None, _) => errors.push(FieldError::new(field, "invalid", "Unknown notification category")),
+                (_, None) => errors.push(FieldError::new(field, "invalid", "Channel must be websocket, email or none")),
+            }
+        }
+        if !errors.is_empty() {
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
+        }
+
+        let db = state.db.lock().await;
+        if let Err(e) = db_notification_preferences_mutations::set_channels(&db, user_id, &channels).await {
+            error!("Failed to update notification preferences for user {}: {}", user_id, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to update notification preferences"));
+        }
+
+        match db_notification_preferences::get_for_user(&db, user_id).await {
+            Ok(preferences) => HttpResponse::Ok().json(NotificationPreferencesResponse::new(
+                "Notification preferences updated successfully",
+                preferences,
+            )),
+            Err(e) => {
+                error!("Failed to load notification preferences for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load notification preferences"))
+            }
+        }
+    }
+
+    /// GET /me/settings - The user's display settings and the settings schema
+    pub async fn settings(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+        match db_settings::get_for_user(&db, user_id).await {
+            Ok(settings) => {
+                drop(db);
+                SettingsMirror::new(state.redis()).store(user_id, &settings).await;
+                HttpResponse::Ok().json(SettingsResponse::new("Settings retrieved", settings))
+            }
+            Err(e) => {
+                error!("Failed to load settings for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load settings"))
+            }
+        }
+    }
+
+    /// PUT /me/settings - Change some settings
+    ///
+    /// Every value is validated against the settings schema before any is
+    /// stored; `language` sets the preferred message locale.
+    ///
+    /// # Responses
+    /// - 200: Settings after the change
+    /// - 400: Unknown key or a value of the wrong type
+    /// - 401: Unauthorized
+    pub async fn update_settings(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<UpdateSettingsRequest>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let mut values = Vec::with_capacity(body.settings.len());
+        let mut errors = Vec::new();
+        for (key, value) in &body.settings {
+            let field = format!("settings.{}", key);
+            match SettingKey::parse(key) {
+                Some(key) => match key.validate(value) {
+                    Ok(value) => values.push((key, value)),
+                    Err(message) => errors.push(FieldError::new(field, "invalid", message)),
+                },
+                None => errors.push(FieldError::new(field, "invalid", "Unknown setting")),
+            }
+        }
+        if !errors.is_empty() {
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
+        }
+
+        let db = state.db.lock().await;
+        if let Err(e) = db_settings_mutations::set_values(&db, user_id, &values).await {
+            error!("Failed to update settings for user {}: {}", user_id, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to update settings"));
+        }
+
+        match db_settings::get_for_user(&db, user_id).await {
+            Ok(settings) => {
+                drop(db);
+                SettingsMirror::new(state.redis()).store(user_id, &settings).await;
+                HttpResponse::Ok().json(SettingsResponse::new("Settings updated successfully", settings))
+            }
+            Err(e) => {
+                error!("Failed to load settings for user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load settings"))
+            }
+        }
+    }
+
+    /// GET /me/blocks - Users the current user blocked, most recent first
+    pub async fn blocks(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+
+        let db = state.read_db_for(user_id).await;
+        let blocks = match db_user_blocks::list(&db, user_id).await {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                error!("Failed to load blocks of user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load blocked users"));
+            }
+        };
+        drop(db);
+
+        // Listing also repairs the gateway's copy of the block list
+        let blocked_ids: Vec<i64> = blocks.iter().map(|b| b.user_id).collect();
+        ChatBlocks::new(state.redis()).sync(user_id, &blocked_ids).await;
+
+        HttpResponse::Ok().json(BlocksResponse {
+            base: BaseResponse::success("Blocked users retrieved"),
+            blocks: blocks.into_iter().map(BlockedUserDto::from).collect(),
+        })
+    }
+
+    /// POST /me/blocks/{user_id} - Block a user
+    ///
+    /// Blocking is idempotent and ends any friendship or pending request
+    /// between the two users.
+    ///
+    /// # Responses
+    /// - 200: User blocked
+    /// - 400: Blocking yourself
+    /// - 401: Unauthorized
+    /// - 404: User not found
+    pub async fn block(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+        let blocked_id = path.into_inner();
+
+        if blocked_id == user_id {
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(vec![
+                FieldError::new("user_id", "invalid", "You cannot block yourself"),
+            ]));
+        }
+
+        let db = state.db.lock().await;
+        match db_user::get_by_id(&db, blocked_id).await {
+            Ok(_) => {}
+            Err(sqlx::Error::RowNotFound) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("User not found"));
+            }
+            Err(e) => {
+                error!("Failed to load user {}: {}", blocked_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to block user"));
+            }
+        }
+
+        if let Err(e) = db_friend_mutations::block_user(&db, user_id, blocked_id).await {
+            error!("Failed to block user {} for {}: {}", blocked_id, user_id, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to block user"));
+        }
+        drop(db);
+
+        ChatBlocks::new(state.redis()).block(user_id, blocked_id).await;
+
+        HttpResponse::Ok().json(BaseResponse::success("User blocked"))
+    }
+
+    /// DELETE /me/blocks/{user_id} - Unblock a user
+    ///
+    /// # Responses
+    /// - 200: User unblocked
+    /// - 401: Unauthorized
+    /// - 404: The user was not blocked
+    pub async fn unblock(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<i64>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => {
+                return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            }
+        };
+        let blocked_id = path.into_inner();
+
+        let db = state.db.lock().await;
+        let removed = match db_friend_mutations::unblock_user(&db, user_id, blocked_id).await {
+            Ok(removed) => removed,
+            Err(e) => {
+                error!("Failed to unblock user {} for {}: {}", blocked_id, user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to unblock user"));
+            }
+        };
+        drop(db);
+
+        // Clear the mirror either way, in case it missed an earlier unblock
+        ChatBlocks::new(state.redis()).unblock(user_id, blocked_id).await;
+
+        if !removed {
+            return HttpResponse::NotFound().json(BaseResponse::error("User is not blocked"));
+        }
+
+        HttpResponse::Ok().json(BaseResponse::success("User unblocked"))
+    }
+}
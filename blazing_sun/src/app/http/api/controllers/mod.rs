@@ -3,43 +3,86 @@
 //! Controllers handle incoming HTTP requests and return responses.
 //! Each controller is organized by feature/resource.
 
+pub mod achievement;
 pub mod activation;
 pub mod admin;
+pub mod analytics;
 pub mod auth;
 pub mod balance;
+pub mod balance_adjustment;
+pub mod balance_transfer;
+pub mod chat_channel;
 pub mod competitions;
+pub mod direct_message;
 pub mod email;
+pub mod feature_flag;
 pub mod gallery;
 pub mod gallery_like;
 pub mod game_chat_config;
 pub mod game_config;
 pub mod game_history;
+pub mod game_room_preset;
+pub mod game_room_schedule;
+pub mod game_room_search;
+pub mod game_stats;
+pub mod game_webhook;
+pub mod game_region;
 pub mod geo_place;
+pub mod impersonation;
+pub mod job;
 pub mod localization;
+pub mod maintenance;
+pub mod me;
 pub mod oauth;
 pub mod oauth_api_product;
 pub mod oauth_client;
 pub mod oauth_gallery;
 pub mod oauth_scope;
+pub mod payments;
 pub mod picture;
 pub mod responses;
+pub mod role;
 pub mod roulette;
 pub mod roulette_ajax;
 pub mod schema;
+pub mod session;
+pub mod tenant_theme;
 pub mod theme;
+pub mod tournament;
+pub mod two_factor;
 pub mod upload;
 pub mod user;
+pub mod ws_penalty;
 
 // Re-export controllers for convenience
+pub use achievement::AchievementController;
 pub use activation::ActivationController;
 pub use admin::AdminController;
+pub use analytics::AnalyticsController;
 pub use auth::AuthController;
 pub use balance::BalanceController;
+pub use balance_adjustment::BalanceAdjustmentController;
+pub use balance_transfer::BalanceTransferController;
+pub use chat_channel::ChatChannelController;
+pub use direct_message::DirectMessageController;
 pub use email::EmailController;
+pub use feature_flag::FeatureFlagController;
 pub use game_chat_config::GameChatConfigController;
+pub use game_region::GameRegionController;
+pub use game_webhook::GameWebhookController;
+pub use impersonation::ImpersonationController;
+pub use job::JobController;
 pub use localization::LocalizationController;
+pub use maintenance::MaintenanceController;
+pub use me::MeController;
+pub use payments::PaymentsController;
+pub use role::RoleController;
 pub use roulette::RouletteController;
 pub use schema::SchemaController;
+pub use session::SessionController;
+pub use tenant_theme::TenantThemeController;
 pub use theme::ThemeController;
+pub use tournament::TournamentController;
 pub use upload::UploadController;
 pub use user::UserController;
+pub use ws_penalty::WsPenaltyController;
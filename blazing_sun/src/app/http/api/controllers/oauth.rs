@@ -1421,7 +1421,7 @@ pub async fn get_authorized_apps(req: HttpRequest, state: web::Data<AppState>) -
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Get user's authorized apps
     let apps = match read::oauth_authorization::get_user_authorized_apps(&db, user_id).await {
@@ -102,7 +102,7 @@ pub async fn list_api_products(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Get OAuth client and verify ownership
     let client = match db_read::oauth_client::get_by_client_id(&db, &client_id).await {
@@ -203,7 +203,7 @@ pub async fn list_enabled_apis(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Get OAuth client and verify ownership
     let client = match db_read::oauth_client::get_by_client_id(&db, &client_id).await {
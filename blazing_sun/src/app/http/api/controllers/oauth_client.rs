@@ -125,7 +125,7 @@ pub async fn get_user_clients(req: HttpRequest, state: web::Data<AppState>) -> H
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Fetch OAuth clients with counts
     match db_read::oauth_client::get_by_user_with_counts(&db, user_id).await {
@@ -183,7 +183,7 @@ pub async fn get_client(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Fetch OAuth client by client_id and verify ownership
     let client = match db_read::oauth_client::get_by_client_id(&db, &client_id).await {
@@ -519,7 +519,7 @@ pub async fn get_redirect_uris(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Get client by client_id and verify ownership
     let client = match db_read::oauth_client::get_by_client_id(&db, &client_id).await {
@@ -738,7 +738,7 @@ pub async fn get_authorized_domains(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Get client by client_id and verify ownership
     let client = match db_read::oauth_client::get_by_client_id(&db, &client_id).await {
@@ -152,7 +152,7 @@ pub async fn list_galleries(
     match mq::enqueue_and_wait_result_dyn(mq, "oauth_list_galleries", &params, options, 30000).await
     {
         Ok(result) => job_result_to_response(result),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+        Err(e) => HttpResponse::build(e.status_code()).json(serde_json::json!({
             "error": "server_error",
             "error_description": format!("Failed to process job: {}", e)
         })),
@@ -190,7 +190,7 @@ pub async fn get_gallery(
     }
 
     let base_url = AppConfig::app_url();
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     // Fetch gallery details (NO OWNERSHIP CHECK for galleries.read)
     match db_read::gallery::get_by_id(&db, gallery_id).await {
@@ -284,7 +284,7 @@ pub async fn list_gallery_images(
         .await
     {
         Ok(result) => job_result_to_response(result),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+        Err(e) => HttpResponse::build(e.status_code()).json(serde_json::json!({
             "error": "server_error",
             "error_description": format!("Failed to process job: {}", e)
         })),
@@ -586,7 +586,7 @@ pub async fn delete_gallery(
     match mq::enqueue_and_wait_result_dyn(mq, "oauth_delete_gallery", &params, options, 30000).await
     {
         Ok(result) => job_result_to_response(result),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+        Err(e) => HttpResponse::build(e.status_code()).json(serde_json::json!({
             "error": "server_error",
             "error_description": format!("Failed to process job: {}", e)
         })),
@@ -642,7 +642,7 @@ pub async fn delete_picture(
     match mq::enqueue_and_wait_result_dyn(mq, "oauth_delete_picture", &params, options, 30000).await
     {
         Ok(result) => job_result_to_response(result),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+        Err(e) => HttpResponse::build(e.status_code()).json(serde_json::json!({
             "error": "server_error",
             "error_description": format!("Failed to process job: {}", e)
         })),
@@ -47,7 +47,7 @@ pub async fn list_scopes_by_api_product(
 ) -> HttpResponse {
     let api_product_id = path.into_inner();
 
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
 
     // Verify API product exists
     let _api_product = match db_read::oauth_scope::get_api_product_by_id(&db, api_product_id).await
@@ -115,7 +115,7 @@ pub async fn list_client_scopes(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Get OAuth client and verify ownership
     let client = match db_read::oauth_client::get_by_client_id(&db, &client_id).await {
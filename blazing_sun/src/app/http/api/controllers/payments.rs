@@ -0,0 +1,160 @@
+//!
+//! Payments Controller
+//!
+//! Unified payments history for the current user:
+//! - GET /api/v1/payments/history: Balance ledger entries and checkout transactions
+//!   merged into one timeline (newest first)
+//!
+//! Ledger entries live in this database; checkout transactions are fetched from
+//! the checkout service's internal API. If the checkout service is unreachable the
+//! ledger part is still returned and `checkout_available` is false.
+//!
+//! Pagination is keyset based: pass the returned `next_before` as `before` to get
+//! the next page.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::app::checkout::client::{self as checkout_client, CheckoutTransaction};
+use crate::app::db_query::read::balance_ledger::{self as db_ledger, BalanceLedgerEntry};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Payments Controller
+pub struct PaymentsController;
+
+/// Query parameters for the history endpoint
+#[derive(Debug, Deserialize)]
+pub struct PaymentHistoryQuery {
+    pub limit: Option<i64>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// One entry of the unified timeline
+#[derive(Debug, Serialize)]
+pub struct PaymentHistoryItem {
+    /// Stable id, prefixed with the origin ("ledger:42", "checkout:<request_id>")
+    pub id: String,
+    /// "ledger" (balance change) or "checkout" (payment / game transaction)
+    pub origin: &'static str,
+    /// Ledger source or checkout purpose
+    pub kind: String,
+    pub amount_cents: i64,
+    pub currency: Option<String>,
+    /// Checkout status; ledger entries are always applied
+    pub status: String,
+    pub balance_after: Option<i64>,
+    /// Correlates a ledger credit with the checkout request that caused it
+    pub reference_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<BalanceLedgerEntry> for PaymentHistoryItem {
+    fn from(entry: BalanceLedgerEntry) -> Self {
+        Self {
+            id: format!("ledger:{}", entry.id),
+            origin: "ledger",
+            kind: entry.source,
+            amount_cents: entry.amount_cents,
+            currency: None,
+            status: "applied".to_string(),
+            balance_after: Some(entry.balance_after),
+            reference_id: entry.reference_id,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+impl From<CheckoutTransaction> for PaymentHistoryItem {
+    fn from(tx: CheckoutTransaction) -> Self {
+        Self {
+            id: format!("checkout:{}", tx.request_id),
+            origin: "checkout",
+            kind: tx.purpose,
+            amount_cents: tx.amount_cents,
+            currency: Some(tx.currency),
+            status: tx.status,
+            balance_after: None,
+            reference_id: Some(tx.request_id),
+            created_at: tx.created_at,
+        }
+    }
+}
+
+/// Payments history response
+#[derive(Debug, Serialize)]
+pub struct PaymentHistoryResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub items: Vec<PaymentHistoryItem>,
+    pub has_more: bool,
+    pub next_before: Option<DateTime<Utc>>,
+    pub checkout_available: bool,
+}
+
+impl PaymentsController {
+    /// GET /api/v1/payments/history - Unified, paginated payments timeline
+    pub async fn history(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        query: web::Query<PaymentHistoryQuery>,
+    ) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+
+        let limit = query.limit.unwrap_or(20).clamp(1, 100);
+        let before = query.before;
+
+        // One extra row from each source tells whether another page exists
+        let db = state.read_db_for(user_id).await;
+        let (ledger, checkout) = tokio::join!(
+            db_ledger::get_by_user_before(&db, user_id, before, limit + 1),
+            checkout_client::fetch_user_transactions(user_id, before, limit + 1),
+        );
+
+        let ledger = match ledger {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to load balance ledger for user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load payments history"));
+            }
+        };
+
+        let (checkout, checkout_available) = match checkout {
+            Ok(transactions) => (transactions, true),
+            Err(e) => {
+                warn!("Checkout history unavailable for user {}: {}", user_id, e);
+                (Vec::new(), false)
+            }
+        };
+
+        let mut items: Vec<PaymentHistoryItem> = ledger
+            .into_iter()
+            .map(PaymentHistoryItem::from)
+            .chain(checkout.into_iter().map(PaymentHistoryItem::from))
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at));
+
+        let has_more = items.len() > limit as usize;
+        items.truncate(limit as usize);
+        let next_before = if has_more {
+            items.last().map(|item| item.created_at)
+        } else {
+            None
+        };
+
+        HttpResponse::Ok().json(PaymentHistoryResponse {
+            base: BaseResponse::success("Payments history retrieved"),
+            items,
+            has_more,
+            next_before,
+            checkout_available,
+        })
+    }
+}
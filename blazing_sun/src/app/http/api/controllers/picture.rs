@@ -97,7 +97,7 @@ pub async fn get_gallery_pictures(
         }
     };
 
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Check if gallery exists and user has access
     match db_read::gallery::get_by_id(&db, gallery_id).await {
@@ -637,7 +637,7 @@ pub async fn bulk_delete_pictures(
         })),
         Err(e) => {
             tracing::error!("Bulk delete pictures job error: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
+            HttpResponse::build(e.status_code()).json(serde_json::json!({
                 "error": "Failed to remove pictures"
             }))
         }
@@ -725,7 +725,7 @@ pub async fn get_gallery_pictures_oauth(
     }
 
     let user_id = claims.user_id;
-    let db = state.db.lock().await;
+    let db = state.read_db_for(user_id).await;
 
     // Check if user owns the gallery
     if !db_read::gallery::user_owns_gallery(&db, gallery_id, user_id).await {
@@ -5,6 +5,8 @@
 use serde::Serialize;
 use std::collections::HashMap;
 
+use crate::app::http::api::validators::FieldError;
+
 /// Base response structure for simple success/error messages
 #[derive(Serialize, Debug)]
 pub struct BaseResponse {
@@ -69,19 +71,50 @@ impl MissingFieldsResponse {
 }
 
 /// Response for validation errors (field-specific error messages)
+///
+/// `errors` keeps the messages per field for forms; `fields` carries the same
+/// failures with their rule `code` and `constraint`.
 #[derive(Serialize, Debug)]
 pub struct ValidationErrorResponse {
     pub status: &'static str,
     pub message: &'static str,
     pub errors: HashMap<String, Vec<String>>,
+    pub fields: Vec<FieldError>,
 }
 
 impl ValidationErrorResponse {
+    /// From plain messages; every failure gets the generic `invalid` code
     pub fn new(errors: HashMap<String, Vec<String>>) -> Self {
+        let mut names: Vec<&String> = errors.keys().collect();
+        names.sort();
+
+        let fields = names
+            .into_iter()
+            .flat_map(|field| FieldError::each(field, "invalid", errors[field].clone()))
+            .collect();
+
+        Self {
+            status: "error",
+            message: "Validation failed",
+            errors,
+            fields,
+        }
+    }
+
+    pub fn from_fields(fields: Vec<FieldError>) -> Self {
+        let mut errors: HashMap<String, Vec<String>> = HashMap::new();
+        for error in &fields {
+            errors
+                .entry(error.field.clone())
+                .or_default()
+                .push(error.message.clone());
+        }
+
         Self {
             status: "error",
             message: "Validation failed",
             errors,
+            fields,
         }
     }
 }
@@ -99,3 +132,42 @@ pub struct UserDto {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api_casing::{contract, Casing};
+
+    #[test]
+    fn every_dto_is_snake_case() {
+        let violations = contract::scan_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+        let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        assert!(violations.is_empty(), "{:#?}", violations);
+    }
+
+    #[test]
+    fn shared_responses_convert_between_casings() {
+        contract::assert_casing(&BaseResponse::success("Done"), Casing::Snake);
+        contract::assert_casing(
+            &ValidationErrorResponse::new(HashMap::from([(
+                "first_name".to_string(),
+                vec!["First name is required".to_string()],
+            )])),
+            Casing::Snake,
+        );
+        contract::assert_casing(
+            &UserDto {
+                id: 1,
+                email: "user@example.com".to_string(),
+                first_name: "Ada".to_string(),
+                last_name: "Lovelace".to_string(),
+                balance: 0,
+                permissions: 1,
+                avatar_uuid: None,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            },
+            Casing::Snake,
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//!
+//! Role Controller
+//!
+//! Role management on top of the shared `rbac` roles:
+//! - GET /api/v1/admin/roles: Roles with their permission level and permissions
+//! - PUT /api/v1/admin/users/{id}/role: Give a user a role
+//!
+//! A role is stored as the user's permission level, so assigning one replaces
+//! the level. Every change publishes a `user.role_changed` audit event.
+//!
+
+use actix_web::{web, HttpResponse};
+use rbac::{Permission, Role};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::app::cache::UserProfileCache;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::bootstrap::middleware::controllers::permission::levels;
+use crate::bootstrap::middleware::controllers::rbac::{perms, role_of, Guard};
+use crate::database::mutations::user as db_user_mutations;
+use crate::database::read::user as db_user_read;
+use crate::database::AppState;
+use crate::events;
+use crate::events::types::payloads::RoleChangedPayload;
+
+/// Role Controller
+pub struct RoleController;
+
+/// One role and what it grants
+#[derive(Debug, Serialize)]
+pub struct RoleDto {
+    pub role: Role,
+    pub permission_level: i32,
+    pub permissions: &'static [Permission],
+}
+
+/// Role list response
+#[derive(Debug, Serialize)]
+pub struct RoleListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub roles: Vec<RoleDto>,
+}
+
+/// Assign role request
+#[derive(Debug, Deserialize)]
+pub struct AssignRoleRequest {
+    pub role: String,
+}
+
+/// Assign role response
+#[derive(Debug, Serialize)]
+pub struct AssignRoleResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub user_id: i64,
+    pub role: Role,
+    pub permissions: i16,
+}
+
+impl RoleController {
+    /// GET /api/v1/admin/roles - Roles and their permissions
+    pub async fn list(_guard: Guard<perms::ManageRoles>) -> HttpResponse {
+        let roles = Role::ALL
+            .into_iter()
+            .map(|role| RoleDto {
+                role,
+                permission_level: role.level(),
+                permissions: role.permissions(),
+            })
+            .collect();
+
+        HttpResponse::Ok().json(RoleListResponse {
+            base: BaseResponse::success("Roles retrieved"),
+            roles,
+        })
+    }
+
+    /// PUT /api/v1/admin/users/{id}/role - Give a user a role
+    ///
+    /// Only super admins may grant the admin role or change an admin's role,
+    /// and a super admin's role cannot be changed here.
+    ///
+    /// # Request Body
+    /// ```json
+    /// { "role": "moderator" }
+    /// ```
+    pub async fn assign(
+        guard: Guard<perms::ManageRoles>,
+        state: web::Data<AppState>,
+        path: web::Path<i64>,
+        body: web::Json<AssignRoleRequest>,
+    ) -> HttpResponse {
+        let admin = guard.user;
+        let user_id = path.into_inner();
+
+        let Some(role) = Role::parse(&body.role) else {
+            return HttpResponse::BadRequest().json(BaseResponse::error(
+                "Invalid role. Must be user, moderator, or admin",
+            ));
+        };
+
+        if user_id == admin.user_id {
+            return HttpResponse::Forbidden()
+                .json(BaseResponse::error("You cannot change your own role"));
+        }
+
+        let db = state.db.lock().await;
+
+        let user = match db_user_read::get_by_id(&db, user_id).await {
+            Ok(user) => user,
+            Err(_) => return HttpResponse::NotFound().json(BaseResponse::error("User not found")),
+        };
+
+        if user.permissions == levels::SUPER_ADMIN {
+            return HttpResponse::Forbidden()
+                .json(BaseResponse::error("The role of a super admin cannot be changed"));
+        }
+
+        let previous_role = role_of(user.permissions);
+        let touches_admin = role == Role::Admin || previous_role == Role::Admin;
+        if touches_admin && admin.permissions != levels::SUPER_ADMIN {
+            return HttpResponse::Forbidden().json(BaseResponse::error("Insufficient permissions"));
+        }
+
+        if previous_role == role {
+            return HttpResponse::Ok().json(AssignRoleResponse {
+                base: BaseResponse::success("User already has this role"),
+                user_id,
+                role,
+                permissions: user.permissions,
+            });
+        }
+
+        let permissions = role.level() as i16;
+        if let Err(e) = db_user_mutations::update_permissions(&db, user_id, permissions).await {
+            error!("Failed to update role of user {}: {}", user_id, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to update role"));
+        }
+        drop(db);
+
+        UserProfileCache::new(state.redis()).invalidate(user_id).await;
+
+        info!(
+            user_id = %user_id,
+            changed_by = %admin.user_id,
+            previous_role = previous_role.as_str(),
+            role = role.as_str(),
+            "User role changed"
+        );
+
+        if let Some(event_bus) = state.event_bus() {
+            let payload = RoleChangedPayload {
+                previous_role: previous_role.as_str().to_string(),
+                role: role.as_str().to_string(),
+                previous_permissions: user.permissions,
+                permissions,
+                changed_by: admin.user_id,
+            };
+            if let Err(e) = events::publish::user_role_changed(event_bus, user_id, payload).await {
+                warn!("Failed to publish user.role_changed event: {}", e);
+            }
+        }
+
+        HttpResponse::Ok().json(AssignRoleResponse {
+            base: BaseResponse::success("User role updated"),
+            user_id,
+            role,
+            permissions,
+        })
+    }
+}
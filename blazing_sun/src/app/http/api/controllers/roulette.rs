@@ -9,15 +9,18 @@
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
-use crate::app::db_query::mutations::user as user_mutations;
+use crate::app::db_query::mutations::balance_ledger::{
+    self as ledger_mutations, ApplyOutcome, LedgerChange,
+};
 use crate::app::db_query::read::user as user_read;
 use crate::app::games::mongodb_roulette::{MongoRouletteClient, RouletteUserStats};
 use crate::app::games::roulette::{
     calculate_total_stake, execute_spin, validate_bet, RouletteBet,
 };
 use crate::app::http::api::controllers::responses::BaseResponse;
-use crate::bootstrap::database::AppState;
+use crate::bootstrap::database::{replica, AppState};
 
 /// Roulette Controller
 pub struct RouletteController;
@@ -243,47 +246,54 @@ impl RouletteController {
         let base_stake = calculate_total_stake(bets);
         let total_stake = base_stake * bet_multiplier;
 
-        // 5. Deduct balance atomically (check and deduct in one operation)
+        // 5. Execute spin
+        let result = execute_spin(bets);
+
+        // Apply bet_multiplier to winnings
+        let final_payout = result.payout * bet_multiplier;
+
+        // 6. Deduct the stake and add winnings atomically, with a ledger entry each
+        let spin_id = Uuid::new_v4().to_string();
+        let changes = [
+            LedgerChange {
+                amount_cents: -total_stake,
+                source: ledger_mutations::SOURCE_ROULETTE_STAKE,
+                reference_id: Some(spin_id.as_str()),
+                metadata: serde_json::json!({ "bets": bets.len() }),
+            },
+            LedgerChange {
+                amount_cents: final_payout,
+                source: ledger_mutations::SOURCE_ROULETTE_PAYOUT,
+                reference_id: Some(spin_id.as_str()),
+                metadata: serde_json::json!({ "result_number": &result.result_number }),
+            },
+        ];
         let db = state.db.lock().await;
-        match user_mutations::deduct_balance_if_sufficient(&db, user_id, total_stake).await {
-            Ok(_) => {}
-            Err(user_mutations::DeductBalanceError::InsufficientBalance { current, required }) => {
+        let new_balance = match ledger_mutations::apply(&db, user_id, &changes).await {
+            Ok(ApplyOutcome::Applied { balance_after }) => balance_after,
+            Ok(ApplyOutcome::InsufficientBalance { current_balance }) => {
                 warn!(
                     user_id = %user_id,
-                    current = %current,
-                    required = %required,
+                    current = %current_balance,
+                    required = %total_stake,
                     "Insufficient balance for roulette spin"
                 );
                 return HttpResponse::BadRequest()
                     .json(ApiResponse::<()>::error("Insufficient balance"));
             }
-            Err(user_mutations::DeductBalanceError::UserNotFound) => {
+            Ok(ApplyOutcome::UnknownUser) => {
                 return HttpResponse::NotFound().json(ApiResponse::<()>::error("User not found"));
             }
             Err(e) => {
-                error!("Failed to deduct balance for user {}: {}", user_id, e);
+                error!("Failed to apply roulette spin for user {}: {}", user_id, e);
                 return HttpResponse::InternalServerError()
                     .json(ApiResponse::<()>::error("Failed to process bet"));
             }
-        }
-
-        // 6. Execute spin
-        let result = execute_spin(bets);
-
-        // Apply bet_multiplier to winnings
-        let final_payout = result.payout * bet_multiplier;
-
-        // 7. Add winnings to balance if any
-        if final_payout > 0 {
-            if let Err(e) = user_mutations::add_balance(&db, user_id, final_payout).await {
-                error!(
-                    "Failed to add winnings to user {}: {}. Payout: {}",
-                    user_id, e, final_payout
-                );
-            }
-        }
+        };
+        drop(db);
+        replica::mark_written(user_id).await;
 
-        // 8. Save to history (MongoDB)
+        // 7. Save to history (MongoDB)
         if let Some(mongodb) = state.mongo() {
             let roulette_client = MongoRouletteClient::new(mongodb.clone());
             if let Err(e) = roulette_client
@@ -303,12 +313,6 @@ impl RouletteController {
             }
         }
 
-        // 9. Get updated balance
-        let new_balance = match user_read::get_by_id(&db, user_id).await {
-            Ok(u) => u.balance,
-            Err(_) => 0,
-        };
-
         info!(
             user_id = %user_id,
             result = %result.result_number,
@@ -469,7 +473,7 @@ impl RouletteController {
         };
 
         // 2. Get user balance
-        let db = state.db.lock().await;
+        let db = state.read_db_for(user_id).await;
         let user = match user_read::get_by_id(&db, user_id).await {
             Ok(u) => u,
             Err(e) => {
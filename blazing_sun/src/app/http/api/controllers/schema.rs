@@ -75,7 +75,7 @@ pub struct SchemaDefinitionResponse {
 impl SchemaController {
     /// GET /api/v1/schemas/categories
     pub async fn categories(state: web::Data<AppState>) -> HttpResponse {
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         let allowed: HashSet<&'static str> = [
             "Action",
             "BioChemEntity",
@@ -121,7 +121,7 @@ impl SchemaController {
     /// GET /api/v1/schemas/children/{type}
     pub async fn children(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
         let parent_name = path.into_inner();
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match schema_catalog_read::get_type_by_name(&db, &parent_name).await {
             Ok(Some(_)) => {}
@@ -166,7 +166,7 @@ impl SchemaController {
     /// For example, Gene (Thing → BioChemEntity → Gene) returns properties from all three types.
     pub async fn schema(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
         let type_name = path.into_inner();
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         let type_row = match schema_catalog_read::get_type_by_name(&db, &type_name).await {
             Ok(Some(row)) => row,
@@ -0,0 +1,147 @@
+//!
+//! Session Controller
+//!
+//! Signed-in devices of the current user (see `app::sessions`):
+//! - GET /api/v1/auth/sessions: Active sessions, most recently seen first
+//! - DELETE /api/v1/auth/sessions/{id}: Sign one device out
+//!
+
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::Serialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::app::db_query::read::auth_sessions::AuthSession;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::impersonation::Impersonation;
+use crate::app::sessions::{self, CurrentSession};
+use crate::database::mutations::auth_sessions as db_auth_session_mut;
+use crate::database::read::auth_sessions as db_auth_session;
+use crate::database::AppState;
+
+/// Session Controller
+pub struct SessionController;
+
+/// A signed-in device
+#[derive(Debug, Serialize)]
+pub struct SessionDto {
+    #[serde(flatten)]
+    pub session: AuthSession,
+    /// Whether this is the session the request was made with
+    pub current: bool,
+}
+
+/// Session list response
+#[derive(Debug, Serialize)]
+pub struct SessionListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub sessions: Vec<SessionDto>,
+}
+
+fn current_session(req: &HttpRequest) -> Option<Uuid> {
+    req.extensions()
+        .get::<CurrentSession>()
+        .map(|session| session.0)
+}
+
+impl SessionController {
+    /// GET /api/v1/auth/sessions - The user's active sessions
+    ///
+    /// # Responses
+    /// - 200: Sessions retrieved
+    /// - 401: Unauthorized
+    /// - 500: Internal server error
+    pub async fn list(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let current = current_session(&req);
+
+        let db = state.read_db_for(user_id).await;
+        match db_auth_session::get_active_by_user(&db, user_id).await {
+            Ok(active) => HttpResponse::Ok().json(SessionListResponse {
+                base: BaseResponse::success("Sessions retrieved"),
+                sessions: active
+                    .into_iter()
+                    .map(|session| SessionDto {
+                        current: Some(session.id) == current,
+                        session,
+                    })
+                    .collect(),
+            }),
+            Err(e) => {
+                error!("Failed to list sessions of user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve sessions"))
+            }
+        }
+    }
+
+    /// DELETE /api/v1/auth/sessions/{id} - Revoke a session: its refresh
+    /// token stops working, its access tokens are refused and its WebSocket
+    /// connections are closed. Revoking the current session also clears the
+    /// auth cookies.
+    ///
+    /// # Responses
+    /// - 200: Session revoked
+    /// - 401: Unauthorized
+    /// - 403: Impersonation tokens cannot revoke sessions
+    /// - 404: No active session with this id
+    /// - 500: Internal server error
+    pub async fn revoke(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<Uuid>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        if req.extensions().get::<Impersonation>().is_some() {
+            return HttpResponse::Forbidden().json(BaseResponse::error(
+                "Sessions cannot be revoked while impersonating",
+            ));
+        }
+        let session_id = path.into_inner();
+
+        let db = state.db.lock().await;
+        let session = match db_auth_session_mut::revoke(&db, user_id, session_id).await {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                return HttpResponse::NotFound().json(BaseResponse::error("Session not found"));
+            }
+            Err(e) => {
+                error!("Failed to revoke session {}: {}", session_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to revoke session"));
+            }
+        };
+        drop(db);
+
+        sessions::enforce_revocation(&state, std::slice::from_ref(&session)).await;
+        info!("User {} revoked session {}", user_id, session_id);
+
+        let mut response = HttpResponse::Ok();
+        if current_session(&req) == Some(session_id) {
+            response
+                .cookie(
+                    Cookie::build("auth_token", "")
+                        .path("/")
+                        .max_age(actix_web::cookie::time::Duration::ZERO)
+                        .http_only(true)
+                        .same_site(SameSite::Lax)
+                        .finish(),
+                )
+                .cookie(
+                    Cookie::build("refresh_token", "")
+                        .path("/api/v1/auth")
+                        .max_age(actix_web::cookie::time::Duration::ZERO)
+                        .http_only(true)
+                        .same_site(SameSite::Strict)
+                        .finish(),
+                );
+        }
+        response.json(BaseResponse::success("Session revoked"))
+    }
+}
@@ -0,0 +1,341 @@
+//!
+//! Tenant Theme Controller
+//!
+//! Per-tenant overrides of the whitelisted theme CSS custom properties:
+//! - GET /admin/theme/tenants: List tenants with overrides
+//! - GET /admin/theme/tenants/{tenant_key}: Overrides and effective variables
+//! - PUT /admin/theme/tenants/{tenant_key}: Replace overrides (builds the tenant bundle)
+//! - DELETE /admin/theme/tenants/{tenant_key}: Reset to the platform default theme
+//! - GET /theme/tenants/{tenant_key}: Stylesheets a page of the tenant should load (public)
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+use crate::app::db_query::mutations::tenant_theme as db_mutations;
+use crate::app::db_query::read::tenant_theme::{self as db_read, TenantTheme};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::bootstrap::includes::theme::tenant::{self, DEFAULT_CSS_URL};
+use crate::bootstrap::includes::theme::{TenantError, ThemeService, ThemeServiceError};
+use crate::config::AppConfig;
+use crate::database::AppState;
+
+/// Tenant Theme Controller
+pub struct TenantThemeController;
+
+/// Tenant theme summary (list item)
+#[derive(Debug, Serialize)]
+pub struct TenantThemeSummaryDto {
+    pub tenant_key: String,
+    pub version: i32,
+    pub stylesheet_url: String,
+    pub updated_at: String,
+}
+
+/// Tenant list response
+#[derive(Debug, Serialize)]
+pub struct TenantThemeListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub tenants: Vec<TenantThemeSummaryDto>,
+}
+
+/// Tenant theme DTO
+#[derive(Debug, Serialize)]
+pub struct TenantThemeDto {
+    pub tenant_key: String,
+    /// True when the tenant has no overrides and uses the platform default theme
+    pub uses_default: bool,
+    /// The tenant's own overrides
+    pub theme_light: Value,
+    pub theme_dark: Value,
+    /// Platform defaults with the overrides applied
+    pub effective_light: Value,
+    pub effective_dark: Value,
+    pub version: Option<i32>,
+    /// Stylesheets to load, in order
+    pub stylesheets: Vec<String>,
+    pub updated_at: Option<String>,
+}
+
+/// Tenant theme response
+#[derive(Debug, Serialize)]
+pub struct TenantThemeResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub theme: TenantThemeDto,
+}
+
+/// Public stylesheet response
+#[derive(Debug, Serialize)]
+pub struct TenantStylesheetsResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub tenant_key: String,
+    pub uses_default: bool,
+    pub stylesheets: Vec<String>,
+}
+
+/// Tenant theme update request (replaces all overrides)
+#[derive(Debug, Deserialize)]
+pub struct TenantThemeUpdateRequest {
+    pub theme_light: Option<Value>,
+    pub theme_dark: Option<Value>,
+}
+
+/// Validation failure response
+#[derive(Debug, Serialize)]
+pub struct TenantThemeErrorResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub error: String,
+}
+
+impl TenantThemeController {
+    /// Default bundle, followed by the tenant bundle when the tenant has overrides
+    fn stylesheets(theme: Option<&TenantTheme>) -> Vec<String> {
+        let mut stylesheets = vec![format!(
+            "{}?v={}",
+            DEFAULT_CSS_URL,
+            AppConfig::assets_version()
+        )];
+        if let Some(theme) = theme {
+            stylesheets.push(tenant::bundle_url(&theme.stylesheet));
+        }
+        stylesheets
+    }
+
+    fn to_dto(
+        tenant_key: String,
+        theme: Option<&TenantTheme>,
+    ) -> Result<TenantThemeDto, ThemeServiceError> {
+        let empty = json!({});
+        let (light, dark) = theme
+            .map(|t| (&t.theme_light, &t.theme_dark))
+            .unwrap_or((&empty, &empty));
+        let (effective_light, effective_dark) = ThemeService::get_tenant_variables(light, dark)?;
+
+        Ok(TenantThemeDto {
+            tenant_key,
+            uses_default: theme.is_none(),
+            theme_light: light.clone(),
+            theme_dark: dark.clone(),
+            effective_light,
+            effective_dark,
+            version: theme.map(|t| t.version),
+            stylesheets: Self::stylesheets(theme),
+            updated_at: theme.map(|t| t.updated_at.to_rfc3339()),
+        })
+    }
+
+    /// Map a theme service error to a response; invalid input is a 400
+    fn error_response(e: ThemeServiceError, message: &'static str) -> HttpResponse {
+        match e {
+            ThemeServiceError::Validation(_)
+            | ThemeServiceError::Tenant(TenantError::InvalidTenantKey(_))
+            | ThemeServiceError::Tenant(TenantError::InvalidValue(_)) => HttpResponse::BadRequest()
+                .json(TenantThemeErrorResponse {
+                    base: BaseResponse::error("Invalid tenant theme"),
+                    error: e.to_string(),
+                }),
+            e => {
+                error!("{}: {}", message, e);
+                HttpResponse::InternalServerError().json(BaseResponse::error(message))
+            }
+        }
+    }
+
+    fn invalid_tenant_key(tenant_key: &str) -> Option<HttpResponse> {
+        tenant::validate_tenant_key(tenant_key)
+            .err()
+            .map(|e| Self::error_response(ThemeServiceError::Tenant(e), "Invalid tenant key"))
+    }
+
+    /// GET /api/v1/admin/theme/tenants - List tenants with theme overrides
+    pub async fn list(state: web::Data<AppState>) -> HttpResponse {
+        let db = state.read_db().await;
+        match db_read::get_all(&db).await {
+            Ok(themes) => HttpResponse::Ok().json(TenantThemeListResponse {
+                base: BaseResponse::success("Tenant themes retrieved"),
+                tenants: themes
+                    .into_iter()
+                    .map(|theme| TenantThemeSummaryDto {
+                        stylesheet_url: tenant::bundle_url(&theme.stylesheet),
+                        tenant_key: theme.tenant_key,
+                        version: theme.version,
+                        updated_at: theme.updated_at.to_rfc3339(),
+                    })
+                    .collect(),
+            }),
+            Err(e) => {
+                error!("Failed to list tenant themes: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve tenant themes"))
+            }
+        }
+    }
+
+    /// GET /api/v1/admin/theme/tenants/{tenant_key} - Get a tenant's theme
+    pub async fn get(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+        let tenant_key = path.into_inner();
+        if let Some(response) = Self::invalid_tenant_key(&tenant_key) {
+            return response;
+        }
+
+        let db = state.read_db().await;
+        let theme = match db_read::get_by_key(&db, &tenant_key).await {
+            Ok(theme) => theme,
+            Err(e) => {
+                error!("Failed to get tenant theme {}: {}", tenant_key, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve tenant theme"));
+            }
+        };
+
+        match Self::to_dto(tenant_key, theme.as_ref()) {
+            Ok(dto) => HttpResponse::Ok().json(TenantThemeResponse {
+                base: BaseResponse::success("Tenant theme retrieved"),
+                theme: dto,
+            }),
+            Err(e) => Self::error_response(e, "Failed to read default theme"),
+        }
+    }
+
+    /// PUT /api/v1/admin/theme/tenants/{tenant_key} - Replace a tenant's overrides
+    ///
+    /// Only whitelisted CSS custom properties are accepted. Sending no
+    /// overrides resets the tenant to the platform default theme.
+    pub async fn update(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<String>,
+        body: web::Json<TenantThemeUpdateRequest>,
+    ) -> HttpResponse {
+        let tenant_key = path.into_inner();
+        let user_id = req.extensions().get::<i64>().copied();
+
+        let (light, dark) = match ThemeService::validate_tenant_variables(
+            &tenant_key,
+            body.theme_light.as_ref(),
+            body.theme_dark.as_ref(),
+        ) {
+            Ok(overrides) => overrides,
+            Err(e) => return Self::error_response(e, "Failed to validate tenant theme"),
+        };
+
+        let is_empty = |v: &Value| v.as_object().is_none_or(|obj| obj.is_empty());
+        if is_empty(&light) && is_empty(&dark) {
+            return Self::reset(state, web::Path::from(tenant_key)).await;
+        }
+
+        info!("Tenant theme update requested for {}", tenant_key);
+
+        let stylesheet = match ThemeService::build_tenant_bundle(&tenant_key, &light, &dark) {
+            Ok(file_name) => file_name,
+            Err(e) => return Self::error_response(e, "Failed to build tenant theme"),
+        };
+
+        let db = state.db.lock().await;
+        let params = db_mutations::UpsertTenantThemeParams {
+            tenant_key: tenant_key.clone(),
+            theme_light: light,
+            theme_dark: dark,
+            stylesheet: stylesheet.clone(),
+            updated_by: user_id,
+        };
+        if let Err(e) = db_mutations::upsert(&db, &params).await {
+            error!("Failed to save tenant theme {}: {}", tenant_key, e);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to save tenant theme"));
+        }
+
+        // The new bundle is live, older ones are no longer referenced
+        if let Err(e) = ThemeService::remove_tenant_bundles(&tenant_key, Some(&stylesheet)) {
+            warn!(
+                "Failed to remove old bundles of tenant {}: {}",
+                tenant_key, e
+            );
+        }
+
+        let theme = match db_read::get_by_key(&db, &tenant_key).await {
+            Ok(theme) => theme,
+            Err(e) => {
+                error!("Failed to reload tenant theme {}: {}", tenant_key, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve tenant theme"));
+            }
+        };
+
+        match Self::to_dto(tenant_key, theme.as_ref()) {
+            Ok(dto) => HttpResponse::Ok().json(TenantThemeResponse {
+                base: BaseResponse::success("Tenant theme updated"),
+                theme: dto,
+            }),
+            Err(e) => Self::error_response(e, "Failed to read default theme"),
+        }
+    }
+
+    /// DELETE /api/v1/admin/theme/tenants/{tenant_key} - Reset to the default theme
+    pub async fn reset(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+        let tenant_key = path.into_inner();
+        if let Some(response) = Self::invalid_tenant_key(&tenant_key) {
+            return response;
+        }
+
+        {
+            let db = state.db.lock().await;
+            if let Err(e) = db_mutations::delete(&db, &tenant_key).await {
+                error!("Failed to delete tenant theme {}: {}", tenant_key, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to reset tenant theme"));
+            }
+        }
+
+        if let Err(e) = ThemeService::remove_tenant_bundles(&tenant_key, None) {
+            warn!("Failed to remove bundles of tenant {}: {}", tenant_key, e);
+        }
+
+        info!("Tenant {} reset to the default theme", tenant_key);
+
+        match Self::to_dto(tenant_key, None) {
+            Ok(dto) => HttpResponse::Ok().json(TenantThemeResponse {
+                base: BaseResponse::success("Tenant theme reset to default"),
+                theme: dto,
+            }),
+            Err(e) => Self::error_response(e, "Failed to read default theme"),
+        }
+    }
+
+    /// GET /api/v1/theme/tenants/{tenant_key} - Stylesheets for a tenant's pages
+    ///
+    /// This is a public endpoint - no authentication required. Unknown tenants
+    /// and tenants without overrides get the default bundle only.
+    pub async fn stylesheets_for(
+        state: web::Data<AppState>,
+        path: web::Path<String>,
+    ) -> HttpResponse {
+        let tenant_key = path.into_inner();
+        if let Some(response) = Self::invalid_tenant_key(&tenant_key) {
+            return response;
+        }
+
+        let db = state.read_db().await;
+        let theme = match db_read::get_by_key(&db, &tenant_key).await {
+            Ok(theme) => theme,
+            Err(e) => {
+                // Serving the default theme beats serving no theme
+                warn!("Failed to get tenant theme {}: {}", tenant_key, e);
+                None
+            }
+        };
+
+        HttpResponse::Ok().json(TenantStylesheetsResponse {
+            base: BaseResponse::success("Tenant stylesheets retrieved"),
+            uses_default: theme.is_none(),
+            stylesheets: Self::stylesheets(theme.as_ref()),
+            tenant_key,
+        })
+    }
+}
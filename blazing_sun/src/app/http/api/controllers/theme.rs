@@ -5,10 +5,16 @@
 //! - GET /admin/theme: Get current theme configuration
 //! - PUT /admin/theme: Update theme variables (triggers build)
 //! - PUT /admin/theme/branding: Update branding (name, description, logo, favicon)
+//! - GET /admin/theme/variables: Get the parsed SCSS variables (cached, with ETag)
 //! - POST /admin/theme/build: Trigger manual rebuild
+//! - POST /admin/theme/reload: Re-parse the SCSS files and rebuild
+//! - GET/POST /admin/theme/previews: List previews / build a preview without publishing
+//! - POST /admin/theme/previews/{token}/promote: Publish a preview
+//! - DELETE /admin/theme/previews/{token}: Discard a preview
 //! - GET /admin/theme/build/status: Get build status
 //!
 
+use actix_web::http::header;
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -27,10 +33,13 @@ use crate::app::db_query::read::page_seo as seo_read;
 use crate::app::db_query::read::schema_entity as schema_entity_read;
 use crate::app::db_query::read::site_config as db_read;
 use crate::app::http::api::controllers::responses::BaseResponse;
-use crate::bootstrap::includes::theme::ThemeService;
+use crate::bootstrap::includes::theme::{
+    PreviewError, ThemePreview, ThemeService, ThemeServiceError,
+};
 use crate::bootstrap::routes::controller::api::{
     get_route_registry_snapshot, route_with_lang, DEFAULT_LANG,
 };
+use crate::config::ThemeConfig;
 use crate::database::AppState;
 use std::future::Future;
 use std::pin::Pin;
@@ -102,6 +111,68 @@ pub struct BuildStatusResponse {
     pub version: String,
 }
 
+/// Parsed theme variables response
+#[derive(Debug, Serialize)]
+pub struct ThemeVariablesResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub scss_variables: Value,
+    pub theme_light: Value,
+    pub theme_dark: Value,
+}
+
+/// Theme preview request (same variables as a theme update)
+#[derive(Debug, Deserialize)]
+pub struct ThemePreviewRequest {
+    pub scss_variables: Option<Value>,
+    pub theme_light: Option<Value>,
+    pub theme_dark: Option<Value>,
+}
+
+/// Theme preview DTO
+#[derive(Debug, Serialize)]
+pub struct ThemePreviewDto {
+    pub token: String,
+    /// Site home page rendered with the preview stylesheet
+    pub preview_url: String,
+    pub stylesheet_url: String,
+    pub created_by: i64,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+impl From<&ThemePreview> for ThemePreviewDto {
+    fn from(preview: &ThemePreview) -> Self {
+        Self {
+            token: preview.token.clone(),
+            preview_url: preview.preview_url(),
+            stylesheet_url: preview.stylesheet_url(),
+            created_by: preview.created_by,
+            created_at: preview.created_at.to_rfc3339(),
+            expires_at: preview
+                .expires_at(ThemeConfig::preview_ttl_secs())
+                .to_rfc3339(),
+        }
+    }
+}
+
+/// Theme preview response
+#[derive(Debug, Serialize)]
+pub struct ThemePreviewResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub preview: ThemePreviewDto,
+    pub build_output: Option<String>,
+}
+
+/// Theme preview list response
+#[derive(Debug, Serialize)]
+pub struct ThemePreviewListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub previews: Vec<ThemePreviewDto>,
+}
+
 /// Build result response
 #[derive(Debug, Serialize)]
 pub struct BuildResultResponse {
@@ -762,7 +833,7 @@ impl ThemeController {
             return response;
         }
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         match db_read::get(&db).await {
             Ok(config) => HttpResponse::Ok().json(ThemeConfigResponse {
                 base: BaseResponse::success("Theme configuration retrieved"),
@@ -1097,6 +1168,50 @@ impl ThemeController {
         }
     }
 
+    /// GET /api/v1/admin/theme/variables - Get the variables parsed from the SCSS files
+    ///
+    /// Served from the theme cache with an ETag; answers 304 when the
+    /// client's `If-None-Match` still matches.
+    pub async fn variables(req: HttpRequest) -> HttpResponse {
+        // Check admin permission
+        if let Some(response) = Self::check_admin_permission(&req) {
+            return response;
+        }
+
+        let theme = match ThemeService::get_cached_variables() {
+            Ok(theme) => theme,
+            Err(e) => {
+                error!("Failed to parse theme variables: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve theme variables"));
+            }
+        };
+
+        let not_modified = req
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|tags| {
+                tags.split(',')
+                    .any(|tag| tag.trim() == theme.etag || tag.trim() == "*")
+            });
+        if not_modified {
+            return HttpResponse::NotModified()
+                .insert_header((header::ETAG, theme.etag.clone()))
+                .finish();
+        }
+
+        HttpResponse::Ok()
+            .insert_header((header::ETAG, theme.etag.clone()))
+            .insert_header((header::CACHE_CONTROL, "private, no-cache"))
+            .json(ThemeVariablesResponse {
+                base: BaseResponse::success("Theme variables retrieved"),
+                scss_variables: theme.scss_variables.clone(),
+                theme_light: theme.theme_light.clone(),
+                theme_dark: theme.theme_dark.clone(),
+            })
+    }
+
     /// POST /api/v1/admin/theme/build - Trigger manual rebuild
     pub async fn trigger_build(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
         // Check admin permission
@@ -1105,7 +1220,37 @@ impl ThemeController {
         }
 
         info!("Manual theme build triggered");
+        Self::build(&state).await
+    }
 
+    /// POST /api/v1/admin/theme/reload - Re-parse the SCSS files and rebuild
+    ///
+    /// For files changed outside the admin UI when the watcher missed it
+    /// (e.g. on a network mount).
+    pub async fn reload(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        // Check admin permission
+        if let Some(response) = Self::check_admin_permission(&req) {
+            return response;
+        }
+
+        info!("Theme reload triggered");
+
+        if let Err(e) = ThemeService::reload_variables() {
+            error!("Failed to parse theme variables: {}", e);
+            return HttpResponse::UnprocessableEntity().json(BuildResultResponse {
+                base: BaseResponse::error("Failed to parse theme files"),
+                success: false,
+                new_version: None,
+                build_output: None,
+                error: Some(e.to_string()),
+            });
+        }
+
+        Self::build(&state).await
+    }
+
+    /// Run a build and record its outcome in the site config
+    async fn build(state: &web::Data<AppState>) -> HttpResponse {
         // Mark build as started
         {
             let db = state.db.lock().await;
@@ -1157,6 +1302,165 @@ impl ThemeController {
         }
     }
 
+    /// GET /api/v1/admin/theme/previews - List previews waiting to be promoted or discarded
+    pub async fn list_previews(req: HttpRequest) -> HttpResponse {
+        // Check admin permission
+        if let Some(response) = Self::check_admin_permission(&req) {
+            return response;
+        }
+
+        match ThemeService::list_previews() {
+            Ok(previews) => HttpResponse::Ok().json(ThemePreviewListResponse {
+                base: BaseResponse::success("Theme previews retrieved"),
+                previews: previews.iter().map(ThemePreviewDto::from).collect(),
+            }),
+            Err(e) => {
+                error!("Failed to list theme previews: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve theme previews"))
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/theme/previews - Build variables into a preview
+    ///
+    /// The production assets and the saved theme are left untouched; open
+    /// `preview_url` to see the result, then promote or discard it.
+    pub async fn create_preview(
+        req: HttpRequest,
+        body: web::Json<ThemePreviewRequest>,
+    ) -> HttpResponse {
+        // Check admin permission
+        if let Some(response) = Self::check_admin_permission(&req) {
+            return response;
+        }
+        let user_id = req.extensions().get::<i64>().copied().unwrap_or_default();
+
+        info!("Theme preview build requested by user {}", user_id);
+
+        let body = body.into_inner();
+        match ThemeService::build_preview(
+            body.scss_variables,
+            body.theme_light,
+            body.theme_dark,
+            user_id,
+        )
+        .await
+        {
+            Ok((preview, result)) => HttpResponse::Created().json(ThemePreviewResponse {
+                base: BaseResponse::success("Theme preview built"),
+                preview: ThemePreviewDto::from(&preview),
+                build_output: Some(result.stdout),
+            }),
+            Err(e) => {
+                error!("Theme preview build failed: {}", e);
+                let (mut response, message) = match &e {
+                    ThemeServiceError::Validation(_) => {
+                        (HttpResponse::BadRequest(), "Invalid theme variables")
+                    }
+                    _ => (
+                        HttpResponse::InternalServerError(),
+                        "Theme preview build failed",
+                    ),
+                };
+                response.json(BuildResultResponse {
+                    base: BaseResponse::error(message),
+                    success: false,
+                    new_version: None,
+                    build_output: None,
+                    error: Some(e.to_string()),
+                })
+            }
+        }
+    }
+
+    /// POST /api/v1/admin/theme/previews/{token}/promote - Publish a preview
+    ///
+    /// The preview's build output replaces the production assets and its
+    /// variables become the saved theme.
+    pub async fn promote_preview(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        path: web::Path<String>,
+    ) -> HttpResponse {
+        // Check admin permission
+        if let Some(response) = Self::check_admin_permission(&req) {
+            return response;
+        }
+
+        let token = path.into_inner();
+        info!("Theme preview {} promotion requested", token);
+
+        let (preview, result) = match ThemeService::promote_preview(&token).await {
+            Ok(promoted) => promoted,
+            Err(e) if Self::is_missing_preview(&e) => {
+                return HttpResponse::NotFound()
+                    .json(BaseResponse::error("Theme preview not found"));
+            }
+            Err(e) => {
+                error!("Theme preview promotion failed: {}", e);
+                return HttpResponse::InternalServerError().json(BuildResultResponse {
+                    base: BaseResponse::error("Theme preview promotion failed"),
+                    success: false,
+                    new_version: None,
+                    build_output: None,
+                    error: Some(e.to_string()),
+                });
+            }
+        };
+
+        // The assets are live now; record the variables they were built from
+        let db = state.db.lock().await;
+        if let Err(e) = db_mutations::update_themes(
+            &db,
+            preview.scss_variables.as_ref(),
+            preview.theme_light.as_ref(),
+            preview.theme_dark.as_ref(),
+        )
+        .await
+        {
+            error!("Failed to save promoted theme in database: {}", e);
+        }
+        if let Some(ref version) = result.new_version {
+            let _ = db_mutations::set_build_success(&db, version).await;
+        }
+
+        HttpResponse::Ok().json(BuildResultResponse {
+            base: BaseResponse::success("Theme preview promoted"),
+            success: true,
+            new_version: result.new_version,
+            build_output: None,
+            error: None,
+        })
+    }
+
+    /// DELETE /api/v1/admin/theme/previews/{token} - Discard a preview
+    pub async fn discard_preview(req: HttpRequest, path: web::Path<String>) -> HttpResponse {
+        // Check admin permission
+        if let Some(response) = Self::check_admin_permission(&req) {
+            return response;
+        }
+
+        match ThemeService::discard_preview(&path.into_inner()) {
+            Ok(()) => HttpResponse::Ok().json(BaseResponse::success("Theme preview discarded")),
+            Err(e) if Self::is_missing_preview(&e) => {
+                HttpResponse::NotFound().json(BaseResponse::error("Theme preview not found"))
+            }
+            Err(e) => {
+                error!("Failed to discard theme preview: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to discard theme preview"))
+            }
+        }
+    }
+
+    fn is_missing_preview(e: &ThemeServiceError) -> bool {
+        matches!(
+            e,
+            ThemeServiceError::Preview(PreviewError::NotFound | PreviewError::InvalidToken)
+        )
+    }
+
     /// GET /api/v1/admin/theme/build/status - Get build status
     pub async fn build_status(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
         // Check admin permission
@@ -1164,7 +1468,7 @@ impl ThemeController {
             return response;
         }
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match db_read::get(&db).await {
             Ok(config) => HttpResponse::Ok().json(BuildStatusResponse {
@@ -1194,7 +1498,7 @@ impl ThemeController {
             return response;
         }
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match seo_read::get_all(&db).await {
             Ok(pages) => {
@@ -1325,7 +1629,7 @@ impl ThemeController {
         }
 
         let route_name = path.into_inner();
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match seo_read::get_by_route(&db, &route_name).await {
             Ok(seo) => {
@@ -1582,7 +1886,7 @@ impl ThemeController {
         }
 
         let page_seo_id = path.into_inner();
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match hreflang_read::get_by_page_id(&db, page_seo_id).await {
             Ok(entries) => {
@@ -1702,7 +2006,7 @@ impl ThemeController {
             .lang_code
             .clone()
             .unwrap_or_else(|| DEFAULT_LANG.to_string());
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match schema_read::get_by_page_seo_id_lang(&db, page_seo_id, &lang_code).await {
             Ok(schemas) => {
@@ -1965,7 +2269,7 @@ impl ThemeController {
         }
 
         let schema_id = path.into_inner();
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match schema_read::get_by_id(&db, schema_id).await {
             Ok(schema) => HttpResponse::Ok().json(SchemaResponse {
@@ -2034,7 +2338,7 @@ impl ThemeController {
         );
         let search = query.q.clone();
         let limit = query.limit.unwrap_or(50).max(1).min(200);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         // If schema_type is specified, filter by type
         let result = if let Some(schema_type) = &query.schema_type {
@@ -2277,7 +2581,7 @@ impl ThemeController {
                 .clone()
                 .unwrap_or_else(|| DEFAULT_LANG.to_string()),
         );
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match schema_entity_read::list_types_by_lang(&db, &lang_code).await {
             Ok(types) => HttpResponse::Ok().json(serde_json::json!({
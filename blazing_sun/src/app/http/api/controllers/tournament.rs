@@ -0,0 +1,426 @@
+//!
+//! Tournament Controller
+//!
+//! Single-elimination game tournaments:
+//! - GET /api/v1/tournaments: List tournaments (optional `status` filter)
+//! - GET /api/v1/tournaments/{id}: Tournament with entrants and bracket
+//! - POST /api/v1/tournaments/{id}/register: Register (pays the entry fee)
+//! - DELETE /api/v1/tournaments/{id}/register: Withdraw before the start (refunds the fee)
+//! - POST /api/v1/tournaments: Create a tournament (admin)
+//! - POST /api/v1/tournaments/{id}/cancel: Cancel and refund every entrant (admin)
+//! - POST /api/v1/tournaments/{id}/matches/{match_id}/winner: Decide a match whose room
+//!   ended without a winner (admin)
+//!
+//! The registration that fills the bracket starts the tournament and opens
+//! the first-round rooms; later rounds are opened by the tournament event
+//! handler as match rooms finish.
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::app::db_query::mutations::tournaments::{
+    self as db_mutations, RegisterOutcome, UnregisterOutcome,
+};
+use crate::app::db_query::read::tournaments::{
+    self as db_read, Tournament, TournamentEntry, TournamentMatch,
+};
+use crate::app::games::tournament;
+use crate::app::games::tournament_runner::TournamentRunner;
+use crate::app::games::types::GameType;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::{replica, AppState};
+
+/// Default and largest page of the tournament list
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// Tournament statuses that can be filtered on
+const STATUSES: &[&str] = &["registering", "running", "finished", "cancelled"];
+
+/// Tournament Controller
+pub struct TournamentController;
+
+/// Tournament list response
+#[derive(Debug, Serialize)]
+pub struct TournamentListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub tournaments: Vec<Tournament>,
+}
+
+/// Tournament with entrants and bracket
+#[derive(Debug, Serialize)]
+pub struct TournamentDetailResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub tournament: Tournament,
+    pub rounds: i32,
+    pub entries: Vec<TournamentEntry>,
+    pub matches: Vec<TournamentMatch>,
+}
+
+/// Registration response
+#[derive(Debug, Serialize)]
+pub struct RegisterResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub balance: i64,
+    /// This registration filled the bracket and started the tournament
+    pub started: bool,
+}
+
+/// Withdrawal response
+#[derive(Debug, Serialize)]
+pub struct UnregisterResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub refund_cents: i64,
+}
+
+/// Single tournament response
+#[derive(Debug, Serialize)]
+pub struct TournamentResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub tournament: Tournament,
+}
+
+/// Tournament list query
+#[derive(Debug, Deserialize)]
+pub struct ListTournamentsQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Create tournament request
+#[derive(Debug, Deserialize)]
+pub struct CreateTournamentRequest {
+    pub name: String,
+    pub game_type: String,
+    pub bracket_size: i32,
+    #[serde(default)]
+    pub entry_fee_cents: i64,
+}
+
+/// Decide match request
+#[derive(Debug, Deserialize)]
+pub struct DecideMatchRequest {
+    pub winner_id: i64,
+}
+
+/// Runner publishing through the app's event bus (events are skipped without one)
+fn runner(state: &AppState, db: sqlx::Pool<sqlx::Postgres>) -> TournamentRunner {
+    TournamentRunner::new(
+        db,
+        state.event_bus().map(|bus| bus.producer().clone()),
+        state.redis(),
+    )
+}
+
+impl TournamentController {
+    /// GET /api/v1/tournaments - List tournaments, newest first
+    pub async fn list(state: web::Data<AppState>, query: web::Query<ListTournamentsQuery>) -> HttpResponse {
+        let query = query.into_inner();
+
+        if let Some(status) = &query.status {
+            if !STATUSES.contains(&status.as_str()) {
+                return HttpResponse::BadRequest().json(BaseResponse::error("Unknown tournament status"));
+            }
+        }
+        let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+
+        let db = state.read_db().await;
+
+        match db_read::get_all(&db, query.status.as_deref(), limit).await {
+            Ok(tournaments) => HttpResponse::Ok().json(TournamentListResponse {
+                base: BaseResponse::success("Tournaments retrieved"),
+                tournaments,
+            }),
+            Err(e) => {
+                error!("Failed to list tournaments: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve tournaments"))
+            }
+        }
+    }
+
+    /// GET /api/v1/tournaments/{id} - Tournament with entrants and bracket
+    pub async fn show(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let id = path.into_inner();
+        let db = state.read_db().await;
+
+        let tournament = match db_read::get_by_id(&db, id).await {
+            Ok(Some(tournament)) => tournament,
+            Ok(None) => return HttpResponse::NotFound().json(BaseResponse::error("Tournament not found")),
+            Err(e) => {
+                error!("Failed to load tournament {}: {}", id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve tournament"));
+            }
+        };
+
+        let details = match db_read::get_entries(&db, id).await {
+            Ok(entries) => db_read::get_matches(&db, id).await.map(|matches| (entries, matches)),
+            Err(e) => Err(e),
+        };
+
+        match details {
+            Ok((entries, matches)) => HttpResponse::Ok().json(TournamentDetailResponse {
+                base: BaseResponse::success("Tournament retrieved"),
+                rounds: tournament::round_count(tournament.bracket_size),
+                tournament,
+                entries,
+                matches,
+            }),
+            Err(e) => {
+                error!("Failed to load bracket of tournament {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve tournament"))
+            }
+        }
+    }
+
+    /// POST /api/v1/tournaments/{id}/register - Register for a tournament
+    ///
+    /// # Responses
+    /// - 200: Registered; the entry fee was debited
+    /// - 402: Balance too low for the entry fee
+    /// - 404: Tournament not found
+    /// - 409: Already registered, or registration is closed
+    pub async fn register(state: web::Data<AppState>, req: HttpRequest, path: web::Path<i64>) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let id = path.into_inner();
+
+        let db = state.db.lock().await.clone();
+
+        let outcome = match db_mutations::register(&db, id, user_id).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                error!("Failed to register user {} for tournament {}: {}", user_id, id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to register for tournament"));
+            }
+        };
+
+        match outcome {
+            RegisterOutcome::Registered { balance_after, started } => {
+                info!("User {} registered for tournament {}", user_id, id);
+                replica::mark_written(user_id).await;
+
+                if started {
+                    // The bracket is stored already; rooms that fail here are
+                    // retried when the next match result comes in
+                    if let Err(e) = runner(&state, db).open_matches(id).await {
+                        error!("Failed to open first-round rooms of tournament {}: {}", id, e);
+                    }
+                }
+
+                HttpResponse::Ok().json(RegisterResponse {
+                    base: BaseResponse::success("Registered for tournament"),
+                    balance: balance_after,
+                    started,
+                })
+            }
+            RegisterOutcome::NotFound => {
+                HttpResponse::NotFound().json(BaseResponse::error("Tournament not found"))
+            }
+            RegisterOutcome::NotOpen => {
+                HttpResponse::Conflict().json(BaseResponse::error("Registration is closed"))
+            }
+            RegisterOutcome::AlreadyRegistered => {
+                HttpResponse::Conflict().json(BaseResponse::error("Already registered"))
+            }
+            RegisterOutcome::InsufficientBalance { .. } => HttpResponse::PaymentRequired()
+                .json(BaseResponse::error("Insufficient balance for the entry fee")),
+        }
+    }
+
+    /// DELETE /api/v1/tournaments/{id}/register - Withdraw a registration
+    ///
+    /// # Responses
+    /// - 200: Withdrawn; the entry fee was refunded
+    /// - 404: Not registered
+    /// - 409: The tournament already started
+    pub async fn unregister(state: web::Data<AppState>, req: HttpRequest, path: web::Path<i64>) -> HttpResponse {
+        let user_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let id = path.into_inner();
+
+        let db = state.db.lock().await;
+        let result = db_mutations::unregister(&db, id, user_id).await;
+        drop(db);
+
+        match result {
+            Ok(UnregisterOutcome::Unregistered { refund_cents }) => {
+                info!("User {} withdrew from tournament {}", user_id, id);
+                replica::mark_written(user_id).await;
+                HttpResponse::Ok().json(UnregisterResponse {
+                    base: BaseResponse::success("Registration withdrawn"),
+                    refund_cents,
+                })
+            }
+            Ok(UnregisterOutcome::NotRegistered) => {
+                HttpResponse::NotFound().json(BaseResponse::error("Not registered"))
+            }
+            Ok(UnregisterOutcome::NotOpen) => {
+                HttpResponse::Conflict().json(BaseResponse::error("The tournament already started"))
+            }
+            Err(e) => {
+                error!("Failed to withdraw user {} from tournament {}: {}", user_id, id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to withdraw registration"))
+            }
+        }
+    }
+
+    /// POST /api/v1/tournaments - Create a tournament (admin)
+    ///
+    /// # Responses
+    /// - 201: Tournament created and open for registration
+    /// - 400: Invalid name, game type, bracket size or entry fee
+    pub async fn create(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CreateTournamentRequest>,
+    ) -> HttpResponse {
+        let admin_id = match req.extensions().get::<i64>() {
+            Some(id) => *id,
+            None => return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")),
+        };
+        let body = body.into_inner();
+        let name = body.name.trim().to_string();
+
+        if name.is_empty() || name.len() > 100 {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Name must be between 1 and 100 characters"));
+        }
+        let Some(game_type) = GameType::from_str(&body.game_type) else {
+            return HttpResponse::BadRequest().json(BaseResponse::error("Unknown game type"));
+        };
+        if !tournament::is_valid_bracket_size(body.bracket_size) {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Bracket size must be 2, 4, 8, 16, 32 or 64"));
+        }
+        if body.entry_fee_cents < 0 {
+            return HttpResponse::BadRequest().json(BaseResponse::error("Entry fee cannot be negative"));
+        }
+
+        let params = db_mutations::CreateTournamentParams {
+            name,
+            game_type: game_type.as_str().to_string(),
+            bracket_size: body.bracket_size,
+            entry_fee_cents: body.entry_fee_cents,
+            created_by: admin_id,
+        };
+
+        let db = state.db.lock().await;
+        let created = match db_mutations::create(&db, &params).await {
+            Ok(id) => db_read::get_by_id(&db, id).await,
+            Err(e) => Err(e),
+        };
+        drop(db);
+
+        match created {
+            Ok(Some(tournament)) => {
+                info!("Tournament {} created by admin {}", tournament.id, admin_id);
+                HttpResponse::Created().json(TournamentResponse {
+                    base: BaseResponse::success("Tournament created"),
+                    tournament,
+                })
+            }
+            Ok(None) | Err(_) => {
+                error!("Failed to create tournament {}", params.name);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to create tournament"))
+            }
+        }
+    }
+
+    /// POST /api/v1/tournaments/{id}/cancel - Cancel and refund every entrant (admin)
+    ///
+    /// Match rooms still being played are left alone; their results no longer count.
+    ///
+    /// # Responses
+    /// - 200: Cancelled
+    /// - 409: Not found, or already finished or cancelled
+    pub async fn cancel(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let id = path.into_inner();
+        let db = state.db.lock().await;
+        let result = db_mutations::cancel(&db, id).await;
+        drop(db);
+
+        match result {
+            Ok(Some(refunded)) => {
+                info!("Tournament {} cancelled, {} entrants refunded", id, refunded.len());
+                for user_id in refunded {
+                    replica::mark_written(user_id).await;
+                }
+                HttpResponse::Ok().json(BaseResponse::success("Tournament cancelled"))
+            }
+            Ok(None) => HttpResponse::Conflict()
+                .json(BaseResponse::error("Only registering or running tournaments can be cancelled")),
+            Err(e) => {
+                error!("Failed to cancel tournament {}: {}", id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to cancel tournament"))
+            }
+        }
+    }
+
+    /// POST /api/v1/tournaments/{id}/matches/{match_id}/winner - Decide a match (admin)
+    ///
+    /// For match rooms that ended without a winner (abandoned or cancelled).
+    ///
+    /// # Responses
+    /// - 200: Winner recorded; the bracket moved on
+    /// - 400: The winner is not a player of the match
+    /// - 404: Match not found
+    /// - 409: The match is already decided
+    pub async fn decide_match(
+        state: web::Data<AppState>,
+        path: web::Path<(i64, i64)>,
+        body: web::Json<DecideMatchRequest>,
+    ) -> HttpResponse {
+        let (id, match_id) = path.into_inner();
+        let winner_id = body.into_inner().winner_id;
+
+        let db = state.db.lock().await.clone();
+
+        let m = match db_read::get_match(&db, match_id).await {
+            Ok(Some(m)) if m.tournament_id == id => m,
+            Ok(_) => return HttpResponse::NotFound().json(BaseResponse::error("Match not found")),
+            Err(e) => {
+                error!("Failed to load tournament match {}: {}", match_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to decide match"));
+            }
+        };
+
+        if m.winner_id.is_some() {
+            return HttpResponse::Conflict().json(BaseResponse::error("Match already decided"));
+        }
+        if winner_id != m.player1_id && winner_id != m.player2_id {
+            return HttpResponse::BadRequest()
+                .json(BaseResponse::error("Winner must be a player of the match"));
+        }
+
+        match runner(&state, db).advance(&m, winner_id).await {
+            Ok(()) => {
+                info!("Tournament {} match {} decided for user {}", id, match_id, winner_id);
+                HttpResponse::Ok().json(BaseResponse::success("Match decided"))
+            }
+            Err(e) => {
+                error!("Failed to decide tournament match {}: {}", match_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to decide match"))
+            }
+        }
+    }
+}
@@ -0,0 +1,776 @@
+//!
+//! Two-Factor Controller
+//!
+//! TOTP two-factor authentication (see `app::two_factor`):
+//! - GET /api/v1/auth/two-factor: Whether it is enabled or required, recovery codes left
+//! - POST /api/v1/auth/two-factor/enroll: New pending secret with its otpauth URI and QR code
+//! - POST /api/v1/auth/two-factor/confirm: Enable it with the first code; returns recovery codes
+//! - POST /api/v1/auth/two-factor/recovery-codes: Replace the recovery codes
+//! - DELETE /api/v1/auth/two-factor: Disable it (not allowed when the role requires it)
+//! - POST /api/v1/auth/sign-in/two-factor: Finish a sign-in with its pre-auth
+//!   token and a code or recovery code
+//! - POST /api/v1/auth/sign-in/two-factor/enroll: Enrollment secret for a
+//!   sign-in whose role requires two-factor; its first code finishes the sign-in
+//!
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use tracing::{error, warn};
+
+use crate::app::http::api::controllers::auth::AuthController;
+use crate::app::http::api::controllers::responses::{BaseResponse, ValidationErrorResponse};
+use crate::app::http::api::validators::FieldError;
+use crate::app::impersonation::Impersonation;
+use crate::app::sessions;
+use crate::app::two_factor::{self, ChallengePurpose};
+use crate::bootstrap::middleware::controllers::rbac::role_of;
+use crate::config::TwoFactorConfig;
+use crate::database::mutations::session_refresh_token::hash_token;
+use crate::database::mutations::two_factor as db_two_factor_mut;
+use crate::database::read::two_factor::{self as db_two_factor, TwoFactorChallenge, UserTwoFactor};
+use crate::database::read::user::{self as db_user, User};
+use crate::database::AppState;
+use crate::events::{self, types::payloads::TwoFactorPayload, AuthEventType};
+
+/// Two-Factor Controller
+pub struct TwoFactorController;
+
+/// A TOTP code or a recovery code
+#[derive(Debug, Deserialize)]
+pub struct CodeRequest {
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+/// Request body for POST /api/v1/auth/sign-in/two-factor
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    pub pre_auth_token: String,
+    #[serde(flatten)]
+    pub code: CodeRequest,
+}
+
+/// Request body for POST /api/v1/auth/sign-in/two-factor/enroll
+#[derive(Debug, Deserialize)]
+pub struct PreAuthEnrollRequest {
+    pub pre_auth_token: String,
+}
+
+/// Two-factor state of the current user
+#[derive(Debug, Serialize)]
+pub struct TwoFactorStatusResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub enabled: bool,
+    /// The user's role must use two-factor
+    pub required: bool,
+    /// Enrollment started but not confirmed
+    pub pending: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+/// A new secret to add to an authenticator app
+#[derive(Debug, Serialize)]
+pub struct EnrollmentResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub secret: String,
+    pub otpauth_uri: String,
+    /// The otpauth URI as an SVG QR code
+    pub qr_svg: Option<String>,
+}
+
+/// Recovery codes, shown once
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub recovery_codes: Vec<String>,
+}
+
+/// Sign-in response while a two-factor code is missing
+#[derive(Debug, Serialize)]
+pub struct PreAuthResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub two_factor_required: bool,
+    /// The role requires two-factor and the user has to enroll first
+    pub two_factor_setup_required: bool,
+    pub pre_auth_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Outcome of checking an entered code
+enum CodeCheck {
+    Accepted { method: &'static str },
+    Rejected,
+    Missing,
+}
+
+fn validation_error(field: &str, message: &'static str) -> HttpResponse {
+    HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(vec![FieldError::new(
+        field, "invalid", message,
+    )]))
+}
+
+/// Check a TOTP code (once per time step) or use up a recovery code
+async fn check_code(
+    db: &Pool<Postgres>,
+    two_factor: &UserTwoFactor,
+    request: &CodeRequest,
+) -> Result<CodeCheck, sqlx::Error> {
+    if let Some(code) = request
+        .code
+        .as_deref()
+        .filter(|code| !code.trim().is_empty())
+    {
+        let last_used_step = two_factor.last_used_step.map(|step| step as u64);
+        let now = Utc::now().timestamp() as u64;
+        return match two_factor::verify_code(&two_factor.secret, code, now, last_used_step) {
+            Some(step)
+                if db_two_factor_mut::use_step(db, two_factor.user_id, step as i64).await? =>
+            {
+                Ok(CodeCheck::Accepted { method: "totp" })
+            }
+            _ => Ok(CodeCheck::Rejected),
+        };
+    }
+
+    if let Some(code) = request
+        .recovery_code
+        .as_deref()
+        .filter(|code| !code.trim().is_empty())
+    {
+        let code_hash = two_factor::hash_recovery_code(code);
+        return if db_two_factor_mut::use_recovery_code(db, two_factor.user_id, &code_hash).await? {
+            Ok(CodeCheck::Accepted {
+                method: "recovery_code",
+            })
+        } else {
+            Ok(CodeCheck::Rejected)
+        };
+    }
+
+    Ok(CodeCheck::Missing)
+}
+
+/// New recovery codes with the hashes to store
+fn new_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let codes = two_factor::generate_recovery_codes();
+    let hashes = codes
+        .iter()
+        .map(|code| two_factor::hash_recovery_code(code))
+        .collect();
+    (codes, hashes)
+}
+
+/// Publish an auth.two_factor_* event, logging failures
+async fn publish(
+    state: &AppState,
+    req: &HttpRequest,
+    event_type: AuthEventType,
+    user_id: i64,
+    actor_id: Option<i64>,
+    payload: TwoFactorPayload,
+) {
+    let Some(event_bus) = state.event_bus() else {
+        return;
+    };
+    let event_name = event_type.to_string();
+    let device_info = sessions::device_info(req.headers());
+    let ip_address = sessions::client_ip(&req.connection_info());
+    if let Err(e) = events::publish::auth_two_factor(
+        event_bus,
+        event_type,
+        user_id,
+        actor_id,
+        payload,
+        ip_address.as_deref(),
+        device_info.as_deref(),
+    )
+    .await
+    {
+        warn!("Failed to publish {} event: {}", event_name, e);
+    }
+}
+
+async fn publish_failed(state: &AppState, req: &HttpRequest, user_id: i64, reason: &str) {
+    let payload = TwoFactorPayload {
+        method: None,
+        failure_reason: Some(reason.to_string()),
+        recovery_codes_remaining: None,
+    };
+    publish(
+        state,
+        req,
+        AuthEventType::TwoFactorChallengeFailed,
+        user_id,
+        None,
+        payload,
+    )
+    .await;
+}
+
+/// Start (or restart) enrollment and describe the secret
+async fn enroll_user(db: &Pool<Postgres>, user: &User) -> HttpResponse {
+    let secret = two_factor::generate_secret();
+    match db_two_factor_mut::start_enrollment(db, user.id, &secret).await {
+        Ok(true) => {
+            let otpauth_uri = two_factor::otpauth_uri(&user.email, &secret);
+            HttpResponse::Ok().json(EnrollmentResponse {
+                base: BaseResponse::success("Scan the QR code with your authenticator app"),
+                qr_svg: two_factor::qr_svg(&otpauth_uri),
+                secret,
+                otpauth_uri,
+            })
+        }
+        Ok(false) => HttpResponse::Conflict().json(BaseResponse::error(
+            "Two-factor authentication is already enabled",
+        )),
+        Err(e) => {
+            error!(
+                "Failed to start two-factor enrollment for user {}: {}",
+                user.id, e
+            );
+            HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to start two-factor enrollment"))
+        }
+    }
+}
+
+/// Enable a pending secret with its first code; returns the recovery codes
+async fn confirm_enrollment(
+    state: &AppState,
+    req: &HttpRequest,
+    db: &Pool<Postgres>,
+    user_id: i64,
+    code: Option<&str>,
+) -> Result<Vec<String>, HttpResponse> {
+    let pending = match db_two_factor::get(db, user_id).await {
+        Ok(Some(two_factor)) if !two_factor.is_enabled() => two_factor,
+        Ok(Some(_)) => {
+            return Err(HttpResponse::Conflict().json(BaseResponse::error(
+                "Two-factor authentication is already enabled",
+            )));
+        }
+        Ok(None) => {
+            return Err(HttpResponse::BadRequest()
+                .json(BaseResponse::error("Start two-factor enrollment first")));
+        }
+        Err(e) => {
+            error!("Failed to load two-factor state of user {}: {}", user_id, e);
+            return Err(
+                HttpResponse::InternalServerError().json(BaseResponse::error(
+                    "Failed to enable two-factor authentication",
+                )),
+            );
+        }
+    };
+
+    let now = Utc::now().timestamp() as u64;
+    let Some(step) =
+        code.and_then(|code| two_factor::verify_code(&pending.secret, code, now, None))
+    else {
+        publish_failed(state, req, user_id, "invalid_enrollment_code").await;
+        return Err(validation_error("code", "Invalid two-factor code"));
+    };
+
+    let (codes, hashes) = new_recovery_codes();
+    if let Err(e) = db_two_factor_mut::enable(db, user_id, step as i64, &hashes).await {
+        error!("Failed to enable two-factor for user {}: {}", user_id, e);
+        return Err(
+            HttpResponse::InternalServerError().json(BaseResponse::error(
+                "Failed to enable two-factor authentication",
+            )),
+        );
+    }
+
+    let payload = TwoFactorPayload {
+        method: Some("totp".to_string()),
+        failure_reason: None,
+        recovery_codes_remaining: Some(codes.len() as i64),
+    };
+    publish(
+        state,
+        req,
+        AuthEventType::TwoFactorEnabled,
+        user_id,
+        Some(user_id),
+        payload,
+    )
+    .await;
+
+    Ok(codes)
+}
+
+impl TwoFactorController {
+    /// What a sign-in of `user` still needs after the password: a code when
+    /// two-factor is enabled, enrollment when the role requires it
+    pub async fn challenge_purpose(
+        db: &Pool<Postgres>,
+        user: &User,
+    ) -> Result<Option<ChallengePurpose>, sqlx::Error> {
+        if user.two_factor == 1
+            && db_two_factor::get(db, user.id)
+                .await?
+                .is_some_and(|two_factor| two_factor.is_enabled())
+        {
+            return Ok(Some(ChallengePurpose::Verify));
+        }
+
+        if two_factor::is_required(role_of(user.permissions)) {
+            return Ok(Some(ChallengePurpose::Enroll));
+        }
+
+        Ok(None)
+    }
+
+    /// Hold a sign-in until the code arrives: store a challenge and hand out
+    /// its pre-auth token instead of a JWT
+    pub async fn start_challenge(
+        db: &Pool<Postgres>,
+        user: &User,
+        purpose: ChallengePurpose,
+        remember_me: bool,
+    ) -> HttpResponse {
+        let token = two_factor::generate_challenge_token();
+        let expires_at = Utc::now() + Duration::seconds(TwoFactorConfig::challenge_ttl_seconds());
+
+        if let Err(e) = db_two_factor_mut::create_challenge(
+            db,
+            user.id,
+            &hash_token(&token),
+            purpose,
+            remember_me,
+            expires_at,
+        )
+        .await
+        {
+            error!(
+                "Failed to open two-factor challenge for user {}: {}",
+                user.id, e
+            );
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to sign in"));
+        }
+
+        let (message, setup_required) = match purpose {
+            ChallengePurpose::Verify => ("Enter the code from your authenticator app", false),
+            ChallengePurpose::Enroll => ("Set up two-factor authentication to sign in", true),
+        };
+        HttpResponse::Ok().json(PreAuthResponse {
+            base: BaseResponse::success(message),
+            two_factor_required: true,
+            two_factor_setup_required: setup_required,
+            pre_auth_token: token,
+            expires_at,
+        })
+    }
+
+    /// GET /api/v1/auth/two-factor - Two-factor state of the current user
+    pub async fn status(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        let permissions = req.extensions().get::<i16>().copied().unwrap_or_default();
+
+        let db = state.read_db_for(user_id).await;
+        let two_factor = match db_two_factor::get(&db, user_id).await {
+            Ok(two_factor) => two_factor,
+            Err(e) => {
+                error!("Failed to load two-factor state of user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load two-factor status"));
+            }
+        };
+        let recovery_codes_remaining = db_two_factor::count_unused_recovery_codes(&db, user_id)
+            .await
+            .unwrap_or_default();
+
+        HttpResponse::Ok().json(TwoFactorStatusResponse {
+            base: BaseResponse::success("Two-factor status retrieved"),
+            enabled: two_factor.as_ref().is_some_and(UserTwoFactor::is_enabled),
+            required: two_factor::is_required(role_of(permissions)),
+            pending: two_factor
+                .as_ref()
+                .is_some_and(|two_factor| !two_factor.is_enabled()),
+            recovery_codes_remaining,
+        })
+    }
+
+    /// POST /api/v1/auth/two-factor/enroll - Start enrollment
+    ///
+    /// # Responses
+    /// - 200: Secret, otpauth URI and QR code; confirm with the first code
+    /// - 401: Unauthorized
+    /// - 403: Impersonation tokens cannot change two-factor
+    /// - 409: Already enabled
+    pub async fn enroll(state: web::Data<AppState>, req: HttpRequest) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        if req.extensions().get::<Impersonation>().is_some() {
+            return HttpResponse::Forbidden().json(BaseResponse::error(
+                "Two-factor authentication cannot be changed while impersonating",
+            ));
+        }
+
+        let db = state.db.lock().await;
+        match db_user::get_by_id(&db, user_id).await {
+            Ok(user) => enroll_user(&db, &user).await,
+            Err(e) => {
+                error!(
+                    "Failed to load user {} for two-factor enrollment: {}",
+                    user_id, e
+                );
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to start two-factor enrollment"))
+            }
+        }
+    }
+
+    /// POST /api/v1/auth/two-factor/confirm - Enable two-factor with the first code
+    ///
+    /// # Responses
+    /// - 200: Enabled; the recovery codes are only shown in this response
+    /// - 400: Wrong code, or no enrollment started
+    /// - 401: Unauthorized
+    /// - 403: Impersonation tokens cannot change two-factor
+    /// - 409: Already enabled
+    pub async fn confirm(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CodeRequest>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        if req.extensions().get::<Impersonation>().is_some() {
+            return HttpResponse::Forbidden().json(BaseResponse::error(
+                "Two-factor authentication cannot be changed while impersonating",
+            ));
+        }
+
+        let db = state.db.lock().await;
+        match confirm_enrollment(&state, &req, &db, user_id, body.code.as_deref()).await {
+            Ok(recovery_codes) => HttpResponse::Ok().json(RecoveryCodesResponse {
+                base: BaseResponse::success("Two-factor authentication enabled"),
+                recovery_codes,
+            }),
+            Err(response) => response,
+        }
+    }
+
+    /// POST /api/v1/auth/two-factor/recovery-codes - Replace the recovery codes
+    ///
+    /// Needs a current code (or an unused recovery code).
+    pub async fn regenerate_recovery_codes(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CodeRequest>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        if req.extensions().get::<Impersonation>().is_some() {
+            return HttpResponse::Forbidden().json(BaseResponse::error(
+                "Two-factor authentication cannot be changed while impersonating",
+            ));
+        }
+
+        let db = state.db.lock().await;
+        let two_factor = match Self::enabled_for(&db, user_id).await {
+            Ok(two_factor) => two_factor,
+            Err(response) => return response,
+        };
+
+        match check_code(&db, &two_factor, &body).await {
+            Ok(CodeCheck::Accepted { .. }) => {}
+            Ok(CodeCheck::Rejected) => {
+                publish_failed(&state, &req, user_id, "invalid_code").await;
+                return validation_error("code", "Invalid two-factor code");
+            }
+            Ok(CodeCheck::Missing) => return validation_error("code", "Enter a two-factor code"),
+            Err(e) => {
+                error!("Failed to check two-factor code of user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to replace recovery codes"));
+            }
+        }
+
+        let (codes, hashes) = new_recovery_codes();
+        if let Err(e) = db_two_factor_mut::replace_recovery_codes(&db, user_id, &hashes).await {
+            error!(
+                "Failed to replace recovery codes of user {}: {}",
+                user_id, e
+            );
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to replace recovery codes"));
+        }
+
+        HttpResponse::Ok().json(RecoveryCodesResponse {
+            base: BaseResponse::success("Recovery codes replaced"),
+            recovery_codes: codes,
+        })
+    }
+
+    /// DELETE /api/v1/auth/two-factor - Disable two-factor
+    ///
+    /// # Responses
+    /// - 200: Disabled
+    /// - 400: Missing or wrong code, or two-factor not enabled
+    /// - 401: Unauthorized
+    /// - 403: The user's role requires two-factor, or an impersonation token
+    pub async fn disable(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<CodeRequest>,
+    ) -> HttpResponse {
+        let Some(user_id) = req.extensions().get::<i64>().copied() else {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        };
+        if req.extensions().get::<Impersonation>().is_some() {
+            return HttpResponse::Forbidden().json(BaseResponse::error(
+                "Two-factor authentication cannot be changed while impersonating",
+            ));
+        }
+        let permissions = req.extensions().get::<i16>().copied().unwrap_or_default();
+        if two_factor::is_required(role_of(permissions)) {
+            return HttpResponse::Forbidden().json(BaseResponse::error(
+                "Your role requires two-factor authentication",
+            ));
+        }
+
+        let db = state.db.lock().await;
+        let two_factor = match Self::enabled_for(&db, user_id).await {
+            Ok(two_factor) => two_factor,
+            Err(response) => return response,
+        };
+
+        let method = match check_code(&db, &two_factor, &body).await {
+            Ok(CodeCheck::Accepted { method }) => method,
+            Ok(CodeCheck::Rejected) => {
+                publish_failed(&state, &req, user_id, "invalid_code").await;
+                return validation_error("code", "Invalid two-factor code");
+            }
+            Ok(CodeCheck::Missing) => return validation_error("code", "Enter a two-factor code"),
+            Err(e) => {
+                error!("Failed to check two-factor code of user {}: {}", user_id, e);
+                return HttpResponse::InternalServerError().json(BaseResponse::error(
+                    "Failed to disable two-factor authentication",
+                ));
+            }
+        };
+
+        if let Err(e) = db_two_factor_mut::disable(&db, user_id).await {
+            error!("Failed to disable two-factor for user {}: {}", user_id, e);
+            return HttpResponse::InternalServerError().json(BaseResponse::error(
+                "Failed to disable two-factor authentication",
+            ));
+        }
+
+        let payload = TwoFactorPayload {
+            method: Some(method.to_string()),
+            failure_reason: None,
+            recovery_codes_remaining: Some(0),
+        };
+        publish(
+            &state,
+            &req,
+            AuthEventType::TwoFactorDisabled,
+            user_id,
+            Some(user_id),
+            payload,
+        )
+        .await;
+
+        HttpResponse::Ok().json(BaseResponse::success("Two-factor authentication disabled"))
+    }
+
+    /// POST /api/v1/auth/sign-in/two-factor - Finish a sign-in
+    ///
+    /// Takes the pre-auth token from sign-in and a `code` (or `recovery_code`;
+    /// for a sign-in that enrolled, the first `code` of the new secret).
+    /// Each token allows `TWO_FACTOR_MAX_ATTEMPTS` wrong codes.
+    ///
+    /// # Responses
+    /// - 200: Signed in, same body as sign-in (plus `recovery_codes` after enrollment)
+    /// - 400: No code given
+    /// - 401: Unknown or expired token, or a wrong code
+    pub async fn complete(
+        state: web::Data<AppState>,
+        req: HttpRequest,
+        body: web::Json<ChallengeRequest>,
+    ) -> HttpResponse {
+        let db = state.db.lock().await;
+        let (challenge, user) = match Self::load_challenge(&db, &body.pre_auth_token).await {
+            Ok(found) => found,
+            Err(response) => return response,
+        };
+
+        if challenge.purpose == ChallengePurpose::Enroll {
+            return match confirm_enrollment(&state, &req, &db, user.id, body.code.code.as_deref())
+                .await
+            {
+                Ok(recovery_codes) => {
+                    Self::close_challenge(&db, &challenge).await;
+                    AuthController::complete_sign_in(
+                        &state,
+                        &req,
+                        &db,
+                        &user,
+                        challenge.remember_me,
+                        Some(recovery_codes),
+                    )
+                    .await
+                }
+                Err(response) => {
+                    Self::count_failure(&db, &challenge).await;
+                    response
+                }
+            };
+        }
+
+        let two_factor = match db_two_factor::get(&db, user.id).await {
+            Ok(Some(two_factor)) if two_factor.is_enabled() => two_factor,
+            Ok(_) => {
+                // Disabled since the password was accepted; sign in again
+                Self::close_challenge(&db, &challenge).await;
+                return HttpResponse::Unauthorized()
+                    .json(BaseResponse::error("Invalid or expired pre-auth token"));
+            }
+            Err(e) => {
+                error!("Failed to load two-factor state of user {}: {}", user.id, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to sign in"));
+            }
+        };
+
+        match check_code(&db, &two_factor, &body.code).await {
+            Ok(CodeCheck::Accepted { .. }) => {
+                Self::close_challenge(&db, &challenge).await;
+                AuthController::complete_sign_in(
+                    &state,
+                    &req,
+                    &db,
+                    &user,
+                    challenge.remember_me,
+                    None,
+                )
+                .await
+            }
+            Ok(CodeCheck::Rejected) => {
+                let reason = if Self::count_failure(&db, &challenge).await {
+                    "too_many_attempts"
+                } else {
+                    "invalid_code"
+                };
+                publish_failed(&state, &req, user.id, reason).await;
+                HttpResponse::Unauthorized().json(BaseResponse::error("Invalid two-factor code"))
+            }
+            Ok(CodeCheck::Missing) => validation_error("code", "Enter a two-factor code"),
+            Err(e) => {
+                error!("Failed to check two-factor code of user {}: {}", user.id, e);
+                HttpResponse::InternalServerError().json(BaseResponse::error("Failed to sign in"))
+            }
+        }
+    }
+
+    /// POST /api/v1/auth/sign-in/two-factor/enroll - Enroll during sign-in
+    ///
+    /// For sign-ins held because the role requires two-factor; confirm with
+    /// `POST /api/v1/auth/sign-in/two-factor` and the first code.
+    pub async fn enroll_pre_auth(
+        state: web::Data<AppState>,
+        body: web::Json<PreAuthEnrollRequest>,
+    ) -> HttpResponse {
+        let db = state.db.lock().await;
+        let (challenge, user) = match Self::load_challenge(&db, &body.pre_auth_token).await {
+            Ok(found) => found,
+            Err(response) => return response,
+        };
+        if challenge.purpose != ChallengePurpose::Enroll {
+            return HttpResponse::Conflict().json(BaseResponse::error(
+                "Two-factor authentication is already enabled",
+            ));
+        }
+
+        enroll_user(&db, &user).await
+    }
+
+    /// The challenge of a pre-auth token and its user
+    async fn load_challenge(
+        db: &Pool<Postgres>,
+        token: &str,
+    ) -> Result<(TwoFactorChallenge, User), HttpResponse> {
+        let invalid = || {
+            HttpResponse::Unauthorized()
+                .json(BaseResponse::error("Invalid or expired pre-auth token"))
+        };
+
+        let challenge = match db_two_factor::get_challenge(db, &hash_token(token)).await {
+            Ok(Some(challenge)) => challenge,
+            Ok(None) => return Err(invalid()),
+            Err(e) => {
+                error!("Failed to load two-factor challenge: {}", e);
+                return Err(HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to sign in")));
+            }
+        };
+
+        match db_user::get_by_id(db, challenge.user_id).await {
+            Ok(user) => Ok((challenge, user)),
+            Err(sqlx::Error::RowNotFound) => Err(invalid()),
+            Err(e) => {
+                error!(
+                    "Failed to load user {} of a two-factor challenge: {}",
+                    challenge.user_id, e
+                );
+                Err(HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to sign in")))
+            }
+        }
+    }
+
+    /// The enabled two-factor of a user, or the response when there is none
+    async fn enabled_for(db: &Pool<Postgres>, user_id: i64) -> Result<UserTwoFactor, HttpResponse> {
+        match db_two_factor::get(db, user_id).await {
+            Ok(Some(two_factor)) if two_factor.is_enabled() => Ok(two_factor),
+            Ok(_) => Err(HttpResponse::BadRequest().json(BaseResponse::error(
+                "Two-factor authentication is not enabled",
+            ))),
+            Err(e) => {
+                error!("Failed to load two-factor state of user {}: {}", user_id, e);
+                Err(HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load two-factor status")))
+            }
+        }
+    }
+
+    /// Count a wrong code; returns whether the challenge ran out of attempts
+    /// (and was closed)
+    async fn count_failure(db: &Pool<Postgres>, challenge: &TwoFactorChallenge) -> bool {
+        match db_two_factor_mut::record_failed_attempt(db, challenge.id).await {
+            Ok(attempts) if attempts >= TwoFactorConfig::max_attempts() => {
+                Self::close_challenge(db, challenge).await;
+                true
+            }
+            Ok(_) => false,
+            Err(e) => {
+                warn!("Failed to count two-factor attempt {}: {}", challenge.id, e);
+                false
+            }
+        }
+    }
+
+    async fn close_challenge(db: &Pool<Postgres>, challenge: &TwoFactorChallenge) {
+        if let Err(e) = db_two_factor_mut::delete_challenge(db, challenge.id).await {
+            warn!(
+                "Failed to close two-factor challenge {}: {}",
+                challenge.id, e
+            );
+        }
+    }
+}
@@ -11,21 +11,29 @@
 //! - POST /upload/chunked/{uuid}/chunk/{index}: Upload a chunk
 //! - POST /upload/chunked/{uuid}/complete: Complete chunked upload
 //! - DELETE /upload/chunked/{uuid}: Cancel chunked upload
+//! - POST /upload/avatar: Upload a profile picture (resized by the process_avatar worker)
+//! - DELETE /upload/avatar: Delete the profile picture
+//! - GET /avatar/{uuid}: Serve a profile picture or one of its variants (ETag cached)
 
 use actix_multipart::Multipart;
+use actix_web::http::header;
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use futures::StreamExt;
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::app::avatars::{self, AvatarError};
 use crate::app::db_query::read::image_variant;
 use crate::app::http::api::controllers::auth::Claims;
 use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::http::api::controllers::user::UserController;
 use crate::app::mq::jobs::delete_upload::DeleteUploadParams;
+use crate::app::mq::jobs::process_avatar::ProcessAvatarParams;
 use crate::app::mq::jobs::resize_image::ResizeImageParams;
 use crate::bootstrap::includes::controllers::uploads::{self, chunked, StorageType};
 use crate::bootstrap::includes::image::is_supported_image;
+use crate::bootstrap::includes::storage::{get_storage, Visibility};
 use crate::bootstrap::middleware::controllers::permission::is_admin;
 use crate::bootstrap::mq::{self, JobOptions, JobStatus};
 use crate::config::upload::UploadConfig;
@@ -503,7 +511,7 @@ impl UploadController {
             }
         };
 
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         // Get public upload
         let upload = match db_upload_read::get_public_by_uuid(&db, &uuid).await {
@@ -623,7 +631,7 @@ impl UploadController {
             }
         };
 
-        let db = state.db.lock().await;
+        let db = state.read_db_for(user_id).await;
 
         // Get private upload (checks user ownership)
         let upload = match db_upload_read::get_private_by_uuid(&db, &uuid, user_id).await {
@@ -746,7 +754,7 @@ impl UploadController {
                 .json(BaseResponse::error("Unexpected job status")),
             Err(e) => {
                 tracing::error!("Delete upload job error: {}", e);
-                HttpResponse::InternalServerError()
+                HttpResponse::build(e.status_code())
                     .json(BaseResponse::error("Failed to delete file"))
             }
         }
@@ -933,7 +941,7 @@ impl UploadController {
             Err(response) => return response,
         };
 
-        let db = state.db.lock().await;
+        let db = state.read_db_for(user_id).await;
         let uploads = db_upload_read::get_by_user_id(&db, user_id).await;
 
         let upload_dtos: Vec<UploadDto> = uploads
@@ -970,7 +978,11 @@ impl UploadController {
     }
 
     /// POST /upload/avatar - Upload profile picture (requires auth)
-    /// Stores in profile-pictures/ subfolder and creates an upload record
+    ///
+    /// The picture is validated on its content (JPEG, PNG or WebP, size and
+    /// dimension limits), stored through the storage driver under
+    /// private/profile-pictures/ and resized by the process_avatar worker.
+    /// A previous picture is removed and a user.updated event is published.
     pub async fn upload_avatar(
         state: web::Data<AppState>,
         req: HttpRequest,
@@ -981,13 +993,16 @@ impl UploadController {
             Err(response) => return response,
         };
 
-        // Initialize storage
-        if let Err(e) = uploads::init_storage().await {
-            return HttpResponse::InternalServerError()
-                .json(error_response(format!("Storage error: {}", e)));
-        }
+        let storage = match get_storage() {
+            Ok(s) => s,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .json(error_response(format!("Storage error: {}", e)));
+            }
+        };
 
-        // Process multipart form
+        // Process multipart form (first file only, stop reading past the limit)
+        let max_size = UploadConfig::avatar_max_file_size();
         let mut file_data: Option<Vec<u8>> = None;
         let mut filename: Option<String> = None;
 
@@ -1011,6 +1026,10 @@ impl UploadController {
                                     .json(error_response(format!("Upload error: {}", e)));
                             }
                         }
+                        if data.len() as u64 > max_size {
+                            return HttpResponse::PayloadTooLarge()
+                                .json(BaseResponse::error("Profile picture is too large"));
+                        }
                     }
                     file_data = Some(data);
                     break; // Only process first file
@@ -1022,127 +1041,120 @@ impl UploadController {
             }
         }
 
-        // Validate we have file data and filename
-        let data = match file_data {
-            Some(d) if !d.is_empty() => d,
-            _ => {
-                return HttpResponse::BadRequest()
-                    .json(BaseResponse::error("No file data received"));
+        let data = file_data.unwrap_or_default();
+        let format = match avatars::validate(&data) {
+            Ok(format) => format,
+            Err(e @ AvatarError::TooLarge { .. }) => {
+                return HttpResponse::PayloadTooLarge().json(BaseResponse::error(e.message()));
             }
-        };
-
-        let original_name = match filename {
-            Some(n) if !n.is_empty() => n,
-            _ => {
-                return HttpResponse::BadRequest()
-                    .json(BaseResponse::error("No filename provided"));
+            Err(e) => {
+                return HttpResponse::BadRequest().json(BaseResponse::error(e.message()));
             }
         };
 
-        // Validate it's an image
-        let mime_type = mime_guess::from_path(&original_name)
-            .first_or_octet_stream()
-            .to_string();
-        if !mime_type.starts_with("image/") {
-            return HttpResponse::BadRequest().json(BaseResponse::error(
-                "Only image files are allowed for profile pictures",
-            ));
-        }
-
-        // Save file to profile-pictures subfolder (private visibility)
-        let result = match uploads::save_file_with_subfolder(
-            &data,
-            &original_name,
-            StorageType::Private,
-            Some(10 * 1024 * 1024), // 10MB max for avatars
-            Some("profile-pictures"),
-        )
-        .await
+        let original_name = filename
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("avatar.{}", format.extension()));
+
+        // Store under the sniffed extension, whatever the client named the file
+        let stored = match storage
+            .put_with_subfolder(
+                &data,
+                &format!("avatar.{}", format.extension()),
+                Visibility::Private,
+                avatars::SUBFOLDER,
+            )
+            .await
         {
-            Ok(r) => r,
+            Ok(s) => s,
             Err(e) => {
-                return HttpResponse::BadRequest().json(error_response(format!("{}", e)));
+                return HttpResponse::InternalServerError()
+                    .json(error_response(format!("Storage error: {}", e)));
             }
         };
 
         let db = state.db.lock().await;
 
+        let previous_avatar = crate::database::read::user::get_by_id(&db, user_id)
+            .await
+            .ok()
+            .and_then(|u| u.avatar_uuid);
+
         // Create upload record (using uploads table, not assets)
+        let upload_uuid = Uuid::new_v4();
         let upload_params = db_upload_mutations::CreateUploadParams {
-            uuid: result.uuid,
-            original_name: result.original_name.clone(),
-            stored_name: result.stored_name.clone(),
-            extension: result.extension.clone(),
-            mime_type: result.mime_type.clone(),
-            size_bytes: result.size_bytes as i64,
+            uuid: upload_uuid,
+            original_name: original_name.clone(),
+            stored_name: stored.stored_name.clone(),
+            extension: stored.extension.clone(),
+            mime_type: format.mime_type().to_string(),
+            size_bytes: stored.size_bytes as i64,
             storage_type: "private".to_string(),
-            storage_path: result.storage_path.clone(),
+            storage_path: stored.storage_path.clone(),
             user_id: Some(user_id),
             title: None,
-            description: Some("profile-picture".to_string()), // Mark as profile picture
+            description: Some(avatars::UPLOAD_DESCRIPTION.to_string()),
         };
 
         let upload_id = match db_upload_mutations::create(&db, &upload_params).await {
             Ok(id) => id,
             Err(e) => {
                 // Try to delete the file if database insert fails
-                let _ = uploads::delete_file(&result.storage_path).await;
+                let _ = storage.delete(&stored.storage_path).await;
                 return HttpResponse::InternalServerError()
                     .json(error_response(format!("Database error: {}", e)));
             }
         };
 
-        // Enqueue image resizing job for supported formats
-        if is_supported_image(&result.extension) {
-            if let Some(mq) = &state.mq {
-                // Build absolute file path for image processor
-                // storage_path in DB is relative (e.g., "private/profile-pictures/filename.jpg")
-                // UPLOAD_STORAGE_PATH contains full path (e.g., "src/storage/app")
-                let storage_base = UploadConfig::storage_path();
-                let full_file_path = format!("{}/{}", storage_base, result.storage_path);
-
-                tracing::info!("Enqueueing resize job for avatar: storage_base={}, storage_path={}, full_file_path={}",
-                    storage_base, result.storage_path, full_file_path);
-
-                let resize_params = ResizeImageParams {
-                    upload_id,
-                    upload_uuid: result.uuid.to_string(),
-                    stored_name: result.stored_name.clone(),
-                    extension: result.extension.clone(),
-                    storage_type: "private".to_string(),
-                    file_path: full_file_path,
-                };
-
-                let options = JobOptions::new().priority(1).fault_tolerance(3);
-                if let Err(e) =
-                    mq::enqueue_job_dyn(mq, "resize_image", &resize_params, options).await
-                {
-                    tracing::warn!("Failed to enqueue resize_image job for avatar: {}", e);
-                }
+        // Render the standard sizes in the background
+        if let Some(mq) = &state.mq {
+            let params = ProcessAvatarParams::new(
+                upload_id,
+                user_id,
+                &stored.stored_name,
+                &stored.storage_path,
+            );
+            let options = JobOptions::new().priority(1).fault_tolerance(3);
+            if let Err(e) = mq::enqueue_job_dyn(mq, "process_avatar", &params, options).await {
+                tracing::warn!("Failed to enqueue process_avatar job: {}", e);
             }
         }
 
         // Update user's avatar (both UUID and ID)
         if let Err(e) =
-            db_user_mutations::update_avatar(&db, user_id, Some(result.uuid), Some(upload_id)).await
+            db_user_mutations::update_avatar(&db, user_id, Some(upload_uuid), Some(upload_id)).await
         {
             tracing::warn!("Failed to update user avatar: {}", e);
             // Don't fail the request - upload was created successfully
         }
 
+        // The old picture is no longer referenced
+        if let Some(previous_uuid) = previous_avatar {
+            if let Ok(previous) = db_upload_read::get_by_uuid(&db, &previous_uuid).await {
+                Self::remove_avatar_upload(&db, &previous).await;
+            }
+        }
+
+        let user = crate::database::read::user::get_by_id(&db, user_id).await;
+        drop(db);
+        if let Ok(user) = user {
+            UserController::profile_changed(&state, &user, vec!["avatar"], user_id).await;
+        }
+
         // Build URL for profile picture (served via API for private files)
-        let url = format!("/api/v1/avatar/{}", result.uuid);
+        let url = format!("/api/v1/avatar/{}", upload_uuid);
 
         HttpResponse::Created().json(serde_json::json!({
             "status": "success",
             "message": "Profile picture uploaded successfully",
             "upload": {
-                "uuid": result.uuid.to_string(),
-                "original_name": result.original_name,
-                "extension": result.extension,
-                "mime_type": result.mime_type,
-                "size_bytes": result.size_bytes,
+                "uuid": upload_uuid.to_string(),
+                "original_name": original_name,
+                "extension": stored.extension,
+                "mime_type": format.mime_type(),
+                "size_bytes": stored.size_bytes,
                 "url": url,
+                "variants": avatars::SIZES.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
                 "created_at": chrono::Utc::now().to_rfc3339()
             }
         }))
@@ -1173,49 +1185,67 @@ impl UploadController {
             }
         };
 
-        // Get upload record from uploads table
-        let upload = match db_upload_read::get_by_uuid(&db, &avatar_uuid).await {
-            Ok(u) => u,
-            Err(_) => {
-                // Avatar UUID exists but upload doesn't - clear the reference
-                let _ = db_user_mutations::update_avatar(&db, user_id, None, None).await;
-                return HttpResponse::Ok()
-                    .json(BaseResponse::success("Profile picture reference cleared"));
-            }
-        };
+        // Get upload record from uploads table (missing: only the reference is cleared)
+        let upload = db_upload_read::get_by_uuid(&db, &avatar_uuid).await.ok();
+
+        if let Some(upload) = &upload {
+            Self::remove_avatar_upload(&db, upload).await;
+        }
 
+        // Clear user's avatar (both UUID and ID)
+        if let Err(e) = db_user_mutations::update_avatar(&db, user_id, None, None).await {
+            tracing::warn!("Failed to clear user avatar: {}", e);
+        }
+        drop(db);
+
+        UserController::profile_changed(&state, &user, vec!["avatar"], user_id).await;
+
+        if upload.is_none() {
+            return HttpResponse::Ok()
+                .json(BaseResponse::success("Profile picture reference cleared"));
+        }
+
+        HttpResponse::Ok().json(BaseResponse::success(
+            "Profile picture deleted successfully",
+        ))
+    }
+
+    /// Delete a profile picture upload: its files, variants and record
+    async fn remove_avatar_upload(
+        db: &sqlx::Pool<sqlx::Postgres>,
+        upload: &db_upload_read::Upload,
+    ) {
         // Check if this upload is used as logo or favicon in site_config
         // If so, clear those references before deleting
-        if let Ok(site_config) = crate::app::db_query::read::site_config::get(&db).await {
+        if let Ok(site_config) = crate::app::db_query::read::site_config::get(db).await {
             use crate::app::db_query::mutations::site_config as site_config_mutations;
 
-            if site_config.logo_uuid == Some(avatar_uuid) {
+            if site_config.logo_uuid == Some(upload.uuid) {
                 tracing::info!("Clearing logo reference from site_config (avatar being deleted)");
-                if let Err(e) = site_config_mutations::update_logo(&db, None).await {
+                if let Err(e) = site_config_mutations::update_logo(db, None).await {
                     tracing::warn!("Failed to clear logo reference: {}", e);
                 }
             }
 
-            if site_config.favicon_uuid == Some(avatar_uuid) {
+            if site_config.favicon_uuid == Some(upload.uuid) {
                 tracing::info!(
                     "Clearing favicon reference from site_config (avatar being deleted)"
                 );
-                if let Err(e) = site_config_mutations::update_favicon(&db, None).await {
+                if let Err(e) = site_config_mutations::update_favicon(db, None).await {
                     tracing::warn!("Failed to clear favicon reference: {}", e);
                 }
             }
         }
 
         // Delete file from storage
-        if let Err(e) = uploads::delete_file(&upload.storage_path).await {
+        if let Err(e) = avatars::delete(&upload.storage_path).await {
             tracing::warn!("Failed to delete avatar file from storage: {}", e);
         }
 
-        // Delete all image variants (files and database records)
-        if let Ok(variants) = image_variant::get_by_upload_id(&db, upload.id).await {
+        // Delete all image variant files
+        if let Ok(variants) = image_variant::get_by_upload_id(db, upload.id).await {
             for variant in variants {
-                // Delete variant file from storage
-                if let Err(e) = uploads::delete_file(&variant.storage_path).await {
+                if let Err(e) = avatars::delete(&variant.storage_path).await {
                     tracing::warn!(
                         "Failed to delete variant file {} from storage: {}",
                         variant.storage_path,
@@ -1227,24 +1257,16 @@ impl UploadController {
         }
 
         // Delete upload record by UUID (CASCADE will delete variant records)
-        if let Err(e) = db_upload_mutations::delete_by_uuid(&db, &avatar_uuid).await {
+        if let Err(e) = db_upload_mutations::delete_by_uuid(db, &upload.uuid).await {
             tracing::warn!("Failed to delete upload record: {}", e);
         }
-
-        // Clear user's avatar (both UUID and ID)
-        if let Err(e) = db_user_mutations::update_avatar(&db, user_id, None, None).await {
-            tracing::warn!("Failed to clear user avatar: {}", e);
-        }
-
-        HttpResponse::Ok().json(BaseResponse::success(
-            "Profile picture deleted successfully",
-        ))
     }
 
     /// GET /api/v1/avatar/{uuid} - Get profile picture from uploads table (auth required)
     /// User can only access their own avatar
     /// Supports both JWT header and cookie authentication (for <img> tags)
-    /// Supports variant query parameter: ?variant=thumb|small|medium|large|full
+    /// Supports variant query parameter: ?variant=thumb|small|medium|large
+    /// Responses carry an ETag; a matching If-None-Match gets 304 Not Modified
     pub async fn get_avatar(
         req: HttpRequest,
         state: web::Data<AppState>,
@@ -1265,7 +1287,7 @@ impl UploadController {
             }
         };
 
-        let db = state.db.lock().await;
+        let db = state.read_db_for(user_id).await;
 
         // Get upload record from uploads table
         let upload = match db_upload_read::get_by_uuid(&db, &uuid).await {
@@ -1276,7 +1298,7 @@ impl UploadController {
         };
 
         // Verify it's a profile picture and belongs to the user
-        if upload.description.as_deref() != Some("profile-picture") {
+        if upload.description.as_deref() != Some(avatars::UPLOAD_DESCRIPTION) {
             return HttpResponse::NotFound().json(BaseResponse::error("Avatar not found"));
         }
 
@@ -1286,77 +1308,68 @@ impl UploadController {
         }
 
         // Check if a variant is requested (e.g., ?variant=thumb)
-        let storage_path = if let Some(variant_name) = query.get("variant") {
-            // Try to get the variant from database
-            match image_variant::get_variant(&db, upload.id, variant_name).await {
-                Ok(variant) => {
-                    // Use the variant's storage path
-                    variant.storage_path
-                }
-                Err(_) => {
-                    // Variant not found, fallback to original
-                    tracing::warn!(
-                        "Variant '{}' not found for avatar {}, serving original",
-                        variant_name,
-                        upload.id
-                    );
-                    upload.storage_path.clone()
+        let variant = match query.get("variant") {
+            Some(variant_name) => {
+                match image_variant::get_variant(&db, upload.id, variant_name).await {
+                    Ok(variant) => Some(variant),
+                    Err(_) => {
+                        // Variant not found (e.g. still processing), fallback to original
+                        tracing::warn!(
+                            "Variant '{}' not found for avatar {}, serving original",
+                            variant_name,
+                            upload.id
+                        );
+                        None
+                    }
                 }
             }
-        } else {
-            upload.storage_path.clone()
+            None => None,
+        };
+        drop(db);
+
+        let (storage_path, mime_type, etag) = match &variant {
+            Some(variant) => (
+                variant.storage_path.clone(),
+                mime_guess::from_path(&variant.stored_name)
+                    .first_or_octet_stream()
+                    .to_string(),
+                avatars::etag(&upload.uuid, &variant.variant_name, variant.size_bytes),
+            ),
+            None => (
+                upload.storage_path.clone(),
+                upload.mime_type.clone(),
+                avatars::etag(&upload.uuid, "original", upload.size_bytes),
+            ),
         };
 
-        // Read file from storage with fallback to variants if original doesn't exist
-        let data = match uploads::read_file(&storage_path).await {
-            Ok(d) => d,
-            Err(_) => {
-                // If original file doesn't exist, try to serve a variant (e.g., _medium)
-                tracing::warn!(
-                    "Original avatar file not found at {}, trying variant",
-                    storage_path
-                );
-
-                // Try common variants in order of preference: medium, large, small, thumb
-                let variants_to_try = vec!["_medium", "_large", "_small", "_thumb"];
-                let mut found_data = None;
-
-                for variant_suffix in variants_to_try {
-                    // Insert variant suffix before file extension
-                    let path_with_variant = if let Some(dot_pos) = storage_path.rfind('.') {
-                        format!(
-                            "{}{}{}",
-                            &storage_path[..dot_pos],
-                            variant_suffix,
-                            &storage_path[dot_pos..]
-                        )
-                    } else {
-                        format!("{}{}", storage_path, variant_suffix)
-                    };
-
-                    if let Ok(variant_data) = uploads::read_file(&path_with_variant).await {
-                        tracing::info!("Serving avatar variant: {}", path_with_variant);
-                        found_data = Some(variant_data);
-                        break;
-                    }
-                }
+        let not_modified = req
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| avatars::etag_matches(value, &etag));
+        if not_modified {
+            return HttpResponse::NotModified()
+                .insert_header((header::ETAG, etag))
+                .insert_header(("Cache-Control", "private, max-age=86400"))
+                .finish();
+        }
 
-                match found_data {
-                    Some(d) => d,
-                    None => {
-                        return HttpResponse::InternalServerError()
-                            .json(BaseResponse::error("Failed to read file"));
-                    }
-                }
+        let data = match avatars::read(&storage_path).await {
+            Ok(d) => d,
+            Err(e) => {
+                tracing::warn!("Failed to read avatar file {}: {}", storage_path, e);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to read file"));
             }
         };
 
         HttpResponse::Ok()
-            .content_type(upload.mime_type)
+            .content_type(mime_type)
             .insert_header((
                 "Content-Disposition",
                 format!("inline; filename=\"{}\"", upload.original_name),
             ))
+            .insert_header((header::ETAG, etag))
             .insert_header(("Cache-Control", "private, max-age=86400")) // Private cache for 24 hours
             .body(data)
     }
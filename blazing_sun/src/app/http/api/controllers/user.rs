@@ -12,15 +12,16 @@
 
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use serde::Deserialize;
-use std::collections::HashMap;
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::app::cache::UserProfileCache;
 use crate::app::http::api::controllers::responses::{
     BaseResponse, MissingFieldsResponse, UserDto, ValidationErrorResponse,
 };
 use crate::app::http::api::validators::auth::validate_password;
+use crate::app::http::api::validators::{validate_request, FieldError};
 use crate::app::http::api::validators::user::{
     PatchUserRequest, PatchUserRequestRaw, PutUserRequest,
 };
@@ -28,7 +29,8 @@ use crate::config::ActivationConfig;
 use crate::database::mutations::activation_hash as db_activation_hash;
 use crate::database::mutations::user as db_mutations;
 use crate::database::read::user as db_user;
-use crate::database::AppState;
+use crate::database::{replica, AppState};
+use crate::events;
 use crate::mq;
 use crate::mq::jobs::email::{EmailTemplate, SendEmailParams};
 use crate::mq::JobOptions;
@@ -75,7 +77,7 @@ impl UserController {
             }
         };
         info!("User ID: {}", user_id);
-        let db = state.db.lock().await;
+        let db = state.read_db_for(user_id).await;
 
         match db_user::get_by_id(&db, user_id).await {
             Ok(user) => HttpResponse::Ok().json(UserResponse {
@@ -106,7 +108,7 @@ impl UserController {
     /// - 404: User not found
     pub async fn get_by_id(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
         let user_id = path.into_inner();
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
 
         match db_user::get_by_id(&db, user_id).await {
             Ok(user) => HttpResponse::Ok().json(UserResponse {
@@ -181,47 +183,33 @@ impl UserController {
         };
 
         // Validate fields
-        let mut errors: HashMap<String, Vec<String>> = HashMap::new();
-        if let Err(validation_errors) = update_data.validate() {
-            for (field, field_errors) in validation_errors.field_errors() {
-                let messages: Vec<String> = field_errors
-                    .iter()
-                    .map(|e| {
-                        e.message
-                            .as_ref()
-                            .map(|m| m.to_string())
-                            .unwrap_or_else(|| e.code.to_string())
-                    })
-                    .collect();
-                errors.insert(field.to_string(), messages);
-            }
-        }
+        let mut errors = validate_request(&update_data);
 
         // Validate password if provided
         let password_errors = update_data.validate_password_if_present();
-        if !password_errors.is_empty() {
-            errors
-                .entry("password".to_string())
-                .or_default()
-                .extend(password_errors);
-        }
+        errors.extend(FieldError::each("password", "password_policy", password_errors));
 
         // Validate balance if provided
         if let Some(balance) = update_data.balance {
             if balance < 0 {
-                errors
-                    .entry("balance".to_string())
-                    .or_default()
-                    .push("must be non-negative".to_string());
+                errors.push(FieldError::new("balance", "range", "must be non-negative"));
             }
         }
 
         if !errors.is_empty() {
-            return HttpResponse::BadRequest().json(ValidationErrorResponse::new(errors));
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
         }
 
         let db = state.db.lock().await;
 
+        let mut fields_changed = vec!["first_name", "last_name"];
+        if update_data.balance.is_some() {
+            fields_changed.push("balance");
+        }
+        if update_data.password.is_some() {
+            fields_changed.push("password");
+        }
+
         let params = db_mutations::UpdateUserFullParams {
             first_name: update_data.first_name,
             last_name: update_data.last_name,
@@ -231,22 +219,28 @@ impl UserController {
 
         match db_mutations::update_full(&db, user_id, &params).await {
             Ok(_) => {
+                // The user reads their new balance and profile right away
+                replica::mark_written(user_id).await;
+
                 // Fetch updated user
                 match db_user::get_by_id(&db, user_id).await {
-                    Ok(user) => HttpResponse::Ok().json(UserResponse {
-                        base: BaseResponse::success("User updated successfully"),
-                        user: UserDto {
-                            id: user.id,
-                            email: user.email,
-                            first_name: user.first_name,
-                            last_name: user.last_name,
-                            balance: user.balance,
-                            permissions: user.permissions,
-                            avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
-                            created_at: user.created_at,
-                            updated_at: user.updated_at,
-                        },
-                    }),
+                    Ok(user) => {
+                        Self::profile_changed(&state, &user, fields_changed, user_id).await;
+                        HttpResponse::Ok().json(UserResponse {
+                            base: BaseResponse::success("User updated successfully"),
+                            user: UserDto {
+                                id: user.id,
+                                email: user.email,
+                                first_name: user.first_name,
+                                last_name: user.last_name,
+                                balance: user.balance,
+                                permissions: user.permissions,
+                                avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
+                                created_at: user.created_at,
+                                updated_at: user.updated_at,
+                            },
+                        })
+                    }
                     Err(_) => HttpResponse::InternalServerError()
                         .json(BaseResponse::error("Failed to retrieve updated user")),
                 }
@@ -305,6 +299,16 @@ impl UserController {
 
         let db = state.db.lock().await;
 
+        let fields_changed: Vec<&str> = [
+            ("first_name", update_data.first_name.is_some()),
+            ("last_name", update_data.last_name.is_some()),
+            ("balance", update_data.balance.is_some()),
+            ("password", update_data.password.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect();
+
         let params = db_mutations::UpdateUserPartialParams {
             first_name: update_data.first_name,
             last_name: update_data.last_name,
@@ -314,22 +318,28 @@ impl UserController {
 
         match db_mutations::update_partial(&db, user_id, &params).await {
             Ok(_) => {
+                // The user reads their new balance and profile right away
+                replica::mark_written(user_id).await;
+
                 // Fetch updated user
                 match db_user::get_by_id(&db, user_id).await {
-                    Ok(user) => HttpResponse::Ok().json(UserResponse {
-                        base: BaseResponse::success("User updated successfully"),
-                        user: UserDto {
-                            id: user.id,
-                            email: user.email,
-                            first_name: user.first_name,
-                            last_name: user.last_name,
-                            balance: user.balance,
-                            permissions: user.permissions,
-                            avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
-                            created_at: user.created_at,
-                            updated_at: user.updated_at,
-                        },
-                    }),
+                    Ok(user) => {
+                        Self::profile_changed(&state, &user, fields_changed, user_id).await;
+                        HttpResponse::Ok().json(UserResponse {
+                            base: BaseResponse::success("User updated successfully"),
+                            user: UserDto {
+                                id: user.id,
+                                email: user.email,
+                                first_name: user.first_name,
+                                last_name: user.last_name,
+                                balance: user.balance,
+                                permissions: user.permissions,
+                                avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
+                                created_at: user.created_at,
+                                updated_at: user.updated_at,
+                            },
+                        })
+                    }
                     Err(_) => HttpResponse::InternalServerError()
                         .json(BaseResponse::error("Failed to retrieve updated user")),
                 }
@@ -400,35 +410,15 @@ impl UserController {
             last_name: raw.last_name.unwrap(),
         };
 
-        let mut errors: HashMap<String, Vec<String>> = HashMap::new();
-
         // Validate using validator crate
-        if let Err(validation_errors) = user_data.validate() {
-            for (field, field_errors) in validation_errors.field_errors() {
-                let messages: Vec<String> = field_errors
-                    .iter()
-                    .map(|e| {
-                        e.message
-                            .as_ref()
-                            .map(|m| m.to_string())
-                            .unwrap_or_else(|| e.code.to_string())
-                    })
-                    .collect();
-                errors.insert(field.to_string(), messages);
-            }
-        }
+        let mut errors = validate_request(&user_data);
 
         // Validate password
         let password_errors = validate_password(&user_data.password);
-        if !password_errors.is_empty() {
-            errors
-                .entry("password".to_string())
-                .or_default()
-                .extend(password_errors);
-        }
+        errors.extend(FieldError::each("password", "password_policy", password_errors));
 
         if !errors.is_empty() {
-            return HttpResponse::BadRequest().json(ValidationErrorResponse::new(errors));
+            return HttpResponse::BadRequest().json(ValidationErrorResponse::from_fields(errors));
         }
 
         let db = state.db.lock().await;
@@ -588,20 +578,23 @@ impl UserController {
             Ok(_) => {
                 // Fetch updated user
                 match db_user::get_by_id(&db, user_id).await {
-                    Ok(user) => HttpResponse::Ok().json(UserResponse {
-                        base: BaseResponse::success("Avatar updated successfully"),
-                        user: UserDto {
-                            id: user.id,
-                            email: user.email,
-                            first_name: user.first_name,
-                            last_name: user.last_name,
-                            balance: user.balance,
-                            permissions: user.permissions,
-                            avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
-                            created_at: user.created_at,
-                            updated_at: user.updated_at,
-                        },
-                    }),
+                    Ok(user) => {
+                        Self::profile_changed(&state, &user, vec!["avatar"], user_id).await;
+                        HttpResponse::Ok().json(UserResponse {
+                            base: BaseResponse::success("Avatar updated successfully"),
+                            user: UserDto {
+                                id: user.id,
+                                email: user.email,
+                                first_name: user.first_name,
+                                last_name: user.last_name,
+                                balance: user.balance,
+                                permissions: user.permissions,
+                                avatar_uuid: user.avatar_uuid.map(|u| u.to_string()),
+                                created_at: user.created_at,
+                                updated_at: user.updated_at,
+                            },
+                        })
+                    }
                     Err(_) => HttpResponse::InternalServerError()
                         .json(BaseResponse::error("Failed to retrieve updated user")),
                 }
@@ -613,6 +606,36 @@ impl UserController {
             }
         }
     }
+
+    /// Drop the cached profile of a changed user and publish `user.updated`
+    pub(crate) async fn profile_changed(
+        state: &AppState,
+        user: &db_user::User,
+        fields_changed: Vec<&str>,
+        actor_id: i64,
+    ) {
+        UserProfileCache::new(state.redis()).invalidate(user.id).await;
+
+        let Some(event_bus) = state.event_bus() else {
+            return;
+        };
+
+        let changed = |field: &str| fields_changed.contains(&field);
+        let result = events::publish::user_updated(
+            event_bus,
+            user.id,
+            fields_changed.iter().map(|field| field.to_string()).collect(),
+            changed("first_name").then(|| user.first_name.clone()),
+            changed("last_name").then(|| user.last_name.clone()),
+            changed("balance").then_some(user.balance),
+            Some(actor_id),
+        )
+        .await;
+
+        if let Err(e) = result {
+            warn!("Failed to publish user.updated event: {}", e);
+        }
+    }
 }
 
 /// Request to update user avatar
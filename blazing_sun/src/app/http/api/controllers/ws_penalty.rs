@@ -0,0 +1,119 @@
+//!
+//! WebSocket Penalty Controller
+//!
+//! Admin view of the gateway's flood penalties (see `app::flood_penalties`):
+//! - GET /api/v1/admin/ws/penalties: Users with strikes or a mute
+//! - GET /api/v1/admin/ws/penalties/{user_id}: One user's strikes and mute
+//! - DELETE /api/v1/admin/ws/penalties/{user_id}: Clear strikes and lift the mute
+//!
+
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::app::flood_penalties::{FloodPenalties, FloodPenalty};
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::database::AppState;
+
+/// Default and largest number of users listed
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 500;
+
+/// WebSocket Penalty Controller
+pub struct WsPenaltyController;
+
+/// Single penalty response
+#[derive(Debug, Serialize)]
+pub struct WsPenaltyResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub penalty: FloodPenalty,
+}
+
+/// Penalty list response
+#[derive(Debug, Serialize)]
+pub struct WsPenaltyListResponse {
+    #[serde(flatten)]
+    pub base: BaseResponse,
+    pub penalties: Vec<FloodPenalty>,
+}
+
+/// List query
+#[derive(Debug, Deserialize)]
+pub struct WsPenaltyListQuery {
+    pub limit: Option<usize>,
+}
+
+fn redis_unavailable() -> HttpResponse {
+    HttpResponse::ServiceUnavailable().json(BaseResponse::error("Redis is not available"))
+}
+
+impl WsPenaltyController {
+    /// GET /api/v1/admin/ws/penalties - Penalized users, most recent first
+    ///
+    /// # Query
+    /// - limit: number of users (default 100, max 500)
+    pub async fn list(
+        state: web::Data<AppState>,
+        query: web::Query<WsPenaltyListQuery>,
+    ) -> HttpResponse {
+        let Some(penalties) = state.redis().map(FloodPenalties::new) else {
+            return redis_unavailable();
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        match penalties.list(limit).await {
+            Ok(penalties) => HttpResponse::Ok().json(WsPenaltyListResponse {
+                base: BaseResponse::success("Penalties retrieved"),
+                penalties,
+            }),
+            Err(e) => {
+                error!("Failed to list flood penalties: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve penalties"))
+            }
+        }
+    }
+
+    /// GET /api/v1/admin/ws/penalties/{user_id} - One user's penalty
+    pub async fn show(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let Some(penalties) = state.redis().map(FloodPenalties::new) else {
+            return redis_unavailable();
+        };
+        let user_id = path.into_inner();
+
+        match penalties.get(user_id).await {
+            Ok(Some(penalty)) => HttpResponse::Ok().json(WsPenaltyResponse {
+                base: BaseResponse::success("Penalty retrieved"),
+                penalty,
+            }),
+            Ok(None) => HttpResponse::NotFound().json(BaseResponse::error("User has no penalty")),
+            Err(e) => {
+                error!("Failed to load flood penalty of user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to retrieve penalties"))
+            }
+        }
+    }
+
+    /// DELETE /api/v1/admin/ws/penalties/{user_id} - Clear strikes and lift the mute
+    pub async fn clear(state: web::Data<AppState>, path: web::Path<i64>) -> HttpResponse {
+        let Some(penalties) = state.redis().map(FloodPenalties::new) else {
+            return redis_unavailable();
+        };
+        let user_id = path.into_inner();
+
+        match penalties.clear(user_id).await {
+            Ok(true) => {
+                info!(user_id = %user_id, "Flood penalty cleared");
+                HttpResponse::Ok().json(BaseResponse::success("Penalty cleared"))
+            }
+            Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("User has no penalty")),
+            Err(e) => {
+                error!("Failed to clear flood penalty of user {}: {}", user_id, e);
+                HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to clear penalty"))
+            }
+        }
+    }
+}
@@ -113,3 +113,35 @@ pub struct SigninRequest {
     pub password: String,
     pub remember_me: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::http::api::validators::validate_request;
+
+    #[test]
+    fn signup_reports_email_and_name_rules() {
+        let request = SignupRequest {
+            email: "not-an-email".to_string(),
+            password: "Secret1!".to_string(),
+            confirm_password: "Secret1!".to_string(),
+            first_name: "A".to_string(),
+            last_name: "Smith".to_string(),
+        };
+
+        let errors = validate_request(&request);
+        let codes: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|e| (e.field.as_str(), e.code.as_str()))
+            .collect();
+        assert_eq!(codes, vec![("email", "email"), ("first_name", "length")]);
+        assert_eq!(errors[1].constraint, Some(serde_json::json!({ "min": 2 })));
+    }
+
+    #[test]
+    fn password_policy_lists_every_missing_rule() {
+        assert_eq!(validate_password("abc").len(), 4);
+        assert!(validate_password("Secret1!").is_empty());
+        assert!(validate_passwords_match("a", "b").is_some());
+    }
+}
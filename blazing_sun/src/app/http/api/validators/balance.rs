@@ -11,3 +11,21 @@ pub struct BalanceCheckoutRequest {
     #[validate(range(min = 1, message = "must be at least 1"))]
     pub amount: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::http::api::validators::validate_request;
+
+    #[test]
+    fn amount_must_be_positive() {
+        let errors = validate_request(&BalanceCheckoutRequest { amount: 0 });
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "amount");
+        assert_eq!(errors[0].code, "range");
+        assert_eq!(errors[0].constraint, Some(serde_json::json!({ "min": 1 })));
+
+        assert!(validate_request(&BalanceCheckoutRequest { amount: 1 }).is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Field Errors
+//!
+//! Standard shape of a single validation failure. Every validation response
+//! (`ValidationErrorResponse`, `json_error_handler`) carries a list of these,
+//! so clients can branch on `code` instead of parsing messages.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use validator::{Validate, ValidationErrors};
+
+/// One failed rule on one field
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    /// Machine-readable rule name (`required`, `range`, `length`, `email`, ...)
+    pub code: String,
+    pub message: String,
+    /// Rule parameters, e.g. `{"min": 1}` for `range`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<Value>,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+            constraint: None,
+        }
+    }
+
+    pub fn with_constraint(mut self, constraint: Value) -> Self {
+        self.constraint = Some(constraint);
+        self
+    }
+
+    /// `{field} is required`
+    pub fn required(field: &str) -> Self {
+        Self::new(field, "required", format!("{} is required", field))
+    }
+
+    /// One error per message, for the hand-written rule helpers that return `Vec<String>`
+    pub fn each<'a>(
+        field: &'a str,
+        code: &str,
+        messages: Vec<String>,
+    ) -> impl Iterator<Item = Self> + 'a {
+        let code = code.to_string();
+        messages
+            .into_iter()
+            .map(move |message| Self::new(field, code.clone(), message))
+    }
+}
+
+/// Flatten `validator` errors into field errors, ordered by field name
+pub fn from_validation_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut fields: Vec<_> = errors.field_errors().into_iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+    fields
+        .into_iter()
+        .flat_map(|(field, field_errors)| {
+            field_errors.iter().map(move |error| {
+                let constraint: Map<String, Value> = error
+                    .params
+                    .iter()
+                    .filter(|(name, _)| name.as_ref() != "value")
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect();
+
+                FieldError {
+                    field: field.to_string(),
+                    code: error.code.to_string(),
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| error.code.to_string()),
+                    constraint: (!constraint.is_empty()).then_some(Value::Object(constraint)),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Run a request's `validator` rules, returning every failure (empty = valid)
+pub fn validate_request<T: Validate>(request: &T) -> Vec<FieldError> {
+    match request.validate() {
+        Ok(()) => Vec::new(),
+        Err(errors) => from_validation_errors(&errors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Validate)]
+    struct Sample {
+        #[validate(range(min = 1, message = "must be at least 1"))]
+        amount: i64,
+        #[validate(length(min = 2))]
+        name: String,
+    }
+
+    #[test]
+    fn validator_errors_carry_code_message_and_constraint() {
+        let errors = validate_request(&Sample {
+            amount: 0,
+            name: "a".to_string(),
+        });
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].field, "amount");
+        assert_eq!(errors[0].code, "range");
+        assert_eq!(errors[0].message, "must be at least 1");
+        assert_eq!(errors[0].constraint, Some(json!({ "min": 1 })));
+
+        // No message configured: the code doubles as the message
+        assert_eq!(errors[1].field, "name");
+        assert_eq!(errors[1].message, "length");
+    }
+
+    #[test]
+    fn valid_requests_have_no_errors() {
+        let sample = Sample {
+            amount: 5,
+            name: "ok".to_string(),
+        };
+        assert!(validate_request(&sample).is_empty());
+    }
+}
@@ -5,6 +5,7 @@
 
 pub mod auth;
 pub mod balance;
+pub mod field_error;
 pub mod user;
 
 // Re-export common validators
@@ -12,4 +13,5 @@ pub use auth::{
     validate_password, SigninRequest, SigninRequestRaw, SignupRequest, SignupRequestRaw,
 };
 pub use balance::{BalanceCheckoutRequest, BalanceCheckoutRequestRaw};
+pub use field_error::{validate_request, FieldError};
 pub use user::{PatchUserRequest, PatchUserRequestRaw, PutUserRequest};
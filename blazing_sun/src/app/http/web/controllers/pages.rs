@@ -18,6 +18,7 @@ use crate::app::db_query::read::page_hreflang as db_page_hreflang;
 use crate::app::db_query::read::page_schema as db_page_schema;
 use crate::app::db_query::read::page_seo as db_page_seo;
 use crate::app::db_query::read::schema_entity as db_schema_entity;
+use crate::bootstrap::includes::theme::preview;
 use crate::bootstrap::routes::controller::api::{
     get_route_registry_snapshot, route_with_lang, DEFAULT_LANG,
 };
@@ -26,6 +27,7 @@ use crate::bootstrap::utility::csrf;
 use crate::bootstrap::utility::template::{
     get_assets_version, get_images_version, register_template_functions,
 };
+use crate::config::ThemeConfig;
 use crate::database::read::site_config as db_site_config;
 use crate::database::read::user as db_user;
 use crate::database::AppState;
@@ -446,6 +448,16 @@ impl PagesController {
             .unwrap_or_else(|| "light".to_string())
     }
 
+    /// Stylesheet of the preview named by `?theme_preview=`; admins only
+    fn get_theme_preview_css(req: &HttpRequest, is_admin: bool) -> Option<String> {
+        if !is_admin {
+            return None;
+        }
+        let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).ok()?;
+        let token = query.get(preview::PREVIEW_QUERY_PARAM)?;
+        preview::exists(ThemeConfig::preview_path(), token).then(|| preview::stylesheet_url(token))
+    }
+
     /// Create base context with common variables and auth info
     fn base_context(req: &HttpRequest, session: &Session) -> Context {
         let auth = is_logged(req);
@@ -463,6 +475,10 @@ impl PagesController {
         // Asset versioning for cache busting
         context.insert("assets_version", get_assets_version());
         context.insert("images_version", get_images_version());
+        // Unpublished theme build opened from the admin theme page
+        if let Some(stylesheet) = Self::get_theme_preview_css(req, auth.is_admin()) {
+            context.insert("theme_preview_css", &stylesheet);
+        }
         if let Some(user_id) = auth.user_id {
             context.insert("user_id", &user_id);
         }
@@ -494,6 +510,15 @@ impl PagesController {
         context
     }
 
+    /// Pool for a page's reads; a signed-in user reads back their own
+    /// profile and balance, so gets `read_db_for`
+    async fn page_db(state: &AppState, user_id: Option<i64>) -> Pool<Postgres> {
+        match user_id {
+            Some(user_id) => state.read_db_for(user_id).await,
+            None => state.read_db().await,
+        }
+    }
+
     /// Add branding info (logo, favicon, site identity) to template context
     /// Uses ID-based asset rendering - automatically determines public/private from database
     async fn add_branding_to_context(context: &mut Context, db: &Pool<Postgres>) {
@@ -1085,7 +1110,7 @@ impl PagesController {
         let mut context = Self::base_context(&req, &session);
 
         // Add branding (logo, favicon, site name)
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.home").await;
@@ -1112,7 +1137,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.sign_up").await;
@@ -1133,7 +1158,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.sign_in").await;
@@ -1154,7 +1179,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.forgot_password").await;
@@ -1175,7 +1200,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
 
         // Add branding (logo, favicon, site name)
         Self::add_branding_to_context(&mut context, &db).await;
@@ -1222,7 +1247,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
 
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
@@ -1253,7 +1278,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
 
         // Add branding (logo, favicon, site name)
         Self::add_branding_to_context(&mut context, &db).await;
@@ -1317,7 +1342,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "admin.uploads").await;
@@ -1345,7 +1370,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "admin.theme").await;
@@ -1373,7 +1398,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "admin.game_chat").await;
@@ -1401,7 +1426,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "admin.users").await;
@@ -1427,7 +1452,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.galleries").await;
@@ -1450,7 +1475,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.geo_galleries").await;
@@ -1472,7 +1497,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.geo_gallery").await;
@@ -1500,7 +1525,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.competitions").await;
@@ -1523,7 +1548,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games").await;
@@ -1569,7 +1594,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games.bigger_dice_lobby").await;
@@ -1640,7 +1665,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games.bigger_dice").await;
@@ -1703,7 +1728,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games.tic_tac_toe_lobby").await;
@@ -1774,7 +1799,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games.tic_tac_toe").await;
@@ -1837,7 +1862,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games.roulette_lobby").await;
@@ -1901,7 +1926,7 @@ impl PagesController {
         }
 
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = Self::page_db(&state, auth.user_id).await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         Self::add_seo_to_context(&req, &mut context, &db, "web.games.roulette").await;
@@ -1946,7 +1971,7 @@ impl PagesController {
         state: web::Data<AppState>,
     ) -> Result<HttpResponse> {
         let mut context = Self::base_context(&req, &session);
-        let db = state.db.lock().await;
+        let db = state.read_db().await;
         Self::add_branding_to_context(&mut context, &db).await;
         Self::add_languages_to_context(&mut context, &db).await;
         drop(db);
@@ -2056,7 +2081,7 @@ pub async fn render_oauth_consent(
     let mut context = PagesController::base_context(req, session);
 
     // Add branding (logo, favicon, site name) and languages
-    let db = state.db.lock().await;
+    let db = state.read_db().await;
     PagesController::add_branding_to_context(&mut context, &db).await;
     PagesController::add_languages_to_context(&mut context, &db).await;
     drop(db);
@@ -0,0 +1,84 @@
+//! Admin impersonation
+//!
+//! Support admins can act as a user for a limited time to see exactly what
+//! the user sees. `POST /api/v1/admin/impersonate/{user_id}` opens an
+//! `impersonation_sessions` row and signs a JWT for the user that also names
+//! the admin and the session.
+//!
+//! `verify_jwt` refuses the token once its session has ended or expired. While
+//! it handles a request made with the token, the session is held in a
+//! task-local (like the request id), so every domain event published for the
+//! request carries `impersonator_id` and `impersonation_id` in its metadata.
+
+use std::future::Future;
+
+use uuid::Uuid;
+
+use crate::config::JwtConfig;
+
+/// Session length when the admin does not ask for one
+const DEFAULT_SESSION_MINUTES: i64 = 15;
+
+tokio::task_local! {
+    static CURRENT: Impersonation;
+}
+
+/// The admin behind a request made with an impersonation token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impersonation {
+    pub session_id: Uuid,
+    pub impersonator_id: i64,
+}
+
+/// Impersonation session of the request the current task is handling, if any
+pub fn current() -> Option<Impersonation> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+/// Run `future` with `impersonation` as the [`current`] one; with `None` the
+/// future runs as is
+pub async fn scope<F: Future>(impersonation: Option<Impersonation>, future: F) -> F::Output {
+    match impersonation {
+        Some(impersonation) => CURRENT.scope(impersonation, future).await,
+        None => future.await,
+    }
+}
+
+/// Length of a new session: the requested minutes, at most
+/// `IMPERSONATION_MAX_MINUTES`
+pub fn session_minutes(requested: Option<i64>) -> i64 {
+    clamp_minutes(requested, JwtConfig::impersonation_max_minutes())
+}
+
+fn clamp_minutes(requested: Option<i64>, max_minutes: i64) -> i64 {
+    let max_minutes = max_minutes.max(1);
+    requested
+        .unwrap_or(DEFAULT_SESSION_MINUTES)
+        .clamp(1, max_minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_length_is_capped() {
+        assert_eq!(clamp_minutes(None, 30), 15);
+        assert_eq!(clamp_minutes(None, 10), 10);
+        assert_eq!(clamp_minutes(Some(120), 30), 30);
+        assert_eq!(clamp_minutes(Some(0), 30), 1);
+    }
+
+    #[tokio::test]
+    async fn scope_sets_current() {
+        let impersonation = Impersonation {
+            session_id: Uuid::new_v4(),
+            impersonator_id: 1,
+        };
+        assert_eq!(current(), None);
+
+        let inside = scope(Some(impersonation.clone()), async { current() }).await;
+        assert_eq!(inside, Some(impersonation));
+        assert_eq!(scope(None, async { current() }).await, None);
+    }
+}
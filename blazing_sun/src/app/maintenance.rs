@@ -0,0 +1,32 @@
+//! Read-only maintenance mode
+//!
+//! The process-wide view of the shared maintenance flag (see the
+//! `maintenance_mode` crate). While it is on, the `read_only` middleware turns
+//! mutating requests away with 503 and the MQ processor stops dequeueing.
+//! Admins switch it for every service with `/api/v1/admin/maintenance`.
+
+use maintenance_mode::{Maintenance, Notice};
+use once_cell::sync::OnceCell;
+
+use crate::database::SharedRedis;
+
+static MAINTENANCE: OnceCell<Maintenance> = OnceCell::new();
+
+/// Start following the flag in `redis`; a second call is ignored
+pub fn init(redis: Option<SharedRedis>) {
+    let maintenance = Maintenance::new(redis);
+    if maintenance.is_forced() {
+        tracing::warn!("MAINTENANCE_MODE is set - writes are rejected until it is removed");
+    }
+    let _ = MAINTENANCE.set(maintenance);
+}
+
+/// The flag, once [`init`] has run
+pub fn get() -> Option<&'static Maintenance> {
+    MAINTENANCE.get()
+}
+
+/// The active maintenance window, if any
+pub async fn current() -> Option<Notice> {
+    get()?.current().await
+}
@@ -5,13 +5,41 @@
 //! - Cron jobs (scheduled tasks)
 //! - Message queue (RabbitMQ for async tasks)
 //! - Database queries (read/mutations)
+//! - Feature flags (runtime toggles with gradual rollouts)
+//! - Flood penalties (WebSocket rate limit strikes and mutes, for admins)
 //! - Chat (real-time messaging via WebSocket gateway)
 //! - Games (real-time multiplayer games via WebSocket gateway)
+//! - Analytics (MongoDB projections of game and checkout events)
+//! - Cache (in-process + Redis two-tier cache, e.g. user profiles)
+//! - Data exports (zip of a user's data behind a signed, expiring download link)
+//! - Notifications (per-user delivery preferences for payments, invites, mentions)
+//! - Achievements (badges unlocked by games played, won and money spent)
+//! - Avatars (profile picture validation, square variants, cache validators)
+//! - Impersonation (time-boxed admin sessions acting as a user, tagged in audit events)
+//! - Maintenance (read-only mode shared with checkout and the WebSocket gateway)
+//! - Seed (demo users, rooms and balance history for local development)
+//! - Sessions (signed-in devices, revoked through a Redis denylist)
+//! - Two-factor authentication (TOTP codes, recovery codes, per-role enforcement)
+//! - User settings (theme, language and sounds, validated against a schema)
 
+pub mod achievements;
+pub mod analytics;
+pub mod avatars;
+pub mod cache;
 pub mod chat;
 pub mod checkout;
 pub mod cron;
+pub mod data_exports;
 pub mod db_query;
+pub mod feature_flags;
+pub mod flood_penalties;
 pub mod games;
 pub mod http;
+pub mod impersonation;
+pub mod maintenance;
 pub mod mq;
+pub mod notifications;
+pub mod seed;
+pub mod sessions;
+pub mod two_factor;
+pub mod user_settings;
@@ -3,10 +3,13 @@ use sqlx::{Pool, Postgres};
 use tracing::info;
 
 use crate::app::db_query::mutations::picture as db_picture_mutations;
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct BulkDeletePicturesParams {
+    #[validate(range(min = 1))]
     pub gallery_id: i64,
+    #[validate(length(min = 1))]
     pub picture_ids: Vec<i64>,
 }
 
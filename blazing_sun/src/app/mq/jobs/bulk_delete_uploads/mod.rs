@@ -3,9 +3,11 @@ use sqlx::{Pool, Postgres};
 use tracing::{info, warn};
 
 use crate::app::mq::jobs::delete_upload::{self, DeleteUploadParams};
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct BulkDeleteUploadsParams {
+    #[validate(length(min = 1))]
     pub upload_uuids: Vec<String>,
 }
 
@@ -3,10 +3,13 @@ use sqlx::{Pool, Postgres};
 use tracing::info;
 
 use crate::app::db_query::mutations::user as db_user_mutations;
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct BulkUserActionParams {
+    #[validate(length(min = 1))]
     pub action: String,
+    #[validate(length(min = 1))]
     pub user_ids: Vec<i64>,
     pub permissions: Option<i16>,
 }
@@ -2,10 +2,12 @@ use crate::database::mutations::user::{self, CreateUserParams as DbCreateUserPar
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
 use tracing::info;
+use validator::Validate;
 
 /// Parameters for create_user job (serializable for queue)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateUserParams {
+    #[validate(email)]
     pub email: String,
     pub password: String,
     pub first_name: String,
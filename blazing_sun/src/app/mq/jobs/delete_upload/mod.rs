@@ -10,9 +10,11 @@ use crate::app::db_query::read::image_variant;
 use crate::app::db_query::read::site_config as site_config_read;
 use crate::app::db_query::read::upload as db_upload_read;
 use crate::bootstrap::includes::controllers::uploads;
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct DeleteUploadParams {
+    #[validate(length(min = 1))]
     pub upload_uuid: String,
 }
 
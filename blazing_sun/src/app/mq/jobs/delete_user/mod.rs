@@ -3,9 +3,11 @@ use sqlx::{Pool, Postgres};
 use tracing::info;
 
 use crate::app::db_query::mutations::user as db_user_mutations;
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct DeleteUserParams {
+    #[validate(range(min = 1))]
     pub user_id: i64,
 }
 
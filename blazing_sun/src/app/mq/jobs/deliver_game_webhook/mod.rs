@@ -0,0 +1,120 @@
+//! Delivery of one game room webhook
+//!
+//! POSTs the stored payload to the webhook URL, signed with the webhook
+//! secret (see `app::games::webhooks`). Any 2xx answer counts as delivered.
+//! Otherwise the attempt is logged on the delivery and, until
+//! GAME_WEBHOOK_MAX_ATTEMPTS is reached, the delivery is rescheduled with
+//! exponential backoff; the `game_webhooks` cron queues it again when due.
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::db_query::mutations::game_webhooks as db_mutations;
+use crate::app::db_query::read::game_webhooks as db_read;
+use crate::app::games::webhooks;
+use crate::config::GamesConfig;
+use validator::Validate;
+
+/// Longest error text kept on a delivery
+const MAX_ERROR_LENGTH: usize = 500;
+
+static HTTP: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(GamesConfig::webhook_timeout_seconds()))
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct DeliverGameWebhookParams {
+    #[validate(range(min = 1))]
+    pub delivery_id: i64,
+}
+
+pub async fn execute(
+    db: &Pool<Postgres>,
+    params: &DeliverGameWebhookParams,
+) -> Result<serde_json::Value, String> {
+    let delivery = db_read::get_delivery(db, params.delivery_id)
+        .await
+        .map_err(|e| format!("Failed to load delivery: {}", e))?
+        .ok_or_else(|| "Delivery not found".to_string())?;
+
+    // Redelivered or handled by an earlier copy of this job
+    if delivery.status != "queued" {
+        return Ok(json!({ "skipped": true, "status": delivery.status }));
+    }
+
+    let webhook = db_read::get_by_id(db, delivery.webhook_id)
+        .await
+        .map_err(|e| format!("Failed to load webhook: {}", e))?
+        .ok_or_else(|| "Webhook not found".to_string())?;
+
+    if !webhook.is_active {
+        db_mutations::mark_attempt_failed(db, delivery.id, None, "Webhook is disabled", None)
+            .await
+            .map_err(|e| format!("Failed to update delivery: {}", e))?;
+        return Ok(json!({ "skipped": true, "status": "failed" }));
+    }
+
+    let body = delivery.payload.to_string();
+    let signature = webhooks::signature(&webhook.secret, Utc::now().timestamp(), &body);
+
+    let result = HTTP
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(webhooks::EVENT_HEADER, &delivery.event_type)
+        .header(webhooks::DELIVERY_HEADER, delivery.id.to_string())
+        .header(webhooks::SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await;
+
+    let (response_status, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            let status = response.status().as_u16() as i32;
+            db_mutations::mark_delivered(db, delivery.id, status)
+                .await
+                .map_err(|e| format!("Failed to update delivery: {}", e))?;
+            info!(
+                "Webhook {} delivery {} ({}) delivered",
+                webhook.id, delivery.id, delivery.event_type
+            );
+            return Ok(json!({ "delivered": true, "response_status": status }));
+        }
+        Ok(response) => (
+            Some(response.status().as_u16() as i32),
+            format!("Endpoint answered {}", response.status()),
+        ),
+        Err(e) => (None, e.to_string()),
+    };
+
+    let attempts = delivery.attempts as u32 + 1;
+    let next_attempt_at = (attempts < GamesConfig::webhook_max_attempts())
+        .then(|| Utc::now() + webhooks::backoff(attempts));
+    let error: String = error.chars().take(MAX_ERROR_LENGTH).collect();
+
+    db_mutations::mark_attempt_failed(db, delivery.id, response_status, &error, next_attempt_at)
+        .await
+        .map_err(|e| format!("Failed to update delivery: {}", e))?;
+
+    warn!(
+        "Webhook {} delivery {} attempt {} failed: {}{}",
+        webhook.id,
+        delivery.id,
+        attempts,
+        error,
+        if next_attempt_at.is_some() { "" } else { " (giving up)" }
+    );
+
+    Ok(json!({
+        "delivered": false,
+        "attempts": attempts,
+        "next_attempt_at": next_attempt_at,
+    }))
+}
@@ -1,12 +1,14 @@
 use crate::bootstrap::includes::controllers::email::{self as email_controller, EmailRecipient};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use validator::Validate;
 
 pub use crate::bootstrap::includes::controllers::email::EmailTemplate;
 
 /// Parameters for send_email job
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct SendEmailParams {
+    #[validate(email)]
     pub to_email: String,
     pub to_name: String,
     pub template: EmailTemplate,
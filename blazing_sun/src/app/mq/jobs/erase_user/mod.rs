@@ -0,0 +1,136 @@
+//! GDPR erasure of a user whose grace period has ended
+//!
+//! Anonymizes the user's PII in Postgres (see `mutations::user_erasure::anonymize_user`)
+//! and MongoDB (chat, channel, game chat messages and game history names), then
+//! publishes user.deleted. Financial records (balance ledger, transactions,
+//! roulette history) are kept; they only reference the user id.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use tracing::{info, warn};
+
+use crate::app::chat::mongodb_channel::MongoChannelClient;
+use crate::app::chat::mongodb_chat::MongoChatClient;
+use crate::app::db_query::mutations::user_erasure::{self as db_mutations, ERASED_CONTENT};
+use crate::app::db_query::read::user_erasure as db_read;
+use crate::app::games::mongodb_game_chat::MongoGameChatClient;
+use crate::app::games::mongodb_games::MongoGameClient;
+use crate::database::create_mongodb;
+use crate::events;
+use crate::mq::{JobProgress, ProgressReporter};
+use validator::Validate;
+
+/// Display name that replaces the user's name in MongoDB documents
+pub const ERASED_USERNAME: &str = "Deleted User";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct EraseUserParams {
+    #[validate(range(min = 1))]
+    pub request_id: i64,
+    #[validate(range(min = 1))]
+    pub user_id: i64,
+}
+
+pub async fn execute(
+    db: &Pool<Postgres>,
+    params: &EraseUserParams,
+    progress: &ProgressReporter<'_>,
+) -> Result<serde_json::Value, String> {
+    info!(
+        "Executing erase_user job for user_id: {} (request {})",
+        params.user_id, params.request_id
+    );
+
+    let request = db_read::get_by_id(db, params.request_id)
+        .await
+        .map_err(|e| format!("Failed to load erasure request: {}", e))?
+        .ok_or_else(|| "Erasure request not found".to_string())?;
+
+    // Cancelled or already handled; nothing to do
+    if request.status != "queued" || request.user_id != params.user_id {
+        info!(
+            "Skipping erasure request {} with status {}",
+            request.id, request.status
+        );
+        return Ok(json!({ "skipped": true, "status": request.status }));
+    }
+
+    match erase(db, params.user_id, progress).await {
+        Ok(email) => {
+            db_mutations::mark_completed(db, request.id)
+                .await
+                .map_err(|e| format!("Failed to complete erasure request: {}", e))?;
+            publish_deleted(params.user_id, &email).await;
+            info!("User {} erased", params.user_id);
+            Ok(json!({ "skipped": false, "user_id": params.user_id }))
+        }
+        Err(e) => {
+            if let Err(db_err) = db_mutations::mark_failed(db, request.id, &e).await {
+                warn!("Failed to record erasure failure: {}", db_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Anonymize Postgres, then MongoDB. Every step is idempotent, so a failed
+/// request can be retried from the start.
+async fn erase(
+    db: &Pool<Postgres>,
+    user_id: i64,
+    progress: &ProgressReporter<'_>,
+) -> Result<String, String> {
+    let step = move |done: u64, message: &str| {
+        progress.report(JobProgress::of(done, 5).message(message))
+    };
+
+    step(0, "Anonymizing account").await;
+    let email = db_mutations::anonymize_user(db, user_id)
+        .await
+        .map_err(|e| format!("Failed to anonymize user: {}", e))?;
+
+    let mongodb = create_mongodb()
+        .await
+        .map_err(|e| format!("MongoDB connection unavailable: {}", e))?;
+
+    step(1, "Erasing private messages").await;
+    MongoChatClient::new(mongodb.clone())
+        .anonymize_sender(user_id, ERASED_CONTENT)
+        .await
+        .map_err(|e| format!("Failed to erase private messages: {}", e))?;
+    step(2, "Erasing channel messages").await;
+    MongoChannelClient::new(mongodb.clone())
+        .anonymize_sender(user_id, ERASED_USERNAME, ERASED_CONTENT)
+        .await
+        .map_err(|e| format!("Failed to erase channel messages: {}", e))?;
+    step(3, "Erasing game chat messages").await;
+    MongoGameChatClient::new(mongodb.clone())
+        .anonymize_user(user_id, ERASED_USERNAME, ERASED_CONTENT)
+        .await
+        .map_err(|e| format!("Failed to erase game chat messages: {}", e))?;
+    step(4, "Erasing game history").await;
+    MongoGameClient::new(mongodb)
+        .anonymize_player(user_id, ERASED_USERNAME)
+        .await
+        .map_err(|e| format!("Failed to erase game history: {}", e))?;
+
+    Ok(email)
+}
+
+/// The erasure is done at this point; a Kafka outage must not undo or retry it
+async fn publish_deleted(user_id: i64, email: &str) {
+    let event_bus = match events::init_producer() {
+        Ok(bus) => bus,
+        Err(e) => {
+            warn!("Kafka unavailable, user.deleted not published: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) =
+        events::publish::user_deleted(&event_bus, user_id, email, Some("gdpr_erasure"), None).await
+    {
+        warn!("Failed to publish user.deleted for user {}: {}", user_id, e);
+    }
+}
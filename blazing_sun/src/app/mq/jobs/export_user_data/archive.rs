@@ -0,0 +1,128 @@
+//! Zip archive of a data export
+//!
+//! Every dataset is written twice: as JSON (complete records) and as CSV
+//! (flat rows for spreadsheets). CSV files use `,` and CRLF line endings.
+
+use serde::Serialize;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+pub struct Archive {
+    zip: ZipWriter<Cursor<Vec<u8>>>,
+    options: SimpleFileOptions,
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Self {
+            zip: ZipWriter::new(Cursor::new(Vec::new())),
+            options: SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+        }
+    }
+
+    pub fn add_file(&mut self, name: &str, content: &[u8]) -> Result<(), String> {
+        self.zip
+            .start_file(name, self.options)
+            .map_err(|e| format!("Failed to add {} to the archive: {}", name, e))?;
+        self.zip
+            .write_all(content)
+            .map_err(|e| format!("Failed to write {} to the archive: {}", name, e))
+    }
+
+    pub fn add_json<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), String> {
+        let json = serde_json::to_vec_pretty(value)
+            .map_err(|e| format!("Failed to encode {}: {}", name, e))?;
+        self.add_file(name, &json)
+    }
+
+    pub fn add_csv(
+        &mut self,
+        name: &str,
+        header: &[&str],
+        rows: &[Vec<String>],
+    ) -> Result<(), String> {
+        self.add_file(name, to_csv(header, rows).as_bytes())
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, String> {
+        self.zip
+            .finish()
+            .map(Cursor::into_inner)
+            .map_err(|e| format!("Failed to finish the archive: {}", e))
+    }
+}
+
+fn to_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+        csv.push_str(&cells.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn csv_cell(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A value as a CSV cell: strings and enums bare, other values as JSON
+pub fn cell<T: Serialize + ?Sized>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(serde_json::Value::Null) | Err(_) => String::new(),
+        Ok(other) => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[test]
+    fn csv_quotes_cells_that_need_it() {
+        let rows = vec![
+            vec!["1".to_string(), "hi, \"you\"".to_string()],
+            vec!["2".to_string(), "two\nlines".to_string()],
+        ];
+        assert_eq!(
+            to_csv(&["id", "content"], &rows),
+            "id,content\r\n1,\"hi, \"\"you\"\"\"\r\n2,\"two\nlines\"\r\n"
+        );
+        assert_eq!(cell(&Some(5)), "5");
+        assert_eq!(cell(&None::<i64>), "");
+        assert_eq!(cell("text"), "text");
+    }
+
+    #[test]
+    fn archives_hold_every_added_file() {
+        let mut archive = Archive::new();
+        archive
+            .add_json("profile.json", &serde_json::json!({ "id": 1 }))
+            .unwrap();
+        archive
+            .add_csv("ledger.csv", &["id"], &[vec!["7".to_string()]])
+            .unwrap();
+        let bytes = archive.finish().unwrap();
+
+        let mut zip = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names: Vec<&str> = zip.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["ledger.csv", "profile.json"]);
+
+        let mut csv = String::new();
+        zip.by_name("ledger.csv")
+            .unwrap()
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(csv, "id\r\n7\r\n");
+    }
+}
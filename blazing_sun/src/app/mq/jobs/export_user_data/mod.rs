@@ -0,0 +1,541 @@
+//! Data export (data portability)
+//!
+//! Gathers everything a user can take with them before deleting their
+//! account into one zip of JSON and CSV files:
+//! - profile, locale, display settings and notification preferences
+//! - checkout transactions (from the checkout service)
+//! - balance ledger
+//! - game history and roulette spins (MongoDB)
+//! - private, channel and game chat messages (MongoDB)
+//!
+//! The archive is stored privately, the export marked ready with the end of
+//! its download window, and user.data_export_ready published so the
+//! notification router tells the user (WebSocket or email).
+
+mod archive;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+use tracing::{info, warn};
+
+use crate::app::chat::mongodb_channel::MongoChannelClient;
+use crate::app::chat::mongodb_chat::MongoChatClient;
+use crate::app::checkout::client as checkout_client;
+use crate::app::data_exports;
+use crate::app::db_query::mutations::user_data_exports as db_mutations;
+use crate::app::db_query::read::balance_ledger as db_ledger;
+use crate::app::db_query::read::notification_preferences as db_notification_preferences;
+use crate::app::db_query::read::user as db_user;
+use crate::app::db_query::read::user_data_exports as db_read;
+use crate::app::db_query::read::user_preferences as db_preferences;
+use crate::app::db_query::read::user_settings as db_settings;
+use crate::app::games::mongodb_game_chat::MongoGameChatClient;
+use crate::app::games::mongodb_games::MongoGameClient;
+use crate::app::games::mongodb_roulette::MongoRouletteClient;
+use crate::bootstrap::includes::storage::{self, Visibility};
+use crate::config::ErasureConfig;
+use crate::database::create_mongodb;
+use crate::events::{self, types::payloads::DataExportReadyPayload};
+use crate::mq::{JobProgress, ProgressReporter};
+use archive::{cell, Archive};
+use validator::Validate;
+
+/// Records loaded per query while walking a user's history
+const PAGE_SIZE: i64 = 500;
+
+/// Storage subfolder of export archives
+pub const STORAGE_SUBFOLDER: &str = "data-exports";
+
+/// Datasets gathered, for progress reporting
+const STEPS: u64 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ExportUserDataParams {
+    #[validate(range(min = 1))]
+    pub export_id: i64,
+    #[validate(range(min = 1))]
+    pub user_id: i64,
+}
+
+pub async fn execute(
+    db: &Pool<Postgres>,
+    params: &ExportUserDataParams,
+    progress: &ProgressReporter<'_>,
+) -> Result<serde_json::Value, String> {
+    info!(
+        "Exporting data of user {} (export {})",
+        params.user_id, params.export_id
+    );
+
+    let export = db_read::get_by_id(db, params.export_id)
+        .await
+        .map_err(|e| format!("Failed to load data export: {}", e))?
+        .ok_or_else(|| "Data export not found".to_string())?;
+
+    let claimed = export.user_id == params.user_id
+        && db_mutations::mark_processing(db, export.id)
+            .await
+            .map_err(|e| format!("Failed to claim data export: {}", e))?;
+    if !claimed {
+        info!(
+            "Skipping data export {} with status {}",
+            export.id, export.status
+        );
+        return Ok(json!({ "skipped": true, "status": export.status }));
+    }
+
+    match build_and_store(db, export.id, params.user_id, progress).await {
+        Ok(ready) => {
+            publish_ready(params.user_id, &ready).await;
+            info!("Data export {} ready", export.id);
+            Ok(json!({
+                "skipped": false,
+                "export_id": export.id,
+                "size_bytes": ready.size_bytes,
+                "expires_at": ready.expires_at,
+            }))
+        }
+        Err(e) => {
+            if let Err(db_err) = db_mutations::mark_failed(db, export.id, &e).await {
+                warn!("Failed to record data export failure: {}", db_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn build_and_store(
+    db: &Pool<Postgres>,
+    export_id: i64,
+    user_id: i64,
+    progress: &ProgressReporter<'_>,
+) -> Result<DataExportReadyPayload, String> {
+    let bytes = build(db, export_id, user_id, progress).await?;
+
+    progress
+        .report(JobProgress::of(STEPS - 1, STEPS).message("Storing archive"))
+        .await;
+    let storage = storage::get_storage().map_err(|e| format!("Storage unavailable: {}", e))?;
+    let stored = storage
+        .put_with_subfolder(
+            &bytes,
+            &format!("data-export-{}.zip", export_id),
+            Visibility::Private,
+            STORAGE_SUBFOLDER,
+        )
+        .await
+        .map_err(|e| format!("Failed to store archive: {}", e))?;
+
+    let expires_at = Utc::now() + Duration::hours(ErasureConfig::export_ttl_hours());
+    let size_bytes = stored.size_bytes as i64;
+    if let Err(e) = db_mutations::mark_ready(
+        db,
+        export_id,
+        &stored.storage_path,
+        size_bytes,
+        &stored.checksum,
+        expires_at,
+    )
+    .await
+    {
+        if let Err(delete_err) = storage.delete(&stored.storage_path).await {
+            warn!(
+                "Failed to delete unused archive {}: {}",
+                stored.storage_path, delete_err
+            );
+        }
+        return Err(format!("Failed to complete data export: {}", e));
+    }
+
+    Ok(DataExportReadyPayload {
+        export_id,
+        download_url: data_exports::download_url(export_id, expires_at),
+        size_bytes,
+        expires_at,
+    })
+}
+
+/// The zip archive of every dataset
+async fn build(
+    db: &Pool<Postgres>,
+    export_id: i64,
+    user_id: i64,
+    progress: &ProgressReporter<'_>,
+) -> Result<Vec<u8>, String> {
+    let step = move |done: u64, message: &str| {
+        progress.report(JobProgress::of(done, STEPS).message(message))
+    };
+    let mut archive = Archive::new();
+    let mut counts = serde_json::Map::new();
+
+    step(0, "Exporting profile").await;
+    archive.add_json("profile.json", &profile(db, user_id).await?)?;
+
+    step(1, "Exporting transactions").await;
+    let transactions = checkout_transactions(user_id).await?;
+    let rows: Vec<Vec<String>> = transactions
+        .iter()
+        .map(|tx| {
+            vec![
+                tx.created_at.to_rfc3339(),
+                tx.request_id.clone(),
+                tx.purpose.clone(),
+                tx.status.clone(),
+                tx.amount_cents.to_string(),
+                tx.currency.clone(),
+                cell(&tx.completed_at),
+                cell(&tx.error_message),
+            ]
+        })
+        .collect();
+    archive.add_json("transactions.json", &transactions)?;
+    archive.add_csv(
+        "transactions.csv",
+        &[
+            "created_at",
+            "request_id",
+            "purpose",
+            "status",
+            "amount_cents",
+            "currency",
+            "completed_at",
+            "error",
+        ],
+        &rows,
+    )?;
+    counts.insert("transactions".to_string(), transactions.len().into());
+
+    step(2, "Exporting balance ledger").await;
+    let ledger = ledger_entries(db, user_id).await?;
+    let rows: Vec<Vec<String>> = ledger
+        .iter()
+        .map(|entry| {
+            vec![
+                entry.created_at.to_rfc3339(),
+                entry.id.to_string(),
+                entry.source.clone(),
+                entry.amount_cents.to_string(),
+                entry.balance_after.to_string(),
+                cell(&entry.reference_id),
+            ]
+        })
+        .collect();
+    archive.add_json("ledger.json", &ledger)?;
+    archive.add_csv(
+        "ledger.csv",
+        &[
+            "created_at",
+            "id",
+            "source",
+            "amount_cents",
+            "balance_after",
+            "reference_id",
+        ],
+        &rows,
+    )?;
+    counts.insert("ledger".to_string(), ledger.len().into());
+
+    let mongodb = create_mongodb()
+        .await
+        .map_err(|e| format!("MongoDB connection unavailable: {}", e))?;
+
+    step(3, "Exporting game history").await;
+    let games_client = MongoGameClient::new(mongodb.clone());
+    let games = &games_client;
+    let history = all_pages(move |skip| games.get_user_games(user_id, PAGE_SIZE, skip))
+        .await
+        .map_err(|e| format!("Failed to load game history: {}", e))?;
+    let rows: Vec<Vec<String>> = history
+        .iter()
+        .map(|game| {
+            let player = game.players.iter().find(|p| p.user_id == user_id);
+            vec![
+                game.finished_at.to_rfc3339(),
+                game.room_id.clone(),
+                game.room_name.clone(),
+                cell(&game.game_type),
+                game.players.len().to_string(),
+                cell(&player.map(|p| p.final_score)),
+                cell(&player.map(|p| p.is_winner)),
+                game.duration_seconds.to_string(),
+            ]
+        })
+        .collect();
+    archive.add_json("game_history.json", &history)?;
+    archive.add_csv(
+        "game_history.csv",
+        &[
+            "finished_at",
+            "room_id",
+            "room_name",
+            "game_type",
+            "players",
+            "final_score",
+            "is_winner",
+            "duration_seconds",
+        ],
+        &rows,
+    )?;
+    counts.insert("game_history".to_string(), history.len().into());
+
+    step(4, "Exporting roulette spins").await;
+    let roulette_client = MongoRouletteClient::new(mongodb.clone());
+    let roulette = &roulette_client;
+    let spins = all_pages(move |skip| roulette.get_user_history(user_id, PAGE_SIZE, skip))
+        .await
+        .map_err(|e| format!("Failed to load roulette history: {}", e))?;
+    let rows: Vec<Vec<String>> = spins
+        .iter()
+        .map(|spin| {
+            vec![
+                spin.created_at.to_rfc3339(),
+                spin.result_number.clone(),
+                spin.result_color.clone(),
+                spin.total_stake.to_string(),
+                spin.payout.to_string(),
+                spin.net_result.to_string(),
+            ]
+        })
+        .collect();
+    archive.add_json("roulette.json", &spins)?;
+    archive.add_csv(
+        "roulette.csv",
+        &[
+            "created_at",
+            "result_number",
+            "result_color",
+            "total_stake",
+            "payout",
+            "net_result",
+        ],
+        &rows,
+    )?;
+    counts.insert("roulette".to_string(), spins.len().into());
+
+    step(5, "Exporting chat messages").await;
+    let chat_client = MongoChatClient::new(mongodb.clone());
+    let chat = &chat_client;
+    let private = all_pages(move |skip| chat.get_user_messages(user_id, PAGE_SIZE, skip))
+        .await
+        .map_err(|e| format!("Failed to load private messages: {}", e))?;
+    let rows: Vec<Vec<String>> = private
+        .iter()
+        .map(|msg| {
+            vec![
+                msg.created_at.to_rfc3339(),
+                cell(&msg.conversation_id),
+                msg.sender_id.to_string(),
+                msg.recipient_id.to_string(),
+                cell(&msg.message_type),
+                msg.content.clone(),
+                cell(&msg.read_at),
+            ]
+        })
+        .collect();
+    archive.add_json("chat/private_messages.json", &private)?;
+    archive.add_csv(
+        "chat/private_messages.csv",
+        &[
+            "created_at",
+            "conversation_id",
+            "sender_id",
+            "recipient_id",
+            "message_type",
+            "content",
+            "read_at",
+        ],
+        &rows,
+    )?;
+    counts.insert("private_messages".to_string(), private.len().into());
+
+    let channel_client = MongoChannelClient::new(mongodb.clone());
+    let channels = &channel_client;
+    let channel_messages =
+        all_pages(move |skip| channels.get_sent_messages(user_id, PAGE_SIZE, skip))
+            .await
+            .map_err(|e| format!("Failed to load channel messages: {}", e))?;
+    let rows: Vec<Vec<String>> = channel_messages
+        .iter()
+        .map(|msg| {
+            vec![
+                msg.created_at.to_rfc3339(),
+                msg.channel_id.to_string(),
+                cell(&msg.message_type),
+                msg.content.clone(),
+            ]
+        })
+        .collect();
+    archive.add_json("chat/channel_messages.json", &channel_messages)?;
+    archive.add_csv(
+        "chat/channel_messages.csv",
+        &["created_at", "channel_id", "message_type", "content"],
+        &rows,
+    )?;
+    counts.insert(
+        "channel_messages".to_string(),
+        channel_messages.len().into(),
+    );
+
+    step(6, "Exporting game chat messages").await;
+    let game_chat_client = MongoGameChatClient::new(mongodb);
+    let game_chat = &game_chat_client;
+    let game_messages =
+        all_pages(move |skip| game_chat.get_sent_messages(user_id, PAGE_SIZE, skip))
+            .await
+            .map_err(|e| format!("Failed to load game chat messages: {}", e))?;
+    let rows: Vec<Vec<String>> = game_messages
+        .iter()
+        .map(|msg| {
+            vec![
+                msg.created_at.to_rfc3339(),
+                msg.room_id.clone(),
+                msg.channel.to_string(),
+                msg.content.clone(),
+                msg.is_moderated.to_string(),
+            ]
+        })
+        .collect();
+    archive.add_json("chat/game_chat_messages.json", &game_messages)?;
+    archive.add_csv(
+        "chat/game_chat_messages.csv",
+        &[
+            "created_at",
+            "room_id",
+            "channel",
+            "content",
+            "is_moderated",
+        ],
+        &rows,
+    )?;
+    counts.insert("game_chat_messages".to_string(), game_messages.len().into());
+
+    archive.add_json(
+        "manifest.json",
+        &json!({
+            "export_id": export_id,
+            "user_id": user_id,
+            "generated_at": Utc::now(),
+            "records": counts,
+        }),
+    )?;
+    archive.finish()
+}
+
+/// Account details and preferences (never the password hash)
+async fn profile(db: &Pool<Postgres>, user_id: i64) -> Result<serde_json::Value, String> {
+    let user = db_user::get_by_id(db, user_id)
+        .await
+        .map_err(|e| format!("Failed to load user: {}", e))?;
+    let locale = db_preferences::get_locale(db, user_id)
+        .await
+        .map_err(|e| format!("Failed to load locale: {}", e))?;
+    let settings = db_settings::get_for_user(db, user_id)
+        .await
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    let notification_preferences = db_notification_preferences::get_for_user(db, user_id)
+        .await
+        .map_err(|e| format!("Failed to load notification preferences: {}", e))?;
+
+    Ok(json!({
+        "id": user.id,
+        "email": user.email,
+        "first_name": user.first_name,
+        "last_name": user.last_name,
+        "balance": user.balance,
+        "activated": user.activated != 0,
+        "verified": user.verified != 0,
+        "two_factor": user.two_factor != 0,
+        "permissions": user.permissions,
+        "avatar_id": user.avatar_id,
+        "created_at": user.created_at,
+        "updated_at": user.updated_at,
+        "locale": locale,
+        "settings": settings,
+        "notification_preferences": notification_preferences,
+    }))
+}
+
+/// Every checkout transaction of the user, newest first
+async fn checkout_transactions(
+    user_id: i64,
+) -> Result<Vec<checkout_client::CheckoutTransaction>, String> {
+    let mut transactions = Vec::new();
+    let mut before: Option<DateTime<Utc>> = None;
+
+    loop {
+        let page = checkout_client::fetch_user_transactions(user_id, before, PAGE_SIZE)
+            .await
+            .map_err(|e| format!("Checkout history unavailable: {}", e))?;
+        let full = page.len() as i64 == PAGE_SIZE;
+        before = page.last().map(|oldest| oldest.created_at);
+        transactions.extend(page);
+        if !full || before.is_none() {
+            return Ok(transactions);
+        }
+    }
+}
+
+/// Every balance ledger entry of the user, newest first
+async fn ledger_entries(
+    db: &Pool<Postgres>,
+    user_id: i64,
+) -> Result<Vec<db_ledger::BalanceLedgerEntry>, String> {
+    let mut entries = Vec::new();
+    let mut before: Option<DateTime<Utc>> = None;
+
+    loop {
+        let page = db_ledger::get_by_user_before(db, user_id, before, PAGE_SIZE)
+            .await
+            .map_err(|e| format!("Failed to load balance ledger: {}", e))?;
+        let full = page.len() as i64 == PAGE_SIZE;
+        before = page.last().map(|oldest| oldest.created_at);
+        entries.extend(page);
+        if !full || before.is_none() {
+            return Ok(entries);
+        }
+    }
+}
+
+/// Load `PAGE_SIZE` records at a time until a short page
+async fn all_pages<T, F, Fut>(mut load: F) -> Result<Vec<T>, mongodb::error::Error>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, mongodb::error::Error>>,
+{
+    let mut records = Vec::new();
+    loop {
+        let page = load(records.len() as u64).await?;
+        let full = page.len() as i64 == PAGE_SIZE;
+        records.extend(page);
+        if !full {
+            return Ok(records);
+        }
+    }
+}
+
+/// The export is ready at this point; a Kafka outage only costs the
+/// notification (the export status endpoint still has the link)
+async fn publish_ready(user_id: i64, ready: &DataExportReadyPayload) {
+    let event_bus = match events::init_producer() {
+        Ok(bus) => bus,
+        Err(e) => {
+            warn!(
+                "Kafka unavailable, user.data_export_ready not published: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    if let Err(e) =
+        events::publish::user_data_export_ready(&event_bus, user_id, ready.clone()).await
+    {
+        warn!(
+            "Failed to publish user.data_export_ready for user {}: {}",
+            user_id, e
+        );
+    }
+}
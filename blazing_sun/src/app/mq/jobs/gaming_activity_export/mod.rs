@@ -0,0 +1,189 @@
+//! Gaming activity export
+//!
+//! Builds a user's annual statement of stakes, winnings and net results:
+//! - Bigger Dice / Tic Tac Toe entry fees and prizes from the checkout service
+//! - Roulette spins from MongoDB
+//!
+//! Rendered as CSV or PDF with locale-aware number and date formatting.
+
+pub mod pdf;
+pub mod statement;
+
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use tracing::info;
+
+use crate::app::checkout::client as checkout_client;
+use crate::app::games::mongodb_roulette::MongoRouletteClient;
+use crate::database::create_mongodb;
+use crate::mq::{JobProgress, ProgressReporter};
+use statement::{ActivityEntry, LocaleFormat, Statement};
+use validator::Validate;
+
+/// Page size used when walking the user's checkout transactions
+const CHECKOUT_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct GamingActivityExportParams {
+    #[validate(range(min = 1))]
+    pub user_id: i64,
+    #[validate(range(min = 2000, max = 9999))]
+    pub year: i32,
+    /// "csv" or "pdf"
+    pub format: String,
+    /// Locale code for number/date formatting, e.g. "en_US" or "sr_RS"
+    pub locale: String,
+}
+
+pub async fn execute(
+    _db: &Pool<Postgres>,
+    params: &GamingActivityExportParams,
+    progress: &ProgressReporter<'_>,
+) -> Result<serde_json::Value, String> {
+    info!(
+        "Exporting {} gaming activity for user {} ({}, {})",
+        params.year, params.user_id, params.format, params.locale
+    );
+
+    let (from, to) = year_bounds(params.year).ok_or_else(|| "Invalid year".to_string())?;
+
+    progress
+        .report(JobProgress::percent(0).message("Loading game transactions"))
+        .await;
+    let mut entries = checkout_entries(params.user_id, from, to).await?;
+
+    progress
+        .report(JobProgress::percent(50).message("Loading roulette history"))
+        .await;
+
+    let mongodb = create_mongodb()
+        .await
+        .map_err(|e| format!("Game history unavailable: {}", e))?;
+    let spins = MongoRouletteClient::new(mongodb)
+        .get_user_history_between(params.user_id, from, to)
+        .await
+        .map_err(|e| format!("Failed to load roulette history: {}", e))?;
+    entries.extend(spins.iter().map(ActivityEntry::from_roulette));
+
+    progress
+        .report(JobProgress::percent(80).message("Rendering statement"))
+        .await;
+    let statement = Statement::new(params.user_id, params.year, entries);
+    let format = LocaleFormat::for_locale(&params.locale);
+    let basename = format!("gaming-activity-{}", params.year);
+
+    let (filename, content_type, content) = match params.format.as_str() {
+        "csv" => (
+            format!("{}.csv", basename),
+            "text/csv; charset=utf-8",
+            statement.to_csv(&format).into_bytes(),
+        ),
+        "pdf" => (
+            format!("{}.pdf", basename),
+            "application/pdf",
+            pdf::render(&text_lines(&statement, &format)),
+        ),
+        other => return Err(format!("Unsupported format: {}", other)),
+    };
+
+    Ok(json!({
+        "filename": filename,
+        "content_type": content_type,
+        "content_base64": base64::engine::general_purpose::STANDARD.encode(content),
+        "entries": statement.entries.len(),
+    }))
+}
+
+/// `[Jan 1 of year, Jan 1 of next year)` in UTC
+fn year_bounds(year: i32) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let from = Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).single()?;
+    let to = Utc.with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0).single()?;
+    Some((from, to))
+}
+
+/// Walk the user's checkout transactions back from the end of the year
+async fn checkout_entries(
+    user_id: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<ActivityEntry>, String> {
+    let mut entries = Vec::new();
+    let mut before = Some(to);
+
+    while let Some(cursor) = before {
+        let page = checkout_client::fetch_user_transactions(user_id, Some(cursor), CHECKOUT_PAGE_SIZE)
+            .await
+            .map_err(|e| format!("Checkout history unavailable: {}", e))?;
+
+        entries.extend(
+            page.iter()
+                .filter(|tx| tx.created_at >= from)
+                .filter_map(ActivityEntry::from_checkout),
+        );
+
+        before = match page.last() {
+            Some(oldest) if page.len() as i64 == CHECKOUT_PAGE_SIZE && oldest.created_at >= from => {
+                Some(oldest.created_at)
+            }
+            _ => None,
+        };
+    }
+
+    Ok(entries)
+}
+
+/// Statement rows laid out as fixed-width text for the PDF
+fn text_lines(statement: &Statement, format: &LocaleFormat) -> Vec<String> {
+    const WIDTHS: [usize; 6] = [12, 13, 26, 14, 14, 14];
+
+    let mut lines = Vec::new();
+    for row in statement.rows(format) {
+        match row.as_slice() {
+            [label, text] if label == "Disclaimer" => {
+                lines.push(String::new());
+                lines.push(format!("{}:", label));
+                lines.extend(wrap(text, pdf::LINE_WIDTH));
+            }
+            [label, value] => lines.push(format!("{:<14}{}", format!("{}:", label), value)),
+            cells => {
+                let line: String = cells
+                    .iter()
+                    .zip(WIDTHS)
+                    .enumerate()
+                    .map(|(i, (cell, width))| {
+                        let cell: String = cell.chars().take(width - 1).collect();
+                        if i >= 3 {
+                            format!("{:>width$}", cell, width = width)
+                        } else {
+                            format!("{:<width$}", cell, width = width)
+                        }
+                    })
+                    .collect();
+                lines.push(line.trim_end().to_string());
+            }
+        }
+    }
+    lines
+}
+
+/// Greedy word wrap
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
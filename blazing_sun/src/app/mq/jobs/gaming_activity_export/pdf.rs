@@ -0,0 +1,103 @@
+//! Minimal text-only PDF writer
+//!
+//! Lays out lines of monospaced text (Courier, one of the standard PDF fonts, so
+//! nothing is embedded) on A4 pages. Enough for tabular statements without
+//! pulling in a PDF library.
+
+/// A4 in points
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 40;
+const FONT_SIZE: u32 = 8;
+const LINE_HEIGHT: u32 = 11;
+
+/// Characters per line at FONT_SIZE (Courier glyphs are 0.6 em wide)
+pub const LINE_WIDTH: usize = ((PAGE_WIDTH - 2 * MARGIN) * 10 / (FONT_SIZE * 6)) as usize;
+
+const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2 * MARGIN) / LINE_HEIGHT) as usize;
+
+/// Render lines of text into a PDF document
+pub fn render(lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(LINES_PER_PAGE).collect()
+    };
+
+    // Object numbers: 1 catalog, 2 page tree, 3 font, then a page + content pair per page
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            id + 1
+        ));
+
+        let mut content = format!(
+            "BT /F1 {} Tf {} TL {} {} Td\n",
+            FONT_SIZE,
+            LINE_HEIGHT,
+            MARGIN,
+            PAGE_HEIGHT - MARGIN
+        );
+        for line in page.iter() {
+            content.push_str(&format!("({}) '\n", escape(line)));
+        }
+        content.push_str("ET");
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+/// Escape a line for a PDF string literal; non-ASCII characters become '?'
+fn escape(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            ' '..='~' => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
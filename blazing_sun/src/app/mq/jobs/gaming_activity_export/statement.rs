@@ -0,0 +1,334 @@
+//! Annual gaming activity statement: entries, totals and locale-aware rendering
+
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::app::checkout::client::CheckoutTransaction;
+use crate::app::games::mongodb_roulette::RouletteHistory;
+
+/// Checkout status of an entry fee deducted for a game
+const STATUS_PARTICIPATION: &str = "game_participation";
+/// Checkout status of a prize credited to a game winner
+const STATUS_PRIZE: &str = "game_prize_won";
+
+pub const DISCLAIMER: &str = "This statement is provided for your convenience and lists stakes and \
+winnings recorded on your account for the selected year. It is not tax advice. Tax treatment of \
+gaming winnings differs between jurisdictions; you are responsible for reporting them where \
+required. Consult a qualified tax advisor if in doubt.";
+
+/// One stake or winning, or a roulette spin (both)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEntry {
+    pub occurred_at: DateTime<Utc>,
+    pub game: String,
+    pub reference: String,
+    pub stake_cents: i64,
+    pub winnings_cents: i64,
+}
+
+impl ActivityEntry {
+    pub fn net_cents(&self) -> i64 {
+        self.winnings_cents - self.stake_cents
+    }
+
+    /// Entry fee or prize recorded by the checkout service; other transactions are ignored
+    pub fn from_checkout(tx: &CheckoutTransaction) -> Option<Self> {
+        let (stake_cents, winnings_cents) = match tx.status.as_str() {
+            STATUS_PARTICIPATION => (tx.amount_cents.abs(), 0),
+            STATUS_PRIZE => (0, tx.amount_cents.abs()),
+            _ => return None,
+        };
+
+        Some(Self {
+            occurred_at: tx.completed_at.unwrap_or(tx.created_at),
+            game: game_from_purpose(&tx.purpose).to_string(),
+            reference: tx.request_id.clone(),
+            stake_cents,
+            winnings_cents,
+        })
+    }
+
+    pub fn from_roulette(spin: &RouletteHistory) -> Self {
+        Self {
+            occurred_at: spin.created_at,
+            game: "roulette".to_string(),
+            reference: spin.id.map(|id| id.to_hex()).unwrap_or_default(),
+            stake_cents: spin.total_stake,
+            winnings_cents: spin.payout,
+        }
+    }
+}
+
+/// Checkout purposes read "PAY BIGGER DICE GAME" / "TIC TAC TOE GAME PRIZE WIN"
+fn game_from_purpose(purpose: &str) -> &'static str {
+    let purpose = purpose.to_ascii_uppercase();
+    if purpose.contains("BIGGER DICE") {
+        "bigger_dice"
+    } else if purpose.contains("TIC TAC TOE") {
+        "tic_tac_toe"
+    } else {
+        "other"
+    }
+}
+
+/// Sums for one game (or the whole year)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    pub entries: i64,
+    pub stake_cents: i64,
+    pub winnings_cents: i64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &ActivityEntry) {
+        self.entries += 1;
+        self.stake_cents += entry.stake_cents;
+        self.winnings_cents += entry.winnings_cents;
+    }
+
+    pub fn net_cents(&self) -> i64 {
+        self.winnings_cents - self.stake_cents
+    }
+}
+
+/// A user's statement for one calendar year
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub user_id: i64,
+    pub year: i32,
+    pub generated_at: DateTime<Utc>,
+    /// Oldest first
+    pub entries: Vec<ActivityEntry>,
+    pub by_game: BTreeMap<String, Totals>,
+    pub total: Totals,
+}
+
+impl Statement {
+    pub fn new(user_id: i64, year: i32, mut entries: Vec<ActivityEntry>) -> Self {
+        entries.sort_by_key(|entry| entry.occurred_at);
+
+        let mut by_game: BTreeMap<String, Totals> = BTreeMap::new();
+        let mut total = Totals::default();
+        for entry in &entries {
+            by_game.entry(entry.game.clone()).or_default().add(entry);
+            total.add(entry);
+        }
+
+        Self {
+            user_id,
+            year,
+            generated_at: Utc::now(),
+            entries,
+            by_game,
+            total,
+        }
+    }
+
+    /// Statement as rows of cells, shared by the CSV and PDF renderers
+    pub fn rows(&self, format: &LocaleFormat) -> Vec<Vec<String>> {
+        let money = |cents: i64| format.money(cents);
+        let mut rows = vec![
+            vec!["Gaming activity statement".to_string(), self.year.to_string()],
+            vec!["User ID".to_string(), self.user_id.to_string()],
+            vec!["Generated".to_string(), format.date(self.generated_at)],
+            vec!["Currency".to_string(), "EUR".to_string()],
+            vec![],
+            vec![
+                "Date".to_string(),
+                "Game".to_string(),
+                "Reference".to_string(),
+                "Stake".to_string(),
+                "Winnings".to_string(),
+                "Net".to_string(),
+            ],
+        ];
+
+        rows.extend(self.entries.iter().map(|entry| {
+            vec![
+                format.date(entry.occurred_at),
+                entry.game.clone(),
+                entry.reference.clone(),
+                money(entry.stake_cents),
+                money(entry.winnings_cents),
+                money(entry.net_cents()),
+            ]
+        }));
+
+        rows.push(vec![]);
+        rows.push(vec![
+            "Totals by game".to_string(),
+            "Entries".to_string(),
+            String::new(),
+            "Stakes".to_string(),
+            "Winnings".to_string(),
+            "Net".to_string(),
+        ]);
+        let total_row = |label: &str, totals: &Totals| {
+            vec![
+                label.to_string(),
+                totals.entries.to_string(),
+                String::new(),
+                money(totals.stake_cents),
+                money(totals.winnings_cents),
+                money(totals.net_cents()),
+            ]
+        };
+        rows.extend(self.by_game.iter().map(|(game, totals)| total_row(game, totals)));
+        rows.push(total_row("Total", &self.total));
+
+        rows.push(vec![]);
+        rows.push(vec!["Disclaimer".to_string(), DISCLAIMER.to_string()]);
+        rows
+    }
+
+    /// CSV using the locale's list separator
+    pub fn to_csv(&self, format: &LocaleFormat) -> String {
+        let separator = format.csv_separator();
+        let mut csv = String::new();
+        for row in self.rows(format) {
+            let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell, separator)).collect();
+            csv.push_str(&cells.join(&separator.to_string()));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+}
+
+fn csv_cell(value: &str, separator: char) -> String {
+    if value.contains(separator) || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Number and date conventions of a locale ("en_US", "sr_RS", "de-DE", ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocaleFormat {
+    decimal: char,
+    group: char,
+    date_pattern: &'static str,
+}
+
+impl LocaleFormat {
+    pub fn for_locale(locale: &str) -> Self {
+        let mut parts = locale.split(['_', '-']);
+        let language = parts.next().unwrap_or("").to_ascii_lowercase();
+        let region = parts.next().unwrap_or("").to_ascii_uppercase();
+
+        let (decimal, group) = match language.as_str() {
+            "fr" | "pl" | "ru" | "sv" | "nb" | "fi" | "cs" => (',', ' '),
+            "de" | "sr" | "hr" | "bs" | "sl" | "es" | "it" | "pt" | "nl" | "tr" | "da" | "ro" => {
+                (',', '.')
+            }
+            _ => ('.', ','),
+        };
+
+        let date_pattern = match (language.as_str(), region.as_str()) {
+            ("en", "US") => "%m/%d/%Y",
+            ("en", _) | ("fr", _) | ("es", _) | ("it", _) | ("pt", _) => "%d/%m/%Y",
+            ("nl", _) => "%d-%m-%Y",
+            ("de", _) | ("sr", _) | ("hr", _) | ("bs", _) | ("sl", _) | ("ru", _) | ("pl", _)
+            | ("fi", _) | ("nb", _) | ("cs", _) | ("tr", _) | ("da", _) | ("ro", _) => "%d.%m.%Y",
+            _ => "%Y-%m-%d",
+        };
+
+        Self {
+            decimal,
+            group,
+            date_pattern,
+        }
+    }
+
+    /// Cents as a decimal amount with grouped thousands ("-1.234,56")
+    pub fn money(&self, cents: i64) -> String {
+        let sign = if cents < 0 { "-" } else { "" };
+        let cents = cents.unsigned_abs();
+        let digits = (cents / 100).to_string();
+
+        let mut whole = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                whole.push(self.group);
+            }
+            whole.push(digit);
+        }
+
+        format!("{}{}{}{:02}", sign, whole, self.decimal, cents % 100)
+    }
+
+    pub fn date(&self, at: DateTime<Utc>) -> String {
+        at.format(self.date_pattern).to_string()
+    }
+
+    /// Spreadsheet apps in decimal-comma locales expect `;` separated CSV
+    pub fn csv_separator(&self) -> char {
+        if self.decimal == ',' {
+            ';'
+        } else {
+            ','
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(game: &str, stake_cents: i64, winnings_cents: i64) -> ActivityEntry {
+        ActivityEntry {
+            occurred_at: Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap(),
+            game: game.to_string(),
+            reference: "ref".to_string(),
+            stake_cents,
+            winnings_cents,
+        }
+    }
+
+    #[test]
+    fn test_locale_money_and_dates() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+
+        let us = LocaleFormat::for_locale("en_US");
+        assert_eq!(us.money(123456789), "1,234,567.89");
+        assert_eq!(us.money(-5), "-0.05");
+        assert_eq!(us.date(at), "03/04/2026");
+
+        let sr = LocaleFormat::for_locale("sr-RS");
+        assert_eq!(sr.money(123456), "1.234,56");
+        assert_eq!(sr.date(at), "04.03.2026");
+        assert_eq!(sr.csv_separator(), ';');
+
+        let unknown = LocaleFormat::for_locale("xx");
+        assert_eq!(unknown.date(at), "2026-03-04");
+    }
+
+    #[test]
+    fn test_totals_per_game() {
+        let statement = Statement::new(
+            7,
+            2026,
+            vec![
+                entry("bigger_dice", 1000, 0),
+                entry("bigger_dice", 0, 1200),
+                entry("roulette", 500, 200),
+            ],
+        );
+
+        assert_eq!(statement.by_game["bigger_dice"].net_cents(), 200);
+        assert_eq!(statement.by_game["roulette"].net_cents(), -300);
+        assert_eq!(statement.total.stake_cents, 1500);
+        assert_eq!(statement.total.winnings_cents, 1400);
+        assert_eq!(statement.total.entries, 3);
+    }
+
+    #[test]
+    fn test_csv_quotes_cells_containing_separator() {
+        let statement = Statement::new(7, 2026, vec![entry("bigger_dice", 123456, 0)]);
+        let csv = statement.to_csv(&LocaleFormat::for_locale("en_US"));
+
+        assert!(csv.contains("\"1,234.56\""));
+        assert!(csv.contains("Disclaimer,This statement"));
+    }
+}
@@ -4,11 +4,16 @@ pub mod bulk_user_action;
 pub mod create_user;
 pub mod delete_upload;
 pub mod delete_user;
+pub mod deliver_game_webhook;
 pub mod email;
+pub mod erase_user;
+pub mod export_user_data;
+pub mod gaming_activity_export;
 pub mod oauth_delete_gallery;
 pub mod oauth_delete_picture;
 pub mod oauth_list_galleries;
 pub mod oauth_list_gallery_images;
+pub mod process_avatar;
 pub mod resize_image;
 
 pub use bulk_delete_pictures::BulkDeletePicturesParams;
@@ -17,9 +22,14 @@ pub use bulk_user_action::BulkUserActionParams;
 pub use create_user::CreateUserParams;
 pub use delete_upload::DeleteUploadParams;
 pub use delete_user::DeleteUserParams;
+pub use deliver_game_webhook::DeliverGameWebhookParams;
 pub use email::{EmailTemplate, SendEmailParams};
+pub use erase_user::EraseUserParams;
+pub use export_user_data::ExportUserDataParams;
+pub use gaming_activity_export::GamingActivityExportParams;
 pub use oauth_delete_gallery::DeleteGalleryParams;
 pub use oauth_delete_picture::DeletePictureParams;
 pub use oauth_list_galleries::ListGalleriesParams;
 pub use oauth_list_gallery_images::ListGalleryImagesParams;
+pub use process_avatar::ProcessAvatarParams;
 pub use resize_image::ResizeImageParams;
@@ -3,10 +3,13 @@ use serde_json::json;
 use sqlx::{Pool, Postgres};
 
 use crate::app::db_query::{mutations as db_mutations, read as db_read};
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct DeletePictureParams {
+    #[validate(range(min = 1))]
     pub picture_id: i64,
+    #[validate(range(min = 1))]
     pub user_id: i64,
     pub client_id: String,
 }
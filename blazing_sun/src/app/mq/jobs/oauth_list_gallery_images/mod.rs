@@ -4,11 +4,15 @@ use sqlx::{Pool, Postgres};
 
 use crate::app::db_query::read as db_read;
 use crate::config::AppConfig;
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct ListGalleryImagesParams {
+    #[validate(range(min = 1))]
     pub gallery_id: i64,
+    #[validate(range(min = 1))]
     pub limit: i64,
+    #[validate(range(min = 0))]
     pub offset: i64,
 }
 
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Parameters for process_avatar job
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ProcessAvatarParams {
+    /// Upload ID (database primary key)
+    #[validate(range(min = 1))]
+    pub upload_id: i64,
+    /// Owner of the profile picture
+    #[validate(range(min = 1))]
+    pub user_id: i64,
+    /// Stored filename of the original
+    pub stored_name: String,
+    /// Storage path of the original (relative, e.g. private/profile-pictures/...)
+    #[validate(length(min = 1))]
+    pub storage_path: String,
+}
+
+impl ProcessAvatarParams {
+    pub fn new(upload_id: i64, user_id: i64, stored_name: &str, storage_path: &str) -> Self {
+        Self {
+            upload_id,
+            user_id,
+            stored_name: stored_name.to_string(),
+            storage_path: storage_path.to_string(),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
 /// Parameters for resize_image job
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct ResizeImageParams {
     /// Upload ID (database primary key)
+    #[validate(range(min = 1))]
     pub upload_id: i64,
     /// UUID of the upload
     pub upload_uuid: String,
@@ -14,6 +16,7 @@ pub struct ResizeImageParams {
     /// Storage type (public/private)
     pub storage_type: String,
     /// Full path to the uploaded file
+    #[validate(length(min = 1))]
     pub file_path: String,
 }
 
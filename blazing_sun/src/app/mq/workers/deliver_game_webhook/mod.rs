@@ -0,0 +1,31 @@
+use crate::app::mq::jobs::deliver_game_webhook::{self, DeliverGameWebhookParams};
+use crate::mq::{JobResult, MessageQueue, QueuedJob, Worker, WorkerResult};
+use async_trait::async_trait;
+use tracing::{error, info};
+
+/// Process a deliver_game_webhook job
+pub struct DeliverGameWebhook;
+
+#[async_trait]
+impl Worker for DeliverGameWebhook {
+    const NAME: &'static str = "deliver_game_webhook";
+    type Payload = DeliverGameWebhookParams;
+
+    async fn handle(
+        mq: &MessageQueue,
+        job: &QueuedJob,
+        params: DeliverGameWebhookParams,
+    ) -> WorkerResult {
+        info!("Processing deliver_game_webhook job: {}", job.id);
+
+        // Endpoint failures are rescheduled on the delivery itself; only our own
+        // errors (database) are retried by the queue
+        match deliver_game_webhook::execute(mq.db(), &params).await {
+            Ok(payload) => Ok(JobResult::Success(payload)),
+            Err(e) => {
+                error!("deliver_game_webhook job {} failed: {}", job.id, e);
+                Ok(JobResult::Retry(e))
+            }
+        }
+    }
+}
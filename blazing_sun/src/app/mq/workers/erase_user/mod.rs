@@ -0,0 +1,27 @@
+use crate::app::mq::jobs::erase_user::{self, EraseUserParams};
+use crate::mq::{JobResult, MessageQueue, ProgressReporter, QueuedJob, Worker, WorkerResult};
+use async_trait::async_trait;
+use tracing::{error, info};
+
+/// Process an erase_user job
+pub struct EraseUser;
+
+#[async_trait]
+impl Worker for EraseUser {
+    const NAME: &'static str = "erase_user";
+    /// Erasures rewrite many tables; one at a time keeps the database responsive
+    const CONCURRENCY: usize = 1;
+    type Payload = EraseUserParams;
+
+    async fn handle(mq: &MessageQueue, job: &QueuedJob, params: EraseUserParams) -> WorkerResult {
+        info!("Processing erase_user job: {}", job.id);
+
+        match erase_user::execute(mq.db(), &params, &ProgressReporter::new(mq, job)).await {
+            Ok(payload) => Ok(JobResult::Success(payload)),
+            Err(e) => {
+                error!("erase_user job {} failed: {}", job.id, e);
+                Ok(JobResult::Failed(e))
+            }
+        }
+    }
+}
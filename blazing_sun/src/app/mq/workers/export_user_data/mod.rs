@@ -0,0 +1,31 @@
+use crate::app::mq::jobs::export_user_data::{self, ExportUserDataParams};
+use crate::mq::{JobResult, MessageQueue, ProgressReporter, QueuedJob, Worker, WorkerResult};
+use async_trait::async_trait;
+use tracing::{error, info};
+
+/// Process an export_user_data job
+pub struct ExportUserData;
+
+#[async_trait]
+impl Worker for ExportUserData {
+    const NAME: &'static str = "export_user_data";
+    /// Exports walk a user's whole history; one at a time keeps MongoDB responsive
+    const CONCURRENCY: usize = 1;
+    type Payload = ExportUserDataParams;
+
+    async fn handle(
+        mq: &MessageQueue,
+        job: &QueuedJob,
+        params: ExportUserDataParams,
+    ) -> WorkerResult {
+        info!("Processing export_user_data job: {}", job.id);
+
+        match export_user_data::execute(mq.db(), &params, &ProgressReporter::new(mq, job)).await {
+            Ok(payload) => Ok(JobResult::Success(payload)),
+            Err(e) => {
+                error!("export_user_data job {} failed: {}", job.id, e);
+                Ok(JobResult::Failed(e))
+            }
+        }
+    }
+}
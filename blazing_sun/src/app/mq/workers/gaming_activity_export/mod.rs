@@ -0,0 +1,33 @@
+use crate::app::mq::jobs::gaming_activity_export::{self, GamingActivityExportParams};
+use crate::mq::{JobResult, MessageQueue, ProgressReporter, QueuedJob, Worker, WorkerResult};
+use async_trait::async_trait;
+use tracing::{error, info};
+
+/// Process a gaming_activity_export job
+pub struct GamingActivityExport;
+
+#[async_trait]
+impl Worker for GamingActivityExport {
+    const NAME: &'static str = "gaming_activity_export";
+    /// Statements read a user's whole year and render PDFs
+    const CONCURRENCY: usize = 1;
+    type Payload = GamingActivityExportParams;
+
+    async fn handle(
+        mq: &MessageQueue,
+        job: &QueuedJob,
+        params: GamingActivityExportParams,
+    ) -> WorkerResult {
+        info!("Processing gaming_activity_export job: {}", job.id);
+
+        match gaming_activity_export::execute(mq.db(), &params, &ProgressReporter::new(mq, job))
+            .await
+        {
+            Ok(payload) => Ok(JobResult::Success(payload)),
+            Err(e) => {
+                error!("gaming_activity_export job {} failed: {}", job.id, e);
+                Ok(JobResult::Failed(e))
+            }
+        }
+    }
+}
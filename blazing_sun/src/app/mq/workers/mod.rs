@@ -4,39 +4,48 @@ pub mod bulk_user_action;
 pub mod create_user;
 pub mod delete_upload;
 pub mod delete_user;
+pub mod deliver_game_webhook;
 pub mod email;
+pub mod erase_user;
+pub mod export_user_data;
+pub mod gaming_activity_export;
 pub mod oauth_delete_gallery;
 pub mod oauth_delete_picture;
 pub mod oauth_list_galleries;
 pub mod oauth_list_gallery_images;
+pub mod process_avatar;
 pub mod resize_image;
 
-use crate::mq::{JobResult, MessageQueue, QueuedJob};
+crate::register_workers! {
+    bulk_delete_pictures::BulkDeletePictures,
+    bulk_delete_uploads::BulkDeleteUploads,
+    bulk_user_action::BulkUserAction,
+    create_user::CreateUser,
+    delete_upload::DeleteUpload,
+    delete_user::DeleteUser,
+    deliver_game_webhook::DeliverGameWebhook,
+    email::SendEmail,
+    erase_user::EraseUser,
+    export_user_data::ExportUserData,
+    gaming_activity_export::GamingActivityExport,
+    oauth_delete_gallery::OauthDeleteGallery,
+    oauth_delete_picture::OauthDeletePicture,
+    oauth_list_galleries::OauthListGalleries,
+    oauth_list_gallery_images::OauthListGalleryImages,
+    process_avatar::ProcessAvatar,
+    resize_image::ResizeImage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-/// Process a job by routing to the appropriate worker
-pub async fn process(
-    mq: &MessageQueue,
-    job: &QueuedJob,
-) -> Result<JobResult<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
-    match job.worker_name.as_str() {
-        "create_user" => create_user::process(mq, job).await,
-        "bulk_user_action" => bulk_user_action::process(mq, job).await,
-        "bulk_delete_pictures" => bulk_delete_pictures::process(mq, job).await,
-        "bulk_delete_uploads" => bulk_delete_uploads::process(mq, job).await,
-        "delete_user" => delete_user::process(mq, job).await,
-        "delete_upload" => delete_upload::process(mq, job).await,
-        "send_email" => email::process(mq, job).await,
-        "oauth_list_galleries" => oauth_list_galleries::process(mq, job).await,
-        "oauth_list_gallery_images" => oauth_list_gallery_images::process(mq, job).await,
-        "oauth_delete_gallery" => oauth_delete_gallery::process(mq, job).await,
-        "oauth_delete_picture" => oauth_delete_picture::process(mq, job).await,
-        "resize_image" => resize_image::process(mq, job).await,
-        _ => {
-            tracing::error!("Unknown worker: {}", job.worker_name);
-            Ok(JobResult::Failed(format!(
-                "Unknown worker: {}",
-                job.worker_name
-            )))
-        }
+    #[test]
+    fn every_worker_is_registered_once() {
+        let names = WORKERS.names();
+        assert_eq!(names.len(), WORKER_ENTRIES.len());
+        assert!(names.contains(&"send_email"));
+        assert_eq!(WORKERS.get("resize_image").map(|w| w.concurrency), Some(2));
+        assert!(WORKERS.get("unknown").is_none());
     }
 }
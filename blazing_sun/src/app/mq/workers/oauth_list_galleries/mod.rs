@@ -1,26 +1,29 @@
 use crate::app::mq::jobs::oauth_list_galleries::{self, ListGalleriesParams};
-use crate::mq::{JobResult, MessageQueue, QueuedJob};
+use crate::mq::{JobResult, MessageQueue, QueuedJob, Worker, WorkerResult};
+use async_trait::async_trait;
 use tracing::{error, info};
 
-pub async fn process(
-    mq: &MessageQueue,
-    job: &QueuedJob,
-) -> Result<JobResult<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
-    info!("Processing oauth_list_galleries job: {}", job.id);
+/// Process an oauth_list_galleries job
+pub struct OauthListGalleries;
 
-    let params: ListGalleriesParams = match serde_json::from_str(&job.payload) {
-        Ok(p) => p,
-        Err(e) => {
-            error!("Failed to deserialize oauth_list_galleries payload: {}", e);
-            return Ok(JobResult::Failed(format!("Invalid payload: {}", e)));
-        }
-    };
+#[async_trait]
+impl Worker for OauthListGalleries {
+    const NAME: &'static str = "oauth_list_galleries";
+    type Payload = ListGalleriesParams;
+
+    async fn handle(
+        mq: &MessageQueue,
+        job: &QueuedJob,
+        params: ListGalleriesParams,
+    ) -> WorkerResult {
+        info!("Processing oauth_list_galleries job: {}", job.id);
 
-    match oauth_list_galleries::execute(mq.db(), &params).await {
-        Ok(payload) => Ok(JobResult::Success(payload)),
-        Err(e) => {
-            error!("oauth_list_galleries job {} failed: {}", job.id, e);
-            Ok(JobResult::Failed(e))
+        match oauth_list_galleries::execute(mq.db(), &params).await {
+            Ok(payload) => Ok(JobResult::Success(payload)),
+            Err(e) => {
+                error!("oauth_list_galleries job {} failed: {}", job.id, e);
+                Ok(JobResult::Failed(e))
+            }
         }
     }
 }
@@ -0,0 +1,141 @@
+use crate::app::avatars;
+use crate::app::db_query::mutations::image_variant::{self, CreateImageVariantParams};
+use crate::app::mq::jobs::process_avatar::ProcessAvatarParams;
+use crate::bootstrap::includes::storage::{get_storage, StorageError, StoredFile, Visibility};
+use crate::mq::{JobResult, MessageQueue, QueuedJob, Worker, WorkerResult};
+use crate::state;
+use async_trait::async_trait;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+/// Process a process_avatar job
+///
+/// Renders the standard square sizes of a profile picture and stores them
+/// next to the original through the storage driver. The original is kept
+/// (served when no variant is requested).
+pub struct ProcessAvatar;
+
+#[async_trait]
+impl Worker for ProcessAvatar {
+    const NAME: &'static str = "process_avatar";
+    /// Image decoding and resizing is CPU bound
+    const CONCURRENCY: usize = 2;
+    type Payload = ProcessAvatarParams;
+
+    async fn handle(
+        _mq: &MessageQueue,
+        job: &QueuedJob,
+        params: ProcessAvatarParams,
+    ) -> WorkerResult {
+        info!("Processing process_avatar job: {}", job.id);
+
+        let storage = match get_storage() {
+            Ok(s) => s,
+            Err(e) => return Ok(JobResult::Retry(format!("Storage unavailable: {}", e))),
+        };
+
+        let original = match storage.get(&params.storage_path).await {
+            Ok(data) => data,
+            Err(StorageError::NotFound) => {
+                // Replaced or deleted before we got to it
+                warn!(
+                    "Avatar original {} is gone, skipping upload_id={}",
+                    params.storage_path, params.upload_id
+                );
+                return Ok(JobResult::Failed("Original not found".to_string()));
+            }
+            Err(e) => return Ok(JobResult::Retry(format!("Storage error: {}", e))),
+        };
+
+        let rendered =
+            match tokio::task::spawn_blocking(move || avatars::render_variants(&original)).await? {
+                Ok(variants) => variants,
+                Err(e) => {
+                    error!(
+                        "Failed to render avatar variants for upload_id={}: {}",
+                        params.upload_id, e
+                    );
+                    return Ok(JobResult::Failed(format!("Image processing error: {}", e)));
+                }
+            };
+
+        let base_name = Path::new(&params.stored_name)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("avatar");
+
+        let mut stored: Vec<(StoredFile, u32)> = Vec::with_capacity(rendered.len());
+        for variant in &rendered {
+            let filename = format!(
+                "{}_{}.{}",
+                base_name,
+                variant.name,
+                variant.format.extension()
+            );
+            match storage
+                .put_with_subfolder(
+                    &variant.data,
+                    &filename,
+                    Visibility::Private,
+                    avatars::SUBFOLDER,
+                )
+                .await
+            {
+                Ok(file) => stored.push((file, variant.size)),
+                Err(e) => {
+                    remove_stored(&stored).await;
+                    return Ok(JobResult::Retry(format!("Storage error: {}", e)));
+                }
+            }
+        }
+
+        let variant_params = rendered
+            .iter()
+            .zip(&stored)
+            .map(|(variant, (file, size))| CreateImageVariantParams {
+                upload_id: params.upload_id,
+                variant_name: variant.name.to_string(),
+                stored_name: file.stored_name.clone(),
+                width: *size as i32,
+                height: *size as i32,
+                size_bytes: file.size_bytes as i64,
+                storage_path: file.storage_path.clone(),
+            })
+            .collect();
+
+        let app_state = state().await;
+        let db = app_state.db.lock().await;
+
+        match image_variant::create_batch(&db, variant_params).await {
+            Ok(ids) => {
+                info!(
+                    "Stored {} avatar variants for upload_id={} (user_id={})",
+                    ids.len(),
+                    params.upload_id,
+                    params.user_id
+                );
+                Ok(JobResult::Success(serde_json::Value::Null))
+            }
+            Err(e) => {
+                error!(
+                    "Failed to store avatar variant records for upload_id={}: {}",
+                    params.upload_id, e
+                );
+                remove_stored(&stored).await;
+                Ok(JobResult::Retry(format!("Database error: {}", e)))
+            }
+        }
+    }
+}
+
+/// Remove variants written by a failed attempt
+async fn remove_stored(stored: &[(StoredFile, u32)]) {
+    for (file, _) in stored {
+        if let Err(e) = avatars::delete(&file.storage_path).await {
+            warn!(
+                "Failed to remove avatar variant {}: {}",
+                file.storage_path, e
+            );
+        }
+    }
+}
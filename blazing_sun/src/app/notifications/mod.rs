@@ -0,0 +1,211 @@
+//! Notifications
+//!
+//! Some events concern one user directly: a payment went through, a host
+//! picked them to play, someone mentioned them in a chat channel, the data
+//! export they asked for is ready. Each of
+//! these belongs to a [`NotificationCategory`], and every user chooses per
+//! category how they are told ([`NotificationChannel`]): over WebSocket, by
+//! email, or not at all. Each category has its own default channel.
+//!
+//! [`sources`] turns bus events into [`Notification`]s; the notification
+//! router (`events::handlers::NotificationRouter`) looks up the recipient's
+//! [`NotificationPreferences`] and delivers each one on the chosen channel.
+
+pub mod sources;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    /// Checkout payments and admin balance adjustments
+    Payments,
+    /// A host picked the user to play, or a tournament match is ready
+    GameInvites,
+    /// `@name` mentions in chat channels
+    ChatMentions,
+    /// A scheduled room the user registered for opens soon, or opened
+    GameReminders,
+    /// A data export the user asked for can be downloaded
+    DataExports,
+}
+
+impl NotificationCategory {
+    /// Every category, in the order they are listed to users
+    pub const ALL: [NotificationCategory; 5] = [
+        NotificationCategory::Payments,
+        NotificationCategory::GameInvites,
+        NotificationCategory::ChatMentions,
+        NotificationCategory::GameReminders,
+        NotificationCategory::DataExports,
+    ];
+
+    /// Name stored in `notification_preferences.category`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationCategory::Payments => "payments",
+            NotificationCategory::GameInvites => "game_invites",
+            NotificationCategory::ChatMentions => "chat_mentions",
+            NotificationCategory::GameReminders => "game_reminders",
+            NotificationCategory::DataExports => "data_exports",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|category| category.as_str() == value)
+    }
+
+    /// Human readable name, used in emails
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationCategory::Payments => "payment",
+            NotificationCategory::GameInvites => "game invite",
+            NotificationCategory::ChatMentions => "chat mention",
+            NotificationCategory::GameReminders => "game reminder",
+            NotificationCategory::DataExports => "data export",
+        }
+    }
+
+    /// Channel used until the user picks one; scheduled room reminders and
+    /// finished data exports are emailed, as the user is often offline when
+    /// they go out (the room events and the export job's progress still reach
+    /// open connections)
+    pub fn default_channel(&self) -> NotificationChannel {
+        match self {
+            NotificationCategory::GameReminders | NotificationCategory::DataExports => {
+                NotificationChannel::Email
+            }
+            _ => NotificationChannel::Websocket,
+        }
+    }
+}
+
+/// How a user is told about a category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// Pushed to the user's open WebSocket connections (buffered briefly
+    /// by the gateway while they reconnect)
+    Websocket,
+    /// Sent by email through the `send_email` job
+    Email,
+    /// Not delivered at all
+    #[serde(rename = "none")]
+    Muted,
+}
+
+impl NotificationChannel {
+    /// Name stored in `notification_preferences.channel`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Websocket => "websocket",
+            NotificationChannel::Email => "email",
+            NotificationChannel::Muted => "none",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "websocket" => Some(NotificationChannel::Websocket),
+            "email" => Some(NotificationChannel::Email),
+            "none" => Some(NotificationChannel::Muted),
+            _ => None,
+        }
+    }
+}
+
+/// A user's channel for every category, defaults filled in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct NotificationPreferences(BTreeMap<NotificationCategory, NotificationChannel>);
+
+impl NotificationPreferences {
+    /// Preferences from stored `(category, channel)` rows; unknown values are
+    /// ignored and missing categories get their default channel
+    pub fn from_rows<'a>(rows: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut channels: BTreeMap<_, _> = NotificationCategory::ALL
+            .into_iter()
+            .map(|category| (category, category.default_channel()))
+            .collect();
+
+        for (category, channel) in rows {
+            if let (Some(category), Some(channel)) =
+                (NotificationCategory::parse(category), NotificationChannel::parse(channel))
+            {
+                channels.insert(category, channel);
+            }
+        }
+
+        Self(channels)
+    }
+
+    /// Channel the user gets `category` on
+    pub fn channel(&self, category: NotificationCategory) -> NotificationChannel {
+        self.0
+            .get(&category)
+            .copied()
+            .unwrap_or_else(|| category.default_channel())
+    }
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self::from_rows([])
+    }
+}
+
+/// Something to tell one user about
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub user_id: i64,
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: String,
+    /// Ids the client needs to act on it (room to join, channel to open, ...)
+    pub data: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_categories_use_the_default_channel() {
+        let preferences = NotificationPreferences::from_rows([("payments", "email"), ("game_invites", "loud")]);
+
+        assert_eq!(preferences.channel(NotificationCategory::Payments), NotificationChannel::Email);
+        assert_eq!(preferences.channel(NotificationCategory::GameInvites), NotificationChannel::Websocket);
+        assert_eq!(preferences.channel(NotificationCategory::ChatMentions), NotificationChannel::Websocket);
+        assert_eq!(preferences.channel(NotificationCategory::GameReminders), NotificationChannel::Email);
+        assert_eq!(preferences.channel(NotificationCategory::DataExports), NotificationChannel::Email);
+    }
+
+    #[test]
+    fn test_preferences_serialize_as_a_category_map() {
+        let preferences = NotificationPreferences::from_rows([("chat_mentions", "none")]);
+
+        assert_eq!(
+            serde_json::to_value(&preferences).unwrap(),
+            serde_json::json!({
+                "payments": "websocket",
+                "game_invites": "websocket",
+                "chat_mentions": "none",
+                "game_reminders": "email",
+                "data_exports": "email",
+            })
+        );
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for category in NotificationCategory::ALL {
+            assert_eq!(NotificationCategory::parse(category.as_str()), Some(category));
+        }
+        for channel in [NotificationChannel::Websocket, NotificationChannel::Email, NotificationChannel::Muted] {
+            assert_eq!(NotificationChannel::parse(channel.as_str()), Some(channel));
+        }
+        assert_eq!(NotificationChannel::parse("sms"), None);
+    }
+}
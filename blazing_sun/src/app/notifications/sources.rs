@@ -0,0 +1,520 @@
+//! Notifications raised by bus events
+//!
+//! Pure mappings from the events the notification router consumes to the
+//! [`Notification`]s they raise; anything that needs the database (resolving
+//! mentioned names to users) stays in the router.
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use super::{Notification, NotificationCategory};
+use crate::app::checkout::CheckoutFinishedEvent;
+use crate::app::games::types::{EventEnvelope, GameEvent};
+
+/// Longest name looked up for an `@mention`
+const MAX_MENTION_LEN: usize = 64;
+
+/// Most distinct names notified for one chat message
+const MAX_MENTIONS: usize = 10;
+
+/// `1234` -> `12.34`
+fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+}
+
+/// A finished top-up; session creation is not worth a notification
+pub fn from_checkout(event: &CheckoutFinishedEvent) -> Option<Notification> {
+    let amount = format!("{} {}", format_cents(event.amount_cents), event.currency.to_uppercase());
+
+    let (title, body) = if event.is_success() {
+        ("Payment received".to_string(), format!("{} was added to your balance.", amount))
+    } else if event.is_failed() {
+        let reason = event.error_message.as_deref().unwrap_or("The payment was declined");
+        ("Payment failed".to_string(), format!("Your payment of {} failed: {}", amount, reason))
+    } else {
+        return None;
+    };
+
+    Some(Notification {
+        user_id: event.user_id,
+        category: NotificationCategory::Payments,
+        title,
+        body,
+        data: json!({
+            "request_id": event.request_id,
+            "status": event.status,
+            "amount_cents": event.amount_cents,
+            "currency": event.currency,
+        }),
+    })
+}
+
+/// A `user.balance_updated` payload; admin adjustments, received transfers and
+/// dispute holds are notified, as checkout credits are already covered by
+/// [`from_checkout`] and senders made their transfer themselves
+pub fn from_balance_update(user_id: i64, payload: &Value) -> Option<Notification> {
+    match payload.get("source").and_then(Value::as_str) {
+        Some("admin_adjustment") => from_admin_adjustment(user_id, payload),
+        Some("transfer") => from_received_transfer(user_id, payload),
+        Some(source @ ("dispute_hold" | "dispute_release" | "dispute_deduction")) => {
+            from_dispute(user_id, source, payload)
+        }
+        _ => None,
+    }
+}
+
+fn from_dispute(user_id: i64, source: &str, payload: &Value) -> Option<Notification> {
+    let change = payload.get("change").and_then(Value::as_i64).filter(|change| *change != 0)?;
+    let coins = format_cents(change.abs());
+    let (title, body) = match source {
+        "dispute_hold" => (
+            "Coins on hold",
+            format!("{} coins are on hold while your bank reviews a disputed payment.", coins),
+        ),
+        "dispute_release" => (
+            "Coins released",
+            format!("The payment dispute was resolved; {} coins are back in your balance.", coins),
+        ),
+        _ => (
+            "Coins deducted",
+            format!("A payment dispute was decided against the purchase; {} coins were deducted.", coins),
+        ),
+    };
+
+    Some(Notification {
+        user_id,
+        category: NotificationCategory::Payments,
+        title: title.to_string(),
+        body,
+        data: json!({
+            "dispute_id": payload.get("dispute_id"),
+            "request_id": payload.get("request_id"),
+            "change": change,
+            "balance": payload.get("balance"),
+        }),
+    })
+}
+
+fn from_admin_adjustment(user_id: i64, payload: &Value) -> Option<Notification> {
+    let change = payload.get("change").and_then(Value::as_i64)?;
+    let balance = payload.get("balance").and_then(Value::as_i64).unwrap_or_default();
+    let reason = payload.get("reason").and_then(Value::as_str).unwrap_or_default();
+    let (title, verb) = if change >= 0 {
+        ("Balance credited", "credited")
+    } else {
+        ("Balance debited", "debited")
+    };
+
+    Some(Notification {
+        user_id,
+        category: NotificationCategory::Payments,
+        title: title.to_string(),
+        body: format!(
+            "Support {} {} to your balance ({}). New balance: {}.",
+            verb,
+            format_cents(change.abs()),
+            reason,
+            format_cents(balance)
+        ),
+        data: json!({
+            "adjustment_id": payload.get("adjustment_id"),
+            "change": change,
+            "balance": balance,
+        }),
+    })
+}
+
+fn from_received_transfer(user_id: i64, payload: &Value) -> Option<Notification> {
+    let change = payload.get("change").and_then(Value::as_i64).filter(|change| *change > 0)?;
+    let sender = payload.get("counterparty_name").and_then(Value::as_str).unwrap_or("Someone");
+    let memo = payload.get("memo").and_then(Value::as_str);
+
+    let mut body = format!("{} sent you {} coins.", sender, format_cents(change));
+    if let Some(memo) = memo {
+        body.push_str(&format!(" \"{}\"", memo));
+    }
+
+    Some(Notification {
+        user_id,
+        category: NotificationCategory::Payments,
+        title: "Coins received".to_string(),
+        body,
+        data: json!({
+            "transfer_id": payload.get("transfer_id"),
+            "sender_id": payload.get("counterparty_id"),
+            "change": change,
+            "balance": payload.get("balance"),
+        }),
+    })
+}
+
+/// A `user.data_export_ready` payload: the archive the user asked for can be
+/// downloaded until the link expires
+pub fn from_data_export(user_id: i64, payload: &Value) -> Option<Notification> {
+    let download_url = payload.get("download_url").and_then(Value::as_str)?;
+    let expires_at = payload
+        .get("expires_at")
+        .and_then(Value::as_str)
+        .and_then(|at| at.parse::<DateTime<Utc>>().ok())?;
+
+    Some(Notification {
+        user_id,
+        category: NotificationCategory::DataExports,
+        title: "Your data export is ready".to_string(),
+        body: format!(
+            "Download a copy of your data at {} before {}.",
+            download_url,
+            expires_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+        data: json!({
+            "export_id": payload.get("export_id"),
+            "download_url": download_url,
+            "expires_at": expires_at,
+        }),
+    })
+}
+
+/// Game events that invite their players somewhere or remind them of a
+/// scheduled room
+pub fn from_game_envelope(envelope: &EventEnvelope) -> Vec<Notification> {
+    let Ok(event) = serde_json::from_value::<GameEvent>(envelope.payload.clone()) else {
+        return Vec::new();
+    };
+
+    match event {
+        GameEvent::PlayerSelected { room_id, player } => vec![Notification {
+            user_id: player.user_id,
+            category: NotificationCategory::GameInvites,
+            title: "You were picked to play".to_string(),
+            body: "The host picked you for their game. Join the room and get ready.".to_string(),
+            data: json!({ "room_id": room_id }),
+        }],
+        GameEvent::TournamentRoundStarted {
+            tournament_id,
+            tournament_name,
+            round,
+            rounds,
+            matches,
+            ..
+        } => matches
+            .into_iter()
+            .flat_map(|game| [(game.player1_id, game.clone()), (game.player2_id, game)])
+            .map(|(user_id, game)| Notification {
+                user_id,
+                category: NotificationCategory::GameInvites,
+                title: format!("{}: your match is ready", tournament_name),
+                body: format!(
+                    "Round {} of {} has started. Your match is waiting in {}.",
+                    round, rounds, game.room_name
+                ),
+                data: json!({
+                    "tournament_id": tournament_id,
+                    "match_id": game.match_id,
+                    "room_id": game.room_id,
+                }),
+            })
+            .collect(),
+        GameEvent::InviteReceived {
+            invite_id,
+            room_id,
+            room_name,
+            inviter_username,
+            expires_at,
+            ..
+        } => audience_users(envelope)
+            .map(|user_id| Notification {
+                user_id,
+                category: NotificationCategory::GameInvites,
+                title: format!("{} invited you to play", inviter_username),
+                body: format!("Join {} before the invitation expires.", room_name),
+                data: json!({
+                    "invite_id": invite_id,
+                    "room_id": room_id,
+                    "expires_at": expires_at,
+                }),
+            })
+            .collect(),
+        GameEvent::ScheduledRoomReminder {
+            room_id,
+            room_name,
+            scheduled_start_at,
+            minutes_before,
+            ..
+        } => audience_users(envelope)
+            .map(|user_id| Notification {
+                user_id,
+                category: NotificationCategory::GameReminders,
+                title: format!("{} starts in {} minutes", room_name, minutes_before),
+                body: format!(
+                    "The room you registered for opens at {}.",
+                    scheduled_start_at.format("%Y-%m-%d %H:%M UTC")
+                ),
+                data: json!({
+                    "room_id": room_id,
+                    "scheduled_start_at": scheduled_start_at,
+                }),
+            })
+            .collect(),
+        GameEvent::ScheduledRoomOpened { room_id, room_name, .. } => audience_users(envelope)
+            .map(|user_id| Notification {
+                user_id,
+                category: NotificationCategory::GameReminders,
+                title: format!("{} is open", room_name),
+                body: "The room you registered for is open. Join it now.".to_string(),
+                data: json!({ "room_id": room_id }),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Users an envelope is addressed to by id
+fn audience_users(envelope: &EventEnvelope) -> impl Iterator<Item = i64> + '_ {
+    envelope
+        .audience
+        .user_ids
+        .iter()
+        .filter_map(|id| id.parse().ok())
+}
+
+/// Lowercased `@names` mentioned in a chat message, without duplicates
+///
+/// A mention starts at an `@` that begins a word and runs over letters,
+/// digits, `_`, `-` and `.`; a trailing `.` is punctuation, not part of it.
+pub fn mentioned_names(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut previous = ' ';
+
+    for (at, c) in content.char_indices() {
+        let starts_word = previous.is_whitespace() || previous == '(';
+        previous = c;
+        if c != '@' || !starts_word {
+            continue;
+        }
+
+        let rest = &content[at + 1..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+            .unwrap_or(rest.len());
+        let name = rest[..end].trim_end_matches('.').to_lowercase();
+
+        if !name.is_empty() && name.chars().count() <= MAX_MENTION_LEN && !names.contains(&name) {
+            names.push(name);
+            if names.len() == MAX_MENTIONS {
+                break;
+            }
+        }
+    }
+
+    names
+}
+
+/// Notification for a user mentioned in a channel message
+pub fn chat_mention(
+    user_id: i64,
+    channel_id: i64,
+    message_id: &str,
+    sender_name: &str,
+    content: &str,
+) -> Notification {
+    const PREVIEW_CHARS: usize = 140;
+    let mut preview: String = content.chars().take(PREVIEW_CHARS).collect();
+    if content.chars().count() > PREVIEW_CHARS {
+        preview.push('…');
+    }
+
+    Notification {
+        user_id,
+        category: NotificationCategory::ChatMentions,
+        title: format!("{} mentioned you", sender_name),
+        body: preview,
+        data: json!({
+            "channel_id": channel_id,
+            "message_id": message_id,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkout(status: &str) -> CheckoutFinishedEvent {
+        CheckoutFinishedEvent {
+            request_id: "req-1".to_string(),
+            user_id: 7,
+            amount_cents: 1250,
+            currency: "eur".to_string(),
+            purpose: "balance_topup".to_string(),
+            status: status.to_string(),
+            session_id: None,
+            session_url: None,
+            expires_at: None,
+            payment_intent_id: None,
+            error_message: None,
+            error_code: None,
+            timestamp: "2026-10-17T10:00:00Z".to_string(),
+            coupon: None,
+        }
+    }
+
+    fn game_envelope(payload: Value) -> EventEnvelope {
+        serde_json::from_value(json!({
+            "event_id": "e1",
+            "event_type": "games.event.tournament_round_started",
+            "timestamp": "2026-10-17T10:00:00Z",
+            "actor": { "user_id": "0", "username": "system" },
+            "audience": { "type": "users", "user_ids": ["1", "2"] },
+            "payload": payload,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_checkout_outcomes_notify_the_payer() {
+        let paid = from_checkout(&checkout("success")).unwrap();
+        assert_eq!(paid.user_id, 7);
+        assert_eq!(paid.category, NotificationCategory::Payments);
+        assert_eq!(paid.body, "12.50 EUR was added to your balance.");
+
+        assert_eq!(from_checkout(&checkout("failed")).unwrap().title, "Payment failed");
+        assert!(from_checkout(&checkout("session_created")).is_none());
+    }
+
+    #[test]
+    fn test_only_admin_adjustments_notify_balance_updates() {
+        let adjusted = json!({ "balance": 500, "change": -250, "source": "admin_adjustment", "reason": "chargeback" });
+        let notification = from_balance_update(3, &adjusted).unwrap();
+        assert_eq!(notification.title, "Balance debited");
+        assert!(notification.body.contains("2.50"));
+
+        let checkout_credit = json!({ "balance": 500, "change": 250, "source": "checkout_kafka" });
+        assert!(from_balance_update(3, &checkout_credit).is_none());
+    }
+
+    #[test]
+    fn test_dispute_holds_notify_the_user() {
+        let held = json!({ "balance": 0, "change": -500, "source": "dispute_hold", "dispute_id": "dp_1" });
+        let notification = from_balance_update(3, &held).unwrap();
+        assert_eq!(notification.title, "Coins on hold");
+        assert!(notification.body.starts_with("5.00 coins are on hold"));
+        assert_eq!(notification.data["dispute_id"], "dp_1");
+
+        let released = json!({ "balance": 500, "change": 500, "source": "dispute_release" });
+        assert_eq!(from_balance_update(3, &released).unwrap().title, "Coins released");
+
+        let nothing_moved = json!({ "balance": 0, "change": 0, "source": "dispute_deduction" });
+        assert!(from_balance_update(3, &nothing_moved).is_none());
+    }
+
+    #[test]
+    fn test_only_recipients_are_notified_of_transfers() {
+        let received = json!({
+            "balance": 900, "change": 150, "source": "transfer", "transfer_id": 5,
+            "counterparty_id": 2, "counterparty_name": "Ana Petrovic", "memo": "pizza",
+        });
+        let notification = from_balance_update(3, &received).unwrap();
+        assert_eq!(notification.body, "Ana Petrovic sent you 1.50 coins. \"pizza\"");
+        assert_eq!(notification.data["transfer_id"], 5);
+
+        let sent = json!({ "balance": 100, "change": -150, "source": "transfer", "counterparty_id": 3 });
+        assert!(from_balance_update(2, &sent).is_none());
+    }
+
+    #[test]
+    fn test_ready_data_exports_carry_their_download_link() {
+        let payload = json!({
+            "export_id": 12,
+            "download_url": "https://example.com/api/v1/data-exports/12/download?expires=1&signature=ab",
+            "size_bytes": 2048,
+            "expires_at": "2026-10-20T18:30:00Z",
+        });
+        let notification = from_data_export(3, &payload).unwrap();
+        assert_eq!(notification.category, NotificationCategory::DataExports);
+        assert!(notification.body.contains("/api/v1/data-exports/12/download"));
+        assert!(notification.body.contains("2026-10-20 18:30 UTC"));
+        assert_eq!(notification.data["export_id"], 12);
+
+        assert!(from_data_export(3, &json!({ "export_id": 12 })).is_none());
+    }
+
+    #[test]
+    fn test_tournament_round_invites_both_players_of_each_match() {
+        let envelope = game_envelope(json!({
+            "type": "tournament_round_started",
+            "tournament_id": 4,
+            "tournament_name": "Friday Cup",
+            "game_type": "bigger_dice",
+            "round": 1,
+            "rounds": 2,
+            "matches": [
+                { "match_id": 10, "position": 0, "player1_id": 1, "player2_id": 2, "room_id": "r1", "room_name": "Match 1" },
+            ],
+        }));
+
+        let notifications = from_game_envelope(&envelope);
+        let users: Vec<i64> = notifications.iter().map(|n| n.user_id).collect();
+        assert_eq!(users, vec![1, 2]);
+        assert_eq!(notifications[0].data["room_id"], "r1");
+        assert_eq!(notifications[0].category, NotificationCategory::GameInvites);
+    }
+
+    #[test]
+    fn test_scheduled_room_reminders_go_to_the_registered_users() {
+        let envelope = game_envelope(json!({
+            "type": "scheduled_room_reminder",
+            "room_id": "r1",
+            "room_name": "Dice night",
+            "game_type": "bigger_dice",
+            "scheduled_start_at": "2026-10-20T18:30:00Z",
+            "minutes_before": 10,
+        }));
+
+        let notifications = from_game_envelope(&envelope);
+        let users: Vec<i64> = notifications.iter().map(|n| n.user_id).collect();
+        assert_eq!(users, vec![1, 2]);
+        assert_eq!(notifications[0].category, NotificationCategory::GameReminders);
+        assert_eq!(notifications[0].title, "Dice night starts in 10 minutes");
+        assert!(notifications[0].body.contains("2026-10-20 18:30 UTC"));
+    }
+
+    #[test]
+    fn test_room_invites_notify_the_invitee() {
+        let envelope = game_envelope(json!({
+            "type": "invite_received",
+            "invite_id": "0b6f4c1e-6c59-4d8e-9a59-5d7f3c1e2a10",
+            "room_id": "r1",
+            "room_name": "Dice night",
+            "game_type": "bigger_dice",
+            "inviter_id": 5,
+            "inviter_username": "Ana Petrovic",
+            "is_password_protected": true,
+            "player_count": 2,
+            "joined_count": 1,
+            "expires_at": "2026-10-17T10:05:00Z",
+        }));
+
+        let notifications = from_game_envelope(&envelope);
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].category, NotificationCategory::GameInvites);
+        assert_eq!(notifications[0].title, "Ana Petrovic invited you to play");
+        assert_eq!(notifications[0].data["room_id"], "r1");
+    }
+
+    #[test]
+    fn test_other_game_events_notify_nobody() {
+        let envelope = game_envelope(json!({ "type": "player_kicked", "room_id": "r1", "user_id": 2, "username": "b" }));
+        assert!(from_game_envelope(&envelope).is_empty());
+    }
+
+    #[test]
+    fn test_mentioned_names() {
+        assert_eq!(
+            mentioned_names("@Ana and @marko.p. said hi to @ana (@jo_1)"),
+            vec!["ana", "marko.p", "jo_1"]
+        );
+        assert!(mentioned_names("mail me at ana@example.com, or @").is_empty());
+    }
+}
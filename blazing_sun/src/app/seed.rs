@@ -0,0 +1,478 @@
+//! Demo data for local development
+//!
+//! `blazing_admin seed run` fills a fresh database with demo users (each
+//! with a long-lived JWT for calling the API), a game room in every state
+//! and a balance history of checkout credits and prediction stakes.
+//!
+//! Seeding is idempotent. Users and rooms are upserted by email and room_id
+//! and brought back to their fixture state. Ledger entries go through
+//! `credit_once` under the `seed` topic, so each is applied once however
+//! often the seed runs. Balances are never reset, only moved by new entries.
+//! Every checkout credit also gets its succeeded `checkout_transactions` row
+//! in the checkout service's database (upserted by request_id, with the same
+//! session and payment intent ids), so payment history and the admin
+//! transaction search show it too.
+//!
+//! `--reset` is the destructive variant: it first deletes the demo users
+//! (with everything that cascades from them), the `seed-` rooms, the seed's
+//! processed_events and checkout transactions, then seeds from scratch. Demo
+//! tokens stay the same between runs while JWT_SECRET and the user IDs do; a
+//! reset gives new IDs.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use rbac::levels;
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::app::db_query::mutations::balance_ledger::{
+    self as db_balance_ledger, CreditOnceParams,
+};
+use crate::app::db_query::mutations::game_room as db_game_room;
+use crate::app::db_query::mutations::seed::{
+    self as db_seed, SeedCheckoutParams, SeedDeletion, SeedRoomParams, SeedUserParams,
+};
+use crate::app::games::room_password;
+use crate::app::http::api::controllers::auth::Claims;
+use crate::bootstrap::middleware::controllers::rbac::role_of;
+use crate::config::JwtConfig;
+
+/// `processed_events` topic the seed's ledger entries are keyed under
+pub const SEED_TOPIC: &str = "seed";
+
+/// Domain of every demo user's email (`.test` never resolves)
+pub const EMAIL_SUFFIX: &str = "@demo.test";
+
+/// Prefix of every demo room's room_id
+pub const ROOM_PREFIX: &str = "seed-";
+
+/// Password of every demo user
+pub const DEMO_PASSWORD: &str = "DemoPass123~";
+
+/// Password of the locked demo room
+pub const ROOM_PASSWORD: &str = "letmein";
+
+/// Expiry of the demo tokens (2099-12-31T23:59:59Z), fixed so they are
+/// the same on every run
+pub const TOKEN_EXPIRES_AT: i64 = 4_102_444_799;
+
+struct DemoUser {
+    handle: &'static str,
+    first_name: &'static str,
+    last_name: &'static str,
+    permissions: i32,
+}
+
+const DEMO_USERS: [DemoUser; 6] = [
+    DemoUser {
+        handle: "alice",
+        first_name: "Alice",
+        last_name: "Demo",
+        permissions: levels::BASIC,
+    },
+    DemoUser {
+        handle: "bob",
+        first_name: "Bob",
+        last_name: "Demo",
+        permissions: levels::BASIC,
+    },
+    DemoUser {
+        handle: "carol",
+        first_name: "Carol",
+        last_name: "Demo",
+        permissions: levels::BASIC,
+    },
+    // No balance and no history: a brand new player
+    DemoUser {
+        handle: "dave",
+        first_name: "Dave",
+        last_name: "Demo",
+        permissions: levels::BASIC,
+    },
+    DemoUser {
+        handle: "moderator",
+        first_name: "Mia",
+        last_name: "Moderator",
+        permissions: levels::MODERATOR,
+    },
+    DemoUser {
+        handle: "admin",
+        first_name: "Ada",
+        last_name: "Admin",
+        permissions: levels::SUPER_ADMIN,
+    },
+];
+
+/// A balance change, applied once per key
+struct LedgerFixture {
+    key: &'static str,
+    handle: &'static str,
+    amount_cents: i64,
+    source: &'static str,
+}
+
+/// In order per user, so no balance goes below zero
+const LEDGER: [LedgerFixture; 7] = [
+    LedgerFixture {
+        key: "seed-alice-checkout-1",
+        handle: "alice",
+        amount_cents: 5_000,
+        source: "checkout",
+    },
+    LedgerFixture {
+        key: "seed-alice-checkout-2",
+        handle: "alice",
+        amount_cents: 2_500,
+        source: "checkout",
+    },
+    LedgerFixture {
+        key: "seed-alice-finals-stake",
+        handle: "alice",
+        amount_cents: -1_000,
+        source: "prediction_stake",
+    },
+    LedgerFixture {
+        key: "seed-alice-finals-payout",
+        handle: "alice",
+        amount_cents: 1_900,
+        source: "prediction_payout",
+    },
+    LedgerFixture {
+        key: "seed-bob-checkout-1",
+        handle: "bob",
+        amount_cents: 10_000,
+        source: "checkout",
+    },
+    LedgerFixture {
+        key: "seed-bob-finals-stake",
+        handle: "bob",
+        amount_cents: -1_000,
+        source: "prediction_stake",
+    },
+    LedgerFixture {
+        key: "seed-carol-checkout-1",
+        handle: "carol",
+        amount_cents: 2_000,
+        source: "checkout",
+    },
+];
+
+/// Currency and purpose of the checkout fixtures
+const CHECKOUT_CURRENCY: &str = "eur";
+const CHECKOUT_PURPOSE: &str = "balance_topup";
+
+/// Room the prediction entries were placed on
+const FINISHED_ROOM: &str = "seed-finished";
+
+/// A demo user as seeded
+#[derive(Debug)]
+pub struct SeededUser {
+    pub email: String,
+    pub id: i64,
+    pub role: &'static str,
+    pub token: String,
+}
+
+/// What a seed run did
+#[derive(Debug, Default)]
+pub struct SeedReport {
+    /// Rows removed first, with `--reset`
+    pub deleted: Option<SeedDeletion>,
+    pub users: Vec<SeededUser>,
+    pub rooms: usize,
+    pub ledger_applied: usize,
+    /// Entries applied by an earlier run
+    pub ledger_skipped: usize,
+    /// Checkout transactions upserted in the checkout database
+    pub checkout_transactions: usize,
+}
+
+/// Access token of a demo user, valid until `TOKEN_EXPIRES_AT`. It carries no
+/// session (`sid`), so only a new JWT_SECRET revokes it.
+pub fn demo_token(secret: &str, user_id: i64, permissions: i16) -> String {
+    let claims = Claims {
+        sub: user_id,
+        role: role_of(permissions).as_str().to_string(),
+        permissions,
+        exp: TOKEN_EXPIRES_AT,
+        locale: None,
+        impersonator_id: None,
+        impersonation_id: None,
+        sid: None,
+    };
+
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap()
+}
+
+/// Seed the demo data, after deleting the previous fixtures when `reset`.
+/// `checkout_db` is the checkout service's database.
+pub async fn run(
+    db: &Pool<Postgres>,
+    checkout_db: &Pool<Postgres>,
+    reset: bool,
+) -> Result<SeedReport, sqlx::Error> {
+    let mut report = SeedReport::default();
+
+    if reset {
+        let mut deleted = db_seed::delete_seeded(db, EMAIL_SUFFIX, ROOM_PREFIX, SEED_TOPIC).await?;
+        deleted.checkout_transactions =
+            db_seed::delete_checkout_transactions(checkout_db, &checkout_keys()).await?;
+        report.deleted = Some(deleted);
+    }
+
+    // One hash for every user: bcrypt is slow on purpose
+    let password_hash = bcrypt::hash(DEMO_PASSWORD, bcrypt::DEFAULT_COST).unwrap();
+    let mut ids = HashMap::new();
+    for user in &DEMO_USERS {
+        let email = format!("{}{}", user.handle, EMAIL_SUFFIX);
+        let permissions = user.permissions as i16;
+        let id = db_seed::upsert_user(
+            db,
+            &SeedUserParams {
+                email: &email,
+                password_hash: &password_hash,
+                first_name: user.first_name,
+                last_name: user.last_name,
+                permissions,
+            },
+        )
+        .await?;
+
+        ids.insert(user.handle, id);
+        report.users.push(SeededUser {
+            email,
+            id,
+            role: role_of(permissions).as_str(),
+            token: demo_token(JwtConfig::secret(), id, permissions),
+        });
+    }
+
+    report.rooms = seed_rooms(db, &ids).await?;
+
+    for entry in &LEDGER {
+        let session_id = format!("cs_test_{}", entry.key);
+        let payment_intent_id = format!("pi_test_{}", entry.key);
+        let metadata = match entry.source {
+            "checkout" => json!({
+                "session_id": session_id,
+                "payment_intent_id": payment_intent_id,
+                "purpose": CHECKOUT_PURPOSE,
+            }),
+            _ => json!({ "room_id": FINISHED_ROOM, "stake_cents": 1_000 }),
+        };
+
+        // Upserted on every run, so a row lost on the checkout side comes
+        // back even when the ledger entry was applied before. The metadata
+        // is what the checkout service puts on the Stripe session.
+        if entry.source == "checkout" {
+            db_seed::upsert_checkout_transaction(
+                checkout_db,
+                &SeedCheckoutParams {
+                    request_id: entry.key,
+                    user_id: ids[entry.handle],
+                    amount_cents: entry.amount_cents,
+                    currency: CHECKOUT_CURRENCY,
+                    purpose: CHECKOUT_PURPOSE,
+                    session_id: &session_id,
+                    payment_intent_id: &payment_intent_id,
+                    metadata: json!({
+                        "user_id": ids[entry.handle].to_string(),
+                        "amount_cents": entry.amount_cents.to_string(),
+                        "request_id": entry.key,
+                        "purpose": CHECKOUT_PURPOSE,
+                        "currency": CHECKOUT_CURRENCY,
+                    }),
+                },
+            )
+            .await?;
+            report.checkout_transactions += 1;
+        }
+
+        let applied = db_balance_ledger::credit_once(
+            db,
+            &CreditOnceParams {
+                topic: SEED_TOPIC,
+                request_id: entry.key,
+                user_id: ids[entry.handle],
+                amount_cents: entry.amount_cents,
+                source: entry.source,
+                metadata,
+            },
+        )
+        .await?;
+
+        match applied {
+            Some(_) => report.ledger_applied += 1,
+            None => report.ledger_skipped += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+/// Request ids of the checkout fixtures, shared by both databases
+fn checkout_keys() -> Vec<&'static str> {
+    LEDGER
+        .iter()
+        .filter(|entry| entry.source == "checkout")
+        .map(|entry| entry.key)
+        .collect()
+}
+
+/// An active room with no players yet
+fn room(
+    room_id: &'static str,
+    room_name: &'static str,
+    game_type: &'static str,
+    status: &'static str,
+    host_id: i64,
+) -> SeedRoomParams<'static> {
+    SeedRoomParams {
+        room_id,
+        room_name,
+        game_type,
+        status,
+        host_id,
+        player_ids: &[],
+        lobby_ids: &[],
+        password_hash: None,
+        current_turn: None,
+        turn_number: 0,
+        winner_id: None,
+        started_at: None,
+        finished_at: None,
+        scheduled_start_at: None,
+        is_active: true,
+    }
+}
+
+/// One room per status (and a locked one), returning how many were seeded
+async fn seed_rooms(db: &Pool<Postgres>, ids: &HashMap<&str, i64>) -> Result<usize, sqlx::Error> {
+    let [alice, bob, carol, dave] = ["alice", "bob", "carol", "dave"].map(|handle| ids[handle]);
+    let now = Utc::now();
+    let room_password_hash = room_password::hash(ROOM_PASSWORD);
+
+    let rooms = [
+        SeedRoomParams {
+            player_ids: &[alice],
+            scheduled_start_at: Some(now + Duration::days(1)),
+            ..room(
+                "seed-scheduled",
+                "Friday Night Dice",
+                "bigger_dice",
+                "scheduled",
+                alice,
+            )
+        },
+        SeedRoomParams {
+            player_ids: &[bob],
+            lobby_ids: &[carol, dave],
+            ..room("seed-waiting", "Open Table", "bigger_dice", "waiting", bob)
+        },
+        SeedRoomParams {
+            player_ids: &[carol],
+            password_hash: room_password_hash.as_deref(),
+            ..room(
+                "seed-locked",
+                "Private Match",
+                "tic_tac_toe",
+                "waiting",
+                carol,
+            )
+        },
+        SeedRoomParams {
+            player_ids: &[alice, bob],
+            current_turn: Some(bob),
+            turn_number: 4,
+            started_at: Some(now - Duration::minutes(10)),
+            ..room(
+                "seed-in-progress",
+                "Tic Tac Showdown",
+                "tic_tac_toe",
+                "in_progress",
+                alice,
+            )
+        },
+        SeedRoomParams {
+            player_ids: &[bob, alice],
+            turn_number: 12,
+            winner_id: Some(alice),
+            started_at: Some(now - Duration::hours(2)),
+            finished_at: Some(now - Duration::minutes(110)),
+            is_active: false,
+            ..room(FINISHED_ROOM, "Dice Finals", "bigger_dice", "finished", bob)
+        },
+        SeedRoomParams {
+            player_ids: &[dave],
+            is_active: false,
+            ..room(
+                "seed-abandoned",
+                "Abandoned Lobby",
+                "bigger_dice",
+                "abandoned",
+                dave,
+            )
+        },
+    ];
+
+    for params in &rooms {
+        db_seed::upsert_room(db, params).await?;
+    }
+
+    // The host is registered like `schedule` does, and two more players
+    for user_id in [alice, bob, carol] {
+        db_game_room::register(db, "seed-scheduled", user_id).await?;
+    }
+
+    Ok(rooms.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demo_tokens_are_stable_and_verify() {
+        let token = demo_token("dev-secret", 7, levels::SUPER_ADMIN as i16);
+        assert_eq!(
+            token,
+            demo_token("dev-secret", 7, levels::SUPER_ADMIN as i16)
+        );
+
+        let claims = jsonwebtoken::decode::<Claims>(
+            &token,
+            &jsonwebtoken::DecodingKey::from_secret(b"dev-secret"),
+            &jsonwebtoken::Validation::default(),
+        )
+        .unwrap()
+        .claims;
+        assert_eq!((claims.sub, claims.role.as_str()), (7, "admin"));
+        assert_eq!(claims.sid, None);
+    }
+
+    #[test]
+    fn ledger_fixtures_never_overdraw() {
+        let mut balances: HashMap<&str, i64> = HashMap::new();
+        for entry in &LEDGER {
+            assert!(DEMO_USERS.iter().any(|user| user.handle == entry.handle));
+            let balance = balances.entry(entry.handle).or_default();
+            *balance += entry.amount_cents;
+            assert!(*balance >= 0, "{} overdraws {}", entry.key, entry.handle);
+        }
+    }
+
+    #[test]
+    fn checkout_fixtures_are_credits() {
+        let keys = checkout_keys();
+        assert_eq!(keys.len(), 4);
+        for entry in LEDGER.iter().filter(|entry| keys.contains(&entry.key)) {
+            assert!(entry.amount_cents > 0, "{} is not a top-up", entry.key);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Signed-in device sessions
+//!
+//! Every sign-in opens an `auth_sessions` row for the device (user agent and
+//! IP at sign-in). Its id travels in the access tokens as `sid` and is linked
+//! from the device's refresh token, so tokens refreshed later stay in the same
+//! session. Users list their devices with `GET /api/v1/auth/sessions` and sign
+//! one out with `DELETE /api/v1/auth/sessions/{id}`.
+//!
+//! Access tokens are not stored, so a revoked session's id is denylisted in
+//! Redis (`auth:revoked_session:{id}`) for as long as a token issued for it
+//! can live; `verify_jwt` and the WebSocket gateway refuse those tokens. The
+//! refresh token is revoked in the database, and a `system.session_revoked`
+//! event makes the gateway close the device's open connections.
+//!
+//! Without Redis the denylist is skipped: a revoked device keeps its current
+//! access token until it expires, but cannot refresh it.
+
+use actix_web::dev::ConnectionInfo;
+use actix_web::http::header::{HeaderMap, USER_AGENT};
+use chrono::{DateTime, Duration, Utc};
+use redis::AsyncCommands;
+use sqlx::{Pool, Postgres};
+use std::net::SocketAddr;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::app::chat::types::{Actor, Audience, EventEnvelope};
+use crate::app::db_query::mutations::auth_sessions as db_auth_session_mut;
+use crate::app::db_query::read::auth_sessions::{self as db_auth_session, AuthSession};
+use crate::app::db_query::read::session_refresh_token::SessionRefreshToken;
+use crate::config::JwtConfig;
+use crate::database::{AppState, SharedRedis};
+use crate::events::topic;
+
+const DENYLIST_PREFIX: &str = "auth:revoked_session:";
+
+/// Event type of the gateway push that closes a session's connections
+pub const SESSION_REVOKED_EVENT: &str = "system.session_revoked";
+
+/// Longest stored user agent (the column of refresh tokens is VARCHAR(255))
+const MAX_DEVICE_INFO_CHARS: usize = 255;
+
+/// Longest IPv6 address in text form
+const MAX_IP_CHARS: usize = 45;
+
+/// Session of the access token a request was made with, stored in the request
+/// extensions by `verify_jwt`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentSession(pub Uuid);
+
+fn denylist_key(id: Uuid) -> String {
+    format!("{}{}", DENYLIST_PREFIX, id)
+}
+
+/// The user agent, as the device description
+pub fn device_info(headers: &HeaderMap) -> Option<String> {
+    let user_agent = headers.get(USER_AGENT)?.to_str().ok()?.trim();
+    if user_agent.is_empty() {
+        return None;
+    }
+    Some(user_agent.chars().take(MAX_DEVICE_INFO_CHARS).collect())
+}
+
+/// Client address without the port (`X-Forwarded-For` behind nginx)
+pub fn client_ip(connection: &ConnectionInfo) -> Option<String> {
+    let address = connection.realip_remote_addr()?;
+    let ip = match address.parse::<SocketAddr>() {
+        Ok(socket) => socket.ip().to_string(),
+        Err(_) => address.to_string(),
+    };
+    Some(ip.chars().take(MAX_IP_CHARS).collect())
+}
+
+/// End of a new session: the refresh token's lifetime with "keep me logged
+/// in", else the access token's
+pub fn expires_at(remember_me: bool) -> DateTime<Utc> {
+    if remember_me {
+        Utc::now() + Duration::days(JwtConfig::refresh_expiration_days())
+    } else {
+        Utc::now() + Duration::minutes(JwtConfig::expiration_minutes())
+    }
+}
+
+/// Session kept alive by a valid refresh token, marked as seen. Tokens issued
+/// before sessions were recorded get one now, described by the token's own
+/// device info. None if the session cannot be recorded; the access token is
+/// then issued without `sid`.
+pub async fn for_refresh_token(
+    db: &Pool<Postgres>,
+    refresh_token: &SessionRefreshToken,
+) -> Option<Uuid> {
+    let linked = match db_auth_session::get_id_by_refresh_token(db, &refresh_token.token_hash).await
+    {
+        Ok(linked) => linked,
+        Err(e) => {
+            warn!("Failed to look up the session of a refresh token: {}", e);
+            return None;
+        }
+    };
+
+    let id = match linked {
+        Some(id) => id,
+        None => {
+            let session = match db_auth_session_mut::create(
+                db,
+                refresh_token.user_id,
+                refresh_token.device_info.as_deref(),
+                refresh_token.ip_address.as_deref(),
+                refresh_token.expires_at,
+            )
+            .await
+            {
+                Ok(session) => session,
+                Err(e) => {
+                    warn!("Failed to record session for a refresh token: {}", e);
+                    return None;
+                }
+            };
+            if let Err(e) =
+                db_auth_session_mut::attach_refresh_token(db, session.id, &refresh_token.token_hash)
+                    .await
+            {
+                warn!(session_id = %session.id, "Failed to link refresh token to session: {}", e);
+            }
+            session.id
+        }
+    };
+
+    if let Err(e) = db_auth_session_mut::touch(db, id).await {
+        warn!(session_id = %id, "Failed to update session last_seen_at: {}", e);
+    }
+    Some(id)
+}
+
+/// Whether a session was revoked while its access tokens are still valid.
+/// Redis errors let the token through; the refresh token is revoked anyway.
+pub async fn is_revoked(redis: Option<SharedRedis>, id: Uuid) -> bool {
+    let Some(mut redis) = redis else {
+        return false;
+    };
+    match redis.exists::<_, bool>(denylist_key(id)).await {
+        Ok(revoked) => revoked,
+        Err(e) => {
+            warn!(session_id = %id, "Failed to check the session denylist: {}", e);
+            false
+        }
+    }
+}
+
+/// Refuse the access tokens of revoked `sessions` and close their WebSocket
+/// connections. The rows must already be revoked in the database.
+pub async fn enforce_revocation(state: &AppState, sessions: &[AuthSession]) {
+    // A token issued just before the revocation lives this long
+    let ttl_seconds = (JwtConfig::expiration_minutes().max(1) * 60 + 60) as u64;
+
+    if let Some(mut redis) = state.redis() {
+        for session in sessions {
+            if let Err(e) = redis
+                .set_ex::<_, _, ()>(denylist_key(session.id), session.user_id, ttl_seconds)
+                .await
+            {
+                warn!(session_id = %session.id, "Failed to denylist revoked session: {}", e);
+            }
+        }
+    }
+
+    let Some(event_bus) = state.event_bus() else {
+        return;
+    };
+    for session in sessions {
+        let bytes = match serde_json::to_vec(&revoked_envelope(session)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(session_id = %session.id, "Failed to encode session revocation: {}", e);
+                continue;
+            }
+        };
+        match event_bus
+            .producer()
+            .send_raw(
+                topic::SYSTEM_EVENTS,
+                Some(&session.user_id.to_string()),
+                &bytes,
+            )
+            .await
+        {
+            Ok(()) => debug!(session_id = %session.id, "Session revocation pushed"),
+            Err(e) => warn!(session_id = %session.id, "Failed to push session revocation: {}", e),
+        }
+    }
+}
+
+/// Gateway envelope closing the connections of a revoked session
+fn revoked_envelope(session: &AuthSession) -> EventEnvelope {
+    EventEnvelope {
+        event_id: Uuid::new_v4().to_string(),
+        event_type: SESSION_REVOKED_EVENT.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        correlation_id: logging::request_id::current(),
+        producer: "blazing_sun".to_string(),
+        actor: Actor {
+            user_id: 0, // System
+            username: "system".to_string(),
+            socket_id: String::new(),
+            roles: vec![],
+        },
+        audience: Audience::users(vec![session.user_id]),
+        payload: serde_json::json!({
+            "session_id": session.id,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn device_and_address_are_trimmed() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(device_info(&headers), None);
+        headers.insert(USER_AGENT, HeaderValue::from_str(&"a".repeat(300)).unwrap());
+        assert_eq!(device_info(&headers).unwrap().len(), MAX_DEVICE_INFO_CHARS);
+
+        let req = TestRequest::default()
+            .insert_header(("X-Forwarded-For", "203.0.113.7"))
+            .to_http_request();
+        assert_eq!(
+            client_ip(&req.connection_info()).as_deref(),
+            Some("203.0.113.7")
+        );
+
+        let req = TestRequest::default()
+            .peer_addr("198.51.100.2:51234".parse().unwrap())
+            .to_http_request();
+        assert_eq!(
+            client_ip(&req.connection_info()).as_deref(),
+            Some("198.51.100.2")
+        );
+    }
+
+    #[test]
+    fn revocation_targets_the_session_owner() {
+        let session = AuthSession {
+            id: Uuid::new_v4(),
+            user_id: 7,
+            device_info: None,
+            ip_address: None,
+            created_at: Utc::now(),
+            last_seen_at: Utc::now(),
+            expires_at: Utc::now(),
+            revoked_at: Some(Utc::now()),
+        };
+        let envelope = revoked_envelope(&session);
+
+        assert_eq!(envelope.event_type, SESSION_REVOKED_EVENT);
+        assert_eq!(envelope.audience.user_ids, vec!["7"]);
+        assert_eq!(envelope.payload["session_id"], session.id.to_string());
+    }
+}
@@ -0,0 +1,308 @@
+//! User settings
+//!
+//! Display settings that follow a user between devices: color theme, language
+//! and notification sounds. Every [`SettingKey`] has a type and a default, and
+//! values are validated against them before they are stored. `user_settings`
+//! only holds the keys a user changed; `language` is the message locale kept
+//! in `user_preferences`, so changing either one changes both.
+//!
+//! Users read and change their settings with `GET/PUT /api/v1/me/settings`.
+//! The resolved settings are mirrored to Redis (`user:settings:{user_id}`),
+//! from where the WebSocket gateway includes them in `system.authenticated`,
+//! letting clients apply them before any HTTP request. The mirror is rebuilt
+//! at startup and rewritten whenever a user reads or changes their settings;
+//! without Redis clients fall back to the HTTP endpoint.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Pool, Postgres};
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+use crate::app::db_query::read::user_settings as user_settings_read;
+use crate::database::SharedRedis;
+
+/// Themes a user can pick; `system` follows the device
+pub const THEMES: [&str; 3] = ["system", "light", "dark"];
+
+/// A setting users can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingKey {
+    /// Color theme (`system`, `light` or `dark`)
+    Theme,
+    /// Locale of the UI and of API messages; null follows the browser
+    Language,
+    /// Play a sound when a notification arrives
+    NotificationSounds,
+}
+
+/// Type of a setting's value, as listed in the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingType {
+    /// One of `options`
+    Choice,
+    /// A supported locale code, or null
+    Locale,
+    Boolean,
+}
+
+impl SettingKey {
+    /// Every key, in the order they are listed to users
+    pub const ALL: [SettingKey; 3] = [
+        SettingKey::Theme,
+        SettingKey::Language,
+        SettingKey::NotificationSounds,
+    ];
+
+    /// Name stored in `user_settings.key`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SettingKey::Theme => "theme",
+            SettingKey::Language => "language",
+            SettingKey::NotificationSounds => "notification_sounds",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+
+    pub fn setting_type(&self) -> SettingType {
+        match self {
+            SettingKey::Theme => SettingType::Choice,
+            SettingKey::Language => SettingType::Locale,
+            SettingKey::NotificationSounds => SettingType::Boolean,
+        }
+    }
+
+    /// Allowed values of a choice
+    pub fn options(&self) -> Vec<&'static str> {
+        match self {
+            SettingKey::Theme => THEMES.to_vec(),
+            SettingKey::Language => i18n::Locale::ALL.iter().map(|l| l.code()).collect(),
+            SettingKey::NotificationSounds => Vec::new(),
+        }
+    }
+
+    /// Value used until the user changes it
+    pub fn default_value(&self) -> Value {
+        match self {
+            SettingKey::Theme => Value::from("system"),
+            SettingKey::Language => Value::Null,
+            SettingKey::NotificationSounds => Value::Bool(true),
+        }
+    }
+
+    /// Check a value against the key's type; returns it normalized (locale
+    /// tags become their code) or a message for the client
+    pub fn validate(&self, value: &Value) -> Result<Value, &'static str> {
+        match self {
+            SettingKey::Theme => match value.as_str() {
+                Some(theme) if THEMES.contains(&theme) => Ok(Value::from(theme)),
+                _ => Err("Theme must be system, light or dark"),
+            },
+            SettingKey::Language => match value {
+                Value::Null => Ok(Value::Null),
+                Value::String(tag) => i18n::Locale::parse(tag)
+                    .map(|locale| Value::from(locale.code()))
+                    .ok_or("Unsupported locale"),
+                _ => Err("Unsupported locale"),
+            },
+            SettingKey::NotificationSounds => match value {
+                Value::Bool(_) => Ok(value.clone()),
+                _ => Err("Value must be true or false"),
+            },
+        }
+    }
+}
+
+/// One key of the settings schema, as sent to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingSchema {
+    pub key: SettingKey,
+    #[serde(rename = "type")]
+    pub setting_type: SettingType,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<&'static str>,
+    pub default: Value,
+}
+
+/// The settings schema, so clients can render the settings page without
+/// hardcoding keys
+pub fn schema() -> Vec<SettingSchema> {
+    SettingKey::ALL
+        .into_iter()
+        .map(|key| SettingSchema {
+            key,
+            setting_type: key.setting_type(),
+            options: key.options(),
+            default: key.default_value(),
+        })
+        .collect()
+}
+
+/// A user's value for every key, defaults filled in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct UserSettings(BTreeMap<SettingKey, Value>);
+
+impl UserSettings {
+    /// Settings from stored `(key, value)` rows and the preferred locale;
+    /// unknown keys and values that no longer validate are ignored, and
+    /// missing keys get their default
+    pub fn from_rows<'a>(
+        rows: impl IntoIterator<Item = (&'a str, &'a Value)>,
+        locale: Option<&str>,
+    ) -> Self {
+        let mut values: BTreeMap<_, _> = SettingKey::ALL
+            .into_iter()
+            .map(|key| (key, key.default_value()))
+            .collect();
+
+        for (key, value) in rows {
+            let Some(key) = SettingKey::parse(key).filter(|key| *key != SettingKey::Language) else {
+                continue;
+            };
+            if let Ok(value) = key.validate(value) {
+                values.insert(key, value);
+            }
+        }
+
+        if let Some(Ok(language)) = locale.map(|locale| SettingKey::Language.validate(&Value::from(locale))) {
+            values.insert(SettingKey::Language, language);
+        }
+
+        Self(values)
+    }
+
+    /// The user's value of `key`
+    pub fn get(&self, key: SettingKey) -> Value {
+        self.0.get(&key).cloned().unwrap_or_else(|| key.default_value())
+    }
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self::from_rows([], None)
+    }
+}
+
+fn mirror_key(user_id: i64) -> String {
+    format!("user:settings:{}", user_id)
+}
+
+/// Redis mirror of resolved settings, read by the WebSocket gateway
+#[derive(Clone)]
+pub struct SettingsMirror {
+    redis: Option<SharedRedis>,
+}
+
+impl SettingsMirror {
+    pub fn new(redis: Option<SharedRedis>) -> Self {
+        Self { redis }
+    }
+
+    /// Mirror a user's settings
+    pub async fn store(&self, user_id: i64, settings: &UserSettings) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let json = match serde_json::to_string(settings) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!(user_id, error = %e, "Failed to serialize settings for Redis");
+                return;
+            }
+        };
+
+        let result: Result<(), redis::RedisError> = redis::cmd("SET")
+            .arg(mirror_key(user_id))
+            .arg(json)
+            .query_async(&mut redis)
+            .await;
+
+        if let Err(e) = result {
+            warn!(user_id, error = %e, "Failed to mirror settings to Redis");
+        }
+    }
+
+    /// Mirror the settings of every user who changed any (at startup); users
+    /// without a mirror entry get their client's defaults
+    pub async fn rebuild_all(&self, db: &Pool<Postgres>) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+
+        let all = match user_settings_read::all(db).await {
+            Ok(all) => all,
+            Err(e) => {
+                warn!(error = %e, "Failed to load settings for the Redis mirror");
+                return;
+            }
+        };
+
+        let mut pipe = redis::pipe();
+        for (user_id, settings) in &all {
+            match serde_json::to_string(settings) {
+                Ok(json) => {
+                    pipe.set(mirror_key(*user_id), json).ignore();
+                }
+                Err(e) => warn!(user_id, error = %e, "Failed to serialize settings for Redis"),
+            }
+        }
+
+        let result: Result<(), redis::RedisError> = pipe.query_async(&mut redis).await;
+        match result {
+            Ok(()) => info!(users = all.len(), "User settings mirror rebuilt"),
+            Err(e) => warn!(error = %e, "Failed to rebuild the settings mirror in Redis"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_missing_keys_use_the_default() {
+        let theme = json!("dark");
+        let sounds = json!("loud");
+        let unknown = json!(1);
+        let settings = UserSettings::from_rows(
+            [("theme", &theme), ("notification_sounds", &sounds), ("font", &unknown)],
+            Some("sr-Latn-RS"),
+        );
+
+        assert_eq!(
+            serde_json::to_value(&settings).unwrap(),
+            json!({ "theme": "dark", "language": "sr", "notification_sounds": true })
+        );
+        assert_eq!(UserSettings::default().get(SettingKey::Language), Value::Null);
+    }
+
+    #[test]
+    fn test_values_are_validated_against_their_type() {
+        assert_eq!(SettingKey::Theme.validate(&json!("light")), Ok(json!("light")));
+        assert!(SettingKey::Theme.validate(&json!("sepia")).is_err());
+        assert_eq!(SettingKey::Language.validate(&json!("en_US")), Ok(json!("en")));
+        assert_eq!(SettingKey::Language.validate(&Value::Null), Ok(Value::Null));
+        assert!(SettingKey::Language.validate(&json!("de")).is_err());
+        assert!(SettingKey::NotificationSounds.validate(&json!("yes")).is_err());
+    }
+
+    #[test]
+    fn test_schema_lists_every_key() {
+        let schema = serde_json::to_value(schema()).unwrap();
+
+        assert_eq!(schema.as_array().unwrap().len(), SettingKey::ALL.len());
+        assert_eq!(
+            schema[0],
+            json!({ "key": "theme", "type": "choice", "options": ["system", "light", "dark"], "default": "system" })
+        );
+        assert!(schema[2].get("options").is_none());
+    }
+}
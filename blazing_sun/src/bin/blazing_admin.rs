@@ -0,0 +1,571 @@
+//! Operator command-line tool
+//!
+//! Usage:
+//!   cargo run --bin blazing_admin -- rooms list [--game-type <type>]
+//!   cargo run --bin blazing_admin -- rooms close <room_id>
+//!   cargo run --bin blazing_admin -- mq failed [--limit <n>]
+//!   cargo run --bin blazing_admin -- mq requeue [--limit <n>] [--worker <name>]
+//!   cargo run --bin blazing_admin -- webhook replay <stripe_event_id>
+//!   cargo run --bin blazing_admin -- webhook replay --file <payload.json>
+//!   cargo run --bin blazing_admin -- kafka publish-test [--topic <topic>]
+//!   cargo run --bin blazing_admin -- kafka replay <topic> --handler <name> [--from <start>]
+//!       [--partition <p>] [--rate <n>] [--dry-run]
+//!   cargo run --bin blazing_admin -- user balance <user_id> [--limit <n>]
+//!   cargo run --bin blazing_admin -- stats backfill [--limit <n>]
+//!   cargo run --bin blazing_admin -- seed run [--reset]
+//!
+//! Reads the same `.env` as the server. Rooms are closed by the game handler of
+//! the region that owns them (it holds the in-memory state), so `rooms close`
+//! publishes a backend-only `close_room` command and returns immediately.
+//! Webhook replays are signed with `STRIPE_WEBHOOK_SECRET` and posted to the
+//! checkout service, whose idempotency makes repeated replays harmless.
+//! `stats backfill` records player results of past games (MongoDB game history
+//! and finished rooms still in game_rooms), `--limit` rooms per batch, and
+//! rebuilds the stats of every player it recorded a result for; rerunning it
+//! only adds what is missing.
+//! `kafka replay` rebuilds a projection (`analytics`, `room_list` or
+//! `player_stats`) by re-consuming a topic into its handler in a throwaway
+//! consumer group, from `beginning` (default), an offset or an RFC 3339 time
+//! up to the end offsets at start; `--dry-run` only decodes and counts.
+//! `seed run` fills a development database with demo users, rooms and balance
+//! history (see `app::seed`) and prints each user's token; it refuses to run
+//! unless APP_ENV is development, and `--reset` deletes the demo data first.
+//! The demo payments go to the checkout database at CHECKOUT_DATABASE_URL.
+
+use blazing_sun::app::db_query::mutations::player_game_stats as player_stats_mutations;
+use blazing_sun::app::db_query::read::player_game_stats as player_stats_read;
+use blazing_sun::app::db_query::read::{balance_ledger, game_room, user};
+use blazing_sun::app::games::mongodb_games::MongoGameClient;
+use blazing_sun::app::games::player_stats::{self, MatchResult};
+use blazing_sun::app::games::types::{Actor, Audience, EventEnvelope};
+use blazing_sun::app::seed;
+use blazing_sun::config::{AppConfig, SecretsConfig};
+use blazing_sun::app::games::room_list::RoomListProjection;
+use blazing_sun::events::handlers::{AnalyticsHandler, PlayerStatsHandler, RoomListProjectionHandler};
+use blazing_sun::events::{
+    self, topic, EventBuilder, EventHandler, EventReplay, EventType, ReplayOptions, SystemEventType,
+};
+use blazing_sun::mq::MessageQueue;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+const USAGE: &str = "usage: blazing_admin <rooms|mq|webhook|kafka|user|stats|seed> <command> [args]
+  rooms list [--game-type <type>]
+  rooms close <room_id>
+  mq failed [--limit <n>]
+  mq requeue [--limit <n>] [--worker <name>]
+  webhook replay <stripe_event_id> | --file <payload.json>
+  kafka publish-test [--topic <topic>]
+  kafka replay <topic> --handler <analytics|room_list|player_stats> [--from <beginning|offset|time>]
+               [--partition <p>] [--rate <events/s>] [--dry-run]
+  user balance <user_id> [--limit <n>]
+  stats backfill [--limit <n>]
+  seed run [--reset]";
+
+const STRIPE_EVENTS_URL: &str = "https://api.stripe.com/v1/events";
+
+/// Positional arguments and `--flag value` pairs
+struct Args {
+    positional: Vec<String>,
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut positional = Vec::new();
+        let mut flags = HashMap::new();
+
+        let mut args = std::env::args().skip(1).peekable();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    // A flag followed by another flag is a switch (`--dry-run`)
+                    let value = args.next_if(|next| !next.starts_with("--"));
+                    flags.insert(name.to_string(), value.unwrap_or_default());
+                }
+                None => positional.push(arg),
+            }
+        }
+
+        Self { positional, flags }
+    }
+
+    fn command(&self) -> (&str, &str) {
+        let get = |i: usize| self.positional.get(i).map(String::as_str).unwrap_or("");
+        (get(0), get(1))
+    }
+
+    fn arg(&self, index: usize, name: &str) -> Result<&str, Box<dyn Error>> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing <{}>\n{}", name, USAGE).into())
+    }
+
+    fn flag(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(String::as_str)
+    }
+
+    fn limit(&self, default: i64) -> Result<i64, Box<dyn Error>> {
+        match self.flag("limit") {
+            Some(value) => Ok(value.parse().map_err(|_| "--limit must be a number")?),
+            None => Ok(default),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> CliResult {
+    dotenv::dotenv().ok();
+    SecretsConfig::load().await?;
+
+    let args = Args::parse();
+
+    match args.command() {
+        ("rooms", "list") => list_rooms(&args).await,
+        ("rooms", "close") => close_room(&args).await,
+        ("mq", "failed") => failed_jobs(&args).await,
+        ("mq", "requeue") => requeue_jobs(&args).await,
+        ("webhook", "replay") => replay_webhook(&args).await,
+        ("kafka", "publish-test") => publish_test_event(&args).await,
+        ("kafka", "replay") => replay_events(&args).await,
+        ("user", "balance") => user_balance(&args).await,
+        ("stats", "backfill") => backfill_player_stats(&args).await,
+        ("seed", "run") => seed_demo_data(&args).await,
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
+async fn connect_db() -> Result<Pool<Postgres>, Box<dyn Error>> {
+    let database_url = SecretsConfig::var("DATABASE_URL")?;
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+    Ok(pool)
+}
+
+/// The checkout service's database, for seeding its transactions
+async fn connect_checkout_db() -> Result<Pool<Postgres>, Box<dyn Error>> {
+    let database_url = SecretsConfig::var("CHECKOUT_DATABASE_URL")
+        .map_err(|_| "CHECKOUT_DATABASE_URL must be set to seed checkout transactions")?;
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+    Ok(pool)
+}
+
+async fn list_rooms(args: &Args) -> CliResult {
+    let db = connect_db().await?;
+    let rooms = game_room::list_active_rooms(&db, args.flag("game-type")).await?;
+
+    println!(
+        "{:<38} {:<14} {:<12} {:>7}  {:<8} {}",
+        "ROOM", "GAME", "STATUS", "PLAYERS", "HOST", "NAME"
+    );
+    for room in &rooms {
+        println!(
+            "{:<38} {:<14} {:<12} {:>7}  {:<8} {}{}",
+            room.room_id,
+            room.game_type,
+            room.status,
+            room.player_count,
+            room.host_id,
+            room.room_name,
+            if room.is_password_protected { " (locked)" } else { "" }
+        );
+    }
+    println!("{} active room(s)", rooms.len());
+
+    Ok(())
+}
+
+async fn close_room(args: &Args) -> CliResult {
+    let room_id = args.arg(2, "room_id")?;
+
+    let db = connect_db().await?;
+    let room = game_room::get_region(&db, room_id)
+        .await?
+        .ok_or_else(|| format!("room {} not found", room_id))?;
+
+    if room.status != "waiting" {
+        return Err(format!("room {} is {}; only waiting rooms can be closed", room_id, room.status).into());
+    }
+
+    let envelope = EventEnvelope {
+        event_id: Uuid::new_v4().to_string(),
+        event_type: "games.command.close_room".to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        correlation_id: None,
+        producer: "blazing_sun".to_string(),
+        actor: Actor {
+            user_id: 0,
+            username: "blazing_admin".to_string(),
+            socket_id: String::new(),
+            roles: vec!["admin".to_string()],
+        },
+        audience: Audience::room(room_id),
+        payload: serde_json::json!({ "room_id": room_id }),
+    };
+
+    let event_bus = events::init_producer().map_err(|e| e.to_string())?;
+    let commands_topic = topic::games_commands_for(&room.region);
+    event_bus
+        .producer()
+        .send_raw(&commands_topic, Some(room_id), &serde_json::to_vec(&envelope)?)
+        .await?;
+
+    println!(
+        "close_room for {} ({}) sent to {}",
+        room_id, room.room_name, commands_topic
+    );
+
+    Ok(())
+}
+
+async fn failed_jobs(args: &Args) -> CliResult {
+    let limit = args.limit(20)?;
+    let queue = MessageQueue::new(connect_db().await?).await?;
+    let jobs = queue.peek_failed(limit.max(0) as usize).await?;
+
+    println!("{:<38} {:<28} {:>8}  {}", "JOB", "WORKER", "ATTEMPTS", "FAILED AT");
+    for job in &jobs {
+        let failed_at = chrono::DateTime::from_timestamp_millis(job.updated_at)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        println!(
+            "{:<38} {:<28} {:>8}  {}",
+            job.id, job.worker_name, job.attempts, failed_at
+        );
+    }
+    println!("{} failed job(s) shown", jobs.len());
+
+    Ok(())
+}
+
+async fn requeue_jobs(args: &Args) -> CliResult {
+    let limit = args.limit(100)?;
+    let queue = MessageQueue::new(connect_db().await?).await?;
+    let requeued = queue
+        .requeue_failed(limit.max(0) as usize, args.flag("worker"))
+        .await?;
+
+    for job_id in &requeued {
+        println!("requeued {}", job_id);
+    }
+    println!("{} job(s) moved back to the main queue", requeued.len());
+
+    Ok(())
+}
+
+async fn replay_webhook(args: &Args) -> CliResult {
+    let client = reqwest::Client::new();
+
+    let payload = match args.flag("file") {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            // Stripe keeps events for 30 days; that is the store replays come from
+            let event_id = args.arg(2, "stripe_event_id")?;
+            let stripe_secret = std::env::var("STRIPE_SECRET")
+                .map_err(|_| "STRIPE_SECRET must be set to fetch events")?;
+            let response = client
+                .get(format!("{}/{}", STRIPE_EVENTS_URL, event_id))
+                .bearer_auth(stripe_secret)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(format!(
+                    "Stripe returned {} for {}: {}",
+                    response.status(),
+                    event_id,
+                    response.text().await.unwrap_or_default()
+                )
+                .into());
+            }
+            response.text().await?
+        }
+    };
+
+    let webhook_secret = std::env::var("STRIPE_WEBHOOK_SECRET")
+        .map_err(|_| "STRIPE_WEBHOOK_SECRET must be set to sign the replay")?;
+    let signature = stripe_signature(&webhook_secret, Utc::now().timestamp(), &payload)?;
+
+    let url = format!(
+        "{}/webhooks/stripe",
+        AppConfig::checkout_service_url().trim_end_matches('/')
+    );
+    let response = client
+        .post(&url)
+        .header("Stripe-Signature", signature)
+        .header("Content-Type", "application/json")
+        .body(payload)
+        .send()
+        .await?;
+
+    let status = response.status();
+    println!("{} -> {}", url, status);
+    println!("{}", response.text().await.unwrap_or_default());
+
+    if !status.is_success() {
+        return Err("checkout service rejected the replay".into());
+    }
+
+    Ok(())
+}
+
+/// `Stripe-Signature` header value for `payload` signed at `timestamp`
+fn stripe_signature(secret: &str, timestamp: i64, payload: &str) -> Result<String, Box<dyn Error>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+    Ok(format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes())))
+}
+
+async fn publish_test_event(args: &Args) -> CliResult {
+    let event = EventBuilder::new(EventType::System(SystemEventType::HealthCheck), "blazing_admin")
+        .payload(serde_json::json!({
+            "source": "blazing_admin",
+            "sent_at": Utc::now().to_rfc3339(),
+        }))
+        .correlation_id(&Uuid::new_v4().to_string())
+        .build();
+
+    let event_bus = events::init_producer().map_err(|e| e.to_string())?;
+    let target = match args.flag("topic") {
+        Some(topic) => {
+            event_bus
+                .producer()
+                .send_raw(topic, Some(&event.entity_id), &serde_json::to_vec(&event)?)
+                .await?;
+            topic.to_string()
+        }
+        None => {
+            event_bus.publish(&event).await?;
+            event.topic().to_string()
+        }
+    };
+
+    println!("test event {} published to {}", event.id, target);
+
+    Ok(())
+}
+
+async fn replay_events(args: &Args) -> CliResult {
+    let topic = args.arg(2, "topic")?;
+    let handler: Arc<dyn EventHandler> = match args.flag("handler") {
+        Some("analytics") => Arc::new(AnalyticsHandler::new(
+            blazing_sun::database::create_mongodb().await?,
+        )),
+        Some("room_list") => Arc::new(RoomListProjectionHandler::new(
+            Arc::new(Mutex::new(connect_db().await?)),
+            RoomListProjection::new(Some(blazing_sun::database::create_redis().await?)),
+        )),
+        Some("player_stats") => Arc::new(PlayerStatsHandler::new(Arc::new(Mutex::new(
+            connect_db().await?,
+        )))),
+        _ => {
+            return Err(format!("--handler must be analytics, room_list or player_stats\n{}", USAGE).into())
+        }
+    };
+
+    let mut options = ReplayOptions::new(topic).dry_run(args.flag("dry-run").is_some());
+    if let Some(from) = args.flag("from") {
+        options = options.start(from.parse()?);
+    }
+    if let Some(partition) = args.flag("partition") {
+        options = options.partition(partition.parse().map_err(|_| "--partition must be a number")?);
+    }
+    if let Some(rate) = args.flag("rate") {
+        options = options.rate_per_second(rate.parse().map_err(|_| "--rate must be a number")?);
+    }
+
+    let replay = EventReplay::new(handler.clone(), options)?;
+    let progress = replay.run(|progress| println!("{}", progress)).await?;
+
+    for (partition, range) in &progress.partitions {
+        println!("partition {}: at {} of {}", partition, range.position, range.end);
+    }
+    println!(
+        "{} {} event(s) of {} into {}",
+        if args.flag("dry-run").is_some() { "decoded" } else { "replayed" },
+        progress.handled,
+        topic,
+        handler.name()
+    );
+
+    Ok(())
+}
+
+async fn user_balance(args: &Args) -> CliResult {
+    let user_id: i64 = args
+        .arg(2, "user_id")?
+        .parse()
+        .map_err(|_| "user_id must be a number")?;
+    let limit = args.limit(20)?;
+
+    let db = connect_db().await?;
+    let account = user::get_by_id(&db, user_id).await?;
+    let entries = balance_ledger::get_by_user_before(&db, user_id, None, limit).await?;
+
+    println!("user {} <{}>", account.id, account.email);
+    println!("balance: {} cents", account.balance);
+    println!();
+    println!(
+        "{:<26} {:>12} {:>14}  {:<20} {}",
+        "AT", "AMOUNT", "BALANCE AFTER", "SOURCE", "REFERENCE"
+    );
+    for entry in &entries {
+        println!(
+            "{:<26} {:>12} {:>14}  {:<20} {}",
+            entry.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            entry.amount_cents,
+            entry.balance_after,
+            entry.source,
+            entry.reference_id.as_deref().unwrap_or("-")
+        );
+    }
+
+    Ok(())
+}
+
+async fn backfill_player_stats(args: &Args) -> CliResult {
+    let batch = args.limit(500)?;
+    let db = connect_db().await?;
+    let mut recorded = 0;
+    let mut players: BTreeSet<(i64, String)> = BTreeSet::new();
+
+    let mut record = |new: Vec<MatchResult>| {
+        recorded += new.len();
+        players.extend(new.into_iter().map(|r| (r.user_id, r.game_type)));
+    };
+
+    // Game history archived in MongoDB (finished rooms are deleted from Postgres)
+    let games = MongoGameClient::new(blazing_sun::database::create_mongodb().await?);
+    let mut after = None;
+    loop {
+        let page = games.get_games_after(after, batch).await?;
+        for game in &page {
+            let player_ids: Vec<i64> = game.players.iter().map(|p| p.user_id).collect();
+            let results = player_stats::results(
+                &game.room_id,
+                game.game_type.as_str(),
+                &player_ids,
+                game.winner_id,
+                Some(game.started_at),
+                game.finished_at,
+            );
+            record(player_stats_mutations::insert_results(&db, &results).await?);
+        }
+        after = match page.last() {
+            Some(game) if page.len() as i64 == batch && game.id.is_some() => game.id,
+            _ => break,
+        };
+    }
+
+    // Finished rooms still in game_rooms
+    let mut after = None;
+    loop {
+        let page = player_stats_read::finished_rooms_after(&db, after, batch).await?;
+        for room in &page {
+            let Some(finished_at) = room.finished_at else {
+                continue;
+            };
+            let results = player_stats::results(
+                &room.room_id,
+                &room.game_type,
+                &room.players,
+                room.winner_id,
+                room.started_at,
+                finished_at,
+            );
+            record(player_stats_mutations::insert_results(&db, &results).await?);
+        }
+        after = match page.last() {
+            Some(room) if page.len() as i64 == batch => {
+                room.finished_at.map(|at| (at, room.room_id.clone()))
+            }
+            _ => break,
+        };
+    }
+
+    for (user_id, game_type) in &players {
+        player_stats_mutations::rebuild(&db, *user_id, game_type).await?;
+    }
+
+    println!(
+        "recorded {} result(s), rebuilt stats of {} player/game type pair(s)",
+        recorded,
+        players.len()
+    );
+
+    Ok(())
+}
+
+async fn seed_demo_data(args: &Args) -> CliResult {
+    if !AppConfig::is_development() {
+        return Err(format!(
+            "refusing to seed with APP_ENV={}; demo data is for development databases only",
+            AppConfig::environment()
+        )
+        .into());
+    }
+
+    let db = connect_db().await?;
+    let checkout_db = connect_checkout_db().await?;
+    let report = seed::run(&db, &checkout_db, args.flag("reset").is_some()).await?;
+
+    if let Some(deleted) = &report.deleted {
+        println!(
+            "reset: deleted {} user(s), {} room(s), {} processed event(s), {} checkout transaction(s)",
+            deleted.users, deleted.rooms, deleted.processed_events, deleted.checkout_transactions
+        );
+    }
+    println!("{:<22} {:>6}  {:<10} {}", "USER", "ID", "ROLE", "TOKEN");
+    for user in &report.users {
+        println!(
+            "{:<22} {:>6}  {:<10} {}",
+            user.email, user.id, user.role, user.token
+        );
+    }
+    println!();
+    println!(
+        "password of every user: {} (locked room: {})",
+        seed::DEMO_PASSWORD,
+        seed::ROOM_PASSWORD
+    );
+    println!(
+        "{} room(s) seeded, {} ledger entries applied, {} already applied, {} checkout transaction(s) upserted",
+        report.rooms, report.ledger_applied, report.ledger_skipped, report.checkout_transactions
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stripe_signature_matches_the_documented_scheme() {
+        let header = stripe_signature("whsec_test", 1_700_000_000, "{\"id\":\"evt_1\"}").unwrap();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"whsec_test").unwrap();
+        mac.update(b"1700000000.{\"id\":\"evt_1\"}");
+        let expected = hex::encode(mac.finalize().into_bytes());
+
+        assert_eq!(header, format!("t=1700000000,v1={}", expected));
+    }
+}
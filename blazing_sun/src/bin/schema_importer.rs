@@ -3,6 +3,7 @@
 //! Usage:
 //!   cargo run --bin schema_importer -- --source <url-or-path> [--reset]
 
+use blazing_sun::config::SecretsConfig;
 use serde_json::Value;
 use sqlx::postgres::PgPoolOptions;
 use std::collections::{HashMap, HashSet};
@@ -273,6 +274,7 @@ fn parse_schema_graph(
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     dotenv::dotenv().ok();
+    SecretsConfig::load().await?;
 
     let mut source = "https://schema.org/version/latest/schemaorg-current-https.jsonld".to_string();
     let mut reset = false;
@@ -296,7 +298,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let json: Value = serde_json::from_str(&body)?;
     let (types, properties) = parse_schema_graph(&json)?;
 
-    let database_url = std::env::var("DATABASE_URL")?;
+    let database_url = SecretsConfig::var("DATABASE_URL")?;
     let pool = PgPoolOptions::new()
         .max_connections(5)
         .connect(&database_url)
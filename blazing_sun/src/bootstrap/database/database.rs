@@ -2,7 +2,10 @@
 //!
 //! This module provides database connection pooling and application state management.
 
-use crate::config::{DatabaseConfig, JwtConfig, MongoDbConfig, OAuthConfig};
+use super::pools::{create_pool, PoolRole};
+use super::replica::{self, ReadReplica};
+use crate::config::{JwtConfig, MongoDbConfig, OAuthConfig, RedisConfig};
+use crate::app::feature_flags::FeatureFlags;
 use crate::events::SharedEventBus;
 use actix_web::web;
 use mongodb::{Client as MongoClient, Database as MongoDatabase};
@@ -18,13 +21,20 @@ pub type DynMq = Arc<Mutex<dyn Any + Send + Sync>>;
 /// Type alias for shared MongoDB client
 pub type SharedMongoDb = Arc<MongoDatabase>;
 
+/// Type alias for shared Redis connection (cheap to clone, reconnects automatically)
+pub type SharedRedis = redis::aio::ConnectionManager;
+
 /// Application state shared across all request handlers
 pub struct AppState {
+    /// Primary pool: mutations, transactions and reads that decide a write
     pub db: Mutex<sqlx::Pool<sqlx::Postgres>>,
+    /// Read replica pool (DATABASE_READ_URL), see `read_db`
+    pub db_read: Option<Arc<ReadReplica>>,
     pub jwt_secret: &'static str,
     pub mq: Option<DynMq>,
     pub events: Option<SharedEventBus>,
     pub mongodb: Option<SharedMongoDb>,
+    pub redis: Option<SharedRedis>,
     /// OAuth public key path for JWT verification (RS256)
     pub oauth_public_key_path: &'static str,
     /// OAuth private key path for JWT signing (RS256)
@@ -40,6 +50,24 @@ pub struct AppState {
 }
 
 impl AppState {
+    /// Pool for `db_query::read` queries: the replica while one is configured
+    /// and healthy, otherwise the primary
+    pub async fn read_db(&self) -> Pool<Postgres> {
+        match self.db_read.as_ref().and_then(|replica| replica.pool()) {
+            Some(pool) => pool.clone(),
+            None => self.db.lock().await.clone(),
+        }
+    }
+
+    /// Like `read_db` for reads of the user's own data, but the primary while
+    /// the user's latest write may not have reached the replica yet
+    pub async fn read_db_for(&self, user_id: i64) -> Pool<Postgres> {
+        match self.db_read.as_ref().and_then(|replica| replica.pool()) {
+            Some(pool) if !replica::recently_written(user_id).await => pool.clone(),
+            _ => self.db.lock().await.clone(),
+        }
+    }
+
     /// Get the event bus for publishing events
     pub fn event_bus(&self) -> Option<&SharedEventBus> {
         self.events.as_ref()
@@ -49,15 +77,17 @@ impl AppState {
     pub fn mongo(&self) -> Option<&SharedMongoDb> {
         self.mongodb.as_ref()
     }
-}
 
-/// Create a new PostgreSQL connection pool
-pub async fn create_pool() -> Pool<Postgres> {
-    sqlx::postgres::PgPoolOptions::new()
-        .max_connections(DatabaseConfig::max_connections())
-        .connect(DatabaseConfig::url())
-        .await
-        .unwrap()
+    /// Get a handle to the shared Redis connection
+    pub fn redis(&self) -> Option<SharedRedis> {
+        self.redis.clone()
+    }
+
+    /// Get a feature flag evaluator backed by this state's database and Redis
+    pub async fn feature_flags(&self) -> FeatureFlags {
+        let db = self.db.lock().await.clone();
+        FeatureFlags::new(db, self.redis())
+    }
 }
 
 /// Create a new MongoDB client and return the database
@@ -84,14 +114,39 @@ pub async fn create_mongodb() -> Result<SharedMongoDb, mongodb::error::Error> {
     Ok(Arc::new(database))
 }
 
+/// Create a new Redis connection manager on the first endpoint that answers
+/// (REDIS_URL, then REDIS_FALLBACK_URLS in order)
+pub async fn create_redis() -> Result<SharedRedis, redis::RedisError> {
+    let mut result = connect_redis(RedisConfig::url()).await;
+
+    for url in RedisConfig::fallback_urls() {
+        match result {
+            Ok(_) => break,
+            Err(e) => {
+                tracing::warn!("Redis endpoint unavailable, trying the next one: {}", e);
+                result = connect_redis(url).await;
+            }
+        }
+    }
+
+    result
+}
+
+async fn connect_redis(url: &str) -> Result<SharedRedis, redis::RedisError> {
+    let client = redis::Client::open(url)?;
+    redis::aio::ConnectionManager::new(client).await
+}
+
 /// Create application state without MQ or events
 pub async fn state() -> web::Data<AppState> {
     web::Data::new(AppState {
-        db: Mutex::new(create_pool().await),
+        db: Mutex::new(create_pool(PoolRole::Http).await),
+        db_read: ReadReplica::connect(PoolRole::Http),
         jwt_secret: JwtConfig::secret(),
         mq: None,
         events: None,
         mongodb: None,
+        redis: None,
         oauth_public_key_path: OAuthConfig::jwt_public_key_path(),
         oauth_private_key_path: OAuthConfig::jwt_private_key_path(),
         oauth_issuer: OAuthConfig::jwt_issuer(),
@@ -104,11 +159,13 @@ pub async fn state() -> web::Data<AppState> {
 /// Create application state with message queue
 pub async fn state_with_mq(mq: DynMq) -> web::Data<AppState> {
     web::Data::new(AppState {
-        db: Mutex::new(create_pool().await),
+        db: Mutex::new(create_pool(PoolRole::Http).await),
+        db_read: ReadReplica::connect(PoolRole::Http),
         jwt_secret: JwtConfig::secret(),
         mq: Some(mq),
         events: None,
         mongodb: None,
+        redis: None,
         oauth_public_key_path: OAuthConfig::jwt_public_key_path(),
         oauth_private_key_path: OAuthConfig::jwt_private_key_path(),
         oauth_issuer: OAuthConfig::jwt_issuer(),
@@ -121,11 +178,13 @@ pub async fn state_with_mq(mq: DynMq) -> web::Data<AppState> {
 /// Create application state with message queue and event bus
 pub async fn state_with_mq_and_events(mq: DynMq, events: SharedEventBus) -> web::Data<AppState> {
     web::Data::new(AppState {
-        db: Mutex::new(create_pool().await),
+        db: Mutex::new(create_pool(PoolRole::Http).await),
+        db_read: ReadReplica::connect(PoolRole::Http),
         jwt_secret: JwtConfig::secret(),
         mq: Some(mq),
         events: Some(events),
         mongodb: None,
+        redis: None,
         oauth_public_key_path: OAuthConfig::jwt_public_key_path(),
         oauth_private_key_path: OAuthConfig::jwt_private_key_path(),
         oauth_issuer: OAuthConfig::jwt_issuer(),
@@ -135,18 +194,21 @@ pub async fn state_with_mq_and_events(mq: DynMq, events: SharedEventBus) -> web:
     })
 }
 
-/// Create application state with all services (MQ, Events, MongoDB)
+/// Create application state with all services (MQ, Events, MongoDB, Redis)
 pub async fn state_full(
     mq: DynMq,
     events: Option<SharedEventBus>,
     mongodb: Option<SharedMongoDb>,
+    redis: Option<SharedRedis>,
 ) -> web::Data<AppState> {
     web::Data::new(AppState {
-        db: Mutex::new(create_pool().await),
+        db: Mutex::new(create_pool(PoolRole::Http).await),
+        db_read: ReadReplica::connect(PoolRole::Http),
         jwt_secret: JwtConfig::secret(),
         mq: Some(mq),
         events,
         mongodb,
+        redis,
         oauth_public_key_path: OAuthConfig::jwt_public_key_path(),
         oauth_private_key_path: OAuthConfig::jwt_private_key_path(),
         oauth_issuer: OAuthConfig::jwt_issuer(),
@@ -1,12 +1,17 @@
 //! Database module
 //!
-//! Provides database connection pooling, application state management,
-//! and re-exports query modules.
+//! Provides database connection pooling, read replica routing, application
+//! state management, and re-exports query modules.
 
 pub mod database;
+pub mod pools;
+pub mod replica;
+pub mod schema_check;
 
 // Re-export connection and state
 pub use database::*;
+pub use pools::{create_pool, pool_stats, PoolRole, PoolStats};
+pub use replica::{replica_stats, ReadReplica, ReplicaStatus};
 
 // Re-export query modules from app
 pub use crate::app::db_query::{mutations, read};
@@ -0,0 +1,277 @@
+//! Per-Subsystem Connection Pools
+//!
+//! The HTTP handlers, cron jobs, MQ workers and Kafka event handlers each get
+//! their own PostgreSQL pool, sized from `DatabaseConfig`, so one busy
+//! subsystem cannot take every connection from the others.
+//!
+//! A sampler per pool records its usage (open, idle and busy connections) and
+//! times a connection checkout every `DATABASE_POOL_SAMPLE_INTERVAL_SECS`. A
+//! checkout slower than `DATABASE_SLOW_ACQUIRE_MS` is logged with the pool's
+//! label; sqlx also logs each slow checkout of a query (target
+//! `sqlx::pool::acquire`) inside the span of the caller that waited.
+//! `pool_stats()` serves the counters to `GET /api/v1/admin/database/pools`.
+
+use crate::config::{DatabaseConfig, PoolSettings};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{Pool, Postgres};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Subsystem a pool serves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolRole {
+    Http,
+    Cron,
+    Mq,
+    Events,
+}
+
+impl PoolRole {
+    pub const ALL: [PoolRole; 4] = [
+        PoolRole::Http,
+        PoolRole::Cron,
+        PoolRole::Mq,
+        PoolRole::Events,
+    ];
+
+    /// Label of the pool in logs and stats
+    pub fn label(self) -> &'static str {
+        match self {
+            PoolRole::Http => "http",
+            PoolRole::Cron => "cron",
+            PoolRole::Mq => "mq",
+            PoolRole::Events => "events",
+        }
+    }
+
+    /// Sizing and timeouts of the pool
+    pub fn settings(self) -> PoolSettings {
+        match self {
+            PoolRole::Http => DatabaseConfig::http_pool(),
+            PoolRole::Cron => DatabaseConfig::cron_pool(),
+            PoolRole::Mq => DatabaseConfig::mq_pool(),
+            PoolRole::Events => DatabaseConfig::events_pool(),
+        }
+    }
+
+    pub(super) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Sampler counters of one pool
+#[derive(Default)]
+struct PoolCounters {
+    samples: AtomicU64,
+    slow_acquires: AtomicU64,
+    acquire_timeouts: AtomicU64,
+    last_acquire_ms: AtomicU64,
+    max_acquire_ms: AtomicU64,
+    peak_in_use: AtomicU64,
+}
+
+impl PoolCounters {
+    /// Record a timed checkout; true if it was slow
+    fn record_acquire(&self, waited_ms: u64, slow_ms: u64) -> bool {
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        self.last_acquire_ms.store(waited_ms, Ordering::Relaxed);
+        self.max_acquire_ms.fetch_max(waited_ms, Ordering::Relaxed);
+
+        let slow = waited_ms >= slow_ms;
+        if slow {
+            self.slow_acquires.fetch_add(1, Ordering::Relaxed);
+        }
+        slow
+    }
+
+    fn record_in_use(&self, in_use: u32) {
+        self.peak_in_use
+            .fetch_max(u64::from(in_use), Ordering::Relaxed);
+    }
+}
+
+/// Counters per pool role, kept per process and reset on restart
+static COUNTERS: Lazy<[PoolCounters; 4]> = Lazy::new(Default::default);
+
+/// Latest pool of each role
+type RolePools = [Option<Pool<Postgres>>; 4];
+
+static POOLS: Lazy<RwLock<RolePools>> = Lazy::new(|| RwLock::new(Default::default()));
+
+/// Usage of one pool
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub pool: &'static str,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_ms: u64,
+    pub statement_timeout_ms: u64,
+    /// Open connections
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub peak_in_use: u64,
+    /// Timed checkouts by the sampler
+    pub samples: u64,
+    pub slow_acquires: u64,
+    pub acquire_timeouts: u64,
+    pub last_acquire_ms: u64,
+    pub max_acquire_ms: u64,
+}
+
+/// Connections handed out and not returned yet
+fn in_use(pool: &Pool<Postgres>) -> u32 {
+    pool.size().saturating_sub(pool.num_idle() as u32)
+}
+
+/// Connect options of a pool on `url`, with the role's statement timeout
+pub(super) fn connect_options(
+    url: &str,
+    settings: &PoolSettings,
+) -> Result<PgConnectOptions, sqlx::Error> {
+    let mut connect_options: PgConnectOptions = url.parse()?;
+    if settings.statement_timeout_ms > 0 {
+        connect_options = connect_options.options([(
+            "statement_timeout",
+            settings.statement_timeout_ms.to_string(),
+        )]);
+    }
+    Ok(connect_options)
+}
+
+/// Sizing and timeouts of a pool
+pub(super) fn pool_options(settings: &PoolSettings) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(settings.max_connections)
+        .min_connections(settings.min_connections)
+        .acquire_timeout(Duration::from_millis(settings.acquire_timeout_ms))
+        .acquire_slow_threshold(Duration::from_millis(DatabaseConfig::slow_acquire_ms()))
+}
+
+/// Create the pool of a subsystem and start sampling it
+pub async fn create_pool(role: PoolRole) -> Pool<Postgres> {
+    let settings = role.settings();
+
+    let connect_options = connect_options(DatabaseConfig::url(), &settings)
+        .expect("DATABASE_URL must be a valid PostgreSQL URL");
+
+    let pool = pool_options(&settings)
+        .connect_with(connect_options)
+        .await
+        .unwrap();
+
+    POOLS.write().unwrap_or_else(|e| e.into_inner())[role.index()] = Some(pool.clone());
+    tokio::spawn(sample(role, pool.clone()));
+
+    pool
+}
+
+/// Record the pool's usage and time a checkout until the pool is closed
+async fn sample(role: PoolRole, pool: Pool<Postgres>) {
+    let counters = &COUNTERS[role.index()];
+    let slow_ms = DatabaseConfig::slow_acquire_ms();
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        DatabaseConfig::pool_sample_interval_secs(),
+    ));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        if pool.is_closed() {
+            break;
+        }
+        counters.record_in_use(in_use(&pool));
+
+        let started = Instant::now();
+        match pool.acquire().await {
+            Ok(conn) => {
+                let waited_ms = started.elapsed().as_millis() as u64;
+                drop(conn);
+                if counters.record_acquire(waited_ms, slow_ms) {
+                    warn!(
+                        pool = role.label(),
+                        waited_ms,
+                        size = pool.size(),
+                        idle = pool.num_idle(),
+                        max_connections = role.settings().max_connections,
+                        "Slow database connection acquisition"
+                    );
+                }
+            }
+            Err(sqlx::Error::PoolClosed) => break,
+            Err(sqlx::Error::PoolTimedOut) => {
+                counters.acquire_timeouts.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    pool = role.label(),
+                    size = pool.size(),
+                    max_connections = role.settings().max_connections,
+                    "Timed out waiting for a database connection"
+                );
+            }
+            Err(e) => warn!(pool = role.label(), error = %e, "Database pool sample failed"),
+        }
+    }
+
+    let mut pools = POOLS.write().unwrap_or_else(|e| e.into_inner());
+    if pools[role.index()].as_ref().is_some_and(Pool::is_closed) {
+        pools[role.index()] = None;
+    }
+}
+
+/// Usage of this process's open pools
+pub fn pool_stats() -> Vec<PoolStats> {
+    let pools = POOLS.read().unwrap_or_else(|e| e.into_inner());
+
+    PoolRole::ALL
+        .into_iter()
+        .filter_map(|role| {
+            let pool = pools[role.index()]
+                .as_ref()
+                .filter(|pool| !pool.is_closed())?;
+            let settings = role.settings();
+            let counters = &COUNTERS[role.index()];
+            Some(PoolStats {
+                pool: role.label(),
+                max_connections: settings.max_connections,
+                min_connections: settings.min_connections,
+                acquire_timeout_ms: settings.acquire_timeout_ms,
+                statement_timeout_ms: settings.statement_timeout_ms,
+                size: pool.size(),
+                idle: pool.num_idle() as u32,
+                in_use: in_use(pool),
+                peak_in_use: counters.peak_in_use.load(Ordering::Relaxed),
+                samples: counters.samples.load(Ordering::Relaxed),
+                slow_acquires: counters.slow_acquires.load(Ordering::Relaxed),
+                acquire_timeouts: counters.acquire_timeouts.load(Ordering::Relaxed),
+                last_acquire_ms: counters.last_acquire_ms.load(Ordering::Relaxed),
+                max_acquire_ms: counters.max_acquire_ms.load(Ordering::Relaxed),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkouts_at_the_threshold_are_slow() {
+        let counters = PoolCounters::default();
+
+        assert!(!counters.record_acquire(3, 500));
+        assert!(counters.record_acquire(800, 500));
+        assert!(counters.record_acquire(500, 500));
+        counters.record_in_use(4);
+        counters.record_in_use(2);
+
+        assert_eq!(counters.samples.load(Ordering::Relaxed), 3);
+        assert_eq!(counters.slow_acquires.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.last_acquire_ms.load(Ordering::Relaxed), 500);
+        assert_eq!(counters.max_acquire_ms.load(Ordering::Relaxed), 800);
+        assert_eq!(counters.peak_in_use.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,289 @@
+//! Read Replica Routing
+//!
+//! With `DATABASE_READ_URL` set, the HTTP handlers get a second pool on the
+//! replica, sized like their primary pool. Routing is by convention:
+//! queries in `db_query::read` run on `AppState::read_db()`, or on
+//! `AppState::read_db_for(user_id)` where the user reads back their own
+//! write; queries in `db_query::mutations`, transactions, and reads that
+//! decide a write run on the primary `AppState::db`.
+//!
+//! A monitor checks the replica every `DATABASE_READ_HEALTH_INTERVAL_SECS`.
+//! While the check fails or the replay lag exceeds `DATABASE_READ_MAX_LAG_MS`
+//! the replica is unhealthy and reads fall back to the primary.
+//!
+//! Read-your-writes: `mark_written(user_id)` after a write the user reads
+//! back right away (a checkout credit, say) keeps that user's `read_db_for`
+//! reads on the primary for `DATABASE_READ_YOUR_WRITES_MS`. The mark is
+//! stored in Redis so every instance sees it, and in process as well.
+
+use super::database::{create_redis, SharedRedis};
+use super::pools::{connect_options, pool_options, PoolRole};
+use crate::config::DatabaseConfig;
+use once_cell::sync::Lazy;
+use redis::AsyncCommands;
+use serde::Serialize;
+use sqlx::{Pool, Postgres, Row};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Longest a health check may take before it counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Replay lag of the replica in ms (0 when it has replayed everything it
+/// received, or when it is not a standby at all)
+const LAG_QUERY: &str = r#"
+    SELECT CASE
+        WHEN NOT pg_is_in_recovery() THEN 0
+        WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0
+        ELSE COALESCE(EXTRACT(EPOCH FROM (now() - pg_last_xact_replay_timestamp())) * 1000, 0)
+    END::BIGINT AS lag_ms
+"#;
+
+/// A role's pool on the read replica and its latest health check
+pub struct ReadReplica {
+    role: PoolRole,
+    pool: Pool<Postgres>,
+    healthy: AtomicBool,
+    lag_ms: AtomicU64,
+    checks: AtomicU64,
+    failed_checks: AtomicU64,
+}
+
+/// Health of one replica pool
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplicaStatus {
+    pub pool: &'static str,
+    pub healthy: bool,
+    /// Replay lag at the last successful check
+    pub lag_ms: u64,
+    pub max_lag_ms: u64,
+    pub checks: u64,
+    pub failed_checks: u64,
+    /// Open connections
+    pub size: u32,
+    pub idle: u32,
+}
+
+/// Latest replica of each role
+static REPLICAS: Lazy<RwLock<[Option<Arc<ReadReplica>>; 4]>> =
+    Lazy::new(|| RwLock::new(Default::default()));
+
+impl ReadReplica {
+    /// Replica pool of a role and its monitor, or None without
+    /// DATABASE_READ_URL. Connects lazily: a replica that is down at startup
+    /// only sends reads to the primary until it answers.
+    pub fn connect(role: PoolRole) -> Option<Arc<Self>> {
+        let url = DatabaseConfig::read_url()?;
+        let settings = role.settings();
+
+        let connect_options = match connect_options(url, &settings) {
+            Ok(options) => options,
+            Err(e) => {
+                warn!(pool = role.label(), error = %e, "Invalid DATABASE_READ_URL, reads use the primary");
+                return None;
+            }
+        };
+
+        let replica = Arc::new(Self {
+            role,
+            pool: pool_options(&settings).connect_lazy_with(connect_options),
+            healthy: AtomicBool::new(false),
+            lag_ms: AtomicU64::new(0),
+            checks: AtomicU64::new(0),
+            failed_checks: AtomicU64::new(0),
+        });
+
+        REPLICAS.write().unwrap_or_else(|e| e.into_inner())[role.index()] = Some(replica.clone());
+        tokio::spawn(replica.clone().monitor());
+
+        Some(replica)
+    }
+
+    /// The replica pool while it is healthy
+    pub fn pool(&self) -> Option<&Pool<Postgres>> {
+        self.healthy.load(Ordering::Relaxed).then_some(&self.pool)
+    }
+
+    /// Check health and lag until the pool is closed
+    async fn monitor(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            DatabaseConfig::read_health_interval_secs(),
+        ));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if self.pool.is_closed() {
+                break;
+            }
+            self.checks.fetch_add(1, Ordering::Relaxed);
+
+            let lag = match tokio::time::timeout(CHECK_TIMEOUT, self.lag_ms()).await {
+                Ok(Ok(lag_ms)) => Ok(lag_ms),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("health check timed out".to_string()),
+            };
+            let healthy = match lag {
+                Ok(lag_ms) => {
+                    self.lag_ms.store(lag_ms, Ordering::Relaxed);
+                    is_usable(lag_ms, DatabaseConfig::read_max_lag_ms())
+                }
+                Err(ref e) => {
+                    self.failed_checks.fetch_add(1, Ordering::Relaxed);
+                    warn!(pool = self.role.label(), error = %e, "Read replica health check failed");
+                    false
+                }
+            };
+
+            let was_healthy = self.healthy.swap(healthy, Ordering::Relaxed);
+            let pool = self.role.label();
+            let lag_ms = self.lag_ms.load(Ordering::Relaxed);
+            if was_healthy && !healthy {
+                warn!(pool, lag_ms, "Read replica unhealthy, using the primary");
+            } else if !was_healthy && healthy {
+                info!(pool, lag_ms, "Read replica healthy, using it for reads");
+            }
+        }
+
+        let mut replicas = REPLICAS.write().unwrap_or_else(|e| e.into_inner());
+        if replicas[self.role.index()]
+            .as_ref()
+            .is_some_and(|replica| Arc::ptr_eq(replica, &self))
+        {
+            replicas[self.role.index()] = None;
+        }
+    }
+
+    async fn lag_ms(&self) -> Result<u64, sqlx::Error> {
+        let row = sqlx::query(LAG_QUERY).fetch_one(&self.pool).await?;
+        Ok(row.get::<i64, _>("lag_ms").max(0) as u64)
+    }
+
+    fn status(&self) -> ReplicaStatus {
+        ReplicaStatus {
+            pool: self.role.label(),
+            healthy: self.healthy.load(Ordering::Relaxed),
+            lag_ms: self.lag_ms.load(Ordering::Relaxed),
+            max_lag_ms: DatabaseConfig::read_max_lag_ms(),
+            checks: self.checks.load(Ordering::Relaxed),
+            failed_checks: self.failed_checks.load(Ordering::Relaxed),
+            size: self.pool.size(),
+            idle: self.pool.num_idle() as u32,
+        }
+    }
+}
+
+/// Whether a replica lagging `lag_ms` may serve reads
+fn is_usable(lag_ms: u64, max_lag_ms: u64) -> bool {
+    lag_ms <= max_lag_ms
+}
+
+/// Health of this process's replica pools
+pub fn replica_stats() -> Vec<ReplicaStatus> {
+    let replicas = REPLICAS.read().unwrap_or_else(|e| e.into_inner());
+    PoolRole::ALL
+        .into_iter()
+        .filter_map(|role| replicas[role.index()].as_ref().map(|r| r.status()))
+        .collect()
+}
+
+// =============================================================================
+// Read-your-writes
+// =============================================================================
+
+/// Users whose reads stay on the primary, until when (this process)
+static WRITTEN: Lazy<Mutex<HashMap<i64, Instant>>> = Lazy::new(Default::default);
+
+/// Redis for marks shared between instances, connected on first use
+static MARKS_REDIS: tokio::sync::OnceCell<Option<SharedRedis>> = tokio::sync::OnceCell::const_new();
+
+fn mark_key(user_id: i64) -> String {
+    format!("db:read_primary:{}", user_id)
+}
+
+async fn marks_redis() -> Option<SharedRedis> {
+    MARKS_REDIS
+        .get_or_init(|| async {
+            create_redis()
+                .await
+                .map_err(|e| {
+                    warn!(
+                        "Redis unavailable, read-your-writes marks stay in process: {}",
+                        e
+                    )
+                })
+                .ok()
+        })
+        .await
+        .clone()
+}
+
+/// Keep the user's `read_db_for` reads on the primary for
+/// DATABASE_READ_YOUR_WRITES_MS; a no-op without a read replica
+pub async fn mark_written(user_id: i64) {
+    let window_ms = DatabaseConfig::read_your_writes_ms();
+    if DatabaseConfig::read_url().is_none() || window_ms == 0 {
+        return;
+    }
+
+    let now = Instant::now();
+    {
+        let mut written = WRITTEN.lock().unwrap_or_else(|e| e.into_inner());
+        written.retain(|_, until| *until > now);
+        written.insert(user_id, now + Duration::from_millis(window_ms));
+    }
+
+    if let Some(mut redis) = marks_redis().await {
+        let stored: Result<(), _> = redis.pset_ex(mark_key(user_id), 1, window_ms).await;
+        if let Err(e) = stored {
+            warn!(
+                "Failed to store read-your-writes mark of user {}: {}",
+                user_id, e
+            );
+        }
+    }
+}
+
+/// Whether the user wrote recently enough that the replica may not have it
+pub async fn recently_written(user_id: i64) -> bool {
+    let local = WRITTEN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&user_id)
+        .is_some_and(|until| *until > Instant::now());
+    if local {
+        return true;
+    }
+
+    let Some(mut redis) = marks_redis().await else {
+        return false;
+    };
+    match redis.exists::<_, bool>(mark_key(user_id)).await {
+        Ok(marked) => marked,
+        Err(e) => {
+            // Unknown: the primary is always consistent
+            warn!(
+                "Failed to read read-your-writes mark of user {}: {}",
+                user_id, e
+            );
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replicas_lagging_past_the_limit_are_skipped() {
+        assert!(is_usable(0, 1_000));
+        assert!(is_usable(1_000, 1_000));
+        assert!(!is_usable(1_001, 1_000));
+        assert!(!is_usable(1, 0));
+        assert_eq!(mark_key(42), "db:read_primary:42");
+    }
+}
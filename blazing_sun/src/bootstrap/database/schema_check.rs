@@ -0,0 +1,322 @@
+//! Startup Schema Drift Detection
+//!
+//! The binary embeds the migrations it was compiled with. Before serving traffic
+//! the live database is compared against them:
+//! - every embedded migration must be applied, successful and have the same checksum
+//! - the columns read by hot queries must exist
+//!
+//! Migrations applied to the database but unknown to this binary (a newer release
+//! already migrated) are only logged, so rolling deploys keep working.
+//!
+//! Controlled by `DATABASE_SCHEMA_CHECK`: `enforce` (default) refuses to start on
+//! drift, `warn` only logs it, `off` skips the check.
+
+use sqlx::migrate::Migrator;
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use tracing::{info, warn};
+
+use crate::config::DatabaseConfig;
+
+/// Migrations embedded at compile time
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Columns used by hot queries, checked in addition to the migration history
+const EXPECTED_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "users",
+        &[
+            "id", "email", "password", "first_name", "last_name", "balance", "permissions",
+            "activated",
+        ],
+    ),
+    (
+        "game_rooms",
+        &[
+            "room_id", "room_name", "game_type", "status", "host_id", "players", "lobby",
+            "spectators", "is_active", "selected_players", "auto_players", "region",
+        ],
+    ),
+    (
+        "uploads",
+        &["id", "uuid", "stored_name", "storage_type", "storage_path", "user_id"],
+    ),
+    (
+        "feature_flags",
+        &["key", "enabled", "rollout_percentage", "allowlist"],
+    ),
+];
+
+/// How a detected drift is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaCheckMode {
+    Enforce,
+    Warn,
+    Off,
+}
+
+impl SchemaCheckMode {
+    pub fn from_config() -> Self {
+        match DatabaseConfig::schema_check().to_ascii_lowercase().as_str() {
+            "off" | "false" | "0" => SchemaCheckMode::Off,
+            "warn" => SchemaCheckMode::Warn,
+            _ => SchemaCheckMode::Enforce,
+        }
+    }
+}
+
+/// A single difference between the expected and the live schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    MigrationMissing { version: i64, description: String },
+    MigrationFailed { version: i64, description: String },
+    ChecksumMismatch { version: i64, description: String },
+    ColumnMissing { table: String, column: String },
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaIssue::MigrationMissing { version, description } => {
+                write!(f, "migration {} ({}) is not applied", version, description)
+            }
+            SchemaIssue::MigrationFailed { version, description } => {
+                write!(f, "migration {} ({}) is recorded as failed", version, description)
+            }
+            SchemaIssue::ChecksumMismatch { version, description } => write!(
+                f,
+                "migration {} ({}) was edited after it was applied (checksum mismatch)",
+                version, description
+            ),
+            SchemaIssue::ColumnMissing { table, column } => {
+                write!(f, "column {}.{} does not exist", table, column)
+            }
+        }
+    }
+}
+
+/// Errors returned by the schema check
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaCheckError {
+    #[error("failed to inspect database schema: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("{}", drift_report(.0))]
+    Drift(Vec<SchemaIssue>),
+}
+
+fn drift_report(issues: &[SchemaIssue]) -> String {
+    let mut report = format!(
+        "database schema drift detected ({} issue(s)); run `sqlx migrate run` or deploy the matching release:",
+        issues.len()
+    );
+    for issue in issues {
+        report.push_str("\n  - ");
+        report.push_str(&issue.to_string());
+    }
+    report
+}
+
+/// Run the check according to `DATABASE_SCHEMA_CHECK`.
+///
+/// Returns an error only in `enforce` mode; the caller should refuse to start.
+pub async fn verify_on_startup(db: &PgPool) -> Result<(), SchemaCheckError> {
+    let mode = SchemaCheckMode::from_config();
+    if mode == SchemaCheckMode::Off {
+        info!("Database schema check disabled");
+        return Ok(());
+    }
+
+    match verify(db).await {
+        Ok(()) => {
+            info!("Database schema matches {} embedded migrations", MIGRATOR.iter().count());
+            Ok(())
+        }
+        Err(e) if mode == SchemaCheckMode::Warn => {
+            warn!("{}", e);
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Compare the live schema with the embedded migrations and expected columns
+pub async fn verify(db: &PgPool) -> Result<(), SchemaCheckError> {
+    let applied = applied_migrations(db).await?;
+    let mut issues = migration_issues(&applied);
+
+    let columns = live_columns(db).await?;
+    issues.extend(column_issues(&columns));
+
+    let known: HashSet<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+    let unknown: Vec<i64> = applied.keys().filter(|v| !known.contains(v)).copied().collect();
+    if !unknown.is_empty() {
+        warn!(
+            "Database has {} migration(s) unknown to this build (newer release?): {:?}",
+            unknown.len(),
+            unknown
+        );
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaCheckError::Drift(issues))
+    }
+}
+
+/// Applied migration state keyed by version
+struct AppliedMigration {
+    success: bool,
+    checksum: Vec<u8>,
+}
+
+async fn applied_migrations(db: &PgPool) -> Result<HashMap<i64, AppliedMigration>, sqlx::Error> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(db)
+        .await?;
+    if !exists {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query("SELECT version, success, checksum FROM _sqlx_migrations")
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.get::<i64, _>("version"),
+                AppliedMigration {
+                    success: row.get("success"),
+                    checksum: row.get("checksum"),
+                },
+            )
+        })
+        .collect())
+}
+
+fn migration_issues(applied: &HashMap<i64, AppliedMigration>) -> Vec<SchemaIssue> {
+    MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .filter_map(|m| {
+            let version = m.version;
+            let description = m.description.to_string();
+            match applied.get(&version) {
+                None => Some(SchemaIssue::MigrationMissing { version, description }),
+                Some(a) if !a.success => Some(SchemaIssue::MigrationFailed { version, description }),
+                Some(a) if a.checksum != m.checksum.as_ref() => {
+                    Some(SchemaIssue::ChecksumMismatch { version, description })
+                }
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+async fn live_columns(db: &PgPool) -> Result<HashSet<(String, String)>, sqlx::Error> {
+    let tables: Vec<&str> = EXPECTED_COLUMNS.iter().map(|(table, _)| *table).collect();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT table_name::TEXT AS table_name, column_name::TEXT AS column_name
+        FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = ANY($1)
+        "#,
+    )
+    .bind(&tables)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("table_name"), row.get("column_name")))
+        .collect())
+}
+
+fn column_issues(columns: &HashSet<(String, String)>) -> Vec<SchemaIssue> {
+    EXPECTED_COLUMNS
+        .iter()
+        .flat_map(|(table, cols)| cols.iter().map(move |col| (*table, *col)))
+        .filter(|(table, col)| !columns.contains(&(table.to_string(), col.to_string())))
+        .map(|(table, col)| SchemaIssue::ColumnMissing {
+            table: table.to_string(),
+            column: col.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every embedded migration, applied successfully
+    fn fully_migrated() -> HashMap<i64, AppliedMigration> {
+        MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| {
+                (
+                    m.version,
+                    AppliedMigration {
+                        success: true,
+                        checksum: m.checksum.to_vec(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Every expected column
+    fn all_columns() -> HashSet<(String, String)> {
+        EXPECTED_COLUMNS
+            .iter()
+            .flat_map(|(table, cols)| {
+                cols.iter()
+                    .map(move |col| (table.to_string(), col.to_string()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clean_schema_has_no_issues() {
+        assert!(migration_issues(&fully_migrated()).is_empty());
+        assert!(column_issues(&all_columns()).is_empty());
+    }
+
+    #[test]
+    fn missing_migration_is_reported() {
+        let mut applied = fully_migrated();
+        let latest = MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .last()
+            .expect("embedded migrations");
+        applied.remove(&latest.version);
+
+        assert_eq!(
+            migration_issues(&applied),
+            vec![SchemaIssue::MigrationMissing {
+                version: latest.version,
+                description: latest.description.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_column_is_reported() {
+        let mut columns = all_columns();
+        columns.remove(&("users".to_string(), "balance".to_string()));
+
+        assert_eq!(
+            column_issues(&columns),
+            vec![SchemaIssue::ColumnMissing {
+                table: "users".to_string(),
+                column: "balance".to_string(),
+            }]
+        );
+    }
+}
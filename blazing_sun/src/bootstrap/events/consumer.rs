@@ -1,14 +1,51 @@
+use super::dead_letter::{self, DeadLetter};
+use super::handler_metrics::{HandlerMetrics, HandlerStats};
+use super::producer::{EventPublishError, SharedProducer};
+use super::retry::RetryPolicy;
+use super::routing::games_routing;
 use super::types::DomainEvent;
 use crate::config::KafkaConfig;
 use async_trait::async_trait;
+use kafka_producer::segment::{Reassembler, SegmentHeader};
+use kafka_rebalance::{InFlight, RebalanceContext, RebalanceMetrics, RebalanceTracker};
+use once_cell::sync::Lazy;
 use rdkafka::config::ClientConfig;
 use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
-use rdkafka::message::{BorrowedMessage, Headers};
-use rdkafka::Message;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::broadcast;
-use tracing::{error, info, warn};
+use rdkafka::message::{BorrowedMessage, Headers, OwnedHeaders, OwnedMessage};
+use rdkafka::{Message, Offset, TopicPartitionList};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error, info, warn, Instrument};
+
+/// Error type of message processing
+type ProcessError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Pause before a message whose dead letter could not be published is tried again
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// In-flight handlers and rebalance counters of this process's consumer
+static REBALANCE: Lazy<RebalanceTracker> = Lazy::new(RebalanceTracker::new);
+
+/// Rebalance counts and handler drain latency of this process's consumer
+pub fn rebalance_metrics() -> RebalanceMetrics {
+    REBALANCE.metrics()
+}
+
+/// In-flight handler count of this process's consumer
+pub fn in_flight_handlers() -> usize {
+    REBALANCE.in_flight()
+}
+
+/// Outcome counters of this process's handlers
+static HANDLER_METRICS: Lazy<HandlerMetrics> = Lazy::new(HandlerMetrics::default);
+
+/// Calls, errors and dead letters of each handler of this process's consumer
+pub fn handler_metrics() -> Vec<HandlerStats> {
+    HANDLER_METRICS.snapshot()
+}
 
 /// Trait for event handlers
 #[async_trait]
@@ -22,6 +59,12 @@ pub trait EventHandler: Send + Sync {
 
     /// Get the handler name for logging
     fn name(&self) -> &'static str;
+
+    /// How retryable errors are retried before the event is dead lettered
+    /// (default: the `KAFKA_HANDLER_*` settings)
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::from_config()
+    }
 }
 
 /// Errors that can occur during event handling
@@ -47,19 +90,73 @@ impl std::fmt::Display for EventHandlerError {
 
 impl std::error::Error for EventHandlerError {}
 
+/// The message must be delivered again (its dead letter could not be published)
+#[derive(Debug)]
+struct RetryLater(String);
+
+impl std::fmt::Display for RetryLater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Retry later: {}", self.0)
+    }
+}
+
+impl std::error::Error for RetryLater {}
+
+/// A polled message after segment reassembly
+enum Reassembled {
+    /// Not a segment, handle it as is
+    Single,
+    /// A segment of a payload still missing others
+    Pending,
+    /// The last segment: the message carrying the whole payload
+    Complete(OwnedMessage),
+}
+
+/// Worker owning a partition: every message of a partition goes to the same
+/// worker, so each partition is still handled in order
+pub fn worker_for(topic: &str, partition: i32, workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    topic.hash(&mut hasher);
+    partition.hash(&mut hasher);
+    (hasher.finish() % workers.max(1) as u64) as usize
+}
+
 /// Kafka event consumer
+///
+/// Before partitions are revoked the consumer context waits for the pool
+/// workers still handling their messages and commits synchronously (see the
+/// `kafka_rebalance` crate), so the next owner does not handle them again.
+///
+/// Oversized payloads arrive as segments (see `kafka_producer::segment`) and
+/// are handled once, with the last one. Segments are not acked on their own,
+/// so a consumer taking over mid-payload is delivered all of them again.
+///
+/// Each handler is retried per its `RetryPolicy`; a message it still fails
+/// goes to the dead letter topic of its source (see `dead_letter`) and the
+/// offset is committed.
 pub struct EventConsumer {
-    consumer: StreamConsumer,
+    consumer: StreamConsumer<RebalanceContext>,
     group_id: String,
     handlers: Vec<Arc<dyn EventHandler>>,
     shutdown_tx: broadcast::Sender<()>,
+    segments: Mutex<Reassembler>,
+    dead_letters: Option<SharedProducer>,
 }
 
 impl EventConsumer {
     /// Create a new Kafka consumer
     pub fn new(group_id: &str) -> Result<Self, rdkafka::error::KafkaError> {
-        let consumer: StreamConsumer = ClientConfig::new()
-            .set("bootstrap.servers", KafkaConfig::bootstrap_servers())
+        let mut config = ClientConfig::new();
+        for (key, value) in KafkaConfig::broker_settings() {
+            config.set(key, value);
+        }
+
+        let context = RebalanceContext::new(
+            REBALANCE.clone(),
+            Duration::from_millis(KafkaConfig::rebalance_drain_timeout_ms()),
+        );
+
+        let consumer: StreamConsumer<RebalanceContext> = config
             .set("group.id", group_id)
             .set(
                 "client.id",
@@ -74,13 +171,16 @@ impl EventConsumer {
                     "false"
                 },
             )
+            // Offsets are stored explicitly once a message is handled, so the
+            // auto-commit timer never commits past an unprocessed message
+            .set("enable.auto.offset.store", "false")
             .set("auto.commit.interval.ms", "5000")
             .set("session.timeout.ms", "30000")
             .set("heartbeat.interval.ms", "10000")
             .set("max.poll.interval.ms", "300000")
             .set("fetch.min.bytes", "1")
             .set("fetch.wait.max.ms", "500")
-            .create()?;
+            .create_with_context(context)?;
 
         let (shutdown_tx, _) = broadcast::channel(1);
 
@@ -95,9 +195,17 @@ impl EventConsumer {
             group_id: group_id.to_string(),
             handlers: Vec::new(),
             shutdown_tx,
+            segments: Mutex::new(Reassembler::default()),
+            dead_letters: None,
         })
     }
 
+    /// Producer publishing dead letters; without one, failed messages are
+    /// only logged
+    pub fn set_dead_letter_producer(&mut self, producer: SharedProducer) {
+        self.dead_letters = Some(producer);
+    }
+
     /// Register an event handler
     pub fn register_handler(&mut self, handler: Arc<dyn EventHandler>) {
         info!(
@@ -129,7 +237,10 @@ impl EventConsumer {
         self.consumer.subscribe(&topics)
     }
 
-    /// Start consuming events
+    /// Start consuming events, handling each message on the polling task
+    ///
+    /// Messages are handled between two polls, so a revocation (which runs
+    /// inside a poll) never finds one in flight.
     pub async fn start(&self) {
         info!("Starting event consumer for group: {}", self.group_id);
 
@@ -146,17 +257,12 @@ impl EventConsumer {
                 // Poll for messages
                 message = self.consumer.recv() => {
                     match message {
-                        Ok(msg) => {
-                            if let Err(e) = self.process_message(&msg).await {
-                                error!(
-                                    topic = %msg.topic(),
-                                    partition = %msg.partition(),
-                                    offset = %msg.offset(),
-                                    error = %e,
-                                    "Failed to process message"
-                                );
-                            }
-                        }
+                        Ok(msg) => match self.reassemble(&msg) {
+                            Reassembled::Single => self.process_polled(&msg).await,
+                            Reassembled::Pending => {}
+                            // Seeking back would only redeliver the last segment
+                            Reassembled::Complete(whole) => self.process_until_done(&whole).await,
+                        },
                         Err(e) => {
                             error!("Error receiving message: {}", e);
                             tokio::time::sleep(Duration::from_millis(100)).await;
@@ -169,19 +275,251 @@ impl EventConsumer {
         info!("Event consumer stopped");
     }
 
+    /// Handle a message of `start`, seeking back to it when it must be
+    /// delivered again (a dead letter could not be published)
+    async fn process_polled(&self, msg: &BorrowedMessage<'_>) {
+        if let Err(e) = self.process_message(msg).await {
+            if e.is::<RetryLater>() {
+                // Seek back so the message is redelivered
+                self.rewind(msg);
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            error!(
+                topic = %msg.topic(),
+                partition = %msg.partition(),
+                offset = %msg.offset(),
+                error = %e,
+                "Failed to process message"
+            );
+        }
+    }
+
+    /// Handle a reassembled message of `start`, retrying it in place (`start`
+    /// handles messages between polls, so its partition cannot be revoked)
+    async fn process_until_done(&self, msg: &OwnedMessage) {
+        loop {
+            let error = match self.process_message(msg).await {
+                Ok(()) => return,
+                Err(e) => e,
+            };
+
+            error!(
+                topic = %msg.topic(),
+                partition = %msg.partition(),
+                offset = %msg.offset(),
+                error = %error,
+                "Failed to process message"
+            );
+            if !error.is::<RetryLater>() {
+                return;
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+
+    /// Collect a segment of an oversized payload; the last one comes back as
+    /// a message carrying the whole payload
+    fn reassemble<M: Message>(&self, msg: &M) -> Reassembled {
+        let (Some(segment), Some(payload)) = (
+            msg.headers().and_then(SegmentHeader::from_headers),
+            msg.payload(),
+        ) else {
+            return Reassembled::Single;
+        };
+
+        let Some(whole) = self
+            .segments
+            .lock()
+            .unwrap()
+            .push(segment, payload, Instant::now())
+        else {
+            return Reassembled::Pending;
+        };
+
+        let headers = msg.headers().map(|headers| {
+            headers
+                .iter()
+                .fold(OwnedHeaders::new(), |owned, header| owned.insert(header))
+        });
+        Reassembled::Complete(OwnedMessage::new(
+            Some(whole),
+            msg.key().map(<[u8]>::to_vec),
+            msg.topic().to_string(),
+            msg.timestamp(),
+            msg.partition(),
+            msg.offset(),
+            headers,
+        ))
+    }
+
+    /// Start consuming events with `workers` workers (KAFKA_CONSUMER_WORKERS).
+    ///
+    /// The polling task hands each message to the worker owning its partition
+    /// (see `worker_for`) through a bounded queue, so partitions are handled
+    /// in parallel while each one stays in order. A full queue pauses polling.
+    /// Messages that must be delivered again are retried by the worker in
+    /// place: seeking back would skip the messages queued behind them.
+    ///
+    /// Polling runs on a thread of its own: a revocation blocks it until the
+    /// workers finished the messages of the revoked partitions, which they
+    /// could not do sharing its thread. Queued messages of revoked partitions
+    /// are dropped, their next owner consumes them from the committed offset.
+    pub async fn start_pool(self: Arc<Self>, workers: usize) {
+        info!(
+            workers,
+            "Starting event consumer worker pool for group: {}", self.group_id
+        );
+
+        let mut queues = Vec::with_capacity(workers);
+        let mut tasks = Vec::with_capacity(workers);
+        for worker in 0..workers {
+            let (tx, mut rx) =
+                mpsc::channel::<(OwnedMessage, u64)>(KafkaConfig::consumer_worker_queue());
+            let consumer = Arc::clone(&self);
+            tasks.push(tokio::spawn(async move {
+                while let Some((msg, generation)) = rx.recv().await {
+                    let Some(in_flight) = REBALANCE.start(msg.topic(), msg.partition(), generation)
+                    else {
+                        debug!(
+                            worker,
+                            topic = %msg.topic(),
+                            partition = %msg.partition(),
+                            offset = %msg.offset(),
+                            "Dropping message of a revoked partition"
+                        );
+                        continue;
+                    };
+                    consumer.process_in_place(worker, &msg, &in_flight).await;
+                }
+            }));
+            queues.push(tx);
+        }
+
+        let poller = Arc::clone(&self);
+        let polling =
+            tokio::task::spawn_blocking(move || poller.poll_on_own_thread(queues, workers));
+        if let Err(e) = polling.await {
+            error!("Consumer polling thread failed: {}", e);
+        }
+
+        // Let the workers finish what is already queued
+        for task in tasks {
+            let _ = task.await;
+        }
+
+        info!("Event consumer stopped");
+    }
+
+    /// Run `poll_into` on the calling (blocking) thread
+    fn poll_on_own_thread(&self, queues: Vec<mpsc::Sender<(OwnedMessage, u64)>>, workers: usize) {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                error!("Failed to start the consumer polling runtime: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(self.poll_into(queues, workers));
+    }
+
+    /// Poll messages and queue each one, tagged with the rebalance
+    /// generation, for the worker owning its partition
+    async fn poll_into(&self, queues: Vec<mpsc::Sender<(OwnedMessage, u64)>>, workers: usize) {
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+
+        loop {
+            let received = tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    info!("Shutdown signal received, stopping consumer");
+                    break;
+                }
+                message = self.consumer.recv() => message.map(|msg| msg.detach()),
+            };
+
+            match received {
+                Ok(msg) => {
+                    let worker = worker_for(msg.topic(), msg.partition(), workers);
+                    let generation = REBALANCE.generation();
+                    if queues[worker].send((msg, generation)).await.is_err() {
+                        error!(worker, "Consumer worker stopped, stopping consumer");
+                        break;
+                    }
+                }
+                Err(e) => {
+                    error!("Error receiving message: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    }
+
+    /// Handle a message on a pool worker, retrying until it is committed or
+    /// its partition is revoked
+    async fn process_in_place(&self, worker: usize, msg: &OwnedMessage, in_flight: &InFlight) {
+        let whole;
+        let msg = match self.reassemble(msg) {
+            Reassembled::Single => msg,
+            Reassembled::Pending => return,
+            Reassembled::Complete(message) => {
+                whole = message;
+                &whole
+            }
+        };
+
+        loop {
+            let error = match self.process_message(msg).await {
+                Ok(()) => return,
+                Err(e) => e,
+            };
+
+            error!(
+                worker,
+                topic = %msg.topic(),
+                partition = %msg.partition(),
+                offset = %msg.offset(),
+                error = %error,
+                "Failed to process message"
+            );
+            if !error.is::<RetryLater>() {
+                return;
+            }
+            if in_flight.revoked() {
+                // Not committed: the partition's next owner handles it again
+                warn!(
+                    worker,
+                    topic = %msg.topic(),
+                    partition = %msg.partition(),
+                    offset = %msg.offset(),
+                    "Partition revoked, leaving the message to its next owner"
+                );
+                return;
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+
     /// Process a single message
-    async fn process_message(
-        &self,
-        msg: &BorrowedMessage<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    async fn process_message<M: Message>(&self, msg: &M) -> Result<(), ProcessError> {
         let payload = msg.payload().ok_or("Empty message payload")?;
         let topic = msg.topic();
 
+        // The other copy of a dual-written game command is the one handled
+        if !games_routing().handles(topic) {
+            debug!(
+                topic = %topic,
+                partition = %msg.partition(),
+                offset = %msg.offset(),
+                "Skipping game command copy during migration"
+            );
+            self.ack(msg)?;
+            return Ok(());
+        }
+
         // Topics using raw JSON format (not DomainEvent)
-        let is_gateway_topic = topic == super::topics::topic::GAMES_COMMANDS
-            || topic == super::topics::topic::CHAT_COMMANDS
-            || topic == super::topics::topic::GATEWAY_PRESENCE
-            || topic == super::topics::topic::CHECKOUT_FINISHED;
+        let is_gateway_topic = is_raw_json_topic(topic);
 
         let event = if is_gateway_topic {
             // For gateway topics, wrap the raw payload in a synthetic DomainEvent
@@ -197,39 +535,18 @@ impl EventConsumer {
                     e
                 })?;
 
-            // Extract event_id from payload if present
-            let event_id = raw_payload
-                .get("event_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("gateway-event")
-                .to_string();
-
-            // Extract event_type string from payload
-            let event_type_str = raw_payload
-                .get("event_type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown");
+            let event = gateway_event(raw_payload);
 
             info!(
-                event_id = %event_id,
-                event_type = %event_type_str,
+                event_id = %event.id,
+                event_type = %event.payload.get("event_type").and_then(|v| v.as_str()).unwrap_or("unknown"),
                 topic = %topic,
                 partition = %msg.partition(),
                 offset = %msg.offset(),
                 "Processing gateway message"
             );
 
-            // Create synthetic DomainEvent with raw payload
-            DomainEvent {
-                id: event_id,
-                event_type: super::types::EventType::System(super::types::SystemEventType::HealthCheck), // Placeholder
-                entity_type: "gateway".to_string(),
-                entity_id: "0".to_string(),
-                payload: raw_payload,
-                metadata: super::types::EventMetadata::default(),
-                timestamp: chrono::Utc::now().timestamp_millis(),
-                version: 1,
-            }
+            event
         } else {
             // Parse as standard DomainEvent
             match DomainEvent::from_bytes(payload) {
@@ -243,7 +560,7 @@ impl EventConsumer {
                         "Failed to deserialize event"
                     );
                     // Commit to avoid reprocessing invalid messages
-                    self.consumer.commit_message(msg, CommitMode::Async)?;
+                    self.ack(msg)?;
                     return Err(e.into());
                 }
             }
@@ -261,43 +578,70 @@ impl EventConsumer {
             );
         }
 
-        // Find and invoke matching handlers
+        // Handlers log and publish under the request that caused the event:
+        // the request_id header of raw messages, the metadata of domain events
+        // or the correlation id of gateway envelopes
+        let request_id = get_request_id(msg)
+            .or_else(|| event.metadata.request_id.clone())
+            .or_else(|| {
+                event
+                    .payload
+                    .get("correlation_id")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            });
+        let span = tracing::info_span!(
+            "kafka_event",
+            event_id = %event.id,
+            topic = %msg.topic(),
+            request_id = request_id.as_deref(),
+        );
+
+        logging::request_id::scope(request_id, self.dispatch(msg, &event))
+            .instrument(span)
+            .await
+    }
+
+    /// Run the handlers subscribed to the message's topic, dead lettering the
+    /// message for those that fail it, then commit it
+    async fn dispatch<M: Message>(&self, msg: &M, event: &DomainEvent) -> Result<(), ProcessError> {
+        // A requeued dead letter only goes back to the handler that failed it
+        let requeued_for = dead_letter::requeued_for(msg);
+
         let mut handled = false;
         for handler in &self.handlers {
-            if handler.topics().contains(&msg.topic()) {
-                match handler.handle(&event).await {
-                    Ok(()) => {
-                        info!(
-                            event_id = %event.id,
-                            handler = %handler.name(),
-                            "Event handled successfully"
-                        );
-                        handled = true;
-                    }
-                    Err(EventHandlerError::Skip) => {
-                        // Handler chose to skip this event
-                        continue;
-                    }
-                    Err(EventHandlerError::Retryable(reason)) => {
-                        warn!(
-                            event_id = %event.id,
-                            handler = %handler.name(),
-                            reason = %reason,
-                            "Handler returned retryable error"
-                        );
-                        // Don't commit - message will be redelivered
-                        return Err(reason.into());
-                    }
-                    Err(EventHandlerError::Fatal(reason)) => {
-                        error!(
-                            event_id = %event.id,
-                            handler = %handler.name(),
-                            reason = %reason,
-                            "Handler returned fatal error"
-                        );
-                        // TODO: Send to dead letter queue
-                        // For now, commit to avoid infinite loop
-                    }
+            if !handler.topics().contains(&msg.topic())
+                || requeued_for
+                    .as_deref()
+                    .is_some_and(|name| name != handler.name())
+            {
+                continue;
+            }
+
+            let (result, attempts) = self.handle_with_retries(handler.as_ref(), event).await;
+            match result {
+                Ok(()) => {
+                    info!(
+                        event_id = %event.id,
+                        handler = %handler.name(),
+                        "Event handled successfully"
+                    );
+                    handled = true;
+                }
+                Err(EventHandlerError::Skip) => {
+                    // Handler chose to skip this event
+                    continue;
+                }
+                Err(error) => {
+                    error!(
+                        event_id = %event.id,
+                        handler = %handler.name(),
+                        attempts,
+                        error = %error,
+                        "Handler failed, dead lettering the event"
+                    );
+                    self.dead_letter(msg, event, handler.name(), &error, attempts)
+                        .await?;
                 }
             }
         }
@@ -311,11 +655,123 @@ impl EventConsumer {
         }
 
         // Commit the offset
-        self.consumer.commit_message(msg, CommitMode::Async)?;
+        self.ack(msg)?;
 
         Ok(())
     }
 
+    /// Call a handler until it succeeds, skips, fails fatally or runs out of
+    /// attempts; returns the last result and the calls made
+    async fn handle_with_retries(
+        &self,
+        handler: &dyn EventHandler,
+        event: &DomainEvent,
+    ) -> (Result<(), EventHandlerError>, u32) {
+        let policy = handler.retry_policy();
+        let mut attempt = 1;
+        loop {
+            match handler.handle(event).await {
+                Err(EventHandlerError::Retryable(reason)) if policy.retries_after(attempt) => {
+                    HANDLER_METRICS.record_retry(handler.name());
+                    let delay = policy.backoff(attempt);
+                    warn!(
+                        event_id = %event.id,
+                        handler = %handler.name(),
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        reason = %reason,
+                        "Handler returned retryable error, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => {
+                    HANDLER_METRICS.record(handler.name(), &result);
+                    return (result, attempt);
+                }
+            }
+        }
+    }
+
+    /// Publish a message a handler failed to the dead letter topic of its
+    /// source. Fails with `RetryLater` when the dead letter could not be
+    /// published, so the message is not committed and lost.
+    async fn dead_letter<M: Message>(
+        &self,
+        msg: &M,
+        event: &DomainEvent,
+        handler: &'static str,
+        error: &EventHandlerError,
+        attempts: u32,
+    ) -> Result<(), ProcessError> {
+        let Some(producer) = &self.dead_letters else {
+            warn!(
+                event_id = %event.id,
+                handler = %handler,
+                "No dead letter producer, dropping the failed event"
+            );
+            return Ok(());
+        };
+
+        let dead_letter = DeadLetter::new(msg, &event.id, handler, &error.to_string(), attempts);
+        match dead_letter.publish(producer).await {
+            Ok(()) => {
+                HANDLER_METRICS.record_dead_lettered(handler);
+                Ok(())
+            }
+            // Too large for the dead letter topic too; retrying cannot help
+            Err(e @ EventPublishError::TooLarge { .. }) => {
+                HANDLER_METRICS.record_dead_letter_error(handler);
+                error!(
+                    event_id = %event.id,
+                    handler = %handler,
+                    error = %e,
+                    "Dead letter over the size limit, dropping the failed event"
+                );
+                Ok(())
+            }
+            Err(e) => {
+                HANDLER_METRICS.record_dead_letter_error(handler);
+                Err(Box::new(RetryLater(format!(
+                    "dead letter of handler '{}' not published: {}",
+                    handler, e
+                ))))
+            }
+        }
+    }
+
+    /// Mark a message as processed: store its offset and commit it
+    fn ack<M: Message>(&self, msg: &M) -> Result<(), rdkafka::error::KafkaError> {
+        self.consumer
+            .store_offset(msg.topic(), msg.partition(), msg.offset())?;
+
+        let mut offsets = TopicPartitionList::new();
+        offsets.add_partition_offset(
+            msg.topic(),
+            msg.partition(),
+            Offset::Offset(msg.offset() + 1),
+        )?;
+        self.consumer.commit(&offsets, CommitMode::Async)
+    }
+
+    /// Seek back to a message so it is consumed again
+    fn rewind(&self, msg: &BorrowedMessage<'_>) {
+        if let Err(e) = self.consumer.seek(
+            msg.topic(),
+            msg.partition(),
+            Offset::Offset(msg.offset()),
+            Duration::from_secs(5),
+        ) {
+            error!(
+                topic = %msg.topic(),
+                partition = %msg.partition(),
+                offset = %msg.offset(),
+                error = %e,
+                "Failed to rewind consumer for redelivery"
+            );
+        }
+    }
+
     /// Get shutdown sender for graceful shutdown
     pub fn shutdown_signal(&self) -> broadcast::Sender<()> {
         self.shutdown_tx.clone()
@@ -327,6 +783,40 @@ impl EventConsumer {
     }
 }
 
+/// Whether a topic carries raw JSON (gateway envelopes, checkout events)
+/// rather than `DomainEvent`s
+pub fn is_raw_json_topic(topic: &str) -> bool {
+    super::topics::topic::is_games_commands(topic)
+        || super::topics::topic::is_games_events(topic)
+        || topic == super::topics::topic::CHAT_COMMANDS
+        || topic == super::topics::topic::CHAT_EVENTS
+        || topic == super::topics::topic::GATEWAY_PRESENCE
+        || topic == super::topics::topic::CHECKOUT_FINISHED
+        || topic == super::topics::topic::CHECKOUT_DISPUTES
+        || topic == super::topics::topic::CHECKOUT_SUBSCRIPTIONS
+}
+
+/// Wrap a raw JSON message in the synthetic `DomainEvent` handlers receive
+pub fn gateway_event(raw_payload: serde_json::Value) -> DomainEvent {
+    // Extract event_id from payload if present
+    let event_id = raw_payload
+        .get("event_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("gateway-event")
+        .to_string();
+
+    DomainEvent {
+        id: event_id,
+        event_type: super::types::EventType::System(super::types::SystemEventType::HealthCheck), // Placeholder
+        entity_type: "gateway".to_string(),
+        entity_id: "0".to_string(),
+        payload: raw_payload,
+        metadata: super::types::EventMetadata::default(),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        version: 1,
+    }
+}
+
 /// Shared consumer instance
 pub type SharedConsumer = Arc<EventConsumer>;
 
@@ -337,13 +827,18 @@ pub fn init(group_id: &str) -> Result<EventConsumer, rdkafka::error::KafkaError>
 
 /// Start the consumer in a background task
 pub fn start_consumer(consumer: SharedConsumer) {
+    let workers = KafkaConfig::consumer_workers();
     tokio::spawn(async move {
-        consumer.start().await;
+        if workers > 1 {
+            consumer.start_pool(workers).await;
+        } else {
+            consumer.start().await;
+        }
     });
 }
 
 /// Extract correlation ID from message headers
-pub fn get_correlation_id(msg: &BorrowedMessage<'_>) -> Option<String> {
+pub fn get_correlation_id<M: Message>(msg: &M) -> Option<String> {
     msg.headers().and_then(|headers| {
         for header in headers.iter() {
             if header.key == "correlation_id" {
@@ -354,8 +849,20 @@ pub fn get_correlation_id(msg: &BorrowedMessage<'_>) -> Option<String> {
     })
 }
 
+/// Extract the originating request ID from message headers
+pub fn get_request_id<M: Message>(msg: &M) -> Option<String> {
+    msg.headers().and_then(|headers| {
+        for header in headers.iter() {
+            if header.key == "request_id" {
+                return header.value.map(|v| String::from_utf8_lossy(v).to_string());
+            }
+        }
+        None
+    })
+}
+
 /// Extract actor ID from message headers
-pub fn get_actor_id(msg: &BorrowedMessage<'_>) -> Option<i64> {
+pub fn get_actor_id<M: Message>(msg: &M) -> Option<i64> {
     msg.headers().and_then(|headers| {
         for header in headers.iter() {
             if header.key == "actor_id" {
@@ -367,3 +874,18 @@ pub fn get_actor_id(msg: &BorrowedMessage<'_>) -> Option<i64> {
         None
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitions_stay_on_one_worker() {
+        for partition in 0..12 {
+            let worker = worker_for("games.commands", partition, 4);
+            assert!(worker < 4);
+            assert_eq!(worker_for("games.commands", partition, 4), worker);
+        }
+        assert_eq!(worker_for("games.commands", 3, 1), 0);
+    }
+}
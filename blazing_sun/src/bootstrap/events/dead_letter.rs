@@ -0,0 +1,326 @@
+//! Dead letter topics
+//!
+//! A message a handler rejects with `EventHandlerError::Fatal`, or that still
+//! fails after the handler's `RetryPolicy`, is published to the dead letter
+//! topic of its source (`<topic>.dlq`, see `topic::dead_letter_for`) wrapped
+//! in a `DeadLetter` that records the handler, the error and where the message
+//! came from. The other handlers of the message are not affected and the
+//! source offset is committed, so one broken event no longer stalls its
+//! partition.
+//!
+//! Admins browse dead letters with `browse` and send one back with `requeue`:
+//! the original payload is published to the source topic again with a
+//! `dead_letter_handler` header, and the consumer hands it to that handler
+//! only, so handlers that already processed it do not see it twice.
+
+use super::producer::{EventProducer, EventPublishError};
+use super::topics::{consumer_groups, topic};
+use crate::config::KafkaConfig;
+use chrono::{DateTime, Utc};
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::error::KafkaError;
+use rdkafka::message::Headers;
+use rdkafka::{Message, Offset, TopicPartitionList};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Header naming the only handler a requeued dead letter is dispatched to
+pub const DEAD_LETTER_HANDLER_HEADER: &str = "dead_letter_handler";
+
+/// Timeout of metadata and watermark lookups
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest time `browse` waits for the messages it planned to read
+const BROWSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A message a handler failed for good, as published to the dead letter topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub source_topic: String,
+    pub source_partition: i32,
+    pub source_offset: i64,
+    pub key: Option<String>,
+    pub event_id: String,
+    pub handler: String,
+    pub error: String,
+    /// Calls of the handler before it gave up
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+    /// The original message (a `DomainEvent` or raw JSON)
+    pub payload: serde_json::Value,
+}
+
+impl DeadLetter {
+    pub fn new<M: Message>(
+        msg: &M,
+        event_id: &str,
+        handler: &str,
+        error: &str,
+        attempts: u32,
+    ) -> Self {
+        let payload = msg.payload().unwrap_or_default();
+        Self {
+            source_topic: msg.topic().to_string(),
+            source_partition: msg.partition(),
+            source_offset: msg.offset(),
+            key: msg
+                .key()
+                .map(|key| String::from_utf8_lossy(key).into_owned()),
+            event_id: event_id.to_string(),
+            handler: handler.to_string(),
+            error: error.to_string(),
+            attempts,
+            failed_at: Utc::now(),
+            payload: serde_json::from_slice(payload).unwrap_or_else(|_| {
+                serde_json::Value::String(String::from_utf8_lossy(payload).into_owned())
+            }),
+        }
+    }
+
+    /// Publish to the dead letter topic of the source, keyed like the source
+    pub async fn publish(&self, producer: &EventProducer) -> Result<(), EventPublishError> {
+        let payload = serde_json::to_vec(self)
+            .map_err(|e| EventPublishError::Serialization(e.to_string()))?;
+        producer
+            .send_raw(
+                &topic::dead_letter_for(&self.source_topic),
+                self.key.as_deref(),
+                &payload,
+            )
+            .await
+    }
+}
+
+/// A dead letter and its position in the dead letter topic
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterRecord {
+    pub partition: i32,
+    pub offset: i64,
+    #[serde(flatten)]
+    pub dead_letter: DeadLetter,
+}
+
+/// Which dead letters of a source topic to read
+#[derive(Debug, Clone)]
+pub struct DeadLetterQuery {
+    pub source_topic: String,
+    /// Only this partition of the dead letter topic (all if None)
+    pub partition: Option<i32>,
+    /// First offset read in each partition (the latest `limit` if None)
+    pub from_offset: Option<i64>,
+    pub limit: usize,
+}
+
+/// Handler a requeued dead letter is meant for, from its headers
+pub fn requeued_for<M: Message>(msg: &M) -> Option<String> {
+    msg.headers().and_then(|headers| {
+        headers
+            .iter()
+            .find(|header| header.key == DEAD_LETTER_HANDLER_HEADER)
+            .and_then(|header| header.value)
+            .map(|value| String::from_utf8_lossy(value).into_owned())
+    })
+}
+
+/// First offset `browse` reads in a partition holding `low..high`
+fn start_offset(from_offset: Option<i64>, limit: usize, low: i64, high: i64) -> i64 {
+    match from_offset {
+        Some(offset) => offset.clamp(low, high),
+        None => (high - limit as i64).max(low),
+    }
+}
+
+/// Read dead letters of a source topic, ordered by partition and offset.
+/// Blocks while it reads; call it off the async runtime.
+pub fn browse(query: &DeadLetterQuery) -> Result<Vec<DeadLetterRecord>, KafkaError> {
+    let dead_letter_topic = topic::dead_letter_for(&query.source_topic);
+
+    let mut config = ClientConfig::new();
+    for (key, value) in KafkaConfig::broker_settings() {
+        config.set(key, value);
+    }
+    // Assigned partitions, never committed: browsing moves no group offsets
+    let consumer: BaseConsumer = config
+        .set("group.id", consumer_groups::DEAD_LETTERS)
+        .set(
+            "client.id",
+            format!("{}-dead-letters", KafkaConfig::client_id()),
+        )
+        .set("enable.auto.commit", "false")
+        .set("enable.auto.offset.store", "false")
+        .create()?;
+
+    let metadata = consumer.fetch_metadata(Some(&dead_letter_topic), LOOKUP_TIMEOUT)?;
+    let partitions: Vec<i32> = metadata
+        .topics()
+        .iter()
+        .filter(|t| t.name() == dead_letter_topic && t.error().is_none())
+        .flat_map(|t| t.partitions().iter().map(|p| p.id()))
+        .filter(|id| query.partition.is_none_or(|only| only == *id))
+        .collect();
+
+    // Messages left to read and end offset of every partition with some
+    let mut pending = BTreeMap::new();
+    let mut assignment = TopicPartitionList::new();
+    for partition in partitions {
+        let (low, high) =
+            consumer.fetch_watermarks(&dead_letter_topic, partition, LOOKUP_TIMEOUT)?;
+        let start = start_offset(query.from_offset, query.limit, low, high);
+        let left = ((high - start) as usize).min(query.limit);
+        if left > 0 {
+            assignment.add_partition_offset(
+                &dead_letter_topic,
+                partition,
+                Offset::Offset(start),
+            )?;
+            pending.insert(partition, (left, high));
+        }
+    }
+    if pending.is_empty() {
+        return Ok(Vec::new());
+    }
+    consumer.assign(&assignment)?;
+
+    let mut records = Vec::new();
+    let deadline = Instant::now() + BROWSE_TIMEOUT;
+    while !pending.is_empty() && Instant::now() < deadline {
+        let msg = match consumer.poll(Duration::from_millis(200)) {
+            Some(Ok(msg)) => msg,
+            Some(Err(e)) => {
+                warn!(topic = %dead_letter_topic, error = %e, "Dead letter consumer error");
+                continue;
+            }
+            None => continue,
+        };
+
+        let partition = msg.partition();
+        let Some((left, end)) = pending.get_mut(&partition) else {
+            continue;
+        };
+        if msg.offset() >= *end {
+            continue;
+        }
+        *left -= 1;
+        if *left == 0 || msg.offset() + 1 >= *end {
+            pending.remove(&partition);
+        }
+
+        match msg.payload().map(serde_json::from_slice::<DeadLetter>) {
+            Some(Ok(dead_letter)) => records.push(DeadLetterRecord {
+                partition,
+                offset: msg.offset(),
+                dead_letter,
+            }),
+            _ => warn!(
+                topic = %dead_letter_topic,
+                partition,
+                offset = msg.offset(),
+                "Skipping undecodable dead letter"
+            ),
+        }
+    }
+
+    records.sort_by_key(|record| (record.partition, record.offset));
+    records.truncate(query.limit);
+    Ok(records)
+}
+
+/// The dead letter at `offset` of a partition of the source's dead letter topic
+pub fn fetch(
+    source_topic: &str,
+    partition: i32,
+    offset: i64,
+) -> Result<Option<DeadLetterRecord>, KafkaError> {
+    let records = browse(&DeadLetterQuery {
+        source_topic: source_topic.to_string(),
+        partition: Some(partition),
+        from_offset: Some(offset),
+        limit: 1,
+    })?;
+    Ok(records.into_iter().find(|record| record.offset == offset))
+}
+
+/// Publish a dead letter's original message to its source topic again, for
+/// its failed handler only
+pub async fn requeue(
+    producer: &EventProducer,
+    dead_letter: &DeadLetter,
+) -> Result<(), EventPublishError> {
+    let payload = serde_json::to_vec(&dead_letter.payload)
+        .map_err(|e| EventPublishError::Serialization(e.to_string()))?;
+    producer
+        .send_raw_with_headers(
+            &dead_letter.source_topic,
+            dead_letter.key.as_deref(),
+            &payload,
+            &[(DEAD_LETTER_HANDLER_HEADER, dead_letter.handler.as_str())],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdkafka::message::{Header, OwnedHeaders, OwnedMessage, Timestamp};
+
+    fn message(payload: &[u8], headers: Option<OwnedHeaders>) -> OwnedMessage {
+        OwnedMessage::new(
+            Some(payload.to_vec()),
+            Some(b"42".to_vec()),
+            "user.events".to_string(),
+            Timestamp::NotAvailable,
+            3,
+            117,
+            headers,
+        )
+    }
+
+    #[test]
+    fn dead_letters_keep_the_source_message() {
+        let msg = message(br#"{"id":"evt-1","entity_id":"42"}"#, None);
+        let dead_letter = DeadLetter::new(&msg, "evt-1", "user_handler", "Fatal error: boom", 5);
+
+        assert_eq!(dead_letter.source_topic, "user.events");
+        assert_eq!(
+            (dead_letter.source_partition, dead_letter.source_offset),
+            (3, 117)
+        );
+        assert_eq!(dead_letter.key.as_deref(), Some("42"));
+        assert_eq!(dead_letter.payload["id"], "evt-1");
+        assert_eq!(
+            topic::dead_letter_for(&dead_letter.source_topic),
+            "user.events.dlq"
+        );
+        assert!(topic::is_dead_letter("user.events.dlq"));
+
+        let json = serde_json::to_vec(&dead_letter).unwrap();
+        let decoded: DeadLetter = serde_json::from_slice(&json).unwrap();
+        assert_eq!(decoded.handler, "user_handler");
+        assert_eq!(decoded.attempts, 5);
+    }
+
+    #[test]
+    fn requeued_messages_name_their_handler() {
+        let headers = OwnedHeaders::new().insert(Header {
+            key: DEAD_LETTER_HANDLER_HEADER,
+            value: Some("user_handler"),
+        });
+        assert_eq!(
+            requeued_for(&message(b"{}", Some(headers))).as_deref(),
+            Some("user_handler")
+        );
+        assert_eq!(requeued_for(&message(b"{}", None)), None);
+    }
+
+    #[test]
+    fn browsing_starts_at_the_latest_dead_letters() {
+        assert_eq!(start_offset(None, 20, 0, 100), 80);
+        assert_eq!(start_offset(None, 20, 90, 100), 90);
+        assert_eq!(start_offset(Some(5), 20, 10, 100), 10);
+        assert_eq!(start_offset(Some(50), 20, 10, 100), 50);
+        assert_eq!(start_offset(Some(500), 20, 10, 100), 100);
+    }
+}
@@ -0,0 +1,153 @@
+//! Event handler outcome counters
+//!
+//! Every call of a handler by the consumer counts once: handled, skipped,
+//! retried (a retryable error that is tried again) or failed (fatal, or
+//! retryable with no attempts left). Failed messages are counted again as
+//! dead lettered once they reach the dead letter topic. Counters are kept per
+//! process and reset on restart.
+
+use super::consumer::EventHandlerError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Counters of one handler
+#[derive(Debug, Default)]
+struct HandlerCounters {
+    handled: AtomicU64,
+    skipped: AtomicU64,
+    retried: AtomicU64,
+    failed: AtomicU64,
+    dead_lettered: AtomicU64,
+    dead_letter_errors: AtomicU64,
+}
+
+/// Point-in-time view of a handler's counters
+#[derive(Debug, Clone, Serialize)]
+pub struct HandlerStats {
+    pub handler: &'static str,
+    pub handled: u64,
+    pub skipped: u64,
+    pub retried: u64,
+    pub failed: u64,
+    pub dead_lettered: u64,
+    /// Dead letters that could not be published (the message was redelivered)
+    pub dead_letter_errors: u64,
+    /// Share of calls that returned an error (0 before the first call)
+    pub error_rate: f64,
+}
+
+impl HandlerCounters {
+    fn snapshot(&self, handler: &'static str) -> HandlerStats {
+        let handled = self.handled.load(Ordering::Relaxed);
+        let skipped = self.skipped.load(Ordering::Relaxed);
+        let retried = self.retried.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let calls = handled + skipped + retried + failed;
+
+        HandlerStats {
+            handler,
+            handled,
+            skipped,
+            retried,
+            failed,
+            dead_lettered: self.dead_lettered.load(Ordering::Relaxed),
+            dead_letter_errors: self.dead_letter_errors.load(Ordering::Relaxed),
+            error_rate: if calls == 0 {
+                0.0
+            } else {
+                (retried + failed) as f64 / calls as f64
+            },
+        }
+    }
+}
+
+/// Counters of every handler that was called, by name
+#[derive(Debug, Default)]
+pub struct HandlerMetrics {
+    handlers: RwLock<BTreeMap<&'static str, Arc<HandlerCounters>>>,
+}
+
+impl HandlerMetrics {
+    fn counters(&self, handler: &'static str) -> Arc<HandlerCounters> {
+        if let Some(counters) = self.handlers.read().unwrap().get(handler) {
+            return Arc::clone(counters);
+        }
+        Arc::clone(self.handlers.write().unwrap().entry(handler).or_default())
+    }
+
+    /// Count the final outcome of a handler call
+    pub fn record(&self, handler: &'static str, result: &Result<(), EventHandlerError>) {
+        let counters = self.counters(handler);
+        let counter = match result {
+            Ok(()) => &counters.handled,
+            Err(EventHandlerError::Skip) => &counters.skipped,
+            Err(_) => &counters.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a retryable error that is tried again
+    pub fn record_retry(&self, handler: &'static str) {
+        self.counters(handler)
+            .retried
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dead_lettered(&self, handler: &'static str) {
+        self.counters(handler)
+            .dead_lettered
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dead_letter_error(&self, handler: &'static str) {
+        self.counters(handler)
+            .dead_letter_errors
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counters of every handler, by name
+    pub fn snapshot(&self) -> Vec<HandlerStats> {
+        self.handlers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(handler, counters)| counters.snapshot(handler))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_rate_counts_retries_and_failures_per_call() {
+        let metrics = HandlerMetrics::default();
+        assert!(metrics.snapshot().is_empty());
+
+        metrics.record("user_handler", &Ok(()));
+        metrics.record("user_handler", &Err(EventHandlerError::Skip));
+        metrics.record_retry("user_handler");
+        metrics.record(
+            "user_handler",
+            &Err(EventHandlerError::Retryable("db down".to_string())),
+        );
+        metrics.record_dead_lettered("user_handler");
+        metrics.record("auth_handler", &Ok(()));
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].handler, "auth_handler");
+        assert_eq!(stats[0].error_rate, 0.0);
+
+        let user = &stats[1];
+        assert_eq!(
+            (user.handled, user.skipped, user.retried, user.failed),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(user.dead_lettered, 1);
+        assert_eq!(user.error_rate, 0.5);
+    }
+}
@@ -0,0 +1,114 @@
+//! Achievement handler
+//!
+//! Turns finished games (this region's game events) and successful checkouts
+//! into achievement progress (see `app::achievements`), unlocks the
+//! achievements it completes and pushes an `achievement.event.unlocked`
+//! envelope with a single-user audience on `system.events` for each.
+//!
+//! Recording is idempotent, so redelivered events neither count twice nor
+//! announce an achievement again. Pushes are best effort: a failed push is
+//! logged, the achievement stays unlocked.
+
+use crate::app::achievements::{self, Progress, UnlockedAchievement};
+use crate::app::checkout::CheckoutFinishedEvent;
+use crate::app::games::types::EventEnvelope;
+use crate::database::mutations::achievements as db_achievements;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::producer::EventProducer;
+use crate::events::topics::topic;
+use crate::events::DomainEvent;
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Handler awarding achievements from game over and payment events
+pub struct AchievementHandler {
+    db: Arc<Mutex<Pool<Postgres>>>,
+    producer: Option<Arc<EventProducer>>,
+}
+
+impl AchievementHandler {
+    /// Create a new handler instance
+    pub fn new(db: Arc<Mutex<Pool<Postgres>>>, producer: Option<Arc<EventProducer>>) -> Self {
+        Self { db, producer }
+    }
+
+    /// Progress made by an event (empty for events that make none)
+    fn progress_for(event: &DomainEvent) -> Result<Vec<Progress>, EventHandlerError> {
+        let envelope_type = event
+            .payload
+            .get("event_type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default();
+
+        if envelope_type.starts_with("games.event.") {
+            let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
+                .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+            return Ok(achievements::from_game_envelope(&envelope));
+        }
+
+        let checkout_event: CheckoutFinishedEvent = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid checkout_finished payload: {}", e)))?;
+        Ok(achievements::from_checkout(&checkout_event).into_iter().collect())
+    }
+
+    /// Push an unlocked achievement to the user's connections through the gateway
+    async fn announce(&self, user_id: i64, achievement: &UnlockedAchievement) -> Result<(), String> {
+        let Some(producer) = &self.producer else {
+            return Err("No Kafka producer available".to_string());
+        };
+
+        let envelope = achievements::unlocked_envelope(user_id, achievement);
+        let bytes = serde_json::to_vec(&envelope).map_err(|e| e.to_string())?;
+        let key = user_id.to_string();
+        producer
+            .send_raw(topic::SYSTEM_EVENTS, Some(&key), &bytes)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl EventHandler for AchievementHandler {
+    fn name(&self) -> &'static str {
+        "achievement_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::region_games_events(), topic::CHECKOUT_FINISHED]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let progress = Self::progress_for(event)?;
+        if progress.is_empty() {
+            return Err(EventHandlerError::Skip);
+        }
+
+        let db = self.db.lock().await.clone();
+        for p in &progress {
+            let unlocked = db_achievements::record_progress(&db, p)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to record achievement progress: {}", e)))?;
+
+            for achievement in &unlocked {
+                info!(
+                    user_id = %p.user_id,
+                    achievement = %achievement.code,
+                    "Achievement unlocked"
+                );
+                if let Err(e) = self.announce(p.user_id, achievement).await {
+                    warn!(
+                        user_id = %p.user_id,
+                        achievement = %achievement.code,
+                        error = %e,
+                        "Failed to announce unlocked achievement"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Analytics projection handler
+//!
+//! Folds this region's game events (rooms created -> games started -> games
+//! finished/abandoned) and `checkout.finished` events into the MongoDB
+//! analytics collections read by `/api/v1/admin/analytics/*`.
+//!
+//! Analytics are best effort: a MongoDB error is logged and the event
+//! acknowledged, so an outage never stalls checkout crediting, which shares
+//! the `checkout.finished` topic with this handler. Writes are idempotent, so
+//! redeliveries caused by other handlers are harmless.
+
+use crate::app::analytics::mongodb_analytics::MongoAnalyticsClient;
+use crate::app::analytics::projection::{CheckoutFact, GameFact};
+use crate::app::checkout::CheckoutFinishedEvent;
+use crate::app::games::types::EventEnvelope;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::topics::topic;
+use crate::events::DomainEvent;
+use async_trait::async_trait;
+use mongodb::Database;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Handler projecting game and checkout events into MongoDB
+pub struct AnalyticsHandler {
+    analytics: MongoAnalyticsClient,
+}
+
+impl AnalyticsHandler {
+    /// Create a new handler instance
+    pub fn new(mongodb: Arc<Database>) -> Self {
+        Self {
+            analytics: MongoAnalyticsClient::new(mongodb),
+        }
+    }
+
+    async fn project_game_event(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+
+        let Some(fact) = GameFact::from_envelope(&envelope) else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        if let Err(e) = self.analytics.record_game_fact(&fact).await {
+            warn!(room_id = %fact.room_id, error = %e, "Failed to record game analytics");
+        }
+
+        Ok(())
+    }
+
+    async fn project_checkout_event(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let checkout_event: CheckoutFinishedEvent = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid checkout_finished payload: {}", e)))?;
+
+        let Some(fact) = CheckoutFact::from_event(&checkout_event) else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        if let Err(e) = self.analytics.record_checkout_fact(&fact).await {
+            warn!(request_id = %fact.request_id, error = %e, "Failed to record checkout analytics");
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventHandler for AnalyticsHandler {
+    fn name(&self) -> &'static str {
+        "analytics_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::region_games_events(), topic::CHECKOUT_FINISHED]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        // Game events are envelopes; checkout events are flat `CheckoutFinishedEvent`s
+        let is_game_event = event
+            .payload
+            .get("event_type")
+            .and_then(|t| t.as_str())
+            .is_some_and(|t| t.starts_with("games.event."));
+
+        if is_game_event {
+            self.project_game_event(event).await
+        } else {
+            self.project_checkout_event(event).await
+        }
+    }
+}
@@ -47,6 +47,12 @@ impl EventHandler for AuthEventHandler {
             }
             AuthEventType::AccountLocked => self.handle_account_locked(event).await,
             AuthEventType::AccountUnlocked => self.handle_account_unlocked(event).await,
+            AuthEventType::ImpersonationStarted | AuthEventType::ImpersonationEnded => {
+                self.handle_impersonation(event).await
+            }
+            AuthEventType::TwoFactorEnabled
+            | AuthEventType::TwoFactorDisabled
+            | AuthEventType::TwoFactorChallengeFailed => self.handle_two_factor(event).await,
         }
     }
 }
@@ -194,6 +200,58 @@ impl AuthEventHandler {
 
         Ok(())
     }
+
+    async fn handle_impersonation(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let session_id = event
+            .payload
+            .get("session_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        let reason = event
+            .payload
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        warn!(
+            event_id = %event.id,
+            event_type = %event.event_type,
+            user_id = %event.entity_id,
+            admin_id = ?event.metadata.actor_id,
+            session_id = %session_id,
+            reason = %reason,
+            "Admin impersonation"
+        );
+
+        Ok(())
+    }
+
+    async fn handle_two_factor(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let method = event
+            .payload
+            .get("method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("-");
+        let reason = event
+            .payload
+            .get("failure_reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("-");
+        let ip_address = event.metadata.ip_address.as_deref().unwrap_or("unknown");
+
+        warn!(
+            event_id = %event.id,
+            event_type = %event.event_type,
+            user_id = %event.entity_id,
+            actor_id = ?event.metadata.actor_id,
+            method = %method,
+            reason = %reason,
+            ip_address = %ip_address,
+            "Two-factor authentication"
+        );
+
+        Ok(())
+    }
 }
 
 /// Security monitoring handler for auth events
@@ -0,0 +1,107 @@
+//! Client cache invalidation hints
+//!
+//! Turns user events into `cache.invalidate` hints for the user's WebSocket
+//! connections (see `app::cache::invalidation`).
+
+use crate::app::cache::invalidation::{CacheEntity, CacheHint};
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::producer::EventProducer;
+use crate::events::topics::topic;
+use crate::events::types::{DomainEvent, EventType, UserEventType};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Entities a user event makes stale
+fn stale_entities(event_type: &EventType) -> &'static [CacheEntity] {
+    match event_type {
+        EventType::User(
+            UserEventType::Updated | UserEventType::ProfileUpdated | UserEventType::RoleChanged,
+        ) => &[CacheEntity::UserProfile],
+        EventType::User(UserEventType::BalanceUpdated) => {
+            &[CacheEntity::Balance, CacheEntity::Transactions]
+        }
+        _ => &[],
+    }
+}
+
+/// Handler publishing cache invalidation hints
+pub struct CacheInvalidationHandler {
+    producer: Option<Arc<EventProducer>>,
+}
+
+impl CacheInvalidationHandler {
+    /// Create a new handler instance
+    pub fn new(producer: Option<Arc<EventProducer>>) -> Self {
+        Self { producer }
+    }
+}
+
+#[async_trait]
+impl EventHandler for CacheInvalidationHandler {
+    fn name(&self) -> &'static str {
+        "cache_invalidation_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::USER_EVENTS]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let entities = stale_entities(&event.event_type);
+        if entities.is_empty() {
+            return Err(EventHandlerError::Skip);
+        }
+        let Some(producer) = &self.producer else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        let user_id: i64 = event.entity_id.parse().map_err(|_| {
+            EventHandlerError::Fatal(format!("Invalid user id: {}", event.entity_id))
+        })?;
+
+        // Hints are best effort; a failed push is not worth a retry
+        for entity in entities {
+            let envelope = CacheHint::new(*entity, user_id).envelope(vec![user_id]);
+            let bytes = match serde_json::to_vec(&envelope) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Failed to encode cache hint: {}", e);
+                    continue;
+                }
+            };
+            match producer
+                .send_raw(topic::SYSTEM_EVENTS, Some(&user_id.to_string()), &bytes)
+                .await
+            {
+                Ok(()) => debug!(user_id, entity = entity.as_str(), "Cache hint pushed"),
+                Err(e) => warn!(
+                    user_id,
+                    entity = entity.as_str(),
+                    "Failed to push cache hint: {}",
+                    e
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance_changes_invalidate_balance_and_transactions() {
+        assert_eq!(
+            stale_entities(&EventType::User(UserEventType::BalanceUpdated)),
+            &[CacheEntity::Balance, CacheEntity::Transactions]
+        );
+        assert_eq!(
+            stale_entities(&EventType::User(UserEventType::RoleChanged)),
+            &[CacheEntity::UserProfile]
+        );
+        assert!(stale_entities(&EventType::User(UserEventType::PasswordChanged)).is_empty());
+    }
+}
@@ -2,17 +2,26 @@
 //!
 //! Processes chat commands from the WebSocket gateway and publishes chat events back.
 
+use crate::app::cache::UserProfileCache;
+use crate::app::chat::mongodb_channel::MongoChannelClient;
 use crate::app::chat::mongodb_chat::MongoChatClient;
-use crate::app::chat::types::{Audience, ChatEvent, EventEnvelope, MessageType};
-use crate::app::db_query::read::{friend, lobby, user};
+use crate::app::chat::types::{
+    channel_room, conversation_id, Audience, ChatEvent, EventEnvelope, MessageType, PrivateMessage,
+};
+use crate::app::chat::unread::ChatUnread;
+use crate::app::db_query::read::{chat_channel, friend, game_chat_config, lobby};
+use crate::app::db_query::mutations::chat_channel as chat_channel_mutations;
 use crate::app::db_query::mutations::lobby as lobby_mutations;
+use crate::database::SharedRedis;
 use crate::events::consumer::{EventHandler, EventHandlerError};
 use crate::events::producer::EventProducer;
 use crate::events::topics::topic;
 use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
 use mongodb::Database;
 use serde_json::Value;
 use sqlx::{Pool, Postgres};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
@@ -23,6 +32,15 @@ pub struct ChatCommandHandler {
     db: Arc<Mutex<Pool<Postgres>>>,
     mongodb: Option<Arc<Database>>,
     producer: Option<Arc<EventProducer>>,
+    profiles: UserProfileCache,
+    unread: ChatUnread,
+}
+
+/// Read a user id that the gateway may send as a number or a string
+fn payload_id(payload: &Value, key: &str) -> Option<i64> {
+    payload
+        .get(key)
+        .and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
 }
 
 impl ChatCommandHandler {
@@ -30,8 +48,28 @@ impl ChatCommandHandler {
         db: Arc<Mutex<Pool<Postgres>>>,
         mongodb: Option<Arc<Database>>,
         producer: Option<Arc<EventProducer>>,
+        profiles: UserProfileCache,
+        redis: Option<SharedRedis>,
     ) -> Self {
-        Self { db, mongodb, producer }
+        Self {
+            db,
+            mongodb,
+            producer,
+            profiles,
+            unread: ChatUnread::new(redis),
+        }
+    }
+
+    /// Mirror the exact unread count of one conversation from MongoDB
+    async fn refresh_unread(&self, chat_client: &MongoChatClient, user_id: i64, other_user_id: i64) {
+        match chat_client.count_unread_from(user_id, other_user_id).await {
+            Ok(count) => {
+                self.unread
+                    .set(user_id, &conversation_id(user_id, other_user_id), count)
+                    .await
+            }
+            Err(e) => warn!(user_id, error = %e, "Failed to count unread messages"),
+        }
     }
 
     /// Send an event back to the WebSocket gateway via Kafka
@@ -43,9 +81,9 @@ impl ChatCommandHandler {
 
         let envelope = EventEnvelope {
             event_id: Uuid::new_v4().to_string(),
-            event_type: "chat.event".to_string(),
+            event_type: format!("chat.event.{}", event.event_type_name()),
             timestamp: chrono::Utc::now().to_rfc3339(),
-            correlation_id: None,
+            correlation_id: logging::request_id::current(),
             producer: "blazing_sun".to_string(),
             actor: crate::app::chat::types::Actor {
                 user_id: 0, // System
@@ -76,8 +114,22 @@ impl ChatCommandHandler {
         content: &str,
         socket_id: &str,
     ) -> Result<(), EventHandlerError> {
+        let content = content.trim();
         let db = self.db.lock().await;
 
+        let max_length = game_chat_config::get_max_message_length(&db)
+            .await
+            .map(|length| length.max(1) as usize)
+            .unwrap_or(512);
+
+        if content.is_empty() || content.chars().count() > max_length {
+            drop(db);
+            let message = format!("Message must be between 1 and {} characters", max_length);
+            return self
+                .publish_error(sender_id, "invalid_message", &message, socket_id)
+                .await;
+        }
+
         // Check if sender can message recipient (friends or admin)
         let can_message = friend::can_message_user(&db, sender_id, recipient_id).await;
         if !can_message {
@@ -92,9 +144,10 @@ impl ChatCommandHandler {
         }
 
         // Get sender info
-        let sender = user::get_by_id(&db, sender_id)
+        let sender = self.profiles.get(&db, sender_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to get sender: {}", e)))?;
+        drop(db);
 
         // Store message in MongoDB
         let Some(mongodb) = &self.mongodb else {
@@ -108,10 +161,12 @@ impl ChatCommandHandler {
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to store message: {}", e)))?;
 
         let message_id = message.id.map(|id| id.to_hex()).unwrap_or_default();
+        self.refresh_unread(&chat_client, recipient_id, sender_id).await;
 
         // Send event to recipient
         let message_event = ChatEvent::MessageReceived {
             message_id: message_id.clone(),
+            conversation_id: conversation_id(sender_id, recipient_id),
             sender_id,
             sender_username: sender.first_name.clone(),
             sender_avatar_id: sender.avatar_id,
@@ -166,7 +221,7 @@ impl ChatCommandHandler {
         }
 
         // Get sender info
-        let sender = user::get_by_id(&db, sender_id)
+        let sender = self.profiles.get(&db, sender_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to get sender: {}", e)))?;
 
@@ -203,7 +258,242 @@ impl ChatCommandHandler {
         Ok(())
     }
 
-    /// Handle mark_read command
+    /// Publish a chat error back to the user who sent a command
+    async fn publish_error(
+        &self,
+        user_id: i64,
+        code: &str,
+        message: &str,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let error_event = ChatEvent::Error {
+            code: code.to_string(),
+            message: message.to_string(),
+            socket_id: socket_id.to_string(),
+        };
+        self.publish_chat_event(error_event, Audience::user(user_id)).await
+    }
+
+    /// Handle channel_join command
+    ///
+    /// Joining is idempotent; the reply is also what makes the gateway register the
+    /// user's connections in the channel room, so reconnecting clients re-send it.
+    async fn handle_channel_join(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await;
+
+        let channel = chat_channel::get_by_id(&db, channel_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to load channel: {}", e)))?;
+
+        let Some(channel) = channel.filter(|c| c.is_active) else {
+            drop(db);
+            return self
+                .publish_error(user_id, "channel_not_found", "The channel does not exist", socket_id)
+                .await;
+        };
+
+        chat_channel_mutations::join(&db, channel_id, user_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to join channel: {}", e)))?;
+
+        let membership = chat_channel::get_membership(&db, channel_id, user_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to load membership: {}", e)))?;
+        drop(db);
+
+        let joined_event = ChatEvent::ChannelJoined {
+            channel_id,
+            channel_name: channel.name,
+            user_id,
+            last_read_message_id: membership.and_then(|m| m.last_read_message_id),
+        };
+
+        self.publish_chat_event(joined_event, Audience::user(user_id)).await?;
+
+        info!(user_id = %user_id, channel_id = %channel_id, "User joined chat channel");
+
+        Ok(())
+    }
+
+    /// Handle channel_leave command
+    async fn handle_channel_leave(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+    ) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await;
+        chat_channel_mutations::leave(&db, channel_id, user_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to leave channel: {}", e)))?;
+        drop(db);
+
+        let left_event = ChatEvent::ChannelLeft { channel_id, user_id };
+        self.publish_chat_event(left_event, Audience::user(user_id)).await?;
+
+        info!(user_id = %user_id, channel_id = %channel_id, "User left chat channel");
+
+        Ok(())
+    }
+
+    /// Handle channel_send command (message to a lobby/global channel)
+    async fn handle_channel_send(
+        &self,
+        sender_id: i64,
+        channel_id: i64,
+        content: &str,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let content = content.trim();
+        let db = self.db.lock().await;
+
+        let max_length = game_chat_config::get_max_message_length(&db)
+            .await
+            .map(|length| length.max(1) as usize)
+            .unwrap_or(512);
+
+        if content.is_empty() || content.chars().count() > max_length {
+            drop(db);
+            let message = format!("Message must be between 1 and {} characters", max_length);
+            return self
+                .publish_error(sender_id, "invalid_message", &message, socket_id)
+                .await;
+        }
+
+        let membership = chat_channel::get_membership(&db, channel_id, sender_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to load membership: {}", e)))?;
+
+        if membership.is_none() {
+            drop(db);
+            return self
+                .publish_error(
+                    sender_id,
+                    "not_channel_member",
+                    "Join the channel before sending messages",
+                    socket_id,
+                )
+                .await;
+        }
+
+        let sender = self.profiles.get(&db, sender_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to get sender: {}", e)))?;
+        drop(db);
+
+        let Some(mongodb) = &self.mongodb else {
+            return Err(EventHandlerError::Fatal("MongoDB not available for channel messages".to_string()));
+        };
+
+        let channel_client = MongoChannelClient::new(mongodb.clone());
+        let message = channel_client
+            .send_message(channel_id, sender_id, &sender.first_name, sender.avatar_id, content)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to store channel message: {}", e)))?;
+
+        let message_id = message.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        let message_event = ChatEvent::ChannelMessageReceived {
+            message_id: message_id.clone(),
+            channel_id,
+            sender_id,
+            sender_username: sender.first_name,
+            sender_avatar_id: sender.avatar_id,
+            content: message.content,
+            message_type: "text".to_string(),
+            created_at: message.created_at.to_rfc3339(),
+        };
+
+        self.publish_chat_event(message_event, Audience::room(channel_room(channel_id)))
+            .await?;
+
+        info!(
+            sender_id = %sender_id,
+            channel_id = %channel_id,
+            message_id = %message_id,
+            "Channel message sent"
+        );
+
+        Ok(())
+    }
+
+    /// Handle mark_read command for specific messages
+    ///
+    /// `receipt` is "delivered" when a client only received the messages and
+    /// "read" when the user saw them. Each sender gets one receipt event per
+    /// conversation listing the ids that changed.
+    async fn handle_receipt(
+        &self,
+        user_id: i64,
+        message_ids: &[String],
+        receipt: &str,
+    ) -> Result<(), EventHandlerError> {
+        let Some(mongodb) = &self.mongodb else {
+            return Err(EventHandlerError::Fatal("MongoDB not available".to_string()));
+        };
+
+        let ids: Vec<ObjectId> = message_ids
+            .iter()
+            .filter_map(|id| ObjectId::parse_str(id).ok())
+            .collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let chat_client = MongoChatClient::new(mongodb.clone());
+        let delivered = receipt == "delivered";
+        let messages = if delivered {
+            chat_client.mark_delivered(user_id, &ids).await
+        } else {
+            chat_client.mark_read(user_id, &ids).await
+        }
+        .map_err(|e| EventHandlerError::Retryable(format!("Failed to record {} receipt: {}", receipt, e)))?;
+
+        let mut by_sender: BTreeMap<i64, Vec<PrivateMessage>> = BTreeMap::new();
+        for message in messages {
+            by_sender.entry(message.sender_id).or_default().push(message);
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        for (sender_id, messages) in by_sender {
+            let conversation_id = conversation_id(user_id, sender_id);
+            let message_ids = messages
+                .iter()
+                .filter_map(|m| m.id.map(|id| id.to_hex()))
+                .collect();
+
+            let event = if delivered {
+                ChatEvent::MessageDelivered {
+                    conversation_id,
+                    message_ids,
+                    recipient_id: user_id,
+                    sender_id,
+                    delivered_at: now.clone(),
+                }
+            } else {
+                self.refresh_unread(&chat_client, user_id, sender_id).await;
+                ChatEvent::MessageRead {
+                    conversation_id,
+                    message_ids,
+                    reader_id: user_id,
+                    sender_id,
+                    read_at: now.clone(),
+                }
+            };
+
+            self.publish_chat_event(event, Audience::user(sender_id)).await?;
+        }
+
+        info!(user_id = %user_id, receipt = %receipt, "Message receipts recorded");
+
+        Ok(())
+    }
+
+    /// Handle mark_read command for a whole conversation (legacy payload)
     async fn handle_mark_read(
         &self,
         user_id: i64,
@@ -218,10 +508,15 @@ impl ChatCommandHandler {
             .mark_messages_read(user_id, other_user_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to mark messages read: {}", e)))?;
+        self.unread
+            .set(user_id, &conversation_id(user_id, other_user_id), 0)
+            .await;
 
         if count > 0 {
             // Notify the sender that their messages were read
             let read_event = ChatEvent::MessageRead {
+                conversation_id: conversation_id(user_id, other_user_id),
+                message_ids: Vec::new(),
                 reader_id: user_id,
                 sender_id: other_user_id,
                 read_at: chrono::Utc::now().to_rfc3339(),
@@ -251,6 +546,14 @@ impl ChatCommandHandler {
         recipient_id: i64,
         is_typing: bool,
     ) -> Result<(), EventHandlerError> {
+        // Nobody sees typing indicators from users they blocked
+        let db = self.db.lock().await;
+        let blocked = friend::is_blocked(&db, recipient_id, sender_id).await;
+        drop(db);
+        if blocked {
+            return Ok(());
+        }
+
         let typing_event = ChatEvent::TypingIndicator {
             sender_id,
             recipient_id,
@@ -282,25 +585,49 @@ impl EventHandler for ChatCommandHandler {
         let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
             .map_err(|e| EventHandlerError::Fatal(format!("Invalid chat command envelope: {}", e)))?;
 
-        // Extract the command type from the envelope's payload
-        let command_type = envelope.payload.get("type")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| EventHandlerError::Fatal("Missing command type".to_string()))?;
+        // Command type comes from the gateway event type ("chat.command.channel_join")
+        // or, for legacy producers, from the payload's "type" field
+        let command_type = match envelope.event_type.strip_prefix("chat.command.") {
+            Some(command) => command,
+            None => envelope.payload.get("type")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| EventHandlerError::Fatal("Missing command type".to_string()))?,
+        };
 
         match command_type {
+            "channel_join" | "channel_leave" | "channel_send" => {
+                let user_id = envelope.actor.user_id;
+                let channel_id = envelope.payload.get("channel_id")
+                    .and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing channel_id".to_string()))?;
+                let socket_id = envelope.actor.socket_id.as_str();
+
+                match command_type {
+                    "channel_join" => self.handle_channel_join(user_id, channel_id, socket_id).await,
+                    "channel_leave" => self.handle_channel_leave(user_id, channel_id).await,
+                    _ => {
+                        let content = envelope.payload.get("content")
+                            .and_then(|v| v.as_str())
+                            .ok_or_else(|| EventHandlerError::Fatal("Missing content".to_string()))?;
+                        self.handle_channel_send(user_id, channel_id, content, socket_id).await
+                    }
+                }
+            }
             "send_message" => {
-                let sender_id = envelope.payload.get("sender_id")
-                    .and_then(|v| v.as_i64())
-                    .ok_or_else(|| EventHandlerError::Fatal("Missing sender_id".to_string()))?;
-                let recipient_id = envelope.payload.get("recipient_id")
-                    .and_then(|v| v.as_i64())
+                // The gateway sends the sender as the actor; legacy producers in the payload
+                let sender_id = match envelope.actor.user_id {
+                    0 => payload_id(&envelope.payload, "sender_id")
+                        .ok_or_else(|| EventHandlerError::Fatal("Missing sender_id".to_string()))?,
+                    user_id => user_id,
+                };
+                let recipient_id = payload_id(&envelope.payload, "recipient_id")
                     .ok_or_else(|| EventHandlerError::Fatal("Missing recipient_id".to_string()))?;
                 let content = envelope.payload.get("content")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| EventHandlerError::Fatal("Missing content".to_string()))?;
                 let socket_id = envelope.payload.get("socket_id")
                     .and_then(|v| v.as_str())
-                    .unwrap_or("");
+                    .unwrap_or(envelope.actor.socket_id.as_str());
 
                 self.handle_send_message(sender_id, recipient_id, content, socket_id).await
             }
@@ -321,21 +648,32 @@ impl EventHandler for ChatCommandHandler {
                 self.handle_send_lobby_message(sender_id, lobby_id, content, socket_id).await
             }
             "mark_read" => {
-                let user_id = envelope.payload.get("user_id")
-                    .and_then(|v| v.as_i64())
+                if let Some(message_ids) = envelope.payload.get("message_ids") {
+                    let message_ids: Vec<String> = serde_json::from_value(message_ids.clone())
+                        .map_err(|e| EventHandlerError::Fatal(format!("Invalid message_ids: {}", e)))?;
+                    let receipt = envelope.payload.get("receipt")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("read");
+
+                    return self
+                        .handle_receipt(envelope.actor.user_id, &message_ids, receipt)
+                        .await;
+                }
+
+                let user_id = payload_id(&envelope.payload, "user_id")
                     .ok_or_else(|| EventHandlerError::Fatal("Missing user_id".to_string()))?;
-                let other_user_id = envelope.payload.get("other_user_id")
-                    .and_then(|v| v.as_i64())
+                let other_user_id = payload_id(&envelope.payload, "other_user_id")
                     .ok_or_else(|| EventHandlerError::Fatal("Missing other_user_id".to_string()))?;
 
                 self.handle_mark_read(user_id, other_user_id).await
             }
             "typing" => {
-                let sender_id = envelope.payload.get("sender_id")
-                    .and_then(|v| v.as_i64())
-                    .ok_or_else(|| EventHandlerError::Fatal("Missing sender_id".to_string()))?;
-                let recipient_id = envelope.payload.get("recipient_id")
-                    .and_then(|v| v.as_i64())
+                let sender_id = match envelope.actor.user_id {
+                    0 => payload_id(&envelope.payload, "sender_id")
+                        .ok_or_else(|| EventHandlerError::Fatal("Missing sender_id".to_string()))?,
+                    user_id => user_id,
+                };
+                let recipient_id = payload_id(&envelope.payload, "recipient_id")
                     .ok_or_else(|| EventHandlerError::Fatal("Missing recipient_id".to_string()))?;
                 let is_typing = envelope.payload.get("is_typing")
                     .and_then(|v| v.as_bool())
@@ -350,3 +688,18 @@ impl EventHandler for ChatCommandHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::payload_id;
+    use serde_json::json;
+
+    #[test]
+    fn payload_ids_accept_gateway_strings_and_legacy_numbers() {
+        let payload = json!({ "recipient_id": "42", "sender_id": 7, "other_user_id": "x" });
+        assert_eq!(payload_id(&payload, "recipient_id"), Some(42));
+        assert_eq!(payload_id(&payload, "sender_id"), Some(7));
+        assert_eq!(payload_id(&payload, "other_user_id"), None);
+        assert_eq!(payload_id(&payload, "user_id"), None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Handler for the `checkout.disputes` Kafka topic
+//!
+//! The checkout service publishes a Stripe dispute (chargeback) of a paid
+//! checkout when it is opened and again when it closes:
+//! - status="opened": Freezes `hold_cents` coins as a balance hold (ledger
+//!   source `dispute_hold`), as far as the balance still covers them
+//! - status="won": Gives the frozen coins back (`dispute_release`)
+//! - status="lost": Keeps the frozen coins and deducts what the hold fell
+//!   short of (`dispute_deduction`)
+//!
+//! Every balance change is published as `user.balance_updated`. Holds are
+//! keyed by dispute id, so redelivered events change nothing.
+
+use crate::app::checkout::CheckoutDisputeEvent;
+use crate::database::mutations::balance_holds::{
+    self as db_balance_holds, BalanceChange, HoldOutcome, NewHold, Resolution, ResolveOutcome,
+};
+use crate::database::replica;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::producer::EventProducer;
+use crate::events::topics::topic;
+use crate::events::{EventBuilder, EventType, UserEventType};
+use async_trait::async_trait;
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Handler for the `checkout.disputes` topic
+pub struct CheckoutDisputesHandler {
+    db: Arc<Mutex<Pool<Postgres>>>,
+    producer: Option<Arc<EventProducer>>,
+}
+
+impl CheckoutDisputesHandler {
+    /// Create a new handler instance
+    pub fn new(db: Arc<Mutex<Pool<Postgres>>>, producer: Option<Arc<EventProducer>>) -> Self {
+        Self { db, producer }
+    }
+
+    async fn hold(&self, dispute: &CheckoutDisputeEvent) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await.clone();
+        let outcome = db_balance_holds::hold(
+            &db,
+            &NewHold {
+                user_id: dispute.user_id,
+                reference_id: &dispute.dispute_id,
+                request_id: &dispute.request_id,
+                amount_cents: dispute.hold_cents,
+                metadata: ledger_metadata(dispute),
+            },
+        )
+        .await
+        .map_err(|err| {
+            EventHandlerError::Retryable(format!("Failed to place dispute hold: {}", err))
+        })?;
+
+        match outcome {
+            HoldOutcome::Placed(change) => {
+                info!(
+                    dispute_id = %dispute.dispute_id,
+                    user_id = %dispute.user_id,
+                    requested_cents = %dispute.hold_cents,
+                    held_cents = -change.change,
+                    "Dispute opened - coins frozen"
+                );
+                replica::mark_written(dispute.user_id).await;
+                self.publish_balance(dispute, db_balance_holds::LEDGER_HOLD, change)
+                    .await;
+            }
+            HoldOutcome::Duplicate => {
+                info!(dispute_id = %dispute.dispute_id, "Duplicate dispute opening - hold already placed");
+            }
+            HoldOutcome::UnknownUser => {
+                warn!(
+                    dispute_id = %dispute.dispute_id,
+                    user_id = %dispute.user_id,
+                    "Dispute for a user that no longer exists"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(
+        &self,
+        dispute: &CheckoutDisputeEvent,
+        resolution: Resolution,
+    ) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await.clone();
+        let outcome = db_balance_holds::resolve(
+            &db,
+            &dispute.dispute_id,
+            resolution,
+            &ledger_metadata(dispute),
+        )
+        .await
+        .map_err(|err| {
+            EventHandlerError::Retryable(format!("Failed to settle dispute hold: {}", err))
+        })?;
+
+        match outcome {
+            ResolveOutcome::Resolved(change) => {
+                info!(
+                    dispute_id = %dispute.dispute_id,
+                    user_id = %dispute.user_id,
+                    status = %dispute.status,
+                    change = %change.change,
+                    "Dispute closed - hold settled"
+                );
+                let source = match resolution {
+                    Resolution::Release => db_balance_holds::LEDGER_RELEASE,
+                    Resolution::Deduct => db_balance_holds::LEDGER_DEDUCTION,
+                };
+                replica::mark_written(dispute.user_id).await;
+                self.publish_balance(dispute, source, change).await;
+            }
+            ResolveOutcome::AlreadyResolved => {
+                info!(dispute_id = %dispute.dispute_id, "Duplicate dispute closing - hold already settled");
+            }
+            ResolveOutcome::NotFound => {
+                warn!(
+                    dispute_id = %dispute.dispute_id,
+                    status = %dispute.status,
+                    "Dispute closed without a hold"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publish user.balance_updated for a hold that moved coins
+    async fn publish_balance(
+        &self,
+        dispute: &CheckoutDisputeEvent,
+        source: &str,
+        change: BalanceChange,
+    ) {
+        let Some(producer) = &self.producer else {
+            return;
+        };
+        if change.change == 0 {
+            return;
+        }
+
+        let balance_event = EventBuilder::new(
+            EventType::User(UserEventType::BalanceUpdated),
+            &change.user_id.to_string(),
+        )
+        .payload(json!({
+            "balance": change.balance,
+            "change": change.change,
+            "source": source,
+            "dispute_id": dispute.dispute_id,
+            "request_id": dispute.request_id,
+            "reason": dispute.reason,
+        }))
+        .build();
+
+        if let Err(err) = producer.publish(&balance_event).await {
+            warn!("Failed to publish user.balance_updated event: {}", err);
+        }
+    }
+}
+
+fn ledger_metadata(dispute: &CheckoutDisputeEvent) -> serde_json::Value {
+    json!({
+        "request_id": dispute.request_id,
+        "payment_intent_id": dispute.payment_intent_id,
+        "reason": dispute.reason,
+        "amount_cents": dispute.amount_cents,
+        "currency": dispute.currency,
+    })
+}
+
+#[async_trait]
+impl EventHandler for CheckoutDisputesHandler {
+    fn name(&self) -> &'static str {
+        "checkout_disputes_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::CHECKOUT_DISPUTES]
+    }
+
+    async fn handle(&self, event: &crate::events::DomainEvent) -> Result<(), EventHandlerError> {
+        let dispute: CheckoutDisputeEvent =
+            serde_json::from_value(event.payload.clone()).map_err(|err| {
+                EventHandlerError::Fatal(format!("Invalid checkout.disputes payload: {}", err))
+            })?;
+
+        match dispute.status.as_str() {
+            "opened" => self.hold(&dispute).await,
+            "won" => self.resolve(&dispute, Resolution::Release).await,
+            "lost" => self.resolve(&dispute, Resolution::Deduct).await,
+            unknown_status => {
+                warn!(
+                    dispute_id = %dispute.dispute_id,
+                    status = %unknown_status,
+                    "Unknown checkout.disputes status"
+                );
+                Ok(())
+            }
+        }
+    }
+}
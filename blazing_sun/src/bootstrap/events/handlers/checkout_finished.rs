@@ -1,17 +1,31 @@
 //! Handler for the `checkout_finished` Kafka topic
 //!
 //! Processes events from the checkout service webhook:
+//! - status="session_created" / "session_failed": Hands the outcome of a Kafka
+//!   checkout to the waiting API request, or pushes it to the user over the
+//!   WebSocket gateway once that request has timed out (only while the
+//!   `checkout_kafka_flow` feature flag is on for the user)
 //! - status="success": Updates user balance after payment completes
 //! - status="failed": Logs payment failure
 //!
 //! Note: DB row is created by checkout service when webhook fires.
 //! This handler only updates the user's balance in the main database.
-
-use crate::app::checkout::CheckoutFinishedEvent;
-use crate::database::mutations::user as db_user_mutations;
-use crate::database::read::user as db_user_read;
+//!
+//! A credit keeps the user's balance reads on the primary database for a
+//! moment (`replica::mark_written`), so the fetch that follows the payment
+//! does not hit a replica that has not replayed it yet.
+//!
+//! Crediting is exactly-once: the balance update and ledger entry are written in
+//! the same transaction as a `processed_events` row keyed by (topic, request_id),
+//! and the consumer only commits the offset after the handler returns Ok.
+
+use crate::app::checkout::{self, CheckoutFinishedEvent, CheckoutSessionResult};
+use crate::app::feature_flags::{flag, FeatureFlags};
+use crate::database::mutations::balance_ledger::{self as db_balance_ledger, CreditOnceParams};
+use crate::database::replica;
 use crate::events::consumer::{EventHandler, EventHandlerError};
 use crate::events::producer::EventProducer;
+use crate::events::retry::RetryPolicy;
 use crate::events::topics::topic;
 use crate::events::{EventBuilder, EventType, UserEventType};
 use async_trait::async_trait;
@@ -36,6 +50,26 @@ impl CheckoutFinishedHandler {
     pub fn new(db: Arc<Mutex<Pool<Postgres>>>, producer: Option<Arc<EventProducer>>) -> Self {
         Self { db, producer }
     }
+
+    /// Deliver a session result to the user's connections through the gateway
+    async fn push_session_result(
+        &self,
+        user_id: i64,
+        request_id: &str,
+        result: &CheckoutSessionResult,
+    ) -> Result<(), String> {
+        let Some(producer) = &self.producer else {
+            return Err("No Kafka producer available".to_string());
+        };
+
+        let envelope = checkout::session_envelope(user_id, request_id, result);
+        let bytes = serde_json::to_vec(&envelope).map_err(|e| e.to_string())?;
+        let key = user_id.to_string();
+        producer
+            .send_raw(topic::SYSTEM_EVENTS, Some(&key), &bytes)
+            .await
+            .map_err(|e| e.to_string())
+    }
 }
 
 #[async_trait]
@@ -48,6 +82,11 @@ impl EventHandler for CheckoutFinishedHandler {
         vec![topic::CHECKOUT_FINISHED]
     }
 
+    /// A paid checkout rides out longer database outages before it is dead lettered
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::from_config().max_attempts(10)
+    }
+
     async fn handle(&self, event: &crate::events::DomainEvent) -> Result<(), EventHandlerError> {
         // Parse the CheckoutFinishedEvent from the domain event payload
         let checkout_event: CheckoutFinishedEvent =
@@ -60,23 +99,84 @@ impl EventHandler for CheckoutFinishedHandler {
         let amount_cents = checkout_event.amount_cents;
 
         match checkout_event.status.as_str() {
-            "success" => {
-                // Payment succeeded - update user balance
-                let db = self.db.lock().await;
+            "session_created" | "session_failed" => {
+                let pool = self.db.lock().await.clone();
+                let flags = FeatureFlags::new(pool, None);
 
-                if let Err(err) = db_user_mutations::add_balance(&db, user_id, amount_cents).await {
-                    return Err(EventHandlerError::Retryable(format!(
-                        "Failed to update balance: {}",
-                        err
-                    )));
+                if !flags
+                    .is_enabled(flag::CHECKOUT_KAFKA_FLOW, Some(user_id))
+                    .await
+                {
+                    info!(
+                        request_id = %request_id,
+                        user_id = %user_id,
+                        status = %checkout_event.status,
+                        "Kafka checkout flow disabled for user - ignoring session result"
+                    );
+                    return Ok(());
                 }
 
-                let new_balance = db_user_read::get_by_id(&db, user_id)
-                    .await
-                    .ok()
-                    .map(|user| user.balance);
+                let result = CheckoutSessionResult::from_event(&checkout_event);
+                if let Some(result) = checkout::fulfill_pending(&request_id, result).await {
+                    info!(
+                        request_id = %request_id,
+                        "No pending checkout request (timed out or handled by another instance) - pushing result to user"
+                    );
+                    if let Err(e) = self.push_session_result(user_id, &request_id, &result).await {
+                        warn!(
+                            request_id = %request_id,
+                            user_id = %user_id,
+                            error = %e,
+                            "Failed to push checkout session result"
+                        );
+                    }
+                }
+            }
+
+            "success" => {
+                // Payment succeeded - credit balance once per request_id
+                let db = self.db.lock().await;
+
+                let credited = db_balance_ledger::credit_once(
+                    &db,
+                    &CreditOnceParams {
+                        topic: topic::CHECKOUT_FINISHED,
+                        request_id: &request_id,
+                        user_id,
+                        amount_cents,
+                        source: "checkout",
+                        metadata: json!({
+                            "session_id": checkout_event.session_id,
+                            "payment_intent_id": checkout_event.payment_intent_id,
+                            "purpose": checkout_event.purpose,
+                        }),
+                    },
+                )
+                .await;
                 drop(db);
 
+                let new_balance = match credited {
+                    Ok(Some(balance)) => {
+                        // The user fetches the new balance right away
+                        replica::mark_written(user_id).await;
+                        Some(balance)
+                    }
+                    Ok(None) => {
+                        info!(
+                            request_id = %request_id,
+                            user_id = %user_id,
+                            "Duplicate checkout_finished event - balance already credited"
+                        );
+                        return Ok(());
+                    }
+                    Err(err) => {
+                        return Err(EventHandlerError::Retryable(format!(
+                            "Failed to update balance: {}",
+                            err
+                        )));
+                    }
+                };
+
                 // Publish user.balance_updated event
                 if let (Some(producer), Some(balance)) = (&self.producer, new_balance) {
                     let balance_event = EventBuilder::new(
@@ -0,0 +1,183 @@
+//! Handler for the `checkout.subscriptions` Kafka topic
+//!
+//! The checkout service publishes a Stripe premium subscription whenever the
+//! premium it grants changes:
+//! - SubscriptionActivated: Marks the subscription active, with its paid period
+//! - SubscriptionLapsed: Marks it inactive; premium ends now
+//!
+//! A user has premium while one of their subscriptions is active
+//! (`read::premium_subscriptions::is_premium`). Events are applied in
+//! checkout's timestamp order, so a redelivered older event changes nothing.
+
+use crate::app::checkout::CheckoutSubscriptionEvent;
+use crate::database::mutations::premium_subscriptions::{
+    self as db_premium, ChangeOutcome, SubscriptionChange,
+};
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::topics::topic;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Handler for the `checkout.subscriptions` topic
+pub struct CheckoutSubscriptionsHandler {
+    db: Arc<Mutex<Pool<Postgres>>>,
+}
+
+impl CheckoutSubscriptionsHandler {
+    /// Create a new handler instance
+    pub fn new(db: Arc<Mutex<Pool<Postgres>>>) -> Self {
+        Self { db }
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// The stored change an event asks for
+fn change_of(
+    event: &CheckoutSubscriptionEvent,
+) -> Result<SubscriptionChange<'_>, EventHandlerError> {
+    let change = match event {
+        CheckoutSubscriptionEvent::SubscriptionActivated {
+            subscription_id,
+            user_id,
+            status,
+            current_period_end,
+            cancel_at_period_end,
+            timestamp,
+        } => SubscriptionChange {
+            subscription_id,
+            user_id: *user_id,
+            status,
+            active: true,
+            current_period_end: current_period_end.as_deref().and_then(parse_time),
+            cancel_at_period_end: *cancel_at_period_end,
+            changed_at: parse_time(timestamp).ok_or_else(|| invalid_timestamp(timestamp))?,
+        },
+        CheckoutSubscriptionEvent::SubscriptionLapsed {
+            subscription_id,
+            user_id,
+            status,
+            timestamp,
+        } => SubscriptionChange {
+            subscription_id,
+            user_id: *user_id,
+            status,
+            active: false,
+            current_period_end: None,
+            cancel_at_period_end: false,
+            changed_at: parse_time(timestamp).ok_or_else(|| invalid_timestamp(timestamp))?,
+        },
+    };
+
+    Ok(change)
+}
+
+fn invalid_timestamp(timestamp: &str) -> EventHandlerError {
+    EventHandlerError::Fatal(format!(
+        "Invalid checkout.subscriptions timestamp: {}",
+        timestamp
+    ))
+}
+
+#[async_trait]
+impl EventHandler for CheckoutSubscriptionsHandler {
+    fn name(&self) -> &'static str {
+        "checkout_subscriptions_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::CHECKOUT_SUBSCRIPTIONS]
+    }
+
+    async fn handle(&self, event: &crate::events::DomainEvent) -> Result<(), EventHandlerError> {
+        let subscription: CheckoutSubscriptionEvent = serde_json::from_value(event.payload.clone())
+            .map_err(|err| {
+                EventHandlerError::Fatal(format!("Invalid checkout.subscriptions payload: {}", err))
+            })?;
+        let change = change_of(&subscription)?;
+
+        let db = self.db.lock().await.clone();
+        let outcome = db_premium::apply(&db, &change).await.map_err(|err| {
+            EventHandlerError::Retryable(format!("Failed to store premium subscription: {}", err))
+        })?;
+
+        match outcome {
+            ChangeOutcome::Applied => {
+                info!(
+                    subscription_id = %change.subscription_id,
+                    user_id = %change.user_id,
+                    status = %change.status,
+                    premium = change.active,
+                    "Premium subscription updated"
+                );
+            }
+            ChangeOutcome::Stale => {
+                info!(subscription_id = %change.subscription_id, "Out-of-date subscription event - already applied a later one");
+            }
+            ChangeOutcome::UnknownUser => {
+                warn!(
+                    subscription_id = %change.subscription_id,
+                    user_id = %change.user_id,
+                    "Subscription for a user that no longer exists"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn activations_and_lapses_map_to_premium() {
+        let activated: CheckoutSubscriptionEvent = serde_json::from_value(json!({
+            "type": "checkout.subscription.activated",
+            "subscription_id": "sub_1",
+            "user_id": 7,
+            "status": "active",
+            "current_period_end": "2027-01-17T00:00:00+00:00",
+            "cancel_at_period_end": false,
+            "timestamp": "2026-10-17T12:00:00+00:00"
+        }))
+        .unwrap();
+        let change = change_of(&activated).unwrap();
+        assert!(change.active);
+        assert_eq!(change.user_id, 7);
+        assert!(change.current_period_end.is_some());
+
+        let lapsed: CheckoutSubscriptionEvent = serde_json::from_value(json!({
+            "type": "checkout.subscription.lapsed",
+            "subscription_id": "sub_1",
+            "user_id": 7,
+            "status": "canceled",
+            "timestamp": "2026-11-17T12:00:00+00:00"
+        }))
+        .unwrap();
+        let change = change_of(&lapsed).unwrap();
+        assert!(!change.active);
+        assert_eq!(change.status, "canceled");
+        assert!(change.current_period_end.is_none());
+
+        let garbled: CheckoutSubscriptionEvent = serde_json::from_value(json!({
+            "type": "checkout.subscription.lapsed",
+            "subscription_id": "sub_1",
+            "user_id": 7,
+            "status": "canceled",
+            "timestamp": "yesterday"
+        }))
+        .unwrap();
+        assert!(change_of(&garbled).is_err());
+    }
+}
@@ -4,39 +4,120 @@
 //! Active game rooms are stored in PostgreSQL for persistence across restarts.
 //! Game history is stored in MongoDB after games complete.
 
+use crate::app::cache::UserProfileCache;
+use crate::app::db_query::mutations::game_chat_mutes as chat_mute_mutations;
 use crate::app::db_query::mutations::game_room as game_room_mutations;
+use crate::app::feature_flags::{flag, FeatureFlags};
 use crate::app::db_query::mutations::game_player_disconnects as disconnect_mutations;
-use crate::app::db_query::mutations::user as user_mutations;
-use crate::app::db_query::read::game_room as game_room_read;
+use crate::app::db_query::mutations::game_predictions::{self as prediction_mutations, PlacePredictionOutcome};
+use crate::app::db_query::mutations::game_invites as invite_mutations;
+use crate::app::db_query::mutations::game_webhooks as webhook_mutations;
+use crate::app::db_query::mutations::balance_ledger::{self as ledger_mutations, ApplyOutcome, LedgerChange};
+use crate::app::db_query::read::game_chat_mutes::{self as chat_mute_read, GameChatMute};
+use crate::app::db_query::read::game_invites as invite_read;
+use crate::app::db_query::read::game_room::{self as game_room_read, RoomSearch, RoomSort};
+use crate::app::db_query::read::game_room_preset as room_preset_read;
 use crate::app::db_query::read::game_player_disconnects as disconnect_read;
-use crate::app::db_query::read::user;
-use crate::app::games::bigger_dice::{self, BiggerDiceRoundState};
+use crate::app::db_query::read::game_predictions as prediction_read;
+use crate::app::db_query::read::player_game_stats as player_stats_read;
+use crate::app::db_query::read::tournaments as tournament_read;
+use crate::app::db_query::read::user_blocks as user_blocks_read;
+use crate::app::games::bigger_dice::{self, BiggerDiceRoundState, BiggerDiceRuleSettings, BiggerDiceRules};
+use crate::app::games::bot_orchestrator::BotOrchestrator;
+use crate::app::games::bots::{self, BotDifficulty, BotGameState};
+use crate::app::games::chat_mutes::{self, ChatMuteError};
+use crate::app::games::inactivity::{InactivityAction, InactivityTracker};
+use crate::app::games::invites::{self, InviteError, InviteStatus};
+use crate::app::games::join_throttle::JoinThrottle;
 use crate::app::games::tic_tac_toe::{self, TicTacToeMatchState};
 use crate::app::games::mongodb_game_chat::{ChatChannel, MongoGameChatClient};
 use crate::app::games::mongodb_games::MongoGameClient;
+use crate::app::games::occupancy::OccupancyThrottle;
+use crate::app::games::player_stats::{PlayerGameStats, PlayerStatsSummary};
+use crate::app::games::predictions::{self, PredictionError, PredictionOutcome, PredictionPool, Stake};
+use crate::app::games::room_config::{RoomConfig, RoomConfigError, RoomSettings};
+use crate::app::games::room_game_types::RoomGameTypes;
+use crate::app::games::room_list::{self, RoomListProjection};
+use crate::app::games::room_password::{self, Verification};
+use crate::app::games::room_schedule;
+use crate::app::games::spectator_waitlist::{SpectatorWaitlist, WaitlistEntry, WaitlistJoin};
+use crate::app::games::state_checksum;
+use crate::app::games::state_diff::{RoomStateLog, StateUpdate};
+use crate::app::games::webhooks;
 use crate::app::db_query::mutations::game_user_mutes as mute_mutations;
+use crate::config::games::DEFAULT_REGION;
 use crate::config::GamesConfig;
+use crate::database::{replica, SharedRedis};
 // game_user_mutes read operations available if needed for filtering
 #[allow(unused_imports)]
 use crate::app::db_query::read::game_user_mutes as mute_read;
 use crate::app::games::types::{
-    Audience, BannedPlayer, BiggerDicePlayerRoll, EventEnvelope, GameEvent, GameHistoryPlayer,
+    Audience, BannedPlayer, BiggerDicePlayerRoll, ChatMute, EventEnvelope, GameEvent, GameHistoryPlayer,
     GamePlayer, GameRoom, GameSpectator, GameTurn, GameType, RoomStatus,
 };
 use crate::events::consumer::{EventHandler, EventHandlerError};
 use crate::events::producer::EventProducer;
+use crate::events::routing;
 use crate::events::topics::topic;
 use async_trait::async_trait;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use mongodb::Database;
+use pagination::{paginate, Cursor};
 use serde_json::Value;
 use sqlx::{Pool, Postgres};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// Commands generated by timers or the gateway rather than by a user action.
+/// When they arrive for a removed room they are dropped without replying.
+const LATE_SYSTEM_COMMANDS: &[&str] = &["player_disconnected", "bigger_dice.auto_roll"];
+
+/// Commands after which the bot of a vs_bot room may have something to do
+/// (ready up once selected, move on its turn)
+const BOT_TRIGGER_COMMANDS: &[&str] = &[
+    "select_player",
+    "ready",
+    "set_ready",
+    "start_game",
+    "bigger_dice.roll",
+    "tic_tac_toe.move",
+];
+
+/// In-game moves after which an elapsed prediction window is announced
+const PREDICTION_CLOSE_COMMANDS: &[&str] = &[
+    "bigger_dice.roll",
+    "bigger_dice.auto_roll",
+    "tic_tac_toe.move",
+];
+
+/// Outcome of checking a room password on join
+enum PasswordCheck {
+    /// Wrong password or locked out; the user has been told why
+    Denied,
+    /// May join; carries the upgraded hash when the stored one was rehashed
+    Allowed { rehashed: Option<String> },
+}
+
+/// Room state kept outside the game_rooms record, loaded with it on a cache miss
+struct RoomExtras {
+    turn_timer_seconds: Option<i64>,
+    bot_difficulty: Option<BotDifficulty>,
+    dice_rules: Option<BiggerDiceRules>,
+    chat_mutes: Vec<ChatMute>,
+}
+
+impl RoomExtras {
+    fn apply(self, room: &mut GameRoom) {
+        room.turn_timer_seconds = self.turn_timer_seconds;
+        room.bot_difficulty = self.bot_difficulty;
+        room.dice_rules = self.dice_rules;
+        room.chat_mutes = self.chat_mutes;
+    }
+}
+
 /// Handler for game commands from WebSocket gateway
 pub struct GameCommandHandler {
     db: Arc<Mutex<Pool<Postgres>>>,
@@ -50,6 +131,26 @@ pub struct GameCommandHandler {
     tic_tac_toe_states: Arc<Mutex<HashMap<String, TicTacToeMatchState>>>,
     /// Votes to auto-replace disconnected players (room_id -> user_id -> voters)
     disconnect_votes: Arc<Mutex<HashMap<String, HashMap<i64, HashSet<i64>>>>>,
+    /// Coalesces room occupancy changes for lobby room lists
+    occupancy: Arc<Mutex<OccupancyThrottle>>,
+    /// Plays the bot seat of vs_bot rooms
+    bots: BotOrchestrator,
+    /// Counts wrong room passwords per user and room
+    join_throttle: JoinThrottle,
+    /// Redis read model answering list_rooms
+    room_list: RoomListProjection,
+    /// Game types of rooms, for the gateway's command routing
+    room_game_types: RoomGameTypes,
+    /// Users queued for the spectator seats of full rooms
+    spectator_waitlist: SpectatorWaitlist,
+    /// Live spectator prediction pools of running games
+    predictions: Arc<Mutex<HashMap<String, PredictionPool>>>,
+    /// Names and avatars of players and spectators
+    profiles: UserProfileCache,
+    /// Inactivity deadlines of rooms (waiting rooms are closed when they pass)
+    inactivity: Arc<Mutex<InactivityTracker>>,
+    /// Last room state sent to each user, the base of their state diffs
+    room_states: Arc<Mutex<RoomStateLog>>,
 }
 
 impl GameCommandHandler {
@@ -57,7 +158,11 @@ impl GameCommandHandler {
         db: Arc<Mutex<Pool<Postgres>>>,
         mongodb: Option<Arc<Database>>,
         producer: Option<Arc<EventProducer>>,
+        redis: Option<SharedRedis>,
+        profiles: UserProfileCache,
     ) -> Self {
+        let bots = BotOrchestrator::new(producer.clone());
+
         Self {
             db,
             mongodb,
@@ -66,6 +171,20 @@ impl GameCommandHandler {
             round_states: Arc::new(Mutex::new(HashMap::new())),
             tic_tac_toe_states: Arc::new(Mutex::new(HashMap::new())),
             disconnect_votes: Arc::new(Mutex::new(HashMap::new())),
+            occupancy: Arc::new(Mutex::new(OccupancyThrottle::default())),
+            bots,
+            join_throttle: JoinThrottle::new(redis.clone()),
+            spectator_waitlist: SpectatorWaitlist::new(redis.clone()),
+            room_game_types: RoomGameTypes::new(redis.clone()),
+            room_list: RoomListProjection::new(redis),
+            predictions: Arc::new(Mutex::new(HashMap::new())),
+            profiles,
+            inactivity: Arc::new(Mutex::new(InactivityTracker::new(
+                Duration::minutes(GamesConfig::room_inactivity_timeout_minutes()),
+                Duration::minutes(GamesConfig::room_inactivity_warning_minutes()),
+                Duration::minutes(GamesConfig::room_inactivity_extension_minutes()),
+            ))),
+            room_states: Arc::new(Mutex::new(RoomStateLog::default())),
         }
     }
 
@@ -87,100 +206,6 @@ impl GameCommandHandler {
         })
     }
 
-    fn json_array_has_user_id(value: &serde_json::Value, user_id: i64) -> bool {
-        value.as_array().is_some_and(|items| {
-            items.iter().any(|item| Self::parse_user_id(item.get("user_id")) == Some(user_id))
-        })
-    }
-
-    fn room_list_item_for_user(
-        record: &game_room_read::GameRoomRecord,
-        user_id: i64,
-    ) -> Option<serde_json::Value> {
-        let is_waiting = record.status == "waiting";
-        let is_in_progress = record.status == "in_progress";
-
-        if record.banned_users.contains(&user_id) {
-            return None;
-        }
-
-        let user_in_room = record.host_id == user_id
-            || record.recorded_players.contains(&user_id)
-            || record.recorded_spectators.contains(&user_id)
-            || record.spectators.contains(&user_id)
-            || Self::json_array_has_user_id(&record.players, user_id)
-            || Self::json_array_has_user_id(&record.lobby, user_id);
-
-        if !is_waiting && !(is_in_progress && user_in_room) {
-            return None;
-        }
-
-        let player_count = record.players.as_array().map(|a| a.len()).unwrap_or(0);
-        let lobby_count = record.lobby.as_array().map(|a| a.len()).unwrap_or(0);
-        let spectator_count = record.spectators.len();
-
-        // Find host name from players or lobby array
-        let host_id = record.host_id;
-        let host_name = record
-            .players
-            .as_array()
-            .and_then(|players| {
-                players.iter().find(|p| {
-                    p.get("user_id").and_then(|id| id.as_i64()) == Some(host_id)
-                })
-            })
-            .and_then(|p| p.get("username").and_then(|n| n.as_str()))
-            .or_else(|| {
-                record.lobby.as_array().and_then(|lobby| {
-                    lobby.iter().find(|p| {
-                        p.get("user_id").and_then(|id| id.as_i64()) == Some(host_id)
-                    })
-                })
-                .and_then(|p| p.get("username").and_then(|n| n.as_str()))
-            })
-            .unwrap_or("Unknown");
-
-        let rejoin_role = if user_in_room {
-            if record.recorded_players.contains(&user_id)
-                || Self::json_array_has_user_id(&record.players, user_id)
-            {
-                Some("player")
-            } else if record.recorded_spectators.contains(&user_id)
-                || record.spectators.contains(&user_id)
-            {
-                Some("spectator")
-            } else if Self::json_array_has_user_id(&record.lobby, user_id) {
-                Some("lobby")
-            } else if record.host_id == user_id {
-                Some("host")
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        Some(serde_json::json!({
-            "room_id": record.room_id,
-            "room_name": record.room_name,
-            "game_type": record.game_type,
-            "host_id": record.host_id,
-            "host_name": host_name,
-            "players": record.players, // Full player array for UI display
-            "lobby": record.lobby,     // Full lobby array
-            "player_count": player_count,
-            "lobby_count": lobby_count,
-            "spectator_count": spectator_count,
-            "max_players": record.player_count, // Use actual player count from room
-            "status": record.status,
-            "is_password_protected": record.is_password_protected,
-            "allow_spectators": record.allow_spectators,
-            "created_at": record.created_at.to_rfc3339(),
-            "can_rejoin": user_in_room,
-            "rejoin_role": rejoin_role,
-        }))
-    }
-
     fn active_kick_voter_ids(
         room: &GameRoom,
         pending_disconnects: &HashSet<i64>,
@@ -220,6 +245,7 @@ impl GameCommandHandler {
             .unwrap_or_default();
         let game_type = GameType::from_str(&record.game_type).unwrap_or_default();
         let status = match record.status.as_str() {
+            "scheduled" => RoomStatus::Scheduled,
             "waiting" => RoomStatus::Waiting,
             "in_progress" => RoomStatus::InProgress,
             "finished" => RoomStatus::Finished,
@@ -265,6 +291,8 @@ impl GameCommandHandler {
             player_count: record.player_count,
             allow_spectators: record.allow_spectators,
             max_spectators: record.max_spectators,
+            // Not part of the record; get_room/get_room_by_name load it separately
+            turn_timer_seconds: None,
             admin_spectator_id: record.admin_spectator_id,
             lobby_chat_enabled: record.lobby_chat_enabled,
             spectators_data,
@@ -272,9 +300,54 @@ impl GameCommandHandler {
             recorded_spectators: record.recorded_spectators.clone(),
             selected_players: record.selected_players.clone(),
             auto_players: record.auto_players.clone(),
+            // Not part of the record; get_room/get_room_by_name load them separately
+            bot_difficulty: None,
+            dice_rules: None,
+            chat_mutes: Vec::new(),
+        }
+    }
+
+    /// Room chat mute from its database record
+    fn db_record_to_chat_mute(record: &GameChatMute) -> ChatMute {
+        ChatMute {
+            user_id: record.user_id,
+            channel: chat_mutes::channel_from_stored(&record.channel),
+            muted_by: record.muted_by,
+            expires_at: record.expires_at,
         }
     }
 
+    /// Room settings stored outside the GameRoomRecord columns
+    /// (turn timer, bot difficulty, dice rules, chat mutes)
+    async fn load_room_extras(
+        db: &Pool<Postgres>,
+        room_id: &str,
+    ) -> Result<RoomExtras, EventHandlerError> {
+        let turn_timer_seconds = game_room_read::get_turn_timer(db, room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        let bot_difficulty = game_room_read::get_bot_difficulty(db, room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?
+            .and_then(|d| BotDifficulty::from_str(&d));
+        let dice_rules = game_room_read::get_dice_rules(db, room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        let chat_mutes = chat_mute_read::get_active_by_room(db, room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?
+            .iter()
+            .map(Self::db_record_to_chat_mute)
+            .collect();
+
+        Ok(RoomExtras {
+            turn_timer_seconds,
+            bot_difficulty,
+            dice_rules,
+            chat_mutes,
+        })
+    }
+
     /// Get room from cache or database
     async fn get_room(&self, room_id: &str) -> Result<Option<GameRoom>, EventHandlerError> {
         // Check cache first
@@ -292,10 +365,15 @@ impl GameCommandHandler {
         let record = game_room_read::get_by_room_id(&db, room_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        let extras = match record {
+            Some(_) => Some(Self::load_room_extras(&db, room_id).await?),
+            None => None,
+        };
         drop(db);
 
-        if let Some(record) = record {
-            let room = Self::db_record_to_game_room(&record);
+        if let (Some(record), Some(extras)) = (record, extras) {
+            let mut room = Self::db_record_to_game_room(&record);
+            extras.apply(&mut room);
             // Update cache
             let mut rooms = self.rooms.lock().await;
             rooms.insert(room_id.to_string(), room.clone());
@@ -328,10 +406,15 @@ impl GameCommandHandler {
         let record = game_room_read::get_by_room_name(&db, room_name)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        let extras = match &record {
+            Some(record) => Some(Self::load_room_extras(&db, &record.room_id).await?),
+            None => None,
+        };
         drop(db);
 
-        if let Some(record) = record {
-            let room = Self::db_record_to_game_room(&record);
+        if let (Some(record), Some(extras)) = (record, extras) {
+            let mut room = Self::db_record_to_game_room(&record);
+            extras.apply(&mut room);
             // Update cache
             let mut rooms = self.rooms.lock().await;
             rooms.insert(room.room_id.clone(), room.clone());
@@ -358,19 +441,176 @@ impl GameCommandHandler {
             rooms.insert(room.room_id.clone(), room.clone());
         }
 
+        self.schedule_occupancy_update(room).await;
+
         // Database sync is handled by specific mutations for each operation
         // This method just ensures cache consistency
         Ok(())
     }
 
+    /// Check a room password, throttling wrong guesses per user and room.
+    /// Legacy/outdated hashes are upgraded in the database and cache on success.
+    async fn check_room_password(
+        &self,
+        room_id: &str,
+        room_name: &str,
+        password_hash: Option<&str>,
+        user_id: i64,
+        socket_id: &str,
+        provided: &str,
+    ) -> Result<PasswordCheck, EventHandlerError> {
+        let Some(password_hash) = password_hash else {
+            return Ok(PasswordCheck::Allowed { rehashed: None });
+        };
+
+        if let Some(retry_after) = self.join_throttle.retry_after(room_id, user_id).await {
+            self.publish_join_denied(room_id, room_name, user_id, socket_id, retry_after)
+                .await?;
+            return Ok(PasswordCheck::Denied);
+        }
+
+        let verification = room_password::verify(provided, password_hash);
+        if !verification.is_valid() {
+            match self.join_throttle.record_failure(room_id, user_id).await {
+                Some(retry_after) => {
+                    self.publish_join_denied(room_id, room_name, user_id, socket_id, retry_after)
+                        .await?;
+                }
+                None => {
+                    let error = GameEvent::Error {
+                        code: "wrong_password".to_string(),
+                        message: "Incorrect room password".to_string(),
+                        socket_id: socket_id.to_string(),
+                    };
+                    self.publish_game_event(error, Audience::user(user_id)).await?;
+                }
+            }
+            return Ok(PasswordCheck::Denied);
+        }
+
+        self.join_throttle.clear(room_id, user_id).await;
+
+        if verification != Verification::ValidNeedsRehash {
+            return Ok(PasswordCheck::Allowed { rehashed: None });
+        }
+
+        let Some(rehashed) = room_password::hash(provided) else {
+            return Ok(PasswordCheck::Allowed { rehashed: None });
+        };
+
+        let db = self.db.lock().await;
+        let result = game_room_mutations::update_password_hash(&db, room_id, &rehashed).await;
+        drop(db);
+
+        if let Err(e) = result {
+            // Not fatal: the old hash still works, the upgrade is retried next join
+            warn!(room_id = %room_id, error = %e, "Failed to upgrade room password hash");
+            return Ok(PasswordCheck::Allowed { rehashed: None });
+        }
+
+        if let Some(room) = self.rooms.lock().await.get_mut(room_id) {
+            room.password_hash = Some(rehashed.clone());
+        }
+        info!(room_id = %room_id, "Upgraded room password hash to argon2id");
+
+        Ok(PasswordCheck::Allowed {
+            rehashed: Some(rehashed),
+        })
+    }
+
+    async fn publish_join_denied(
+        &self,
+        room_id: &str,
+        room_name: &str,
+        user_id: i64,
+        socket_id: &str,
+        retry_after_seconds: u64,
+    ) -> Result<(), EventHandlerError> {
+        let event = GameEvent::RoomJoinDenied {
+            room_id: room_id.to_string(),
+            room_name: room_name.to_string(),
+            reason: "too_many_attempts".to_string(),
+            retry_after_seconds,
+            socket_id: socket_id.to_string(),
+        };
+        self.publish_game_event(event, Audience::user(user_id)).await
+    }
+
     /// Remove room from cache
     async fn remove_room_from_cache(&self, room_id: &str) {
         let mut rooms = self.rooms.lock().await;
         rooms.remove(room_id);
+        drop(rooms);
+
+        self.occupancy.lock().await.forget(room_id);
+        self.inactivity.lock().await.forget(room_id);
+        self.room_states.lock().await.forget(room_id);
+        self.spectator_waitlist.clear(room_id).await;
+    }
+
+    /// Push back the inactivity deadline of a cached room
+    async fn record_activity(&self, room_id: &str) {
+        if !self.rooms.lock().await.contains_key(room_id) {
+            return;
+        }
+
+        self.inactivity.lock().await.touch(room_id, Utc::now());
+    }
+
+    /// Record a room's occupancy and, when this opens a new throttle window,
+    /// publish `room_occupancy_changed` once the window closes
+    async fn schedule_occupancy_update(&self, room: &GameRoom) {
+        if !self.occupancy.lock().await.record(room) {
+            return;
+        }
+
+        let Some(producer) = self.producer.clone() else {
+            return;
+        };
+        let occupancy = self.occupancy.clone();
+        let room_id = room.room_id.clone();
+        let window = std::time::Duration::from_millis(GamesConfig::occupancy_throttle_ms());
+
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+
+            let Some(update) = occupancy.lock().await.flush(&room_id) else {
+                return;
+            };
+
+            if let Err(e) = Self::send_game_event(
+                &producer,
+                update.into_event(),
+                Audience::broadcast(),
+                None,
+            )
+            .await
+            {
+                warn!(room_id = %room_id, error = %e, "Failed to publish room occupancy");
+            }
+        });
     }
 
     /// Build a room state event that keeps the ready phase in a waiting UI state.
-    fn room_state_event(room: &GameRoom) -> GameEvent {
+    /// Stats shown next to a player entering a lobby (best effort: `None` if
+    /// they can't be loaded, zeroed before the player's first finished game)
+    async fn lobby_stats(&self, user_id: i64, game_type: &GameType) -> Option<PlayerStatsSummary> {
+        let db = self.db.lock().await.clone();
+        match player_stats_read::get(&db, user_id, game_type.as_str()).await {
+            Ok(stats) => Some(
+                stats
+                    .unwrap_or_else(|| PlayerGameStats::new(user_id, game_type.as_str()))
+                    .summary(),
+            ),
+            Err(e) => {
+                warn!(user_id = %user_id, error = %e, "Failed to load lobby player stats");
+                None
+            }
+        }
+    }
+
+    /// The room as clients are shown it
+    fn room_state_view(room: &GameRoom) -> GameRoom {
         let mut room_state = room.clone();
         let selected_full = room_state.selected_players.len() as i32 == room_state.player_count;
 
@@ -384,7 +624,59 @@ impl GameCommandHandler {
             room_state.status = RoomStatus::Waiting;
         }
 
-        GameEvent::RoomState { room: room_state }
+        room_state
+    }
+
+    /// Full room state for a user; it becomes the base of the user's next diffs
+    async fn room_state_event(&self, room: &GameRoom, user_id: i64) -> GameEvent {
+        let room_state = Self::room_state_view(room);
+        let state_checksum = state_checksum::checksum(&room_state);
+        let state = serde_json::to_value(&room_state).unwrap_or(Value::Null);
+        let version = self.room_states.lock().await.snapshot(&room.room_id, user_id, state);
+
+        GameEvent::RoomState {
+            room: room_state,
+            state_checksum,
+            version,
+        }
+    }
+
+    /// Changes of the room since the state a user was sent last: a diff, or
+    /// the full state when they hold none; `None` when nothing changed for them
+    async fn room_state_update(&self, room: &GameRoom, user_id: i64) -> Option<GameEvent> {
+        let room_state = Self::room_state_view(room);
+        let state_checksum = state_checksum::checksum(&room_state);
+        let state = serde_json::to_value(&room_state).unwrap_or(Value::Null);
+        let update = self.room_states.lock().await.update(&room.room_id, user_id, state);
+
+        match update {
+            StateUpdate::Snapshot { version } => Some(GameEvent::RoomState {
+                room: room_state,
+                state_checksum,
+                version,
+            }),
+            StateUpdate::Diff { base_version, version, ops } => Some(GameEvent::RoomStateDiff {
+                room_id: room.room_id.clone(),
+                base_version,
+                version,
+                ops,
+                state_checksum,
+            }),
+            StateUpdate::Unchanged => None,
+        }
+    }
+
+    /// Send a user the room's changes since their last room state
+    async fn publish_room_state_update(&self, room: &GameRoom, user_id: i64) -> Result<(), EventHandlerError> {
+        let gt = room.game_type.as_str();
+        match self.room_state_update(room, user_id).await {
+            // Snapshots keep their game prefix, diffs are the same for every game
+            Some(event @ GameEvent::RoomState { .. }) => {
+                self.publish_game_event_typed(event, Audience::user(user_id), Some(gt)).await
+            }
+            Some(event) => self.publish_game_event(event, Audience::user(user_id)).await,
+            None => Ok(()),
+        }
     }
 
     /// Send an event back to the WebSocket gateway via Kafka
@@ -399,118 +691,844 @@ impl GameCommandHandler {
         self.publish_game_event_typed(event, audience, None).await
     }
 
-    /// Send a game-typed event back to the WebSocket gateway via Kafka
+    /// Check whether a command targets a room that no longer exists.
     ///
-    /// This version includes the game_type prefix for events like room_created, player_left, etc.
-    /// Use this for events that need to be filtered by game type on the frontend.
-    async fn publish_game_event_typed(
+    /// Rooms still in the in-memory cache are never considered gone. Otherwise the
+    /// room's tombstone is looked up: user commands get a `room_gone` reply, while
+    /// late system-generated commands (timers, disconnect notices) are dropped quietly.
+    /// Returns true when the command must not be processed.
+    async fn reject_if_room_gone(
         &self,
-        event: GameEvent,
-        audience: Audience,
-        game_type: Option<&str>,
-    ) -> Result<(), EventHandlerError> {
-        let Some(producer) = &self.producer else {
-            warn!("No Kafka producer available for game events");
-            return Ok(());
-        };
-
-        let base_event_name = event.event_type_name();
+        command_type: &str,
+        room_id: &str,
+        user_id: i64,
+        socket_id: &str,
+    ) -> Result<bool, EventHandlerError> {
+        if self.rooms.lock().await.contains_key(room_id) {
+            return Ok(false);
+        }
 
-        // Construct full event type:
-        // - If event already has game prefix (e.g., "bigger_dice.rolled"), use as-is
-        // - If game_type provided and no prefix, add game prefix (e.g., "bigger_dice.room_created")
-        // - Otherwise, use base name (e.g., "room_created") for backward compatibility
-        let event_type = if base_event_name.contains('.') {
-            // Already has a game prefix like "bigger_dice.rolled" or "tic_tac_toe.moved"
-            format!("games.event.{}", base_event_name)
-        } else if let Some(gt) = game_type {
-            // Add game prefix like "games.event.bigger_dice.room_created"
-            format!("games.event.{}.{}", gt, base_event_name)
-        } else {
-            // Fallback to original behavior (no game prefix)
-            format!("games.event.{}", base_event_name)
-        };
+        let db = self.db.lock().await;
+        let tombstone = game_room_read::get_tombstone(&db, room_id).await;
+        drop(db);
 
-        let envelope = EventEnvelope {
-            event_id: Uuid::new_v4().to_string(),
-            event_type,
-            timestamp: Utc::now().to_rfc3339(),
-            correlation_id: None,
-            producer: "blazing_sun".to_string(),
-            actor: crate::app::games::types::Actor {
-                user_id: 0,
-                username: "system".to_string(),
-                socket_id: String::new(),
-                roles: vec![],
-            },
-            audience,
-            payload: serde_json::to_value(&event).unwrap_or(Value::Null),
+        let Some(tombstone) = tombstone else {
+            return Ok(false);
         };
 
-        let bytes = serde_json::to_vec(&envelope)
-            .map_err(|e| EventHandlerError::Fatal(format!("Failed to serialize game event: {}", e)))?;
+        if LATE_SYSTEM_COMMANDS.contains(&command_type) {
+            info!(
+                room_id = %room_id,
+                command_type = %command_type,
+                reason = %tombstone.reason,
+                "Dropping late command for removed room"
+            );
+            return Ok(true);
+        }
 
-        producer
-            .send_raw(topic::GAMES_EVENTS, None, &bytes)
-            .await
-            .map_err(|e| EventHandlerError::Retryable(format!("Failed to publish game event: {}", e)))?;
+        let event = GameEvent::RoomGone {
+            room_id: tombstone.room_id,
+            room_name: tombstone.room_name,
+            reason: tombstone.reason,
+            socket_id: socket_id.to_string(),
+        };
+        self.publish_game_event(event, Audience::user(user_id)).await?;
 
-        Ok(())
+        Ok(true)
     }
 
-    /// Publish a game participation event to Kafka for the checkout service
-    /// This event is published when a player is selected to play and their balance is deducted
-    async fn publish_game_participation_event(
+    /// Check whether a command targets a room owned by another region.
+    ///
+    /// Rooms in the in-memory cache belong to this instance. Otherwise the room's
+    /// region is looked up by `room_id`, or by `room_name` for join commands; when
+    /// it is owned elsewhere the user gets a `room_migrated` reply pointing at the
+    /// owning region's gateway. Returns true when the command must not be processed.
+    async fn reject_if_room_elsewhere(
         &self,
+        command_type: &str,
+        payload: &Value,
         user_id: i64,
-        amount_cents: i64,
-        room_id: &str,
-        room_name: &str,
-        game_type: GameType,
-        username: Option<&str>,
-    ) {
-        let Some(producer) = &self.producer else {
-            warn!("No Kafka producer available for game participation events");
-            return;
+    ) -> Result<bool, EventHandlerError> {
+        let room_id = payload.get("room_id").and_then(|v| v.as_str());
+        let room_name = match command_type {
+            "join_room" | "join_as_spectator" => payload.get("room_name").and_then(|v| v.as_str()),
+            _ => None,
         };
 
-        let event_payload = serde_json::json!({
-            "event_type": "game.participation.deducted",
-            "event_id": Uuid::new_v4().to_string(),
-            "timestamp": Utc::now().to_rfc3339(),
-            "user_id": user_id,
-            "amount_cents": amount_cents,
-            "game_type": game_type.as_str(),
-            "room_id": room_id,
-            "room_name": room_name,
-            "username": username,
-            "description": format!("PAY {} GAME", game_type.as_str().to_uppercase().replace("_", " ")),
-        });
+        if room_id.is_none() && room_name.is_none() {
+            return Ok(false);
+        }
 
-        let bytes = match serde_json::to_vec(&event_payload) {
-            Ok(b) => b,
-            Err(e) => {
-                error!(error = %e, "Failed to serialize game participation event");
-                return;
+        if let Some(room_id) = room_id {
+            if self.rooms.lock().await.contains_key(room_id) {
+                return Ok(false);
             }
-        };
+        }
 
-        // Route to the correct topic based on game type
-        let topic_name = match game_type {
-            GameType::BiggerDice => topic::BIGGER_DICE_PARTICIPATION_PAYED,
-            GameType::TicTacToe => topic::TIC_TAC_TOE_PARTICIPATION_PAYED,
+        let db = self.db.lock().await;
+        let region = match (room_id, room_name) {
+            (Some(room_id), _) => game_room_read::get_region(&db, room_id).await,
+            (None, Some(room_name)) => game_room_read::get_region_by_name(&db, room_name).await,
+            (None, None) => Ok(None),
+        }
+        .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        drop(db);
+
+        let Some(region) = region else {
+            return Ok(false);
         };
 
-        // Use user_id as partition key to ensure ordering per user
-        let key = user_id.to_string();
-        if let Err(e) = producer.send_raw(topic_name, Some(&key), &bytes).await {
-            error!(
-                error = %e,
-                user_id = %user_id,
-                amount = %amount_cents,
-                room_id = %room_id,
-                topic = %topic_name,
-                "Failed to publish game participation event"
+        if region.region == GamesConfig::region() {
+            return Ok(false);
+        }
+
+        if LATE_SYSTEM_COMMANDS.contains(&command_type) {
+            info!(
+                room_id = %region.room_id,
+                region = %region.region,
+                command_type = %command_type,
+                "Dropping command for room owned by another region"
+            );
+            return Ok(true);
+        }
+
+        let event = GameEvent::RoomMigrated {
+            room_id: region.room_id,
+            room_name: region.room_name,
+            from_region: GamesConfig::region().to_string(),
+            gateway_url: GamesConfig::find_region(&region.region).map(|r| r.gateway_url.clone()),
+            to_region: region.region,
+        };
+        self.publish_game_event(event, Audience::user(user_id)).await?;
+
+        Ok(true)
+    }
+
+    /// Move a waiting room owned by this region to another region.
+    ///
+    /// The room is dropped from the local caches and everyone in it is told to
+    /// reconnect to the target region's gateway. In-progress rooms are never moved.
+    async fn handle_migrate_room(
+        &self,
+        room_id: &str,
+        to_region: &str,
+        trigger: &str,
+        requested_by: Option<i64>,
+    ) -> Result<(), EventHandlerError> {
+        let from_region = GamesConfig::region();
+
+        if to_region == from_region {
+            warn!(room_id = %room_id, region = %to_region, "Room already belongs to the target region");
+            return Ok(());
+        }
+
+        let Some(target) = GamesConfig::find_region(to_region) else {
+            warn!(room_id = %room_id, region = %to_region, "Unknown target region for room migration");
+            return Err(EventHandlerError::Skip);
+        };
+
+        let Some(room) = self.get_room(room_id).await? else {
+            warn!(room_id = %room_id, "Room to migrate not found");
+            return Err(EventHandlerError::Skip);
+        };
+
+        if room.status != RoomStatus::Waiting {
+            warn!(
+                room_id = %room_id,
+                status = ?room.status,
+                "Only waiting rooms can be migrated"
+            );
+            return Ok(());
+        }
+
+        let db = self.db.lock().await;
+        let migrated = game_room_mutations::migrate_region(
+            &db,
+            room_id,
+            from_region,
+            to_region,
+            trigger,
+            requested_by,
+        )
+        .await
+        .map_err(|e| EventHandlerError::Retryable(format!("Failed to migrate room: {}", e)))?;
+        drop(db);
+
+        if !migrated {
+            warn!(room_id = %room_id, "Room changed before it could be migrated");
+            return Ok(());
+        }
+
+        self.remove_room_from_cache(room_id).await;
+        self.round_states.lock().await.remove(room_id);
+        self.tic_tac_toe_states.lock().await.remove(room_id);
+        self.clear_disconnect_votes_room(room_id).await;
+
+        let event = GameEvent::RoomMigrated {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            from_region: from_region.to_string(),
+            to_region: to_region.to_string(),
+            gateway_url: Some(target.gateway_url.clone()),
+        };
+        self.publish_game_event_typed(event, Audience::room(room_id), Some(room.game_type.as_str()))
+            .await?;
+
+        info!(
+            room_id = %room_id,
+            from_region = %from_region,
+            to_region = %to_region,
+            trigger = %trigger,
+            "Room migrated to another region"
+        );
+
+        Ok(())
+    }
+
+    /// Close a waiting room on behalf of an operator.
+    async fn handle_close_room(
+        &self,
+        room_id: &str,
+        requested_by: Option<i64>,
+    ) -> Result<(), EventHandlerError> {
+        self.close_waiting_room(room_id, "closed_by_admin", requested_by).await
+    }
+
+    /// Close a waiting room (operator request or inactivity).
+    ///
+    /// Goes through the same teardown as a host leaving. Members get
+    /// `room_closing` and lobbies `room_removed`, both with `reason`.
+    /// In-progress rooms hold bets and are never closed here.
+    async fn close_waiting_room(
+        &self,
+        room_id: &str,
+        reason: &str,
+        requested_by: Option<i64>,
+    ) -> Result<(), EventHandlerError> {
+        let Some(room) = self.get_room(room_id).await? else {
+            warn!(room_id = %room_id, "Room to close not found");
+            self.inactivity.lock().await.forget(room_id);
+            return Err(EventHandlerError::Skip);
+        };
+
+        if room.status != RoomStatus::Waiting {
+            warn!(
+                room_id = %room_id,
+                status = ?room.status,
+                "Only waiting rooms can be closed"
+            );
+            self.inactivity.lock().await.touch(room_id, Utc::now());
+            return Ok(());
+        }
+
+        let db = self.db.lock().await;
+        game_room_mutations::deactivate(&db, room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to close room: {}", e)))?;
+        if let Err(e) = disconnect_mutations::delete_for_room(&db, room_id).await {
+            warn!(error = %e, "Failed to clear disconnect records for room");
+        }
+        drop(db);
+
+        self.remove_room_from_cache(room_id).await;
+        self.round_states.lock().await.remove(room_id);
+        self.tic_tac_toe_states.lock().await.remove(room_id);
+        self.clear_disconnect_votes_room(room_id).await;
+
+        let closing = GameEvent::RoomClosing {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            reason: reason.to_string(),
+        };
+        self.publish_game_event(closing, Audience::room(room.room_id.clone())).await?;
+
+        let event = GameEvent::RoomRemoved {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            reason: reason.to_string(),
+        };
+        self.publish_game_event_typed(event, Audience::broadcast(), Some(room.game_type.as_str()))
+            .await?;
+
+        info!(room_id = %room_id, reason = %reason, requested_by = ?requested_by, "Room closed");
+
+        Ok(())
+    }
+
+    /// Warn and close waiting rooms of this region that saw no commands
+    /// (GAME_ROOM_INACTIVITY_TIMEOUT_MINUTES)
+    pub async fn close_inactive_rooms(&self) {
+        let now = Utc::now();
+
+        // Rooms nobody sent a command to since this process started are
+        // tracked from their last update
+        let db = self.db.lock().await;
+        let waiting = game_room_read::get_waiting_room_activity_in_region(&db, GamesConfig::region()).await;
+        drop(db);
+
+        match waiting {
+            Ok(rooms) => {
+                let timeout = Duration::minutes(GamesConfig::room_inactivity_timeout_minutes());
+                let mut inactivity = self.inactivity.lock().await;
+                for (room_id, updated_at) in rooms {
+                    if !inactivity.is_tracked(&room_id) {
+                        inactivity.track(&room_id, updated_at + timeout);
+                    }
+                }
+            }
+            Err(e) => error!("Failed to load waiting rooms for inactivity check: {}", e),
+        }
+
+        let actions = self.inactivity.lock().await.due(now);
+        for action in actions {
+            let result = match &action {
+                InactivityAction::Warn { room_id, closes_at } => {
+                    self.warn_inactive_room(room_id, *closes_at).await
+                }
+                InactivityAction::Close { room_id } => {
+                    self.close_waiting_room(room_id, "inactive", None).await
+                }
+            };
+
+            match result {
+                Ok(()) | Err(EventHandlerError::Skip) => {}
+                Err(e) => warn!(action = ?action, error = %e, "Failed to handle inactive room"),
+            }
+        }
+    }
+
+    /// Tell the members of an inactive waiting room when it closes
+    async fn warn_inactive_room(
+        &self,
+        room_id: &str,
+        closes_at: DateTime<Utc>,
+    ) -> Result<(), EventHandlerError> {
+        let Some(room) = self.get_room(room_id).await? else {
+            self.inactivity.lock().await.forget(room_id);
+            return Ok(());
+        };
+
+        // Running games are never closed for inactivity
+        if room.status != RoomStatus::Waiting {
+            self.inactivity.lock().await.touch(room_id, Utc::now());
+            return Ok(());
+        }
+
+        let event = GameEvent::RoomInactivityWarning {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            closes_at,
+            closes_in_seconds: (closes_at - Utc::now()).num_seconds().max(0),
+        };
+        self.publish_game_event(event, Audience::room(room.room_id.clone())).await
+    }
+
+    /// Remind the users registered for scheduled rooms and open the rooms
+    /// that are due
+    pub async fn run_room_schedule(&self) {
+        let now = Utc::now();
+        let reminders = GamesConfig::room_schedule_reminder_minutes();
+        // Reminders are sorted largest first
+        let horizon = now + Duration::minutes(reminders.first().copied().unwrap_or(0));
+
+        let db = self.db.lock().await;
+        let rooms = game_room_read::get_scheduled_rooms(&db, None, Some(GamesConfig::region()), horizon).await;
+        drop(db);
+
+        let rooms = match rooms {
+            Ok(rooms) => rooms,
+            Err(e) => {
+                error!("Failed to load scheduled rooms: {}", e);
+                return;
+            }
+        };
+
+        for room in rooms {
+            let result = if room.scheduled_start_at <= now {
+                self.open_scheduled_room(&room).await
+            } else {
+                self.remind_scheduled_room(&room, now).await
+            };
+
+            match result {
+                Ok(()) | Err(EventHandlerError::Skip) => {}
+                Err(e) => warn!(room_id = %room.room_id, error = %e, "Failed to handle scheduled room"),
+            }
+        }
+    }
+
+    /// Send the nearest due reminder of a scheduled room no instance sent yet
+    async fn remind_scheduled_room(
+        &self,
+        room: &game_room_read::ScheduledRoom,
+        now: DateTime<Utc>,
+    ) -> Result<(), EventHandlerError> {
+        let due = room_schedule::due_reminders(
+            now,
+            room.scheduled_start_at,
+            GamesConfig::room_schedule_reminder_minutes(),
+        );
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let db = self.db.lock().await;
+        let claimed = game_room_mutations::claim_schedule_reminders(&db, &room.room_id, &due)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to record reminders: {}", e)))?;
+        let Some(minutes_before) = claimed.into_iter().min() else {
+            return Ok(());
+        };
+        let users = game_room_read::get_registered_users(&db, &room.room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        drop(db);
+
+        if users.is_empty() {
+            return Ok(());
+        }
+
+        let event = GameEvent::ScheduledRoomReminder {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            game_type: room.game_type.clone(),
+            scheduled_start_at: room.scheduled_start_at,
+            minutes_before,
+        };
+        self.publish_game_event(event, Audience::users(users)).await
+    }
+
+    /// Open a due scheduled room to the lobby and tell its registered users
+    async fn open_scheduled_room(&self, scheduled: &game_room_read::ScheduledRoom) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await;
+        let opened = game_room_mutations::open_scheduled(&db, &scheduled.room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to open scheduled room: {}", e)))?;
+        if !opened {
+            return Ok(());
+        }
+        let users = game_room_read::get_registered_users(&db, &scheduled.room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        drop(db);
+
+        // A cached copy still says scheduled; read the room again
+        self.rooms.lock().await.remove(&scheduled.room_id);
+        let Some(room) = self.get_room(&scheduled.room_id).await? else {
+            return Ok(());
+        };
+
+        let host_username = room
+            .lobby
+            .iter()
+            .find(|p| p.user_id == room.host_id)
+            .map(|p| p.username.clone())
+            .unwrap_or_else(|| scheduled.host_name.clone());
+        self.announce_room_created(&room, &host_username).await?;
+
+        info!(room_id = %room.room_id, registered = users.len(), "Scheduled room opened");
+
+        if users.is_empty() {
+            return Ok(());
+        }
+        let event = GameEvent::ScheduledRoomOpened {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            game_type: room.game_type.as_str().to_string(),
+        };
+        self.publish_game_event(event, Audience::users(users)).await
+    }
+
+    /// Host pushes back the inactivity deadline of their room
+    async fn handle_extend_room(
+        &self,
+        user_id: i64,
+        room_id: &str,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        if GamesConfig::room_inactivity_timeout_minutes() <= 0 {
+            return Ok(());
+        }
+
+        let Some(room) = self.get_room(room_id).await? else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room not found".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        if room.host_id != user_id {
+            let error = GameEvent::Error {
+                code: "not_host".to_string(),
+                message: "Only the host can extend the room".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        let closes_at = self.inactivity.lock().await.extend(room_id, Utc::now());
+
+        let event = GameEvent::RoomInactivityExtended {
+            room_id: room.room_id.clone(),
+            closes_at,
+            extended_by: user_id,
+        };
+        self.publish_game_event(event, Audience::room(room.room_id.clone())).await
+    }
+
+    /// Handle invite_user command: invite another user into a room the sender is in
+    async fn handle_invite_user(
+        &self,
+        user_id: i64,
+        username: &str,
+        room_id: &str,
+        invitee_id: i64,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let Some(room) = self.get_room(room_id).await? else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room not found".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        let db = self.db.lock().await;
+        let mut checked = invites::check_invite(&room, user_id, invitee_id);
+        if checked.is_ok() && game_room_read::is_user_banned(&db, &room.room_id, invitee_id).await {
+            checked = Err(InviteError::NotAllowed);
+        }
+        // Users who blocked the inviter are told nothing
+        if checked.is_ok() {
+            let blocked = user_blocks_read::blocked_ids(&db, invitee_id)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to load blocked users: {}", e)))?;
+            if blocked.contains(&user_id) {
+                checked = Err(InviteError::NotAllowed);
+            }
+        }
+        if checked.is_ok() {
+            match self.profiles.get(&db, invitee_id).await {
+                Ok(_) => {}
+                Err(sqlx::Error::RowNotFound) => checked = Err(InviteError::NotAllowed),
+                Err(e) => return Err(EventHandlerError::Retryable(format!("Failed to get user: {}", e))),
+            }
+        }
+        if let Err(e) = checked {
+            drop(db);
+            let error = GameEvent::Error {
+                code: e.code().to_string(),
+                message: e.to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        let expires_at = Utc::now() + Duration::seconds(GamesConfig::room_invite_ttl_seconds());
+        let invite = invite_mutations::create(&db, &room.room_id, user_id, invitee_id, expires_at)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to create invite: {}", e)))?;
+        drop(db);
+
+        info!(
+            room_id = %room.room_id,
+            inviter_id = %user_id,
+            invitee_id = %invitee_id,
+            "Game room invite sent"
+        );
+
+        let event = GameEvent::InviteReceived {
+            invite_id: invite.id.to_string(),
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            game_type: room.game_type.as_str().to_string(),
+            inviter_id: user_id,
+            inviter_username: username.to_string(),
+            is_password_protected: room.is_password_protected,
+            player_count: room.player_count,
+            joined_count: invites::joined_count(&room) as i32,
+            expires_at: invite.expires_at,
+        };
+        self.publish_game_event(event, Audience::user(invitee_id)).await
+    }
+
+    /// Handle accept_invite command: join the invite's room without its password
+    async fn handle_accept_invite(
+        &self,
+        user_id: i64,
+        username: &str,
+        avatar_id: Option<i64>,
+        invite_id: Uuid,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await;
+        let invite = invite_read::get_by_id(&db, invite_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+        drop(db);
+
+        let room = match &invite {
+            Some(invite) if invite.invitee_id == user_id => self.get_room(&invite.room_id).await?,
+            _ => None,
+        };
+        let Some(room) = room.filter(|room| room.status == RoomStatus::Waiting) else {
+            let error = GameEvent::Error {
+                code: "invite_not_available".to_string(),
+                message: "This invitation is no longer available".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        if !self.answer_invite(user_id, username, invite_id, InviteStatus::Accepted, socket_id).await? {
+            return Ok(());
+        }
+
+        self.handle_join_room(user_id, username, avatar_id, &room.room_name, socket_id, None, true).await
+    }
+
+    /// Handle decline_invite command
+    async fn handle_decline_invite(
+        &self,
+        user_id: i64,
+        username: &str,
+        invite_id: Uuid,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        self.answer_invite(user_id, username, invite_id, InviteStatus::Declined, socket_id)
+            .await
+            .map(|_| ())
+    }
+
+    /// Record the invitee's answer and tell the inviter; false (after telling
+    /// the invitee) when the invite was already answered or has expired
+    async fn answer_invite(
+        &self,
+        user_id: i64,
+        username: &str,
+        invite_id: Uuid,
+        status: InviteStatus,
+        socket_id: &str,
+    ) -> Result<bool, EventHandlerError> {
+        let db = self.db.lock().await;
+        let answered = invite_mutations::respond(&db, invite_id, user_id, status.as_str())
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to answer invite: {}", e)))?;
+        drop(db);
+
+        let Some(invite) = answered else {
+            let error = GameEvent::Error {
+                code: "invite_not_available".to_string(),
+                message: "This invitation is no longer available".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(false);
+        };
+
+        let event = GameEvent::InviteAnswered {
+            invite_id: invite.id.to_string(),
+            room_id: invite.room_id.clone(),
+            invitee_id: user_id,
+            invitee_username: username.to_string(),
+            status: status.as_str().to_string(),
+        };
+        self.publish_game_event(event, Audience::user(invite.inviter_id)).await?;
+        Ok(true)
+    }
+
+    /// Expire unanswered invites and tell their inviters
+    pub async fn expire_invites(&self) {
+        let db = self.db.lock().await;
+        let expired = match invite_mutations::expire_due(&db).await {
+            Ok(expired) => expired,
+            Err(e) => {
+                error!("Failed to expire game invites: {}", e);
+                return;
+            }
+        };
+
+        let mut answers = Vec::with_capacity(expired.len());
+        for invite in expired {
+            let invitee_username = match self.profiles.get(&db, invite.invitee_id).await {
+                Ok(profile) => profile.display_name(),
+                Err(_) => String::new(),
+            };
+            answers.push((invite, invitee_username));
+        }
+        drop(db);
+
+        for (invite, invitee_username) in answers {
+            let event = GameEvent::InviteAnswered {
+                invite_id: invite.id.to_string(),
+                room_id: invite.room_id.clone(),
+                invitee_id: invite.invitee_id,
+                invitee_username,
+                status: InviteStatus::Expired.as_str().to_string(),
+            };
+            if let Err(e) = self.publish_game_event(event, Audience::user(invite.inviter_id)).await {
+                warn!(invite_id = %invite.id, error = %e, "Failed to announce expired invite");
+            }
+        }
+    }
+
+    /// Move every waiting room of this region to the drain target (GAME_REGION_DRAIN_TO)
+    pub async fn drain_waiting_rooms(&self) {
+        let Some(target) = GamesConfig::region_drain_target() else {
+            return;
+        };
+
+        let db = self.db.lock().await;
+        let rooms = game_room_read::get_waiting_rooms_in_region(&db, GamesConfig::region()).await;
+        drop(db);
+
+        let rooms = match rooms {
+            Ok(rooms) => rooms,
+            Err(e) => {
+                error!("Failed to load waiting rooms for region drain: {}", e);
+                return;
+            }
+        };
+
+        for room in rooms {
+            if let Err(e) = self.handle_migrate_room(&room.room_id, target, "drain", None).await {
+                warn!(room_id = %room.room_id, error = %e, "Failed to drain room");
+            }
+        }
+    }
+
+    /// Send a game-typed event back to the WebSocket gateway via Kafka
+    ///
+    /// This version includes the game_type prefix for events like room_created, player_left, etc.
+    /// Use this for events that need to be filtered by game type on the frontend.
+    async fn publish_game_event_typed(
+        &self,
+        event: GameEvent,
+        audience: Audience,
+        game_type: Option<&str>,
+    ) -> Result<(), EventHandlerError> {
+        let Some(producer) = &self.producer else {
+            warn!("No Kafka producer available for game events");
+            return Ok(());
+        };
+
+        Self::send_game_event(producer, event, audience, game_type).await
+    }
+
+    /// Wrap a game event in an envelope and publish it to this region's games events topic
+    async fn send_game_event(
+        producer: &EventProducer,
+        event: GameEvent,
+        audience: Audience,
+        game_type: Option<&str>,
+    ) -> Result<(), EventHandlerError> {
+        let base_event_name = event.event_type_name();
+
+        // Construct full event type:
+        // - If event already has game prefix (e.g., "bigger_dice.rolled"), use as-is
+        // - If game_type provided and no prefix, add game prefix (e.g., "bigger_dice.room_created")
+        // - Otherwise, use base name (e.g., "room_created") for backward compatibility
+        let event_type = if base_event_name.contains('.') {
+            // Already has a game prefix like "bigger_dice.rolled" or "tic_tac_toe.moved"
+            format!("games.event.{}", base_event_name)
+        } else if let Some(gt) = game_type {
+            // Add game prefix like "games.event.bigger_dice.room_created"
+            format!("games.event.{}.{}", gt, base_event_name)
+        } else {
+            // Fallback to original behavior (no game prefix)
+            format!("games.event.{}", base_event_name)
+        };
+
+        let envelope = EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type,
+            timestamp: Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: crate::app::games::types::Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience,
+            payload: serde_json::to_value(&event).unwrap_or(Value::Null),
+        };
+
+        let bytes = serde_json::to_vec(&envelope)
+            .map_err(|e| EventHandlerError::Fatal(format!("Failed to serialize game event: {}", e)))?;
+
+        // Room states can outgrow the broker's message limit, the gateway and
+        // this app's consumers reassemble segments
+        producer
+            .send_segmented(topic::region_games_events(), None, &bytes)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to publish game event: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Publish a game participation event to Kafka for the checkout service
+    /// This event is published when a player is selected to play and their balance is deducted
+    async fn publish_game_participation_event(
+        &self,
+        user_id: i64,
+        amount_cents: i64,
+        room_id: &str,
+        room_name: &str,
+        game_type: GameType,
+        username: Option<&str>,
+    ) {
+        let Some(producer) = &self.producer else {
+            warn!("No Kafka producer available for game participation events");
+            return;
+        };
+
+        let event_payload = serde_json::json!({
+            "event_type": "game.participation.deducted",
+            "event_id": Uuid::new_v4().to_string(),
+            "timestamp": Utc::now().to_rfc3339(),
+            "user_id": user_id,
+            "amount_cents": amount_cents,
+            "game_type": game_type.as_str(),
+            "room_id": room_id,
+            "room_name": room_name,
+            "username": username,
+            "description": format!("PAY {} GAME", game_type.as_str().to_uppercase().replace("_", " ")),
+        });
+
+        let bytes = match serde_json::to_vec(&event_payload) {
+            Ok(b) => b,
+            Err(e) => {
+                error!(error = %e, "Failed to serialize game participation event");
+                return;
+            }
+        };
+
+        // Route to the correct topic based on game type
+        let topic_name = match game_type {
+            GameType::BiggerDice => topic::BIGGER_DICE_PARTICIPATION_PAYED,
+            GameType::TicTacToe => topic::TIC_TAC_TOE_PARTICIPATION_PAYED,
+        };
+
+        // Use user_id as partition key to ensure ordering per user
+        let key = user_id.to_string();
+        if let Err(e) = producer.send_raw(topic_name, Some(&key), &bytes).await {
+            error!(
+                error = %e,
+                user_id = %user_id,
+                amount = %amount_cents,
+                room_id = %room_id,
+                topic = %topic_name,
+                "Failed to publish game participation event"
             );
         } else {
             info!(
@@ -593,6 +1611,37 @@ impl GameCommandHandler {
         }
     }
 
+    /// Resolve the settings of a new room, starting from `preset` when given.
+    /// The outer error is a database failure, the inner one an invalid request.
+    async fn resolve_room_config(
+        &self,
+        game_type: &GameType,
+        preset: Option<&str>,
+        settings: RoomSettings,
+    ) -> Result<Result<RoomConfig, RoomConfigError>, EventHandlerError> {
+        let base = match preset {
+            Some(preset_key) => {
+                let db = self.db.lock().await;
+                let preset = room_preset_read::get_by_key(&db, game_type.as_str(), preset_key)
+                    .await
+                    .map_err(|e| EventHandlerError::Retryable(format!("Failed to load room preset: {}", e)))?;
+                match preset {
+                    Some(preset) => RoomSettings {
+                        player_count: Some(preset.player_count),
+                        allow_spectators: Some(preset.allow_spectators),
+                        max_spectators: Some(preset.max_spectators),
+                        turn_timer_seconds: preset.turn_timer_seconds.map(i64::from),
+                        dice_rules: Default::default(),
+                    },
+                    None => return Ok(Err(RoomConfigError::UnknownPreset(preset_key.to_string()))),
+                }
+            }
+            None => RoomSettings::default(),
+        };
+
+        Ok(RoomConfig::resolve(game_type, settings.or(base)))
+    }
+
     /// Handle create_room command
     async fn handle_create_room(
         &self,
@@ -603,27 +1652,120 @@ impl GameCommandHandler {
         room_name: &str,
         socket_id: &str,
         password: Option<&str>,
-        player_count: Option<i32>,
-        allow_spectators: Option<bool>,
+        preset: Option<&str>,
+        settings: RoomSettings,
+        bot_difficulty: Option<&str>,
+        scheduled_start_at: Option<&str>,
     ) -> Result<(), EventHandlerError> {
         let game_type_enum = GameType::from_str(game_type).ok_or_else(|| {
             EventHandlerError::Fatal(format!("Unknown game type: {}", game_type))
         })?;
 
+        // Games can be rolled out (or switched off) per user via feature flags;
+        // a game without a flag stays available
+        let flags = FeatureFlags::new(self.db.lock().await.clone(), None);
+        if !flags.is_enabled_or(&flag::game(game_type), Some(user_id), true).await {
+            let error = GameEvent::Error {
+                code: "game_unavailable".to_string(),
+                message: "This game is not available yet".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        // Requested settings win over the preset; both are checked against the
+        // game's room constraints
+        let config = match self.resolve_room_config(&game_type_enum, preset, settings).await? {
+            Ok(config) => config,
+            Err(e) => {
+                let error = GameEvent::Error {
+                    code: "invalid_room_config".to_string(),
+                    message: e.to_string(),
+                    socket_id: socket_id.to_string(),
+                };
+                self.publish_game_event(error, Audience::user(user_id)).await?;
+                return Ok(());
+            }
+        };
+
+        // Practice rooms seat one human against the bot
+        let bot_difficulty = match bot_difficulty {
+            None => None,
+            Some(requested) => {
+                let difficulty = BotDifficulty::from_str(requested);
+                let rejection = match difficulty {
+                    _ if !self.bots.is_enabled() => {
+                        Some(("bots_unavailable", "Bot opponents are not available".to_string()))
+                    }
+                    None => Some(("invalid_room_config", format!("Unknown bot difficulty: {}", requested))),
+                    Some(_) if config.player_count != 2 => {
+                        Some(("invalid_room_config", "Rooms against a bot are for 2 players".to_string()))
+                    }
+                    Some(_) if password.is_some_and(|p| !p.is_empty()) => {
+                        Some(("invalid_room_config", "Rooms against a bot cannot have a password".to_string()))
+                    }
+                    Some(_) => None,
+                };
+
+                if let Some((code, message)) = rejection {
+                    let error = GameEvent::Error {
+                        code: code.to_string(),
+                        message,
+                        socket_id: socket_id.to_string(),
+                    };
+                    self.publish_game_event(error, Audience::user(user_id)).await?;
+                    return Ok(());
+                }
+
+                difficulty
+            }
+        };
+
+        // Scheduled rooms open later; practice rooms always open right away
+        let scheduled_start_at = match scheduled_start_at {
+            None => None,
+            Some(requested) => {
+                let start_at = DateTime::parse_from_rfc3339(requested)
+                    .map(|at| at.with_timezone(&Utc))
+                    .map_err(|_| "Invalid scheduled_start_at, expected an RFC 3339 time".to_string())
+                    .and_then(|at| {
+                        room_schedule::validate_start(
+                            Utc::now(),
+                            at,
+                            GamesConfig::room_schedule_min_lead_minutes(),
+                            GamesConfig::room_schedule_max_days(),
+                        )
+                        .map(|()| at)
+                        .map_err(|e| e.to_string())
+                    })
+                    .and_then(|at| match bot_difficulty {
+                        Some(_) => Err("Rooms against a bot cannot be scheduled".to_string()),
+                        None => Ok(at),
+                    });
+
+                match start_at {
+                    Ok(at) => Some(at),
+                    Err(message) => {
+                        let error = GameEvent::Error {
+                            code: "invalid_room_config".to_string(),
+                            message,
+                            socket_id: socket_id.to_string(),
+                        };
+                        self.publish_game_event(error, Audience::user(user_id)).await?;
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
         let room_id = Uuid::new_v4().to_string();
 
         // Hash password if provided
-        let password_hash = password.and_then(|p| {
-            if p.is_empty() {
-                None
-            } else {
-                bcrypt::hash(p, bcrypt::DEFAULT_COST).ok()
-            }
-        });
+        let password_hash = password.and_then(room_password::hash);
 
-        // Validate and clamp player_count (2-10)
-        let player_count_val = player_count.unwrap_or(2).clamp(2, 10);
-        let allow_spectators_val = allow_spectators.unwrap_or(true);
+        let player_count_val = config.player_count;
+        let allow_spectators_val = config.allow_spectators;
 
         // Create room in database using stored procedure
         let db = self.db.lock().await;
@@ -641,13 +1783,47 @@ impl GameCommandHandler {
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to create room in database: {}", e)))?;
 
+        game_room_mutations::apply_config(&db, &room_id, config.max_spectators, config.turn_timer_seconds)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to store room config: {}", e)))?;
+
+        if let Some(rules) = &config.dice_rules {
+            game_room_mutations::set_dice_rules(&db, &room_id, rules)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to store dice rules: {}", e)))?;
+        }
+
+        if let Some(difficulty) = bot_difficulty {
+            game_room_mutations::set_bot_opponent(&db, &room_id, difficulty.as_str())
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to store bot opponent: {}", e)))?;
+        }
+
         // Add host to lobby in database (important for persistence)
         game_room_mutations::add_to_lobby(&db, &room_id, user_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to add host to lobby: {}", e)))?;
 
+        // Rooms belong to the region of the instance that created them
+        if GamesConfig::region() != DEFAULT_REGION {
+            game_room_mutations::assign_region(&db, &room_id, GamesConfig::region())
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to assign room region: {}", e)))?;
+        }
+
+        // The host joined the lobby while the room was waiting; from here on it
+        // is held until its start
+        if let Some(start_at) = scheduled_start_at {
+            game_room_mutations::schedule(&db, &room_id, user_id, start_at)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to schedule room: {}", e)))?;
+        }
+
         drop(db);
 
+        // The gateway routes the room's commands by this record (see room_game_types)
+        self.room_game_types.remember(&room_id, game_type).await;
+
         // Build room for cache and events
         let is_password_protected = password_hash.is_some();
         let mut room = GameRoom::new_with_settings(
@@ -659,6 +1835,13 @@ impl GameCommandHandler {
             player_count_val,
             allow_spectators_val,
         );
+        room.max_spectators = config.max_spectators;
+        room.turn_timer_seconds = config.turn_timer_seconds;
+        room.bot_difficulty = bot_difficulty;
+        room.dice_rules = config.dice_rules;
+        if scheduled_start_at.is_some() {
+            room.status = RoomStatus::Scheduled;
+        }
 
         // Add host to lobby (they can be selected to play like any other player)
         room.lobby.push(GamePlayer {
@@ -668,6 +1851,7 @@ impl GameCommandHandler {
             score: 0,
             is_ready: false,
             joined_at: Utc::now(),
+            stats: self.lobby_stats(user_id, &game_type_enum).await,
         });
 
         // Store room in cache
@@ -676,23 +1860,22 @@ impl GameCommandHandler {
             rooms.insert(room_id.clone(), room.clone());
         }
 
-        // Emit room created event (broadcast so all lobby viewers see it)
-        let event = GameEvent::RoomCreated {
-            room_id: room_id.clone(),
-            room_name: room_name.to_string(),
-            game_type: game_type_enum.as_str().to_string(),
-            host_id: user_id,
-            host_username: username.to_string(),
-            is_password_protected,
-            player_count: room.player_count,
-            allow_spectators: room.allow_spectators,
-        };
-
         let gt = game_type_enum.as_str();
-        self.publish_game_event_typed(event, Audience::broadcast(), Some(gt)).await?;
+        if let Some(start_at) = scheduled_start_at {
+            // The lobby hears about a scheduled room when it opens
+            let event = GameEvent::RoomScheduled {
+                room_id: room_id.clone(),
+                room_name: room_name.to_string(),
+                game_type: gt.to_string(),
+                scheduled_start_at: start_at,
+            };
+            self.publish_game_event(event, Audience::user(user_id)).await?;
+        } else {
+            self.announce_room_created(&room, username).await?;
+        }
 
         // Send room state to the host so they have the full state including themselves in lobby
-        let room_state = Self::room_state_event(&room);
+        let room_state = self.room_state_event(&room, user_id).await;
         self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await?;
 
         info!(
@@ -701,9 +1884,34 @@ impl GameCommandHandler {
             host_id = %user_id,
             game_type = %game_type_enum.as_str(),
             is_password_protected = %is_password_protected,
+            vs_bot = %room.is_vs_bot(),
+            scheduled_start_at = ?scheduled_start_at,
             "Game room created and stored in database"
         );
 
+        // The bot joins the lobby like any other player
+        self.drive_bot(&room_id).await;
+
+        Ok(())
+    }
+
+    /// Tell the lobby (and webhooks) about a room that can be joined
+    async fn announce_room_created(&self, room: &GameRoom, host_username: &str) -> Result<(), EventHandlerError> {
+        // Broadcast so all lobby viewers see it
+        let event = GameEvent::RoomCreated {
+            room_id: room.room_id.clone(),
+            room_name: room.room_name.clone(),
+            game_type: room.game_type.as_str().to_string(),
+            host_id: room.host_id,
+            host_username: host_username.to_string(),
+            is_password_protected: room.is_password_protected,
+            player_count: room.player_count,
+            allow_spectators: room.allow_spectators,
+        };
+
+        self.publish_game_event_typed(event, Audience::broadcast(), Some(room.game_type.as_str()))
+            .await?;
+        self.notify_webhooks(webhooks::ROOM_CREATED, room).await;
         Ok(())
     }
 
@@ -716,6 +1924,7 @@ impl GameCommandHandler {
         room_name: &str,
         socket_id: &str,
         password: Option<&str>,
+        invited: bool,
     ) -> Result<(), EventHandlerError> {
         // Get room from cache or database
         let room_opt = self.get_room_by_name(room_name).await?;
@@ -730,6 +1939,16 @@ impl GameCommandHandler {
             return Ok(());
         };
 
+        if room.status == RoomStatus::Scheduled {
+            let error = GameEvent::Error {
+                code: "room_not_open".to_string(),
+                message: "This room has not opened yet".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
         // Check if user is banned (check database for authoritative state)
         let db = self.db.lock().await;
         let is_banned = game_room_read::is_user_banned(&db, &room.room_id, user_id).await;
@@ -745,17 +1964,26 @@ impl GameCommandHandler {
             return Ok(());
         }
 
-        // Check password for protected rooms
-        if room.is_password_protected {
-            let provided_password = password.unwrap_or("");
-            if !room.verify_password(provided_password) {
-                let error = GameEvent::Error {
-                    code: "wrong_password".to_string(),
-                    message: "Incorrect room password".to_string(),
-                    socket_id: socket_id.to_string(),
-                };
-                self.publish_game_event(error, Audience::user(user_id)).await?;
-                return Ok(());
+        // Check password for protected rooms (an accepted invite stands in for it)
+        if room.is_password_protected && !invited {
+            let check = self
+                .check_room_password(
+                    &room.room_id,
+                    &room.room_name,
+                    room.password_hash.as_deref(),
+                    user_id,
+                    socket_id,
+                    password.unwrap_or(""),
+                )
+                .await?;
+
+            match check {
+                PasswordCheck::Denied => return Ok(()),
+                PasswordCheck::Allowed { rehashed } => {
+                    if rehashed.is_some() {
+                        room.password_hash = rehashed;
+                    }
+                }
             }
         }
 
@@ -798,6 +2026,7 @@ impl GameCommandHandler {
             score: 0,
             is_ready: false,
             joined_at: Utc::now(),
+            stats: self.lobby_stats(user_id, &room.game_type).await,
         };
 
         room.lobby.push(player.clone());
@@ -811,17 +2040,17 @@ impl GameCommandHandler {
         let gt = room.game_type.as_str();
 
         // Notify the joining user with full room state
-        let room_state = Self::room_state_event(&room);
+        let room_state = self.room_state_event(&room, user_id).await;
         self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await?;
 
-        // Notify room that someone joined lobby
+        // Tell the host and admin spectator, who pick players from the lobby
         let event = GameEvent::LobbyJoined {
             room_id: room_id.clone(),
             room_name: room_name_str.clone(),
             player,
         };
 
-        self.publish_game_event_typed(event, Audience::room(room_id.clone()), Some(gt)).await?;
+        self.publish_game_event_typed(event, Audience::admins(room_id.clone()), Some(gt)).await?;
 
         info!(
             room_id = %room_id,
@@ -1186,7 +2415,7 @@ impl GameCommandHandler {
         let db = self.db.lock().await;
 
         // Get user info
-        let user = user::get_by_id(&db, user_id)
+        let user = self.profiles.get(&db, user_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to get user: {}", e)))?;
 
@@ -1273,6 +2502,7 @@ impl GameCommandHandler {
                 score: 0,
                 is_ready: false,
                 joined_at: Utc::now(),
+                stats: self.lobby_stats(user_id, &room.game_type).await,
             };
             room.lobby.push(host_player.clone());
 
@@ -1316,7 +2546,7 @@ impl GameCommandHandler {
 
         // Send room state to rejoin user
         let gt = room.game_type.as_str();
-        let room_state = Self::room_state_event(&room);
+        let room_state = self.room_state_event(&room, user_id).await;
 
         // Send room state to the rejoining user
         self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await?;
@@ -1447,6 +2677,102 @@ impl GameCommandHandler {
         Ok(())
     }
 
+    /// Handle report_desync command - a client's state checksum differs from
+    /// the one it was sent
+    ///
+    /// The report is checked against the room as it is now: when it still
+    /// differs, the authoritative room state is resent to the user. Reports
+    /// are counted per game type either way (see `state_checksum`).
+    async fn handle_report_desync(
+        &self,
+        user_id: i64,
+        room_id: &str,
+        client_checksum: &str,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let Some(room) = self.get_room(room_id).await? else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room no longer exists".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        // Only users who receive the room's events can fall out of sync with it
+        if !(room.is_player(user_id) || room.is_spectator(user_id) || room.is_in_lobby(user_id) || room.host_id == user_id) {
+            let error = GameEvent::Error {
+                code: "not_in_room".to_string(),
+                message: "You are not in this room".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        let gt = room.game_type.as_str();
+        let desynced = state_checksum::checksum(&Self::room_state_view(&room)) != client_checksum;
+        state_checksum::record_report(gt, desynced);
+
+        if !desynced {
+            debug!(room_id = %room_id, user_id = %user_id, "Desync report already matches the room");
+            return Ok(());
+        }
+
+        warn!(
+            room_id = %room_id,
+            user_id = %user_id,
+            game_type = %gt,
+            client_checksum = %client_checksum,
+            turn_number = room.turn_number,
+            "Client state out of sync, resending room state"
+        );
+        let room_state = self.room_state_event(&room, user_id).await;
+        self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await
+    }
+
+    /// Handle get_room_state command - the client asks for a full room state,
+    /// usually because a state diff did not apply to the version it holds
+    async fn handle_get_room_state(
+        &self,
+        user_id: i64,
+        room_id: Option<&str>,
+        room_name: Option<&str>,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let room = match (room_id, room_name) {
+            (Some(id), _) => self.get_room(id).await?,
+            (None, Some(name)) => self.get_room_by_name(name).await?,
+            (None, None) => None,
+        };
+        let Some(room) = room else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room no longer exists".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        // Only users who receive the room's events are sent its state
+        if !(room.is_player(user_id) || room.is_spectator(user_id) || room.is_in_lobby(user_id) || room.host_id == user_id) {
+            let error = GameEvent::Error {
+                code: "not_in_room".to_string(),
+                message: "You are not in this room".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        debug!(room_id = %room.room_id, user_id = %user_id, "Sending requested room state");
+        let gt = room.game_type.as_str();
+        let room_state = self.room_state_event(&room, user_id).await;
+        self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await
+    }
+
     /// Handle ready command
     async fn handle_ready(
         &self,
@@ -1511,7 +2837,8 @@ impl GameCommandHandler {
             room.status = RoomStatus::InProgress;
 
             // Start game and get initialized round state
-            let (events, round_state) = bigger_dice::start_game(&mut room);
+            let (mut events, round_state) = bigger_dice::start_game(&mut room);
+            state_checksum::stamp(&mut events, &room);
 
             // Update game start in database with full state
             let first_turn = room.players.first().map(|p| p.user_id);
@@ -1543,101 +2870,342 @@ impl GameCommandHandler {
                 self.publish_game_event_typed(event, Audience::room(room_id.to_string()), Some(gt)).await?;
             }
 
-            // Broadcast room_removed to all users so lobby viewers remove this room from their list
-            // (game has started, no longer available to join)
-            let room_removed_event = GameEvent::RoomRemoved {
-                room_id: room_id.to_string(),
-                room_name: room.room_name.clone(),
-                reason: "game_started".to_string(),
-            };
-            self.publish_game_event_typed(room_removed_event, Audience::broadcast(), Some(gt)).await?;
-        } else {
-            // Update cache with ready state
-            self.update_room(&room).await?;
+            self.open_predictions(&room).await?;
+            self.notify_webhooks(webhooks::GAME_STARTED, &room).await;
+
+            // Broadcast room_removed to all users so lobby viewers remove this room from their list
+            // (game has started, no longer available to join)
+            let room_removed_event = GameEvent::RoomRemoved {
+                room_id: room_id.to_string(),
+                room_name: room.room_name.clone(),
+                reason: "game_started".to_string(),
+            };
+            self.publish_game_event_typed(room_removed_event, Audience::broadcast(), Some(gt)).await?;
+        } else {
+            // Update cache with ready state
+            self.update_room(&room).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle spectate command
+    async fn handle_spectate(
+        &self,
+        user_id: i64,
+        room_id: &str,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let db = self.db.lock().await;
+
+        // Get user info
+        let user = self.profiles.get(&db, user_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to get user: {}", e)))?;
+
+        drop(db);
+
+        // Get room from cache or database
+        let room_opt = self.get_room(room_id).await?;
+
+        let Some(mut room) = room_opt else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room not found".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        // Check if room allows spectators
+        if !room.allow_spectators {
+            let error = GameEvent::Error {
+                code: "spectators_not_allowed".to_string(),
+                message: "This room does not allow spectators".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        // Add spectator to cache
+        if !room.spectators.contains(&user_id) {
+            room.spectators.push(user_id);
+        }
+
+        // Add spectator in database
+        let db = self.db.lock().await;
+        if let Err(e) = game_room_mutations::add_spectator(&db, room_id, user_id).await {
+            warn!(error = %e, "Failed to add spectator in database");
+        }
+        drop(db);
+
+        // Update cache
+        self.update_room(&room).await?;
+
+        let gt = room.game_type.as_str();
+
+        let event = GameEvent::SpectatorJoined {
+            room_id: room_id.to_string(),
+            user_id,
+            username: user.first_name.clone(),
+        };
+
+        // Send room state to spectator
+        let room_state = self.room_state_event(&room, user_id).await;
+
+        self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await?;
+        self.publish_game_event_typed(event, Audience::room(room_id.to_string()), Some(gt)).await?;
+
+        info!(
+            room_id = %room_id,
+            user_id = %user_id,
+            "Spectator joined game room"
+        );
+
+        Ok(())
+    }
+
+    /// Handle predict_winner command - spectator stakes coins on a player
+    async fn handle_predict_winner(
+        &self,
+        user_id: i64,
+        room_id: &str,
+        predicted_winner_id: i64,
+        stake_cents: i64,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let Some(room) = self.get_room(room_id).await? else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room not found".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let checked = if !room.is_spectator(user_id) {
+            Err(PredictionError::NotSpectator)
+        } else {
+            let pools = self.predictions.lock().await;
+            match pools.get(room_id) {
+                Some(pool) => pool.validate(
+                    user_id,
+                    predicted_winner_id,
+                    stake_cents,
+                    GamesConfig::prediction_stake_limits(),
+                    now,
+                ),
+                None => Err(PredictionError::Closed),
+            }
+        };
+
+        if let Err(reason) = checked {
+            if reason == PredictionError::Closed {
+                self.close_expired_predictions(room_id).await?;
+            }
+            let error = GameEvent::Error {
+                code: reason.code().to_string(),
+                message: reason.message(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
+        let params = prediction_mutations::PlacePredictionParams {
+            room_id,
+            game_type: room.game_type.as_str(),
+            user_id,
+            predicted_winner_id,
+            stake_cents,
+        };
+        let db = self.db.lock().await;
+        let placed = prediction_mutations::place(&db, &params)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to place prediction: {}", e)))?;
+        drop(db);
+        if matches!(placed, PlacePredictionOutcome::Placed { .. }) {
+            replica::mark_written(user_id).await;
+        }
+
+        let prediction_id = match placed {
+            PlacePredictionOutcome::Placed { prediction_id, .. } => prediction_id,
+            PlacePredictionOutcome::InsufficientBalance { current_balance } => {
+                let error = GameEvent::Error {
+                    code: "insufficient_balance".to_string(),
+                    message: format!(
+                        "You do not have enough balance. Have {} cents, need {} cents.",
+                        current_balance, stake_cents
+                    ),
+                    socket_id: socket_id.to_string(),
+                };
+                self.publish_game_event(error, Audience::user(user_id)).await?;
+                return Ok(());
+            }
+            PlacePredictionOutcome::AlreadyPlaced => {
+                let reason = PredictionError::AlreadyPredicted;
+                let error = GameEvent::Error {
+                    code: reason.code().to_string(),
+                    message: reason.message(),
+                    socket_id: socket_id.to_string(),
+                };
+                self.publish_game_event(error, Audience::user(user_id)).await?;
+                return Ok(());
+            }
+        };
+
+        // The game may have ended while the stake was escrowed; settlement
+        // reads stakes from the database, so the pool update is only cosmetic
+        let updated = {
+            let mut pools = self.predictions.lock().await;
+            pools.get_mut(room_id).map(|pool| {
+                pool.add(Stake {
+                    prediction_id,
+                    user_id,
+                    predicted_winner_id,
+                    stake_cents,
+                });
+                GameEvent::PredictionPoolUpdated {
+                    room_id: room_id.to_string(),
+                    total_pool_cents: pool.total_cents(),
+                    candidates: pool.candidates(),
+                }
+            })
+        };
+
+        info!(
+            room_id = %room_id,
+            user_id = %user_id,
+            predicted_winner_id = %predicted_winner_id,
+            stake_cents = %stake_cents,
+            "Prediction placed"
+        );
+
+        if let Some(event) = updated {
+            self.publish_game_event(event, Audience::room(room_id)).await?;
         }
 
         Ok(())
     }
 
-    /// Handle spectate command
-    async fn handle_spectate(
-        &self,
-        user_id: i64,
-        room_id: &str,
-        socket_id: &str,
-    ) -> Result<(), EventHandlerError> {
-        let db = self.db.lock().await;
-
-        // Get user info
-        let user = user::get_by_id(&db, user_id)
-            .await
-            .map_err(|e| EventHandlerError::Retryable(format!("Failed to get user: {}", e)))?;
+    /// Open the prediction pool of a game that just started.
+    /// Practice rooms against a bot have no pool.
+    async fn open_predictions(&self, room: &GameRoom) -> Result<(), EventHandlerError> {
+        if room.is_vs_bot() {
+            return Ok(());
+        }
 
-        drop(db);
+        let window = Duration::seconds(GamesConfig::prediction_window_seconds());
+        let pool = PredictionPool::open(&room.room_id, &room.players, Utc::now(), window);
+        let (min_stake_cents, max_stake_cents) = GamesConfig::prediction_stake_limits();
+        let event = GameEvent::PredictionOpened {
+            room_id: room.room_id.clone(),
+            closes_at: pool.closes_at,
+            min_stake_cents,
+            max_stake_cents,
+            candidates: pool.candidates(),
+        };
 
-        // Get room from cache or database
-        let room_opt = self.get_room(room_id).await?;
+        self.predictions.lock().await.insert(room.room_id.clone(), pool);
+        self.publish_game_event(event, Audience::room(room.room_id.clone())).await
+    }
 
-        let Some(mut room) = room_opt else {
-            let error = GameEvent::Error {
-                code: "room_not_found".to_string(),
-                message: "Room not found".to_string(),
-                socket_id: socket_id.to_string(),
-            };
-            self.publish_game_event(error, Audience::user(user_id)).await?;
-            return Ok(());
+    /// Announce that the prediction window of a room elapsed (once)
+    async fn close_expired_predictions(&self, room_id: &str) -> Result<(), EventHandlerError> {
+        let closed = {
+            let mut pools = self.predictions.lock().await;
+            match pools.get_mut(room_id) {
+                Some(pool) if !pool.closed && Utc::now() >= pool.closes_at => {
+                    pool.closed = true;
+                    Some(GameEvent::PredictionClosed {
+                        room_id: room_id.to_string(),
+                        total_pool_cents: pool.total_cents(),
+                        candidates: pool.candidates(),
+                    })
+                }
+                _ => None,
+            }
         };
 
-        // Check if room allows spectators
-        if !room.allow_spectators {
-            let error = GameEvent::Error {
-                code: "spectators_not_allowed".to_string(),
-                message: "This room does not allow spectators".to_string(),
-                socket_id: socket_id.to_string(),
-            };
-            self.publish_game_event(error, Audience::user(user_id)).await?;
-            return Ok(());
+        match closed {
+            Some(event) => self.publish_game_event(event, Audience::room(room_id)).await,
+            None => Ok(()),
         }
+    }
 
-        // Add spectator to cache
-        if !room.spectators.contains(&user_id) {
-            room.spectators.push(user_id);
-        }
+    /// Pay out the escrowed predictions of a finished game. Stakes are read
+    /// from the database so predictions survive a restart mid-game; games
+    /// without a winner (or against a bot) refund every stake.
+    async fn settle_predictions(&self, room: &GameRoom) {
+        self.predictions.lock().await.remove(&room.room_id);
 
-        // Add spectator in database
         let db = self.db.lock().await;
-        if let Err(e) = game_room_mutations::add_spectator(&db, room_id, user_id).await {
-            warn!(error = %e, "Failed to add spectator in database");
-        }
-        drop(db);
-
-        // Update cache
-        self.update_room(&room).await?;
+        let stakes = match prediction_read::escrowed_for_room(&db, &room.room_id).await {
+            Ok(stakes) if !stakes.is_empty() => stakes,
+            Ok(_) => return,
+            Err(e) => {
+                error!(error = %e, room_id = %room.room_id, "Failed to load predictions for settlement");
+                return;
+            }
+        };
 
-        let gt = room.game_type.as_str();
+        let winner_id = room.winner_id.filter(|_| !room.is_vs_bot());
+        let payouts = predictions::settle(&stakes, winner_id);
+        let applied = match prediction_mutations::settle(&db, &room.room_id, &payouts).await {
+            Ok(applied) => applied,
+            Err(e) => {
+                error!(error = %e, room_id = %room.room_id, "Failed to settle predictions");
+                return;
+            }
+        };
+        drop(db);
+        for payout in applied.iter().filter(|p| p.payout_cents > 0) {
+            replica::mark_written(payout.user_id).await;
+        }
 
-        let event = GameEvent::SpectatorJoined {
-            room_id: room_id.to_string(),
-            user_id,
-            username: user.first_name.clone(),
+        let refunded = applied.iter().all(|p| p.outcome == PredictionOutcome::Refunded);
+        let event = GameEvent::PredictionSettled {
+            room_id: room.room_id.clone(),
+            winner_id: winner_id.filter(|_| !refunded),
+            total_pool_cents: stakes.iter().map(|s| s.stake_cents).sum(),
+            payouts: applied,
         };
 
-        // Send room state to spectator
-        let room_state = Self::room_state_event(&room);
+        info!(room_id = %room.room_id, winner_id = ?winner_id, "Predictions settled");
 
-        self.publish_game_event_typed(room_state, Audience::user(user_id), Some(gt)).await?;
-        self.publish_game_event_typed(event, Audience::room(room_id.to_string()), Some(gt)).await?;
+        if let Err(e) = self.publish_game_event(event, Audience::room(room.room_id.clone())).await {
+            warn!(error = %e, room_id = %room.room_id, "Failed to publish prediction settlement");
+        }
+    }
 
-        info!(
-            room_id = %room_id,
-            user_id = %user_id,
-            "Spectator joined game room"
-        );
+    /// Record room lifecycle webhook deliveries; the game_webhooks cron sends them
+    async fn notify_webhooks(&self, event: &str, room: &GameRoom) {
+        let payload = webhooks::payload(event, room, Utc::now());
+        let db = self.db.lock().await;
 
-        Ok(())
+        if let Err(e) = webhook_mutations::enqueue_deliveries(&db, event, &room.room_id, &payload).await {
+            warn!(error = %e, room_id = %room.room_id, event = %event, "Failed to record webhook deliveries");
+        }
     }
 
     /// Handle leave_spectate command
+    /// Whether the room hosts a tournament match (no per-game prize).
+    /// A lookup failure counts as a regular room.
+    async fn is_tournament_match(&self, room_id: &str) -> bool {
+        let db = self.db.lock().await;
+        match tournament_read::is_match_room(&db, room_id).await {
+            Ok(found) => found,
+            Err(e) => {
+                warn!(room_id = %room_id, error = %e, "Failed to check for tournament match");
+                false
+            }
+        }
+    }
+
     async fn handle_leave_spectate(
         &self,
         user_id: i64,
@@ -1647,7 +3215,7 @@ impl GameCommandHandler {
         let db = self.db.lock().await;
 
         // Get user info
-        let user = user::get_by_id(&db, user_id)
+        let user = self.profiles.get(&db, user_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to get user: {}", e)))?;
 
@@ -1738,33 +3306,48 @@ impl GameCommandHandler {
             return Ok(());
         }
 
-        // Game participation fee in cents (configurable via env, default 1000 = 10 coins)
-        let game_fee_cents = GamesConfig::bigger_dice_entry_fee_cents();
+        // Game participation fee in cents (configurable via env, default 1000 = 10 coins);
+        // practice rooms against a bot are free
+        let game_fee_cents = if room.is_vs_bot() {
+            0
+        } else {
+            GamesConfig::bigger_dice_entry_fee_cents()
+        };
 
         // Deduct balance from the target player BEFORE selecting them
-        // This is atomic - checks and deducts in one query
+        // This is atomic - checks, deducts and records the ledger entry in one transaction
         let db = self.db.lock().await;
-        let deduct_result = user_mutations::deduct_balance_if_sufficient(&db, target_user_id, game_fee_cents).await;
+        let deduct_result = ledger_mutations::apply(
+            &db,
+            target_user_id,
+            &[LedgerChange {
+                amount_cents: -game_fee_cents,
+                source: ledger_mutations::SOURCE_GAME_FEE,
+                reference_id: Some(room_id),
+                metadata: serde_json::json!({ "selected_by": user_id }),
+            }],
+        )
+        .await;
         drop(db);
 
         let new_balance = match deduct_result {
-            Ok(balance) => balance,
-            Err(user_mutations::DeductBalanceError::InsufficientBalance { current, required }) => {
+            Ok(ApplyOutcome::Applied { balance_after }) => balance_after,
+            Ok(ApplyOutcome::InsufficientBalance { current_balance }) => {
                 warn!(
                     target_user_id = %target_user_id,
-                    current_balance = %current,
-                    required = %required,
+                    current_balance = %current_balance,
+                    required = %game_fee_cents,
                     "Player has insufficient balance to be selected"
                 );
                 let error = GameEvent::Error {
                     code: "insufficient_balance".to_string(),
-                    message: format!("Player does not have enough balance. Has {} cents, needs {} cents.", current, required),
+                    message: format!("Player does not have enough balance. Has {} cents, needs {} cents.", current_balance, game_fee_cents),
                     socket_id: socket_id.to_string(),
                 };
                 self.publish_game_event(error, Audience::user(user_id)).await?;
                 return Ok(());
             }
-            Err(user_mutations::DeductBalanceError::UserNotFound) => {
+            Ok(ApplyOutcome::UnknownUser) => {
                 warn!(target_user_id = %target_user_id, "Target user not found when deducting balance");
                 let error = GameEvent::Error {
                     code: "user_not_found".to_string(),
@@ -1774,7 +3357,7 @@ impl GameCommandHandler {
                 self.publish_game_event(error, Audience::user(user_id)).await?;
                 return Ok(());
             }
-            Err(user_mutations::DeductBalanceError::DatabaseError(e)) => {
+            Err(e) => {
                 error!(error = %e, "Database error when deducting balance");
                 return Err(EventHandlerError::Retryable(format!("Database error: {}", e)));
             }
@@ -1786,6 +3369,7 @@ impl GameCommandHandler {
             new_balance = %new_balance,
             "Deducted game participation fee from player"
         );
+        replica::mark_written(target_user_id).await;
 
         // Select player for game in database (adds to selected_players, keeps in lobby)
         let db = self.db.lock().await;
@@ -1800,12 +3384,19 @@ impl GameCommandHandler {
         if !success {
             // Refund the player since selection failed
             let db = self.db.lock().await;
-            if let Err(e) = user_mutations::add_balance(&db, target_user_id, game_fee_cents).await {
+            let refund = LedgerChange {
+                amount_cents: game_fee_cents,
+                source: ledger_mutations::SOURCE_GAME_FEE_REFUND,
+                reference_id: Some(room_id),
+                metadata: serde_json::json!({ "selected_by": user_id }),
+            };
+            if let Err(e) = ledger_mutations::apply(&db, target_user_id, &[refund]).await {
                 error!(error = %e, target_user_id = %target_user_id, "Failed to refund player after selection failure");
             } else {
                 info!(target_user_id = %target_user_id, refunded_amount = %game_fee_cents, "Refunded player after selection failure");
             }
             drop(db);
+            replica::mark_written(target_user_id).await;
 
             let error = GameEvent::Error {
                 code: "player_not_in_lobby".to_string(),
@@ -1828,14 +3419,16 @@ impl GameCommandHandler {
         let gt = room.game_type.as_str();
 
         // Publish game participation event for checkout service
-        self.publish_game_participation_event(
-            target_user_id,
-            game_fee_cents,
-            &room_id_str,
-            &room.room_name,
-            room.game_type.clone(),
-            player.as_ref().map(|p| p.username.as_str()),
-        ).await;
+        if game_fee_cents > 0 {
+            self.publish_game_participation_event(
+                target_user_id,
+                game_fee_cents,
+                &room_id_str,
+                &room.room_name,
+                room.game_type.clone(),
+                player.as_ref().map(|p| p.username.as_str()),
+            ).await;
+        }
 
         // Update cache
         self.update_room(&room).await?;
@@ -1914,13 +3507,12 @@ impl GameCommandHandler {
                 self.publish_game_event_typed(starting_event.clone(), Audience::user(*spectator_id), Some(gt)).await?;
             }
 
-            // Send updated room state to remaining players
-            let room_state = Self::room_state_event(&room);
+            // Send the room's changes to remaining players
             for selected_id in &room.selected_players {
-                self.publish_game_event_typed(room_state.clone(), Audience::user(*selected_id), Some(gt)).await?;
+                self.publish_room_state_update(&room, *selected_id).await?;
             }
             for spectator_id in &room.spectators {
-                self.publish_game_event_typed(room_state.clone(), Audience::user(*spectator_id), Some(gt)).await?;
+                self.publish_room_state_update(&room, *spectator_id).await?;
             }
         }
 
@@ -1992,6 +3584,7 @@ impl GameCommandHandler {
             score: 0,
             is_ready: false,
             joined_at: spectator.joined_at,
+            stats: None,
         };
 
         // Add to players list
@@ -2381,13 +3974,13 @@ impl GameCommandHandler {
         // Update cache
         self.update_room(&room).await?;
 
-        // Notify room that player was unbanned
+        // Only the host and admin spectator keep the ban list
         let event = GameEvent::PlayerUnbanned {
             room_id: room_id_str.clone(),
             user_id: target_user_id,
             username: username.clone(),
         };
-        self.publish_game_event_typed(event, Audience::room(room_id_str.clone()), Some(gt)).await?;
+        self.publish_game_event_typed(event, Audience::admins(room_id_str.clone()), Some(gt)).await?;
 
         info!(
             room_id = %room_id_str,
@@ -2675,7 +4268,8 @@ impl GameCommandHandler {
         }
 
         // Process the roll
-        let (events, game_ended) = bigger_dice::process_roll(&mut room, round_state, user_id);
+        let (mut events, game_ended) = bigger_dice::process_roll(&mut room, round_state, user_id);
+        state_checksum::stamp(&mut events, &room);
 
         // Get round number for saving round results (before dropping round_states)
         let current_round_number = round_state.round_number;
@@ -2812,7 +4406,10 @@ impl GameCommandHandler {
 
             // Award prize to winner (configurable percentage of total pool)
             // Winner gets BIGGER_DICE_WINNING_PERCENTAGE% of (total_players * BIGGER_DICE_ENTRY_FEE_CENTS)
-            if let Some(winner_id) = room.winner_id {
+            // Practice rooms against a bot have no pool; tournament matches
+            // are paid from the tournament's prize pool instead
+            let tournament_match = self.is_tournament_match(&room_id_str).await;
+            if let Some(winner_id) = room.winner_id.filter(|_| !room.is_vs_bot() && !tournament_match) {
                 let total_players = room.players.len();
                 let entry_fee_cents = GamesConfig::bigger_dice_entry_fee_cents();
                 let total_pool_cents = (total_players as i64) * entry_fee_cents;
@@ -2828,8 +4425,14 @@ impl GameCommandHandler {
 
                 // Add prize to winner's balance
                 let db = self.db.lock().await;
-                match user_mutations::add_balance(&db, winner_id, prize_cents).await {
-                    Ok(()) => {
+                let prize = LedgerChange {
+                    amount_cents: prize_cents,
+                    source: ledger_mutations::SOURCE_GAME_PRIZE,
+                    reference_id: Some(room_id_str.as_str()),
+                    metadata: serde_json::json!({ "total_players": total_players }),
+                };
+                match ledger_mutations::apply(&db, winner_id, &[prize]).await {
+                    Ok(ApplyOutcome::Applied { .. }) => {
                         info!(
                             winner_id = %winner_id,
                             prize_cents = %prize_cents,
@@ -2838,6 +4441,7 @@ impl GameCommandHandler {
                         );
 
                         drop(db);
+                        replica::mark_written(winner_id).await;
 
                         // Publish prize win event to Kafka for checkout transaction
                         self.publish_game_prize_win_event(
@@ -2850,6 +4454,15 @@ impl GameCommandHandler {
                             total_players,
                         ).await;
                     }
+                    Ok(outcome) => {
+                        drop(db);
+                        error!(
+                            outcome = ?outcome,
+                            winner_id = %winner_id,
+                            prize_cents = %prize_cents,
+                            "Failed to add prize to winner's balance"
+                        );
+                    }
                     Err(e) => {
                         drop(db);
                         error!(
@@ -2862,6 +4475,9 @@ impl GameCommandHandler {
                 }
             }
 
+            self.settle_predictions(&room).await;
+            self.notify_webhooks(webhooks::GAME_OVER, &room).await;
+
             // Update PostgreSQL: mark as finished then delete
             let db = self.db.lock().await;
             if let Err(e) = game_room_mutations::end_game(&db, room_id, room.winner_id).await {
@@ -2964,9 +4580,10 @@ impl GameCommandHandler {
             .or_insert_with(|| {
                 // If state doesn't exist, reinitialize from room
                 if room.players.len() == 2 {
-                    TicTacToeMatchState::initialize(
+                    TicTacToeMatchState::initialize_with_timer(
                         room.players[0].user_id,
                         room.players[1].user_id,
+                        room.turn_timer_seconds.unwrap_or(tic_tac_toe::TURN_TIMER_SECONDS),
                     )
                 } else {
                     TicTacToeMatchState::default()
@@ -2974,12 +4591,13 @@ impl GameCommandHandler {
             });
 
         // Process the move
-        let (events, game_ended, match_ended) = tic_tac_toe::process_move(
+        let (mut events, game_ended, match_ended) = tic_tac_toe::process_move(
             &mut room,
             match_state,
             user_id,
             position,
         );
+        state_checksum::stamp(&mut events, &room);
 
         let room_id_str = room_id.to_string();
         let gt = room.game_type.as_str();
@@ -2993,7 +4611,10 @@ impl GameCommandHandler {
 
         // If match ended, handle prize and cleanup
         if match_ended {
-            if let Some(winner_id) = room.winner_id {
+            // Practice rooms against a bot have no pool; tournament matches
+            // are paid from the tournament's prize pool instead
+            let tournament_match = self.is_tournament_match(&room_id_str).await;
+            if let Some(winner_id) = room.winner_id.filter(|_| !room.is_vs_bot() && !tournament_match) {
                 // Award prize to winner
                 let total_pool = tic_tac_toe::ENTRY_FEE_CENTS * 2;
                 let prize = (total_pool * tic_tac_toe::WINNING_PERCENTAGE) / 100;
@@ -3038,6 +4659,9 @@ impl GameCommandHandler {
                 }
             }
 
+            self.settle_predictions(&room).await;
+            self.notify_webhooks(webhooks::GAME_OVER, &room).await;
+
             // Update database to finished
             let db = self.db.lock().await;
             if let Err(e) = game_room_mutations::end_game(&db, room_id, room.winner_id).await {
@@ -3141,11 +4765,44 @@ impl GameCommandHandler {
         }
     }
 
-    /// Handle list_rooms command - return list of available rooms for a game type
+    /// Let the bot of a vs_bot room take its next step (join, ready up, move), if any
+    async fn drive_bot(&self, room_id: &str) {
+        let Some(bot_id) = self.bots.bot_user_id() else {
+            return;
+        };
+
+        let room = match self.get_room(room_id).await {
+            Ok(Some(room)) if room.is_vs_bot() => room,
+            Ok(_) => return,
+            Err(e) => {
+                warn!(room_id = %room_id, error = ?e, "Failed to load room for bot");
+                return;
+            }
+        };
+
+        let action = match room.game_type {
+            GameType::BiggerDice => {
+                let round_states = self.round_states.lock().await;
+                bots::next_action(&room, bot_id, round_states.get(room_id).map(BotGameState::BiggerDice))
+            }
+            GameType::TicTacToe => {
+                let match_states = self.tic_tac_toe_states.lock().await;
+                bots::next_action(&room, bot_id, match_states.get(room_id).map(BotGameState::TicTacToe))
+            }
+        };
+
+        if let Some(action) = action {
+            self.bots.dispatch(&room, action);
+        }
+    }
+
+    /// Handle list_rooms command - return one page of available rooms for a game type
     async fn handle_list_rooms(
         &self,
         user_id: i64,
         game_type: &str,
+        cursor: Option<&Cursor>,
+        limit: i64,
         socket_id: &str,
     ) -> Result<(), EventHandlerError> {
         info!(
@@ -3154,22 +4811,37 @@ impl GameCommandHandler {
             "Listing rooms"
         );
 
-        // Get active rooms from database (waiting + in-progress)
-        let db = self.db.lock().await;
-        let records = game_room_read::get_active_rooms(&db, game_type)
+        // Active rooms (waiting + in-progress) from the Redis projection, or
+        // from the database when there is none
+        let db = self.db.lock().await.clone();
+        let projected = self
+            .room_list
+            .rooms(&db, game_type)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
-        drop(db);
+        let records = match projected {
+            Some(rooms) => room_list::page_in_query_order(rooms, cursor, limit),
+            None => game_room_read::get_active_rooms_page(&db, game_type, cursor, limit)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?,
+        };
+
+        let page = paginate(records, cursor, limit as usize, |record| {
+            (record.created_at, record.id)
+        });
 
         // Convert to room list format expected by frontend
-        let rooms: Vec<serde_json::Value> = records
+        let rooms: Vec<serde_json::Value> = page
+            .items
             .iter()
-            .filter_map(|record| Self::room_list_item_for_user(record, user_id))
+            .filter_map(|record| room_list::list_item_for_user(record, user_id))
             .collect();
 
         // Publish room list event to the requesting user (unprefixed - generic event)
         let event = GameEvent::RoomList {
             rooms: rooms.clone(),
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
             socket_id: socket_id.to_string(),
         };
         self.publish_game_event(event, Audience::user(user_id)).await?;
@@ -3183,6 +4855,58 @@ impl GameCommandHandler {
         Ok(())
     }
 
+    /// Handle search_rooms command - A filtered page of the lobby room list.
+    /// Searches always read Postgres; the Redis projection only serves the
+    /// unfiltered list.
+    async fn handle_search_rooms(
+        &self,
+        user_id: i64,
+        search: &RoomSearch,
+        sort: RoomSort,
+        cursor: Option<&Cursor>,
+        limit: i64,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            user_id = %user_id,
+            game_type = ?search.game_type,
+            sort = sort.as_str(),
+            "Searching rooms"
+        );
+
+        let db = self.db.lock().await.clone();
+        let records = game_room_read::search_active_rooms_page(&db, search, sort, cursor, limit)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+
+        let page = paginate(records, cursor, limit as usize, |record| {
+            (record.created_at, record.id)
+        });
+
+        let rooms: Vec<serde_json::Value> = page
+            .items
+            .iter()
+            .filter_map(|record| room_list::list_item_for_user(record, user_id))
+            .collect();
+        let room_count = rooms.len();
+
+        let event = GameEvent::RoomList {
+            rooms,
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
+            socket_id: socket_id.to_string(),
+        };
+        self.publish_game_event(event, Audience::user(user_id)).await?;
+
+        info!(
+            user_id = %user_id,
+            room_count = %room_count,
+            "Room search results sent"
+        );
+
+        Ok(())
+    }
+
     // ========== Enhanced Game Room Handlers ==========
 
     /// Get the MongoDB chat client
@@ -3199,7 +4923,7 @@ impl GameCommandHandler {
         room_id: &str,
         channel_str: &str,
         content: &str,
-        _socket_id: &str,
+        socket_id: &str,
     ) -> Result<(), EventHandlerError> {
         // Parse channel
         let channel: ChatChannel = channel_str.parse().map_err(|e: String| {
@@ -3220,6 +4944,21 @@ impl GameCommandHandler {
             return Err(EventHandlerError::Fatal("Cannot chat in this channel".to_string()));
         }
 
+        // Muted senders are refused before anything is stored or published
+        if let Some(mute) = room.chat_mute(user_id, &types_channel, Utc::now()) {
+            let message = match mute.expires_at {
+                Some(expires_at) => format!("You are muted in this channel until {}", expires_at.to_rfc3339()),
+                None => "You are muted in this channel".to_string(),
+            };
+            let error = GameEvent::Error {
+                code: "chat_muted".to_string(),
+                message,
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+
         // Save message to MongoDB
         if let Some(chat_client) = self.get_chat_client() {
             let _ = chat_client.save_message(
@@ -3302,72 +5041,285 @@ impl GameCommandHandler {
         target_user_id: i64,
         socket_id: &str,
     ) -> Result<(), EventHandlerError> {
-        // Save mute to database
-        let db = self.db.lock().await;
-        mute_mutations::mute_user(&db, user_id, target_user_id, Some(room_id))
-            .await
-            .map_err(|e| EventHandlerError::Retryable(format!("Failed to mute user: {}", e)))?;
-        drop(db);
+        // Save mute to database
+        let db = self.db.lock().await;
+        mute_mutations::mute_user(&db, user_id, target_user_id, Some(room_id))
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to mute user: {}", e)))?;
+        drop(db);
+
+        // Get target username for the event
+        let db = self.db.lock().await;
+        let target_username = self
+            .profiles
+            .get(&db, target_user_id)
+            .await
+            .ok()
+            .map(|profile| profile.display_name())
+            .unwrap_or_else(|| format!("User #{}", target_user_id));
+        drop(db);
+
+        // Send confirmation event to the user who muted
+        let event = GameEvent::UserMuted {
+            room_id: room_id.to_string(),
+            target_user_id,
+            target_username,
+            socket_id: socket_id.to_string(),
+        };
+
+        self.publish_game_event(event, Audience::user(user_id)).await?;
+
+        Ok(())
+    }
+
+    /// Handle unmute_user command - Unmute a previously muted user
+    async fn handle_unmute_user(
+        &self,
+        user_id: i64,
+        room_id: &str,
+        target_user_id: i64,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        // Remove mute from database
+        let db = self.db.lock().await;
+        mute_mutations::unmute_user(&db, user_id, target_user_id, Some(room_id))
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to unmute user: {}", e)))?;
+        drop(db);
+
+        // Get target username for the event
+        let db = self.db.lock().await;
+        let target_username = self
+            .profiles
+            .get(&db, target_user_id)
+            .await
+            .ok()
+            .map(|profile| profile.display_name())
+            .unwrap_or_else(|| format!("User #{}", target_user_id));
+        drop(db);
+
+        // Send confirmation event to the user who unmuted
+        let event = GameEvent::UserUnmuted {
+            room_id: room_id.to_string(),
+            target_user_id,
+            target_username,
+            socket_id: socket_id.to_string(),
+        };
+
+        self.publish_game_event(event, Audience::user(user_id)).await?;
+
+        Ok(())
+    }
+
+    /// Report a rejected chat mute command to the moderator
+    async fn reject_chat_mute(
+        &self,
+        user_id: i64,
+        error: ChatMuteError,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let event = GameEvent::Error {
+            code: error.code().to_string(),
+            message: error.to_string(),
+            socket_id: socket_id.to_string(),
+        };
+        self.publish_game_event(event, Audience::user(user_id)).await
+    }
+
+    /// Handle chat_mute command - host or admin spectator mutes a member in a
+    /// chat channel (every channel without one)
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_chat_mute(
+        &self,
+        user_id: i64,
+        room_id: &str,
+        target_user_id: i64,
+        channel: Option<&str>,
+        duration_seconds: Option<i64>,
+        socket_id: &str,
+    ) -> Result<(), EventHandlerError> {
+        let Some(mut room) = self.get_room(room_id).await? else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room not found".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        let checked = chat_mutes::parse_channel(channel).and_then(|channel| {
+            chat_mutes::check_moderation(&room, user_id, target_user_id)?;
+            Ok((channel, chat_mutes::expires_at(duration_seconds, Utc::now())?))
+        });
+        let (channel, expires_at) = match checked {
+            Ok(checked) => checked,
+            Err(e) => return self.reject_chat_mute(user_id, e, socket_id).await,
+        };
 
-        // Get target username for the event
         let db = self.db.lock().await;
-        let target_username = user::get_by_id(&db, target_user_id)
+        chat_mute_mutations::upsert(
+            &db,
+            room_id,
+            target_user_id,
+            &chat_mutes::stored_channel(channel.as_ref()),
+            user_id,
+            expires_at,
+        )
+        .await
+        .map_err(|e| EventHandlerError::Retryable(format!("Failed to mute chat: {}", e)))?;
+        let username = self
+            .profiles
+            .get(&db, target_user_id)
             .await
             .ok()
-            .map(|u| format!("{} {}", u.first_name, u.last_name))
+            .map(|profile| profile.display_name())
             .unwrap_or_else(|| format!("User #{}", target_user_id));
         drop(db);
 
-        // Send confirmation event to the user who muted
-        let event = GameEvent::UserMuted {
+        room.set_chat_mute(ChatMute {
+            user_id: target_user_id,
+            channel: channel.clone(),
+            muted_by: user_id,
+            expires_at,
+        });
+        self.update_room(&room).await?;
+
+        let event = GameEvent::ChatMuted {
             room_id: room_id.to_string(),
-            target_user_id,
-            target_username,
-            socket_id: socket_id.to_string(),
+            user_id: target_user_id,
+            username,
+            channel: channel.clone(),
+            muted_by: user_id,
+            expires_at,
         };
+        self.publish_game_event(event, Audience::room(room_id)).await?;
 
-        self.publish_game_event(event, Audience::user(user_id)).await?;
+        info!(
+            room_id = %room_id,
+            moderator_id = %user_id,
+            muted_user_id = %target_user_id,
+            channel = ?channel,
+            expires_at = ?expires_at,
+            "Chat mute set"
+        );
 
         Ok(())
     }
 
-    /// Handle unmute_user command - Unmute a previously muted user
-    async fn handle_unmute_user(
+    /// Handle chat_unmute command - host or admin spectator lifts a chat mute
+    async fn handle_chat_unmute(
         &self,
         user_id: i64,
         room_id: &str,
         target_user_id: i64,
+        channel: Option<&str>,
         socket_id: &str,
     ) -> Result<(), EventHandlerError> {
-        // Remove mute from database
-        let db = self.db.lock().await;
-        mute_mutations::unmute_user(&db, user_id, target_user_id, Some(room_id))
-            .await
-            .map_err(|e| EventHandlerError::Retryable(format!("Failed to unmute user: {}", e)))?;
-        drop(db);
+        let Some(mut room) = self.get_room(room_id).await? else {
+            let error = GameEvent::Error {
+                code: "room_not_found".to_string(),
+                message: "Room not found".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        };
+
+        // Members who left can still be unmuted
+        let checked = chat_mutes::parse_channel(channel).and_then(|channel| {
+            match chat_mutes::check_moderation(&room, user_id, target_user_id) {
+                Ok(()) | Err(ChatMuteError::NotMember) => Ok(channel),
+                Err(e) => Err(e),
+            }
+        });
+        let channel = match checked {
+            Ok(channel) => channel,
+            Err(e) => return self.reject_chat_mute(user_id, e, socket_id).await,
+        };
 
-        // Get target username for the event
         let db = self.db.lock().await;
-        let target_username = user::get_by_id(&db, target_user_id)
+        let removed = chat_mute_mutations::delete(
+            &db,
+            room_id,
+            target_user_id,
+            &chat_mutes::stored_channel(channel.as_ref()),
+        )
+        .await
+        .map_err(|e| EventHandlerError::Retryable(format!("Failed to unmute chat: {}", e)))?;
+        let username = self
+            .profiles
+            .get(&db, target_user_id)
             .await
             .ok()
-            .map(|u| format!("{} {}", u.first_name, u.last_name))
+            .map(|profile| profile.display_name())
             .unwrap_or_else(|| format!("User #{}", target_user_id));
         drop(db);
 
-        // Send confirmation event to the user who unmuted
-        let event = GameEvent::UserUnmuted {
+        let was_cached = room.remove_chat_mute(target_user_id, channel.as_ref()).is_some();
+        if !removed && !was_cached {
+            let error = GameEvent::Error {
+                code: "not_muted".to_string(),
+                message: "This user is not muted in this channel".to_string(),
+                socket_id: socket_id.to_string(),
+            };
+            self.publish_game_event(error, Audience::user(user_id)).await?;
+            return Ok(());
+        }
+        self.update_room(&room).await?;
+
+        let event = GameEvent::ChatUnmuted {
             room_id: room_id.to_string(),
-            target_user_id,
-            target_username,
-            socket_id: socket_id.to_string(),
+            user_id: target_user_id,
+            username,
+            channel,
+            expired: false,
         };
-
-        self.publish_game_event(event, Audience::user(user_id)).await?;
+        self.publish_game_event(event, Audience::room(room_id)).await?;
 
         Ok(())
     }
 
+    /// End chat mutes that ran out and tell their rooms
+    pub async fn expire_chat_mutes(&self) {
+        let db = self.db.lock().await;
+        let expired = match chat_mute_mutations::delete_expired(&db).await {
+            Ok(expired) => expired,
+            Err(e) => {
+                error!("Failed to expire chat mutes: {}", e);
+                return;
+            }
+        };
+
+        let mut ended = Vec::with_capacity(expired.len());
+        for mute in expired {
+            let username = match self.profiles.get(&db, mute.user_id).await {
+                Ok(profile) => profile.display_name(),
+                Err(_) => format!("User #{}", mute.user_id),
+            };
+            ended.push((mute, username));
+        }
+        drop(db);
+
+        for (mute, username) in ended {
+            let channel = chat_mutes::channel_from_stored(&mute.channel);
+            if let Some(room) = self.rooms.lock().await.get_mut(&mute.room_id) {
+                room.remove_chat_mute(mute.user_id, channel.as_ref());
+            }
+
+            let event = GameEvent::ChatUnmuted {
+                room_id: mute.room_id.clone(),
+                user_id: mute.user_id,
+                username,
+                channel,
+                expired: true,
+            };
+            if let Err(e) = self.publish_game_event(event, Audience::room(mute.room_id.clone())).await {
+                warn!(room_id = %mute.room_id, error = %e, "Failed to announce expired chat mute");
+            }
+        }
+    }
+
     /// Handle deselect_player command - Admin deselects a player
     async fn handle_deselect_player(
         &self,
@@ -3517,17 +5469,19 @@ impl GameCommandHandler {
 
         // Check password if protected
         if record.is_password_protected {
-            let provided_password = password.unwrap_or("");
-            if let Some(hash) = &record.password_hash {
-                if !bcrypt::verify(provided_password, hash).unwrap_or(false) {
-                    let error_event = GameEvent::Error {
-                        code: "wrong_password".to_string(),
-                        message: "Incorrect room password".to_string(),
-                        socket_id: socket_id.to_string(),
-                    };
-                    self.publish_game_event(error_event, Audience::user(user_id)).await?;
-                    return Ok(());
-                }
+            let check = self
+                .check_room_password(
+                    room_id,
+                    room_name,
+                    record.password_hash.as_deref(),
+                    user_id,
+                    socket_id,
+                    password.unwrap_or(""),
+                )
+                .await?;
+
+            if let PasswordCheck::Denied = check {
+                return Ok(());
             }
         }
 
@@ -3542,23 +5496,63 @@ impl GameCommandHandler {
             return Ok(());
         }
 
-        // Check spectator capacity
+        // Check spectator capacity; queued users go first when a seat frees up
         let db = self.db.lock().await;
         let can_join = game_room_read::can_join_as_spectator(&db, room_id)
             .await
             .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
         drop(db);
 
-        if !can_join {
+        let (queued, _) = self.spectator_waitlist.entries(room_id).await;
+        let queued_ahead = queued.first().is_some_and(|entry| entry.user_id != user_id);
+
+        if !can_join || queued_ahead {
+            let entry = WaitlistEntry::new(user_id, username, avatar_id, socket_id);
+            let (code, message) = match self.spectator_waitlist.join(room_id, &entry).await {
+                Some(WaitlistJoin::Queued { position, size }) => {
+                    let event = GameEvent::SpectatorWaitlistPosition {
+                        room_id: room_id.to_string(),
+                        room_name: room_name.to_string(),
+                        position,
+                        waitlist_size: size,
+                        expires_at: entry.expires_at,
+                        socket_id: socket_id.to_string(),
+                    };
+                    self.publish_game_event(event, Audience::user(user_id)).await?;
+                    info!(room_id = %room_id, user_id = %user_id, position = position, "Spectator queued for a full room");
+                    return Ok(());
+                }
+                Some(WaitlistJoin::Full) => ("spectator_waitlist_full", "Spectator waiting list is full"),
+                None => ("spectator_capacity_full", "Spectator capacity is full"),
+            };
+
             let error_event = GameEvent::Error {
-                code: "spectator_capacity_full".to_string(),
-                message: "Spectator capacity is full".to_string(),
+                code: code.to_string(),
+                message: message.to_string(),
                 socket_id: socket_id.to_string(),
             };
             self.publish_game_event(error_event, Audience::user(user_id)).await?;
             return Ok(());
         }
 
+        self.admit_spectator(room_id, user_id, username, avatar_id).await?;
+        if !queued.is_empty() {
+            // The user was first in line
+            self.spectator_waitlist.remove(room_id, user_id).await;
+            self.publish_waitlist_positions(room_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a user to a room's spectators and announce it
+    async fn admit_spectator(
+        &self,
+        room_id: &str,
+        user_id: i64,
+        username: &str,
+        avatar_id: Option<i64>,
+    ) -> Result<(), EventHandlerError> {
         // Add spectator to database
         let db = self.db.lock().await;
         game_room_mutations::add_spectator_with_data(&db, room_id, user_id)
@@ -3593,7 +5587,7 @@ impl GameCommandHandler {
         self.publish_game_event_typed(event, Audience::room(room_id), Some(gt)).await?;
 
         // Send room state to the new spectator
-        let state_event = Self::room_state_event(&room);
+        let state_event = self.room_state_event(&room, user_id).await;
         self.publish_game_event_typed(state_event, Audience::user(user_id), Some(gt)).await?;
 
         info!(
@@ -3606,6 +5600,80 @@ impl GameCommandHandler {
         Ok(())
     }
 
+    /// Handle promote_spectators - fill free spectator seats from the waitlist.
+    /// Issued by the spectator waitlist handler after a spectator left.
+    async fn handle_promote_spectators(&self, room_id: &str) -> Result<(), EventHandlerError> {
+        let Some(room) = self.get_room(room_id).await? else {
+            self.spectator_waitlist.clear(room_id).await;
+            return Ok(());
+        };
+
+        loop {
+            let db = self.db.lock().await;
+            let can_join = game_room_read::can_join_as_spectator(&db, room_id)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?;
+            drop(db);
+
+            if !can_join {
+                break;
+            }
+            let Some(entry) = self.spectator_waitlist.pop_next(room_id).await else {
+                break;
+            };
+
+            // Banned while waiting, or already in the room another way
+            if room.is_banned(entry.user_id)
+                || room.is_spectator(entry.user_id)
+                || room.is_player(entry.user_id)
+                || room.is_in_lobby(entry.user_id)
+            {
+                continue;
+            }
+
+            self.admit_spectator(room_id, entry.user_id, &entry.username, entry.avatar_id)
+                .await?;
+            info!(room_id = %room_id, user_id = %entry.user_id, "Promoted spectator from the waitlist");
+        }
+
+        self.publish_waitlist_positions(room_id).await
+    }
+
+    /// Tell every queued user their current place; users whose place expired
+    /// are told it is gone
+    async fn publish_waitlist_positions(&self, room_id: &str) -> Result<(), EventHandlerError> {
+        let (queued, expired) = self.spectator_waitlist.entries(room_id).await;
+        let room_name = self
+            .get_room(room_id)
+            .await?
+            .map(|room| room.room_name)
+            .unwrap_or_default();
+
+        for entry in expired {
+            let event = GameEvent::Error {
+                code: "spectator_waitlist_expired".to_string(),
+                message: "Your place in the spectator waiting list expired".to_string(),
+                socket_id: entry.socket_id,
+            };
+            self.publish_game_event(event, Audience::user(entry.user_id)).await?;
+        }
+
+        let waitlist_size = queued.len() as u32;
+        for (index, entry) in queued.into_iter().enumerate() {
+            let event = GameEvent::SpectatorWaitlistPosition {
+                room_id: room_id.to_string(),
+                room_name: room_name.clone(),
+                position: index as u32 + 1,
+                waitlist_size,
+                expires_at: entry.expires_at,
+                socket_id: entry.socket_id,
+            };
+            self.publish_game_event(event, Audience::user(entry.user_id)).await?;
+        }
+
+        Ok(())
+    }
+
     /// Handle become_spectator command - Admin/player moves themselves from lobby to spectators
     async fn handle_become_spectator(
         &self,
@@ -3690,7 +5758,7 @@ impl GameCommandHandler {
         self.publish_game_event_typed(joined_event, Audience::room(room_id), Some(gt)).await?;
 
         // Send updated room state to the user
-        let state_event = Self::room_state_event(&room);
+        let state_event = self.room_state_event(&room, user_id).await;
         self.publish_game_event_typed(state_event, Audience::user(user_id), Some(gt)).await?;
 
         info!(
@@ -3744,6 +5812,8 @@ impl GameCommandHandler {
             .map_err(|e| EventHandlerError::Retryable(format!("Failed to add to lobby: {}", e)))?;
         drop(db);
 
+        let stats = self.lobby_stats(user_id, &room.game_type).await;
+
         // Update cache
         room.remove_spectator(user_id);
         room.lobby.push(GamePlayer {
@@ -3753,6 +5823,7 @@ impl GameCommandHandler {
             score: 0,
             is_ready: false,
             joined_at: Utc::now(),
+            stats: stats.clone(),
         });
         self.update_room(&room).await?;
 
@@ -3764,6 +5835,7 @@ impl GameCommandHandler {
             score: 0,
             is_ready: false,
             joined_at: Utc::now(),
+            stats,
         };
 
         let gt = room.game_type.as_str();
@@ -3781,10 +5853,10 @@ impl GameCommandHandler {
             room_name: room.room_name.clone(),
             player: player.clone(),
         };
-        self.publish_game_event_typed(joined_event, Audience::room(room_id), Some(gt)).await?;
+        self.publish_game_event_typed(joined_event, Audience::admins(room_id), Some(gt)).await?;
 
         // Send updated room state to the user
-        let state_event = Self::room_state_event(&room);
+        let state_event = self.room_state_event(&room, user_id).await;
         self.publish_game_event_typed(state_event, Audience::user(user_id), Some(gt)).await?;
 
         info!(
@@ -3886,6 +5958,9 @@ impl GameCommandHandler {
                 first_turn,
             };
             self.publish_game_event_typed(started_event, Audience::room(room_id), Some(gt)).await?;
+
+            self.open_predictions(&room).await?;
+            self.notify_webhooks(webhooks::GAME_STARTED, &room).await;
         }
 
         Ok(())
@@ -3995,10 +6070,15 @@ impl GameCommandHandler {
         let gt = room.game_type.as_str();
 
         // Publish all game events
+        let mut game_events = game_events;
+        state_checksum::stamp(&mut game_events, &room);
         for event in game_events {
             self.publish_game_event_typed(event, Audience::room(room_id), Some(gt)).await?;
         }
 
+        self.open_predictions(&room).await?;
+        self.notify_webhooks(webhooks::GAME_STARTED, &room).await;
+
         info!(
             room_id = %room_id,
             player_count = %room.players.len(),
@@ -4048,10 +6128,16 @@ impl GameCommandHandler {
             }
         }
 
-        // Get chat history from MongoDB
+        // Get chat history from MongoDB, without messages of users the reader blocked
         let messages: Vec<serde_json::Value> = if let Some(chat_client) = self.get_chat_client() {
             let limit = limit.unwrap_or(50);
-            chat_client.get_messages(room_id, channel.clone(), limit, None)
+            let db = self.db.lock().await;
+            let hidden_users = user_blocks_read::blocked_ids(&db, user_id)
+                .await
+                .map_err(|e| EventHandlerError::Retryable(format!("Failed to load blocked users: {}", e)))?;
+            drop(db);
+
+            chat_client.get_messages(room_id, channel.clone(), limit, None, &hidden_users)
                 .await
                 .map_err(|e| EventHandlerError::Retryable(format!("Failed to get chat history: {}", e)))?
                 .into_iter()
@@ -4107,7 +6193,7 @@ impl EventHandler for GameCommandHandler {
     }
 
     fn topics(&self) -> Vec<&'static str> {
-        vec![topic::GAMES_COMMANDS]
+        routing::region_games_command_topics()
     }
 
     async fn handle(&self, event: &crate::events::types::DomainEvent) -> Result<(), EventHandlerError> {
@@ -4138,7 +6224,62 @@ impl EventHandler for GameCommandHandler {
         let username = &envelope.actor.username;
         let socket_id = &envelope.actor.socket_id;
 
-        match command_type {
+        // Region migration is only accepted from the backend itself, never from the gateway
+        if command_type == "migrate_room" {
+            if envelope.producer != "blazing_sun" {
+                warn!(producer = %envelope.producer, "Rejecting migrate_room from external producer");
+                return Err(EventHandlerError::Skip);
+            }
+
+            let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+            let to_region = envelope.payload.get("to_region").and_then(|v| v.as_str())
+                .ok_or_else(|| EventHandlerError::Fatal("Missing to_region".to_string()))?;
+            let requested_by = (user_id > 0).then_some(user_id);
+
+            return self.handle_migrate_room(room_id, to_region, "admin", requested_by).await;
+        }
+
+        // Spectator promotion is requested by the waitlist handler, never by a client
+        if command_type == "promote_spectators" {
+            if envelope.producer != "blazing_sun" {
+                warn!(producer = %envelope.producer, "Rejecting promote_spectators from external producer");
+                return Err(EventHandlerError::Skip);
+            }
+
+            let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+
+            return self.handle_promote_spectators(room_id).await;
+        }
+
+        // Operator room closing follows the same rule
+        if command_type == "close_room" {
+            if envelope.producer != "blazing_sun" {
+                warn!(producer = %envelope.producer, "Rejecting close_room from external producer");
+                return Err(EventHandlerError::Skip);
+            }
+
+            let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+            let requested_by = (user_id > 0).then_some(user_id);
+
+            return self.handle_close_room(room_id, requested_by).await;
+        }
+
+        // Rooms that moved to another region are served by that region's gateway
+        if self.reject_if_room_elsewhere(command_type, &envelope.payload, user_id).await? {
+            return Ok(());
+        }
+
+        // Commands for a room that was just deleted/finished get a definitive answer
+        if let Some(room_id) = envelope.payload.get("room_id").and_then(|v| v.as_str()) {
+            if self.reject_if_room_gone(command_type, room_id, user_id, socket_id).await? {
+                return Ok(());
+            }
+        }
+
+        let result = match command_type {
             "create_room" => {
                 // Debug: log the entire payload to see what's being received
                 info!(
@@ -4157,6 +6298,23 @@ impl EventHandler for GameCommandHandler {
                     .and_then(|v| v.as_i64())
                     .map(|v| v as i32);
                 let allow_spectators = envelope.payload.get("allow_spectators").and_then(|v| v.as_bool());
+                let max_spectators = envelope.payload.get("max_spectators")
+                    .and_then(|v| v.as_i64())
+                    .map(|v| v as i32);
+                let turn_timer_seconds = envelope.payload.get("turn_timer_seconds").and_then(|v| v.as_i64());
+                let preset = envelope.payload.get("preset").and_then(|v| v.as_str());
+                // Practice room against a bot; difficulty defaults to medium
+                let vs_bot = envelope.payload.get("vs_bot").and_then(|v| v.as_bool()).unwrap_or(false);
+                let bot_difficulty = vs_bot.then(|| {
+                    envelope.payload.get("bot_difficulty").and_then(|v| v.as_str()).unwrap_or("medium")
+                });
+                // Optional RFC 3339 time the room opens at
+                let scheduled_start_at = envelope.payload.get("scheduled_start_at").and_then(|v| v.as_str());
+                // Optional Bigger Dice rules; unset rules keep the defaults
+                let dice_rules = match envelope.payload.get("rules") {
+                    None | Some(serde_json::Value::Null) => Ok(BiggerDiceRuleSettings::default()),
+                    Some(rules) => serde_json::from_value::<BiggerDiceRuleSettings>(rules.clone()),
+                };
 
                 info!(
                     player_count = ?player_count,
@@ -4165,17 +6323,35 @@ impl EventHandler for GameCommandHandler {
                     "create_room: parsed player_count"
                 );
 
-                self.handle_create_room(
-                    user_id,
-                    username,
-                    avatar_id,
-                    game_type,
-                    room_name,
-                    socket_id,
-                    password,
-                    player_count,
-                    allow_spectators,
-                ).await
+                match dice_rules {
+                    Ok(dice_rules) => self.handle_create_room(
+                        user_id,
+                        username,
+                        avatar_id,
+                        game_type,
+                        room_name,
+                        socket_id,
+                        password,
+                        preset,
+                        RoomSettings {
+                            player_count,
+                            allow_spectators,
+                            max_spectators,
+                            turn_timer_seconds,
+                            dice_rules,
+                        },
+                        bot_difficulty,
+                        scheduled_start_at,
+                    ).await,
+                    Err(e) => {
+                        let error = GameEvent::Error {
+                            code: "invalid_room_config".to_string(),
+                            message: format!("Invalid rules: {}", e),
+                            socket_id: socket_id.to_string(),
+                        };
+                        self.publish_game_event(error, Audience::user(user_id)).await
+                    }
+                }
             }
             "join_room" => {
                 let avatar_id = Self::parse_optional_i64(envelope.payload.get("avatar_id"));
@@ -4183,7 +6359,7 @@ impl EventHandler for GameCommandHandler {
                     .ok_or_else(|| EventHandlerError::Fatal("Missing room_name".to_string()))?;
                 let password = envelope.payload.get("password").and_then(|v| v.as_str());
 
-                self.handle_join_room(user_id, username, avatar_id, room_name, socket_id, password).await
+                self.handle_join_room(user_id, username, avatar_id, room_name, socket_id, password, false).await
             }
             "leave_room" => {
                 let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
@@ -4221,6 +6397,23 @@ impl EventHandler for GameCommandHandler {
 
                 self.handle_ready(user_id, room_id, socket_id).await
             }
+            "report_desync" => {
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+                let checksum = envelope.payload.get("state_checksum").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing state_checksum".to_string()))?;
+
+                self.handle_report_desync(user_id, room_id, checksum, socket_id).await
+            }
+            "get_room_state" => {
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str());
+                let room_name = envelope.payload.get("room_name").and_then(|v| v.as_str());
+                if room_id.is_none() && room_name.is_none() {
+                    return Err(EventHandlerError::Fatal("Missing room_id or room_name".to_string()));
+                }
+
+                self.handle_get_room_state(user_id, room_id, room_name, socket_id).await
+            }
             "spectate" => {
                 let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
                     .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
@@ -4233,6 +6426,16 @@ impl EventHandler for GameCommandHandler {
 
                 self.handle_leave_spectate(user_id, room_id, socket_id).await
             }
+            "predict_winner" => {
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+                let predicted_winner_id = Self::parse_user_id(envelope.payload.get("predicted_winner_id"))
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing predicted_winner_id".to_string()))?;
+                let stake_cents = Self::parse_optional_i64(envelope.payload.get("stake_cents"))
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing stake_cents".to_string()))?;
+
+                self.handle_predict_winner(user_id, room_id, predicted_winner_id, stake_cents, socket_id).await
+            }
             "select_player" => {
                 let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
                     .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
@@ -4313,7 +6516,50 @@ impl EventHandler for GameCommandHandler {
             }
             "list_rooms" => {
                 let game_type = envelope.payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("bigger_dice");
-                self.handle_list_rooms(user_id, game_type, socket_id).await
+                let cursor = match envelope.payload.get("cursor").and_then(|v| v.as_str()) {
+                    Some(value) => Some(Cursor::decode(value)
+                        .ok_or_else(|| EventHandlerError::Fatal("Invalid cursor".to_string()))?),
+                    None => None,
+                };
+                let limit = Self::parse_optional_i64(envelope.payload.get("limit"))
+                    .unwrap_or(50)
+                    .clamp(1, 100);
+                self.handle_list_rooms(user_id, game_type, cursor.as_ref(), limit, socket_id).await
+            }
+            "search_rooms" => {
+                let text = |key: &str| {
+                    envelope.payload.get(key)
+                        .and_then(|v| v.as_str())
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .map(str::to_string)
+                };
+                let status = text("status");
+                if status.as_deref().is_some_and(|s| s != "waiting" && s != "in_progress") {
+                    return Err(EventHandlerError::Fatal("Invalid status".to_string()));
+                }
+                let sort = match text("sort") {
+                    Some(value) => RoomSort::parse(&value)
+                        .ok_or_else(|| EventHandlerError::Fatal("Invalid sort".to_string()))?,
+                    None => RoomSort::default(),
+                };
+                let search = RoomSearch {
+                    game_type: text("game_type"),
+                    status,
+                    has_password: envelope.payload.get("has_password").and_then(|v| v.as_bool()),
+                    allow_spectators: envelope.payload.get("allow_spectators").and_then(|v| v.as_bool()),
+                    host_prefix: text("host"),
+                    query: text("query"),
+                };
+                let cursor = match envelope.payload.get("cursor").and_then(|v| v.as_str()) {
+                    Some(value) => Some(Cursor::decode(value)
+                        .ok_or_else(|| EventHandlerError::Fatal("Invalid cursor".to_string()))?),
+                    None => None,
+                };
+                let limit = Self::parse_optional_i64(envelope.payload.get("limit"))
+                    .unwrap_or(50)
+                    .clamp(1, 100);
+                self.handle_search_rooms(user_id, &search, sort, cursor.as_ref(), limit, socket_id).await
             }
             "send_chat" => {
                 let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
@@ -4333,6 +6579,25 @@ impl EventHandler for GameCommandHandler {
 
                 self.handle_mute_user(user_id, room_id, target_user_id, socket_id).await
             }
+            "chat_mute" => {
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+                let target_user_id = Self::parse_user_id(envelope.payload.get("target_user_id"))
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing target_user_id".to_string()))?;
+                let channel = envelope.payload.get("channel").and_then(|v| v.as_str());
+                let duration_seconds = envelope.payload.get("duration_seconds").and_then(|v| v.as_i64());
+
+                self.handle_chat_mute(user_id, room_id, target_user_id, channel, duration_seconds, socket_id).await
+            }
+            "chat_unmute" => {
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+                let target_user_id = Self::parse_user_id(envelope.payload.get("target_user_id"))
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing target_user_id".to_string()))?;
+                let channel = envelope.payload.get("channel").and_then(|v| v.as_str());
+
+                self.handle_chat_unmute(user_id, room_id, target_user_id, channel, socket_id).await
+            }
             "unmute_user" => {
                 let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
                     .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
@@ -4392,6 +6657,32 @@ impl EventHandler for GameCommandHandler {
 
                 self.handle_start_game(user_id, room_id, socket_id).await
             }
+            "extend_room" => {
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+
+                self.handle_extend_room(user_id, room_id, socket_id).await
+            }
+            "invite_user" => {
+                let invitee_id = Self::parse_optional_i64(envelope.payload.get("user_id"))
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing user_id".to_string()))?;
+                let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
+
+                self.handle_invite_user(user_id, username, room_id, invitee_id, socket_id).await
+            }
+            "accept_invite" | "decline_invite" => {
+                let invite_id = envelope.payload.get("invite_id").and_then(|v| v.as_str())
+                    .and_then(|v| Uuid::parse_str(v).ok())
+                    .ok_or_else(|| EventHandlerError::Fatal("Missing invite_id".to_string()))?;
+
+                if command_type == "accept_invite" {
+                    let avatar_id = Self::parse_optional_i64(envelope.payload.get("avatar_id"));
+                    self.handle_accept_invite(user_id, username, avatar_id, invite_id, socket_id).await
+                } else {
+                    self.handle_decline_invite(user_id, username, invite_id, socket_id).await
+                }
+            }
             "get_chat_history" => {
                 let room_id = envelope.payload.get("room_id").and_then(|v| v.as_str())
                     .ok_or_else(|| EventHandlerError::Fatal("Missing room_id".to_string()))?;
@@ -4404,7 +6695,29 @@ impl EventHandler for GameCommandHandler {
                 warn!(command_type = %other, "Unknown game command type");
                 Err(EventHandlerError::Skip)
             }
+        };
+
+        // Predictions close lazily: the first move after the window elapsed announces it
+        if result.is_ok() && PREDICTION_CLOSE_COMMANDS.contains(&command_type) {
+            if let Some(room_id) = envelope.payload.get("room_id").and_then(|v| v.as_str()) {
+                self.close_expired_predictions(room_id).await?;
+            }
+        }
+
+        if result.is_ok() && BOT_TRIGGER_COMMANDS.contains(&command_type) {
+            if let Some(room_id) = envelope.payload.get("room_id").and_then(|v| v.as_str()) {
+                self.drive_bot(room_id).await;
+            }
         }
+
+        // Any user command keeps its room from closing for inactivity
+        if !LATE_SYSTEM_COMMANDS.contains(&command_type) {
+            if let Some(room_id) = envelope.payload.get("room_id").and_then(|v| v.as_str()) {
+                self.record_activity(room_id).await;
+            }
+        }
+
+        result
     }
 }
 
@@ -4421,6 +6734,7 @@ mod tests {
             score: 0,
             is_ready: false,
             joined_at: Utc::now(),
+            stats: None,
         }
     }
 
@@ -4461,7 +6775,7 @@ mod tests {
     #[test]
     fn room_list_includes_waiting_rooms() {
         let record = base_record("waiting");
-        let item = GameCommandHandler::room_list_item_for_user(&record, 42);
+        let item = room_list::list_item_for_user(&record, 42);
 
         let item = item.expect("waiting rooms should be listed");
         assert_eq!(item.get("can_rejoin").and_then(|v| v.as_bool()), Some(false));
@@ -4479,7 +6793,7 @@ mod tests {
             "joined_at": "2026-01-13T00:00:00Z"
         }]);
 
-        let item = GameCommandHandler::room_list_item_for_user(&record, 42)
+        let item = room_list::list_item_for_user(&record, 42)
             .expect("waiting rooms with membership should be listed");
 
         assert_eq!(item.get("can_rejoin").and_then(|v| v.as_bool()), Some(true));
@@ -4498,7 +6812,7 @@ mod tests {
             "joined_at": "2026-01-13T00:00:00Z"
         }]);
 
-        let item = GameCommandHandler::room_list_item_for_user(&record, 42)
+        let item = room_list::list_item_for_user(&record, 42)
             .expect("rejoinable in-progress rooms should be listed");
 
         assert_eq!(item.get("can_rejoin").and_then(|v| v.as_bool()), Some(true));
@@ -4510,7 +6824,7 @@ mod tests {
         record.recorded_players = vec![42];
         record.banned_users = vec![42];
 
-        let item = GameCommandHandler::room_list_item_for_user(&record, 42);
+        let item = room_list::list_item_for_user(&record, 42);
 
         assert!(item.is_none());
     }
@@ -4518,7 +6832,7 @@ mod tests {
     #[test]
     fn room_list_skips_in_progress_room_without_membership() {
         let record = base_record("in_progress");
-        let item = GameCommandHandler::room_list_item_for_user(&record, 42);
+        let item = room_list::list_item_for_user(&record, 42);
 
         assert!(item.is_none());
     }
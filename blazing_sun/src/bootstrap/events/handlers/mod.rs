@@ -1,28 +1,74 @@
+pub mod achievements;
+pub mod analytics;
 pub mod auth;
+pub mod cache_invalidation;
 pub mod chat;
+pub mod checkout_disputes;
+pub mod checkout_subscriptions;
 pub mod checkout_finished;
 pub mod games;
+pub mod notifications;
+pub mod player_stats;
+pub mod room_list;
+pub mod spectator_waitlist;
+pub mod tournaments;
 pub mod user;
+pub mod user_profile_cache;
 
+pub use achievements::AchievementHandler;
+pub use analytics::AnalyticsHandler;
 pub use auth::{AuthEventHandler, SecurityMonitorHandler};
+pub use cache_invalidation::CacheInvalidationHandler;
 pub use chat::ChatCommandHandler;
+pub use checkout_disputes::CheckoutDisputesHandler;
+pub use checkout_subscriptions::CheckoutSubscriptionsHandler;
 pub use checkout_finished::CheckoutFinishedHandler;
 pub use games::GameCommandHandler;
+pub use notifications::NotificationRouter;
+pub use player_stats::PlayerStatsHandler;
+pub use room_list::RoomListProjectionHandler;
+pub use spectator_waitlist::SpectatorWaitlistHandler;
+pub use tournaments::TournamentHandler;
 pub use user::{UserAuditHandler, UserEventHandler};
+pub use user_profile_cache::UserProfileCacheHandler;
 
+use crate::app::cache::UserProfileCache;
+use crate::app::games::room_game_types::RoomGameTypes;
+use crate::app::games::room_list::RoomListProjection;
+use crate::app::games::spectator_waitlist::SpectatorWaitlist;
+use crate::config::GamesConfig;
+use crate::database::SharedRedis;
 use crate::events::consumer::EventConsumer;
 use crate::events::producer::EventProducer;
+use crate::mq::SharedQueue;
 use mongodb::Database;
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::info;
 
+/// How often a draining region re-checks for waiting rooms to move
+const REGION_DRAIN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often waiting rooms are checked for inactivity
+const ROOM_INACTIVITY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often scheduled rooms are checked for due reminders and openings
+const ROOM_SCHEDULE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often unanswered game room invites are expired
+const INVITE_EXPIRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How often timed chat mutes are ended
+const CHAT_MUTE_EXPIRY_INTERVAL: Duration = Duration::from_secs(15);
+
 /// Register all default event handlers with a consumer
 pub fn register_default_handlers(
     consumer: &mut EventConsumer,
     db: Arc<Mutex<Pool<Postgres>>>,
     producer: Option<Arc<EventProducer>>,
+    mq: Option<SharedQueue>,
 ) {
     info!("Registering default event handlers");
 
@@ -43,9 +89,21 @@ pub fn register_default_handlers(
     consumer.register_handler(Arc::new(security_handler));
 
     // Checkout finished handler (checkout/checkout_finished flow)
-    let checkout_finished_handler = CheckoutFinishedHandler::new(db, producer);
+    let checkout_finished_handler = CheckoutFinishedHandler::new(db.clone(), producer.clone());
     consumer.register_handler(Arc::new(checkout_finished_handler));
 
+    // Checkout disputes handler (freezes and settles coins of disputed payments)
+    let checkout_disputes_handler = CheckoutDisputesHandler::new(db.clone(), producer.clone());
+    consumer.register_handler(Arc::new(checkout_disputes_handler));
+
+    // Checkout subscriptions handler (premium membership status)
+    let checkout_subscriptions_handler = CheckoutSubscriptionsHandler::new(db.clone());
+    consumer.register_handler(Arc::new(checkout_subscriptions_handler));
+
+    // Notification router (payments, game invites and reminders, chat mentions by user preference)
+    let notification_router = NotificationRouter::new(db, producer, mq);
+    consumer.register_handler(Arc::new(notification_router));
+
     info!("Default event handlers registered");
 }
 
@@ -55,17 +113,121 @@ pub fn register_all_handlers(
     db: Arc<Mutex<Pool<Postgres>>>,
     mongodb: Option<Arc<Database>>,
     producer: Option<Arc<EventProducer>>,
+    redis: Option<SharedRedis>,
+    mq: Option<SharedQueue>,
 ) {
     // Register default handlers first
-    register_default_handlers(consumer, db.clone(), producer.clone());
+    register_default_handlers(consumer, db.clone(), producer.clone(), mq);
+
+    // Register user profile cache invalidation (profiles shown by chat and games)
+    let profiles = UserProfileCache::new(redis.clone());
+    consumer.register_handler(Arc::new(UserProfileCacheHandler::new(profiles.clone())));
+
+    // Register client cache invalidation hints (pushed through the gateway)
+    consumer.register_handler(Arc::new(CacheInvalidationHandler::new(producer.clone())));
 
     // Register chat command handler for WebSocket gateway
-    let chat_handler = ChatCommandHandler::new(db.clone(), mongodb.clone(), producer.clone());
+    let chat_handler = ChatCommandHandler::new(
+        db.clone(),
+        mongodb.clone(),
+        producer.clone(),
+        profiles.clone(),
+        redis.clone(),
+    );
     consumer.register_handler(Arc::new(chat_handler));
 
+    // Register analytics projections (game funnels + checkouts)
+    if let Some(mongodb) = &mongodb {
+        consumer.register_handler(Arc::new(AnalyticsHandler::new(mongodb.clone())));
+    }
+
+    // Register tournament progression (advances winners of finished match rooms)
+    consumer.register_handler(Arc::new(TournamentHandler::new(
+        db.clone(),
+        producer.clone(),
+        redis.clone(),
+    )));
+
+    // Register achievements (game over and payment events)
+    consumer.register_handler(Arc::new(AchievementHandler::new(db.clone(), producer.clone())));
+
+    // Register player stats (game over events)
+    consumer.register_handler(Arc::new(PlayerStatsHandler::new(db.clone())));
+
+    // Register the lobby room list projection; rebuild it and the room game
+    // type records from Postgres
+    let room_list = RoomListProjection::new(redis.clone());
+    consumer.register_handler(Arc::new(RoomListProjectionHandler::new(db.clone(), room_list.clone())));
+    let room_game_types = RoomGameTypes::new(redis.clone());
+    let projection_db = db.clone();
+    tokio::spawn(async move {
+        let db = projection_db.lock().await.clone();
+        room_list.rebuild_all(&db).await;
+        room_game_types.rebuild_all(&db).await;
+    });
+
+    // Register spectator promotion from the waitlists of full rooms
+    let waitlist = SpectatorWaitlist::new(redis.clone());
+    consumer.register_handler(Arc::new(SpectatorWaitlistHandler::new(producer.clone(), waitlist)));
+
     // Register game command handler for WebSocket gateway
-    let game_handler = GameCommandHandler::new(db.clone(), mongodb, producer);
-    consumer.register_handler(Arc::new(game_handler));
+    let game_handler = Arc::new(GameCommandHandler::new(db.clone(), mongodb, producer, redis, profiles));
+    consumer.register_handler(game_handler.clone());
+
+    // Warn and close waiting rooms nobody uses any more
+    if GamesConfig::room_inactivity_timeout_minutes() > 0 {
+        let inactivity_handler = game_handler.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ROOM_INACTIVITY_INTERVAL);
+            loop {
+                interval.tick().await;
+                inactivity_handler.close_inactive_rooms().await;
+            }
+        });
+    }
+
+    // Remind registered users of scheduled rooms and open the due ones (also
+    // when scheduling is switched off, so rooms scheduled before still open)
+    let schedule_handler = game_handler.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ROOM_SCHEDULE_INTERVAL);
+        loop {
+            interval.tick().await;
+            schedule_handler.run_room_schedule().await;
+        }
+    });
+
+    // Expire unanswered room invites and tell their inviters
+    let invite_handler = game_handler.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(INVITE_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            invite_handler.expire_invites().await;
+        }
+    });
+
+    // End timed chat mutes and let the rooms re-enable the inputs
+    let chat_mute_handler = game_handler.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHAT_MUTE_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            chat_mute_handler.expire_chat_mutes().await;
+        }
+    });
+
+    // While this region is being drained, keep moving its waiting rooms out
+    if let Some(target) = GamesConfig::region_drain_target() {
+        info!("Region {} is draining waiting rooms to {}", GamesConfig::region(), target);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REGION_DRAIN_INTERVAL);
+            loop {
+                interval.tick().await;
+                game_handler.drain_waiting_rooms().await;
+            }
+        });
+    }
 
-    info!("WebSocket gateway handlers registered (chat + games)");
+    info!("WebSocket gateway handlers registered (chat + games + room lists + spectator waitlists + tournaments + achievements + player stats + analytics + profile cache + cache hints)");
 }
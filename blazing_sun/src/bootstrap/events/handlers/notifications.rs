@@ -0,0 +1,250 @@
+//! Notification router
+//!
+//! Turns payment, game invite, game reminder, chat mention and data export events into notifications
+//! (see `app::notifications`) and delivers each one on the channel its
+//! recipient picked for the category:
+//!
+//! - websocket: a `notification.event.received` envelope with a single-user
+//!   audience on `system.events`, pushed by the gateway
+//! - email: a `send_email` job with the `notification.html` template
+//! - none: dropped
+//!
+//! Notifications are best effort: delivery errors are logged and the event
+//! acknowledged, so a mail queue or Kafka hiccup never redelivers an event to
+//! the handlers it shares a topic with (checkout crediting, game commands).
+
+use crate::app::chat::types::{Actor, Audience, ChatEvent, EventEnvelope as ChatEnvelope};
+use crate::app::checkout::CheckoutFinishedEvent;
+use crate::app::games::types::EventEnvelope as GameEnvelope;
+use crate::app::notifications::{sources, Notification, NotificationChannel};
+use crate::database::read::chat_channel as db_chat_channel;
+use crate::database::read::notification_preferences as db_notification_preferences;
+use crate::database::read::user as db_user;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::producer::EventProducer;
+use crate::events::topics::topic;
+use crate::events::types::{EventType, UserEventType};
+use crate::events::DomainEvent;
+use crate::mq::jobs::email::{EmailTemplate, SendEmailParams};
+use crate::mq::{self, JobOptions, SharedQueue};
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Handler routing notifications by user preference
+pub struct NotificationRouter {
+    db: Arc<Mutex<Pool<Postgres>>>,
+    producer: Option<Arc<EventProducer>>,
+    mq: Option<SharedQueue>,
+}
+
+impl NotificationRouter {
+    /// Create a new handler instance
+    pub fn new(
+        db: Arc<Mutex<Pool<Postgres>>>,
+        producer: Option<Arc<EventProducer>>,
+        mq: Option<SharedQueue>,
+    ) -> Self {
+        Self { db, producer, mq }
+    }
+
+    /// Notifications raised by an event; `None` when the event is not one
+    /// the router cares about
+    async fn notifications_for(&self, event: &DomainEvent) -> Result<Option<Vec<Notification>>, EventHandlerError> {
+        if let EventType::User(user_event) = &event.event_type {
+            let Ok(user_id) = event.entity_id.parse::<i64>() else {
+                return Ok(None);
+            };
+            return Ok(match user_event {
+                UserEventType::BalanceUpdated => sources::from_balance_update(user_id, &event.payload),
+                UserEventType::DataExportReady => sources::from_data_export(user_id, &event.payload),
+                _ => None,
+            }
+            .map(|n| vec![n]));
+        }
+
+        // Gateway topics arrive as raw JSON wrapped in a synthetic event
+        if event.entity_type != "gateway" {
+            return Ok(None);
+        }
+
+        let envelope_type = event.payload.get("event_type").and_then(|t| t.as_str()).unwrap_or_default();
+
+        if envelope_type.starts_with("games.event.") {
+            let envelope: GameEnvelope = serde_json::from_value(event.payload.clone())
+                .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+            return Ok(Some(sources::from_game_envelope(&envelope)));
+        }
+
+        if envelope_type == "chat.event.channel_message" {
+            let envelope: ChatEnvelope = serde_json::from_value(event.payload.clone())
+                .map_err(|e| EventHandlerError::Fatal(format!("Invalid chat event envelope: {}", e)))?;
+            return self.chat_mentions(&envelope).await.map(Some);
+        }
+
+        if envelope_type.starts_with("chat.event.") {
+            return Ok(None);
+        }
+
+        let checkout_event: CheckoutFinishedEvent = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid checkout_finished payload: {}", e)))?;
+        Ok(sources::from_checkout(&checkout_event).map(|n| vec![n]))
+    }
+
+    /// Channel members mentioned by name in a channel message (never the sender)
+    async fn chat_mentions(&self, envelope: &ChatEnvelope) -> Result<Vec<Notification>, EventHandlerError> {
+        let Ok(ChatEvent::ChannelMessageReceived {
+            message_id,
+            channel_id,
+            sender_id,
+            sender_username,
+            content,
+            ..
+        }) = serde_json::from_value::<ChatEvent>(envelope.payload.clone())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let names = sources::mentioned_names(&content);
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let db = self.db.lock().await;
+        let user_ids = db_chat_channel::find_member_ids_by_name(&db, channel_id, &names)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to resolve mentions: {}", e)))?;
+
+        Ok(user_ids
+            .into_iter()
+            .filter(|user_id| *user_id != sender_id)
+            .map(|user_id| sources::chat_mention(user_id, channel_id, &message_id, &sender_username, &content))
+            .collect())
+    }
+
+    /// Deliver one notification on the recipient's channel for its category
+    async fn deliver(&self, notification: &Notification) {
+        let preferences = {
+            let db = self.db.lock().await;
+            db_notification_preferences::get_for_user(&db, notification.user_id).await
+        };
+        let channel = match preferences {
+            Ok(preferences) => preferences.channel(notification.category),
+            Err(e) => {
+                warn!(user_id = %notification.user_id, error = %e, "Failed to load notification preferences, using default");
+                notification.category.default_channel()
+            }
+        };
+
+        let delivered = match channel {
+            NotificationChannel::Websocket => self.push(notification).await,
+            NotificationChannel::Email => self.email(notification).await,
+            NotificationChannel::Muted => Ok(()),
+        };
+
+        match delivered {
+            Ok(()) => debug!(
+                user_id = %notification.user_id,
+                category = notification.category.as_str(),
+                channel = channel.as_str(),
+                "Notification routed"
+            ),
+            Err(e) => warn!(
+                user_id = %notification.user_id,
+                category = notification.category.as_str(),
+                channel = channel.as_str(),
+                error = %e,
+                "Failed to deliver notification"
+            ),
+        }
+    }
+
+    /// Push to the user's WebSocket connections through the gateway
+    async fn push(&self, notification: &Notification) -> Result<(), String> {
+        let Some(producer) = &self.producer else {
+            return Err("No Kafka producer available".to_string());
+        };
+
+        let envelope = ChatEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: "notification.event.received".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0, // System
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::user(notification.user_id),
+            payload: serde_json::json!({
+                "category": notification.category.as_str(),
+                "title": notification.title,
+                "body": notification.body,
+                "data": notification.data,
+            }),
+        };
+
+        let bytes = serde_json::to_vec(&envelope).map_err(|e| e.to_string())?;
+        let key = notification.user_id.to_string();
+        producer
+            .send_raw(topic::SYSTEM_EVENTS, Some(&key), &bytes)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Queue an email to the user's address
+    async fn email(&self, notification: &Notification) -> Result<(), String> {
+        let Some(queue) = &self.mq else {
+            return Err("No message queue available".to_string());
+        };
+
+        let user = {
+            let db = self.db.lock().await;
+            db_user::get_by_id(&db, notification.user_id).await.map_err(|e| e.to_string())?
+        };
+
+        let params = SendEmailParams::new(&user.email, &user.first_name, EmailTemplate::Notification)
+            .with_variable("first_name", &user.first_name)
+            .with_variable("title", &notification.title)
+            .with_variable("body", &notification.body)
+            .with_variable("category_label", notification.category.label());
+
+        mq::enqueue_job(queue, "send_email", &params, JobOptions::new().priority(1).fault_tolerance(3))
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl EventHandler for NotificationRouter {
+    fn name(&self) -> &'static str {
+        "notification_router"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![
+            topic::CHECKOUT_FINISHED,
+            topic::USER_EVENTS,
+            topic::region_games_events(),
+            topic::CHAT_EVENTS,
+        ]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let Some(notifications) = self.notifications_for(event).await? else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        for notification in &notifications {
+            self.deliver(notification).await;
+        }
+
+        Ok(())
+    }
+}
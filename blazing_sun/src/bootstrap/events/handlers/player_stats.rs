@@ -0,0 +1,107 @@
+//! Player stats handler
+//!
+//! Records every finished game of this region's game events as one result per
+//! player and adds it to the player's totals for the game type (see
+//! `app::games::player_stats`). Results are keyed by user and room, so
+//! redelivered events are counted once.
+
+use crate::app::games::player_stats::{self, FinishedRoom};
+use crate::app::games::types::EventEnvelope;
+use crate::database::mutations::player_game_stats as db_player_stats;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::topics::topic;
+use crate::events::DomainEvent;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Handler keeping per-player game stats from game over events
+pub struct PlayerStatsHandler {
+    db: Arc<Mutex<Pool<Postgres>>>,
+}
+
+impl PlayerStatsHandler {
+    /// Create a new handler instance
+    pub fn new(db: Arc<Mutex<Pool<Postgres>>>) -> Self {
+        Self { db }
+    }
+
+    /// The finished room and its game type (`None` for events that aren't a game over)
+    fn finished_room(
+        event: &DomainEvent,
+    ) -> Result<Option<(String, FinishedRoom)>, EventHandlerError> {
+        let envelope_type = event
+            .payload
+            .get("event_type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default();
+        if !envelope_type.starts_with("games.event.") {
+            return Ok(None);
+        }
+
+        let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+
+        let Some(room) = player_stats::finished_room(&envelope) else {
+            return Ok(None);
+        };
+        let Some(game_type) = player_stats::game_type_of(&envelope.event_type) else {
+            warn!(
+                room_id = %room.room_id,
+                event_type = %envelope.event_type,
+                "Game over event without a game type, not counted in player stats"
+            );
+            return Ok(None);
+        };
+
+        Ok(Some((game_type.to_string(), room)))
+    }
+}
+
+#[async_trait]
+impl EventHandler for PlayerStatsHandler {
+    fn name(&self) -> &'static str {
+        "player_stats_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::region_games_events()]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let Some((game_type, room)) = Self::finished_room(event)? else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        let results = player_stats::results(
+            &room.room_id,
+            &game_type,
+            &room.players,
+            room.winner_id,
+            room.started_at,
+            room.finished_at.unwrap_or_else(Utc::now),
+        );
+
+        let db = self.db.lock().await.clone();
+        for result in &results {
+            let recorded = db_player_stats::record_result(&db, result)
+                .await
+                .map_err(|e| {
+                    EventHandlerError::Retryable(format!("Failed to record player stats: {}", e))
+                })?;
+
+            if recorded.is_none() {
+                debug!(
+                    user_id = %result.user_id,
+                    room_id = %result.room_id,
+                    "Player result already recorded"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
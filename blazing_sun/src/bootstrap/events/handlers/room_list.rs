@@ -0,0 +1,54 @@
+//! Room list projection handler
+//!
+//! Keeps the Redis room list projection in step with this region's game
+//! events: after a room lifecycle event the room is re-read from Postgres and
+//! its list entry is updated, or dropped once the room is no longer listed.
+
+use crate::app::games::room_list::{self, RoomListProjection};
+use crate::app::games::types::EventEnvelope;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::topics::topic;
+use crate::events::DomainEvent;
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Handler maintaining the lobby room list projection
+pub struct RoomListProjectionHandler {
+    db: Arc<Mutex<Pool<Postgres>>>,
+    projection: RoomListProjection,
+}
+
+impl RoomListProjectionHandler {
+    /// Create a new handler instance
+    pub fn new(db: Arc<Mutex<Pool<Postgres>>>, projection: RoomListProjection) -> Self {
+        Self { db, projection }
+    }
+}
+
+#[async_trait]
+impl EventHandler for RoomListProjectionHandler {
+    fn name(&self) -> &'static str {
+        "room_list_projection_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::region_games_events()]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+
+        let Some(room_id) = room_list::affected_room(&envelope) else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        let db = self.db.lock().await.clone();
+        self.projection
+            .refresh_room(&db, &room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))
+    }
+}
@@ -0,0 +1,90 @@
+//! Spectator waitlist handler
+//!
+//! Watches this region's game events for spectators leaving a room. When a
+//! seat frees up, a `promote_spectators` command is sent to the game command
+//! handler, which admits the next users from the room's waitlist and tells
+//! the rest of the queue their new place.
+
+use crate::app::games::spectator_waitlist::{self, SpectatorWaitlist};
+use crate::app::games::types::{Actor, Audience, EventEnvelope};
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::producer::EventProducer;
+use crate::events::routing;
+use crate::events::topics::topic;
+use crate::events::DomainEvent;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+/// Handler refilling spectator seats from the waitlist
+pub struct SpectatorWaitlistHandler {
+    producer: Option<Arc<EventProducer>>,
+    waitlist: SpectatorWaitlist,
+}
+
+impl SpectatorWaitlistHandler {
+    /// Create a new handler instance
+    pub fn new(producer: Option<Arc<EventProducer>>, waitlist: SpectatorWaitlist) -> Self {
+        Self { producer, waitlist }
+    }
+}
+
+#[async_trait]
+impl EventHandler for SpectatorWaitlistHandler {
+    fn name(&self) -> &'static str {
+        "spectator_waitlist_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::region_games_events()]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+
+        let Some(room_id) = spectator_waitlist::freed_seat(&envelope) else {
+            return Err(EventHandlerError::Skip);
+        };
+        if !self.waitlist.is_enabled() {
+            return Err(EventHandlerError::Skip);
+        }
+        let Some(producer) = &self.producer else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        let command = EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: "games.command.promote_spectators".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0,
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::room(room_id.clone()),
+            payload: serde_json::json!({ "room_id": room_id }),
+        };
+
+        let bytes = serde_json::to_vec(&command).map_err(|e| {
+            EventHandlerError::Fatal(format!("Failed to serialize promote_spectators: {}", e))
+        })?;
+
+        // Follows the room's other commands (the game type is in the event type)
+        let game_type = routing::game_type_of(&envelope.event_type, &envelope.payload);
+        producer
+            .send_games_command(game_type, &room_id, &bytes)
+            .await
+            .map_err(|e| {
+                EventHandlerError::Retryable(format!("Failed to publish promote_spectators: {}", e))
+            })?;
+
+        info!(room_id = %room_id, "Spectator seat freed, promoting from the waitlist");
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! Tournament progression handler
+//!
+//! Watches this region's game events for finished games. When the room hosted
+//! a tournament match, its winner advances: the next round's rooms are
+//! created, or the final's prizes are paid out. Recording a result is
+//! idempotent, so redelivered events are harmless.
+
+use crate::app::db_query::read::tournaments as tournament_read;
+use crate::app::games::tournament;
+use crate::app::games::tournament_runner::TournamentRunner;
+use crate::app::games::types::EventEnvelope;
+use crate::database::SharedRedis;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::producer::EventProducer;
+use crate::events::topics::topic;
+use crate::events::DomainEvent;
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Handler advancing tournament winners from game over events
+pub struct TournamentHandler {
+    db: Arc<Mutex<Pool<Postgres>>>,
+    producer: Option<Arc<EventProducer>>,
+    redis: Option<SharedRedis>,
+}
+
+impl TournamentHandler {
+    /// Create a new handler instance
+    pub fn new(
+        db: Arc<Mutex<Pool<Postgres>>>,
+        producer: Option<Arc<EventProducer>>,
+        redis: Option<SharedRedis>,
+    ) -> Self {
+        Self { db, producer, redis }
+    }
+}
+
+#[async_trait]
+impl EventHandler for TournamentHandler {
+    fn name(&self) -> &'static str {
+        "tournament_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::region_games_events()]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let envelope: EventEnvelope = serde_json::from_value(event.payload.clone())
+            .map_err(|e| EventHandlerError::Fatal(format!("Invalid game event envelope: {}", e)))?;
+
+        let Some((room_id, winner_id)) = tournament::finished_match(&envelope) else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        let db = self.db.lock().await.clone();
+        let Some(m) = tournament_read::get_match_by_room(&db, &room_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Database error: {}", e)))?
+        else {
+            return Err(EventHandlerError::Skip);
+        };
+
+        info!(
+            tournament_id = %m.tournament_id,
+            match_id = %m.id,
+            winner_id = %winner_id,
+            "Tournament match finished"
+        );
+
+        TournamentRunner::new(db, self.producer.clone(), self.redis.clone())
+            .advance(&m, winner_id)
+            .await
+            .map_err(|e| EventHandlerError::Retryable(format!("Failed to advance tournament: {}", e)))
+    }
+}
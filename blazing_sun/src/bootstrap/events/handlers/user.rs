@@ -190,6 +190,8 @@ impl UserAuditHandler {
             audit_entity_type = %event.entity_type,
             audit_entity_id = %event.entity_id,
             audit_actor_id = ?event.metadata.actor_id,
+            audit_impersonator_id = ?event.metadata.impersonator_id,
+            audit_impersonation_id = ?event.metadata.impersonation_id,
             audit_timestamp = %event.timestamp,
             "Audit log entry created"
         );
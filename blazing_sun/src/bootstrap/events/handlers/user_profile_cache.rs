@@ -0,0 +1,53 @@
+//! User profile cache invalidation
+//!
+//! Drops a user's cached profile when an event says it may have changed.
+
+use crate::app::cache::UserProfileCache;
+use crate::events::consumer::{EventHandler, EventHandlerError};
+use crate::events::topics::topic;
+use crate::events::types::{DomainEvent, EventType, UserEventType};
+use async_trait::async_trait;
+
+/// Handler invalidating the user profile cache
+pub struct UserProfileCacheHandler {
+    profiles: UserProfileCache,
+}
+
+impl UserProfileCacheHandler {
+    /// Create a new handler instance
+    pub fn new(profiles: UserProfileCache) -> Self {
+        Self { profiles }
+    }
+}
+
+#[async_trait]
+impl EventHandler for UserProfileCacheHandler {
+    fn name(&self) -> &'static str {
+        "user_profile_cache_handler"
+    }
+
+    fn topics(&self) -> Vec<&'static str> {
+        vec![topic::USER_EVENTS]
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        match &event.event_type {
+            EventType::User(
+                UserEventType::Updated
+                | UserEventType::ProfileUpdated
+                | UserEventType::RoleChanged
+                | UserEventType::Deactivated
+                | UserEventType::Deleted,
+            ) => {}
+            _ => return Err(EventHandlerError::Skip),
+        }
+
+        let user_id: i64 = event
+            .entity_id
+            .parse()
+            .map_err(|_| EventHandlerError::Fatal(format!("Invalid user id: {}", event.entity_id)))?;
+
+        self.profiles.invalidate(user_id).await;
+        Ok(())
+    }
+}
@@ -67,15 +67,38 @@
 //! consumer.subscribe()?;
 //! consumer.start().await;
 //! ```
+//!
+//! ## Replaying Events
+//!
+//! Derived state can be rebuilt by replaying a topic into one handler in an
+//! isolated consumer group (see [`replay`] and `blazing_admin kafka replay`).
+//!
+//! ## Failed Events
+//!
+//! Handlers are retried with exponential backoff (see [`retry`]); events a
+//! handler still fails go to the `<topic>.dlq` dead letter topic of their
+//! source, where admins can browse and requeue them (see [`dead_letter`]).
 
 pub mod consumer;
+pub mod dead_letter;
+pub mod handler_metrics;
 pub mod handlers;
 pub mod producer;
+pub mod replay;
+pub mod retry;
+pub mod routing;
 pub mod topics;
 pub mod types;
 
-pub use consumer::{EventConsumer, EventHandler, EventHandlerError};
+pub use consumer::{
+    handler_metrics, in_flight_handlers, rebalance_metrics, EventConsumer, EventHandler,
+    EventHandlerError,
+};
+pub use dead_letter::{DeadLetter, DeadLetterQuery, DeadLetterRecord};
+pub use handler_metrics::HandlerStats;
 pub use producer::{EventProducer, EventPublishError, SharedProducer};
+pub use replay::{EventReplay, ReplayOptions, ReplayProgress, ReplayStart};
+pub use retry::RetryPolicy;
 pub use topics::{consumer_groups, topic};
 pub use types::{
     AuthEventType, CategoryEventType, DomainEvent, EventBuilder, EventMetadata, EventType,
@@ -134,14 +157,18 @@ pub async fn init(
     db: Arc<Mutex<Pool<Postgres>>>,
 ) -> Result<(SharedEventBus, Arc<EventConsumer>), Box<dyn std::error::Error + Send + Sync>> {
     // Delegate to init_full with no MongoDB (default handlers only)
-    init_full(db, None).await
+    init_full(db, None, None, None).await
 }
 
 /// Initialize the event system with MongoDB support (for WebSocket gateway handlers)
 /// This registers chat and game handlers in addition to default handlers.
+/// Redis (optional) backs the game handler's room password throttling.
+/// The message queue (optional) sends notifications users asked to get by email.
 pub async fn init_full(
     db: Arc<Mutex<Pool<Postgres>>>,
     mongodb: Option<Arc<mongodb::Database>>,
+    redis: Option<crate::database::SharedRedis>,
+    mq: Option<crate::mq::SharedQueue>,
 ) -> Result<(SharedEventBus, Arc<EventConsumer>), Box<dyn std::error::Error + Send + Sync>> {
     info!("Initializing Kafka event system...");
 
@@ -158,15 +185,16 @@ pub async fn init_full(
         error!("Failed to initialize Kafka consumer: {}", e);
         Box::new(e) as Box<dyn std::error::Error + Send + Sync>
     })?;
+    consumer.set_dead_letter_producer(producer.clone());
 
     // Register handlers based on whether MongoDB is available
     if mongodb.is_some() {
         // Register all handlers including WebSocket gateway handlers
-        handlers::register_all_handlers(&mut consumer, db, mongodb, Some(producer.clone()));
+        handlers::register_all_handlers(&mut consumer, db, mongodb, Some(producer.clone()), redis, mq);
         info!("Registered all handlers (default + WebSocket gateway)");
     } else {
         // Register only default handlers
-        handlers::register_default_handlers(&mut consumer, db, Some(producer.clone()));
+        handlers::register_default_handlers(&mut consumer, db, Some(producer.clone()), mq);
         info!("Registered default handlers only");
     }
 
@@ -347,7 +375,7 @@ pub mod publish {
         let metadata = EventMetadata::new("auth-service")
             .with_actor(user_id)
             .with_request_context(
-                None,
+                logging::request_id::current(),
                 ip_address.map(|s| s.to_string()),
                 user_agent.map(|s| s.to_string()),
             );
@@ -377,7 +405,7 @@ pub mod publish {
         };
 
         let metadata = EventMetadata::new("auth-service").with_request_context(
-            None,
+            logging::request_id::current(),
             ip_address.map(|s| s.to_string()),
             user_agent.map(|s| s.to_string()),
         );
@@ -395,6 +423,145 @@ pub mod publish {
         Ok(event_id)
     }
 
+    /// Publish an auth.two_factor_* event (`TwoFactorEnabled`,
+    /// `TwoFactorDisabled` or `TwoFactorChallengeFailed`); the user is the
+    /// entity, `actor_id` whoever made the change
+    pub async fn auth_two_factor(
+        event_bus: &EventBus,
+        event_type: AuthEventType,
+        user_id: i64,
+        actor_id: Option<i64>,
+        payload: TwoFactorPayload,
+        ip_address: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<String, EventPublishError> {
+        let mut metadata = EventMetadata::new("auth-service").with_request_context(
+            logging::request_id::current(),
+            ip_address.map(|s| s.to_string()),
+            user_agent.map(|s| s.to_string()),
+        );
+        if let Some(actor_id) = actor_id {
+            metadata = metadata.with_actor(actor_id);
+        }
+
+        let event = EventBuilder::new(EventType::Auth(event_type), &user_id.to_string())
+            .payload(payload)
+            .metadata(metadata)
+            .build();
+
+        let event_id = event.id.clone();
+        event_bus.publish(&event).await?;
+        Ok(event_id)
+    }
+
+    /// Publish a user.balance_updated event for an approved admin adjustment.
+    /// The approver is the actor; the payload keeps both admins for the audit trail.
+    pub async fn balance_adjusted(
+        event_bus: &EventBus,
+        user_id: i64,
+        payload: BalanceAdjustedPayload,
+    ) -> Result<String, EventPublishError> {
+        let event = EventBuilder::new(
+            EventType::User(UserEventType::BalanceUpdated),
+            &user_id.to_string(),
+        )
+        .actor(payload.approved_by)
+        .payload(payload)
+        .build();
+        let event_id = event.id.clone();
+
+        event_bus.publish(&event).await?;
+        Ok(event_id)
+    }
+
+    /// Publish an auth.impersonation_started event. The admin is the actor and
+    /// the impersonated user the entity.
+    pub async fn impersonation_started(
+        event_bus: &EventBus,
+        payload: ImpersonationPayload,
+    ) -> Result<String, EventPublishError> {
+        let admin_id = payload.admin_id;
+        impersonation_event(event_bus, AuthEventType::ImpersonationStarted, admin_id, payload).await
+    }
+
+    /// Publish an auth.impersonation_ended event; `ended_by` is the actor
+    pub async fn impersonation_ended(
+        event_bus: &EventBus,
+        ended_by: i64,
+        payload: ImpersonationPayload,
+    ) -> Result<String, EventPublishError> {
+        impersonation_event(event_bus, AuthEventType::ImpersonationEnded, ended_by, payload).await
+    }
+
+    async fn impersonation_event(
+        event_bus: &EventBus,
+        event_type: AuthEventType,
+        actor_id: i64,
+        payload: ImpersonationPayload,
+    ) -> Result<String, EventPublishError> {
+        let event = EventBuilder::new(EventType::Auth(event_type), &payload.user_id.to_string())
+            .actor(actor_id)
+            .payload(payload)
+            .build();
+        let event_id = event.id.clone();
+
+        event_bus.publish(&event).await?;
+        Ok(event_id)
+    }
+
+    /// Publish a transaction.transfer_completed event plus a user.balance_updated
+    /// event for each party. The sender is the actor of all three.
+    pub async fn balance_transferred(
+        event_bus: &EventBus,
+        payload: BalanceTransferredPayload,
+        sender_balance: i64,
+        recipient_balance: i64,
+    ) -> Result<String, EventPublishError> {
+        let side = |user_id: i64, balance: i64, change: i64, counterparty_id: i64, counterparty_name: &str| {
+            EventBuilder::new(EventType::User(UserEventType::BalanceUpdated), &user_id.to_string())
+                .actor(payload.sender_id)
+                .payload(TransferBalanceUpdatedPayload {
+                    balance,
+                    change,
+                    source: "transfer".to_string(),
+                    transfer_id: payload.transfer_id,
+                    counterparty_id,
+                    counterparty_name: counterparty_name.to_string(),
+                    memo: payload.memo.clone(),
+                })
+                .build()
+        };
+        let events = [
+            side(
+                payload.sender_id,
+                sender_balance,
+                -payload.amount_cents,
+                payload.recipient_id,
+                &payload.recipient_name,
+            ),
+            side(
+                payload.recipient_id,
+                recipient_balance,
+                payload.amount_cents,
+                payload.sender_id,
+                &payload.sender_name,
+            ),
+            EventBuilder::new(
+                EventType::Transaction(TransactionEventType::TransferCompleted),
+                &payload.transfer_id.to_string(),
+            )
+            .actor(payload.sender_id)
+            .payload(payload.clone())
+            .build(),
+        ];
+        let event_id = events[2].id.clone();
+
+        for result in event_bus.publish_batch(&events).await {
+            result?;
+        }
+        Ok(event_id)
+    }
+
     /// Publish a user.password_changed event
     pub async fn user_password_changed(
         event_bus: &EventBus,
@@ -417,4 +584,41 @@ pub mod publish {
         event_bus.publish(&event).await?;
         Ok(event_id)
     }
+
+    /// Publish a user.data_export_ready event
+    pub async fn user_data_export_ready(
+        event_bus: &EventBus,
+        user_id: i64,
+        payload: DataExportReadyPayload,
+    ) -> Result<String, EventPublishError> {
+        let event = EventBuilder::new(
+            EventType::User(UserEventType::DataExportReady),
+            &user_id.to_string(),
+        )
+        .payload(payload)
+        .build();
+        let event_id = event.id.clone();
+
+        event_bus.publish(&event).await?;
+        Ok(event_id)
+    }
+
+    /// Publish a user.role_changed event
+    pub async fn user_role_changed(
+        event_bus: &EventBus,
+        user_id: i64,
+        payload: RoleChangedPayload,
+    ) -> Result<String, EventPublishError> {
+        let event = EventBuilder::new(
+            EventType::User(UserEventType::RoleChanged),
+            &user_id.to_string(),
+        )
+        .actor(payload.changed_by)
+        .payload(payload)
+        .build();
+        let event_id = event.id.clone();
+
+        event_bus.publish(&event).await?;
+        Ok(event_id)
+    }
 }
@@ -1,6 +1,9 @@
 use super::types::DomainEvent;
 use crate::config::KafkaConfig;
+use kafka_producer::segment::{self, SegmentHeader};
+use kafka_producer::{SizeCheck, SizeLimits, SizeMetrics};
 use rdkafka::config::ClientConfig;
+use rdkafka::message::Headers;
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
 use rdkafka::util::Timeout;
 use std::sync::Arc;
@@ -8,22 +11,31 @@ use std::time::Duration;
 use tracing::{error, info, warn};
 
 /// Kafka event producer for publishing domain events
+///
+/// Payloads over `KAFKA_MAX_MESSAGE_BYTES` are refused, except those sent with
+/// `send_segmented`, which go out as segments (see `kafka_producer::segment`).
 pub struct EventProducer {
     producer: FutureProducer,
+    size: SizeLimits,
+    size_metrics: SizeMetrics,
 }
 
 impl EventProducer {
     /// Create a new Kafka producer
     pub fn new() -> Result<Self, rdkafka::error::KafkaError> {
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", KafkaConfig::bootstrap_servers())
+        let mut config = ClientConfig::new();
+        for (key, value) in KafkaConfig::broker_settings() {
+            config.set(key, value);
+        }
+
+        let producer: FutureProducer = config
             .set("client.id", KafkaConfig::client_id())
             .set("message.timeout.ms", "5000")
             .set("acks", "all") // Wait for all replicas to acknowledge
             .set("retries", "3")
             .set("retry.backoff.ms", "100")
             .set("enable.idempotence", "true") // Ensure exactly-once delivery
-            .set("compression.type", "lz4") // Compress messages
+            .set("compression.type", KafkaConfig::compression_type())
             .set("linger.ms", "5") // Small batching for low latency
             .set("batch.size", "16384")
             .create()?;
@@ -33,7 +45,52 @@ impl EventProducer {
             KafkaConfig::bootstrap_servers()
         );
 
-        Ok(Self { producer })
+        Ok(Self {
+            producer,
+            size: SizeLimits::new(KafkaConfig::max_message_bytes()),
+            size_metrics: SizeMetrics::default(),
+        })
+    }
+
+    /// Payloads near or over the size limit
+    pub fn size_metrics(&self) -> &SizeMetrics {
+        &self.size_metrics
+    }
+
+    pub fn size_limits(&self) -> &SizeLimits {
+        &self.size
+    }
+
+    /// Count a payload against the size limit, logging ones near it
+    fn check_size(&self, topic: &str, len: usize) -> SizeCheck {
+        let check = self.size_metrics.record(len, self.size.check(len));
+        if check == SizeCheck::NearLimit {
+            warn!(
+                topic = %topic,
+                size = len,
+                max = self.size.max_bytes,
+                "Kafka payload near the size limit"
+            );
+        }
+        check
+    }
+
+    /// Refuse a payload over the size limit
+    fn refuse_oversized(&self, topic: &str, len: usize) -> Result<(), EventPublishError> {
+        if self.check_size(topic, len) != SizeCheck::TooLarge {
+            return Ok(());
+        }
+        self.size_metrics.record_rejected();
+        error!(
+            topic = %topic,
+            size = len,
+            max = self.size.max_bytes,
+            "Kafka payload over the size limit"
+        );
+        Err(EventPublishError::TooLarge {
+            size: len,
+            max: self.size.max_bytes,
+        })
     }
 
     /// Publish a domain event to Kafka
@@ -43,6 +100,11 @@ impl EventProducer {
         let payload = event
             .to_bytes()
             .map_err(|e| EventPublishError::Serialization(e.to_string()))?;
+        self.refuse_oversized(topic, payload.len())?;
+
+        fault_injection::check(fault_injection::Target::KafkaProducer)
+            .await
+            .map_err(|fault| EventPublishError::Kafka(fault.to_string()))?;
 
         let record = FutureRecord::to(topic)
             .key(key)
@@ -153,6 +215,13 @@ impl EventProducer {
             });
         }
 
+        if let Some(ref request_id) = event.metadata.request_id {
+            headers = headers.insert(rdkafka::message::Header {
+                key: "request_id",
+                value: Some(request_id.as_bytes()),
+            });
+        }
+
         if let Some(actor_id) = event.metadata.actor_id {
             headers = headers.insert(rdkafka::message::Header {
                 key: "actor_id",
@@ -175,12 +244,92 @@ impl EventProducer {
         key: Option<&str>,
         payload: &[u8],
     ) -> Result<(), EventPublishError> {
+        self.refuse_oversized(topic, payload.len())?;
+        self.send_record(topic, key, payload, None, &[]).await
+    }
+
+    /// Send raw bytes to a topic with extra headers (dead letters and their
+    /// requeues, see `dead_letter`)
+    pub async fn send_raw_with_headers(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+        headers: &[(&str, &str)],
+    ) -> Result<(), EventPublishError> {
+        self.refuse_oversized(topic, payload.len())?;
+        self.send_record(topic, key, payload, None, headers).await
+    }
+
+    /// Send raw bytes to a topic, as segments when they are over the size
+    /// limit. Only for topics whose consumers reassemble (the gateway and
+    /// this app's consumers). Segments share the key, or one made up for them,
+    /// so they stay on one partition in order.
+    pub async fn send_segmented(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+    ) -> Result<(), EventPublishError> {
+        if self.check_size(topic, payload.len()) != SizeCheck::TooLarge {
+            return self.send_record(topic, key, payload, None, &[]).await;
+        }
+
+        let segments = segment::split(payload, self.size.max_bytes);
+        self.size_metrics.record_segmented();
+        warn!(
+            topic = %topic,
+            size = payload.len(),
+            segments = segments.len(),
+            "Sending oversized Kafka payload in segments"
+        );
+        for (header, chunk) in &segments {
+            let key = key.unwrap_or(&header.id);
+            self.send_record(topic, Some(key), chunk, Some(header), &[])
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn send_record(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+        segment: Option<&SegmentHeader>,
+        extra_headers: &[(&str, &str)],
+    ) -> Result<(), EventPublishError> {
+        fault_injection::check(fault_injection::Target::KafkaProducer)
+            .await
+            .map_err(|fault| EventPublishError::Kafka(fault.to_string()))?;
+
         let mut record = FutureRecord::to(topic).payload(payload);
 
         if let Some(k) = key {
             record = record.key(k);
         }
 
+        // Lets consumers (checkout, the gateway) log under the same request
+        let mut headers = rdkafka::message::OwnedHeaders::new();
+        if let Some(request_id) = logging::request_id::current() {
+            headers = headers.insert(rdkafka::message::Header {
+                key: "request_id",
+                value: Some(request_id.as_bytes()),
+            });
+        }
+        if let Some(segment) = segment {
+            headers = segment.insert_into(headers);
+        }
+        for (key, value) in extra_headers {
+            headers = headers.insert(rdkafka::message::Header {
+                key,
+                value: Some(value.as_bytes()),
+            });
+        }
+        if headers.count() > 0 {
+            record = record.headers(headers);
+        }
+
         match self
             .producer
             .send(record, Timeout::After(Duration::from_secs(5)))
@@ -205,6 +354,21 @@ impl EventProducer {
             }
         }
     }
+
+    /// Publish a raw game command to the topics `GAMES_COMMANDS_PARTITIONING`
+    /// routes it to (see `routing`); `key` is the room_id
+    pub async fn send_games_command(
+        &self,
+        game_type: Option<&str>,
+        key: &str,
+        payload: &[u8],
+    ) -> Result<(), EventPublishError> {
+        for route in super::routing::games_routing().routes(game_type, key) {
+            self.send_raw(&route.topic, Some(&route.key), payload)
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 /// Errors that can occur during event publishing
@@ -212,6 +376,7 @@ impl EventProducer {
 pub enum EventPublishError {
     Serialization(String),
     Kafka(String),
+    TooLarge { size: usize, max: usize },
     Unknown,
 }
 
@@ -220,6 +385,9 @@ impl std::fmt::Display for EventPublishError {
         match self {
             EventPublishError::Serialization(e) => write!(f, "Serialization error: {}", e),
             EventPublishError::Kafka(e) => write!(f, "Kafka error: {}", e),
+            EventPublishError::TooLarge { size, max } => {
+                write!(f, "Payload of {} bytes exceeds the {} byte limit", size, max)
+            }
             EventPublishError::Unknown => write!(f, "Unknown error"),
         }
     }
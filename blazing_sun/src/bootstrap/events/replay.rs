@@ -0,0 +1,580 @@
+//! Event replay
+//!
+//! Re-consumes a topic into one handler to rebuild the state it derives
+//! (analytics, the lobby room list, player stats) after a projection was
+//! corrupted or lost. A replay reads in its own consumer group, assigns the
+//! partitions itself and never commits, so the live consumer groups keep their
+//! offsets and a replay can be rerun from any point.
+//!
+//! The replay starts at the beginning of the retained log, an offset or a point
+//! in time, and stops at the end offsets seen when it started: events published
+//! meanwhile are handled by the live consumer. Handlers must be idempotent for
+//! the replayed range, which the projection handlers are (they upsert).
+//!
+//! ```rust,ignore
+//! let options = ReplayOptions::new(topic::CHECKOUT_FINISHED)
+//!     .start("2026-10-01T00:00:00Z".parse()?)
+//!     .rate_per_second(200);
+//! let progress = EventReplay::new(handler, options)?
+//!     .run(|progress| println!("{}", progress))
+//!     .await?;
+//! ```
+
+use super::consumer::{gateway_event, is_raw_json_topic, EventHandler, EventHandlerError};
+use super::topics::consumer_groups;
+use super::types::DomainEvent;
+use crate::config::KafkaConfig;
+use chrono::{DateTime, Utc};
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::error::KafkaError;
+use rdkafka::{Message, Offset, TopicPartitionList};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Timeout of metadata, watermark and offset lookups
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Attempts of an event whose handler keeps returning a retryable error
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Pause before a retryable event is handled again
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Where a replay starts in each partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayStart {
+    /// Oldest message still retained
+    Beginning,
+    /// This offset, clamped to the retained range of each partition
+    Offset(i64),
+    /// First message published at or after this time (epoch milliseconds)
+    Timestamp(i64),
+}
+
+impl FromStr for ReplayStart {
+    type Err = String;
+
+    /// `beginning`, an offset or an RFC 3339 time
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "beginning" {
+            return Ok(Self::Beginning);
+        }
+        if let Ok(offset) = value.parse::<i64>() {
+            return if offset < 0 {
+                Err("offset must not be negative".to_string())
+            } else {
+                Ok(Self::Offset(offset))
+            };
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| Self::Timestamp(time.with_timezone(&Utc).timestamp_millis()))
+            .map_err(|_| {
+                format!(
+                    "'{}' is not 'beginning', an offset or an RFC 3339 time",
+                    value
+                )
+            })
+    }
+}
+
+/// What to replay and how fast
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    pub topic: String,
+    pub start: ReplayStart,
+    /// Only this partition (all partitions if None)
+    pub partition: Option<i32>,
+    /// Most events handled per second (unlimited if None)
+    pub rate_per_second: Option<u32>,
+    /// Decode and count the events without handling them
+    pub dry_run: bool,
+    /// How often progress is reported
+    pub progress_interval: Duration,
+    /// Stop when no message arrives for this long (e.g. the end offsets were
+    /// compacted or deleted away)
+    pub idle_timeout: Duration,
+}
+
+impl ReplayOptions {
+    pub fn new(topic: &str) -> Self {
+        Self {
+            topic: topic.to_string(),
+            start: ReplayStart::Beginning,
+            partition: None,
+            rate_per_second: None,
+            dry_run: false,
+            progress_interval: Duration::from_secs(5),
+            idle_timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn start(mut self, start: ReplayStart) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn partition(mut self, partition: i32) -> Self {
+        self.partition = Some(partition);
+        self
+    }
+
+    pub fn rate_per_second(mut self, rate: u32) -> Self {
+        self.rate_per_second = Some(rate).filter(|rate| *rate > 0);
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// Errors that stop a replay before it starts
+#[derive(Debug)]
+pub enum ReplayError {
+    Kafka(KafkaError),
+    /// The topic does not exist or has no such partition
+    UnknownTopic(String),
+    /// The handler does not consume the topic
+    TopicNotHandled {
+        handler: &'static str,
+        topic: String,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Kafka(e) => write!(f, "Kafka error: {}", e),
+            ReplayError::UnknownTopic(topic) => write!(f, "Unknown topic or partition: {}", topic),
+            ReplayError::TopicNotHandled { handler, topic } => {
+                write!(
+                    f,
+                    "Handler '{}' does not consume topic '{}'",
+                    handler, topic
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<KafkaError> for ReplayError {
+    fn from(e: KafkaError) -> Self {
+        ReplayError::Kafka(e)
+    }
+}
+
+/// Position of a partition in its replayed range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionProgress {
+    /// Next offset to replay
+    pub position: i64,
+    /// End offset seen when the replay started (exclusive)
+    pub end: i64,
+}
+
+impl PartitionProgress {
+    pub fn remaining(&self) -> i64 {
+        (self.end - self.position).max(0)
+    }
+}
+
+/// Counters of a replay
+#[derive(Debug, Clone, Default)]
+pub struct ReplayProgress {
+    pub partitions: BTreeMap<i32, PartitionProgress>,
+    /// Handled, or decoded in a dry run
+    pub handled: u64,
+    /// The handler skipped the event
+    pub skipped: u64,
+    /// The handler failed, or still returned a retryable error after
+    /// `MAX_ATTEMPTS`
+    pub failed: u64,
+    /// Neither a `DomainEvent` nor JSON
+    pub undecodable: u64,
+}
+
+impl ReplayProgress {
+    /// Messages left before the end offsets
+    pub fn remaining(&self) -> i64 {
+        self.partitions
+            .values()
+            .map(PartitionProgress::remaining)
+            .sum()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Record that `offset` of `partition` was replayed. Returns false for
+    /// messages past the replayed range.
+    fn advance(&mut self, partition: i32, offset: i64) -> bool {
+        match self.partitions.get_mut(&partition) {
+            Some(progress) if offset < progress.end => {
+                progress.position = progress.position.max(offset + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ReplayProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "handled {}, skipped {}, failed {}, undecodable {}, remaining {}",
+            self.handled,
+            self.skipped,
+            self.failed,
+            self.undecodable,
+            self.remaining()
+        )
+    }
+}
+
+/// Spaces events evenly to stay under a rate
+struct Pacer {
+    interval: Option<Duration>,
+    next: Option<Instant>,
+}
+
+impl Pacer {
+    fn new(rate_per_second: Option<u32>) -> Self {
+        Self {
+            interval: rate_per_second.map(|rate| Duration::from_secs(1) / rate.max(1)),
+            next: None,
+        }
+    }
+
+    /// How long to wait at `now` before the next event
+    fn delay(&mut self, now: Instant) -> Duration {
+        let Some(interval) = self.interval else {
+            return Duration::ZERO;
+        };
+        // A slow handler does not earn a burst afterwards
+        let slot = self.next.map_or(now, |next| next.max(now));
+        self.next = Some(slot + interval);
+        slot - now
+    }
+
+    async fn wait(&mut self) {
+        let delay = self.delay(Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Isolated consumer group of a replay into `handler`
+pub fn replay_group(handler: &str) -> String {
+    format!(
+        "{}-{}-{}",
+        consumer_groups::REPLAY,
+        handler,
+        Utc::now().timestamp_millis()
+    )
+}
+
+/// Decode a message the way the live consumer does
+fn decode(topic: &str, payload: &[u8]) -> Option<DomainEvent> {
+    if is_raw_json_topic(topic) {
+        serde_json::from_slice(payload).ok().map(gateway_event)
+    } else {
+        DomainEvent::from_bytes(payload).ok()
+    }
+}
+
+/// Replay of one topic into one handler
+pub struct EventReplay {
+    consumer: StreamConsumer,
+    handler: Arc<dyn EventHandler>,
+    options: ReplayOptions,
+}
+
+impl EventReplay {
+    pub fn new(
+        handler: Arc<dyn EventHandler>,
+        options: ReplayOptions,
+    ) -> Result<Self, ReplayError> {
+        if !handler.topics().contains(&options.topic.as_str()) {
+            return Err(ReplayError::TopicNotHandled {
+                handler: handler.name(),
+                topic: options.topic,
+            });
+        }
+
+        let mut config = ClientConfig::new();
+        for (key, value) in KafkaConfig::broker_settings() {
+            config.set(key, value);
+        }
+
+        let consumer: StreamConsumer = config
+            .set("group.id", replay_group(handler.name()))
+            .set("client.id", format!("{}-replay", KafkaConfig::client_id()))
+            .set("enable.auto.commit", "false")
+            .set("enable.auto.offset.store", "false")
+            .set("auto.offset.reset", "earliest")
+            .set("fetch.wait.max.ms", "500")
+            .create()?;
+
+        Ok(Self {
+            consumer,
+            handler,
+            options,
+        })
+    }
+
+    /// Replay up to the current end offsets, calling `report` every
+    /// `progress_interval` and once at the end
+    pub async fn run<F>(&self, mut report: F) -> Result<ReplayProgress, ReplayError>
+    where
+        F: FnMut(&ReplayProgress),
+    {
+        let mut progress = self.plan()?;
+        let topic = self.options.topic.as_str();
+
+        info!(
+            handler = %self.handler.name(),
+            topic = %topic,
+            dry_run = self.options.dry_run,
+            remaining = progress.remaining(),
+            "Starting event replay"
+        );
+
+        if progress.is_done() {
+            report(&progress);
+            return Ok(progress);
+        }
+
+        let mut assignment = TopicPartitionList::new();
+        for (partition, range) in &progress.partitions {
+            if range.remaining() > 0 {
+                assignment.add_partition_offset(
+                    topic,
+                    *partition,
+                    Offset::Offset(range.position),
+                )?;
+            }
+        }
+        self.consumer.assign(&assignment)?;
+
+        let mut pacer = Pacer::new(self.options.rate_per_second);
+        let mut last_report = Instant::now();
+
+        while !progress.is_done() {
+            let msg =
+                match tokio::time::timeout(self.options.idle_timeout, self.consumer.recv()).await {
+                    Ok(Ok(msg)) => msg,
+                    Ok(Err(e)) => {
+                        warn!(topic = %topic, error = %e, "Replay consumer error");
+                        continue;
+                    }
+                    Err(_) => {
+                        warn!(
+                            topic = %topic,
+                            remaining = progress.remaining(),
+                            "No message before the replay idle timeout, stopping"
+                        );
+                        break;
+                    }
+                };
+
+            if !progress.advance(msg.partition(), msg.offset()) {
+                continue;
+            }
+
+            match msg.payload().and_then(|payload| decode(topic, payload)) {
+                None => progress.undecodable += 1,
+                Some(_) if self.options.dry_run => progress.handled += 1,
+                Some(event) => {
+                    pacer.wait().await;
+                    match self.handle(&event).await {
+                        Ok(()) => progress.handled += 1,
+                        Err(EventHandlerError::Skip) => progress.skipped += 1,
+                        Err(e) => {
+                            warn!(
+                                partition = %msg.partition(),
+                                offset = %msg.offset(),
+                                event_id = %event.id,
+                                error = %e,
+                                "Replayed event failed"
+                            );
+                            progress.failed += 1;
+                        }
+                    }
+                }
+            }
+
+            if last_report.elapsed() >= self.options.progress_interval {
+                report(&progress);
+                last_report = Instant::now();
+            }
+        }
+
+        self.consumer.unassign()?;
+        info!(
+            handler = %self.handler.name(),
+            topic = %topic,
+            "Event replay finished: {}",
+            progress
+        );
+        report(&progress);
+        Ok(progress)
+    }
+
+    /// Replayed range of every partition
+    fn plan(&self) -> Result<ReplayProgress, ReplayError> {
+        let topic = self.options.topic.as_str();
+        let metadata = self.consumer.fetch_metadata(Some(topic), LOOKUP_TIMEOUT)?;
+        let partitions: Vec<i32> = metadata
+            .topics()
+            .iter()
+            .filter(|t| t.name() == topic && t.error().is_none())
+            .flat_map(|t| t.partitions().iter().map(|p| p.id()))
+            .filter(|id| self.options.partition.is_none_or(|only| only == *id))
+            .collect();
+        if partitions.is_empty() {
+            return Err(ReplayError::UnknownTopic(match self.options.partition {
+                Some(partition) => format!("{}[{}]", topic, partition),
+                None => topic.to_string(),
+            }));
+        }
+
+        let mut watermarks = BTreeMap::new();
+        for partition in &partitions {
+            let range = self
+                .consumer
+                .fetch_watermarks(topic, *partition, LOOKUP_TIMEOUT)?;
+            watermarks.insert(*partition, range);
+        }
+
+        let mut starts = BTreeMap::new();
+        match self.options.start {
+            ReplayStart::Beginning => {
+                for (partition, (low, _)) in &watermarks {
+                    starts.insert(*partition, *low);
+                }
+            }
+            ReplayStart::Offset(offset) => {
+                for (partition, (low, high)) in &watermarks {
+                    starts.insert(*partition, offset.clamp(*low, *high));
+                }
+            }
+            ReplayStart::Timestamp(millis) => {
+                let mut times = TopicPartitionList::new();
+                for partition in &partitions {
+                    times.add_partition_offset(topic, *partition, Offset::Offset(millis))?;
+                }
+                let offsets = self.consumer.offsets_for_times(times, LOOKUP_TIMEOUT)?;
+                for element in offsets.elements() {
+                    let (low, high) = watermarks[&element.partition()];
+                    let start = match element.offset() {
+                        Offset::Offset(offset) => offset.clamp(low, high),
+                        // Nothing published since then
+                        _ => high,
+                    };
+                    starts.insert(element.partition(), start);
+                }
+            }
+        }
+
+        let mut progress = ReplayProgress::default();
+        for (partition, (_, high)) in watermarks {
+            progress.partitions.insert(
+                partition,
+                PartitionProgress {
+                    position: starts.get(&partition).copied().unwrap_or(high),
+                    end: high,
+                },
+            );
+        }
+        Ok(progress)
+    }
+
+    /// Handle an event, retrying retryable errors
+    async fn handle(&self, event: &DomainEvent) -> Result<(), EventHandlerError> {
+        let mut attempt = 1;
+        loop {
+            match self.handler.handle(event).await {
+                Err(EventHandlerError::Retryable(e)) if attempt < MAX_ATTEMPTS => {
+                    warn!(event_id = %event.id, attempt, error = %e, "Retrying replayed event");
+                    attempt += 1;
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_parses_beginning_offsets_and_times() {
+        assert_eq!("beginning".parse(), Ok(ReplayStart::Beginning));
+        assert_eq!("1200".parse(), Ok(ReplayStart::Offset(1200)));
+        assert_eq!(
+            "2026-10-01T00:00:00+02:00".parse(),
+            Ok(ReplayStart::Timestamp(1_790_805_600_000))
+        );
+        assert!("-1".parse::<ReplayStart>().is_err());
+        assert!("yesterday".parse::<ReplayStart>().is_err());
+    }
+
+    #[test]
+    fn pacer_spaces_events_without_bursting_after_a_stall() {
+        let mut pacer = Pacer::new(Some(4));
+        let now = Instant::now();
+        assert_eq!(pacer.delay(now), Duration::ZERO);
+        assert_eq!(pacer.delay(now), Duration::from_millis(250));
+        assert_eq!(pacer.delay(now), Duration::from_millis(500));
+
+        // A slow handler: the next slot starts from now, not from the backlog
+        let later = now + Duration::from_secs(10);
+        assert_eq!(pacer.delay(later), Duration::ZERO);
+        assert_eq!(pacer.delay(later), Duration::from_millis(250));
+
+        let mut unlimited = Pacer::new(None);
+        assert_eq!(unlimited.delay(now), Duration::ZERO);
+        assert_eq!(unlimited.delay(now), Duration::ZERO);
+    }
+
+    #[test]
+    fn progress_ends_at_the_planned_offsets() {
+        let mut progress = ReplayProgress::default();
+        progress.partitions.insert(
+            0,
+            PartitionProgress {
+                position: 8,
+                end: 10,
+            },
+        );
+        progress.partitions.insert(
+            1,
+            PartitionProgress {
+                position: 5,
+                end: 5,
+            },
+        );
+        assert_eq!(progress.remaining(), 2);
+
+        assert!(progress.advance(0, 8));
+        assert!(!progress.is_done());
+        assert!(progress.advance(0, 9));
+        assert!(progress.is_done());
+
+        // Published after the replay started, or an unassigned partition
+        assert!(!progress.advance(0, 10));
+        assert!(!progress.advance(2, 0));
+    }
+}
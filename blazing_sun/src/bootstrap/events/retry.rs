@@ -0,0 +1,92 @@
+//! Event handler retries
+//!
+//! A handler returning `EventHandlerError::Retryable` is called again with
+//! exponential backoff until its `RetryPolicy` runs out of attempts; the
+//! message then goes to the dead letter topic of its source (see
+//! `dead_letter`), like one the handler rejected with `Fatal`. Handlers use
+//! the `KAFKA_HANDLER_*` policy unless they override
+//! `EventHandler::retry_policy`.
+
+use crate::config::KafkaConfig;
+use std::time::Duration;
+
+/// How often and how patiently a handler is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Calls of the handler, the first one included
+    pub max_attempts: u32,
+    /// Pause after the first failed attempt
+    pub initial_backoff: Duration,
+    /// Longest pause between two attempts
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+            max_backoff: max_backoff.max(initial_backoff),
+        }
+    }
+
+    /// Policy of `KAFKA_HANDLER_MAX_ATTEMPTS` and `KAFKA_HANDLER_RETRY_BACKOFF_*`
+    pub fn from_config() -> Self {
+        Self::new(
+            KafkaConfig::handler_max_attempts(),
+            Duration::from_millis(KafkaConfig::handler_retry_backoff_ms()),
+            Duration::from_millis(KafkaConfig::handler_retry_backoff_max_ms()),
+        )
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Pause after failed attempt `attempt` (1-based): the initial backoff,
+    /// doubled per attempt and capped at the max
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(31);
+        self.initial_backoff
+            .saturating_mul(1 << doublings)
+            .min(self.max_backoff)
+    }
+
+    /// Whether a retryable failure of attempt `attempt` is tried again
+    pub fn retries_after(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_max() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(200), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn attempts_stop_at_the_policy_limit() {
+        let policy = RetryPolicy::new(3, Duration::ZERO, Duration::ZERO);
+        assert!(policy.retries_after(1));
+        assert!(policy.retries_after(2));
+        assert!(!policy.retries_after(3));
+
+        // At least the first call is always made
+        assert!(!policy.max_attempts(0).retries_after(1));
+    }
+}
@@ -0,0 +1,48 @@
+//! Game command routing
+//!
+//! The topics and keys of game commands come from the shared `games_routing`
+//! crate, which the gateway uses as well; this module reads the settings of
+//! this instance (`GAMES_COMMANDS_PARTITIONING`, `GAMES_COMMANDS_MIGRATION`
+//! and the region).
+
+use crate::config::games::GamesConfig;
+use crate::config::KafkaConfig;
+use games_routing::{GamesRouting, Migration, Partitioning};
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+pub use games_routing::game_type_of;
+
+static GAMES_ROUTING: Lazy<GamesRouting> = Lazy::new(|| {
+    let partitioning =
+        Partitioning::parse(KafkaConfig::games_partitioning()).unwrap_or_else(|| {
+            warn!(
+                value = %KafkaConfig::games_partitioning(),
+                "Unknown GAMES_COMMANDS_PARTITIONING, using room"
+            );
+            Partitioning::Room
+        });
+    let migration = Migration::parse(KafkaConfig::games_migration()).unwrap_or_else(|| {
+        warn!(
+            value = %KafkaConfig::games_migration(),
+            "Unknown GAMES_COMMANDS_MIGRATION, using none"
+        );
+        Migration::None
+    });
+
+    GamesRouting::new(partitioning, migration, GamesConfig::region())
+});
+
+/// Game command routing of this instance's region
+pub fn games_routing() -> &'static GamesRouting {
+    &GAMES_ROUTING
+}
+
+/// Game command topics this instance's command handler subscribes to
+pub fn region_games_command_topics() -> Vec<&'static str> {
+    GAMES_ROUTING
+        .subscribed_topics()
+        .iter()
+        .map(String::as_str)
+        .collect()
+}
@@ -13,9 +13,13 @@
 /// - games.commands: Game commands from WebSocket gateway
 /// - games.events: Game events to send to WebSocket gateway
 /// - gateway.presence: Presence updates from WebSocket gateway
+/// - <topic>.dlq: Messages a handler of <topic> failed for good (see `dead_letter`)
 
 /// Main event topics
 pub mod topic {
+    use crate::config::games::{GamesConfig, DEFAULT_REGION};
+    use once_cell::sync::Lazy;
+
     /// User domain events (user.created, user.updated, user.deleted, user.activated)
     pub const USER_EVENTS: &str = "user.events";
 
@@ -31,8 +35,8 @@ pub mod topic {
     /// System events (health checks, metrics, errors)
     pub const SYSTEM_EVENTS: &str = "system.events";
 
-    /// Dead letter topic for failed event processing
-    pub const DEAD_LETTER: &str = "events.dead_letter";
+    /// Suffix of the dead letter topic of each source topic
+    pub const DEAD_LETTER_SUFFIX: &str = ".dlq";
 
     /// Checkout request topic (user_id, amount_cents, success_url, cancel_url)
     pub const CHECKOUT_REQUESTS: &str = "checkout.requests";
@@ -40,6 +44,12 @@ pub mod topic {
     /// Checkout finished topic (session_created/success/failed status)
     pub const CHECKOUT_FINISHED: &str = "checkout.finished";
 
+    /// Stripe disputes of paid checkouts (opened/won/lost status)
+    pub const CHECKOUT_DISPUTES: &str = "checkout.disputes";
+
+    /// Premium subscriptions (SubscriptionActivated/SubscriptionLapsed)
+    pub const CHECKOUT_SUBSCRIPTIONS: &str = "checkout.subscriptions";
+
     // === WebSocket Gateway Topics ===
 
     /// Chat commands from WebSocket gateway (send_message, mark_read, etc.)
@@ -77,6 +87,61 @@ pub mod topic {
     /// Consumed by checkout service to create refund transaction records
     pub const TIC_TAC_TOE_MATCH_CANCELLED: &str = "tic_tac_toe.match_cancelled";
 
+    /// Game commands topic for a region ("games.commands" for the default region,
+    /// "games.commands.<region>" otherwise)
+    pub fn games_commands_for(region: &str) -> String {
+        regional(GAMES_COMMANDS, region)
+    }
+
+    /// Game events topic for a region ("games.events" for the default region,
+    /// "games.events.<region>" otherwise)
+    pub fn games_events_for(region: &str) -> String {
+        regional(GAMES_EVENTS, region)
+    }
+
+    /// Game commands topic of this instance's region
+    pub fn region_games_commands() -> &'static str {
+        &REGION_GAMES_COMMANDS
+    }
+
+    /// Game events topic of this instance's region
+    pub fn region_games_events() -> &'static str {
+        &REGION_GAMES_EVENTS
+    }
+
+    /// Whether a topic carries game commands (any region)
+    pub fn is_games_commands(topic: &str) -> bool {
+        topic == GAMES_COMMANDS || topic.starts_with("games.commands.")
+    }
+
+    /// Whether a topic carries game events (any region)
+    pub fn is_games_events(topic: &str) -> bool {
+        topic == GAMES_EVENTS || topic.starts_with("games.events.")
+    }
+
+    /// Dead letter topic of a source topic ("auth.events" -> "auth.events.dlq")
+    pub fn dead_letter_for(source: &str) -> String {
+        format!("{}{}", source, DEAD_LETTER_SUFFIX)
+    }
+
+    /// Whether a topic holds dead letters
+    pub fn is_dead_letter(topic: &str) -> bool {
+        topic.ends_with(DEAD_LETTER_SUFFIX)
+    }
+
+    fn regional(base: &str, region: &str) -> String {
+        if region == DEFAULT_REGION {
+            base.to_string()
+        } else {
+            format!("{}.{}", base, region)
+        }
+    }
+
+    static REGION_GAMES_COMMANDS: Lazy<String> =
+        Lazy::new(|| games_commands_for(GamesConfig::region()));
+
+    static REGION_GAMES_EVENTS: Lazy<String> = Lazy::new(|| games_events_for(GamesConfig::region()));
+
     /// Get all topics for initialization
     pub fn all() -> Vec<&'static str> {
         vec![
@@ -85,9 +150,10 @@ pub mod topic {
             TRANSACTION_EVENTS,
             CATEGORY_EVENTS,
             SYSTEM_EVENTS,
-            DEAD_LETTER,
             CHECKOUT_REQUESTS,
             CHECKOUT_FINISHED,
+            CHECKOUT_DISPUTES,
+            CHECKOUT_SUBSCRIPTIONS,
             CHAT_COMMANDS,
             CHAT_EVENTS,
             GAMES_COMMANDS,
@@ -115,4 +181,10 @@ pub mod consumer_groups {
 
     /// Audit log consumer group
     pub const AUDIT: &str = "blazing-sun-audit";
+
+    /// Prefix of the throwaway groups of event replays (see `events::replay`)
+    pub const REPLAY: &str = "blazing-sun-replay";
+
+    /// Group of dead letter browsing (assigned partitions, never committed)
+    pub const DEAD_LETTERS: &str = "blazing-sun-dead-letters";
 }
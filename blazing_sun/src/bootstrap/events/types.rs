@@ -15,6 +15,8 @@ pub enum UserEventType {
     PasswordChanged,
     ProfileUpdated,
     BalanceUpdated,
+    RoleChanged,
+    DataExportReady,
 }
 
 impl fmt::Display for UserEventType {
@@ -28,6 +30,8 @@ impl fmt::Display for UserEventType {
             UserEventType::PasswordChanged => "user.password_changed",
             UserEventType::ProfileUpdated => "user.profile_updated",
             UserEventType::BalanceUpdated => "user.balance_updated",
+            UserEventType::RoleChanged => "user.role_changed",
+            UserEventType::DataExportReady => "user.data_export_ready",
         };
         write!(f, "{}", s)
     }
@@ -44,6 +48,11 @@ pub enum AuthEventType {
     PasswordResetCompleted,
     AccountLocked,
     AccountUnlocked,
+    ImpersonationStarted,
+    ImpersonationEnded,
+    TwoFactorEnabled,
+    TwoFactorDisabled,
+    TwoFactorChallengeFailed,
 }
 
 impl fmt::Display for AuthEventType {
@@ -56,6 +65,11 @@ impl fmt::Display for AuthEventType {
             AuthEventType::PasswordResetCompleted => "auth.password_reset_completed",
             AuthEventType::AccountLocked => "auth.account_locked",
             AuthEventType::AccountUnlocked => "auth.account_unlocked",
+            AuthEventType::ImpersonationStarted => "auth.impersonation_started",
+            AuthEventType::ImpersonationEnded => "auth.impersonation_ended",
+            AuthEventType::TwoFactorEnabled => "auth.two_factor_enabled",
+            AuthEventType::TwoFactorDisabled => "auth.two_factor_disabled",
+            AuthEventType::TwoFactorChallengeFailed => "auth.two_factor_challenge_failed",
         };
         write!(f, "{}", s)
     }
@@ -70,6 +84,7 @@ pub enum TransactionEventType {
     Deleted,
     Categorized,
     AmountAdjusted,
+    TransferCompleted,
 }
 
 impl fmt::Display for TransactionEventType {
@@ -80,6 +95,7 @@ impl fmt::Display for TransactionEventType {
             TransactionEventType::Deleted => "transaction.deleted",
             TransactionEventType::Categorized => "transaction.categorized",
             TransactionEventType::AmountAdjusted => "transaction.amount_adjusted",
+            TransactionEventType::TransferCompleted => "transaction.transfer_completed",
         };
         write!(f, "{}", s)
     }
@@ -209,12 +225,22 @@ pub struct EventMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_id: Option<String>,
 
+    /// Admin acting as the user when the event was triggered (see `app::impersonation`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<i64>,
+
+    /// Impersonation session the event was triggered in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonation_id: Option<String>,
+
     /// Schema version for payload compatibility
     pub schema_version: String,
 }
 
 impl Default for EventMetadata {
     fn default() -> Self {
+        // Set while a request made with an impersonation token is being handled
+        let impersonation = crate::app::impersonation::current();
         Self {
             correlation_id: None,
             causation_id: None,
@@ -222,7 +248,10 @@ impl Default for EventMetadata {
             source: "blazing-sun-api".to_string(),
             ip_address: None,
             user_agent: None,
-            request_id: None,
+            // Set while an HTTP request, MQ job or consumed event is being handled
+            request_id: logging::request_id::current(),
+            impersonator_id: impersonation.as_ref().map(|i| i.impersonator_id),
+            impersonation_id: impersonation.map(|i| i.session_id.to_string()),
             schema_version: "1.0".to_string(),
         }
     }
@@ -426,6 +455,63 @@ pub mod payloads {
         pub reason: Option<String>,
     }
 
+    /// Payload for user balance updated event from an approved admin adjustment
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BalanceAdjustedPayload {
+        pub balance: i64,
+        pub change: i64,
+        pub source: String,
+        pub adjustment_id: i64,
+        pub reason: String,
+        pub requested_by: Option<i64>,
+        pub approved_by: i64,
+    }
+
+    /// Payload for a completed user-to-user coin transfer
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BalanceTransferredPayload {
+        pub transfer_id: i64,
+        pub sender_id: i64,
+        pub sender_name: String,
+        pub recipient_id: i64,
+        pub recipient_name: String,
+        pub amount_cents: i64,
+        pub memo: Option<String>,
+    }
+
+    /// Payload for user balance updated event of one side of a transfer
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct TransferBalanceUpdatedPayload {
+        pub balance: i64,
+        /// Negative for the sender, positive for the recipient
+        pub change: i64,
+        pub source: String,
+        pub transfer_id: i64,
+        pub counterparty_id: i64,
+        pub counterparty_name: String,
+        pub memo: Option<String>,
+    }
+
+    /// Payload for user role changed event, the audit record of a role assignment
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RoleChangedPayload {
+        pub previous_role: String,
+        pub role: String,
+        pub previous_permissions: i16,
+        pub permissions: i16,
+        pub changed_by: i64,
+    }
+
+    /// Payload for user data export ready event
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct DataExportReadyPayload {
+        pub export_id: i64,
+        /// Signed link that works until `expires_at`, no JWT needed
+        pub download_url: String,
+        pub size_bytes: i64,
+        pub expires_at: chrono::DateTime<chrono::Utc>,
+    }
+
     /// Payload for auth sign in event
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct AuthSignInPayload {
@@ -434,6 +520,27 @@ pub mod payloads {
         pub failure_reason: Option<String>,
     }
 
+    /// Payload for auth impersonation started/ended events
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ImpersonationPayload {
+        pub session_id: uuid::Uuid,
+        pub admin_id: i64,
+        pub user_id: i64,
+        pub reason: String,
+        pub expires_at: chrono::DateTime<chrono::Utc>,
+    }
+
+    /// Payload for auth two-factor enabled/disabled/challenge failed events
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct TwoFactorPayload {
+        /// `totp` or `recovery_code`, when a code was entered
+        pub method: Option<String>,
+        /// Why a challenge failed (`invalid_code`, `too_many_attempts`, ...)
+        pub failure_reason: Option<String>,
+        /// Recovery codes left after the event
+        pub recovery_codes_remaining: Option<i64>,
+    }
+
     /// Payload for transaction created event
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct TransactionCreatedPayload {
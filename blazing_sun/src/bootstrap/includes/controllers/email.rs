@@ -43,6 +43,7 @@ pub enum EmailTemplate {
     EmailChangeVerifyOld,
     EmailChangeVerifyNew,
     EmailChangeSuccess,
+    Notification,
 }
 
 impl EmailTemplate {
@@ -58,6 +59,7 @@ impl EmailTemplate {
             EmailTemplate::EmailChangeVerifyOld => "email_change_verify_old.html",
             EmailTemplate::EmailChangeVerifyNew => "email_change_verify_new.html",
             EmailTemplate::EmailChangeSuccess => "email_change_success.html",
+            EmailTemplate::Notification => "notification.html",
         }
     }
 
@@ -73,6 +75,7 @@ impl EmailTemplate {
             EmailTemplate::EmailChangeVerifyOld => "Verify Your Email Change",
             EmailTemplate::EmailChangeVerifyNew => "Confirm Your New Email Address",
             EmailTemplate::EmailChangeSuccess => "Email Changed Successfully",
+            EmailTemplate::Notification => "You Have a New Notification",
         }
     }
 }
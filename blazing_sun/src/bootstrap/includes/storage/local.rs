@@ -4,7 +4,6 @@
 
 use super::{StorageDriver, StorageDriverType, StorageError, StoredFile, Visibility};
 use async_trait::async_trait;
-use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
@@ -61,51 +60,6 @@ impl LocalStorageDriver {
         }
     }
 
-    /// Generate a unique filename
-    fn generate_filename(extension: &str) -> String {
-        let uuid = Uuid::new_v4();
-        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        format!("{}_{}.{}", timestamp, uuid, extension)
-    }
-
-    /// Extract file extension
-    fn get_extension(filename: &str) -> Option<String> {
-        Path::new(filename)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.to_lowercase())
-    }
-
-    /// Calculate SHA256 checksum
-    fn checksum(data: &[u8]) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        hex::encode(hasher.finalize())
-    }
-
-    /// Validate file extension
-    fn validate_extension(extension: &str) -> Result<(), StorageError> {
-        if !UploadConfig::is_type_allowed(extension) {
-            return Err(StorageError::InvalidExtension {
-                extension: extension.to_string(),
-                allowed: UploadConfig::allowed_types().clone(),
-            });
-        }
-        Ok(())
-    }
-
-    /// Validate file size
-    fn validate_size(size: u64) -> Result<(), StorageError> {
-        let max = UploadConfig::max_file_size();
-        if size > max {
-            return Err(StorageError::FileTooLarge {
-                max_size: max,
-                actual_size: size,
-            });
-        }
-        Ok(())
-    }
-
     /// Build URL with optional subfolder support
     fn url_with_subfolder(&self, path: &str, visibility: Visibility, subfolder: &str) -> String {
         // Extract just the filename from the path
@@ -155,14 +109,14 @@ impl StorageDriver for LocalStorageDriver {
         subfolder: &str,
     ) -> Result<StoredFile, StorageError> {
         // Validate size
-        Self::validate_size(data.len() as u64)?;
+        super::validate_size(data.len() as u64)?;
 
         // Get and validate extension
-        let extension = Self::get_extension(filename).ok_or(StorageError::InvalidFileName)?;
-        Self::validate_extension(&extension)?;
+        let extension = super::get_extension(filename).ok_or(StorageError::InvalidFileName)?;
+        super::validate_extension(&extension)?;
 
         // Generate unique filename
-        let stored_name = Self::generate_filename(&extension);
+        let stored_name = super::generate_filename(&extension);
 
         // Get storage path - include subfolder if provided
         let base_dir = self.visibility_path(visibility);
@@ -182,7 +136,7 @@ impl StorageDriver for LocalStorageDriver {
         file.flush().await?;
 
         // Calculate checksum
-        let checksum = Self::checksum(data);
+        let checksum = super::checksum(data);
 
         // Guess MIME type
         let mime_type = mime_guess::from_path(filename)
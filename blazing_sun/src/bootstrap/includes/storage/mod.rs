@@ -2,8 +2,7 @@
 //!
 //! Provides a unified interface for file storage that can be backed by:
 //! - Local filesystem (default)
-//! - Amazon S3 (future)
-//! - Other cloud providers (future)
+//! - Amazon S3 and S3-compatible services (MinIO, R2, ...)
 //!
 //! The driver is selected via STORAGE_DRIVER environment variable.
 
@@ -11,7 +10,8 @@ pub mod local;
 pub mod s3;
 
 use async_trait::async_trait;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 
 pub use local::LocalStorageDriver;
 pub use s3::S3StorageDriver;
@@ -152,6 +152,53 @@ impl From<std::io::Error> for StorageError {
     }
 }
 
+/// Generate a unique filename
+fn generate_filename(extension: &str) -> String {
+    let uuid = uuid::Uuid::new_v4();
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    format!("{}_{}.{}", timestamp, uuid, extension)
+}
+
+/// Extract file extension
+fn get_extension(filename: &str) -> Option<String> {
+    Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+}
+
+/// Calculate SHA256 checksum
+fn checksum(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+/// Validate file extension
+fn validate_extension(extension: &str) -> Result<(), StorageError> {
+    use crate::config::UploadConfig;
+
+    if !UploadConfig::is_type_allowed(extension) {
+        return Err(StorageError::InvalidExtension {
+            extension: extension.to_string(),
+            allowed: UploadConfig::allowed_types().clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Validate file size
+fn validate_size(size: u64) -> Result<(), StorageError> {
+    let max = crate::config::UploadConfig::max_file_size();
+    if size > max {
+        return Err(StorageError::FileTooLarge {
+            max_size: max,
+            actual_size: size,
+        });
+    }
+    Ok(())
+}
+
 /// Storage driver trait - implement this for new storage backends
 #[async_trait]
 pub trait StorageDriver: Send + Sync {
@@ -50,6 +50,12 @@ impl BuildResult {
 
 /// Run npm build in the specified directory
 pub fn run_build(working_dir: &Path, _timeout_secs: u64) -> Result<BuildResult, BuilderError> {
+    run_npm_build(working_dir, None)
+}
+
+/// Run npm build, writing the output to `out_dir` instead of the production
+/// assets when given
+fn run_npm_build(working_dir: &Path, out_dir: Option<&Path>) -> Result<BuildResult, BuilderError> {
     // Verify working directory exists
     if !working_dir.exists() {
         return Err(BuilderError::WorkingDirNotFound(
@@ -60,9 +66,12 @@ pub fn run_build(working_dir: &Path, _timeout_secs: u64) -> Result<BuildResult,
     let start = std::time::Instant::now();
 
     // Run npm run build
-    let output = Command::new("npm")
-        .arg("run")
-        .arg("build")
+    let mut command = Command::new("npm");
+    command.arg("run").arg("build");
+    if let Some(out_dir) = out_dir {
+        command.arg("--").arg("--outDir").arg(out_dir);
+    }
+    let output = command
         .current_dir(working_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -113,6 +122,27 @@ pub async fn run_build_async(
     }
 }
 
+/// Run npm build into `out_dir` asynchronously with timeout (used for previews)
+pub async fn run_build_into_async(
+    working_dir: &Path,
+    out_dir: &Path,
+    timeout_secs: u64,
+) -> Result<BuildResult, BuilderError> {
+    let working_dir = working_dir.to_path_buf();
+    let out_dir = out_dir.to_path_buf();
+
+    let build_future =
+        tokio::task::spawn_blocking(move || run_npm_build(&working_dir, Some(&out_dir)));
+
+    match timeout(Duration::from_secs(timeout_secs), build_future).await {
+        Ok(result) => match result {
+            Ok(build_result) => build_result,
+            Err(e) => Err(BuilderError::BuildFailed(format!("Task panicked: {}", e))),
+        },
+        Err(_) => Err(BuilderError::Timeout(timeout_secs)),
+    }
+}
+
 /// Check if npm is available
 pub fn check_npm_available() -> bool {
     Command::new("npm")
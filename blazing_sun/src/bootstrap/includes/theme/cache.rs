@@ -0,0 +1,144 @@
+//! Theme Cache
+//!
+//! Keeps the parsed SCSS variables and light/dark theme in memory. The files
+//! are parsed on the first read and again only after the cache is invalidated:
+//! by the file watcher when `_variables.scss` or `_theme.scss` change on disk,
+//! after a theme update, or on an explicit reload.
+
+use notify::{recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Parsed theme variables
+#[derive(Debug)]
+pub struct CachedTheme {
+    pub scss_variables: Value,
+    pub theme_light: Value,
+    pub theme_dark: Value,
+    /// Quoted strong ETag of the variables' JSON
+    pub etag: String,
+}
+
+impl CachedTheme {
+    pub fn new(scss_variables: Value, theme_light: Value, theme_dark: Value) -> Self {
+        let body = json!([scss_variables, theme_light, theme_dark]).to_string();
+        let digest = Sha256::digest(body.as_bytes());
+
+        Self {
+            scss_variables,
+            theme_light,
+            theme_dark,
+            etag: format!("\"{}\"", hex::encode(&digest[..16])),
+        }
+    }
+}
+
+/// Current parse; `None` until the first read after startup or an invalidation
+static CACHE: Lazy<RwLock<Option<Arc<CachedTheme>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Bumped by every invalidation so a parse that raced with a file change is
+/// not stored
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Cached theme, parsed with `load` on a miss
+pub fn get_or_load<E>(
+    load: impl FnOnce() -> Result<CachedTheme, E>,
+) -> Result<Arc<CachedTheme>, E> {
+    if let Some(theme) = CACHE.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return Ok(theme.clone());
+    }
+
+    let generation = GENERATION.load(Ordering::Acquire);
+    let theme = Arc::new(load()?);
+
+    let mut cache = CACHE.write().unwrap_or_else(|e| e.into_inner());
+    if GENERATION.load(Ordering::Acquire) == generation {
+        *cache = Some(theme.clone());
+    }
+    Ok(theme)
+}
+
+/// Drop the cached parse; the next read parses the files again
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Invalidates the cache when a watched file changes; stops watching when dropped
+pub struct ThemeWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Watch `files` for changes.
+///
+/// The parent directories are watched rather than the files themselves:
+/// editors and the updater replace files, which would end a watch on the
+/// original file.
+pub fn watch(files: &[&Path]) -> notify::Result<ThemeWatcher> {
+    let names: HashSet<OsString> = files
+        .iter()
+        .filter_map(|file| file.file_name().map(OsString::from))
+        .collect();
+
+    let mut watcher = recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) if !event.kind.is_access() && touches(&event.paths, &names) => {
+            tracing::info!("Theme files changed, invalidating theme cache");
+            invalidate();
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Theme file watcher error: {}", e),
+    })?;
+
+    let dirs: HashSet<PathBuf> = files
+        .iter()
+        .filter_map(|file| file.parent().map(Path::to_path_buf))
+        .collect();
+    for dir in &dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        tracing::info!("Watching {:?} for theme changes", dir);
+    }
+
+    Ok(ThemeWatcher { _watcher: watcher })
+}
+
+fn touches(paths: &[PathBuf], names: &HashSet<OsString>) -> bool {
+    paths
+        .iter()
+        .any(|path| path.file_name().is_some_and(|name| names.contains(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_follows_content() {
+        let a = CachedTheme::new(json!({"color-primary": "#667eea"}), json!({}), json!({}));
+        let b = CachedTheme::new(json!({"color-primary": "#667eea"}), json!({}), json!({}));
+        let c = CachedTheme::new(json!({"color-primary": "#5a6fd6"}), json!({}), json!({}));
+
+        assert_eq!(a.etag, b.etag);
+        assert_ne!(a.etag, c.etag);
+        assert!(a.etag.starts_with('"') && a.etag.ends_with('"'));
+    }
+
+    #[test]
+    fn test_only_watched_files_invalidate() {
+        let names: HashSet<OsString> = [OsString::from("_theme.scss")].into_iter().collect();
+
+        assert!(touches(
+            &[PathBuf::from("/app/src/styles/_theme.scss")],
+            &names
+        ));
+        assert!(!touches(
+            &[PathBuf::from("/app/src/styles/_theme.scss.swp")],
+            &names
+        ));
+    }
+}
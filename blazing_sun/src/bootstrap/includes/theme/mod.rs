@@ -1,20 +1,34 @@
 //! Theme Service
 //!
 //! Manages theme configuration including SCSS variables, CSS custom properties,
-//! file updates, builds, and version management.
+//! file updates, builds, and version management. Tenants can override the
+//! whitelisted CSS custom properties on top of the platform theme.
+//! Parsed variables are cached until the SCSS files change (see `cache`).
 
 pub mod builder;
+pub mod cache;
 pub mod parser;
+pub mod preview;
+pub mod tenant;
 pub mod updater;
 pub mod versioner;
 
 pub use builder::{BuildResult, BuilderError};
+pub use cache::{CachedTheme, ThemeWatcher};
 pub use parser::ParserError;
+pub use preview::{PreviewError, ThemePreview};
+pub use tenant::TenantError;
 pub use updater::{Backup, UpdaterError};
 pub use versioner::VersionerError;
 
 use crate::config::ThemeConfig;
 use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Serializes everything that writes the SCSS files or runs a build; a
+/// preview temporarily writes its variables into the shared sources
+static BUILD_LOCK: Mutex<()> = Mutex::const_new(());
 
 /// Error type for theme service operations
 #[derive(Debug, thiserror::Error)]
@@ -27,6 +41,12 @@ pub enum ThemeServiceError {
     Builder(#[from] BuilderError),
     #[error("Versioner error: {0}")]
     Versioner(#[from] VersionerError),
+    #[error("Tenant theme error: {0}")]
+    Tenant(#[from] TenantError),
+    #[error("Preview error: {0}")]
+    Preview(#[from] PreviewError),
+    #[error("Watcher error: {0}")]
+    Watcher(#[from] notify::Error),
     #[error("Validation error: {0}")]
     Validation(String),
     #[error("Rollback triggered: {0}")]
@@ -48,6 +68,36 @@ pub struct ThemeService;
 impl ThemeService {
     /// Get current theme variables from SCSS files
     pub fn get_current_variables() -> Result<(Value, Value, Value), ThemeServiceError> {
+        let theme = Self::get_cached_variables()?;
+        Ok((
+            theme.scss_variables.clone(),
+            theme.theme_light.clone(),
+            theme.theme_dark.clone(),
+        ))
+    }
+
+    /// Get current theme variables with their ETag, parsing the SCSS files
+    /// only when they changed since the last read
+    pub fn get_cached_variables() -> Result<Arc<CachedTheme>, ThemeServiceError> {
+        cache::get_or_load(Self::parse_variables)
+    }
+
+    /// Parse the SCSS files again, discarding the cached variables
+    pub fn reload_variables() -> Result<Arc<CachedTheme>, ThemeServiceError> {
+        cache::invalidate();
+        Self::get_cached_variables()
+    }
+
+    /// Invalidate the cached variables whenever the SCSS files change on disk.
+    /// The returned watcher must be kept alive.
+    pub fn watch() -> Result<ThemeWatcher, ThemeServiceError> {
+        Ok(cache::watch(&[
+            ThemeConfig::variables_file().as_path(),
+            ThemeConfig::theme_file().as_path(),
+        ])?)
+    }
+
+    fn parse_variables() -> Result<CachedTheme, ThemeServiceError> {
         let variables_path = ThemeConfig::variables_file();
         let theme_path = ThemeConfig::theme_file();
 
@@ -60,7 +110,7 @@ impl ThemeService {
         let light_json = parser::variables_to_json(&light);
         let dark_json = parser::variables_to_json(&dark);
 
-        Ok((scss_json, light_json, dark_json))
+        Ok(CachedTheme::new(scss_json, light_json, dark_json))
     }
 
     /// Validate that all variable names are in the whitelist
@@ -120,6 +170,19 @@ impl ThemeService {
         scss_variables: Option<Value>,
         theme_light: Option<Value>,
         theme_dark: Option<Value>,
+    ) -> Result<ThemeUpdateResult, ThemeServiceError> {
+        let _build = BUILD_LOCK.lock().await;
+        let result = Self::update_files_and_build(scss_variables, theme_light, theme_dark).await;
+
+        // The files were updated or rolled back; don't wait for the watcher
+        cache::invalidate();
+        result
+    }
+
+    async fn update_files_and_build(
+        scss_variables: Option<Value>,
+        theme_light: Option<Value>,
+        theme_dark: Option<Value>,
     ) -> Result<ThemeUpdateResult, ThemeServiceError> {
         tracing::info!("=== ThemeService::update_and_build STARTED ===");
         tracing::info!("scss_variables: {:?}", scss_variables.is_some());
@@ -153,51 +216,16 @@ impl ThemeService {
         tracing::info!("Backups created successfully");
 
         // 3. Update SCSS variables if provided
-        tracing::info!("Step 3: Checking SCSS variables...");
-        if let Some(scss) = &scss_variables {
-            let vars = parser::json_to_variables(scss);
-            if let Err(e) = updater::update_scss_variables(variables_path, &vars) {
-                // Rollback on error
-                let _ = backup.rollback(Some(variables_path), Some(theme_path), Some(env_path));
-                return Err(ThemeServiceError::RollbackTriggered(format!(
-                    "Failed to update SCSS variables: {}",
-                    e
-                )));
-            }
-        }
-
-        // 4. Update theme file if provided
-        tracing::info!("Step 4: Checking theme file update...");
-        tracing::info!("theme_light.is_some(): {}", theme_light.is_some());
-        tracing::info!("theme_dark.is_some(): {}", theme_dark.is_some());
-
-        if theme_light.is_some() || theme_dark.is_some() {
-            let light = theme_light
-                .as_ref()
-                .map(parser::json_to_variables)
-                .unwrap_or_default();
-            let dark = theme_dark
-                .as_ref()
-                .map(parser::json_to_variables)
-                .unwrap_or_default();
-
-            tracing::info!("Updating theme file: {:?}", theme_path);
-            tracing::info!("Light theme vars ({} entries): {:?}", light.len(), light);
-            tracing::info!("Dark theme vars ({} entries): {:?}", dark.len(), dark);
-            tracing::info!("Calling updater::update_theme_file...");
-
-            if let Err(e) = updater::update_theme_file(theme_path, &light, &dark) {
-                // Rollback on error
-                tracing::error!("Failed to update theme file: {}", e);
-                let _ = backup.rollback(Some(variables_path), Some(theme_path), Some(env_path));
-                return Err(ThemeServiceError::RollbackTriggered(format!(
-                    "Failed to update theme file: {}",
-                    e
-                )));
-            }
-            tracing::info!("Theme file updated successfully!");
-        } else {
-            tracing::info!("No theme updates provided, skipping file update");
+        tracing::info!("Step 3-4: Updating SCSS variables and theme file...");
+        if let Err(e) = Self::write_variables(
+            scss_variables.as_ref(),
+            theme_light.as_ref(),
+            theme_dark.as_ref(),
+        ) {
+            // Rollback on error
+            tracing::error!("{}", e);
+            let _ = backup.rollback(Some(variables_path), Some(theme_path), Some(env_path));
+            return Err(ThemeServiceError::RollbackTriggered(e));
         }
 
         // 5. Run npm build
@@ -258,8 +286,41 @@ impl ThemeService {
         }
     }
 
+    /// Write the given variables into `_variables.scss` and `_theme.scss`
+    fn write_variables(
+        scss_variables: Option<&Value>,
+        theme_light: Option<&Value>,
+        theme_dark: Option<&Value>,
+    ) -> Result<(), String> {
+        if let Some(scss) = scss_variables {
+            let vars = parser::json_to_variables(scss);
+            updater::update_scss_variables(ThemeConfig::variables_file(), &vars)
+                .map_err(|e| format!("Failed to update SCSS variables: {}", e))?;
+        }
+
+        if theme_light.is_some() || theme_dark.is_some() {
+            let light = theme_light
+                .map(parser::json_to_variables)
+                .unwrap_or_default();
+            let dark = theme_dark
+                .map(parser::json_to_variables)
+                .unwrap_or_default();
+
+            tracing::info!(
+                "Updating theme file ({} light, {} dark entries)",
+                light.len(),
+                dark.len()
+            );
+            updater::update_theme_file(ThemeConfig::theme_file(), &light, &dark)
+                .map_err(|e| format!("Failed to update theme file: {}", e))?;
+        }
+
+        Ok(())
+    }
+
     /// Trigger a build without updating files
     pub async fn rebuild() -> Result<ThemeUpdateResult, ThemeServiceError> {
+        let _build = BUILD_LOCK.lock().await;
         let global_path = ThemeConfig::global_page_path();
         let env_path = ThemeConfig::env_file();
         let timeout = ThemeConfig::build_timeout_secs();
@@ -290,11 +351,216 @@ impl ThemeService {
         }
     }
 
+    /// Build proposed variables into a preview directory without touching the
+    /// production assets. The variables are written into the SCSS sources for
+    /// the duration of the build only.
+    pub async fn build_preview(
+        scss_variables: Option<Value>,
+        theme_light: Option<Value>,
+        theme_dark: Option<Value>,
+        created_by: i64,
+    ) -> Result<(ThemePreview, BuildResult), ThemeServiceError> {
+        Self::validate_variables(
+            scss_variables.as_ref(),
+            theme_light.as_ref(),
+            theme_dark.as_ref(),
+        )?;
+
+        let _build = BUILD_LOCK.lock().await;
+        let variables_path = ThemeConfig::variables_file();
+        let theme_path = ThemeConfig::theme_file();
+        let global_path = ThemeConfig::global_page_path();
+        let root = ThemeConfig::preview_path();
+
+        let preview = ThemePreview::new(scss_variables, theme_light, theme_dark, created_by);
+        let out_dir = preview::preview_dir(root, &preview.token)?;
+
+        let mut backup = Backup::new(ThemeConfig::backup_path());
+        backup.create(Some(variables_path), Some(theme_path), None)?;
+
+        let build = async {
+            Self::write_variables(
+                preview.scss_variables.as_ref(),
+                preview.theme_light.as_ref(),
+                preview.theme_dark.as_ref(),
+            )
+            .map_err(ThemeServiceError::RollbackTriggered)?;
+            builder::ensure_dependencies(global_path)?;
+            Ok::<_, ThemeServiceError>(
+                builder::run_build_into_async(
+                    global_path,
+                    &out_dir,
+                    ThemeConfig::build_timeout_secs(),
+                )
+                .await?,
+            )
+        }
+        .await;
+
+        // The sources always go back to the published theme
+        let _ = backup.rollback(Some(variables_path), Some(theme_path), None);
+        let _ = backup.cleanup();
+        cache::invalidate();
+
+        match build {
+            Ok(result) if result.success => {
+                preview::save(root, &preview)?;
+                tracing::info!("Built theme preview {}", preview.token);
+                Ok((preview, result))
+            }
+            Ok(result) => {
+                let _ = std::fs::remove_dir_all(&out_dir);
+                Err(BuilderError::BuildFailed(result.stderr).into())
+            }
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&out_dir);
+                Err(e)
+            }
+        }
+    }
+
+    /// Publish a preview: its build output replaces the production assets,
+    /// its variables are written into the SCSS sources and the assets version
+    /// is bumped. The preview is removed afterwards.
+    pub async fn promote_preview(
+        token: &str,
+    ) -> Result<(ThemePreview, ThemeUpdateResult), ThemeServiceError> {
+        let _build = BUILD_LOCK.lock().await;
+        let variables_path = ThemeConfig::variables_file();
+        let theme_path = ThemeConfig::theme_file();
+        let env_path = ThemeConfig::env_file();
+        let root = ThemeConfig::preview_path();
+
+        let preview = preview::load(root, token)?;
+
+        let mut backup = Backup::new(ThemeConfig::backup_path());
+        backup.create(Some(variables_path), Some(theme_path), Some(env_path))?;
+
+        let published = Self::write_variables(
+            preview.scss_variables.as_ref(),
+            preview.theme_light.as_ref(),
+            preview.theme_dark.as_ref(),
+        )
+        .and_then(|()| {
+            preview::publish(root, token, ThemeConfig::resources_path())
+                .map_err(|e| format!("Failed to publish preview: {}", e))
+        });
+        cache::invalidate();
+
+        if let Err(e) = published {
+            let _ = backup.rollback(Some(variables_path), Some(theme_path), Some(env_path));
+            return Err(ThemeServiceError::RollbackTriggered(e));
+        }
+
+        let new_version = match versioner::increment_and_update(env_path) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                tracing::warn!("Failed to update version: {}", e);
+                None
+            }
+        };
+        let _ = backup.cleanup();
+
+        if let Err(e) = preview::remove(root, token) {
+            tracing::warn!("Failed to remove promoted preview {}: {}", token, e);
+        }
+        tracing::info!("Promoted theme preview {}", token);
+
+        Ok((
+            preview,
+            ThemeUpdateResult {
+                success: true,
+                new_version,
+                build_output: None,
+                error: None,
+            },
+        ))
+    }
+
+    /// Delete a preview without publishing it
+    pub fn discard_preview(token: &str) -> Result<(), ThemeServiceError> {
+        Ok(preview::remove(ThemeConfig::preview_path(), token)?)
+    }
+
+    /// Previews waiting to be promoted or discarded, newest first
+    pub fn list_previews() -> Result<Vec<ThemePreview>, ThemeServiceError> {
+        Ok(preview::list(ThemeConfig::preview_path())?)
+    }
+
+    /// Delete previews older than `THEME_PREVIEW_TTL`; returns how many were removed
+    pub fn remove_stale_previews() -> Result<usize, ThemeServiceError> {
+        Ok(preview::remove_stale(
+            ThemeConfig::preview_path(),
+            ThemeConfig::preview_ttl_secs(),
+        )?)
+    }
+
     /// Get the current assets version
     pub fn get_current_version() -> Result<String, ThemeServiceError> {
         let env_path = ThemeConfig::env_file();
         Ok(versioner::get_current_version(env_path)?)
     }
+
+    /// Get a tenant's effective light/dark variables: the platform defaults
+    /// with the tenant's overrides applied
+    pub fn get_tenant_variables(
+        theme_light: &Value,
+        theme_dark: &Value,
+    ) -> Result<(Value, Value), ThemeServiceError> {
+        let (_, light, dark) = Self::get_current_variables()?;
+        Ok((
+            tenant::merge_overrides(&light, theme_light),
+            tenant::merge_overrides(&dark, theme_dark),
+        ))
+    }
+
+    /// Validate tenant overrides against the CSS property whitelist and
+    /// return them normalized for storage. SCSS variables are compile-time
+    /// values of the shared bundle and cannot be overridden per tenant.
+    pub fn validate_tenant_variables(
+        tenant_key: &str,
+        theme_light: Option<&Value>,
+        theme_dark: Option<&Value>,
+    ) -> Result<(Value, Value), ThemeServiceError> {
+        tenant::validate_tenant_key(tenant_key)?;
+
+        let light = tenant::normalize_overrides(theme_light);
+        let dark = tenant::normalize_overrides(theme_dark);
+        Self::validate_variables(None, Some(&light), Some(&dark))?;
+
+        for overrides in [theme_light, theme_dark].into_iter().flatten() {
+            tenant::validate_values(overrides)?;
+        }
+
+        Ok((light, dark))
+    }
+
+    /// Build a tenant's CSS bundle from validated overrides.
+    /// Returns the content-hashed file name; previous bundles are kept until
+    /// `remove_tenant_bundles` is called so pages being served keep working.
+    pub fn build_tenant_bundle(
+        tenant_key: &str,
+        theme_light: &Value,
+        theme_dark: &Value,
+    ) -> Result<String, ThemeServiceError> {
+        let light = parser::json_to_variables(theme_light);
+        let dark = parser::json_to_variables(theme_dark);
+        let css = tenant::render_css(tenant_key, &light, &dark);
+
+        let file_name = tenant::write_bundle(ThemeConfig::tenant_css_path(), tenant_key, &css)?;
+        tracing::info!("Built tenant theme bundle {}", file_name);
+        Ok(file_name)
+    }
+
+    /// Remove a tenant's bundles, except the one currently in use (if any)
+    pub fn remove_tenant_bundles(tenant_key: &str, keep: Option<&str>) -> Result<(), ThemeServiceError> {
+        tenant::validate_tenant_key(tenant_key)?;
+        Ok(tenant::remove_bundles(
+            ThemeConfig::tenant_css_path(),
+            tenant_key,
+            keep,
+        )?)
+    }
 }
 
 /// Convenience function to get allowed variables (for frontend)
@@ -0,0 +1,289 @@
+//! Theme Previews
+//!
+//! A preview is a build of proposed theme variables into its own directory,
+//! `resources/css/PREVIEWS/{token}`, instead of the production assets. Pages
+//! opened with `?theme_preview={token}` load the preview stylesheet. An admin
+//! then promotes the preview (its build output replaces the production assets)
+//! or discards it; previews older than `THEME_PREVIEW_TTL` are removed by the
+//! `theme_previews` cron job.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use uuid::Uuid;
+
+/// Public URL of the preview directory
+pub const PREVIEW_CSS_URL: &str = "/assets/css/PREVIEWS";
+
+/// Query parameter that renders a page with a preview stylesheet
+pub const PREVIEW_QUERY_PARAM: &str = "theme_preview";
+
+/// Preview description, stored next to its build output
+const METADATA_FILE: &str = "preview.json";
+
+/// Build output directories copied to the production assets on promotion
+const OUTPUT_DIRS: [&str; 3] = ["css", "js", "assets"];
+
+/// Error type for preview operations
+#[derive(Debug, thiserror::Error)]
+pub enum PreviewError {
+    #[error("Preview IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid preview metadata: {0}")]
+    Metadata(#[from] serde_json::Error),
+    #[error("Invalid preview token")]
+    InvalidToken,
+    #[error("Preview not found")]
+    NotFound,
+}
+
+/// A preview build and the variables it was built from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemePreview {
+    pub token: String,
+    pub scss_variables: Option<Value>,
+    pub theme_light: Option<Value>,
+    pub theme_dark: Option<Value>,
+    pub created_by: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ThemePreview {
+    pub fn new(
+        scss_variables: Option<Value>,
+        theme_light: Option<Value>,
+        theme_dark: Option<Value>,
+        created_by: i64,
+    ) -> Self {
+        Self {
+            token: Uuid::new_v4().simple().to_string(),
+            scss_variables,
+            theme_light,
+            theme_dark,
+            created_by,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// When the cleanup job removes the preview
+    pub fn expires_at(&self, ttl_secs: u64) -> DateTime<Utc> {
+        self.created_at + chrono::Duration::seconds(ttl_secs as i64)
+    }
+
+    /// Page URL rendering the site with this preview
+    pub fn preview_url(&self) -> String {
+        format!("/?{}={}", PREVIEW_QUERY_PARAM, self.token)
+    }
+
+    /// Public URL of the preview's GLOBAL stylesheet
+    pub fn stylesheet_url(&self) -> String {
+        stylesheet_url(&self.token)
+    }
+}
+
+/// Tokens are simple UUIDs (32 lowercase hex characters)
+pub fn validate_token(token: &str) -> Result<(), PreviewError> {
+    let valid = token.len() == 32
+        && token
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, 'a'..='f'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PreviewError::InvalidToken)
+    }
+}
+
+/// Build output directory of a preview
+pub fn preview_dir(root: &Path, token: &str) -> Result<PathBuf, PreviewError> {
+    validate_token(token)?;
+    Ok(root.join(token))
+}
+
+/// Public URL of a preview's GLOBAL stylesheet
+pub fn stylesheet_url(token: &str) -> String {
+    format!("{}/{}/css/GLOBAL/style.css", PREVIEW_CSS_URL, token)
+}
+
+/// Whether a built preview exists for `token` (invalid tokens never exist)
+pub fn exists(root: &Path, token: &str) -> bool {
+    preview_dir(root, token).is_ok_and(|dir| dir.join(METADATA_FILE).is_file())
+}
+
+/// Record a finished preview build
+pub fn save(root: &Path, preview: &ThemePreview) -> Result<(), PreviewError> {
+    let dir = preview_dir(root, &preview.token)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(METADATA_FILE), serde_json::to_vec_pretty(preview)?)?;
+    Ok(())
+}
+
+pub fn load(root: &Path, token: &str) -> Result<ThemePreview, PreviewError> {
+    let path = preview_dir(root, token)?.join(METADATA_FILE);
+    if !path.is_file() {
+        return Err(PreviewError::NotFound);
+    }
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
+
+/// All finished previews, newest first
+pub fn list(root: &Path) -> Result<Vec<ThemePreview>, PreviewError> {
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut previews: Vec<ThemePreview> = fs::read_dir(root)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| load(root, &entry.file_name().to_string_lossy()).ok())
+        .collect();
+    previews.sort_by_key(|preview| std::cmp::Reverse(preview.created_at));
+    Ok(previews)
+}
+
+/// Delete a preview and its build output
+pub fn remove(root: &Path, token: &str) -> Result<(), PreviewError> {
+    let dir = preview_dir(root, token)?;
+    if !dir.exists() {
+        return Err(PreviewError::NotFound);
+    }
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+/// Delete previews older than `max_age_secs`, including output of builds that
+/// never finished (judged by the directory's modification time). Returns the
+/// number of removed previews.
+pub fn remove_stale(root: &Path, max_age_secs: u64) -> Result<usize, PreviewError> {
+    if !root.exists() {
+        return Ok(0);
+    }
+
+    let cutoff = Utc::now() - chrono::Duration::seconds(max_age_secs as i64);
+    let mut removed = 0;
+
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let token = entry.file_name().to_string_lossy().to_string();
+        if validate_token(&token).is_err() || !entry.path().is_dir() {
+            continue;
+        }
+
+        let created_at = match load(root, &token) {
+            Ok(preview) => preview.created_at,
+            Err(_) => entry
+                .metadata()?
+                .modified()
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+                .into(),
+        };
+        if created_at < cutoff {
+            fs::remove_dir_all(entry.path())?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Copy a preview's build output over the production assets in `resources`
+pub fn publish(root: &Path, token: &str, resources: &Path) -> Result<(), PreviewError> {
+    let dir = preview_dir(root, token)?;
+    for output in OUTPUT_DIRS {
+        let source = dir.join(output);
+        if source.is_dir() {
+            copy_dir(&source, &resources.join(output))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &target.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, target.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("theme_previews_{}", Uuid::new_v4().simple()))
+    }
+
+    #[test]
+    fn test_token_validation() {
+        let preview = ThemePreview::new(None, None, None, 1);
+        assert!(validate_token(&preview.token).is_ok());
+        assert!(validate_token("../../../../etc").is_err());
+        assert!(validate_token(&"A".repeat(32)).is_err());
+        assert!(validate_token("").is_err());
+    }
+
+    #[test]
+    fn test_save_list_and_remove() {
+        let root = temp_root();
+        let preview = ThemePreview::new(None, Some(json!({"nav-bg": "#112233"})), None, 7);
+        save(&root, &preview).unwrap();
+
+        assert!(exists(&root, &preview.token));
+        let loaded = load(&root, &preview.token).unwrap();
+        assert_eq!(loaded.theme_light, preview.theme_light);
+        assert_eq!(list(&root).unwrap().len(), 1);
+
+        remove(&root, &preview.token).unwrap();
+        assert!(!exists(&root, &preview.token));
+        assert!(matches!(
+            remove(&root, &preview.token),
+            Err(PreviewError::NotFound)
+        ));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_stale_previews_are_removed() {
+        let root = temp_root();
+        let mut old = ThemePreview::new(None, None, None, 1);
+        old.created_at = Utc::now() - chrono::Duration::hours(2);
+        let fresh = ThemePreview::new(None, None, None, 1);
+        save(&root, &old).unwrap();
+        save(&root, &fresh).unwrap();
+
+        assert_eq!(remove_stale(&root, 3600).unwrap(), 1);
+        assert!(!exists(&root, &old.token));
+        assert!(exists(&root, &fresh.token));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_publish_copies_build_output() {
+        let root = temp_root();
+        let resources = root.join("resources");
+        let preview = ThemePreview::new(None, None, None, 1);
+        let css = root.join(&preview.token).join("css/GLOBAL");
+        fs::create_dir_all(&css).unwrap();
+        fs::write(css.join("style.css"), ":root{}").unwrap();
+        save(&root, &preview).unwrap();
+
+        publish(&root, &preview.token, &resources).unwrap();
+        assert_eq!(
+            fs::read_to_string(resources.join("css/GLOBAL/style.css")).unwrap(),
+            ":root{}"
+        );
+        assert!(!resources.join(METADATA_FILE).exists());
+        let _ = fs::remove_dir_all(root);
+    }
+}
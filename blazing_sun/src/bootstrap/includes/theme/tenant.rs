@@ -0,0 +1,237 @@
+//! Tenant Themes
+//!
+//! Tenants override whitelisted CSS custom properties of the platform theme.
+//! The overrides are compiled into a small stylesheet that is loaded after the
+//! default GLOBAL bundle. Its file name carries a hash of its content, so a
+//! changed theme is never served from a stale cache. Tenants without overrides
+//! get the default bundle only.
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Public URL of the tenant bundle directory
+pub const TENANT_CSS_URL: &str = "/assets/css/TENANTS";
+
+/// Public URL of the default theme bundle
+pub const DEFAULT_CSS_URL: &str = "/assets/css/GLOBAL/style.css";
+
+/// Longest accepted value of a single custom property
+const MAX_VALUE_LEN: usize = 200;
+
+/// Error type for tenant theme operations
+#[derive(Debug, thiserror::Error)]
+pub enum TenantError {
+    #[error("Failed to write tenant bundle: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid tenant key: {0}")]
+    InvalidTenantKey(String),
+    #[error("Invalid value for '{0}'")]
+    InvalidValue(String),
+}
+
+/// Tenant keys are lowercase slugs ("acme", "acme-eu"), at most 64 characters
+pub fn validate_tenant_key(tenant_key: &str) -> Result<(), TenantError> {
+    let valid = !tenant_key.is_empty()
+        && tenant_key.len() <= 64
+        && !tenant_key.starts_with('-')
+        && !tenant_key.ends_with('-')
+        && tenant_key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(TenantError::InvalidTenantKey(tenant_key.to_string()))
+    }
+}
+
+/// Property name in theme file format: "--bg_gradient_start" -> "bg-gradient-start"
+pub fn normalize_name(name: &str) -> String {
+    name.trim_start_matches("--").replace('_', "-")
+}
+
+/// Reject values that could break out of the declaration they are written into
+pub fn validate_values(overrides: &Value) -> Result<(), TenantError> {
+    let Some(obj) = overrides.as_object() else {
+        return Ok(());
+    };
+
+    for (name, value) in obj {
+        let valid = value.as_str().is_some_and(|v| {
+            let v = v.trim();
+            !v.is_empty()
+                && v.len() <= MAX_VALUE_LEN
+                && !v.contains("/*")
+                && !v
+                    .chars()
+                    .any(|c| matches!(c, ';' | '{' | '}' | '<' | '>' | '\\') || c.is_control())
+        });
+        if !valid {
+            return Err(TenantError::InvalidValue(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Overrides with normalized names and trimmed values, as stored in the database
+pub fn normalize_overrides(overrides: Option<&Value>) -> Value {
+    let map: serde_json::Map<String, Value> = overrides
+        .and_then(Value::as_object)
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(name, value)| {
+                    Some((normalize_name(name), json!(value.as_str()?.trim())))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Value::Object(map)
+}
+
+/// Default variables with the tenant's overrides applied
+pub fn merge_overrides(defaults: &Value, overrides: &Value) -> Value {
+    let mut merged = defaults.as_object().cloned().unwrap_or_default();
+    if let Some(obj) = overrides.as_object() {
+        for (name, value) in obj {
+            merged.insert(normalize_name(name), value.clone());
+        }
+    }
+    Value::Object(merged)
+}
+
+/// Render the tenant stylesheet (properties sorted so equal themes hash equally)
+pub fn render_css(
+    tenant_key: &str,
+    light: &HashMap<String, String>,
+    dark: &HashMap<String, String>,
+) -> String {
+    let mut css = format!("/* Theme overrides for tenant '{}' */\n", tenant_key);
+
+    for (selector, variables) in [(":root", light), ("[data-theme=\"dark\"]", dark)] {
+        if variables.is_empty() {
+            continue;
+        }
+        let sorted: BTreeMap<String, &String> = variables
+            .iter()
+            .map(|(name, value)| (normalize_name(name), value))
+            .collect();
+
+        css.push_str(selector);
+        css.push_str(" {\n");
+        for (name, value) in sorted {
+            css.push_str(&format!("  --{}: {};\n", name, value.trim()));
+        }
+        css.push_str("}\n");
+    }
+
+    css
+}
+
+/// Content-hashed bundle file name, e.g. "acme.3f2a9c1b04de.css"
+pub fn bundle_file_name(tenant_key: &str, css: &str) -> String {
+    let hash = hex::encode(Sha256::digest(css.as_bytes()));
+    format!("{}.{}.css", tenant_key, &hash[..12])
+}
+
+/// Write a tenant bundle, returning its file name
+pub fn write_bundle(dir: &Path, tenant_key: &str, css: &str) -> Result<String, TenantError> {
+    fs::create_dir_all(dir)?;
+
+    let file_name = bundle_file_name(tenant_key, css);
+    fs::write(dir.join(&file_name), css)?;
+    Ok(file_name)
+}
+
+/// Remove a tenant's bundles except `keep`
+pub fn remove_bundles(dir: &Path, tenant_key: &str, keep: Option<&str>) -> Result<(), TenantError> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let prefix = format!("{}.", tenant_key);
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(&prefix) && name.ends_with(".css") && Some(name.as_str()) != keep {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Public URL of a tenant bundle
+pub fn bundle_url(file_name: &str) -> String {
+    format!("{}/{}", TENANT_CSS_URL, file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_key_validation() {
+        assert!(validate_tenant_key("acme").is_ok());
+        assert!(validate_tenant_key("acme-eu-2").is_ok());
+        assert!(validate_tenant_key("").is_err());
+        assert!(validate_tenant_key("Acme").is_err());
+        assert!(validate_tenant_key("-acme").is_err());
+        assert!(validate_tenant_key("../acme").is_err());
+        assert!(validate_tenant_key(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_values_cannot_escape_declaration() {
+        assert!(validate_values(&json!({"nav-bg": "#112233"})).is_ok());
+        assert!(validate_values(&json!({"nav-bg": "rgba(0, 0, 0, 0.5)"})).is_ok());
+        assert!(validate_values(&json!({"nav-bg": "red; } body { display: none"})).is_err());
+        assert!(validate_values(&json!({"nav-bg": "red /* x"})).is_err());
+        assert!(validate_values(&json!({"nav-bg": "</style>"})).is_err());
+        assert!(validate_values(&json!({"nav-bg": ""})).is_err());
+        assert!(validate_values(&json!({"nav-bg": 5})).is_err());
+    }
+
+    #[test]
+    fn test_render_and_bundle_name_are_stable() {
+        let mut light = HashMap::new();
+        light.insert("nav_bg".to_string(), "#112233".to_string());
+        light.insert("--card-bg".to_string(), " #ffffff ".to_string());
+        let mut dark = HashMap::new();
+        dark.insert("nav-bg".to_string(), "#000000".to_string());
+
+        let css = render_css("acme", &light, &dark);
+        assert_eq!(
+            css,
+            "/* Theme overrides for tenant 'acme' */\n\
+             :root {\n  --card-bg: #ffffff;\n  --nav-bg: #112233;\n}\n\
+             [data-theme=\"dark\"] {\n  --nav-bg: #000000;\n}\n"
+        );
+
+        let name = bundle_file_name("acme", &css);
+        assert!(name.starts_with("acme.") && name.ends_with(".css"));
+        assert_eq!(
+            name,
+            bundle_file_name("acme", &render_css("acme", &light, &dark))
+        );
+
+        light.insert("nav_bg".to_string(), "#445566".to_string());
+        assert_ne!(
+            name,
+            bundle_file_name("acme", &render_css("acme", &light, &dark))
+        );
+    }
+
+    #[test]
+    fn test_merge_falls_back_to_defaults() {
+        let defaults = json!({"nav-bg": "#7c88de", "card-bg": "#ffffff"});
+        let merged = merge_overrides(&defaults, &json!({"nav_bg": "#112233"}));
+
+        assert_eq!(merged["nav-bg"], "#112233");
+        assert_eq!(merged["card-bg"], "#ffffff");
+        assert_eq!(merge_overrides(&defaults, &json!({})), defaults);
+    }
+}
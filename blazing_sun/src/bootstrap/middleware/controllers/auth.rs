@@ -1,9 +1,14 @@
+use crate::app::db_query::read::impersonation_sessions as db_impersonation;
 use crate::app::http::api::controllers::auth::Claims;
+use crate::app::impersonation::{self, Impersonation};
 use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::sessions::{self, CurrentSession};
+use crate::bootstrap::middleware::controllers::rbac::role_of;
 use crate::config::JwtConfig;
 use crate::database::mutations::session_refresh_token as db_refresh_token_mut;
 use crate::database::read::session_refresh_token as db_refresh_token;
 use crate::database::read::user as db_user;
+use crate::database::read::user_preferences as db_user_preferences;
 use crate::database::AppState;
 use actix_web::cookie::{Cookie, SameSite};
 use actix_web::HttpMessage;
@@ -14,8 +19,56 @@ use actix_web::{
     web, HttpResponse,
 };
 use chrono::{Duration, Utc};
+use i18n::Locale;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 
+/// Store the token's message locale for the response localization middleware
+fn insert_locale(request: &ServiceRequest, claims: &Claims) {
+    if let Some(locale) = claims.locale.as_deref().and_then(Locale::parse) {
+        request.extensions_mut().insert(locale);
+    }
+}
+
+/// The impersonation session of a token, if it is an impersonation token.
+/// Err when the session has ended or expired (or cannot be checked); such
+/// tokens are refused rather than treated as the user's own.
+async fn impersonation_of(
+    state: &web::Data<AppState>,
+    claims: &Claims,
+) -> Result<Option<Impersonation>, &'static str> {
+    let (Some(session_id), Some(impersonator_id)) =
+        (claims.impersonation_id, claims.impersonator_id)
+    else {
+        return Ok(None);
+    };
+
+    let db = state.db.lock().await;
+    let active = db_impersonation::is_active(&db, session_id).await;
+    drop(db);
+
+    match active {
+        Ok(true) => Ok(Some(Impersonation {
+            session_id,
+            impersonator_id,
+        })),
+        Ok(false) => Err("Impersonation session has ended"),
+        Err(e) => {
+            tracing::error!("Failed to check impersonation session {}: {}", session_id, e);
+            Err("Impersonation session could not be verified")
+        }
+    }
+}
+
+/// Whether an expired token was an impersonation token; those must not fall
+/// back to the refresh cookie, which belongs to the admin
+fn is_impersonation_token(token: &str, decoding_key: &DecodingKey) -> bool {
+    let mut validation = Validation::default();
+    validation.validate_exp = false;
+    decode::<Claims>(token, decoding_key, &validation)
+        .map(|data| data.claims.impersonation_id.is_some())
+        .unwrap_or(false)
+}
+
 /// Helper to create JSON error response
 fn unauthorized_response(
     request: ServiceRequest,
@@ -26,7 +79,7 @@ fn unauthorized_response(
 }
 
 /// Extract JWT token from Authorization header or auth_token cookie
-fn extract_token(request: &ServiceRequest) -> Option<String> {
+pub(crate) fn extract_token(request: &ServiceRequest) -> Option<String> {
     // First, try Authorization header
     if let Some(auth_header) = request.headers().get("Authorization") {
         if let Ok(auth_str) = auth_header.to_str() {
@@ -82,12 +135,26 @@ pub async fn verify_jwt(
     match decode::<Claims>(&token, &decoding_key, &Validation::default()) {
         Ok(token_data) => {
             let claims = token_data.claims;
+            if let Some(session_id) = claims.sid {
+                if sessions::is_revoked(state.redis(), session_id).await {
+                    return Ok(unauthorized_response(request, "Session has been revoked"));
+                }
+                request.extensions_mut().insert(CurrentSession(session_id));
+            }
+            let impersonation = match impersonation_of(&state, &claims).await {
+                Ok(impersonation) => impersonation,
+                Err(message) => return Ok(unauthorized_response(request, message)),
+            };
             // Store user ID in request extensions
             request.extensions_mut().insert(claims.sub);
             // Store permissions in request extensions for permission middleware
             request.extensions_mut().insert(claims.permissions);
-            // Proceed to next middleware/handler
-            next.call(request).await
+            insert_locale(&request, &claims);
+            if let Some(impersonation) = &impersonation {
+                request.extensions_mut().insert(impersonation.clone());
+            }
+            // Proceed to next middleware/handler; events it publishes name the admin
+            impersonation::scope(impersonation, next.call(request)).await
         }
         Err(err) => {
             // Check if error is specifically due to token expiration
@@ -95,6 +162,9 @@ pub async fn verify_jwt(
                 err.kind(),
                 jsonwebtoken::errors::ErrorKind::ExpiredSignature
             ) {
+                if is_impersonation_token(&token, &decoding_key) {
+                    return Ok(unauthorized_response(request, "Impersonation session has ended"));
+                }
                 // Try to use refresh token
                 return try_refresh_or_unauthorized(request, next, &state).await;
             }
@@ -136,12 +206,27 @@ pub async fn verify_jwt_optional(
     match decode::<Claims>(&token, &decoding_key, &Validation::default()) {
         Ok(token_data) => {
             let claims = token_data.claims;
+            if let Some(session_id) = claims.sid {
+                if sessions::is_revoked(state.redis(), session_id).await {
+                    // Revoked device - proceed without user_id (don't reject)
+                    return next.call(request).await;
+                }
+                request.extensions_mut().insert(CurrentSession(session_id));
+            }
+            let Ok(impersonation) = impersonation_of(&state, &claims).await else {
+                // Ended impersonation session - proceed without user_id (don't reject)
+                return next.call(request).await;
+            };
             // Store user ID in request extensions
             request.extensions_mut().insert(claims.sub);
             // Store permissions in request extensions for permission middleware
             request.extensions_mut().insert(claims.permissions);
+            insert_locale(&request, &claims);
+            if let Some(impersonation) = &impersonation {
+                request.extensions_mut().insert(impersonation.clone());
+            }
             // Proceed to next middleware/handler
-            next.call(request).await
+            impersonation::scope(impersonation, next.call(request)).await
         }
         Err(err) => {
             // Check if error is specifically due to token expiration
@@ -149,6 +234,9 @@ pub async fn verify_jwt_optional(
                 err.kind(),
                 jsonwebtoken::errors::ErrorKind::ExpiredSignature
             ) {
+                if is_impersonation_token(&token, &decoding_key) {
+                    return next.call(request).await;
+                }
                 // Try to use refresh token
                 return try_refresh_optional(request, next, &state).await;
             }
@@ -210,9 +298,13 @@ async fn try_refresh_optional(
     // Generate new access token
     let claims = Claims {
         sub: user.id,
-        role: "user".to_string(),
+        role: role_of(user.permissions).as_str().to_string(),
         permissions: user.permissions,
         exp: (Utc::now() + Duration::minutes(JwtConfig::expiration_minutes())).timestamp(),
+        locale: db_user_preferences::get_locale(&db, user.id).await.ok().flatten(),
+        impersonator_id: None,
+        impersonation_id: None,
+        sid: sessions::for_refresh_token(&db, &refresh_record).await,
     };
 
     let new_token = match encode(
@@ -230,6 +322,10 @@ async fn try_refresh_optional(
     // Store user ID and permissions in request extensions
     request.extensions_mut().insert(claims.sub);
     request.extensions_mut().insert(claims.permissions);
+    insert_locale(&request, &claims);
+    if let Some(session_id) = claims.sid {
+        request.extensions_mut().insert(CurrentSession(session_id));
+    }
 
     // Drop database lock before calling next middleware
     drop(db);
@@ -307,9 +403,13 @@ async fn try_refresh_or_unauthorized(
     // Generate new access token
     let claims = Claims {
         sub: user.id,
-        role: "user".to_string(),
+        role: role_of(user.permissions).as_str().to_string(),
         permissions: user.permissions,
         exp: (Utc::now() + Duration::minutes(JwtConfig::expiration_minutes())).timestamp(),
+        locale: db_user_preferences::get_locale(&db, user.id).await.ok().flatten(),
+        impersonator_id: None,
+        impersonation_id: None,
+        sid: sessions::for_refresh_token(&db, &refresh_record).await,
     };
 
     let new_token = match encode(
@@ -328,6 +428,10 @@ async fn try_refresh_or_unauthorized(
     // Store user ID and permissions in request extensions
     request.extensions_mut().insert(claims.sub);
     request.extensions_mut().insert(claims.permissions);
+    insert_locale(&request, &claims);
+    if let Some(session_id) = claims.sid {
+        request.extensions_mut().insert(CurrentSession(session_id));
+    }
 
     // Drop database lock before calling next middleware
     drop(db);
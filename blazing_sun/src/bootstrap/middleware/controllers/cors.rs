@@ -1,6 +1,34 @@
 use actix_cors::Cors;
 
+use crate::config::security::SecurityProfile;
+use crate::config::SecurityConfig;
+
+/// CORS policy from SecurityConfig (CORS_* env, APP_ENV profile)
 pub fn configure() -> Cors {
-    // CORS - allow all for development
-    Cors::permissive()
+    let any_origin = SecurityConfig::cors_allows_any_origin();
+
+    // Development keeps the old allow-everything behaviour
+    if any_origin && SecurityConfig::profile() == SecurityProfile::Development {
+        return Cors::permissive();
+    }
+
+    let mut cors = Cors::default()
+        .allowed_methods(SecurityConfig::cors_allowed_methods().iter().map(String::as_str))
+        .allowed_headers(SecurityConfig::cors_allowed_headers().iter().map(String::as_str))
+        .max_age(SecurityConfig::cors_max_age());
+
+    if any_origin {
+        // A wildcard origin is never combined with credentials
+        return cors.allow_any_origin().send_wildcard();
+    }
+
+    for origin in SecurityConfig::cors_allowed_origins() {
+        cors = cors.allowed_origin(origin.trim_end_matches('/'));
+    }
+
+    if SecurityConfig::cors_allow_credentials() {
+        cors = cors.supports_credentials();
+    }
+
+    cors
 }
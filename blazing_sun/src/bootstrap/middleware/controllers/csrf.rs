@@ -45,6 +45,12 @@ fn is_excluded_path(path: &str) -> bool {
         return true;
     }
 
+    // Fault injection endpoints are driven by test harnesses, not browsers
+    #[cfg(feature = "chaos")]
+    if path.starts_with("/internal/faults") {
+        return true;
+    }
+
     false
 }
 
@@ -48,8 +48,10 @@ fn try_jwt_auth(request: &ServiceRequest) -> Option<i64> {
     let state = request.app_data::<web::Data<AppState>>()?;
     let decoding_key = DecodingKey::from_secret(state.jwt_secret.as_bytes());
 
-    // Decode and validate JWT
-    let token_data = decode::<Claims>(&token, &decoding_key, &Validation::default()).ok()?;
+    // Decode and validate JWT; impersonation tokens may not manage OAuth clients
+    let token_data = decode::<Claims>(&token, &decoding_key, &Validation::default())
+        .ok()
+        .filter(|data| data.claims.impersonation_id.is_none())?;
 
     // Return i64 user ID from JWT claims
     Some(token_data.claims.sub)
@@ -0,0 +1,345 @@
+//! Idempotency-Key middleware
+//!
+//! Mutating requests (POST/PUT/PATCH/DELETE) that carry an `Idempotency-Key`
+//! header are executed at most once per key. The first successful (2xx) response
+//! is stored in a Redis hash and replayed for retries with the same key and body;
+//! reusing a key with a different body returns 409 Conflict. Error responses are
+//! not stored, so a retry after e.g. a 401 from an expired token runs again.
+//!
+//! Replays carry the stored status, `Content-Type`, `Location` and body only;
+//! other response headers (`Set-Cookie`, ...) are not stored.
+//!
+//! Keys are scoped per user (JWT `sub`) so clients cannot replay each other's
+//! responses; requests without a token are scoped by client IP and path.
+//! Requests without the header, `multipart/*` uploads (their bodies are not
+//! buffered here, so the upload routes' own size limits apply), and all
+//! requests while Redis is unavailable, pass through untouched.
+
+use crate::app::http::api::controllers::auth::Claims;
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::app::sessions::client_ip;
+use crate::bootstrap::middleware::controllers::auth::extract_token;
+use crate::config::IdempotencyConfig;
+use crate::database::AppState;
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    web, HttpResponse,
+};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+/// Request header carrying the client-generated key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Response header set when a stored response is replayed
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// Maximum accepted key length
+const MAX_KEY_LENGTH: usize = 255;
+
+/// How long an in-flight key is held before it can be retried (covers crashes mid-request)
+const IN_FLIGHT_TTL_SECONDS: i64 = 60;
+
+/// Stored response looked up from Redis
+struct StoredResponse {
+    fingerprint: Option<String>,
+    status: Option<u16>,
+    content_type: Option<String>,
+    location: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+/// Idempotency middleware
+pub async fn idempotency<B>(
+    mut request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    if !IdempotencyConfig::enabled() || !is_mutating(request.method()) {
+        return next.call(request).await.map(|res| res.map_into_boxed_body());
+    }
+
+    let key = match request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(key) => key.trim().to_string(),
+        None => return next.call(request).await.map(|res| res.map_into_boxed_body()),
+    };
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        let response = HttpResponse::BadRequest().json(BaseResponse::error(
+            "Idempotency-Key must be between 1 and 255 characters",
+        ));
+        return Ok(request.into_response(response).map_into_boxed_body());
+    }
+
+    if is_multipart(&request) {
+        return next.call(request).await.map(|res| res.map_into_boxed_body());
+    }
+
+    let state = request.app_data::<web::Data<AppState>>().cloned();
+    let mut redis = match state.as_ref().and_then(|s| s.redis()) {
+        Some(conn) => conn,
+        None => return next.call(request).await.map(|res| res.map_into_boxed_body()),
+    };
+
+    // Buffer the body so it can be fingerprinted, then hand it back to the handler
+    let body_bytes = request.extract::<web::Bytes>().await?;
+    request.set_payload(Payload::from(body_bytes.clone()));
+
+    let scope = state
+        .as_ref()
+        .and_then(|s| user_scope(&request, s.jwt_secret))
+        .unwrap_or_else(|| anonymous_scope(&request));
+    let redis_key = format!("idempotency:{}:{}", scope, key);
+    let fingerprint = fingerprint(&request, &body_bytes);
+
+    // Claim the key; only the first request gets to run the handler
+    let claimed: Result<bool, redis::RedisError> =
+        redis.hset_nx(&redis_key, "fingerprint", &fingerprint).await;
+
+    match claimed {
+        Ok(true) => {
+            let _: Result<(), _> = redis.expire(&redis_key, IN_FLIGHT_TTL_SECONDS).await;
+        }
+        Ok(false) => {
+            let stored = match load(&mut redis, &redis_key).await {
+                Ok(stored) => stored,
+                Err(e) => {
+                    warn!("Idempotency lookup failed for {}: {}", redis_key, e);
+                    return next.call(request).await.map(|res| res.map_into_boxed_body());
+                }
+            };
+            let response = replay_or_conflict(stored, &fingerprint);
+            return Ok(request.into_response(response).map_into_boxed_body());
+        }
+        Err(e) => {
+            warn!("Idempotency claim failed for {}: {}", redis_key, e);
+            return next.call(request).await.map(|res| res.map_into_boxed_body());
+        }
+    }
+
+    let response = next.call(request).await;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            // Let the client retry with the same key
+            let _: Result<(), _> = redis.del(&redis_key).await;
+            return Err(e);
+        }
+    };
+
+    // Only successes are stored; a retry after an error response can succeed
+    if !response.status().is_success() {
+        let _: Result<(), _> = redis.del(&redis_key).await;
+        return Ok(response.map_into_boxed_body());
+    }
+
+    let (http_request, http_response) = response.into_parts();
+    let status = http_response.status();
+    let content_type = http_response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+    let location = http_response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let (http_response, response_body) = http_response.into_parts();
+    let response_bytes = match body::to_bytes(response_body).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let _: Result<(), _> = redis.del(&redis_key).await;
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Failed to read response body",
+            ));
+        }
+    };
+
+    let mut pipe = redis::pipe();
+    pipe.hset(&redis_key, "status", status.as_u16())
+        .ignore()
+        .hset(&redis_key, "content_type", &content_type)
+        .ignore()
+        .hset(&redis_key, "body", response_bytes.as_ref())
+        .ignore();
+    if let Some(location) = &location {
+        pipe.hset(&redis_key, "location", location).ignore();
+    }
+    let stored: Result<(), redis::RedisError> = pipe
+        .expire(&redis_key, IdempotencyConfig::ttl_seconds() as i64)
+        .ignore()
+        .query_async(&mut redis)
+        .await;
+    if let Err(e) = stored {
+        warn!("Failed to store idempotent response for {}: {}", redis_key, e);
+    }
+
+    let http_response = http_response.set_body(response_bytes).map_into_boxed_body();
+    Ok(ServiceResponse::new(http_request, http_response))
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+/// Resolve the user the key belongs to from the JWT (expired tokens still count,
+/// so a retry after a silent refresh maps to the same scope)
+fn user_scope(request: &ServiceRequest, jwt_secret: &str) -> Option<String> {
+    let token = extract_token(request)?;
+    let mut validation = Validation::default();
+    validation.validate_exp = false;
+    decode::<Claims>(
+        &token,
+        &DecodingKey::from_secret(jwt_secret.as_bytes()),
+        &validation,
+    )
+    .ok()
+    .map(|data| data.claims.sub.to_string())
+}
+
+/// Scope of a request without a token: clients behind different addresses, or
+/// the same client on different routes, never share a key
+fn anonymous_scope(request: &ServiceRequest) -> String {
+    let ip = client_ip(&request.connection_info()).unwrap_or_else(|| "unknown".to_string());
+    format!("anon:{}:{}", ip, request.path())
+}
+
+/// Uploads are streamed to their handlers rather than buffered for a fingerprint
+fn is_multipart(request: &ServiceRequest) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim_start().to_ascii_lowercase().starts_with("multipart/"))
+}
+
+/// Hash of everything that makes two requests "the same"
+fn fingerprint(request: &ServiceRequest, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request.method().as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(request.path().as_bytes());
+    hasher.update(b"?");
+    hasher.update(request.query_string().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Hash fields of a stored response, in `load` order
+type StoredFields = (
+    Option<String>,
+    Option<u16>,
+    Option<String>,
+    Option<String>,
+    Option<Vec<u8>>,
+);
+
+async fn load(
+    redis: &mut redis::aio::ConnectionManager,
+    redis_key: &str,
+) -> Result<StoredResponse, redis::RedisError> {
+    let (fingerprint, status, content_type, location, body): StoredFields = redis
+        .hget(
+            redis_key,
+            &["fingerprint", "status", "content_type", "location", "body"],
+        )
+        .await?;
+
+    Ok(StoredResponse {
+        fingerprint,
+        status,
+        content_type,
+        location,
+        body,
+    })
+}
+
+fn replay_or_conflict(stored: StoredResponse, fingerprint: &str) -> HttpResponse {
+    if stored.fingerprint.as_deref() != Some(fingerprint) {
+        return HttpResponse::Conflict().json(BaseResponse::error(
+            "Idempotency-Key was already used with a different request",
+        ));
+    }
+
+    match (stored.status, stored.body) {
+        (Some(status), Some(body)) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+            let mut response = HttpResponse::build(status);
+            response
+                .insert_header((
+                    header::CONTENT_TYPE,
+                    stored
+                        .content_type
+                        .unwrap_or_else(|| "application/json".to_string()),
+                ))
+                .insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"));
+            if let Some(location) = stored.location {
+                response.insert_header((header::LOCATION, location));
+            }
+            response.body(body)
+        }
+        _ => HttpResponse::Conflict().json(BaseResponse::error(
+            "A request with this Idempotency-Key is still being processed",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn multipart_requests_are_not_buffered() {
+        let upload = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "multipart/form-data; boundary=x"))
+            .to_srv_request();
+        let json = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .to_srv_request();
+
+        assert!(is_multipart(&upload));
+        assert!(!is_multipart(&json));
+    }
+
+    #[test]
+    fn anonymous_keys_are_scoped_by_client_and_route() {
+        let sign_up = TestRequest::post()
+            .uri("/api/v1/auth/sign-up")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .to_srv_request();
+        let other_client = TestRequest::post()
+            .uri("/api/v1/auth/sign-up")
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .to_srv_request();
+        let sign_in = TestRequest::post()
+            .uri("/api/v1/auth/sign-in")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .to_srv_request();
+
+        assert_eq!(
+            anonymous_scope(&sign_up),
+            "anon:10.0.0.1:/api/v1/auth/sign-up"
+        );
+        assert_ne!(anonymous_scope(&sign_up), anonymous_scope(&other_client));
+        assert_ne!(anonymous_scope(&sign_up), anonymous_scope(&sign_in));
+    }
+}
@@ -1,24 +1,95 @@
 use actix_web::{error::JsonPayloadError, HttpRequest, HttpResponse};
 use serde::Serialize;
+use std::collections::HashMap;
 
+use crate::app::http::api::validators::FieldError;
+
+/// Same envelope as `ValidationErrorResponse`, for bodies that fail to deserialize
 #[derive(Serialize)]
 struct JsonErrorResponse {
     status: &'static str,
     message: &'static str,
-    errors: Vec<String>,
+    errors: HashMap<String, Vec<String>>,
+    fields: Vec<FieldError>,
 }
 
 pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::error::Error {
-    let detail = err.to_string();
+    let field_error = payload_field_error(&err);
 
-    // For JSON syntax errors, return the detail
-    let errors = vec![detail];
+    let mut errors = HashMap::new();
+    errors.insert(field_error.field.clone(), vec![field_error.message.clone()]);
 
     let response = HttpResponse::BadRequest().json(JsonErrorResponse {
         status: "error",
         message: "Invalid request body",
         errors,
+        fields: vec![field_error],
     });
 
     actix_web::error::InternalError::from_response(err, response).into()
 }
+
+/// Map a payload error to the field it concerns (`body` when it is not field-specific)
+fn payload_field_error(err: &JsonPayloadError) -> FieldError {
+    let detail = err.to_string();
+
+    match err {
+        JsonPayloadError::Deserialize(source) if source.is_data() => {
+            let message = source.to_string();
+            match backticked(&message) {
+                Some(field) if message.starts_with("missing field") => {
+                    FieldError::required(field)
+                }
+                Some(field) if message.starts_with("unknown field") => {
+                    FieldError::new(field, "unknown_field", detail)
+                }
+                _ => FieldError::new("body", "invalid_type", detail),
+            }
+        }
+        JsonPayloadError::Deserialize(_) => FieldError::new("body", "invalid_json", detail),
+        JsonPayloadError::ContentType => FieldError::new("body", "content_type", detail),
+        JsonPayloadError::Overflow { limit } | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+            FieldError::new("body", "too_large", detail)
+                .with_constraint(serde_json::json!({ "max_bytes": limit }))
+        }
+        _ => FieldError::new("body", "invalid", detail),
+    }
+}
+
+/// First name wrapped in backticks in a serde error message
+fn backticked(message: &str) -> Option<&str> {
+    let start = message.find('`')? + 1;
+    let len = message[start..].find('`')?;
+    Some(&message[start..start + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Body {
+        amount: i64,
+    }
+
+    fn deserialize_error(json: &str) -> JsonPayloadError {
+        JsonPayloadError::Deserialize(serde_json::from_str::<Body>(json).unwrap_err())
+    }
+
+    #[test]
+    fn missing_fields_are_reported_as_required() {
+        let error = payload_field_error(&deserialize_error("{}"));
+        assert_eq!(error.field, "amount");
+        assert_eq!(error.code, "required");
+    }
+
+    #[test]
+    fn syntax_and_type_errors_concern_the_body() {
+        assert_eq!(payload_field_error(&deserialize_error("{")).code, "invalid_json");
+        assert_eq!(
+            payload_field_error(&deserialize_error(r#"{"amount":"x"}"#)).code,
+            "invalid_type"
+        );
+    }
+}
@@ -0,0 +1,418 @@
+//! Load-shedding middleware
+//!
+//! Every request is put in a route group (auth, payments, admin, api, assets,
+//! web), each with a priority. Per group the middleware tracks in-flight
+//! requests and the p99 latency of the last few seconds; their ratio to
+//! `LOAD_SHEDDING_MAX_IN_FLIGHT` / `LOAD_SHEDDING_P99_TARGET_MS` is the group's
+//! pressure. A request is rejected up front with 503 and `Retry-After` once the
+//! pressure reaches its priority's limit:
+//!
+//! - Low (pages, assets): 1.0, measured on the busiest group, so page traffic
+//!   is the first to go when any part of the API struggles
+//! - Normal (the rest of the API): 1.5 on its own group
+//! - High (auth, payments, admin): 2.0 on its own group
+//!
+//! Paths under `LOAD_SHEDDING_EXEMPT_PREFIXES` (health checks, webhooks) are
+//! neither shed nor counted. State is per process.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    HttpResponse,
+};
+use once_cell::sync::Lazy;
+use tracing::debug;
+
+use crate::app::http::api::controllers::responses::BaseResponse;
+use crate::config::LoadSheddingConfig;
+
+/// Latency samples kept per group
+const MAX_SAMPLES: usize = 512;
+
+/// Fewer samples than this are too noisy for a p99
+const MIN_SAMPLES: usize = 20;
+
+/// The p99 is recomputed after this many new samples
+const RECOMPUTE_EVERY: usize = 16;
+
+/// Request priority; lower priorities are shed first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Priority {
+    /// Pressure at which requests of this priority are shed
+    fn shed_at(self) -> f64 {
+        match self {
+            Priority::Low => 1.0,
+            Priority::Normal => 1.5,
+            Priority::High => 2.0,
+        }
+    }
+}
+
+/// Route group tracked by the shedder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    Auth,
+    Payments,
+    Admin,
+    Api,
+    Assets,
+    Web,
+}
+
+impl RouteGroup {
+    pub const ALL: [RouteGroup; 6] = [
+        RouteGroup::Auth,
+        RouteGroup::Payments,
+        RouteGroup::Admin,
+        RouteGroup::Api,
+        RouteGroup::Assets,
+        RouteGroup::Web,
+    ];
+
+    /// Group of a request path
+    pub fn of(path: &str) -> Self {
+        let under = |prefix: &str| has_prefix(path, prefix);
+
+        if under("/api/v1/auth") || under("/api/v1/password") || under("/oauth") {
+            RouteGroup::Auth
+        } else if under("/api/v1/payments") || under("/api/v1/balance") {
+            RouteGroup::Payments
+        } else if under("/api/v1/admin") {
+            RouteGroup::Admin
+        } else if under("/api") {
+            RouteGroup::Api
+        } else if under("/assets") {
+            RouteGroup::Assets
+        } else {
+            RouteGroup::Web
+        }
+    }
+
+    pub fn priority(self) -> Priority {
+        match self {
+            RouteGroup::Auth | RouteGroup::Payments | RouteGroup::Admin => Priority::High,
+            RouteGroup::Api => Priority::Normal,
+            RouteGroup::Assets | RouteGroup::Web => Priority::Low,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RouteGroup::Auth => "auth",
+            RouteGroup::Payments => "payments",
+            RouteGroup::Admin => "admin",
+            RouteGroup::Api => "api",
+            RouteGroup::Assets => "assets",
+            RouteGroup::Web => "web",
+        }
+    }
+}
+
+/// Whether `path` is `prefix` or below it
+fn has_prefix(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Whether a path is never shed
+pub fn is_exempt(path: &str, prefixes: &[String]) -> bool {
+    prefixes.iter().any(|prefix| has_prefix(path, prefix))
+}
+
+/// Nearest-rank 99th percentile
+fn p99(mut samples: Vec<u64>) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    samples.sort_unstable();
+    let rank = (samples.len() * 99).div_ceil(100);
+    samples[rank.saturating_sub(1)]
+}
+
+/// Recent latencies of one group
+#[derive(Default)]
+struct LatencyWindow {
+    samples: VecDeque<(Instant, u64)>,
+    p99_ms: u64,
+    since_compute: usize,
+}
+
+impl LatencyWindow {
+    fn record(&mut self, at: Instant, ms: u64, window: Duration) {
+        self.samples.push_back((at, ms));
+        if self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.since_compute += 1;
+        if self.since_compute >= RECOMPUTE_EVERY {
+            self.prune(at, window);
+            self.recompute();
+        }
+    }
+
+    fn p99_ms(&mut self, now: Instant, window: Duration) -> u64 {
+        if self.prune(now, window) {
+            self.recompute();
+        }
+        if self.samples.len() < MIN_SAMPLES {
+            return 0;
+        }
+        self.p99_ms
+    }
+
+    /// Drop samples older than the window
+    fn prune(&mut self, now: Instant, window: Duration) -> bool {
+        let mut pruned = false;
+        while let Some((at, _)) = self.samples.front() {
+            if now.saturating_duration_since(*at) <= window {
+                break;
+            }
+            self.samples.pop_front();
+            pruned = true;
+        }
+        pruned
+    }
+
+    fn recompute(&mut self) {
+        self.since_compute = 0;
+        self.p99_ms = p99(self.samples.iter().map(|(_, ms)| *ms).collect());
+    }
+}
+
+#[derive(Default)]
+struct GroupStats {
+    in_flight: AtomicUsize,
+    latencies: Mutex<LatencyWindow>,
+}
+
+/// In-flight and latency tracking for all route groups
+pub struct LoadShedder {
+    max_in_flight: usize,
+    p99_target_ms: u64,
+    window: Duration,
+    groups: [GroupStats; RouteGroup::ALL.len()],
+}
+
+/// An admitted request; releases its in-flight slot when dropped
+pub struct Permit<'a> {
+    shedder: &'a LoadShedder,
+    group: RouteGroup,
+}
+
+impl Permit<'_> {
+    /// Record how long the request took
+    pub fn finish(self, elapsed: Duration) {
+        self.shedder.record(self.group, Instant::now(), elapsed);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.shedder
+            .stats(self.group)
+            .in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl LoadShedder {
+    pub fn new(max_in_flight: usize, p99_target_ms: u64, window: Duration) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            p99_target_ms: p99_target_ms.max(1),
+            window,
+            groups: Default::default(),
+        }
+    }
+
+    fn stats(&self, group: RouteGroup) -> &GroupStats {
+        &self.groups[group as usize]
+    }
+
+    /// Load of a group relative to its thresholds (1.0 = at the limit)
+    pub fn pressure(&self, group: RouteGroup, now: Instant) -> f64 {
+        let stats = self.stats(group);
+        let in_flight = stats.in_flight.load(Ordering::Relaxed) as f64;
+        let p99_ms = stats
+            .latencies
+            .lock()
+            .map(|mut window| window.p99_ms(now, self.window))
+            .unwrap_or(0);
+
+        f64::max(
+            in_flight / self.max_in_flight as f64,
+            p99_ms as f64 / self.p99_target_ms as f64,
+        )
+    }
+
+    /// Admit a request, or `None` when it should be shed
+    pub fn try_admit(&self, group: RouteGroup, now: Instant) -> Option<Permit<'_>> {
+        let priority = group.priority();
+        let pressure = match priority {
+            Priority::Low => RouteGroup::ALL
+                .iter()
+                .map(|g| self.pressure(*g, now))
+                .fold(0.0, f64::max),
+            _ => self.pressure(group, now),
+        };
+
+        if pressure >= priority.shed_at() {
+            return None;
+        }
+
+        self.stats(group).in_flight.fetch_add(1, Ordering::Relaxed);
+        Some(Permit {
+            shedder: self,
+            group,
+        })
+    }
+
+    fn record(&self, group: RouteGroup, at: Instant, elapsed: Duration) {
+        if let Ok(mut window) = self.stats(group).latencies.lock() {
+            window.record(at, elapsed.as_millis() as u64, self.window);
+        }
+    }
+}
+
+static SHEDDER: Lazy<LoadShedder> = Lazy::new(|| {
+    LoadShedder::new(
+        LoadSheddingConfig::max_in_flight(),
+        LoadSheddingConfig::p99_target_ms(),
+        Duration::from_secs(LoadSheddingConfig::latency_window_seconds()),
+    )
+});
+
+/// Load-shedding middleware
+pub async fn shed_load<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    if !LoadSheddingConfig::enabled()
+        || is_exempt(request.path(), LoadSheddingConfig::exempt_prefixes())
+    {
+        return next.call(request).await.map(|res| res.map_into_boxed_body());
+    }
+
+    let group = RouteGroup::of(request.path());
+    let Some(permit) = SHEDDER.try_admit(group, Instant::now()) else {
+        debug!(group = group.as_str(), path = %request.path(), "Request shed");
+        let response = HttpResponse::ServiceUnavailable()
+            .insert_header((
+                header::RETRY_AFTER,
+                LoadSheddingConfig::retry_after_seconds().to_string(),
+            ))
+            .json(BaseResponse::error("Server is busy, please retry shortly"));
+        return Ok(request.into_response(response).map_into_boxed_body());
+    };
+
+    let started = Instant::now();
+    let result = next.call(request).await;
+    permit.finish(started.elapsed());
+
+    result.map(|res| res.map_into_boxed_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shedder() -> LoadShedder {
+        LoadShedder::new(10, 1000, Duration::from_secs(10))
+    }
+
+    #[test]
+    fn groups_paths_by_prefix() {
+        assert_eq!(RouteGroup::of("/api/v1/auth/sign-in"), RouteGroup::Auth);
+        assert_eq!(RouteGroup::of("/oauth/authorize"), RouteGroup::Auth);
+        assert_eq!(RouteGroup::of("/api/v1/balance"), RouteGroup::Payments);
+        assert_eq!(RouteGroup::of("/api/v1/admin/users"), RouteGroup::Admin);
+        assert_eq!(RouteGroup::of("/api/v1/authors"), RouteGroup::Api);
+        assert_eq!(RouteGroup::of("/assets/js/app.js"), RouteGroup::Assets);
+        assert_eq!(RouteGroup::of("/sign_in"), RouteGroup::Web);
+    }
+
+    #[test]
+    fn exempt_prefixes_match_whole_segments() {
+        let prefixes = vec!["/health".to_string(), "/webhooks".to_string()];
+        assert!(is_exempt("/health", &prefixes));
+        assert!(is_exempt("/webhooks/stripe", &prefixes));
+        assert!(!is_exempt("/healthz", &prefixes));
+        assert!(!is_exempt("/api/v1/admin/games/webhooks", &prefixes));
+    }
+
+    #[test]
+    fn p99_uses_nearest_rank() {
+        assert_eq!(p99(vec![]), 0);
+        assert_eq!(p99((1..=100).collect()), 99);
+        assert_eq!(p99((1..=50).collect()), 50);
+    }
+
+    #[test]
+    fn sheds_low_priority_first_on_in_flight() {
+        let shedder = shedder();
+        let now = Instant::now();
+
+        let held: Vec<_> = (0..10)
+            .map(|_| shedder.try_admit(RouteGroup::Api, now).unwrap())
+            .collect();
+
+        // Api is at 1.0: pages go, the API itself and auth still pass
+        assert!(shedder.try_admit(RouteGroup::Web, now).is_none());
+        assert!(shedder.try_admit(RouteGroup::Api, now).is_some());
+        assert!(shedder.try_admit(RouteGroup::Auth, now).is_some());
+
+        drop(held);
+        assert!(shedder.try_admit(RouteGroup::Web, now).is_some());
+    }
+
+    #[test]
+    fn sheds_normal_before_high_within_its_own_group() {
+        let shedder = shedder();
+        let now = Instant::now();
+
+        let api: Vec<_> = (0..15)
+            .map(|_| shedder.try_admit(RouteGroup::Api, now).unwrap())
+            .collect();
+        assert!(shedder.try_admit(RouteGroup::Api, now).is_none());
+
+        let auth: Vec<_> = (0..19)
+            .map(|_| shedder.try_admit(RouteGroup::Auth, now).unwrap())
+            .collect();
+        assert!(shedder.try_admit(RouteGroup::Auth, now).is_some());
+        let _last = shedder.try_admit(RouteGroup::Auth, now).unwrap();
+        assert!(shedder.try_admit(RouteGroup::Auth, now).is_none());
+
+        drop((api, auth));
+    }
+
+    #[test]
+    fn slow_groups_are_shed_until_the_window_passes() {
+        let shedder = shedder();
+        let now = Instant::now();
+
+        for _ in 0..32 {
+            shedder.record(RouteGroup::Api, now, Duration::from_millis(1600));
+        }
+        assert!(shedder.try_admit(RouteGroup::Api, now).is_none());
+        assert!(shedder.try_admit(RouteGroup::Auth, now).is_some());
+
+        let later = now + Duration::from_secs(11);
+        assert!(shedder.try_admit(RouteGroup::Api, later).is_some());
+    }
+}
@@ -0,0 +1,74 @@
+//! Response localization middleware
+//!
+//! Translates the user-facing strings of JSON responses (`message`, validation
+//! `errors` and `fields[].message`) with the `i18n` catalogs. Handlers keep
+//! returning English; this runs last, on the way out.
+//!
+//! The locale is the user's profile setting when the request was authenticated
+//! (`verify_jwt` stores it from the token's `locale` claim), otherwise the
+//! `Accept-Language` header. English responses pass through untouched and the
+//! response carries `Content-Language`.
+
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderValue},
+    middleware::Next,
+    HttpMessage,
+};
+use i18n::Locale;
+
+/// Localization middleware
+pub async fn localize_response<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    let accept_language = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.call(request).await?.map_into_boxed_body();
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if !is_json {
+        return Ok(response);
+    }
+
+    // Set by verify_jwt from the token's locale claim
+    let profile_locale = response.request().extensions().get::<Locale>().copied();
+    let locale = profile_locale
+        .or_else(|| accept_language.as_deref().and_then(Locale::from_accept_language))
+        .unwrap_or_default();
+
+    let (http_request, mut http_response) = response.into_parts();
+    http_response.headers_mut().insert(
+        header::CONTENT_LANGUAGE,
+        HeaderValue::from_static(locale.code()),
+    );
+    if locale == Locale::En {
+        return Ok(ServiceResponse::new(http_request, http_response));
+    }
+
+    let (http_response, response_body) = http_response.into_parts();
+    let bytes = body::to_bytes(response_body).await.map_err(|_| {
+        actix_web::error::ErrorInternalServerError("Failed to read response body")
+    })?;
+    let bytes = match i18n::localize_json(&bytes, locale) {
+        Some(localized) => localized.into(),
+        None => bytes,
+    };
+
+    Ok(ServiceResponse::new(
+        http_request,
+        http_response.set_body(BoxBody::new(bytes)),
+    ))
+}
@@ -0,0 +1,64 @@
+//! Read-only maintenance middleware
+//!
+//! While maintenance mode is on (see `app::maintenance`), every request that
+//! is not a GET, HEAD or OPTIONS gets 503 with `Retry-After` and a body that
+//! tells the user what is going on:
+//!
+//! ```json
+//! {
+//!   "status": "error",
+//!   "code": "maintenance",
+//!   "message": "We are performing scheduled maintenance. ...",
+//!   "maintenance": { "read_only": true, "started_at": "...", "ends_at": null }
+//! }
+//! ```
+//!
+//! Reads keep working. Paths under `MAINTENANCE_EXEMPT_PREFIXES` (the toggle
+//! itself, signing in and out, health checks) still take writes.
+
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    HttpResponse,
+};
+use chrono::Utc;
+use tracing::debug;
+
+use crate::app::maintenance;
+use crate::config::MaintenanceConfig;
+
+/// Maintenance middleware
+pub async fn read_only<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    if maintenance_mode::is_read_only(request.method().as_str())
+        || maintenance_mode::is_exempt(request.path(), MaintenanceConfig::exempt_prefixes())
+    {
+        return next
+            .call(request)
+            .await
+            .map(|res| res.map_into_boxed_body());
+    }
+
+    let Some(notice) = maintenance::current().await else {
+        return next
+            .call(request)
+            .await
+            .map(|res| res.map_into_boxed_body());
+    };
+
+    debug!(method = %request.method(), path = %request.path(), "Write rejected during maintenance");
+    let response = HttpResponse::ServiceUnavailable()
+        .insert_header((
+            header::RETRY_AFTER,
+            notice.retry_after_secs(Utc::now()).to_string(),
+        ))
+        .json(notice.rejection_body());
+    Ok(request.into_response(response).map_into_boxed_body())
+}
@@ -2,9 +2,14 @@ pub mod auth;
 pub mod cors;
 pub mod csrf;
 pub mod dual_auth;
+pub mod idempotency;
 pub mod json_error;
+pub mod load_shedding;
+pub mod locale;
+pub mod maintenance;
 pub mod oauth_auth;
 pub mod permission;
+pub mod rbac;
 pub mod security_headers;
 pub mod tracing_logger;
 
@@ -14,3 +19,4 @@ pub use oauth_auth::{
     OAuthClaimsExt, OAuthExtractor, RequireScopes,
 };
 pub use permission::{is_admin, is_super_admin, levels, require_permission};
+pub use self::rbac::{perms, require_group, Guard};
@@ -4,6 +4,7 @@
 //!
 //! ## Permission Levels
 //! - 1: Basic (default for all users)
+//! - 5: Moderator (moderation tools, see `rbac`)
 //! - 10: Admin (can view uploads, manage assets)
 //! - 50: Affiliate (future affiliate features)
 //! - 100: Super Admin (full access, can view all users)
@@ -38,6 +39,8 @@ use actix_web::{
 pub mod levels {
     /// Basic user (default)
     pub const BASIC: i16 = 1;
+    /// Moderator - chat and room moderation
+    pub const MODERATOR: i16 = 5;
     /// Admin - can manage uploads, assets
     pub const ADMIN: i16 = 10;
     /// Affiliate - future affiliate features
@@ -61,6 +64,7 @@ fn forbidden_response(request: ServiceRequest, message: &'static str) -> Service
 /// * `required_level` - Required permission level for the route
 ///
 /// # Access Rules
+/// - MODERATOR (5): Allows Moderator (5), Admin (10) or Super Admin (100)
 /// - ADMIN (10): Allows Admin (10) or Super Admin (100)
 /// - SUPER_ADMIN (100): Allows only Super Admin (100)
 /// - AFFILIATE (50): Allows Affiliate (50) or Super Admin (100)
@@ -102,6 +106,7 @@ pub fn require_permission(
             // Other levels only have access to their specific level
             let has_access = match required {
                 levels::SUPER_ADMIN => permissions == levels::SUPER_ADMIN,
+                levels::MODERATOR => permissions == levels::MODERATOR || is_admin(permissions),
                 levels::ADMIN => permissions == levels::ADMIN || permissions == levels::SUPER_ADMIN,
                 levels::AFFILIATE => {
                     permissions == levels::AFFILIATE || permissions == levels::SUPER_ADMIN
@@ -141,6 +146,7 @@ pub fn permission_name(level: i16) -> &'static str {
         levels::SUPER_ADMIN => "Super Admin",
         levels::AFFILIATE => "Affiliate",
         levels::ADMIN => "Admin",
+        levels::MODERATOR => "Moderator",
         levels::BASIC => "Basic",
         _ => "Unknown",
     }
@@ -153,6 +159,7 @@ mod tests {
     #[test]
     fn test_is_admin() {
         assert!(!is_admin(1));
+        assert!(!is_admin(5)); // Moderator is NOT admin
         assert!(!is_admin(9));
         assert!(is_admin(10)); // Admin
         assert!(!is_admin(50)); // Affiliate is NOT admin
@@ -180,7 +187,7 @@ mod tests {
     #[test]
     fn test_permission_name() {
         assert_eq!(permission_name(1), "Basic");
-        assert_eq!(permission_name(5), "Unknown");
+        assert_eq!(permission_name(5), "Moderator");
         assert_eq!(permission_name(10), "Admin");
         assert_eq!(permission_name(49), "Unknown");
         assert_eq!(permission_name(50), "Affiliate");
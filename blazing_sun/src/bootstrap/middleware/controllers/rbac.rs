@@ -0,0 +1,216 @@
+//! RBAC Middleware
+//!
+//! Enforces the roles and permissions of the shared `rbac` crate on routes.
+//! A user's role follows from the permission level `verify_jwt` stores in the
+//! request extensions: 5 is a moderator, 10 and 100 are admins, everyone else
+//! is a user.
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use actix_web::middleware::from_fn;
+//! use crate::bootstrap::middleware::controllers::rbac::{require_group, Guard, perms};
+//!
+//! // Guard a whole route group
+//! scope("/api/v1/admin/roles")
+//!     .wrap(from_fn(require_group(RouteGroup::Roles)))
+//!     .wrap(from_fn(verify_jwt))
+//!
+//! // Or a single handler
+//! pub async fn assign(guard: Guard<perms::ManageRoles>) -> HttpResponse {
+//!     let admin_id = guard.user.user_id;
+//!     ...
+//! }
+//! ```
+
+use std::future::{ready, Future, Ready};
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use actix_web::{
+    body::BoxBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error::InternalError,
+    middleware::Next,
+    FromRequest, HttpMessage, HttpRequest, HttpResponse,
+};
+use rbac::{Permission, Role, RouteGroup};
+
+use crate::app::http::api::controllers::responses::BaseResponse;
+
+/// Role of a user with the given permission level
+pub fn role_of(permissions: i16) -> Role {
+    Role::from_level(i32::from(permissions))
+}
+
+type MiddlewareFuture =
+    Pin<Box<dyn Future<Output = Result<ServiceResponse<BoxBody>, actix_web::Error>>>>;
+
+fn forbidden(message: &'static str) -> HttpResponse {
+    HttpResponse::Forbidden().json(BaseResponse::error(message))
+}
+
+/// Factory function to create a middleware that lets through users whose role
+/// grants the group's permission
+///
+/// # Example
+/// ```rust,ignore
+/// .wrap(from_fn(require_group(RouteGroup::Moderation)))
+/// ```
+pub fn require_group(
+    group: RouteGroup,
+) -> impl Fn(ServiceRequest, Next<BoxBody>) -> MiddlewareFuture + Clone {
+    move |request: ServiceRequest, next: Next<BoxBody>| {
+        Box::pin(async move {
+            let Some(permissions) = request.extensions().get::<i16>().copied() else {
+                let response = forbidden("Authentication required");
+                return Ok(request.into_response(response).map_into_boxed_body());
+            };
+
+            if let Some(permission) = group.permission() {
+                if !role_of(permissions).can(permission) {
+                    let response = forbidden("Insufficient permissions");
+                    return Ok(request.into_response(response).map_into_boxed_body());
+                }
+            }
+
+            next.call(request).await
+        })
+    }
+}
+
+/// The signed-in user as seen by RBAC
+#[derive(Debug, Clone, Copy)]
+pub struct Authorized {
+    pub user_id: i64,
+    /// Permission level from the token
+    pub permissions: i16,
+    pub role: Role,
+}
+
+impl Authorized {
+    fn of(req: &HttpRequest) -> Option<Self> {
+        let extensions = req.extensions();
+        let user_id = extensions.get::<i64>().copied()?;
+        let permissions = extensions.get::<i16>().copied()?;
+
+        Some(Self {
+            user_id,
+            permissions,
+            role: role_of(permissions),
+        })
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.role.can(permission)
+    }
+}
+
+/// A permission a [`Guard`] can require
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+/// Marker types naming each permission for [`Guard`]
+pub mod perms {
+    use super::RequiredPermission;
+    use rbac::Permission;
+
+    macro_rules! permission_markers {
+        ($($name:ident),* $(,)?) => {
+            $(
+                pub struct $name;
+
+                impl RequiredPermission for $name {
+                    const PERMISSION: Permission = Permission::$name;
+                }
+            )*
+        };
+    }
+
+    permission_markers!(
+        PlayGames,
+        Chat,
+        ModerateChat,
+        ModerateRooms,
+        ViewUsers,
+        ManageUsers,
+        ManageRoles,
+        ManageContent,
+        ManageSystem,
+    );
+}
+
+/// Actix-web extractor that only lets the handler run when the user's role
+/// grants `P`; answers 401 without a signed-in user and 403 without the
+/// permission
+pub struct Guard<P: RequiredPermission> {
+    pub user: Authorized,
+    permission: PhantomData<P>,
+}
+
+impl<P: RequiredPermission> FromRequest for Guard<P> {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let Some(user) = Authorized::of(req) else {
+            let response = HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+            return ready(Err(InternalError::from_response("Unauthorized", response).into()));
+        };
+
+        if !user.can(P::PERMISSION) {
+            let response = forbidden("Insufficient permissions");
+            return ready(Err(
+                InternalError::from_response("Insufficient permissions", response).into(),
+            ));
+        }
+
+        ready(Ok(Self {
+            user,
+            permission: PhantomData,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootstrap::middleware::controllers::permission::levels;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn levels_match_the_shared_roles() {
+        assert_eq!(role_of(levels::BASIC), Role::User);
+        assert_eq!(role_of(levels::MODERATOR), Role::Moderator);
+        assert_eq!(role_of(levels::ADMIN), Role::Admin);
+        assert_eq!(role_of(levels::AFFILIATE), Role::User);
+        assert_eq!(role_of(levels::SUPER_ADMIN), Role::Admin);
+        for role in Role::ALL {
+            assert_eq!(role_of(role.level() as i16), role);
+        }
+    }
+
+    #[actix_web::test]
+    async fn guard_checks_the_role_of_the_token() {
+        let req = TestRequest::default().to_http_request();
+        req.extensions_mut().insert(7_i64);
+        req.extensions_mut().insert(levels::MODERATOR);
+
+        let guard = Guard::<perms::ModerateChat>::extract(&req).await.expect("moderator");
+        assert_eq!(guard.user.user_id, 7);
+        assert_eq!(guard.user.role, Role::Moderator);
+
+        let denied = Guard::<perms::ManageRoles>::extract(&req).await;
+        let status = denied.err().expect("denied").error_response().status();
+        assert_eq!(status, 403);
+
+        let anonymous = TestRequest::default().to_http_request();
+        let status = Guard::<perms::Chat>::extract(&anonymous)
+            .await
+            .err()
+            .expect("denied")
+            .error_response()
+            .status();
+        assert_eq!(status, 401);
+    }
+}
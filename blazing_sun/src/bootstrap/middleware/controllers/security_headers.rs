@@ -1,24 +1,29 @@
 use actix_web::http::header;
 use actix_web::middleware::DefaultHeaders;
 
+use crate::config::SecurityConfig;
+
+/// Security response headers from SecurityConfig (CSP_*, HSTS_MAX_AGE, X_FRAME_OPTIONS)
 pub fn configure() -> DefaultHeaders {
-    DefaultHeaders::new()
+    let mut headers = DefaultHeaders::new()
         .add((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
-        .add((header::X_FRAME_OPTIONS, "DENY"))
-        .add((
-            header::STRICT_TRANSPORT_SECURITY,
-            "max-age=31536000; includeSubDomains",
-        ))
+        .add((header::X_FRAME_OPTIONS, SecurityConfig::frame_options()))
         .add((header::X_XSS_PROTECTION, "1; mode=block"))
-        .add((
-            header::CONTENT_SECURITY_POLICY,
-            "default-src 'self'; \
-             script-src 'self' 'unsafe-inline'; \
-             style-src 'self' 'unsafe-inline'; \
-             img-src 'self' data: blob: https://*.tile.openstreetmap.org; \
-             font-src 'self'; \
-             connect-src 'self'; \
-             frame-ancestors 'none'",
-        ))
-        .add((header::REFERRER_POLICY, "strict-origin-when-cross-origin"))
+        .add((header::REFERRER_POLICY, "strict-origin-when-cross-origin"));
+
+    let csp_header = if SecurityConfig::csp_report_only() {
+        header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+    } else {
+        header::CONTENT_SECURITY_POLICY
+    };
+    headers = headers.add((csp_header, SecurityConfig::csp_policy()));
+
+    if SecurityConfig::hsts_max_age() > 0 {
+        headers = headers.add((
+            header::STRICT_TRANSPORT_SECURITY,
+            format!("max-age={}; includeSubDomains", SecurityConfig::hsts_max_age()),
+        ));
+    }
+
+    headers
 }
@@ -1,13 +1,11 @@
-use tracing_actix_web::TracingLogger;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+//! Structured logging
+//!
+//! Logs are JSON lines tagged `service: blazing_sun` (see the `logging` crate).
+//! `trace_request` gives every HTTP request a `request_id` span; MQ jobs and
+//! Kafka events created while handling it carry the same id.
 
-pub fn init() {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer().pretty())
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
-}
+pub use logging::http::{trace_request, RequestId};
 
-pub fn configure() -> TracingLogger<impl tracing_actix_web::RootSpanBuilder> {
-    TracingLogger::default()
+pub fn init() {
+    logging::init("blazing_sun", "info");
 }
@@ -3,7 +3,12 @@ pub mod controllers;
 pub use controllers::auth;
 pub use controllers::cors;
 pub use controllers::dual_auth;
+pub use controllers::idempotency;
+pub use controllers::load_shedding;
+pub use controllers::locale;
+pub use controllers::maintenance;
 pub use controllers::oauth_auth;
 pub use controllers::permission;
+pub use controllers::rbac;
 pub use controllers::security_headers;
 pub use controllers::tracing_logger;
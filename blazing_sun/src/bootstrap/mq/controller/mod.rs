@@ -1,5 +1,9 @@
 //! MQ Controller module
 
 pub mod mq;
+pub mod progress;
+pub mod worker;
 
 pub use mq::*;
+pub use progress::{JobProgress, JobSnapshot, ProgressReporter};
+pub use worker::{Registry, Worker, WorkerEntry, WorkerResult};
@@ -2,6 +2,7 @@
 //!
 //! Core RabbitMQ message queue infrastructure.
 
+use super::progress::{JobProgress, JobTracker};
 use crate::config::RabbitMQConfig;
 use futures_lite::StreamExt;
 use lapin::{
@@ -12,8 +13,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
+use thiserror::Error;
 use tokio::sync::Mutex;
-use tracing::{error, info, warn};
+use tracing::{error, info, warn, Instrument};
 use uuid::Uuid;
 
 const QUEUE_NAME: &str = "jobs";
@@ -56,6 +58,9 @@ pub struct JobOptions {
     pub priority: Priority,
     pub fault_tolerance: u32,
     pub delay_ms: Option<u64>,
+    /// User who submitted the job; their jobs' progress is tracked (see `progress`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<i64>,
 }
 
 impl Default for JobOptions {
@@ -64,6 +69,7 @@ impl Default for JobOptions {
             priority: Priority::Fifo,
             fault_tolerance: 3,
             delay_ms: None,
+            owner_id: None,
         }
     }
 }
@@ -87,6 +93,11 @@ impl JobOptions {
         self.delay_ms = Some(ms);
         self
     }
+
+    pub fn owner(mut self, user_id: i64) -> Self {
+        self.owner_id = Some(user_id);
+        self
+    }
 }
 
 /// Job status
@@ -99,6 +110,18 @@ pub enum JobStatus {
     Retrying,
 }
 
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Processing => "processing",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Retrying => "retrying",
+        }
+    }
+}
+
 /// A queued job with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueuedJob {
@@ -110,6 +133,12 @@ pub struct QueuedJob {
     pub attempts: u32,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Id of the HTTP request (or job, or event) that enqueued this job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Last progress the worker reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<JobProgress>,
 }
 
 impl QueuedJob {
@@ -124,6 +153,8 @@ impl QueuedJob {
             attempts: 0,
             created_at: now,
             updated_at: now,
+            request_id: logging::request_id::current(),
+            progress: None,
         }
     }
 }
@@ -136,14 +167,52 @@ pub enum JobResult<T> {
     Failed(String),
 }
 
+/// Message queue errors
+#[derive(Debug, Error)]
+pub enum MqError {
+    #[error("RabbitMQ error: {0}")]
+    Broker(#[from] lapin::Error),
+
+    #[error("Failed to serialize job: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("Message queue is not a RabbitMQ queue")]
+    Downcast,
+
+    #[error("Job {job_id} timed out after {timeout_ms}ms")]
+    Timeout { job_id: String, timeout_ms: u64 },
+}
+
+impl MqError {
+    /// HTTP status for handlers that surface queue failures to the client
+    pub fn status_code(&self) -> actix_web::http::StatusCode {
+        use actix_web::http::StatusCode;
+
+        match self {
+            MqError::Broker(_) => StatusCode::SERVICE_UNAVAILABLE,
+            MqError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            MqError::Serialize(_) | MqError::Downcast => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Whether retrying the same operation later can succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, MqError::Broker(_) | MqError::Timeout { .. })
+    }
+}
+
+/// Result type for message queue operations
+pub type MqResult<T> = Result<T, MqError>;
+
 /// The Message Queue manager using RabbitMQ
 pub struct MessageQueue {
     channel: Channel,
     db: Pool<Postgres>,
+    tracker: JobTracker,
 }
 
 impl MessageQueue {
-    pub async fn new(db: Pool<Postgres>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn new(db: Pool<Postgres>) -> MqResult<Self> {
         let url = RabbitMQConfig::url();
         info!("Connecting to RabbitMQ at {}", url);
 
@@ -179,14 +248,18 @@ impl MessageQueue {
 
         info!("RabbitMQ connection established");
 
-        Ok(Self { channel, db })
+        Ok(Self {
+            channel,
+            db,
+            tracker: JobTracker::default(),
+        })
     }
 
     /// Enqueue a job with priority support
     pub async fn enqueue(
         &self,
         job: QueuedJob,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> MqResult<String> {
         let job_id = job.id.clone();
         let job_json = serde_json::to_string(&job)?;
 
@@ -211,14 +284,46 @@ impl MessageQueue {
             "Job {} enqueued with priority {:?}",
             job_id, job.options.priority
         );
+        self.tracker.track(&job, None).await;
         Ok(job_id)
     }
 
+    /// Record how far a running job got; stored and pushed to the job's owner
+    /// (jobs without an owner are not tracked)
+    pub async fn report_progress(&self, job: &QueuedJob, progress: JobProgress) {
+        let mut job = job.clone();
+        job.status = JobStatus::Processing;
+        job.progress = Some(progress);
+        job.updated_at = chrono::Utc::now().timestamp_millis();
+        self.tracker.track(&job, None).await;
+    }
+
+    /// Track a status change of a job that is not re-enqueued
+    async fn set_status(&self, job: &QueuedJob, status: JobStatus, error: Option<&str>) {
+        let mut job = job.clone();
+        job.status = status;
+        job.updated_at = chrono::Utc::now().timestamp_millis();
+        self.tracker.track(&job, error).await;
+    }
+
+    /// Run a job's worker, tracking it as processing and, once it succeeds, completed
+    async fn run(
+        &self,
+        job: &QueuedJob,
+    ) -> Result<JobResult<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        self.set_status(job, JobStatus::Processing, None).await;
+        let result = crate::app::mq::workers::WORKERS.dispatch(self, job).await;
+        if let Ok(JobResult::Success(_)) = &result {
+            self.set_status(job, JobStatus::Completed, None).await;
+        }
+        result
+    }
+
     /// Get a consumer for the queue with unique tag
     pub async fn get_consumer(
         &self,
         worker_id: usize,
-    ) -> Result<Consumer, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> MqResult<Consumer> {
         let consumer_tag = format!("worker-{}", worker_id);
         let consumer = self
             .channel
@@ -237,7 +342,7 @@ impl MessageQueue {
     pub async fn ack(
         &self,
         delivery_tag: u64,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> MqResult<()> {
         self.channel
             .basic_ack(delivery_tag, BasicAckOptions::default())
             .await?;
@@ -249,7 +354,7 @@ impl MessageQueue {
         &self,
         delivery_tag: u64,
         requeue: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> MqResult<()> {
         self.channel
             .basic_nack(
                 delivery_tag,
@@ -267,7 +372,7 @@ impl MessageQueue {
         &self,
         job: &QueuedJob,
         error: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> MqResult<()> {
         let mut failed_job = job.clone();
         failed_job.status = JobStatus::Failed;
         failed_job.updated_at = chrono::Utc::now().timestamp_millis();
@@ -288,6 +393,7 @@ impl MessageQueue {
             .await?;
 
         error!("Job {} moved to failed queue: {}", failed_job.id, error);
+        self.tracker.track(&failed_job, Some(error)).await;
         Ok(())
     }
 
@@ -296,7 +402,7 @@ impl MessageQueue {
         &self,
         mut job: QueuedJob,
         error: &str,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> MqResult<bool> {
         job.attempts += 1;
 
         if job.attempts >= job.options.fault_tolerance {
@@ -317,6 +423,88 @@ impl MessageQueue {
         Ok(true)
     }
 
+    /// Read up to `limit` jobs from the failed queue without removing them
+    pub async fn peek_failed(&self, limit: usize) -> MqResult<Vec<QueuedJob>> {
+        let mut jobs = Vec::new();
+        let mut held = Vec::new();
+
+        // Messages stay unacked until the end so basic_get never returns one twice
+        while held.len() < limit {
+            let Some(message) = self
+                .channel
+                .basic_get(FAILED_QUEUE, BasicGetOptions::default())
+                .await?
+            else {
+                break;
+            };
+
+            held.push(message.delivery.delivery_tag);
+            match serde_json::from_slice::<QueuedJob>(&message.delivery.data) {
+                Ok(job) => jobs.push(job),
+                Err(e) => warn!("Unreadable message in failed queue: {}", e),
+            }
+        }
+
+        for delivery_tag in held {
+            self.nack(delivery_tag, true).await?;
+        }
+
+        Ok(jobs)
+    }
+
+    /// Move up to `limit` jobs from the failed queue back onto the main queue
+    ///
+    /// Requeued jobs start over with zero attempts. Jobs for other workers
+    /// (when `worker_name` is given) and unreadable messages stay in the
+    /// failed queue. Returns the ids of the requeued jobs.
+    pub async fn requeue_failed(
+        &self,
+        limit: usize,
+        worker_name: Option<&str>,
+    ) -> MqResult<Vec<String>> {
+        let mut requeued = Vec::new();
+        let mut kept = Vec::new();
+
+        while requeued.len() < limit {
+            let Some(message) = self
+                .channel
+                .basic_get(FAILED_QUEUE, BasicGetOptions::default())
+                .await?
+            else {
+                break;
+            };
+
+            let delivery_tag = message.delivery.delivery_tag;
+            let mut job = match serde_json::from_slice::<QueuedJob>(&message.delivery.data) {
+                Ok(job) if worker_name.map_or(true, |name| job.worker_name == name) => job,
+                Ok(_) => {
+                    kept.push(delivery_tag);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Unreadable message in failed queue: {}", e);
+                    kept.push(delivery_tag);
+                    continue;
+                }
+            };
+
+            job.status = JobStatus::Pending;
+            job.attempts = 0;
+            job.updated_at = chrono::Utc::now().timestamp_millis();
+
+            let job_id = self.enqueue(job).await?;
+            self.ack(delivery_tag).await?;
+            info!("Failed job {} requeued", job_id);
+            requeued.push(job_id);
+        }
+
+        for delivery_tag in kept {
+            self.nack(delivery_tag, true).await?;
+        }
+
+        Ok(requeued)
+    }
+
     /// Get the database pool
     pub fn db(&self) -> &Pool<Postgres> {
         &self.db
@@ -340,7 +528,7 @@ pub async fn enqueue_job<T: serde::Serialize>(
     worker_name: &str,
     params: &T,
     options: JobOptions,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+) -> MqResult<String> {
     let payload = serde_json::to_string(params)?;
     let job = QueuedJob::new(worker_name, payload, options);
     let mq = queue.lock().await;
@@ -353,13 +541,13 @@ pub async fn enqueue_job_dyn<T: serde::Serialize>(
     worker_name: &str,
     params: &T,
     options: JobOptions,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+) -> MqResult<String> {
     let payload = serde_json::to_string(params)?;
     let job = QueuedJob::new(worker_name, payload, options);
     let guard = queue.lock().await;
     let mq = guard
         .downcast_ref::<MessageQueue>()
-        .ok_or("Failed to downcast message queue")?;
+        .ok_or(MqError::Downcast)?;
     mq.enqueue(job).await
 }
 
@@ -371,42 +559,55 @@ pub async fn enqueue_and_wait_dyn<T: serde::Serialize>(
     params: &T,
     options: JobOptions,
     timeout_ms: u64,
-) -> Result<JobStatus, Box<dyn std::error::Error + Send + Sync>> {
+) -> MqResult<JobStatus> {
     let payload = serde_json::to_string(params)?;
     let job = QueuedJob::new(worker_name, payload, options.clone());
 
     let guard = queue.lock().await;
     let mq = guard
         .downcast_ref::<MessageQueue>()
-        .ok_or("Failed to downcast message queue")?;
+        .ok_or(MqError::Downcast)?;
 
     // Process the job synchronously for wait operations
     let result = tokio::time::timeout(
         std::time::Duration::from_millis(timeout_ms),
-        crate::app::mq::workers::process(mq, &job),
+        mq.run(&job),
     )
     .await;
 
     match result {
         Ok(Ok(JobResult::Success(_))) => Ok(JobStatus::Completed),
-        Ok(Ok(JobResult::Failed(_))) => Ok(JobStatus::Failed),
+        Ok(Ok(JobResult::Failed(reason))) => {
+            mq.set_status(&job, JobStatus::Failed, Some(&reason)).await;
+            Ok(JobStatus::Failed)
+        }
         Ok(Ok(JobResult::Retry(reason))) => {
             // Try to retry
             let mut retry_job = job.clone();
             retry_job.attempts += 1;
             if retry_job.attempts < options.fault_tolerance {
+                retry_job.status = JobStatus::Retrying;
                 mq.enqueue(retry_job).await?;
                 Ok(JobStatus::Retrying)
             } else {
                 warn!("Job failed after retries: {}", reason);
+                mq.set_status(&job, JobStatus::Failed, Some(&reason)).await;
                 Ok(JobStatus::Failed)
             }
         }
         Ok(Err(e)) => {
             error!("Job execution error: {}", e);
+            mq.set_status(&job, JobStatus::Failed, Some(&e.to_string())).await;
             Ok(JobStatus::Failed)
         }
-        Err(_) => Err("Job timeout".into()),
+        Err(_) => {
+            let error = MqError::Timeout {
+                job_id: job.id.clone(),
+                timeout_ms,
+            };
+            mq.set_status(&job, JobStatus::Failed, Some(&error.to_string())).await;
+            Err(error)
+        }
     }
 }
 
@@ -417,47 +618,58 @@ pub async fn enqueue_and_wait_result_dyn<T: serde::Serialize>(
     params: &T,
     options: JobOptions,
     timeout_ms: u64,
-) -> Result<JobResult<Value>, Box<dyn std::error::Error + Send + Sync>> {
+) -> MqResult<JobResult<Value>> {
     let payload = serde_json::to_string(params)?;
     let job = QueuedJob::new(worker_name, payload, options.clone());
 
     let guard = queue.lock().await;
     let mq = guard
         .downcast_ref::<MessageQueue>()
-        .ok_or("Failed to downcast message queue")?;
+        .ok_or(MqError::Downcast)?;
 
     let result = tokio::time::timeout(
         std::time::Duration::from_millis(timeout_ms),
-        crate::app::mq::workers::process(mq, &job),
+        mq.run(&job),
     )
     .await;
 
-    match result {
-        Ok(Ok(JobResult::Success(value))) => Ok(JobResult::Success(value)),
-        Ok(Ok(JobResult::Failed(reason))) => Ok(JobResult::Failed(reason)),
+    let result = match result {
+        Ok(Ok(JobResult::Success(value))) => return Ok(JobResult::Success(value)),
+        Ok(Ok(JobResult::Failed(reason))) => JobResult::Failed(reason),
         Ok(Ok(JobResult::Retry(reason))) => {
             let mut retry_job = job.clone();
             retry_job.attempts += 1;
             if retry_job.attempts < options.fault_tolerance {
+                retry_job.status = JobStatus::Retrying;
                 mq.enqueue(retry_job).await?;
-                Ok(JobResult::Retry(reason))
-            } else {
-                warn!("Job failed after retries: {}", reason);
-                Ok(JobResult::Failed(reason))
+                return Ok(JobResult::Retry(reason));
             }
+            warn!("Job failed after retries: {}", reason);
+            JobResult::Failed(reason)
         }
         Ok(Err(e)) => {
             error!("Job execution error: {}", e);
-            Ok(JobResult::Failed(e.to_string()))
+            JobResult::Failed(e.to_string())
         }
-        Err(_) => Ok(JobResult::Failed("Job timeout".to_string())),
+        Err(_) => JobResult::Failed(
+            MqError::Timeout {
+                job_id: job.id.clone(),
+                timeout_ms,
+            }
+            .to_string(),
+        ),
+    };
+
+    if let JobResult::Failed(reason) = &result {
+        mq.set_status(&job, JobStatus::Failed, Some(reason)).await;
     }
+    Ok(result)
 }
 
 /// Initialize the message queue
 pub async fn init(
     db: Pool<Postgres>,
-) -> Result<SharedQueue, Box<dyn std::error::Error + Send + Sync>> {
+) -> MqResult<SharedQueue> {
     info!("Initializing RabbitMQ message queue...");
     let mq = MessageQueue::new(db).await?;
     let shared = Arc::new(Mutex::new(mq));
@@ -482,10 +694,25 @@ pub async fn start_processor(queue: SharedQueue, concurrency: usize) {
     }
 }
 
+/// Hold a worker while read-only maintenance mode is on
+async fn wait_out_maintenance(worker_id: usize) {
+    let mut paused = false;
+    while crate::app::maintenance::current().await.is_some() {
+        if !paused {
+            info!("Worker {}: Paused for maintenance", worker_id);
+            paused = true;
+        }
+        tokio::time::sleep(maintenance_mode::REFRESH_INTERVAL).await;
+    }
+    if paused {
+        info!("Worker {}: Maintenance over, resuming", worker_id);
+    }
+}
+
 async fn process_worker(
     queue: SharedQueue,
     worker_id: usize,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> MqResult<()> {
     let consumer = {
         let mq = queue.lock().await;
         mq.get_consumer(worker_id).await?
@@ -496,6 +723,9 @@ async fn process_worker(
     info!("Worker {} started consuming", worker_id);
 
     while let Some(delivery) = consumer.next().await {
+        // Jobs write to the database, so none starts during maintenance
+        wait_out_maintenance(worker_id).await;
+
         match delivery {
             Ok(delivery) => {
                 let job_json = String::from_utf8_lossy(&delivery.data);
@@ -514,8 +744,17 @@ async fn process_worker(
                     worker_id, job.id, job.worker_name
                 );
 
+                // Jobs log, enqueue and publish under the request that created them
+                let span = tracing::info_span!(
+                    "job",
+                    job_id = %job.id,
+                    worker = %job.worker_name,
+                    request_id = job.request_id.as_deref(),
+                );
                 let mq = queue.lock().await;
-                let result = crate::app::mq::workers::process(&mq, &job).await;
+                let result = logging::request_id::scope(job.request_id.clone(), mq.run(&job))
+                    .instrument(span)
+                    .await;
 
                 match result {
                     Ok(JobResult::Success(_)) => {
@@ -0,0 +1,277 @@
+//! Job progress
+//!
+//! Jobs enqueued for a user (see [`JobOptions::owner`]) are tracked: every
+//! status change and every [`MessageQueue::report_progress`] call stores a
+//! [`JobSnapshot`] in Redis under `mq:job:{id}` for `GET /api/v1/jobs/{id}`,
+//! and pushes a `system.job_progress` event to the owner's WebSocket
+//! connections through the gateway (`system.events` topic).
+//!
+//! Tracking is best effort: Redis and Kafka errors are logged and never fail
+//! the job.
+//!
+//! [`JobOptions::owner`]: super::mq::JobOptions::owner
+//! [`MessageQueue::report_progress`]: super::mq::MessageQueue::report_progress
+
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::mq::{MessageQueue, QueuedJob};
+use crate::app::chat::types::{Actor, Audience, EventEnvelope};
+use crate::database::{create_redis, SharedRedis};
+use crate::events::{self, topic, SharedEventBus};
+
+const SNAPSHOT_PREFIX: &str = "mq:job:";
+
+/// How long a snapshot is kept after the job's last update
+const SNAPSHOT_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Event type of the gateway push (and of the client's server message)
+pub const JOB_PROGRESS_EVENT: &str = "system.job_progress";
+
+fn snapshot_key(job_id: &str) -> String {
+    format!("{}{}", SNAPSHOT_PREFIX, job_id)
+}
+
+/// How far a running job got
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// 0 to 100
+    pub percent: u8,
+    /// What the job is doing, e.g. "Anonymizing chat messages"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+impl JobProgress {
+    pub fn percent(percent: u8) -> Self {
+        Self {
+            percent: percent.min(100),
+            message: None,
+            processed: None,
+            total: None,
+        }
+    }
+
+    /// `processed` out of `total` items; an empty job counts as done
+    pub fn of(processed: u64, total: u64) -> Self {
+        let percent = if total == 0 {
+            100
+        } else {
+            (processed.min(total) * 100 / total) as u8
+        };
+
+        Self {
+            percent,
+            message: None,
+            processed: Some(processed),
+            total: Some(total),
+        }
+    }
+
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Reports the progress of one running job; handed to job code that does
+/// not see the queue
+pub struct ProgressReporter<'a> {
+    mq: &'a MessageQueue,
+    job: &'a QueuedJob,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(mq: &'a MessageQueue, job: &'a QueuedJob) -> Self {
+        Self { mq, job }
+    }
+
+    pub async fn report(&self, progress: JobProgress) {
+        self.mq.report_progress(self.job, progress).await;
+    }
+}
+
+/// What a client sees of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSnapshot {
+    pub id: String,
+    pub job_type: String,
+    /// pending, processing, retrying, completed or failed
+    pub status: String,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<JobProgress>,
+    /// Why the job failed or is being retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub owner_id: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl JobSnapshot {
+    /// Snapshot of a job; `None` for jobs without an owner
+    pub fn of(job: &QueuedJob, error: Option<&str>) -> Option<Self> {
+        let owner_id = job.options.owner_id?;
+        let timestamp = |ms: i64| DateTime::from_timestamp_millis(ms).unwrap_or_else(Utc::now);
+
+        Some(Self {
+            id: job.id.clone(),
+            job_type: job.worker_name.clone(),
+            status: job.status.as_str().to_string(),
+            attempts: job.attempts,
+            progress: job.progress.clone(),
+            error: error.map(str::to_string),
+            owner_id,
+            created_at: timestamp(job.created_at),
+            updated_at: timestamp(job.updated_at),
+        })
+    }
+
+    /// Stored snapshot of a job, if it is tracked and has not expired
+    pub async fn load(redis: &SharedRedis, job_id: &str) -> Result<Option<Self>, redis::RedisError> {
+        let mut redis = redis.clone();
+        let json: Option<String> = redis.get(snapshot_key(job_id)).await?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Gateway envelope pushing this snapshot to the owner
+    fn envelope(&self) -> EventEnvelope {
+        EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: JOB_PROGRESS_EVENT.to_string(),
+            timestamp: self.updated_at.to_rfc3339(),
+            correlation_id: logging::request_id::current(),
+            producer: "blazing_sun".to_string(),
+            actor: Actor {
+                user_id: 0, // System
+                username: "system".to_string(),
+                socket_id: String::new(),
+                roles: vec![],
+            },
+            audience: Audience::user(self.owner_id),
+            payload: serde_json::json!({
+                "job_id": self.id,
+                "job_type": self.job_type,
+                "status": self.status,
+                "percent": self.progress.as_ref().map(|p| p.percent),
+                "message": self.progress.as_ref().and_then(|p| p.message.clone()).or(self.error.clone()),
+            }),
+        }
+    }
+}
+
+/// Stores snapshots and pushes them to their owners; connects to Redis and
+/// Kafka on the first tracked job
+#[derive(Default)]
+pub struct JobTracker {
+    redis: tokio::sync::OnceCell<Option<SharedRedis>>,
+    event_bus: tokio::sync::OnceCell<Option<SharedEventBus>>,
+}
+
+impl JobTracker {
+    async fn redis(&self) -> Option<SharedRedis> {
+        self.redis
+            .get_or_init(|| async {
+                create_redis()
+                    .await
+                    .map_err(|e| warn!("Redis unavailable, job snapshots are not stored: {}", e))
+                    .ok()
+            })
+            .await
+            .clone()
+    }
+
+    async fn event_bus(&self) -> Option<&SharedEventBus> {
+        self.event_bus
+            .get_or_init(|| async {
+                events::init_producer()
+                    .map_err(|e| warn!("Kafka unavailable, job progress is not pushed: {}", e))
+                    .ok()
+            })
+            .await
+            .as_ref()
+    }
+
+    /// Store and push the job's current state; untracked jobs are ignored
+    pub async fn track(&self, job: &QueuedJob, error: Option<&str>) {
+        let Some(snapshot) = JobSnapshot::of(job, error) else {
+            return;
+        };
+
+        if let Some(mut redis) = self.redis().await {
+            match serde_json::to_string(&snapshot) {
+                Ok(json) => {
+                    let stored: Result<(), _> = redis
+                        .set_ex(snapshot_key(&snapshot.id), json, SNAPSHOT_TTL_SECS)
+                        .await;
+                    if let Err(e) = stored {
+                        warn!(job_id = %snapshot.id, "Failed to store job snapshot: {}", e);
+                    }
+                }
+                Err(e) => warn!(job_id = %snapshot.id, "Failed to encode job snapshot: {}", e),
+            }
+        }
+
+        let Some(event_bus) = self.event_bus().await else {
+            return;
+        };
+        let bytes = match serde_json::to_vec(&snapshot.envelope()) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(job_id = %snapshot.id, "Failed to encode job progress event: {}", e);
+                return;
+            }
+        };
+        let key = snapshot.owner_id.to_string();
+        match event_bus
+            .producer()
+            .send_raw(topic::SYSTEM_EVENTS, Some(&key), &bytes)
+            .await
+        {
+            Ok(()) => debug!(job_id = %snapshot.id, status = %snapshot.status, "Job progress pushed"),
+            Err(e) => warn!(job_id = %snapshot.id, "Failed to push job progress: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mq::{JobOptions, JobStatus};
+
+    #[test]
+    fn progress_is_capped_and_counts_empty_jobs_as_done() {
+        assert_eq!(JobProgress::percent(140).percent, 100);
+        assert_eq!(JobProgress::of(1, 3).percent, 33);
+        assert_eq!(JobProgress::of(5, 3).percent, 100);
+        assert_eq!(JobProgress::of(0, 0).percent, 100);
+    }
+
+    #[test]
+    fn only_owned_jobs_have_snapshots() {
+        let job = QueuedJob::new("erase_user", "{}".to_string(), JobOptions::new());
+        assert!(JobSnapshot::of(&job, None).is_none());
+
+        let mut job = QueuedJob::new("erase_user", "{}".to_string(), JobOptions::new().owner(7));
+        job.status = JobStatus::Processing;
+        job.progress = Some(JobProgress::of(2, 4).message("Anonymizing chat messages"));
+
+        let snapshot = JobSnapshot::of(&job, None).expect("snapshot");
+        assert_eq!(snapshot.owner_id, 7);
+        assert_eq!(snapshot.status, "processing");
+
+        let envelope = snapshot.envelope();
+        assert_eq!(envelope.event_type, JOB_PROGRESS_EVENT);
+        assert_eq!(envelope.audience.user_ids, vec!["7"]);
+        assert_eq!(envelope.payload["percent"], 50);
+        assert_eq!(envelope.payload["message"], "Anonymizing chat messages");
+    }
+}
@@ -0,0 +1,193 @@
+//! Typed workers
+//!
+//! A worker binds a job name to a payload type and an async handler:
+//!
+//! ```ignore
+//! pub struct DeleteUser;
+//!
+//! #[async_trait]
+//! impl Worker for DeleteUser {
+//!     const NAME: &'static str = "delete_user";
+//!     type Payload = DeleteUserParams;
+//!
+//!     async fn handle(mq: &MessageQueue, job: &QueuedJob, params: DeleteUserParams) -> WorkerResult {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! Workers are listed once, in the [`register_workers!`] call of
+//! `app::mq::workers`, which builds the registry from a constant table.
+//!
+//! The payload is decoded and checked with `validator::Validate` before the
+//! handler runs. A payload that does not decode or validate will not do any
+//! better on a retry, so its job goes straight to the failed queue.
+//! `CONCURRENCY` caps how many jobs of one worker run at once, whatever the
+//! processor's own concurrency.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tracing::error;
+use validator::Validate;
+
+use super::mq::{JobResult, MessageQueue, QueuedJob};
+
+/// What a worker's handler returns; `Err` is retried like [`JobResult::Retry`]
+pub type WorkerResult = Result<JobResult<Value>, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A job handler with a typed payload
+#[async_trait]
+pub trait Worker: 'static {
+    /// `QueuedJob::worker_name` of the jobs it runs
+    const NAME: &'static str;
+
+    /// Most jobs of this worker running at once (0 = no limit of its own)
+    const CONCURRENCY: usize = 0;
+
+    type Payload: DeserializeOwned + Validate + Send;
+
+    async fn handle(mq: &MessageQueue, job: &QueuedJob, payload: Self::Payload) -> WorkerResult;
+}
+
+type RunFn = for<'a> fn(&'a MessageQueue, &'a QueuedJob) -> BoxFuture<'a, WorkerResult>;
+
+/// A registered worker
+pub struct WorkerEntry {
+    pub name: &'static str,
+    pub concurrency: usize,
+    run: RunFn,
+}
+
+impl WorkerEntry {
+    pub const fn of<W: Worker>() -> Self {
+        Self {
+            name: W::NAME,
+            concurrency: W::CONCURRENCY,
+            run: run::<W>,
+        }
+    }
+}
+
+fn run<'a, W: Worker>(mq: &'a MessageQueue, job: &'a QueuedJob) -> BoxFuture<'a, WorkerResult> {
+    match decode::<W::Payload>(&job.payload) {
+        Ok(payload) => W::handle(mq, job, payload),
+        Err(reason) => {
+            error!("{} job {}: {}", W::NAME, job.id, reason);
+            Box::pin(async move { Ok(JobResult::Failed(reason)) })
+        }
+    }
+}
+
+/// Decode and validate a job payload; the error is the job's failure reason
+pub fn decode<P: DeserializeOwned + Validate>(payload: &str) -> Result<P, String> {
+    let payload: P =
+        serde_json::from_str(payload).map_err(|e| format!("Invalid payload: {}", e))?;
+    payload
+        .validate()
+        .map_err(|e| format!("Invalid payload: {}", e))?;
+    Ok(payload)
+}
+
+/// Workers by job name, with the semaphores enforcing their concurrency
+pub struct Registry {
+    workers: HashMap<&'static str, &'static WorkerEntry>,
+    limits: HashMap<&'static str, Semaphore>,
+}
+
+impl Registry {
+    /// Panics when two workers share a name
+    pub fn new(entries: &'static [WorkerEntry]) -> Self {
+        let mut workers = HashMap::new();
+        let mut limits = HashMap::new();
+
+        for entry in entries {
+            assert!(
+                workers.insert(entry.name, entry).is_none(),
+                "worker \"{}\" is registered twice",
+                entry.name
+            );
+            if entry.concurrency > 0 {
+                limits.insert(entry.name, Semaphore::new(entry.concurrency));
+            }
+        }
+
+        Self { workers, limits }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'static WorkerEntry> {
+        self.workers.get(name).copied()
+    }
+
+    /// Names of every registered worker, sorted
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.workers.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Run a job with its worker, waiting for a free slot when the worker is at its limit
+    pub async fn dispatch(&self, mq: &MessageQueue, job: &QueuedJob) -> WorkerResult {
+        let Some(worker) = self.get(&job.worker_name) else {
+            error!("Unknown worker: {}", job.worker_name);
+            return Ok(JobResult::Failed(format!(
+                "Unknown worker: {}",
+                job.worker_name
+            )));
+        };
+
+        let _permit = match self.limits.get(worker.name) {
+            Some(limit) => Some(limit.acquire().await?),
+            None => None,
+        };
+
+        (worker.run)(mq, job).await
+    }
+}
+
+/// Build the worker registry, `WORKERS`, from a list of [`Worker`] types
+///
+/// ```ignore
+/// register_workers! {
+///     create_user::CreateUser,
+///     email::SendEmail,
+/// }
+/// ```
+#[macro_export]
+macro_rules! register_workers {
+    ($($worker:ty),+ $(,)?) => {
+        const WORKER_ENTRIES: &[$crate::mq::WorkerEntry] =
+            &[$($crate::mq::WorkerEntry::of::<$worker>()),+];
+
+        /// Every worker, by job name
+        pub static WORKERS: once_cell::sync::Lazy<$crate::mq::Registry> =
+            once_cell::sync::Lazy::new(|| $crate::mq::Registry::new(WORKER_ENTRIES));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Payload {
+        #[validate(range(min = 1))]
+        user_id: i64,
+    }
+
+    #[test]
+    fn payloads_are_decoded_then_validated() {
+        assert_eq!(decode::<Payload>(r#"{"user_id":7}"#).unwrap().user_id, 7);
+
+        let malformed = decode::<Payload>(r#"{"user":7}"#).unwrap_err();
+        assert!(malformed.starts_with("Invalid payload: missing field `user_id`"));
+
+        let invalid = decode::<Payload>(r#"{"user_id":0}"#).unwrap_err();
+        assert!(invalid.starts_with("Invalid payload:") && invalid.contains("user_id"));
+    }
+}
@@ -5,6 +5,8 @@
 pub mod controller;
 
 pub use controller::mq::*;
+pub use controller::progress::{JobProgress, JobSnapshot, ProgressReporter};
+pub use controller::worker::{Registry, Worker, WorkerEntry, WorkerResult};
 
 // Re-export jobs and workers from app::mq
 pub use crate::app::mq::jobs;
@@ -180,6 +180,9 @@ impl Schedule {
 
 /// Convenience constants for common schedules
 pub mod schedules {
+    /// Every 10 seconds: "*/10 * * * * *"
+    pub const EVERY_TEN_SECONDS: &str = "*/10 * * * * *";
+
     /// Every minute: "0 * * * * *"
     pub const EVERY_MINUTE: &str = "0 * * * * *";
 
@@ -5,7 +5,10 @@ pub struct AppConfig {
     pub port: u16,
     pub rust_log: String,
     pub app_url: String,
-    pub checkout_service_token: String,
+    /// Deployment environment (APP_ENV): "production" or "development"
+    pub environment: String,
+    /// Base URL of the checkout service's internal HTTP API
+    pub checkout_service_url: String,
     /// Version string for CSS and JavaScript assets (e.g., "1.0.43")
     /// Used as query parameter: /assets/css/PAGE/style.css?v=1.0.43
     /// Update this when CSS/JS files change to bust browser cache.
@@ -27,8 +30,13 @@ pub static APP: Lazy<AppConfig> = Lazy::new(|| {
             .expect("PORT must be a valid number"),
         rust_log: std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
         app_url: std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:8888".to_string()),
-        checkout_service_token: std::env::var("CHECKOUT_SERVICE_TOKEN")
-            .unwrap_or_else(|_| "".to_string()),
+        environment: std::env::var("APP_ENV")
+            .map(|v| v.trim().to_ascii_lowercase())
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "production".to_string()),
+        checkout_service_url: std::env::var("CHECKOUT_SERVICE_URL")
+            .unwrap_or_else(|_| "http://checkout:9996".to_string()),
         assets_version: std::env::var("ASSETS_VERSION").unwrap_or_else(|_| "1.0.0".to_string()),
         images_assets_version: std::env::var("IMAGES_ASSETS_VERSION")
             .unwrap_or_else(|_| "1.0.0".to_string()),
@@ -52,8 +60,17 @@ impl AppConfig {
         &APP.app_url
     }
 
-    pub fn checkout_service_token() -> &'static str {
-        &APP.checkout_service_token
+    pub fn environment() -> &'static str {
+        &APP.environment
+    }
+
+    /// Whether APP_ENV selects the permissive development defaults
+    pub fn is_development() -> bool {
+        matches!(APP.environment.as_str(), "development" | "dev" | "local")
+    }
+
+    pub fn checkout_service_url() -> &'static str {
+        &APP.checkout_service_url
     }
 
     /// Get the current assets version (CSS/JS)
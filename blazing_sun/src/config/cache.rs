@@ -0,0 +1,46 @@
+use once_cell::sync::Lazy;
+
+pub struct CacheConfig {
+    pub user_profile_local_ttl_seconds: u64,
+    pub user_profile_redis_ttl_seconds: u64,
+    pub user_profile_max_entries: u64,
+}
+
+pub static CACHE: Lazy<CacheConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    CacheConfig {
+        user_profile_local_ttl_seconds: std::env::var("USER_PROFILE_CACHE_LOCAL_TTL_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("USER_PROFILE_CACHE_LOCAL_TTL_SECONDS must be a valid number"),
+        user_profile_redis_ttl_seconds: std::env::var("USER_PROFILE_CACHE_REDIS_TTL_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("USER_PROFILE_CACHE_REDIS_TTL_SECONDS must be a valid number"),
+        user_profile_max_entries: std::env::var("USER_PROFILE_CACHE_MAX_ENTRIES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .expect("USER_PROFILE_CACHE_MAX_ENTRIES must be a valid number"),
+    }
+});
+
+impl CacheConfig {
+    /// How long a profile stays in the in-process tier (default: 30)
+    ///
+    /// Other instances only learn about a change through Redis, so this bounds
+    /// how stale their copy can get.
+    pub fn user_profile_local_ttl_seconds() -> u64 {
+        CACHE.user_profile_local_ttl_seconds
+    }
+
+    /// How long a profile stays in the shared Redis tier (default: 300)
+    pub fn user_profile_redis_ttl_seconds() -> u64 {
+        CACHE.user_profile_redis_ttl_seconds
+    }
+
+    /// Profiles kept in the in-process tier before the least used are evicted (default: 10000)
+    pub fn user_profile_max_entries() -> u64 {
+        CACHE.user_profile_max_entries
+    }
+}
@@ -1,19 +1,102 @@
+use super::SecretsConfig;
 use once_cell::sync::Lazy;
 
+/// Sizing and timeouts of one subsystem's connection pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    /// Longest wait for a free connection before the query fails
+    pub acquire_timeout_ms: u64,
+    /// Postgres statement_timeout of the pool's connections (0 = no limit)
+    pub statement_timeout_ms: u64,
+}
+
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
+    pub schema_check: String,
+    pub http_pool: PoolSettings,
+    pub cron_pool: PoolSettings,
+    pub mq_pool: PoolSettings,
+    pub events_pool: PoolSettings,
+    pub slow_acquire_ms: u64,
+    pub pool_sample_interval_secs: u64,
+    pub read_url: Option<String>,
+    pub read_max_lag_ms: u64,
+    pub read_health_interval_secs: u64,
+    pub read_your_writes_ms: u64,
+}
+
+fn env_u64(key: &str) -> Option<u64> {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+}
+
+/// Settings of the DATABASE_{prefix}_* pool, falling back to the shared ones
+fn pool_settings(prefix: &str, defaults: PoolSettings) -> PoolSettings {
+    let max_connections = env_u64(&format!("DATABASE_{}_MAX_CONNECTIONS", prefix))
+        .map(|v| v as u32)
+        .filter(|max| *max > 0)
+        .unwrap_or(defaults.max_connections);
+    let min_connections = env_u64(&format!("DATABASE_{}_MIN_CONNECTIONS", prefix))
+        .map(|v| v as u32)
+        .unwrap_or(defaults.min_connections)
+        .min(max_connections);
+
+    PoolSettings {
+        max_connections,
+        min_connections,
+        acquire_timeout_ms: env_u64(&format!("DATABASE_{}_ACQUIRE_TIMEOUT_MS", prefix))
+            .filter(|ms| *ms > 0)
+            .unwrap_or(defaults.acquire_timeout_ms),
+        statement_timeout_ms: env_u64(&format!("DATABASE_{}_STATEMENT_TIMEOUT_MS", prefix))
+            .unwrap_or(defaults.statement_timeout_ms),
+    }
 }
 
 pub static DATABASE: Lazy<DatabaseConfig> = Lazy::new(|| {
     dotenv::dotenv().ok();
 
+    let max_connections = std::env::var("DATABASE_MAX_CONNECTIONS")
+        .unwrap_or_else(|_| "10000".to_string())
+        .parse()
+        .expect("DATABASE_MAX_CONNECTIONS must be a valid number");
+
+    // Shared defaults; each subsystem can override them with DATABASE_{HTTP,CRON,MQ,EVENTS}_*
+    let defaults = PoolSettings {
+        max_connections,
+        min_connections: env_u64("DATABASE_MIN_CONNECTIONS").unwrap_or(0) as u32,
+        acquire_timeout_ms: env_u64("DATABASE_ACQUIRE_TIMEOUT_MS")
+            .filter(|ms| *ms > 0)
+            .unwrap_or(30_000),
+        statement_timeout_ms: env_u64("DATABASE_STATEMENT_TIMEOUT_MS").unwrap_or(0),
+    };
+
     DatabaseConfig {
-        url: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
-        max_connections: std::env::var("DATABASE_MAX_CONNECTIONS")
-            .unwrap_or_else(|_| "10000".to_string())
-            .parse()
-            .expect("DATABASE_MAX_CONNECTIONS must be a valid number"),
+        url: SecretsConfig::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+        max_connections,
+        schema_check: std::env::var("DATABASE_SCHEMA_CHECK")
+            .unwrap_or_else(|_| "enforce".to_string()),
+        http_pool: pool_settings("HTTP", defaults),
+        cron_pool: pool_settings("CRON", defaults),
+        mq_pool: pool_settings("MQ", defaults),
+        events_pool: pool_settings("EVENTS", defaults),
+        // Waits for a connection longer than this are logged as slow
+        slow_acquire_ms: env_u64("DATABASE_SLOW_ACQUIRE_MS")
+            .filter(|ms| *ms > 0)
+            .unwrap_or(500),
+        pool_sample_interval_secs: env_u64("DATABASE_POOL_SAMPLE_INTERVAL_SECS")
+            .filter(|secs| *secs > 0)
+            .unwrap_or(10),
+        // Read replica; unset means every query runs on the primary
+        read_url: SecretsConfig::var("DATABASE_READ_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty()),
+        read_max_lag_ms: env_u64("DATABASE_READ_MAX_LAG_MS").unwrap_or(1_000),
+        read_health_interval_secs: env_u64("DATABASE_READ_HEALTH_INTERVAL_SECS")
+            .filter(|secs| *secs > 0)
+            .unwrap_or(5),
+        read_your_writes_ms: env_u64("DATABASE_READ_YOUR_WRITES_MS").unwrap_or(5_000),
     }
 });
 
@@ -25,4 +108,61 @@ impl DatabaseConfig {
     pub fn max_connections() -> u32 {
         DATABASE.max_connections
     }
+
+    /// Startup schema drift check mode: enforce | warn | off
+    pub fn schema_check() -> &'static str {
+        &DATABASE.schema_check
+    }
+
+    /// Pool of the HTTP handlers (AppState)
+    pub fn http_pool() -> PoolSettings {
+        DATABASE.http_pool
+    }
+
+    /// Pool of the cron jobs
+    pub fn cron_pool() -> PoolSettings {
+        DATABASE.cron_pool
+    }
+
+    /// Pool of the RabbitMQ job workers
+    pub fn mq_pool() -> PoolSettings {
+        DATABASE.mq_pool
+    }
+
+    /// Pool of the Kafka event handlers
+    pub fn events_pool() -> PoolSettings {
+        DATABASE.events_pool
+    }
+
+    /// Connection waits logged as slow (DATABASE_SLOW_ACQUIRE_MS, default 500)
+    pub fn slow_acquire_ms() -> u64 {
+        DATABASE.slow_acquire_ms
+    }
+
+    /// How often pool usage is sampled (DATABASE_POOL_SAMPLE_INTERVAL_SECS, default 10)
+    pub fn pool_sample_interval_secs() -> u64 {
+        DATABASE.pool_sample_interval_secs
+    }
+
+    /// Read replica URL (DATABASE_READ_URL), None when reads use the primary
+    pub fn read_url() -> Option<&'static str> {
+        DATABASE.read_url.as_deref()
+    }
+
+    /// Replay lag above which the replica is skipped (DATABASE_READ_MAX_LAG_MS, default 1000)
+    pub fn read_max_lag_ms() -> u64 {
+        DATABASE.read_max_lag_ms
+    }
+
+    /// How often the replica's health and lag are checked
+    /// (DATABASE_READ_HEALTH_INTERVAL_SECS, default 5)
+    pub fn read_health_interval_secs() -> u64 {
+        DATABASE.read_health_interval_secs
+    }
+
+    /// How long a user's reads stay on the primary after their own write
+    /// (DATABASE_READ_YOUR_WRITES_MS, default 5000)
+    pub fn read_your_writes_ms() -> u64 {
+        DATABASE.read_your_writes_ms
+    }
 }
@@ -1,3 +1,4 @@
+use super::SecretsConfig;
 use once_cell::sync::Lazy;
 
 pub struct EmailConfig {
@@ -29,7 +30,7 @@ pub static EMAIL: Lazy<EmailConfig> = Lazy::new(|| {
             .parse()
             .expect("MAIL_PORT must be a valid number"),
         username: std::env::var("MAIL_USERNAME").expect("MAIL_USERNAME must be set"),
-        password: std::env::var("MAIL_PASSWORD").expect("MAIL_PASSWORD must be set"),
+        password: SecretsConfig::var("MAIL_PASSWORD").expect("MAIL_PASSWORD must be set"),
         from_address: std::env::var("MAIL_FROM_ADDRESS")
             .unwrap_or_else(|_| "noreply@example.com".to_string()),
         from_name: std::env::var("MAIL_FROM_NAME").unwrap_or_else(|_| "App".to_string()),
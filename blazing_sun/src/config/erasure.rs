@@ -0,0 +1,33 @@
+use once_cell::sync::Lazy;
+
+pub struct ErasureConfig {
+    pub grace_period_days: i64,
+    pub export_ttl_hours: i64,
+}
+
+pub static ERASURE: Lazy<ErasureConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    ErasureConfig {
+        grace_period_days: std::env::var("ERASURE_GRACE_PERIOD_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("ERASURE_GRACE_PERIOD_DAYS must be a valid number"),
+        export_ttl_hours: std::env::var("DATA_EXPORT_TTL_HOURS")
+            .unwrap_or_else(|_| "72".to_string())
+            .parse()
+            .expect("DATA_EXPORT_TTL_HOURS must be a valid number"),
+    }
+});
+
+impl ErasureConfig {
+    /// Days an account deletion can be cancelled before the data is erased (default: 30)
+    pub fn grace_period_days() -> i64 {
+        ERASURE.grace_period_days
+    }
+
+    /// Hours a data export can be downloaded before its archive is deleted (default: 72)
+    pub fn export_ttl_hours() -> i64 {
+        ERASURE.export_ttl_hours
+    }
+}
@@ -1,9 +1,97 @@
 use once_cell::sync::Lazy;
 
+/// Region used when GAME_REGION is not set (single-region deployments)
+pub const DEFAULT_REGION: &str = "default";
+
+/// A region that can own game rooms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRegion {
+    pub name: String,
+    /// WebSocket gateway URL clients reconnect to after a room moves here
+    pub gateway_url: String,
+}
+
 pub struct GamesConfig {
     pub bigger_dice_winning_percentage: i32,
     pub bigger_dice_entry_fee_cents: i64,
     pub bigger_dice_ready_timeout_seconds: i32,
+    pub region: String,
+    pub regions: Vec<GameRegion>,
+    pub region_drain_target: Option<String>,
+    pub occupancy_throttle_ms: u64,
+    pub bot_user_id: Option<i64>,
+    pub bot_username: String,
+    pub bot_move_delay_ms: u64,
+    pub room_password_max_attempts: u32,
+    pub room_password_lockout_seconds: u64,
+    pub prediction_window_seconds: i64,
+    pub prediction_min_stake_cents: i64,
+    pub prediction_max_stake_cents: i64,
+    pub webhook_max_attempts: u32,
+    pub webhook_timeout_seconds: u64,
+    pub tournament_prize_split: Vec<u32>,
+    pub room_list_projection_ttl_seconds: u64,
+    pub room_inactivity_timeout_minutes: i64,
+    pub room_inactivity_warning_minutes: i64,
+    pub room_inactivity_extension_minutes: i64,
+    pub spectator_waitlist_max_size: u32,
+    pub spectator_waitlist_ttl_seconds: u64,
+    pub room_schedule_max_days: i64,
+    pub room_schedule_min_lead_minutes: i64,
+    pub room_schedule_reminder_minutes: Vec<i64>,
+    pub room_invite_ttl_seconds: i64,
+}
+
+/// Parse `GAME_REGIONS` ("eu-west=wss://eu.example.com/ws,us-east=wss://us.example.com/ws")
+fn parse_regions(value: &str) -> Vec<GameRegion> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (name, url) = entry.split_once('=')?;
+            let (name, url) = (name.trim(), url.trim());
+            (!name.is_empty() && !url.is_empty()).then(|| GameRegion {
+                name: name.to_string(),
+                gateway_url: url.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `GAME_ROOM_SCHEDULE_REMINDER_MINUTES` ("60,10": an hour and ten
+/// minutes before a scheduled room opens), largest first
+fn parse_reminder_minutes(value: &str) -> Vec<i64> {
+    let mut minutes: Vec<i64> = value
+        .split(',')
+        .filter(|m| !m.trim().is_empty())
+        .map(|m| {
+            m.trim()
+                .parse()
+                .expect("GAME_ROOM_SCHEDULE_REMINDER_MINUTES must be comma-separated minutes")
+        })
+        .filter(|m| *m > 0)
+        .collect();
+
+    minutes.sort_unstable_by(|a, b| b.cmp(a));
+    minutes.dedup();
+    minutes
+}
+
+/// Parse `GAME_TOURNAMENT_PRIZE_SPLIT` ("60,25,15": champion, runner-up, semi-final losers, ...)
+fn parse_prize_split(value: &str) -> Vec<u32> {
+    let split: Vec<u32> = value
+        .split(',')
+        .map(|p| {
+            p.trim()
+                .parse()
+                .expect("GAME_TOURNAMENT_PRIZE_SPLIT must be comma-separated percentages")
+        })
+        .collect();
+
+    assert!(
+        split.iter().sum::<u32>() <= 100,
+        "GAME_TOURNAMENT_PRIZE_SPLIT must not add up to more than 100"
+    );
+    split
 }
 
 pub static GAMES: Lazy<GamesConfig> = Lazy::new(|| {
@@ -22,6 +110,100 @@ pub static GAMES: Lazy<GamesConfig> = Lazy::new(|| {
             .unwrap_or_else(|_| "30".to_string())
             .parse()
             .expect("BIGGER_DICE_READY_TIMEOUT_SECONDS must be a valid number"),
+        region: std::env::var("GAME_REGION")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_REGION.to_string()),
+        regions: parse_regions(&std::env::var("GAME_REGIONS").unwrap_or_default()),
+        region_drain_target: std::env::var("GAME_REGION_DRAIN_TO")
+            .ok()
+            .filter(|v| !v.trim().is_empty()),
+        occupancy_throttle_ms: std::env::var("GAME_OCCUPANCY_THROTTLE_MS")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse()
+            .expect("GAME_OCCUPANCY_THROTTLE_MS must be a valid number"),
+        bot_user_id: std::env::var("GAME_BOT_USER_ID")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| v.trim().parse().expect("GAME_BOT_USER_ID must be a valid user id")),
+        bot_username: std::env::var("GAME_BOT_USERNAME")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| "Bot".to_string()),
+        bot_move_delay_ms: std::env::var("GAME_BOT_MOVE_DELAY_MS")
+            .unwrap_or_else(|_| "800".to_string())
+            .parse()
+            .expect("GAME_BOT_MOVE_DELAY_MS must be a valid number"),
+        room_password_max_attempts: std::env::var("GAME_ROOM_PASSWORD_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("GAME_ROOM_PASSWORD_MAX_ATTEMPTS must be a valid number"),
+        room_password_lockout_seconds: std::env::var("GAME_ROOM_PASSWORD_LOCKOUT_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("GAME_ROOM_PASSWORD_LOCKOUT_SECONDS must be a valid number"),
+        prediction_window_seconds: std::env::var("GAME_PREDICTION_WINDOW_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("GAME_PREDICTION_WINDOW_SECONDS must be a valid number"),
+        prediction_min_stake_cents: std::env::var("GAME_PREDICTION_MIN_STAKE_CENTS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .expect("GAME_PREDICTION_MIN_STAKE_CENTS must be a valid number"),
+        prediction_max_stake_cents: std::env::var("GAME_PREDICTION_MAX_STAKE_CENTS")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .expect("GAME_PREDICTION_MAX_STAKE_CENTS must be a valid number"),
+        webhook_max_attempts: std::env::var("GAME_WEBHOOK_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()
+            .expect("GAME_WEBHOOK_MAX_ATTEMPTS must be a valid number"),
+        webhook_timeout_seconds: std::env::var("GAME_WEBHOOK_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .expect("GAME_WEBHOOK_TIMEOUT_SECONDS must be a valid number"),
+        tournament_prize_split: parse_prize_split(
+            &std::env::var("GAME_TOURNAMENT_PRIZE_SPLIT").unwrap_or_else(|_| "70,30".to_string()),
+        ),
+        room_list_projection_ttl_seconds: std::env::var("GAME_ROOM_LIST_PROJECTION_TTL_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("GAME_ROOM_LIST_PROJECTION_TTL_SECONDS must be a valid number"),
+        room_inactivity_timeout_minutes: std::env::var("GAME_ROOM_INACTIVITY_TIMEOUT_MINUTES")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("GAME_ROOM_INACTIVITY_TIMEOUT_MINUTES must be a valid number"),
+        room_inactivity_warning_minutes: std::env::var("GAME_ROOM_INACTIVITY_WARNING_MINUTES")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("GAME_ROOM_INACTIVITY_WARNING_MINUTES must be a valid number"),
+        room_inactivity_extension_minutes: std::env::var("GAME_ROOM_INACTIVITY_EXTENSION_MINUTES")
+            .unwrap_or_else(|_| "15".to_string())
+            .parse()
+            .expect("GAME_ROOM_INACTIVITY_EXTENSION_MINUTES must be a valid number"),
+        spectator_waitlist_max_size: std::env::var("GAME_SPECTATOR_WAITLIST_MAX_SIZE")
+            .unwrap_or_else(|_| "50".to_string())
+            .parse()
+            .expect("GAME_SPECTATOR_WAITLIST_MAX_SIZE must be a valid number"),
+        spectator_waitlist_ttl_seconds: std::env::var("GAME_SPECTATOR_WAITLIST_TTL_SECONDS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("GAME_SPECTATOR_WAITLIST_TTL_SECONDS must be a valid number"),
+        room_schedule_max_days: std::env::var("GAME_ROOM_SCHEDULE_MAX_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("GAME_ROOM_SCHEDULE_MAX_DAYS must be a valid number"),
+        room_schedule_min_lead_minutes: std::env::var("GAME_ROOM_SCHEDULE_MIN_LEAD_MINUTES")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("GAME_ROOM_SCHEDULE_MIN_LEAD_MINUTES must be a valid number"),
+        room_schedule_reminder_minutes: parse_reminder_minutes(
+            &std::env::var("GAME_ROOM_SCHEDULE_REMINDER_MINUTES").unwrap_or_else(|_| "60,10".to_string()),
+        ),
+        room_invite_ttl_seconds: std::env::var("GAME_ROOM_INVITE_TTL_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("GAME_ROOM_INVITE_TTL_SECONDS must be a valid number"),
     }
 });
 
@@ -40,4 +222,137 @@ impl GamesConfig {
     pub fn bigger_dice_ready_timeout_seconds() -> i32 {
         GAMES.bigger_dice_ready_timeout_seconds
     }
+
+    /// Region this instance owns rooms for (default: "default")
+    pub fn region() -> &'static str {
+        &GAMES.region
+    }
+
+    /// Configured regions (GAME_REGIONS); empty in single-region deployments
+    pub fn regions() -> &'static [GameRegion] {
+        &GAMES.regions
+    }
+
+    /// Look up a configured region by name
+    pub fn find_region(name: &str) -> Option<&'static GameRegion> {
+        GAMES.regions.iter().find(|r| r.name == name)
+    }
+
+    /// Region that waiting rooms of this instance are moved to while it is
+    /// being drained (GAME_REGION_DRAIN_TO), if any
+    pub fn region_drain_target() -> Option<&'static str> {
+        GAMES.region_drain_target.as_deref()
+    }
+
+    /// Window in which room occupancy changes are coalesced into one
+    /// room_occupancy_changed event (default: 500 ms)
+    pub fn occupancy_throttle_ms() -> u64 {
+        GAMES.occupancy_throttle_ms
+    }
+
+    /// Account bots play as in `vs_bot` rooms (GAME_BOT_USER_ID); bot rooms
+    /// are unavailable when unset
+    pub fn bot_user_id() -> Option<i64> {
+        GAMES.bot_user_id
+    }
+
+    /// Display name of the bot account (default: "Bot")
+    pub fn bot_username() -> &'static str {
+        &GAMES.bot_username
+    }
+
+    /// Pause before a bot command is published (default: 800 ms)
+    pub fn bot_move_delay_ms() -> u64 {
+        GAMES.bot_move_delay_ms
+    }
+
+    /// Wrong room passwords a user may enter per room before being locked out (default: 5)
+    pub fn room_password_max_attempts() -> u32 {
+        GAMES.room_password_max_attempts
+    }
+
+    /// How long wrong attempts are counted and a lockout lasts (default: 300 s)
+    pub fn room_password_lockout_seconds() -> u64 {
+        GAMES.room_password_lockout_seconds
+    }
+
+    /// How long after the game starts spectators may predict the winner (default: 60 s)
+    pub fn prediction_window_seconds() -> i64 {
+        GAMES.prediction_window_seconds
+    }
+
+    /// Smallest and largest prediction stake in cents (default: 10 to 10000)
+    pub fn prediction_stake_limits() -> (i64, i64) {
+        (GAMES.prediction_min_stake_cents, GAMES.prediction_max_stake_cents)
+    }
+
+    /// Attempts per webhook delivery before it is marked failed (default: 8)
+    pub fn webhook_max_attempts() -> u32 {
+        GAMES.webhook_max_attempts
+    }
+
+    /// Timeout of one webhook delivery attempt (default: 10 s)
+    pub fn webhook_timeout_seconds() -> u64 {
+        GAMES.webhook_timeout_seconds
+    }
+
+    /// Percent of a tournament pool per place (default: 70 to the champion, 30 to the runner-up)
+    pub fn tournament_prize_split() -> &'static [u32] {
+        &GAMES.tournament_prize_split
+    }
+
+    /// How long a Redis room list projection is trusted before it is rebuilt
+    /// from Postgres (default: 300 s; 0 disables the projection)
+    pub fn room_list_projection_ttl_seconds() -> u64 {
+        GAMES.room_list_projection_ttl_seconds
+    }
+
+    /// Minutes without a command after which a waiting room is closed
+    /// (default: 30; 0 disables inactivity closing)
+    pub fn room_inactivity_timeout_minutes() -> i64 {
+        GAMES.room_inactivity_timeout_minutes
+    }
+
+    /// How long before closing an inactive room its members are warned (default: 5 min)
+    pub fn room_inactivity_warning_minutes() -> i64 {
+        GAMES.room_inactivity_warning_minutes
+    }
+
+    /// Minutes the host adds when extending an inactive room (default: 15)
+    pub fn room_inactivity_extension_minutes() -> i64 {
+        GAMES.room_inactivity_extension_minutes
+    }
+
+    /// Users that can queue for a full room's spectator seats (default: 50;
+    /// 0 disables the waitlist)
+    pub fn spectator_waitlist_max_size() -> u32 {
+        GAMES.spectator_waitlist_max_size
+    }
+
+    /// Seconds a queued spectator keeps their place without asking again (default: 600)
+    pub fn spectator_waitlist_ttl_seconds() -> u64 {
+        GAMES.spectator_waitlist_ttl_seconds
+    }
+
+    /// How far ahead a room can be scheduled (default: 30 days; 0 stops new
+    /// rooms from being scheduled)
+    pub fn room_schedule_max_days() -> i64 {
+        GAMES.room_schedule_max_days
+    }
+
+    /// How soon a scheduled room may open at the earliest (default: 5 min)
+    pub fn room_schedule_min_lead_minutes() -> i64 {
+        GAMES.room_schedule_min_lead_minutes
+    }
+
+    /// Minutes before a scheduled room opens at which registered users are
+    /// reminded, largest first (default: 60 and 10)
+    pub fn room_schedule_reminder_minutes() -> &'static [i64] {
+        &GAMES.room_schedule_reminder_minutes
+    }
+
+    /// How long an invitation into a room can be answered (default: 300 seconds)
+    pub fn room_invite_ttl_seconds() -> i64 {
+        GAMES.room_invite_ttl_seconds
+    }
 }
@@ -0,0 +1,32 @@
+use once_cell::sync::Lazy;
+
+pub struct IdempotencyConfig {
+    pub enabled: bool,
+    pub ttl_seconds: u64,
+}
+
+pub static IDEMPOTENCY: Lazy<IdempotencyConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    IdempotencyConfig {
+        enabled: std::env::var("IDEMPOTENCY_ENABLED")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true),
+        ttl_seconds: std::env::var("IDEMPOTENCY_TTL_SECONDS")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .expect("IDEMPOTENCY_TTL_SECONDS must be a valid number"),
+    }
+});
+
+impl IdempotencyConfig {
+    /// Whether the Idempotency-Key middleware is active (default: true)
+    pub fn enabled() -> bool {
+        IDEMPOTENCY.enabled
+    }
+
+    /// How long a stored response can be replayed (default: 86400 = 24 hours)
+    pub fn ttl_seconds() -> u64 {
+        IDEMPOTENCY.ttl_seconds
+    }
+}
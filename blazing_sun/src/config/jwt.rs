@@ -1,3 +1,4 @@
+use super::SecretsConfig;
 use once_cell::sync::Lazy;
 
 pub struct JwtConfig {
@@ -5,23 +6,29 @@ pub struct JwtConfig {
     pub expiration_minutes: i64,
     pub refresh_secret: String,
     pub refresh_expiration_days: i64,
+    /// Longest an admin impersonation session (and its token) lasts
+    pub impersonation_max_minutes: i64,
 }
 
 pub static JWT: Lazy<JwtConfig> = Lazy::new(|| {
     dotenv::dotenv().ok();
 
     JwtConfig {
-        secret: std::env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
+        secret: SecretsConfig::var("JWT_SECRET").expect("JWT_SECRET must be set"),
         expiration_minutes: std::env::var("JWT_EXPIRATION_MINUTES")
             .unwrap_or_else(|_| "120".to_string())
             .parse()
             .expect("JWT_EXPIRATION_MINUTES must be a valid number"),
-        refresh_secret: std::env::var("REFRESH_TOKEN_SECRET")
+        refresh_secret: SecretsConfig::var("REFRESH_TOKEN_SECRET")
             .expect("REFRESH_TOKEN_SECRET must be set"),
         refresh_expiration_days: std::env::var("REFRESH_TOKEN_EXPIRATION_DAYS")
             .unwrap_or_else(|_| "30".to_string())
             .parse()
             .expect("REFRESH_TOKEN_EXPIRATION_DAYS must be a valid number"),
+        impersonation_max_minutes: std::env::var("IMPERSONATION_MAX_MINUTES")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("IMPERSONATION_MAX_MINUTES must be a valid number"),
     }
 });
 
@@ -41,4 +48,8 @@ impl JwtConfig {
     pub fn refresh_expiration_days() -> i64 {
         JWT.refresh_expiration_days
     }
+
+    pub fn impersonation_max_minutes() -> i64 {
+        JWT.impersonation_max_minutes
+    }
 }
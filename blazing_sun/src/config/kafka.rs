@@ -8,6 +8,19 @@ pub struct KafkaConfig {
     pub group_id: String,
     pub auto_offset_reset: String,
     pub enable_auto_commit: bool,
+    pub consumer_workers: usize,
+    pub consumer_worker_queue: usize,
+    pub rebalance_drain_timeout_ms: u64,
+    pub games_partitioning: String,
+    pub games_migration: String,
+    pub client_rack: Option<String>,
+    pub reconnect_backoff_ms: u64,
+    pub reconnect_backoff_max_ms: u64,
+    pub compression_type: String,
+    pub max_message_bytes: usize,
+    pub handler_max_attempts: u32,
+    pub handler_retry_backoff_ms: u64,
+    pub handler_retry_backoff_max_ms: u64,
 }
 
 pub static KAFKA: Lazy<KafkaConfig> = Lazy::new(|| {
@@ -19,7 +32,11 @@ pub static KAFKA: Lazy<KafkaConfig> = Lazy::new(|| {
         .parse()
         .expect("KAFKA_PORT must be a valid number");
 
-    let bootstrap_servers = format!("{}:{}", host, port);
+    // Several brokers (e.g. one per zone) so losing one is not fatal
+    let bootstrap_servers = std::env::var("KAFKA_BOOTSTRAP_SERVERS")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| format!("{}:{}", host, port));
 
     let client_id =
         std::env::var("KAFKA_CLIENT_ID").unwrap_or_else(|_| "blazing-sun-app".to_string());
@@ -35,6 +52,81 @@ pub static KAFKA: Lazy<KafkaConfig> = Lazy::new(|| {
         .parse()
         .unwrap_or(true);
 
+    // Messages are handled by this many workers, each owning a share of the
+    // partitions (1 handles everything on the polling task)
+    let consumer_workers: usize = std::env::var("KAFKA_CONSUMER_WORKERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|workers| *workers > 0)
+        .unwrap_or(1);
+
+    let consumer_worker_queue: usize = std::env::var("KAFKA_CONSUMER_WORKER_QUEUE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(256);
+
+    // Longest wait for in-flight handlers before partitions are handed to
+    // another group member (must stay well below max.poll.interval.ms)
+    let rebalance_drain_timeout_ms: u64 = std::env::var("KAFKA_REBALANCE_DRAIN_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10_000);
+
+    // Game command topics and keys, see bootstrap/events/routing.rs
+    let games_partitioning =
+        std::env::var("GAMES_COMMANDS_PARTITIONING").unwrap_or_else(|_| "room".to_string());
+
+    let games_migration =
+        std::env::var("GAMES_COMMANDS_MIGRATION").unwrap_or_else(|_| "none".to_string());
+
+    // Rack (zone) of this instance, consumers fetch from an in-rack replica
+    let client_rack = std::env::var("KAFKA_CLIENT_RACK")
+        .ok()
+        .filter(|v| !v.trim().is_empty());
+
+    let reconnect_backoff_ms: u64 = std::env::var("KAFKA_RECONNECT_BACKOFF_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100);
+
+    let reconnect_backoff_max_ms: u64 = std::env::var("KAFKA_RECONNECT_BACKOFF_MAX_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10000)
+        .max(reconnect_backoff_ms);
+
+    let compression_type =
+        std::env::var("KAFKA_COMPRESSION_TYPE").unwrap_or_else(|_| "lz4".to_string());
+
+    // Larger payloads are refused, or segmented for topics the gateway reads
+    // (keep it below the broker's message.max.bytes, which also counts key
+    // and headers)
+    let max_message_bytes: usize = std::env::var("KAFKA_MAX_MESSAGE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(kafka_producer::DEFAULT_MAX_MESSAGE_BYTES);
+
+    // Default retry policy of event handlers: attempts before a message goes
+    // to the dead letter topic of its source, backoff doubling up to the max
+    let handler_max_attempts: u32 = std::env::var("KAFKA_HANDLER_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|attempts| *attempts > 0)
+        .unwrap_or(5);
+
+    let handler_retry_backoff_ms: u64 = std::env::var("KAFKA_HANDLER_RETRY_BACKOFF_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(200);
+
+    let handler_retry_backoff_max_ms: u64 = std::env::var("KAFKA_HANDLER_RETRY_BACKOFF_MAX_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5000)
+        .max(handler_retry_backoff_ms);
+
     KafkaConfig {
         bootstrap_servers,
         host,
@@ -43,6 +135,19 @@ pub static KAFKA: Lazy<KafkaConfig> = Lazy::new(|| {
         group_id,
         auto_offset_reset,
         enable_auto_commit,
+        consumer_workers,
+        consumer_worker_queue,
+        rebalance_drain_timeout_ms,
+        games_partitioning,
+        games_migration,
+        client_rack,
+        reconnect_backoff_ms,
+        reconnect_backoff_max_ms,
+        compression_type,
+        max_message_bytes,
+        handler_max_attempts,
+        handler_retry_backoff_ms,
+        handler_retry_backoff_max_ms,
     }
 });
 
@@ -74,4 +179,61 @@ impl KafkaConfig {
     pub fn enable_auto_commit() -> bool {
         KAFKA.enable_auto_commit
     }
+
+    pub fn consumer_workers() -> usize {
+        KAFKA.consumer_workers
+    }
+
+    pub fn consumer_worker_queue() -> usize {
+        KAFKA.consumer_worker_queue
+    }
+
+    pub fn rebalance_drain_timeout_ms() -> u64 {
+        KAFKA.rebalance_drain_timeout_ms
+    }
+
+    pub fn games_partitioning() -> &'static str {
+        &KAFKA.games_partitioning
+    }
+
+    pub fn games_migration() -> &'static str {
+        &KAFKA.games_migration
+    }
+
+    pub fn client_rack() -> Option<&'static str> {
+        KAFKA.client_rack.as_deref()
+    }
+
+    pub fn compression_type() -> &'static str {
+        &KAFKA.compression_type
+    }
+
+    pub fn max_message_bytes() -> usize {
+        KAFKA.max_message_bytes
+    }
+
+    pub fn handler_max_attempts() -> u32 {
+        KAFKA.handler_max_attempts
+    }
+
+    pub fn handler_retry_backoff_ms() -> u64 {
+        KAFKA.handler_retry_backoff_ms
+    }
+
+    pub fn handler_retry_backoff_max_ms() -> u64 {
+        KAFKA.handler_retry_backoff_max_ms
+    }
+
+    /// Broker connection settings shared by every producer and consumer
+    pub fn broker_settings() -> Vec<(&'static str, String)> {
+        let mut settings = vec![
+            ("bootstrap.servers", KAFKA.bootstrap_servers.clone()),
+            ("reconnect.backoff.ms", KAFKA.reconnect_backoff_ms.to_string()),
+            ("reconnect.backoff.max.ms", KAFKA.reconnect_backoff_max_ms.to_string()),
+        ];
+        if let Some(rack) = &KAFKA.client_rack {
+            settings.push(("client.rack", rack.clone()));
+        }
+        settings
+    }
 }
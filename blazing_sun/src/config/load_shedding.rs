@@ -0,0 +1,74 @@
+use once_cell::sync::Lazy;
+
+pub struct LoadSheddingConfig {
+    pub enabled: bool,
+    pub max_in_flight: usize,
+    pub p99_target_ms: u64,
+    pub latency_window_seconds: u64,
+    pub retry_after_seconds: u64,
+    pub exempt_prefixes: Vec<String>,
+}
+
+pub static LOAD_SHEDDING: Lazy<LoadSheddingConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    LoadSheddingConfig {
+        enabled: std::env::var("LOAD_SHEDDING_ENABLED")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true),
+        max_in_flight: std::env::var("LOAD_SHEDDING_MAX_IN_FLIGHT")
+            .unwrap_or_else(|_| "256".to_string())
+            .parse()
+            .expect("LOAD_SHEDDING_MAX_IN_FLIGHT must be a valid number"),
+        p99_target_ms: std::env::var("LOAD_SHEDDING_P99_TARGET_MS")
+            .unwrap_or_else(|_| "2000".to_string())
+            .parse()
+            .expect("LOAD_SHEDDING_P99_TARGET_MS must be a valid number"),
+        latency_window_seconds: std::env::var("LOAD_SHEDDING_LATENCY_WINDOW_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .expect("LOAD_SHEDDING_LATENCY_WINDOW_SECONDS must be a valid number"),
+        retry_after_seconds: std::env::var("LOAD_SHEDDING_RETRY_AFTER_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("LOAD_SHEDDING_RETRY_AFTER_SECONDS must be a valid number"),
+        exempt_prefixes: std::env::var("LOAD_SHEDDING_EXEMPT_PREFIXES")
+            .unwrap_or_else(|_| "/health,/metrics,/webhooks,/api/v1/webhooks".to_string())
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+    }
+});
+
+impl LoadSheddingConfig {
+    /// Whether the load-shedding middleware is active (default: true)
+    pub fn enabled() -> bool {
+        LOAD_SHEDDING.enabled
+    }
+
+    /// In-flight requests per route group before it counts as overloaded (default: 256)
+    pub fn max_in_flight() -> usize {
+        LOAD_SHEDDING.max_in_flight
+    }
+
+    /// p99 latency per route group before it counts as overloaded (default: 2000 ms)
+    pub fn p99_target_ms() -> u64 {
+        LOAD_SHEDDING.p99_target_ms
+    }
+
+    /// Only latencies from the last window count towards the p99 (default: 10 seconds)
+    pub fn latency_window_seconds() -> u64 {
+        LOAD_SHEDDING.latency_window_seconds
+    }
+
+    /// `Retry-After` sent with shed requests (default: 5 seconds)
+    pub fn retry_after_seconds() -> u64 {
+        LOAD_SHEDDING.retry_after_seconds
+    }
+
+    /// Path prefixes that are never shed nor counted (default: health, metrics and webhooks)
+    pub fn exempt_prefixes() -> &'static [String] {
+        &LOAD_SHEDDING.exempt_prefixes
+    }
+}
@@ -0,0 +1,28 @@
+use once_cell::sync::Lazy;
+
+pub struct MaintenanceConfig {
+    pub exempt_prefixes: Vec<String>,
+}
+
+pub static MAINTENANCE: Lazy<MaintenanceConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    MaintenanceConfig {
+        exempt_prefixes: std::env::var("MAINTENANCE_EXEMPT_PREFIXES")
+            .unwrap_or_else(|_| {
+                "/health,/metrics,/api/v1/admin/maintenance,/api/v1/auth/sign-in,/api/v1/auth/refresh,/api/v1/auth/sign-out".to_string()
+            })
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+    }
+});
+
+impl MaintenanceConfig {
+    /// Path prefixes that still take writes during maintenance (default: health,
+    /// metrics, the maintenance toggle itself, and signing in and out)
+    pub fn exempt_prefixes() -> &'static [String] {
+        &MAINTENANCE.exempt_prefixes
+    }
+}
@@ -1,31 +1,51 @@
 pub mod activation;
 pub mod app;
+pub mod cache;
 pub mod cron;
 pub mod database;
 pub mod email;
+pub mod erasure;
 pub mod games;
+pub mod idempotency;
 pub mod jwt;
 pub mod kafka;
+pub mod load_shedding;
+pub mod maintenance;
 pub mod mongodb;
 pub mod oauth;
 pub mod rabbitmq;
 pub mod redis;
+pub mod secrets;
+pub mod security;
+pub mod service_auth;
 pub mod session;
 pub mod theme;
+pub mod transfers;
+pub mod two_factor;
 pub mod upload;
 
 pub use activation::ActivationConfig;
 pub use app::AppConfig;
+pub use cache::CacheConfig;
 pub use cron::CronConfig;
-pub use database::DatabaseConfig;
+pub use database::{DatabaseConfig, PoolSettings};
 pub use email::EmailConfig;
+pub use erasure::ErasureConfig;
 pub use games::GamesConfig;
+pub use idempotency::IdempotencyConfig;
 pub use jwt::JwtConfig;
 pub use kafka::KafkaConfig;
+pub use load_shedding::LoadSheddingConfig;
+pub use maintenance::MaintenanceConfig;
 pub use mongodb::MongoDbConfig;
 pub use oauth::OAuthConfig;
 pub use rabbitmq::RabbitMQConfig;
 pub use redis::RedisConfig;
+pub use secrets::SecretsConfig;
+pub use security::SecurityConfig;
+pub use service_auth::ServiceAuthConfig;
 pub use session::SessionConfig;
 pub use theme::ThemeConfig;
+pub use transfers::TransferConfig;
+pub use two_factor::TwoFactorConfig;
 pub use upload::UploadConfig;
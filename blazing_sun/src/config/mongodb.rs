@@ -1,3 +1,4 @@
+use super::SecretsConfig;
 use once_cell::sync::Lazy;
 
 pub struct MongoDbConfig {
@@ -21,12 +22,12 @@ pub static MONGODB: Lazy<MongoDbConfig> = Lazy::new(|| {
         .parse()
         .expect("MONGO_PORT must be a valid number");
     let user = std::env::var("MONGO_USER").unwrap_or_else(|_| "app".to_string());
-    let password = std::env::var("MONGO_PASSWORD").unwrap_or_else(|_| "".to_string());
+    let password = SecretsConfig::var("MONGO_PASSWORD").unwrap_or_else(|_| "".to_string());
     let database =
         std::env::var("MONGO_INITDB_DATABASE").unwrap_or_else(|_| "blazing_sun".to_string());
 
     // Build URL from components if MONGO_URL not provided
-    let url = std::env::var("MONGO_URL").unwrap_or_else(|_| {
+    let url = SecretsConfig::var("MONGO_URL").unwrap_or_else(|_| {
         if password.is_empty() {
             format!("mongodb://{}:{}/{}", host, port, database)
         } else {
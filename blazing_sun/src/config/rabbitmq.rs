@@ -1,3 +1,4 @@
+use super::SecretsConfig;
 use once_cell::sync::Lazy;
 
 pub struct RabbitMQConfig {
@@ -18,7 +19,7 @@ pub static RABBITMQ: Lazy<RabbitMQConfig> = Lazy::new(|| {
         .parse()
         .expect("RABBITMQ_PORT must be a valid number");
     let user = std::env::var("RABBITMQ_USER").unwrap_or_else(|_| "guest".to_string());
-    let password = std::env::var("RABBITMQ_PASSWORD").unwrap_or_else(|_| "guest".to_string());
+    let password = SecretsConfig::var("RABBITMQ_PASSWORD").unwrap_or_else(|_| "guest".to_string());
     let vhost = std::env::var("RABBITMQ_VHOST").unwrap_or_else(|_| "/".to_string());
 
     // Build AMQP URL
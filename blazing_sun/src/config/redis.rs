@@ -1,3 +1,4 @@
+use super::SecretsConfig;
 use once_cell::sync::Lazy;
 
 pub struct RedisConfig {
@@ -7,6 +8,7 @@ pub struct RedisConfig {
     pub user: String,
     pub password: String,
     pub db: u8,
+    pub fallback_urls: Vec<String>,
 }
 
 pub static REDIS: Lazy<RedisConfig> = Lazy::new(|| {
@@ -18,14 +20,14 @@ pub static REDIS: Lazy<RedisConfig> = Lazy::new(|| {
         .parse()
         .expect("REDIS_PORT must be a valid number");
     let user = std::env::var("REDIS_USER").unwrap_or_else(|_| "default".to_string());
-    let password = std::env::var("REDIS_PASSWORD").unwrap_or_default();
+    let password = SecretsConfig::var("REDIS_PASSWORD").unwrap_or_default();
     let db: u8 = std::env::var("REDIS_DB")
         .unwrap_or_else(|_| "0".to_string())
         .parse()
         .expect("REDIS_DB must be a valid number");
 
     // Build URL if not provided
-    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| {
+    let url = SecretsConfig::var("REDIS_URL").unwrap_or_else(|_| {
         if password.is_empty() {
             format!("redis://{}:{}/{}", host, port, db)
         } else {
@@ -33,6 +35,15 @@ pub static REDIS: Lazy<RedisConfig> = Lazy::new(|| {
         }
     });
 
+    // Standby primaries tried in order when REDIS_URL does not answer
+    let fallback_urls = std::env::var("REDIS_FALLBACK_URLS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .map(str::to_string)
+        .collect();
+
     RedisConfig {
         url,
         host,
@@ -40,6 +51,7 @@ pub static REDIS: Lazy<RedisConfig> = Lazy::new(|| {
         user,
         password,
         db,
+        fallback_urls,
     }
 });
 
@@ -67,4 +79,8 @@ impl RedisConfig {
     pub fn db() -> u8 {
         REDIS.db
     }
+
+    pub fn fallback_urls() -> &'static [String] {
+        &REDIS.fallback_urls
+    }
 }
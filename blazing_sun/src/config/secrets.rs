@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use secrets_provider::{SecretsError, SecretsProvider};
+
+/// Settings the app cannot start without
+const REQUIRED: &[&str] = &["DATABASE_URL", "JWT_SECRET", "REFRESH_TOKEN_SECRET", "MAIL_PASSWORD"];
+
+static PROVIDER: OnceCell<Arc<dyn SecretsProvider>> = OnceCell::new();
+
+/// Where secrets are read from (SECRETS_PROVIDER: env, file or vault)
+///
+/// The config statics read their secrets through [`SecretsConfig::var`], so
+/// [`SecretsConfig::load`] must run before any of them is first used.
+pub struct SecretsConfig;
+
+impl SecretsConfig {
+    /// Connect the provider and check that every required secret is set
+    pub async fn load() -> Result<(), SecretsError> {
+        let provider = secrets_provider::from_env().await?;
+
+        let missing = secrets_provider::missing(provider.as_ref(), REQUIRED);
+        if !missing.is_empty() {
+            return Err(SecretsError::Missing(missing.join(", ")));
+        }
+
+        // A second load keeps the first provider
+        let _ = PROVIDER.set(provider);
+        Ok(())
+    }
+
+    /// Drop-in for `std::env::var`, served by the loaded provider (the
+    /// environment before [`SecretsConfig::load`], e.g. in tests)
+    pub fn var(key: &str) -> Result<String, std::env::VarError> {
+        match PROVIDER.get() {
+            Some(provider) => provider.var(key),
+            None => std::env::var(key),
+        }
+    }
+
+    /// Name of the loaded provider (default: env)
+    pub fn provider_name() -> &'static str {
+        PROVIDER.get().map(|provider| provider.name()).unwrap_or("env")
+    }
+}
@@ -0,0 +1,156 @@
+use once_cell::sync::Lazy;
+
+use crate::config::AppConfig;
+
+/// Content-Security-Policy used when CSP_POLICY is not set
+pub const DEFAULT_CSP: &str = "default-src 'self'; \
+     script-src 'self' 'unsafe-inline'; \
+     style-src 'self' 'unsafe-inline'; \
+     img-src 'self' data: blob: https://*.tile.openstreetmap.org; \
+     font-src 'self'; \
+     connect-src 'self'; \
+     frame-ancestors 'none'";
+
+/// Which set of defaults applies (APP_ENV)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityProfile {
+    /// Strict defaults: CORS limited to APP_URL, enforced CSP, HSTS
+    Production,
+    /// Any origin, report-only CSP, no HSTS (local http setups)
+    Development,
+}
+
+pub struct SecurityConfig {
+    pub profile: SecurityProfile,
+    /// Allowed CORS origins; `*` allows any origin (without credentials)
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    pub cors_allowed_headers: Vec<String>,
+    pub cors_allow_credentials: bool,
+    pub cors_max_age: usize,
+    pub csp_policy: String,
+    /// Send the CSP as Content-Security-Policy-Report-Only instead of enforcing it
+    pub csp_report_only: bool,
+    /// Strict-Transport-Security max-age in seconds (0 = header not sent)
+    pub hsts_max_age: u64,
+    pub frame_options: String,
+}
+
+/// Split a comma separated env value, dropping empty entries
+fn env_list(key: &str, default: &[&str]) -> Vec<String> {
+    match std::env::var(key) {
+        Ok(value) if !value.trim().is_empty() => value
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect(),
+        _ => default.iter().map(|v| v.to_string()).collect(),
+    }
+}
+
+fn env_bool(key: &str, default: bool) -> bool {
+    std::env::var(key)
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(default)
+}
+
+pub static SECURITY: Lazy<SecurityConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    let profile = if AppConfig::is_development() {
+        SecurityProfile::Development
+    } else {
+        SecurityProfile::Production
+    };
+    let dev = profile == SecurityProfile::Development;
+
+    let default_origins: &[&str] = if dev { &["*"] } else { &[AppConfig::app_url()] };
+
+    SecurityConfig {
+        profile,
+        cors_allowed_origins: env_list("CORS_ALLOWED_ORIGINS", default_origins),
+        cors_allowed_methods: env_list(
+            "CORS_ALLOWED_METHODS",
+            &["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"],
+        ),
+        cors_allowed_headers: env_list(
+            "CORS_ALLOWED_HEADERS",
+            &[
+                "Authorization",
+                "Accept",
+                "Content-Type",
+                "X-CSRF-TOKEN",
+                "X-Requested-With",
+                "Idempotency-Key",
+            ],
+        ),
+        cors_allow_credentials: env_bool("CORS_ALLOW_CREDENTIALS", true),
+        cors_max_age: std::env::var("CORS_MAX_AGE")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .expect("CORS_MAX_AGE must be a valid number"),
+        csp_policy: std::env::var("CSP_POLICY")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_CSP.to_string()),
+        csp_report_only: env_bool("CSP_REPORT_ONLY", dev),
+        hsts_max_age: std::env::var("HSTS_MAX_AGE")
+            .unwrap_or_else(|_| if dev { "0" } else { "31536000" }.to_string())
+            .parse()
+            .expect("HSTS_MAX_AGE must be a valid number"),
+        frame_options: std::env::var("X_FRAME_OPTIONS").unwrap_or_else(|_| "DENY".to_string()),
+    }
+});
+
+impl SecurityConfig {
+    /// Active profile (APP_ENV=development selects the permissive one)
+    pub fn profile() -> SecurityProfile {
+        SECURITY.profile
+    }
+
+    /// Allowed CORS origins (default: APP_URL, `*` in development)
+    pub fn cors_allowed_origins() -> &'static [String] {
+        &SECURITY.cors_allowed_origins
+    }
+
+    /// Whether any origin is allowed (`*` in CORS_ALLOWED_ORIGINS)
+    pub fn cors_allows_any_origin() -> bool {
+        SECURITY.cors_allowed_origins.iter().any(|o| o == "*")
+    }
+
+    pub fn cors_allowed_methods() -> &'static [String] {
+        &SECURITY.cors_allowed_methods
+    }
+
+    pub fn cors_allowed_headers() -> &'static [String] {
+        &SECURITY.cors_allowed_headers
+    }
+
+    /// Whether cookies/Authorization may be sent cross-origin (default: true)
+    pub fn cors_allow_credentials() -> bool {
+        SECURITY.cors_allow_credentials
+    }
+
+    /// Preflight cache lifetime in seconds (default: 3600)
+    pub fn cors_max_age() -> usize {
+        SECURITY.cors_max_age
+    }
+
+    pub fn csp_policy() -> &'static str {
+        &SECURITY.csp_policy
+    }
+
+    /// Report CSP violations without blocking (default: true in development)
+    pub fn csp_report_only() -> bool {
+        SECURITY.csp_report_only
+    }
+
+    /// HSTS max-age (default: 1 year, 0 in development)
+    pub fn hsts_max_age() -> u64 {
+        SECURITY.hsts_max_age
+    }
+
+    pub fn frame_options() -> &'static str {
+        &SECURITY.frame_options
+    }
+}
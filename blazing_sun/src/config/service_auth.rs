@@ -0,0 +1,32 @@
+use super::SecretsConfig;
+use once_cell::sync::Lazy;
+
+pub struct ServiceAuthConfig {
+    /// Signing keys as "kid:secret" pairs, newest first
+    pub keys: String,
+    pub token_ttl_seconds: i64,
+}
+
+pub static SERVICE_AUTH: Lazy<ServiceAuthConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    ServiceAuthConfig {
+        keys: SecretsConfig::var("SERVICE_AUTH_KEYS").unwrap_or_default(),
+        token_ttl_seconds: std::env::var("SERVICE_AUTH_TOKEN_TTL_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("SERVICE_AUTH_TOKEN_TTL_SECONDS must be a valid number"),
+    }
+});
+
+impl ServiceAuthConfig {
+    /// Keys shared with the internal services (SERVICE_AUTH_KEYS, empty when unset)
+    pub fn keys() -> &'static str {
+        &SERVICE_AUTH.keys
+    }
+
+    /// Lifetime of the service tokens issued by this app (default: 60)
+    pub fn token_ttl_seconds() -> i64 {
+        SERVICE_AUTH.token_ttl_seconds
+    }
+}
@@ -1,7 +1,8 @@
+use super::SecretsConfig;
 use std::env;
 
 /// Session configuration
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct SessionConfig {
     pub driver: String,
     pub redis_url: String,
@@ -16,12 +17,30 @@ pub struct SessionConfig {
     pub key_prefix: String,
 }
 
+impl std::fmt::Debug for SessionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("driver", &self.driver)
+            .field("redis_url", &secrets_provider::redact_url(&self.redis_url))
+            .field("cookie_name", &self.cookie_name)
+            .field("lifetime_minutes", &self.lifetime_minutes)
+            .field("refresh_ttl", &self.refresh_ttl)
+            .field("regenerate_on_login", &self.regenerate_on_login)
+            .field("secure_cookie", &self.secure_cookie)
+            .field("http_only", &self.http_only)
+            .field("same_site", &self.same_site)
+            .field("cookie_path", &self.cookie_path)
+            .field("key_prefix", &self.key_prefix)
+            .finish()
+    }
+}
+
 impl SessionConfig {
     /// Load session configuration from environment
     pub fn from_env() -> Result<Self, env::VarError> {
         Ok(Self {
             driver: env::var("SESSION_DRIVER").unwrap_or_else(|_| "redis".to_string()),
-            redis_url: env::var("SESSION_REDIS_URL")?,
+            redis_url: SecretsConfig::var("SESSION_REDIS_URL")?,
             cookie_name: env::var("SESSION_COOKIE").unwrap_or_else(|_| "app_session".to_string()),
             lifetime_minutes: env::var("SESSION_LIFETIME_MINUTES")
                 .unwrap_or_else(|_| "120".to_string())
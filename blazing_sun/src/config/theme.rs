@@ -18,6 +18,14 @@ pub struct ThemeConfig {
     pub build_timeout_secs: u64,
     /// Backup directory for rollback
     pub backup_path: PathBuf,
+    /// Output directory for tenant theme bundles (served under /assets/css/TENANTS)
+    pub tenant_css_path: PathBuf,
+    /// Production assets directory the GLOBAL build writes to
+    pub resources_path: PathBuf,
+    /// Output directory for preview builds (served under /assets/css/PREVIEWS)
+    pub preview_path: PathBuf,
+    /// Seconds a preview is kept before the cleanup job removes it
+    pub preview_ttl_secs: u64,
     /// Allowed SCSS variable names (whitelist)
     pub allowed_scss_variables: Vec<String>,
     /// Allowed CSS custom property names (whitelist)
@@ -41,6 +49,12 @@ pub static THEME: Lazy<ThemeConfig> = Lazy::new(|| {
 
     let backup_path = PathBuf::from(&project_root).join("storage/app/private/theme_backups");
 
+    let tenant_css_path = PathBuf::from(&project_root).join("src/resources/css/TENANTS");
+
+    let resources_path = PathBuf::from(&project_root).join("src/resources");
+
+    let preview_path = resources_path.join("css/PREVIEWS");
+
     // SCSS variables whitelist - only these can be modified
     let allowed_scss_variables = vec![
         // Identity/Branding
@@ -209,6 +223,13 @@ pub static THEME: Lazy<ThemeConfig> = Lazy::new(|| {
             .parse()
             .expect("THEME_BUILD_TIMEOUT must be a valid number"),
         backup_path,
+        tenant_css_path,
+        resources_path,
+        preview_path,
+        preview_ttl_secs: std::env::var("THEME_PREVIEW_TTL")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .expect("THEME_PREVIEW_TTL must be a valid number"),
         allowed_scss_variables,
         allowed_css_properties,
     }
@@ -245,6 +266,26 @@ impl ThemeConfig {
         &THEME.backup_path
     }
 
+    /// Tenant theme bundle directory
+    pub fn tenant_css_path() -> &'static PathBuf {
+        &THEME.tenant_css_path
+    }
+
+    /// Production assets directory
+    pub fn resources_path() -> &'static PathBuf {
+        &THEME.resources_path
+    }
+
+    /// Preview build directory
+    pub fn preview_path() -> &'static PathBuf {
+        &THEME.preview_path
+    }
+
+    /// Preview lifetime in seconds
+    pub fn preview_ttl_secs() -> u64 {
+        THEME.preview_ttl_secs
+    }
+
     /// Allowed SCSS variable names
     pub fn allowed_scss_variables() -> &'static Vec<String> {
         &THEME.allowed_scss_variables
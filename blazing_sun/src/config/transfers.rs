@@ -0,0 +1,33 @@
+use once_cell::sync::Lazy;
+
+pub struct TransferConfig {
+    pub max_transfer_cents: i64,
+    pub daily_limit_cents: i64,
+}
+
+pub static TRANSFERS: Lazy<TransferConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    TransferConfig {
+        max_transfer_cents: std::env::var("TRANSFER_MAX_CENTS")
+            .unwrap_or_else(|_| "100000".to_string())
+            .parse()
+            .expect("TRANSFER_MAX_CENTS must be a valid number"),
+        daily_limit_cents: std::env::var("TRANSFER_DAILY_LIMIT_CENTS")
+            .unwrap_or_else(|_| "500000".to_string())
+            .parse()
+            .expect("TRANSFER_DAILY_LIMIT_CENTS must be a valid number"),
+    }
+});
+
+impl TransferConfig {
+    /// Largest single transfer in cents (default: 100000, i.e. 1000 coins)
+    pub fn max_transfer_cents() -> i64 {
+        TRANSFERS.max_transfer_cents
+    }
+
+    /// Most a user can send in any 24 hours, in cents (default: 500000)
+    pub fn daily_limit_cents() -> i64 {
+        TRANSFERS.daily_limit_cents
+    }
+}
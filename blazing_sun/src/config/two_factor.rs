@@ -0,0 +1,57 @@
+use once_cell::sync::Lazy;
+use rbac::Role;
+
+pub struct TwoFactorConfig {
+    pub issuer: String,
+    pub required_roles: Vec<Role>,
+    pub challenge_ttl_seconds: i64,
+    pub max_attempts: i32,
+}
+
+pub static TWO_FACTOR: Lazy<TwoFactorConfig> = Lazy::new(|| {
+    dotenv::dotenv().ok();
+
+    TwoFactorConfig {
+        issuer: std::env::var("TWO_FACTOR_ISSUER").unwrap_or_else(|_| "Blazing Sun".to_string()),
+        required_roles: std::env::var("TWO_FACTOR_REQUIRED_ROLES")
+            .unwrap_or_else(|_| "admin".to_string())
+            .split(',')
+            .filter(|role| !role.trim().is_empty())
+            .map(|role| {
+                Role::parse(role).unwrap_or_else(|| {
+                    panic!("TWO_FACTOR_REQUIRED_ROLES: unknown role {}", role.trim())
+                })
+            })
+            .collect(),
+        challenge_ttl_seconds: std::env::var("TWO_FACTOR_CHALLENGE_TTL_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("TWO_FACTOR_CHALLENGE_TTL_SECONDS must be a valid number"),
+        max_attempts: std::env::var("TWO_FACTOR_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("TWO_FACTOR_MAX_ATTEMPTS must be a valid number"),
+    }
+});
+
+impl TwoFactorConfig {
+    /// Issuer shown in authenticator apps (default: "Blazing Sun")
+    pub fn issuer() -> &'static str {
+        &TWO_FACTOR.issuer
+    }
+
+    /// Roles that must use two-factor authentication to sign in (default: admin)
+    pub fn required_roles() -> &'static [Role] {
+        &TWO_FACTOR.required_roles
+    }
+
+    /// Seconds a pre-auth token stays valid after the password was accepted (default: 300)
+    pub fn challenge_ttl_seconds() -> i64 {
+        TWO_FACTOR.challenge_ttl_seconds
+    }
+
+    /// Wrong codes accepted per pre-auth token before it is revoked (default: 5)
+    pub fn max_attempts() -> i32 {
+        TWO_FACTOR.max_attempts
+    }
+}
@@ -8,6 +8,8 @@ pub struct UploadConfig {
     pub storage_driver: String,
     pub public_url_base: String,
     pub private_url_base: String,
+    pub avatar_max_file_size: u64,
+    pub avatar_max_dimension: u32,
 }
 
 pub static UPLOAD: Lazy<UploadConfig> = Lazy::new(|| {
@@ -41,6 +43,14 @@ pub static UPLOAD: Lazy<UploadConfig> = Lazy::new(|| {
             .unwrap_or_else(|_| "/storage".to_string()),
         private_url_base: std::env::var("STORAGE_PRIVATE_URL")
             .unwrap_or_else(|_| "/api/v1/upload/private".to_string()),
+        avatar_max_file_size: std::env::var("AVATAR_MAX_FILE_SIZE")
+            .unwrap_or_else(|_| "5242880".to_string()) // 5MB default
+            .parse()
+            .expect("AVATAR_MAX_FILE_SIZE must be a valid number"),
+        avatar_max_dimension: std::env::var("AVATAR_MAX_DIMENSION")
+            .unwrap_or_else(|_| "8192".to_string())
+            .parse()
+            .expect("AVATAR_MAX_DIMENSION must be a valid number"),
     }
 });
 
@@ -80,6 +90,16 @@ impl UploadConfig {
         &UPLOAD.private_url_base
     }
 
+    /// Maximum profile picture size in bytes
+    pub fn avatar_max_file_size() -> u64 {
+        UPLOAD.avatar_max_file_size
+    }
+
+    /// Maximum profile picture width or height in pixels (checked before decoding)
+    pub fn avatar_max_dimension() -> u32 {
+        UPLOAD.avatar_max_dimension
+    }
+
     /// Check if a file extension is allowed
     pub fn is_type_allowed(extension: &str) -> bool {
         UPLOAD
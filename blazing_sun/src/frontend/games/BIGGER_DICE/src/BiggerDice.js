@@ -1455,6 +1455,21 @@ template.innerHTML = `
     .dice[data-value="6"] .dice-dot:nth-child(7),
     .dice[data-value="6"] .dice-dot:nth-child(9) { background: #1e1e2e; }
 
+    /* Totals one six-sided die can't show (several dice or more sides) */
+    .dice--total { position: relative; }
+    .dice--total .dice-dot { visibility: hidden; }
+    .dice--total::after {
+      content: attr(data-value);
+      position: absolute;
+      inset: 0;
+      display: flex;
+      align-items: center;
+      justify-content: center;
+      font-size: 1.75rem;
+      font-weight: 700;
+      color: #1e1e2e;
+    }
+
     .action-buttons {
       display: flex;
       gap: 1rem;
@@ -3032,6 +3047,31 @@ template.innerHTML = `
           </select>
           <span class="form-hint">Game starts when all players are ready</span>
         </div>
+        <div class="form-group">
+          <label class="form-label" for="roundsToWinInput">Points to Win</label>
+          <input type="number" class="form-input" id="roundsToWinInput" min="1" max="25" value="10">
+        </div>
+        <div class="form-group">
+          <label class="form-label" for="diceInput">Dice</label>
+          <select class="form-input" id="diceInput">
+            <option value="1d6" selected>1 six-sided die</option>
+            <option value="2d6">2 six-sided dice</option>
+            <option value="3d6">3 six-sided dice</option>
+            <option value="1d4">1 four-sided die</option>
+            <option value="1d8">1 eight-sided die</option>
+            <option value="1d12">1 twelve-sided die</option>
+            <option value="1d20">1 twenty-sided die</option>
+          </select>
+          <span class="form-hint">A roll is the total of the dice</span>
+        </div>
+        <div class="form-group">
+          <label class="form-label" for="tiebreakerInput">Tiebreaker</label>
+          <select class="form-input" id="tiebreakerInput">
+            <option value="sudden_death" selected>Sudden death</option>
+            <option value="best_of">Best of 3 roll-offs</option>
+          </select>
+          <span class="form-hint">How players tied for the highest roll settle the point</span>
+        </div>
         <div class="form-group form-group--checkbox">
           <label class="form-checkbox">
             <input type="checkbox" id="allowSpectatorsInput" checked>
@@ -3164,6 +3204,29 @@ const ComponentMode = {
   GAME: 'game'
 };
 
+/**
+ * Apply the ops of a room_state_diff (add / remove / replace with JSON
+ * pointer paths) to a copy of a room state
+ */
+function applyRoomStateDiff(room, ops) {
+  let patched = structuredClone(room);
+  for (const op of ops) {
+    if (op.path === '') {
+      patched = structuredClone(op.value);
+      continue;
+    }
+    const keys = op.path.slice(1).split('/').map(key => key.replace(/~1/g, '/').replace(/~0/g, '~'));
+    const last = keys.pop();
+    const parent = keys.reduce((node, key) => node[key], patched);
+    if (op.op === 'remove') {
+      delete parent[last];
+    } else {
+      parent[last] = structuredClone(op.value);
+    }
+  }
+  return patched;
+}
+
 /**
  * BiggerDice Custom Element
  */
@@ -3346,6 +3409,9 @@ export class BiggerDice extends HTMLElement {
       roomNameInput: $('roomNameInput'),
       roomPasswordInput: $('roomPasswordInput'),
       playerCountInput: $('playerCountInput'),
+      roundsToWinInput: $('roundsToWinInput'),
+      diceInput: $('diceInput'),
+      tiebreakerInput: $('tiebreakerInput'),
       allowSpectatorsInput: $('allowSpectatorsInput'),
       modalCloseBtn: $('modalCloseBtn'),
       modalCancelBtn: $('modalCancelBtn'),
@@ -3831,192 +3897,216 @@ export class BiggerDice extends HTMLElement {
 
   handleMessage(event) {
     try {
-      const message = JSON.parse(event.data);
-      console.log('BiggerDice: Received', message.type, message);
-
-      switch (message.type) {
-        case 'system.welcome':
-          this.handleWelcome(message);
-          break;
-        case 'system.authenticated':
-          this.handleAuthenticated(message);
-          break;
-        case 'system.heartbeat_ack':
-          this.handleHeartbeatAck();
-          break;
-        case 'system.error':
-          this.handleSystemError(message);
-          break;
-
-        // Lobby messages
-        case 'room_list':
-        case 'games.event.room_list':
-          this.handleRoomList(message.rooms);
-          break;
-        // Room created - bigger_dice prefixed only
-        case 'games.event.bigger_dice.room_created':
-          this.handleRoomCreated(message);
-          break;
-        case 'games.event.bigger_dice.room_joined':
-          this.handleRoomJoined(message);
-          break;
-        // Room removed - bigger_dice prefixed only
-        case 'games.event.bigger_dice.room_removed':
-          this.handleRoomRemoved(message);
-          break;
-
-        // Game messages - bigger_dice prefixed only
-        case 'games.event.bigger_dice.room_state':
-          this.handleRoomState(message.room);
-          break;
-        case 'games.event.bigger_dice.player_joined':
-          this.handlePlayerJoined(message);
-          break;
-        // Player left - bigger_dice prefixed only
-        case 'games.event.bigger_dice.player_left':
-          this.handlePlayerLeft(message);
-          break;
-        case 'games.event.bigger_dice.player_disconnected':
-          this.handlePlayerDisconnected(message);
-          break;
-        case 'games.event.bigger_dice.player_rejoined':
-          this.handlePlayerRejoined(message);
-          break;
-        case 'games.event.bigger_dice.player_auto_enabled':
-          this.handlePlayerAutoEnabled(message);
-          break;
-        case 'games.event.bigger_dice.player_auto_disabled':
-          this.handlePlayerAutoDisabled(message);
-          break;
-        // Lobby events (admin/player selection system) - bigger_dice prefixed only
-        case 'games.event.bigger_dice.lobby_joined':
-          this.handleLobbyJoined(message);
-          break;
-        // Player selected - bigger_dice prefixed only
-        case 'games.event.bigger_dice.player_selected':
-          this.handlePlayerSelected(message);
-          break;
-        // Player kicked - bigger_dice prefixed only
-        case 'games.event.bigger_dice.player_kicked':
-          this.handlePlayerKicked(message);
-          break;
-        // Player banned - bigger_dice prefixed only
-        case 'games.event.bigger_dice.player_banned':
-          this.handlePlayerBanned(message);
-          break;
-        // Player unbanned - bigger_dice prefixed only
-        case 'games.event.bigger_dice.player_unbanned':
-          this.handlePlayerUnbanned(message);
-          break;
-        case 'games.event.bigger_dice.user_banned':
-          this.handleUserBanned(message);
-          break;
-        case 'games.event.bigger_dice.lobby_updated':
-          this.handleLobbyUpdated(message);
-          break;
-        // Game started - bigger_dice prefixed only
-        case 'games.event.bigger_dice.game_started':
-          this.handleGameStarted(message);
-          break;
-        // Player ready - bigger_dice prefixed only
-        case 'games.event.bigger_dice.player_ready':
-          this.handlePlayerReady(message);
-          break;
-        case 'games.event.bigger_dice.rolled':
-          this.handleDiceRolled(message);
-          break;
-        case 'games.event.bigger_dice.state':
-          this.handleBiggerDiceState(message);
-          break;
-        case 'games.event.bigger_dice.round_result':
-          this.handleRoundResult(message);
-          break;
-        case 'games.event.bigger_dice.tiebreaker_started':
-          this.handleTiebreakerStarted(message);
-          break;
-        case 'games.event.bigger_dice.turn_changed':
-          this.handleTurnChanged(message);
-          break;
-        case 'games.event.bigger_dice.round_complete':
-          this.handleRoundComplete(message);
-          break;
-        case 'games.event.bigger_dice.game_over':
-          this.handleGameOver(message);
-          break;
-        case 'error':
-        case 'games.event.error':
-          this.handleGameError(message);
-          break;
-        case 'games.event.bigger_dice.not_in_room':
-          this.handleNotInRoom(message);
-          break;
-
-        // Chat events - bigger_dice channel-specific
-        case 'games.event.bigger_dice.lobby_chat':
-          this.handleChatMessage(message, 'lobby');
-          break;
-        case 'games.event.bigger_dice.player_chat':
-          this.handleChatMessage(message, 'players');
-          break;
-        case 'games.event.bigger_dice.spectator_chat':
-          this.handleChatMessage(message, 'spectators');
-          break;
-        case 'games.event.bigger_dice.lobby_chat_history':
-          this.handleChatHistory(message, 'lobby');
-          break;
-        case 'games.event.bigger_dice.player_chat_history':
-          this.handleChatHistory(message, 'players');
-          break;
-        case 'games.event.bigger_dice.spectator_chat_history':
-          this.handleChatHistory(message, 'spectators');
-          break;
-        // Legacy generic chat events (fallback)
-        case 'games.event.bigger_dice.chat_message':
-          this.handleChatMessage(message, message.channel || 'lobby');
-          break;
-        case 'games.event.bigger_dice.chat_history':
-          this.handleChatHistory(message, message.channel || 'lobby');
-          break;
-        case 'games.event.bigger_dice.user_muted':
-          // Server confirmed user was muted
-          console.log('[Chat] User muted:', message.target_user_id);
-          break;
-        case 'games.event.bigger_dice.user_unmuted':
-          // Server confirmed user was unmuted
-          console.log('[Chat] User unmuted:', message.target_user_id);
-          break;
-
-        // Spectator events - bigger_dice prefixed only
-        case 'games.event.bigger_dice.spectator_joined':
-        case 'games.event.bigger_dice.spectator_data_joined':
-          this.handleSpectatorJoined(message);
-          break;
-        case 'games.event.bigger_dice.spectator_left':
-          this.handleSpectatorLeft(message);
-          break;
-        case 'games.event.bigger_dice.spectator_kicked':
-          this.handleSpectatorKicked(message);
-          break;
-        case 'games.event.bigger_dice.request_to_play_accepted':
-          this.handleRequestToPlayAccepted(message);
-          break;
-
-        // Game transition events - bigger_dice prefixed only
-        case 'games.event.bigger_dice.removed_from_game':
-          this.handleRemovedFromGame(message);
-          break;
-        case 'games.event.bigger_dice.game_starting':
-          this.handleGameStarting(message);
-          break;
-
-        default:
-          console.warn('BiggerDice: Unknown message type', message.type);
-      }
+      this.dispatchMessage(JSON.parse(event.data));
     } catch (error) {
       console.error('BiggerDice: Error parsing message', error);
     }
   }
 
+  dispatchMessage(message) {
+    console.log('BiggerDice: Received', message.type, message);
+
+    switch (message.type) {
+      case 'system.batch':
+        // Coalesced high-frequency updates, handled in order
+        message.messages.forEach((inner) => this.dispatchMessage(inner));
+        break;
+      case 'system.welcome':
+        this.handleWelcome(message);
+        break;
+      case 'system.authenticated':
+        this.handleAuthenticated(message);
+        break;
+      case 'system.deprecation_warning':
+        console.warn('BiggerDice:', message.message);
+        break;
+      case 'system.heartbeat_ack':
+        this.handleHeartbeatAck();
+        break;
+      case 'system.error':
+        this.handleSystemError(message);
+        break;
+
+      // Lobby messages
+      case 'room_list':
+      case 'games.event.room_list':
+        this.handleRoomList(message.rooms);
+        break;
+      // Room created - bigger_dice prefixed only
+      case 'games.event.bigger_dice.room_created':
+        this.handleRoomCreated(message);
+        break;
+      case 'games.event.bigger_dice.room_joined':
+        this.handleRoomJoined(message);
+        break;
+      // Room removed - bigger_dice prefixed only
+      case 'games.event.room_inactivity_warning':
+        this.handleInactivityWarning(message);
+        break;
+      case 'games.event.room_inactivity_extended':
+        console.log('[BiggerDice] Room extended until', message.closes_at);
+        break;
+      case 'games.event.room_closing':
+        this.handleRoomClosing(message);
+        break;
+      case 'games.event.bigger_dice.room_removed':
+        this.handleRoomRemoved(message);
+        break;
+
+      // Game messages - bigger_dice prefixed only
+      case 'games.event.bigger_dice.room_state':
+        this.roomStateVersion = message.version ?? null;
+        this.roomStateSnapshot = structuredClone(message.room);
+        this.handleRoomState(message.room);
+        break;
+      case 'games.event.room_state_diff':
+        this.handleRoomStateDiff(message);
+        break;
+      case 'games.event.bigger_dice.player_joined':
+        this.handlePlayerJoined(message);
+        break;
+      // Player left - bigger_dice prefixed only
+      case 'games.event.bigger_dice.player_left':
+        this.handlePlayerLeft(message);
+        break;
+      case 'games.event.bigger_dice.player_disconnected':
+        this.handlePlayerDisconnected(message);
+        break;
+      case 'games.event.bigger_dice.player_rejoined':
+        this.handlePlayerRejoined(message);
+        break;
+      case 'games.event.bigger_dice.player_auto_enabled':
+        this.handlePlayerAutoEnabled(message);
+        break;
+      case 'games.event.bigger_dice.player_auto_disabled':
+        this.handlePlayerAutoDisabled(message);
+        break;
+      // Lobby events (admin/player selection system) - bigger_dice prefixed only
+      case 'games.event.bigger_dice.lobby_joined':
+        this.handleLobbyJoined(message);
+        break;
+      // Player selected - bigger_dice prefixed only
+      case 'games.event.bigger_dice.player_selected':
+        this.handlePlayerSelected(message);
+        break;
+      // Player kicked - bigger_dice prefixed only
+      case 'games.event.bigger_dice.player_kicked':
+        this.handlePlayerKicked(message);
+        break;
+      // Player banned - bigger_dice prefixed only
+      case 'games.event.bigger_dice.player_banned':
+        this.handlePlayerBanned(message);
+        break;
+      // Player unbanned - bigger_dice prefixed only
+      case 'games.event.bigger_dice.player_unbanned':
+        this.handlePlayerUnbanned(message);
+        break;
+      case 'games.event.bigger_dice.user_banned':
+        this.handleUserBanned(message);
+        break;
+      case 'games.event.bigger_dice.lobby_updated':
+        this.handleLobbyUpdated(message);
+        break;
+      // Game started - bigger_dice prefixed only
+      case 'games.event.bigger_dice.game_started':
+        this.handleGameStarted(message);
+        break;
+      // Player ready - bigger_dice prefixed only
+      case 'games.event.bigger_dice.player_ready':
+        this.handlePlayerReady(message);
+        break;
+      case 'games.event.bigger_dice.rolled':
+        this.handleDiceRolled(message);
+        break;
+      case 'games.event.bigger_dice.state':
+        this.handleBiggerDiceState(message);
+        break;
+      case 'games.event.bigger_dice.round_result':
+        this.handleRoundResult(message);
+        break;
+      case 'games.event.bigger_dice.tiebreaker_started':
+        this.handleTiebreakerStarted(message);
+        break;
+      case 'games.event.bigger_dice.turn_changed':
+        this.handleTurnChanged(message);
+        break;
+      case 'games.event.bigger_dice.round_complete':
+        this.handleRoundComplete(message);
+        break;
+      case 'games.event.bigger_dice.game_over':
+        this.handleGameOver(message);
+        break;
+      case 'error':
+      case 'games.event.error':
+        this.handleGameError(message);
+        break;
+      case 'games.event.bigger_dice.not_in_room':
+        this.handleNotInRoom(message);
+        break;
+
+      // Chat events - bigger_dice channel-specific
+      case 'games.event.bigger_dice.lobby_chat':
+        this.handleChatMessage(message, 'lobby');
+        break;
+      case 'games.event.bigger_dice.player_chat':
+        this.handleChatMessage(message, 'players');
+        break;
+      case 'games.event.bigger_dice.spectator_chat':
+        this.handleChatMessage(message, 'spectators');
+        break;
+      case 'games.event.bigger_dice.lobby_chat_history':
+        this.handleChatHistory(message, 'lobby');
+        break;
+      case 'games.event.bigger_dice.player_chat_history':
+        this.handleChatHistory(message, 'players');
+        break;
+      case 'games.event.bigger_dice.spectator_chat_history':
+        this.handleChatHistory(message, 'spectators');
+        break;
+      // Legacy generic chat events (fallback)
+      case 'games.event.bigger_dice.chat_message':
+        this.handleChatMessage(message, message.channel || 'lobby');
+        break;
+      case 'games.event.bigger_dice.chat_history':
+        this.handleChatHistory(message, message.channel || 'lobby');
+        break;
+      case 'games.event.bigger_dice.user_muted':
+        // Server confirmed user was muted
+        console.log('[Chat] User muted:', message.target_user_id);
+        break;
+      case 'games.event.bigger_dice.user_unmuted':
+        // Server confirmed user was unmuted
+        console.log('[Chat] User unmuted:', message.target_user_id);
+        break;
+
+      // Spectator events - bigger_dice prefixed only
+      case 'games.event.bigger_dice.spectator_joined':
+      case 'games.event.bigger_dice.spectator_data_joined':
+        this.handleSpectatorJoined(message);
+        break;
+      case 'games.event.bigger_dice.spectator_left':
+        this.handleSpectatorLeft(message);
+        break;
+      case 'games.event.bigger_dice.spectator_kicked':
+        this.handleSpectatorKicked(message);
+        break;
+      case 'games.event.bigger_dice.request_to_play_accepted':
+        this.handleRequestToPlayAccepted(message);
+        break;
+
+      // Game transition events - bigger_dice prefixed only
+      case 'games.event.bigger_dice.removed_from_game':
+        this.handleRemovedFromGame(message);
+        break;
+      case 'games.event.bigger_dice.game_starting':
+        this.handleGameStarting(message);
+        break;
+
+      default:
+        console.warn('BiggerDice: Unknown message type', message.type);
+    }
+  }
+
   handleClose(event) {
     console.log('BiggerDice: WebSocket closed', event.code, event.reason);
     this.stopHeartbeat();
@@ -4125,12 +4215,16 @@ export class BiggerDice extends HTMLElement {
       type: 'system.authenticate',
       user_id: String(this.userId),
       username: this.username || 'Guest',
-      avatar_id: this.avatarId || null
+      avatar_id: this.avatarId || null,
+      protocol_version: 9
     });
   }
 
   handleAuthenticated(message) {
     console.log('BiggerDice: Authenticated as', message.username);
+    if (message.settings?.theme) {
+      window.Blazing_Sun?.theme?.applyUserSetting(message.settings.theme);
+    }
     this.setConnectionState(ConnectionState.CONNECTED);
 
     if (this.mode === ComponentMode.LOBBY) {
@@ -4270,6 +4364,29 @@ export class BiggerDice extends HTMLElement {
     }
   }
 
+  handleInactivityWarning(message) {
+    if (this.roomId !== message.room_id) return;
+
+    const minutes = Math.max(1, Math.round(message.closes_in_seconds / 60));
+    console.warn(`[BiggerDice] Room closes in ${minutes} min due to inactivity`);
+
+    // Only the host can keep an idle room open
+    if (this.isAdmin && window.confirm(`This room closes in ${minutes} minute(s) due to inactivity. Keep it open?`)) {
+      this.send({
+        type: 'games.command.extend_room',
+        room_id: this.roomId
+      });
+    }
+  }
+
+  handleRoomClosing(message) {
+    if (this.roomId !== message.room_id || this.mode !== ComponentMode.GAME) return;
+
+    this.showRoomClosedMessage(message.reason === 'inactive'
+      ? 'This room has been closed due to inactivity.'
+      : 'This room has been closed by an administrator.');
+  }
+
   renderRoomList() {
     const grid = this.elements.roomsGrid;
     const loading = this.elements.loadingState;
@@ -4397,6 +4514,15 @@ export class BiggerDice extends HTMLElement {
     if (this.elements.playerCountInput) {
       this.elements.playerCountInput.value = '2';
     }
+    if (this.elements.roundsToWinInput) {
+      this.elements.roundsToWinInput.value = '10';
+    }
+    if (this.elements.diceInput) {
+      this.elements.diceInput.value = '1d6';
+    }
+    if (this.elements.tiebreakerInput) {
+      this.elements.tiebreakerInput.value = 'sudden_death';
+    }
     if (this.elements.allowSpectatorsInput) {
       this.elements.allowSpectatorsInput.checked = true;
     }
@@ -4648,15 +4774,23 @@ export class BiggerDice extends HTMLElement {
     const password = this.elements.roomPasswordInput?.value.trim() || '';
     const playerCount = parseInt(this.elements.playerCountInput?.value || '2', 10);
     const allowSpectators = this.elements.allowSpectatorsInput?.checked ?? true;
+    const [diceCount, diceSides] = (this.elements.diceInput?.value || '1d6').split('d').map(Number);
+    const rules = {
+      rounds_to_win: parseInt(this.elements.roundsToWinInput?.value || '10', 10),
+      dice_count: diceCount,
+      dice_sides: diceSides,
+      tiebreaker: this.elements.tiebreakerInput?.value || 'sudden_death'
+    };
 
-    console.log('[BiggerDice] Creating room:', roomName, 'players:', playerCount, 'spectators:', allowSpectators);
+    console.log('[BiggerDice] Creating room:', roomName, 'players:', playerCount, 'spectators:', allowSpectators, 'rules:', rules);
 
     const message = {
       type: 'games.command.create_room',
       game_type: 'bigger_dice',
       room_name: roomName,
       max_players: playerCount,
-      allow_spectators: allowSpectators
+      allow_spectators: allowSpectators,
+      rules
     };
 
     // Only include password if provided
@@ -5343,6 +5477,27 @@ export class BiggerDice extends HTMLElement {
   // Game Handlers
   // ============================================
 
+  /**
+   * Apply a room_state_diff to the room state we hold. A diff for another
+   * version means an update was missed, so the full room state is requested.
+   */
+  handleRoomStateDiff(message) {
+    if (message.room_id !== this.roomId) return;
+
+    if (!this.roomStateSnapshot || message.base_version !== this.roomStateVersion) {
+      console.warn('[BiggerDice] Room state diff for another version, requesting room state');
+      this.send({
+        type: 'games.command.get_room_state',
+        room_id: this.roomId
+      });
+      return;
+    }
+
+    this.roomStateSnapshot = applyRoomStateDiff(this.roomStateSnapshot, message.ops);
+    this.roomStateVersion = message.version;
+    this.handleRoomState(structuredClone(this.roomStateSnapshot));
+  }
+
   handleRoomState(room) {
     // Clear "not in room" state since we're now receiving room state
     this.notInRoomInfo = null;
@@ -5571,7 +5726,7 @@ export class BiggerDice extends HTMLElement {
     this.updateGameUI();
   }
 
-  showRoomClosedMessage() {
+  showRoomClosedMessage(text = 'This room has been closed. The admin has left the game.') {
     // Hide all game states
     if (this.elements.waitingState) this.elements.waitingState.classList.add('hidden');
     if (this.elements.adminLobby) this.elements.adminLobby.classList.add('hidden');
@@ -5586,7 +5741,7 @@ export class BiggerDice extends HTMLElement {
 
       if (iconEl) iconEl.textContent = '🚪';
       if (titleEl) titleEl.textContent = 'Room Closed';
-      if (messageEl) messageEl.textContent = 'This room has been closed. The admin has left the game.';
+      if (messageEl) messageEl.textContent = text;
 
       this.elements.waitingForAdmin.classList.remove('hidden');
     }
@@ -6189,6 +6344,7 @@ export class BiggerDice extends HTMLElement {
     if (!diceEl) return;
     const value = Number.isInteger(roll) ? roll : 0;
     diceEl.dataset.value = String(value);
+    diceEl.classList.toggle('dice--total', value > 6);
   }
 
   handlePlayerReady(message) {
@@ -6405,6 +6561,7 @@ export class BiggerDice extends HTMLElement {
     console.log('[DEBUG] handleTurnChanged - current player scores:', this.players.map(p => ({id: p.user_id || p.id, score: p.score})));
     this.currentTurn = String(message.current_turn);
     this.round = message.turn_number || this.round;
+    this.verifyStateChecksum(message);
 
     // Update UI to show whose turn it is
     this.updateTurnIndicator();
@@ -6417,6 +6574,37 @@ export class BiggerDice extends HTMLElement {
     this.checkAutoRollNeeded();
   }
 
+  /**
+   * Compare a turn change's state checksum with the room as this client has
+   * it; when they differ the server resends the room state.
+   * The canonical form is described in the backend's
+   * app/games/state_checksum.rs (a turn change always leaves the room in
+   * progress without a winner).
+   */
+  async verifyStateChecksum(message) {
+    if (!message.state_checksum || !window.crypto?.subtle) return;
+
+    const playerIds = this.players
+      .map(p => Number(p.user_id ?? p.id))
+      .sort((a, b) => a - b)
+      .join(',');
+    const canonical = `${this.roomId}|in_progress|${message.turn_number}|${message.current_turn}|-|${playerIds}`;
+    const digest = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(canonical));
+    const checksum = Array.from(new Uint8Array(digest))
+      .map(b => b.toString(16).padStart(2, '0'))
+      .join('')
+      .slice(0, 16);
+
+    if (checksum !== message.state_checksum) {
+      console.warn('[BiggerDice] State out of sync, requesting room state:', canonical);
+      this.send({
+        type: 'games.command.report_desync',
+        room_id: this.roomId,
+        state_checksum: checksum
+      });
+    }
+  }
+
   /**
    * Check if the current turn player is an auto-player (kicked) and trigger auto-roll.
    * This is a frontend fallback mechanism to ensure kicked players' turns are handled
@@ -7845,13 +8033,13 @@ export class BiggerDice extends HTMLElement {
 
       const rollInterval = setInterval(() => {
         const randomValue = Math.floor(Math.random() * 6) + 1;
-        diceEl.dataset.value = randomValue;
+        this.setDiceValue(diceEl, randomValue);
         rollCount++;
 
         if (rollCount >= maxRolls) {
           clearInterval(rollInterval);
           diceEl.classList.remove('dice--rolling');
-          diceEl.dataset.value = finalValue;
+          this.setDiceValue(diceEl, finalValue);
 
           // Clear animation flag
           this.isAnimating = false;
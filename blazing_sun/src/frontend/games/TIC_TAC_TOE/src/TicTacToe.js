@@ -2269,6 +2269,29 @@ template.innerHTML = `
   </div>
 `;
 
+/**
+  * Apply the ops of a room_state_diff (add / remove / replace with JSON
+  * pointer paths) to a copy of a room state
+  */
+function applyRoomStateDiff(room, ops) {
+    let patched = structuredClone(room);
+    for (const op of ops) {
+        if (op.path === '') {
+            patched = structuredClone(op.value);
+            continue;
+        }
+        const keys = op.path.slice(1).split('/').map(key => key.replace(/~1/g, '/').replace(/~0/g, '~'));
+        const last = keys.pop();
+        const parent = keys.reduce((node, key) => node[key], patched);
+        if (op.op === 'remove') {
+            delete parent[last];
+        } else {
+            parent[last] = structuredClone(op.value);
+        }
+    }
+    return patched;
+}
+
 export class TicTacToe extends HTMLElement {
     constructor() {
         super();
@@ -2705,6 +2728,7 @@ export class TicTacToe extends HTMLElement {
             user_id: this.userId,
             username: this.username,
             avatar_id: this.avatarId || null,
+            protocol_version: 9,
         });
     }
 
@@ -2762,9 +2786,16 @@ export class TicTacToe extends HTMLElement {
         console.log('[TicTacToe] Message:', msg.type, msg);
 
         switch (msg.type) {
+            case 'system.batch':
+                // Coalesced high-frequency updates, handled in order
+                msg.messages.forEach((inner) => this._handleMessage(inner));
+                break;
             case 'system.authenticated':
                 this._onAuthenticated(msg);
                 break;
+            case 'system.deprecation_warning':
+                console.warn('[TicTacToe]', msg.message);
+                break;
             case 'games.event.room_list':
                 this._onRoomList(msg);
                 break;
@@ -2776,10 +2807,26 @@ export class TicTacToe extends HTMLElement {
             case 'games.event.tic_tac_toe.room_removed':
                 this._onRoomRemoved(msg);
                 break;
+            case 'games.event.room_inactivity_warning':
+                this._onInactivityWarning(msg);
+                break;
+            case 'games.event.room_inactivity_extended':
+                if (msg.room_id === this.roomId) {
+                    this._showToast('The host kept the room open', 'info');
+                }
+                break;
+            case 'games.event.room_closing':
+                this._onRoomClosing(msg);
+                break;
             // Room state - tic_tac_toe prefixed
             case 'games.event.tic_tac_toe.room_state':
+                this.roomStateVersion = msg.version ?? null;
+                this.roomStateSnapshot = structuredClone(msg.room);
                 this._onRoomState(msg);
                 break;
+            case 'games.event.room_state_diff':
+                this._onRoomStateDiff(msg);
+                break;
             // Waiting room updates - tic_tac_toe prefixed
             case 'games.event.tic_tac_toe.player_selected':
             case 'games.event.tic_tac_toe.lobby_joined':
@@ -2862,6 +2909,9 @@ export class TicTacToe extends HTMLElement {
 
     _onAuthenticated(msg) {
         console.log('[TicTacToe] Authenticated as', msg.username);
+        if (msg.settings?.theme) {
+            window.Blazing_Sun?.theme?.applyUserSetting(msg.settings.theme);
+        }
 
         if (this.mode === 'lobby') {
             this._showLobby();
@@ -3280,6 +3330,33 @@ export class TicTacToe extends HTMLElement {
         }
     }
 
+    _onInactivityWarning(msg) {
+        if (msg.room_id !== this.roomId) return;
+
+        const minutes = Math.max(1, Math.round(msg.closes_in_seconds / 60));
+        this._showToast(`Room closes in ${minutes} min due to inactivity`, 'info');
+
+        // Only the host can keep an idle room open
+        if (String(this.hostId) === String(this.userId)
+            && window.confirm(`This room closes in ${minutes} minute(s) due to inactivity. Keep it open?`)) {
+            this._send({
+                type: 'games.command.extend_room',
+                room_id: this.roomId,
+            });
+        }
+    }
+
+    _onRoomClosing(msg) {
+        if (msg.room_id !== this.roomId || this.mode === 'lobby') return;
+
+        this._showToast(msg.reason === 'inactive'
+            ? 'Room was closed due to inactivity'
+            : 'Room was closed by an administrator', 'info');
+        this.mode = 'lobby';
+        this._showLobby();
+        this._listRooms();
+    }
+
     _onRoomRemoved(msg) {
         console.log('[TicTacToe] Room removed:', msg);
         const roomId = msg.room_id;
@@ -3452,6 +3529,27 @@ export class TicTacToe extends HTMLElement {
         });
     }
 
+    /**
+     * Apply a room_state_diff to the room state we hold. A diff for another
+     * version means an update was missed, so the full room state is requested.
+     */
+    _onRoomStateDiff(msg) {
+        if (msg.room_id !== this.roomId) return;
+
+        if (!this.roomStateSnapshot || msg.base_version !== this.roomStateVersion) {
+            console.warn('[TicTacToe] Room state diff for another version, requesting room state');
+            this._send({
+                type: 'games.command.get_room_state',
+                room_id: this.roomId,
+            });
+            return;
+        }
+
+        this.roomStateSnapshot = applyRoomStateDiff(this.roomStateSnapshot, msg.ops);
+        this.roomStateVersion = msg.version;
+        this._onRoomState({ room: structuredClone(this.roomStateSnapshot), state_checksum: msg.state_checksum });
+    }
+
     _onRoomState(msg) {
         const room = msg.room;
         console.log('[TicTacToe] Room state received:', room);
@@ -4280,6 +4378,38 @@ export class TicTacToe extends HTMLElement {
         this.currentTurn = msg.current_turn;
         this._startTimer();
         this._updateTurnIndicator();
+        this._verifyStateChecksum(msg);
+    }
+
+    /**
+     * Compare a turn change's state checksum with the room as this client
+     * has it; when they differ the server resends the room state.
+     * The canonical form is described in the backend's
+     * app/games/state_checksum.rs (a turn change always leaves the room in
+     * progress without a winner).
+     */
+    async _verifyStateChecksum(msg) {
+        if (!msg.state_checksum || !window.crypto?.subtle) return;
+
+        const playerIds = this.players
+            .map(p => Number(p.user_id ?? p.id))
+            .sort((a, b) => a - b)
+            .join(',');
+        const canonical = `${this.roomId}|in_progress|${msg.turn_number}|${msg.current_turn}|-|${playerIds}`;
+        const digest = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(canonical));
+        const checksum = Array.from(new Uint8Array(digest))
+            .map(b => b.toString(16).padStart(2, '0'))
+            .join('')
+            .slice(0, 16);
+
+        if (checksum !== msg.state_checksum) {
+            console.warn('[TicTacToe] State out of sync, requesting room state:', canonical);
+            this._send({
+                type: 'games.command.report_desync',
+                room_id: this.roomId,
+                state_checksum: checksum
+            });
+        }
     }
 
     _onMoveMade(msg) {
@@ -155,7 +155,8 @@ export class GamesPage {
       type: 'system.authenticate',
       user_id: String(this.userId),
       username: this.username || 'Guest',
-      avatar_id: this.avatarId || null
+      avatar_id: this.avatarId || null,
+      protocol_version: 9
     });
   }
 
@@ -164,6 +165,9 @@ export class GamesPage {
    */
   handleSystemAuthenticated(data) {
     console.log('Authenticated as:', data.username);
+    if (data.settings?.theme) {
+      window.Blazing_Sun?.theme?.applyUserSetting(data.settings.theme);
+    }
     this.setConnectionState(ConnectionState.CONNECTED);
     this.requestRoomList();
     this.showToast('Connected to game server', 'success');
@@ -215,46 +219,59 @@ export class GamesPage {
    */
   handleMessage(event) {
     try {
-      const message = JSON.parse(event.data);
-      console.log('Received:', message);
-
-      switch (message.type) {
-        case 'system.welcome':
-          this.handleSystemWelcome(message);
-          break;
-        case 'system.authenticated':
-          this.handleSystemAuthenticated(message);
-          break;
-        case 'system.error':
-          this.handleError(message);
-          break;
-        case 'system.heartbeat_ack':
-        case 'system.pong':
-        case 'pong':
-          this.handlePong();
-          break;
-
-        // Room messages
-        case 'room_list':
-        case 'games.event.room_list':
-          this.handleRoomList(message.rooms);
-          break;
-        case 'room_created':
-        case 'games.event.room_created':
-          this.handleRoomCreated(message);
-          break;
-        case 'error':
-        case 'games.event.error':
-          this.handleError(message);
-          break;
-        default:
-          console.warn('Unknown message type:', message.type);
-      }
+      this.dispatchMessage(JSON.parse(event.data));
     } catch (error) {
       console.error('Error parsing message:', error);
     }
   }
 
+  /**
+   * Handle a parsed server message (also each message of a batch)
+   */
+  dispatchMessage(message) {
+    console.log('Received:', message);
+
+    switch (message.type) {
+      case 'system.batch':
+        // Coalesced high-frequency updates, handled in order
+        message.messages.forEach((inner) => this.dispatchMessage(inner));
+        break;
+      case 'system.welcome':
+        this.handleSystemWelcome(message);
+        break;
+      case 'system.authenticated':
+        this.handleSystemAuthenticated(message);
+        break;
+      case 'system.deprecation_warning':
+        console.warn('Protocol:', message.message);
+        break;
+      case 'system.error':
+        this.handleError(message);
+        break;
+      case 'system.heartbeat_ack':
+      case 'system.pong':
+      case 'pong':
+        this.handlePong();
+        break;
+
+      // Room messages
+      case 'room_list':
+      case 'games.event.room_list':
+        this.handleRoomList(message.rooms);
+        break;
+      case 'room_created':
+      case 'games.event.room_created':
+        this.handleRoomCreated(message);
+        break;
+      case 'error':
+      case 'games.event.error':
+        this.handleError(message);
+        break;
+      default:
+        console.warn('Unknown message type:', message.type);
+    }
+  }
+
   /**
    * Handle WebSocket close event
    */
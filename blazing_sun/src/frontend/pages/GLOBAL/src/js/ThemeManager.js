@@ -116,6 +116,18 @@ export class ThemeManager {
     }
   }
 
+  /**
+   * Apply the theme saved in the user's account settings
+   * @param {string} theme - 'system', 'light' or 'dark'
+   */
+  applyUserSetting(theme) {
+    if (theme === 'system') {
+      this.applyTheme(this.getSystemTheme());
+    } else {
+      this.setTheme(theme);
+    }
+  }
+
   /**
    * Check if dark theme is active
    * @returns {boolean}
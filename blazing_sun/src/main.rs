@@ -4,12 +4,21 @@ use actix_web::cookie::{time::Duration, Key, SameSite};
 use actix_web::middleware::from_fn;
 use actix_web::web::{Data, JsonConfig};
 use actix_web::{App, HttpServer};
+use blazing_sun::app::analytics::mongodb_analytics::MongoAnalyticsClient;
+use blazing_sun::app::chat::blocks::ChatBlocks;
+use blazing_sun::app::chat::mongodb_channel::MongoChannelClient;
+use blazing_sun::app::user_settings::SettingsMirror;
+use blazing_sun::bootstrap::includes::{storage, ThemeService};
 use blazing_sun::bootstrap::middleware::controllers::csrf;
-use blazing_sun::config::{AppConfig, SessionConfig};
-use blazing_sun::database::{create_mongodb, create_pool, state_full, AppState};
+use blazing_sun::config::{AppConfig, SecretsConfig, SessionConfig};
+use blazing_sun::database::{
+    create_mongodb, create_pool, create_redis, schema_check, state_full, AppState, PoolRole,
+};
 use blazing_sun::events;
 use blazing_sun::init_crons;
-use blazing_sun::middleware::{cors, security_headers, tracing_logger};
+use blazing_sun::middleware::{
+    cors, idempotency, load_shedding, locale, maintenance, security_headers, tracing_logger,
+};
 use blazing_sun::mq;
 use blazing_sun::{configure_api, configure_web, json_error_handler};
 use std::sync::Arc;
@@ -23,11 +32,32 @@ async fn main() -> std::io::Result<()> {
 
     tracing_logger::init();
 
+    // Every config static reads its secrets through the provider, so it goes first
+    if let Err(e) = SecretsConfig::load().await {
+        error!("Invalid configuration: {}", e);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            e.to_string(),
+        ));
+    }
+    info!("Secrets provider: {}", SecretsConfig::provider_name());
+
     let host = AppConfig::host();
     let port = AppConfig::port();
 
+    // Refuse to serve traffic against a database that drifted from our migrations
+    let schema_pool = create_pool(PoolRole::Http).await;
+    if let Err(e) = schema_check::verify_on_startup(&schema_pool).await {
+        error!("{}", e);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            e.to_string(),
+        ));
+    }
+    schema_pool.close().await;
+
     // Initialize cron jobs with a separate database pool
-    let cron_pool = create_pool().await;
+    let cron_pool = create_pool(PoolRole::Cron).await;
     let _scheduler = match init_crons(cron_pool).await {
         Ok(scheduler) => scheduler,
         Err(e) => {
@@ -39,8 +69,29 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    // Re-parse the theme variables only when the SCSS files change
+    let _theme_watcher = match ThemeService::watch() {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            warn!(
+                "Theme file watcher unavailable (theme cache refreshes on updates and reloads only): {}",
+                e
+            );
+            None
+        }
+    };
+
+    // Initialize file storage (local disk or S3-compatible, per STORAGE_DRIVER)
+    if let Err(e) = storage::init().await {
+        error!("Failed to initialize storage: {}", e);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            e.to_string(),
+        ));
+    }
+
     // Initialize message queue (RabbitMQ for async tasks)
-    let mq_pool = create_pool().await;
+    let mq_pool = create_pool(PoolRole::Mq).await;
     let mq_queue = match mq::init(mq_pool).await {
         Ok(queue) => queue,
         Err(e) => {
@@ -59,6 +110,12 @@ async fn main() -> std::io::Result<()> {
     let mongodb = match create_mongodb().await {
         Ok(db) => {
             info!("MongoDB connected successfully");
+            if let Err(e) = MongoChannelClient::new(db.clone()).init_indexes().await {
+                warn!("Failed to create channel message indexes: {}", e);
+            }
+            if let Err(e) = MongoAnalyticsClient::new(db.clone()).init_indexes().await {
+                warn!("Failed to create analytics indexes: {}", e);
+            }
             Some(db)
         }
         Err(e) => {
@@ -70,12 +127,27 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    // Initialize shared Redis connection (idempotency keys, caches, room password throttling)
+    let redis = match create_redis().await {
+        Ok(conn) => {
+            info!("Redis connected successfully");
+            Some(conn)
+        }
+        Err(e) => {
+            warn!("Failed to connect to Redis (continuing without Redis): {}", e);
+            None
+        }
+    };
+
+    // Follow the shared read-only maintenance flag (also pauses the MQ processor)
+    blazing_sun::app::maintenance::init(redis.clone());
+
     // Initialize Kafka event system (for event-driven architecture)
     // Uses init_full to register WebSocket gateway handlers (chat, games) when MongoDB is available
-    let events_pool = create_pool().await;
+    let events_pool = create_pool(PoolRole::Events).await;
     let events_db = Arc::new(Mutex::new(events_pool));
 
-    let (event_bus, event_consumer) = match events::init_full(events_db, mongodb.clone()).await {
+    let (event_bus, event_consumer) = match events::init_full(events_db, mongodb.clone(), redis.clone(), Some(mq_queue.clone())).await {
         Ok((bus, consumer)) => {
             info!("Kafka event system initialized successfully");
             (Some(bus), Some(consumer))
@@ -98,8 +170,22 @@ async fn main() -> std::io::Result<()> {
     // Cast SharedQueue to DynMq for AppState (avoids circular dependency)
     let dyn_mq: blazing_sun::database::DynMq = mq_queue;
 
-    // Create state with all services (MQ, Events, MongoDB)
-    let state: Data<AppState> = state_full(dyn_mq, event_bus, mongodb).await;
+    // Create state with all services (MQ, Events, MongoDB, Redis)
+    let state: Data<AppState> = state_full(dyn_mq, event_bus, mongodb, redis).await;
+
+    // Rebuild the chat block mirror the WebSocket gateway filters on
+    let blocks_state = state.clone();
+    tokio::spawn(async move {
+        let db = blocks_state.db.lock().await.clone();
+        ChatBlocks::new(blocks_state.redis()).rebuild_all(&db).await;
+    });
+
+    // Mirror user settings the WebSocket gateway hydrates clients with
+    let settings_state = state.clone();
+    tokio::spawn(async move {
+        let db = settings_state.db.lock().await.clone();
+        SettingsMirror::new(settings_state.redis()).rebuild_all(&db).await;
+    });
 
     // Initialize session configuration
     let session_config = SessionConfig::from_env().map_err(|e| {
@@ -121,7 +207,7 @@ async fn main() -> std::io::Result<()> {
     info!("Redis session store initialized successfully");
 
     // Use persistent secret key for session cookies (from env or generate once)
-    let secret_key = match std::env::var("SESSION_SECRET_KEY") {
+    let secret_key = match SecretsConfig::var("SESSION_SECRET_KEY") {
         Ok(key) if key.len() >= 64 => {
             // Use the provided key (must be at least 64 bytes for security)
             Key::from(key.as_bytes())
@@ -155,16 +241,26 @@ async fn main() -> std::io::Result<()> {
                 .cookie_content_security(CookieContentSecurity::Private)
                 .build();
 
-        App::new()
-            .wrap(tracing_logger::configure())
+        let app = App::new()
             .wrap(cors::configure())
             .wrap(security_headers::configure())
+            .wrap(from_fn(idempotency::idempotency))
+            .wrap(from_fn(maintenance::read_only))
             .wrap(from_fn(csrf::verify_csrf))
+            .wrap(from_fn(locale::localize_response))
             .wrap(session_middleware)
+            .wrap(from_fn(api_casing::http::apply_policy))
+            .wrap(from_fn(load_shedding::shed_load))
+            .wrap(from_fn(tracing_logger::trace_request))
             .app_data(state.clone())
             .app_data(JsonConfig::default().error_handler(json_error_handler))
             .configure(configure_api)
-            .configure(configure_web)
+            .configure(configure_web);
+
+        #[cfg(feature = "chaos")]
+        let app = app.configure(fault_injection::http::configure);
+
+        app
     })
     .bind((host, port))?;
 
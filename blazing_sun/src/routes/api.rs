@@ -6,25 +6,49 @@
 
 use actix_web::{middleware::from_fn, web};
 
+use crate::app::http::api::controllers::achievement::AchievementController;
 use crate::app::http::api::controllers::activation::ActivationController;
 use crate::app::http::api::controllers::admin::AdminController;
+use crate::app::http::api::controllers::analytics::AnalyticsController;
 use crate::app::http::api::controllers::auth::AuthController;
 use crate::app::http::api::controllers::balance::BalanceController;
+use crate::app::http::api::controllers::balance_adjustment::BalanceAdjustmentController;
+use crate::app::http::api::controllers::balance_transfer::BalanceTransferController;
+use crate::app::http::api::controllers::chat_channel::ChatChannelController;
+use crate::app::http::api::controllers::direct_message::DirectMessageController;
 use crate::app::http::api::controllers::email::EmailController;
+use crate::app::http::api::controllers::feature_flag::FeatureFlagController;
 use crate::app::http::api::controllers::game_chat_config::GameChatConfigController;
+use crate::app::http::api::controllers::game_region::GameRegionController;
+use crate::app::http::api::controllers::game_room_schedule::GameRoomScheduleController;
+use crate::app::http::api::controllers::game_room_search::GameRoomSearchController;
+use crate::app::http::api::controllers::game_webhook::GameWebhookController;
+use crate::app::http::api::controllers::impersonation::ImpersonationController;
 use crate::app::http::api::controllers::localization::LocalizationController;
+use crate::app::http::api::controllers::maintenance::MaintenanceController;
+use crate::app::http::api::controllers::me::MeController;
+use crate::app::http::api::controllers::job::JobController;
+use crate::app::http::api::controllers::payments::PaymentsController;
+use crate::app::http::api::controllers::role::RoleController;
 use crate::app::http::api::controllers::roulette::RouletteController;
 use crate::app::http::api::controllers::roulette_ajax::RouletteAjaxController;
 use crate::app::http::api::controllers::schema::SchemaController;
+use crate::app::http::api::controllers::session::SessionController;
+use crate::app::http::api::controllers::tenant_theme::TenantThemeController;
 use crate::app::http::api::controllers::theme::ThemeController;
+use crate::app::http::api::controllers::tournament::TournamentController;
+use crate::app::http::api::controllers::two_factor::TwoFactorController;
 use crate::app::http::api::controllers::upload::UploadController;
 use crate::app::http::api::controllers::user::UserController;
+use crate::app::http::api::controllers::ws_penalty::WsPenaltyController;
 use crate::app::http::api::controllers::{
-    competitions, gallery, gallery_like, game_config, game_history, geo_place, oauth,
-    oauth_api_product, oauth_client, oauth_gallery, oauth_scope, picture,
+    competitions, gallery, gallery_like, game_config, game_history, game_room_preset, game_stats,
+    geo_place, oauth, oauth_api_product, oauth_client, oauth_gallery, oauth_scope, picture,
 };
 use crate::middleware;
 use crate::middleware::permission::{levels, require_permission};
+use crate::middleware::rbac::require_group;
+use rbac::RouteGroup;
 use crate::route;
 
 /// Register all API routes
@@ -32,6 +56,32 @@ pub fn register(cfg: &mut web::ServiceConfig) {
     // Register named routes for URL generation
     register_route_names();
 
+    // ============================================
+    // Device Session Routes (Protected) - before the public /api/v1/auth scope
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/auth/sessions")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("", web::get().to(SessionController::list))
+            .route("/{id}", web::delete().to(SessionController::revoke)),
+    );
+
+    // ============================================
+    // Two-Factor Routes (Protected) - before the public /api/v1/auth scope
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/auth/two-factor")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("", web::get().to(TwoFactorController::status))
+            .route("", web::delete().to(TwoFactorController::disable))
+            .route("/enroll", web::post().to(TwoFactorController::enroll))
+            .route("/confirm", web::post().to(TwoFactorController::confirm))
+            .route(
+                "/recovery-codes",
+                web::post().to(TwoFactorController::regenerate_recovery_codes),
+            ),
+    );
+
     // ============================================
     // Authentication Routes (Public)
     // ============================================
@@ -39,6 +89,15 @@ pub fn register(cfg: &mut web::ServiceConfig) {
         web::scope("/api/v1/auth")
             .route("/sign-up", web::post().to(AuthController::sign_up))
             .route("/sign-in", web::post().to(AuthController::sign_in))
+            // Second step of a sign-in held for a two-factor code (pre-auth token)
+            .route(
+                "/sign-in/two-factor",
+                web::post().to(TwoFactorController::complete),
+            )
+            .route(
+                "/sign-in/two-factor/enroll",
+                web::post().to(TwoFactorController::enroll_pre_auth),
+            )
             .route("/sign-out", web::post().to(AuthController::sign_out))
             .route(
                 "/sign-out-all",
@@ -97,6 +156,14 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             .route("/{type_name}", web::get().to(SchemaController::schema)),
     );
 
+    // ============================================
+    // Tenant Theme Routes (Public)
+    // ============================================
+    cfg.service(web::scope("/api/v1/theme").route(
+        "/tenants/{tenant_key}",
+        web::get().to(TenantThemeController::stylesheets_for),
+    ));
+
     // ============================================
     // Password Change Routes (Protected - requires JWT)
     // ============================================
@@ -141,6 +208,7 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             .wrap(from_fn(middleware::auth::verify_jwt))
             .route("", web::get().to(UserController::get_current))
             .route("/{id}", web::get().to(UserController::get_by_id))
+            .route("/{id}/achievements", web::get().to(AchievementController::for_user))
             .route("", web::patch().to(UserController::update_partial))
             .route("", web::put().to(UserController::update_full))
             .route("", web::post().to(UserController::admin_create))
@@ -148,13 +216,74 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             .route("/{id}", web::delete().to(UserController::delete)),
     );
 
+    // ============================================
+    // Data Export Download (Public - authorized by the link's signature)
+    // ============================================
+    cfg.service(
+        web::resource("/api/v1/data-exports/{id}/download")
+            .route(web::get().to(MeController::download_export)),
+    );
+
+    // ============================================
+    // Me Routes (Protected - requires JWT)
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/me")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("", web::delete().to(MeController::request_erasure))
+            .route("/bootstrap", web::get().to(MeController::bootstrap))
+            .route(
+                "/gaming-activity/export",
+                web::get().to(MeController::gaming_activity_export),
+            )
+            .route("/erasure", web::get().to(MeController::erasure_status))
+            .route("/erasure", web::delete().to(MeController::cancel_erasure))
+            .route("/export", web::post().to(MeController::request_export))
+            .route("/export", web::get().to(MeController::export_status))
+            .route("/locale", web::get().to(MeController::locale))
+            .route("/locale", web::put().to(MeController::update_locale))
+            .route(
+                "/notification-preferences",
+                web::get().to(MeController::notification_preferences),
+            )
+            .route(
+                "/notification-preferences",
+                web::put().to(MeController::update_notification_preferences),
+            )
+            .route("/settings", web::get().to(MeController::settings))
+            .route("/settings", web::put().to(MeController::update_settings))
+            .route("/blocks", web::get().to(MeController::blocks))
+            .route("/blocks/{user_id}", web::post().to(MeController::block))
+            .route("/blocks/{user_id}", web::delete().to(MeController::unblock)),
+    );
+
     // ============================================
     // Balance Routes (Protected - requires JWT)
     // ============================================
     cfg.service(
         web::scope("/api/v1/balance")
             .wrap(from_fn(middleware::auth::verify_jwt))
-            .route("/checkout", web::post().to(BalanceController::create_checkout_session)),
+            .route("/checkout", web::post().to(BalanceController::create_checkout_session))
+            .route("/transfers", web::get().to(BalanceTransferController::list))
+            .route("/transfers", web::post().to(BalanceTransferController::create)),
+    );
+
+    // ============================================
+    // Payments Routes (Protected - requires JWT)
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/payments")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("/history", web::get().to(PaymentsController::history)),
+    );
+
+    // ============================================
+    // Background Job Routes (Protected - requires JWT)
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/jobs")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("/{id}", web::get().to(JobController::show)),
     );
 
     // ============================================
@@ -188,6 +317,36 @@ pub fn register(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api/v1/games")
             .route("/config", web::get().to(game_config::get_config))
+            // Fairness and reliability stats (Public - no auth)
+            .route("/stats", web::get().to(game_stats::get_all_stats))
+            .route("/{game_type}/stats", web::get().to(game_stats::get_stats))
+            .route(
+                "/{game_type}/stats/{user_id}",
+                web::get().to(game_stats::get_player_stats),
+            )
+            // Room presets and constraints (Public - no auth)
+            .route(
+                "/{game_type}/room-presets",
+                web::get().to(game_room_preset::get_presets),
+            )
+            // Room search (Requires JWT - in-progress rooms are only shown to members)
+            .service(
+                web::resource("/rooms/search")
+                    .wrap(from_fn(middleware::auth::verify_jwt))
+                    .route(web::get().to(GameRoomSearchController::search)),
+            )
+            // Scheduled rooms, soonest first (Public - also served as an iCalendar feed)
+            .route(
+                "/rooms/upcoming",
+                web::get().to(GameRoomScheduleController::upcoming),
+            )
+            // Registration for scheduled rooms (Requires JWT)
+            .service(
+                web::resource("/rooms/{room_id}/registration")
+                    .wrap(from_fn(middleware::auth::verify_jwt))
+                    .route(web::post().to(GameRoomScheduleController::register))
+                    .route(web::delete().to(GameRoomScheduleController::unregister)),
+            )
             // Game History Routes (Requires JWT - wrapped individually)
             .service(
                 web::resource("/{game_type}/history")
@@ -201,6 +360,32 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             ),
     );
 
+    // ============================================
+    // Chat Channel Routes (Requires JWT)
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/chat/channels")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("", web::get().to(ChatChannelController::list))
+            .route("/{id}/join", web::post().to(ChatChannelController::join))
+            .route("/{id}/leave", web::post().to(ChatChannelController::leave))
+            .route("/{id}/read", web::post().to(ChatChannelController::mark_read))
+            .route("/{id}/messages", web::get().to(ChatChannelController::messages)),
+    );
+
+    // ============================================
+    // Direct Message Routes (Requires JWT)
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/chat/conversations")
+            .wrap(from_fn(middleware::auth::verify_jwt))
+            .route("", web::get().to(DirectMessageController::list))
+            .route(
+                "/{user_id}/messages",
+                web::get().to(DirectMessageController::messages),
+            ),
+    );
+
     // ============================================
     // Upload Downloads (Public files - no auth required)
     // ============================================
@@ -283,10 +468,37 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             .route("", web::get().to(ThemeController::get))
             .route("", web::put().to(ThemeController::update))
             .route("/branding", web::put().to(ThemeController::update_branding))
+            .route("/variables", web::get().to(ThemeController::variables))
             .route("/build", web::post().to(ThemeController::trigger_build))
+            .route("/reload", web::post().to(ThemeController::reload))
+            // Preview builds
+            .route("/previews", web::get().to(ThemeController::list_previews))
+            .route("/previews", web::post().to(ThemeController::create_preview))
+            .route(
+                "/previews/{token}/promote",
+                web::post().to(ThemeController::promote_preview),
+            )
+            .route(
+                "/previews/{token}",
+                web::delete().to(ThemeController::discard_preview),
+            )
             .route(
                 "/build/status",
                 web::get().to(ThemeController::build_status),
+            )
+            // Per-tenant overrides
+            .route("/tenants", web::get().to(TenantThemeController::list))
+            .route(
+                "/tenants/{tenant_key}",
+                web::get().to(TenantThemeController::get),
+            )
+            .route(
+                "/tenants/{tenant_key}",
+                web::put().to(TenantThemeController::update),
+            )
+            .route(
+                "/tenants/{tenant_key}",
+                web::delete().to(TenantThemeController::reset),
             ),
     );
 
@@ -428,6 +640,48 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             ),
     );
 
+    // ============================================
+    // Tournaments (Public + Protected)
+    // ============================================
+    cfg.service(
+        web::scope("/api/v1/tournaments")
+            .route("", web::get().to(TournamentController::list))
+            .route("/{id}", web::get().to(TournamentController::show))
+            .route(
+                "/{id}/register",
+                web::post()
+                    .to(TournamentController::register)
+                    .wrap(from_fn(middleware::auth::verify_jwt)),
+            )
+            .route(
+                "/{id}/register",
+                web::delete()
+                    .to(TournamentController::unregister)
+                    .wrap(from_fn(middleware::auth::verify_jwt)),
+            )
+            .route(
+                "",
+                web::post()
+                    .to(TournamentController::create)
+                    .wrap(from_fn(require_permission(levels::ADMIN)))
+                    .wrap(from_fn(middleware::auth::verify_jwt)),
+            )
+            .route(
+                "/{id}/cancel",
+                web::post()
+                    .to(TournamentController::cancel)
+                    .wrap(from_fn(require_permission(levels::ADMIN)))
+                    .wrap(from_fn(middleware::auth::verify_jwt)),
+            )
+            .route(
+                "/{id}/matches/{match_id}/winner",
+                web::post()
+                    .to(TournamentController::decide_match)
+                    .wrap(from_fn(require_permission(levels::ADMIN)))
+                    .wrap(from_fn(middleware::auth::verify_jwt)),
+            ),
+    );
+
     // ============================================
     // OAuth-Protected Gallery Routes
     // ============================================
@@ -547,6 +801,135 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             ),
     );
 
+    // Feature Flag routes (Admin+ permission = 10 or 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/feature-flags")
+            .wrap(from_fn(require_permission(levels::ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("", web::get().to(FeatureFlagController::list))
+            .route("", web::post().to(FeatureFlagController::create))
+            .route("/{key}", web::patch().to(FeatureFlagController::update))
+            .route("/{key}", web::delete().to(FeatureFlagController::delete)),
+    );
+
+    // Game region, desync and webhook routes (Admin+ permission = 10 or 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/games")
+            .wrap(from_fn(require_permission(levels::ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("/regions", web::get().to(GameRegionController::list))
+            .route("/desyncs", web::get().to(AdminController::game_desyncs))
+            .route(
+                "/rooms/{room_id}/migrate",
+                web::post().to(GameRegionController::migrate_room),
+            )
+            .route("/webhooks", web::get().to(GameWebhookController::list))
+            .route("/webhooks", web::post().to(GameWebhookController::create))
+            .route("/webhooks/{id}", web::patch().to(GameWebhookController::update))
+            .route("/webhooks/{id}", web::delete().to(GameWebhookController::delete))
+            .route(
+                "/webhooks/{id}/rotate-secret",
+                web::post().to(GameWebhookController::rotate_secret),
+            )
+            .route(
+                "/webhooks/{id}/deliveries",
+                web::get().to(GameWebhookController::deliveries),
+            )
+            .route(
+                "/webhooks/deliveries/{id}/redeliver",
+                web::post().to(GameWebhookController::redeliver),
+            ),
+    );
+
+    // Analytics routes (Admin+ permission = 10 or 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/analytics")
+            .wrap(from_fn(require_permission(levels::ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("/games", web::get().to(AnalyticsController::games))
+            .route("/checkouts", web::get().to(AnalyticsController::checkouts)),
+    );
+
+    // Chat Channel admin routes (Admin+ permission = 10 or 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/chat/channels")
+            .wrap(from_fn(require_permission(levels::ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("", web::get().to(ChatChannelController::admin_list))
+            .route("", web::post().to(ChatChannelController::create))
+            .route("/{id}", web::patch().to(ChatChannelController::update))
+            .route("/{id}", web::delete().to(ChatChannelController::delete)),
+    );
+
+    // Maintenance mode routes (Super Admin permission = 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/maintenance")
+            .wrap(from_fn(require_permission(levels::SUPER_ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("", web::get().to(MaintenanceController::status))
+            .route("", web::put().to(MaintenanceController::enable))
+            .route("", web::delete().to(MaintenanceController::disable)),
+    );
+
+    // Balance adjustment routes (Super Admin permission = 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/balance-adjustments")
+            .wrap(from_fn(require_permission(levels::SUPER_ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("", web::get().to(BalanceAdjustmentController::list))
+            .route("", web::post().to(BalanceAdjustmentController::create))
+            .route(
+                "/{id}/approve",
+                web::post().to(BalanceAdjustmentController::approve),
+            )
+            .route(
+                "/{id}/reject",
+                web::post().to(BalanceAdjustmentController::reject),
+            ),
+    );
+
+    // Role management (RBAC `roles` group) - must be registered before the
+    // /api/v1/admin/users scope, which would otherwise swallow /users/{id}/role
+    cfg.service(
+        web::resource("/api/v1/admin/roles")
+            .wrap(from_fn(require_group(RouteGroup::Roles))) // Runs second (checks role)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route(web::get().to(RoleController::list)),
+    );
+    cfg.service(
+        web::resource("/api/v1/admin/users/{id}/role")
+            .wrap(from_fn(require_group(RouteGroup::Roles))) // Runs second (checks role)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route(web::put().to(RoleController::assign)),
+    );
+
+    // Moderation routes (RBAC `moderation` group: moderators and admins)
+    cfg.service(
+        web::scope("/api/v1/admin/ws")
+            .wrap(from_fn(require_group(RouteGroup::Moderation))) // Runs second (checks role)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("/penalties", web::get().to(WsPenaltyController::list))
+            .route("/penalties/{user_id}", web::get().to(WsPenaltyController::show))
+            .route("/penalties/{user_id}", web::delete().to(WsPenaltyController::clear)),
+    );
+
+    // Impersonation routes (Admin permission = 10 or 100)
+    // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
+    cfg.service(
+        web::scope("/api/v1/admin/impersonate")
+            .wrap(from_fn(require_permission(levels::ADMIN))) // Runs second (checks permissions)
+            .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
+            .route("/sessions", web::get().to(ImpersonationController::list))
+            .route("/sessions/{id}", web::delete().to(ImpersonationController::end))
+            .route("/{user_id}", web::post().to(ImpersonationController::start)),
+    );
+
     // Super Admin routes (permission = 100) - must be registered before Admin routes
     // to ensure /users is matched before /users/{id}/avatar
     // NOTE: Actix middleware order is REVERSED - last .wrap() runs first!
@@ -557,7 +940,9 @@ pub fn register(cfg: &mut web::ServiceConfig) {
             .wrap(from_fn(middleware::auth::verify_jwt)) // Runs first (extracts permissions)
             .route("", web::get().to(AdminController::list_users))
             .route("/bulk", web::post().to(AdminController::bulk_user_actions))
+            .route("/erasures", web::get().to(AdminController::pending_erasures))
             .route("/{id}", web::delete().to(AdminController::delete_user))
+            .route("/{id}/balance", web::get().to(AdminController::user_balance_at))
             .route(
                 "/{id}/permissions",
                 web::patch().to(AdminController::update_user_permissions),
@@ -580,6 +965,19 @@ pub fn register(cfg: &mut web::ServiceConfig) {
                 web::patch().to(AdminController::update_upload_metadata),
             )
             .route("/assets", web::get().to(AdminController::list_assets))
+            .route("/cache/stats", web::get().to(AdminController::cache_stats))
+            .route("/kafka/consumer", web::get().to(AdminController::kafka_consumer))
+            .route("/kafka/producer", web::get().to(AdminController::kafka_producer))
+            .route("/kafka/handlers", web::get().to(AdminController::kafka_handlers))
+            .route(
+                "/kafka/dead-letters",
+                web::get().to(AdminController::kafka_dead_letters),
+            )
+            .route(
+                "/kafka/dead-letters/replay",
+                web::post().to(AdminController::replay_dead_letter),
+            )
+            .route("/database/pools", web::get().to(AdminController::database_pools))
             .route("/geo-places", web::get().to(geo_place::list_admin))
             .route("/geo-places", web::post().to(geo_place::create_place))
             .route("/geo-places/{id}/images", web::post().to(geo_place::add_place_image))
@@ -748,6 +1146,20 @@ fn register_route_names() {
     route!("auth.sign_out", "/api/v1/auth/sign-out");
     route!("auth.sign_out_all", "/api/v1/auth/sign-out-all");
     route!("auth.refresh", "/api/v1/auth/refresh");
+    route!("auth.sessions", "/api/v1/auth/sessions");
+    route!("auth.sessions.revoke", "/api/v1/auth/sessions/{id}");
+    route!("auth.two_factor", "/api/v1/auth/two-factor");
+    route!("auth.two_factor.enroll", "/api/v1/auth/two-factor/enroll");
+    route!("auth.two_factor.confirm", "/api/v1/auth/two-factor/confirm");
+    route!(
+        "auth.two_factor.recovery_codes",
+        "/api/v1/auth/two-factor/recovery-codes"
+    );
+    route!("auth.sign_in.two_factor", "/api/v1/auth/sign-in/two-factor");
+    route!(
+        "auth.sign_in.two_factor.enroll",
+        "/api/v1/auth/sign-in/two-factor/enroll"
+    );
 
     // Account routes
     route!("account.activate", "/api/v1/account/activate-account");
@@ -769,15 +1181,32 @@ fn register_route_names() {
     // User routes
     route!("user.current", "/api/v1/user");
     route!("user.show", "/api/v1/user/{id}");
+    route!("user.achievements", "/api/v1/user/{id}/achievements");
     route!("user.update_full", "/api/v1/user");
     route!("user.update_partial", "/api/v1/user");
     route!("user.admin_create", "/api/v1/user");
     route!("user.avatar", "/api/v1/user/avatar");
     route!("user.delete", "/api/v1/user/{id}");
 
+    // Me routes
+    route!("me.bootstrap", "/api/v1/me/bootstrap");
+    route!("me.gaming_activity.export", "/api/v1/me/gaming-activity/export");
+    route!("me.delete", "/api/v1/me");
+    route!("me.erasure", "/api/v1/me/erasure");
+    route!("me.export", "/api/v1/me/export");
+    route!("data_exports.download", "/api/v1/data-exports/{id}/download");
+    route!("me.locale", "/api/v1/me/locale");
+    route!("me.notification_preferences", "/api/v1/me/notification-preferences");
+    route!("me.settings", "/api/v1/me/settings");
+    route!("me.blocks", "/api/v1/me/blocks");
+    route!("me.blocks.user", "/api/v1/me/blocks/{user_id}");
+
     // Balance routes
     route!("balance.checkout", "/api/v1/balance/checkout");
     route!("balance.checkout_kafka", "/api/v1/balance/checkout-kafka");
+    route!("balance.transfers", "/api/v1/balance/transfers");
+    route!("payments.history", "/api/v1/payments/history");
+    route!("jobs.show", "/api/v1/jobs/{id}");
 
     // Roulette routes
     route!("roulette.place_bet", "/api/v1/roulette/place-bet");
@@ -789,6 +1218,25 @@ fn register_route_names() {
 
     // Games routes
     route!("games.config", "/api/v1/games/config");
+    route!("games.stats", "/api/v1/games/stats");
+    route!("games.stats.type", "/api/v1/games/{game_type}/stats");
+    route!("games.stats.player", "/api/v1/games/{game_type}/stats/{user_id}");
+    route!("games.room_presets", "/api/v1/games/{game_type}/room-presets");
+    route!("games.rooms.search", "/api/v1/games/rooms/search");
+    route!("games.rooms.upcoming", "/api/v1/games/rooms/upcoming");
+    route!("games.rooms.registration", "/api/v1/games/rooms/{room_id}/registration");
+
+    // Chat channel routes
+    route!("chat.channels", "/api/v1/chat/channels");
+    route!("chat.channels.join", "/api/v1/chat/channels/{id}/join");
+    route!("chat.channels.leave", "/api/v1/chat/channels/{id}/leave");
+    route!("chat.channels.read", "/api/v1/chat/channels/{id}/read");
+    route!("chat.channels.messages", "/api/v1/chat/channels/{id}/messages");
+    route!("chat.conversations", "/api/v1/chat/conversations");
+    route!(
+        "chat.conversations.messages",
+        "/api/v1/chat/conversations/{user_id}/messages"
+    );
 
     // Gallery routes
     route!("galleries.list", "/api/v1/galleries");
@@ -840,6 +1288,18 @@ fn register_route_names() {
         "competitions.finalize",
         "/api/v1/competitions/{id}/finalize"
     );
+
+    // Tournaments
+    route!("tournaments.list", "/api/v1/tournaments");
+    route!("tournaments.create", "/api/v1/tournaments");
+    route!("tournaments.show", "/api/v1/tournaments/{id}");
+    route!("tournaments.register", "/api/v1/tournaments/{id}/register");
+    route!("tournaments.unregister", "/api/v1/tournaments/{id}/register");
+    route!("tournaments.cancel", "/api/v1/tournaments/{id}/cancel");
+    route!(
+        "tournaments.matches.winner",
+        "/api/v1/tournaments/{id}/matches/{match_id}/winner"
+    );
     route!(
         "pictures.remove",
         "/api/v1/galleries/{gallery_id}/pictures/{picture_id}"
@@ -890,9 +1350,70 @@ fn register_route_names() {
         "/api/v1/admin/uploads/{uuid}/metadata"
     );
     route!("admin.assets", "/api/v1/admin/assets");
+    route!("admin.cache.stats", "/api/v1/admin/cache/stats");
+    route!("admin.kafka.consumer", "/api/v1/admin/kafka/consumer");
+    route!("admin.kafka.producer", "/api/v1/admin/kafka/producer");
+    route!("admin.kafka.handlers", "/api/v1/admin/kafka/handlers");
+    route!("admin.kafka.dead_letters", "/api/v1/admin/kafka/dead-letters");
+    route!(
+        "admin.kafka.dead_letters.replay",
+        "/api/v1/admin/kafka/dead-letters/replay"
+    );
+    route!("admin.database.pools", "/api/v1/admin/database/pools");
+    route!("admin.ws.penalties", "/api/v1/admin/ws/penalties");
+    route!("admin.ws.penalties.user", "/api/v1/admin/ws/penalties/{user_id}");
+    route!("admin.roles", "/api/v1/admin/roles");
+    route!("admin.users.role", "/api/v1/admin/users/{id}/role");
+    route!("admin.maintenance", "/api/v1/admin/maintenance");
+    route!("admin.feature_flags", "/api/v1/admin/feature-flags");
+    route!("admin.feature_flags.update", "/api/v1/admin/feature-flags/{key}");
+    route!("admin.feature_flags.delete", "/api/v1/admin/feature-flags/{key}");
+    route!("admin.games.regions", "/api/v1/admin/games/regions");
+    route!("admin.games.desyncs", "/api/v1/admin/games/desyncs");
+    route!(
+        "admin.games.rooms.migrate",
+        "/api/v1/admin/games/rooms/{room_id}/migrate"
+    );
+    route!("admin.games.webhooks", "/api/v1/admin/games/webhooks");
+    route!("admin.games.webhooks.update", "/api/v1/admin/games/webhooks/{id}");
+    route!("admin.games.webhooks.delete", "/api/v1/admin/games/webhooks/{id}");
+    route!(
+        "admin.games.webhooks.rotate_secret",
+        "/api/v1/admin/games/webhooks/{id}/rotate-secret"
+    );
+    route!(
+        "admin.games.webhooks.deliveries",
+        "/api/v1/admin/games/webhooks/{id}/deliveries"
+    );
+    route!(
+        "admin.games.webhooks.redeliver",
+        "/api/v1/admin/games/webhooks/deliveries/{id}/redeliver"
+    );
+    route!("admin.analytics.games", "/api/v1/admin/analytics/games");
+    route!("admin.analytics.checkouts", "/api/v1/admin/analytics/checkouts");
+    route!("admin.chat_channels", "/api/v1/admin/chat/channels");
+    route!("admin.chat_channels.update", "/api/v1/admin/chat/channels/{id}");
+    route!("admin.chat_channels.delete", "/api/v1/admin/chat/channels/{id}");
+    route!("admin.balance_adjustments", "/api/v1/admin/balance-adjustments");
+    route!(
+        "admin.balance_adjustments.approve",
+        "/api/v1/admin/balance-adjustments/{id}/approve"
+    );
+    route!(
+        "admin.balance_adjustments.reject",
+        "/api/v1/admin/balance-adjustments/{id}/reject"
+    );
+    route!("admin.impersonate", "/api/v1/admin/impersonate/{user_id}");
+    route!("admin.impersonate.sessions", "/api/v1/admin/impersonate/sessions");
+    route!(
+        "admin.impersonate.sessions.end",
+        "/api/v1/admin/impersonate/sessions/{id}"
+    );
     route!("admin.users", "/api/v1/admin/users");
     route!("admin.users.bulk", "/api/v1/admin/users/bulk");
+    route!("admin.users.erasures", "/api/v1/admin/users/erasures");
     route!("admin.delete_user", "/api/v1/admin/users/{id}");
+    route!("admin.users.balance", "/api/v1/admin/users/{id}/balance");
     route!(
         "admin.delete_user_avatar",
         "/api/v1/admin/users/{id}/avatar"
@@ -906,11 +1427,28 @@ fn register_route_names() {
     route!("admin.theme", "/api/v1/admin/theme");
     route!("admin.theme.update", "/api/v1/admin/theme");
     route!("admin.theme.branding", "/api/v1/admin/theme/branding");
+    route!("admin.theme.variables", "/api/v1/admin/theme/variables");
     route!("admin.theme.build", "/api/v1/admin/theme/build");
+    route!("admin.theme.reload", "/api/v1/admin/theme/reload");
+    route!("admin.theme.previews", "/api/v1/admin/theme/previews");
+    route!(
+        "admin.theme.preview.promote",
+        "/api/v1/admin/theme/previews/{token}/promote"
+    );
+    route!(
+        "admin.theme.preview",
+        "/api/v1/admin/theme/previews/{token}"
+    );
     route!(
         "admin.theme.build_status",
         "/api/v1/admin/theme/build/status"
     );
+    route!("admin.theme.tenants", "/api/v1/admin/theme/tenants");
+    route!(
+        "admin.theme.tenant",
+        "/api/v1/admin/theme/tenants/{tenant_key}"
+    );
+    route!("theme.tenant.stylesheets", "/api/v1/theme/tenants/{tenant_key}");
 
     // SEO routes (Admin+ permission)
     route!("admin.seo.list", "/api/v1/admin/seo");
@@ -27,7 +27,10 @@
 //! ```
 //!
 //!
-use crate::app::cron::{list_user_emails, user_counter};
+use crate::app::cron::{
+    balance_snapshots, data_exports, game_room_retention, game_type_stats, game_webhooks,
+    list_user_emails, prediction_refunds, theme_previews, user_counter, user_erasure,
+};
 use crate::bootstrap::routes::controller::crons::{schedules, Schedule};
 use crate::config::CronConfig;
 use sqlx::{Pool, Postgres};
@@ -61,6 +64,80 @@ pub async fn register(scheduler: &JobScheduler, db: Pool<Postgres>) {
         error!("Failed to register list_user_emails: {}", e);
     }
 
+    // Game room retention - purges expired room tombstones every 5 minutes
+    if let Err(e) = Schedule::job("game_room_retention", game_room_retention::run)
+        .cron(schedules::EVERY_FIVE_MINUTES)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register game_room_retention: {}", e);
+    }
+
+    // Prediction refunds - refunds escrowed predictions of games that ended
+    // without settling them, every 5 minutes
+    if let Err(e) = Schedule::job("prediction_refunds", prediction_refunds::run)
+        .cron(schedules::EVERY_FIVE_MINUTES)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register prediction_refunds: {}", e);
+    }
+
+    // Game webhooks - queues due room lifecycle webhook deliveries every 10 seconds
+    if let Err(e) = Schedule::job("game_webhooks", game_webhooks::run)
+        .cron(schedules::EVERY_TEN_SECONDS)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register game_webhooks: {}", e);
+    }
+
+    // Game type stats - recomputes public per-game stats every hour
+    if let Err(e) = Schedule::job("game_type_stats", game_type_stats::run)
+        .cron(schedules::HOURLY)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register game_type_stats: {}", e);
+    }
+
+    // User erasure - queues GDPR erasure of accounts past their grace period, hourly
+    if let Err(e) = Schedule::job("user_erasure", user_erasure::run)
+        .cron(schedules::HOURLY)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register user_erasure: {}", e);
+    }
+
+    // Data exports - deletes archives past DATA_EXPORT_TTL_HOURS, hourly
+    if let Err(e) = Schedule::job("data_exports", data_exports::run)
+        .cron(schedules::HOURLY)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register data_exports: {}", e);
+    }
+
+    // Theme previews - removes previews past THEME_PREVIEW_TTL, hourly
+    if let Err(e) = Schedule::job("theme_previews", theme_previews::run)
+        .cron(schedules::HOURLY)
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register theme_previews: {}", e);
+    }
+
+    // Balance snapshots - end-of-day balances for historical balance queries,
+    // daily at 00:15 so late ledger writes of the previous day are included
+    if let Err(e) = Schedule::job("balance_snapshots", balance_snapshots::run)
+        .daily_at("00:15")
+        .register(scheduler, db.clone())
+        .await
+    {
+        error!("Failed to register balance_snapshots: {}", e);
+    }
+
     // =========================================================================
     // Add more cron jobs below:
     // =========================================================================
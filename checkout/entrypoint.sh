@@ -13,9 +13,11 @@ echo "Kafka is ready!"
 
 if [ "$BUILD_ENV" = "dev" ]; then
     echo "Starting in DEVELOPMENT mode with hot reload..."
-    exec cargo watch --poll -x "run --bin checkout"
+    # CARGO_FEATURES=chaos turns on fault injection for integration tests
+    exec cargo watch --poll -x "run --bin checkout ${CARGO_FEATURES:+--features $CARGO_FEATURES}"
 else
     echo "Starting in PRODUCTION mode..."
     cargo build --release --bin checkout
+    ./target/release/checkout --migrate-only
     exec ./target/release/checkout
 fi
@@ -0,0 +1,3 @@
+-- Keyset pagination of a user's transactions (internal payments history API)
+CREATE INDEX IF NOT EXISTS idx_checkout_transactions_user_created
+    ON checkout_transactions(user_id, created_at DESC);
@@ -0,0 +1,6 @@
+-- Keyset pagination orders by (created_at, id); include id so ties on
+-- created_at are resolved from the index as well
+CREATE INDEX IF NOT EXISTS idx_checkout_transactions_user_created_id
+    ON checkout_transactions(user_id, created_at DESC, id DESC);
+
+DROP INDEX IF EXISTS idx_checkout_transactions_user_created;
@@ -0,0 +1,29 @@
+-- Nightly Stripe reconciliation: one row per run, one row per session whose
+-- Stripe outcome did not match checkout_transactions
+CREATE TABLE IF NOT EXISTS checkout_reconciliation_runs (
+    id BIGSERIAL PRIMARY KEY,
+    window_start TIMESTAMPTZ NOT NULL,
+    sessions_checked INTEGER NOT NULL DEFAULT 0,
+    repaired INTEGER NOT NULL DEFAULT 0,
+    discrepancies INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS checkout_reconciliation_discrepancies (
+    id BIGSERIAL PRIMARY KEY,
+    run_id BIGINT NOT NULL REFERENCES checkout_reconciliation_runs(id) ON DELETE CASCADE,
+    stripe_session_id VARCHAR(255) NOT NULL,
+    request_id TEXT,
+    kind VARCHAR(32) NOT NULL,
+    stripe_status VARCHAR(32) NOT NULL,
+    local_status VARCHAR(32),
+    stripe_amount_cents BIGINT,
+    local_amount_cents BIGINT,
+    repaired BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkout_reconciliation_discrepancies_run_id
+    ON checkout_reconciliation_discrepancies(run_id);
@@ -0,0 +1,6 @@
+-- Stripe Customer of each user, so saved cards are offered on later checkouts
+CREATE TABLE IF NOT EXISTS checkout_customers (
+    user_id BIGINT PRIMARY KEY,
+    stripe_customer_id VARCHAR(255) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,34 @@
+-- Promo codes applied to checkout sessions as Stripe discounts, and one row
+-- per paid checkout that used one
+CREATE TABLE IF NOT EXISTS checkout_coupons (
+    id BIGSERIAL PRIMARY KEY,
+    code VARCHAR(32) NOT NULL UNIQUE,
+    description TEXT,
+    percent_off INTEGER CHECK (percent_off BETWEEN 1 AND 100),
+    amount_off_cents BIGINT CHECK (amount_off_cents > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'eur',
+    max_redemptions INTEGER CHECK (max_redemptions > 0),
+    max_redemptions_per_user INTEGER CHECK (max_redemptions_per_user > 0),
+    starts_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    stripe_coupon_id VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((percent_off IS NULL) <> (amount_off_cents IS NULL))
+);
+
+-- coupon_id is cleared when a coupon is deleted; the code is kept for reporting
+CREATE TABLE IF NOT EXISTS checkout_coupon_redemptions (
+    id BIGSERIAL PRIMARY KEY,
+    coupon_id BIGINT REFERENCES checkout_coupons(id) ON DELETE SET NULL,
+    code VARCHAR(32) NOT NULL,
+    user_id BIGINT NOT NULL,
+    request_id TEXT NOT NULL UNIQUE,
+    amount_cents BIGINT NOT NULL,
+    discount_cents BIGINT NOT NULL,
+    redeemed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkout_coupon_redemptions_coupon_user
+    ON checkout_coupon_redemptions(coupon_id, user_id);
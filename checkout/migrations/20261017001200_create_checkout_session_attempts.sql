@@ -0,0 +1,16 @@
+-- Checkout sessions opened per user, for the sessions-per-hour velocity limit.
+-- Rows older than a day are no longer read and may be purged.
+CREATE TABLE IF NOT EXISTS checkout_session_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    purpose VARCHAR(100) NOT NULL,
+    amount_cents BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkout_session_attempts_user_created
+    ON checkout_session_attempts(user_id, created_at);
+
+-- Daily volume caps sum a user's recent paid transactions
+CREATE INDEX IF NOT EXISTS idx_checkout_transactions_user_status_created
+    ON checkout_transactions(user_id, status, created_at);
@@ -0,0 +1,8 @@
+-- When the Stripe session behind a transaction stops accepting payment.
+-- The sweeper closes open sessions past this point as `session_expired`.
+ALTER TABLE checkout_transactions
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_checkout_transactions_open_expires
+    ON checkout_transactions(expires_at)
+    WHERE status = 'session_created';
@@ -0,0 +1,27 @@
+-- Stripe disputes (chargebacks) against paid checkouts. A row is written when
+-- `charge.dispute.created` arrives and closed by `charge.dispute.closed`;
+-- hold_cents is the coin amount rust-app freezes while the dispute is open.
+CREATE TABLE IF NOT EXISTS checkout_disputes (
+    id BIGSERIAL PRIMARY KEY,
+    dispute_id VARCHAR(255) NOT NULL UNIQUE,
+    request_id TEXT NOT NULL REFERENCES checkout_transactions(request_id),
+    user_id BIGINT NOT NULL,
+    payment_intent_id VARCHAR(255),
+    charge_id VARCHAR(255),
+    amount_cents BIGINT NOT NULL,
+    currency VARCHAR(10) NOT NULL,
+    hold_cents BIGINT NOT NULL CHECK (hold_cents >= 0),
+    reason VARCHAR(64) NOT NULL,
+    -- Last status Stripe reported (needs_response, under_review, won, lost, ...)
+    stripe_status VARCHAR(32) NOT NULL,
+    outcome VARCHAR(8) NOT NULL DEFAULT 'open' CHECK (outcome IN ('open', 'won', 'lost')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkout_disputes_request_id ON checkout_disputes (request_id);
+
+-- Disputes find their checkout by payment intent
+CREATE INDEX IF NOT EXISTS idx_checkout_transactions_payment_intent_id
+    ON checkout_transactions (payment_intent_id);
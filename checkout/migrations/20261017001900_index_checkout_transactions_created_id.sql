@@ -0,0 +1,4 @@
+-- Admin transaction search pages over every user's transactions by
+-- (created_at, id), optionally within a date range
+CREATE INDEX IF NOT EXISTS idx_checkout_transactions_created_id
+    ON checkout_transactions(created_at DESC, id DESC);
@@ -0,0 +1,24 @@
+-- Stripe subscriptions of the premium membership. A row is written by the
+-- first `invoice.paid` / `customer.subscription.*` webhook of a subscription
+-- and kept in step by the later ones; `entitled` is what rust-app was last
+-- told (SubscriptionActivated / SubscriptionLapsed on checkout.subscriptions).
+CREATE TABLE IF NOT EXISTS checkout_subscriptions (
+    id BIGSERIAL PRIMARY KEY,
+    subscription_id VARCHAR(255) NOT NULL UNIQUE,
+    user_id BIGINT NOT NULL,
+    customer_id VARCHAR(255) NOT NULL,
+    price_id VARCHAR(255),
+    -- Last status Stripe reported (incomplete, trialing, active, past_due, canceled, unpaid, ...)
+    stripe_status VARCHAR(32) NOT NULL,
+    current_period_end TIMESTAMPTZ,
+    cancel_at_period_end BOOLEAN NOT NULL DEFAULT FALSE,
+    entitled BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Stripe `created` of the last event applied; older deliveries are ignored
+    last_event_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    canceled_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkout_subscriptions_user_id
+    ON checkout_subscriptions (user_id, updated_at DESC);
@@ -8,6 +8,9 @@ pub struct JwtClaims {
     pub role: String,
     pub permissions: i16,
     pub exp: i64,
+    /// Preferred message locale from the user's profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 pub fn extract_token(req: &HttpRequest) -> Option<String> {
@@ -22,6 +25,14 @@ pub fn extract_token(req: &HttpRequest) -> Option<String> {
     req.cookie("auth_token").map(|cookie| cookie.value().to_string())
 }
 
+/// Service token sent by other backend services on internal endpoints
+pub fn extract_service_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(service_auth::HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
 pub fn decode_token(
     token: &str,
     secret: &str,
@@ -33,7 +44,7 @@ pub fn decode_token(
 
 #[cfg(test)]
 mod tests {
-    use super::{decode_token, extract_token, JwtClaims};
+    use super::{decode_token, extract_service_token, extract_token, JwtClaims};
     use actix_web::cookie::Cookie;
     use actix_web::test::TestRequest;
     use jsonwebtoken::{encode, EncodingKey, Header};
@@ -57,6 +68,20 @@ mod tests {
         assert_eq!(extract_token(&req), Some("cookie_token".to_string()));
     }
 
+    #[test]
+    fn extract_service_token_reads_header_only() {
+        let req = TestRequest::default()
+            .insert_header(("X-Service-Token", "service_token"))
+            .cookie(Cookie::new("auth_token", "cookie_token"))
+            .to_http_request();
+        assert_eq!(extract_service_token(&req), Some("service_token".to_string()));
+
+        let req = TestRequest::default()
+            .insert_header(("Authorization", "Bearer header_token"))
+            .to_http_request();
+        assert_eq!(extract_service_token(&req), None);
+    }
+
     #[test]
     fn decode_token_reads_claims() {
         let secret = "test_secret";
@@ -65,6 +90,7 @@ mod tests {
             role: "user".to_string(),
             permissions: 1,
             exp: 9999999999,
+            locale: Some("sr".to_string()),
         };
 
         let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
@@ -73,5 +99,6 @@ mod tests {
 
         assert_eq!(decoded.sub, 42);
         assert_eq!(decoded.permissions, 1);
+        assert_eq!(decoded.locale.as_deref(), Some("sr"));
     }
 }
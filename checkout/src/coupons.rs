@@ -0,0 +1,476 @@
+//! Coupons (promo codes)
+//!
+//! Admins manage coupons in `checkout_coupons` through `/admin/coupons`. A
+//! user enters a code with `POST /sessions`; the code is checked against the
+//! coupon's validity window and redemption limits, and the session is opened
+//! with a Stripe discount on its line item. The Stripe coupon is created on
+//! first use and remembered on the row.
+//!
+//! Redemptions are recorded in `checkout_coupon_redemptions` when Stripe
+//! reports the payment, so limits count paid checkouts only. The coupon code,
+//! id and discount travel in the session metadata and end up on the
+//! `checkout_finished` event.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::db;
+use crate::error::{CheckoutError, CheckoutResult};
+use crate::stripe;
+use crate::types::CheckoutCoupon;
+use crate::ServiceState;
+
+const STRIPE_COUPONS_PATH: &str = "/v1/coupons";
+
+/// Longest accepted code
+pub const MAX_CODE_LENGTH: usize = 32;
+
+/// Smallest amount Stripe charges in EUR; discounts may not go below it
+pub const MIN_CHARGE_CENTS: i64 = 50;
+
+/// A coupon row, with the number of paid checkouts that used it
+#[derive(Debug, Clone, Serialize)]
+pub struct Coupon {
+    pub id: i64,
+    pub code: String,
+    pub description: Option<String>,
+    pub percent_off: Option<i32>,
+    pub amount_off_cents: Option<i64>,
+    pub currency: String,
+    /// Total paid checkouts allowed (None = unlimited)
+    pub max_redemptions: Option<i32>,
+    /// Paid checkouts allowed per user (None = unlimited)
+    pub max_redemptions_per_user: Option<i32>,
+    pub redemptions: i64,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub active: bool,
+    pub stripe_coupon_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body of `POST /admin/coupons` and `PUT /admin/coupons/{id}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CouponRequest {
+    pub code: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub percent_off: Option<i32>,
+    #[serde(default)]
+    pub amount_off_cents: Option<i64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    pub max_redemptions: Option<i32>,
+    #[serde(default)]
+    pub max_redemptions_per_user: Option<i32>,
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_currency() -> String {
+    "eur".to_string()
+}
+
+fn default_active() -> bool {
+    true
+}
+
+/// Why a code cannot be used for a checkout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CouponRejection {
+    NotFound,
+    Inactive,
+    NotStarted,
+    Expired,
+    Exhausted,
+    AlreadyRedeemed,
+    Currency,
+    AmountTooLow,
+}
+
+impl CouponRejection {
+    /// `code` of the `coupon` field error
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::Inactive => "inactive",
+            Self::NotStarted => "not_started",
+            Self::Expired => "expired",
+            Self::Exhausted => "exhausted",
+            Self::AlreadyRedeemed => "already_redeemed",
+            Self::Currency => "currency",
+            Self::AmountTooLow => "amount_too_low",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::NotFound => "Coupon not found",
+            Self::Inactive => "Coupon is not active",
+            Self::NotStarted => "Coupon is not valid yet",
+            Self::Expired => "Coupon has expired",
+            Self::Exhausted => "Coupon has been fully redeemed",
+            Self::AlreadyRedeemed => "You have already used this coupon",
+            Self::Currency => "Coupon is not valid for this currency",
+            Self::AmountTooLow => "Amount is too low for this coupon",
+        }
+    }
+}
+
+/// Codes are matched case-insensitively and stored uppercase
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_ascii_uppercase()
+}
+
+/// Letters, digits, `-` and `_`, at most `MAX_CODE_LENGTH` characters
+pub fn is_valid_code(code: &str) -> bool {
+    !code.is_empty()
+        && code.len() <= MAX_CODE_LENGTH
+        && code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl Coupon {
+    /// Amount taken off a checkout of `amount_cents`
+    pub fn discount_cents(&self, amount_cents: i64) -> i64 {
+        let discount = match (self.percent_off, self.amount_off_cents) {
+            (Some(percent), _) => (amount_cents * i64::from(percent) + 50) / 100,
+            (None, Some(amount_off)) => amount_off,
+            (None, None) => 0,
+        };
+        discount.clamp(0, amount_cents)
+    }
+
+    /// Whether the coupon can be used by a user who already paid
+    /// `user_redemptions` checkouts with it
+    pub fn check(
+        &self,
+        now: DateTime<Utc>,
+        amount_cents: i64,
+        currency: &str,
+        user_redemptions: i64,
+    ) -> Result<(), CouponRejection> {
+        if !self.active {
+            return Err(CouponRejection::Inactive);
+        }
+        if self.starts_at.is_some_and(|starts_at| now < starts_at) {
+            return Err(CouponRejection::NotStarted);
+        }
+        if self.expires_at.is_some_and(|expires_at| now >= expires_at) {
+            return Err(CouponRejection::Expired);
+        }
+        if self
+            .max_redemptions
+            .is_some_and(|max| self.redemptions >= i64::from(max))
+        {
+            return Err(CouponRejection::Exhausted);
+        }
+        if self
+            .max_redemptions_per_user
+            .is_some_and(|max| user_redemptions >= i64::from(max))
+        {
+            return Err(CouponRejection::AlreadyRedeemed);
+        }
+        if self.amount_off_cents.is_some() && !self.currency.eq_ignore_ascii_case(currency) {
+            return Err(CouponRejection::Currency);
+        }
+        if amount_cents - self.discount_cents(amount_cents) < MIN_CHARGE_CENTS {
+            return Err(CouponRejection::AmountTooLow);
+        }
+        Ok(())
+    }
+
+    /// Stripe coupon parameters; a Stripe coupon's discount cannot change, so
+    /// the idempotency key includes it
+    fn stripe_params(&self) -> (Vec<(String, String)>, String) {
+        let mut params = vec![
+            ("duration".to_string(), "once".to_string()),
+            ("name".to_string(), self.code.clone()),
+            ("metadata[coupon_id]".to_string(), self.id.to_string()),
+        ];
+        let terms = match (self.percent_off, self.amount_off_cents) {
+            (Some(percent), _) => {
+                params.push(("percent_off".to_string(), percent.to_string()));
+                format!("{}pct", percent)
+            }
+            (None, amount_off) => {
+                let amount_off = amount_off.unwrap_or_default();
+                params.push(("amount_off".to_string(), amount_off.to_string()));
+                params.push(("currency".to_string(), self.currency.clone()));
+                format!("{}{}", amount_off, self.currency)
+            }
+        };
+
+        (params, format!("checkout-coupon-{}-{}", self.id, terms))
+    }
+}
+
+/// Look a code up, check it for a checkout by `user_id` and make sure its
+/// Stripe coupon exists
+pub async fn apply(
+    state: &ServiceState,
+    code: &str,
+    user_id: i64,
+    amount_cents: i64,
+    currency: &str,
+) -> CheckoutResult<Result<CheckoutCoupon, CouponRejection>> {
+    let Some(coupon) = db::fetch_coupon_by_code(&state.db, &normalize_code(code)).await? else {
+        return Ok(Err(CouponRejection::NotFound));
+    };
+    let user_redemptions = db::count_coupon_redemptions(&state.db, coupon.id, user_id).await?;
+    if let Err(rejection) = coupon.check(Utc::now(), amount_cents, currency, user_redemptions) {
+        return Ok(Err(rejection));
+    }
+
+    let stripe_coupon_id = ensure_stripe_coupon(state, &coupon).await?;
+
+    Ok(Ok(CheckoutCoupon {
+        coupon_id: coupon.id,
+        discount_cents: coupon.discount_cents(amount_cents),
+        code: coupon.code,
+        stripe_coupon_id: Some(stripe_coupon_id),
+    }))
+}
+
+/// The coupon's Stripe coupon id, creating the Stripe coupon on first use
+pub async fn ensure_stripe_coupon(state: &ServiceState, coupon: &Coupon) -> CheckoutResult<String> {
+    if let Some(stripe_coupon_id) = &coupon.stripe_coupon_id {
+        return Ok(stripe_coupon_id.clone());
+    }
+
+    if state.stripe_secret.is_empty() {
+        return Err(CheckoutError::stripe_not_configured());
+    }
+
+    let (params, idempotency_key) = coupon.stripe_params();
+    let request = state
+        .http_client
+        .post(stripe::api_url(&state.stripe_api_base, STRIPE_COUPONS_PATH))
+        .bearer_auth(&state.stripe_secret)
+        .header("Idempotency-Key", idempotency_key)
+        .form(&params);
+    let response = stripe::ensure_success(stripe::send(request).await?).await?;
+    let created: Value = response
+        .json()
+        .await
+        .map_err(CheckoutError::StripeResponse)?;
+    let stripe_coupon_id = created
+        .get("id")
+        .and_then(Value::as_str)
+        .filter(|id| !id.is_empty())
+        .ok_or(CheckoutError::StripeMissingField { field: "id" })?
+        .to_string();
+
+    db::set_stripe_coupon_id(&state.db, coupon.id, &stripe_coupon_id).await?;
+    info!(coupon_id = %coupon.id, stripe_coupon_id = %stripe_coupon_id, "Stripe coupon created");
+
+    Ok(stripe_coupon_id)
+}
+
+/// Session parameters applying the discount and recording the coupon in the
+/// session metadata
+pub fn session_coupon_params(coupon: &CheckoutCoupon) -> Vec<(String, String)> {
+    let mut params = vec![
+        (
+            "metadata[coupon_id]".to_string(),
+            coupon.coupon_id.to_string(),
+        ),
+        ("metadata[coupon_code]".to_string(), coupon.code.clone()),
+        (
+            "metadata[discount_cents]".to_string(),
+            coupon.discount_cents.to_string(),
+        ),
+    ];
+    if let Some(stripe_coupon_id) = &coupon.stripe_coupon_id {
+        params.push(("discounts[0][coupon]".to_string(), stripe_coupon_id.clone()));
+    }
+    params
+}
+
+/// Coupon recorded in a Stripe session's metadata
+pub fn from_session(session: &Value) -> Option<CheckoutCoupon> {
+    let metadata = session.get("metadata")?;
+    let text = |key: &str| metadata.get(key).and_then(Value::as_str);
+
+    Some(CheckoutCoupon {
+        coupon_id: text("coupon_id")?.parse().ok()?,
+        code: text("coupon_code")?.to_string(),
+        discount_cents: text("discount_cents")?.parse().ok()?,
+        stripe_coupon_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn coupon() -> Coupon {
+        let now = Utc::now();
+        Coupon {
+            id: 3,
+            code: "SPRING20".to_string(),
+            description: None,
+            percent_off: Some(20),
+            amount_off_cents: None,
+            currency: "eur".to_string(),
+            max_redemptions: Some(100),
+            max_redemptions_per_user: Some(1),
+            redemptions: 0,
+            starts_at: None,
+            expires_at: Some(now + Duration::days(7)),
+            active: true,
+            stripe_coupon_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn codes_are_normalized_and_restricted() {
+        assert_eq!(normalize_code("  spring20 "), "SPRING20");
+        assert!(is_valid_code("SPRING-20_X"));
+        assert!(!is_valid_code(""));
+        assert!(!is_valid_code("SPRING 20"));
+        assert!(!is_valid_code(&"A".repeat(MAX_CODE_LENGTH + 1)));
+    }
+
+    #[test]
+    fn discounts_are_capped_at_the_amount() {
+        let percent = coupon();
+        assert_eq!(percent.discount_cents(1000), 200);
+        assert_eq!(percent.discount_cents(999), 200);
+
+        let fixed = Coupon {
+            percent_off: None,
+            amount_off_cents: Some(500),
+            ..coupon()
+        };
+        assert_eq!(fixed.discount_cents(2000), 500);
+        assert_eq!(fixed.discount_cents(300), 300);
+    }
+
+    #[test]
+    fn checks_follow_window_limits_and_amount() {
+        let now = Utc::now();
+        assert_eq!(coupon().check(now, 1000, "eur", 0), Ok(()));
+
+        let inactive = Coupon {
+            active: false,
+            ..coupon()
+        };
+        assert_eq!(
+            inactive.check(now, 1000, "eur", 0),
+            Err(CouponRejection::Inactive)
+        );
+
+        let later = Coupon {
+            starts_at: Some(now + Duration::hours(1)),
+            ..coupon()
+        };
+        assert_eq!(
+            later.check(now, 1000, "eur", 0),
+            Err(CouponRejection::NotStarted)
+        );
+
+        let expired = Coupon {
+            expires_at: Some(now),
+            ..coupon()
+        };
+        assert_eq!(
+            expired.check(now, 1000, "eur", 0),
+            Err(CouponRejection::Expired)
+        );
+
+        let used_up = Coupon {
+            redemptions: 100,
+            ..coupon()
+        };
+        assert_eq!(
+            used_up.check(now, 1000, "eur", 0),
+            Err(CouponRejection::Exhausted)
+        );
+
+        assert_eq!(
+            coupon().check(now, 1000, "eur", 1),
+            Err(CouponRejection::AlreadyRedeemed)
+        );
+
+        let fixed = Coupon {
+            percent_off: None,
+            amount_off_cents: Some(500),
+            ..coupon()
+        };
+        assert_eq!(
+            fixed.check(now, 1000, "usd", 0),
+            Err(CouponRejection::Currency)
+        );
+        assert_eq!(
+            fixed.check(now, 500, "eur", 0),
+            Err(CouponRejection::AmountTooLow)
+        );
+    }
+
+    #[test]
+    fn coupon_round_trips_through_session_metadata() {
+        let applied = CheckoutCoupon {
+            coupon_id: 3,
+            code: "SPRING20".to_string(),
+            discount_cents: 200,
+            stripe_coupon_id: Some("co_test_123".to_string()),
+        };
+        let params = session_coupon_params(&applied);
+        assert!(params.contains(&(
+            "discounts[0][coupon]".to_string(),
+            "co_test_123".to_string()
+        )));
+
+        let metadata: serde_json::Map<String, Value> = params
+            .iter()
+            .filter_map(|(key, value)| {
+                let key = key.strip_prefix("metadata[")?.strip_suffix(']')?;
+                Some((key.to_string(), json!(value)))
+            })
+            .collect();
+        let session = json!({ "metadata": metadata });
+
+        assert_eq!(
+            from_session(&session),
+            Some(CheckoutCoupon {
+                stripe_coupon_id: None,
+                ..applied
+            })
+        );
+        assert_eq!(
+            from_session(&json!({ "metadata": { "user_id": "7" } })),
+            None
+        );
+    }
+
+    #[test]
+    fn stripe_coupons_are_keyed_by_their_discount() {
+        let (params, key) = coupon().stripe_params();
+        assert!(params.contains(&("percent_off".to_string(), "20".to_string())));
+        assert_eq!(key, "checkout-coupon-3-20pct");
+
+        let fixed = Coupon {
+            percent_off: None,
+            amount_off_cents: Some(500),
+            ..coupon()
+        };
+        let (params, key) = fixed.stripe_params();
+        assert!(params.contains(&("currency".to_string(), "eur".to_string())));
+        assert_eq!(key, "checkout-coupon-3-500eur");
+    }
+}
@@ -0,0 +1,212 @@
+//! Stripe customers and saved payment methods
+//!
+//! Every user gets one Stripe Customer, created on first use and remembered in
+//! `checkout_customers`. Checkout sessions are opened for that customer so
+//! Stripe offers the cards saved earlier instead of asking for card details
+//! again. Cards can also be saved without paying through a SetupIntent
+//! (`POST /setup-intents`), and listed with `GET /payment-methods`.
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::info;
+
+use crate::db;
+use crate::error::{CheckoutError, CheckoutResult};
+use crate::stripe;
+use crate::ServiceState;
+
+const STRIPE_CUSTOMERS_PATH: &str = "/v1/customers";
+const STRIPE_PAYMENT_METHODS_PATH: &str = "/v1/payment_methods";
+const STRIPE_SETUP_INTENTS_PATH: &str = "/v1/setup_intents";
+const PAGE_SIZE: &str = "100";
+
+/// Card saved on a user's Stripe Customer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SavedCard {
+    pub id: String,
+    pub brand: String,
+    pub last4: String,
+    pub exp_month: i64,
+    pub exp_year: i64,
+}
+
+impl SavedCard {
+    /// Card details of a Stripe PaymentMethod object
+    pub fn of(payment_method: &Value) -> Option<Self> {
+        let card = payment_method.get("card")?;
+        let text = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+
+        Some(Self {
+            id: text(payment_method, "id")?,
+            brand: text(card, "brand").unwrap_or_default(),
+            last4: text(card, "last4")?,
+            exp_month: card.get("exp_month").and_then(Value::as_i64).unwrap_or_default(),
+            exp_year: card.get("exp_year").and_then(Value::as_i64).unwrap_or_default(),
+        })
+    }
+}
+
+/// SetupIntent the frontend confirms with Stripe.js to save a card
+#[derive(Debug, Serialize)]
+pub struct SetupIntent {
+    pub id: String,
+    pub client_secret: String,
+}
+
+/// Parameters that open a Checkout Session for a customer and let the user
+/// save the card they pay with
+pub fn session_customer_params(customer_id: &str) -> Vec<(String, String)> {
+    vec![
+        ("customer".to_string(), customer_id.to_string()),
+        (
+            "saved_payment_method_options[payment_method_save]".to_string(),
+            "enabled".to_string(),
+        ),
+    ]
+}
+
+async fn read_response(response: reqwest::Response) -> CheckoutResult<Value> {
+    stripe::ensure_success(response)
+        .await?
+        .json()
+        .await
+        .map_err(CheckoutError::StripeResponse)
+}
+
+fn required_field(object: &Value, field: &'static str) -> CheckoutResult<String> {
+    object
+        .get(field)
+        .and_then(Value::as_str)
+        .filter(|val| !val.is_empty())
+        .map(str::to_string)
+        .ok_or(CheckoutError::StripeMissingField { field })
+}
+
+/// The user's Stripe Customer id, creating the customer on first use
+pub async fn ensure_customer(
+    state: &ServiceState,
+    user_id: i64,
+    email: Option<&str>,
+) -> CheckoutResult<String> {
+    if let Some(customer_id) = db::fetch_customer_id(&state.db, user_id).await? {
+        return Ok(customer_id);
+    }
+
+    if state.stripe_secret.is_empty() {
+        return Err(CheckoutError::stripe_not_configured());
+    }
+
+    let mut params = vec![("metadata[user_id]".to_string(), user_id.to_string())];
+    if let Some(email) = email.filter(|val| !val.is_empty()) {
+        params.push(("email".to_string(), email.to_string()));
+    }
+
+    // The idempotency key makes concurrent first checkouts share one customer
+    let request = state
+        .http_client
+        .post(stripe::api_url(&state.stripe_api_base, STRIPE_CUSTOMERS_PATH))
+        .bearer_auth(&state.stripe_secret)
+        .header("Idempotency-Key", format!("checkout-customer-{}", user_id))
+        .form(&params);
+    let response = stripe::send(request).await?;
+
+    let customer = read_response(response).await?;
+    let created_id = required_field(&customer, "id")?;
+    let customer_id = db::insert_customer(&state.db, user_id, &created_id).await?;
+
+    info!(user_id = %user_id, customer_id = %customer_id, "Stripe customer created");
+
+    Ok(customer_id)
+}
+
+/// Cards saved on a customer
+pub async fn list_payment_methods(
+    state: &ServiceState,
+    customer_id: &str,
+) -> CheckoutResult<Vec<SavedCard>> {
+    if state.stripe_secret.is_empty() {
+        return Err(CheckoutError::stripe_not_configured());
+    }
+
+    let request = state
+        .http_client
+        .get(stripe::api_url(&state.stripe_api_base, STRIPE_PAYMENT_METHODS_PATH))
+        .bearer_auth(&state.stripe_secret)
+        .query(&[("customer", customer_id), ("type", "card"), ("limit", PAGE_SIZE)]);
+    let response = stripe::send(request).await?;
+
+    let page = read_response(response).await?;
+
+    Ok(page
+        .get("data")
+        .and_then(Value::as_array)
+        .map(|methods| methods.iter().filter_map(SavedCard::of).collect())
+        .unwrap_or_default())
+}
+
+/// Start saving a card for later checkouts without charging it
+pub async fn create_setup_intent(
+    state: &ServiceState,
+    user_id: i64,
+    customer_id: &str,
+) -> CheckoutResult<SetupIntent> {
+    if state.stripe_secret.is_empty() {
+        return Err(CheckoutError::stripe_not_configured());
+    }
+
+    let params = [
+        ("customer", customer_id.to_string()),
+        ("payment_method_types[0]", "card".to_string()),
+        ("usage", "on_session".to_string()),
+        ("metadata[user_id]", user_id.to_string()),
+    ];
+
+    let request = state
+        .http_client
+        .post(stripe::api_url(&state.stripe_api_base, STRIPE_SETUP_INTENTS_PATH))
+        .bearer_auth(&state.stripe_secret)
+        .form(&params);
+    let response = stripe::send(request).await?;
+
+    let intent = read_response(response).await?;
+
+    Ok(SetupIntent {
+        id: required_field(&intent, "id")?,
+        client_secret: required_field(&intent, "client_secret")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session_customer_params, SavedCard};
+    use serde_json::json;
+
+    #[test]
+    fn saved_cards_come_from_card_payment_methods() {
+        let card = json!({
+            "id": "pm_123",
+            "type": "card",
+            "card": { "brand": "visa", "last4": "4242", "exp_month": 12, "exp_year": 2030 }
+        });
+        assert_eq!(
+            SavedCard::of(&card),
+            Some(SavedCard {
+                id: "pm_123".to_string(),
+                brand: "visa".to_string(),
+                last4: "4242".to_string(),
+                exp_month: 12,
+                exp_year: 2030,
+            })
+        );
+
+        let sepa = json!({ "id": "pm_456", "type": "sepa_debit", "sepa_debit": { "last4": "3000" } });
+        assert_eq!(SavedCard::of(&sepa), None);
+    }
+
+    #[test]
+    fn sessions_are_opened_for_the_customer() {
+        let params = session_customer_params("cus_123");
+        assert!(params.contains(&("customer".to_string(), "cus_123".to_string())));
+        assert!(params.iter().all(|(key, _)| key != "customer_email"));
+    }
+}
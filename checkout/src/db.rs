@@ -1,13 +1,33 @@
 use chrono::{DateTime, Utc};
+use pagination::{Cursor, Direction};
 use serde::Serialize;
 use serde_json::Value;
-use sqlx::postgres::PgPoolOptions;
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::postgres::{PgPoolOptions, PgRow};
 use sqlx::types::Json;
 use sqlx::Row;
 use sqlx::PgPool;
+use std::collections::HashSet;
+
+use crate::coupons::{normalize_code, Coupon, CouponRequest};
+use crate::transaction_search::TransactionFilters;
+use crate::types::CheckoutCoupon;
+
+/// Versioned migrations embedded from ./migrations at compile time
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Whether an embedded migration has been applied to the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+}
 
 #[derive(Debug, Serialize)]
 pub struct CheckoutTransaction {
+    #[serde(skip)]
+    pub id: i64,
     pub request_id: String,
     pub user_id: i64,
     pub amount_cents: i64,
@@ -29,9 +49,41 @@ pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
         .await
 }
 
-pub async fn run_migrations(pool: &PgPool) -> Result<(), sqlx::Error> {
-    sqlx::migrate!("./migrations").run(pool).await?;
-    Ok(())
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await
+}
+
+/// Status of every embedded migration, oldest first. Read-only: unlike
+/// `run_migrations` this does not create the `_sqlx_migrations` table.
+pub async fn migration_status(pool: &PgPool) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+    let has_history: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+
+    let applied: HashSet<i64> = if has_history {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    Ok(status_for(&applied))
+}
+
+fn status_for(applied: &HashSet<i64>) -> Vec<MigrationStatus> {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied: applied.contains(&migration.version),
+        })
+        .collect()
 }
 
 pub async fn upsert_session_created(
@@ -43,8 +95,10 @@ pub async fn upsert_session_created(
     purpose: &str,
     session_id: &str,
     session_url: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
     metadata: &Value,
 ) -> Result<(), sqlx::Error> {
+    // A webhook that beat us here has already closed the row; keep its outcome
     sqlx::query(
         r#"
         INSERT INTO checkout_transactions (
@@ -56,9 +110,10 @@ pub async fn upsert_session_created(
             stripe_session_id,
             stripe_session_url,
             status,
-            metadata
+            metadata,
+            expires_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, 'session_created', $8)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'session_created', $8, $9)
         ON CONFLICT (request_id) DO UPDATE
         SET user_id = EXCLUDED.user_id,
             amount_cents = EXCLUDED.amount_cents,
@@ -68,8 +123,10 @@ pub async fn upsert_session_created(
             stripe_session_url = EXCLUDED.stripe_session_url,
             status = 'session_created',
             metadata = EXCLUDED.metadata,
+            expires_at = EXCLUDED.expires_at,
             error_message = NULL,
             updated_at = NOW()
+        WHERE checkout_transactions.status NOT IN ('payment_succeeded', 'payment_failed')
         "#,
     )
     .bind(request_id)
@@ -80,6 +137,7 @@ pub async fn upsert_session_created(
     .bind(session_id)
     .bind(session_url)
     .bind(Json(metadata.clone()))
+    .bind(expires_at)
     .execute(pool)
     .await?;
 
@@ -244,15 +302,247 @@ pub async fn mark_payment_failed(
     Ok(row.is_some())
 }
 
-pub async fn fetch_transactions_by_user(
+/// Where a user's checkout session stands, for `GET /sessions/{id}/status`
+#[derive(Debug, Clone)]
+pub struct SessionState {
+    pub request_id: String,
+    pub status: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// The user's transaction for a Stripe session (None when it is someone else's)
+pub async fn fetch_session_state(
+    pool: &PgPool,
+    session_id: &str,
+    user_id: i64,
+) -> Result<Option<SessionState>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT request_id, status, expires_at, completed_at
+        FROM checkout_transactions
+        WHERE stripe_session_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| {
+        Ok(SessionState {
+            request_id: row.try_get("request_id")?,
+            status: row.try_get("status")?,
+            expires_at: row.try_get("expires_at")?,
+            completed_at: row.try_get("completed_at")?,
+        })
+    })
+    .transpose()
+}
+
+/// An open session the sweeper closed as expired
+#[derive(Debug, Clone)]
+pub struct ExpiredSession {
+    pub request_id: String,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub purpose: String,
+    pub session_id: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Close up to `limit` open sessions whose `expires_at` is before `now` as
+/// `session_expired`. Concurrent sweepers skip each other's rows, and a payment
+/// webhook arriving later still wins (see `mark_payment_succeeded`).
+pub async fn expire_open_sessions(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ExpiredSession>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        UPDATE checkout_transactions
+        SET status = 'session_expired',
+            error_message = 'session_expired',
+            updated_at = NOW(),
+            completed_at = NOW()
+        WHERE id IN (
+            SELECT id
+            FROM checkout_transactions
+            WHERE status = 'session_created'
+              AND expires_at <= $1
+            ORDER BY expires_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING request_id, user_id, amount_cents, currency, purpose,
+                  stripe_session_id, expires_at
+        "#,
+    )
+    .bind(now)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(ExpiredSession {
+                request_id: row.try_get("request_id")?,
+                user_id: row.try_get("user_id")?,
+                amount_cents: row.try_get("amount_cents")?,
+                currency: row.try_get("currency")?,
+                purpose: row.try_get("purpose")?,
+                session_id: row.try_get("stripe_session_id")?,
+                expires_at: row.try_get("expires_at")?,
+            })
+        })
+        .collect()
+}
+
+/// One page of a user's transactions, newest first, using keyset pagination on
+/// `(created_at, id)` so deep pages cost the same as the first one.
+/// Returns up to `limit + 1` rows in query order; see `pagination::paginate`.
+pub async fn fetch_transactions_page(
+    pool: &PgPool,
+    user_id: i64,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<CheckoutTransaction>, sqlx::Error> {
+    let query = match cursor.map(|c| c.direction) {
+        None | Some(Direction::Next) => {
+            r#"
+            SELECT
+                id, request_id, user_id, amount_cents, currency, purpose, status,
+                stripe_session_id, payment_intent_id, error_message,
+                created_at, updated_at, completed_at
+            FROM checkout_transactions
+            WHERE user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR (created_at, id) < ($2, $3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4
+            "#
+        }
+        Some(Direction::Prev) => {
+            r#"
+            SELECT
+                id, request_id, user_id, amount_cents, currency, purpose, status,
+                stripe_session_id, payment_intent_id, error_message,
+                created_at, updated_at, completed_at
+            FROM checkout_transactions
+            WHERE user_id = $1
+              AND (created_at, id) > ($2, $3)
+            ORDER BY created_at ASC, id ASC
+            LIMIT $4
+            "#
+        }
+    };
+
+    let rows = sqlx::query(query)
+        .bind(user_id)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id).unwrap_or_default())
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(transaction_from_row).collect()
+}
+
+fn transaction_from_row(row: &PgRow) -> Result<CheckoutTransaction, sqlx::Error> {
+    Ok(CheckoutTransaction {
+        id: row.try_get("id")?,
+        request_id: row.try_get("request_id")?,
+        user_id: row.try_get("user_id")?,
+        amount_cents: row.try_get("amount_cents")?,
+        currency: row.try_get("currency")?,
+        purpose: row.try_get("purpose")?,
+        status: row.try_get("status")?,
+        checkout_id: row.try_get("stripe_session_id")?,
+        payment_intent_id: row.try_get("payment_intent_id")?,
+        error_message: row.try_get("error_message")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        completed_at: row.try_get("completed_at")?,
+    })
+}
+
+/// One keyset page of the transactions matching the admin search `filters`
+/// (any user), newest first like `fetch_transactions_page`. `limit + 1` rows
+/// are read so the caller can tell whether another page follows.
+pub async fn search_transactions(
+    pool: &PgPool,
+    filters: &TransactionFilters,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<CheckoutTransaction>, sqlx::Error> {
+    let query = match cursor.map(|c| c.direction) {
+        None | Some(Direction::Next) => {
+            r#"
+            SELECT
+                id, request_id, user_id, amount_cents, currency, purpose, status,
+                stripe_session_id, payment_intent_id, error_message,
+                created_at, updated_at, completed_at
+            FROM checkout_transactions
+            WHERE ($1::TEXT IS NULL OR stripe_session_id = $1)
+              AND ($2::TEXT IS NULL OR payment_intent_id = $2)
+              AND ($3::BIGINT IS NULL OR user_id = $3)
+              AND ($4::TEXT IS NULL OR status = $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR created_at < $6)
+              AND ($7::TIMESTAMPTZ IS NULL OR (created_at, id) < ($7, $8))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $9
+            "#
+        }
+        Some(Direction::Prev) => {
+            r#"
+            SELECT
+                id, request_id, user_id, amount_cents, currency, purpose, status,
+                stripe_session_id, payment_intent_id, error_message,
+                created_at, updated_at, completed_at
+            FROM checkout_transactions
+            WHERE ($1::TEXT IS NULL OR stripe_session_id = $1)
+              AND ($2::TEXT IS NULL OR payment_intent_id = $2)
+              AND ($3::BIGINT IS NULL OR user_id = $3)
+              AND ($4::TEXT IS NULL OR status = $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR created_at < $6)
+              AND (created_at, id) > ($7, $8)
+            ORDER BY created_at ASC, id ASC
+            LIMIT $9
+            "#
+        }
+    };
+
+    let rows = sqlx::query(query)
+        .bind(filters.session_id.as_deref())
+        .bind(filters.payment_intent_id.as_deref())
+        .bind(filters.user_id)
+        .bind(filters.status.as_deref())
+        .bind(filters.from)
+        .bind(filters.to)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id).unwrap_or_default())
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(transaction_from_row).collect()
+}
+
+/// Fetch a user's transactions created strictly before `before` (newest first).
+/// Used by the internal history API, which pages by timestamp instead of offset.
+pub async fn fetch_transactions_by_user_before(
     pool: &PgPool,
     user_id: i64,
+    before: Option<DateTime<Utc>>,
     limit: i64,
-    offset: i64,
 ) -> Result<Vec<CheckoutTransaction>, sqlx::Error> {
     let rows = sqlx::query(
         r#"
         SELECT
+            id,
             request_id,
             user_id,
             amount_cents,
@@ -267,35 +557,772 @@ pub async fn fetch_transactions_by_user(
             completed_at
         FROM checkout_transactions
         WHERE user_id = $1
+          AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
         ORDER BY created_at DESC
-        LIMIT $2 OFFSET $3
+        LIMIT $3
         "#,
     )
     .bind(user_id)
+    .bind(before)
     .bind(limit)
-    .bind(offset)
     .fetch_all(pool)
     .await?;
 
-    let mut transactions = Vec::with_capacity(rows.len());
-    for row in rows {
-        transactions.push(CheckoutTransaction {
+    rows.iter().map(transaction_from_row).collect()
+}
+
+/// Transactions for the given request ids (any user), used by reconciliation
+pub async fn fetch_transactions_by_request_ids(
+    pool: &PgPool,
+    request_ids: &[String],
+) -> Result<Vec<CheckoutTransaction>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            id,
+            request_id,
+            user_id,
+            amount_cents,
+            currency,
+            purpose,
+            status,
+            stripe_session_id,
+            payment_intent_id,
+            error_message,
+            created_at,
+            updated_at,
+            completed_at
+        FROM checkout_transactions
+        WHERE request_id = ANY($1)
+        "#,
+    )
+    .bind(request_ids)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(transaction_from_row).collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationRun {
+    pub id: i64,
+    pub window_start: DateTime<Utc>,
+    pub sessions_checked: i32,
+    pub repaired: i32,
+    pub discrepancies: i32,
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// A Stripe session whose outcome did not match `checkout_transactions`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationDiscrepancy {
+    pub stripe_session_id: String,
+    pub request_id: Option<String>,
+    pub kind: String,
+    pub stripe_status: String,
+    pub local_status: Option<String>,
+    pub stripe_amount_cents: Option<i64>,
+    pub local_amount_cents: Option<i64>,
+    pub repaired: bool,
+}
+
+pub async fn start_reconciliation_run(
+    pool: &PgPool,
+    window_start: DateTime<Utc>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO checkout_reconciliation_runs (window_start)
+        VALUES ($1)
+        RETURNING id
+        "#,
+    )
+    .bind(window_start)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn insert_reconciliation_discrepancy(
+    pool: &PgPool,
+    run_id: i64,
+    discrepancy: &ReconciliationDiscrepancy,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO checkout_reconciliation_discrepancies (
+            run_id,
+            stripe_session_id,
+            request_id,
+            kind,
+            stripe_status,
+            local_status,
+            stripe_amount_cents,
+            local_amount_cents,
+            repaired
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(run_id)
+    .bind(&discrepancy.stripe_session_id)
+    .bind(&discrepancy.request_id)
+    .bind(&discrepancy.kind)
+    .bind(&discrepancy.stripe_status)
+    .bind(&discrepancy.local_status)
+    .bind(discrepancy.stripe_amount_cents)
+    .bind(discrepancy.local_amount_cents)
+    .bind(discrepancy.repaired)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn finish_reconciliation_run(
+    pool: &PgPool,
+    run_id: i64,
+    sessions_checked: i32,
+    repaired: i32,
+    discrepancies: i32,
+    error_message: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE checkout_reconciliation_runs
+        SET sessions_checked = $2,
+            repaired = $3,
+            discrepancies = $4,
+            error_message = $5,
+            finished_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(run_id)
+    .bind(sessions_checked)
+    .bind(repaired)
+    .bind(discrepancies)
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A reconciliation run by id, or the most recent one when `run_id` is None
+pub async fn fetch_reconciliation_run(
+    pool: &PgPool,
+    run_id: Option<i64>,
+) -> Result<Option<ReconciliationRun>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            id,
+            window_start,
+            sessions_checked,
+            repaired,
+            discrepancies,
+            error_message,
+            started_at,
+            finished_at
+        FROM checkout_reconciliation_runs
+        WHERE $1::BIGINT IS NULL OR id = $1
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(run_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| {
+        Ok(ReconciliationRun {
+            id: row.try_get("id")?,
+            window_start: row.try_get("window_start")?,
+            sessions_checked: row.try_get("sessions_checked")?,
+            repaired: row.try_get("repaired")?,
+            discrepancies: row.try_get("discrepancies")?,
+            error_message: row.try_get("error_message")?,
+            started_at: row.try_get("started_at")?,
+            finished_at: row.try_get("finished_at")?,
+        })
+    })
+    .transpose()
+}
+
+pub async fn fetch_reconciliation_discrepancies(
+    pool: &PgPool,
+    run_id: i64,
+) -> Result<Vec<ReconciliationDiscrepancy>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            stripe_session_id,
+            request_id,
+            kind,
+            stripe_status,
+            local_status,
+            stripe_amount_cents,
+            local_amount_cents,
+            repaired
+        FROM checkout_reconciliation_discrepancies
+        WHERE run_id = $1
+        ORDER BY id
+        "#,
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(ReconciliationDiscrepancy {
+                stripe_session_id: row.try_get("stripe_session_id")?,
+                request_id: row.try_get("request_id")?,
+                kind: row.try_get("kind")?,
+                stripe_status: row.try_get("stripe_status")?,
+                local_status: row.try_get("local_status")?,
+                stripe_amount_cents: row.try_get("stripe_amount_cents")?,
+                local_amount_cents: row.try_get("local_amount_cents")?,
+                repaired: row.try_get("repaired")?,
+            })
+        })
+        .collect()
+}
+
+/// Stripe Customer id stored for a user
+pub async fn fetch_customer_id(pool: &PgPool, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT stripe_customer_id FROM checkout_customers WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Remember a user's Stripe Customer. When another request stored one first,
+/// that one is kept and returned.
+pub async fn insert_customer(
+    pool: &PgPool,
+    user_id: i64,
+    stripe_customer_id: &str,
+) -> Result<String, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO checkout_customers (user_id, stripe_customer_id)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET user_id = checkout_customers.user_id
+        RETURNING stripe_customer_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(stripe_customer_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Coupon columns plus the number of paid checkouts that used the coupon
+const COUPON_SELECT: &str = r#"
+    SELECT
+        c.*,
+        (SELECT COUNT(*) FROM checkout_coupon_redemptions r WHERE r.coupon_id = c.id) AS redemptions
+    FROM checkout_coupons c
+"#;
+
+fn coupon_from_row(row: &PgRow) -> Result<Coupon, sqlx::Error> {
+    Ok(Coupon {
+        id: row.try_get("id")?,
+        code: row.try_get("code")?,
+        description: row.try_get("description")?,
+        percent_off: row.try_get("percent_off")?,
+        amount_off_cents: row.try_get("amount_off_cents")?,
+        currency: row.try_get("currency")?,
+        max_redemptions: row.try_get("max_redemptions")?,
+        max_redemptions_per_user: row.try_get("max_redemptions_per_user")?,
+        redemptions: row.try_get("redemptions")?,
+        starts_at: row.try_get("starts_at")?,
+        expires_at: row.try_get("expires_at")?,
+        active: row.try_get("active")?,
+        stripe_coupon_id: row.try_get("stripe_coupon_id")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+/// All coupons, newest first
+pub async fn fetch_coupons(pool: &PgPool) -> Result<Vec<Coupon>, sqlx::Error> {
+    let rows = sqlx::query(&format!("{} ORDER BY c.id DESC", COUPON_SELECT))
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(coupon_from_row).collect()
+}
+
+pub async fn fetch_coupon(pool: &PgPool, id: i64) -> Result<Option<Coupon>, sqlx::Error> {
+    let row = sqlx::query(&format!("{} WHERE c.id = $1", COUPON_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(coupon_from_row).transpose()
+}
+
+/// Coupon by its normalized (uppercase) code
+pub async fn fetch_coupon_by_code(pool: &PgPool, code: &str) -> Result<Option<Coupon>, sqlx::Error> {
+    let row = sqlx::query(&format!("{} WHERE c.code = $1", COUPON_SELECT))
+        .bind(code)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(coupon_from_row).transpose()
+}
+
+pub async fn insert_coupon(pool: &PgPool, coupon: &CouponRequest) -> Result<Coupon, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO checkout_coupons (
+            code,
+            description,
+            percent_off,
+            amount_off_cents,
+            currency,
+            max_redemptions,
+            max_redemptions_per_user,
+            starts_at,
+            expires_at,
+            active
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING *, 0::BIGINT AS redemptions
+        "#,
+    )
+    .bind(normalize_code(&coupon.code))
+    .bind(&coupon.description)
+    .bind(coupon.percent_off)
+    .bind(coupon.amount_off_cents)
+    .bind(coupon.currency.to_ascii_lowercase())
+    .bind(coupon.max_redemptions)
+    .bind(coupon.max_redemptions_per_user)
+    .bind(coupon.starts_at)
+    .bind(coupon.expires_at)
+    .bind(coupon.active)
+    .fetch_one(pool)
+    .await?;
+
+    coupon_from_row(&row)
+}
+
+/// Replace a coupon's settings. Stripe coupons cannot change their discount,
+/// so a changed discount drops the Stripe coupon and the next checkout
+/// creates a new one.
+pub async fn update_coupon(
+    pool: &PgPool,
+    id: i64,
+    coupon: &CouponRequest,
+) -> Result<Option<Coupon>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        UPDATE checkout_coupons
+        SET
+            code = $2,
+            description = $3,
+            percent_off = $4,
+            amount_off_cents = $5,
+            currency = $6,
+            max_redemptions = $7,
+            max_redemptions_per_user = $8,
+            starts_at = $9,
+            expires_at = $10,
+            active = $11,
+            stripe_coupon_id = CASE
+                WHEN percent_off IS NOT DISTINCT FROM $4
+                    AND amount_off_cents IS NOT DISTINCT FROM $5
+                    AND currency = $6
+                THEN stripe_coupon_id
+                ELSE NULL
+            END,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *, (
+            SELECT COUNT(*) FROM checkout_coupon_redemptions r
+            WHERE r.coupon_id = checkout_coupons.id
+        ) AS redemptions
+        "#,
+    )
+    .bind(id)
+    .bind(normalize_code(&coupon.code))
+    .bind(&coupon.description)
+    .bind(coupon.percent_off)
+    .bind(coupon.amount_off_cents)
+    .bind(coupon.currency.to_ascii_lowercase())
+    .bind(coupon.max_redemptions)
+    .bind(coupon.max_redemptions_per_user)
+    .bind(coupon.starts_at)
+    .bind(coupon.expires_at)
+    .bind(coupon.active)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(coupon_from_row).transpose()
+}
+
+/// Delete a coupon; its redemptions keep the code. Returns whether it existed.
+pub async fn delete_coupon(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM checkout_coupons WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn set_stripe_coupon_id(
+    pool: &PgPool,
+    id: i64,
+    stripe_coupon_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE checkout_coupons SET stripe_coupon_id = $2 WHERE id = $1")
+        .bind(id)
+        .bind(stripe_coupon_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Paid checkouts of a user that used the coupon
+pub async fn count_coupon_redemptions(
+    pool: &PgPool,
+    coupon_id: i64,
+    user_id: i64,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM checkout_coupon_redemptions WHERE coupon_id = $1 AND user_id = $2",
+    )
+    .bind(coupon_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Record a paid checkout that used a coupon (the coupon may have been
+/// deleted since the session opened). Returns false when the checkout was
+/// already recorded (webhook retry).
+pub async fn record_coupon_redemption(
+    pool: &PgPool,
+    coupon: &CheckoutCoupon,
+    user_id: i64,
+    request_id: &str,
+    amount_cents: i64,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO checkout_coupon_redemptions (
+            coupon_id,
+            code,
+            user_id,
+            request_id,
+            amount_cents,
+            discount_cents
+        )
+        VALUES ((SELECT id FROM checkout_coupons WHERE id = $1), $2, $3, $4, $5, $6)
+        ON CONFLICT (request_id) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(coupon.coupon_id)
+    .bind(&coupon.code)
+    .bind(user_id)
+    .bind(request_id)
+    .bind(amount_cents)
+    .bind(coupon.discount_cents)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// A paid checkout a dispute was opened against
+#[derive(Debug, Clone)]
+pub struct DisputedCheckout {
+    pub request_id: String,
+    pub user_id: i64,
+    /// Coins credited
+    pub amount_cents: i64,
+    /// Coupon discount, 0 without one; the user paid `amount_cents - discount_cents`
+    pub discount_cents: i64,
+}
+
+/// The paid checkout of a Stripe payment intent
+pub async fn fetch_paid_checkout_by_payment_intent(
+    pool: &PgPool,
+    payment_intent_id: &str,
+) -> Result<Option<DisputedCheckout>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT t.request_id, t.user_id, t.amount_cents,
+               COALESCE(r.discount_cents, 0) AS discount_cents
+        FROM checkout_transactions t
+        LEFT JOIN checkout_coupon_redemptions r ON r.request_id = t.request_id
+        WHERE t.payment_intent_id = $1 AND t.status = 'payment_succeeded'
+        "#,
+    )
+    .bind(payment_intent_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| {
+        Ok(DisputedCheckout {
             request_id: row.try_get("request_id")?,
             user_id: row.try_get("user_id")?,
             amount_cents: row.try_get("amount_cents")?,
-            currency: row.try_get("currency")?,
-            purpose: row.try_get("purpose")?,
-            status: row.try_get("status")?,
-            checkout_id: row.try_get("stripe_session_id")?,
-            payment_intent_id: row.try_get("payment_intent_id")?,
-            error_message: row.try_get("error_message")?,
-            created_at: row.try_get("created_at")?,
-            updated_at: row.try_get("updated_at")?,
-            completed_at: row.try_get("completed_at")?,
-        });
-    }
+            discount_cents: row.try_get("discount_cents")?,
+        })
+    })
+    .transpose()
+}
+
+/// A row of `checkout_disputes`
+#[derive(Debug, Clone)]
+pub struct CheckoutDispute {
+    pub dispute_id: String,
+    pub request_id: String,
+    pub user_id: i64,
+    pub payment_intent_id: Option<String>,
+    pub charge_id: Option<String>,
+    /// Disputed amount, in the payment's currency
+    pub amount_cents: i64,
+    pub currency: String,
+    /// Coins frozen for the dispute
+    pub hold_cents: i64,
+    pub reason: String,
+    pub stripe_status: String,
+    /// "open", "won" or "lost"
+    pub outcome: String,
+}
 
-    Ok(transactions)
+fn dispute_from_row(row: &PgRow) -> Result<CheckoutDispute, sqlx::Error> {
+    Ok(CheckoutDispute {
+        dispute_id: row.try_get("dispute_id")?,
+        request_id: row.try_get("request_id")?,
+        user_id: row.try_get("user_id")?,
+        payment_intent_id: row.try_get("payment_intent_id")?,
+        charge_id: row.try_get("charge_id")?,
+        amount_cents: row.try_get("amount_cents")?,
+        currency: row.try_get("currency")?,
+        hold_cents: row.try_get("hold_cents")?,
+        reason: row.try_get("reason")?,
+        stripe_status: row.try_get("stripe_status")?,
+        outcome: row.try_get("outcome")?,
+    })
+}
+
+/// Record an open dispute. Returns false when it was already recorded
+/// (webhook retry).
+pub async fn insert_dispute(pool: &PgPool, dispute: &CheckoutDispute) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO checkout_disputes (
+            dispute_id,
+            request_id,
+            user_id,
+            payment_intent_id,
+            charge_id,
+            amount_cents,
+            currency,
+            hold_cents,
+            reason,
+            stripe_status
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (dispute_id) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(&dispute.dispute_id)
+    .bind(&dispute.request_id)
+    .bind(dispute.user_id)
+    .bind(&dispute.payment_intent_id)
+    .bind(&dispute.charge_id)
+    .bind(dispute.amount_cents)
+    .bind(&dispute.currency)
+    .bind(dispute.hold_cents)
+    .bind(&dispute.reason)
+    .bind(&dispute.stripe_status)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// Close an open dispute as "won" or "lost". None when it is unknown or was
+/// already closed (webhook retry).
+pub async fn close_dispute(
+    pool: &PgPool,
+    dispute_id: &str,
+    stripe_status: &str,
+    outcome: &str,
+) -> Result<Option<CheckoutDispute>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        UPDATE checkout_disputes
+        SET stripe_status = $2,
+            outcome = $3,
+            updated_at = NOW(),
+            closed_at = NOW()
+        WHERE dispute_id = $1 AND outcome = 'open'
+        RETURNING dispute_id, request_id, user_id, payment_intent_id, charge_id,
+                  amount_cents, currency, hold_cents, reason, stripe_status, outcome
+        "#,
+    )
+    .bind(dispute_id)
+    .bind(stripe_status)
+    .bind(outcome)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(dispute_from_row).transpose()
+}
+
+/// A row of `checkout_subscriptions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckoutSubscription {
+    pub subscription_id: String,
+    pub user_id: i64,
+    pub customer_id: String,
+    pub price_id: Option<String>,
+    pub stripe_status: String,
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub cancel_at_period_end: bool,
+    /// Whether the user has premium, as last published
+    pub entitled: bool,
+    /// Stripe time of the last event applied
+    pub last_event_at: DateTime<Utc>,
+}
+
+const SUBSCRIPTION_COLUMNS: &str = "subscription_id, user_id, customer_id, price_id, stripe_status, \
+     current_period_end, cancel_at_period_end, entitled, last_event_at";
+
+fn subscription_from_row(row: &PgRow) -> Result<CheckoutSubscription, sqlx::Error> {
+    Ok(CheckoutSubscription {
+        subscription_id: row.try_get("subscription_id")?,
+        user_id: row.try_get("user_id")?,
+        customer_id: row.try_get("customer_id")?,
+        price_id: row.try_get("price_id")?,
+        stripe_status: row.try_get("stripe_status")?,
+        current_period_end: row.try_get("current_period_end")?,
+        cancel_at_period_end: row.try_get("cancel_at_period_end")?,
+        entitled: row.try_get("entitled")?,
+        last_event_at: row.try_get("last_event_at")?,
+    })
+}
+
+/// User a Stripe Customer was created for
+pub async fn fetch_user_by_customer(
+    pool: &PgPool,
+    stripe_customer_id: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT user_id FROM checkout_customers WHERE stripe_customer_id = $1")
+        .bind(stripe_customer_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The user's subscription that grants premium, else the latest one
+pub async fn fetch_subscription_by_user(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Option<CheckoutSubscription>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        SELECT {SUBSCRIPTION_COLUMNS}
+        FROM checkout_subscriptions
+        WHERE user_id = $1
+        ORDER BY entitled DESC, updated_at DESC
+        LIMIT 1
+        "#
+    ))
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(subscription_from_row).transpose()
+}
+
+/// Store the next state of a subscription. `update` gets the stored row
+/// (None for a new subscription) and returns the row to write, or None to
+/// leave it as it is. Runs under a per-subscription advisory lock so webhook
+/// deliveries of one subscription are applied one after the other. Returns
+/// the previous and the written row when something was written.
+pub async fn update_subscription(
+    pool: &PgPool,
+    subscription_id: &str,
+    update: impl FnOnce(Option<&CheckoutSubscription>) -> Option<CheckoutSubscription>,
+) -> Result<Option<(Option<CheckoutSubscription>, CheckoutSubscription)>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(subscription_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let previous = sqlx::query(&format!(
+        "SELECT {SUBSCRIPTION_COLUMNS} FROM checkout_subscriptions WHERE subscription_id = $1"
+    ))
+    .bind(subscription_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .as_ref()
+    .map(subscription_from_row)
+    .transpose()?;
+
+    let Some(next) = update(previous.as_ref()) else {
+        tx.rollback().await?;
+        return Ok(None);
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO checkout_subscriptions (
+            subscription_id,
+            user_id,
+            customer_id,
+            price_id,
+            stripe_status,
+            current_period_end,
+            cancel_at_period_end,
+            entitled,
+            last_event_at,
+            canceled_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, CASE WHEN $5 = 'canceled' THEN NOW() END)
+        ON CONFLICT (subscription_id) DO UPDATE
+        SET user_id = EXCLUDED.user_id,
+            customer_id = EXCLUDED.customer_id,
+            price_id = EXCLUDED.price_id,
+            stripe_status = EXCLUDED.stripe_status,
+            current_period_end = EXCLUDED.current_period_end,
+            cancel_at_period_end = EXCLUDED.cancel_at_period_end,
+            entitled = EXCLUDED.entitled,
+            last_event_at = EXCLUDED.last_event_at,
+            canceled_at = COALESCE(checkout_subscriptions.canceled_at, EXCLUDED.canceled_at),
+            updated_at = NOW()
+        "#,
+    )
+    .bind(&next.subscription_id)
+    .bind(next.user_id)
+    .bind(&next.customer_id)
+    .bind(&next.price_id)
+    .bind(&next.stripe_status)
+    .bind(next.current_period_end)
+    .bind(next.cancel_at_period_end)
+    .bind(next.entitled)
+    .bind(next.last_event_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some((previous, next)))
 }
 
 /// Create a Bigger Dice participation transaction (deduction from balance for playing)
@@ -469,3 +1496,95 @@ pub async fn create_tic_tac_toe_prize_win(
 
     Ok(row.is_some())
 }
+
+/// Cents a user paid since `since` (game entries and other debits excluded)
+pub async fn sum_paid_cents_since(
+    pool: &PgPool,
+    user_id: i64,
+    since: DateTime<Utc>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(amount_cents), 0)::BIGINT
+        FROM checkout_transactions
+        WHERE user_id = $1
+          AND status = 'payment_succeeded'
+          AND amount_cents > 0
+          AND created_at >= $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+}
+
+/// Count the sessions a user opened since `since` and, when `admit` accepts
+/// the count, record a new one. Runs under a per-user advisory lock so
+/// concurrent sessions are counted one after the other.
+pub async fn record_session_attempt<E>(
+    pool: &PgPool,
+    user_id: i64,
+    purpose: &str,
+    amount_cents: i64,
+    since: DateTime<Utc>,
+    admit: impl FnOnce(i64) -> Result<(), E>,
+) -> Result<Result<(), E>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let recent: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM checkout_session_attempts WHERE user_id = $1 AND created_at >= $2",
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if let Err(rejection) = admit(recent) {
+        tx.rollback().await?;
+        return Ok(Err(rejection));
+    }
+
+    sqlx::query(
+        "INSERT INTO checkout_session_attempts (user_id, purpose, amount_cents) VALUES ($1, $2, $3)",
+    )
+    .bind(user_id)
+    .bind(purpose)
+    .bind(amount_cents)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_embedded_in_version_order() {
+        let status = status_for(&HashSet::new());
+        assert!(!status.is_empty());
+        assert!(status
+            .windows(2)
+            .all(|pair| pair[0].version < pair[1].version));
+        assert!(status.iter().all(|migration| !migration.applied));
+        assert_eq!(status[0].version, 20260115000000);
+        assert_eq!(status[0].description, "create checkout transactions");
+    }
+
+    #[test]
+    fn pending_migrations_are_the_unapplied_ones() {
+        let first = status_for(&HashSet::new())[0].version;
+        let status = status_for(&HashSet::from([first]));
+
+        assert!(status[0].applied);
+        assert!(status[1..].iter().all(|migration| !migration.applied));
+    }
+}
@@ -0,0 +1,273 @@
+//! Stripe disputes (chargebacks)
+//!
+//! `charge.dispute.created` records the dispute in `checkout_disputes`, linked
+//! to the checkout of the disputed payment intent, and publishes
+//! status="opened" on `checkout.disputes`: rust-app freezes the coins the
+//! payment credited (a `dispute_hold` ledger entry) so they cannot be spent
+//! while the bank decides. `charge.dispute.closed` publishes "won" (the coins
+//! are released) or "lost" (they are deducted for good).
+//!
+//! Both deliveries are idempotent: a dispute is recorded once and closed
+//! once. A dispute that closes before its opening was seen (e.g. opened
+//! before this service handled disputes) is recorded on the spot, so rust-app
+//! always gets "opened" before the outcome.
+
+use chrono::Utc;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::db::{self, CheckoutDispute};
+use crate::error::{CheckoutError, CheckoutResult};
+use crate::types::CheckoutDisputeEvent;
+use crate::ServiceState;
+
+/// The parts of a Stripe dispute object the webhook needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StripeDispute {
+    pub id: String,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub charge_id: Option<String>,
+    pub payment_intent_id: Option<String>,
+    pub reason: String,
+    pub status: String,
+}
+
+impl StripeDispute {
+    pub fn parse(object: &Value) -> Option<Self> {
+        let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+
+        Some(Self {
+            id: text("id").filter(|id| !id.is_empty())?,
+            amount_cents: object.get("amount").and_then(Value::as_i64)?,
+            currency: text("currency").unwrap_or_else(|| "eur".to_string()),
+            charge_id: text("charge"),
+            payment_intent_id: text("payment_intent"),
+            reason: text("reason").unwrap_or_else(|| "general".to_string()),
+            status: text("status").unwrap_or_default(),
+        })
+    }
+}
+
+/// How a closed dispute ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+impl Outcome {
+    /// Outcome of a closed dispute's Stripe status; `warning_closed` (an
+    /// inquiry that never became a chargeback) counts as won
+    pub fn of(stripe_status: &str) -> Option<Self> {
+        match stripe_status {
+            "won" | "warning_closed" => Some(Outcome::Won),
+            "lost" => Some(Outcome::Lost),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+        }
+    }
+}
+
+/// Coins to freeze for a dispute: the disputed share of what the payment
+/// credited, rounded up. `credited_cents` coins were paid with
+/// `credited_cents - discount_cents` cents.
+pub fn hold_cents(credited_cents: i64, discount_cents: i64, disputed_cents: i64) -> i64 {
+    let paid = credited_cents - discount_cents;
+    if paid <= 0 || disputed_cents >= paid {
+        return credited_cents;
+    }
+
+    let share = credited_cents as i128 * disputed_cents.max(0) as i128;
+    ((share + paid as i128 - 1) / paid as i128) as i64
+}
+
+/// Handle `charge.dispute.created` / `charge.dispute.closed`
+pub async fn apply(
+    state: &ServiceState,
+    event_type: &str,
+    event: &Value,
+) -> CheckoutResult<&'static str> {
+    let dispute = event
+        .get("data")
+        .and_then(|data| data.get("object"))
+        .and_then(StripeDispute::parse)
+        .ok_or(CheckoutError::Validation("Stripe dispute missing"))?;
+
+    if event_type == "charge.dispute.created" {
+        return Ok(match record(state, &dispute).await? {
+            Some(true) => "Dispute recorded",
+            Some(false) => "Dispute already recorded",
+            None => UNKNOWN_PAYMENT,
+        });
+    }
+
+    let Some(outcome) = Outcome::of(&dispute.status) else {
+        warn!(dispute_id = %dispute.id, status = %dispute.status, "Dispute closed with an unknown status");
+        return Ok("Dispute status ignored");
+    };
+
+    // Nothing to close when the disputed payment is not ours
+    if record(state, &dispute).await?.is_none() {
+        return Ok(UNKNOWN_PAYMENT);
+    }
+
+    let Some(closed) =
+        db::close_dispute(&state.db, &dispute.id, &dispute.status, outcome.as_str()).await?
+    else {
+        return Ok("Dispute already closed");
+    };
+
+    info!(
+        dispute_id = %closed.dispute_id,
+        request_id = %closed.request_id,
+        outcome = outcome.as_str(),
+        "Dispute closed"
+    );
+    publish(state, &closed, outcome.as_str()).await;
+
+    Ok("Dispute closed")
+}
+
+const UNKNOWN_PAYMENT: &str = "Dispute ignored (unknown payment)";
+
+/// Record the dispute and announce it, once. Some(true) when it was new, None
+/// when the disputed payment is not a paid checkout.
+async fn record(state: &ServiceState, dispute: &StripeDispute) -> CheckoutResult<Option<bool>> {
+    let payment_intent_id =
+        dispute
+            .payment_intent_id
+            .as_deref()
+            .ok_or(CheckoutError::Validation(
+                "Stripe dispute missing payment_intent",
+            ))?;
+
+    let Some(checkout) =
+        db::fetch_paid_checkout_by_payment_intent(&state.db, payment_intent_id).await?
+    else {
+        warn!(
+            dispute_id = %dispute.id,
+            payment_intent_id = %payment_intent_id,
+            "Dispute for a payment without a paid checkout"
+        );
+        return Ok(None);
+    };
+
+    let record = CheckoutDispute {
+        dispute_id: dispute.id.clone(),
+        request_id: checkout.request_id,
+        user_id: checkout.user_id,
+        payment_intent_id: dispute.payment_intent_id.clone(),
+        charge_id: dispute.charge_id.clone(),
+        amount_cents: dispute.amount_cents,
+        currency: dispute.currency.clone(),
+        hold_cents: hold_cents(
+            checkout.amount_cents,
+            checkout.discount_cents,
+            dispute.amount_cents,
+        ),
+        reason: dispute.reason.clone(),
+        stripe_status: dispute.status.clone(),
+        outcome: "open".to_string(),
+    };
+
+    if !db::insert_dispute(&state.db, &record).await? {
+        return Ok(Some(false));
+    }
+
+    info!(
+        dispute_id = %record.dispute_id,
+        request_id = %record.request_id,
+        hold_cents = record.hold_cents,
+        reason = %record.reason,
+        "Dispute opened"
+    );
+    publish(state, &record, "opened").await;
+
+    Ok(Some(true))
+}
+
+/// Publish on `checkout.disputes`; while Kafka is down the event is buffered
+async fn publish(state: &ServiceState, dispute: &CheckoutDispute, status: &str) {
+    let event = dispute_event(dispute, status);
+
+    if let Err(err) = state
+        .producer
+        .send_dispute_event(&event, Some(&dispute.request_id))
+        .await
+    {
+        warn!("Failed to publish checkout dispute event: {}", err);
+    }
+}
+
+fn dispute_event(dispute: &CheckoutDispute, status: &str) -> CheckoutDisputeEvent {
+    CheckoutDisputeEvent {
+        dispute_id: dispute.dispute_id.clone(),
+        request_id: dispute.request_id.clone(),
+        user_id: dispute.user_id,
+        status: status.to_string(),
+        amount_cents: dispute.amount_cents,
+        currency: dispute.currency.clone(),
+        hold_cents: dispute.hold_cents,
+        reason: dispute.reason.clone(),
+        payment_intent_id: dispute.payment_intent_id.clone(),
+        timestamp: Utc::now().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn dispute_objects_are_parsed() {
+        let object = json!({
+            "id": "dp_1",
+            "object": "dispute",
+            "amount": 500,
+            "currency": "eur",
+            "charge": "ch_1",
+            "payment_intent": "pi_1",
+            "reason": "fraudulent",
+            "status": "needs_response"
+        });
+
+        let dispute = StripeDispute::parse(&object).unwrap();
+        assert_eq!(dispute.id, "dp_1");
+        assert_eq!(dispute.amount_cents, 500);
+        assert_eq!(dispute.payment_intent_id.as_deref(), Some("pi_1"));
+        assert_eq!(dispute.reason, "fraudulent");
+
+        assert!(StripeDispute::parse(&json!({ "id": "dp_1" })).is_none());
+        assert!(StripeDispute::parse(&json!({ "amount": 500 })).is_none());
+    }
+
+    #[test]
+    fn closed_statuses_map_to_outcomes() {
+        assert_eq!(Outcome::of("won"), Some(Outcome::Won));
+        assert_eq!(Outcome::of("warning_closed"), Some(Outcome::Won));
+        assert_eq!(Outcome::of("lost"), Some(Outcome::Lost));
+        assert_eq!(Outcome::of("under_review"), None);
+    }
+
+    #[test]
+    fn holds_cover_the_disputed_share_of_the_credit() {
+        // Full dispute of a plain payment
+        assert_eq!(hold_cents(500, 0, 500), 500);
+        // Partial dispute
+        assert_eq!(hold_cents(1000, 0, 250), 250);
+        // 1000 coins paid with 800 cents; disputing 400 cents freezes half
+        assert_eq!(hold_cents(1000, 200, 400), 500);
+        // Rounded up, and never more than was credited
+        assert_eq!(hold_cents(1000, 700, 100), 334);
+        assert_eq!(hold_cents(1000, 200, 900), 1000);
+        assert_eq!(hold_cents(1000, 1000, 50), 1000);
+    }
+}
@@ -0,0 +1,203 @@
+//! Checkout domain errors
+//!
+//! Every fallible path (API handlers, the Stripe webhook and the Kafka
+//! consumer) returns a `CheckoutError`, so callers can branch on the cause:
+//! the HTTP status a handler answers with, the `error_code` carried by
+//! `checkout.finished` events and whether the consumer should expect a retry
+//! to succeed all come from the variant.
+
+use actix_web::http::StatusCode;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CheckoutError {
+    /// A required setting (Stripe secret, service auth keys) is missing
+    #[error("{setting} is not configured")]
+    Config { setting: &'static str },
+
+    #[error("Invalid service token: {0}")]
+    InvalidServiceToken(#[from] service_auth::VerifyError),
+
+    /// Input that will never be accepted as sent; the message is shown to the caller
+    #[error("{0}")]
+    Validation(&'static str),
+
+    #[error("Stripe request failed: {0}")]
+    StripeRequest(#[source] reqwest::Error),
+
+    /// Stripe answered with a non-success status
+    #[error("Stripe API error: {status} {body}")]
+    StripeApi {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+
+    #[error("Stripe response invalid: {0}")]
+    StripeResponse(#[source] reqwest::Error),
+
+    #[error("Stripe session URL missing for session {session_id}")]
+    MissingSessionUrl { session_id: String },
+
+    #[error("Stripe response missing {field}")]
+    StripeMissingField { field: &'static str },
+
+    #[error("Empty payload on topic {topic}")]
+    EmptyPayload { topic: String },
+
+    #[error("Invalid payload on topic {topic}: {source}")]
+    InvalidPayload {
+        topic: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to serialize event: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("Kafka error: {0}")]
+    Kafka(#[from] kafka_producer::ProducerError),
+
+    #[error("Kafka consumer error: {0}")]
+    KafkaConsumer(#[from] rdkafka::error::KafkaError),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+impl CheckoutError {
+    pub fn stripe_not_configured() -> Self {
+        Self::Config {
+            setting: "Stripe secret key",
+        }
+    }
+
+    /// HTTP status used when the error surfaces from an API handler.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Validation(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidServiceToken(_) => StatusCode::UNAUTHORIZED,
+            Self::StripeRequest(_)
+            | Self::StripeApi { .. }
+            | Self::StripeResponse(_)
+            | Self::MissingSessionUrl { .. }
+            | Self::StripeMissingField { .. } => StatusCode::BAD_GATEWAY,
+            Self::Config { .. }
+            | Self::EmptyPayload { .. }
+            | Self::InvalidPayload { .. }
+            | Self::Serialize(_)
+            | Self::Kafka(_)
+            | Self::KafkaConsumer(_)
+            | Self::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable code for the cause, sent as `error_code` on failure events.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Config { .. } => "config",
+            Self::InvalidServiceToken(_) => "unauthorized",
+            Self::Validation(_) => "validation",
+            Self::StripeApi { .. } => "stripe_api",
+            Self::StripeRequest(_)
+            | Self::StripeResponse(_)
+            | Self::MissingSessionUrl { .. }
+            | Self::StripeMissingField { .. } => "stripe_unavailable",
+            Self::EmptyPayload { .. } | Self::InvalidPayload { .. } => "invalid_payload",
+            Self::Serialize(_) => "internal",
+            Self::Kafka(_) | Self::KafkaConsumer(_) => "kafka",
+            Self::Db(_) => "db",
+        }
+    }
+
+    /// Message that is safe to return to API clients.
+    pub fn public_message(&self) -> &'static str {
+        match self {
+            Self::Validation(message) => *message,
+            Self::InvalidServiceToken(_) => "Invalid service token",
+            _ => match self.status_code() {
+                StatusCode::BAD_GATEWAY => "Checkout failed",
+                _ => "Checkout is not available",
+            },
+        }
+    }
+
+    /// Whether a consumer should expect a retry of the same message to succeed.
+    /// Malformed payloads and requests Stripe refused as invalid will never
+    /// succeed and are skipped.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::EmptyPayload { .. } | Self::InvalidPayload { .. } | Self::Validation(_) => false,
+            Self::StripeApi { status, .. } => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Kafka(err) => err.is_retryable(),
+            _ => true,
+        }
+    }
+}
+
+pub type CheckoutResult<T> = Result<T, CheckoutError>;
+
+#[cfg(test)]
+mod tests {
+    use super::CheckoutError;
+    use actix_web::http::StatusCode;
+
+    #[test]
+    fn stripe_failures_map_to_bad_gateway() {
+        let err = CheckoutError::StripeApi {
+            status: reqwest::StatusCode::PAYMENT_REQUIRED,
+            body: "card_declined".to_string(),
+        };
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert_eq!(err.public_message(), "Checkout failed");
+        assert_eq!(err.code(), "stripe_api");
+        assert!(err.to_string().contains("card_declined"));
+    }
+
+    #[test]
+    fn stripe_api_errors_are_retryable_only_when_stripe_is_at_fault() {
+        let rejected = |status| CheckoutError::StripeApi {
+            status,
+            body: String::new(),
+        };
+        assert!(!rejected(reqwest::StatusCode::BAD_REQUEST).is_retryable());
+        assert!(rejected(reqwest::StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(rejected(reqwest::StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+    }
+
+    #[test]
+    fn validation_errors_are_shown_to_the_caller() {
+        let err = CheckoutError::Validation("Amount must be positive");
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.public_message(), "Amount must be positive");
+        assert_eq!(err.code(), "validation");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn malformed_payloads_are_not_retryable() {
+        let source = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let err = CheckoutError::InvalidPayload {
+            topic: "checkout.requests".to_string(),
+            source,
+        };
+        assert!(!err.is_retryable());
+        assert_eq!(err.code(), "invalid_payload");
+
+        let config = CheckoutError::stripe_not_configured();
+        assert!(config.is_retryable());
+        assert_eq!(config.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(config.to_string(), "Stripe secret key is not configured");
+    }
+
+    #[test]
+    fn kafka_errors_keep_their_classification() {
+        let fatal = CheckoutError::Kafka(kafka_producer::ProducerError::Fatal("too large".into()));
+        let retryable =
+            CheckoutError::Kafka(kafka_producer::ProducerError::Retryable("broker down".into()));
+        assert!(!fatal.is_retryable());
+        assert!(retryable.is_retryable());
+        assert_eq!(fatal.code(), "kafka");
+    }
+}
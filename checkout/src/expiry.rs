@@ -0,0 +1,189 @@
+//! Checkout session expiry
+//!
+//! Every session is opened with an explicit Stripe `expires_at`
+//! (CHECKOUT_SESSION_TTL_MINUTES from now, which Stripe only accepts between
+//! 30 minutes and 24 hours) and recorded as a `session_created` row carrying
+//! it. The sweeper closes open rows once their expiry has passed as
+//! `session_expired` and publishes `checkout.event.session_expired` on
+//! `checkout.events`; `GET /sessions/{id}/status` reports where a session
+//! stands so the frontend can count down and poll instead of guessing.
+//!
+//! Expired is not final for Stripe's sake: a payment confirmed right at the
+//! deadline still arrives through the webhook (or reconciliation) and turns
+//! the row into `payment_succeeded`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::db::{self, ExpiredSession};
+use crate::error::CheckoutResult;
+use crate::types::CheckoutEvent;
+use crate::ServiceState;
+
+/// Shortest session lifetime Stripe accepts
+pub const MIN_TTL_MINUTES: i64 = 30;
+/// Longest session lifetime Stripe accepts (and its default)
+pub const MAX_TTL_MINUTES: i64 = 24 * 60;
+
+/// Rows closed per sweeper query
+const SWEEP_BATCH: i64 = 100;
+
+/// Session lifetime for a configured number of minutes, kept within Stripe's bounds
+pub fn session_ttl(minutes: i64) -> ChronoDuration {
+    ChronoDuration::minutes(minutes.clamp(MIN_TTL_MINUTES, MAX_TTL_MINUTES))
+}
+
+/// Where a checkout session stands, as the client sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// Waiting for the user to pay on Stripe
+    Open,
+    Paid,
+    Failed,
+    /// Stripe no longer accepts a payment; open a new session
+    Expired,
+}
+
+impl SessionStatus {
+    /// Status of a transaction row at `now`. An open row past its expiry is
+    /// already expired, whether or not the sweeper got to it.
+    pub fn of(status: &str, expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        match status {
+            "payment_succeeded" => SessionStatus::Paid,
+            "session_expired" => SessionStatus::Expired,
+            "session_created" if expires_at.is_some_and(|at| at <= now) => SessionStatus::Expired,
+            "session_created" => SessionStatus::Open,
+            _ => SessionStatus::Failed,
+        }
+    }
+}
+
+/// Whole seconds left until `expires_at` (0 once it has passed)
+pub fn seconds_remaining(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (expires_at - now).num_seconds().max(0)
+}
+
+/// Close every open session that has expired by `now`, publishing an event for each
+pub async fn sweep(state: &ServiceState, now: DateTime<Utc>) -> CheckoutResult<usize> {
+    let mut closed = 0;
+
+    loop {
+        let expired = db::expire_open_sessions(&state.db, now, SWEEP_BATCH).await?;
+        closed += expired.len();
+
+        for session in &expired {
+            publish_expired(state, session).await;
+        }
+
+        if (expired.len() as i64) < SWEEP_BATCH {
+            return Ok(closed);
+        }
+    }
+}
+
+async fn publish_expired(state: &ServiceState, session: &ExpiredSession) {
+    let event = CheckoutEvent::SessionExpired {
+        request_id: session.request_id.clone(),
+        user_id: session.user_id,
+        amount_cents: session.amount_cents,
+        currency: session.currency.clone(),
+        session_id: session.session_id.clone(),
+        purpose: session.purpose.clone(),
+        expires_at: session.expires_at.to_rfc3339(),
+        expired_at: Utc::now().to_rfc3339(),
+    };
+
+    let key = session.user_id.to_string();
+    if let Err(err) = state.producer.send_event(&event, Some(&key)).await {
+        warn!(
+            request_id = %session.request_id,
+            error = %err,
+            "Failed to publish session_expired event"
+        );
+    }
+}
+
+/// Sweep expired sessions every `interval`
+pub async fn run_sweeper(state: Arc<ServiceState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        match sweep(&state, Utc::now()).await {
+            Ok(0) => {}
+            Ok(closed) => info!("Closed {} expired checkout sessions", closed),
+            Err(err) => error!("Checkout session expiry sweep failed: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 17, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn ttl_stays_within_stripe_bounds() {
+        assert_eq!(session_ttl(5), ChronoDuration::minutes(30));
+        assert_eq!(session_ttl(90), ChronoDuration::minutes(90));
+        assert_eq!(session_ttl(10_000), ChronoDuration::hours(24));
+    }
+
+    #[test]
+    fn open_sessions_expire_at_their_deadline() {
+        let deadline = Some(at(12, 0));
+
+        assert_eq!(
+            SessionStatus::of("session_created", deadline, at(11, 59)),
+            SessionStatus::Open
+        );
+        assert_eq!(
+            SessionStatus::of("session_created", deadline, at(12, 0)),
+            SessionStatus::Expired
+        );
+        assert_eq!(
+            SessionStatus::of("session_expired", deadline, at(11, 0)),
+            SessionStatus::Expired
+        );
+        // Rows recorded before expiry tracking stay open until Stripe decides
+        assert_eq!(
+            SessionStatus::of("session_created", None, at(23, 0)),
+            SessionStatus::Open
+        );
+    }
+
+    #[test]
+    fn closed_sessions_keep_their_outcome_past_expiry() {
+        let deadline = Some(at(12, 0));
+
+        assert_eq!(
+            SessionStatus::of("payment_succeeded", deadline, at(13, 0)),
+            SessionStatus::Paid
+        );
+        assert_eq!(
+            SessionStatus::of("payment_failed", deadline, at(13, 0)),
+            SessionStatus::Failed
+        );
+        assert_eq!(
+            SessionStatus::of("session_failed", None, at(13, 0)),
+            SessionStatus::Failed
+        );
+    }
+
+    #[test]
+    fn countdown_never_goes_negative() {
+        assert_eq!(seconds_remaining(at(12, 0), at(11, 58)), 120);
+        assert_eq!(seconds_remaining(at(12, 0), at(12, 5)), 0);
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    web, HttpResponse,
+};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::auth::{extract_token, JwtClaims};
+use crate::{BaseResponse, ServiceState};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+// In-flight keys expire quickly so a crash mid-request doesn't block retries.
+const IN_FLIGHT_TTL_SECONDS: i64 = 60;
+
+struct StoredResponse {
+    fingerprint: Option<String>,
+    status: Option<u16>,
+    content_type: Option<String>,
+    location: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+/// Replays the stored response for retried mutating requests carrying an
+/// `Idempotency-Key` header, and rejects key reuse with a different body (409).
+/// Only 2xx responses are stored, with their status, `Content-Type`, `Location`
+/// and body; other headers (`Set-Cookie`, ...) are not replayed. Requests pass
+/// through untouched when Redis is not configured or unavailable.
+pub async fn idempotency<B>(
+    mut request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    if !matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    ) {
+        return next.call(request).await.map(|res| res.map_into_boxed_body());
+    }
+
+    let key = match request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(key) => key.trim().to_string(),
+        None => return next.call(request).await.map(|res| res.map_into_boxed_body()),
+    };
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        let response = HttpResponse::BadRequest().json(BaseResponse::error(
+            "Idempotency-Key must be between 1 and 255 characters",
+        ));
+        return Ok(request.into_response(response).map_into_boxed_body());
+    }
+
+    let state = match request.app_data::<web::Data<Arc<ServiceState>>>() {
+        Some(state) => state.clone(),
+        None => return next.call(request).await.map(|res| res.map_into_boxed_body()),
+    };
+    let mut redis = match state.redis.clone() {
+        Some(conn) => conn,
+        None => return next.call(request).await.map(|res| res.map_into_boxed_body()),
+    };
+
+    let body_bytes = request.extract::<web::Bytes>().await?;
+    request.set_payload(Payload::from(body_bytes.clone()));
+
+    let scope = user_scope(&request, &state.jwt_secret).unwrap_or_else(|| "anon".to_string());
+    let redis_key = format!("checkout:idempotency:{}:{}", scope, key);
+    let fingerprint = fingerprint(
+        request.method().as_str(),
+        request.path(),
+        request.query_string(),
+        &body_bytes,
+    );
+
+    let claimed: Result<bool, redis::RedisError> =
+        redis.hset_nx(&redis_key, "fingerprint", &fingerprint).await;
+
+    match claimed {
+        Ok(true) => {
+            let _: Result<(), _> = redis.expire(&redis_key, IN_FLIGHT_TTL_SECONDS).await;
+        }
+        Ok(false) => {
+            return match load(&mut redis, &redis_key).await {
+                Ok(stored) => {
+                    let response = replay_or_conflict(stored, &fingerprint);
+                    Ok(request.into_response(response).map_into_boxed_body())
+                }
+                Err(err) => {
+                    warn!(key = %redis_key, error = %err, "Idempotency lookup failed");
+                    next.call(request).await.map(|res| res.map_into_boxed_body())
+                }
+            };
+        }
+        Err(err) => {
+            warn!(key = %redis_key, error = %err, "Idempotency claim failed");
+            return next.call(request).await.map(|res| res.map_into_boxed_body());
+        }
+    }
+
+    let response = match next.call(request).await {
+        Ok(response) => response,
+        Err(err) => {
+            let _: Result<(), _> = redis.del(&redis_key).await;
+            return Err(err);
+        }
+    };
+
+    // Don't pin errors (Stripe outages, 401s from expired tokens, 429s) to the
+    // key; let the client retry.
+    if !response.status().is_success() {
+        let _: Result<(), _> = redis.del(&redis_key).await;
+        return Ok(response.map_into_boxed_body());
+    }
+
+    let (http_request, http_response) = response.into_parts();
+    let status = http_response.status();
+    let content_type = http_response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+    let location = http_response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let (http_response, response_body) = http_response.into_parts();
+    let response_bytes = match body::to_bytes(response_body).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let _: Result<(), _> = redis.del(&redis_key).await;
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Failed to read response body",
+            ));
+        }
+    };
+
+    let mut pipe = redis::pipe();
+    pipe.hset(&redis_key, "status", status.as_u16())
+        .ignore()
+        .hset(&redis_key, "content_type", &content_type)
+        .ignore()
+        .hset(&redis_key, "body", response_bytes.as_ref())
+        .ignore();
+    if let Some(location) = &location {
+        pipe.hset(&redis_key, "location", location).ignore();
+    }
+    let stored: Result<(), redis::RedisError> = pipe
+        .expire(&redis_key, state.idempotency_ttl_seconds as i64)
+        .ignore()
+        .query_async(&mut redis)
+        .await;
+    if let Err(err) = stored {
+        warn!(key = %redis_key, error = %err, "Failed to store idempotent response");
+    }
+
+    let http_response = http_response.set_body(response_bytes).map_into_boxed_body();
+    Ok(ServiceResponse::new(http_request, http_response))
+}
+
+fn user_scope(request: &ServiceRequest, jwt_secret: &str) -> Option<String> {
+    if jwt_secret.is_empty() {
+        return None;
+    }
+
+    let token = extract_token(request.request())?;
+    let mut validation = Validation::default();
+    validation.validate_exp = false;
+    decode::<JwtClaims>(
+        &token,
+        &DecodingKey::from_secret(jwt_secret.as_bytes()),
+        &validation,
+    )
+    .ok()
+    .map(|data| data.claims.sub.to_string())
+}
+
+fn fingerprint(method: &str, path: &str, query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"?");
+    hasher.update(query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Hash fields of a stored response, in `load` order
+type StoredFields = (
+    Option<String>,
+    Option<u16>,
+    Option<String>,
+    Option<String>,
+    Option<Vec<u8>>,
+);
+
+async fn load(
+    redis: &mut redis::aio::ConnectionManager,
+    redis_key: &str,
+) -> Result<StoredResponse, redis::RedisError> {
+    let (fingerprint, status, content_type, location, body): StoredFields = redis
+        .hget(
+            redis_key,
+            &["fingerprint", "status", "content_type", "location", "body"],
+        )
+        .await?;
+
+    Ok(StoredResponse {
+        fingerprint,
+        status,
+        content_type,
+        location,
+        body,
+    })
+}
+
+fn replay_or_conflict(stored: StoredResponse, fingerprint: &str) -> HttpResponse {
+    if stored.fingerprint.as_deref() != Some(fingerprint) {
+        return HttpResponse::Conflict().json(BaseResponse::error(
+            "Idempotency-Key was already used with a different request",
+        ));
+    }
+
+    match (stored.status, stored.body) {
+        (Some(status), Some(body)) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+            let mut response = HttpResponse::build(status);
+            response
+                .insert_header((
+                    header::CONTENT_TYPE,
+                    stored
+                        .content_type
+                        .unwrap_or_else(|| "application/json".to_string()),
+                ))
+                .insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"));
+            if let Some(location) = stored.location {
+                response.insert_header((header::LOCATION, location));
+            }
+            response.body(body)
+        }
+        _ => HttpResponse::Conflict().json(BaseResponse::error(
+            "A request with this Idempotency-Key is still being processed",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fingerprint, replay_or_conflict, StoredResponse, IDEMPOTENT_REPLAYED_HEADER};
+    use actix_web::http::StatusCode;
+
+    fn stored(fingerprint: &str, status: Option<u16>) -> StoredResponse {
+        StoredResponse {
+            fingerprint: Some(fingerprint.to_string()),
+            status,
+            content_type: Some("application/json".to_string()),
+            location: None,
+            body: status.map(|_| br#"{"status":"success"}"#.to_vec()),
+        }
+    }
+
+    #[test]
+    fn fingerprint_depends_on_body_and_path() {
+        let base = fingerprint("POST", "/sessions", "", br#"{"amount":10}"#);
+        assert_eq!(base, fingerprint("POST", "/sessions", "", br#"{"amount":10}"#));
+        assert_ne!(base, fingerprint("POST", "/sessions", "", br#"{"amount":11}"#));
+        assert_ne!(base, fingerprint("POST", "/other", "", br#"{"amount":10}"#));
+    }
+
+    #[test]
+    fn replay_returns_stored_response() {
+        let response = replay_or_conflict(stored("abc", Some(201)), "abc");
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+    }
+
+    #[test]
+    fn replay_keeps_the_location() {
+        let mut created = stored("abc", Some(201));
+        created.location = Some("/sessions/42".to_string());
+        let response = replay_or_conflict(created, "abc");
+        assert_eq!(response.headers().get("location").unwrap(), "/sessions/42");
+    }
+
+    #[test]
+    fn different_body_conflicts() {
+        let response = replay_or_conflict(stored("abc", Some(200)), "def");
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn in_flight_request_conflicts() {
+        let response = replay_or_conflict(stored("abc", None), "abc");
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,379 @@
+//! Session limits
+//!
+//! Every checkout session, whether opened with `POST /sessions` or from the
+//! `checkout.requests` topic, is checked before Stripe is called:
+//! - the amount must lie within the purpose's bounds (`CHECKOUT_PURPOSE_LIMITS`)
+//! - the user's paid volume over the last 24 hours (from `checkout_transactions`)
+//!   plus the amount may not exceed `CHECKOUT_DAILY_VOLUME_CAP_CENTS`
+//! - the user may open at most `CHECKOUT_SESSIONS_PER_HOUR` sessions an hour
+//!
+//! Sessions are counted in `checkout_session_attempts`; the count and the new
+//! row are taken under a per-user advisory lock so parallel requests cannot
+//! slip past the velocity check together. Rejections answer with a structured
+//! body and are published as `checkout.event.session_rejected` on
+//! `checkout.events` for monitoring.
+
+use std::collections::HashMap;
+use std::env;
+
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::db;
+use crate::error::CheckoutResult;
+use crate::types::CheckoutEvent;
+use crate::ServiceState;
+
+/// Purpose whose bounds apply to purposes without their own
+pub const DEFAULT_PURPOSE: &str = "*";
+
+/// Smallest and largest amount a session of one purpose may charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountBounds {
+    pub min_cents: i64,
+    pub max_cents: i64,
+}
+
+/// Limits read from the environment at startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitsConfig {
+    purposes: HashMap<String, AmountBounds>,
+    /// Paid cents per user per rolling 24 hours (0 = unlimited)
+    pub daily_volume_cap_cents: i64,
+    /// Sessions per user per rolling hour (0 = unlimited)
+    pub sessions_per_hour: i64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            purposes: parse_purpose_limits(DEFAULT_PURPOSE_LIMITS),
+            daily_volume_cap_cents: 200_000,
+            sessions_per_hour: 10,
+        }
+    }
+}
+
+/// 0.50 to 1000.00 EUR for any purpose, top-ups from 1.00 EUR
+const DEFAULT_PURPOSE_LIMITS: &str = "*=50:100000,balance_topup=100:100000";
+
+impl LimitsConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let purposes = env::var("CHECKOUT_PURPOSE_LIMITS")
+            .ok()
+            .map(|value| parse_purpose_limits(&value))
+            .filter(|purposes| !purposes.is_empty())
+            .unwrap_or(defaults.purposes);
+        let daily_volume_cap_cents = env::var("CHECKOUT_DAILY_VOLUME_CAP_CENTS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|cap| *cap >= 0)
+            .unwrap_or(defaults.daily_volume_cap_cents);
+        let sessions_per_hour = env::var("CHECKOUT_SESSIONS_PER_HOUR")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|limit| *limit >= 0)
+            .unwrap_or(defaults.sessions_per_hour);
+
+        Self {
+            purposes,
+            daily_volume_cap_cents,
+            sessions_per_hour,
+        }
+    }
+
+    /// Bounds of a purpose, falling back to the `*` entry
+    pub fn bounds(&self, purpose: &str) -> Option<AmountBounds> {
+        self.purposes
+            .get(purpose)
+            .or_else(|| self.purposes.get(DEFAULT_PURPOSE))
+            .copied()
+    }
+
+    /// Check the amount against the purpose's bounds
+    pub fn check_amount(&self, purpose: &str, amount_cents: i64) -> Result<(), LimitRejection> {
+        let Some(bounds) = self.bounds(purpose) else {
+            return Ok(());
+        };
+        if amount_cents < bounds.min_cents {
+            return Err(LimitRejection::BelowMinimum {
+                min_cents: bounds.min_cents,
+            });
+        }
+        if amount_cents > bounds.max_cents {
+            return Err(LimitRejection::AboveMaximum {
+                max_cents: bounds.max_cents,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the amount against what the user paid in the last 24 hours
+    pub fn check_daily_volume(
+        &self,
+        paid_cents: i64,
+        amount_cents: i64,
+    ) -> Result<(), LimitRejection> {
+        if self.daily_volume_cap_cents == 0 {
+            return Ok(());
+        }
+        if paid_cents.saturating_add(amount_cents) > self.daily_volume_cap_cents {
+            return Err(LimitRejection::DailyVolumeExceeded {
+                cap_cents: self.daily_volume_cap_cents,
+                remaining_cents: (self.daily_volume_cap_cents - paid_cents).max(0),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the sessions the user opened in the last hour
+    pub fn check_velocity(&self, sessions_last_hour: i64) -> Result<(), LimitRejection> {
+        if self.sessions_per_hour == 0 || sessions_last_hour < self.sessions_per_hour {
+            return Ok(());
+        }
+        Err(LimitRejection::TooManySessions {
+            limit: self.sessions_per_hour,
+        })
+    }
+}
+
+/// `purpose=min:max` pairs in cents, comma-separated; malformed entries are skipped
+fn parse_purpose_limits(value: &str) -> HashMap<String, AmountBounds> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (purpose, bounds) = entry.trim().split_once('=')?;
+            let (min, max) = bounds.split_once(':')?;
+            let bounds = AmountBounds {
+                min_cents: min.trim().parse().ok()?,
+                max_cents: max.trim().parse().ok()?,
+            };
+            (bounds.min_cents > 0 && bounds.min_cents <= bounds.max_cents)
+                .then(|| (purpose.trim().to_string(), bounds))
+        })
+        .collect()
+}
+
+/// Why a session was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitRejection {
+    BelowMinimum {
+        min_cents: i64,
+    },
+    AboveMaximum {
+        max_cents: i64,
+    },
+    DailyVolumeExceeded {
+        cap_cents: i64,
+        remaining_cents: i64,
+    },
+    TooManySessions {
+        limit: i64,
+    },
+}
+
+/// Body of a rejected session request
+#[derive(Debug, Serialize)]
+struct LimitRejectionResponse {
+    status: &'static str,
+    message: &'static str,
+    code: &'static str,
+    limit: Value,
+}
+
+impl LimitRejection {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BelowMinimum { .. } => "amount_below_minimum",
+            Self::AboveMaximum { .. } => "amount_above_maximum",
+            Self::DailyVolumeExceeded { .. } => "daily_volume_exceeded",
+            Self::TooManySessions { .. } => "too_many_sessions",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::BelowMinimum { .. } => "Amount is below the minimum",
+            Self::AboveMaximum { .. } => "Amount is above the maximum",
+            Self::DailyVolumeExceeded { .. } => "Daily payment limit reached",
+            Self::TooManySessions { .. } => "Too many checkout sessions, try again later",
+        }
+    }
+
+    /// The limit that was hit, for the client and the event
+    pub fn limit(&self) -> Value {
+        match self {
+            Self::BelowMinimum { min_cents } => json!({ "min_cents": min_cents }),
+            Self::AboveMaximum { max_cents } => json!({ "max_cents": max_cents }),
+            Self::DailyVolumeExceeded {
+                cap_cents,
+                remaining_cents,
+            } => json!({ "cap_cents": cap_cents, "remaining_cents": remaining_cents }),
+            Self::TooManySessions { limit } => json!({ "sessions_per_hour": limit }),
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BelowMinimum { .. } | Self::AboveMaximum { .. } => StatusCode::BAD_REQUEST,
+            Self::DailyVolumeExceeded { .. } | Self::TooManySessions { .. } => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+
+    pub fn response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(LimitRejectionResponse {
+            status: "error",
+            message: self.message(),
+            code: self.code(),
+            limit: self.limit(),
+        })
+    }
+}
+
+/// Check a session against every limit and count it when it passes
+pub async fn admit_session(
+    state: &ServiceState,
+    user_id: i64,
+    purpose: &str,
+    amount_cents: i64,
+) -> CheckoutResult<Result<(), LimitRejection>> {
+    let limits = &state.limits;
+    if let Err(rejection) = limits.check_amount(purpose, amount_cents) {
+        return Ok(Err(rejection));
+    }
+
+    let now = Utc::now();
+    if limits.daily_volume_cap_cents > 0 {
+        let paid_cents =
+            db::sum_paid_cents_since(&state.db, user_id, now - Duration::hours(24)).await?;
+        if let Err(rejection) = limits.check_daily_volume(paid_cents, amount_cents) {
+            return Ok(Err(rejection));
+        }
+    }
+
+    Ok(db::record_session_attempt(
+        &state.db,
+        user_id,
+        purpose,
+        amount_cents,
+        now - Duration::hours(1),
+        |recent| limits.check_velocity(recent),
+    )
+    .await?)
+}
+
+/// Publish a rejection for monitoring; failures are only logged
+pub async fn report_rejection(
+    state: &ServiceState,
+    request_id: &str,
+    user_id: i64,
+    amount_cents: i64,
+    currency: &str,
+    purpose: &str,
+    rejection: LimitRejection,
+) {
+    warn!(
+        request_id = %request_id,
+        user_id = %user_id,
+        amount_cents,
+        purpose = %purpose,
+        reason = rejection.code(),
+        "Checkout session rejected by limits"
+    );
+
+    let event = CheckoutEvent::SessionRejected {
+        request_id: request_id.to_string(),
+        user_id,
+        amount_cents,
+        currency: currency.to_string(),
+        purpose: purpose.to_string(),
+        reason: rejection.code().to_string(),
+        limit: rejection.limit(),
+        rejected_at: Utc::now().to_rfc3339(),
+    };
+    if let Err(err) = state
+        .producer
+        .send_event(&event, Some(&user_id.to_string()))
+        .await
+    {
+        warn!(request_id = %request_id, error = %err, "Failed to publish session_rejected event");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> LimitsConfig {
+        LimitsConfig {
+            purposes: parse_purpose_limits("*=50:1000,balance_topup=100:5000"),
+            daily_volume_cap_cents: 10_000,
+            sessions_per_hour: 3,
+        }
+    }
+
+    #[test]
+    fn test_purpose_limits_fall_back_to_the_default() {
+        let limits = limits();
+
+        assert_eq!(limits.check_amount("balance_topup", 2000), Ok(()));
+        assert_eq!(
+            limits.check_amount("balance_topup", 50),
+            Err(LimitRejection::BelowMinimum { min_cents: 100 })
+        );
+        assert_eq!(
+            limits.check_amount("gift", 2000),
+            Err(LimitRejection::AboveMaximum { max_cents: 1000 })
+        );
+    }
+
+    #[test]
+    fn test_malformed_purpose_limits_are_skipped() {
+        let purposes = parse_purpose_limits("a=1:2, b=x:5, c=9:3, d=4");
+        assert_eq!(purposes.len(), 1);
+        assert!(purposes.contains_key("a"));
+    }
+
+    #[test]
+    fn test_daily_volume_includes_the_new_amount() {
+        let limits = limits();
+
+        assert_eq!(limits.check_daily_volume(6000, 4000), Ok(()));
+        assert_eq!(
+            limits.check_daily_volume(6000, 4001),
+            Err(LimitRejection::DailyVolumeExceeded {
+                cap_cents: 10_000,
+                remaining_cents: 4000
+            })
+        );
+    }
+
+    #[test]
+    fn test_velocity_and_zero_disables() {
+        let mut limits = limits();
+        assert_eq!(limits.check_velocity(2), Ok(()));
+        assert_eq!(
+            limits.check_velocity(3),
+            Err(LimitRejection::TooManySessions { limit: 3 })
+        );
+
+        limits.sessions_per_hour = 0;
+        limits.daily_volume_cap_cents = 0;
+        assert_eq!(limits.check_velocity(100), Ok(()));
+        assert_eq!(limits.check_daily_volume(i64::MAX, 1), Ok(()));
+    }
+
+    #[test]
+    fn test_rejections_are_structured() {
+        let rejection = LimitRejection::TooManySessions { limit: 3 };
+        assert_eq!(rejection.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(rejection.limit(), json!({ "sessions_per_hour": 3 }));
+    }
+}
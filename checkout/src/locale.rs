@@ -0,0 +1,111 @@
+//! Response localization
+//!
+//! Translates the `message` of JSON responses with the shared `i18n` catalogs.
+//! The locale is the `locale` claim of the caller's JWT (their profile
+//! setting) when present, otherwise `Accept-Language`, otherwise English.
+
+use actix_web::body::{self, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::web;
+use i18n::Locale;
+
+use crate::auth::{decode_token, extract_token};
+use crate::ServiceState;
+
+/// Locale for a request: JWT `locale` claim, then Accept-Language
+fn request_locale(request: &ServiceRequest) -> Locale {
+    let preferred = request
+        .app_data::<web::Data<ServiceState>>()
+        .filter(|state| !state.jwt_secret.is_empty())
+        .and_then(|state| {
+            let token = extract_token(request.request())?;
+            decode_token(&token, &state.jwt_secret).ok()?.locale
+        });
+    let accept_language = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok());
+
+    i18n::negotiate(preferred.as_deref(), accept_language)
+}
+
+pub async fn localize_response<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    let locale = request_locale(&request);
+    let response = next.call(request).await?.map_into_boxed_body();
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if !is_json {
+        return Ok(response);
+    }
+
+    let (http_request, mut http_response) = response.into_parts();
+    http_response.headers_mut().insert(
+        header::CONTENT_LANGUAGE,
+        HeaderValue::from_static(locale.code()),
+    );
+    if locale == Locale::En {
+        return Ok(ServiceResponse::new(http_request, http_response));
+    }
+
+    let (http_response, response_body) = http_response.into_parts();
+    let bytes = body::to_bytes(response_body).await.map_err(|_| {
+        actix_web::error::ErrorInternalServerError("Failed to read response body")
+    })?;
+    let bytes = match i18n::localize_json(&bytes, locale) {
+        Some(localized) => localized.into(),
+        None => bytes,
+    };
+
+    Ok(ServiceResponse::new(
+        http_request,
+        http_response.set_body(BoxBody::new(bytes)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::localize_response;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn messages_follow_accept_language() {
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(localize_response))
+                .route(
+                    "/",
+                    web::get().to(|| async {
+                        HttpResponse::Unauthorized()
+                            .json(serde_json::json!({ "status": "error", "message": "Unauthorized" }))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Accept-Language", "sr-RS,en;q=0.5"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.headers().get("Content-Language").unwrap(), "sr");
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["message"], "Neovlašćen pristup");
+
+        let req = test::TestRequest::get().uri("/").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["message"], "Unauthorized");
+    }
+}
@@ -1,75 +1,164 @@
+use actix_web::middleware::from_fn;
 use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rdkafka::config::ClientConfig;
 use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
-use rdkafka::message::BorrowedMessage;
+use rdkafka::message::{BorrowedMessage, Headers};
 use rdkafka::Message;
-use rdkafka::producer::{FutureProducer, FutureRecord};
-use rdkafka::util::Timeout;
+use kafka_producer::{ResilienceConfig, ResilientProducer};
+use kafka_rebalance::{RebalanceContext, RebalanceTracker};
+use pagination::{paginate, Cursor};
+use rdkafka::producer::FutureProducer;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use secrets_provider::{redact_url, Redacted, SecretsProvider};
+use service_auth::{KeyRing, RejectionMetrics, ServiceClaims, Verifier};
 use sqlx::PgPool;
 use std::time::Duration;
 use std::{env, sync::Arc};
-use tracing::{error, info, warn};
+use tracing::{error, info, warn, Instrument};
 use uuid::Uuid;
 
 mod db;
 mod auth;
+mod coupons;
+mod customers;
+mod disputes;
+mod error;
+mod expiry;
+mod idempotency;
+mod limits;
+mod locale;
+mod maintenance;
+mod reconcile;
 mod stripe;
+#[cfg(feature = "stripe-mock")]
+mod stripe_mock;
+mod subscriptions;
+mod transaction_search;
 mod types;
+mod validation;
+mod webhooks;
 
-use auth::{decode_token, extract_token};
-use types::{CheckoutCommand, CheckoutFinishedEvent, CheckoutRequestEvent};
+use auth::{decode_token, extract_service_token, extract_token};
+use error::{CheckoutError, CheckoutResult};
+use types::{
+    CheckoutCommand, CheckoutDisputeEvent, CheckoutEvent, CheckoutFinishedEvent, CheckoutRequestEvent,
+    CheckoutSubscriptionEvent,
+};
+use validation::{FieldError, Validate, ValidationErrorResponse};
 
 // Kafka topics
 const CHECKOUT_REQUESTS_TOPIC: &str = "checkout.requests";
 const CHECKOUT_FINISHED_TOPIC: &str = "checkout.finished";
+/// Monitoring events (`CheckoutEvent`)
+const CHECKOUT_EVENTS_TOPIC: &str = "checkout.events";
+/// Stripe disputes (`CheckoutDisputeEvent`)
+const CHECKOUT_DISPUTES_TOPIC: &str = "checkout.disputes";
+/// Premium subscriptions (`CheckoutSubscriptionEvent`)
+const CHECKOUT_SUBSCRIPTIONS_TOPIC: &str = "checkout.subscriptions";
 const BIGGER_DICE_PARTICIPATION_TOPIC: &str = "bigger_dice.participation_payed";
 const BIGGER_DICE_WIN_PRIZE_TOPIC: &str = "bigger_dice.win_prize";
 const TIC_TAC_TOE_PARTICIPATION_TOPIC: &str = "tic_tac_toe.participation_payed";
 const TIC_TAC_TOE_WIN_PRIZE_TOPIC: &str = "tic_tac_toe.win_prize";
 
+/// Secrets checkout cannot serve requests without
+const REQUIRED_SECRETS: &[&str] = &["JWT_SECRET"];
+
 #[derive(Clone)]
 struct AppConfig {
     host: String,
     port: u16,
     kafka_bootstrap: String,
     kafka_group_id: String,
+    /// KAFKA_REBALANCE_DRAIN_TIMEOUT_MS, longest wait for the handler in
+    /// flight before partitions are handed to another member
+    kafka_rebalance_drain_timeout_ms: u64,
     stripe_secret: String,
-    stripe_webhook_secret: String,
+    /// Webhook secrets, timestamp tolerance and replay window (see `webhooks.rs`)
+    webhook: webhooks::WebhookConfig,
+    /// STRIPE_API_BASE, the `stripe-mock` sandbox in CI
+    stripe_api_base: String,
+    /// STRIPE_PREMIUM_PRICE_ID, the recurring price of the premium membership
+    stripe_premium_price_id: Option<String>,
     jwt_secret: String,
-    service_token: String,
+    /// SERVICE_AUTH_KEYS, shared with the services calling the internal API
+    service_auth_keys: String,
+    service_auth_issuers: Vec<String>,
+    /// CHECKOUT_ADMIN_TOKENS, for `/admin/transactions` (see `transaction_search.rs`)
+    admin_tokens: transaction_search::AdminTokens,
     database_url: String,
+    redis_url: Option<String>,
+    idempotency_ttl_seconds: u64,
+    auto_migrate: bool,
+    /// UTC hour the nightly Stripe reconciliation runs at
+    reconcile_hour_utc: u32,
+    /// How far back each reconciliation run looks at Stripe sessions
+    reconcile_lookback_hours: i64,
+    /// CHECKOUT_SESSION_TTL_MINUTES, how long a Stripe session accepts payment
+    session_ttl_minutes: i64,
+    /// How often open sessions past their expiry are closed
+    session_sweep_interval_seconds: u64,
+    limits: limits::LimitsConfig,
 }
 
 impl AppConfig {
-    fn from_env() -> Self {
-        let host = env::var("CHECKOUT_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-        let port = env::var("CHECKOUT_PORT")
+    /// Read the configuration through `secrets` and check it
+    fn load(secrets: &dyn SecretsProvider) -> Result<Self, ConfigError> {
+        let host = secrets.var("CHECKOUT_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
+        let mut problems = secrets_provider::missing(secrets, REQUIRED_SECRETS);
+
+        let port = secrets.var("CHECKOUT_PORT")
             .unwrap_or_else(|_| "9996".to_string())
             .parse()
-            .unwrap_or(9996);
+            .unwrap_or_else(|_| {
+                problems.push("CHECKOUT_PORT must be a port number".to_string());
+                9996
+            });
 
-        let kafka_host = env::var("KAFKA_HOST").unwrap_or_else(|_| "kafka".to_string());
-        let kafka_port = env::var("KAFKA_PORT").unwrap_or_else(|_| "9092".to_string());
+        let kafka_host = secrets.var("KAFKA_HOST").unwrap_or_else(|_| "kafka".to_string());
+        let kafka_port = secrets.var("KAFKA_PORT").unwrap_or_else(|_| "9092".to_string());
         let kafka_bootstrap = format!("{}:{}", kafka_host, kafka_port);
 
         let kafka_group_id =
-            env::var("CHECKOUT_KAFKA_GROUP").unwrap_or_else(|_| "checkout-service".to_string());
-
-        let stripe_secret = env::var("STRIPE_SECRET").unwrap_or_default();
-        let stripe_webhook_secret = env::var("STRIPE_WEBHOOK_SECRET").unwrap_or_default();
-        let jwt_secret = env::var("JWT_SECRET").unwrap_or_default();
-        let service_token = env::var("CHECKOUT_SERVICE_TOKEN").unwrap_or_default();
+            secrets.var("CHECKOUT_KAFKA_GROUP").unwrap_or_else(|_| "checkout-service".to_string());
+        let kafka_rebalance_drain_timeout_ms = secrets.var("KAFKA_REBALANCE_DRAIN_TIMEOUT_MS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(10_000);
+
+        let stripe_secret = secrets.var("STRIPE_SECRET").unwrap_or_default();
+        let stripe_api_base = secrets.var("STRIPE_API_BASE")
+            .ok()
+            .filter(|base| !base.trim().is_empty())
+            .unwrap_or_else(|| stripe::DEFAULT_API_BASE.to_string());
+        let stripe_premium_price_id = secrets.var("STRIPE_PREMIUM_PRICE_ID")
+            .ok()
+            .map(|price| price.trim().to_string())
+            .filter(|price| !price.is_empty());
+        let jwt_secret = secrets.var("JWT_SECRET").unwrap_or_default();
+        let service_auth_keys = secrets.var("SERVICE_AUTH_KEYS").unwrap_or_default();
+        let service_auth_issuers = secrets.var("CHECKOUT_SERVICE_AUTH_ISSUERS")
+            .unwrap_or_else(|_| "blazing_sun".to_string())
+            .split(',')
+            .map(|issuer| issuer.trim().to_string())
+            .filter(|issuer| !issuer.is_empty())
+            .collect();
+        let admin_tokens = transaction_search::AdminTokens::parse(
+            &secrets.var("CHECKOUT_ADMIN_TOKENS").unwrap_or_default(),
+        )
+        .unwrap_or_else(|problem| {
+            problems.push(problem);
+            transaction_search::AdminTokens::default()
+        });
 
-        let database_url = env::var("CHECKOUT_DATABASE_URL").unwrap_or_else(|_| {
+        let database_url = secrets.var("CHECKOUT_DATABASE_URL").unwrap_or_else(|_| {
             let db_host =
-                env::var("CHECKOUT_DB_HOST").unwrap_or_else(|_| "checkout-postgres".to_string());
-            let db_port = env::var("CHECKOUT_DB_PORT").unwrap_or_else(|_| "5433".to_string());
-            let db_user = env::var("CHECKOUT_DB_USER").unwrap_or_else(|_| "checkout".to_string());
-            let db_password = env::var("CHECKOUT_DB_PASSWORD").unwrap_or_default();
-            let db_name = env::var("CHECKOUT_DB_NAME").unwrap_or_else(|_| "checkout".to_string());
+                secrets.var("CHECKOUT_DB_HOST").unwrap_or_else(|_| "checkout-postgres".to_string());
+            let db_port = secrets.var("CHECKOUT_DB_PORT").unwrap_or_else(|_| "5433".to_string());
+            let db_user = secrets.var("CHECKOUT_DB_USER").unwrap_or_else(|_| "checkout".to_string());
+            let db_password = secrets.var("CHECKOUT_DB_PASSWORD").unwrap_or_default();
+            let db_name = secrets.var("CHECKOUT_DB_NAME").unwrap_or_else(|_| "checkout".to_string());
 
             if db_password.is_empty() {
                 format!("postgres://{}@{}:{}/{}", db_user, db_host, db_port, db_name)
@@ -81,23 +170,118 @@ impl AppConfig {
             }
         });
 
-        Self {
+        let redis_url = secrets.var("CHECKOUT_REDIS_URL")
+            .or_else(|_| secrets.var("REDIS_URL"))
+            .ok()
+            .filter(|url| !url.is_empty());
+        let idempotency_ttl_seconds = secrets.var("IDEMPOTENCY_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(86400);
+
+        // Production applies migrations explicitly (`checkout --migrate-only`)
+        let auto_migrate = secrets.var("CHECKOUT_AUTO_MIGRATE")
+            .map(|value| matches!(value.to_ascii_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or_else(|_| secrets.var("BUILD_ENV").as_deref() == Ok("dev"));
+
+        let reconcile_hour_utc = match secrets.var("CHECKOUT_RECONCILE_HOUR_UTC") {
+            Ok(value) => value.parse::<u32>().ok().filter(|hour| *hour < 24).unwrap_or_else(|| {
+                problems.push("CHECKOUT_RECONCILE_HOUR_UTC must be an hour from 0 to 23".to_string());
+                3
+            }),
+            Err(_) => 3,
+        };
+        let reconcile_lookback_hours = secrets.var("CHECKOUT_RECONCILE_LOOKBACK_HOURS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|hours| *hours > 0)
+            .unwrap_or(48);
+
+        let session_ttl_minutes = secrets.var("CHECKOUT_SESSION_TTL_MINUTES")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(expiry::MAX_TTL_MINUTES);
+        let session_sweep_interval_seconds = secrets.var("CHECKOUT_SESSION_SWEEP_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(60);
+
+        if !problems.is_empty() {
+            return Err(ConfigError(problems));
+        }
+
+        Ok(Self {
             host,
             port,
             kafka_bootstrap,
             kafka_group_id,
+            kafka_rebalance_drain_timeout_ms,
             stripe_secret,
-            stripe_webhook_secret,
+            webhook: webhooks::WebhookConfig::load(secrets),
+            stripe_api_base,
+            stripe_premium_price_id,
             jwt_secret,
-            service_token,
+            service_auth_keys,
+            service_auth_issuers,
+            admin_tokens,
             database_url,
-        }
+            redis_url,
+            idempotency_ttl_seconds,
+            auto_migrate,
+            reconcile_hour_utc,
+            reconcile_lookback_hours,
+            session_ttl_minutes,
+            session_sweep_interval_seconds,
+            limits: limits::LimitsConfig::from_env(),
+        })
+    }
+}
+
+/// Secrets and passwords are masked, so the configuration can be logged
+impl std::fmt::Debug for AppConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("kafka_bootstrap", &self.kafka_bootstrap)
+            .field("kafka_group_id", &self.kafka_group_id)
+            .field("kafka_rebalance_drain_timeout_ms", &self.kafka_rebalance_drain_timeout_ms)
+            .field("stripe_secret", &Redacted(&self.stripe_secret))
+            .field("webhook", &self.webhook)
+            .field("stripe_api_base", &self.stripe_api_base)
+            .field("stripe_premium_price_id", &self.stripe_premium_price_id)
+            .field("jwt_secret", &Redacted(&self.jwt_secret))
+            .field("service_auth_keys", &Redacted(&self.service_auth_keys))
+            .field("service_auth_issuers", &self.service_auth_issuers)
+            .field("admin_tokens", &self.admin_tokens)
+            .field("database_url", &redact_url(&self.database_url))
+            .field("redis_url", &self.redis_url.as_deref().map(redact_url))
+            .field("idempotency_ttl_seconds", &self.idempotency_ttl_seconds)
+            .field("auto_migrate", &self.auto_migrate)
+            .field("reconcile_hour_utc", &self.reconcile_hour_utc)
+            .field("reconcile_lookback_hours", &self.reconcile_lookback_hours)
+            .field("session_ttl_minutes", &self.session_ttl_minutes)
+            .field("session_sweep_interval_seconds", &self.session_sweep_interval_seconds)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+/// Settings that are missing or invalid, all reported at once
+#[derive(Debug)]
+struct ConfigError(Vec<String>);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid configuration: {}", self.0.join("; "))
     }
 }
 
+
 #[derive(Clone)]
 struct KafkaProducer {
-    producer: FutureProducer,
+    producer: Arc<ResilientProducer>,
 }
 
 impl KafkaProducer {
@@ -112,27 +296,67 @@ impl KafkaProducer {
             .set("linger.ms", "5")
             .create()?;
 
+        let producer = Arc::new(ResilientProducer::new(producer, ResilienceConfig::default()));
+        producer.start_flusher();
+
         Ok(Self { producer })
     }
 
-    /// Send a CheckoutFinishedEvent to the checkout.finished topic
+    /// Send a CheckoutFinishedEvent to the checkout.finished topic.
+    /// While Kafka is down the event is buffered and delivered once it is back.
     async fn send_finished_event(
         &self,
         event: &CheckoutFinishedEvent,
         key: Option<&str>,
-    ) -> Result<(), String> {
-        let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
-        let mut record = FutureRecord::to(CHECKOUT_FINISHED_TOPIC).payload(&payload);
+    ) -> CheckoutResult<()> {
+        let payload = serde_json::to_vec(event)?;
 
-        if let Some(k) = key {
-            record = record.key(k);
-        }
+        self.producer
+            .send(CHECKOUT_FINISHED_TOPIC, key, &payload)
+            .await
+            .map(|_| ())
+            .map_err(CheckoutError::Kafka)
+    }
+
+    /// Send a monitoring event to the checkout.events topic
+    async fn send_event(&self, event: &CheckoutEvent, key: Option<&str>) -> CheckoutResult<()> {
+        let payload = serde_json::to_vec(event)?;
+
+        self.producer
+            .send(CHECKOUT_EVENTS_TOPIC, key, &payload)
+            .await
+            .map(|_| ())
+            .map_err(CheckoutError::Kafka)
+    }
+
+    /// Send a CheckoutDisputeEvent to the checkout.disputes topic
+    async fn send_dispute_event(
+        &self,
+        event: &CheckoutDisputeEvent,
+        key: Option<&str>,
+    ) -> CheckoutResult<()> {
+        let payload = serde_json::to_vec(event)?;
+
+        self.producer
+            .send(CHECKOUT_DISPUTES_TOPIC, key, &payload)
+            .await
+            .map(|_| ())
+            .map_err(CheckoutError::Kafka)
+    }
+
+    /// Send a CheckoutSubscriptionEvent to the checkout.subscriptions topic
+    async fn send_subscription_event(
+        &self,
+        event: &CheckoutSubscriptionEvent,
+        key: Option<&str>,
+    ) -> CheckoutResult<()> {
+        let payload = serde_json::to_vec(event)?;
 
         self.producer
-            .send(record, Timeout::After(Duration::from_secs(5)))
+            .send(CHECKOUT_SUBSCRIPTIONS_TOPIC, key, &payload)
             .await
             .map(|_| ())
-            .map_err(|(err, _)| err.to_string())
+            .map_err(CheckoutError::Kafka)
     }
 }
 
@@ -140,11 +364,29 @@ impl KafkaProducer {
 struct ServiceState {
     producer: KafkaProducer,
     stripe_secret: String,
-    stripe_webhook_secret: String,
+    webhook: webhooks::WebhookConfig,
+    stripe_api_base: String,
+    /// None when STRIPE_PREMIUM_PRICE_ID is not set; `POST /subscriptions` then fails
+    stripe_premium_price_id: Option<String>,
     http_client: reqwest::Client,
     jwt_secret: String,
-    service_token: String,
+    /// None when SERVICE_AUTH_KEYS is not set; internal endpoints then refuse every call
+    service_auth: Option<Verifier>,
+    service_auth_rejections: Arc<RejectionMetrics>,
+    /// Empty when CHECKOUT_ADMIN_TOKENS is not set; `/admin/transactions` then
+    /// refuses every call
+    admin_tokens: transaction_search::AdminTokens,
+    webhook_rejections: Arc<webhooks::WebhookMetrics>,
+    /// Handler in flight and rebalance counters of the consumer
+    rebalance: RebalanceTracker,
     db: PgPool,
+    redis: Option<redis::aio::ConnectionManager>,
+    /// Read-only maintenance flag, read from `redis` (see `maintenance.rs`)
+    maintenance: Arc<maintenance_mode::Maintenance>,
+    idempotency_ttl_seconds: u64,
+    /// Lifetime of new Stripe sessions (see `expiry.rs`)
+    session_ttl: chrono::Duration,
+    limits: limits::LimitsConfig,
 }
 
 #[derive(Serialize)]
@@ -173,17 +415,43 @@ impl BaseResponse {
 struct StripeCheckoutSession {
     id: String,
     url: Option<String>,
+    /// Unix seconds after which Stripe no longer accepts payment
+    #[serde(default)]
+    expires_at: Option<i64>,
+}
+
+impl StripeCheckoutSession {
+    fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct CheckoutSessionRequest {
     amount: i64,
+    /// Promo code to apply to the session
+    #[serde(default)]
+    coupon: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct TransactionsQuery {
     limit: Option<i64>,
-    offset: Option<i64>,
+    /// Opaque cursor from a previous response's `next_cursor`/`prev_cursor`
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReconciliationReportQuery {
+    /// Defaults to the most recent run
+    run_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InternalTransactionsQuery {
+    limit: Option<i64>,
+    before: Option<chrono::DateTime<Utc>>,
 }
 
 #[derive(Serialize)]
@@ -192,6 +460,24 @@ struct CheckoutSessionResponse {
     base: BaseResponse,
     session_id: String,
     url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<DateTime<Utc>>,
+    /// Countdown for the client, from the server's clock
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_in_seconds: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct SessionStatusResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    session_id: String,
+    request_id: String,
+    session_status: expiry::SessionStatus,
+    expires_at: Option<DateTime<Utc>>,
+    /// Seconds left to pay; 0 unless the session is open
+    expires_in_seconds: i64,
+    completed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Serialize)]
@@ -199,18 +485,65 @@ struct TransactionsResponse {
     #[serde(flatten)]
     base: BaseResponse,
     transactions: Vec<db::CheckoutTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prev_cursor: Option<String>,
 }
 
-fn validate_service_token_value(expected: &str, actual: &str) -> Result<(), String> {
-    if expected.is_empty() {
-        return Err("Checkout service token not configured".to_string());
-    }
+#[derive(Serialize)]
+struct ReconciliationReportResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    run: db::ReconciliationRun,
+    discrepancies: Vec<db::ReconciliationDiscrepancy>,
+}
 
-    if actual != expected {
-        return Err("Invalid service token".to_string());
-    }
+#[derive(Serialize)]
+struct CouponsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    coupons: Vec<coupons::Coupon>,
+}
 
-    Ok(())
+#[derive(Serialize)]
+struct CouponResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    coupon: coupons::Coupon,
+}
+
+#[derive(Serialize)]
+struct PaymentMethodsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    payment_methods: Vec<customers::SavedCard>,
+}
+
+#[derive(Serialize)]
+struct SetupIntentResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    setup_intent_id: String,
+    client_secret: String,
+}
+
+#[derive(Serialize)]
+struct SubscriptionStatusResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    premium: bool,
+    /// Stripe status of the user's subscription; None without one
+    subscription_status: Option<String>,
+    current_period_end: Option<DateTime<Utc>>,
+    cancel_at_period_end: bool,
+}
+
+fn validate_service_token(verifier: Option<&Verifier>, token: &str) -> CheckoutResult<ServiceClaims> {
+    let verifier = verifier.ok_or(CheckoutError::Config {
+        setting: "Service auth keys",
+    })?;
+    Ok(verifier.verify(token)?)
 }
 
 fn request_base_url(req: &HttpRequest) -> String {
@@ -219,12 +552,21 @@ fn request_base_url(req: &HttpRequest) -> String {
 }
 
 fn build_balance_urls(base_url: &str) -> (String, String) {
+    build_return_urls(base_url, "balance")
+}
+
+fn build_premium_urls(base_url: &str) -> (String, String) {
+    build_return_urls(base_url, "premium")
+}
+
+/// Success and cancel URLs Stripe sends the user back to, on `page`
+fn build_return_urls(base_url: &str, page: &str) -> (String, String) {
     let base = base_url.trim_end_matches('/');
     let success_url = format!(
-        "{}/balance?status=success&session_id={{CHECKOUT_SESSION_ID}}",
-        base
+        "{}/{}?status=success&session_id={{CHECKOUT_SESSION_ID}}",
+        base, page
     );
-    let cancel_url = format!("{}/balance?status=cancel", base);
+    let cancel_url = format!("{}/{}?status=cancel", base, page);
     (success_url, cancel_url)
 }
 
@@ -308,7 +650,7 @@ fn metadata_product_label(metadata: &Value, key: &str) -> Option<String> {
 async fn create_checkout_session(
     state: &ServiceState,
     command: &CheckoutCommand,
-) -> Result<StripeCheckoutSession, String> {
+) -> CheckoutResult<StripeCheckoutSession> {
     let CheckoutCommand::CreateSession {
         request_id,
         user_id,
@@ -319,17 +661,19 @@ async fn create_checkout_session(
         purpose,
         metadata,
         customer_email,
+        coupon,
         ..
     } = command;
 
     if state.stripe_secret.is_empty() {
-        return Err("Stripe secret key is not configured".to_string());
+        return Err(CheckoutError::stripe_not_configured());
     }
 
     if *amount_cents <= 0 {
-        return Err("Amount must be positive".to_string());
+        return Err(CheckoutError::Validation("Amount must be positive"));
     }
 
+    let expires_at = Utc::now() + state.session_ttl;
     let product_name = metadata_product_label(metadata, "product_name")
         .unwrap_or_else(|| "Checkout".to_string());
     let description = metadata_product_label(metadata, "description")
@@ -366,46 +710,132 @@ async fn create_checkout_session(
         ("metadata[request_id]".to_string(), request_id.to_string()),
         ("metadata[purpose]".to_string(), purpose.to_string()),
         ("metadata[currency]".to_string(), currency.to_string()),
+        ("expires_at".to_string(), expires_at.timestamp().to_string()),
     ];
 
     metadata_to_params(metadata, &mut params);
 
-    if let Some(email) = customer_email.as_ref().filter(|val| !val.is_empty()) {
-        params.push(("customer_email".to_string(), email.to_string()));
+    if let Some(coupon) = coupon {
+        params.extend(coupons::session_coupon_params(coupon));
+    }
+
+    // Open the session for the user's Stripe Customer so saved cards are
+    // offered; Stripe rejects customer_email together with customer
+    match customers::ensure_customer(state, *user_id, customer_email.as_deref()).await {
+        Ok(customer_id) => params.extend(customers::session_customer_params(&customer_id)),
+        Err(err) => {
+            warn!(
+                request_id = %request_id,
+                user_id = %user_id,
+                error = %err,
+                "Stripe customer unavailable, checkout will not offer saved cards"
+            );
+            if let Some(email) = customer_email.as_ref().filter(|val| !val.is_empty()) {
+                params.push(("customer_email".to_string(), email.to_string()));
+            }
+        }
     }
 
-    let response = state
+    let request = state
         .http_client
-        .post("https://api.stripe.com/v1/checkout/sessions")
+        .post(stripe::api_url(&state.stripe_api_base, "/v1/checkout/sessions"))
         .bearer_auth(&state.stripe_secret)
-        .form(&params)
-        .send()
-        .await
-        .map_err(|err| format!("Stripe request failed: {}", err))?;
+        .form(&params);
+    let response = stripe::ensure_success(stripe::send(request).await?).await?;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Stripe session creation failed: {} {}", status, body));
-    }
-
-    let session: StripeCheckoutSession = response
+    let mut session: StripeCheckoutSession = response
         .json()
         .await
-        .map_err(|err| format!("Stripe response invalid: {}", err))?;
+        .map_err(CheckoutError::StripeResponse)?;
+    session.expires_at.get_or_insert(expires_at.timestamp());
 
     Ok(session)
 }
 
+/// Record a freshly created session as open, with its expiry, so the sweeper
+/// and `GET /sessions/{id}/status` know about it before any webhook arrives
+async fn record_open_session(
+    state: &ServiceState,
+    command: &CheckoutCommand,
+    session: &StripeCheckoutSession,
+) {
+    let CheckoutCommand::CreateSession {
+        request_id,
+        user_id,
+        amount_cents,
+        currency,
+        purpose,
+        metadata,
+        ..
+    } = command;
+
+    if let Err(err) = db::upsert_session_created(
+        &state.db,
+        request_id,
+        *user_id,
+        *amount_cents,
+        currency,
+        purpose,
+        &session.id,
+        session.url.as_deref(),
+        session.expires_at(),
+        metadata,
+    )
+    .await
+    {
+        // The webhook still records the outcome; only the countdown is lost
+        warn!(
+            request_id = %request_id,
+            session_id = %session.id,
+            error = %err,
+            "Failed to record open checkout session"
+        );
+    }
+}
+
 /// Handle a checkout request from the "checkout.requests" topic
-/// Creates a Stripe session and publishes result to "checkout.finished" topic
-async fn handle_checkout_request(state: &ServiceState, request: CheckoutRequestEvent) {
+/// Creates a Stripe session and answers on the "checkout.finished" topic, keyed
+/// and correlated by request_id: `session_created` with the session URL, or
+/// `session_failed` when no session could be opened. Failures to open the
+/// session are answered with `session_failed`; only a failure to publish the
+/// answer is returned.
+async fn handle_checkout_request(
+    state: &ServiceState,
+    request: CheckoutRequestEvent,
+) -> CheckoutResult<()> {
     let request_id = request.request_id.clone();
     let user_id = request.user_id;
     let amount_cents = request.amount_cents;
     let currency = request.currency.clone();
     let purpose = request.purpose.clone();
 
+    match limits::admit_session(state, user_id, &purpose, amount_cents).await {
+        Ok(Ok(())) => {}
+        Ok(Err(rejection)) => {
+            limits::report_rejection(
+                state,
+                &request_id,
+                user_id,
+                amount_cents,
+                &currency,
+                &purpose,
+                rejection,
+            )
+            .await;
+            return publish_session_failed(state, &request, rejection.code(), rejection.message())
+                .await;
+        }
+        Err(err) => {
+            warn!(
+                request_id = %request_id,
+                user_id = %user_id,
+                error = %err,
+                "Failed to check session limits"
+            );
+            return publish_session_failed(state, &request, err.code(), err.public_message()).await;
+        }
+    }
+
     // Convert CheckoutRequestEvent to CheckoutCommand for reuse of create_checkout_session
     let metadata = json!({
         "coins": amount_cents / 100,
@@ -417,7 +847,6 @@ async fn handle_checkout_request(state: &ServiceState, request: CheckoutRequestE
 
     let command = CheckoutCommand::CreateSession {
         request_id: request_id.clone(),
-        service_token: state.service_token.clone(), // We validate internally
         user_id,
         amount_cents,
         currency: currency.clone(),
@@ -427,46 +856,129 @@ async fn handle_checkout_request(state: &ServiceState, request: CheckoutRequestE
         metadata: metadata.clone(),
         requested_at: request.timestamp.clone(),
         customer_email: None,
+        coupon: None,
     };
 
-    // Create Stripe session - don't store in DB yet, wait for webhook
-    match create_checkout_session(state, &command).await {
-        Ok(session) => {
-            let session_url = session.url.clone().unwrap_or_default();
-
-            // Don't create DB row or publish checkout_finished here
-            // DB row and checkout_finished event are created when webhook fires
-            info!(
+    let session = match create_checkout_session(state, &command).await {
+        Ok(session) => session,
+        Err(err) => {
+            warn!(
                 request_id = %request_id,
                 user_id = %user_id,
-                session_id = %session.id,
-                "Stripe session created via Kafka flow, awaiting webhook for payment completion"
+                error = %err,
+                "Failed to create Stripe session via Kafka flow"
             );
-
-            // Note: If using this Kafka flow, the caller needs another way to get the URL
-            // The recommended approach is to use the HTTP endpoint directly which returns the URL
-            let _ = session_url; // URL is created but not published - use HTTP flow instead
+            if let Err(db_err) = db::upsert_session_failed(
+                &state.db,
+                &request_id,
+                user_id,
+                amount_cents,
+                &currency,
+                &purpose,
+                &err.to_string(),
+                &metadata,
+            )
+            .await
+            {
+                warn!(request_id = %request_id, error = %db_err, "Failed to record session failure");
+            }
+            return publish_session_failed(state, &request, err.code(), err.public_message()).await;
         }
-        Err(error_message) => {
-            // Log failure but don't create DB row
+    };
+
+    let session_url = match session.url.clone() {
+        Some(url) if !url.is_empty() => url,
+        _ => {
+            let err = CheckoutError::MissingSessionUrl {
+                session_id: session.id.clone(),
+            };
             warn!(
                 request_id = %request_id,
                 user_id = %user_id,
-                error = %error_message,
-                "Failed to create Stripe session via Kafka flow"
+                error = %err,
+                "Stripe session URL missing"
             );
+            return publish_session_failed(state, &request, err.code(), err.public_message()).await;
         }
-    }
+    };
+
+    record_open_session(state, &command, &session).await;
+
+    let finished_event = CheckoutFinishedEvent::session_created(
+        request_id.clone(),
+        user_id,
+        amount_cents,
+        currency,
+        purpose,
+        session.id.clone(),
+        session_url,
+        session.expires_at(),
+    );
+    state
+        .producer
+        .send_finished_event(&finished_event, Some(&request_id))
+        .await?;
+
+    info!(
+        request_id = %request_id,
+        user_id = %user_id,
+        session_id = %session.id,
+        "Stripe session created via Kafka flow, awaiting webhook for payment completion"
+    );
+    Ok(())
+}
+
+/// Tell the requesting service that no session was opened for its request,
+/// and why (`error_code`)
+async fn publish_session_failed(
+    state: &ServiceState,
+    request: &CheckoutRequestEvent,
+    code: &str,
+    message: &str,
+) -> CheckoutResult<()> {
+    let event = CheckoutFinishedEvent::session_failed(
+        request.request_id.clone(),
+        request.user_id,
+        request.amount_cents,
+        request.currency.clone(),
+        request.purpose.clone(),
+        code,
+        message.to_string(),
+    );
+
+    state
+        .producer
+        .send_finished_event(&event, Some(&request.request_id))
+        .await
+}
+
+/// Deserialize a consumed message, tagging failures with the topic
+fn decode_payload<T: serde::de::DeserializeOwned>(msg: &BorrowedMessage<'_>) -> CheckoutResult<T> {
+    let payload = msg.payload().ok_or_else(|| CheckoutError::EmptyPayload {
+        topic: msg.topic().to_string(),
+    })?;
+
+    serde_json::from_slice(payload).map_err(|source| CheckoutError::InvalidPayload {
+        topic: msg.topic().to_string(),
+        source,
+    })
+}
+
+/// Value of a message header as text
+fn header_value(msg: &BorrowedMessage<'_>, key: &str) -> Option<String> {
+    msg.headers()?
+        .iter()
+        .find(|header| header.key == key)?
+        .value
+        .map(|value| String::from_utf8_lossy(value).into_owned())
 }
 
 /// Process a message from the "checkout.requests" topic
 async fn process_checkout_request(
     state: &ServiceState,
     msg: &BorrowedMessage<'_>,
-) -> Result<(), String> {
-    let payload = msg.payload().ok_or_else(|| "Empty payload".to_string())?;
-    let request: CheckoutRequestEvent =
-        serde_json::from_slice(payload).map_err(|err| err.to_string())?;
+) -> CheckoutResult<()> {
+    let request: CheckoutRequestEvent = decode_payload(msg)?;
 
     info!(
         request_id = %request.request_id,
@@ -475,8 +987,7 @@ async fn process_checkout_request(
         "Processing checkout request from checkout.requests topic"
     );
 
-    handle_checkout_request(state, request).await;
-    Ok(())
+    handle_checkout_request(state, request).await
 }
 
 /// Event received from game.participation topic when a player is selected for a game
@@ -549,10 +1060,8 @@ async fn handle_bigger_dice_participation(state: &ServiceState, event: GameParti
 async fn process_bigger_dice_participation(
     state: &ServiceState,
     msg: &BorrowedMessage<'_>,
-) -> Result<(), String> {
-    let payload = msg.payload().ok_or_else(|| "Empty payload".to_string())?;
-    let event: GameParticipationEvent =
-        serde_json::from_slice(payload).map_err(|err| err.to_string())?;
+) -> CheckoutResult<()> {
+    let event: GameParticipationEvent = decode_payload(msg)?;
 
     info!(
         event_id = %event.event_id,
@@ -638,10 +1147,8 @@ async fn handle_bigger_dice_prize_win(state: &ServiceState, event: GamePrizeWinE
 async fn process_bigger_dice_prize_win(
     state: &ServiceState,
     msg: &BorrowedMessage<'_>,
-) -> Result<(), String> {
-    let payload = msg.payload().ok_or_else(|| "Empty payload".to_string())?;
-    let event: GamePrizeWinEvent =
-        serde_json::from_slice(payload).map_err(|err| err.to_string())?;
+) -> CheckoutResult<()> {
+    let event: GamePrizeWinEvent = decode_payload(msg)?;
 
     info!(
         event_id = %event.event_id,
@@ -711,10 +1218,8 @@ async fn handle_tic_tac_toe_participation(state: &ServiceState, event: GameParti
 async fn process_tic_tac_toe_participation(
     state: &ServiceState,
     msg: &BorrowedMessage<'_>,
-) -> Result<(), String> {
-    let payload = msg.payload().ok_or_else(|| "Empty payload".to_string())?;
-    let event: GameParticipationEvent =
-        serde_json::from_slice(payload).map_err(|err| err.to_string())?;
+) -> CheckoutResult<()> {
+    let event: GameParticipationEvent = decode_payload(msg)?;
 
     info!(
         event_id = %event.event_id,
@@ -785,10 +1290,8 @@ async fn handle_tic_tac_toe_prize_win(state: &ServiceState, event: GamePrizeWinE
 async fn process_tic_tac_toe_prize_win(
     state: &ServiceState,
     msg: &BorrowedMessage<'_>,
-) -> Result<(), String> {
-    let payload = msg.payload().ok_or_else(|| "Empty payload".to_string())?;
-    let event: GamePrizeWinEvent =
-        serde_json::from_slice(payload).map_err(|err| err.to_string())?;
+) -> CheckoutResult<()> {
+    let event: GamePrizeWinEvent = decode_payload(msg)?;
 
     info!(
         event_id = %event.event_id,
@@ -802,15 +1305,25 @@ async fn process_tic_tac_toe_prize_win(
     Ok(())
 }
 
-async fn run_consumer(state: Arc<ServiceState>, config: &AppConfig) -> Result<(), String> {
-    let consumer: StreamConsumer = ClientConfig::new()
+/// Consume checkout requests and game prize events.
+///
+/// Messages are handled one at a time between two polls, so a rebalance
+/// (which runs inside a poll) never revokes a partition mid-message; before
+/// partitions are revoked the consumer context commits synchronously (see
+/// the `kafka_rebalance` crate) so their next owner does not handle the last
+/// messages again.
+async fn run_consumer(state: Arc<ServiceState>, config: &AppConfig) -> CheckoutResult<()> {
+    let context = RebalanceContext::new(
+        state.rebalance.clone(),
+        Duration::from_millis(config.kafka_rebalance_drain_timeout_ms),
+    );
+    let consumer: StreamConsumer<RebalanceContext> = ClientConfig::new()
         .set("bootstrap.servers", &config.kafka_bootstrap)
         .set("group.id", &config.kafka_group_id)
         .set("auto.offset.reset", "earliest")
         .set("enable.auto.commit", "true")
         .set("session.timeout.ms", "30000")
-        .create()
-        .map_err(|err| err.to_string())?;
+        .create_with_context(context)?;
 
     // Subscribe to checkout requests and game event topics
     consumer
@@ -820,8 +1333,7 @@ async fn run_consumer(state: Arc<ServiceState>, config: &AppConfig) -> Result<()
             BIGGER_DICE_WIN_PRIZE_TOPIC,
             TIC_TAC_TOE_PARTICIPATION_TOPIC,
             TIC_TAC_TOE_WIN_PRIZE_TOPIC,
-        ])
-        .map_err(|err| err.to_string())?;
+        ])?;
 
     info!(
         "Checkout consumer subscribed to topics: {}, {}, {}, {}, {}",
@@ -837,23 +1349,42 @@ async fn run_consumer(state: Arc<ServiceState>, config: &AppConfig) -> Result<()
             Ok(msg) => {
                 // Route message to appropriate handler based on topic
                 let topic = msg.topic();
-                let result = if topic == CHECKOUT_REQUESTS_TOPIC {
-                    process_checkout_request(&state, &msg).await
-                } else if topic == BIGGER_DICE_PARTICIPATION_TOPIC {
-                    process_bigger_dice_participation(&state, &msg).await
-                } else if topic == BIGGER_DICE_WIN_PRIZE_TOPIC {
-                    process_bigger_dice_prize_win(&state, &msg).await
-                } else if topic == TIC_TAC_TOE_PARTICIPATION_TOPIC {
-                    process_tic_tac_toe_participation(&state, &msg).await
-                } else if topic == TIC_TAC_TOE_WIN_PRIZE_TOPIC {
-                    process_tic_tac_toe_prize_win(&state, &msg).await
-                } else {
-                    warn!("Unknown topic: {}", topic);
-                    Ok(())
-                };
-
-                if let Err(err) = result {
-                    error!("Failed to process message from {}: {}", topic, err);
+                let _in_flight = state
+                    .rebalance
+                    .start(topic, msg.partition(), state.rebalance.generation());
+                // Log under the blazing_sun request that produced the message
+                let span = tracing::info_span!(
+                    "kafka_event",
+                    topic = %topic,
+                    request_id = header_value(&msg, "request_id").as_deref(),
+                );
+                let result = async {
+                    if topic == CHECKOUT_REQUESTS_TOPIC {
+                        process_checkout_request(&state, &msg).await
+                    } else if topic == BIGGER_DICE_PARTICIPATION_TOPIC {
+                        process_bigger_dice_participation(&state, &msg).await
+                    } else if topic == BIGGER_DICE_WIN_PRIZE_TOPIC {
+                        process_bigger_dice_prize_win(&state, &msg).await
+                    } else if topic == TIC_TAC_TOE_PARTICIPATION_TOPIC {
+                        process_tic_tac_toe_participation(&state, &msg).await
+                    } else if topic == TIC_TAC_TOE_WIN_PRIZE_TOPIC {
+                        process_tic_tac_toe_prize_win(&state, &msg).await
+                    } else {
+                        warn!("Unknown topic: {}", topic);
+                        Ok(())
+                    }
+                }
+                .instrument(span)
+                .await;
+
+                match result {
+                    Ok(()) => {}
+                    Err(err) if err.is_retryable() => {
+                        error!("Failed to process message from {}: {}", topic, err);
+                    }
+                    Err(err) => {
+                        warn!("Skipping malformed message from {}: {}", topic, err);
+                    }
                 }
                 let _ = consumer.commit_message(&msg, CommitMode::Async);
             }
@@ -869,6 +1400,19 @@ async fn health() -> HttpResponse {
     HttpResponse::Ok().json(BaseResponse::success("ok"))
 }
 
+/// Prometheus scrape endpoint
+async fn metrics(state: web::Data<Arc<ServiceState>>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(format!(
+            "{}{}{}{}",
+            state.service_auth_rejections.render_prometheus("checkout"),
+            state.webhook_rejections.render_prometheus("checkout"),
+            state.producer.producer.render_prometheus("checkout"),
+            state.rebalance.render_prometheus("checkout")
+        ))
+}
+
 async fn transactions(
     state: web::Data<Arc<ServiceState>>,
     req: HttpRequest,
@@ -894,12 +1438,20 @@ async fn transactions(
     };
 
     let limit = query.limit.unwrap_or(50).clamp(1, 200);
-    let offset = query.offset.unwrap_or(0).max(0);
+    let cursor = match query.cursor.as_deref() {
+        Some(value) => match Cursor::decode(value) {
+            Some(cursor) => Some(cursor),
+            None => {
+                return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor"));
+            }
+        },
+        None => None,
+    };
 
-    let transactions = match db::fetch_transactions_by_user(&state.db, claims.sub, limit, offset)
+    let rows = match db::fetch_transactions_page(&state.db, claims.sub, cursor.as_ref(), limit)
         .await
     {
-        Ok(transactions) => transactions,
+        Ok(rows) => rows,
         Err(err) => {
             error!("Failed to fetch transactions: {}", err);
             return HttpResponse::InternalServerError()
@@ -907,104 +1459,654 @@ async fn transactions(
         }
     };
 
+    let page = paginate(rows, cursor.as_ref(), limit as usize, |tx| (tx.created_at, tx.id));
+
     HttpResponse::Ok().json(TransactionsResponse {
         base: BaseResponse::success("Transactions retrieved"),
-        transactions,
+        transactions: page.items,
+        next_cursor: page.next_cursor,
+        prev_cursor: page.prev_cursor,
     })
 }
 
-async fn create_session(
+/// Internal: a user's transactions for the unified payments history in blazing_sun.
+/// Authenticated with a signed service token instead of a user JWT.
+async fn internal_user_transactions(
     state: web::Data<Arc<ServiceState>>,
     req: HttpRequest,
-    body: web::Json<CheckoutSessionRequest>,
+    path: web::Path<i64>,
+    query: web::Query<InternalTransactionsQuery>,
 ) -> HttpResponse {
-    let token = match extract_token(&req) {
-        Some(token) => token,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+    let provided = extract_service_token(&req).unwrap_or_default();
+    if let Err(err) = validate_service_token(state.service_auth.as_ref(), &provided) {
+        if let CheckoutError::InvalidServiceToken(reason) = &err {
+            state.service_auth_rejections.record(*reason);
         }
-    };
-
-    if state.jwt_secret.is_empty() {
-        return HttpResponse::InternalServerError()
-            .json(BaseResponse::error("JWT secret not configured"));
+        warn!("Rejected internal transactions request: {}", err);
+        return HttpResponse::build(err.status_code())
+            .json(BaseResponse::error(err.public_message()));
     }
 
-    let claims = match decode_token(&token, &state.jwt_secret) {
-        Ok(claims) => claims,
-        Err(_) => {
-            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+    let user_id = path.into_inner();
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+
+    match db::fetch_transactions_by_user_before(&state.db, user_id, query.before, limit).await {
+        Ok(transactions) => HttpResponse::Ok().json(TransactionsResponse {
+            base: BaseResponse::success("Transactions retrieved"),
+            transactions,
+            next_cursor: None,
+            prev_cursor: None,
+        }),
+        Err(err) => {
+            error!("Failed to fetch transactions for user {}: {}", user_id, err);
+            HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load transactions"))
         }
-    };
+    }
+}
+
+/// Error response unless the caller's JWT carries admin permissions
+fn require_admin(state: &ServiceState, req: &HttpRequest) -> Result<(), HttpResponse> {
+    authorize_admin(extract_token(req), &state.jwt_secret)
+}
+
+/// Only admins and super admins (`rbac::Role::Admin`) pass; affiliates and
+/// moderators do not
+fn authorize_admin(token: Option<String>, jwt_secret: &str) -> Result<(), HttpResponse> {
+    let token = token
+        .ok_or_else(|| HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized")))?;
 
-    if body.amount < 1 {
-        return HttpResponse::BadRequest()
-            .json(BaseResponse::error("Amount must be at least 1"));
+    if jwt_secret.is_empty() {
+        return Err(HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured")));
     }
 
-    let amount_cents = match body.amount.checked_mul(100) {
-        Some(value) => value,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Amount is too large"));
+    let claims = decode_token(&token, jwt_secret)
+        .map_err(|_| HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token")))?;
+
+    if rbac::Role::from_level(i32::from(claims.permissions)) != rbac::Role::Admin {
+        return Err(HttpResponse::Forbidden().json(BaseResponse::error("Forbidden")));
+    }
+
+    Ok(())
+}
+
+/// Admin: discrepancies found by a Stripe reconciliation run
+async fn reconciliation_report(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    query: web::Query<ReconciliationReportQuery>,
+) -> HttpResponse {
+    if let Err(response) = require_admin(&state, &req) {
+        return response;
+    }
+
+    let run = match db::fetch_reconciliation_run(&state.db, query.run_id).await {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(BaseResponse::error("No reconciliation run found"));
+        }
+        Err(err) => {
+            error!("Failed to fetch reconciliation run: {}", err);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load reconciliation report"));
         }
     };
 
-    let request_id = Uuid::new_v4().to_string();
-    let (success_url, cancel_url) = build_balance_urls(&request_base_url(&req));
-    let metadata = json!({
-        "coins": body.amount,
-        "balance_cents": amount_cents,
-        "product_name": "Coins",
-        "description": "Account top-up",
-        "source": "balance",
-    });
+    match db::fetch_reconciliation_discrepancies(&state.db, run.id).await {
+        Ok(discrepancies) => HttpResponse::Ok().json(ReconciliationReportResponse {
+            base: BaseResponse::success("Reconciliation report retrieved"),
+            run,
+            discrepancies,
+        }),
+        Err(err) => {
+            error!("Failed to fetch reconciliation discrepancies for run {}: {}", run.id, err);
+            HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load reconciliation report"))
+        }
+    }
+}
 
-    let command = CheckoutCommand::CreateSession {
-        request_id: request_id.clone(),
-        service_token: state.service_token.clone(),
-        user_id: claims.sub,
-        amount_cents,
-        currency: "eur".to_string(),
+/// Admin: search transactions by Stripe ids, user, status and creation date,
+/// as a JSON page or a CSV export of the whole filtered set.
+/// Authenticated with an admin token (`X-Admin-Token`) instead of a user JWT.
+async fn admin_transactions(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    query: web::Query<transaction_search::SearchQuery>,
+) -> HttpResponse {
+    let provided = req
+        .headers()
+        .get(transaction_search::HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let Some(token_name) = state.admin_tokens.verify(provided) else {
+        warn!("Rejected admin transaction search: invalid or missing admin token");
+        return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+    };
+
+    let filters = match query.filters() {
+        Ok(filters) => filters,
+        Err(message) => return HttpResponse::BadRequest().json(BaseResponse::error(message)),
+    };
+    let format = match query.format() {
+        Ok(format) => format,
+        Err(message) => return HttpResponse::BadRequest().json(BaseResponse::error(message)),
+    };
+
+    info!(admin_token = token_name, filters = ?filters, format = ?format, "Admin transaction search");
+
+    if format == transaction_search::Format::Csv {
+        let mut rows = match db::search_transactions(
+            &state.db,
+            &filters,
+            None,
+            transaction_search::MAX_EXPORT_ROWS,
+        )
+        .await
+        {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Failed to export transactions: {}", err);
+                return HttpResponse::InternalServerError()
+                    .json(BaseResponse::error("Failed to load transactions"));
+            }
+        };
+        let truncated = rows.len() as i64 > transaction_search::MAX_EXPORT_ROWS;
+        rows.truncate(transaction_search::MAX_EXPORT_ROWS as usize);
+
+        return HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                "attachment; filename=\"transactions.csv\"",
+            ))
+            .insert_header(("X-Export-Truncated", if truncated { "true" } else { "false" }))
+            .body(transaction_search::to_csv(&rows));
+    }
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let cursor = match query.cursor.as_deref() {
+        Some(value) => match Cursor::decode(value) {
+            Some(cursor) => Some(cursor),
+            None => {
+                return HttpResponse::BadRequest().json(BaseResponse::error("Invalid cursor"));
+            }
+        },
+        None => None,
+    };
+
+    let rows = match db::search_transactions(&state.db, &filters, cursor.as_ref(), limit).await {
+        Ok(rows) => rows,
+        Err(err) => {
+            error!("Failed to search transactions: {}", err);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load transactions"));
+        }
+    };
+
+    let page = paginate(rows, cursor.as_ref(), limit as usize, |tx| (tx.created_at, tx.id));
+
+    HttpResponse::Ok().json(TransactionsResponse {
+        base: BaseResponse::success("Transactions retrieved"),
+        transactions: page.items,
+        next_cursor: page.next_cursor,
+        prev_cursor: page.prev_cursor,
+    })
+}
+
+/// Coupon write failures: a taken code is a conflict, anything else a 500
+fn coupon_write_error(err: sqlx::Error) -> HttpResponse {
+    if err
+        .as_database_error()
+        .is_some_and(|db_err| db_err.is_unique_violation())
+    {
+        return HttpResponse::Conflict().json(BaseResponse::error("Coupon code already exists"));
+    }
+
+    error!("Failed to save coupon: {}", err);
+    HttpResponse::InternalServerError().json(BaseResponse::error("Failed to save coupon"))
+}
+
+async fn list_coupons(state: web::Data<Arc<ServiceState>>, req: HttpRequest) -> HttpResponse {
+    if let Err(response) = require_admin(&state, &req) {
+        return response;
+    }
+
+    match db::fetch_coupons(&state.db).await {
+        Ok(coupons) => HttpResponse::Ok().json(CouponsResponse {
+            base: BaseResponse::success("Coupons retrieved"),
+            coupons,
+        }),
+        Err(err) => {
+            error!("Failed to fetch coupons: {}", err);
+            HttpResponse::InternalServerError().json(BaseResponse::error("Failed to load coupons"))
+        }
+    }
+}
+
+async fn get_coupon(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    if let Err(response) = require_admin(&state, &req) {
+        return response;
+    }
+
+    match db::fetch_coupon(&state.db, path.into_inner()).await {
+        Ok(Some(coupon)) => HttpResponse::Ok().json(CouponResponse {
+            base: BaseResponse::success("Coupon retrieved"),
+            coupon,
+        }),
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse::error("Coupon not found")),
+        Err(err) => {
+            error!("Failed to fetch coupon: {}", err);
+            HttpResponse::InternalServerError().json(BaseResponse::error("Failed to load coupons"))
+        }
+    }
+}
+
+async fn create_coupon(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    body: web::Json<coupons::CouponRequest>,
+) -> HttpResponse {
+    if let Err(response) = require_admin(&state, &req) {
+        return response;
+    }
+    if let Some(response) = body.validation_response() {
+        return response;
+    }
+
+    match db::insert_coupon(&state.db, &body).await {
+        Ok(coupon) => {
+            info!(coupon_id = %coupon.id, code = %coupon.code, "Coupon created");
+            HttpResponse::Created().json(CouponResponse {
+                base: BaseResponse::success("Coupon created"),
+                coupon,
+            })
+        }
+        Err(err) => coupon_write_error(err),
+    }
+}
+
+async fn update_coupon(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    body: web::Json<coupons::CouponRequest>,
+) -> HttpResponse {
+    if let Err(response) = require_admin(&state, &req) {
+        return response;
+    }
+    if let Some(response) = body.validation_response() {
+        return response;
+    }
+
+    match db::update_coupon(&state.db, path.into_inner(), &body).await {
+        Ok(Some(coupon)) => {
+            info!(coupon_id = %coupon.id, code = %coupon.code, "Coupon updated");
+            HttpResponse::Ok().json(CouponResponse {
+                base: BaseResponse::success("Coupon updated"),
+                coupon,
+            })
+        }
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse::error("Coupon not found")),
+        Err(err) => coupon_write_error(err),
+    }
+}
+
+async fn delete_coupon(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+) -> HttpResponse {
+    if let Err(response) = require_admin(&state, &req) {
+        return response;
+    }
+
+    let coupon_id = path.into_inner();
+    match db::delete_coupon(&state.db, coupon_id).await {
+        Ok(true) => {
+            info!(coupon_id = %coupon_id, "Coupon deleted");
+            HttpResponse::Ok().json(BaseResponse::success("Coupon deleted"))
+        }
+        Ok(false) => HttpResponse::NotFound().json(BaseResponse::error("Coupon not found")),
+        Err(err) => {
+            error!("Failed to delete coupon {}: {}", coupon_id, err);
+            HttpResponse::InternalServerError().json(BaseResponse::error("Failed to delete coupon"))
+        }
+    }
+}
+
+/// Cards saved on the caller's Stripe Customer
+async fn payment_methods(state: web::Data<Arc<ServiceState>>, req: HttpRequest) -> HttpResponse {
+    let token = match extract_token(&req) {
+        Some(token) => token,
+        None => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        }
+    };
+
+    if state.jwt_secret.is_empty() {
+        return HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured"));
+    }
+
+    let claims = match decode_token(&token, &state.jwt_secret) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+        }
+    };
+
+    // Users who never checked out have no customer and therefore no cards
+    let customer_id = match db::fetch_customer_id(&state.db, claims.sub).await {
+        Ok(Some(customer_id)) => customer_id,
+        Ok(None) => {
+            return HttpResponse::Ok().json(PaymentMethodsResponse {
+                base: BaseResponse::success("Payment methods retrieved"),
+                payment_methods: Vec::new(),
+            });
+        }
+        Err(err) => {
+            error!("Failed to fetch Stripe customer for user {}: {}", claims.sub, err);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load payment methods"));
+        }
+    };
+
+    match customers::list_payment_methods(&state, &customer_id).await {
+        Ok(payment_methods) => HttpResponse::Ok().json(PaymentMethodsResponse {
+            base: BaseResponse::success("Payment methods retrieved"),
+            payment_methods,
+        }),
+        Err(err) => {
+            warn!(user_id = %claims.sub, error = %err, "Failed to list payment methods");
+            HttpResponse::build(err.status_code()).json(BaseResponse::error(err.public_message()))
+        }
+    }
+}
+
+/// Start saving a card for the caller without charging it. The frontend
+/// confirms the returned client secret with Stripe.js.
+async fn create_setup_intent(state: web::Data<Arc<ServiceState>>, req: HttpRequest) -> HttpResponse {
+    let token = match extract_token(&req) {
+        Some(token) => token,
+        None => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        }
+    };
+
+    if state.jwt_secret.is_empty() {
+        return HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured"));
+    }
+
+    let claims = match decode_token(&token, &state.jwt_secret) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+        }
+    };
+
+    let intent = match customers::ensure_customer(&state, claims.sub, None).await {
+        Ok(customer_id) => customers::create_setup_intent(&state, claims.sub, &customer_id).await,
+        Err(err) => Err(err),
+    };
+
+    match intent {
+        Ok(intent) => {
+            info!(user_id = %claims.sub, setup_intent_id = %intent.id, "Stripe setup intent created");
+            HttpResponse::Ok().json(SetupIntentResponse {
+                base: BaseResponse::success("Setup intent created"),
+                setup_intent_id: intent.id,
+                client_secret: intent.client_secret,
+            })
+        }
+        Err(err) => {
+            warn!(user_id = %claims.sub, error = %err, "Failed to create setup intent");
+            HttpResponse::build(err.status_code()).json(BaseResponse::error(err.public_message()))
+        }
+    }
+}
+
+/// Whether the caller has premium, from their latest subscription
+async fn subscription_status(state: web::Data<Arc<ServiceState>>, req: HttpRequest) -> HttpResponse {
+    let token = match extract_token(&req) {
+        Some(token) => token,
+        None => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        }
+    };
+
+    if state.jwt_secret.is_empty() {
+        return HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured"));
+    }
+
+    let claims = match decode_token(&token, &state.jwt_secret) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+        }
+    };
+
+    match db::fetch_subscription_by_user(&state.db, claims.sub).await {
+        Ok(subscription) => HttpResponse::Ok().json(SubscriptionStatusResponse {
+            base: BaseResponse::success("Subscription retrieved"),
+            premium: subscription.as_ref().is_some_and(|sub| sub.entitled),
+            subscription_status: subscription.as_ref().map(|sub| sub.stripe_status.clone()),
+            current_period_end: subscription.as_ref().and_then(|sub| sub.current_period_end),
+            cancel_at_period_end: subscription.is_some_and(|sub| sub.cancel_at_period_end),
+        }),
+        Err(err) => {
+            error!("Failed to fetch subscription for user {}: {}", claims.sub, err);
+            HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load subscription"))
+        }
+    }
+}
+
+/// Open a Stripe subscription checkout for the premium membership. Callers
+/// who already have premium are refused.
+async fn create_subscription(state: web::Data<Arc<ServiceState>>, req: HttpRequest) -> HttpResponse {
+    let token = match extract_token(&req) {
+        Some(token) => token,
+        None => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        }
+    };
+
+    if state.jwt_secret.is_empty() {
+        return HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured"));
+    }
+
+    let claims = match decode_token(&token, &state.jwt_secret) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+        }
+    };
+
+    match db::fetch_subscription_by_user(&state.db, claims.sub).await {
+        Ok(Some(subscription)) if subscription.entitled => {
+            return HttpResponse::Conflict().json(BaseResponse::error("Premium is already active"));
+        }
+        Ok(_) => {}
+        Err(err) => {
+            error!("Failed to fetch subscription for user {}: {}", claims.sub, err);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load subscription"));
+        }
+    }
+
+    let (success_url, cancel_url) = build_premium_urls(&request_base_url(&req));
+    let session =
+        match subscriptions::create_session(&state, claims.sub, &success_url, &cancel_url).await {
+            Ok(session) => session,
+            Err(err) => {
+                warn!(user_id = %claims.sub, error = %err, "Failed to create subscription session");
+                return HttpResponse::build(err.status_code())
+                    .json(BaseResponse::error(err.public_message()));
+            }
+        };
+
+    let Some(session_url) = session.url.clone().filter(|url| !url.is_empty()) else {
+        let err = CheckoutError::MissingSessionUrl {
+            session_id: session.id.clone(),
+        };
+        warn!(user_id = %claims.sub, error = %err, "Stripe subscription session URL missing");
+        return HttpResponse::build(err.status_code())
+            .json(BaseResponse::error(err.public_message()));
+    };
+
+    info!(user_id = %claims.sub, session_id = %session.id, "Stripe subscription session created");
+
+    let expires_at = session.expires_at();
+    HttpResponse::Ok().json(CheckoutSessionResponse {
+        base: BaseResponse::success("Subscription checkout created"),
+        session_id: session.id,
+        url: session_url,
+        expires_at,
+        expires_in_seconds: expires_at.map(|at| expiry::seconds_remaining(at, Utc::now())),
+    })
+}
+
+async fn create_session(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    body: web::Json<CheckoutSessionRequest>,
+) -> HttpResponse {
+    let token = match extract_token(&req) {
+        Some(token) => token,
+        None => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        }
+    };
+
+    if state.jwt_secret.is_empty() {
+        return HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured"));
+    }
+
+    let claims = match decode_token(&token, &state.jwt_secret) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+        }
+    };
+
+    if let Some(response) = body.validation_response() {
+        return response;
+    }
+
+    // Validation guarantees the cent value fits in an i64
+    let amount_cents = body.amount * 100;
+    let currency = "eur";
+    let purpose = "balance_topup";
+    let request_id = Uuid::new_v4().to_string();
+
+    match limits::admit_session(&state, claims.sub, purpose, amount_cents).await {
+        Ok(Ok(())) => {}
+        Ok(Err(rejection)) => {
+            limits::report_rejection(
+                &state,
+                &request_id,
+                claims.sub,
+                amount_cents,
+                currency,
+                purpose,
+                rejection,
+            )
+            .await;
+            return rejection.response();
+        }
+        Err(err) => {
+            warn!(user_id = %claims.sub, error = %err, "Failed to check session limits");
+            return HttpResponse::build(err.status_code())
+                .json(BaseResponse::error(err.public_message()));
+        }
+    }
+
+    let coupon = match &body.coupon {
+        Some(code) => match coupons::apply(&state, code, claims.sub, amount_cents, currency).await {
+            Ok(Ok(coupon)) => Some(coupon),
+            Ok(Err(rejection)) => {
+                return HttpResponse::BadRequest().json(ValidationErrorResponse::new(
+                    "Invalid coupon",
+                    vec![FieldError::new("coupon", rejection.code(), rejection.message())],
+                ));
+            }
+            Err(err) => {
+                warn!(user_id = %claims.sub, error = %err, "Failed to apply coupon");
+                return HttpResponse::build(err.status_code())
+                    .json(BaseResponse::error(err.public_message()));
+            }
+        },
+        None => None,
+    };
+
+    let (success_url, cancel_url) = build_balance_urls(&request_base_url(&req));
+    let metadata = json!({
+        "coins": body.amount,
+        "balance_cents": amount_cents,
+        "product_name": "Coins",
+        "description": "Account top-up",
+        "source": "balance",
+    });
+
+    let command = CheckoutCommand::CreateSession {
+        request_id: request_id.clone(),
+        user_id: claims.sub,
+        amount_cents,
+        currency: currency.to_string(),
         success_url,
         cancel_url,
-        purpose: "balance_topup".to_string(),
+        purpose: purpose.to_string(),
         metadata: metadata.clone(),
         requested_at: Utc::now().to_rfc3339(),
         customer_email: None,
+        coupon,
     };
 
     let session = match create_checkout_session(&state, &command).await {
         Ok(session) => session,
-        Err(error_message) => {
+        Err(err) => {
             // Don't create DB row for failed session creation - only log
             warn!(
                 request_id = %request_id,
                 user_id = %claims.sub,
-                error = %error_message,
+                error = %err,
                 "Failed to create Stripe session"
             );
-            return HttpResponse::BadGateway()
-                .json(BaseResponse::error("Checkout failed"));
+            return HttpResponse::build(err.status_code())
+                .json(BaseResponse::error(err.public_message()));
         }
     };
 
-    let session_url = match session.url {
+    let session_url = match session.url.clone() {
         Some(url) if !url.is_empty() => url,
         _ => {
             // Log the failure but don't create DB row yet - wait for webhook
+            let err = CheckoutError::MissingSessionUrl {
+                session_id: session.id.clone(),
+            };
             warn!(
                 request_id = %request_id,
                 user_id = %claims.sub,
+                error = %err,
                 "Stripe session URL missing"
             );
-            return HttpResponse::BadGateway()
-                .json(BaseResponse::error("Checkout failed"));
+            return HttpResponse::build(err.status_code())
+                .json(BaseResponse::error(err.public_message()));
         }
     };
 
-    // Don't create DB row here - wait for webhook to create it when payment completes
-    // This ensures checkout_transactions only contains completed payments
+    record_open_session(&state, &command, &session).await;
     info!(
         request_id = %request_id,
         user_id = %claims.sub,
@@ -1012,10 +2114,70 @@ async fn create_session(
         "Stripe session created, returning URL to frontend"
     );
 
+    let expires_at = session.expires_at();
     HttpResponse::Ok().json(CheckoutSessionResponse {
         base: BaseResponse::success("Checkout session created"),
         session_id: session.id,
         url: session_url,
+        expires_at,
+        expires_in_seconds: expires_at.map(|at| expiry::seconds_remaining(at, Utc::now())),
+    })
+}
+
+/// Where one of the caller's sessions stands, polled by the frontend while
+/// the user is on Stripe
+async fn session_status(
+    state: web::Data<Arc<ServiceState>>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> HttpResponse {
+    let token = match extract_token(&req) {
+        Some(token) => token,
+        None => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Unauthorized"));
+        }
+    };
+
+    if state.jwt_secret.is_empty() {
+        return HttpResponse::InternalServerError()
+            .json(BaseResponse::error("JWT secret not configured"));
+    }
+
+    let claims = match decode_token(&token, &state.jwt_secret) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return HttpResponse::Unauthorized().json(BaseResponse::error("Invalid token"));
+        }
+    };
+
+    let session_id = path.into_inner();
+    let session = match db::fetch_session_state(&state.db, &session_id, claims.sub).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(BaseResponse::error("Checkout session not found"));
+        }
+        Err(err) => {
+            error!("Failed to fetch checkout session {}: {}", session_id, err);
+            return HttpResponse::InternalServerError()
+                .json(BaseResponse::error("Failed to load checkout session"));
+        }
+    };
+
+    let now = Utc::now();
+    let status = expiry::SessionStatus::of(&session.status, session.expires_at, now);
+    let expires_in_seconds = match (status, session.expires_at) {
+        (expiry::SessionStatus::Open, Some(at)) => expiry::seconds_remaining(at, now),
+        _ => 0,
+    };
+
+    HttpResponse::Ok().json(SessionStatusResponse {
+        base: BaseResponse::success("Checkout session retrieved"),
+        session_id,
+        request_id: session.request_id,
+        session_status: status,
+        expires_at: session.expires_at,
+        expires_in_seconds,
+        completed_at: session.completed_at,
     })
 }
 
@@ -1031,37 +2193,102 @@ async fn stripe_webhook(
         req.headers()
     );
 
-    let signature = match req.headers().get("Stripe-Signature") {
-        Some(header) => match header.to_str() {
-            Ok(value) => value,
-            Err(_) => {
-                return HttpResponse::BadRequest()
-                    .json(BaseResponse::error("Invalid Stripe signature header"));
-            }
-        },
+    let signature = req
+        .headers()
+        .get("Stripe-Signature")
+        .map(|header| header.to_str());
+
+    match process_stripe_webhook(&state, signature, &payload).await {
+        Ok(message) => HttpResponse::Ok().json(BaseResponse::success(message)),
+        Err(err) => {
+            warn!(code = err.code(), error = %err, "Stripe webhook rejected");
+            HttpResponse::build(err.status_code()).json(BaseResponse::error(err.public_message()))
+        }
+    }
+}
+
+/// Verify and apply a Stripe webhook, returning the acknowledgement message.
+/// Malformed deliveries are `Validation` errors (400, Stripe does not retry
+/// them); failures to record the outcome are 500s so Stripe redelivers.
+/// Events already received are acknowledged without being applied again.
+async fn process_stripe_webhook(
+    state: &ServiceState,
+    signature: Option<Result<&str, actix_web::http::header::ToStrError>>,
+    payload: &[u8],
+) -> CheckoutResult<&'static str> {
+    let signature = match signature {
+        Some(Ok(value)) => value,
+        Some(Err(_)) => {
+            state
+                .webhook_rejections
+                .record(webhooks::Rejection::Signature(stripe::SignatureError::MalformedHeader));
+            return Err(CheckoutError::Validation("Invalid Stripe signature header"));
+        }
         None => {
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Missing Stripe signature header"));
+            state.webhook_rejections.record(webhooks::Rejection::MissingHeader);
+            return Err(CheckoutError::Validation("Missing Stripe signature header"));
         }
     };
 
     info!("=== VERIFYING SIGNATURE ===");
-    if !stripe::verify_signature(&payload, signature, &state.stripe_webhook_secret) {
-        warn!("Stripe webhook signature verification failed");
-        return HttpResponse::BadRequest()
-            .json(BaseResponse::error("Stripe signature verification failed"));
+    let secret_index = stripe::verify_signature(
+        payload,
+        signature,
+        &state.webhook.secrets,
+        state.webhook.tolerance_seconds,
+    )
+    .map_err(|err| {
+        state.webhook_rejections.record(webhooks::Rejection::Signature(err));
+        CheckoutError::Validation(err.public_message())
+    })?;
+    if secret_index > 0 {
+        info!("Stripe webhook signed with a previous secret, rotation still in progress");
     }
     info!("=== SIGNATURE VERIFIED ===");
 
-    let event: Value = match serde_json::from_slice(&payload) {
-        Ok(event) => event,
-        Err(err) => {
-            error!("Stripe webhook payload invalid: {}", err);
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Invalid Stripe payload"));
+    let event: Value = serde_json::from_slice(payload)
+        .map_err(|_| CheckoutError::Validation("Invalid Stripe payload"))?;
+
+    let event_id = event
+        .get("id")
+        .and_then(|value| value.as_str())
+        .ok_or(CheckoutError::Validation("Stripe event missing id"))?;
+
+    let claimed = match state.redis.clone() {
+        Some(mut redis) => {
+            match webhooks::claim_event(&mut redis, event_id, state.webhook.replay_ttl_seconds)
+                .await
+            {
+                Ok(true) => Some(redis),
+                Ok(false) => {
+                    state.webhook_rejections.record(webhooks::Rejection::Replayed);
+                    warn!(event_id = %event_id, "Stripe webhook event already received");
+                    return Ok("Event already received");
+                }
+                Err(err) => {
+                    warn!(event_id = %event_id, error = %err, "Stripe webhook replay check failed");
+                    None
+                }
+            }
         }
+        None => None,
     };
 
+    let result = apply_stripe_event(state, &event).await;
+
+    if result.is_err() {
+        if let Some(mut redis) = claimed {
+            if let Err(err) = webhooks::release_event(&mut redis, event_id).await {
+                warn!(event_id = %event_id, error = %err, "Failed to release Stripe event id");
+            }
+        }
+    }
+
+    result
+}
+
+/// Apply a verified Stripe event
+async fn apply_stripe_event(state: &ServiceState, event: &Value) -> CheckoutResult<&'static str> {
     let event_type = event
         .get("type")
         .and_then(|value| value.as_str())
@@ -1069,44 +2296,43 @@ async fn stripe_webhook(
 
     info!("=== WEBHOOK EVENT TYPE: {} ===", event_type);
 
-    if event_type != "checkout.session.completed" {
-        info!("=== IGNORING EVENT (not checkout.session.completed) ===");
-        return HttpResponse::Ok().json(BaseResponse::success("Event ignored"));
+    match event_type {
+        "checkout.session.completed" => {}
+        "charge.dispute.created" | "charge.dispute.closed" => {
+            return disputes::apply(state, event_type, event).await;
+        }
+        "invoice.paid" | "customer.subscription.updated" | "customer.subscription.deleted" => {
+            return subscriptions::apply(state, event_type, event).await;
+        }
+        _ => {
+            info!("=== IGNORING EVENT (not checkout.session.completed) ===");
+            return Ok("Event ignored");
+        }
     }
 
     info!("=== PROCESSING checkout.session.completed ===");
 
-    let session = match event.get("data").and_then(|data| data.get("object")) {
-        Some(session) => session,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Stripe session missing"));
-        }
-    };
+    let session = event
+        .get("data")
+        .and_then(|data| data.get("object"))
+        .ok_or(CheckoutError::Validation("Stripe session missing"))?;
 
-    let user_id = match parse_user_id(session) {
-        Some(user_id) => user_id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Stripe metadata missing user_id"));
-        }
-    };
+    // The subscription's own events (invoice.paid, customer.subscription.*) grant premium
+    if session.get("mode").and_then(Value::as_str) == Some("subscription") {
+        return Ok("Subscription checkout completed");
+    }
 
-    let amount_cents = match parse_amount_cents(session) {
-        Some(amount) if amount > 0 => amount,
-        _ => {
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Stripe metadata missing amount"));
-        }
-    };
+    let user_id = parse_user_id(session)
+        .ok_or(CheckoutError::Validation("Stripe metadata missing user_id"))?;
 
-    let request_id = match parse_request_id(session) {
-        Some(request_id) => request_id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(BaseResponse::error("Stripe metadata missing request_id"));
-        }
-    };
+    let amount_cents = parse_amount_cents(session)
+        .filter(|amount| *amount > 0)
+        .ok_or(CheckoutError::Validation("Stripe metadata missing amount"))?;
+
+    let request_id = parse_request_id(session)
+        .ok_or(CheckoutError::Validation("Stripe metadata missing request_id"))?;
+
+    let coupon = coupons::from_session(session);
 
     let payment_status = session
         .get("payment_status")
@@ -1129,7 +2355,7 @@ async fn stripe_webhook(
 
         let metadata = session.get("metadata").cloned().unwrap_or(Value::Null);
 
-        match db::mark_payment_failed(
+        let should_emit = db::mark_payment_failed(
             &state.db,
             &request_id,
             user_id,
@@ -1140,37 +2366,30 @@ async fn stripe_webhook(
             &failure_reason,
             &metadata,
         )
-        .await
-        {
-            Ok(should_emit) => {
-                if should_emit {
-                    let finished_event = CheckoutFinishedEvent::failed(
-                        request_id.clone(),
-                        user_id,
-                        amount_cents,
-                        currency,
-                        purpose,
-                        session_id,
-                        failure_reason,
-                    );
-
-                    if let Err(err) = state
-                        .producer
-                        .send_finished_event(&finished_event, Some(&request_id))
-                        .await
-                    {
-                        warn!("Failed to publish checkout_finished failure event: {}", err);
-                    }
-                }
-            }
-            Err(err) => {
-                warn!("Failed to record checkout failure: {}", err);
-                return HttpResponse::InternalServerError()
-                    .json(BaseResponse::error("Failed to record payment failure"));
+        .await?;
+
+        if should_emit {
+            let finished_event = CheckoutFinishedEvent::failed(
+                request_id.clone(),
+                user_id,
+                amount_cents,
+                currency,
+                purpose,
+                session_id,
+                failure_reason,
+            )
+            .with_coupon(coupon);
+
+            if let Err(err) = state
+                .producer
+                .send_finished_event(&finished_event, Some(&request_id))
+                .await
+            {
+                warn!("Failed to publish checkout_finished failure event: {}", err);
             }
         }
 
-        return HttpResponse::Ok().json(BaseResponse::success("Payment not completed"));
+        return Ok("Payment not completed");
     }
 
     let currency = parse_currency(session).unwrap_or_else(|| "eur".to_string());
@@ -1187,7 +2406,7 @@ async fn stripe_webhook(
 
     let metadata = session.get("metadata").cloned().unwrap_or(Value::Null);
 
-    let should_emit = match db::mark_payment_succeeded(
+    let should_emit = db::mark_payment_succeeded(
         &state.db,
         &request_id,
         user_id,
@@ -1198,54 +2417,86 @@ async fn stripe_webhook(
         payment_intent_id.as_deref(),
         &metadata,
     )
-    .await
-    {
-        Ok(should_emit) => should_emit,
-        Err(err) => {
-            warn!("Failed to record checkout payment: {}", err);
-            return HttpResponse::InternalServerError()
-                .json(BaseResponse::error("Failed to record payment"));
-        }
-    };
+    .await?;
 
-    if should_emit {
-        let finished_event = CheckoutFinishedEvent::success(
-            request_id.clone(),
-            user_id,
-            amount_cents,
-            currency,
-            purpose,
-            Some(session_id),
-            payment_intent_id,
-        );
+    if !should_emit {
+        return Ok("Payment already processed");
+    }
 
-        if let Err(err) = state
-            .producer
-            .send_finished_event(&finished_event, Some(&request_id))
-            .await
+    if let Some(coupon) = &coupon {
+        // The payment stands either way; a missing row only loosens the limits
+        if let Err(err) =
+            db::record_coupon_redemption(&state.db, coupon, user_id, &request_id, amount_cents)
+                .await
         {
-            warn!("Failed to publish checkout_finished event: {}", err);
+            warn!(
+                request_id = %request_id,
+                coupon_code = %coupon.code,
+                error = %err,
+                "Failed to record coupon redemption"
+            );
         }
-    } else {
-        return HttpResponse::Ok().json(BaseResponse::success("Payment already processed"));
     }
 
-    HttpResponse::Ok().json(BaseResponse::success("Payment processed"))
+    let finished_event = CheckoutFinishedEvent::success(
+        request_id.clone(),
+        user_id,
+        amount_cents,
+        currency,
+        purpose,
+        Some(session_id),
+        payment_intent_id,
+    )
+    .with_coupon(coupon);
+
+    if let Err(err) = state
+        .producer
+        .send_finished_event(&finished_event, Some(&request_id))
+        .await
+    {
+        warn!("Failed to publish checkout_finished event: {}", err);
+    }
+
+    Ok("Payment processed")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{build_balance_urls, validate_service_token_value};
+    use super::{authorize_admin, build_balance_urls, validate_service_token};
+    use crate::auth::JwtClaims;
+    use crate::error::CheckoutError;
+    use actix_web::http::StatusCode;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use service_auth::{KeyRing, Signer, Verifier, VerifyError};
+
+    const KEYS: &str = "k1:test_service_auth_key_0123456789abcdef";
 
     #[test]
-    fn validate_service_token_value_accepts_match() {
-        assert!(validate_service_token_value("token", "token").is_ok());
+    fn validate_service_token_accepts_signed_token() {
+        let verifier = Verifier::new(KeyRing::parse(KEYS).unwrap(), "checkout");
+        let token = Signer::new(KeyRing::parse(KEYS).unwrap(), "blazing_sun").sign("checkout");
+
+        let claims = validate_service_token(Some(&verifier), &token).expect("valid token");
+        assert_eq!(claims.iss, "blazing_sun");
     }
 
     #[test]
-    fn validate_service_token_value_rejects_empty_or_mismatch() {
-        assert!(validate_service_token_value("", "token").is_err());
-        assert!(validate_service_token_value("token", "wrong").is_err());
+    fn validate_service_token_rejects_unconfigured_or_invalid() {
+        let verifier = Verifier::new(KeyRing::parse(KEYS).unwrap(), "checkout");
+        let token = Signer::new(KeyRing::parse(KEYS).unwrap(), "blazing_sun").sign("checkout");
+
+        assert!(matches!(
+            validate_service_token(None, &token),
+            Err(CheckoutError::Config { .. })
+        ));
+        assert!(matches!(
+            validate_service_token(Some(&verifier), "checkout_service_token_change_me"),
+            Err(CheckoutError::InvalidServiceToken(VerifyError::Malformed))
+        ));
+        assert!(matches!(
+            validate_service_token(Some(&verifier), ""),
+            Err(CheckoutError::InvalidServiceToken(VerifyError::Missing))
+        ));
     }
 
     #[test]
@@ -1257,39 +2508,351 @@ mod tests {
         );
         assert_eq!(cancel, "https://local.rust.com/balance?status=cancel");
     }
+
+    fn token_with_permissions(permissions: i16, secret: &str) -> String {
+        let claims = JwtClaims {
+            sub: 7,
+            role: "user".to_string(),
+            permissions,
+            exp: 9999999999,
+            locale: None,
+        };
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+            .expect("encode token")
+    }
+
+    #[test]
+    fn authorize_admin_accepts_only_admins_and_super_admins() {
+        let secret = "admin_test_secret";
+
+        for permissions in [10, 100] {
+            let token = token_with_permissions(permissions, secret);
+            assert!(
+                authorize_admin(Some(token), secret).is_ok(),
+                "level {permissions}"
+            );
+        }
+        for permissions in [1, 5] {
+            let token = token_with_permissions(permissions, secret);
+            let response = authorize_admin(Some(token), secret).expect_err("not an admin");
+            assert_eq!(
+                response.status(),
+                StatusCode::FORBIDDEN,
+                "level {permissions}"
+            );
+        }
+    }
+
+    #[test]
+    fn authorize_admin_rejects_affiliate_token() {
+        let secret = "admin_test_secret";
+        let token = token_with_permissions(50, secret);
+
+        let response = authorize_admin(Some(token), secret).expect_err("affiliate is not an admin");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+/// The webhook handler fed with payloads signed by the Stripe sandbox
+#[cfg(all(test, feature = "stripe-mock"))]
+mod webhook_tests {
+    use super::*;
+    use crate::stripe_mock::{SessionBuilder, SignedWebhook, WebhookEvent};
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+
+    const SECRET: &str = "whsec_webhook_tests";
+
+    fn state(database_url: &str) -> Arc<ServiceState> {
+        Arc::new(ServiceState {
+            producer: KafkaProducer::new("localhost:9092").expect("producer"),
+            stripe_secret: "sk_test_webhook_tests".to_string(),
+            webhook: webhooks::WebhookConfig {
+                secrets: vec![SECRET.to_string()],
+                tolerance_seconds: stripe::DEFAULT_WEBHOOK_TOLERANCE_SECONDS,
+                replay_ttl_seconds: 60,
+            },
+            stripe_api_base: stripe::DEFAULT_API_BASE.to_string(),
+            stripe_premium_price_id: None,
+            http_client: reqwest::Client::new(),
+            jwt_secret: String::new(),
+            service_auth: None,
+            service_auth_rejections: Arc::new(RejectionMetrics::new()),
+            admin_tokens: transaction_search::AdminTokens::default(),
+            webhook_rejections: Arc::new(webhooks::WebhookMetrics::new()),
+            rebalance: RebalanceTracker::new(),
+            // Lazy: only the tests that get past validation touch the database
+            db: PgPool::connect_lazy(database_url).expect("database url"),
+            redis: None,
+            maintenance: Arc::new(maintenance_mode::Maintenance::new(None)),
+            idempotency_ttl_seconds: 60,
+            session_ttl: expiry::session_ttl(expiry::MAX_TTL_MINUTES),
+            limits: limits::LimitsConfig::default(),
+        })
+    }
+
+    async fn deliver(state: Arc<ServiceState>, webhook: &SignedWebhook) -> (u16, Value) {
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .route("/webhooks/stripe", web::post().to(stripe_webhook)),
+        )
+        .await;
+        let req = TestRequest::post()
+            .uri("/webhooks/stripe")
+            .insert_header(("Stripe-Signature", webhook.signature.as_str()))
+            .set_payload(webhook.payload.clone())
+            .to_request();
+        let response = call_service(&app, req).await;
+        let status = response.status().as_u16();
+        let body: Value = read_body_json(response).await;
+        (status, body)
+    }
+
+    fn offline_state() -> Arc<ServiceState> {
+        state("postgres://checkout@localhost:5433/checkout")
+    }
+
+    #[actix_web::test]
+    async fn webhooks_signed_with_another_secret_are_rejected() {
+        let session = SessionBuilder::for_checkout("req-1", 7, 500).completed("paid").build();
+        let webhook = WebhookEvent::session_completed(session).sign("whsec_someone_else");
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["message"], "Stripe signature verification failed");
+    }
+
+    #[actix_web::test]
+    async fn webhooks_signed_outside_the_tolerance_are_rejected() {
+        let session = SessionBuilder::for_checkout("req-1", 7, 500).completed("paid").build();
+        let webhook = WebhookEvent::session_completed(session)
+            .sign_at(SECRET, Utc::now().timestamp() - 3600);
+
+        let state = offline_state();
+        let (status, body) = deliver(state.clone(), &webhook).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["message"], "Stripe signature timestamp outside tolerance");
+        assert_eq!(
+            state.webhook_rejections.count(webhooks::Rejection::Signature(
+                stripe::SignatureError::OutsideTolerance
+            )),
+            1
+        );
+    }
+
+    #[actix_web::test]
+    async fn other_events_are_acknowledged_and_ignored() {
+        let session = SessionBuilder::for_checkout("req-1", 7, 500).build();
+        let webhook = WebhookEvent::new("checkout.session.expired", session).sign(SECRET);
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["message"], "Event ignored");
+    }
+
+    #[actix_web::test]
+    async fn sessions_without_checkout_metadata_are_rejected() {
+        let session = SessionBuilder::new().amount_total(500).completed("paid").build();
+        let webhook = WebhookEvent::session_completed(session).sign(SECRET);
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["message"], "Stripe metadata missing user_id");
+    }
+
+    #[actix_web::test]
+    async fn disputes_without_a_payment_intent_are_rejected() {
+        let dispute = json!({ "id": "dp_1", "amount": 500, "charge": "ch_1", "status": "needs_response" });
+        let webhook = WebhookEvent::new("charge.dispute.created", dispute).sign(SECRET);
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["message"], "Stripe dispute missing payment_intent");
+    }
+
+    #[actix_web::test]
+    async fn disputes_closed_without_an_outcome_are_ignored() {
+        let dispute = json!({ "id": "dp_1", "amount": 500, "payment_intent": "pi_1", "status": "under_review" });
+        let webhook = WebhookEvent::new("charge.dispute.closed", dispute).sign(SECRET);
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["message"], "Dispute status ignored");
+    }
+
+    #[actix_web::test]
+    async fn subscription_checkouts_leave_premium_to_the_subscription_events() {
+        let session = SessionBuilder::new()
+            .amount_total(0)
+            .field("mode", "subscription")
+            .completed("paid")
+            .build();
+        let webhook = WebhookEvent::session_completed(session).sign(SECRET);
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["message"], "Subscription checkout completed");
+
+        let invoice = json!({ "id": "in_1", "object": "invoice", "customer": "cus_1" });
+        let webhook = WebhookEvent::new("invoice.paid", invoice).sign(SECRET);
+
+        let (status, body) = deliver(offline_state(), &webhook).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["message"], "Invoice ignored (not a subscription)");
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs CHECKOUT_TEST_DATABASE_URL pointing at a migrated database"]
+    async fn paid_sessions_are_recorded_once() {
+        let database_url =
+            env::var("CHECKOUT_TEST_DATABASE_URL").expect("CHECKOUT_TEST_DATABASE_URL");
+        let state = state(&database_url);
+        let request_id = Uuid::new_v4().to_string();
+        let session = SessionBuilder::for_checkout(&request_id, 7, 500).completed("paid").build();
+
+        let first = WebhookEvent::session_completed(session.clone()).sign(SECRET);
+        let (status, body) = deliver(state.clone(), &first).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["message"], "Payment processed");
+
+        // Stripe retries deliveries; the replay must not credit the user again
+        let retry = WebhookEvent::session_completed(session).sign(SECRET);
+        let (status, body) = deliver(state, &retry).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["message"], "Payment already processed");
+    }
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    logging::init("checkout", "info");
 
-    let config = AppConfig::from_env();
+    #[cfg(feature = "stripe-mock")]
+    if env::args().any(|arg| arg == "--stripe-mock") {
+        return stripe_mock::serve(stripe_mock::MockConfig::from_env()).await;
+    }
 
-    let producer = KafkaProducer::new(&config.kafka_bootstrap).map_err(|err| {
-        std::io::Error::new(std::io::ErrorKind::Other, format!("Kafka error: {}", err))
-    })?;
+    let secrets = secrets_provider::from_env()
+        .await
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))?;
+    let config = AppConfig::load(secrets.as_ref())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string()))?;
+    info!(secrets = secrets.name(), "Configuration loaded: {:?}", config);
+    if config.stripe_api_base != stripe::DEFAULT_API_BASE {
+        warn!("Stripe requests go to {} instead of Stripe", config.stripe_api_base);
+    }
+    let migrate_only = env::args().any(|arg| arg == "--migrate-only");
 
     let db_pool = db::connect(&config.database_url)
         .await
         .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))?;
-    if let Err(err) = db::run_migrations(&db_pool).await {
+
+    if migrate_only || config.auto_migrate {
+        if let Err(err) = db::run_migrations(&db_pool).await {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Database migrations failed: {}", err),
+            ));
+        }
+    }
+
+    let migrations = db::migration_status(&db_pool)
+        .await
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))?;
+    let pending: Vec<&db::MigrationStatus> =
+        migrations.iter().filter(|migration| !migration.applied).collect();
+
+    if migrate_only {
+        for migration in &migrations {
+            info!(
+                "Migration {} {} ({})",
+                migration.version,
+                migration.description,
+                if migration.applied { "applied" } else { "pending" }
+            );
+        }
+        info!("Migrations complete, exiting (--migrate-only)");
+        return Ok(());
+    }
+
+    if !pending.is_empty() {
+        for migration in &pending {
+            error!(
+                "Pending migration {} {}",
+                migration.version, migration.description
+            );
+        }
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            format!("Database migrations failed: {}", err),
+            format!(
+                "{} pending database migration(s); run `checkout --migrate-only` \
+                 or set CHECKOUT_AUTO_MIGRATE=true",
+                pending.len()
+            ),
         ));
     }
 
+    let producer = KafkaProducer::new(&config.kafka_bootstrap).map_err(|err| {
+        std::io::Error::new(std::io::ErrorKind::Other, format!("Kafka error: {}", err))
+    })?;
+
+    let redis = match config.redis_url.as_deref() {
+        Some(url) => match redis::Client::open(url) {
+            Ok(client) => match redis::aio::ConnectionManager::new(client).await {
+                Ok(conn) => Some(conn),
+                Err(err) => {
+                    warn!("Redis unavailable, idempotency keys disabled: {}", err);
+                    None
+                }
+            },
+            Err(err) => {
+                warn!("Invalid Redis URL, idempotency keys disabled: {}", err);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let service_auth = if config.service_auth_keys.trim().is_empty() {
+        warn!("SERVICE_AUTH_KEYS not set, internal endpoints are disabled");
+        None
+    } else {
+        let keys = KeyRing::parse(&config.service_auth_keys).map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
+        })?;
+        info!("Service auth keys loaded: {:?}", keys);
+        Some(
+            Verifier::new(keys, "checkout")
+                .with_allowed_issuers(config.service_auth_issuers.clone()),
+        )
+    };
+
+    if config.admin_tokens.is_empty() {
+        warn!("CHECKOUT_ADMIN_TOKENS not set, admin transaction search is disabled");
+    } else {
+        info!("Admin tokens loaded: {:?}", config.admin_tokens);
+    }
+
     let state = Arc::new(ServiceState {
         producer,
         stripe_secret: config.stripe_secret.clone(),
-        stripe_webhook_secret: config.stripe_webhook_secret.clone(),
+        webhook: config.webhook.clone(),
+        stripe_api_base: config.stripe_api_base.clone(),
+        stripe_premium_price_id: config.stripe_premium_price_id.clone(),
         http_client: reqwest::Client::new(),
         jwt_secret: config.jwt_secret.clone(),
-        service_token: config.service_token.clone(),
+        service_auth,
+        service_auth_rejections: Arc::new(RejectionMetrics::new()),
+        admin_tokens: config.admin_tokens.clone(),
+        webhook_rejections: Arc::new(webhooks::WebhookMetrics::new()),
+        rebalance: RebalanceTracker::new(),
         db: db_pool,
+        maintenance: Arc::new(maintenance_mode::Maintenance::new(redis.clone())),
+        redis,
+        idempotency_ttl_seconds: config.idempotency_ttl_seconds,
+        session_ttl: expiry::session_ttl(config.session_ttl_minutes),
+        limits: config.limits.clone(),
     });
 
     let consumer_state = state.clone();
@@ -1300,15 +2863,61 @@ async fn main() -> std::io::Result<()> {
         }
     });
 
+    if config.stripe_secret.is_empty() {
+        warn!("STRIPE_SECRET not set, nightly Stripe reconciliation is disabled");
+    } else {
+        tokio::spawn(reconcile::run_nightly(
+            state.clone(),
+            config.reconcile_hour_utc,
+            chrono::Duration::hours(config.reconcile_lookback_hours),
+        ));
+    }
+
+    tokio::spawn(expiry::run_sweeper(
+        state.clone(),
+        Duration::from_secs(config.session_sweep_interval_seconds),
+    ));
+
     info!("Checkout service listening on {}:{}", config.host, config.port);
 
     HttpServer::new(move || {
-        App::new()
+        let app = App::new()
             .app_data(web::Data::new(state.clone()))
+            .app_data(web::JsonConfig::default().error_handler(validation::json_error_handler))
+            .wrap(from_fn(idempotency::idempotency))
+            .wrap(from_fn(maintenance::read_only))
+            .wrap(from_fn(locale::localize_response))
+            .wrap(from_fn(api_casing::http::apply_policy))
+            .wrap(from_fn(logging::http::trace_request))
             .route("/health", web::get().to(health))
+            .route("/metrics", web::get().to(metrics))
             .route("/sessions", web::post().to(create_session))
+            .route("/sessions/{id}/status", web::get().to(session_status))
             .route("/transactions", web::get().to(transactions))
-            .route("/webhooks/stripe", web::post().to(stripe_webhook))
+            .route("/payment-methods", web::get().to(payment_methods))
+            .route("/setup-intents", web::post().to(create_setup_intent))
+            .route("/subscriptions", web::get().to(subscription_status))
+            .route("/subscriptions", web::post().to(create_subscription))
+            .route(
+                "/internal/users/{user_id}/transactions",
+                web::get().to(internal_user_transactions),
+            )
+            .route(
+                "/admin/reconciliation/report",
+                web::get().to(reconciliation_report),
+            )
+            .route("/admin/transactions", web::get().to(admin_transactions))
+            .route("/admin/coupons", web::get().to(list_coupons))
+            .route("/admin/coupons", web::post().to(create_coupon))
+            .route("/admin/coupons/{id}", web::get().to(get_coupon))
+            .route("/admin/coupons/{id}", web::put().to(update_coupon))
+            .route("/admin/coupons/{id}", web::delete().to(delete_coupon))
+            .route("/webhooks/stripe", web::post().to(stripe_webhook));
+
+        #[cfg(feature = "chaos")]
+        let app = app.configure(fault_injection::http::configure);
+
+        app
     })
     .bind((config.host.as_str(), config.port))?
     .run()
@@ -0,0 +1,66 @@
+//! Read-only maintenance mode
+//!
+//! While the shared maintenance flag is on (set by rust-app's
+//! `/api/v1/admin/maintenance`, or `MAINTENANCE_MODE=true` here; see the
+//! `maintenance_mode` crate) every POST, PUT, PATCH and DELETE gets 503 with
+//! `Retry-After`, while reads keep working. That includes Stripe webhooks:
+//! Stripe retries them for days, so nothing is lost while the database is
+//! being maintained. `/health` and `/metrics` are never rejected.
+
+use std::sync::Arc;
+
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    web, HttpResponse,
+};
+use chrono::Utc;
+use tracing::debug;
+
+use crate::ServiceState;
+
+const EXEMPT_PREFIXES: &[&str] = &["/health", "/metrics"];
+
+pub async fn read_only<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    if maintenance_mode::is_read_only(request.method().as_str())
+        || EXEMPT_PREFIXES.contains(&request.path())
+    {
+        return next
+            .call(request)
+            .await
+            .map(|res| res.map_into_boxed_body());
+    }
+
+    let state = match request.app_data::<web::Data<Arc<ServiceState>>>() {
+        Some(state) => state.clone(),
+        None => {
+            return next
+                .call(request)
+                .await
+                .map(|res| res.map_into_boxed_body())
+        }
+    };
+    let Some(notice) = state.maintenance.current().await else {
+        return next
+            .call(request)
+            .await
+            .map(|res| res.map_into_boxed_body());
+    };
+
+    debug!(method = %request.method(), path = %request.path(), "Write rejected during maintenance");
+    let response = HttpResponse::ServiceUnavailable()
+        .insert_header((
+            header::RETRY_AFTER,
+            notice.retry_after_secs(Utc::now()).to_string(),
+        ))
+        .json(notice.rejection_body());
+    Ok(request.into_response(response).map_into_boxed_body())
+}
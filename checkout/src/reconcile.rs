@@ -0,0 +1,510 @@
+//! Stripe reconciliation
+//!
+//! Webhooks can be lost (deploys, network errors, Stripe giving up on
+//! retries), which leaves paid sessions without a `payment_succeeded` row and
+//! the user's balance untouched. Once a night this job lists the Checkout
+//! Sessions Stripe created in the lookback window, compares their outcome with
+//! `checkout_transactions`, records the missing success/failure rows (emitting
+//! the `checkout.finished` event the webhook would have sent) and stores every
+//! mismatch so admins can review it via `GET /admin/reconciliation/report`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration as ChronoDuration, NaiveTime, Utc};
+use serde_json::Value;
+use tracing::{error, info, warn};
+
+use crate::db::{self, CheckoutTransaction, ReconciliationDiscrepancy};
+use crate::error::{CheckoutError, CheckoutResult};
+use crate::stripe;
+use crate::types::CheckoutFinishedEvent;
+use crate::{
+    parse_amount_cents, parse_currency, parse_purpose, parse_request_id, parse_user_id,
+    ServiceState,
+};
+
+const STRIPE_SESSIONS_PATH: &str = "/v1/checkout/sessions";
+const PAGE_SIZE: &str = "100";
+
+/// What Stripe says happened to a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StripeOutcome {
+    Paid,
+    /// Completed without payment or expired; carries the failure reason
+    Failed(String),
+    /// Still open, nothing to reconcile yet
+    Pending,
+}
+
+impl StripeOutcome {
+    /// Mirrors the webhook: a completed session is a failure unless it is paid
+    pub fn of(session: &Value) -> Self {
+        let status = session.get("status").and_then(Value::as_str).unwrap_or("");
+        let payment_status = session
+            .get("payment_status")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+
+        match (status, payment_status) {
+            ("complete", "paid") => StripeOutcome::Paid,
+            ("complete", "") => StripeOutcome::Failed("payment_not_completed".to_string()),
+            ("complete", other) => StripeOutcome::Failed(format!("payment_status: {}", other)),
+            ("expired", _) => StripeOutcome::Failed("session_expired".to_string()),
+            _ => StripeOutcome::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscrepancyKind {
+    /// Paid in Stripe, no `payment_succeeded` row (repaired)
+    MissingSuccess,
+    /// Failed/expired in Stripe, still open locally (repaired)
+    MissingFailure,
+    /// Stripe and the local row disagree on the final outcome (report only)
+    StatusMismatch,
+    /// Both succeeded but with different amounts (report only)
+    AmountMismatch,
+    /// Session has no usable request_id/user_id/amount metadata (report only)
+    MissingMetadata,
+}
+
+impl DiscrepancyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscrepancyKind::MissingSuccess => "missing_success",
+            DiscrepancyKind::MissingFailure => "missing_failure",
+            DiscrepancyKind::StatusMismatch => "status_mismatch",
+            DiscrepancyKind::AmountMismatch => "amount_mismatch",
+            DiscrepancyKind::MissingMetadata => "missing_metadata",
+        }
+    }
+
+    /// Whether reconciliation writes the missing row itself
+    pub fn is_repairable(&self) -> bool {
+        matches!(
+            self,
+            DiscrepancyKind::MissingSuccess | DiscrepancyKind::MissingFailure
+        )
+    }
+}
+
+/// Compare Stripe's outcome with the local transaction (None = no row)
+pub fn classify(
+    outcome: &StripeOutcome,
+    stripe_amount_cents: i64,
+    local: Option<&CheckoutTransaction>,
+) -> Option<DiscrepancyKind> {
+    let local_status = local.map(|tx| tx.status.as_str());
+
+    match outcome {
+        StripeOutcome::Pending => None,
+        StripeOutcome::Paid => match local_status {
+            Some("payment_succeeded") => local
+                .filter(|tx| tx.amount_cents != stripe_amount_cents)
+                .map(|_| DiscrepancyKind::AmountMismatch),
+            Some("payment_failed") => Some(DiscrepancyKind::StatusMismatch),
+            _ => Some(DiscrepancyKind::MissingSuccess),
+        },
+        StripeOutcome::Failed(_) => match local_status {
+            Some("payment_succeeded") => Some(DiscrepancyKind::StatusMismatch),
+            Some("payment_failed") | Some("session_failed") | Some("session_expired") => None,
+            _ => Some(DiscrepancyKind::MissingFailure),
+        },
+    }
+}
+
+/// Totals of one reconciliation run
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunSummary {
+    pub run_id: i64,
+    pub sessions_checked: i32,
+    pub repaired: i32,
+    pub discrepancies: i32,
+}
+
+/// Reconcile every session Stripe created since `window_start`
+pub async fn run(state: &ServiceState, window_start: DateTime<Utc>) -> CheckoutResult<RunSummary> {
+    let run_id = db::start_reconciliation_run(&state.db, window_start).await?;
+    let mut summary = RunSummary {
+        run_id,
+        ..RunSummary::default()
+    };
+
+    let result = reconcile_window(state, run_id, window_start, &mut summary).await;
+    let error_message = result.as_ref().err().map(|err| err.to_string());
+
+    db::finish_reconciliation_run(
+        &state.db,
+        run_id,
+        summary.sessions_checked,
+        summary.repaired,
+        summary.discrepancies,
+        error_message.as_deref(),
+    )
+    .await?;
+
+    result.map(|()| summary)
+}
+
+async fn reconcile_window(
+    state: &ServiceState,
+    run_id: i64,
+    window_start: DateTime<Utc>,
+    summary: &mut RunSummary,
+) -> CheckoutResult<()> {
+    let sessions = list_sessions(state, window_start).await?;
+
+    let request_ids: Vec<String> = sessions.iter().filter_map(parse_request_id).collect();
+    let local: HashMap<String, CheckoutTransaction> =
+        db::fetch_transactions_by_request_ids(&state.db, &request_ids)
+            .await?
+            .into_iter()
+            .map(|tx| (tx.request_id.clone(), tx))
+            .collect();
+
+    for session in &sessions {
+        summary.sessions_checked += 1;
+
+        let Some(mut discrepancy) = check_session(session, &local) else {
+            continue;
+        };
+
+        if discrepancy.repaired {
+            match repair(state, session).await {
+                Ok(()) => summary.repaired += 1,
+                Err(err) => {
+                    warn!(
+                        "Failed to repair session {}: {}",
+                        discrepancy.stripe_session_id, err
+                    );
+                    discrepancy.repaired = false;
+                }
+            }
+        }
+
+        db::insert_reconciliation_discrepancy(&state.db, run_id, &discrepancy).await?;
+        summary.discrepancies += 1;
+    }
+
+    Ok(())
+}
+
+/// The discrepancy for one session, with `repaired` set when it can be fixed
+fn check_session(
+    session: &Value,
+    local: &HashMap<String, CheckoutTransaction>,
+) -> Option<ReconciliationDiscrepancy> {
+    let outcome = StripeOutcome::of(session);
+    if outcome == StripeOutcome::Pending {
+        return None;
+    }
+    // Subscription sessions credit no coins; their invoices are not reconciled here
+    if session.get("mode").and_then(Value::as_str) == Some("subscription") {
+        return None;
+    }
+
+    let request_id = parse_request_id(session);
+    let stripe_amount_cents = parse_amount_cents(session);
+    let tx = request_id.as_ref().and_then(|id| local.get(id));
+
+    let kind = match (&request_id, parse_user_id(session), stripe_amount_cents) {
+        (Some(_), Some(_), Some(amount)) if amount > 0 => classify(&outcome, amount, tx)?,
+        _ => DiscrepancyKind::MissingMetadata,
+    };
+
+    Some(ReconciliationDiscrepancy {
+        stripe_session_id: session_id(session).to_string(),
+        request_id,
+        kind: kind.as_str().to_string(),
+        stripe_status: match outcome {
+            StripeOutcome::Paid => "paid".to_string(),
+            _ => session
+                .get("status")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+                .to_string(),
+        },
+        local_status: tx.map(|tx| tx.status.clone()),
+        stripe_amount_cents,
+        local_amount_cents: tx.map(|tx| tx.amount_cents),
+        repaired: kind.is_repairable(),
+    })
+}
+
+/// Record the session's outcome and emit `checkout.finished`, as the webhook would
+async fn repair(state: &ServiceState, session: &Value) -> CheckoutResult<()> {
+    let (Some(request_id), Some(user_id), Some(amount_cents)) = (
+        parse_request_id(session),
+        parse_user_id(session),
+        parse_amount_cents(session),
+    ) else {
+        return Ok(());
+    };
+
+    let currency = parse_currency(session).unwrap_or_else(|| "eur".to_string());
+    let purpose = parse_purpose(session).unwrap_or_else(|| "unknown".to_string());
+    let session_id = session_id(session).to_string();
+    let metadata = session.get("metadata").cloned().unwrap_or(Value::Null);
+
+    let event = match StripeOutcome::of(session) {
+        StripeOutcome::Paid => {
+            let payment_intent_id = session
+                .get("payment_intent")
+                .and_then(Value::as_str)
+                .map(|val| val.to_string());
+
+            let recorded = db::mark_payment_succeeded(
+                &state.db,
+                &request_id,
+                user_id,
+                amount_cents,
+                &currency,
+                &purpose,
+                &session_id,
+                payment_intent_id.as_deref(),
+                &metadata,
+            )
+            .await?;
+
+            recorded.then(|| {
+                CheckoutFinishedEvent::success(
+                    request_id.clone(),
+                    user_id,
+                    amount_cents,
+                    currency,
+                    purpose,
+                    Some(session_id),
+                    payment_intent_id,
+                )
+            })
+        }
+        StripeOutcome::Failed(reason) => {
+            let recorded = db::mark_payment_failed(
+                &state.db,
+                &request_id,
+                user_id,
+                amount_cents,
+                &currency,
+                &purpose,
+                Some(&session_id),
+                &reason,
+                &metadata,
+            )
+            .await?;
+
+            recorded.then(|| {
+                CheckoutFinishedEvent::failed(
+                    request_id.clone(),
+                    user_id,
+                    amount_cents,
+                    currency,
+                    purpose,
+                    Some(session_id),
+                    reason,
+                )
+            })
+        }
+        StripeOutcome::Pending => None,
+    };
+
+    if let Some(event) = event {
+        state
+            .producer
+            .send_finished_event(&event, Some(&request_id))
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn session_id(session: &Value) -> &str {
+    session.get("id").and_then(Value::as_str).unwrap_or("")
+}
+
+/// All Checkout Sessions created since `since`, following Stripe's pagination
+async fn list_sessions(state: &ServiceState, since: DateTime<Utc>) -> CheckoutResult<Vec<Value>> {
+    if state.stripe_secret.is_empty() {
+        return Err(CheckoutError::stripe_not_configured());
+    }
+
+    let mut sessions = Vec::new();
+    let mut starting_after: Option<String> = None;
+
+    loop {
+        let mut params = vec![
+            ("limit", PAGE_SIZE.to_string()),
+            ("created[gte]", since.timestamp().to_string()),
+        ];
+        if let Some(last) = &starting_after {
+            params.push(("starting_after", last.clone()));
+        }
+
+        let request = state
+            .http_client
+            .get(stripe::api_url(&state.stripe_api_base, STRIPE_SESSIONS_PATH))
+            .bearer_auth(&state.stripe_secret)
+            .query(&params);
+        let response = stripe::ensure_success(stripe::send(request).await?).await?;
+
+        let page: Value = response
+            .json()
+            .await
+            .map_err(CheckoutError::StripeResponse)?;
+
+        let data = page
+            .get("data")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let has_more = page.get("has_more").and_then(Value::as_bool).unwrap_or(false);
+
+        starting_after = data.last().map(|session| session_id(session).to_string());
+        sessions.extend(data);
+
+        if !has_more || starting_after.is_none() {
+            return Ok(sessions);
+        }
+    }
+}
+
+/// Next occurrence of `hour_utc`:00 strictly after `now`
+pub fn next_run_after(now: DateTime<Utc>, hour_utc: u32) -> DateTime<Utc> {
+    let time = NaiveTime::from_hms_opt(hour_utc.min(23), 0, 0).unwrap_or_default();
+    let today = now.date_naive().and_time(time).and_utc();
+
+    if today > now {
+        today
+    } else {
+        today + ChronoDuration::days(1)
+    }
+}
+
+/// Run reconciliation every night at `hour_utc`, covering the last `lookback`
+pub async fn run_nightly(state: Arc<ServiceState>, hour_utc: u32, lookback: ChronoDuration) {
+    loop {
+        let now = Utc::now();
+        let next = next_run_after(now, hour_utc);
+        info!("Next Stripe reconciliation at {}", next);
+
+        let wait = (next - now).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        match run(&state, Utc::now() - lookback).await {
+            Ok(summary) => info!(
+                "Stripe reconciliation run {} checked {} sessions: {} discrepancies, {} repaired",
+                summary.run_id, summary.sessions_checked, summary.discrepancies, summary.repaired
+            ),
+            Err(err) => error!("Stripe reconciliation failed: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn tx(status: &str, amount_cents: i64) -> CheckoutTransaction {
+        CheckoutTransaction {
+            id: 1,
+            request_id: "req-1".to_string(),
+            user_id: 7,
+            amount_cents,
+            currency: "eur".to_string(),
+            purpose: "balance_topup".to_string(),
+            status: status.to_string(),
+            checkout_id: Some("cs_1".to_string()),
+            payment_intent_id: None,
+            error_message: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn outcome_follows_webhook_rules() {
+        let paid = json!({ "status": "complete", "payment_status": "paid" });
+        let unpaid = json!({ "status": "complete", "payment_status": "unpaid" });
+        let expired = json!({ "status": "expired", "payment_status": "unpaid" });
+        let open = json!({ "status": "open", "payment_status": "unpaid" });
+
+        assert_eq!(StripeOutcome::of(&paid), StripeOutcome::Paid);
+        assert_eq!(
+            StripeOutcome::of(&unpaid),
+            StripeOutcome::Failed("payment_status: unpaid".to_string())
+        );
+        assert_eq!(
+            StripeOutcome::of(&expired),
+            StripeOutcome::Failed("session_expired".to_string())
+        );
+        assert_eq!(StripeOutcome::of(&open), StripeOutcome::Pending);
+    }
+
+    #[test]
+    fn paid_sessions_without_a_success_row_are_repairable() {
+        let paid = StripeOutcome::Paid;
+
+        assert_eq!(classify(&paid, 500, None), Some(DiscrepancyKind::MissingSuccess));
+        assert_eq!(
+            classify(&paid, 500, Some(&tx("session_created", 500))),
+            Some(DiscrepancyKind::MissingSuccess)
+        );
+        assert_eq!(classify(&paid, 500, Some(&tx("payment_succeeded", 500))), None);
+        assert_eq!(
+            classify(&paid, 500, Some(&tx("payment_succeeded", 400))),
+            Some(DiscrepancyKind::AmountMismatch)
+        );
+        assert_eq!(
+            classify(&paid, 500, Some(&tx("payment_failed", 500))),
+            Some(DiscrepancyKind::StatusMismatch)
+        );
+        assert!(DiscrepancyKind::MissingSuccess.is_repairable());
+        assert!(!DiscrepancyKind::StatusMismatch.is_repairable());
+    }
+
+    #[test]
+    fn failed_sessions_close_open_rows_but_never_override_success() {
+        let failed = StripeOutcome::Failed("session_expired".to_string());
+
+        assert_eq!(
+            classify(&failed, 500, Some(&tx("session_created", 500))),
+            Some(DiscrepancyKind::MissingFailure)
+        );
+        assert_eq!(classify(&failed, 500, Some(&tx("payment_failed", 500))), None);
+        assert_eq!(classify(&failed, 500, Some(&tx("session_expired", 500))), None);
+        assert_eq!(
+            classify(&failed, 500, Some(&tx("payment_succeeded", 500))),
+            Some(DiscrepancyKind::StatusMismatch)
+        );
+        assert_eq!(classify(&StripeOutcome::Pending, 500, None), None);
+    }
+
+    #[test]
+    fn sessions_without_metadata_are_reported_not_repaired() {
+        let session = json!({ "id": "cs_1", "status": "complete", "payment_status": "paid" });
+        let discrepancy = check_session(&session, &HashMap::new()).expect("discrepancy");
+
+        assert_eq!(discrepancy.kind, "missing_metadata");
+        assert_eq!(discrepancy.stripe_status, "paid");
+        assert!(!discrepancy.repaired);
+    }
+
+    #[test]
+    fn next_run_is_today_or_tomorrow() {
+        let before = Utc.with_ymd_and_hms(2026, 10, 17, 1, 30, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 10, 17, 3, 0, 0).unwrap();
+
+        assert_eq!(
+            next_run_after(before, 3),
+            Utc.with_ymd_and_hms(2026, 10, 17, 3, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_run_after(after, 3),
+            Utc.with_ymd_and_hms(2026, 10, 18, 3, 0, 0).unwrap()
+        );
+    }
+}
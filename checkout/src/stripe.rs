@@ -1,8 +1,23 @@
+use chrono::Utc;
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 
+use crate::error::{CheckoutError, CheckoutResult};
+
 type HmacSha256 = Hmac<Sha256>;
 
+/// Default `STRIPE_WEBHOOK_TOLERANCE_SECONDS`, as in Stripe's own libraries
+pub const DEFAULT_WEBHOOK_TOLERANCE_SECONDS: i64 = 300;
+
+/// Where Stripe requests go unless STRIPE_API_BASE points elsewhere (the
+/// `stripe-mock` sandbox in CI)
+pub const DEFAULT_API_BASE: &str = "https://api.stripe.com";
+
+/// Full URL of a Stripe API path such as `/v1/customers`
+pub fn api_url(base: &str, path: &str) -> String {
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
 fn parse_signature_header(header: &str) -> Option<(String, Vec<String>)> {
     let mut timestamp: Option<String> = None;
     let mut signatures = Vec::new();
@@ -27,6 +42,33 @@ fn parse_signature_header(header: &str) -> Option<(String, Vec<String>)> {
     Some((timestamp, signatures))
 }
 
+/// Send a request to the Stripe API
+///
+/// With the `chaos` feature an injected Stripe fault answers instead of Stripe,
+/// as a 503 the caller handles like any other rejected request.
+pub async fn send(request: reqwest::RequestBuilder) -> CheckoutResult<reqwest::Response> {
+    if let Err(fault) = fault_injection::check(fault_injection::Target::Stripe).await {
+        return Err(CheckoutError::StripeApi {
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            body: fault.to_string(),
+        });
+    }
+
+    request.send().await.map_err(CheckoutError::StripeRequest)
+}
+
+/// Pass a successful Stripe response through, turning any other status into
+/// `CheckoutError::StripeApi` with Stripe's error body
+pub async fn ensure_success(response: reqwest::Response) -> CheckoutResult<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Err(CheckoutError::StripeApi { status, body })
+}
+
 pub fn compute_signature(secret: &str, signed_payload: &str) -> Option<String> {
     let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).ok()?;
     mac.update(signed_payload.as_bytes());
@@ -34,60 +76,209 @@ pub fn compute_signature(secret: &str, signed_payload: &str) -> Option<String> {
     Some(hex::encode(result))
 }
 
-pub fn verify_signature(payload: &[u8], header: &str, secret: &str) -> bool {
-    if secret.is_empty() {
-        return false;
+/// Why a `Stripe-Signature` header was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    /// No webhook secret is configured
+    NoSecret,
+    /// No `t=` timestamp or `v1=` signature in the header
+    MalformedHeader,
+    /// No signature matches any configured secret
+    Mismatch,
+    /// Correctly signed, but the timestamp is too far from now
+    OutsideTolerance,
+}
+
+impl SignatureError {
+    pub const ALL: [SignatureError; 4] = [
+        SignatureError::NoSecret,
+        SignatureError::MalformedHeader,
+        SignatureError::Mismatch,
+        SignatureError::OutsideTolerance,
+    ];
+
+    /// Label of the rejection metric
+    pub fn reason(self) -> &'static str {
+        match self {
+            SignatureError::NoSecret => "no_secret",
+            SignatureError::MalformedHeader => "malformed_header",
+            SignatureError::Mismatch => "signature_mismatch",
+            SignatureError::OutsideTolerance => "outside_tolerance",
+        }
+    }
+
+    pub fn public_message(self) -> &'static str {
+        match self {
+            SignatureError::NoSecret | SignatureError::Mismatch => {
+                "Stripe signature verification failed"
+            }
+            SignatureError::MalformedHeader => "Invalid Stripe signature header",
+            SignatureError::OutsideTolerance => "Stripe signature timestamp outside tolerance",
+        }
     }
+}
 
-    let (timestamp, signatures) = match parse_signature_header(header) {
-        Some(parsed) => parsed,
-        None => return false,
-    };
+/// Verify a webhook signed within `tolerance_seconds` of now, see `verify_signature_at`
+pub fn verify_signature(
+    payload: &[u8],
+    header: &str,
+    secrets: &[String],
+    tolerance_seconds: i64,
+) -> Result<usize, SignatureError> {
+    verify_signature_at(payload, header, secrets, tolerance_seconds, Utc::now().timestamp())
+}
+
+/// Verify a `Stripe-Signature` header against each of `secrets` (the current
+/// secret first, then the ones still accepted during a rotation) and check
+/// that its `t=` timestamp is within `tolerance_seconds` of `now` (0 skips the
+/// check). Returns the index of the secret that matched.
+pub fn verify_signature_at(
+    payload: &[u8],
+    header: &str,
+    secrets: &[String],
+    tolerance_seconds: i64,
+    now: i64,
+) -> Result<usize, SignatureError> {
+    if secrets.iter().all(|secret| secret.is_empty()) {
+        return Err(SignatureError::NoSecret);
+    }
+
+    let (timestamp, signatures) =
+        parse_signature_header(header).ok_or(SignatureError::MalformedHeader)?;
+    let signed_at: i64 = timestamp
+        .parse()
+        .map_err(|_| SignatureError::MalformedHeader)?;
 
     let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(payload));
+    let signatures: Vec<Vec<u8>> = signatures
+        .iter()
+        .filter_map(|signature| hex::decode(signature).ok())
+        .collect();
 
-    for signature in signatures {
-        let signature_bytes = match hex::decode(signature) {
-            Ok(bytes) => bytes,
-            Err(_) => continue,
-        };
+    let index = secrets
+        .iter()
+        .position(|secret| is_signed_with(secret, &signed_payload, &signatures))
+        .ok_or(SignatureError::Mismatch)?;
 
-        let mut mac = match HmacSha256::new_from_slice(secret.as_bytes()) {
-            Ok(mac) => mac,
-            Err(_) => return false,
-        };
-        mac.update(signed_payload.as_bytes());
+    if tolerance_seconds > 0 && (now - signed_at).abs() > tolerance_seconds {
+        return Err(SignatureError::OutsideTolerance);
+    }
 
-        if mac.verify_slice(&signature_bytes).is_ok() {
-            return true;
-        }
+    Ok(index)
+}
+
+fn is_signed_with(secret: &str, signed_payload: &str, signatures: &[Vec<u8>]) -> bool {
+    if secret.is_empty() {
+        return false;
     }
 
-    false
+    signatures.iter().any(|signature| {
+        let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(signed_payload.as_bytes());
+        mac.verify_slice(signature).is_ok()
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{compute_signature, verify_signature};
+    use super::{api_url, compute_signature, verify_signature_at, SignatureError};
+
+    const SECRET: &str = "whsec_test_secret";
+    const PAYLOAD: &[u8] = br#"{"type":"checkout.session.completed"}"#;
+    const SIGNED_AT: i64 = 1_700_000_000;
+
+    fn header_signed_with(secret: &str, timestamp: i64) -> String {
+        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(PAYLOAD));
+        let signature = compute_signature(secret, &signed_payload).unwrap();
+        format!("t={},v1={}", timestamp, signature)
+    }
+
+    fn secrets(secrets: &[&str]) -> Vec<String> {
+        secrets.iter().map(|secret| secret.to_string()).collect()
+    }
+
+    #[test]
+    fn api_url_joins_base_and_path() {
+        assert_eq!(
+            api_url("https://api.stripe.com", "/v1/customers"),
+            "https://api.stripe.com/v1/customers"
+        );
+        assert_eq!(
+            api_url("http://stripe-mock:12111/", "/v1/checkout/sessions"),
+            "http://stripe-mock:12111/v1/checkout/sessions"
+        );
+    }
 
     #[test]
     fn verify_signature_accepts_valid_signature() {
-        let secret = "whsec_test_secret";
-        let payload = br#"{"type":"checkout.session.completed"}"#;
-        let timestamp = "1700000000";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(payload));
-        let signature = compute_signature(secret, &signed_payload).unwrap();
-        let header = format!("t={},v1={}", timestamp, signature);
+        let header = header_signed_with(SECRET, SIGNED_AT);
 
-        assert!(verify_signature(payload, &header, secret));
+        assert_eq!(
+            verify_signature_at(PAYLOAD, &header, &secrets(&[SECRET]), 300, SIGNED_AT + 10),
+            Ok(0)
+        );
     }
 
     #[test]
     fn verify_signature_rejects_invalid_signature() {
-        let secret = "whsec_test_secret";
-        let payload = br#"{"type":"checkout.session.completed"}"#;
         let header = "t=1700000000,v1=deadbeef";
 
-        assert!(!verify_signature(payload, header, secret));
+        assert_eq!(
+            verify_signature_at(PAYLOAD, header, &secrets(&[SECRET]), 300, SIGNED_AT),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_signature_at(PAYLOAD, "v1=deadbeef", &secrets(&[SECRET]), 300, SIGNED_AT),
+            Err(SignatureError::MalformedHeader)
+        );
+        assert_eq!(
+            verify_signature_at(PAYLOAD, header, &secrets(&[""]), 300, SIGNED_AT),
+            Err(SignatureError::NoSecret)
+        );
+    }
+
+    #[test]
+    fn verify_signature_enforces_the_tolerance() {
+        let header = header_signed_with(SECRET, SIGNED_AT);
+        let secrets = secrets(&[SECRET]);
+
+        assert_eq!(
+            verify_signature_at(PAYLOAD, &header, &secrets, 300, SIGNED_AT + 300),
+            Ok(0)
+        );
+        assert_eq!(
+            verify_signature_at(PAYLOAD, &header, &secrets, 300, SIGNED_AT + 301),
+            Err(SignatureError::OutsideTolerance)
+        );
+        assert_eq!(
+            verify_signature_at(PAYLOAD, &header, &secrets, 300, SIGNED_AT - 301),
+            Err(SignatureError::OutsideTolerance)
+        );
+        assert_eq!(
+            verify_signature_at(PAYLOAD, &header, &secrets, 0, SIGNED_AT + 86_400),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn verify_signature_accepts_previous_secrets_during_rotation() {
+        let header = header_signed_with("whsec_previous", SIGNED_AT);
+
+        assert_eq!(
+            verify_signature_at(
+                PAYLOAD,
+                &header,
+                &secrets(&[SECRET, "whsec_previous"]),
+                300,
+                SIGNED_AT
+            ),
+            Ok(1)
+        );
+        assert_eq!(
+            verify_signature_at(PAYLOAD, &header, &secrets(&[SECRET]), 300, SIGNED_AT),
+            Err(SignatureError::Mismatch)
+        );
     }
 }
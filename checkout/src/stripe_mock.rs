@@ -0,0 +1,867 @@
+//! Stripe test-mode sandbox
+//!
+//! Integration tests and CI cannot reach Stripe, so the `stripe-mock` feature
+//! builds a small in-memory stand-in for the parts of the Stripe API checkout
+//! uses. `checkout --stripe-mock` serves it instead of the service; point the
+//! service at it with `STRIPE_API_BASE=http://stripe-mock:12111`.
+//!
+//! Stripe API (any `Authorization: Bearer` key is accepted):
+//! - POST /v1/checkout/sessions: Open a session, applying `discounts[0][coupon]`;
+//!   `mode=subscription` sessions take a `line_items[0][price]` instead
+//! - GET /v1/checkout/sessions: List sessions (`created[gte]`, `starting_after`, `limit`)
+//! - POST /v1/coupons: Create a coupon, honouring `Idempotency-Key`
+//! - POST /v1/customers: Create a customer, honouring `Idempotency-Key`
+//! - GET /v1/payment_methods: Always an empty list
+//! - POST /v1/setup_intents: Create a SetupIntent
+//!
+//! Test controls:
+//! - POST /_mock/checkout/sessions/{id}/complete: Finish a session and send
+//!   its signed `checkout.session.completed` webhook to
+//!   `STRIPE_MOCK_WEBHOOK_URL`
+//! - POST /_mock/webhooks/sign: Sign any event body with the webhook secret
+//!
+//! The builders at the bottom produce the same signed payloads for tests that
+//! call the webhook handler directly.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+
+use actix_web::http::StatusCode;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::stripe;
+
+/// stripe-mock's usual port
+const DEFAULT_PORT: u16 = 12111;
+const DEFAULT_PAGE_SIZE: usize = 10;
+const MAX_PAGE_SIZE: usize = 100;
+
+type Params = Vec<(String, String)>;
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    pub host: String,
+    pub port: u16,
+    /// Where completed sessions' webhooks go, normally checkout's `/webhooks/stripe`
+    pub webhook_url: Option<String>,
+    /// Signs webhooks; must match the service's STRIPE_WEBHOOK_SECRET
+    pub webhook_secret: String,
+}
+
+impl MockConfig {
+    pub fn from_env() -> Self {
+        Self {
+            host: env::var("STRIPE_MOCK_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+            port: env::var("STRIPE_MOCK_PORT")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_PORT),
+            webhook_url: env::var("STRIPE_MOCK_WEBHOOK_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            webhook_secret: env::var("STRIPE_WEBHOOK_SECRET").unwrap_or_default(),
+        }
+    }
+}
+
+/// Objects the sandbox has created, in creation order
+#[derive(Default)]
+pub struct MockStripe {
+    sessions: Mutex<Vec<Value>>,
+    /// Idempotency-Key to customer id
+    customers: Mutex<HashMap<String, String>>,
+    /// Coupon id to coupon, and Idempotency-Key to coupon id
+    coupons: Mutex<HashMap<String, Value>>,
+    coupon_keys: Mutex<HashMap<String, String>>,
+}
+
+struct MockState {
+    config: MockConfig,
+    stripe: MockStripe,
+    http_client: reqwest::Client,
+}
+
+/// Run the sandbox until the process is stopped
+pub async fn serve(config: MockConfig) -> std::io::Result<()> {
+    if config.webhook_secret.is_empty() {
+        warn!("STRIPE_WEBHOOK_SECRET not set, the sandbox signs webhooks with an empty secret");
+    }
+    info!("Stripe sandbox listening on {}:{}", config.host, config.port);
+
+    let bind = (config.host.clone(), config.port);
+    let state = web::Data::new(MockState {
+        config,
+        stripe: MockStripe::default(),
+        http_client: reqwest::Client::new(),
+    });
+
+    HttpServer::new(move || App::new().app_data(state.clone()).configure(configure))
+        .bind(bind)?
+        .run()
+        .await
+}
+
+fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/v1/checkout/sessions", web::post().to(create_session))
+        .route("/v1/checkout/sessions", web::get().to(list_sessions))
+        .route("/v1/coupons", web::post().to(create_coupon))
+        .route("/v1/customers", web::post().to(create_customer))
+        .route("/v1/payment_methods", web::get().to(list_payment_methods))
+        .route("/v1/setup_intents", web::post().to(create_setup_intent))
+        .route(
+            "/_mock/checkout/sessions/{id}/complete",
+            web::post().to(complete_session),
+        )
+        .route("/_mock/webhooks/sign", web::post().to(sign_webhook));
+}
+
+fn object_id(prefix: &str) -> String {
+    format!("{}_test_{}", prefix, Uuid::new_v4().simple())
+}
+
+/// Stripe's error body
+fn stripe_error(
+    status: StatusCode,
+    message: &str,
+    param: Option<&str>,
+) -> HttpResponse {
+    HttpResponse::build(status).json(json!({
+        "error": {
+            "type": "invalid_request_error",
+            "message": message,
+            "param": param,
+        }
+    }))
+}
+
+fn authorized(req: &HttpRequest) -> bool {
+    req.headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|key| !key.trim().is_empty())
+}
+
+fn unauthorized() -> HttpResponse {
+    stripe_error(
+        StatusCode::UNAUTHORIZED,
+        "You did not provide an API key",
+        None,
+    )
+}
+
+fn param<'a>(params: &'a Params, key: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.as_str())
+}
+
+/// `metadata[key]=value` parameters as a metadata object
+fn metadata(params: &Params) -> Map<String, Value> {
+    params
+        .iter()
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix("metadata[")?.strip_suffix(']')?;
+            Some((key.to_string(), Value::String(value.clone())))
+        })
+        .collect()
+}
+
+/// Required parameters, or the error Stripe answers with for the first one missing
+fn required<'a>(params: &'a Params, keys: &[&'static str]) -> Result<Vec<&'a str>, &'static str> {
+    keys.iter()
+        .map(|key| param(params, key).ok_or(*key))
+        .collect()
+}
+
+async fn create_session(
+    state: web::Data<MockState>,
+    req: HttpRequest,
+    form: web::Form<Params>,
+) -> HttpResponse {
+    if !authorized(&req) {
+        return unauthorized();
+    }
+    let params = form.into_inner();
+
+    if param(&params, "mode") == Some("subscription") {
+        return create_subscription_session(&state, &params);
+    }
+
+    let values = match required(
+        &params,
+        &[
+            "mode",
+            "success_url",
+            "cancel_url",
+            "line_items[0][price_data][currency]",
+            "line_items[0][price_data][unit_amount]",
+        ],
+    ) {
+        Ok(values) => values,
+        Err(missing) => {
+            return stripe_error(
+                StatusCode::BAD_REQUEST,
+                &format!("Missing required param: {}.", missing),
+                Some(missing),
+            )
+        }
+    };
+    let Ok(amount_total) = values[4].parse::<i64>() else {
+        return stripe_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid integer: unit_amount",
+            Some("line_items[0][price_data][unit_amount]"),
+        );
+    };
+
+    let mut amount_discount = 0;
+    if let Some(coupon_id) = param(&params, "discounts[0][coupon]") {
+        let coupons = state.stripe.coupons.lock().unwrap();
+        let Some(coupon) = coupons.get(coupon_id) else {
+            return stripe_error(
+                StatusCode::BAD_REQUEST,
+                &format!("No such coupon: '{}'", coupon_id),
+                Some("discounts[0][coupon]"),
+            );
+        };
+        amount_discount = coupon_discount(coupon, amount_total);
+    }
+
+    let mut session = SessionBuilder::new()
+        .amount_total(amount_total - amount_discount)
+        .field("total_details", json!({ "amount_discount": amount_discount }))
+        .currency(values[3])
+        .metadata(metadata(&params))
+        .field("mode", values[0])
+        .field("success_url", values[1])
+        .field("cancel_url", values[2]);
+    for key in ["customer", "customer_email", "client_reference_id"] {
+        if let Some(value) = param(&params, key) {
+            session = session.field(key, value);
+        }
+    }
+    if let Some(expires_at) = param(&params, "expires_at").and_then(|value| value.parse::<i64>().ok()) {
+        session = session.field("expires_at", expires_at);
+    }
+    let session = session.build();
+    let session_id = session["id"].as_str().unwrap_or_default();
+
+    info!(session_id = %session_id, "Sandbox checkout session created");
+    state.stripe.sessions.lock().unwrap().push(session.clone());
+    HttpResponse::Ok().json(session)
+}
+
+/// A subscription session for a recurring price; the sandbox knows no prices,
+/// so nothing is due until the subscription's own invoices
+fn create_subscription_session(state: &MockState, params: &Params) -> HttpResponse {
+    let values = match required(
+        params,
+        &["success_url", "cancel_url", "customer", "line_items[0][price]"],
+    ) {
+        Ok(values) => values,
+        Err(missing) => {
+            return stripe_error(
+                StatusCode::BAD_REQUEST,
+                &format!("Missing required param: {}.", missing),
+                Some(missing),
+            )
+        }
+    };
+
+    let mut session = SessionBuilder::new()
+        .amount_total(0)
+        .currency("eur")
+        .metadata(metadata(params))
+        .field("mode", "subscription")
+        .field("success_url", values[0])
+        .field("cancel_url", values[1])
+        .field("customer", values[2]);
+    if let Some(value) = param(params, "client_reference_id") {
+        session = session.field("client_reference_id", value);
+    }
+    if let Some(expires_at) = param(params, "expires_at").and_then(|value| value.parse::<i64>().ok()) {
+        session = session.field("expires_at", expires_at);
+    }
+    let session = session.build();
+    let session_id = session["id"].as_str().unwrap_or_default();
+
+    info!(session_id = %session_id, "Sandbox subscription session created");
+    state.stripe.sessions.lock().unwrap().push(session.clone());
+    HttpResponse::Ok().json(session)
+}
+
+async fn list_sessions(
+    state: web::Data<MockState>,
+    req: HttpRequest,
+    query: web::Query<Params>,
+) -> HttpResponse {
+    if !authorized(&req) {
+        return unauthorized();
+    }
+    let created_gte = param(&query, "created[gte]")
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(0);
+    let limit = param(&query, "limit")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let starting_after = param(&query, "starting_after");
+
+    // Stripe lists newest first
+    let sessions = state.stripe.sessions.lock().unwrap();
+    let mut matching = sessions
+        .iter()
+        .rev()
+        .filter(|session| session["created"].as_i64().unwrap_or(0) >= created_gte)
+        .skip_while(|session| starting_after.is_some_and(|id| session["id"] != id))
+        .skip(usize::from(starting_after.is_some()));
+
+    let data: Vec<Value> = matching.by_ref().take(limit).cloned().collect();
+    let has_more = matching.next().is_some();
+
+    HttpResponse::Ok().json(json!({
+        "object": "list",
+        "url": "/v1/checkout/sessions",
+        "data": data,
+        "has_more": has_more,
+    }))
+}
+
+/// Discount a coupon gives on `amount` (Stripe rounds percentages to the cent)
+fn coupon_discount(coupon: &Value, amount: i64) -> i64 {
+    let discount = match coupon["percent_off"].as_f64() {
+        Some(percent) => (amount as f64 * percent / 100.0).round() as i64,
+        None => coupon["amount_off"].as_i64().unwrap_or(0),
+    };
+    discount.clamp(0, amount)
+}
+
+async fn create_coupon(
+    state: web::Data<MockState>,
+    req: HttpRequest,
+    form: web::Form<Params>,
+) -> HttpResponse {
+    if !authorized(&req) {
+        return unauthorized();
+    }
+    let params = form.into_inner();
+
+    let key = req
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if let Some(id) = key
+        .as_ref()
+        .and_then(|key| state.stripe.coupon_keys.lock().unwrap().get(key).cloned())
+    {
+        let coupons = state.stripe.coupons.lock().unwrap();
+        return HttpResponse::Ok().json(coupons.get(&id).cloned().unwrap_or(Value::Null));
+    }
+
+    let percent_off = param(&params, "percent_off").and_then(|value| value.parse::<f64>().ok());
+    let amount_off = param(&params, "amount_off").and_then(|value| value.parse::<i64>().ok());
+    if percent_off.is_some() == amount_off.is_some() {
+        return stripe_error(
+            StatusCode::BAD_REQUEST,
+            "You must pass exactly one of percent_off or amount_off.",
+            Some("percent_off"),
+        );
+    }
+    if amount_off.is_some() && param(&params, "currency").is_none() {
+        return stripe_error(
+            StatusCode::BAD_REQUEST,
+            "You must pass currency when passing amount_off.",
+            Some("currency"),
+        );
+    }
+
+    let id = object_id("co");
+    let coupon = json!({
+        "id": id,
+        "object": "coupon",
+        "name": param(&params, "name"),
+        "percent_off": percent_off,
+        "amount_off": amount_off,
+        "currency": param(&params, "currency"),
+        "duration": param(&params, "duration").unwrap_or("once"),
+        "metadata": metadata(&params),
+        "valid": true,
+        "livemode": false,
+    });
+
+    state.stripe.coupons.lock().unwrap().insert(id.clone(), coupon.clone());
+    if let Some(key) = key {
+        state.stripe.coupon_keys.lock().unwrap().insert(key, id);
+    }
+    HttpResponse::Ok().json(coupon)
+}
+
+async fn create_customer(
+    state: web::Data<MockState>,
+    req: HttpRequest,
+    form: web::Form<Params>,
+) -> HttpResponse {
+    if !authorized(&req) {
+        return unauthorized();
+    }
+    let params = form.into_inner();
+
+    let id = match req
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(key) => state
+            .stripe
+            .customers
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| object_id("cus"))
+            .clone(),
+        None => object_id("cus"),
+    };
+
+    HttpResponse::Ok().json(json!({
+        "id": id,
+        "object": "customer",
+        "email": param(&params, "email"),
+        "metadata": metadata(&params),
+        "livemode": false,
+    }))
+}
+
+async fn list_payment_methods(req: HttpRequest) -> HttpResponse {
+    if !authorized(&req) {
+        return unauthorized();
+    }
+
+    HttpResponse::Ok().json(json!({
+        "object": "list",
+        "url": "/v1/payment_methods",
+        "data": [],
+        "has_more": false,
+    }))
+}
+
+async fn create_setup_intent(req: HttpRequest, form: web::Form<Params>) -> HttpResponse {
+    if !authorized(&req) {
+        return unauthorized();
+    }
+    let params = form.into_inner();
+    let Some(customer) = param(&params, "customer") else {
+        return stripe_error(
+            StatusCode::BAD_REQUEST,
+            "Missing required param: customer.",
+            Some("customer"),
+        );
+    };
+
+    let id = object_id("seti");
+    HttpResponse::Ok().json(json!({
+        "id": id,
+        "object": "setup_intent",
+        "client_secret": format!("{}_secret_{}", id, Uuid::new_v4().simple()),
+        "customer": customer,
+        "status": "requires_payment_method",
+        "usage": param(&params, "usage").unwrap_or("off_session"),
+        "metadata": metadata(&params),
+        "livemode": false,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct CompleteSessionRequest {
+    /// paid (default), unpaid or no_payment_required
+    payment_status: Option<String>,
+}
+
+async fn complete_session(
+    state: web::Data<MockState>,
+    path: web::Path<String>,
+    body: Option<web::Json<CompleteSessionRequest>>,
+) -> HttpResponse {
+    let session_id = path.into_inner();
+    let payment_status = body
+        .and_then(|body| body.into_inner().payment_status)
+        .unwrap_or_else(|| "paid".to_string());
+
+    let session = {
+        let mut sessions = state.stripe.sessions.lock().unwrap();
+        let Some(session) = sessions.iter_mut().find(|session| session["id"] == session_id.as_str())
+        else {
+            return stripe_error(
+                StatusCode::NOT_FOUND,
+                &format!("No such checkout.session: '{}'", session_id),
+                Some("id"),
+            );
+        };
+
+        session["status"] = json!("complete");
+        session["payment_status"] = json!(payment_status);
+        if payment_status == "paid" {
+            session["payment_intent"] = json!(object_id("pi"));
+        }
+        session.clone()
+    };
+
+    let webhook = WebhookEvent::session_completed(session).sign(&state.config.webhook_secret);
+
+    let delivered_status = match &state.config.webhook_url {
+        Some(url) => match state
+            .http_client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("Stripe-Signature", &webhook.signature)
+            .body(webhook.payload.clone())
+            .send()
+            .await
+        {
+            Ok(response) => Some(response.status().as_u16()),
+            Err(err) => {
+                warn!("Sandbox webhook delivery to {} failed: {}", url, err);
+                None
+            }
+        },
+        None => None,
+    };
+
+    HttpResponse::Ok().json(json!({
+        "event": serde_json::from_str::<Value>(&webhook.payload).unwrap_or(Value::Null),
+        "stripe_signature": webhook.signature,
+        "delivered_status": delivered_status,
+    }))
+}
+
+async fn sign_webhook(state: web::Data<MockState>, payload: web::Bytes) -> HttpResponse {
+    let payload = String::from_utf8_lossy(&payload).into_owned();
+    let webhook = SignedWebhook::sign(payload, &state.config.webhook_secret, Utc::now().timestamp());
+
+    HttpResponse::Ok().json(json!({
+        "payload": webhook.payload,
+        "stripe_signature": webhook.signature,
+    }))
+}
+
+/// Builds Checkout Session objects shaped like Stripe's
+pub struct SessionBuilder {
+    session: Map<String, Value>,
+}
+
+impl SessionBuilder {
+    /// An open, unpaid session with a fresh id
+    pub fn new() -> Self {
+        let id = object_id("cs");
+        let session = json!({
+            "id": id,
+            "object": "checkout.session",
+            "url": format!("https://checkout.stripe.com/c/pay/{}", id),
+            "status": "open",
+            "payment_status": "unpaid",
+            "payment_intent": null,
+            "amount_total": 0,
+            "currency": "eur",
+            "metadata": {},
+            "created": Utc::now().timestamp(),
+            "expires_at": Utc::now().timestamp() + 24 * 60 * 60,
+            "livemode": false,
+        });
+
+        Self {
+            session: session.as_object().cloned().unwrap_or_default(),
+        }
+    }
+
+    /// A session as checkout opens it for a balance top-up, with the metadata
+    /// the webhook handler reads
+    #[cfg(test)]
+    pub fn for_checkout(request_id: &str, user_id: i64, amount_cents: i64) -> Self {
+        let metadata = [
+            ("request_id", request_id.to_string()),
+            ("user_id", user_id.to_string()),
+            ("amount_cents", amount_cents.to_string()),
+            ("currency", "eur".to_string()),
+            ("purpose", "balance_topup".to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), Value::String(value)))
+        .collect();
+
+        Self::new()
+            .amount_total(amount_cents)
+            .metadata(metadata)
+            .field("client_reference_id", user_id.to_string())
+    }
+
+    pub fn field(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.session.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn amount_total(self, amount_cents: i64) -> Self {
+        self.field("amount_total", amount_cents)
+    }
+
+    pub fn currency(self, currency: &str) -> Self {
+        self.field("currency", currency.to_ascii_lowercase())
+    }
+
+    pub fn metadata(self, metadata: Map<String, Value>) -> Self {
+        self.field("metadata", metadata)
+    }
+
+    /// Mark the session finished the way Stripe does before the webhook
+    #[cfg(test)]
+    pub fn completed(self, payment_status: &str) -> Self {
+        let session = self
+            .field("status", "complete")
+            .field("payment_status", payment_status);
+        if payment_status == "paid" {
+            session.field("payment_intent", object_id("pi"))
+        } else {
+            session
+        }
+    }
+
+    pub fn build(self) -> Value {
+        Value::Object(self.session)
+    }
+}
+
+impl Default for SessionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A Stripe event around an API object
+pub struct WebhookEvent {
+    event: Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: &str, object: Value) -> Self {
+        Self {
+            event: json!({
+                "id": object_id("evt"),
+                "object": "event",
+                "type": event_type,
+                "created": Utc::now().timestamp(),
+                "livemode": false,
+                "data": { "object": object },
+            }),
+        }
+    }
+
+    pub fn session_completed(session: Value) -> Self {
+        Self::new("checkout.session.completed", session)
+    }
+
+    /// Serialize and sign the event as Stripe would right now
+    pub fn sign(self, secret: &str) -> SignedWebhook {
+        self.sign_at(secret, Utc::now().timestamp())
+    }
+
+    pub fn sign_at(self, secret: &str, timestamp: i64) -> SignedWebhook {
+        SignedWebhook::sign(self.event.to_string(), secret, timestamp)
+    }
+}
+
+/// A webhook body with its `Stripe-Signature` header
+pub struct SignedWebhook {
+    pub payload: String,
+    pub signature: String,
+}
+
+impl SignedWebhook {
+    pub fn sign(payload: String, secret: &str, timestamp: i64) -> Self {
+        let signed_payload = format!("{}.{}", timestamp, payload);
+        let signature = stripe::compute_signature(secret, &signed_payload).unwrap_or_default();
+
+        Self {
+            payload,
+            signature: format!("t={},v1={}", timestamp, signature),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_and_read_body_json, call_service, init_service, TestRequest};
+
+    const SECRET: &str = "whsec_sandbox";
+
+    fn sandbox() -> web::Data<MockState> {
+        web::Data::new(MockState {
+            config: MockConfig {
+                host: "127.0.0.1".to_string(),
+                port: DEFAULT_PORT,
+                webhook_url: None,
+                webhook_secret: SECRET.to_string(),
+            },
+            stripe: MockStripe::default(),
+            http_client: reqwest::Client::new(),
+        })
+    }
+
+    fn session_form(amount: &str) -> Vec<(&'static str, String)> {
+        vec![
+            ("mode", "payment".to_string()),
+            ("success_url", "https://local.rust.com/balance?status=success".to_string()),
+            ("cancel_url", "https://local.rust.com/balance?status=cancel".to_string()),
+            ("line_items[0][price_data][currency]", "eur".to_string()),
+            ("line_items[0][price_data][unit_amount]", amount.to_string()),
+            ("metadata[user_id]", "7".to_string()),
+            ("metadata[request_id]", "req-1".to_string()),
+        ]
+    }
+
+    #[test]
+    fn signed_webhooks_pass_verification() {
+        let session = SessionBuilder::for_checkout("req-1", 7, 500).completed("paid").build();
+        let webhook = WebhookEvent::session_completed(session).sign_at(SECRET, 1_700_000_000);
+
+        assert!(webhook.signature.starts_with("t=1700000000,v1="));
+        let verify = |secret: &str| {
+            stripe::verify_signature_at(
+                webhook.payload.as_bytes(),
+                &webhook.signature,
+                &[secret.to_string()],
+                stripe::DEFAULT_WEBHOOK_TOLERANCE_SECONDS,
+                1_700_000_000,
+            )
+        };
+        assert_eq!(verify(SECRET), Ok(0));
+        assert_eq!(verify("whsec_other"), Err(stripe::SignatureError::Mismatch));
+    }
+
+    #[actix_web::test]
+    async fn sessions_are_created_listed_and_completed() {
+        let app = init_service(App::new().app_data(sandbox()).configure(configure)).await;
+
+        let req = TestRequest::post()
+            .uri("/v1/checkout/sessions")
+            .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+            .set_form(session_form("500"))
+            .to_request();
+        let session: Value = call_and_read_body_json(&app, req).await;
+        let session_id = session["id"].as_str().expect("session id").to_string();
+        assert_eq!(session["amount_total"], 500);
+        assert_eq!(session["metadata"]["request_id"], "req-1");
+        assert_eq!(session["payment_status"], "unpaid");
+
+        let req = TestRequest::get()
+            .uri("/v1/checkout/sessions?limit=10&created%5Bgte%5D=0")
+            .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+            .to_request();
+        let page: Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(page["data"][0]["id"], session_id.as_str());
+        assert_eq!(page["has_more"], false);
+
+        let req = TestRequest::post()
+            .uri(&format!("/_mock/checkout/sessions/{}/complete", session_id))
+            .to_request();
+        let completed: Value = call_and_read_body_json(&app, req).await;
+        let event = &completed["event"];
+        assert_eq!(event["type"], "checkout.session.completed");
+        assert_eq!(event["data"]["object"]["payment_status"], "paid");
+        assert!(event["data"]["object"]["payment_intent"].is_string());
+        assert_eq!(completed["delivered_status"], Value::Null);
+
+        let payload = event.to_string();
+        let signature = completed["stripe_signature"].as_str().expect("signature");
+        let secrets = [SECRET.to_string()];
+        assert_eq!(
+            stripe::verify_signature(payload.as_bytes(), signature, &secrets, 300),
+            Ok(0)
+        );
+    }
+
+    #[actix_web::test]
+    async fn requests_need_a_key_and_required_params() {
+        let app = init_service(App::new().app_data(sandbox()).configure(configure)).await;
+
+        let req = TestRequest::post()
+            .uri("/v1/checkout/sessions")
+            .set_form(session_form("500"))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 401);
+
+        let mut form = session_form("500");
+        form.retain(|(key, _)| *key != "success_url");
+        let req = TestRequest::post()
+            .uri("/v1/checkout/sessions")
+            .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+            .set_form(form)
+            .to_request();
+        let error: Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(error["error"]["param"], "success_url");
+    }
+
+    #[actix_web::test]
+    async fn customers_are_idempotent() {
+        let app = init_service(App::new().app_data(sandbox()).configure(configure)).await;
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let req = TestRequest::post()
+                .uri("/v1/customers")
+                .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+                .insert_header(("Idempotency-Key", "checkout-customer-7"))
+                .set_form([("metadata[user_id]", "7")])
+                .to_request();
+            let customer: Value = call_and_read_body_json(&app, req).await;
+            ids.push(customer["id"].clone());
+        }
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[actix_web::test]
+    async fn coupons_discount_sessions() {
+        let app = init_service(App::new().app_data(sandbox()).configure(configure)).await;
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let req = TestRequest::post()
+                .uri("/v1/coupons")
+                .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+                .insert_header(("Idempotency-Key", "checkout-coupon-3-20pct"))
+                .set_form([("percent_off", "20"), ("duration", "once")])
+                .to_request();
+            let coupon: Value = call_and_read_body_json(&app, req).await;
+            ids.push(coupon["id"].as_str().expect("coupon id").to_string());
+        }
+        assert_eq!(ids[0], ids[1]);
+
+        let mut form = session_form("1000");
+        form.push(("discounts[0][coupon]", ids[0].clone()));
+        let req = TestRequest::post()
+            .uri("/v1/checkout/sessions")
+            .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+            .set_form(form)
+            .to_request();
+        let session: Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(session["amount_total"], 800);
+        assert_eq!(session["total_details"]["amount_discount"], 200);
+
+        let mut form = session_form("1000");
+        form.push(("discounts[0][coupon]", "co_unknown".to_string()));
+        let req = TestRequest::post()
+            .uri("/v1/checkout/sessions")
+            .insert_header(("Authorization", "Bearer sk_test_sandbox"))
+            .set_form(form)
+            .to_request();
+        let error: Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(error["error"]["param"], "discounts[0][coupon]");
+    }
+}
@@ -0,0 +1,523 @@
+//! Premium membership subscriptions
+//!
+//! `POST /subscriptions` opens a Stripe Checkout Session in subscription mode
+//! for STRIPE_PREMIUM_PRICE_ID, on the user's Stripe Customer. From then on
+//! Stripe drives the subscription and tells us through webhooks:
+//! - `invoice.paid`: a period was paid (the first one, or a renewal)
+//! - `customer.subscription.updated`: status, period or cancellation changed
+//! - `customer.subscription.deleted`: the subscription ended
+//!
+//! Each delivery is applied to `checkout_subscriptions` under a lock, in
+//! Stripe's event order: a delivery older than the last one applied, or one
+//! for a subscription that already ended, changes nothing. When a change
+//! grants premium (or extends the paid period) `SubscriptionActivated` is
+//! published on `checkout.subscriptions`; when it takes premium away,
+//! `SubscriptionLapsed`. A user keeps premium while the subscription is
+//! active, trialing or past_due (Stripe is still retrying the payment).
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::db::{self, CheckoutSubscription};
+use crate::error::{CheckoutError, CheckoutResult};
+use crate::types::CheckoutSubscriptionEvent;
+use crate::{customers, stripe, ServiceState, StripeCheckoutSession};
+
+/// `metadata[purpose]` of subscription checkout sessions
+pub const PURPOSE: &str = "premium_subscription";
+
+/// Whether a subscription in this Stripe status grants premium
+pub fn entitled(stripe_status: &str) -> bool {
+    matches!(stripe_status, "active" | "trialing" | "past_due")
+}
+
+/// Stripe statuses a subscription never leaves
+fn is_final(stripe_status: &str) -> bool {
+    matches!(stripe_status, "canceled" | "incomplete_expired")
+}
+
+/// What one webhook says about a subscription; None fields keep the stored value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionUpdate {
+    pub subscription_id: String,
+    pub customer_id: Option<String>,
+    /// From the subscription's `metadata[user_id]`
+    pub user_id: Option<i64>,
+    pub price_id: Option<String>,
+    pub status: String,
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub cancel_at_period_end: Option<bool>,
+}
+
+fn text(object: &Value, key: &str) -> Option<String> {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|val| !val.is_empty())
+        .map(str::to_string)
+}
+
+fn timestamp(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(Value::as_i64)
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+}
+
+fn metadata_user_id(object: Option<&Value>) -> Option<i64> {
+    let value = object?.get("metadata")?.get("user_id")?;
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|val| val.parse().ok()))
+}
+
+fn first_line(object: &Value, list: &str) -> Option<Value> {
+    object.get(list)?.get("data")?.get(0).cloned()
+}
+
+impl SubscriptionUpdate {
+    /// From a Stripe subscription object (`customer.subscription.*`)
+    pub fn from_subscription(object: &Value) -> Option<Self> {
+        let item = first_line(object, "items");
+
+        Some(Self {
+            subscription_id: text(object, "id")?,
+            customer_id: text(object, "customer"),
+            user_id: metadata_user_id(Some(object)),
+            price_id: item
+                .as_ref()
+                .and_then(|item| item.get("price"))
+                .and_then(|price| text(price, "id")),
+            status: text(object, "status")?,
+            // Newer API versions keep the period on the subscription item
+            current_period_end: timestamp(object.get("current_period_end")).or_else(|| {
+                timestamp(
+                    item.as_ref()
+                        .and_then(|item| item.get("current_period_end")),
+                )
+            }),
+            cancel_at_period_end: object.get("cancel_at_period_end").and_then(Value::as_bool),
+        })
+    }
+
+    /// From a paid Stripe invoice (`invoice.paid`); None when the invoice is
+    /// not for a subscription
+    pub fn from_invoice(object: &Value) -> Option<Self> {
+        // Newer API versions moved the subscription under `parent`
+        let details = object
+            .get("parent")
+            .and_then(|parent| parent.get("subscription_details"))
+            .or_else(|| object.get("subscription_details"));
+        let line = first_line(object, "lines");
+
+        Some(Self {
+            subscription_id: text(object, "subscription")
+                .or_else(|| details.and_then(|details| text(details, "subscription")))?,
+            customer_id: text(object, "customer"),
+            user_id: metadata_user_id(details),
+            price_id: line
+                .as_ref()
+                .and_then(|line| line.get("price"))
+                .and_then(|price| text(price, "id")),
+            status: "active".to_string(),
+            current_period_end: timestamp(
+                line.as_ref()
+                    .and_then(|line| line.get("period"))
+                    .and_then(|period| period.get("end")),
+            ),
+            cancel_at_period_end: None,
+        })
+    }
+}
+
+/// Why a webhook left the stored subscription as it was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skip {
+    /// A later event was applied already
+    Stale,
+    /// The subscription was canceled or expired before
+    Ended,
+    /// Neither the subscription nor its customer leads to a user
+    UnknownUser,
+}
+
+impl Skip {
+    fn as_str(self) -> &'static str {
+        match self {
+            Skip::Stale => "Subscription event ignored (out of date)",
+            Skip::Ended => "Subscription event ignored (subscription ended)",
+            Skip::UnknownUser => "Subscription event ignored (unknown customer)",
+        }
+    }
+}
+
+/// The subscription after `update`, sent by Stripe at `event_at`.
+/// `user_id` is the user found for the update, if any.
+pub fn next_state(
+    current: Option<&CheckoutSubscription>,
+    update: &SubscriptionUpdate,
+    user_id: Option<i64>,
+    event_at: DateTime<Utc>,
+) -> Result<CheckoutSubscription, Skip> {
+    if let Some(current) = current {
+        if current.last_event_at > event_at {
+            return Err(Skip::Stale);
+        }
+        if is_final(&current.stripe_status) {
+            return Err(Skip::Ended);
+        }
+    }
+
+    let user_id = user_id
+        .or(current.map(|current| current.user_id))
+        .ok_or(Skip::UnknownUser)?;
+    let customer_id = update
+        .customer_id
+        .clone()
+        .or_else(|| current.map(|current| current.customer_id.clone()))
+        .ok_or(Skip::UnknownUser)?;
+
+    Ok(CheckoutSubscription {
+        subscription_id: update.subscription_id.clone(),
+        user_id,
+        customer_id,
+        price_id: update
+            .price_id
+            .clone()
+            .or_else(|| current.and_then(|current| current.price_id.clone())),
+        stripe_status: update.status.clone(),
+        current_period_end: update
+            .current_period_end
+            .or(current.and_then(|current| current.current_period_end)),
+        cancel_at_period_end: update
+            .cancel_at_period_end
+            .or(current.map(|current| current.cancel_at_period_end))
+            .unwrap_or(false),
+        entitled: entitled(&update.status),
+        last_event_at: event_at,
+    })
+}
+
+/// Change of premium worth telling rust-app about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// Premium was granted, or its paid period or cancellation changed
+    Activated,
+    Lapsed,
+}
+
+impl Transition {
+    pub fn between(
+        previous: Option<&CheckoutSubscription>,
+        next: &CheckoutSubscription,
+    ) -> Option<Self> {
+        let was_entitled = previous.is_some_and(|previous| previous.entitled);
+
+        match (was_entitled, next.entitled) {
+            (false, true) => Some(Transition::Activated),
+            (true, false) => Some(Transition::Lapsed),
+            (true, true) => previous
+                .filter(|previous| {
+                    previous.current_period_end != next.current_period_end
+                        || previous.cancel_at_period_end != next.cancel_at_period_end
+                })
+                .map(|_| Transition::Activated),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Handle `invoice.paid` / `customer.subscription.updated` /
+/// `customer.subscription.deleted`
+pub async fn apply(
+    state: &ServiceState,
+    event_type: &str,
+    event: &Value,
+) -> CheckoutResult<&'static str> {
+    let object = event
+        .get("data")
+        .and_then(|data| data.get("object"))
+        .ok_or(CheckoutError::Validation("Stripe object missing"))?;
+
+    let update = if event_type == "invoice.paid" {
+        match SubscriptionUpdate::from_invoice(object) {
+            Some(update) => update,
+            None => return Ok("Invoice ignored (not a subscription)"),
+        }
+    } else {
+        SubscriptionUpdate::from_subscription(object)
+            .ok_or(CheckoutError::Validation("Stripe subscription missing"))?
+    };
+
+    let event_at = timestamp(event.get("created")).unwrap_or_else(Utc::now);
+    let user_id = match (update.user_id, &update.customer_id) {
+        (Some(user_id), _) => Some(user_id),
+        (None, Some(customer_id)) => db::fetch_user_by_customer(&state.db, customer_id).await?,
+        (None, None) => None,
+    };
+
+    let mut skipped = None;
+    let stored = db::update_subscription(&state.db, &update.subscription_id, |current| {
+        next_state(current, &update, user_id, event_at)
+            .map_err(|skip| skipped = Some(skip))
+            .ok()
+    })
+    .await?;
+
+    let Some((previous, next)) = stored else {
+        let skip = skipped.unwrap_or(Skip::Stale);
+        if skip == Skip::UnknownUser {
+            warn!(
+                subscription_id = %update.subscription_id,
+                customer_id = ?update.customer_id,
+                "Subscription of a customer without a user"
+            );
+        }
+        return Ok(skip.as_str());
+    };
+
+    info!(
+        subscription_id = %next.subscription_id,
+        user_id = %next.user_id,
+        status = %next.stripe_status,
+        entitled = next.entitled,
+        "Subscription updated"
+    );
+
+    if let Some(transition) = Transition::between(previous.as_ref(), &next) {
+        publish(state, &next, transition).await;
+    }
+
+    Ok("Subscription updated")
+}
+
+/// Publish on `checkout.subscriptions`; while Kafka is down the event is buffered
+async fn publish(
+    state: &ServiceState,
+    subscription: &CheckoutSubscription,
+    transition: Transition,
+) {
+    let event = subscription_event(subscription, transition);
+    let key = subscription.user_id.to_string();
+
+    if let Err(err) = state
+        .producer
+        .send_subscription_event(&event, Some(&key))
+        .await
+    {
+        warn!("Failed to publish checkout subscription event: {}", err);
+    }
+}
+
+fn subscription_event(
+    subscription: &CheckoutSubscription,
+    transition: Transition,
+) -> CheckoutSubscriptionEvent {
+    let timestamp = Utc::now().to_rfc3339();
+
+    match transition {
+        Transition::Activated => CheckoutSubscriptionEvent::SubscriptionActivated {
+            subscription_id: subscription.subscription_id.clone(),
+            user_id: subscription.user_id,
+            status: subscription.stripe_status.clone(),
+            current_period_end: subscription.current_period_end.map(|at| at.to_rfc3339()),
+            cancel_at_period_end: subscription.cancel_at_period_end,
+            timestamp,
+        },
+        Transition::Lapsed => CheckoutSubscriptionEvent::SubscriptionLapsed {
+            subscription_id: subscription.subscription_id.clone(),
+            user_id: subscription.user_id,
+            status: subscription.stripe_status.clone(),
+            timestamp,
+        },
+    }
+}
+
+/// Open a subscription Checkout Session for the premium price
+pub async fn create_session(
+    state: &ServiceState,
+    user_id: i64,
+    success_url: &str,
+    cancel_url: &str,
+) -> CheckoutResult<StripeCheckoutSession> {
+    let price_id = state
+        .stripe_premium_price_id
+        .as_deref()
+        .ok_or(CheckoutError::Config {
+            setting: "Premium price",
+        })?;
+    if state.stripe_secret.is_empty() {
+        return Err(CheckoutError::stripe_not_configured());
+    }
+
+    // Subscriptions bill the customer every period, so one is required
+    let customer_id = customers::ensure_customer(state, user_id, None).await?;
+    let expires_at = Utc::now() + state.session_ttl;
+
+    let params = [
+        ("mode", "subscription".to_string()),
+        ("success_url", success_url.to_string()),
+        ("cancel_url", cancel_url.to_string()),
+        ("customer", customer_id),
+        ("line_items[0][price]", price_id.to_string()),
+        ("line_items[0][quantity]", "1".to_string()),
+        ("client_reference_id", user_id.to_string()),
+        ("metadata[user_id]", user_id.to_string()),
+        ("metadata[purpose]", PURPOSE.to_string()),
+        ("subscription_data[metadata][user_id]", user_id.to_string()),
+        ("expires_at", expires_at.timestamp().to_string()),
+    ];
+
+    let request = state
+        .http_client
+        .post(stripe::api_url(
+            &state.stripe_api_base,
+            "/v1/checkout/sessions",
+        ))
+        .bearer_auth(&state.stripe_secret)
+        .form(&params);
+    let response = stripe::ensure_success(stripe::send(request).await?).await?;
+
+    let mut session: StripeCheckoutSession = response
+        .json()
+        .await
+        .map_err(CheckoutError::StripeResponse)?;
+    session.expires_at.get_or_insert(expires_at.timestamp());
+
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    fn stored(status: &str, period_end: i64, event_at: i64) -> CheckoutSubscription {
+        CheckoutSubscription {
+            subscription_id: "sub_1".to_string(),
+            user_id: 7,
+            customer_id: "cus_1".to_string(),
+            price_id: Some("price_premium".to_string()),
+            stripe_status: status.to_string(),
+            current_period_end: Some(at(period_end)),
+            cancel_at_period_end: false,
+            entitled: entitled(status),
+            last_event_at: at(event_at),
+        }
+    }
+
+    #[test]
+    fn subscriptions_and_invoices_are_parsed() {
+        let subscription = json!({
+            "id": "sub_1",
+            "object": "subscription",
+            "customer": "cus_1",
+            "status": "active",
+            "cancel_at_period_end": true,
+            "metadata": { "user_id": "7" },
+            "items": { "data": [{ "price": { "id": "price_premium" }, "current_period_end": 1_800_000_000 }] }
+        });
+        let update = SubscriptionUpdate::from_subscription(&subscription).unwrap();
+        assert_eq!(update.user_id, Some(7));
+        assert_eq!(update.price_id.as_deref(), Some("price_premium"));
+        assert_eq!(update.current_period_end, Some(at(1_800_000_000)));
+        assert_eq!(update.cancel_at_period_end, Some(true));
+
+        let invoice = json!({
+            "id": "in_1",
+            "object": "invoice",
+            "customer": "cus_1",
+            "parent": { "subscription_details": { "subscription": "sub_1", "metadata": { "user_id": "7" } } },
+            "lines": { "data": [{ "period": { "start": 1_797_000_000, "end": 1_800_000_000 } }] }
+        });
+        let update = SubscriptionUpdate::from_invoice(&invoice).unwrap();
+        assert_eq!(update.subscription_id, "sub_1");
+        assert_eq!(update.status, "active");
+        assert_eq!(update.user_id, Some(7));
+        assert_eq!(update.cancel_at_period_end, None);
+
+        let one_off = json!({ "id": "in_2", "object": "invoice", "customer": "cus_1" });
+        assert!(SubscriptionUpdate::from_invoice(&one_off).is_none());
+    }
+
+    #[test]
+    fn premium_follows_the_stripe_status() {
+        assert!(entitled("active"));
+        assert!(entitled("trialing"));
+        assert!(entitled("past_due"));
+        assert!(!entitled("incomplete"));
+        assert!(!entitled("unpaid"));
+        assert!(!entitled("canceled"));
+    }
+
+    #[test]
+    fn stale_and_post_cancellation_events_change_nothing() {
+        let update = SubscriptionUpdate {
+            subscription_id: "sub_1".to_string(),
+            customer_id: Some("cus_1".to_string()),
+            user_id: None,
+            price_id: None,
+            status: "active".to_string(),
+            current_period_end: None,
+            cancel_at_period_end: None,
+        };
+
+        let current = stored("active", 1_800_000_000, 200);
+        assert_eq!(
+            next_state(Some(&current), &update, None, at(100)),
+            Err(Skip::Stale)
+        );
+
+        let canceled = stored("canceled", 1_800_000_000, 100);
+        assert_eq!(
+            next_state(Some(&canceled), &update, None, at(200)),
+            Err(Skip::Ended)
+        );
+
+        assert_eq!(
+            next_state(None, &update, None, at(200)),
+            Err(Skip::UnknownUser)
+        );
+
+        // Fields the event leaves out keep their stored values
+        let next = next_state(Some(&current), &update, None, at(200)).unwrap();
+        assert_eq!(next.user_id, 7);
+        assert_eq!(next.price_id.as_deref(), Some("price_premium"));
+        assert_eq!(next.current_period_end, Some(at(1_800_000_000)));
+    }
+
+    #[test]
+    fn only_premium_changes_are_published() {
+        let active = stored("active", 1_800_000_000, 100);
+        let renewed = stored("active", 1_802_600_000, 200);
+        let past_due = stored("past_due", 1_800_000_000, 200);
+        let canceled = stored("canceled", 1_800_000_000, 300);
+
+        assert_eq!(
+            Transition::between(None, &active),
+            Some(Transition::Activated)
+        );
+        assert_eq!(
+            Transition::between(Some(&active), &renewed),
+            Some(Transition::Activated)
+        );
+        assert_eq!(Transition::between(Some(&active), &past_due), None);
+        assert_eq!(
+            Transition::between(Some(&past_due), &canceled),
+            Some(Transition::Lapsed)
+        );
+        assert_eq!(
+            Transition::between(None, &stored("incomplete", 0, 100)),
+            None
+        );
+
+        let event = subscription_event(&canceled, Transition::Lapsed);
+        let value = serde_json::to_value(event).unwrap();
+        assert_eq!(value["type"], "checkout.subscription.lapsed");
+        assert_eq!(value["user_id"], 7);
+    }
+}
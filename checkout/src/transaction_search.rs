@@ -0,0 +1,377 @@
+//! Admin transaction search
+//!
+//! `GET /admin/transactions` lets support find a payment by its Stripe session
+//! id or payment intent, or list a user's, a status's or a period's
+//! transactions (newest first, keyset-paged like `/transactions`). With
+//! `format=csv` the whole filtered set, up to [`MAX_EXPORT_ROWS`], is returned
+//! as a CSV attachment instead.
+//!
+//! Callers authenticate with a static admin token in the `X-Admin-Token`
+//! header, configured as `name:token` pairs in `CHECKOUT_ADMIN_TOKENS`. These
+//! are separate from user JWTs: a support tool holding one cannot act as a
+//! user, and a leaked user session cannot search payments. Tokens are kept as
+//! SHA-256 digests and compared in constant time; the name of the token used
+//! is logged with each search. Without any tokens the endpoint refuses every
+//! call.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::db::CheckoutTransaction;
+
+/// Header carrying the admin token
+pub const HEADER: &str = "X-Admin-Token";
+
+/// Shortest accepted admin token
+const MIN_TOKEN_LEN: usize = 32;
+
+/// Longest accepted filter value (ids and statuses are far shorter)
+const MAX_FILTER_LEN: usize = 255;
+
+/// Most rows one CSV export returns; larger sets are cut and flagged with
+/// `X-Export-Truncated: true`
+pub const MAX_EXPORT_ROWS: i64 = 10_000;
+
+/// Named admin tokens from CHECKOUT_ADMIN_TOKENS
+#[derive(Clone, Default)]
+pub struct AdminTokens {
+    tokens: Vec<(String, [u8; 32])>,
+}
+
+impl AdminTokens {
+    /// Parse `"support:token,ops:token"`; an empty value configures none
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut tokens: Vec<(String, [u8; 32])> = Vec::new();
+
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((name, token)) = entry.split_once(':') else {
+                return Err(
+                    "CHECKOUT_ADMIN_TOKENS entries must be written as name:token".to_string(),
+                );
+            };
+            let name = name.trim();
+            let token = token.trim();
+
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!(
+                    "CHECKOUT_ADMIN_TOKENS name '{}' may only contain letters, digits, '-' and '_'",
+                    name
+                ));
+            }
+            if token.len() < MIN_TOKEN_LEN {
+                return Err(format!(
+                    "CHECKOUT_ADMIN_TOKENS token '{}' is shorter than {} characters",
+                    name, MIN_TOKEN_LEN
+                ));
+            }
+            if tokens.iter().any(|(existing, _)| existing == name) {
+                return Err(format!(
+                    "CHECKOUT_ADMIN_TOKENS name '{}' is listed twice",
+                    name
+                ));
+            }
+
+            tokens.push((name.to_string(), digest(token)));
+        }
+
+        Ok(Self { tokens })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Name of the token `provided` matches. Every token is compared, each in
+    /// constant time, so the timing does not reveal which one came close.
+    pub fn verify(&self, provided: &str) -> Option<&str> {
+        let provided = digest(provided);
+        let mut matched = None;
+
+        for (name, token) in &self.tokens {
+            let difference = token
+                .iter()
+                .zip(provided.iter())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+            if difference == 0 {
+                matched = Some(name.as_str());
+            }
+        }
+
+        matched
+    }
+}
+
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+/// Never print tokens
+impl fmt::Debug for AdminTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminTokens")
+            .field(
+                "names",
+                &self
+                    .tokens
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Query of `GET /admin/transactions`
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchQuery {
+    /// Stripe checkout session id (`cs_...`)
+    pub session_id: Option<String>,
+    /// Stripe payment intent id (`pi_...`)
+    pub payment_intent_id: Option<String>,
+    pub user_id: Option<i64>,
+    pub status: Option<String>,
+    /// Created at or after
+    pub from: Option<DateTime<Utc>>,
+    /// Created before
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    /// Opaque cursor from a previous response's `next_cursor`/`prev_cursor`
+    pub cursor: Option<String>,
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+/// Checked search filters; every filter that is set must match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionFilters {
+    pub session_id: Option<String>,
+    pub payment_intent_id: Option<String>,
+    pub user_id: Option<i64>,
+    pub status: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Response body of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl SearchQuery {
+    /// The filters, or the message of the first invalid one
+    pub fn filters(&self) -> Result<TransactionFilters, &'static str> {
+        let text = |value: &Option<String>, message: &'static str| match value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            Some(v) if v.len() > MAX_FILTER_LEN => Err(message),
+            other => Ok(other.map(str::to_string)),
+        };
+
+        let filters = TransactionFilters {
+            session_id: text(&self.session_id, "session_id is too long")?,
+            payment_intent_id: text(&self.payment_intent_id, "payment_intent_id is too long")?,
+            user_id: self.user_id,
+            status: text(&self.status, "status is too long")?,
+            from: self.from,
+            to: self.to,
+        };
+
+        if let (Some(from), Some(to)) = (filters.from, filters.to) {
+            if from >= to {
+                return Err("from must be before to");
+            }
+        }
+
+        Ok(filters)
+    }
+
+    pub fn format(&self) -> Result<Format, &'static str> {
+        match self
+            .format
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("") | Some("json") => Ok(Format::Json),
+            Some("csv") => Ok(Format::Csv),
+            Some(_) => Err("format must be json or csv"),
+        }
+    }
+}
+
+const CSV_HEADER: [&str; 13] = [
+    "id",
+    "request_id",
+    "user_id",
+    "amount_cents",
+    "currency",
+    "purpose",
+    "status",
+    "checkout_id",
+    "payment_intent_id",
+    "error_message",
+    "created_at",
+    "updated_at",
+    "completed_at",
+];
+
+/// The transactions as CSV (RFC 4180, CRLF line endings), header first
+pub fn to_csv(transactions: &[CheckoutTransaction]) -> String {
+    let mut csv = String::new();
+    push_row(&mut csv, CSV_HEADER.iter().map(|h| h.to_string()));
+
+    for tx in transactions {
+        push_row(
+            &mut csv,
+            [
+                tx.id.to_string(),
+                tx.request_id.clone(),
+                tx.user_id.to_string(),
+                tx.amount_cents.to_string(),
+                tx.currency.clone(),
+                tx.purpose.clone(),
+                tx.status.clone(),
+                tx.checkout_id.clone().unwrap_or_default(),
+                tx.payment_intent_id.clone().unwrap_or_default(),
+                tx.error_message.clone().unwrap_or_default(),
+                tx.created_at.to_rfc3339(),
+                tx.updated_at.to_rfc3339(),
+                tx.completed_at
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_default(),
+            ],
+        );
+    }
+
+    csv
+}
+
+fn push_row(csv: &mut String, cells: impl IntoIterator<Item = String>) {
+    let cells: Vec<String> = cells.into_iter().map(|cell| csv_cell(&cell)).collect();
+    csv.push_str(&cells.join(","));
+    csv.push_str("\r\n");
+}
+
+/// Quote cells that need it, and defuse text a spreadsheet would run as a
+/// formula (error messages come from Stripe)
+fn csv_cell(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn tokens_are_parsed_and_verified_by_name() {
+        let tokens = AdminTokens::parse(&format!(" support:{TOKEN} , ops:{TOKEN}-ops")).unwrap();
+        assert_eq!(tokens.verify(TOKEN), Some("support"));
+        assert_eq!(tokens.verify(&format!("{TOKEN}-ops")), Some("ops"));
+        assert_eq!(tokens.verify("0123456789abcdef0123456789abcdeX"), None);
+        assert_eq!(tokens.verify(""), None);
+        assert!(!format!("{:?}", tokens).contains(TOKEN));
+
+        let none = AdminTokens::parse("").unwrap();
+        assert!(none.is_empty());
+        assert_eq!(none.verify(""), None);
+
+        assert!(AdminTokens::parse(TOKEN).is_err());
+        assert!(AdminTokens::parse("support:short").is_err());
+        assert!(AdminTokens::parse(&format!("sup port:{TOKEN}")).is_err());
+        assert!(AdminTokens::parse(&format!("a:{TOKEN},a:{TOKEN}x")).is_err());
+    }
+
+    #[test]
+    fn filters_are_trimmed_and_checked() {
+        let query = SearchQuery {
+            session_id: Some(" cs_test_1 ".to_string()),
+            status: Some(String::new()),
+            user_id: Some(7),
+            ..Default::default()
+        };
+        let filters = query.filters().unwrap();
+        assert_eq!(filters.session_id.as_deref(), Some("cs_test_1"));
+        assert_eq!(filters.status, None);
+        assert_eq!(filters.user_id, Some(7));
+        assert_eq!(query.format(), Ok(Format::Json));
+
+        let day = |d| Utc.with_ymd_and_hms(2026, 10, d, 0, 0, 0).unwrap();
+        let backwards = SearchQuery {
+            from: Some(day(2)),
+            to: Some(day(1)),
+            ..Default::default()
+        };
+        assert_eq!(backwards.filters(), Err("from must be before to"));
+
+        let long = SearchQuery {
+            payment_intent_id: Some("p".repeat(MAX_FILTER_LEN + 1)),
+            ..Default::default()
+        };
+        assert!(long.filters().is_err());
+
+        let csv = SearchQuery {
+            format: Some("CSV".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(csv.format(), Ok(Format::Csv));
+        let xml = SearchQuery {
+            format: Some("xml".to_string()),
+            ..Default::default()
+        };
+        assert!(xml.format().is_err());
+    }
+
+    #[test]
+    fn csv_quotes_and_defuses_cells() {
+        let created_at = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let tx = CheckoutTransaction {
+            id: 1,
+            request_id: "req-1".to_string(),
+            user_id: 7,
+            amount_cents: 1000,
+            currency: "eur".to_string(),
+            purpose: "balance_topup".to_string(),
+            status: "payment_failed".to_string(),
+            checkout_id: Some("cs_test_1".to_string()),
+            payment_intent_id: None,
+            error_message: Some("=HYPERLINK(\"x\"), declined".to_string()),
+            created_at,
+            updated_at: created_at,
+            completed_at: None,
+        };
+
+        let csv = to_csv(&[tx]);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "1,req-1,7,1000,eur,balance_topup,payment_failed,cs_test_1,,\
+             \"'=HYPERLINK(\"\"x\"\"), declined\",2026-10-01T12:00:00+00:00,\
+             2026-10-01T12:00:00+00:00,"
+        );
+        assert_eq!(lines[2], "");
+    }
+}
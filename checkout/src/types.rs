@@ -7,7 +7,6 @@ pub enum CheckoutCommand {
     #[serde(rename = "checkout.command.create_session")]
     CreateSession {
         request_id: String,
-        service_token: String,
         user_id: i64,
         amount_cents: i64,
         currency: String,
@@ -17,9 +16,24 @@ pub enum CheckoutCommand {
         metadata: Value,
         requested_at: String,
         customer_email: Option<String>,
+        /// Promo code applied to the session as a Stripe discount
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coupon: Option<CheckoutCoupon>,
     },
 }
 
+/// Promo code applied to a checkout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckoutCoupon {
+    pub coupon_id: i64,
+    pub code: String,
+    /// Taken off `amount_cents`; the user pays `amount_cents - discount_cents`
+    pub discount_cents: i64,
+    /// Stripe coupon the discount is applied with (not sent to rust-app)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stripe_coupon_id: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum CheckoutEvent {
@@ -68,6 +82,33 @@ pub enum CheckoutEvent {
         purpose: String,
         failed_at: String,
     },
+    /// A session refused by the amount, daily volume or velocity limits
+    /// (see `limits.rs`), published for monitoring
+    #[serde(rename = "checkout.event.session_rejected")]
+    SessionRejected {
+        request_id: String,
+        user_id: i64,
+        amount_cents: i64,
+        currency: String,
+        purpose: String,
+        /// `code` of the rejection, e.g. "daily_volume_exceeded"
+        reason: String,
+        limit: Value,
+        rejected_at: String,
+    },
+    /// An open session whose Stripe expiry passed without a payment, closed
+    /// by the expiry sweeper (see `expiry.rs`)
+    #[serde(rename = "checkout.event.session_expired")]
+    SessionExpired {
+        request_id: String,
+        user_id: i64,
+        amount_cents: i64,
+        currency: String,
+        session_id: Option<String>,
+        purpose: String,
+        expires_at: String,
+        expired_at: String,
+    },
 }
 
 // ============================================================================
@@ -97,8 +138,10 @@ pub struct CheckoutRequestEvent {
 }
 
 /// Outgoing event to rust-app on the "checkout_finished" topic
-/// Published in three scenarios:
-/// - status="session_created": Immediately after Stripe session is created (includes session_url)
+/// Published in four scenarios:
+/// - status="session_created": Immediately after a `checkout.requests` session is created
+///   (includes session_url and expires_at)
+/// - status="session_failed": A `checkout.requests` session could not be created
 /// - status="success": After Stripe webhook confirms payment succeeded
 /// - status="failed": After Stripe webhook indicates payment failed
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,18 +156,28 @@ pub struct CheckoutFinishedEvent {
     pub currency: String,
     /// Purpose of the checkout
     pub purpose: String,
-    /// Payment status: "session_created", "success", or "failed"
+    /// Payment status: "session_created", "session_failed", "success", or "failed"
     pub status: String,
     /// Stripe session ID (if available)
     pub session_id: Option<String>,
     /// Stripe session URL for redirect (only for status="session_created")
     pub session_url: Option<String>,
+    /// RFC 3339 time the session stops accepting payment (only for status="session_created")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
     /// Stripe payment intent ID (if available)
     pub payment_intent_id: Option<String>,
     /// Error message (if failed)
     pub error_message: Option<String>,
+    /// Stable cause of a status="session_failed", e.g. "stripe_api" or a limit
+    /// code such as "daily_volume_exceeded" (see `CheckoutError::code`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
     /// ISO 8601 timestamp when checkout finished
     pub timestamp: String,
+    /// Promo code used for the checkout (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coupon: Option<CheckoutCoupon>,
 }
 
 impl CheckoutRequestEvent {
@@ -159,6 +212,7 @@ impl CheckoutFinishedEvent {
         purpose: String,
         session_id: String,
         session_url: String,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Self {
         Self {
             request_id,
@@ -169,9 +223,40 @@ impl CheckoutFinishedEvent {
             status: "session_created".to_string(),
             session_id: Some(session_id),
             session_url: Some(session_url),
+            expires_at: expires_at.map(|at| at.to_rfc3339()),
             payment_intent_id: None,
             error_message: None,
+            error_code: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
+            coupon: None,
+        }
+    }
+
+    /// Create a session_failed event (the requested session was never opened)
+    pub fn session_failed(
+        request_id: String,
+        user_id: i64,
+        amount_cents: i64,
+        currency: String,
+        purpose: String,
+        error_code: &str,
+        error_message: String,
+    ) -> Self {
+        Self {
+            request_id,
+            user_id,
+            amount_cents,
+            currency,
+            purpose,
+            status: "session_failed".to_string(),
+            session_id: None,
+            session_url: None,
+            expires_at: None,
+            payment_intent_id: None,
+            error_message: Some(error_message),
+            error_code: Some(error_code.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            coupon: None,
         }
     }
 
@@ -194,9 +279,12 @@ impl CheckoutFinishedEvent {
             status: "success".to_string(),
             session_id,
             session_url: None,
+            expires_at: None,
             payment_intent_id,
             error_message: None,
+            error_code: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
+            coupon: None,
         }
     }
 
@@ -219,23 +307,92 @@ impl CheckoutFinishedEvent {
             status: "failed".to_string(),
             session_id,
             session_url: None,
+            expires_at: None,
             payment_intent_id: None,
             error_message: Some(error_message),
+            error_code: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
+            coupon: None,
         }
     }
+
+    /// Attach the promo code the checkout was opened with
+    pub fn with_coupon(mut self, coupon: Option<CheckoutCoupon>) -> Self {
+        self.coupon = coupon.map(|coupon| CheckoutCoupon {
+            stripe_coupon_id: None,
+            ..coupon
+        });
+        self
+    }
+}
+
+/// Outgoing event to rust-app on the "checkout.disputes" topic, keyed by request_id
+/// Published in three scenarios:
+/// - status="opened": A chargeback was opened; freeze `hold_cents` coins
+/// - status="won": The dispute closed in our favour; release the frozen coins
+/// - status="lost": The dispute closed against us; the frozen coins are gone for good
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutDisputeEvent {
+    /// Stripe dispute ID (dp_...)
+    pub dispute_id: String,
+    /// Checkout the disputed payment belongs to
+    pub request_id: String,
+    pub user_id: i64,
+    /// "opened", "won" or "lost"
+    pub status: String,
+    /// Disputed amount, in the payment's currency
+    pub amount_cents: i64,
+    pub currency: String,
+    /// Coins to freeze (and, once lost, to deduct)
+    pub hold_cents: i64,
+    /// Stripe dispute reason, e.g. "fraudulent"
+    pub reason: String,
+    pub payment_intent_id: Option<String>,
+    /// ISO 8601 timestamp of the change
+    pub timestamp: String,
+}
+
+/// Outgoing event to rust-app on the "checkout.subscriptions" topic, keyed by user_id
+/// Published when a premium subscription starts or stops granting premium:
+/// - SubscriptionActivated: Paid (or trialing); premium until `current_period_end`
+/// - SubscriptionLapsed: Canceled or unpaid; premium ends now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CheckoutSubscriptionEvent {
+    #[serde(rename = "checkout.subscription.activated")]
+    SubscriptionActivated {
+        /// Stripe subscription ID (sub_...)
+        subscription_id: String,
+        user_id: i64,
+        /// Stripe subscription status, e.g. "active"
+        status: String,
+        /// ISO 8601 end of the paid period
+        current_period_end: Option<String>,
+        cancel_at_period_end: bool,
+        /// ISO 8601 timestamp of the change
+        timestamp: String,
+    },
+    #[serde(rename = "checkout.subscription.lapsed")]
+    SubscriptionLapsed {
+        subscription_id: String,
+        user_id: i64,
+        /// Stripe subscription status, e.g. "canceled" or "unpaid"
+        status: String,
+        timestamp: String,
+    },
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{CheckoutCommand, CheckoutEvent, CheckoutFinishedEvent, CheckoutRequestEvent};
+    use super::{
+        CheckoutCommand, CheckoutCoupon, CheckoutEvent, CheckoutFinishedEvent, CheckoutRequestEvent,
+    };
     use serde_json::json;
 
     #[test]
     fn checkout_command_serializes_type() {
         let command = CheckoutCommand::CreateSession {
             request_id: "req_123".to_string(),
-            service_token: "svc_token".to_string(),
             user_id: 9,
             amount_cents: 1200,
             currency: "eur".to_string(),
@@ -245,6 +402,7 @@ mod tests {
             metadata: json!({"source": "balance"}),
             requested_at: "2024-01-01T00:00:00Z".to_string(),
             customer_email: None,
+            coupon: None,
         };
 
         let value = serde_json::to_value(command).expect("serialize command");
@@ -253,10 +411,8 @@ mod tests {
             Some("checkout.command.create_session")
         );
         assert_eq!(value.get("amount_cents").and_then(|val| val.as_i64()), Some(1200));
-        assert_eq!(
-            value.get("service_token").and_then(|val| val.as_str()),
-            Some("svc_token")
-        );
+        assert!(value.get("service_token").is_none());
+        assert!(value.get("coupon").is_none());
     }
 
     #[test]
@@ -348,4 +504,96 @@ mod tests {
         assert_eq!(event.error_message, Some("Card declined".to_string()));
         assert!(event.payment_intent_id.is_none());
     }
+
+    #[test]
+    fn checkout_finished_event_session_created_carries_url_and_expiry() {
+        let expires_at = chrono::DateTime::from_timestamp(1_792_238_400, 0);
+        let event = CheckoutFinishedEvent::session_created(
+            "req_654".to_string(),
+            8,
+            1500,
+            "eur".to_string(),
+            "balance_topup".to_string(),
+            "cs_test_ready".to_string(),
+            "https://checkout.stripe.com/c/pay/cs_test_ready".to_string(),
+            expires_at,
+        );
+
+        let value = serde_json::to_value(&event).expect("serialize");
+        assert_eq!(value["status"], "session_created");
+        assert_eq!(value["session_url"], "https://checkout.stripe.com/c/pay/cs_test_ready");
+        assert_eq!(value["expires_at"], "2026-10-17T12:00:00+00:00");
+
+        let failed = CheckoutFinishedEvent::session_failed(
+            "req_655".to_string(),
+            8,
+            1500,
+            "eur".to_string(),
+            "balance_topup".to_string(),
+            "daily_volume_exceeded",
+            "Daily payment limit reached".to_string(),
+        );
+        let value = serde_json::to_value(&failed).expect("serialize");
+        assert_eq!(value["status"], "session_failed");
+        assert_eq!(value["error_code"], "daily_volume_exceeded");
+        assert!(value["session_url"].is_null());
+        assert!(value.get("expires_at").is_none());
+    }
+
+    #[test]
+    fn checkout_finished_event_carries_coupon_without_stripe_id() {
+        let event = CheckoutFinishedEvent::success(
+            "req_321".to_string(),
+            12,
+            1000,
+            "eur".to_string(),
+            "balance_topup".to_string(),
+            Some("cs_test_coupon".to_string()),
+            None,
+        )
+        .with_coupon(Some(CheckoutCoupon {
+            coupon_id: 3,
+            code: "SPRING20".to_string(),
+            discount_cents: 200,
+            stripe_coupon_id: Some("co_test_123".to_string()),
+        }));
+
+        let value = serde_json::to_value(&event).expect("serialize");
+        assert_eq!(value["coupon"]["code"], "SPRING20");
+        assert_eq!(value["coupon"]["discount_cents"], 200);
+        assert!(value["coupon"].get("stripe_coupon_id").is_none());
+
+        let plain = CheckoutFinishedEvent::failed(
+            "req_322".to_string(),
+            12,
+            1000,
+            "eur".to_string(),
+            "balance_topup".to_string(),
+            None,
+            "Card declined".to_string(),
+        );
+        let value = serde_json::to_value(&plain).expect("serialize");
+        assert!(value.get("coupon").is_none());
+    }
+
+    #[test]
+    fn every_dto_is_snake_case() {
+        let violations = api_casing::contract::scan_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+        let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        assert!(violations.is_empty(), "{:#?}", violations);
+    }
+
+    #[test]
+    fn events_convert_between_casings() {
+        let event = CheckoutFinishedEvent::failed(
+            "req_323".to_string(),
+            12,
+            1000,
+            "eur".to_string(),
+            "balance_topup".to_string(),
+            None,
+            "Card declined".to_string(),
+        );
+        api_casing::contract::assert_casing(&event, api_casing::Casing::Snake);
+    }
 }
@@ -0,0 +1,279 @@
+//! Request validation
+//!
+//! Request DTOs implement `Validate`; failures are returned in the same
+//! envelope blazing_sun uses (`status`, `message`, `errors` per field and
+//! `fields` with `field`/`code`/`message`/`constraint`), including for bodies
+//! that fail to deserialize (`json_error_handler`).
+
+use actix_web::error::JsonPayloadError;
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::coupons::{self, CouponRequest};
+use crate::CheckoutSessionRequest;
+
+/// One failed rule on one field
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<Value>,
+}
+
+impl FieldError {
+    pub fn new(field: &str, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: message.into(),
+            constraint: None,
+        }
+    }
+
+    pub fn with_constraint(mut self, constraint: Value) -> Self {
+        self.constraint = Some(constraint);
+        self
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ValidationErrorResponse {
+    status: &'static str,
+    message: &'static str,
+    errors: HashMap<String, Vec<String>>,
+    fields: Vec<FieldError>,
+}
+
+impl ValidationErrorResponse {
+    pub fn new(message: &'static str, fields: Vec<FieldError>) -> Self {
+        let mut errors: HashMap<String, Vec<String>> = HashMap::new();
+        for error in &fields {
+            errors
+                .entry(error.field.clone())
+                .or_default()
+                .push(error.message.clone());
+        }
+
+        Self {
+            status: "error",
+            message,
+            errors,
+            fields,
+        }
+    }
+}
+
+pub trait Validate {
+    /// Every failed rule (empty = valid)
+    fn validate(&self) -> Vec<FieldError>;
+
+    /// 400 response when the request is invalid
+    fn validation_response(&self) -> Option<HttpResponse> {
+        let errors = self.validate();
+        (!errors.is_empty()).then(|| {
+            HttpResponse::BadRequest().json(ValidationErrorResponse::new("Validation failed", errors))
+        })
+    }
+}
+
+/// Largest coin amount whose cent value still fits in an i64
+const MAX_COINS: i64 = i64::MAX / 100;
+
+impl Validate for CheckoutSessionRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.amount < 1 {
+            errors.push(
+                FieldError::new("amount", "range", "Amount must be at least 1")
+                    .with_constraint(json!({ "min": 1 })),
+            );
+        } else if self.amount > MAX_COINS {
+            errors.push(
+                FieldError::new("amount", "range", "Amount is too large")
+                    .with_constraint(json!({ "max": MAX_COINS })),
+            );
+        }
+
+        if let Some(code) = &self.coupon {
+            validate_code("coupon", code, &mut errors);
+        }
+
+        errors
+    }
+}
+
+fn validate_code(field: &str, code: &str, errors: &mut Vec<FieldError>) {
+    if !coupons::is_valid_code(&coupons::normalize_code(code)) {
+        errors.push(
+            FieldError::new(
+                field,
+                "format",
+                "Coupon code may only contain letters, digits, - and _",
+            )
+            .with_constraint(json!({ "max_length": coupons::MAX_CODE_LENGTH })),
+        );
+    }
+}
+
+impl Validate for CouponRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        validate_code("code", &self.code, &mut errors);
+
+        match (self.percent_off, self.amount_off_cents) {
+            (Some(_), Some(_)) | (None, None) => errors.push(FieldError::new(
+                "percent_off",
+                "required",
+                "Exactly one of percent_off and amount_off_cents is required",
+            )),
+            (Some(percent), None) if !(1..=100).contains(&percent) => errors.push(
+                FieldError::new("percent_off", "range", "Percent off must be between 1 and 100")
+                    .with_constraint(json!({ "min": 1, "max": 100 })),
+            ),
+            (None, Some(amount)) if amount < 1 => errors.push(
+                FieldError::new("amount_off_cents", "range", "Amount off must be at least 1")
+                    .with_constraint(json!({ "min": 1 })),
+            ),
+            _ => {}
+        }
+
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            errors.push(FieldError::new(
+                "currency",
+                "format",
+                "Currency must be a three-letter ISO code",
+            ));
+        }
+
+        for (field, limit) in [
+            ("max_redemptions", self.max_redemptions),
+            ("max_redemptions_per_user", self.max_redemptions_per_user),
+        ] {
+            if limit.is_some_and(|limit| limit < 1) {
+                errors.push(
+                    FieldError::new(field, "range", "Limit must be at least 1")
+                        .with_constraint(json!({ "min": 1 })),
+                );
+            }
+        }
+
+        if let (Some(starts_at), Some(expires_at)) = (self.starts_at, self.expires_at) {
+            if expires_at <= starts_at {
+                errors.push(FieldError::new(
+                    "expires_at",
+                    "after",
+                    "Expiry must be after the start",
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+/// `JsonConfig` error handler: malformed bodies get the validation envelope
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::error::Error {
+    let detail = err.to_string();
+
+    let field_error = match &err {
+        JsonPayloadError::Deserialize(source) if source.is_data() => {
+            let message = source.to_string();
+            match message.strip_prefix("missing field `").and_then(|rest| rest.split('`').next()) {
+                Some(field) => FieldError::new(field, "required", format!("{} is required", field)),
+                None => FieldError::new("body", "invalid_type", detail),
+            }
+        }
+        JsonPayloadError::Deserialize(_) => FieldError::new("body", "invalid_json", detail),
+        JsonPayloadError::ContentType => FieldError::new("body", "content_type", detail),
+        _ => FieldError::new("body", "invalid", detail),
+    };
+
+    let response = HttpResponse::BadRequest().json(ValidationErrorResponse::new(
+        "Invalid request body",
+        vec![field_error],
+    ));
+
+    actix_web::error::InternalError::from_response(err, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_amount_must_be_positive_and_fit_in_cents() {
+        let errors = session(0, None).validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "range");
+        assert_eq!(errors[0].constraint, Some(json!({ "min": 1 })));
+
+        let errors = session(i64::MAX, None).validate();
+        assert_eq!(errors[0].message, "Amount is too large");
+
+        assert!(session(5, None).validate().is_empty());
+    }
+
+    fn session(amount: i64, coupon: Option<&str>) -> CheckoutSessionRequest {
+        CheckoutSessionRequest {
+            amount,
+            coupon: coupon.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn session_coupon_must_be_a_code() {
+        assert!(session(5, Some("spring20")).validate().is_empty());
+
+        let errors = session(5, Some("DROP TABLE;")).validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "coupon");
+        assert_eq!(errors[0].code, "format");
+    }
+
+    #[test]
+    fn coupons_need_exactly_one_valid_discount() {
+        let coupon: CouponRequest =
+            serde_json::from_value(json!({ "code": "SPRING20", "percent_off": 20 })).unwrap();
+        assert!(coupon.validate().is_empty());
+        assert_eq!(coupon.currency, "eur");
+        assert!(coupon.active);
+
+        let both = CouponRequest {
+            amount_off_cents: Some(500),
+            ..coupon.clone()
+        };
+        assert_eq!(both.validate()[0].code, "required");
+
+        let too_much = CouponRequest {
+            percent_off: Some(120),
+            ..coupon.clone()
+        };
+        assert_eq!(too_much.validate()[0].field, "percent_off");
+
+        let backwards = CouponRequest {
+            starts_at: Some(chrono::Utc::now()),
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::days(1)),
+            max_redemptions: Some(0),
+            ..coupon
+        };
+        let fields: Vec<String> = backwards.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["max_redemptions", "expires_at"]);
+    }
+
+    #[test]
+    fn response_groups_messages_per_field() {
+        let response = ValidationErrorResponse::new(
+            "Validation failed",
+            vec![FieldError::new("amount", "range", "Amount must be at least 1")],
+        );
+
+        assert_eq!(response.errors["amount"], vec!["Amount must be at least 1"]);
+        assert_eq!(response.fields.len(), 1);
+    }
+}
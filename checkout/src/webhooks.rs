@@ -0,0 +1,220 @@
+//! Stripe webhook guard
+//!
+//! Deliveries to `/webhooks/stripe` must carry a `Stripe-Signature` made with
+//! `STRIPE_WEBHOOK_SECRET` or, while a secret is being rotated, one of
+//! `STRIPE_WEBHOOK_PREVIOUS_SECRETS`, timestamped within
+//! `STRIPE_WEBHOOK_TOLERANCE_SECONDS` of now.
+//!
+//! Accepted event ids are remembered in Redis for
+//! `STRIPE_WEBHOOK_REPLAY_TTL_SECONDS`; a second delivery of the same event is
+//! acknowledged without being applied again. The id is forgotten when applying
+//! the event fails, so Stripe's retry of a failed delivery still goes through.
+//! Without Redis the replay check is skipped (payments stay idempotent in the
+//! database). Rejections are counted per reason in
+//! `stripe_webhook_rejections_total` on `/metrics`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use redis::AsyncCommands;
+use secrets_provider::{Redacted, SecretsProvider};
+
+use crate::stripe::{SignatureError, DEFAULT_WEBHOOK_TOLERANCE_SECONDS};
+
+/// Default `STRIPE_WEBHOOK_REPLAY_TTL_SECONDS`
+const DEFAULT_REPLAY_TTL_SECONDS: u64 = 86_400;
+
+/// Webhook settings read at startup
+#[derive(Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    /// The current secret first, then the previous ones
+    pub secrets: Vec<String>,
+    /// Largest accepted distance of the `t=` timestamp from now (0 = unchecked)
+    pub tolerance_seconds: i64,
+    /// How long accepted event ids are remembered
+    pub replay_ttl_seconds: u64,
+}
+
+impl WebhookConfig {
+    pub fn load(provider: &dyn SecretsProvider) -> Self {
+        let secrets = parse_secrets(
+            &provider.var("STRIPE_WEBHOOK_SECRET").unwrap_or_default(),
+            &provider.var("STRIPE_WEBHOOK_PREVIOUS_SECRETS").unwrap_or_default(),
+        );
+        let tolerance_seconds = provider.var("STRIPE_WEBHOOK_TOLERANCE_SECONDS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|seconds| *seconds >= 0)
+            .unwrap_or(DEFAULT_WEBHOOK_TOLERANCE_SECONDS);
+        let replay_ttl_seconds = provider.var("STRIPE_WEBHOOK_REPLAY_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(DEFAULT_REPLAY_TTL_SECONDS);
+
+        Self {
+            secrets,
+            tolerance_seconds,
+            // A replay inside the tolerance must still find the id
+            replay_ttl_seconds: replay_ttl_seconds.max(tolerance_seconds as u64),
+        }
+    }
+}
+
+impl std::fmt::Debug for WebhookConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secrets: Vec<Redacted> = self.secrets.iter().map(|secret| Redacted(secret.as_str())).collect();
+        f.debug_struct("WebhookConfig")
+            .field("secrets", &secrets)
+            .field("tolerance_seconds", &self.tolerance_seconds)
+            .field("replay_ttl_seconds", &self.replay_ttl_seconds)
+            .finish()
+    }
+}
+
+/// The current secret followed by the comma separated previous ones
+fn parse_secrets(current: &str, previous: &str) -> Vec<String> {
+    std::iter::once(current)
+        .chain(previous.split(','))
+        .map(str::trim)
+        .filter(|secret| !secret.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Why a webhook delivery was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    MissingHeader,
+    Signature(SignatureError),
+    Replayed,
+}
+
+impl Rejection {
+    pub const ALL: [Rejection; 6] = [
+        Rejection::MissingHeader,
+        Rejection::Signature(SignatureError::NoSecret),
+        Rejection::Signature(SignatureError::MalformedHeader),
+        Rejection::Signature(SignatureError::Mismatch),
+        Rejection::Signature(SignatureError::OutsideTolerance),
+        Rejection::Replayed,
+    ];
+
+    pub fn reason(self) -> &'static str {
+        match self {
+            Rejection::MissingHeader => "missing_header",
+            Rejection::Signature(error) => error.reason(),
+            Rejection::Replayed => "replayed",
+        }
+    }
+}
+
+/// Counters behind `stripe_webhook_rejections_total`
+#[derive(Debug, Default)]
+pub struct WebhookMetrics {
+    counts: [AtomicU64; Rejection::ALL.len()],
+}
+
+impl WebhookMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn index(rejection: Rejection) -> usize {
+        Rejection::ALL
+            .iter()
+            .position(|r| *r == rejection)
+            .unwrap_or_default()
+    }
+
+    pub fn record(&self, rejection: Rejection) {
+        self.counts[Self::index(rejection)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self, rejection: Rejection) -> u64 {
+        self.counts[Self::index(rejection)].load(Ordering::Relaxed)
+    }
+
+    /// Prometheus text exposition of `stripe_webhook_rejections_total`
+    pub fn render_prometheus(&self, service: &str) -> String {
+        let mut out = String::from(
+            "# HELP stripe_webhook_rejections_total Refused Stripe webhook deliveries by reason\n\
+             # TYPE stripe_webhook_rejections_total counter\n",
+        );
+        for rejection in Rejection::ALL {
+            out.push_str(&format!(
+                "stripe_webhook_rejections_total{{service=\"{}\",reason=\"{}\"}} {}\n",
+                service,
+                rejection.reason(),
+                self.count(rejection)
+            ));
+        }
+        out
+    }
+}
+
+fn event_key(event_id: &str) -> String {
+    format!("checkout:stripe_event:{}", event_id)
+}
+
+/// Remember an event id; false when it was already received within the TTL
+pub async fn claim_event(
+    redis: &mut redis::aio::ConnectionManager,
+    event_id: &str,
+    ttl_seconds: u64,
+) -> redis::RedisResult<bool> {
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(event_key(event_id))
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl_seconds)
+        .query_async(redis)
+        .await?;
+
+    Ok(claimed.is_some())
+}
+
+/// Forget an event id so a redelivery is applied
+pub async fn release_event(
+    redis: &mut redis::aio::ConnectionManager,
+    event_id: &str,
+) -> redis::RedisResult<()> {
+    redis.del(event_key(event_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_keep_the_current_one_first() {
+        assert_eq!(
+            parse_secrets("whsec_new", " whsec_old , ,whsec_older"),
+            vec!["whsec_new", "whsec_old", "whsec_older"]
+        );
+        assert_eq!(parse_secrets("", "whsec_old"), vec!["whsec_old"]);
+        assert!(parse_secrets("", "").is_empty());
+    }
+
+    #[test]
+    fn metrics_count_and_render_every_reason() {
+        let metrics = WebhookMetrics::new();
+        metrics.record(Rejection::Replayed);
+        metrics.record(Rejection::Signature(SignatureError::OutsideTolerance));
+        metrics.record(Rejection::Signature(SignatureError::OutsideTolerance));
+
+        assert_eq!(metrics.count(Rejection::Replayed), 1);
+        assert_eq!(
+            metrics.count(Rejection::Signature(SignatureError::OutsideTolerance)),
+            2
+        );
+
+        let rendered = metrics.render_prometheus("checkout");
+        assert!(rendered.contains(
+            "stripe_webhook_rejections_total{service=\"checkout\",reason=\"outside_tolerance\"} 2"
+        ));
+        assert!(rendered.contains(
+            "stripe_webhook_rejections_total{service=\"checkout\",reason=\"missing_header\"} 0"
+        ));
+    }
+}
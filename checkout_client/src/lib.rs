@@ -0,0 +1,459 @@
+//! Checkout service HTTP client
+//!
+//! Typed calls to the checkout service: creating checkout sessions and listing
+//! transactions on behalf of a user (with the user's JWT), and the internal
+//! transaction listing (with a short-lived service token from `service_auth`).
+//! Idempotent calls are retried with exponential backoff when checkout is
+//! unreachable or overloaded; a session is only retried when the caller passes
+//! an `Idempotency-Key`, so a retry can never open a second session.
+//!
+//! Callers depend on the `CheckoutApi` trait rather than `HttpCheckoutClient`,
+//! so unit tests can swap in a fake. Checkout does not expose refunds yet; they
+//! get a method here once it does.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use service_auth::Signer;
+
+/// Audience checkout expects on service tokens
+pub const AUDIENCE: &str = "checkout";
+
+/// Header checkout's idempotency middleware keys replays on
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Errors returned when calling the checkout service
+#[derive(Debug, thiserror::Error)]
+pub enum CheckoutClientError {
+    #[error("SERVICE_AUTH_KEYS is not configured")]
+    NotConfigured,
+
+    #[error("checkout service request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("checkout service responded with {status}")]
+    Status {
+        status: StatusCode,
+        /// `message` of checkout's error body, when it sent one
+        message: Option<String>,
+    },
+}
+
+impl CheckoutClientError {
+    /// Whether the same request may succeed if sent again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::NotConfigured => false,
+            Self::Request(e) => e.is_connect() || e.is_timeout(),
+            Self::Status { status, .. } => matches!(
+                *status,
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+        }
+    }
+}
+
+/// How often and how patiently retryable calls are repeated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before attempt `attempt + 1`: the base delay doubled per attempt, capped
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Body of `POST /sessions`
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateSessionRequest {
+    /// Top-up amount in whole currency units
+    pub amount: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coupon: Option<String>,
+}
+
+/// Stripe checkout session the user is redirected to
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckoutSession {
+    pub session_id: String,
+    pub url: String,
+}
+
+/// Transaction as recorded by the checkout service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutTransaction {
+    pub request_id: String,
+    pub user_id: i64,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub purpose: String,
+    pub status: String,
+    pub checkout_id: Option<String>,
+    pub payment_intent_id: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Query of `GET /transactions`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransactionsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    /// `next_cursor` or `prev_cursor` of a previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// A page of `GET /transactions`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionsPage {
+    pub transactions: Vec<CheckoutTransaction>,
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+    #[serde(default)]
+    pub prev_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// Calls made against the checkout service
+#[async_trait]
+pub trait CheckoutApi: Send + Sync {
+    /// Open a checkout session for the user the JWT belongs to
+    ///
+    /// With an `idempotency_key` the call is retried, and checkout replays the
+    /// first session instead of opening another.
+    async fn create_session(
+        &self,
+        user_token: &str,
+        request: &CreateSessionRequest,
+        idempotency_key: Option<&str>,
+    ) -> Result<CheckoutSession, CheckoutClientError>;
+
+    /// A page of the JWT user's transactions, newest first
+    async fn transactions(
+        &self,
+        user_token: &str,
+        query: &TransactionsQuery,
+    ) -> Result<TransactionsPage, CheckoutClientError>;
+
+    /// A user's transactions created before `before`, newest first (service token)
+    async fn user_transactions(
+        &self,
+        user_id: i64,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<CheckoutTransaction>, CheckoutClientError>;
+}
+
+/// Where checkout lives and how calls to it behave
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub base_url: String,
+    /// Per attempt
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+}
+
+impl ClientConfig {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            timeout: Duration::from_secs(5),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+/// `CheckoutApi` over HTTP
+#[derive(Debug, Clone)]
+pub struct HttpCheckoutClient {
+    http: reqwest::Client,
+    base_url: String,
+    retry: RetryPolicy,
+    /// None when service tokens are not configured; internal calls then fail
+    signer: Option<Signer>,
+}
+
+impl HttpCheckoutClient {
+    pub fn new(config: ClientConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            http,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            retry: config.retry,
+            signer: None,
+        }
+    }
+
+    /// Sign internal calls with this signer
+    pub fn with_signer(mut self, signer: Option<Signer>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Send a request built by `build`, retrying it per the policy when `retry` is set
+    async fn send<T: DeserializeOwned>(
+        &self,
+        retry: bool,
+        build: impl Fn() -> Result<RequestBuilder, CheckoutClientError> + Send + Sync,
+    ) -> Result<T, CheckoutClientError> {
+        let attempts = if retry {
+            self.retry.max_attempts.max(1)
+        } else {
+            1
+        };
+
+        let mut attempt = 1;
+        loop {
+            let result = async {
+                let mut request = build()?;
+                if let Some(request_id) = logging::request_id::current() {
+                    request = request.header(logging::request_id::HEADER, request_id);
+                }
+                read_response(request.send().await?).await
+            }
+            .await;
+
+            match result {
+                Err(e) if attempt < attempts && e.is_retryable() => {
+                    tracing::debug!(attempt, error = %e, "Retrying checkout service call");
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+async fn read_response<T: DeserializeOwned>(response: Response) -> Result<T, CheckoutClientError> {
+    let status = response.status();
+    if !status.is_success() {
+        let message = response
+            .json::<ErrorBody>()
+            .await
+            .ok()
+            .map(|body| body.message);
+        return Err(CheckoutClientError::Status { status, message });
+    }
+
+    Ok(response.json::<T>().await?)
+}
+
+#[async_trait]
+impl CheckoutApi for HttpCheckoutClient {
+    async fn create_session(
+        &self,
+        user_token: &str,
+        request: &CreateSessionRequest,
+        idempotency_key: Option<&str>,
+    ) -> Result<CheckoutSession, CheckoutClientError> {
+        self.send(idempotency_key.is_some(), || {
+            let mut builder = self
+                .http
+                .post(self.url("/sessions"))
+                .bearer_auth(user_token)
+                .json(request);
+            if let Some(key) = idempotency_key {
+                builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+            }
+            Ok(builder)
+        })
+        .await
+    }
+
+    async fn transactions(
+        &self,
+        user_token: &str,
+        query: &TransactionsQuery,
+    ) -> Result<TransactionsPage, CheckoutClientError> {
+        self.send(true, || {
+            Ok(self
+                .http
+                .get(self.url("/transactions"))
+                .bearer_auth(user_token)
+                .query(query))
+        })
+        .await
+    }
+
+    async fn user_transactions(
+        &self,
+        user_id: i64,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<CheckoutTransaction>, CheckoutClientError> {
+        let mut query = vec![("limit", limit.to_string())];
+        if let Some(before) = before {
+            query.push(("before", before.to_rfc3339()));
+        }
+
+        let page: TransactionsPage = self
+            .send(true, || {
+                // Signed per attempt, so a retry never sends an expired token
+                let token = self
+                    .signer
+                    .as_ref()
+                    .ok_or(CheckoutClientError::NotConfigured)?
+                    .sign(AUDIENCE);
+                Ok(self
+                    .http
+                    .get(self.url(&format!("/internal/users/{}/transactions", user_id)))
+                    .header(service_auth::HEADER, token)
+                    .query(&query))
+            })
+            .await?;
+
+        Ok(page.transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(350));
+        assert_eq!(policy.delay(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_only_overload_statuses_are_retried() {
+        let status = |status| CheckoutClientError::Status {
+            status,
+            message: None,
+        };
+
+        assert!(status(StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+        assert!(status(StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(!status(StatusCode::INTERNAL_SERVER_ERROR).is_retryable());
+        assert!(!status(StatusCode::BAD_REQUEST).is_retryable());
+        assert!(!CheckoutClientError::NotConfigured.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_internal_calls_need_a_signer() {
+        // Fails before anything is sent, so the address is never dialled
+        let client = HttpCheckoutClient::new(ClientConfig::new("http://checkout.invalid"));
+
+        let result = client.user_transactions(1, None, 10).await;
+        assert!(matches!(result, Err(CheckoutClientError::NotConfigured)));
+    }
+
+    /// Callers take `&dyn CheckoutApi`, so tests hand them a fake
+    struct FakeCheckout;
+
+    #[async_trait]
+    impl CheckoutApi for FakeCheckout {
+        async fn create_session(
+            &self,
+            _user_token: &str,
+            request: &CreateSessionRequest,
+            _idempotency_key: Option<&str>,
+        ) -> Result<CheckoutSession, CheckoutClientError> {
+            Ok(CheckoutSession {
+                session_id: format!("cs_{}", request.amount),
+                url: "https://checkout.test/pay".to_string(),
+            })
+        }
+
+        async fn transactions(
+            &self,
+            _user_token: &str,
+            _query: &TransactionsQuery,
+        ) -> Result<TransactionsPage, CheckoutClientError> {
+            Ok(TransactionsPage {
+                transactions: Vec::new(),
+                next_cursor: None,
+                prev_cursor: None,
+            })
+        }
+
+        async fn user_transactions(
+            &self,
+            _user_id: i64,
+            _before: Option<DateTime<Utc>>,
+            _limit: i64,
+        ) -> Result<Vec<CheckoutTransaction>, CheckoutClientError> {
+            Err(CheckoutClientError::NotConfigured)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trait_objects_accept_fakes() {
+        let api: &dyn CheckoutApi = &FakeCheckout;
+        let request = CreateSessionRequest {
+            amount: 25,
+            coupon: None,
+        };
+
+        let session = api.create_session("jwt", &request, None).await.unwrap();
+        assert_eq!(session.session_id, "cs_25");
+        assert!(api.user_transactions(1, None, 10).await.is_err());
+    }
+}
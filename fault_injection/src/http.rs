@@ -0,0 +1,92 @@
+//! `/internal/faults` endpoints for actix-web
+//!
+//! - `GET /internal/faults`: configured targets with their spec and injected count
+//! - `PUT /internal/faults/{target}`: set a target's [`FaultSpec`] (JSON body)
+//! - `DELETE /internal/faults/{target}`: stop injecting into a target
+//! - `DELETE /internal/faults`: stop injecting into every target
+//!
+//! They act on the process-wide [`global`] injector. There is no
+//! authentication: the routes only exist in `chaos` builds, which are meant
+//! for test environments.
+
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+
+use crate::{global, FaultSpec, FaultStatus, Target};
+
+#[derive(Serialize)]
+struct FaultsResponse {
+    faults: std::collections::BTreeMap<Target, FaultStatus>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Mount the endpoints, e.g. `App::new().configure(fault_injection::http::configure)`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/internal/faults")
+            .route(web::get().to(list))
+            .route(web::delete().to(clear_all)),
+    )
+    .service(
+        web::resource("/internal/faults/{target}")
+            .route(web::put().to(set))
+            .route(web::delete().to(clear)),
+    );
+}
+
+fn faults() -> HttpResponse {
+    HttpResponse::Ok().json(FaultsResponse {
+        faults: global().snapshot(),
+    })
+}
+
+fn bad_request(error: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse { error })
+}
+
+fn parse_target(value: &str) -> Result<Target, String> {
+    Target::parse(value).ok_or_else(|| {
+        let known: Vec<&str> = Target::ALL.iter().map(Target::as_str).collect();
+        format!(
+            "unknown target `{}`; expected one of {}",
+            value,
+            known.join(", ")
+        )
+    })
+}
+
+async fn list() -> HttpResponse {
+    faults()
+}
+
+async fn set(path: web::Path<String>, spec: web::Json<FaultSpec>) -> HttpResponse {
+    let target = match parse_target(&path) {
+        Ok(target) => target,
+        Err(error) => return bad_request(error),
+    };
+    if let Err(error) = spec.validate() {
+        return bad_request(error);
+    }
+
+    global().set(target, spec.into_inner());
+    faults()
+}
+
+async fn clear(path: web::Path<String>) -> HttpResponse {
+    match parse_target(&path) {
+        Ok(target) => {
+            global().clear(target);
+            faults()
+        }
+        Err(error) => bad_request(error),
+    }
+}
+
+async fn clear_all() -> HttpResponse {
+    global().clear_all();
+    faults()
+}
@@ -0,0 +1,434 @@
+//! Fault injection
+//!
+//! Test-only hooks that make a dependency look flaky, so integration tests can
+//! check how the services retry, buffer and dead-letter when Kafka, Stripe or
+//! Redis misbehave. Each hooked call site awaits [`check`] before talking to
+//! the dependency; an [`InjectedFault`] is turned into the error that call site
+//! would get from a real outage.
+//!
+//! Without the `enabled` feature [`check`] always succeeds and nothing else in
+//! this crate is consulted, so regular builds pay nothing for the hooks. The
+//! services forward it as their own `chaos` feature.
+//!
+//! A [`FaultSpec`] per [`Target`] says how long every call is delayed, how many
+//! of the next calls fail outright and what share of the rest fails at random.
+//! Specs are read from the environment at first use and can be changed while
+//! running through the `/internal/faults` endpoints ([`http`], `actix` feature):
+//!
+//! ```text
+//! FAULT_KAFKA_PRODUCER=failure_rate=0.5,latency_ms=200
+//! FAULT_STRIPE=fail_next=3
+//! FAULT_REDIS=latency_ms=1500
+//! FAULT_SEED=42
+//! ```
+//!
+//! `fail_next` is exact, which is what tests asserting a number of retries or a
+//! dead-lettered message want. Random failures come from a seeded generator, so
+//! with `FAULT_SEED` set the same sequence of calls sees the same failures.
+
+#[cfg(feature = "actix")]
+pub mod http;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Dependency a fault is injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    /// Records sent to Kafka (retried, then buffered while the breaker is open)
+    KafkaProducer,
+    /// Requests to the Stripe API (answered like a 503)
+    Stripe,
+    /// Gateway Redis commands (fail like a dropped connection)
+    Redis,
+}
+
+impl Target {
+    pub const ALL: [Target; 3] = [Target::KafkaProducer, Target::Stripe, Target::Redis];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Target::KafkaProducer => "kafka_producer",
+            Target::Stripe => "stripe",
+            Target::Redis => "redis",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|target| target.as_str() == value)
+    }
+
+    /// Environment variable holding the target's spec, e.g. `FAULT_STRIPE`
+    pub fn env_var(&self) -> String {
+        format!("FAULT_{}", self.as_str().to_ascii_uppercase())
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How calls to one target misbehave
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultSpec {
+    /// Share of calls failing at random, 0.0 to 1.0
+    #[serde(default)]
+    pub failure_rate: f64,
+    /// Delay added to every call, failing or not
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// The next this many calls fail, before `failure_rate` applies
+    #[serde(default)]
+    pub fail_next: u32,
+}
+
+impl FaultSpec {
+    /// Parse `failure_rate=0.5,latency_ms=200,fail_next=3`; every key is optional
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut spec = FaultSpec::default();
+
+        for pair in value
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, raw) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got `{}`", pair))?;
+            match key.trim() {
+                "failure_rate" => spec.failure_rate = parse_value(key, raw)?,
+                "latency_ms" => spec.latency_ms = parse_value(key, raw)?,
+                "fail_next" => spec.fail_next = parse_value(key, raw)?,
+                other => return Err(format!("unknown fault setting `{}`", other)),
+            }
+        }
+
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.failure_rate) {
+            return Err("failure_rate must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, raw: &str) -> Result<T, String> {
+    raw.trim()
+        .parse()
+        .map_err(|_| format!("invalid value for {}: `{}`", key.trim(), raw.trim()))
+}
+
+/// A call that was made to fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InjectedFault {
+    pub target: Target,
+}
+
+impl fmt::Display for InjectedFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "injected {} fault", self.target)
+    }
+}
+
+impl std::error::Error for InjectedFault {}
+
+/// What happens to one call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decision {
+    pub delay: Duration,
+    pub fail: bool,
+}
+
+/// A target's spec and how many calls it failed so far
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FaultStatus {
+    #[serde(flatten)]
+    pub spec: FaultSpec,
+    pub injected: u64,
+}
+
+struct State {
+    faults: HashMap<Target, FaultStatus>,
+    /// xorshift64* state; never zero
+    rng: u64,
+}
+
+/// Fault specs of every target
+pub struct FaultInjector {
+    state: Mutex<State>,
+}
+
+impl FaultInjector {
+    /// No faults; random failures drawn from `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                faults: HashMap::new(),
+                rng: seed.max(1),
+            }),
+        }
+    }
+
+    /// Specs from `FAULT_<TARGET>` and the seed from `FAULT_SEED` (random when
+    /// unset). Invalid specs are logged and ignored.
+    pub fn from_env() -> Self {
+        let seed = std::env::var("FAULT_SEED")
+            .ok()
+            .and_then(|seed| seed.trim().parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_nanos() as u64)
+                    .unwrap_or(1)
+            });
+        let injector = Self::new(seed);
+
+        for target in Target::ALL {
+            let value = std::env::var(target.env_var()).unwrap_or_default();
+            if value.trim().is_empty() {
+                continue;
+            }
+            match FaultSpec::parse(&value) {
+                Ok(spec) => injector.set(target, spec),
+                Err(err) => {
+                    warn!(dependency = %target, error = %err, "Ignoring invalid fault spec")
+                }
+            }
+        }
+
+        injector
+    }
+
+    /// Replace a target's spec (its injected count starts over)
+    pub fn set(&self, target: Target, spec: FaultSpec) {
+        warn!(dependency = %target, ?spec, "Fault injection configured");
+        self.state
+            .lock()
+            .unwrap()
+            .faults
+            .insert(target, FaultStatus { spec, injected: 0 });
+    }
+
+    /// Stop injecting faults into a target
+    pub fn clear(&self, target: Target) {
+        self.state.lock().unwrap().faults.remove(&target);
+    }
+
+    pub fn clear_all(&self) {
+        self.state.lock().unwrap().faults.clear();
+    }
+
+    /// Every configured target with its remaining spec and injected count
+    pub fn snapshot(&self) -> BTreeMap<Target, FaultStatus> {
+        self.state
+            .lock()
+            .unwrap()
+            .faults
+            .iter()
+            .map(|(target, status)| (*target, status.clone()))
+            .collect()
+    }
+
+    /// Decide the fate of the next call to `target`
+    pub fn decide(&self, target: Target) -> Decision {
+        let mut state = self.state.lock().unwrap();
+        let State { faults, rng } = &mut *state;
+        let Some(status) = faults.get_mut(&target) else {
+            return Decision::default();
+        };
+
+        let fail = if status.spec.fail_next > 0 {
+            status.spec.fail_next -= 1;
+            true
+        } else {
+            status.spec.failure_rate > 0.0 && next_unit(rng) < status.spec.failure_rate
+        };
+        if fail {
+            status.injected += 1;
+        }
+
+        Decision {
+            delay: Duration::from_millis(status.spec.latency_ms),
+            fail,
+        }
+    }
+
+    /// Apply the next decision for `target`: wait out the latency, then fail or not
+    pub async fn check(&self, target: Target) -> Result<(), InjectedFault> {
+        let decision = self.decide(target);
+        if !decision.delay.is_zero() {
+            tokio::time::sleep(decision.delay).await;
+        }
+
+        if decision.fail {
+            warn!(dependency = %target, "Injecting fault");
+            Err(InjectedFault { target })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Uniform value in [0, 1) from a xorshift64* step
+fn next_unit(state: &mut u64) -> f64 {
+    let mut x = *state;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    *state = x;
+    (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Injector shared by every hook of the process, configured from the
+/// environment on first use
+pub fn global() -> &'static FaultInjector {
+    static GLOBAL: OnceLock<FaultInjector> = OnceLock::new();
+    GLOBAL.get_or_init(|| {
+        warn!("Fault injection is compiled in; this build must not serve real traffic");
+        FaultInjector::from_env()
+    })
+}
+
+/// Hook for one call to `target`; always `Ok` unless built with `enabled`
+pub async fn check(target: Target) -> Result<(), InjectedFault> {
+    #[cfg(feature = "enabled")]
+    {
+        global().check(target).await
+    }
+
+    #[cfg(not(feature = "enabled"))]
+    {
+        let _ = target;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = FaultSpec::parse("failure_rate=0.25, latency_ms=150,fail_next=2").unwrap();
+        assert_eq!(
+            spec,
+            FaultSpec {
+                failure_rate: 0.25,
+                latency_ms: 150,
+                fail_next: 2
+            }
+        );
+        assert_eq!(FaultSpec::parse("").unwrap(), FaultSpec::default());
+        assert!(FaultSpec::parse("failure_rate=2").is_err());
+        assert!(FaultSpec::parse("failure_rate").is_err());
+        assert!(FaultSpec::parse("jitter=5").is_err());
+    }
+
+    #[test]
+    fn test_fail_next_is_exact() {
+        let injector = FaultInjector::new(7);
+        injector.set(
+            Target::Stripe,
+            FaultSpec {
+                fail_next: 2,
+                ..Default::default()
+            },
+        );
+
+        let fails: Vec<bool> = (0..4)
+            .map(|_| injector.decide(Target::Stripe).fail)
+            .collect();
+        assert_eq!(fails, vec![true, true, false, false]);
+        assert_eq!(injector.snapshot()[&Target::Stripe].injected, 2);
+        assert!(!injector.decide(Target::Redis).fail);
+    }
+
+    #[test]
+    fn test_random_failures_follow_the_seed() {
+        let run = |seed| {
+            let injector = FaultInjector::new(seed);
+            injector.set(
+                Target::KafkaProducer,
+                FaultSpec {
+                    failure_rate: 0.5,
+                    ..Default::default()
+                },
+            );
+            (0..64)
+                .map(|_| injector.decide(Target::KafkaProducer).fail)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(42), run(42));
+        let failures = run(42).into_iter().filter(|fail| *fail).count();
+        assert!((16..=48).contains(&failures), "{failures} of 64");
+
+        let injector = FaultInjector::new(1);
+        injector.set(
+            Target::Redis,
+            FaultSpec {
+                failure_rate: 1.0,
+                ..Default::default()
+            },
+        );
+        assert!((0..16).all(|_| injector.decide(Target::Redis).fail));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_check_waits_then_fails() {
+        let injector = FaultInjector::new(3);
+        injector.set(
+            Target::Redis,
+            FaultSpec {
+                latency_ms: 500,
+                fail_next: 1,
+                ..Default::default()
+            },
+        );
+
+        let started = tokio::time::Instant::now();
+        assert_eq!(
+            injector.check(Target::Redis).await,
+            Err(InjectedFault {
+                target: Target::Redis
+            })
+        );
+        assert!(injector.check(Target::Redis).await.is_ok());
+        assert_eq!(started.elapsed(), Duration::from_millis(1000));
+
+        injector.clear(Target::Redis);
+        assert!(injector.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_serializes_by_target() {
+        let injector = FaultInjector::new(5);
+        injector.set(
+            Target::KafkaProducer,
+            FaultSpec {
+                latency_ms: 10,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            serde_json::to_value(injector.snapshot()).unwrap(),
+            serde_json::json!({
+                "kafka_producer": { "failure_rate": 0.0, "latency_ms": 10, "fail_next": 0, "injected": 0 }
+            })
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Game command routing shared by blazing_sun and ws_gateway
+//!
+//! A room's commands must be handled in order, so every copy of them has to
+//! land on one partition. `GAMES_COMMANDS_PARTITIONING` chooses how commands
+//! are spread:
+//!
+//! - `room` (default): the region's `games.commands` topic, keyed by room_id
+//! - `composite`: `games.commands.composite`, keyed by `{game_type}:{room_id}`
+//! - `game_type`: one topic per game (`games.commands.bigger_dice`, ...) keyed
+//!   by room_id
+//!
+//! Commands outside a room are keyed by the user; the ones that do not name a
+//! game use `lobby` in place of the game type. Room commands are always routed
+//! by the room's recorded game type (`room_game_type_key`), never by what the
+//! command claims, so a room's commands never move between partitions.
+//!
+//! Non-default regions append `.<region>` to every name, as for the other
+//! games topics. Both services must run with the same settings.
+//!
+//! Leaving `room` goes through `GAMES_COMMANDS_MIGRATION` phases:
+//!
+//! - `dual_write`: commands are written to the room topic and the new topics;
+//!   the room topic is handled, the new copies are acked without handling
+//! - `cutover`: still written to both; the new topics are handled and the
+//!   room topic copies are acked without handling
+//! - `none` (default): only the new topics are written and read
+
+use serde_json::Value;
+
+/// Region whose topics carry no suffix
+pub const DEFAULT_REGION: &str = "default";
+
+/// Game types with their own command topic
+pub const GAME_TYPES: [&str; 2] = ["bigger_dice", "tic_tac_toe"];
+
+/// Topic name and key part for commands without a game type
+const LOBBY: &str = "lobby";
+
+/// The room-keyed command topic every deployment starts from
+const GAMES_COMMANDS: &str = "games.commands";
+
+/// How game commands are spread over topics and partitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partitioning {
+    Room,
+    Composite,
+    GameType,
+}
+
+impl Partitioning {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "room" => Some(Self::Room),
+            "composite" => Some(Self::Composite),
+            "game_type" => Some(Self::GameType),
+            _ => None,
+        }
+    }
+}
+
+/// Phase of a move away from the room-keyed topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Migration {
+    None,
+    DualWrite,
+    Cutover,
+}
+
+impl Migration {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "none" => Some(Self::None),
+            "dual_write" => Some(Self::DualWrite),
+            "cutover" => Some(Self::Cutover),
+            _ => None,
+        }
+    }
+}
+
+/// Where one copy of a game command is published
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub topic: String,
+    pub key: String,
+}
+
+/// Game command topics of one region
+#[derive(Debug, Clone)]
+pub struct GamesRouting {
+    partitioning: Partitioning,
+    migration: Migration,
+    region: String,
+    subscribed: Vec<String>,
+}
+
+impl GamesRouting {
+    pub fn new(partitioning: Partitioning, migration: Migration, region: &str) -> Self {
+        let mut routing = Self {
+            partitioning,
+            migration,
+            region: region.to_string(),
+            subscribed: Vec::new(),
+        };
+        routing.subscribed = routing.topics_to_subscribe();
+        routing
+    }
+
+    /// Whether routes depend on the game type (room commands then need the
+    /// room's recorded game type)
+    pub fn needs_game_type(&self) -> bool {
+        self.partitioning != Partitioning::Room
+    }
+
+    /// The room-keyed topic every deployment starts from
+    pub fn legacy_topic(&self) -> String {
+        self.regional(GAMES_COMMANDS)
+    }
+
+    /// Whether commands are written to the room topic besides the new ones
+    fn dual_writes(&self) -> bool {
+        self.partitioning != Partitioning::Room && self.migration != Migration::None
+    }
+
+    /// Topics and keys a command is published to; `key` is the room_id (or
+    /// the user for commands outside a room) and `game_type` the room's
+    /// recorded game type. During a migration the room topic copy comes first.
+    pub fn routes(&self, game_type: Option<&str>, key: &str) -> Vec<Route> {
+        let legacy = Route {
+            topic: self.legacy_topic(),
+            key: key.to_string(),
+        };
+
+        let route = match self.partitioning {
+            Partitioning::Room => return vec![legacy],
+            Partitioning::Composite => Route {
+                topic: self.regional("games.commands.composite"),
+                key: format!("{}:{}", game_type.unwrap_or(LOBBY), key),
+            },
+            Partitioning::GameType => Route {
+                topic: self.game_topic(game_type.unwrap_or(LOBBY)),
+                key: key.to_string(),
+            },
+        };
+
+        if self.dual_writes() {
+            vec![legacy, route]
+        } else {
+            vec![route]
+        }
+    }
+
+    /// Command topics the consumer subscribes to
+    pub fn subscribed_topics(&self) -> &[String] {
+        &self.subscribed
+    }
+
+    /// Whether commands read from `topic` are handled; the other copy of a
+    /// dual-written command is acked without handling
+    pub fn handles(&self, topic: &str) -> bool {
+        if !is_games_commands(topic) || !self.dual_writes() {
+            return true;
+        }
+
+        let is_legacy = topic == self.legacy_topic();
+        match self.migration {
+            Migration::DualWrite => is_legacy,
+            Migration::Cutover | Migration::None => !is_legacy,
+        }
+    }
+
+    fn topics_to_subscribe(&self) -> Vec<String> {
+        let mut topics = Vec::new();
+        if self.partitioning == Partitioning::Room || self.dual_writes() {
+            topics.push(self.legacy_topic());
+        }
+
+        match self.partitioning {
+            Partitioning::Room => {}
+            Partitioning::Composite => topics.push(self.regional("games.commands.composite")),
+            Partitioning::GameType => {
+                topics.extend(GAME_TYPES.iter().map(|game| self.game_topic(game)));
+                topics.push(self.game_topic(LOBBY));
+            }
+        }
+        topics
+    }
+
+    fn game_topic(&self, game_type: &str) -> String {
+        self.regional(&format!("games.commands.{}", game_type))
+    }
+
+    fn regional(&self, base: &str) -> String {
+        if self.region == DEFAULT_REGION {
+            base.to_string()
+        } else {
+            format!("{}.{}", base, self.region)
+        }
+    }
+}
+
+/// Whether a topic carries game commands (any region or strategy)
+fn is_games_commands(topic: &str) -> bool {
+    topic == GAMES_COMMANDS || topic.starts_with("games.commands.")
+}
+
+/// Redis key holding a room's game type; written by blazing_sun when the room
+/// is created, read by the gateway to route the room's commands
+pub fn room_game_type_key(room_id: &str) -> String {
+    format!("games:room_game_type:{}", room_id)
+}
+
+/// The known game type `value` names
+pub fn known_game_type(value: &str) -> Option<&'static str> {
+    GAME_TYPES
+        .iter()
+        .copied()
+        .find(|game_type| *game_type == value)
+}
+
+/// Game type of a command or event: the `<game_type>` of
+/// `games.command.<game_type>.<action>` / `games.event.<game_type>.<action>`,
+/// else the payload's `game_type`
+pub fn game_type_of(event_type: &str, payload: &Value) -> Option<&'static str> {
+    let from_event_type = event_type
+        .strip_prefix("games.command.")
+        .or_else(|| event_type.strip_prefix("games.event."))
+        .and_then(|rest| rest.split_once('.'))
+        .and_then(|(game_type, _)| known_game_type(game_type));
+
+    from_event_type.or_else(|| {
+        payload
+            .get("game_type")
+            .and_then(|v| v.as_str())
+            .and_then(known_game_type)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn routing(partitioning: Partitioning, migration: Migration) -> GamesRouting {
+        GamesRouting::new(partitioning, migration, DEFAULT_REGION)
+    }
+
+    fn topics(routes: &[Route]) -> Vec<&str> {
+        routes.iter().map(|route| route.topic.as_str()).collect()
+    }
+
+    #[test]
+    fn room_partitioning_keeps_the_shared_topic() {
+        let routing = routing(Partitioning::Room, Migration::DualWrite);
+        assert!(!routing.needs_game_type());
+        assert_eq!(
+            routing.routes(Some("bigger_dice"), "r-1"),
+            vec![Route {
+                topic: "games.commands".to_string(),
+                key: "r-1".to_string(),
+            }]
+        );
+        assert_eq!(routing.subscribed_topics(), ["games.commands"]);
+        assert!(routing.handles("games.commands"));
+    }
+
+    #[test]
+    fn composite_keys_carry_the_game_type() {
+        let routing = routing(Partitioning::Composite, Migration::None);
+        assert!(routing.needs_game_type());
+        let routes = routing.routes(Some("tic_tac_toe"), "r-1");
+        assert_eq!(topics(&routes), ["games.commands.composite"]);
+        assert_eq!(routes[0].key, "tic_tac_toe:r-1");
+        assert_eq!(routing.routes(None, "7")[0].key, "lobby:7");
+    }
+
+    #[test]
+    fn game_type_topics_are_regional() {
+        let routing = GamesRouting::new(Partitioning::GameType, Migration::None, "eu-west");
+        assert_eq!(
+            topics(&routing.routes(Some("bigger_dice"), "r-1")),
+            ["games.commands.bigger_dice.eu-west"]
+        );
+        assert_eq!(
+            topics(&routing.routes(None, "7")),
+            ["games.commands.lobby.eu-west"]
+        );
+        assert_eq!(
+            routing.subscribed_topics(),
+            [
+                "games.commands.bigger_dice.eu-west",
+                "games.commands.tic_tac_toe.eu-west",
+                "games.commands.lobby.eu-west",
+            ]
+        );
+    }
+
+    #[test]
+    fn migration_dual_writes_and_handles_one_copy() {
+        let dual = routing(Partitioning::GameType, Migration::DualWrite);
+        assert_eq!(
+            topics(&dual.routes(Some("bigger_dice"), "r-1")),
+            ["games.commands", "games.commands.bigger_dice"]
+        );
+        assert!(dual
+            .subscribed_topics()
+            .contains(&"games.commands".to_string()));
+        assert!(dual.handles("games.commands"));
+        assert!(!dual.handles("games.commands.bigger_dice"));
+
+        let cutover = routing(Partitioning::GameType, Migration::Cutover);
+        assert!(!cutover.handles("games.commands"));
+        assert!(cutover.handles("games.commands.bigger_dice"));
+
+        let done = routing(Partitioning::GameType, Migration::None);
+        assert!(!done
+            .subscribed_topics()
+            .contains(&"games.commands".to_string()));
+        assert!(done.handles("chat.commands"));
+    }
+
+    #[test]
+    fn game_type_comes_from_event_type_or_payload() {
+        let empty = json!({});
+        assert_eq!(
+            game_type_of("games.command.bigger_dice.roll", &empty),
+            Some("bigger_dice")
+        );
+        assert_eq!(
+            game_type_of("games.event.tic_tac_toe.spectator_left", &empty),
+            Some("tic_tac_toe")
+        );
+        assert_eq!(
+            game_type_of(
+                "games.command.create_room",
+                &json!({"game_type": "tic_tac_toe"})
+            ),
+            Some("tic_tac_toe")
+        );
+        assert_eq!(game_type_of("games.command.join_room", &empty), None);
+        assert_eq!(
+            game_type_of("games.command.create_room", &json!({"game_type": "poker"})),
+            None
+        );
+    }
+}
@@ -0,0 +1,25 @@
+//! Message catalogs, keyed by the English message
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::locale::Locale;
+
+type Catalog = HashMap<String, String>;
+
+fn load(source: &str) -> Catalog {
+    serde_json::from_str(source).expect("invalid message catalog")
+}
+
+fn entries(locale: Locale) -> Option<&'static Catalog> {
+    static SR: OnceLock<Catalog> = OnceLock::new();
+
+    match locale {
+        Locale::En => None,
+        Locale::Sr => Some(SR.get_or_init(|| load(include_str!("../catalog/sr.json")))),
+    }
+}
+
+pub(crate) fn lookup(locale: Locale, message: &str) -> Option<&'static str> {
+    entries(locale)?.get(message).map(String::as_str)
+}
@@ -0,0 +1,113 @@
+//! I18n
+//!
+//! Message catalogs for user-facing strings returned by the HTTP APIs and the
+//! WebSocket gateway. Catalogs are gettext-style: the English message is the
+//! key, so code keeps emitting English literals and only the response edge
+//! translates them. A message missing from a catalog falls back to English.
+//!
+//! The locale comes from the user's profile setting when there is one (carried
+//! in the JWT as `locale`), otherwise from `Accept-Language`, otherwise English.
+//!
+//! Adding a locale: add a variant to [`Locale`], a `catalog/<code>.json` file
+//! and a branch in `catalog::entries`.
+
+mod catalog;
+mod locale;
+
+pub use locale::{negotiate, Locale};
+
+/// Translate an English message; unknown messages are returned unchanged
+pub fn translate(locale: Locale, message: &str) -> &str {
+    catalog::lookup(locale, message).unwrap_or(message)
+}
+
+/// Translate the user-facing strings of a JSON response body: the top-level
+/// `message`, `errors` (a list of strings or objects, or a map of field to
+/// strings) and `fields[].message`. Returns `None` when the body is not JSON
+/// or nothing was translated.
+pub fn localize_json(body: &[u8], locale: Locale) -> Option<Vec<u8>> {
+    if locale == Locale::En {
+        return None;
+    }
+
+    let mut value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let mut changed = translate_message(&mut value, locale);
+
+    match value.get_mut("errors") {
+        Some(serde_json::Value::Array(errors)) => {
+            for error in errors {
+                changed |= translate_entry(error, locale);
+            }
+        }
+        Some(serde_json::Value::Object(fields)) => {
+            for messages in fields.values_mut().filter_map(|m| m.as_array_mut()) {
+                for message in messages {
+                    changed |= translate_entry(message, locale);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(fields) = value.get_mut("fields").and_then(|f| f.as_array_mut()) {
+        for field in fields {
+            changed |= translate_message(field, locale);
+        }
+    }
+
+    if changed {
+        serde_json::to_vec(&value).ok()
+    } else {
+        None
+    }
+}
+
+/// A bare string or an object with a `message`
+fn translate_entry(value: &mut serde_json::Value, locale: Locale) -> bool {
+    if value.is_object() {
+        return translate_message(value, locale);
+    }
+    translate_string(value, locale)
+}
+
+fn translate_message(value: &mut serde_json::Value, locale: Locale) -> bool {
+    value
+        .get_mut("message")
+        .is_some_and(|message| translate_string(message, locale))
+}
+
+fn translate_string(value: &mut serde_json::Value, locale: Locale) -> bool {
+    let Some(translated) = value.as_str().and_then(|m| catalog::lookup(locale, m)) else {
+        return false;
+    };
+
+    *value = serde_json::Value::String(translated.to_string());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_messages_fall_back_to_english() {
+        assert_eq!(translate(Locale::Sr, "Room not found"), "Soba nije pronađena");
+        assert_eq!(translate(Locale::Sr, "Something new"), "Something new");
+        assert_eq!(translate(Locale::En, "Room not found"), "Room not found");
+    }
+
+    #[test]
+    fn json_bodies_translate_messages_and_field_errors() {
+        let body = br#"{"status":"error","message":"Validation failed","errors":{"email":["Invalid email or password"]},"fields":[{"field":"email","message":"Invalid email or password"}]}"#;
+
+        let localized = localize_json(body, Locale::Sr).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&localized).unwrap();
+
+        assert_eq!(value["status"], "error");
+        assert_eq!(value["message"], "Validacija nije uspela");
+        assert_eq!(value["errors"]["email"][0], "Neispravan email ili lozinka");
+        assert_eq!(value["fields"][0]["message"], "Neispravan email ili lozinka");
+        assert!(localize_json(b"not json", Locale::Sr).is_none());
+        assert!(localize_json(br#"{"message":"Something new"}"#, Locale::Sr).is_none());
+    }
+}
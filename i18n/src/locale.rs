@@ -0,0 +1,84 @@
+//! Supported locales and negotiation
+
+/// Locales with a message catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    /// Serbian (Latin script)
+    Sr,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Sr];
+
+    /// Language code as stored in profiles and JWTs
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Sr => "sr",
+        }
+    }
+
+    /// Parse a language tag (`sr`, `sr-Latn-RS`, `en_US`), matching on the
+    /// primary language subtag
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let language = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "sr" => Some(Locale::Sr),
+            _ => None,
+        }
+    }
+
+    /// Best supported locale in an `Accept-Language` header, honouring q-values
+    pub fn from_accept_language(header: &str) -> Option<Locale> {
+        let mut best: Option<(Locale, f32)> = None;
+
+        for item in header.split(',') {
+            let mut parts = item.split(';');
+            let Some(locale) = parts.next().and_then(Locale::parse) else {
+                continue;
+            };
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                best = Some((locale, quality));
+            }
+        }
+
+        best.map(|(locale, _)| locale)
+    }
+}
+
+/// Pick the response locale: the user's profile setting first, then the
+/// `Accept-Language` header, then English
+pub fn negotiate(preferred: Option<&str>, accept_language: Option<&str>) -> Locale {
+    preferred
+        .and_then(Locale::parse)
+        .or_else(|| accept_language.and_then(Locale::from_accept_language))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_language_respects_quality_values() {
+        assert_eq!(Locale::from_accept_language("sr-Latn-RS,sr;q=0.9,en;q=0.8"), Some(Locale::Sr));
+        assert_eq!(Locale::from_accept_language("de-DE, en;q=0.5, sr;q=0.7"), Some(Locale::Sr));
+        assert_eq!(Locale::from_accept_language("de, fr;q=0.9"), None);
+        assert_eq!(Locale::from_accept_language("sr;q=0, en;q=0.1"), Some(Locale::En));
+    }
+
+    #[test]
+    fn profile_setting_wins_over_the_header() {
+        assert_eq!(negotiate(Some("en"), Some("sr")), Locale::En);
+        assert_eq!(negotiate(Some("xx"), Some("sr-RS")), Locale::Sr);
+        assert_eq!(negotiate(None, None), Locale::En);
+    }
+}
@@ -32,7 +32,7 @@ create_topics() {
     TOPICS="user.events auth.events transaction.events category.events system.events events.dead_letter"
 
     # Checkout topics
-    CHECKOUT_TOPICS="checkout.requests checkout.finished"
+    CHECKOUT_TOPICS="checkout.requests checkout.finished checkout.events checkout.disputes checkout.subscriptions"
 
     # WebSocket Gateway topics (chat and games)
     WS_TOPICS="chat.commands chat.events games.commands games.events gateway.presence"
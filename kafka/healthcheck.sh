@@ -16,6 +16,8 @@ for topic in \
   events.dead_letter \
   checkout.requests \
   checkout.finished \
+  checkout.disputes \
+  checkout.subscriptions \
   chat.commands \
   chat.events \
   games.commands \
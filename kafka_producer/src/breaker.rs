@@ -0,0 +1,109 @@
+//! Circuit breaker over broker availability
+
+use std::time::{Duration, Instant};
+
+/// Breaker position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Sends go to the broker
+    Closed,
+    /// Broker considered down: sends are buffered until `until`
+    Open { until: Instant },
+    /// Cool-down over: the next send is a probe
+    HalfOpen,
+}
+
+impl BreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open { .. } => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Opens after `failure_threshold` consecutive failed sends and stays open for
+/// `open_for`; the first send after that decides whether it closes again
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    failure_threshold: u32,
+    open_for: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+        }
+    }
+
+    /// Current state, moving from open to half-open once the cool-down passed
+    pub fn state(&mut self, now: Instant) -> BreakerState {
+        if let BreakerState::Open { until } = self.state {
+            if now >= until {
+                self.state = BreakerState::HalfOpen;
+            }
+        }
+        self.state
+    }
+
+    /// Whether a send may go to the broker now
+    pub fn allows(&mut self, now: Instant) -> bool {
+        !matches!(self.state(now), BreakerState::Open { .. })
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.state = BreakerState::Closed;
+    }
+
+    /// Count a failed send; returns true when this opened the breaker
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+
+        let trips = match self.state {
+            BreakerState::HalfOpen => true,
+            BreakerState::Closed => self.consecutive_failures >= self.failure_threshold,
+            BreakerState::Open { .. } => false,
+        };
+
+        if trips {
+            self.state = BreakerState::Open {
+                until: now + self.open_for,
+            };
+        }
+        trips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_probes_after_cool_down() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(2, Duration::from_secs(5));
+
+        assert!(!breaker.record_failure(start));
+        assert!(breaker.allows(start));
+        assert!(breaker.record_failure(start));
+        assert!(!breaker.allows(start + Duration::from_secs(4)));
+
+        let later = start + Duration::from_secs(5);
+        assert_eq!(breaker.state(later), BreakerState::HalfOpen);
+
+        // A failed probe re-opens immediately
+        assert!(breaker.record_failure(later));
+        assert!(!breaker.allows(later));
+
+        breaker.record_success();
+        assert_eq!(breaker.state(later), BreakerState::Closed);
+    }
+}
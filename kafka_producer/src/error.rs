@@ -0,0 +1,88 @@
+//! Producer error classification
+
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use thiserror::Error;
+
+/// Why a record could not be delivered
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProducerError {
+    /// Broker or network trouble; the same record can succeed later
+    #[error("Kafka temporarily unavailable: {0}")]
+    Retryable(String),
+
+    /// The broker rejected the record or the producer is misconfigured
+    #[error("Kafka rejected the record: {0}")]
+    Fatal(String),
+
+    /// The event could not be encoded
+    #[error("Failed to serialize event: {0}")]
+    Serialization(String),
+
+    /// The payload is over the producer's size limit
+    #[error("Payload of {size} bytes exceeds the {max} byte limit")]
+    TooLarge { size: usize, max: usize },
+}
+
+impl ProducerError {
+    /// Whether sending the same record again can succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ProducerError::Retryable(_))
+    }
+}
+
+impl From<KafkaError> for ProducerError {
+    fn from(err: KafkaError) -> Self {
+        let retryable = match &err {
+            KafkaError::MessageProduction(code) | KafkaError::Flush(code) | KafkaError::Global(code) => {
+                is_retryable_code(*code)
+            }
+            _ => false,
+        };
+
+        if retryable {
+            ProducerError::Retryable(err.to_string())
+        } else {
+            ProducerError::Fatal(err.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ProducerError {
+    fn from(err: serde_json::Error) -> Self {
+        ProducerError::Serialization(err.to_string())
+    }
+}
+
+/// librdkafka codes that describe an unreachable or overloaded cluster
+fn is_retryable_code(code: RDKafkaErrorCode) -> bool {
+    matches!(
+        code,
+        RDKafkaErrorCode::MessageTimedOut
+            | RDKafkaErrorCode::QueueFull
+            | RDKafkaErrorCode::BrokerTransportFailure
+            | RDKafkaErrorCode::AllBrokersDown
+            | RDKafkaErrorCode::Resolve
+            | RDKafkaErrorCode::OperationTimedOut
+            | RDKafkaErrorCode::RequestTimedOut
+            | RDKafkaErrorCode::BrokerNotAvailable
+            | RDKafkaErrorCode::LeaderNotAvailable
+            | RDKafkaErrorCode::NotLeaderForPartition
+            | RDKafkaErrorCode::NetworkException
+            | RDKafkaErrorCode::NotEnoughReplicas
+            | RDKafkaErrorCode::NotEnoughReplicasAfterAppend
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broker_outages_are_retryable() {
+        let err = ProducerError::from(KafkaError::MessageProduction(RDKafkaErrorCode::AllBrokersDown));
+        assert!(err.is_retryable());
+
+        let err = ProducerError::from(KafkaError::MessageProduction(RDKafkaErrorCode::MessageSizeTooLarge));
+        assert!(matches!(err, ProducerError::Fatal(_)));
+    }
+}
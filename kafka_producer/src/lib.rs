@@ -0,0 +1,30 @@
+//! Kafka Producer
+//!
+//! Delivery policy shared by the services that publish to Kafka (checkout and
+//! ws_gateway). Every send failure is classified as a [`ProducerError`]:
+//! retryable errors (broker down, timeouts, full queue) are retried a few times
+//! with jittered backoff; fatal and serialization errors are returned at once.
+//!
+//! When sends keep failing the [`CircuitBreaker`] opens. While it is open (and
+//! until everything buffered has been flushed) new records go to a bounded
+//! in-memory buffer instead of the broker, and a background task replays them
+//! in order once a probe send succeeds. The buffer lives in process memory, so
+//! it bridges broker restarts, not service restarts.
+//!
+//! Payloads are checked against [`SizeLimits`] before they are sent: oversized
+//! ones are refused with [`ProducerError::TooLarge`] and ones close to the
+//! limit are counted in [`SizeMetrics`]. Producers whose consumers reassemble
+//! can send oversized payloads as segments instead (see [`segment`]).
+
+mod breaker;
+mod error;
+mod producer;
+mod retry;
+pub mod segment;
+mod size;
+
+pub use breaker::{BreakerState, CircuitBreaker};
+pub use error::ProducerError;
+pub use producer::{Delivery, ProducerMetrics, ResilienceConfig, ResilientProducer};
+pub use retry::RetryPolicy;
+pub use size::{SizeCheck, SizeLimits, SizeMetrics, DEFAULT_MAX_MESSAGE_BYTES, NEAR_LIMIT_RATIO};
@@ -0,0 +1,377 @@
+//! Producer wrapper applying the retry policy, breaker and buffer
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::breaker::{BreakerState, CircuitBreaker};
+use crate::error::ProducerError;
+use crate::retry::RetryPolicy;
+use crate::size::{SizeCheck, SizeLimits, SizeMetrics};
+
+/// Delivery policy knobs
+#[derive(Debug, Clone)]
+pub struct ResilienceConfig {
+    pub retry: RetryPolicy,
+    /// Per-attempt delivery timeout
+    pub send_timeout: Duration,
+    /// Consecutive failed sends (after retries) that open the breaker
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before a probe
+    pub open_for: Duration,
+    /// Records kept while the broker is down; beyond this sends fail
+    pub buffer_capacity: usize,
+    /// How often the background task tries to flush the buffer
+    pub flush_interval: Duration,
+    /// Payloads over this are refused
+    pub size: SizeLimits,
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            send_timeout: Duration::from_secs(5),
+            failure_threshold: 3,
+            open_for: Duration::from_secs(10),
+            buffer_capacity: 10_000,
+            flush_interval: Duration::from_secs(1),
+            size: SizeLimits::default(),
+        }
+    }
+}
+
+/// Where a record ended up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Acknowledged by the broker
+    Sent,
+    /// Held in memory until the broker is back
+    Buffered,
+}
+
+/// Delivery counters
+#[derive(Debug, Default)]
+pub struct ProducerMetrics {
+    sent: AtomicU64,
+    retries: AtomicU64,
+    buffered: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl ProducerMetrics {
+    /// Records acknowledged by the broker (including flushed ones)
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Retried attempts
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Records that went to the buffer
+    pub fn buffered(&self) -> u64 {
+        self.buffered.load(Ordering::Relaxed)
+    }
+
+    /// Records given up on
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BufferedRecord {
+    topic: String,
+    key: Option<String>,
+    payload: Vec<u8>,
+}
+
+/// `FutureProducer` with classified errors, retries and outage buffering
+pub struct ResilientProducer {
+    producer: FutureProducer,
+    config: ResilienceConfig,
+    breaker: Mutex<CircuitBreaker>,
+    buffer: Mutex<VecDeque<BufferedRecord>>,
+    metrics: ProducerMetrics,
+    size_metrics: SizeMetrics,
+}
+
+impl ResilientProducer {
+    pub fn new(producer: FutureProducer, config: ResilienceConfig) -> Self {
+        Self {
+            producer,
+            breaker: Mutex::new(CircuitBreaker::new(config.failure_threshold, config.open_for)),
+            buffer: Mutex::new(VecDeque::new()),
+            metrics: ProducerMetrics::default(),
+            size_metrics: SizeMetrics::default(),
+            config,
+        }
+    }
+
+    pub fn metrics(&self) -> &ProducerMetrics {
+        &self.metrics
+    }
+
+    /// Payloads near or over the size limit
+    pub fn size_metrics(&self) -> &SizeMetrics {
+        &self.size_metrics
+    }
+
+    pub fn size_limits(&self) -> &SizeLimits {
+        &self.config.size
+    }
+
+    /// Records waiting for the broker
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    pub fn breaker_state(&self) -> BreakerState {
+        self.breaker.lock().unwrap().state(Instant::now())
+    }
+
+    /// Prometheus text exposition of the delivery counters, buffer size and
+    /// breaker state
+    pub fn render_prometheus(&self, service: &str) -> String {
+        let breaker_open = !matches!(self.breaker_state(), BreakerState::Closed) as u8;
+        let counters = [
+            ("sent", self.metrics.sent()),
+            ("retries", self.metrics.retries()),
+            ("buffered", self.metrics.buffered()),
+            ("failed", self.metrics.failed()),
+        ];
+
+        let mut out = String::from(
+            "# HELP kafka_producer_records_total Kafka producer records by outcome\n\
+             # TYPE kafka_producer_records_total counter\n",
+        );
+        for (outcome, count) in counters {
+            out.push_str(&format!(
+                "kafka_producer_records_total{{service=\"{}\",outcome=\"{}\"}} {}\n",
+                service, outcome, count
+            ));
+        }
+        out.push_str(&format!(
+            "# HELP kafka_producer_buffered_records Records waiting for the broker\n\
+             # TYPE kafka_producer_buffered_records gauge\n\
+             kafka_producer_buffered_records{{service=\"{}\"}} {}\n\
+             # HELP kafka_producer_breaker_open Whether the circuit breaker is open or probing\n\
+             # TYPE kafka_producer_breaker_open gauge\n\
+             kafka_producer_breaker_open{{service=\"{}\"}} {}\n",
+            service,
+            self.buffered_len(),
+            service,
+            breaker_open
+        ));
+        out.push_str(&self.size_metrics.render_prometheus(service, &self.config.size));
+        out
+    }
+
+    /// Deliver one record.
+    ///
+    /// Retryable failures are retried per the policy. If they persist and open
+    /// the breaker the record is buffered instead of failing. Records never
+    /// overtake ones already in the buffer. Payloads over the size limit are
+    /// refused without being sent.
+    pub async fn send(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+    ) -> Result<Delivery, ProducerError> {
+        match self.size_metrics.record(payload.len(), self.config.size.check(payload.len())) {
+            SizeCheck::Fits => {}
+            SizeCheck::NearLimit => {
+                warn!(
+                    topic = %topic,
+                    size = payload.len(),
+                    max = self.config.size.max_bytes,
+                    "Kafka payload near the size limit"
+                );
+            }
+            SizeCheck::TooLarge => {
+                self.size_metrics.record_rejected();
+                self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+                error!(
+                    topic = %topic,
+                    size = payload.len(),
+                    max = self.config.size.max_bytes,
+                    "Kafka payload over the size limit"
+                );
+                return Err(ProducerError::TooLarge {
+                    size: payload.len(),
+                    max: self.config.size.max_bytes,
+                });
+            }
+        }
+
+        let breaker_allows = self.breaker.lock().unwrap().allows(Instant::now());
+        if !breaker_allows || self.buffered_len() > 0 {
+            return self.push_buffer(topic, key, payload);
+        }
+
+        match self.send_with_retries(topic, key, payload).await {
+            Ok(()) => Ok(Delivery::Sent),
+            Err(err) if err.is_retryable() && !self.breaker.lock().unwrap().allows(Instant::now()) => {
+                warn!(topic = %topic, error = %err, "Kafka unavailable, buffering record");
+                self.push_buffer(topic, key, payload)
+            }
+            Err(err) => {
+                self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+                Err(err)
+            }
+        }
+    }
+
+    /// Replay buffered records in order; stops at the first retryable failure.
+    /// Returns how many were delivered.
+    pub async fn flush_buffer(&self) -> usize {
+        let mut flushed = 0;
+
+        loop {
+            if !self.breaker.lock().unwrap().allows(Instant::now()) {
+                break;
+            }
+
+            // Only this loop removes records, so the front stays put while in flight
+            let Some(record) = self.buffer.lock().unwrap().front().cloned() else {
+                break;
+            };
+
+            match self.send_once(&record.topic, record.key.as_deref(), &record.payload).await {
+                Ok(()) => {
+                    self.breaker.lock().unwrap().record_success();
+                    self.metrics.sent.fetch_add(1, Ordering::Relaxed);
+                    flushed += 1;
+                }
+                Err(err) if err.is_retryable() => {
+                    self.breaker.lock().unwrap().record_failure(Instant::now());
+                    break;
+                }
+                Err(err) => {
+                    self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+                    error!(topic = %record.topic, error = %err, "Dropping buffered record");
+                }
+            }
+
+            self.buffer.lock().unwrap().pop_front();
+        }
+
+        if flushed > 0 {
+            info!(flushed, remaining = self.buffered_len(), "Flushed buffered Kafka records");
+        }
+        flushed
+    }
+
+    /// Flush the buffer every `flush_interval` in the background
+    pub fn start_flusher(self: &Arc<Self>) -> JoinHandle<()> {
+        let producer = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(producer.config.flush_interval);
+            loop {
+                ticker.tick().await;
+                if producer.buffered_len() > 0 {
+                    producer.flush_buffer().await;
+                }
+            }
+        })
+    }
+
+    async fn send_with_retries(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+    ) -> Result<(), ProducerError> {
+        let mut attempt = 1;
+
+        loop {
+            match self.send_once(topic, key, payload).await {
+                Ok(()) => {
+                    self.breaker.lock().unwrap().record_success();
+                    self.metrics.sent.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(err) if err.is_retryable() && attempt < self.config.retry.max_attempts => {
+                    let delay = self.config.retry.jittered_delay(attempt);
+                    warn!(
+                        topic = %topic,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %err,
+                        "Kafka send failed, retrying"
+                    );
+                    self.metrics.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    // Fatal errors mean the broker answered, so they never open the breaker
+                    if err.is_retryable() && self.breaker.lock().unwrap().record_failure(Instant::now()) {
+                        warn!(
+                            open_for_secs = self.config.open_for.as_secs(),
+                            "Kafka circuit breaker opened"
+                        );
+                    }
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    async fn send_once(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+    ) -> Result<(), ProducerError> {
+        // Shows up as broker trouble, so retries, the breaker and the outage
+        // buffer all see it
+        fault_injection::check(fault_injection::Target::KafkaProducer)
+            .await
+            .map_err(|fault| ProducerError::Retryable(fault.to_string()))?;
+
+        let mut record = FutureRecord::to(topic).payload(payload);
+        if let Some(k) = key {
+            record = record.key(k);
+        }
+
+        self.producer
+            .send(record, Timeout::After(self.config.send_timeout))
+            .await
+            .map(|_| ())
+            .map_err(|(err, _)| err.into())
+    }
+
+    fn push_buffer(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        payload: &[u8],
+    ) -> Result<Delivery, ProducerError> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() >= self.config.buffer_capacity {
+            self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+            return Err(ProducerError::Retryable(format!(
+                "Kafka unavailable and outage buffer full ({} records)",
+                buffer.len()
+            )));
+        }
+
+        buffer.push_back(BufferedRecord {
+            topic: topic.to_string(),
+            key: key.map(str::to_string),
+            payload: payload.to_vec(),
+        });
+        self.metrics.buffered.fetch_add(1, Ordering::Relaxed);
+        Ok(Delivery::Buffered)
+    }
+}
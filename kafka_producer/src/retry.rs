@@ -0,0 +1,79 @@
+//! Bounded retries with jittered exponential backoff
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How often and how patiently a retryable send is repeated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+    /// Backoff ceiling before the first retry
+    pub base_delay: Duration,
+    /// Backoff never grows past this
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (1-based) given a jitter factor in
+    /// `[0, 1)`: a "full jitter" draw below the exponential ceiling
+    pub fn delay(&self, retry: u32, jitter: f64) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let ceiling = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        ceiling.mul_f64(jitter.clamp(0.0, 1.0))
+    }
+
+    /// Delay before retry number `retry` with a random jitter
+    pub fn jittered_delay(&self, retry: u32) -> Duration {
+        self.delay(retry, random_unit())
+    }
+}
+
+/// Uniform-ish value in `[0, 1)` from the std hasher's random keys
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+        };
+
+        assert_eq!(policy.delay(1, 1.0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2, 1.0), Duration::from_millis(200));
+        assert_eq!(policy.delay(3, 1.0), Duration::from_millis(350));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_millis(100));
+        assert_eq!(policy.delay(40, 0.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn jitter_stays_below_the_ceiling() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            assert!(policy.jittered_delay(2) <= Duration::from_millis(200));
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Segmenting of oversized payloads
+//!
+//! A payload over the size limit can be sent as several records instead of
+//! being refused. Each record carries a slice of the payload and three
+//! headers: `segment_id` (shared by all slices), `segment_index` and
+//! `segment_count`. The records use the same key, so they land on one
+//! partition in order; a consumer feeds them to a [`Reassembler`] and gets the
+//! whole payload back once the last slice arrived.
+//!
+//! Only topics whose consumers reassemble may receive segments.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rdkafka::message::{Header, Headers, OwnedHeaders};
+use tracing::warn;
+
+pub const SEGMENT_ID_HEADER: &str = "segment_id";
+pub const SEGMENT_INDEX_HEADER: &str = "segment_index";
+pub const SEGMENT_COUNT_HEADER: &str = "segment_count";
+
+/// Where a record's slice belongs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentHeader {
+    pub id: String,
+    pub index: u32,
+    pub count: u32,
+}
+
+impl SegmentHeader {
+    /// Add the segment headers to a record's headers
+    pub fn insert_into(&self, headers: OwnedHeaders) -> OwnedHeaders {
+        headers
+            .insert(Header {
+                key: SEGMENT_ID_HEADER,
+                value: Some(self.id.as_bytes()),
+            })
+            .insert(Header {
+                key: SEGMENT_INDEX_HEADER,
+                value: Some(self.index.to_string().as_bytes()),
+            })
+            .insert(Header {
+                key: SEGMENT_COUNT_HEADER,
+                value: Some(self.count.to_string().as_bytes()),
+            })
+    }
+
+    /// Segment headers of a record, `None` for a whole payload
+    pub fn from_headers<H: Headers>(headers: &H) -> Option<Self> {
+        let (mut id, mut index, mut count) = (None, None, None);
+        for header in headers.iter() {
+            let value = header.value.and_then(|value| std::str::from_utf8(value).ok());
+            match header.key {
+                SEGMENT_ID_HEADER => id = value.map(str::to_string),
+                SEGMENT_INDEX_HEADER => index = value.and_then(|value| value.parse().ok()),
+                SEGMENT_COUNT_HEADER => count = value.and_then(|value| value.parse().ok()),
+                _ => {}
+            }
+        }
+        Some(Self {
+            id: id?,
+            index: index?,
+            count: count?,
+        })
+    }
+}
+
+/// Cut a payload into slices of at most `max_bytes`
+pub fn split(payload: &[u8], max_bytes: usize) -> Vec<(SegmentHeader, &[u8])> {
+    let id = next_segment_id();
+    let chunks: Vec<&[u8]> = payload.chunks(max_bytes.max(1)).collect();
+    let count = chunks.len() as u32;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let header = SegmentHeader {
+                id: id.clone(),
+                index: index as u32,
+                count,
+            };
+            (header, chunk)
+        })
+        .collect()
+}
+
+/// Unique across producer processes: pid, start time and a counter
+fn next_segment_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    format!(
+        "{:x}-{:x}-{:x}",
+        std::process::id(),
+        nanos,
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+#[derive(Debug)]
+struct Partial {
+    parts: Vec<Option<Vec<u8>>>,
+    received: u32,
+    started: Instant,
+}
+
+/// Collects slices until a payload is complete
+///
+/// Incomplete payloads are dropped once they are older than `max_age` or when
+/// more than `max_pending` are open, so a producer dying mid-payload does not
+/// leak memory.
+#[derive(Debug)]
+pub struct Reassembler {
+    pending: HashMap<String, Partial>,
+    max_pending: usize,
+    max_age: Duration,
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(64, Duration::from_secs(30))
+    }
+}
+
+impl Reassembler {
+    pub fn new(max_pending: usize, max_age: Duration) -> Self {
+        Self {
+            pending: HashMap::new(),
+            max_pending,
+            max_age,
+        }
+    }
+
+    /// Add a slice; returns the whole payload when this was the last one
+    pub fn push(&mut self, header: SegmentHeader, data: &[u8], now: Instant) -> Option<Vec<u8>> {
+        if header.count == 0 || header.index >= header.count {
+            warn!(segment_id = %header.id, index = header.index, count = header.count, "Dropping malformed segment");
+            return None;
+        }
+
+        self.expire(now);
+        if !self.pending.contains_key(&header.id) && self.pending.len() >= self.max_pending {
+            self.drop_oldest();
+        }
+
+        let partial = self.pending.entry(header.id.clone()).or_insert_with(|| Partial {
+            parts: vec![None; header.count as usize],
+            received: 0,
+            started: now,
+        });
+        if partial.parts.len() != header.count as usize {
+            warn!(segment_id = %header.id, "Segment count changed mid-payload, dropping it");
+            self.pending.remove(&header.id);
+            return None;
+        }
+
+        let slot = &mut partial.parts[header.index as usize];
+        if slot.is_none() {
+            *slot = Some(data.to_vec());
+            partial.received += 1;
+        }
+        if partial.received < header.count {
+            return None;
+        }
+
+        let partial = self.pending.remove(&header.id)?;
+        Some(partial.parts.into_iter().flatten().flatten().collect())
+    }
+
+    /// Payloads still missing slices
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let max_age = self.max_age;
+        self.pending.retain(|id, partial| {
+            let alive = now.duration_since(partial.started) < max_age;
+            if !alive {
+                warn!(segment_id = %id, received = partial.received, "Dropping incomplete segmented payload");
+            }
+            alive
+        });
+    }
+
+    fn drop_oldest(&mut self) {
+        let oldest = self
+            .pending
+            .iter()
+            .min_by_key(|(_, partial)| partial.started)
+            .map(|(id, _)| id.clone());
+        if let Some(id) = oldest {
+            warn!(segment_id = %id, "Too many segmented payloads pending, dropping the oldest");
+            self.pending.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_payloads_are_reassembled() {
+        let payload: Vec<u8> = (0..=255u8).cycle().take(2500).collect();
+        let segments = split(&payload, 1000);
+        assert_eq!(segments.len(), 3);
+        assert!(segments.iter().all(|(header, _)| header.count == 3));
+
+        let now = Instant::now();
+        let mut reassembler = Reassembler::default();
+        let (last, rest) = segments.split_last().unwrap();
+        for (header, data) in rest.iter().rev() {
+            assert_eq!(reassembler.push(header.clone(), data, now), None);
+        }
+        assert_eq!(reassembler.push(last.0.clone(), last.1, now), Some(payload));
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn segment_headers_round_trip() {
+        let header = SegmentHeader {
+            id: "abc".to_string(),
+            index: 1,
+            count: 4,
+        };
+        let headers = header.insert_into(OwnedHeaders::new());
+
+        assert_eq!(SegmentHeader::from_headers(&headers), Some(header));
+        assert_eq!(SegmentHeader::from_headers(&OwnedHeaders::new()), None);
+    }
+
+    #[test]
+    fn stale_payloads_are_dropped() {
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(1, Duration::from_secs(30));
+        let first = split(b"abcd", 2);
+        let second = split(b"efgh", 2);
+
+        reassembler.push(first[0].0.clone(), first[0].1, now);
+        reassembler.push(second[0].0.clone(), second[0].1, now);
+        assert_eq!(reassembler.pending(), 1);
+        assert_eq!(reassembler.push(first[1].0.clone(), first[1].1, now), None);
+
+        let later = now + Duration::from_secs(31);
+        assert_eq!(reassembler.push(second[1].0.clone(), second[1].1, later), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Record size guard
+//!
+//! Brokers reject records larger than `message.max.bytes` (1 MB by default).
+//! [`SizeLimits`] keeps payloads under a configured limit, leaving room for the
+//! key and headers, and flags payloads that come close to it so they show up
+//! in metrics before they start failing.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Largest payload sent as one record when nothing else is configured
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 900_000;
+
+/// Share of the limit from which a payload counts as near it
+pub const NEAR_LIMIT_RATIO: f64 = 0.8;
+
+/// How a payload compares to the limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeCheck {
+    Fits,
+    /// Fits, but is at least `NEAR_LIMIT_RATIO` of the limit
+    NearLimit,
+    TooLarge,
+}
+
+/// Payload size limit of a producer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    pub max_bytes: usize,
+    pub near_limit_bytes: usize,
+}
+
+impl SizeLimits {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            near_limit_bytes: (max_bytes as f64 * NEAR_LIMIT_RATIO) as usize,
+        }
+    }
+
+    pub fn check(&self, len: usize) -> SizeCheck {
+        if len > self.max_bytes {
+            SizeCheck::TooLarge
+        } else if len >= self.near_limit_bytes {
+            SizeCheck::NearLimit
+        } else {
+            SizeCheck::Fits
+        }
+    }
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_MESSAGE_BYTES)
+    }
+}
+
+/// Counters of payloads that came close to or over the limit
+#[derive(Debug, Default)]
+pub struct SizeMetrics {
+    near_limit: AtomicU64,
+    segmented: AtomicU64,
+    rejected: AtomicU64,
+    largest_bytes: AtomicU64,
+}
+
+impl SizeMetrics {
+    /// Count a payload by its check; returns the check for chaining
+    pub fn record(&self, len: usize, check: SizeCheck) -> SizeCheck {
+        self.largest_bytes.fetch_max(len as u64, Ordering::Relaxed);
+        if check == SizeCheck::NearLimit {
+            self.near_limit.fetch_add(1, Ordering::Relaxed);
+        }
+        check
+    }
+
+    /// Count an oversized payload sent as segments
+    pub fn record_segmented(&self) {
+        self.segmented.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count an oversized payload that was refused
+    pub fn record_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Payloads that fit but were near the limit
+    pub fn near_limit(&self) -> u64 {
+        self.near_limit.load(Ordering::Relaxed)
+    }
+
+    /// Oversized payloads sent as segments
+    pub fn segmented(&self) -> u64 {
+        self.segmented.load(Ordering::Relaxed)
+    }
+
+    /// Oversized payloads that were refused
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Largest payload seen since start
+    pub fn largest_bytes(&self) -> u64 {
+        self.largest_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Prometheus text exposition of the counters and the limit
+    pub fn render_prometheus(&self, service: &str, limits: &SizeLimits) -> String {
+        let counters = [
+            ("near_limit", self.near_limit()),
+            ("segmented", self.segmented()),
+            ("rejected", self.rejected()),
+        ];
+
+        let mut out = String::from(
+            "# HELP kafka_producer_large_records_total Kafka payloads near or over the size limit\n\
+             # TYPE kafka_producer_large_records_total counter\n",
+        );
+        for (outcome, count) in counters {
+            out.push_str(&format!(
+                "kafka_producer_large_records_total{{service=\"{}\",outcome=\"{}\"}} {}\n",
+                service, outcome, count
+            ));
+        }
+        out.push_str(&format!(
+            "# HELP kafka_producer_largest_record_bytes Largest Kafka payload since start\n\
+             # TYPE kafka_producer_largest_record_bytes gauge\n\
+             kafka_producer_largest_record_bytes{{service=\"{}\"}} {}\n\
+             # HELP kafka_producer_max_record_bytes Kafka payload size limit\n\
+             # TYPE kafka_producer_max_record_bytes gauge\n\
+             kafka_producer_max_record_bytes{{service=\"{}\"}} {}\n",
+            service,
+            self.largest_bytes(),
+            service,
+            limits.max_bytes
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payloads_are_checked_against_the_limit() {
+        let limits = SizeLimits::new(1000);
+        let metrics = SizeMetrics::default();
+
+        assert_eq!(metrics.record(799, limits.check(799)), SizeCheck::Fits);
+        assert_eq!(metrics.record(800, limits.check(800)), SizeCheck::NearLimit);
+        assert_eq!(metrics.record(1000, limits.check(1000)), SizeCheck::NearLimit);
+        assert_eq!(metrics.record(1001, limits.check(1001)), SizeCheck::TooLarge);
+
+        assert_eq!(metrics.near_limit(), 2);
+        assert_eq!(metrics.largest_bytes(), 1001);
+        assert!(metrics
+            .render_prometheus("ws_gateway", &limits)
+            .contains("kafka_producer_max_record_bytes{service=\"ws_gateway\"} 1000"));
+    }
+}
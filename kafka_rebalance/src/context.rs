@@ -0,0 +1,115 @@
+//! Consumer context draining and committing before partitions are revoked
+
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use rdkafka::bindings as rdsys;
+use rdkafka::client::{ClientContext, NativeClient};
+use rdkafka::consumer::{CommitMode, ConsumerContext, Rebalance};
+use rdkafka::error::RDKafkaErrorCode;
+use rdkafka::types::RDKafkaRespErr;
+use rdkafka::TopicPartitionList;
+use tracing::{error, info, warn};
+
+use crate::tracker::RebalanceTracker;
+
+/// Consumer context handing partitions off gracefully (see the crate docs)
+pub struct RebalanceContext {
+    hooks: Hooks,
+    drain_timeout: Duration,
+}
+
+/// Runs librdkafka's default assign / unassign and counts the outcome
+struct Hooks {
+    tracker: RebalanceTracker,
+}
+
+impl RebalanceContext {
+    /// `drain_timeout` bounds the wait for in-flight handlers; keep it well
+    /// below `max.poll.interval.ms` so the member is not kicked out meanwhile
+    pub fn new(tracker: RebalanceTracker, drain_timeout: Duration) -> Self {
+        Self {
+            hooks: Hooks { tracker },
+            drain_timeout,
+        }
+    }
+
+    pub fn tracker(&self) -> &RebalanceTracker {
+        &self.hooks.tracker
+    }
+
+    /// Wait for the handlers of the revoked partitions, then commit synchronously
+    fn hand_off(&self, native_client: &NativeClient, tpl: &TopicPartitionList) {
+        let partitions: Vec<(String, i32)> = tpl
+            .elements()
+            .iter()
+            .map(|element| (element.topic().to_string(), element.partition()))
+            .collect();
+
+        let started = Instant::now();
+        let drained = self.hooks.tracker.revoke(&partitions, self.drain_timeout);
+        let drain_ms = started.elapsed().as_millis() as u64;
+        if drained {
+            info!(
+                partitions = partitions.len(),
+                drain_ms, "Partitions revoked, in-flight handlers finished"
+            );
+        } else {
+            warn!(
+                partitions = partitions.len(),
+                drain_ms,
+                "Partitions revoked before in-flight handlers finished; their messages may be handled again"
+            );
+        }
+
+        // Commit what was stored so the new owner resumes after the last
+        // handled message instead of at the last periodic commit
+        let result = unsafe {
+            rdsys::rd_kafka_commit(native_client.ptr(), ptr::null(), CommitMode::Sync as i32)
+        };
+        match result {
+            RDKafkaRespErr::RD_KAFKA_RESP_ERR_NO_ERROR
+            // Nothing was handled since the last commit
+            | RDKafkaRespErr::RD_KAFKA_RESP_ERR__NO_OFFSET => {}
+            err => {
+                let code: RDKafkaErrorCode = err.into();
+                error!(error = %code, "Failed to commit offsets before revocation");
+            }
+        }
+    }
+}
+
+impl ClientContext for RebalanceContext {}
+
+impl ConsumerContext for RebalanceContext {
+    fn rebalance(
+        &self,
+        native_client: &NativeClient,
+        err: RDKafkaRespErr,
+        tpl: &mut TopicPartitionList,
+    ) {
+        if err == RDKafkaRespErr::RD_KAFKA_RESP_ERR__REVOKE_PARTITIONS {
+            self.hand_off(native_client, tpl);
+        }
+        self.hooks.rebalance(native_client, err, tpl);
+    }
+}
+
+impl ClientContext for Hooks {}
+
+impl ConsumerContext for Hooks {
+    fn post_rebalance(&self, rebalance: &Rebalance<'_>) {
+        match rebalance {
+            Rebalance::Assign(tpl) => {
+                self.tracker.record_assignment();
+                info!(partitions = tpl.count(), "Partitions assigned");
+            }
+            // Counted by the drain
+            Rebalance::Revoke(_) => {}
+            Rebalance::Error(e) => {
+                self.tracker.record_error();
+                error!(error = %e, "Kafka rebalance failed");
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Kafka Rebalance
+//!
+//! Graceful partition handoff shared by the Kafka consumers (blazing_sun
+//! events and checkout). Without it a rebalance in the middle of a game
+//! command hands the partition to another member while the old owner is
+//! still handling the message, and both process it.
+//!
+//! [`RebalanceContext`] is the consumer context. Before partitions are
+//! revoked it:
+//! 1. pauses intake: the rebalance callback runs on the polling thread, so no
+//!    message is fetched until it returns, and messages of the revoked
+//!    partitions already fetched are no longer started,
+//! 2. waits (up to a timeout) for the handlers still running on them, and
+//! 3. commits the stored offsets synchronously,
+//!
+//! so the new owner resumes right after the last handled message. The
+//! [`RebalanceTracker`] counts in-flight handlers per partition and keeps the
+//! rebalance counts and drain latency, rendered for Prometheus by
+//! [`RebalanceTracker::render_prometheus`].
+//!
+//! The drain only completes when handlers run on another thread than the
+//! polling one, or inline between two polls.
+
+mod context;
+mod tracker;
+
+pub use context::RebalanceContext;
+pub use tracker::{InFlight, RebalanceMetrics, RebalanceTracker};
@@ -0,0 +1,312 @@
+//! In-flight handlers and rebalance counters
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+type Partition = (String, i32);
+
+#[derive(Debug, Default)]
+struct State {
+    /// Handlers running per partition
+    in_flight: HashMap<Partition, usize>,
+    /// Bumped by every revocation; messages remember the one they were polled in
+    generation: u64,
+    /// Generation in which each partition was last revoked
+    revoked_in: HashMap<Partition, u64>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    assignments: AtomicU64,
+    revocations: AtomicU64,
+    errors: AtomicU64,
+    drain_timeouts: AtomicU64,
+    drain_count: AtomicU64,
+    drain_total_ms: AtomicU64,
+    drain_last_ms: AtomicU64,
+    drain_max_ms: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    state: Mutex<State>,
+    drained: Condvar,
+    counters: Counters,
+}
+
+/// Rebalance counters and drain latency
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebalanceMetrics {
+    pub assignments: u64,
+    pub revocations: u64,
+    pub errors: u64,
+    /// Revocations that gave up waiting for their handlers
+    pub drain_timeouts: u64,
+    pub drain_count: u64,
+    pub drain_total_ms: u64,
+    pub drain_last_ms: u64,
+    pub drain_max_ms: u64,
+}
+
+/// Tracks the handlers running per partition and the consumer's rebalances.
+/// Clones share their state.
+#[derive(Debug, Clone, Default)]
+pub struct RebalanceTracker {
+    inner: Arc<Inner>,
+}
+
+/// A handler running on a message; dropping it marks the handler finished
+#[derive(Debug)]
+pub struct InFlight {
+    tracker: RebalanceTracker,
+    partition: Partition,
+    generation: u64,
+}
+
+impl RebalanceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Generation to tag a freshly polled message with
+    pub fn generation(&self) -> u64 {
+        self.state().generation
+    }
+
+    /// Start handling a message polled in `generation`.
+    ///
+    /// None when its partition was revoked since: the new owner consumes the
+    /// message again from the committed offset, so it must not be handled here.
+    pub fn start(&self, topic: &str, partition: i32, generation: u64) -> Option<InFlight> {
+        let key = (topic.to_string(), partition);
+        let mut state = self.state();
+        if state.revoked_in.get(&key).is_some_and(|&revoked| revoked > generation) {
+            return None;
+        }
+        *state.in_flight.entry(key.clone()).or_default() += 1;
+        drop(state);
+
+        Some(InFlight {
+            tracker: self.clone(),
+            partition: key,
+            generation,
+        })
+    }
+
+    /// Whether a partition was revoked after `generation`
+    pub fn is_revoked(&self, topic: &str, partition: i32, generation: u64) -> bool {
+        self.state()
+            .revoked_in
+            .get(&(topic.to_string(), partition))
+            .is_some_and(|&revoked| revoked > generation)
+    }
+
+    /// Mark partitions revoked and wait up to `timeout` for their running
+    /// handlers. Returns whether every handler finished in time.
+    pub fn revoke(&self, partitions: &[(String, i32)], timeout: Duration) -> bool {
+        let started = Instant::now();
+        let mut state = self.state();
+        state.generation += 1;
+        let generation = state.generation;
+        for partition in partitions {
+            state.revoked_in.insert(partition.clone(), generation);
+        }
+
+        let busy = |state: &State| {
+            partitions
+                .iter()
+                .any(|partition| state.in_flight.get(partition).copied().unwrap_or(0) > 0)
+        };
+        let (state, result) = self
+            .inner
+            .drained
+            .wait_timeout_while(state, timeout, |state| busy(state))
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        drop(state);
+
+        let counters = &self.inner.counters;
+        counters.revocations.fetch_add(1, Ordering::Relaxed);
+        self.record_drain(started.elapsed());
+        if result.timed_out() {
+            counters.drain_timeouts.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    pub fn record_assignment(&self) {
+        self.inner.counters.assignments.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self) {
+        self.inner.counters.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_drain(&self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        let counters = &self.inner.counters;
+        counters.drain_count.fetch_add(1, Ordering::Relaxed);
+        counters.drain_total_ms.fetch_add(ms, Ordering::Relaxed);
+        counters.drain_last_ms.store(ms, Ordering::Relaxed);
+        counters.drain_max_ms.fetch_max(ms, Ordering::Relaxed);
+    }
+
+    /// Handlers running right now, all partitions together
+    pub fn in_flight(&self) -> usize {
+        self.state().in_flight.values().sum()
+    }
+
+    pub fn metrics(&self) -> RebalanceMetrics {
+        let counters = &self.inner.counters;
+        RebalanceMetrics {
+            assignments: counters.assignments.load(Ordering::Relaxed),
+            revocations: counters.revocations.load(Ordering::Relaxed),
+            errors: counters.errors.load(Ordering::Relaxed),
+            drain_timeouts: counters.drain_timeouts.load(Ordering::Relaxed),
+            drain_count: counters.drain_count.load(Ordering::Relaxed),
+            drain_total_ms: counters.drain_total_ms.load(Ordering::Relaxed),
+            drain_last_ms: counters.drain_last_ms.load(Ordering::Relaxed),
+            drain_max_ms: counters.drain_max_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Prometheus text exposition of the counters, labelled with `service`
+    pub fn render_prometheus(&self, service: &str) -> String {
+        let metrics = self.metrics();
+        let mut out = String::from(
+            "# HELP kafka_consumer_rebalances_total Kafka consumer rebalance callbacks by kind\n\
+             # TYPE kafka_consumer_rebalances_total counter\n",
+        );
+        for (kind, count) in [
+            ("assign", metrics.assignments),
+            ("revoke", metrics.revocations),
+            ("error", metrics.errors),
+        ] {
+            out.push_str(&format!(
+                "kafka_consumer_rebalances_total{{service=\"{}\",kind=\"{}\"}} {}\n",
+                service, kind, count
+            ));
+        }
+        out.push_str(&format!(
+            "# HELP kafka_consumer_drain_timeouts_total Revocations that stopped waiting for their handlers\n\
+             # TYPE kafka_consumer_drain_timeouts_total counter\n\
+             kafka_consumer_drain_timeouts_total{{service=\"{service}\"}} {}\n\
+             # HELP kafka_consumer_drain_seconds Time spent waiting for in-flight handlers before a revocation\n\
+             # TYPE kafka_consumer_drain_seconds summary\n\
+             kafka_consumer_drain_seconds_sum{{service=\"{service}\"}} {:.3}\n\
+             kafka_consumer_drain_seconds_count{{service=\"{service}\"}} {}\n\
+             # HELP kafka_consumer_drain_max_seconds Longest drain since start\n\
+             # TYPE kafka_consumer_drain_max_seconds gauge\n\
+             kafka_consumer_drain_max_seconds{{service=\"{service}\"}} {:.3}\n\
+             # HELP kafka_consumer_in_flight_handlers Handlers running right now\n\
+             # TYPE kafka_consumer_in_flight_handlers gauge\n\
+             kafka_consumer_in_flight_handlers{{service=\"{service}\"}} {}\n",
+            metrics.drain_timeouts,
+            metrics.drain_total_ms as f64 / 1000.0,
+            metrics.drain_count,
+            metrics.drain_max_ms as f64 / 1000.0,
+            self.in_flight(),
+            service = service,
+        ));
+        out
+    }
+}
+
+impl InFlight {
+    /// Whether the message's partition was revoked while it was handled;
+    /// retrying it here would race the new owner
+    pub fn revoked(&self) -> bool {
+        self.tracker
+            .is_revoked(&self.partition.0, self.partition.1, self.generation)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut state = self.tracker.state();
+        if let Some(count) = state.in_flight.get_mut(&self.partition) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.in_flight.remove(&self.partition);
+            }
+        }
+        drop(state);
+        self.tracker.inner.drained.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn partition(topic: &str, partition: i32) -> Vec<(String, i32)> {
+        vec![(topic.to_string(), partition)]
+    }
+
+    #[test]
+    fn messages_of_revoked_partitions_are_not_started() {
+        let tracker = RebalanceTracker::new();
+        let generation = tracker.generation();
+
+        assert!(tracker.revoke(&partition("games.commands", 0), Duration::ZERO));
+
+        assert!(tracker.start("games.commands", 0, generation).is_none());
+        assert!(tracker.start("games.commands", 1, generation).is_some());
+        // Polled after the partition came back
+        assert!(tracker
+            .start("games.commands", 0, tracker.generation())
+            .is_some());
+    }
+
+    #[test]
+    fn revoke_waits_for_running_handlers() {
+        let tracker = RebalanceTracker::new();
+        let handler = tracker.start("games.commands", 0, 0).unwrap();
+        assert_eq!(tracker.in_flight(), 1);
+
+        let finisher = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            assert!(handler.revoked());
+            drop(handler);
+        });
+
+        assert!(tracker.revoke(&partition("games.commands", 0), Duration::from_secs(5)));
+        finisher.join().unwrap();
+        assert_eq!(tracker.in_flight(), 0);
+
+        let metrics = tracker.metrics();
+        assert_eq!(metrics.revocations, 1);
+        assert_eq!(metrics.drain_timeouts, 0);
+        assert_eq!(metrics.drain_count, 1);
+    }
+
+    #[test]
+    fn revoke_gives_up_after_the_timeout() {
+        let tracker = RebalanceTracker::new();
+        let _stuck = tracker.start("games.commands", 0, 0).unwrap();
+
+        assert!(!tracker.revoke(&partition("games.commands", 0), Duration::from_millis(10)));
+        assert_eq!(tracker.metrics().drain_timeouts, 1);
+        // Other partitions never wait
+        assert!(tracker.revoke(&partition("games.commands", 1), Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn prometheus_output_labels_the_service() {
+        let tracker = RebalanceTracker::new();
+        tracker.record_assignment();
+        let out = tracker.render_prometheus("checkout");
+        assert!(out.contains("kafka_consumer_rebalances_total{service=\"checkout\",kind=\"assign\"} 1"));
+        assert!(out.contains("kafka_consumer_drain_seconds_count{service=\"checkout\"} 0"));
+    }
+}
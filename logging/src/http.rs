@@ -0,0 +1,74 @@
+//! Request id middleware for actix-web
+//!
+//! Takes the caller's `X-Request-Id` (or makes one up), runs the rest of the
+//! request inside an `http_request` span carrying it, makes it the
+//! [`request_id::current`](crate::request_id::current) one and echoes it on the
+//! response. One line is logged per finished request.
+
+use std::time::Instant;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use tracing::{info, warn, Instrument};
+
+use crate::request_id;
+
+/// Request id of the request, also stored in the request extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Request id middleware; wrap it outermost so every other middleware logs
+/// inside the request's span
+pub async fn trace_request<B>(
+    request: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<B>, Error>
+where
+    B: MessageBody + 'static,
+{
+    let incoming = request
+        .headers()
+        .get(request_id::HEADER)
+        .and_then(|v| v.to_str().ok());
+    let request_id = request_id::accept(incoming);
+
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.path(),
+    );
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let started = Instant::now();
+    let result = request_id::scope(Some(request_id.clone()), next.call(request))
+        .instrument(span.clone())
+        .await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    span.in_scope(|| match result {
+        Ok(mut response) => {
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                response
+                    .headers_mut()
+                    .insert(HeaderName::from_static("x-request-id"), value);
+            }
+
+            let status = response.status().as_u16();
+            if response.status().is_server_error() {
+                warn!(status, elapsed_ms, "Request failed");
+            } else {
+                info!(status, elapsed_ms, "Request completed");
+            }
+            Ok(response)
+        }
+        Err(err) => {
+            let status = err.as_response_error().status_code().as_u16();
+            warn!(status, elapsed_ms, error = %err, "Request failed");
+            Err(err)
+        }
+    })
+}
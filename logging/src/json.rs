@@ -0,0 +1,214 @@
+//! JSON log lines
+//!
+//! [`SpanFieldsLayer`] keeps the fields of every span as JSON; [`JsonFormat`]
+//! writes each event as one flat object: timestamp, level, service and target,
+//! then the fields of the enclosing spans (outermost first) and finally the
+//! event's own fields. A field set by an inner span or by the event wins over
+//! one of the same name further out.
+
+use std::fmt::{self, Write as _};
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{span, Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span fields as JSON, stored in the span's extensions
+struct SpanFields(Map<String, Value>);
+
+/// Records span fields so [`JsonFormat`] can add them to every event
+pub struct SpanFieldsLayer;
+
+impl<S> Layer<S> for SpanFieldsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = JsonVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.0));
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SpanFields>() {
+            let mut visitor = JsonVisitor(std::mem::take(&mut fields.0));
+            values.record(&mut visitor);
+            fields.0 = visitor.0;
+        }
+    }
+}
+
+/// Event formatter writing one JSON object per line
+pub struct JsonFormat {
+    service: &'static str,
+}
+
+impl JsonFormat {
+    pub fn new(service: &'static str) -> Self {
+        Self { service }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut timestamp = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        line.insert("timestamp".into(), timestamp.into());
+        line.insert("level".into(), metadata.level().to_string().into());
+        line.insert("service".into(), self.service.into());
+        line.insert("target".into(), metadata.target().into());
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<SpanFields>() {
+                    line.extend(fields.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+            }
+        }
+
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        line.extend(visitor.0);
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Collects fields into a JSON object, keeping numbers and booleans typed
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let mut text = String::new();
+        let _ = write!(text, "{:?}", value);
+        self.0.insert(field.name().into(), text.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::{fmt, layer::SubscriberExt};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture_lines(emit: impl FnOnce()) -> Vec<Value> {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::registry().with(SpanFieldsLayer).with(
+            fmt::layer()
+                .event_format(JsonFormat::new("test_service"))
+                .with_writer(move || writer.clone()),
+        );
+
+        tracing::subscriber::with_default(subscriber, emit);
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_event_carries_service_and_span_fields() {
+        let lines = capture_lines(|| {
+            let request = tracing::info_span!(
+                "http_request",
+                request_id = "req-1",
+                user_id = tracing::field::Empty
+            );
+            let _request = request.enter();
+            request.record("user_id", 42);
+
+            let job = tracing::info_span!("job", job_id = "job-7");
+            let _job = job.enter();
+            tracing::info!(attempt = 2, retry = true, "Processing");
+        });
+
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line["service"], "test_service");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["message"], "Processing");
+        assert_eq!(line["request_id"], "req-1");
+        assert_eq!(line["user_id"], 42);
+        assert_eq!(line["job_id"], "job-7");
+        assert_eq!(line["attempt"], 2);
+        assert_eq!(line["retry"], true);
+        assert!(line["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_inner_fields_win() {
+        let lines = capture_lines(|| {
+            let outer = tracing::info_span!("outer", event_id = "outer-event");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner", event_id = "inner-event");
+            let _inner = inner.enter();
+            tracing::warn!("Handled");
+            tracing::warn!(event_id = "own-event", "Handled");
+        });
+
+        assert_eq!(lines[0]["event_id"], "inner-event");
+        assert_eq!(lines[1]["event_id"], "own-event");
+    }
+}
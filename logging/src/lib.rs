@@ -0,0 +1,84 @@
+//! Logging
+//!
+//! Log setup shared by the backend services. Every service logs one JSON object
+//! per line carrying its `service` name, so the logs of blazing_sun, checkout
+//! and ws_gateway can be merged and searched together. Fields of the spans an
+//! event happened in are flattened into the line, which is how the correlation
+//! ids reach every log written while handling something:
+//!
+//! - `request_id`: one per HTTP request ([`http::trace_request`]); handed on to
+//!   the MQ jobs and Kafka events the request produces (see [`request_id`])
+//! - `connection_id`: one per WebSocket connection in the gateway
+//! - `event_id`: the Kafka event being consumed
+//!
+//! So a single user action can be followed across services with
+//! `grep '"request_id":"<id>"'`.
+//!
+//! `LOG_FORMAT=pretty` switches to human readable, multi-line output for local
+//! development. Levels are taken from `RUST_LOG`.
+
+mod json;
+pub mod request_id;
+
+#[cfg(feature = "actix")]
+pub mod http;
+
+pub use json::{JsonFormat, SpanFieldsLayer};
+
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Output format of the service logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON object per line (default)
+    Json,
+    /// Human readable, for local development
+    Pretty,
+}
+
+impl LogFormat {
+    /// Parse a `LOG_FORMAT` value; anything unknown falls back to JSON
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "pretty" | "text" => LogFormat::Pretty,
+            _ => LogFormat::Json,
+        }
+    }
+
+    /// Format from the `LOG_FORMAT` environment variable
+    pub fn from_env() -> Self {
+        std::env::var("LOG_FORMAT")
+            .map(|value| Self::parse(&value))
+            .unwrap_or(LogFormat::Json)
+    }
+}
+
+/// Install the global subscriber for `service`.
+///
+/// `default_filter` applies when `RUST_LOG` is not set.
+pub fn init(service: &'static str, default_filter: &str) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match LogFormat::from_env() {
+        LogFormat::Json => registry
+            .with(SpanFieldsLayer)
+            .with(fmt::layer().event_format(JsonFormat::new(service)))
+            .init(),
+        LogFormat::Pretty => registry.with(fmt::layer().pretty()).init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parse() {
+        assert_eq!(LogFormat::parse("pretty"), LogFormat::Pretty);
+        assert_eq!(LogFormat::parse(" TEXT "), LogFormat::Pretty);
+        assert_eq!(LogFormat::parse("json"), LogFormat::Json);
+        assert_eq!(LogFormat::parse("yaml"), LogFormat::Json);
+    }
+}
@@ -0,0 +1,89 @@
+//! Request ids
+//!
+//! The id of the HTTP request (or job, or consumed event) being handled is held
+//! in a task-local for the duration of the handler, so code that enqueues MQ
+//! jobs or publishes Kafka events can stamp it on them with [`current`] without
+//! every call site passing it along. Work moved to another task with
+//! `tokio::spawn` does not inherit it; wrap that future in [`scope`] as well.
+
+use std::future::Future;
+
+/// Header carrying the request id on HTTP requests and responses
+pub const HEADER: &str = "X-Request-Id";
+
+/// Longest client-supplied request id that is kept
+const MAX_LEN: usize = 64;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// A new random request id
+pub fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// The id sent by the caller when it is usable, a new one otherwise.
+///
+/// Accepted ids are 1 to 64 characters of ASCII letters, digits, `-`, `_`, `.`
+/// and `:`, so they are safe to log and to echo back in a header.
+pub fn accept(incoming: Option<&str>) -> String {
+    match incoming.map(str::trim) {
+        Some(id) if is_valid(id) => id.to_string(),
+        _ => generate(),
+    }
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+/// Request id of the work the current task is doing, if any
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Run `future` with `request_id` as the [`current`] one; with `None` the
+/// future runs as is
+pub async fn scope<F: Future>(request_id: Option<String>, future: F) -> F::Output {
+    match request_id {
+        Some(request_id) => REQUEST_ID.scope(request_id, future).await,
+        None => future.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_keeps_valid_ids() {
+        assert_eq!(accept(Some("abc-123_x.y:z")), "abc-123_x.y:z");
+        assert_eq!(accept(Some("  req-1 ")), "req-1");
+    }
+
+    #[test]
+    fn test_accept_replaces_unusable_ids() {
+        for incoming in [None, Some(""), Some("has space"), Some("line\nbreak"), Some("é")] {
+            let id = accept(incoming);
+            assert!(uuid::Uuid::parse_str(&id).is_ok(), "{incoming:?} -> {id}");
+        }
+        assert_ne!(accept(Some(&"a".repeat(MAX_LEN + 1))), "a".repeat(MAX_LEN + 1));
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current() {
+        assert_eq!(current(), None);
+
+        let inside = scope(Some("req-9".to_string()), async { current() }).await;
+        assert_eq!(inside.as_deref(), Some("req-9"));
+
+        let without = scope(None, async { current() }).await;
+        assert_eq!(without, None);
+        assert_eq!(current(), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Read-only maintenance mode
+//!
+//! While the database is being maintained the services keep serving reads and
+//! turn writes away: blazing_sun and checkout answer mutating requests with a
+//! 503 and a [`Notice::rejection_body`], the gateway tells its clients with a
+//! `system.maintenance_notice` message and the MQ processor stops dequeueing.
+//!
+//! The flag is a [`Notice`] stored as JSON under [`KEY`] in the shared Redis,
+//! so one admin call (`PUT /api/v1/admin/maintenance`) switches every service.
+//! `MAINTENANCE_MODE=true` forces it on for a single service regardless of
+//! Redis, e.g. when Redis itself is what is being maintained:
+//!
+//! ```text
+//! MAINTENANCE_MODE=true
+//! MAINTENANCE_MESSAGE="Back at 04:00 UTC"
+//! ```
+//!
+//! [`Maintenance::current`] is called on every request, so it serves a cached
+//! value for [`REFRESH_INTERVAL`] and keeps the last one while Redis is down.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::{AsyncCommands, RedisResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::warn;
+
+/// Redis key of the active [`Notice`]
+pub const KEY: &str = "maintenance:mode";
+
+/// How long a read of the flag is trusted
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Retry-After of a maintenance window without a known end
+pub const DEFAULT_RETRY_AFTER_SECS: u64 = 60;
+
+pub const DEFAULT_MESSAGE: &str =
+    "We are performing scheduled maintenance. You can keep browsing, but changes are paused for a few minutes.";
+
+const MODE_VAR: &str = "MAINTENANCE_MODE";
+const MESSAGE_VAR: &str = "MAINTENANCE_MESSAGE";
+
+/// An active maintenance window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notice {
+    /// Shown to users
+    pub message: String,
+    pub started_at: DateTime<Utc>,
+    /// Expected end, when known
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl Notice {
+    /// A window starting now; a blank message falls back to [`DEFAULT_MESSAGE`]
+    pub fn new(message: Option<String>, ends_at: Option<DateTime<Utc>>) -> Self {
+        let message = message
+            .map(|message| message.trim().to_string())
+            .filter(|message| !message.is_empty())
+            .unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
+
+        Self {
+            message,
+            started_at: Utc::now(),
+            ends_at,
+        }
+    }
+
+    /// Seconds until the expected end (at least 1), or the default
+    pub fn retry_after_secs(&self, now: DateTime<Utc>) -> u64 {
+        match self.ends_at {
+            Some(ends_at) => (ends_at - now).num_seconds().max(1) as u64,
+            None => DEFAULT_RETRY_AFTER_SECS,
+        }
+    }
+
+    /// JSON body of a rejected write
+    pub fn rejection_body(&self) -> Value {
+        json!({
+            "status": "error",
+            "code": "maintenance",
+            "message": self.message,
+            "maintenance": {
+                "read_only": true,
+                "started_at": self.started_at,
+                "ends_at": self.ends_at,
+            },
+        })
+    }
+}
+
+/// The window `MAINTENANCE_MODE` forces on, if any
+pub fn env_override() -> Option<Notice> {
+    let mode = std::env::var(MODE_VAR).ok()?;
+    if !matches!(
+        mode.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "on" | "yes"
+    ) {
+        return None;
+    }
+
+    Some(Notice::new(std::env::var(MESSAGE_VAR).ok(), None))
+}
+
+/// Methods that keep working during maintenance
+pub fn is_read_only(method: &str) -> bool {
+    matches!(method, "GET" | "HEAD" | "OPTIONS")
+}
+
+/// Whether `path` is, or is under, one of `prefixes`
+pub fn is_exempt(path: &str, prefixes: &[String]) -> bool {
+    prefixes.iter().any(|prefix| {
+        let prefix = prefix.trim_end_matches('/');
+        !prefix.is_empty()
+            && path.starts_with(prefix)
+            && matches!(path.as_bytes().get(prefix.len()), None | Some(b'/'))
+    })
+}
+
+/// The window stored in Redis; a value that does not parse counts as none
+pub async fn read<C: ConnectionLike + Send>(conn: &mut C) -> RedisResult<Option<Notice>> {
+    let raw: Option<String> = conn.get(KEY).await?;
+
+    Ok(raw.and_then(|raw| match serde_json::from_str(&raw) {
+        Ok(notice) => Some(notice),
+        Err(err) => {
+            warn!("Ignoring malformed {} value: {}", KEY, err);
+            None
+        }
+    }))
+}
+
+/// Store the window, switching every service to read-only
+pub async fn enable<C: ConnectionLike + Send>(conn: &mut C, notice: &Notice) -> RedisResult<()> {
+    let raw = serde_json::to_string(notice).expect("Notice serializes");
+    conn.set(KEY, raw).await
+}
+
+/// Remove the window; false when none was stored
+pub async fn disable<C: ConnectionLike + Send>(conn: &mut C) -> RedisResult<bool> {
+    let removed: u32 = conn.del(KEY).await?;
+    Ok(removed > 0)
+}
+
+/// A service's view of the flag
+pub struct Maintenance {
+    redis: Option<ConnectionManager>,
+    forced: Option<Notice>,
+    cache: Mutex<Option<(Instant, Option<Notice>)>>,
+}
+
+impl Maintenance {
+    /// Read the flag from `redis`, unless `MAINTENANCE_MODE` forces it on.
+    /// Without Redis only the override can enable it.
+    pub fn new(redis: Option<ConnectionManager>) -> Self {
+        Self {
+            redis,
+            forced: env_override(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Whether `MAINTENANCE_MODE` forces the flag on
+    pub fn is_forced(&self) -> bool {
+        self.forced.is_some()
+    }
+
+    /// The active window, if any
+    pub async fn current(&self) -> Option<Notice> {
+        if let Some(forced) = &self.forced {
+            return Some(forced.clone());
+        }
+        let mut redis = self.redis.clone()?;
+
+        if let Some((read_at, notice)) = &*self.cache.lock().unwrap() {
+            if read_at.elapsed() < REFRESH_INTERVAL {
+                return notice.clone();
+            }
+        }
+
+        let notice = match read(&mut redis).await {
+            Ok(notice) => notice,
+            Err(err) => {
+                warn!(
+                    "Failed to read maintenance mode, keeping the last value: {}",
+                    err
+                );
+                self.cache
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(|(_, notice)| notice.clone())
+            }
+        };
+
+        // A failed read is retried after the next interval, not on every request
+        *self.cache.lock().unwrap() = Some((Instant::now(), notice.clone()));
+        notice
+    }
+
+    /// Switch every service to read-only
+    pub async fn enable(&self, notice: &Notice) -> RedisResult<()> {
+        let mut redis = self.connection()?;
+        enable(&mut redis, notice).await?;
+        *self.cache.lock().unwrap() = Some((Instant::now(), Some(notice.clone())));
+        Ok(())
+    }
+
+    /// End the window; false when none was active
+    pub async fn disable(&self) -> RedisResult<bool> {
+        let mut redis = self.connection()?;
+        let removed = disable(&mut redis).await?;
+        *self.cache.lock().unwrap() = Some((Instant::now(), None));
+        Ok(removed)
+    }
+
+    fn connection(&self) -> RedisResult<ConnectionManager> {
+        self.redis.clone().ok_or_else(|| {
+            redis::RedisError::from((redis::ErrorKind::IoError, "Redis is not configured"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn only_reads_keep_working() {
+        for method in ["GET", "HEAD", "OPTIONS"] {
+            assert!(is_read_only(method));
+        }
+        for method in ["POST", "PUT", "PATCH", "DELETE"] {
+            assert!(!is_read_only(method));
+        }
+    }
+
+    #[test]
+    fn exempt_prefixes_match_whole_segments() {
+        let prefixes = vec![
+            "/api/v1/admin/maintenance".to_string(),
+            "/health/".to_string(),
+        ];
+
+        assert!(is_exempt("/api/v1/admin/maintenance", &prefixes));
+        assert!(is_exempt("/health", &prefixes));
+        assert!(is_exempt("/health/ready", &prefixes));
+        assert!(!is_exempt("/healthz", &prefixes));
+        assert!(!is_exempt("/api/v1/admin/maintenance-window", &prefixes));
+        assert!(!is_exempt("/api/v1/user", &[String::new()]));
+    }
+
+    #[test]
+    fn notices_default_their_message_and_retry_after() {
+        let notice = Notice::new(Some("  ".to_string()), None);
+        assert_eq!(notice.message, DEFAULT_MESSAGE);
+        assert_eq!(
+            notice.retry_after_secs(Utc::now()),
+            DEFAULT_RETRY_AFTER_SECS
+        );
+
+        let now = Utc::now();
+        let notice = Notice::new(
+            Some("Back soon".to_string()),
+            Some(now + TimeDelta::seconds(90)),
+        );
+        assert_eq!(notice.retry_after_secs(now), 90);
+        assert_eq!(notice.retry_after_secs(now + TimeDelta::seconds(600)), 1);
+
+        let body = notice.rejection_body();
+        assert_eq!(body["code"], "maintenance");
+        assert_eq!(body["message"], "Back soon");
+        assert_eq!(body["maintenance"]["read_only"], true);
+    }
+
+    #[test]
+    fn notices_round_trip_without_an_end() {
+        let notice: Notice =
+            serde_json::from_str(r#"{"message":"m","started_at":"2026-10-17T03:00:00Z"}"#).unwrap();
+        assert_eq!(notice.ends_at, None);
+        assert_eq!(
+            serde_json::from_value::<Notice>(serde_json::to_value(&notice).unwrap()).unwrap(),
+            notice
+        );
+    }
+
+    #[tokio::test]
+    async fn without_redis_the_flag_is_off() {
+        let maintenance = Maintenance {
+            redis: None,
+            forced: None,
+            cache: Mutex::new(None),
+        };
+
+        assert_eq!(maintenance.current().await, None);
+        assert!(maintenance.disable().await.is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Keyset pagination cursors
+//!
+//! Lists are ordered newest first by `(created_at, id)`. A cursor is the key of
+//! the row a page ends at plus the direction to read in, encoded as opaque
+//! base64 so clients never build them by hand.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Rows older than the cursor
+    Next,
+    /// Rows newer than the cursor
+    Prev,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: i64,
+    pub direction: Direction,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let direction = match self.direction {
+            Direction::Next => 'n',
+            Direction::Prev => 'p',
+        };
+        let raw = format!(
+            "{}:{}:{}",
+            direction,
+            self.created_at.timestamp_micros(),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(value.trim()).ok()?).ok()?;
+        let mut parts = raw.splitn(3, ':');
+
+        let direction = match parts.next()? {
+            "n" => Direction::Next,
+            "p" => Direction::Prev,
+            _ => return None,
+        };
+        let created_at = DateTime::from_timestamp_micros(parts.next()?.parse().ok()?)?;
+        let id = parts.next()?.parse().ok()?;
+
+        Some(Self {
+            created_at,
+            id,
+            direction,
+        })
+    }
+}
+
+/// One page of a newest-first list
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+/// Build a page from rows fetched with `limit + 1` in query order (newest first
+/// for `Next`/no cursor, oldest first for `Prev`). The extra row only tells
+/// whether another page exists in the reading direction.
+pub fn paginate<T>(
+    mut rows: Vec<T>,
+    cursor: Option<&Cursor>,
+    limit: usize,
+    key: impl Fn(&T) -> (DateTime<Utc>, i64),
+) -> Page<T> {
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+
+    let reading_back = matches!(cursor.map(|c| c.direction), Some(Direction::Prev));
+    if reading_back {
+        rows.reverse();
+    }
+
+    let cursor_at = |row: Option<&T>, direction| {
+        row.map(|row| {
+            let (created_at, id) = key(row);
+            Cursor {
+                created_at,
+                id,
+                direction,
+            }
+            .encode()
+        })
+    };
+
+    // Coming from a page in the other direction means that page still exists
+    let (older, newer) = match cursor {
+        None => (has_more, false),
+        Some(_) if reading_back => (true, has_more),
+        Some(_) => (has_more, true),
+    };
+
+    Page {
+        next_cursor: older
+            .then(|| cursor_at(rows.last(), Direction::Next))
+            .flatten(),
+        prev_cursor: newer
+            .then(|| cursor_at(rows.first(), Direction::Prev))
+            .flatten(),
+        items: rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{paginate, Cursor, Direction};
+    use chrono::{DateTime, Utc};
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).expect("timestamp")
+    }
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = Cursor {
+            created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456).expect("ts"),
+            id: 42,
+            direction: Direction::Prev,
+        };
+
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(""), None);
+    }
+
+    #[test]
+    fn paginate_walks_forward_and_back() {
+        // Rows (created_at secs == id), newest first: 5 4 3 2 1, pages of 2
+        let key = |id: &i64| (at(*id), *id);
+
+        let first = paginate(vec![5, 4, 3], None, 2, key);
+        assert_eq!(first.items, vec![5, 4]);
+        assert!(first.prev_cursor.is_none());
+        let next = Cursor::decode(first.next_cursor.as_deref().expect("next")).expect("decode");
+        assert_eq!((next.id, next.direction), (4, Direction::Next));
+
+        // Query for rows older than 4 returned 3 2 1
+        let second = paginate(vec![3, 2, 1], Some(&next), 2, key);
+        assert_eq!(second.items, vec![3, 2]);
+        let prev = Cursor::decode(second.prev_cursor.as_deref().expect("prev")).expect("decode");
+        assert_eq!((prev.id, prev.direction), (3, Direction::Prev));
+
+        // Query for rows newer than 3 (oldest first) returned 4 5
+        let back = paginate(vec![4, 5], Some(&prev), 2, key);
+        assert_eq!(back.items, vec![5, 4]);
+        assert!(back.prev_cursor.is_none());
+        assert!(back.next_cursor.is_some());
+
+        // Last page: nothing older
+        let last = paginate(vec![1], Some(&next), 2, key);
+        assert!(last.next_cursor.is_none());
+        assert!(last.prev_cursor.is_some());
+    }
+}
@@ -0,0 +1,41 @@
+//! Role-Based Access Control
+//!
+//! Roles, the permissions they grant, and the permission each API route group
+//! and WebSocket command needs. blazing_sun enforces it on HTTP routes and the
+//! WebSocket gateway on client commands, so both services answer "may this
+//! user do that?" from the same table.
+//!
+//! A user's role follows from their permission level (the `permissions`
+//! column and JWT claim):
+//!
+//! | Level                         | Role      |
+//! |-------------------------------|-----------|
+//! | 1 (basic), 50 (affiliate)     | user      |
+//! | 5                             | moderator |
+//! | 10 (admin), 100 (super admin) | admin     |
+//!
+//! ```
+//! use rbac::{Permission, Role, RouteGroup};
+//!
+//! let role = Role::from_level(5);
+//! assert_eq!(role, Role::Moderator);
+//! assert!(role.can(Permission::ModerateChat));
+//! assert!(!role.can(RouteGroup::Roles.permission().unwrap()));
+//! ```
+
+mod role;
+mod routes;
+mod ws;
+
+pub use role::{Permission, Role};
+pub use routes::RouteGroup;
+pub use ws::command_permission;
+
+/// Permission levels the roles are derived from
+pub mod levels {
+    pub const BASIC: i32 = 1;
+    pub const MODERATOR: i32 = 5;
+    pub const ADMIN: i32 = 10;
+    pub const AFFILIATE: i32 = 50;
+    pub const SUPER_ADMIN: i32 = 100;
+}
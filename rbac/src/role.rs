@@ -0,0 +1,167 @@
+//! Roles and permissions
+
+use serde::{Deserialize, Serialize};
+
+use crate::levels;
+
+/// Something a role may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Create, join and play game rooms, and run the rooms one hosts
+    PlayGames,
+    /// Direct, lobby, channel and game room chat
+    Chat,
+    /// Inspect and lift chat and WebSocket flood penalties
+    ModerateChat,
+    /// Moderate game rooms one does not host
+    ModerateRooms,
+    /// Look up users and their activity
+    ViewUsers,
+    /// Edit, delete and bulk-manage users
+    ManageUsers,
+    /// Give users a role
+    ManageRoles,
+    /// Uploads, assets, galleries, themes and game configuration
+    ManageContent,
+    /// Feature flags, caches, analytics and payments
+    ManageSystem,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 9] = [
+        Permission::PlayGames,
+        Permission::Chat,
+        Permission::ModerateChat,
+        Permission::ModerateRooms,
+        Permission::ViewUsers,
+        Permission::ManageUsers,
+        Permission::ManageRoles,
+        Permission::ManageContent,
+        Permission::ManageSystem,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::PlayGames => "play_games",
+            Permission::Chat => "chat",
+            Permission::ModerateChat => "moderate_chat",
+            Permission::ModerateRooms => "moderate_rooms",
+            Permission::ViewUsers => "view_users",
+            Permission::ManageUsers => "manage_users",
+            Permission::ManageRoles => "manage_roles",
+            Permission::ManageContent => "manage_content",
+            Permission::ManageSystem => "manage_system",
+        }
+    }
+}
+
+const USER_PERMISSIONS: &[Permission] = &[Permission::PlayGames, Permission::Chat];
+
+const MODERATOR_PERMISSIONS: &[Permission] = &[
+    Permission::PlayGames,
+    Permission::Chat,
+    Permission::ModerateChat,
+    Permission::ModerateRooms,
+    Permission::ViewUsers,
+];
+
+/// A user's role; every role has the permissions of the roles below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::User, Role::Moderator, Role::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|role| role.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Role of a user with the given permission level
+    pub fn from_level(level: i32) -> Self {
+        match level {
+            levels::ADMIN | levels::SUPER_ADMIN => Role::Admin,
+            levels::MODERATOR => Role::Moderator,
+            _ => Role::User,
+        }
+    }
+
+    /// Permission level stored for a user given this role
+    pub fn level(&self) -> i32 {
+        match self {
+            Role::User => levels::BASIC,
+            Role::Moderator => levels::MODERATOR,
+            Role::Admin => levels::ADMIN,
+        }
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::User => USER_PERMISSIONS,
+            Role::Moderator => MODERATOR_PERMISSIONS,
+            Role::Admin => &Permission::ALL,
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_follow_permission_levels() {
+        assert_eq!(Role::from_level(levels::BASIC), Role::User);
+        assert_eq!(Role::from_level(levels::AFFILIATE), Role::User);
+        assert_eq!(Role::from_level(levels::MODERATOR), Role::Moderator);
+        assert_eq!(Role::from_level(levels::ADMIN), Role::Admin);
+        assert_eq!(Role::from_level(levels::SUPER_ADMIN), Role::Admin);
+        assert_eq!(Role::from_level(0), Role::User);
+
+        for role in Role::ALL {
+            assert_eq!(Role::from_level(role.level()), role);
+            assert_eq!(Role::parse(role.as_str()), Some(role));
+        }
+        assert_eq!(Role::parse(" Moderator "), Some(Role::Moderator));
+        assert_eq!(Role::parse("super_admin"), None);
+    }
+
+    #[test]
+    fn higher_roles_keep_the_permissions_of_lower_ones() {
+        for pair in Role::ALL.windows(2) {
+            let (lower, higher) = (pair[0], pair[1]);
+            assert!(lower.permissions().iter().all(|p| higher.can(*p)));
+            assert!(higher.permissions().len() > lower.permissions().len());
+        }
+        assert!(!Role::User.can(Permission::ModerateChat));
+        assert!(!Role::Moderator.can(Permission::ManageRoles));
+        assert!(Permission::ALL.iter().all(|p| Role::Admin.can(*p)));
+    }
+
+    #[test]
+    fn serialized_names_match_as_str() {
+        for permission in Permission::ALL {
+            let json = serde_json::to_string(&permission).unwrap();
+            assert_eq!(json, format!("\"{}\"", permission.as_str()));
+        }
+        assert_eq!(serde_json::to_string(&Role::Moderator).unwrap(), "\"moderator\"");
+    }
+}
@@ -0,0 +1,60 @@
+//! API route groups
+
+use crate::Permission;
+
+/// A group of HTTP routes guarded by one permission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    /// The signed-in user's own profile, balance and settings
+    Account,
+    Games,
+    Chat,
+    /// Flood penalties and other moderation tools
+    Moderation,
+    /// User lookups
+    Users,
+    /// User edits, deletion and bulk actions
+    UserManagement,
+    /// Role assignment
+    Roles,
+    /// Uploads, assets, galleries, themes and game configuration
+    Content,
+    /// Feature flags, caches, analytics and payments
+    System,
+}
+
+impl RouteGroup {
+    /// Permission the group needs; None when any signed-in user may use it
+    pub fn permission(&self) -> Option<Permission> {
+        match self {
+            RouteGroup::Account => None,
+            RouteGroup::Games => Some(Permission::PlayGames),
+            RouteGroup::Chat => Some(Permission::Chat),
+            RouteGroup::Moderation => Some(Permission::ModerateChat),
+            RouteGroup::Users => Some(Permission::ViewUsers),
+            RouteGroup::UserManagement => Some(Permission::ManageUsers),
+            RouteGroup::Roles => Some(Permission::ManageRoles),
+            RouteGroup::Content => Some(Permission::ManageContent),
+            RouteGroup::System => Some(Permission::ManageSystem),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+
+    #[test]
+    fn moderators_reach_moderation_but_not_roles() {
+        let allowed = |role: Role, group: RouteGroup| group.permission().is_none_or(|p| role.can(p));
+
+        assert!(allowed(Role::User, RouteGroup::Account));
+        assert!(allowed(Role::User, RouteGroup::Games));
+        assert!(!allowed(Role::User, RouteGroup::Moderation));
+        assert!(allowed(Role::Moderator, RouteGroup::Moderation));
+        assert!(allowed(Role::Moderator, RouteGroup::Users));
+        assert!(!allowed(Role::Moderator, RouteGroup::Roles));
+        assert!(allowed(Role::Admin, RouteGroup::Roles));
+    }
+}
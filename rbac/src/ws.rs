@@ -0,0 +1,57 @@
+//! WebSocket commands
+
+use crate::Permission;
+
+/// Permission a WebSocket command needs, by its `type`; None for the system
+/// commands every connection sends
+///
+/// Room moderation (kick, ban, mute) only needs [`Permission::PlayGames`]
+/// here: whether the sender hosts the room is checked by the games handler.
+/// Commands this table does not know are left to admins.
+pub fn command_permission(command: &str) -> Option<Permission> {
+    match command {
+        "system.authenticate" | "system.heartbeat" | "system.sync_state" => None,
+        "games.command.player_chat"
+        | "games.command.spectator_chat"
+        | "games.command.send_chat"
+        | "games.command.get_chat_history" => Some(Permission::Chat),
+        _ if command.starts_with("chat.command.") => Some(Permission::Chat),
+        _ if command.starts_with("games.command.") => Some(Permission::PlayGames),
+        _ => Some(Permission::ManageSystem),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+
+    #[test]
+    fn commands_map_to_permissions() {
+        assert_eq!(command_permission("system.heartbeat"), None);
+        assert_eq!(
+            command_permission("chat.command.send_message"),
+            Some(Permission::Chat)
+        );
+        assert_eq!(
+            command_permission("games.command.send_chat"),
+            Some(Permission::Chat)
+        );
+        assert_eq!(
+            command_permission("games.command.bigger_dice.roll"),
+            Some(Permission::PlayGames)
+        );
+        assert_eq!(
+            command_permission("games.command.ban_player"),
+            Some(Permission::PlayGames)
+        );
+    }
+
+    #[test]
+    fn unknown_commands_are_for_admins_only() {
+        let permission = command_permission("system.shutdown").expect("permission");
+        assert!(!Role::User.can(permission));
+        assert!(!Role::Moderator.can(permission));
+        assert!(Role::Admin.can(permission));
+    }
+}
@@ -152,7 +152,8 @@ if [ "$BUILD_ENV" = "dev" ]; then
     cargo sqlx prepare || true
 
     echo "Starting with hot reload..."
-    exec cargo watch --poll -i ".sqlx" -i "*.json" -i "tests" -i "*.spec.ts" -i "test-results" -i "**/storage/**" -i "**/storage" -i "src/storage/*" -i "src/resources/css/*" -i "src/resources/js/*" -i "src/frontend/**" -i "node_modules" -x "run --bin blazing_sun"
+    # CARGO_FEATURES=chaos turns on fault injection for integration tests
+    exec cargo watch --poll -i ".sqlx" -i "*.json" -i "tests" -i "*.spec.ts" -i "test-results" -i "**/storage/**" -i "**/storage" -i "src/storage/*" -i "src/resources/css/*" -i "src/resources/js/*" -i "src/frontend/**" -i "node_modules" -x "run --bin blazing_sun ${CARGO_FEATURES:+--features $CARGO_FEATURES}"
 else
     echo "Starting in PRODUCTION mode..."
 
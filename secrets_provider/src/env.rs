@@ -0,0 +1,15 @@
+use crate::SecretsProvider;
+
+/// Plain environment variables
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvProvider;
+
+impl SecretsProvider for EnvProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+}
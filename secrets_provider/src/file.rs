@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::SecretsProvider;
+
+/// Default `SECRETS_DIR`, where docker mounts its secrets
+pub const DEFAULT_SECRETS_DIR: &str = "/run/secrets";
+
+/// Docker secrets: one file per key
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    dir: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Reads `SECRETS_DIR`
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("SECRETS_DIR")
+                .ok()
+                .filter(|dir| !dir.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_SECRETS_DIR.to_string()),
+        )
+    }
+
+    fn read(path: &Path) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        Some(content.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+impl SecretsProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    /// `KEY_FILE`, then `dir/key` and `dir/KEY`, then the `KEY` env var
+    fn get(&self, key: &str) -> Option<String> {
+        if let Ok(path) = std::env::var(format!("{}_FILE", key)) {
+            if let Some(value) = Self::read(Path::new(&path)) {
+                return Some(value);
+            }
+        }
+
+        Self::read(&self.dir.join(key.to_ascii_lowercase()))
+            .or_else(|| Self::read(&self.dir.join(key)))
+            .or_else(|| std::env::var(key).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_read_from_the_directory_without_the_trailing_newline() {
+        let dir = std::env::temp_dir().join(format!("secrets_provider_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("secrets_provider_test_db_password"), "hunter2\n").unwrap();
+
+        let provider = FileProvider::new(&dir);
+        assert_eq!(
+            provider.get("SECRETS_PROVIDER_TEST_DB_PASSWORD").as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(provider.get("SECRETS_PROVIDER_TEST_UNSET"), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
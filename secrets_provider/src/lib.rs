@@ -0,0 +1,118 @@
+//! Secrets Provider
+//!
+//! Where services read their settings from. `SECRETS_PROVIDER` selects one:
+//!
+//! - `env` (default): environment variables, as before.
+//! - `file`: docker secrets. `KEY` is read from the file named by `KEY_FILE`,
+//!   else from `SECRETS_DIR/key` (default `/run/secrets`).
+//! - `vault`: a HashiCorp Vault KV v2 secret (`VAULT_ADDR`, `VAULT_TOKEN` or
+//!   `VAULT_TOKEN_FILE`, `VAULT_KV_MOUNT`, `VAULT_SECRET_PATH`), read at
+//!   startup. The token is renewed in the background before it expires and
+//!   the secret re-read with it.
+//!
+//! The file and Vault providers fall back to the environment for keys they do
+//! not hold, so ordinary settings stay plain env vars. Config structs print
+//! secrets through [`Redacted`] and [`redact_url`] so they can be logged.
+
+mod env;
+mod file;
+mod redact;
+mod vault;
+
+use std::sync::Arc;
+
+use thiserror::Error;
+
+pub use env::EnvProvider;
+pub use file::FileProvider;
+pub use redact::{redact_url, Redacted};
+pub use vault::{VaultProvider, VaultSettings};
+
+/// Env var selecting the provider
+pub const PROVIDER_VAR: &str = "SECRETS_PROVIDER";
+
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error("Unknown SECRETS_PROVIDER \"{0}\" (expected env, file or vault)")]
+    UnknownProvider(String),
+    #[error("{0} must be set")]
+    Missing(String),
+    #[error("Vault: {0}")]
+    Vault(String),
+}
+
+/// A source of settings and secrets
+pub trait SecretsProvider: Send + Sync {
+    /// Short name for logs ("env", "file" or "vault")
+    fn name(&self) -> &'static str;
+
+    /// The value of `key`, None when neither the provider nor the environment has it
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Drop-in for `std::env::var`
+    fn var(&self, key: &str) -> Result<String, std::env::VarError> {
+        self.get(key).ok_or(std::env::VarError::NotPresent)
+    }
+
+    /// The value of `key`, which must be set and not blank
+    fn require(&self, key: &str) -> Result<String, SecretsError> {
+        self.get(key)
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| SecretsError::Missing(key.to_string()))
+    }
+}
+
+/// The provider `SECRETS_PROVIDER` selects; a Vault provider is connected and
+/// its token renewal started (call from within a Tokio runtime)
+pub async fn from_env() -> Result<Arc<dyn SecretsProvider>, SecretsError> {
+    let kind = std::env::var(PROVIDER_VAR).unwrap_or_default().trim().to_ascii_lowercase();
+
+    match kind.as_str() {
+        "" | "env" => Ok(Arc::new(EnvProvider)),
+        "file" => Ok(Arc::new(FileProvider::from_env())),
+        "vault" => {
+            let provider = VaultProvider::connect(VaultSettings::from_env()?).await?;
+            provider.spawn_renewal();
+            Ok(provider)
+        }
+        other => Err(SecretsError::UnknownProvider(other.to_string())),
+    }
+}
+
+/// Every key of `keys` that is unset or blank
+pub fn missing(provider: &dyn SecretsProvider, keys: &[&str]) -> Vec<String> {
+    keys.iter()
+        .filter(|key| provider.require(key).is_err())
+        .map(|key| key.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Fixed(HashMap<&'static str, &'static str>);
+
+    impl SecretsProvider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.get(key).map(|value| value.to_string())
+        }
+    }
+
+    #[test]
+    fn blank_values_are_missing() {
+        let provider = Fixed(HashMap::from([("JWT_SECRET", "s3cret"), ("STRIPE_SECRET", "  ")]));
+
+        assert_eq!(provider.require("JWT_SECRET").unwrap(), "s3cret");
+        assert_eq!(provider.var("NOPE"), Err(std::env::VarError::NotPresent));
+        assert_eq!(
+            missing(&provider, &["JWT_SECRET", "STRIPE_SECRET", "DATABASE_URL"]),
+            vec!["STRIPE_SECRET", "DATABASE_URL"]
+        );
+    }
+}
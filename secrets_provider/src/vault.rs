@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{SecretsError, SecretsProvider};
+
+/// Default `VAULT_KV_MOUNT`
+pub const DEFAULT_KV_MOUNT: &str = "secret";
+
+/// Earliest renewal, whatever the token's TTL
+const MIN_RENEW_DELAY: Duration = Duration::from_secs(5);
+
+/// Wait before retrying a failed renewal
+const RENEW_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the secret lives and the token to read it with
+#[derive(Clone)]
+pub struct VaultSettings {
+    /// `VAULT_ADDR`, e.g. `https://vault.internal:8200`
+    pub addr: String,
+    /// `VAULT_TOKEN`, or the content of `VAULT_TOKEN_FILE`
+    pub token: String,
+    /// `VAULT_KV_MOUNT` (default `secret`)
+    pub mount: String,
+    /// `VAULT_SECRET_PATH`, e.g. `rust_api/checkout`
+    pub path: String,
+}
+
+impl std::fmt::Debug for VaultSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultSettings")
+            .field("addr", &self.addr)
+            .field("token", &crate::Redacted(&self.token))
+            .field("mount", &self.mount)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl VaultSettings {
+    pub fn from_env() -> Result<Self, SecretsError> {
+        let var = |key: &str| std::env::var(key).ok().filter(|value| !value.trim().is_empty());
+
+        let token = match var("VAULT_TOKEN") {
+            Some(token) => token,
+            None => {
+                let path = var("VAULT_TOKEN_FILE")
+                    .ok_or_else(|| SecretsError::Missing("VAULT_TOKEN or VAULT_TOKEN_FILE".to_string()))?;
+                std::fs::read_to_string(&path)
+                    .map_err(|e| SecretsError::Vault(format!("cannot read {}: {}", path, e)))?
+                    .trim()
+                    .to_string()
+            }
+        };
+
+        Ok(Self {
+            addr: var("VAULT_ADDR")
+                .ok_or_else(|| SecretsError::Missing("VAULT_ADDR".to_string()))?
+                .trim_end_matches('/')
+                .to_string(),
+            token,
+            mount: var("VAULT_KV_MOUNT").unwrap_or_else(|| DEFAULT_KV_MOUNT.to_string()),
+            path: var("VAULT_SECRET_PATH")
+                .ok_or_else(|| SecretsError::Missing("VAULT_SECRET_PATH".to_string()))?
+                .trim_matches('/')
+                .to_string(),
+        })
+    }
+}
+
+/// Keys of a Vault KV v2 secret
+pub struct VaultProvider {
+    settings: VaultSettings,
+    client: reqwest::Client,
+    values: RwLock<HashMap<String, String>>,
+    /// Seconds left on the token when last looked up or renewed (0 = does not expire)
+    ttl_seconds: RwLock<u64>,
+    renewable: bool,
+}
+
+impl VaultProvider {
+    /// Look the token up and read the secret
+    pub async fn connect(settings: VaultSettings) -> Result<Arc<Self>, SecretsError> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| SecretsError::Vault(e.to_string()))?;
+
+        let mut provider = Self {
+            settings,
+            client,
+            values: RwLock::new(HashMap::new()),
+            ttl_seconds: RwLock::new(0),
+            renewable: false,
+        };
+
+        let lookup = provider.request(reqwest::Method::GET, "auth/token/lookup-self").await?;
+        let data = &lookup["data"];
+        provider.renewable = data["renewable"].as_bool().unwrap_or(false);
+        *provider.ttl_seconds.write().unwrap() = data["ttl"].as_u64().unwrap_or(0);
+
+        provider.refresh().await?;
+        info!(
+            path = %provider.settings.path,
+            keys = provider.values.read().unwrap().len(),
+            "Secrets loaded from Vault"
+        );
+
+        Ok(Arc::new(provider))
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str) -> Result<Value, SecretsError> {
+        let url = format!("{}/v1/{}", self.settings.addr, path);
+        let response = self
+            .client
+            .request(method, &url)
+            .header("X-Vault-Token", &self.settings.token)
+            .send()
+            .await
+            .map_err(|e| SecretsError::Vault(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SecretsError::Vault(format!("{} answered {}", path, status)));
+        }
+
+        response.json().await.map_err(|e| SecretsError::Vault(e.to_string()))
+    }
+
+    /// Read the secret again
+    pub async fn refresh(&self) -> Result<(), SecretsError> {
+        let path = format!("{}/data/{}", self.settings.mount, self.settings.path);
+        let body = self.request(reqwest::Method::GET, &path).await?;
+        *self.values.write().unwrap() = parse_kv(&body)?;
+        Ok(())
+    }
+
+    /// Renew the token; returns its new TTL in seconds
+    pub async fn renew(&self) -> Result<u64, SecretsError> {
+        let body = self.request(reqwest::Method::POST, "auth/token/renew-self").await?;
+        let ttl = body["auth"]["lease_duration"].as_u64().unwrap_or(0);
+        *self.ttl_seconds.write().unwrap() = ttl;
+        Ok(ttl)
+    }
+
+    /// Keep the token alive, re-reading the secret after each renewal;
+    /// nothing to do for tokens that do not expire or cannot be renewed
+    pub fn spawn_renewal(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let ttl = *self.ttl_seconds.read().unwrap();
+        if !self.renewable || ttl == 0 {
+            return None;
+        }
+
+        let provider = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut delay = renew_delay(ttl);
+            loop {
+                tokio::time::sleep(delay).await;
+
+                delay = match provider.renew().await {
+                    Ok(ttl) => {
+                        if let Err(e) = provider.refresh().await {
+                            warn!(error = %e, "Failed to re-read secrets from Vault");
+                        }
+                        renew_delay(ttl)
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Failed to renew the Vault token");
+                        RENEW_RETRY_DELAY
+                    }
+                };
+            }
+        }))
+    }
+}
+
+impl SecretsProvider for VaultProvider {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        let from_vault = self.values.read().unwrap().get(key).cloned();
+        from_vault.or_else(|| std::env::var(key).ok())
+    }
+}
+
+/// Renew halfway through the token's TTL
+pub fn renew_delay(ttl_seconds: u64) -> Duration {
+    Duration::from_secs(ttl_seconds / 2).max(MIN_RENEW_DELAY)
+}
+
+/// The key/value pairs of a KV v2 read response (`data.data`)
+fn parse_kv(body: &Value) -> Result<HashMap<String, String>, SecretsError> {
+    let data = body["data"]["data"]
+        .as_object()
+        .ok_or_else(|| SecretsError::Vault("the secret has no data".to_string()))?;
+
+    Ok(data
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => return None,
+            };
+            Some((key.clone(), value))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn kv_v2_responses_keep_scalar_values() {
+        let body = json!({
+            "data": {
+                "data": { "STRIPE_SECRET": "sk_test_1", "CHECKOUT_PORT": 9996, "NESTED": { "a": 1 } },
+                "metadata": { "version": 3 }
+            }
+        });
+
+        let values = parse_kv(&body).unwrap();
+        assert_eq!(values["STRIPE_SECRET"], "sk_test_1");
+        assert_eq!(values["CHECKOUT_PORT"], "9996");
+        assert!(!values.contains_key("NESTED"));
+
+        assert!(parse_kv(&json!({ "errors": [] })).is_err());
+    }
+
+    #[test]
+    fn tokens_are_renewed_halfway_through_their_ttl() {
+        assert_eq!(renew_delay(3600), Duration::from_secs(1800));
+        assert_eq!(renew_delay(4), MIN_RENEW_DELAY);
+    }
+
+    #[test]
+    fn settings_never_print_the_token() {
+        let settings = VaultSettings {
+            addr: "https://vault:8200".to_string(),
+            token: "hvs.secret".to_string(),
+            mount: "secret".to_string(),
+            path: "rust_api/checkout".to_string(),
+        };
+        assert!(!format!("{:?}", settings).contains("hvs.secret"));
+    }
+}
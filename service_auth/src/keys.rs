@@ -0,0 +1,141 @@
+//! Signing keys
+
+use std::fmt;
+
+/// Shortest accepted secret; HMAC-SHA256 keys should carry at least 256 bits
+const MIN_SECRET_LEN: usize = 32;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum KeyError {
+    #[error("no service auth keys configured")]
+    Empty,
+    /// 1-based position of the entry in the list; the entry itself may
+    /// be a bare secret, so it is never echoed
+    #[error("service auth key #{0} must be written as kid:secret")]
+    Malformed(usize),
+    #[error("service auth key id '{0}' may only contain letters, digits, '-' and '_'")]
+    InvalidKid(String),
+    #[error("service auth key '{0}' is shorter than {MIN_SECRET_LEN} bytes")]
+    TooShort(String),
+    #[error("service auth key id '{0}' is listed twice")]
+    Duplicate(String),
+}
+
+/// Ordered set of `kid -> secret` pairs; the first key signs
+#[derive(Clone)]
+pub struct KeyRing {
+    keys: Vec<(String, Vec<u8>)>,
+}
+
+impl KeyRing {
+    /// Parse `"kid2:secret2,kid1:secret1"` (the SERVICE_AUTH_KEYS format)
+    pub fn parse(value: &str) -> Result<Self, KeyError> {
+        let mut keys: Vec<(String, Vec<u8>)> = Vec::new();
+
+        let entries = value
+            .split(',')
+            .map(str::trim)
+            .enumerate()
+            .filter(|(_, e)| !e.is_empty());
+
+        for (index, entry) in entries {
+            let (kid, secret) = entry
+                .split_once(':')
+                .ok_or(KeyError::Malformed(index + 1))?;
+            let kid = kid.trim();
+            let secret = secret.trim();
+
+            if kid.is_empty()
+                || !kid
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(KeyError::InvalidKid(kid.to_string()));
+            }
+            if secret.len() < MIN_SECRET_LEN {
+                return Err(KeyError::TooShort(kid.to_string()));
+            }
+            if keys.iter().any(|(existing, _)| existing == kid) {
+                return Err(KeyError::Duplicate(kid.to_string()));
+            }
+
+            keys.push((kid.to_string(), secret.as_bytes().to_vec()));
+        }
+
+        if keys.is_empty() {
+            return Err(KeyError::Empty);
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Key used for signing new tokens
+    pub fn signing_key(&self) -> (&str, &[u8]) {
+        let (kid, secret) = &self.keys[0];
+        (kid, secret)
+    }
+
+    /// Secret for a key id, if the key is still in the ring
+    pub fn get(&self, kid: &str) -> Option<&[u8]> {
+        self.keys
+            .iter()
+            .find(|(id, _)| id == kid)
+            .map(|(_, secret)| secret.as_slice())
+    }
+
+    pub fn kids(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|(kid, _)| kid.as_str())
+    }
+}
+
+/// Never print secrets
+impl fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyRing")
+            .field("kids", &self.kids().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET_A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const SECRET_B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    #[test]
+    fn test_first_key_signs_and_all_verify() {
+        let ring = KeyRing::parse(&format!(" k2:{} , k1:{} ", SECRET_B, SECRET_A)).unwrap();
+
+        assert_eq!(ring.signing_key().0, "k2");
+        assert_eq!(ring.get("k1"), Some(SECRET_A.as_bytes()));
+        assert_eq!(ring.get("k3"), None);
+        assert!(!format!("{:?}", ring).contains(SECRET_A));
+    }
+
+    #[test]
+    fn test_rejects_bad_key_lists() {
+        assert_eq!(KeyRing::parse("").unwrap_err(), KeyError::Empty);
+        assert_eq!(
+            KeyRing::parse(&format!("k1:{},{}", SECRET_A, SECRET_B)).unwrap_err(),
+            KeyError::Malformed(2)
+        );
+        assert!(!KeyRing::parse(SECRET_B)
+            .unwrap_err()
+            .to_string()
+            .contains("bbbb"));
+        assert!(matches!(
+            KeyRing::parse(&format!("k 1:{}", SECRET_A)).unwrap_err(),
+            KeyError::InvalidKid(_)
+        ));
+        assert!(matches!(
+            KeyRing::parse("k1:short").unwrap_err(),
+            KeyError::TooShort(_)
+        ));
+        assert!(matches!(
+            KeyRing::parse(&format!("k1:{},k1:{}", SECRET_A, SECRET_B)).unwrap_err(),
+            KeyError::Duplicate(_)
+        ));
+    }
+}
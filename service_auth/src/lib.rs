@@ -0,0 +1,35 @@
+//! Service Auth
+//!
+//! Short-lived, HMAC-SHA256 signed tokens for calls between backend services.
+//! A token names the calling service (`iss`), the service it is meant for
+//! (`aud`) and expires after a minute or so, so a leaked token is of little use.
+//!
+//! Keys are identified by a `kid` carried in the token. A [`KeyRing`] signs with
+//! its first key and verifies with all of them, which allows rotation:
+//!
+//! 1. Add the new key at the end of every service's ring and deploy.
+//! 2. Move it to the front (callers start signing with it) and deploy.
+//! 3. Once the old tokens have expired, remove the old key.
+//!
+//! Verification failures carry a short reason label and are counted by
+//! [`RejectionMetrics`].
+
+mod keys;
+mod metrics;
+mod token;
+
+pub use keys::{KeyError, KeyRing};
+pub use metrics::RejectionMetrics;
+pub use token::{ServiceClaims, Signer, Verifier, VerifyError};
+
+/// Header carrying the service token on internal HTTP calls
+pub const HEADER: &str = "X-Service-Token";
+
+/// Default token lifetime in seconds
+pub const DEFAULT_TTL_SECONDS: i64 = 60;
+
+/// Tolerated clock difference between services, in seconds
+pub const DEFAULT_LEEWAY_SECONDS: i64 = 30;
+
+/// Longest lifetime a verifier accepts, whatever the issuer asked for
+pub const MAX_TTL_SECONDS: i64 = 300;
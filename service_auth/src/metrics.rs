@@ -0,0 +1,82 @@
+//! Rejection counters
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::VerifyError;
+
+/// Per-reason count of rejected service tokens
+#[derive(Debug, Default)]
+pub struct RejectionMetrics {
+    counts: [AtomicU64; VerifyError::ALL.len()],
+}
+
+impl RejectionMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn index(error: VerifyError) -> usize {
+        VerifyError::ALL
+            .iter()
+            .position(|e| *e == error)
+            .unwrap_or_default()
+    }
+
+    pub fn record(&self, error: VerifyError) {
+        self.counts[Self::index(error)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self, error: VerifyError) -> u64 {
+        self.counts[Self::index(error)].load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|c| c.load(Ordering::Relaxed)).sum()
+    }
+
+    /// `(reason, count)` for every reason, including zeros
+    pub fn snapshot(&self) -> Vec<(&'static str, u64)> {
+        VerifyError::ALL
+            .iter()
+            .map(|error| (error.reason(), self.count(*error)))
+            .collect()
+    }
+
+    /// Prometheus text exposition of `service_auth_rejections_total`
+    pub fn render_prometheus(&self, service: &str) -> String {
+        let mut out = String::from(
+            "# HELP service_auth_rejections_total Rejected service tokens by reason\n\
+             # TYPE service_auth_rejections_total counter\n",
+        );
+        for (reason, count) in self.snapshot() {
+            out.push_str(&format!(
+                "service_auth_rejections_total{{service=\"{}\",reason=\"{}\"}} {}\n",
+                service, reason, count
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_per_reason() {
+        let metrics = RejectionMetrics::new();
+        metrics.record(VerifyError::Expired);
+        metrics.record(VerifyError::Expired);
+        metrics.record(VerifyError::BadSignature);
+
+        assert_eq!(metrics.count(VerifyError::Expired), 2);
+        assert_eq!(metrics.count(VerifyError::Missing), 0);
+        assert_eq!(metrics.total(), 3);
+
+        let text = metrics.render_prometheus("checkout");
+        assert!(text.contains(
+            "service_auth_rejections_total{service=\"checkout\",reason=\"expired\"} 2\n"
+        ));
+        assert!(text.contains("reason=\"unknown_key\"} 0\n"));
+    }
+}
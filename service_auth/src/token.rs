@@ -0,0 +1,330 @@
+//! Token signing and verification
+//!
+//! Wire format: `<kid>.<base64url(claims json)>.<base64url(hmac)>`, the HMAC
+//! covering `<kid>.<claims>` so the key id cannot be swapped.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{KeyRing, DEFAULT_LEEWAY_SECONDS, DEFAULT_TTL_SECONDS, MAX_TTL_SECONDS};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceClaims {
+    /// Calling service
+    pub iss: String,
+    /// Service the token is meant for
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error, PartialEq, Eq)]
+pub enum VerifyError {
+    #[error("service token missing")]
+    Missing,
+    #[error("service token malformed")]
+    Malformed,
+    #[error("service token signed with an unknown key")]
+    UnknownKey,
+    #[error("service token signature invalid")]
+    BadSignature,
+    #[error("service token expired")]
+    Expired,
+    #[error("service token issued in the future")]
+    NotYetValid,
+    #[error("service token lifetime too long")]
+    LifetimeTooLong,
+    #[error("service token meant for another service")]
+    WrongAudience,
+    #[error("service token issuer not allowed")]
+    UnknownIssuer,
+}
+
+impl VerifyError {
+    pub const ALL: [VerifyError; 9] = [
+        Self::Missing,
+        Self::Malformed,
+        Self::UnknownKey,
+        Self::BadSignature,
+        Self::Expired,
+        Self::NotYetValid,
+        Self::LifetimeTooLong,
+        Self::WrongAudience,
+        Self::UnknownIssuer,
+    ];
+
+    /// Short label used in logs and metrics
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::Malformed => "malformed",
+            Self::UnknownKey => "unknown_key",
+            Self::BadSignature => "bad_signature",
+            Self::Expired => "expired",
+            Self::NotYetValid => "not_yet_valid",
+            Self::LifetimeTooLong => "lifetime_too_long",
+            Self::WrongAudience => "wrong_audience",
+            Self::UnknownIssuer => "unknown_issuer",
+        }
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn mac(secret: &[u8], signed: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(signed.as_bytes());
+    mac
+}
+
+/// Issues tokens on behalf of one service
+#[derive(Debug, Clone)]
+pub struct Signer {
+    keys: KeyRing,
+    issuer: String,
+    ttl_seconds: i64,
+}
+
+impl Signer {
+    pub fn new(keys: KeyRing, issuer: impl Into<String>) -> Self {
+        Self {
+            keys,
+            issuer: issuer.into(),
+            ttl_seconds: DEFAULT_TTL_SECONDS,
+        }
+    }
+
+    /// Token lifetime, capped at MAX_TTL_SECONDS
+    pub fn with_ttl(mut self, ttl_seconds: i64) -> Self {
+        self.ttl_seconds = ttl_seconds.clamp(1, MAX_TTL_SECONDS);
+        self
+    }
+
+    /// Token for a call to `audience`
+    pub fn sign(&self, audience: &str) -> String {
+        self.sign_at(audience, now())
+    }
+
+    pub fn sign_at(&self, audience: &str, now: i64) -> String {
+        let claims = ServiceClaims {
+            iss: self.issuer.clone(),
+            aud: audience.to_string(),
+            iat: now,
+            exp: now + self.ttl_seconds,
+        };
+        let payload = serde_json::to_vec(&claims).expect("claims serialize");
+
+        let (kid, secret) = self.keys.signing_key();
+        let signed = format!("{}.{}", kid, URL_SAFE_NO_PAD.encode(payload));
+        let signature = mac(secret, &signed).finalize().into_bytes();
+
+        format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature))
+    }
+}
+
+/// Checks tokens presented to one service
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    keys: KeyRing,
+    audience: String,
+    allowed_issuers: Vec<String>,
+    leeway_seconds: i64,
+}
+
+impl Verifier {
+    pub fn new(keys: KeyRing, audience: impl Into<String>) -> Self {
+        Self {
+            keys,
+            audience: audience.into(),
+            allowed_issuers: Vec::new(),
+            leeway_seconds: DEFAULT_LEEWAY_SECONDS,
+        }
+    }
+
+    /// Only accept tokens from these services (default: any key holder)
+    pub fn with_allowed_issuers(mut self, issuers: Vec<String>) -> Self {
+        self.allowed_issuers = issuers;
+        self
+    }
+
+    pub fn with_leeway(mut self, leeway_seconds: i64) -> Self {
+        self.leeway_seconds = leeway_seconds.max(0);
+        self
+    }
+
+    pub fn verify(&self, token: &str) -> Result<ServiceClaims, VerifyError> {
+        self.verify_at(token, now())
+    }
+
+    pub fn verify_at(&self, token: &str, now: i64) -> Result<ServiceClaims, VerifyError> {
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(VerifyError::Missing);
+        }
+
+        let (signed, signature) = token.rsplit_once('.').ok_or(VerifyError::Malformed)?;
+        let (kid, payload) = signed.split_once('.').ok_or(VerifyError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| VerifyError::Malformed)?;
+
+        let secret = self.keys.get(kid).ok_or(VerifyError::UnknownKey)?;
+        mac(secret, signed)
+            .verify_slice(&signature)
+            .map_err(|_| VerifyError::BadSignature)?;
+
+        let claims: ServiceClaims = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or(VerifyError::Malformed)?;
+
+        if claims.exp + self.leeway_seconds <= now {
+            return Err(VerifyError::Expired);
+        }
+        if claims.iat - self.leeway_seconds > now {
+            return Err(VerifyError::NotYetValid);
+        }
+        if claims.exp - claims.iat > MAX_TTL_SECONDS {
+            return Err(VerifyError::LifetimeTooLong);
+        }
+        if claims.aud != self.audience {
+            return Err(VerifyError::WrongAudience);
+        }
+        if !self.allowed_issuers.is_empty() && !self.allowed_issuers.contains(&claims.iss) {
+            return Err(VerifyError::UnknownIssuer);
+        }
+
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_800_000_000;
+
+    fn ring(value: &str) -> KeyRing {
+        KeyRing::parse(value).unwrap()
+    }
+
+    fn old_ring() -> KeyRing {
+        ring("k1:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+    }
+
+    fn rotated_ring() -> KeyRing {
+        ring("k2:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb,k1:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let token = Signer::new(old_ring(), "blazing_sun").sign_at("checkout", NOW);
+        let claims = Verifier::new(old_ring(), "checkout")
+            .verify_at(&token, NOW + 5)
+            .unwrap();
+
+        assert_eq!(claims.iss, "blazing_sun");
+        assert_eq!(claims.aud, "checkout");
+        assert_eq!(claims.exp, NOW + DEFAULT_TTL_SECONDS);
+    }
+
+    #[test]
+    fn test_rotation_accepts_old_and_new_keys() {
+        let verifier = Verifier::new(rotated_ring(), "checkout");
+
+        let old = Signer::new(old_ring(), "blazing_sun").sign_at("checkout", NOW);
+        let new = Signer::new(rotated_ring(), "blazing_sun").sign_at("checkout", NOW);
+        assert!(new.starts_with("k2."));
+        assert!(verifier.verify_at(&old, NOW).is_ok());
+        assert!(verifier.verify_at(&new, NOW).is_ok());
+
+        // A service that has not picked up the new key yet
+        assert_eq!(
+            Verifier::new(old_ring(), "checkout").verify_at(&new, NOW),
+            Err(VerifyError::UnknownKey)
+        );
+    }
+
+    #[test]
+    fn test_rejects_expired_and_future_tokens() {
+        let signer = Signer::new(old_ring(), "blazing_sun");
+        let verifier = Verifier::new(old_ring(), "checkout").with_leeway(10);
+        let token = signer.sign_at("checkout", NOW);
+
+        assert!(verifier
+            .verify_at(&token, NOW + DEFAULT_TTL_SECONDS + 9)
+            .is_ok());
+        assert_eq!(
+            verifier.verify_at(&token, NOW + DEFAULT_TTL_SECONDS + 10),
+            Err(VerifyError::Expired)
+        );
+        assert_eq!(
+            verifier.verify_at(&token, NOW - 11),
+            Err(VerifyError::NotYetValid)
+        );
+    }
+
+    #[test]
+    fn test_rejects_tampering_and_wrong_audience() {
+        let verifier = Verifier::new(old_ring(), "checkout");
+        let token = Signer::new(old_ring(), "blazing_sun").sign_at("checkout", NOW);
+
+        let (kid_payload, _) = token.rsplit_once('.').unwrap();
+        let forged_claims = ServiceClaims {
+            iss: "blazing_sun".to_string(),
+            aud: "checkout".to_string(),
+            iat: NOW,
+            exp: NOW + 3600,
+        };
+        let forged = format!(
+            "k1.{}.{}",
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged_claims).unwrap()),
+            token.rsplit_once('.').unwrap().1
+        );
+        assert_eq!(
+            verifier.verify_at(&forged, NOW),
+            Err(VerifyError::BadSignature)
+        );
+        assert_eq!(
+            verifier.verify_at(kid_payload, NOW),
+            Err(VerifyError::Malformed)
+        );
+        assert_eq!(verifier.verify_at("", NOW), Err(VerifyError::Missing));
+        assert_eq!(
+            verifier.verify_at("checkout_service_token_change_me", NOW),
+            Err(VerifyError::Malformed)
+        );
+
+        let other = Signer::new(old_ring(), "blazing_sun").sign_at("money_flow", NOW);
+        assert_eq!(
+            verifier.verify_at(&other, NOW),
+            Err(VerifyError::WrongAudience)
+        );
+    }
+
+    #[test]
+    fn test_issuer_allow_list() {
+        let verifier = Verifier::new(old_ring(), "checkout")
+            .with_allowed_issuers(vec!["blazing_sun".to_string()]);
+
+        let allowed = Signer::new(old_ring(), "blazing_sun").sign_at("checkout", NOW);
+        let other = Signer::new(old_ring(), "ws_gateway").sign_at("checkout", NOW);
+        assert!(verifier.verify_at(&allowed, NOW).is_ok());
+        assert_eq!(
+            verifier.verify_at(&other, NOW),
+            Err(VerifyError::UnknownIssuer)
+        );
+    }
+}
@@ -21,7 +21,8 @@ echo "Redis is ready!"
 
 if [ "$BUILD_ENV" = "dev" ]; then
     echo "Starting in DEVELOPMENT mode with hot reload..."
-    exec cargo watch --poll -x "run --bin ws_gateway"
+    # CARGO_FEATURES=chaos turns on fault injection for integration tests
+    exec cargo watch --poll -x "run --bin ws_gateway ${CARGO_FEATURES:+--features $CARGO_FEATURES}"
 else
     echo "Starting in PRODUCTION mode..."
     cargo build --release --bin ws_gateway
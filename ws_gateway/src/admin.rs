@@ -0,0 +1,457 @@
+//! Operator endpoints on the health port
+//!
+//! Callers send a blazing_sun JWT whose role may manage the system (admins) as
+//! `Authorization: Bearer <token>`:
+//! GET /admin/connections: Open connections with user, rooms, age and message rates
+//! GET /admin/rooms: Member count of every room
+//! DELETE /admin/connections/{connection_id}: Close one connection
+//! DELETE /admin/users/{user_id}/connections: Close every connection of a user
+//! POST /admin/announcements: `{"message": "...", "level": "info"}` to every client
+//!
+//! Each gateway instance reports and acts on its own connections only.
+
+use chrono::Utc;
+use rbac::Permission;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tracing::{info, warn};
+
+use crate::auth::{AuthenticatedUser, SharedJwtValidator};
+use crate::connection::SharedConnectionManager;
+use crate::protocol::ServerMessage;
+
+/// Requests larger than this are refused
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// Longest announcement text
+const MAX_ANNOUNCEMENT_CHARS: usize = 500;
+
+/// What a disconnected client is told before its connection closes
+const DISCONNECT_NOTICE: &str = "You were disconnected by an operator";
+
+/// A request read off the health port
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Status code and JSON body of a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl HttpResponse {
+    pub fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "status": "error", "message": message }),
+        }
+    }
+
+    /// The response as written to the socket
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            _ => "Error",
+        };
+        let body = self.body.to_string();
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+}
+
+/// Read one request: the head, then as much body as `Content-Length` announces.
+/// `None` when the peer sent nothing parseable.
+pub async fn read_request<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> std::io::Result<Option<HttpRequest>> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let Some((mut request, content_length)) = parse_head(&head) else {
+        return Ok(None);
+    };
+    if content_length > MAX_REQUEST_BYTES {
+        return Ok(None);
+    }
+
+    let mut body = buf[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+    request.body = body;
+
+    Ok(Some(request))
+}
+
+/// Request line and the headers the admin endpoints use
+fn parse_head(head: &str) -> Option<(HttpRequest, usize)> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut authorization = None;
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().ok()?;
+        }
+    }
+
+    Some((
+        HttpRequest {
+            method,
+            path,
+            authorization,
+            body: Vec::new(),
+        },
+        content_length,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct AnnouncementRequest {
+    message: String,
+    #[serde(default = "default_level")]
+    level: String,
+}
+
+fn default_level() -> String {
+    "info".to_string()
+}
+
+/// Operator view of this gateway's connections
+pub struct Admin {
+    connections: SharedConnectionManager,
+    validator: SharedJwtValidator,
+}
+
+impl Admin {
+    pub fn new(connections: SharedConnectionManager, validator: SharedJwtValidator) -> Self {
+        Self {
+            connections,
+            validator,
+        }
+    }
+
+    /// Whether a path belongs to the admin endpoints
+    pub fn handles(path: &str) -> bool {
+        path == "/admin" || path.starts_with("/admin/")
+    }
+
+    pub fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        let operator = match self.authorize(request) {
+            Ok(operator) => operator,
+            Err(response) => return response,
+        };
+
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["admin", "connections"]) => {
+                let connections = self.connections.snapshot_connections();
+                HttpResponse::ok(json!({
+                    "status": "success",
+                    "message": "Connections retrieved",
+                    "count": connections.len(),
+                    "connections": connections,
+                }))
+            }
+            ("GET", ["admin", "rooms"]) => {
+                let rooms: Vec<Value> = self
+                    .connections
+                    .room_member_counts()
+                    .into_iter()
+                    .map(|(room_id, members)| json!({ "room_id": room_id, "members": members }))
+                    .collect();
+                HttpResponse::ok(json!({
+                    "status": "success",
+                    "message": "Rooms retrieved",
+                    "rooms": rooms,
+                }))
+            }
+            ("DELETE", ["admin", "connections", connection_id]) => {
+                if !self
+                    .connections
+                    .disconnect(connection_id, disconnect_notice())
+                {
+                    return HttpResponse::error(404, "Connection not found");
+                }
+                info!(
+                    operator = %operator.user_id,
+                    connection_id = %connection_id,
+                    "Operator closed a connection"
+                );
+                HttpResponse::ok(json!({ "status": "success", "message": "Connection closed" }))
+            }
+            ("DELETE", ["admin", "users", user_id, "connections"]) => {
+                let closed = self
+                    .connections
+                    .disconnect_user(user_id, disconnect_notice());
+                info!(
+                    operator = %operator.user_id,
+                    user_id = %user_id,
+                    closed,
+                    "Operator closed a user's connections"
+                );
+                HttpResponse::ok(json!({
+                    "status": "success",
+                    "message": "Connections closed",
+                    "closed": closed,
+                }))
+            }
+            ("POST", ["admin", "announcements"]) => self.announce(&operator, &request.body),
+            _ => HttpResponse::error(404, "Not found"),
+        }
+    }
+
+    /// The caller, if its token may manage the system
+    fn authorize(&self, request: &HttpRequest) -> Result<AuthenticatedUser, HttpResponse> {
+        let token = request
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| HttpResponse::error(401, "Unauthorized"))?;
+
+        let user = self
+            .validator
+            .validate(token)
+            .map_err(|_| HttpResponse::error(401, "Unauthorized"))?;
+        if !user.role().can(Permission::ManageSystem) {
+            warn!(user_id = %user.user_id, "Admin endpoint refused: role may not manage the system");
+            return Err(HttpResponse::error(403, "Forbidden"));
+        }
+
+        Ok(user)
+    }
+
+    fn announce(&self, operator: &AuthenticatedUser, body: &[u8]) -> HttpResponse {
+        let Ok(request) = serde_json::from_slice::<AnnouncementRequest>(body) else {
+            return HttpResponse::error(400, "Body must be {\"message\": ..., \"level\": ...}");
+        };
+        let message = request.message.trim();
+        if message.is_empty() || message.chars().count() > MAX_ANNOUNCEMENT_CHARS {
+            return HttpResponse::error(400, "Message must be 1 to 500 characters");
+        }
+        if !matches!(request.level.as_str(), "info" | "warning") {
+            return HttpResponse::error(400, "Level must be info or warning");
+        }
+
+        let delivered = self.connections.broadcast(ServerMessage::Announcement {
+            message: message.to_string(),
+            level: request.level.clone(),
+            timestamp: Utc::now(),
+        });
+        info!(
+            operator = %operator.user_id,
+            level = %request.level,
+            delivered,
+            "Operator announcement broadcast"
+        );
+
+        HttpResponse::ok(json!({
+            "status": "success",
+            "message": "Announcement sent",
+            "delivered": delivered,
+        }))
+    }
+}
+
+fn disconnect_notice() -> ServerMessage {
+    ServerMessage::Error {
+        code: "DISCONNECTED_BY_OPERATOR".to_string(),
+        message: DISCONNECT_NOTICE.to_string(),
+        retry_after_ms: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::JwtValidator;
+    use crate::connection::{ConnectionManager, OutboundMetrics, OutboundQueue};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const SECRET: &str = "admin-test-secret";
+
+    fn token(permissions: i32) -> String {
+        let claims = json!({ "sub": "7", "permissions": permissions, "exp": 9999999999u64 });
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(SECRET.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn admin() -> (Admin, SharedConnectionManager) {
+        let connections = Arc::new(ConnectionManager::new());
+        let validator = Arc::new(JwtValidator::from_secret(SECRET).unwrap());
+        (Admin::new(connections.clone(), validator), connections)
+    }
+
+    fn request(method: &str, path: &str, permissions: Option<i32>, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            authorization: permissions.map(|level| format!("Bearer {}", token(level))),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn open(connections: &ConnectionManager, id: &str, user_id: &str) -> Arc<OutboundQueue> {
+        let queue = Arc::new(OutboundQueue::new(
+            id.to_string(),
+            8,
+            Duration::from_secs(5),
+            Arc::new(OutboundMetrics::default()),
+        ));
+        connections.register(id, Some(user_id), queue.clone());
+        queue
+    }
+
+    #[tokio::test]
+    async fn test_read_request_parses_head_and_body() {
+        let raw = b"POST /admin/announcements?x=1 HTTP/1.1\r\nHost: gw\r\nauthorization: Bearer abc\r\nContent-Length: 5\r\n\r\nhello";
+        let request = read_request(&mut &raw[..]).await.unwrap().unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/admin/announcements");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer abc"));
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[test]
+    fn test_only_admins_are_let_in() {
+        let (admin, _) = admin();
+
+        assert_eq!(
+            admin
+                .handle(&request("GET", "/admin/rooms", None, ""))
+                .status,
+            401
+        );
+        assert_eq!(
+            admin
+                .handle(&request("GET", "/admin/rooms", Some(5), ""))
+                .status,
+            403
+        );
+        assert_eq!(
+            admin
+                .handle(&request("GET", "/admin/rooms", Some(10), ""))
+                .status,
+            200
+        );
+    }
+
+    #[test]
+    fn test_lists_connections_and_rooms() {
+        let (admin, connections) = admin();
+        open(&connections, "c-1", "1");
+        open(&connections, "c-2", "2");
+        connections.join_room("c-1", "room-a");
+        connections.join_room("c-2", "room-a");
+
+        let listed = admin.handle(&request("GET", "/admin/connections", Some(10), ""));
+        assert_eq!(listed.body["count"], 2);
+        assert_eq!(listed.body["connections"][0]["rooms"][0], "room-a");
+
+        let rooms = admin.handle(&request("GET", "/admin/rooms", Some(10), ""));
+        assert_eq!(rooms.body["rooms"][0]["members"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_delivers_the_notice_then_closes() {
+        let (admin, connections) = admin();
+        let queue = open(&connections, "c-1", "1");
+
+        let missing = admin.handle(&request("DELETE", "/admin/connections/nope", Some(10), ""));
+        assert_eq!(missing.status, 404);
+
+        let closed = admin.handle(&request(
+            "DELETE",
+            "/admin/users/1/connections",
+            Some(10),
+            "",
+        ));
+        assert_eq!(closed.body["closed"], 1);
+        assert!(matches!(
+            queue.next().await,
+            Some(ServerMessage::Error { .. })
+        ));
+        assert!(queue.next().await.is_none());
+    }
+
+    #[test]
+    fn test_announcements_reach_every_connection() {
+        let (admin, connections) = admin();
+        open(&connections, "c-1", "1");
+        open(&connections, "c-2", "2");
+
+        let sent = admin.handle(&request(
+            "POST",
+            "/admin/announcements",
+            Some(10),
+            r#"{"message": "Restart in 5 minutes", "level": "warning"}"#,
+        ));
+        assert_eq!(sent.body["delivered"], 2);
+
+        let invalid = admin.handle(&request(
+            "POST",
+            "/admin/announcements",
+            Some(10),
+            r#"{"message": "hi", "level": "shout"}"#,
+        ));
+        assert_eq!(invalid.status, 400);
+    }
+}
@@ -3,7 +3,8 @@
 //! Validates JWT tokens from blazing_sun application.
 
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-use serde::{Deserialize, Serialize};
+use rbac::Role;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fs;
 use std::sync::Arc;
 use tracing::{debug, error};
@@ -24,8 +25,12 @@ pub struct Claims {
     /// User roles
     #[serde(default)]
     pub roles: Vec<String>,
-    /// Permission level (1=basic, 10=admin, 50=affiliate, 100=super admin)
+    /// RBAC role (blazing_sun tokens carry a single `role`)
     #[serde(default)]
+    pub role: Option<String>,
+    /// Permission level (1=basic, 5=moderator, 10=admin, 50=affiliate, 100=super admin);
+    /// blazing_sun names the claim `permissions`
+    #[serde(default, alias = "permissions")]
     pub permission_level: Option<i32>,
     /// Expiration time (Unix timestamp)
     pub exp: usize,
@@ -35,6 +40,27 @@ pub struct Claims {
     /// Issuer
     #[serde(default)]
     pub iss: Option<String>,
+    /// Preferred message locale (profile setting)
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Admin acting as `sub`; only set on blazing_sun impersonation tokens
+    #[serde(default, deserialize_with = "optional_id")]
+    pub impersonator_id: Option<String>,
+    /// Impersonation session the token belongs to
+    #[serde(default)]
+    pub impersonation_id: Option<String>,
+    /// Sign-in session (device) the token belongs to; revocable in blazing_sun
+    #[serde(default)]
+    pub sid: Option<String>,
+}
+
+/// A user id that blazing_sun writes as a number; strings are accepted too
+fn optional_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+        Some(serde_json::Value::String(id)) => Some(id),
+        _ => None,
+    })
 }
 
 /// Authenticated user information
@@ -45,16 +71,38 @@ pub struct AuthenticatedUser {
     pub email: Option<String>,
     pub roles: Vec<String>,
     pub permission_level: i32,
+    pub locale: Option<String>,
+    /// Admin acting as the user on an impersonated connection
+    pub impersonator_id: Option<String>,
+    /// Sign-in session of the token; its revocation closes the connection
+    pub session_id: Option<String>,
+}
+
+impl AuthenticatedUser {
+    /// RBAC role, derived from the permission level
+    pub fn role(&self) -> Role {
+        Role::from_level(self.permission_level)
+    }
 }
 
 impl From<Claims> for AuthenticatedUser {
     fn from(claims: Claims) -> Self {
+        let mut roles = claims.roles;
+        if let Some(role) = claims.role {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+
         Self {
             user_id: claims.sub,
             username: claims.username.unwrap_or_else(|| "unknown".to_string()),
             email: claims.email,
-            roles: claims.roles,
+            roles,
             permission_level: claims.permission_level.unwrap_or(1),
+            locale: claims.locale,
+            impersonator_id: claims.impersonator_id,
+            session_id: claims.sid,
         }
     }
 }
@@ -163,4 +211,57 @@ mod tests {
         assert_eq!(claims.username, Some("testuser".to_string()));
         assert_eq!(claims.roles, vec!["user"]);
     }
+
+    #[test]
+    fn test_blazing_sun_role_claims() {
+        let json = r#"{
+            "sub": "42",
+            "role": "moderator",
+            "permissions": 5,
+            "exp": 9999999999
+        }"#;
+
+        let claims: Claims = serde_json::from_str(json).unwrap();
+        let user = AuthenticatedUser::from(claims);
+        assert_eq!(user.permission_level, 5);
+        assert_eq!(user.roles, vec!["moderator"]);
+        assert_eq!(user.role(), Role::Moderator);
+        assert_eq!(user.impersonator_id, None);
+    }
+
+    #[test]
+    fn test_impersonation_claims_flag_the_user() {
+        let json = r#"{
+            "sub": "42",
+            "permissions": 1,
+            "exp": 9999999999,
+            "impersonator_id": 7,
+            "impersonation_id": "5f0c6b8e-8a43-4f7e-9d6a-0c2b1f3e4a5d"
+        }"#;
+
+        let claims: Claims = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            claims.impersonation_id.as_deref(),
+            Some("5f0c6b8e-8a43-4f7e-9d6a-0c2b1f3e4a5d")
+        );
+        let user = AuthenticatedUser::from(claims);
+        assert_eq!(user.impersonator_id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn test_session_claim_names_the_device() {
+        let json = r#"{
+            "sub": "42",
+            "permissions": 1,
+            "exp": 9999999999,
+            "sid": "9b2f4c1e-3d5a-4e6b-8c7d-1a2b3c4d5e6f"
+        }"#;
+
+        let claims: Claims = serde_json::from_str(json).unwrap();
+        let user = AuthenticatedUser::from(claims);
+        assert_eq!(
+            user.session_id.as_deref(),
+            Some("9b2f4c1e-3d5a-4e6b-8c7d-1a2b3c4d5e6f")
+        );
+    }
 }
@@ -0,0 +1,120 @@
+//! Per-connection activity counters
+//!
+//! Kept by the connection manager for every open connection so operators can
+//! see who is connected and how chatty each connection is (see `admin.rs`).
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// What a connection has done since it opened
+#[derive(Debug)]
+pub struct ConnectionActivity {
+    connected_at: DateTime<Utc>,
+    user_id: Mutex<Option<String>>,
+    impersonator_id: Mutex<Option<String>>,
+    received: AtomicU64,
+    sent: AtomicU64,
+}
+
+impl ConnectionActivity {
+    pub fn new(user_id: Option<&str>) -> Self {
+        Self {
+            connected_at: Utc::now(),
+            user_id: Mutex::new(user_id.map(String::from)),
+            impersonator_id: Mutex::new(None),
+            received: AtomicU64::new(0),
+            sent: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_user(&self, user_id: &str, impersonator_id: Option<&str>) {
+        *self.user_id.lock().unwrap() = Some(user_id.to_string());
+        *self.impersonator_id.lock().unwrap() = impersonator_id.map(String::from);
+    }
+
+    /// A message arrived from the client
+    pub fn record_received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A message was written to the client
+    pub fn record_sent(&self) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The connection as reported to operators
+    pub fn snapshot(
+        &self,
+        connection_id: &str,
+        rooms: Vec<String>,
+        now: DateTime<Utc>,
+    ) -> ConnectionSnapshot {
+        let age_secs = (now - self.connected_at).num_seconds().max(0);
+        let received = self.received.load(Ordering::Relaxed);
+        let sent = self.sent.load(Ordering::Relaxed);
+        // Rates over the connection's lifetime; a connection younger than a
+        // minute is rated on its first minute
+        let minutes = (age_secs as f64 / 60.0).max(1.0);
+
+        ConnectionSnapshot {
+            connection_id: connection_id.to_string(),
+            user_id: self.user_id.lock().unwrap().clone(),
+            impersonator_id: self.impersonator_id.lock().unwrap().clone(),
+            rooms,
+            connected_at: self.connected_at,
+            age_secs,
+            messages_received: received,
+            messages_sent: sent,
+            received_per_minute: received as f64 / minutes,
+            sent_per_minute: sent as f64 / minutes,
+        }
+    }
+}
+
+/// A connection as listed by the admin endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSnapshot {
+    pub connection_id: String,
+    /// None until the connection authenticates
+    pub user_id: Option<String>,
+    /// Admin acting as the user on an impersonated connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<String>,
+    pub rooms: Vec<String>,
+    pub connected_at: DateTime<Utc>,
+    pub age_secs: i64,
+    pub messages_received: u64,
+    pub messages_sent: u64,
+    pub received_per_minute: f64,
+    pub sent_per_minute: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_are_per_minute_of_connection_age() {
+        let activity = ConnectionActivity::new(None);
+        for _ in 0..30 {
+            activity.record_received();
+        }
+        activity.record_sent();
+        activity.set_user("42", None);
+
+        let snapshot = activity.snapshot(
+            "c-1",
+            vec!["room-1".to_string()],
+            activity.connected_at + chrono::Duration::minutes(3),
+        );
+        assert_eq!(snapshot.user_id.as_deref(), Some("42"));
+        assert_eq!(snapshot.age_secs, 180);
+        assert_eq!(snapshot.received_per_minute, 10.0);
+
+        // Young connections are rated on their first minute
+        let fresh = activity.snapshot("c-1", Vec::new(), activity.connected_at);
+        assert_eq!(fresh.sent_per_minute, 1.0);
+    }
+}
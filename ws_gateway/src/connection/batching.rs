@@ -0,0 +1,177 @@
+//! Outbound batching
+//!
+//! Rapid dice rolls, turn changes and lobby churn produce bursts of small
+//! updates. The writer coalesces consecutive updates of the same category
+//! into one `system.batch` frame: after the first update of a burst it waits
+//! up to the category's window (`WS_BATCH_WINDOW_*_MS`) for more, stopping
+//! early at `WS_BATCH_MAX_MESSAGES` or as soon as a message of another kind is
+//! next in the queue, so ordering is kept.
+//!
+//! Only connections on a protocol version that knows `system.batch` are
+//! batched, and direct responses to the client's own commands are never held
+//! back (see `OutboundQueue::respond`).
+
+use std::time::Duration;
+
+use crate::protocol::ServerMessage;
+
+/// First protocol version that understands `system.batch`
+pub const BATCH_PROTOCOL_VERSION: u32 = 3;
+
+/// Kind of high-frequency update a message is batched with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchCategory {
+    /// Room states, turn changes, rolls and moves
+    GameState,
+    /// Online/offline and typing indicators
+    Presence,
+    /// Room lists, lobbies, selections, spectators and prediction pools
+    Lobby,
+}
+
+impl BatchCategory {
+    /// Category of a message; `None` for messages that are always sent alone
+    pub fn of(message: &ServerMessage) -> Option<Self> {
+        match message {
+            ServerMessage::GameRoomState { .. }
+            | ServerMessage::TicTacToeRoomState { .. }
+            | ServerMessage::BiggerDiceRoomState { .. }
+            | ServerMessage::GameRoomStateDiff { .. }
+            | ServerMessage::GameTurnChanged { .. }
+            | ServerMessage::TicTacToeTurnChanged { .. }
+            | ServerMessage::BiggerDiceTurnChanged { .. }
+            | ServerMessage::BiggerDiceState { .. }
+            | ServerMessage::BiggerDiceStateSync { .. }
+            | ServerMessage::BiggerDiceRolled { .. }
+            | ServerMessage::TicTacToeState { .. }
+            | ServerMessage::TicTacToeMoved { .. } => Some(BatchCategory::GameState),
+            ServerMessage::UserOnline { .. }
+            | ServerMessage::UserOffline { .. }
+            | ServerMessage::ChatTyping { .. } => Some(BatchCategory::Presence),
+            ServerMessage::GameRoomsUpdated { .. }
+            | ServerMessage::GameRoomOccupancy { .. }
+            | ServerMessage::GamePredictionPoolUpdated { .. }
+            | ServerMessage::GameSelectedPlayersUpdated { .. }
+            | ServerMessage::TicTacToeSelectedPlayersUpdated { .. }
+            | ServerMessage::BiggerDiceSelectedPlayersUpdated { .. }
+            | ServerMessage::GameSpectatorsUpdated { .. }
+            | ServerMessage::GameLobbyUpdated { .. }
+            | ServerMessage::TicTacToeLobbyUpdated { .. }
+            | ServerMessage::BiggerDiceLobbyUpdated { .. } => Some(BatchCategory::Lobby),
+            _ => None,
+        }
+    }
+}
+
+/// How long the writer collects each category before sending a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchPolicy {
+    pub game_state_window: Duration,
+    pub presence_window: Duration,
+    pub lobby_window: Duration,
+    /// Largest number of messages in one batch
+    pub max_messages: usize,
+}
+
+impl BatchPolicy {
+    /// Policy that sends every message on its own
+    pub fn disabled() -> Self {
+        Self {
+            game_state_window: Duration::ZERO,
+            presence_window: Duration::ZERO,
+            lobby_window: Duration::ZERO,
+            max_messages: 1,
+        }
+    }
+
+    /// Collection window for a category; zero when the category isn't batched
+    pub fn window(&self, category: BatchCategory) -> Duration {
+        if self.max_messages < 2 {
+            return Duration::ZERO;
+        }
+        match category {
+            BatchCategory::GameState => self.game_state_window,
+            BatchCategory::Presence => self.presence_window,
+            BatchCategory::Lobby => self.lobby_window,
+        }
+    }
+
+    /// Frame a batch for sending: a single message goes out as itself
+    pub fn frame(mut messages: Vec<ServerMessage>) -> Option<ServerMessage> {
+        match messages.len() {
+            0 => None,
+            1 => messages.pop(),
+            _ => Some(ServerMessage::Batch { messages }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn_changed() -> ServerMessage {
+        ServerMessage::BiggerDiceTurnChanged {
+            room_id: "r1".to_string(),
+            current_turn: "42".to_string(),
+            turn_number: 3,
+            state_checksum: None,
+        }
+    }
+
+    fn online() -> ServerMessage {
+        ServerMessage::UserOnline {
+            user_id: "1".to_string(),
+            username: "alice".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_messages_are_grouped_by_category() {
+        assert_eq!(
+            BatchCategory::of(&turn_changed()),
+            Some(BatchCategory::GameState)
+        );
+        assert_eq!(BatchCategory::of(&online()), Some(BatchCategory::Presence));
+
+        let error = ServerMessage::Error {
+            code: "E".to_string(),
+            message: "boom".to_string(),
+            retry_after_ms: None,
+        };
+        assert_eq!(BatchCategory::of(&error), None);
+    }
+
+    #[test]
+    fn test_windows_are_per_category_and_need_room_for_two_messages() {
+        let policy = BatchPolicy {
+            game_state_window: Duration::from_millis(30),
+            presence_window: Duration::ZERO,
+            ..BatchPolicy::disabled()
+        };
+        assert_eq!(policy.window(BatchCategory::GameState), Duration::ZERO);
+
+        let policy = BatchPolicy {
+            max_messages: 10,
+            ..policy
+        };
+        assert_eq!(
+            policy.window(BatchCategory::GameState),
+            Duration::from_millis(30)
+        );
+        assert_eq!(policy.window(BatchCategory::Presence), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_single_messages_are_not_wrapped() {
+        assert!(BatchPolicy::frame(Vec::new()).is_none());
+        assert!(matches!(
+            BatchPolicy::frame(vec![online()]),
+            Some(ServerMessage::UserOnline { .. })
+        ));
+        match BatchPolicy::frame(vec![turn_changed(), turn_changed()]) {
+            Some(ServerMessage::Batch { messages }) => assert_eq!(messages.len(), 2),
+            other => panic!("unexpected frame: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Connection manager throughput benchmarks
+//!
+//! Ignored by default; run them in release mode with
+//! `cargo test --release -- --ignored --nocapture connection::benches`.
+//! Each workload runs on every CPU, once with a single shard (one lock per
+//! map, like an unsharded manager) and once with the default shard count.
+
+use super::shards::default_shard_count;
+use super::{ConnectionManager, OutboundQueue};
+use crate::protocol::ServerMessage;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Connections each thread opens
+const CONNECTIONS_PER_THREAD: usize = 5_000;
+
+/// Rooms the connections are spread over
+const ROOMS: usize = 500;
+
+fn threads() -> usize {
+    std::thread::available_parallelism().map_or(4, |n| n.get())
+}
+
+fn message() -> ServerMessage {
+    ServerMessage::UserOnline {
+        user_id: "1".to_string(),
+        username: "alice".to_string(),
+    }
+}
+
+fn queue(manager: &ConnectionManager, id: &str) -> Arc<OutboundQueue> {
+    Arc::new(OutboundQueue::new(
+        id,
+        4,
+        Duration::from_secs(60),
+        manager.outbound_metrics(),
+    ))
+}
+
+/// Run `work(manager, thread)` on every CPU and print operations per second
+fn run(
+    name: &str,
+    shards: usize,
+    setup: impl Fn(&ConnectionManager),
+    work: impl Fn(&ConnectionManager, usize) -> usize + Sync,
+) {
+    let manager = ConnectionManager::with_shards(shards);
+    setup(&manager);
+
+    let started = Instant::now();
+    let operations: usize = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads())
+            .map(|thread| {
+                let (manager, work) = (&manager, &work);
+                scope.spawn(move || work(manager, thread))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    let elapsed = started.elapsed();
+
+    println!(
+        "{:<28} shards={:<4} threads={:<3} ops={:<9} {:>12.0} ops/s",
+        name,
+        shards,
+        threads(),
+        operations,
+        operations as f64 / elapsed.as_secs_f64()
+    );
+}
+
+/// Connect, authenticate, join a room and leave, like a client session
+fn churn(manager: &ConnectionManager, thread: usize) -> usize {
+    for i in 0..CONNECTIONS_PER_THREAD {
+        let id = format!("c-{}-{}", thread, i);
+        let user_id = format!("{}", i % 1_000);
+        let room_id = format!("room-{}", i % ROOMS);
+        manager.register(&id, None, queue(manager, &id));
+        manager.set_user(&id, &user_id, None);
+        manager.join_room(&id, &room_id);
+        manager.send_to_connection(&id, message());
+        manager.unregister(&id, Some(&user_id));
+    }
+    CONNECTIONS_PER_THREAD * 5
+}
+
+/// Open connections spread over users and rooms, to send to
+fn populate(manager: &ConnectionManager) {
+    for i in 0..CONNECTIONS_PER_THREAD * threads() {
+        let id = format!("c-{}", i);
+        let user_id = format!("{}", i % 10_000);
+        manager.register(&id, Some(&user_id), queue(manager, &id));
+        manager.join_room(&id, &format!("room-{}", i % ROOMS));
+    }
+}
+
+/// Fan out to users and rooms while others connect and disconnect
+fn mixed(manager: &ConnectionManager, thread: usize) -> usize {
+    let mut operations = 0;
+    for i in 0..CONNECTIONS_PER_THREAD {
+        manager.send_to_user(&format!("{}", (thread * 7_919 + i) % 10_000), message());
+        manager.send_to_room(&format!("room-{}", (thread + i) % ROOMS), message());
+        operations += 2;
+        if i % 10 == 0 {
+            let id = format!("m-{}-{}", thread, i);
+            manager.register(&id, Some("bench"), queue(manager, &id));
+            manager.join_room(&id, "room-0");
+            manager.unregister(&id, Some("bench"));
+            operations += 3;
+        }
+    }
+    operations
+}
+
+#[test]
+#[ignore = "benchmark; run with --release --ignored --nocapture"]
+fn bench_connection_churn() {
+    for shards in [1, default_shard_count()] {
+        run("connect/join/send/leave", shards, |_| {}, churn);
+    }
+}
+
+#[test]
+#[ignore = "benchmark; run with --release --ignored --nocapture"]
+fn bench_fan_out_under_churn() {
+    for shards in [1, default_shard_count()] {
+        run("send to users/rooms + churn", shards, populate, mixed);
+    }
+}
@@ -0,0 +1,148 @@
+//! Per-command rate limits for game commands
+//!
+//! The connection's token bucket caps how many messages a client sends in
+//! total; these limits cap how often a user may send one particular game
+//! command (a roll, a ready toggle, a chat line). They are configured with
+//! `WS_GAME_COMMAND_LIMITS` as `command=count/secs` pairs, where `command` is
+//! the command type without the `games.command.` prefix:
+//!
+//! ```text
+//! bigger_dice.roll=1/1,ready=2/1,send_chat=5/1
+//! ```
+//!
+//! Counts are kept per user and command in Redis fixed windows
+//! (`cmd:limit:{user_id}:{command}:{window}`), so all of a user's connections
+//! share one budget. A command over its limit is refused with a `RATE_LIMIT`
+//! error carrying `retry_after_ms`; it is not a flood strike.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix stripped from a command type before looking up its limit
+const COMMAND_PREFIX: &str = "games.command.";
+
+/// Limits applied when `WS_GAME_COMMAND_LIMITS` is not set
+pub const DEFAULT_COMMAND_LIMITS: &str = "bigger_dice.roll=1/1,bigger_dice.auto_roll=1/1,\
+ready=2/1,set_ready=2/1,player_chat=5/1,spectator_chat=5/1,send_chat=5/1";
+
+/// At most `max` commands per `window_ms`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandLimit {
+    pub max: u32,
+    pub window_ms: u64,
+}
+
+/// Where `now_ms` falls in a limit's windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitWindow {
+    /// Index of the current window, part of its Redis key
+    pub index: u64,
+    /// Time left until the next window opens
+    pub remaining_ms: u64,
+}
+
+impl CommandLimit {
+    pub fn window(&self, now_ms: u64) -> LimitWindow {
+        LimitWindow {
+            index: now_ms / self.window_ms,
+            remaining_ms: self.window_ms - now_ms % self.window_ms,
+        }
+    }
+}
+
+/// Configured limits by command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandLimits {
+    limits: HashMap<String, CommandLimit>,
+}
+
+impl CommandLimits {
+    /// Name a command type is limited (and counted in Redis) under
+    pub fn command_name(command_type: &str) -> &str {
+        command_type.strip_prefix(COMMAND_PREFIX).unwrap_or(command_type)
+    }
+
+    /// Limit of a command type, if it has one
+    pub fn for_command(&self, command_type: &str) -> Option<CommandLimit> {
+        self.limits.get(Self::command_name(command_type)).copied()
+    }
+}
+
+/// A malformed `WS_GAME_COMMAND_LIMITS` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCommandLimit(String);
+
+impl fmt::Display for InvalidCommandLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid command limit '{}', expected command=count/secs", self.0)
+    }
+}
+
+impl std::error::Error for InvalidCommandLimit {}
+
+impl FromStr for CommandLimits {
+    type Err = InvalidCommandLimit;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut limits = HashMap::new();
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let invalid = || InvalidCommandLimit(entry.to_string());
+            let (command, limit) = entry.split_once('=').ok_or_else(invalid)?;
+            let (max, secs) = limit.split_once('/').ok_or_else(invalid)?;
+            let max: u32 = max.trim().parse().map_err(|_| invalid())?;
+            let secs: u64 = secs.trim().parse().map_err(|_| invalid())?;
+            let command = CommandLimits::command_name(command.trim());
+            if command.is_empty() || max == 0 || secs == 0 {
+                return Err(invalid());
+            }
+            limits.insert(
+                command.to_string(),
+                CommandLimit {
+                    max,
+                    window_ms: secs * 1000,
+                },
+            );
+        }
+        Ok(Self { limits })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_are_looked_up_without_the_command_prefix() {
+        let limits: CommandLimits = "bigger_dice.roll=1/1, games.command.send_chat=5/2".parse().unwrap();
+
+        assert_eq!(
+            limits.for_command("games.command.bigger_dice.roll"),
+            Some(CommandLimit { max: 1, window_ms: 1000 })
+        );
+        assert_eq!(
+            limits.for_command("games.command.send_chat"),
+            Some(CommandLimit { max: 5, window_ms: 2000 })
+        );
+        assert_eq!(limits.for_command("games.command.list_rooms"), None);
+    }
+
+    #[test]
+    fn test_malformed_limits_are_rejected() {
+        assert!("roll".parse::<CommandLimits>().is_err());
+        assert!("roll=1".parse::<CommandLimits>().is_err());
+        assert!("roll=0/1".parse::<CommandLimits>().is_err());
+        assert!("roll=1/0".parse::<CommandLimits>().is_err());
+        assert!("=1/1".parse::<CommandLimits>().is_err());
+        assert_eq!("".parse::<CommandLimits>(), Ok(CommandLimits::default()));
+        assert!(DEFAULT_COMMAND_LIMITS.parse::<CommandLimits>().is_ok());
+    }
+
+    #[test]
+    fn test_window_reports_time_until_the_next_one() {
+        let limit = CommandLimit { max: 1, window_ms: 1000 };
+
+        assert_eq!(limit.window(12_250), LimitWindow { index: 12, remaining_ms: 750 });
+        assert_eq!(limit.window(13_000), LimitWindow { index: 13, remaining_ms: 1000 });
+    }
+}
@@ -0,0 +1,221 @@
+//! Flood protection
+//!
+//! The connection's token bucket decides whether a message is over the rate
+//! limit; this module decides what happens to the sender. Each violation is a
+//! strike, counted per user in Redis (`flood:penalty:{user_id}`) so that
+//! reconnecting or opening more tabs does not start over. Anonymous
+//! connections count their own strikes.
+//!
+//! 1. The first strike is a warning.
+//! 2. Later strikes mute the user for a cooldown that doubles with every
+//!    strike, from `WS_FLOOD_MUTE_SECS` up to `WS_FLOOD_MAX_MUTE_SECS`.
+//! 3. At `WS_FLOOD_DISCONNECT_AFTER` strikes the connection is closed and the
+//!    user stays muted for the longest cooldown.
+//!
+//! Every penalty is announced with `system.rate_limited`. Messages sent while
+//! muted are dropped without adding strikes, and violations within a second
+//! of the previous strike belong to the same burst. Strikes expire
+//! `WS_FLOOD_STRIKE_WINDOW_SECS` after the latest one; admins can inspect and
+//! clear penalties through blazing_sun.
+
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+/// Violations this close to the previous strike count as the same burst
+const STRIKE_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// How often a muted connection re-reads its mute from Redis, so a mute an
+/// admin cleared is lifted
+const MUTE_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What a strike costs the sender
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Penalty {
+    Warning,
+    Mute { cooldown_secs: u64 },
+    Disconnect { cooldown_secs: u64 },
+}
+
+impl Penalty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Penalty::Warning => "warning",
+            Penalty::Mute { .. } => "mute",
+            Penalty::Disconnect { .. } => "disconnect",
+        }
+    }
+
+    /// How long the sender is muted for, if at all
+    pub fn cooldown_secs(&self) -> Option<u64> {
+        match self {
+            Penalty::Warning => None,
+            Penalty::Mute { cooldown_secs } | Penalty::Disconnect { cooldown_secs } => Some(*cooldown_secs),
+        }
+    }
+
+    /// User-facing explanation, translated by the connection's writer
+    pub fn message(&self) -> &'static str {
+        match self {
+            Penalty::Warning => "You are sending messages too quickly. Slow down or you will be muted",
+            Penalty::Mute { .. } => "You are muted for sending messages too quickly",
+            Penalty::Disconnect { .. } => "Disconnected for repeatedly sending messages too quickly",
+        }
+    }
+}
+
+/// Escalation settings
+#[derive(Debug, Clone, Copy)]
+pub struct FloodPolicy {
+    pub mute_secs: u64,
+    pub max_mute_secs: u64,
+    pub disconnect_after: u32,
+    pub strike_window_secs: u64,
+}
+
+impl FloodPolicy {
+    /// Penalty for a sender's `strikes`-th strike
+    pub fn penalty_for(&self, strikes: u32) -> Penalty {
+        if strikes >= self.disconnect_after.max(2) {
+            return Penalty::Disconnect {
+                cooldown_secs: self.max_mute_secs,
+            };
+        }
+        if strikes <= 1 {
+            return Penalty::Warning;
+        }
+
+        let doublings = (strikes - 2).min(63);
+        let cooldown_secs = self
+            .mute_secs
+            .saturating_mul(1 << doublings)
+            .min(self.max_mute_secs);
+        Penalty::Mute { cooldown_secs }
+    }
+
+    /// How long a user's penalty record is kept: long enough to outlive any mute
+    pub fn record_ttl_secs(&self) -> u64 {
+        self.strike_window_secs.max(self.max_mute_secs)
+    }
+}
+
+/// Flood state of one connection
+#[derive(Debug, Default)]
+pub struct FloodGuard {
+    muted_until: Option<DateTime<Utc>>,
+    last_strike: Option<Instant>,
+    last_refresh: Option<Instant>,
+    /// Strikes of an anonymous connection (users count theirs in Redis)
+    local_strikes: u32,
+}
+
+impl FloodGuard {
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        self.muted_until.is_some_and(|until| until > now)
+    }
+
+    pub fn muted_until(&self) -> Option<DateTime<Utc>> {
+        self.muted_until
+    }
+
+    /// Whether a violation is a new strike rather than part of the last burst
+    pub fn begin_strike(&mut self, now: Instant) -> bool {
+        if self
+            .last_strike
+            .is_some_and(|last| now.duration_since(last) < STRIKE_DEBOUNCE)
+        {
+            return false;
+        }
+        self.last_strike = Some(now);
+        true
+    }
+
+    /// Count a strike locally; returns the connection's strike count
+    pub fn local_strike(&mut self) -> u32 {
+        self.local_strikes += 1;
+        self.local_strikes
+    }
+
+    pub fn mute(&mut self, until: DateTime<Utc>) {
+        self.muted_until = Some(until);
+    }
+
+    /// Whether a muted connection should re-read its mute from Redis
+    pub fn refresh_due(&mut self, now: Instant) -> bool {
+        if self
+            .last_refresh
+            .is_some_and(|last| now.duration_since(last) < MUTE_REFRESH_INTERVAL)
+        {
+            return false;
+        }
+        self.last_refresh = Some(now);
+        true
+    }
+
+    /// Replace the local mute with the one stored for the user
+    pub fn sync_mute(&mut self, until: Option<DateTime<Utc>>) {
+        self.muted_until = until;
+        if until.is_none() {
+            self.last_strike = None;
+            self.local_strikes = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> FloodPolicy {
+        FloodPolicy {
+            mute_secs: 10,
+            max_mute_secs: 60,
+            disconnect_after: 5,
+            strike_window_secs: 600,
+        }
+    }
+
+    #[test]
+    fn test_penalties_escalate_from_warning_to_disconnect() {
+        let policy = policy();
+        assert_eq!(policy.penalty_for(1), Penalty::Warning);
+        assert_eq!(policy.penalty_for(2), Penalty::Mute { cooldown_secs: 10 });
+        assert_eq!(policy.penalty_for(3), Penalty::Mute { cooldown_secs: 20 });
+        assert_eq!(policy.penalty_for(4), Penalty::Mute { cooldown_secs: 40 });
+        assert_eq!(policy.penalty_for(5), Penalty::Disconnect { cooldown_secs: 60 });
+        assert_eq!(policy.penalty_for(40), Penalty::Disconnect { cooldown_secs: 60 });
+    }
+
+    #[test]
+    fn test_mute_cooldown_is_capped() {
+        let policy = FloodPolicy {
+            disconnect_after: 100,
+            ..policy()
+        };
+        assert_eq!(policy.penalty_for(4), Penalty::Mute { cooldown_secs: 40 });
+        assert_eq!(policy.penalty_for(5), Penalty::Mute { cooldown_secs: 60 });
+        assert_eq!(policy.penalty_for(99), Penalty::Mute { cooldown_secs: 60 });
+    }
+
+    #[test]
+    fn test_violations_in_one_burst_are_one_strike() {
+        let mut guard = FloodGuard::default();
+        let start = Instant::now();
+
+        assert!(guard.begin_strike(start));
+        assert!(!guard.begin_strike(start + Duration::from_millis(300)));
+        assert!(guard.begin_strike(start + Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_cleared_mute_resets_the_connection() {
+        let mut guard = FloodGuard::default();
+        let now = Utc::now();
+        guard.local_strike();
+        guard.mute(now + chrono::Duration::seconds(30));
+        assert!(guard.is_muted(now));
+
+        guard.sync_mute(None);
+        assert!(!guard.is_muted(now));
+        assert_eq!(guard.local_strike(), 1);
+    }
+}
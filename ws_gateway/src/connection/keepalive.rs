@@ -0,0 +1,172 @@
+//! Native WebSocket keepalive
+//!
+//! Browsers answer protocol-level pings on their own, so the gateway can find
+//! dead sockets without relying on the client's `system.heartbeat`. Every frame
+//! received from the client counts as a sign of life. Once a connection has
+//! been quiet for the ping interval the writer task sends a `Ping`; if nothing
+//! arrives within the pong timeout after that, the connection is evicted and
+//! goes through the normal disconnect cleanup.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// What the writer task should do on a keepalive tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepaliveAction {
+    /// Connection is active (or a ping is still within its timeout)
+    Wait,
+    /// Connection went quiet: send a ping
+    Ping,
+    /// Ping went unanswered: drop the connection
+    Evict,
+}
+
+/// Decide the next step from how long the client has been quiet and how long
+/// ago the outstanding ping (if any) was sent
+pub fn decide(
+    idle: Duration,
+    ping_outstanding: Option<Duration>,
+    ping_interval: Duration,
+    pong_timeout: Duration,
+) -> KeepaliveAction {
+    match ping_outstanding {
+        Some(waited) if waited >= pong_timeout => KeepaliveAction::Evict,
+        Some(_) => KeepaliveAction::Wait,
+        None if idle >= ping_interval => KeepaliveAction::Ping,
+        None => KeepaliveAction::Wait,
+    }
+}
+
+/// Keepalive counters shared by all connections
+#[derive(Debug, Default)]
+pub struct KeepaliveMetrics {
+    /// Pings sent to quiet connections
+    pings_sent: AtomicU64,
+    /// Connections closed because a ping went unanswered
+    idle_evictions: AtomicU64,
+}
+
+impl KeepaliveMetrics {
+    pub fn pings_sent(&self) -> u64 {
+        self.pings_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn idle_evictions(&self) -> u64 {
+        self.idle_evictions.load(Ordering::Relaxed)
+    }
+}
+
+struct KeepaliveState {
+    last_seen: Instant,
+    ping_sent_at: Option<Instant>,
+}
+
+/// Liveness tracking for one connection, shared by its reader and writer
+pub struct Keepalive {
+    connection_id: String,
+    state: Mutex<KeepaliveState>,
+    ping_interval: Duration,
+    pong_timeout: Duration,
+    metrics: Arc<KeepaliveMetrics>,
+}
+
+impl Keepalive {
+    pub fn new(
+        connection_id: impl Into<String>,
+        ping_interval: Duration,
+        pong_timeout: Duration,
+        metrics: Arc<KeepaliveMetrics>,
+    ) -> Self {
+        Self {
+            connection_id: connection_id.into(),
+            state: Mutex::new(KeepaliveState {
+                last_seen: Instant::now(),
+                ping_sent_at: None,
+            }),
+            ping_interval: ping_interval.max(Duration::from_secs(1)),
+            pong_timeout,
+            metrics,
+        }
+    }
+
+    /// How often the writer should call `tick`
+    pub fn tick_interval(&self) -> Duration {
+        (self.ping_interval / 2).max(Duration::from_secs(1))
+    }
+
+    /// A frame (text, pong, ping, ...) arrived from the client
+    pub fn seen(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.last_seen = Instant::now();
+        state.ping_sent_at = None;
+    }
+
+    /// Next keepalive step; records pings and evictions
+    pub fn tick(&self) -> KeepaliveAction {
+        let mut state = self.state.lock().unwrap();
+        let action = decide(
+            state.last_seen.elapsed(),
+            state.ping_sent_at.map(|sent| sent.elapsed()),
+            self.ping_interval,
+            self.pong_timeout,
+        );
+
+        match action {
+            KeepaliveAction::Ping => {
+                state.ping_sent_at = Some(Instant::now());
+                self.metrics.pings_sent.fetch_add(1, Ordering::Relaxed);
+            }
+            KeepaliveAction::Evict => {
+                self.metrics.idle_evictions.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Evicting connection {}: no pong within {}s",
+                    self.connection_id,
+                    self.pong_timeout.as_secs()
+                );
+            }
+            KeepaliveAction::Wait => {}
+        }
+
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(15);
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_quiet_connections_are_pinged() {
+        assert_eq!(decide(secs(5), None, INTERVAL, TIMEOUT), KeepaliveAction::Wait);
+        assert_eq!(decide(secs(15), None, INTERVAL, TIMEOUT), KeepaliveAction::Ping);
+    }
+
+    #[test]
+    fn test_unanswered_pings_evict_after_the_timeout() {
+        assert_eq!(decide(secs(20), Some(secs(5)), INTERVAL, TIMEOUT), KeepaliveAction::Wait);
+        assert_eq!(decide(secs(25), Some(secs(10)), INTERVAL, TIMEOUT), KeepaliveAction::Evict);
+    }
+
+    #[test]
+    fn test_activity_clears_the_outstanding_ping() {
+        let metrics = Arc::new(KeepaliveMetrics::default());
+        let keepalive = Keepalive::new("conn", INTERVAL, Duration::ZERO, metrics.clone());
+
+        keepalive.state.lock().unwrap().ping_sent_at = Some(Instant::now());
+        keepalive.seen();
+        assert_eq!(keepalive.tick(), KeepaliveAction::Wait);
+
+        keepalive.state.lock().unwrap().ping_sent_at = Some(Instant::now());
+        assert_eq!(keepalive.tick(), KeepaliveAction::Evict);
+        assert_eq!(metrics.idle_evictions(), 1);
+    }
+}
@@ -1,99 +1,167 @@
 //! Connection manager for WebSocket Gateway
 //!
 //! Manages all active WebSocket connections and provides lookup functionality.
+//! State is split into shards (see `shards`) so tens of thousands of sockets
+//! registering, joining rooms and receiving messages do not queue on one lock.
 
-use dashmap::DashMap;
+use chrono::Utc;
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tracing::{debug, info};
 
 use crate::protocol::ServerMessage;
 
-use super::Connection;
+use super::shards::{default_shard_count, ShardedIndex, ShardedMap};
+use super::{
+    Connection, ConnectionActivity, ConnectionSnapshot, KeepaliveMetrics, OutboundMetrics,
+    OutboundQueue,
+};
+
+/// An open connection, kept in the shard of its ID
+struct ConnectionEntry {
+    queue: Arc<OutboundQueue>,
+    /// What it has done (for operators)
+    activity: Arc<ConnectionActivity>,
+    user_id: Option<String>,
+    /// The sign-in session its token belongs to
+    session_id: Option<String>,
+    rooms: HashSet<String>,
+}
 
 /// Manages all active WebSocket connections
+///
+/// Connections are sharded by connection ID, and the user and room indexes
+/// by user and room ID. A connection's shard may stay locked while its user
+/// or room index shard is updated, never the other way round: sends copy the
+/// connection IDs out of an index before looking the connections up.
 pub struct ConnectionManager {
-    /// Map of connection ID to connection sender
-    connections: DashMap<String, tokio::sync::mpsc::UnboundedSender<ServerMessage>>,
+    /// Open connections by connection ID
+    connections: ShardedMap<ConnectionEntry>,
 
-    /// Map of user ID to set of connection IDs
-    user_connections: DashMap<String, HashSet<String>>,
+    /// Connection IDs by user ID
+    user_connections: ShardedIndex,
 
-    /// Map of room ID to set of connection IDs
-    room_connections: DashMap<String, HashSet<String>>,
+    /// Connection IDs by room ID
+    room_connections: ShardedIndex,
 
     /// Total connection count
     connection_count: AtomicUsize,
+
+    /// Backpressure counters shared by every outbound queue
+    outbound_metrics: Arc<OutboundMetrics>,
+
+    /// Ping/eviction counters shared by every connection's keepalive
+    keepalive_metrics: Arc<KeepaliveMetrics>,
 }
 
 impl ConnectionManager {
-    /// Create a new connection manager
+    /// Create a new connection manager with four shards per CPU
     pub fn new() -> Self {
+        Self::with_shards(default_shard_count())
+    }
+
+    /// Create a connection manager with `shards` shards per map (rounded up
+    /// to a power of two)
+    pub fn with_shards(shards: usize) -> Self {
         Self {
-            connections: DashMap::new(),
-            user_connections: DashMap::new(),
-            room_connections: DashMap::new(),
+            connections: ShardedMap::new(shards),
+            user_connections: ShardedIndex::new(shards),
+            room_connections: ShardedIndex::new(shards),
             connection_count: AtomicUsize::new(0),
+            outbound_metrics: Arc::new(OutboundMetrics::default()),
+            keepalive_metrics: Arc::new(KeepaliveMetrics::default()),
         }
     }
 
-    /// Register a new connection
+    /// Register a new connection; the returned counters are the ones the
+    /// admin endpoint reports for it
     pub fn register(
         &self,
         connection_id: &str,
         user_id: Option<&str>,
-        tx: tokio::sync::mpsc::UnboundedSender<ServerMessage>,
-    ) {
-        // Store connection sender
-        self.connections.insert(connection_id.to_string(), tx);
-        self.connection_count.fetch_add(1, Ordering::Relaxed);
+        tx: Arc<OutboundQueue>,
+    ) -> Arc<ConnectionActivity> {
+        let id: Arc<str> = Arc::from(connection_id);
+        let activity = Arc::new(ConnectionActivity::new(user_id));
+        let entry = ConnectionEntry {
+            queue: tx,
+            activity: activity.clone(),
+            user_id: user_id.map(String::from),
+            session_id: None,
+            rooms: HashSet::new(),
+        };
+
+        let mut shard = self.connections.write(connection_id);
+        if shard.insert(id.clone(), entry).is_none() {
+            self.connection_count.fetch_add(1, Ordering::Relaxed);
+        }
 
         // Map user to connection if authenticated
         if let Some(uid) = user_id {
-            self.user_connections
-                .entry(uid.to_string())
-                .or_insert_with(HashSet::new)
-                .insert(connection_id.to_string());
+            self.user_connections.insert(uid, &id);
         }
+        drop(shard);
 
         debug!(
             "Registered connection {}, total: {}",
             connection_id,
             self.connection_count.load(Ordering::Relaxed)
         );
+
+        activity
     }
 
     /// Update user mapping for a connection (after authentication)
-    pub fn set_user(&self, connection_id: &str, user_id: &str) {
+    pub fn set_user(&self, connection_id: &str, user_id: &str, impersonator_id: Option<&str>) {
+        let mut shard = self.connections.write(connection_id);
+        let Some(entry) = shard.get_mut(connection_id) else {
+            return;
+        };
+        entry.activity.set_user(user_id, impersonator_id);
+        if let Some(previous) = entry.user_id.replace(user_id.to_string()) {
+            if previous != user_id {
+                self.user_connections.remove(&previous, connection_id);
+            }
+        }
         self.user_connections
-            .entry(user_id.to_string())
-            .or_insert_with(HashSet::new)
-            .insert(connection_id.to_string());
+            .insert(user_id, &Arc::from(connection_id));
+        drop(shard);
 
         debug!("Mapped connection {} to user {}", connection_id, user_id);
     }
 
+    /// Remember the sign-in session a connection authenticated with
+    pub fn set_session(&self, connection_id: &str, session_id: &str) {
+        if let Some(entry) = self.connections.write(connection_id).get_mut(connection_id) {
+            entry.session_id = Some(session_id.to_string());
+        }
+    }
+
     /// Unregister a connection
     pub fn unregister(&self, connection_id: &str, user_id: Option<&str>) {
-        // Remove connection sender
-        self.connections.remove(connection_id);
-        self.connection_count.fetch_sub(1, Ordering::Relaxed);
+        let removed = self.connections.write(connection_id).remove(connection_id);
 
         // Remove from user mapping
         if let Some(uid) = user_id {
-            if let Some(mut entry) = self.user_connections.get_mut(uid) {
-                entry.remove(connection_id);
-                if entry.is_empty() {
-                    drop(entry);
-                    self.user_connections.remove(uid);
-                }
-            }
+            self.user_connections.remove(uid, connection_id);
+        }
+
+        let Some(entry) = removed else {
+            return;
+        };
+        // Close connection queue
+        entry.queue.close();
+        self.connection_count.fetch_sub(1, Ordering::Relaxed);
+
+        if let Some(uid) = entry.user_id.as_deref().filter(|uid| Some(*uid) != user_id) {
+            self.user_connections.remove(uid, connection_id);
         }
 
-        // Remove from all rooms
-        self.room_connections.iter_mut().for_each(|mut entry| {
-            entry.remove(connection_id);
-        });
+        // Remove from the rooms it joined
+        for room_id in &entry.rooms {
+            self.room_connections.remove(room_id, connection_id);
+        }
 
         debug!(
             "Unregistered connection {}, total: {}",
@@ -102,62 +170,57 @@ impl ConnectionManager {
         );
     }
 
-    /// Add connection to a room
+    /// Add connection to a room (ignored once it is unregistered)
     pub fn join_room(&self, connection_id: &str, room_id: &str) {
-        self.room_connections
-            .entry(room_id.to_string())
-            .or_insert_with(HashSet::new)
-            .insert(connection_id.to_string());
+        let mut shard = self.connections.write(connection_id);
+        let Some(entry) = shard.get_mut(connection_id) else {
+            return;
+        };
+        if entry.rooms.insert(room_id.to_string()) {
+            self.room_connections
+                .insert(room_id, &Arc::from(connection_id));
+        }
+        drop(shard);
 
         debug!("Connection {} joined room {}", connection_id, room_id);
     }
 
     /// Remove connection from a room
     pub fn leave_room(&self, connection_id: &str, room_id: &str) {
-        if let Some(mut entry) = self.room_connections.get_mut(room_id) {
-            entry.remove(connection_id);
-            if entry.is_empty() {
-                drop(entry);
-                self.room_connections.remove(room_id);
-            }
+        let mut shard = self.connections.write(connection_id);
+        if let Some(entry) = shard.get_mut(connection_id) {
+            entry.rooms.remove(room_id);
         }
+        self.room_connections.remove(room_id, connection_id);
+        drop(shard);
 
         debug!("Connection {} left room {}", connection_id, room_id);
     }
 
     /// Send message to a specific connection
     pub fn send_to_connection(&self, connection_id: &str, message: ServerMessage) -> bool {
-        if let Some(tx) = self.connections.get(connection_id) {
-            tx.send(message).is_ok()
-        } else {
-            false
-        }
+        self.connections
+            .read(connection_id)
+            .get(connection_id)
+            .is_some_and(|entry| entry.queue.push(message).is_queued())
+    }
+
+    /// Send message to each of some connections
+    fn send_to_all(&self, connection_ids: &[Arc<str>], message: ServerMessage) -> usize {
+        connection_ids
+            .iter()
+            .filter(|conn_id| self.send_to_connection(conn_id, message.clone()))
+            .count()
     }
 
     /// Send message to all connections of a user
     pub fn send_to_user(&self, user_id: &str, message: ServerMessage) -> usize {
-        let mut sent = 0;
-        if let Some(connections) = self.user_connections.get(user_id) {
-            for conn_id in connections.iter() {
-                if self.send_to_connection(conn_id, message.clone()) {
-                    sent += 1;
-                }
-            }
-        }
-        sent
+        self.send_to_all(&self.user_connections.members(user_id), message)
     }
 
     /// Send message to all connections in a room
     pub fn send_to_room(&self, room_id: &str, message: ServerMessage) -> usize {
-        let mut sent = 0;
-        if let Some(connections) = self.room_connections.get(room_id) {
-            for conn_id in connections.iter() {
-                if self.send_to_connection(conn_id, message.clone()) {
-                    sent += 1;
-                }
-            }
-        }
-        sent
+        self.send_to_all(&self.room_connections.members(room_id), message)
     }
 
     /// Send message to all connections in a room except one
@@ -167,25 +230,67 @@ impl ConnectionManager {
         message: ServerMessage,
         except_connection: &str,
     ) -> usize {
+        let mut connections = self.room_connections.members(room_id);
+        connections.retain(|conn_id| &**conn_id != except_connection);
+        self.send_to_all(&connections, message)
+    }
+
+    /// Broadcast message to all connections
+    pub fn broadcast(&self, message: ServerMessage) -> usize {
         let mut sent = 0;
-        if let Some(connections) = self.room_connections.get(room_id) {
-            for conn_id in connections.iter() {
-                if conn_id != except_connection {
-                    if self.send_to_connection(conn_id, message.clone()) {
-                        sent += 1;
-                    }
+        for shard in self.connections.shards() {
+            for entry in shard.values() {
+                if entry.queue.push(message.clone()).is_queued() {
+                    sent += 1;
                 }
             }
         }
         sent
     }
 
-    /// Broadcast message to all connections
-    pub fn broadcast(&self, message: ServerMessage) -> usize {
+    /// Send message to all connections in a room except those of some users
+    pub fn send_to_room_except_users(
+        &self,
+        room_id: &str,
+        message: ServerMessage,
+        except_users: &HashSet<String>,
+    ) -> usize {
+        if except_users.is_empty() {
+            return self.send_to_room(room_id, message);
+        }
+
+        let excluded = self.user_connections.members_of(except_users);
+        let mut connections = self.room_connections.members(room_id);
+        connections.retain(|conn_id| !excluded.contains(conn_id));
+        self.send_to_all(&connections, message)
+    }
+
+    /// Send message to the connections in a room that belong to some users
+    pub fn send_to_room_users(
+        &self,
+        room_id: &str,
+        message: ServerMessage,
+        user_ids: &HashSet<String>,
+    ) -> usize {
+        let allowed = self.user_connections.members_of(user_ids);
+        let mut connections = self.room_connections.members(room_id);
+        connections.retain(|conn_id| allowed.contains(conn_id));
+        self.send_to_all(&connections, message)
+    }
+
+    /// Broadcast message to all connections except those of some users
+    pub fn broadcast_except_users(&self, message: ServerMessage, except_users: &HashSet<String>) -> usize {
+        if except_users.is_empty() {
+            return self.broadcast(message);
+        }
+
+        let excluded = self.user_connections.members_of(except_users);
         let mut sent = 0;
-        for entry in self.connections.iter() {
-            if entry.send(message.clone()).is_ok() {
-                sent += 1;
+        for shard in self.connections.shards() {
+            for (conn_id, entry) in shard.iter() {
+                if !excluded.contains(conn_id) && entry.queue.push(message.clone()).is_queued() {
+                    sent += 1;
+                }
             }
         }
         sent
@@ -194,17 +299,19 @@ impl ConnectionManager {
     /// Get all connection IDs for a user
     pub fn get_user_connections(&self, user_id: &str) -> Vec<String> {
         self.user_connections
-            .get(user_id)
-            .map(|entry| entry.iter().cloned().collect())
-            .unwrap_or_default()
+            .members(user_id)
+            .iter()
+            .map(|conn_id| conn_id.to_string())
+            .collect()
     }
 
     /// Get all connection IDs in a room
     pub fn get_room_connections(&self, room_id: &str) -> Vec<String> {
         self.room_connections
-            .get(room_id)
-            .map(|entry| entry.iter().cloned().collect())
-            .unwrap_or_default()
+            .members(room_id)
+            .iter()
+            .map(|conn_id| conn_id.to_string())
+            .collect()
     }
 
     /// Get total connection count
@@ -214,31 +321,106 @@ impl ConnectionManager {
 
     /// Get count of connections in a room
     pub fn room_connection_count(&self, room_id: &str) -> usize {
-        self.room_connections
-            .get(room_id)
-            .map(|entry| entry.len())
-            .unwrap_or(0)
+        self.room_connections.len_of(room_id)
     }
 
     /// Check if a connection exists
     pub fn has_connection(&self, connection_id: &str) -> bool {
-        self.connections.contains_key(connection_id)
+        self.connections
+            .read(connection_id)
+            .contains_key(connection_id)
     }
 
     /// Check if a user is connected
     pub fn is_user_connected(&self, user_id: &str) -> bool {
+        self.user_connections.contains(user_id)
+    }
+
+    /// Every open connection with its user, rooms and message counts, oldest first
+    pub fn snapshot_connections(&self) -> Vec<ConnectionSnapshot> {
+        let now = Utc::now();
+        let mut snapshots = Vec::with_capacity(self.connection_count());
+        for shard in self.connections.shards() {
+            for (conn_id, entry) in shard.iter() {
+                let mut rooms: Vec<String> = entry.rooms.iter().cloned().collect();
+                rooms.sort();
+                snapshots.push(entry.activity.snapshot(conn_id, rooms, now));
+            }
+        }
+        snapshots.sort_by_key(|snapshot| snapshot.connected_at);
+        snapshots
+    }
+
+    /// Member count of every room, largest first
+    pub fn room_member_counts(&self) -> Vec<(String, usize)> {
+        let mut counts = self.room_connections.counts();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// Close a connection after delivering `notice`; false if it is not open
+    pub fn disconnect(&self, connection_id: &str, notice: ServerMessage) -> bool {
+        let Some(queue) = self
+            .connections
+            .read(connection_id)
+            .get(connection_id)
+            .map(|entry| entry.queue.clone())
+        else {
+            return false;
+        };
+        info!("Disconnecting connection {}", connection_id);
+        queue.push(notice);
+        queue.close_when_drained();
+        true
+    }
+
+    /// Close every connection of a user; returns how many were open
+    pub fn disconnect_user(&self, user_id: &str, notice: ServerMessage) -> usize {
+        self.user_connections
+            .members(user_id)
+            .iter()
+            .filter(|conn_id| self.disconnect(conn_id, notice.clone()))
+            .count()
+    }
+
+    /// Close a user's connections authenticated with a revoked sign-in
+    /// session; returns how many were open
+    pub fn disconnect_session(&self, user_id: &str, session_id: &str, notice: ServerMessage) -> usize {
         self.user_connections
-            .get(user_id)
-            .map(|entry| !entry.is_empty())
-            .unwrap_or(false)
+            .members(user_id)
+            .iter()
+            .filter(|conn_id| {
+                let conn_id: &str = conn_id;
+                self.connections
+                    .read(conn_id)
+                    .get(conn_id)
+                    .is_some_and(|entry| entry.session_id.as_deref() == Some(session_id))
+            })
+            .filter(|conn_id| self.disconnect(conn_id, notice.clone()))
+            .count()
+    }
+
+    /// Counters to hand to new outbound queues
+    pub fn outbound_metrics(&self) -> Arc<OutboundMetrics> {
+        self.outbound_metrics.clone()
+    }
+
+    /// Counters to hand to new keepalives
+    pub fn keepalive_metrics(&self) -> Arc<KeepaliveMetrics> {
+        self.keepalive_metrics.clone()
     }
 
-    /// Get statistics
+    /// Get statistics (counters only, no shard is locked)
     pub fn stats(&self) -> ConnectionStats {
         ConnectionStats {
             total_connections: self.connection_count.load(Ordering::Relaxed),
-            unique_users: self.user_connections.len(),
-            active_rooms: self.room_connections.len(),
+            unique_users: self.user_connections.key_count(),
+            active_rooms: self.room_connections.key_count(),
+            slow_connections: self.outbound_metrics.slow_connections(),
+            dropped_messages: self.outbound_metrics.dropped_messages(),
+            stall_disconnects: self.outbound_metrics.stall_disconnects(),
+            pings_sent: self.keepalive_metrics.pings_sent(),
+            idle_evictions: self.keepalive_metrics.idle_evictions(),
         }
     }
 }
@@ -255,4 +437,85 @@ pub struct ConnectionStats {
     pub total_connections: usize,
     pub unique_users: usize,
     pub active_rooms: usize,
+    pub slow_connections: usize,
+    pub dropped_messages: u64,
+    pub stall_disconnects: u64,
+    pub pings_sent: u64,
+    pub idle_evictions: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn open(manager: &ConnectionManager, id: &str, user_id: Option<&str>) -> Arc<OutboundQueue> {
+        let queue = Arc::new(OutboundQueue::new(
+            id,
+            8,
+            Duration::from_secs(5),
+            manager.outbound_metrics(),
+        ));
+        manager.register(id, user_id, queue.clone());
+        queue
+    }
+
+    fn notice() -> ServerMessage {
+        ServerMessage::UserOnline {
+            user_id: "1".to_string(),
+            username: "alice".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_unregister_leaves_only_the_connections_rooms() {
+        let manager = ConnectionManager::with_shards(4);
+        let queue = open(&manager, "c-1", None);
+        open(&manager, "c-2", Some("7"));
+        manager.set_user("c-1", "42", None);
+        manager.join_room("c-1", "room-a");
+        manager.join_room("c-1", "room-b");
+        manager.join_room("c-2", "room-a");
+
+        let stats = manager.stats();
+        assert_eq!(
+            (stats.total_connections, stats.unique_users, stats.active_rooms),
+            (2, 2, 2)
+        );
+        assert_eq!(manager.send_to_room_except("room-a", notice(), "c-2"), 1);
+
+        manager.unregister("c-1", Some("42"));
+        assert!(!queue.push(notice()).is_queued());
+        assert!(!manager.is_user_connected("42"));
+        assert_eq!(
+            manager.room_member_counts(),
+            vec![("room-a".to_string(), 1)]
+        );
+
+        // Late joins of a closed connection are ignored
+        manager.join_room("c-1", "room-c");
+        assert_eq!(manager.stats().active_rooms, 1);
+        assert_eq!(manager.connection_count(), 1);
+    }
+
+    #[test]
+    fn test_user_filters_and_sessions_span_shards() {
+        let manager = ConnectionManager::with_shards(2);
+        for (id, user) in [("c-1", "1"), ("c-2", "1"), ("c-3", "2"), ("c-4", "3")] {
+            open(&manager, id, Some(user));
+            manager.join_room(id, "lobby");
+        }
+        manager.set_session("c-1", "s-1");
+        manager.set_session("c-2", "s-2");
+
+        let users: HashSet<String> = ["1".to_string()].into();
+        assert_eq!(manager.send_to_room_users("lobby", notice(), &users), 2);
+        assert_eq!(manager.send_to_room_except_users("lobby", notice(), &users), 2);
+        assert_eq!(manager.broadcast_except_users(notice(), &users), 2);
+        assert_eq!(manager.send_to_user("1", notice()), 2);
+
+        assert_eq!(manager.disconnect_session("1", "s-2", notice()), 1);
+        assert_eq!(manager.get_room_connections("lobby").len(), 4);
+        assert_eq!(manager.snapshot_connections()[0].rooms, vec!["lobby".to_string()]);
+    }
 }
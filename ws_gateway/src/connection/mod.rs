@@ -1,9 +1,24 @@
 //! Connection management for WebSocket Gateway
 
+mod activity;
+mod batching;
+#[cfg(test)]
+mod benches;
+mod command_limits;
+mod flood;
+mod keepalive;
 mod manager;
+mod outbound;
 mod session;
+mod shards;
 
+pub use activity::{ConnectionActivity, ConnectionSnapshot};
+pub use batching::BatchPolicy;
+pub use command_limits::{CommandLimits, DEFAULT_COMMAND_LIMITS};
+pub use flood::{FloodGuard, FloodPolicy, Penalty};
+pub use keepalive::{Keepalive, KeepaliveAction, KeepaliveMetrics};
 pub use manager::ConnectionManager;
+pub use outbound::{OutboundMetrics, OutboundQueue};
 pub use session::{Connection, ConnectionState};
 
 use std::sync::Arc;
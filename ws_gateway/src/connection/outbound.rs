@@ -0,0 +1,585 @@
+//! Bounded outbound queue for a WebSocket connection
+//!
+//! Sits between event producers (Kafka consumer, command handlers) and the task
+//! that writes to the socket. When a client reads slower than events arrive the
+//! queue fills up: high-frequency state updates are then dropped oldest-first,
+//! everything else is kept, and a connection that stays full for longer than the
+//! stall timeout is disconnected.
+//!
+//! Consecutive high-frequency updates can be taken off the queue together and
+//! written as one batch (see `batching.rs`); responses to the client's own
+//! commands are queued with [`OutboundQueue::respond`] and never wait for one.
+//!
+//! The queue also carries the connection's message locale and protocol version
+//! so the writer can translate messages and write them in the shape the client
+//! understands as they go out.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use i18n::Locale;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use super::batching::{BatchCategory, BatchPolicy, BATCH_PROTOCOL_VERSION};
+use crate::protocol::{ServerMessage, PROTOCOL_VERSION};
+
+/// How a message is treated when the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Superseded by the next update of the same kind, may be dropped
+    Droppable,
+    /// Must reach the client (errors, game results, chat, ...)
+    Critical,
+}
+
+impl Delivery {
+    /// Classify a message by how much a newer message supersedes it
+    pub fn of(message: &ServerMessage) -> Self {
+        match message {
+            ServerMessage::HeartbeatAck { .. }
+            | ServerMessage::ChatTyping { .. }
+            | ServerMessage::UserOnline { .. }
+            | ServerMessage::UserOffline { .. }
+            | ServerMessage::GameRoomsUpdated { .. }
+            | ServerMessage::GameRoomState { .. }
+            | ServerMessage::TicTacToeRoomState { .. }
+            | ServerMessage::BiggerDiceRoomState { .. }
+            | ServerMessage::BiggerDiceState { .. }
+            | ServerMessage::BiggerDiceStateSync { .. }
+            | ServerMessage::TicTacToeState { .. }
+            | ServerMessage::GameSelectedPlayersUpdated { .. }
+            | ServerMessage::TicTacToeSelectedPlayersUpdated { .. }
+            | ServerMessage::BiggerDiceSelectedPlayersUpdated { .. }
+            | ServerMessage::GameSpectatorsUpdated { .. }
+            | ServerMessage::GamePredictionPoolUpdated { .. }
+            | ServerMessage::GameLobbyUpdated { .. }
+            | ServerMessage::TicTacToeLobbyUpdated { .. }
+            | ServerMessage::BiggerDiceLobbyUpdated { .. } => Delivery::Droppable,
+            _ => Delivery::Critical,
+        }
+    }
+}
+
+/// Outbound counters shared by all connections
+#[derive(Debug, Default)]
+pub struct OutboundMetrics {
+    /// Connections whose queue is currently full
+    slow_connections: AtomicUsize,
+    /// Droppable messages discarded because a queue was full
+    dropped_messages: AtomicU64,
+    /// Connections closed because their queue stalled or overflowed
+    stall_disconnects: AtomicU64,
+}
+
+impl OutboundMetrics {
+    pub fn slow_connections(&self) -> usize {
+        self.slow_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped_messages(&self) -> u64 {
+        self.dropped_messages.load(Ordering::Relaxed)
+    }
+
+    pub fn stall_disconnects(&self) -> u64 {
+        self.stall_disconnects.load(Ordering::Relaxed)
+    }
+}
+
+/// Result of queueing a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// Queued without loss
+    Queued,
+    /// Queued after evicting the oldest droppable message
+    QueuedDroppingOldest,
+    /// The message itself was droppable and discarded
+    Dropped,
+    /// The queue is closed (or was closed by this push)
+    Closed,
+}
+
+impl PushOutcome {
+    /// Whether the message will be delivered
+    pub fn is_queued(self) -> bool {
+        matches!(self, PushOutcome::Queued | PushOutcome::QueuedDroppingOldest)
+    }
+}
+
+/// A queued message; `batch` is `None` for messages that are always sent alone
+struct Queued {
+    delivery: Delivery,
+    batch: Option<BatchCategory>,
+    message: ServerMessage,
+}
+
+struct QueueState {
+    messages: VecDeque<Queued>,
+    /// Set when the queue first became full, cleared once it drains to half
+    stalled_since: Option<Instant>,
+}
+
+/// Bounded per-connection message queue
+pub struct OutboundQueue {
+    connection_id: String,
+    state: Mutex<QueueState>,
+    available: Notify,
+    closed: CancellationToken,
+    /// Close once the queued messages are written
+    draining: AtomicBool,
+    capacity: usize,
+    stall_timeout: Duration,
+    metrics: Arc<OutboundMetrics>,
+    locale: Mutex<Locale>,
+    protocol_version: AtomicU32,
+}
+
+impl OutboundQueue {
+    pub fn new(
+        connection_id: impl Into<String>,
+        capacity: usize,
+        stall_timeout: Duration,
+        metrics: Arc<OutboundMetrics>,
+    ) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            connection_id: connection_id.into(),
+            state: Mutex::new(QueueState {
+                messages: VecDeque::with_capacity(capacity),
+                stalled_since: None,
+            }),
+            available: Notify::new(),
+            closed: CancellationToken::new(),
+            draining: AtomicBool::new(false),
+            capacity,
+            stall_timeout,
+            metrics,
+            locale: Mutex::new(Locale::default()),
+            protocol_version: AtomicU32::new(PROTOCOL_VERSION),
+        }
+    }
+
+    /// Locale the writer renders user-facing messages in
+    pub fn locale(&self) -> Locale {
+        *self.locale.lock().unwrap()
+    }
+
+    pub fn set_locale(&self, locale: Locale) {
+        *self.locale.lock().unwrap() = locale;
+    }
+
+    /// Protocol version the writer serializes messages for
+    pub fn protocol_version(&self) -> u32 {
+        self.protocol_version.load(Ordering::Relaxed)
+    }
+
+    pub fn set_protocol_version(&self, version: u32) {
+        self.protocol_version.store(version, Ordering::Relaxed);
+    }
+
+    /// Translate the user-facing text of a message into the connection's locale
+    pub fn localize(&self, message: ServerMessage) -> ServerMessage {
+        match message {
+            ServerMessage::Error { code, message, retry_after_ms } => ServerMessage::Error {
+                message: i18n::translate(self.locale(), &message).to_string(),
+                code,
+                retry_after_ms,
+            },
+            ServerMessage::DeprecationWarning { protocol_version, current_version, min_supported_version, message } => {
+                ServerMessage::DeprecationWarning {
+                    message: i18n::translate(self.locale(), &message).to_string(),
+                    protocol_version,
+                    current_version,
+                    min_supported_version,
+                }
+            }
+            ServerMessage::RateLimited { penalty, strikes, cooldown_secs, muted_until, message } => {
+                ServerMessage::RateLimited {
+                    message: i18n::translate(self.locale(), &message).to_string(),
+                    penalty,
+                    strikes,
+                    cooldown_secs,
+                    muted_until,
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Maximum time a single socket write or a full queue may stall
+    pub fn stall_timeout(&self) -> Duration {
+        self.stall_timeout
+    }
+
+    /// Queue a message for the writer task
+    pub fn push(&self, message: ServerMessage) -> PushOutcome {
+        let batch = BatchCategory::of(&message);
+        self.enqueue(message, batch)
+    }
+
+    /// Queue a direct response to the client's own command; it is written as
+    /// soon as the messages before it are, never held back for a batch
+    pub fn respond(&self, message: ServerMessage) -> PushOutcome {
+        self.enqueue(message, None)
+    }
+
+    fn enqueue(&self, message: ServerMessage, batch: Option<BatchCategory>) -> PushOutcome {
+        if self.closed.is_cancelled() {
+            return PushOutcome::Closed;
+        }
+
+        let delivery = Delivery::of(&message);
+        let queued = Queued { delivery, batch, message };
+        let mut state = self.state.lock().unwrap();
+
+        if state.messages.len() < self.capacity {
+            state.messages.push_back(queued);
+            drop(state);
+            self.available.notify_one();
+            return PushOutcome::Queued;
+        }
+
+        // Queue is full: the client is not keeping up
+        let stalled_since = match state.stalled_since {
+            Some(since) => since,
+            None => {
+                let now = Instant::now();
+                state.stalled_since = Some(now);
+                self.metrics.slow_connections.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Slow client on connection {}: outbound queue full ({} messages)",
+                    self.connection_id, self.capacity
+                );
+                now
+            }
+        };
+
+        if stalled_since.elapsed() >= self.stall_timeout {
+            drop(state);
+            self.disconnect("outbound queue stalled");
+            return PushOutcome::Closed;
+        }
+
+        let oldest_droppable = state
+            .messages
+            .iter()
+            .position(|queued| queued.delivery == Delivery::Droppable);
+
+        let outcome = match (oldest_droppable, delivery) {
+            (Some(index), _) => {
+                state.messages.remove(index);
+                state.messages.push_back(queued);
+                PushOutcome::QueuedDroppingOldest
+            }
+            (None, Delivery::Droppable) => PushOutcome::Dropped,
+            (None, Delivery::Critical) => {
+                // Critical messages are never dropped, but only up to twice the
+                // capacity; past that the connection is closed instead
+                if state.messages.len() >= self.capacity * 2 {
+                    drop(state);
+                    self.disconnect("outbound queue overflowed");
+                    return PushOutcome::Closed;
+                }
+                state.messages.push_back(queued);
+                PushOutcome::Queued
+            }
+        };
+        drop(state);
+
+        if outcome != PushOutcome::Queued {
+            self.metrics.dropped_messages.fetch_add(1, Ordering::Relaxed);
+        }
+        self.available.notify_one();
+        outcome
+    }
+
+    /// Take the front message if `accept` allows it
+    fn pop_front_if(&self, accept: impl FnOnce(&Queued) -> bool) -> Option<Queued> {
+        let mut state = self.state.lock().unwrap();
+        if !accept(state.messages.front()?) {
+            return None;
+        }
+
+        let queued = state.messages.pop_front();
+        if state.stalled_since.is_some() && state.messages.len() <= self.capacity / 2 {
+            state.stalled_since = None;
+            self.metrics.slow_connections.fetch_sub(1, Ordering::Relaxed);
+        }
+        queued
+    }
+
+    /// Wait for the next message; `None` once the queue is closed
+    pub async fn next(&self) -> Option<ServerMessage> {
+        self.next_queued().await.map(|queued| queued.message)
+    }
+
+    /// Wait for the next message and, when it starts a burst of batchable
+    /// updates, collect the updates of the same category that follow it
+    /// within the category's window; `None` once the queue is closed
+    pub async fn next_batch(&self, policy: &BatchPolicy) -> Option<Vec<ServerMessage>> {
+        let first = self.next_queued().await?;
+        let window = match first.batch {
+            Some(category) if self.protocol_version() >= BATCH_PROTOCOL_VERSION => policy.window(category),
+            _ => Duration::ZERO,
+        };
+
+        let mut batch = vec![first.message];
+        if window.is_zero() {
+            return Some(batch);
+        }
+
+        let deadline = tokio::time::Instant::now() + window;
+        while batch.len() < policy.max_messages {
+            if let Some(queued) = self.pop_front_if(|queued| queued.batch == first.batch) {
+                batch.push(queued.message);
+                continue;
+            }
+
+            // Something else is next, or the queue is closing: send what we have
+            if !self.state.lock().unwrap().messages.is_empty()
+                || self.draining.load(Ordering::Relaxed)
+                || self.closed.is_cancelled()
+            {
+                break;
+            }
+
+            tokio::select! {
+                _ = self.available.notified() => {}
+                _ = tokio::time::sleep_until(deadline) => break,
+                _ = self.closed.cancelled() => break,
+            }
+        }
+
+        Some(batch)
+    }
+
+    async fn next_queued(&self) -> Option<Queued> {
+        loop {
+            if self.closed.is_cancelled() {
+                return None;
+            }
+
+            if let Some(queued) = self.pop_front_if(|_| true) {
+                return Some(queued);
+            }
+
+            if self.draining.load(Ordering::Relaxed) {
+                self.close();
+                return None;
+            }
+
+            tokio::select! {
+                _ = self.available.notified() => {}
+                _ = self.closed.cancelled() => return None,
+            }
+        }
+    }
+
+    /// Close the queue because the client stopped reading
+    pub fn disconnect(&self, reason: &str) {
+        if self.closed.is_cancelled() {
+            return;
+        }
+        warn!("Disconnecting connection {}: {}", self.connection_id, reason);
+        self.metrics.stall_disconnects.fetch_add(1, Ordering::Relaxed);
+        self.close();
+    }
+
+    /// Close the queue once the messages already queued are written, e.g. to
+    /// deliver the reason for a disconnect
+    pub fn close_when_drained(&self) {
+        self.draining.store(true, Ordering::Relaxed);
+        self.available.notify_one();
+    }
+
+    /// Close the queue; pending messages are discarded
+    pub fn close(&self) {
+        if self.closed.is_cancelled() {
+            return;
+        }
+        self.closed.cancel();
+
+        let mut state = self.state.lock().unwrap();
+        state.messages.clear();
+        if state.stalled_since.take().is_some() {
+            self.metrics.slow_connections.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Resolves once the queue has been closed
+    pub async fn closed(&self) {
+        self.closed.cancelled().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(capacity: usize, stall_timeout: Duration) -> OutboundQueue {
+        OutboundQueue::new("conn", capacity, stall_timeout, Arc::new(OutboundMetrics::default()))
+    }
+
+    fn state_update() -> ServerMessage {
+        ServerMessage::UserOnline {
+            user_id: "1".to_string(),
+            username: "alice".to_string(),
+        }
+    }
+
+    fn critical() -> ServerMessage {
+        ServerMessage::Error {
+            code: "E".to_string(),
+            message: "boom".to_string(),
+            retry_after_ms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_oldest_droppable() {
+        let q = queue(2, Duration::from_secs(60));
+        assert_eq!(q.push(state_update()), PushOutcome::Queued);
+        assert_eq!(q.push(critical()), PushOutcome::Queued);
+        assert_eq!(q.push(critical()), PushOutcome::QueuedDroppingOldest);
+
+        assert!(matches!(q.next().await, Some(ServerMessage::Error { .. })));
+        assert!(matches!(q.next().await, Some(ServerMessage::Error { .. })));
+        assert_eq!(q.metrics.dropped_messages(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_critical_messages_are_kept_and_droppable_discarded() {
+        let q = queue(1, Duration::from_secs(60));
+        assert_eq!(q.push(critical()), PushOutcome::Queued);
+        assert_eq!(q.push(state_update()), PushOutcome::Dropped);
+        assert_eq!(q.push(critical()), PushOutcome::Queued);
+        assert_eq!(q.state.lock().unwrap().messages.len(), 2);
+        assert_eq!(q.metrics.slow_connections(), 1);
+
+        // Hard limit is twice the capacity
+        assert_eq!(q.push(critical()), PushOutcome::Closed);
+        assert!(q.closed.is_cancelled());
+        assert_eq!(q.metrics.stall_disconnects(), 1);
+        assert_eq!(q.metrics.slow_connections(), 0);
+        assert!(q.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stalled_queue_disconnects() {
+        let q = queue(1, Duration::ZERO);
+        assert_eq!(q.push(critical()), PushOutcome::Queued);
+        assert_eq!(q.push(critical()), PushOutcome::Closed);
+        assert!(q.closed.is_cancelled());
+        assert_eq!(q.metrics.stall_disconnects(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_draining_clears_slow_state() {
+        let q = queue(2, Duration::from_secs(60));
+        q.push(critical());
+        q.push(critical());
+        q.push(critical());
+        assert_eq!(q.metrics.slow_connections(), 1);
+
+        q.next().await;
+        q.next().await;
+        assert_eq!(q.metrics.slow_connections(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_close_when_drained_delivers_queued_messages_first() {
+        let q = queue(4, Duration::from_secs(60));
+        q.push(critical());
+        q.close_when_drained();
+
+        assert!(q.next().await.is_some());
+        assert!(q.next().await.is_none());
+        assert!(q.closed.is_cancelled());
+    }
+
+    fn turn_changed() -> ServerMessage {
+        ServerMessage::GameTurnChanged {
+            room_id: "r1".to_string(),
+            current_turn: "42".to_string(),
+            turn_number: 1,
+            state_checksum: None,
+        }
+    }
+
+    fn batching(window_ms: u64) -> BatchPolicy {
+        BatchPolicy {
+            game_state_window: Duration::from_millis(window_ms),
+            presence_window: Duration::from_millis(window_ms),
+            lobby_window: Duration::from_millis(window_ms),
+            max_messages: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_updates_of_one_category_are_batched() {
+        let q = queue(8, Duration::from_secs(60));
+        for _ in 0..4 {
+            q.push(turn_changed());
+        }
+        q.push(state_update());
+        q.push(critical());
+
+        let policy = batching(1_000);
+        assert_eq!(q.next_batch(&policy).await.unwrap().len(), 3);
+        assert_eq!(q.next_batch(&policy).await.unwrap().len(), 1);
+        assert!(matches!(
+            q.next_batch(&policy).await.unwrap()[..],
+            [ServerMessage::UserOnline { .. }]
+        ));
+        assert!(matches!(q.next_batch(&policy).await.unwrap()[..], [ServerMessage::Error { .. }]));
+    }
+
+    #[tokio::test]
+    async fn test_batch_collects_updates_arriving_within_the_window() {
+        let q = Arc::new(queue(8, Duration::from_secs(60)));
+        q.push(turn_changed());
+
+        let producer = q.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            producer.push(turn_changed());
+        });
+
+        assert_eq!(q.next_batch(&batching(500)).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_responses_and_old_clients_are_never_batched() {
+        let q = queue(8, Duration::from_secs(60));
+        q.respond(turn_changed());
+        q.push(turn_changed());
+        assert_eq!(q.next_batch(&batching(20)).await.unwrap().len(), 1);
+        assert_eq!(q.next_batch(&batching(20)).await.unwrap().len(), 1);
+
+        q.set_protocol_version(BATCH_PROTOCOL_VERSION - 1);
+        q.push(turn_changed());
+        q.push(turn_changed());
+        assert_eq!(q.next_batch(&batching(20)).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_errors_are_rendered_in_the_connection_locale() {
+        let queue = queue(4, Duration::from_secs(5));
+        let error = || ServerMessage::Error {
+            code: "game_error".to_string(),
+            message: "Room not found".to_string(),
+            retry_after_ms: None,
+        };
+
+        let message = |msg: ServerMessage| match msg {
+            ServerMessage::Error { message, .. } => message,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(message(queue.localize(error())), "Room not found");
+        queue.set_locale(Locale::Sr);
+        assert_eq!(message(queue.localize(error())), "Soba nije pronađena");
+    }
+}
@@ -2,13 +2,14 @@
 
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
-use tokio::sync::mpsc;
-use uuid::Uuid;
 
 use crate::auth::AuthenticatedUser;
 use crate::protocol::ServerMessage;
 
+use super::{FloodGuard, OutboundQueue};
+
 /// Connection state
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConnectionState {
@@ -89,12 +90,15 @@ pub struct Connection {
     /// Last activity timestamp
     pub last_activity: DateTime<Utc>,
 
-    /// Sender for outgoing messages
-    pub tx: mpsc::UnboundedSender<ServerMessage>,
+    /// Bounded queue for outgoing messages
+    pub tx: Arc<OutboundQueue>,
 
     /// Rate limiter
     rate_limiter: RateLimiter,
 
+    /// Flood penalties (mute) of this connection
+    pub flood: FloodGuard,
+
     /// Rooms this connection is subscribed to
     pub rooms: Vec<String>,
 }
@@ -102,14 +106,15 @@ pub struct Connection {
 impl Connection {
     /// Create a new anonymous connection
     pub fn new(
+        id: String,
         addr: SocketAddr,
-        tx: mpsc::UnboundedSender<ServerMessage>,
+        tx: Arc<OutboundQueue>,
         rate_limit_per_sec: u32,
         rate_limit_burst: u32,
     ) -> Self {
         let now = Utc::now();
         Self {
-            id: Uuid::new_v4().to_string(),
+            id,
             addr,
             state: ConnectionState::Anonymous,
             user: None,
@@ -117,6 +122,7 @@ impl Connection {
             last_activity: now,
             tx,
             rate_limiter: RateLimiter::new(rate_limit_burst as u64, rate_limit_per_sec as u64),
+            flood: FloodGuard::default(),
             rooms: Vec::new(),
         }
     }
@@ -141,13 +147,32 @@ impl Connection {
         self.user.as_ref().map(|u| u.username.as_str())
     }
 
+    /// Admin acting as the user, if this is an impersonated connection
+    pub fn impersonator_id(&self) -> Option<&str> {
+        self.user.as_ref().and_then(|u| u.impersonator_id.as_deref())
+    }
+
+    /// Sign-in session of the token the connection authenticated with
+    pub fn session_id(&self) -> Option<&str> {
+        self.user.as_ref().and_then(|u| u.session_id.as_deref())
+    }
+
     /// Authenticate the connection
     pub fn authenticate(&mut self, user: AuthenticatedUser) {
+        // The profile setting overrides the handshake's Accept-Language
+        if let Some(locale) = user.locale.as_deref().and_then(i18n::Locale::parse) {
+            self.tx.set_locale(locale);
+        }
         self.user = Some(user);
         self.state = ConnectionState::Authenticated;
         self.last_activity = Utc::now();
     }
 
+    /// Write this connection's messages in a negotiated protocol version
+    pub fn set_protocol_version(&self, version: u32) {
+        self.tx.set_protocol_version(version);
+    }
+
     /// Update last activity
     pub fn touch(&mut self) {
         self.last_activity = Utc::now();
@@ -163,9 +188,9 @@ impl Connection {
         self.rate_limiter.try_consume()
     }
 
-    /// Send a message to this connection
+    /// Send a response to this connection (never held back for batching)
     pub fn send(&self, message: ServerMessage) -> bool {
-        self.tx.send(message).is_ok()
+        self.tx.respond(message).is_queued()
     }
 
     /// Join a room
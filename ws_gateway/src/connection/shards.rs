@@ -0,0 +1,181 @@
+//! Sharded maps behind the connection manager
+//!
+//! Keys are hashed to one of a power-of-two number of shards, each a
+//! `HashMap` behind its own `RwLock`, so operations on different connections,
+//! users or rooms rarely contend and sends only take read locks. Counts are
+//! kept in atomics and read without locking.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Shards per CPU when the count is not given
+const SHARDS_PER_CPU: usize = 4;
+
+/// Upper bound of the default shard count
+const MAX_DEFAULT_SHARDS: usize = 256;
+
+/// Shard count used by `ConnectionManager::new`: four per CPU, as a power of two
+pub fn default_shard_count() -> usize {
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    (cpus * SHARDS_PER_CPU)
+        .next_power_of_two()
+        .min(MAX_DEFAULT_SHARDS)
+}
+
+/// A map split into independently locked shards
+pub struct ShardedMap<V> {
+    shards: Box<[RwLock<HashMap<Arc<str>, V>>]>,
+    hasher: RandomState,
+}
+
+impl<V> ShardedMap<V> {
+    /// `count` is rounded up to a power of two (at least 1)
+    pub fn new(count: usize) -> Self {
+        let count = count.max(1).next_power_of_two();
+        Self {
+            shards: (0..count).map(|_| RwLock::new(HashMap::new())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    fn shard_of(&self, key: &str) -> &RwLock<HashMap<Arc<str>, V>> {
+        let index = self.hasher.hash_one(key) as usize & (self.shards.len() - 1);
+        &self.shards[index]
+    }
+
+    /// The shard holding `key`, locked for reading
+    pub fn read(&self, key: &str) -> RwLockReadGuard<'_, HashMap<Arc<str>, V>> {
+        self.shard_of(key).read().unwrap()
+    }
+
+    /// The shard holding `key`, locked for writing
+    pub fn write(&self, key: &str) -> RwLockWriteGuard<'_, HashMap<Arc<str>, V>> {
+        self.shard_of(key).write().unwrap()
+    }
+
+    /// Every shard in turn, each locked for reading while it is visited
+    pub fn shards(&self) -> impl Iterator<Item = RwLockReadGuard<'_, HashMap<Arc<str>, V>>> {
+        self.shards.iter().map(|shard| shard.read().unwrap())
+    }
+}
+
+/// Connection IDs grouped by a user or room ID
+pub struct ShardedIndex {
+    map: ShardedMap<HashSet<Arc<str>>>,
+    /// Keys with at least one connection
+    keys: AtomicUsize,
+}
+
+impl ShardedIndex {
+    pub fn new(count: usize) -> Self {
+        Self {
+            map: ShardedMap::new(count),
+            keys: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn insert(&self, key: &str, connection_id: &Arc<str>) {
+        let mut shard = self.map.write(key);
+        if let Some(connections) = shard.get_mut(key) {
+            connections.insert(connection_id.clone());
+            return;
+        }
+        shard.insert(Arc::from(key), HashSet::from([connection_id.clone()]));
+        self.keys.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drop a connection from a key, and the key once it has none left
+    pub fn remove(&self, key: &str, connection_id: &str) {
+        let mut shard = self.map.write(key);
+        let Some(connections) = shard.get_mut(key) else {
+            return;
+        };
+        connections.remove(connection_id);
+        if connections.is_empty() {
+            shard.remove(key);
+            self.keys.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Connections of a key (copied out, so no lock is held while sending)
+    pub fn members(&self, key: &str) -> Vec<Arc<str>> {
+        self.map
+            .read(key)
+            .get(key)
+            .map(|connections| connections.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Connections of several keys
+    pub fn members_of<'a>(&self, keys: impl IntoIterator<Item = &'a String>) -> HashSet<Arc<str>> {
+        let mut members = HashSet::new();
+        for key in keys {
+            if let Some(connections) = self.map.read(key).get(key.as_str()) {
+                members.extend(connections.iter().cloned());
+            }
+        }
+        members
+    }
+
+    pub fn len_of(&self, key: &str) -> usize {
+        self.map.read(key).get(key).map_or(0, HashSet::len)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.len_of(key) > 0
+    }
+
+    /// Keys with at least one connection, without locking
+    pub fn key_count(&self) -> usize {
+        self.keys.load(Ordering::Relaxed)
+    }
+
+    /// Connection count of every key
+    pub fn counts(&self) -> Vec<(String, usize)> {
+        self.map
+            .shards()
+            .flat_map(|shard| {
+                shard
+                    .iter()
+                    .map(|(key, connections)| (key.to_string(), connections.len()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_counts_are_powers_of_two() {
+        assert_eq!(ShardedMap::<()>::new(0).shards.len(), 1);
+        assert_eq!(ShardedMap::<()>::new(5).shards.len(), 8);
+        assert_eq!(ShardedMap::<()>::new(64).shards.len(), 64);
+        assert!(default_shard_count().is_power_of_two());
+        assert!(default_shard_count() <= MAX_DEFAULT_SHARDS);
+    }
+
+    #[test]
+    fn test_index_counts_keys_with_connections() {
+        let index = ShardedIndex::new(4);
+        let (a, b): (Arc<str>, Arc<str>) = (Arc::from("c-1"), Arc::from("c-2"));
+        index.insert("room-1", &a);
+        index.insert("room-1", &b);
+        index.insert("room-2", &a);
+        assert_eq!(index.key_count(), 2);
+        assert_eq!(index.len_of("room-1"), 2);
+
+        index.remove("room-1", "c-1");
+        index.remove("room-2", "c-1");
+        index.remove("room-3", "c-1");
+        assert_eq!(index.key_count(), 1);
+        assert!(!index.contains("room-2"));
+        assert_eq!(index.members("room-1"), vec![b]);
+        assert_eq!(index.counts(), vec![("room-1".to_string(), 1)]);
+    }
+}
@@ -1,7 +1,10 @@
 //! Error types for WebSocket Gateway
 
+use kafka_producer::ProducerError;
 use thiserror::Error;
 
+use crate::redis_client::RedisClientError;
+
 /// Gateway-specific errors
 #[derive(Error, Debug)]
 pub enum GatewayError {
@@ -9,11 +12,14 @@ pub enum GatewayError {
     WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
 
     #[error("Redis error: {0}")]
-    Redis(#[from] redis::RedisError),
+    Redis(#[from] RedisClientError),
 
     #[error("Kafka error: {0}")]
     Kafka(#[from] rdkafka::error::KafkaError),
 
+    #[error("Kafka publish failed: {0}")]
+    Publish(#[from] ProducerError),
+
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
@@ -26,12 +32,21 @@ pub enum GatewayError {
     #[error("Connection not authenticated")]
     NotAuthenticated,
 
+    #[error("Not allowed to send {0}")]
+    Forbidden(String),
+
     #[error("Invalid message: {0}")]
     InvalidMessage(String),
 
     #[error("Rate limit exceeded")]
     RateLimitExceeded,
 
+    #[error("Rate limit of {command} exceeded")]
+    CommandRateLimited { command: String, retry_after_ms: u64 },
+
+    #[error("Protocol version no longer supported, please update the app")]
+    UnsupportedProtocolVersion(u32),
+
     #[error("Connection closed")]
     ConnectionClosed,
 
@@ -39,5 +54,60 @@ pub enum GatewayError {
     Internal(String),
 }
 
+impl GatewayError {
+    /// Error code reported to the client in `ServerMessage::Error`
+    pub fn code(&self) -> &'static str {
+        match self {
+            GatewayError::Redis(e) if e.is_transient() => "TEMPORARILY_UNAVAILABLE",
+            GatewayError::Publish(e) if e.is_retryable() => "TEMPORARILY_UNAVAILABLE",
+            GatewayError::Publish(ProducerError::TooLarge { .. }) => "MESSAGE_TOO_LARGE",
+            GatewayError::AuthFailed(_) | GatewayError::Jwt(_) | GatewayError::NotAuthenticated => {
+                "NOT_AUTHENTICATED"
+            }
+            GatewayError::Forbidden(_) => "FORBIDDEN",
+            GatewayError::Json(_) | GatewayError::InvalidMessage(_) => "INVALID_FORMAT",
+            GatewayError::RateLimitExceeded | GatewayError::CommandRateLimited { .. } => "RATE_LIMIT",
+            GatewayError::UnsupportedProtocolVersion(_) => "UNSUPPORTED_PROTOCOL_VERSION",
+            GatewayError::ConnectionClosed => "CONNECTION_CLOSED",
+            GatewayError::WebSocket(_)
+            | GatewayError::Redis(_)
+            | GatewayError::Kafka(_)
+            | GatewayError::Publish(_)
+            | GatewayError::Internal(_) => "MESSAGE_ERROR",
+        }
+    }
+
+    /// Message safe to show to the client (infrastructure details stay in the logs)
+    pub fn client_message(&self) -> String {
+        match self {
+            GatewayError::Redis(e) if e.is_transient() => {
+                "Service temporarily unavailable, please retry".to_string()
+            }
+            GatewayError::Publish(e) if e.is_retryable() => {
+                "Service temporarily unavailable, please retry".to_string()
+            }
+            GatewayError::Publish(ProducerError::TooLarge { .. }) => "Message is too large to send".to_string(),
+            GatewayError::Forbidden(_) => "You are not allowed to send this command".to_string(),
+            GatewayError::CommandRateLimited { .. } => {
+                "You are sending this command too quickly, try again shortly".to_string()
+            }
+            GatewayError::WebSocket(_)
+            | GatewayError::Redis(_)
+            | GatewayError::Kafka(_)
+            | GatewayError::Publish(_)
+            | GatewayError::Internal(_) => "Internal error".to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    /// How long the client should wait before sending the command again
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            GatewayError::CommandRateLimited { retry_after_ms, .. } => Some(*retry_after_ms),
+            _ => None,
+        }
+    }
+}
+
 /// Result type alias for gateway operations
 pub type GatewayResult<T> = Result<T, GatewayError>;
@@ -0,0 +1,55 @@
+//! Broker connection settings shared by the producer and consumer
+
+use rdkafka::config::ClientConfig;
+
+use crate::config::Config;
+
+/// Bootstrap servers, rack and reconnect backoff for the Kafka clients
+#[derive(Debug, Clone)]
+pub struct BrokerSettings {
+    /// Comma-separated bootstrap servers (any reachable one is enough)
+    pub bootstrap_servers: String,
+    /// Rack (availability zone) this gateway runs in
+    pub rack: Option<String>,
+    pub reconnect_backoff_ms: u64,
+    pub reconnect_backoff_max_ms: u64,
+}
+
+impl BrokerSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            bootstrap_servers: config.kafka_brokers.clone(),
+            rack: config.kafka_client_rack.clone(),
+            reconnect_backoff_ms: config.kafka_reconnect_backoff_ms,
+            reconnect_backoff_max_ms: config
+                .kafka_reconnect_backoff_max_ms
+                .max(config.kafka_reconnect_backoff_ms),
+        }
+    }
+
+    /// Client config with the broker settings applied
+    pub fn client_config(&self) -> ClientConfig {
+        let mut client = ClientConfig::new();
+        client
+            .set("bootstrap.servers", &self.bootstrap_servers)
+            .set("reconnect.backoff.ms", self.reconnect_backoff_ms.to_string())
+            .set("reconnect.backoff.max.ms", self.reconnect_backoff_max_ms.to_string());
+
+        // Consumers fetch from the closest replica when brokers have
+        // replica.selector.class set to RackAwareReplicaSelector
+        if let Some(rack) = &self.rack {
+            client.set("client.rack", rack);
+        }
+        client
+    }
+
+    /// Broker settings for the health endpoint
+    pub fn health(&self) -> serde_json::Value {
+        serde_json::json!({
+            "bootstrap_servers": self.bootstrap_servers.split(',').map(str::trim).collect::<Vec<_>>(),
+            "rack": self.rack,
+            "reconnect_backoff_ms": self.reconnect_backoff_ms,
+            "reconnect_backoff_max_ms": self.reconnect_backoff_max_ms,
+        })
+    }
+}
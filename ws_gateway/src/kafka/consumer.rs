@@ -1,14 +1,17 @@
 //! Kafka Consumer for receiving events from domain services
 
-use rdkafka::config::ClientConfig;
+use kafka_producer::segment::{Reassembler, SegmentHeader};
 use rdkafka::consumer::{Consumer, StreamConsumer};
 use rdkafka::message::Message;
 use rdkafka::TopicPartitionList;
 use futures_util::StreamExt;
+use std::borrow::Cow;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 
+use super::brokers::BrokerSettings;
 use crate::config::KafkaTopics;
 use crate::error::{GatewayError, GatewayResult};
 use crate::protocol::EventEnvelope;
@@ -32,11 +35,20 @@ pub struct KafkaConsumer {
 
 impl KafkaConsumer {
     /// Create a new Kafka consumer
-    pub fn new(brokers: &str, group_id: &str) -> GatewayResult<(Self, broadcast::Receiver<KafkaEvent>)> {
-        info!("Creating Kafka consumer for brokers: {}, group: {}", brokers, group_id);
-
-        let consumer: StreamConsumer = ClientConfig::new()
-            .set("bootstrap.servers", brokers)
+    pub fn new(
+        brokers: &BrokerSettings,
+        group_id: &str,
+        topics: KafkaTopics,
+    ) -> GatewayResult<(Self, broadcast::Receiver<KafkaEvent>)> {
+        info!(
+            "Creating Kafka consumer for brokers: {}, group: {}, rack: {}",
+            brokers.bootstrap_servers,
+            group_id,
+            brokers.rack.as_deref().unwrap_or("none")
+        );
+
+        let consumer: StreamConsumer = brokers
+            .client_config()
             .set("group.id", group_id)
             .set("enable.auto.commit", "true")
             .set("auto.commit.interval.ms", "5000")
@@ -49,8 +61,6 @@ impl KafkaConsumer {
             .create()
             .map_err(|e| GatewayError::Internal(format!("Failed to create Kafka consumer: {}", e)))?;
 
-        let topics = KafkaTopics::default();
-
         // Subscribe to event topics
         let topic_list: Vec<&str> = topics.consumer_topics();
         consumer.subscribe(&topic_list)
@@ -81,6 +91,7 @@ impl KafkaConsumer {
         info!("Starting Kafka consumer loop");
 
         let mut message_stream = self.consumer.stream();
+        let mut reassembler = Reassembler::default();
 
         while let Some(result) = message_stream.next().await {
             match result {
@@ -94,35 +105,45 @@ impl KafkaConsumer {
                         String::from_utf8_lossy(k).to_string()
                     });
 
-                    // Extract payload
-                    if let Some(payload) = message.payload() {
-                        match serde_json::from_slice::<EventEnvelope>(payload) {
-                            Ok(envelope) => {
-                                debug!(
-                                    "Received event from {}[{}]@{}: type={}",
-                                    topic, partition, offset, envelope.event_type
-                                );
-
-                                let event = KafkaEvent {
-                                    topic,
-                                    partition,
-                                    offset,
-                                    key,
-                                    envelope,
-                                };
-
-                                // Broadcast to all listeners
-                                if let Err(e) = self.event_tx.send(event) {
-                                    warn!("No active listeners for Kafka event: {}", e);
-                                }
-                            }
-                            Err(e) => {
-                                warn!(
-                                    "Failed to parse event from {}[{}]@{}: {}",
-                                    topic, partition, offset, e
-                                );
+                    // Extract payload; oversized ones arrive in segments
+                    // (see kafka_producer::segment) and are parsed once whole
+                    let Some(payload) = message.payload() else {
+                        continue;
+                    };
+                    let payload = match message.headers().and_then(SegmentHeader::from_headers) {
+                        Some(segment) => match reassembler.push(segment, payload, Instant::now()) {
+                            Some(whole) => Cow::Owned(whole),
+                            None => continue,
+                        },
+                        None => Cow::Borrowed(payload),
+                    };
+
+                    match serde_json::from_slice::<EventEnvelope>(&payload) {
+                        Ok(envelope) => {
+                            debug!(
+                                "Received event from {}[{}]@{}: type={}",
+                                topic, partition, offset, envelope.event_type
+                            );
+
+                            let event = KafkaEvent {
+                                topic,
+                                partition,
+                                offset,
+                                key,
+                                envelope,
+                            };
+
+                            // Broadcast to all listeners
+                            if let Err(e) = self.event_tx.send(event) {
+                                warn!("No active listeners for Kafka event: {}", e);
                             }
                         }
+                        Err(e) => {
+                            warn!(
+                                "Failed to parse event from {}[{}]@{}: {}",
+                                topic, partition, offset, e
+                            );
+                        }
                     }
                 }
                 Err(e) => {
@@ -2,9 +2,12 @@
 //!
 //! Handles publishing commands and consuming events from Kafka.
 
+mod brokers;
 mod producer;
 mod consumer;
+pub mod routing;
 
+pub use brokers::BrokerSettings;
 pub use producer::KafkaProducer;
 pub use consumer::{KafkaConsumer, KafkaEvent};
 
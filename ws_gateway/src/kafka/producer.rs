@@ -1,45 +1,81 @@
 //! Kafka Producer for publishing commands and events
 
-use rdkafka::config::ClientConfig;
-use rdkafka::producer::{FutureProducer, FutureRecord};
-use std::time::Duration;
+use kafka_producer::{Delivery, ResilienceConfig, ResilientProducer, SizeLimits};
+use rdkafka::producer::FutureProducer;
+use std::sync::Arc;
 use tracing::{debug, error, info};
 
+use super::brokers::BrokerSettings;
+use super::routing::{game_type_of, known_game_type, GamesRouting, RoomGameTypes};
 use crate::config::KafkaTopics;
 use crate::error::{GatewayError, GatewayResult};
 use crate::protocol::EventEnvelope;
+use crate::redis_client::SharedRedisManager;
 
 /// Kafka producer for the WebSocket Gateway
 pub struct KafkaProducer {
-    producer: FutureProducer,
+    producer: Arc<ResilientProducer>,
+    brokers: BrokerSettings,
     topics: KafkaTopics,
+    games_routing: GamesRouting,
+    room_game_types: RoomGameTypes,
+    redis: SharedRedisManager,
 }
 
 impl KafkaProducer {
-    /// Create a new Kafka producer
-    pub fn new(brokers: &str) -> GatewayResult<Self> {
-        info!("Creating Kafka producer for brokers: {}", brokers);
-
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", brokers)
+    /// Create a new Kafka producer; payloads over `size` are refused
+    pub fn new(
+        brokers: &BrokerSettings,
+        compression: &str,
+        size: SizeLimits,
+        topics: KafkaTopics,
+        games_routing: GamesRouting,
+        redis: SharedRedisManager,
+    ) -> GatewayResult<Self> {
+        info!("Creating Kafka producer for brokers: {}", brokers.bootstrap_servers);
+
+        let producer: FutureProducer = brokers
+            .client_config()
             .set("message.timeout.ms", "5000")
             .set("queue.buffering.max.messages", "100000")
             .set("queue.buffering.max.kbytes", "1048576")
             .set("batch.num.messages", "10000")
             .set("linger.ms", "5")
-            .set("compression.type", "snappy")
+            .set("compression.type", compression)
             .set("acks", "1")
             .create()
             .map_err(|e| GatewayError::Internal(format!("Failed to create Kafka producer: {}", e)))?;
 
+        // Broker outages are retried, then bridged by an in-memory buffer
+        let resilience = ResilienceConfig {
+            size,
+            ..ResilienceConfig::default()
+        };
+        let producer = Arc::new(ResilientProducer::new(producer, resilience));
+        producer.start_flusher();
+
         info!("Kafka producer created successfully");
 
         Ok(Self {
             producer,
-            topics: KafkaTopics::default(),
+            brokers: brokers.clone(),
+            topics,
+            games_routing,
+            room_game_types: RoomGameTypes::default(),
+            redis,
         })
     }
 
+    /// Delivery policy state (counters, buffer, breaker) for the health endpoint
+    pub fn resilience(&self) -> &ResilientProducer {
+        &self.producer
+    }
+
+    /// Broker settings (used by the health endpoint)
+    pub fn brokers(&self) -> &BrokerSettings {
+        &self.brokers
+    }
+
     /// Publish an event to a specific topic
     pub async fn publish(&self, topic: &str, key: &str, envelope: &EventEnvelope) -> GatewayResult<()> {
         let payload = serde_json::to_string(envelope)?;
@@ -49,21 +85,18 @@ impl KafkaProducer {
             topic, envelope.event_type, key
         );
 
-        let record = FutureRecord::to(topic)
-            .key(key)
-            .payload(&payload);
-
-        match self.producer.send(record, Duration::from_secs(5)).await {
-            Ok((partition, offset)) => {
-                debug!(
-                    "Published to {}[{}] at offset {}",
-                    topic, partition, offset
-                );
+        match self.producer.send(topic, Some(key), payload.as_bytes()).await {
+            Ok(Delivery::Sent) => {
+                debug!("Published to {}", topic);
                 Ok(())
             }
-            Err((err, _)) => {
+            Ok(Delivery::Buffered) => {
+                debug!("Buffered for {} until Kafka is back", topic);
+                Ok(())
+            }
+            Err(err) => {
                 error!("Failed to publish to {}: {}", topic, err);
-                Err(GatewayError::Kafka(err))
+                Err(GatewayError::Publish(err))
             }
         }
     }
@@ -78,14 +111,38 @@ impl KafkaProducer {
         self.publish(self.topics.chat_events, key, envelope).await
     }
 
-    /// Publish a games command
+    /// Publish a games command to the topics its game type routes it to
+    /// (see `routing`); a room's commands go by the room's recorded game type
     pub async fn publish_games_command(&self, key: &str, envelope: &EventEnvelope) -> GatewayResult<()> {
-        self.publish(self.topics.games_commands, key, envelope).await
+        let game_type = match envelope.audience.room_id.as_deref() {
+            Some(room_id) if self.games_routing.needs_game_type() => Some(self.room_game_type(room_id).await?),
+            _ => game_type_of(&envelope.event_type, &envelope.payload),
+        };
+
+        for route in self.games_routing.routes(game_type, key) {
+            self.publish(&route.topic, &route.key, envelope).await?;
+        }
+        Ok(())
+    }
+
+    /// Game type blazing_sun recorded for a room; rooms without one are
+    /// refused so their commands never reach another partition
+    async fn room_game_type(&self, room_id: &str) -> GatewayResult<&'static str> {
+        if let Some(game_type) = self.room_game_types.get(room_id) {
+            return Ok(game_type);
+        }
+
+        let recorded = self.redis.get_room_game_type(room_id).await?;
+        let Some(game_type) = recorded.as_deref().and_then(known_game_type) else {
+            return Err(GatewayError::InvalidMessage(format!("Unknown room {}", room_id)));
+        };
+        self.room_game_types.insert(room_id, game_type);
+        Ok(game_type)
     }
 
     /// Publish a games event
     pub async fn publish_games_event(&self, key: &str, envelope: &EventEnvelope) -> GatewayResult<()> {
-        self.publish(self.topics.games_events, key, envelope).await
+        self.publish(&self.topics.games_events, key, envelope).await
     }
 
     /// Publish a system event
@@ -103,7 +160,7 @@ impl KafkaProducer {
         if event_type.starts_with("chat.") {
             self.topics.chat_commands
         } else if event_type.starts_with("games.") {
-            self.topics.games_commands
+            &self.topics.games_commands
         } else {
             self.topics.system_events
         }
@@ -0,0 +1,83 @@
+//! Game command routing
+//!
+//! Topics and keys come from the shared `games_routing` crate, which
+//! blazing_sun uses as well; both services must run with the same
+//! `GAMES_COMMANDS_PARTITIONING` and `GAMES_COMMANDS_MIGRATION`.
+//!
+//! When the routes depend on the game type, a room's commands are routed by
+//! the game type blazing_sun recorded for the room in Redis, whatever the
+//! command names, so they always share a partition. Commands for a room
+//! without a record are refused rather than sent to another partition.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::warn;
+
+pub use games_routing::{game_type_of, known_game_type, GamesRouting, Migration, Partitioning};
+
+/// Rooms whose game type is cached; the cache is cleared when full and rooms
+/// are read from Redis again
+const ROOM_GAME_TYPES_CAP: usize = 10_000;
+
+/// Routing from the configured names; unknown values fall back to `room` / `none`
+pub fn from_config(partitioning: &str, migration: &str, region: &str) -> GamesRouting {
+    let parsed_partitioning = Partitioning::parse(partitioning).unwrap_or_else(|| {
+        warn!(
+            "Unknown GAMES_COMMANDS_PARTITIONING '{}', using room",
+            partitioning
+        );
+        Partitioning::Room
+    });
+    let parsed_migration = Migration::parse(migration).unwrap_or_else(|| {
+        warn!(
+            "Unknown GAMES_COMMANDS_MIGRATION '{}', using none",
+            migration
+        );
+        Migration::None
+    });
+
+    GamesRouting::new(parsed_partitioning, parsed_migration, region)
+}
+
+/// Recorded game types of rooms, cached; a room's game type never changes
+#[derive(Debug, Default)]
+pub struct RoomGameTypes {
+    rooms: Mutex<HashMap<String, &'static str>>,
+}
+
+impl RoomGameTypes {
+    pub fn get(&self, room_id: &str) -> Option<&'static str> {
+        self.rooms.lock().unwrap().get(room_id).copied()
+    }
+
+    pub fn insert(&self, room_id: &str, game_type: &'static str) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if rooms.len() >= ROOM_GAME_TYPES_CAP {
+            rooms.clear();
+        }
+        rooms.insert(room_id.to_string(), game_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_settings_fall_back_to_room() {
+        let routing = from_config("by_magic", "soon", "default");
+        assert!(!routing.needs_game_type());
+        let routes = routing.routes(None, "r-1");
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].topic, "games.commands");
+    }
+
+    #[test]
+    fn cached_rooms_keep_their_game_type() {
+        let rooms = RoomGameTypes::default();
+        rooms.insert("r-1", "bigger_dice");
+
+        assert_eq!(rooms.get("r-1"), Some("bigger_dice"));
+        assert_eq!(rooms.get("r-2"), None);
+    }
+}
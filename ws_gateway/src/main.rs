@@ -9,16 +9,24 @@ use tokio::net::TcpListener;
 use tokio::signal;
 use tracing::{info, error, warn};
 
+mod admin;
 mod config;
 mod server;
 mod connection;
 mod kafka;
+mod maintenance;
 mod redis_client;
+mod redis_failover;
+mod room_roles;
 mod auth;
 mod protocol;
 mod error;
 
+use admin::{Admin, HttpResponse};
 use config::Config;
+use connection::SharedConnectionManager;
+use kafka::SharedKafkaProducer;
+use redis_client::SharedRedisManager;
 use server::WebSocketServer;
 
 #[tokio::main]
@@ -32,8 +40,9 @@ async fn main() -> anyhow::Result<()> {
     info!("Starting WebSocket Gateway...");
 
     // Load configuration
-    let config = Config::from_env()?;
-    info!("Configuration loaded: {}:{}", config.host, config.port);
+    let secrets = secrets_provider::from_env().await?;
+    let config = Config::load(secrets.as_ref())?;
+    info!(secrets = secrets.name(), "Configuration loaded: {:?}", config);
 
     // Create the WebSocket server
     let server = WebSocketServer::new(config.clone()).await?;
@@ -46,8 +55,12 @@ async fn main() -> anyhow::Result<()> {
 
     // Spawn health check server
     let health_port = config.health_port;
+    let connections = server.connections();
+    let kafka_producer = server.kafka_producer();
+    let redis = server.redis();
+    let admin = Arc::new(Admin::new(server.connections(), server.jwt_validator()));
     tokio::spawn(async move {
-        if let Err(e) = run_health_server(health_port).await {
+        if let Err(e) = run_health_server(health_port, connections, kafka_producer, redis, admin).await {
             error!("Health server error: {}", e);
         }
     });
@@ -95,9 +108,17 @@ async fn accept_connections(
     }
 }
 
-/// Run a simple HTTP health check server
-async fn run_health_server(port: u16) -> anyhow::Result<()> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+/// Run a simple HTTP health check server (also reports connection/backpressure/keepalive
+/// stats, Kafka delivery and broker settings and Redis failover state, and serves the
+/// operator endpoints under /admin)
+async fn run_health_server(
+    port: u16,
+    connections: SharedConnectionManager,
+    kafka_producer: SharedKafkaProducer,
+    redis: SharedRedisManager,
+    admin: Arc<Admin>,
+) -> anyhow::Result<()> {
+    use tokio::io::AsyncWriteExt;
 
     let addr: SocketAddr = format!("0.0.0.0:{}", port).parse()?;
     let listener = TcpListener::bind(&addr).await?;
@@ -105,17 +126,62 @@ async fn run_health_server(port: u16) -> anyhow::Result<()> {
 
     loop {
         let (mut socket, _) = listener.accept().await?;
+        let connections = connections.clone();
+        let kafka_producer = kafka_producer.clone();
+        let redis = redis.clone();
+        let admin = admin.clone();
 
         tokio::spawn(async move {
-            let mut buf = [0u8; 1024];
-            if socket.read(&mut buf).await.is_ok() {
-                let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 15\r\n\r\n{\"status\":\"ok\"}";
-                let _ = socket.write_all(response.as_bytes()).await;
-            }
+            let Ok(Some(request)) = admin::read_request(&mut socket).await else {
+                return;
+            };
+            let response = if Admin::handles(&request.path) {
+                admin.handle(&request)
+            } else {
+                HttpResponse::ok(health_body(&connections, &kafka_producer, &redis))
+            };
+            let _ = socket.write_all(&response.to_bytes()).await;
         });
     }
 }
 
+/// Connection, backpressure, keepalive, Kafka delivery and Redis failover stats
+/// (status is "degraded" while the Redis primary misses probes)
+fn health_body(
+    connections: &SharedConnectionManager,
+    kafka_producer: &SharedKafkaProducer,
+    redis: &SharedRedisManager,
+) -> serde_json::Value {
+    let stats = connections.stats();
+    let kafka = kafka_producer.resilience();
+    let status = if redis.failover().is_healthy() { "ok" } else { "degraded" };
+
+    serde_json::json!({
+        "status": status,
+        "connections": stats.total_connections,
+        "users": stats.unique_users,
+        "rooms": stats.active_rooms,
+        "slow_connections": stats.slow_connections,
+        "dropped_messages": stats.dropped_messages,
+        "stall_disconnects": stats.stall_disconnects,
+        "pings_sent": stats.pings_sent,
+        "idle_evictions": stats.idle_evictions,
+        "kafka": {
+            "sent": kafka.metrics().sent(),
+            "retries": kafka.metrics().retries(),
+            "failed": kafka.metrics().failed(),
+            "buffered": kafka.buffered_len(),
+            "breaker": kafka.breaker_state().as_str(),
+            "near_limit": kafka.size_metrics().near_limit(),
+            "oversized": kafka.size_metrics().rejected(),
+            "largest_payload_bytes": kafka.size_metrics().largest_bytes(),
+            "max_payload_bytes": kafka.size_limits().max_bytes,
+            "brokers": kafka_producer.brokers().health(),
+        },
+        "redis": redis.failover().health(),
+    })
+}
+
 /// Wait for shutdown signal (Ctrl+C or SIGTERM)
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -143,13 +209,5 @@ async fn shutdown_signal() {
 
 /// Initialize tracing subscriber
 fn init_tracing() {
-    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
-
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,ws_gateway=debug"));
-
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    logging::init("ws_gateway", "info,ws_gateway=debug");
 }
@@ -0,0 +1,135 @@
+//! Read-only maintenance notices
+//!
+//! blazing_sun admins switch read-only maintenance on and off for every
+//! service through Redis (see the `maintenance_mode` crate). The gateway
+//! polls the flag and tells every connected client with
+//! `system.maintenance_notice` when it changes; clients that authenticate
+//! while it is on get the notice right away. `MAINTENANCE_MODE=true` forces
+//! it on for this instance.
+
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use maintenance_mode::Notice;
+use tracing::{info, warn};
+
+use crate::connection::SharedConnectionManager;
+use crate::protocol::ServerMessage;
+use crate::redis_client::SharedRedisManager;
+
+/// The maintenance window as last seen by this instance
+#[derive(Default)]
+pub struct MaintenanceWatch {
+    current: RwLock<Option<Notice>>,
+}
+
+pub type SharedMaintenanceWatch = Arc<MaintenanceWatch>;
+
+impl MaintenanceWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Poll the flag every `maintenance_mode::REFRESH_INTERVAL` and broadcast
+    /// its changes; a failed read keeps the last state
+    pub fn start(
+        self: &Arc<Self>,
+        redis: SharedRedisManager,
+        connections: SharedConnectionManager,
+    ) {
+        let watch = self.clone();
+        let forced = maintenance_mode::env_override();
+        if forced.is_some() {
+            warn!(
+                "MAINTENANCE_MODE is set - clients are told about maintenance until it is removed"
+            );
+        }
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(maintenance_mode::REFRESH_INTERVAL);
+            loop {
+                ticker.tick().await;
+
+                let notice = match &forced {
+                    Some(forced) => Some(forced.clone()),
+                    None => match redis.get_maintenance().await {
+                        Ok(notice) => notice,
+                        Err(e) => {
+                            warn!("Failed to read maintenance mode: {}", e);
+                            continue;
+                        }
+                    },
+                };
+
+                if let Some(message) = watch.update(notice) {
+                    let delivered = connections.broadcast(message);
+                    info!(delivered, "Maintenance notice broadcast");
+                }
+            }
+        });
+    }
+
+    /// The notice for a client that just authenticated, while maintenance is on
+    pub fn notice(&self) -> Option<ServerMessage> {
+        let current = self.current.read().unwrap();
+        current.as_ref().map(|notice| message(Some(notice)))
+    }
+
+    /// Store the latest window; the message to broadcast when it changed
+    fn update(&self, notice: Option<Notice>) -> Option<ServerMessage> {
+        let mut current = self.current.write().unwrap();
+        if *current == notice {
+            return None;
+        }
+
+        match &notice {
+            Some(notice) => info!(ends_at = ?notice.ends_at, "Maintenance mode started"),
+            None => info!("Maintenance mode ended"),
+        }
+        let message = message(notice.as_ref());
+        *current = notice;
+        Some(message)
+    }
+}
+
+/// `system.maintenance_notice` for a window, or for its end
+fn message(notice: Option<&Notice>) -> ServerMessage {
+    ServerMessage::MaintenanceNotice {
+        active: notice.is_some(),
+        message: notice.map(|notice| notice.message.clone()),
+        ends_at: notice.and_then(|notice| notice.ends_at),
+        timestamp: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changes_are_broadcast() {
+        let watch = MaintenanceWatch::new();
+        assert!(watch.update(None).is_none());
+        assert!(watch.notice().is_none());
+
+        let notice = Notice::new(Some("Back soon".to_string()), None);
+        let started = watch.update(Some(notice.clone())).unwrap();
+        assert!(matches!(
+            started,
+            ServerMessage::MaintenanceNotice { active: true, message: Some(ref m), .. } if m == "Back soon"
+        ));
+        assert!(watch.update(Some(notice)).is_none());
+        assert!(watch.notice().is_some());
+
+        let ended = watch.update(None).unwrap();
+        assert!(matches!(
+            ended,
+            ServerMessage::MaintenanceNotice {
+                active: false,
+                message: None,
+                ..
+            }
+        ));
+        assert!(watch.notice().is_none());
+    }
+}
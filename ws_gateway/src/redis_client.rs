@@ -2,14 +2,16 @@
 //!
 //! Handles all ephemeral state storage in Redis.
 
-use redis::{aio::ConnectionManager, AsyncCommands, Client};
+use redis::{aio::ConnectionManager, AsyncCommands};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use tracing::{debug, error, info};
 
-use crate::error::{GatewayError, GatewayResult};
+use crate::redis_failover::{FailoverSettings, RedisFailover};
+
+use thiserror::Error;
 
 /// Redis key prefixes
 pub mod keys {
@@ -25,6 +27,23 @@ pub mod keys {
     pub const GAME_SPECTATORS: &str = "game:spectators:";
     pub const GAME_TURN: &str = "game:turn:";
     pub const RECONNECT: &str = "reconnect:";
+    pub const OFFLINE_EVENTS: &str = "offline:events:";
+    /// Hash of a user's flood strikes and mute (read by blazing_sun admins)
+    pub const FLOOD_PENALTY: &str = "flood:penalty:";
+    /// Sorted set of penalized users, scored by when their record expires (ms)
+    pub const FLOOD_PENALIZED: &str = "flood:penalized";
+    /// Count of a user's game command in one limit window
+    pub const COMMAND_LIMIT: &str = "cmd:limit:";
+    /// Set of users a user blocked (mirrored by blazing_sun)
+    pub const CHAT_BLOCKS: &str = "chat:blocks:";
+    /// Set of users who blocked a user (mirrored by blazing_sun)
+    pub const CHAT_BLOCKED_BY: &str = "chat:blocked_by:";
+    /// Hash of a user's unread direct messages per conversation (mirrored by blazing_sun)
+    pub const CHAT_UNREAD: &str = "chat:unread:";
+    /// Sign-in sessions revoked while their tokens are still valid (set by blazing_sun)
+    pub const REVOKED_SESSION: &str = "auth:revoked_session:";
+    /// JSON of a user's display settings (mirrored by blazing_sun)
+    pub const USER_SETTINGS: &str = "user:settings:";
 }
 
 /// TTL values in seconds
@@ -36,6 +55,55 @@ pub mod ttl {
     pub const RECONNECT: u64 = 300;           // 5 minutes
 }
 
+/// Redis client errors
+#[derive(Error, Debug)]
+pub enum RedisClientError {
+    #[error("Invalid Redis URL: {0}")]
+    InvalidUrl(#[source] redis::RedisError),
+
+    #[error("Failed to connect to Redis: {0}")]
+    Connect(#[source] redis::RedisError),
+
+    #[error("Redis command on {key} failed: {source}")]
+    Command {
+        key: String,
+        #[source]
+        source: redis::RedisError,
+    },
+
+    #[error("Failed to encode or decode Redis value: {0}")]
+    Codec(#[from] serde_json::Error),
+}
+
+impl RedisClientError {
+    /// Whether the failure is likely to clear up on its own (dropped connection, timeout)
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Connect(e) | Self::Command { source: e, .. } => {
+                e.is_connection_dropped() || e.is_io_error() || e.is_timeout()
+            }
+            Self::InvalidUrl(_) | Self::Codec(_) => false,
+        }
+    }
+}
+
+/// Result type alias for Redis operations
+pub type RedisResult<T> = Result<T, RedisClientError>;
+
+/// Attach the Redis key to a failed command
+trait KeyContext<T> {
+    fn context(self, key: &str) -> RedisResult<T>;
+}
+
+impl<T> KeyContext<T> for Result<T, redis::RedisError> {
+    fn context(self, key: &str) -> RedisResult<T> {
+        self.map_err(|source| RedisClientError::Command {
+            key: key.to_string(),
+            source,
+        })
+    }
+}
+
 /// Socket session data
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SocketSession {
@@ -85,23 +153,38 @@ pub enum RoomType {
 
 /// Redis manager for WebSocket Gateway
 pub struct RedisManager {
-    conn: ConnectionManager,
+    failover: Arc<RedisFailover>,
 }
 
 impl RedisManager {
-    /// Create a new Redis manager
-    pub async fn new(redis_url: &str) -> GatewayResult<Self> {
+    /// Create a new Redis manager on the first reachable primary and start
+    /// probing it (see `redis_failover`)
+    pub async fn new(settings: FailoverSettings) -> RedisResult<Self> {
         info!("Connecting to Redis...");
 
-        let client = Client::open(redis_url)
-            .map_err(|e| GatewayError::Internal(format!("Failed to create Redis client: {}", e)))?;
-
-        let conn = ConnectionManager::new(client)
-            .await
-            .map_err(|e| GatewayError::Internal(format!("Failed to connect to Redis: {}", e)))?;
+        let failover = Arc::new(RedisFailover::connect(settings).await?);
+        failover.start_probe();
 
         info!("Connected to Redis");
-        Ok(Self { conn })
+        Ok(Self { failover })
+    }
+
+    /// Failover state (used by the health endpoint)
+    pub fn failover(&self) -> &RedisFailover {
+        &self.failover
+    }
+
+    /// Handle for one operation
+    ///
+    /// With the `chaos` feature an injected Redis fault fails here as a
+    /// dropped connection, which callers treat as transient.
+    async fn connection(&self) -> RedisResult<ConnectionManager> {
+        if let Err(fault) = fault_injection::check(fault_injection::Target::Redis).await {
+            let dropped = std::io::Error::new(std::io::ErrorKind::ConnectionReset, fault.to_string());
+            return Err(RedisClientError::Connect(dropped.into()));
+        }
+
+        Ok(self.failover.connection())
     }
 
     // ========================================================================
@@ -115,8 +198,8 @@ impl RedisManager {
         user_id: &str,
         username: &str,
         roles: Vec<String>,
-    ) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    ) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let now = Utc::now();
 
         let session = SocketSession {
@@ -132,25 +215,25 @@ impl RedisManager {
         let user_sockets_key = format!("{}{}", keys::USER_SOCKETS, user_id);
 
         // Store socket session
-        conn.set_ex::<_, _, ()>(&socket_key, &session_json, ttl::SOCKET).await?;
+        conn.set_ex::<_, _, ()>(&socket_key, &session_json, ttl::SOCKET).await.context(&socket_key)?;
 
         // Add socket to user's socket set
-        conn.sadd::<_, _, ()>(&user_sockets_key, socket_id).await?;
+        conn.sadd::<_, _, ()>(&user_sockets_key, socket_id).await.context(&user_sockets_key)?;
 
         // Add user to online set
-        conn.sadd::<_, _, ()>(keys::PRESENCE_ONLINE, user_id).await?;
+        conn.sadd::<_, _, ()>(keys::PRESENCE_ONLINE, user_id).await.context(keys::PRESENCE_ONLINE)?;
 
         debug!("Registered socket {} for user {}", socket_id, user_id);
         Ok(())
     }
 
     /// Unregister a socket connection
-    pub async fn unregister_socket(&self, socket_id: &str) -> GatewayResult<Option<String>> {
-        let mut conn = self.conn.clone();
+    pub async fn unregister_socket(&self, socket_id: &str) -> RedisResult<Option<String>> {
+        let mut conn = self.connection().await?;
         let socket_key = format!("{}{}", keys::SOCKET, socket_id);
 
         // Get session to find user_id
-        let session_json: Option<String> = conn.get(&socket_key).await?;
+        let session_json: Option<String> = conn.get(&socket_key).await.context(&socket_key)?;
 
         if let Some(json) = session_json {
             let session: SocketSession = serde_json::from_str(&json)?;
@@ -158,16 +241,16 @@ impl RedisManager {
             let user_sockets_key = format!("{}{}", keys::USER_SOCKETS, user_id);
 
             // Remove socket from user's set
-            conn.srem::<_, _, ()>(&user_sockets_key, socket_id).await?;
+            conn.srem::<_, _, ()>(&user_sockets_key, socket_id).await.context(&user_sockets_key)?;
 
             // Delete socket session
-            conn.del::<_, ()>(&socket_key).await?;
+            conn.del::<_, ()>(&socket_key).await.context(&socket_key)?;
 
             // Check if user has other sockets
-            let remaining: i64 = conn.scard(&user_sockets_key).await?;
+            let remaining: i64 = conn.scard(&user_sockets_key).await.context(&user_sockets_key)?;
             if remaining == 0 {
                 // Remove from online set
-                conn.srem::<_, _, ()>(keys::PRESENCE_ONLINE, &user_id).await?;
+                conn.srem::<_, _, ()>(keys::PRESENCE_ONLINE, &user_id).await.context(keys::PRESENCE_ONLINE)?;
             }
 
             debug!("Unregistered socket {} for user {}", socket_id, user_id);
@@ -178,11 +261,11 @@ impl RedisManager {
     }
 
     /// Get socket session
-    pub async fn get_socket_session(&self, socket_id: &str) -> GatewayResult<Option<SocketSession>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_socket_session(&self, socket_id: &str) -> RedisResult<Option<SocketSession>> {
+        let mut conn = self.connection().await?;
         let socket_key = format!("{}{}", keys::SOCKET, socket_id);
 
-        let session_json: Option<String> = conn.get(&socket_key).await?;
+        let session_json: Option<String> = conn.get(&socket_key).await.context(&socket_key)?;
 
         if let Some(json) = session_json {
             let session: SocketSession = serde_json::from_str(&json)?;
@@ -193,12 +276,12 @@ impl RedisManager {
     }
 
     /// Update last seen for a socket (heartbeat)
-    pub async fn update_heartbeat(&self, socket_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn update_heartbeat(&self, socket_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let socket_key = format!("{}{}", keys::SOCKET, socket_id);
 
         // Get current session
-        let session_json: Option<String> = conn.get(&socket_key).await?;
+        let session_json: Option<String> = conn.get(&socket_key).await.context(&socket_key)?;
 
         if let Some(json) = session_json {
             let mut session: SocketSession = serde_json::from_str(&json)?;
@@ -206,7 +289,7 @@ impl RedisManager {
             let updated_json = serde_json::to_string(&session)?;
 
             // Update with new TTL
-            conn.set_ex::<_, _, ()>(&socket_key, &updated_json, ttl::SOCKET).await?;
+            conn.set_ex::<_, _, ()>(&socket_key, &updated_json, ttl::SOCKET).await.context(&socket_key)?;
 
             // Update user presence
             let presence_key = format!("{}{}", keys::USER_PRESENCE, session.user_id);
@@ -217,7 +300,7 @@ impl RedisManager {
                 current_game: None,
             };
             let presence_json = serde_json::to_string(&presence)?;
-            conn.set_ex::<_, _, ()>(&presence_key, &presence_json, ttl::PRESENCE).await?;
+            conn.set_ex::<_, _, ()>(&presence_key, &presence_json, ttl::PRESENCE).await.context(&presence_key)?;
         }
 
         Ok(())
@@ -228,25 +311,25 @@ impl RedisManager {
     // ========================================================================
 
     /// Get all socket IDs for a user
-    pub async fn get_user_sockets(&self, user_id: &str) -> GatewayResult<HashSet<String>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_user_sockets(&self, user_id: &str) -> RedisResult<HashSet<String>> {
+        let mut conn = self.connection().await?;
         let user_sockets_key = format!("{}{}", keys::USER_SOCKETS, user_id);
 
-        let sockets: HashSet<String> = conn.smembers(&user_sockets_key).await?;
+        let sockets: HashSet<String> = conn.smembers(&user_sockets_key).await.context(&user_sockets_key)?;
         Ok(sockets)
     }
 
     /// Check if user is online
-    pub async fn is_user_online(&self, user_id: &str) -> GatewayResult<bool> {
-        let mut conn = self.conn.clone();
-        let is_online: bool = conn.sismember(keys::PRESENCE_ONLINE, user_id).await?;
+    pub async fn is_user_online(&self, user_id: &str) -> RedisResult<bool> {
+        let mut conn = self.connection().await?;
+        let is_online: bool = conn.sismember(keys::PRESENCE_ONLINE, user_id).await.context(keys::PRESENCE_ONLINE)?;
         Ok(is_online)
     }
 
     /// Get all online users
-    pub async fn get_online_users(&self) -> GatewayResult<HashSet<String>> {
-        let mut conn = self.conn.clone();
-        let users: HashSet<String> = conn.smembers(keys::PRESENCE_ONLINE).await?;
+    pub async fn get_online_users(&self) -> RedisResult<HashSet<String>> {
+        let mut conn = self.connection().await?;
+        let users: HashSet<String> = conn.smembers(keys::PRESENCE_ONLINE).await.context(keys::PRESENCE_ONLINE)?;
         Ok(users)
     }
 
@@ -262,8 +345,8 @@ impl RedisManager {
         name: &str,
         created_by: &str,
         game_type: Option<String>,
-    ) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    ) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
 
         let room_info = RoomInfo {
             room_type,
@@ -276,62 +359,62 @@ impl RedisManager {
 
         let room_key = format!("{}{}", keys::ROOM_INFO, room_id);
         let room_json = serde_json::to_string(&room_info)?;
-        conn.set_ex::<_, _, ()>(&room_key, &room_json, ttl::ROOM_INFO).await?;
+        conn.set_ex::<_, _, ()>(&room_key, &room_json, ttl::ROOM_INFO).await.context(&room_key)?;
 
         debug!("Created room {}: {}", room_id, name);
         Ok(())
     }
 
     /// Add user to room
-    pub async fn join_room(&self, room_id: &str, user_id: &str, socket_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn join_room(&self, room_id: &str, user_id: &str, socket_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
 
         let room_users_key = format!("{}{}", keys::ROOM_USERS, room_id);
         let room_sockets_key = format!("{}{}", keys::ROOM_SOCKETS, room_id);
 
-        conn.sadd::<_, _, ()>(&room_users_key, user_id).await?;
-        conn.sadd::<_, _, ()>(&room_sockets_key, socket_id).await?;
+        conn.sadd::<_, _, ()>(&room_users_key, user_id).await.context(&room_users_key)?;
+        conn.sadd::<_, _, ()>(&room_sockets_key, socket_id).await.context(&room_sockets_key)?;
 
         debug!("User {} joined room {}", user_id, room_id);
         Ok(())
     }
 
     /// Remove user from room
-    pub async fn leave_room(&self, room_id: &str, user_id: &str, socket_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn leave_room(&self, room_id: &str, user_id: &str, socket_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
 
         let room_users_key = format!("{}{}", keys::ROOM_USERS, room_id);
         let room_sockets_key = format!("{}{}", keys::ROOM_SOCKETS, room_id);
 
-        conn.srem::<_, _, ()>(&room_users_key, user_id).await?;
-        conn.srem::<_, _, ()>(&room_sockets_key, socket_id).await?;
+        conn.srem::<_, _, ()>(&room_users_key, user_id).await.context(&room_users_key)?;
+        conn.srem::<_, _, ()>(&room_sockets_key, socket_id).await.context(&room_sockets_key)?;
 
         debug!("User {} left room {}", user_id, room_id);
         Ok(())
     }
 
     /// Get all sockets in a room
-    pub async fn get_room_sockets(&self, room_id: &str) -> GatewayResult<HashSet<String>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_room_sockets(&self, room_id: &str) -> RedisResult<HashSet<String>> {
+        let mut conn = self.connection().await?;
         let room_sockets_key = format!("{}{}", keys::ROOM_SOCKETS, room_id);
-        let sockets: HashSet<String> = conn.smembers(&room_sockets_key).await?;
+        let sockets: HashSet<String> = conn.smembers(&room_sockets_key).await.context(&room_sockets_key)?;
         Ok(sockets)
     }
 
     /// Get all users in a room
-    pub async fn get_room_users(&self, room_id: &str) -> GatewayResult<HashSet<String>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_room_users(&self, room_id: &str) -> RedisResult<HashSet<String>> {
+        let mut conn = self.connection().await?;
         let room_users_key = format!("{}{}", keys::ROOM_USERS, room_id);
-        let users: HashSet<String> = conn.smembers(&room_users_key).await?;
+        let users: HashSet<String> = conn.smembers(&room_users_key).await.context(&room_users_key)?;
         Ok(users)
     }
 
     /// Get room info
-    pub async fn get_room_info(&self, room_id: &str) -> GatewayResult<Option<RoomInfo>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_room_info(&self, room_id: &str) -> RedisResult<Option<RoomInfo>> {
+        let mut conn = self.connection().await?;
         let room_key = format!("{}{}", keys::ROOM_INFO, room_id);
 
-        let room_json: Option<String> = conn.get(&room_key).await?;
+        let room_json: Option<String> = conn.get(&room_key).await.context(&room_key)?;
         if let Some(json) = room_json {
             let info: RoomInfo = serde_json::from_str(&json)?;
             return Ok(Some(info));
@@ -344,59 +427,67 @@ impl RedisManager {
     // Game-Specific Operations
     // ========================================================================
 
+    /// Game type blazing_sun recorded for a room
+    pub async fn get_room_game_type(&self, room_id: &str) -> RedisResult<Option<String>> {
+        let mut conn = self.connection().await?;
+        let game_type_key = games_routing::room_game_type_key(room_id);
+        let game_type: Option<String> = conn.get(&game_type_key).await.context(&game_type_key)?;
+        Ok(game_type)
+    }
+
     /// Add player to game
-    pub async fn add_game_player(&self, game_id: &str, user_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn add_game_player(&self, game_id: &str, user_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let game_players_key = format!("{}{}", keys::GAME_PLAYERS, game_id);
-        conn.rpush::<_, _, ()>(&game_players_key, user_id).await?;
+        conn.rpush::<_, _, ()>(&game_players_key, user_id).await.context(&game_players_key)?;
         Ok(())
     }
 
     /// Get game players (ordered)
-    pub async fn get_game_players(&self, game_id: &str) -> GatewayResult<Vec<String>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_game_players(&self, game_id: &str) -> RedisResult<Vec<String>> {
+        let mut conn = self.connection().await?;
         let game_players_key = format!("{}{}", keys::GAME_PLAYERS, game_id);
-        let players: Vec<String> = conn.lrange(&game_players_key, 0, -1).await?;
+        let players: Vec<String> = conn.lrange(&game_players_key, 0, -1).await.context(&game_players_key)?;
         Ok(players)
     }
 
     /// Add spectator to game
-    pub async fn add_game_spectator(&self, game_id: &str, user_id: &str, socket_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn add_game_spectator(&self, game_id: &str, user_id: &str, socket_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let spectators_key = format!("{}{}", keys::GAME_SPECTATORS, game_id);
-        conn.sadd::<_, _, ()>(&spectators_key, format!("{}:{}", user_id, socket_id)).await?;
+        conn.sadd::<_, _, ()>(&spectators_key, format!("{}:{}", user_id, socket_id)).await.context(&spectators_key)?;
         Ok(())
     }
 
     /// Remove spectator from game
-    pub async fn remove_game_spectator(&self, game_id: &str, user_id: &str, socket_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn remove_game_spectator(&self, game_id: &str, user_id: &str, socket_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let spectators_key = format!("{}{}", keys::GAME_SPECTATORS, game_id);
-        conn.srem::<_, _, ()>(&spectators_key, format!("{}:{}", user_id, socket_id)).await?;
+        conn.srem::<_, _, ()>(&spectators_key, format!("{}:{}", user_id, socket_id)).await.context(&spectators_key)?;
         Ok(())
     }
 
     /// Get spectator count for game
-    pub async fn get_spectator_count(&self, game_id: &str) -> GatewayResult<u32> {
-        let mut conn = self.conn.clone();
+    pub async fn get_spectator_count(&self, game_id: &str) -> RedisResult<u32> {
+        let mut conn = self.connection().await?;
         let spectators_key = format!("{}{}", keys::GAME_SPECTATORS, game_id);
-        let count: u32 = conn.scard(&spectators_key).await?;
+        let count: u32 = conn.scard(&spectators_key).await.context(&spectators_key)?;
         Ok(count)
     }
 
     /// Set current turn for a game
-    pub async fn set_game_turn(&self, game_id: &str, user_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn set_game_turn(&self, game_id: &str, user_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let turn_key = format!("{}{}", keys::GAME_TURN, game_id);
-        conn.set_ex::<_, _, ()>(&turn_key, user_id, ttl::GAME_STATE).await?;
+        conn.set_ex::<_, _, ()>(&turn_key, user_id, ttl::GAME_STATE).await.context(&turn_key)?;
         Ok(())
     }
 
     /// Get current turn for a game
-    pub async fn get_game_turn(&self, game_id: &str) -> GatewayResult<Option<String>> {
-        let mut conn = self.conn.clone();
+    pub async fn get_game_turn(&self, game_id: &str) -> RedisResult<Option<String>> {
+        let mut conn = self.connection().await?;
         let turn_key = format!("{}{}", keys::GAME_TURN, game_id);
-        let turn: Option<String> = conn.get(&turn_key).await?;
+        let turn: Option<String> = conn.get(&turn_key).await.context(&turn_key)?;
         Ok(turn)
     }
 
@@ -410,11 +501,11 @@ impl RedisManager {
         user_id: &str,
         game_id: &str,
         state: &serde_json::Value,
-    ) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    ) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let reconnect_key = format!("{}{}:{}", keys::RECONNECT, user_id, game_id);
         let state_json = serde_json::to_string(state)?;
-        conn.set_ex::<_, _, ()>(&reconnect_key, &state_json, ttl::RECONNECT).await?;
+        conn.set_ex::<_, _, ()>(&reconnect_key, &state_json, ttl::RECONNECT).await.context(&reconnect_key)?;
         Ok(())
     }
 
@@ -423,10 +514,10 @@ impl RedisManager {
         &self,
         user_id: &str,
         game_id: &str,
-    ) -> GatewayResult<Option<serde_json::Value>> {
-        let mut conn = self.conn.clone();
+    ) -> RedisResult<Option<serde_json::Value>> {
+        let mut conn = self.connection().await?;
         let reconnect_key = format!("{}{}:{}", keys::RECONNECT, user_id, game_id);
-        let state_json: Option<String> = conn.get(&reconnect_key).await?;
+        let state_json: Option<String> = conn.get(&reconnect_key).await.context(&reconnect_key)?;
 
         if let Some(json) = state_json {
             let state: serde_json::Value = serde_json::from_str(&json)?;
@@ -437,13 +528,261 @@ impl RedisManager {
     }
 
     /// Clear reconnection data
-    pub async fn clear_reconnection_data(&self, user_id: &str, game_id: &str) -> GatewayResult<()> {
-        let mut conn = self.conn.clone();
+    pub async fn clear_reconnection_data(&self, user_id: &str, game_id: &str) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
         let reconnect_key = format!("{}{}:{}", keys::RECONNECT, user_id, game_id);
-        conn.del::<_, ()>(&reconnect_key).await?;
+        conn.del::<_, ()>(&reconnect_key).await.context(&reconnect_key)?;
         Ok(())
     }
+
+    // ========================================================================
+    // Flood Penalties
+    // ========================================================================
+
+    /// Count a flood strike for a user and return their strikes; the record
+    /// expires `ttl_secs` after the latest strike
+    pub async fn record_flood_strike(&self, user_id: &str, ttl_secs: u64) -> RedisResult<u32> {
+        let mut conn = self.connection().await?;
+        let penalty_key = format!("{}{}", keys::FLOOD_PENALTY, user_id);
+        let now_ms = Utc::now().timestamp_millis();
+
+        let (strikes,): (u32,) = redis::pipe()
+            .atomic()
+            .hincr(&penalty_key, "strikes", 1)
+            .hset(&penalty_key, "last_strike_at", now_ms)
+            .ignore()
+            .expire(&penalty_key, ttl_secs as i64)
+            .ignore()
+            .zadd(keys::FLOOD_PENALIZED, user_id, now_ms + (ttl_secs as i64) * 1000)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .context(&penalty_key)?;
+
+        Ok(strikes)
+    }
+
+    /// Mute a user until `until`
+    pub async fn set_flood_mute(&self, user_id: &str, until: DateTime<Utc>) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
+        let penalty_key = format!("{}{}", keys::FLOOD_PENALTY, user_id);
+        conn.hset::<_, _, _, ()>(&penalty_key, "muted_until", until.timestamp_millis())
+            .await
+            .context(&penalty_key)?;
+        Ok(())
+    }
+
+    /// When a user's mute ends; `None` when they are not muted (or an admin
+    /// cleared their penalties)
+    pub async fn get_flood_mute(&self, user_id: &str) -> RedisResult<Option<DateTime<Utc>>> {
+        let mut conn = self.connection().await?;
+        let penalty_key = format!("{}{}", keys::FLOOD_PENALTY, user_id);
+        let until: Option<i64> = conn.hget(&penalty_key, "muted_until").await.context(&penalty_key)?;
+
+        Ok(until
+            .and_then(DateTime::from_timestamp_millis)
+            .filter(|until| *until > Utc::now()))
+    }
+
+    /// Count one use of a rate-limited game command in window `window` and
+    /// return the user's uses in it; the count expires with the window
+    pub async fn count_command_use(
+        &self,
+        user_id: &str,
+        command: &str,
+        window: u64,
+        ttl_ms: u64,
+    ) -> RedisResult<u32> {
+        let mut conn = self.connection().await?;
+        let limit_key = format!("{}{}:{}:{}", keys::COMMAND_LIMIT, user_id, command, window);
+
+        let (uses,): (u32,) = redis::pipe()
+            .atomic()
+            .incr(&limit_key, 1)
+            .pexpire(&limit_key, ttl_ms as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .context(&limit_key)?;
+
+        Ok(uses)
+    }
+
+    // ========================================================================
+    // Chat Blocks
+    // ========================================================================
+
+    /// Whether `blocker_id` blocked `blocked_id`
+    pub async fn is_blocked(&self, blocker_id: &str, blocked_id: &str) -> RedisResult<bool> {
+        let mut conn = self.connection().await?;
+        let blocks_key = format!("{}{}", keys::CHAT_BLOCKS, blocker_id);
+        let blocked: bool = conn.sismember(&blocks_key, blocked_id).await.context(&blocks_key)?;
+        Ok(blocked)
+    }
+
+    /// Users who blocked `user_id`
+    pub async fn get_blockers(&self, user_id: &str) -> RedisResult<HashSet<String>> {
+        let mut conn = self.connection().await?;
+        let blocked_by_key = format!("{}{}", keys::CHAT_BLOCKED_BY, user_id);
+        let blockers: HashSet<String> = conn.smembers(&blocked_by_key).await.context(&blocked_by_key)?;
+        Ok(blockers)
+    }
+
+    /// A user's unread direct messages per conversation id
+    pub async fn get_unread_counts(&self, user_id: &str) -> RedisResult<BTreeMap<String, u32>> {
+        let mut conn = self.connection().await?;
+        let unread_key = format!("{}{}", keys::CHAT_UNREAD, user_id);
+        let counts: BTreeMap<String, u32> = conn.hgetall(&unread_key).await.context(&unread_key)?;
+        Ok(counts)
+    }
+
+    // ========================================================================
+    // Revoked Sessions
+    // ========================================================================
+
+    /// Whether blazing_sun revoked the sign-in session a token was issued for
+    pub async fn is_session_revoked(&self, session_id: &str) -> RedisResult<bool> {
+        let mut conn = self.connection().await?;
+        let revoked_key = format!("{}{}", keys::REVOKED_SESSION, session_id);
+        let revoked: bool = conn.exists(&revoked_key).await.context(&revoked_key)?;
+        Ok(revoked)
+    }
+
+    // ========================================================================
+    // User Settings
+    // ========================================================================
+
+    /// A user's display settings, if blazing_sun mirrored them
+    pub async fn get_user_settings(&self, user_id: &str) -> RedisResult<Option<serde_json::Value>> {
+        let mut conn = self.connection().await?;
+        let settings_key = format!("{}{}", keys::USER_SETTINGS, user_id);
+        let settings_json: Option<String> = conn.get(&settings_key).await.context(&settings_key)?;
+
+        if let Some(json) = settings_json {
+            let settings: serde_json::Value = serde_json::from_str(&json)?;
+            return Ok(Some(settings));
+        }
+
+        Ok(None)
+    }
+
+    // ========================================================================
+    // Maintenance Mode
+    // ========================================================================
+
+    /// The read-only maintenance window set by blazing_sun admins, if any
+    pub async fn get_maintenance(&self) -> RedisResult<Option<maintenance_mode::Notice>> {
+        let mut conn = self.connection().await?;
+        maintenance_mode::read(&mut conn).await.context(maintenance_mode::KEY)
+    }
+
+    // ========================================================================
+    // Offline Event Buffer
+    // ========================================================================
+
+    /// Buffer an event for a user without active connections.
+    ///
+    /// Each user has their own capped stream, so buffers are spread across the
+    /// keyspace (and cluster slots) instead of piling up in one hot key.
+    pub async fn buffer_offline_event(
+        &self,
+        user_id: &str,
+        event_json: &str,
+        max_events: usize,
+        window_secs: u64,
+    ) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
+        let stream_key = offline_events_key(user_id);
+
+        redis::pipe()
+            .cmd("XADD")
+            .arg(&stream_key)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(max_events)
+            .arg("*")
+            .arg("event")
+            .arg(event_json)
+            .ignore()
+            .expire(&stream_key, window_secs as i64)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .context(&stream_key)?;
+
+        Ok(())
+    }
+
+    /// Take all buffered events for a user that are still inside the window,
+    /// oldest first, and clear the buffer.
+    pub async fn drain_offline_events(&self, user_id: &str, window_secs: u64) -> RedisResult<Vec<String>> {
+        let mut conn = self.connection().await?;
+        let stream_key = offline_events_key(user_id);
+        let min_id = offline_window_start(Utc::now().timestamp_millis(), window_secs);
+
+        let (entries,): (Vec<(String, Vec<String>)>,) = redis::pipe()
+            .atomic()
+            .cmd("XRANGE")
+            .arg(&stream_key)
+            .arg(&min_id)
+            .arg("+")
+            .del(&stream_key)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .context(&stream_key)?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(_, fields)| stream_field(fields, "event"))
+            .collect())
+    }
+}
+
+/// Stream key holding a user's offline events (hash-tagged by user)
+fn offline_events_key(user_id: &str) -> String {
+    format!("{}{{{}}}", keys::OFFLINE_EVENTS, user_id)
+}
+
+/// Lowest stream ID still inside the delivery window
+fn offline_window_start(now_ms: i64, window_secs: u64) -> String {
+    let start = now_ms.saturating_sub(window_secs.saturating_mul(1000) as i64).max(0);
+    format!("{}-0", start)
+}
+
+/// Value of `field` in a flat `[field, value, ...]` stream entry
+fn stream_field(fields: Vec<String>, field: &str) -> Option<String> {
+    let mut iter = fields.into_iter();
+    while let (Some(name), Some(value)) = (iter.next(), iter.next()) {
+        if name == field {
+            return Some(value);
+        }
+    }
+    None
 }
 
 /// Shared Redis manager
 pub type SharedRedisManager = Arc<RedisManager>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_keys_are_hash_tagged_per_user() {
+        assert_eq!(offline_events_key("42"), "offline:events:{42}");
+    }
+
+    #[test]
+    fn window_start_is_clamped_at_zero() {
+        assert_eq!(offline_window_start(10_000, 5), "5000-0");
+        assert_eq!(offline_window_start(1_000, 5), "0-0");
+    }
+
+    #[test]
+    fn stream_field_finds_value_by_name() {
+        let fields = vec!["other".to_string(), "x".to_string(), "event".to_string(), "{}".to_string()];
+        assert_eq!(stream_field(fields, "event").as_deref(), Some("{}"));
+        assert_eq!(stream_field(vec!["event".to_string()], "event"), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Room roles for host- and admin-only events
+//!
+//! Events with a `host` or `admins` audience (pending lobby joins, ban list
+//! changes) go only to the room's host, or to the host and the designated
+//! admin spectator. The gateway learns who they are from the game events it
+//! routes anyway: `room_created` and `room_state` carry the host,
+//! `room_state` and `admin_spectator_designated` the admin spectator, and a
+//! removed room is forgotten.
+//!
+//! A room the gateway has not seen a `room_state` for has no known roles, so
+//! its host- and admin-only events are dropped rather than sent to the room.
+
+use dashmap::DashMap;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::protocol::EventEnvelope;
+
+/// Who may see a room's restricted events
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Roles {
+    pub host_id: Option<String>,
+    pub admin_spectator_id: Option<String>,
+}
+
+impl Roles {
+    /// The host, when known
+    pub fn host(&self) -> HashSet<String> {
+        self.host_id.iter().cloned().collect()
+    }
+
+    /// The host and the admin spectator
+    pub fn admins(&self) -> HashSet<String> {
+        self.host_id
+            .iter()
+            .chain(self.admin_spectator_id.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Roles of every room this gateway routes events for
+#[derive(Default)]
+pub struct RoomRoles {
+    rooms: DashMap<String, Roles>,
+}
+
+impl RoomRoles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Roles of a room, when known
+    pub fn get(&self, room_id: &str) -> Option<Roles> {
+        self.rooms.get(room_id).map(|roles| roles.clone())
+    }
+
+    /// Update the roles from a routed game event
+    pub fn observe(&self, envelope: &EventEnvelope) {
+        let event_type = envelope.event_type.as_str();
+        let payload = &envelope.payload;
+
+        if is_game_event(event_type, "room_state") {
+            let Some(room) = payload.get("room") else {
+                return;
+            };
+            let Some(room_id) = room.get("room_id").and_then(Value::as_str) else {
+                return;
+            };
+            self.rooms.insert(
+                room_id.to_string(),
+                Roles {
+                    host_id: id(room.get("host_id")),
+                    admin_spectator_id: id(room.get("admin_spectator_id")),
+                },
+            );
+        } else if is_game_event(event_type, "room_created") {
+            if let Some(room_id) = payload.get("room_id").and_then(Value::as_str) {
+                self.rooms.entry(room_id.to_string()).or_default().host_id =
+                    id(payload.get("host_id"));
+            }
+        } else if is_game_event(event_type, "admin_spectator_designated") {
+            if let Some(room_id) = payload.get("room_id").and_then(Value::as_str) {
+                if let Some(mut roles) = self.rooms.get_mut(room_id) {
+                    roles.admin_spectator_id = id(payload.get("user_id"));
+                }
+            }
+        } else if is_game_event(event_type, "room_removed") {
+            if let Some(room_id) = payload.get("room_id").and_then(Value::as_str) {
+                self.rooms.remove(room_id);
+            }
+        }
+    }
+}
+
+/// `games.event.{name}` or a game-prefixed `games.event.{game}.{name}`
+fn is_game_event(event_type: &str, name: &str) -> bool {
+    event_type
+        .strip_prefix("games.event.")
+        .is_some_and(|rest| rest == name || rest.ends_with(&format!(".{}", name)))
+}
+
+/// User ids arrive as numbers from blazing_sun
+fn id(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{Actor, Audience, AudienceType};
+    use serde_json::json;
+
+    fn event(event_type: &str, payload: Value) -> EventEnvelope {
+        EventEnvelope::new(
+            event_type,
+            Actor {
+                user_id: "system".to_string(),
+                username: None,
+                roles: vec![],
+                impersonator_id: None,
+            },
+            Audience {
+                audience_type: AudienceType::User,
+                user_ids: vec!["7".to_string()],
+                room_id: None,
+                game_id: None,
+            },
+            payload,
+        )
+    }
+
+    #[test]
+    fn roles_follow_the_room_lifecycle() {
+        let roles = RoomRoles::new();
+        assert!(roles.get("r1").is_none());
+
+        roles.observe(&event(
+            "games.event.bigger_dice.room_created",
+            json!({ "room_id": "r1", "host_id": 7 }),
+        ));
+        assert_eq!(
+            roles.get("r1").unwrap().admins(),
+            HashSet::from(["7".to_string()])
+        );
+
+        roles.observe(&event(
+            "games.event.bigger_dice.room_state",
+            json!({ "room": { "room_id": "r1", "host_id": 7, "admin_spectator_id": 9 } }),
+        ));
+        let known = roles.get("r1").unwrap();
+        assert_eq!(known.host(), HashSet::from(["7".to_string()]));
+        assert_eq!(
+            known.admins(),
+            HashSet::from(["7".to_string(), "9".to_string()])
+        );
+
+        roles.observe(&event(
+            "games.event.admin_spectator_designated",
+            json!({ "room_id": "r1", "user_id": 11, "username": "eve" }),
+        ));
+        assert_eq!(
+            roles.get("r1").unwrap().admin_spectator_id.as_deref(),
+            Some("11")
+        );
+
+        roles.observe(&event(
+            "games.event.tic_tac_toe.room_removed",
+            json!({ "room_id": "r1", "room_name": "r", "reason": "host_left" }),
+        ));
+        assert!(roles.get("r1").is_none());
+    }
+
+    #[test]
+    fn other_events_leave_roles_alone() {
+        let roles = RoomRoles::new();
+        roles.observe(&event(
+            "games.event.room_state_requested",
+            json!({ "room": { "room_id": "r1", "host_id": 7 } }),
+        ));
+        roles.observe(&event(
+            "chat.event.room_state",
+            json!({ "room": { "room_id": "r2", "host_id": 7 } }),
+        ));
+        assert!(roles.rooms.is_empty());
+    }
+}
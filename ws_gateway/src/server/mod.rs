@@ -1,23 +1,125 @@
 //! WebSocket Server implementation
 
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use futures_util::{SinkExt, StreamExt};
 use tokio::net::TcpStream;
-use tokio::sync::mpsc;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
-use tracing::{debug, error, info, warn};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message};
+use tracing::{debug, error, info, warn, Instrument};
 use chrono::Utc;
+use uuid::Uuid;
+use kafka_producer::SizeLimits;
 
 use crate::auth::{create_validator, SharedJwtValidator};
-use crate::config::Config;
-use crate::connection::{Connection, ConnectionManager, ConnectionState, SharedConnectionManager};
+use crate::config::{Config, KafkaTopics};
+use crate::connection::{
+    BatchPolicy, CommandLimits, Connection, ConnectionActivity, ConnectionManager, ConnectionState, FloodPolicy,
+    Keepalive, KeepaliveAction, OutboundQueue, Penalty, SharedConnectionManager,
+};
 use crate::error::{GatewayError, GatewayResult};
-use crate::kafka::{KafkaConsumer, KafkaProducer, SharedKafkaProducer};
+use crate::kafka::{routing, BrokerSettings, KafkaConsumer, KafkaProducer, SharedKafkaProducer};
+use crate::maintenance::{MaintenanceWatch, SharedMaintenanceWatch};
+use crate::room_roles::RoomRoles;
 use crate::protocol::{
-    Actor, Audience, AudienceType, ClientMessage, EventEnvelope, ServerMessage,
+    negotiate, Actor, Audience, AudienceType, ClientMessage, EventEnvelope, Negotiation,
+    ServerMessage, PROTOCOL_VERSION,
 };
 use crate::redis_client::{RedisManager, SharedRedisManager};
+use crate::redis_failover::FailoverSettings;
+
+/// Settings for buffering events of users without active connections
+#[derive(Debug, Clone, Copy)]
+struct OfflineBuffer {
+    window_secs: u64,
+    max_events: usize,
+}
+
+impl OfflineBuffer {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            window_secs: config.offline_buffer_window_secs,
+            max_events: config.offline_buffer_max_events,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.window_secs > 0 && self.max_events > 0
+    }
+
+    /// Whether an event is still worth delivering after a reconnect.
+    /// Typing indicators and presence are stale by then, and clients
+    /// re-fetch everything after a reconnect, so cache hints are too.
+    fn should_buffer(event_type: &str) -> bool {
+        !(event_type.starts_with("presence.")
+            || event_type.starts_with("system.")
+            || event_type.starts_with("cache.")
+            || event_type.ends_with(".typing"))
+    }
+}
+
+/// Published by blazing_sun when a user signs a device out (see blazing_sun
+/// `app/sessions.rs`); the payload names the session
+const SESSION_REVOKED_EVENT: &str = "system.session_revoked";
+
+/// Last message of a connection whose sign-in session was revoked
+fn session_revoked_notice() -> ServerMessage {
+    ServerMessage::Error {
+        code: "SESSION_REVOKED".to_string(),
+        message: "This device was signed out, please sign in again".to_string(),
+        retry_after_ms: None,
+    }
+}
+
+/// The user whose presence, typing or chat message an event carries. Users
+/// who blocked them (see blazing_sun `app/chat/blocks.rs`) do not receive it.
+fn blockable_sender(envelope: &EventEnvelope) -> Option<String> {
+    match envelope.event_type.as_str() {
+        event_type if event_type.starts_with("presence.") => Some(envelope.actor.user_id.clone()),
+        "chat.event.typing_indicator" | "chat.event.lobby_message" | "chat.event.channel_message" => envelope
+            .payload
+            .get("sender_id")
+            .and_then(|v| v.as_i64().map(|id| id.to_string()).or_else(|| v.as_str().map(String::from))),
+        _ => None,
+    }
+}
+
+/// String array in an event payload (e.g. `message_ids`); other values are skipped
+fn string_list(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+/// Check the sender's RBAC role against the permission the command needs
+/// (see `rbac::command_permission`)
+fn authorize_command(connection: &Connection, message: &ClientMessage) -> GatewayResult<()> {
+    let command = message.message_type();
+    let Some(permission) = rbac::command_permission(&command) else {
+        return Ok(());
+    };
+
+    let role = connection
+        .user
+        .as_ref()
+        .map(|user| user.role())
+        .unwrap_or(rbac::Role::User);
+    if role.can(permission) {
+        return Ok(());
+    }
+
+    warn!(
+        connection_id = %connection.id(),
+        user_id = ?connection.user_id(),
+        role = role.as_str(),
+        command = %command,
+        "Command rejected by RBAC"
+    );
+    Err(GatewayError::Forbidden(command))
+}
 
 /// WebSocket Server
 pub struct WebSocketServer {
@@ -26,6 +128,7 @@ pub struct WebSocketServer {
     redis: SharedRedisManager,
     kafka_producer: SharedKafkaProducer,
     jwt_validator: SharedJwtValidator,
+    maintenance: SharedMaintenanceWatch,
 }
 
 impl WebSocketServer {
@@ -34,11 +137,19 @@ impl WebSocketServer {
         info!("Initializing WebSocket Server...");
 
         // Initialize Redis
-        let redis = Arc::new(RedisManager::new(&config.redis_url).await?);
+        let redis = Arc::new(RedisManager::new(FailoverSettings::from_config(&config)).await?);
         info!("Redis connection established");
 
         // Initialize Kafka producer
-        let kafka_producer = Arc::new(KafkaProducer::new(&config.kafka_brokers)?);
+        let brokers = BrokerSettings::from_config(&config);
+        let kafka_producer = Arc::new(KafkaProducer::new(
+            &brokers,
+            &config.kafka_compression,
+            SizeLimits::new(config.kafka_max_message_bytes),
+            KafkaTopics::for_region(&config.region),
+            routing::from_config(&config.games_partitioning, &config.games_migration, &config.region),
+            redis.clone(),
+        )?);
         info!("Kafka producer initialized");
 
         // Initialize JWT validator
@@ -50,18 +161,21 @@ impl WebSocketServer {
 
         // Start Kafka consumer in background
         let (consumer, mut event_rx) = KafkaConsumer::new(
-            &config.kafka_brokers,
+            &brokers,
             &config.kafka_consumer_group,
+            KafkaTopics::for_region(&config.region),
         )?;
 
         let connections_clone = connections.clone();
         let redis_clone = redis.clone();
+        let offline_buffer = OfflineBuffer::from_config(&config);
+        let room_roles = RoomRoles::new();
 
         tokio::spawn(async move {
             // Handle events from Kafka
             tokio::spawn(async move {
                 while let Ok(event) = event_rx.recv().await {
-                    Self::handle_kafka_event(&connections_clone, &redis_clone, event).await;
+                    Self::handle_kafka_event(&connections_clone, &redis_clone, &room_roles, offline_buffer, event).await;
                 }
             });
 
@@ -71,6 +185,10 @@ impl WebSocketServer {
             }
         });
 
+        // Tell clients when read-only maintenance starts and ends
+        let maintenance = Arc::new(MaintenanceWatch::new());
+        maintenance.start(redis.clone(), connections.clone());
+
         info!("WebSocket Server initialized");
 
         Ok(Self {
@@ -79,10 +197,17 @@ impl WebSocketServer {
             redis,
             kafka_producer,
             jwt_validator,
+            maintenance,
         })
     }
 
-    /// Handle an incoming connection
+    /// Handle an incoming connection; everything logged for it carries its
+    /// `connection_id` (and `user_id` once authenticated)
+    #[tracing::instrument(
+        name = "ws_connection",
+        skip_all,
+        fields(connection_id = tracing::field::Empty, user_id = tracing::field::Empty)
+    )]
     pub async fn handle_connection(
         &self,
         stream: TcpStream,
@@ -90,54 +215,118 @@ impl WebSocketServer {
     ) -> GatewayResult<()> {
         debug!("New connection from {}", addr);
 
-        // Upgrade to WebSocket
-        let ws_stream = accept_async(stream).await?;
+        // Upgrade to WebSocket, keeping Accept-Language for message localization
+        let mut accept_language = None;
+        let capture_locale = |request: &Request, response: Response| {
+            accept_language = request
+                .headers()
+                .get("Accept-Language")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            Ok::<_, ErrorResponse>(response)
+        };
+        let ws_stream = accept_hdr_async(stream, capture_locale).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
-        // Create message channel for this connection
-        let (tx, mut rx) = mpsc::unbounded_channel::<ServerMessage>();
+        // Create bounded outbound queue for this connection
+        let connection_id = Uuid::new_v4().to_string();
+        let queue = Arc::new(OutboundQueue::new(
+            connection_id.clone(),
+            self.config.outbound_queue_capacity,
+            Duration::from_secs(self.config.outbound_stall_timeout_secs),
+            self.connections.outbound_metrics(),
+        ));
+        queue.set_locale(i18n::negotiate(None, accept_language.as_deref()));
 
         // Create connection object
         let mut connection = Connection::new(
+            connection_id,
             addr,
-            tx.clone(),
+            queue.clone(),
             self.config.rate_limit_messages_per_sec,
             self.config.rate_limit_burst,
         );
 
         let connection_id = connection.id().to_string();
+        tracing::Span::current().record("connection_id", connection_id.as_str());
         info!("WebSocket connected: {} from {}", connection_id, addr);
 
         // Register connection
-        self.connections.register(&connection_id, None, tx);
+        let activity = self.connections.register(&connection_id, None, queue.clone());
 
         // Send welcome message
-        let welcome = ServerMessage::Welcome {
-            connection_id: connection_id.clone(),
-            timestamp: Utc::now(),
-        };
-        if let Err(e) = ws_sender.send(Message::Text(serde_json::to_string(&welcome)?)).await {
+        let welcome = ServerMessage::welcome(connection_id.clone(), self.config.min_protocol_version);
+        if let Err(e) = ws_sender.send(Message::Text(welcome.to_json()?)).await {
             error!("Failed to send welcome: {}", e);
             self.connections.unregister(&connection_id, None);
             return Err(GatewayError::WebSocket(e));
         }
 
-        // Spawn task to forward outgoing messages
-        let mut outgoing_rx = rx;
+        let keepalive = Arc::new(Keepalive::new(
+            connection_id.clone(),
+            Duration::from_secs(self.config.heartbeat_interval_secs),
+            Duration::from_secs(self.config.pong_timeout_secs),
+            self.connections.keepalive_metrics(),
+        ));
+
+        // Spawn task to forward outgoing messages; a write that blocks longer
+        // than the stall timeout means the client stopped reading. The same task
+        // pings quiet connections and evicts the ones that never answer.
+        // Bursts of high-frequency updates go out as one system.batch frame.
+        let outgoing = queue.clone();
+        let batching = self.batch_policy();
+        let liveness = keepalive.clone();
+        let written = activity.clone();
         let send_task = tokio::spawn(async move {
-            while let Some(msg) = outgoing_rx.recv().await {
-                if let Ok(json) = serde_json::to_string(&msg) {
-                    if ws_sender.send(Message::Text(json)).await.is_err() {
+            let mut ticker = tokio::time::interval(liveness.tick_interval());
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                let frame = tokio::select! {
+                    batch = outgoing.next_batch(&batching) => match batch {
+                        Some(batch) => {
+                            let batch = batch.into_iter().map(|msg| outgoing.localize(msg)).collect();
+                            let Some(msg) = BatchPolicy::frame(batch) else { continue };
+                            match msg.to_json_for(outgoing.protocol_version()) {
+                                Ok(Some(json)) => Message::Text(json),
+                                // Unknown to the client's protocol version, or unserializable
+                                Ok(None) | Err(_) => continue,
+                            }
+                        }
+                        None => break,
+                    },
+                    _ = ticker.tick() => match liveness.tick() {
+                        KeepaliveAction::Wait => continue,
+                        KeepaliveAction::Ping => Message::Ping(Vec::new()),
+                        KeepaliveAction::Evict => {
+                            // Closing the queue ends the reader and runs the disconnect cleanup
+                            outgoing.close();
+                            break;
+                        }
+                    },
+                };
+
+                let is_message = frame.is_text();
+                match tokio::time::timeout(outgoing.stall_timeout(), ws_sender.send(frame)).await {
+                    Ok(Ok(())) => {
+                        if is_message {
+                            written.record_sent();
+                        }
+                    }
+                    Ok(Err(_)) => break,
+                    Err(_) => {
+                        outgoing.disconnect("socket write stalled");
                         break;
                     }
                 }
             }
-        });
+        }.instrument(tracing::Span::current()));
 
-        // Process incoming messages
-        let result = self
-            .process_messages(&mut connection, &mut ws_receiver)
-            .await;
+        // Process incoming messages until the client leaves or its queue is closed
+        let result = tokio::select! {
+            result = self.process_messages(&mut connection, &keepalive, &activity, &mut ws_receiver) => result,
+            _ = queue.closed() => Ok(()),
+        };
 
         // Cleanup
         let user_id = connection.user_id().map(String::from);
@@ -156,6 +345,7 @@ impl WebSocketServer {
                             user_id: uid.clone(),
                             username: connection.username().map(String::from),
                             roles: connection.user.as_ref().map(|u| u.roles.clone()).unwrap_or_default(),
+                            impersonator_id: connection.impersonator_id().map(String::from),
                         },
                         Audience {
                             audience_type: AudienceType::Room,
@@ -185,6 +375,7 @@ impl WebSocketServer {
                     user_id: uid.clone(),
                     username: connection.username().map(String::from),
                     roles: connection.user.as_ref().map(|u| u.roles.clone()).unwrap_or_default(),
+                    impersonator_id: connection.impersonator_id().map(String::from),
                 },
                 Audience {
                     audience_type: AudienceType::Broadcast,
@@ -214,31 +405,51 @@ impl WebSocketServer {
     async fn process_messages(
         &self,
         connection: &mut Connection,
+        keepalive: &Keepalive,
+        activity: &ConnectionActivity,
         receiver: &mut futures_util::stream::SplitStream<
             tokio_tungstenite::WebSocketStream<TcpStream>,
         >,
     ) -> GatewayResult<()> {
         while let Some(msg) = receiver.next().await {
+            // Any frame from the client proves the connection is alive
+            if msg.is_ok() {
+                keepalive.seen();
+            }
+
             match msg {
                 Ok(Message::Text(text)) => {
+                    activity.record_received();
+
+                    // Muted senders are ignored until their cooldown ends
+                    if self.is_muted(connection).await {
+                        continue;
+                    }
+
                     // Check rate limit
                     if !connection.check_rate_limit() {
-                        let error = ServerMessage::Error {
-                            code: "RATE_LIMIT".to_string(),
-                            message: "Rate limit exceeded".to_string(),
-                        };
-                        connection.send(error);
+                        self.penalize_flood(connection).await;
                         continue;
                     }
 
                     // Parse and handle message
-                    match serde_json::from_str::<ClientMessage>(&text) {
+                    match ClientMessage::from_json(&text) {
                         Ok(client_msg) => {
-                            if let Err(e) = self.handle_client_message(connection, client_msg).await {
+                            // Commands forwarded to Kafka carry this id as their correlation id
+                            let request_id = logging::request_id::generate();
+                            let span = tracing::info_span!("ws_message", request_id = %request_id);
+                            let handled = logging::request_id::scope(
+                                Some(request_id),
+                                self.handle_client_message(connection, client_msg),
+                            )
+                            .instrument(span)
+                            .await;
+                            if let Err(e) = handled {
                                 warn!("Error handling message: {}", e);
                                 let error = ServerMessage::Error {
-                                    code: "MESSAGE_ERROR".to_string(),
-                                    message: e.to_string(),
+                                    code: e.code().to_string(),
+                                    message: e.client_message(),
+                                    retry_after_ms: e.retry_after_ms(),
                                 };
                                 connection.send(error);
                             }
@@ -248,14 +459,24 @@ impl WebSocketServer {
                             let error = ServerMessage::Error {
                                 code: "INVALID_FORMAT".to_string(),
                                 message: "Invalid message format".to_string(),
+                                retry_after_ms: None,
                             };
                             connection.send(error);
                         }
                     }
                 }
-                Ok(Message::Ping(data)) => {
-                    // Respond to ping with pong
+                Ok(Message::Ping(_)) => {
+                    // tungstenite queues the pong reply itself
+                    connection.touch();
+                }
+                Ok(Message::Pong(_)) => {
+                    // Answer to our keepalive ping; counts as a heartbeat for presence
                     connection.touch();
+                    if connection.is_authenticated() {
+                        if let Err(e) = self.redis.update_heartbeat(connection.id()).await {
+                            debug!("Failed to refresh heartbeat on pong: {}", e);
+                        }
+                    }
                 }
                 Ok(Message::Close(_)) => {
                     debug!("Connection {} closing", connection.id());
@@ -272,6 +493,96 @@ impl WebSocketServer {
         Ok(())
     }
 
+    fn flood_policy(&self) -> FloodPolicy {
+        FloodPolicy {
+            mute_secs: self.config.flood_mute_secs,
+            max_mute_secs: self.config.flood_max_mute_secs,
+            disconnect_after: self.config.flood_disconnect_after,
+            strike_window_secs: self.config.flood_strike_window_secs,
+        }
+    }
+
+    fn batch_policy(&self) -> BatchPolicy {
+        BatchPolicy {
+            game_state_window: Duration::from_millis(self.config.batch_window_game_state_ms),
+            presence_window: Duration::from_millis(self.config.batch_window_presence_ms),
+            lobby_window: Duration::from_millis(self.config.batch_window_lobby_ms),
+            max_messages: self.config.batch_max_messages,
+        }
+    }
+
+    /// Whether the connection's sender is muted; users re-check Redis now and
+    /// then so a mute cleared by an admin is lifted
+    async fn is_muted(&self, connection: &mut Connection) -> bool {
+        if !connection.flood.is_muted(Utc::now()) {
+            return false;
+        }
+
+        if let Some(user_id) = connection.user_id().map(String::from) {
+            if connection.flood.refresh_due(std::time::Instant::now()) {
+                match self.redis.get_flood_mute(&user_id).await {
+                    Ok(until) => connection.flood.sync_mute(until),
+                    Err(e) => debug!("Failed to refresh flood mute: {}", e),
+                }
+            }
+        }
+
+        connection.flood.is_muted(Utc::now())
+    }
+
+    /// Apply the next flood penalty to a connection that went over the rate limit
+    async fn penalize_flood(&self, connection: &mut Connection) {
+        if !connection.flood.begin_strike(std::time::Instant::now()) {
+            return;
+        }
+
+        let policy = self.flood_policy();
+        let user_id = connection.user_id().map(String::from);
+        let strikes = match &user_id {
+            Some(user_id) => match self.redis.record_flood_strike(user_id, policy.record_ttl_secs()).await {
+                Ok(strikes) => strikes,
+                Err(e) => {
+                    warn!("Failed to record flood strike: {}", e);
+                    connection.flood.local_strike()
+                }
+            },
+            None => connection.flood.local_strike(),
+        };
+
+        let penalty = policy.penalty_for(strikes);
+        let muted_until = penalty
+            .cooldown_secs()
+            .map(|secs| Utc::now() + chrono::Duration::seconds(secs as i64));
+        if let Some(until) = muted_until {
+            connection.flood.mute(until);
+            if let Some(user_id) = &user_id {
+                if let Err(e) = self.redis.set_flood_mute(user_id, until).await {
+                    warn!("Failed to store flood mute: {}", e);
+                }
+            }
+        }
+
+        warn!(
+            user_id = user_id.as_deref().unwrap_or("anonymous"),
+            strikes,
+            penalty = penalty.as_str(),
+            "Connection went over the rate limit"
+        );
+
+        connection.send(ServerMessage::RateLimited {
+            penalty: penalty.as_str().to_string(),
+            strikes,
+            cooldown_secs: penalty.cooldown_secs(),
+            muted_until,
+            message: penalty.message().to_string(),
+        });
+
+        if let Penalty::Disconnect { .. } = penalty {
+            // The writer closes the connection once the penalty is delivered
+            connection.tx.close_when_drained();
+        }
+    }
+
     /// Handle a client message
     async fn handle_client_message(
         &self,
@@ -281,8 +592,8 @@ impl WebSocketServer {
         connection.touch();
 
         match message {
-            ClientMessage::Authenticate { token, user_id, username, avatar_id } => {
-                self.handle_authenticate(connection, token, user_id, username, avatar_id).await
+            ClientMessage::Authenticate { token, user_id, username, avatar_id, protocol_version } => {
+                self.handle_authenticate(connection, token, user_id, username, avatar_id, protocol_version).await
             }
             ClientMessage::Heartbeat => {
                 self.handle_heartbeat(connection).await
@@ -295,10 +606,15 @@ impl WebSocketServer {
                 if !connection.is_authenticated() {
                     return Err(GatewayError::NotAuthenticated);
                 }
+                authorize_command(connection, &message)?;
 
                 match message {
                     // Chat commands
                     ClientMessage::ChatSendMessage { recipient_id, content } => {
+                        // Dropped silently so the sender cannot tell they are blocked
+                        if self.blocked_by_recipient(connection, &recipient_id).await {
+                            return Ok(());
+                        }
                         self.forward_chat_command(connection, "chat.command.send_message", serde_json::json!({
                             "recipient_id": recipient_id,
                             "content": content,
@@ -311,18 +627,51 @@ impl WebSocketServer {
                         })).await
                     }
                     ClientMessage::ChatTyping { recipient_id } => {
+                        if self.blocked_by_recipient(connection, &recipient_id).await {
+                            return Ok(());
+                        }
                         self.forward_chat_command(connection, "chat.command.typing", serde_json::json!({
                             "recipient_id": recipient_id,
                         })).await
                     }
-                    ClientMessage::ChatMarkRead { message_ids } => {
+                    ClientMessage::ChatMarkRead { message_ids, receipt } => {
                         self.forward_chat_command(connection, "chat.command.mark_read", serde_json::json!({
                             "message_ids": message_ids,
+                            "receipt": receipt,
+                        })).await
+                    }
+                    ClientMessage::ChatChannelJoin { channel_id } => {
+                        self.forward_chat_command(connection, "chat.command.channel_join", serde_json::json!({
+                            "channel_id": channel_id,
+                        })).await
+                    }
+                    ClientMessage::ChatChannelLeave { channel_id } => {
+                        self.forward_chat_command(connection, "chat.command.channel_leave", serde_json::json!({
+                            "channel_id": channel_id,
+                        })).await
+                    }
+                    ClientMessage::ChatChannelSend { channel_id, content } => {
+                        self.forward_chat_command(connection, "chat.command.channel_send", serde_json::json!({
+                            "channel_id": channel_id,
+                            "content": content,
                         })).await
                     }
 
                     // Game commands
-                    ClientMessage::GameCreateRoom { game_type, room_name, password, max_players, allow_spectators } => {
+                    ClientMessage::GameCreateRoom {
+                        game_type,
+                        room_name,
+                        password,
+                        max_players,
+                        allow_spectators,
+                        max_spectators,
+                        turn_timer_seconds,
+                        preset,
+                        vs_bot,
+                        bot_difficulty,
+                        scheduled_start_at,
+                        rules,
+                    } => {
                         let mut payload = serde_json::json!({
                             "game_type": game_type,
                             "room_name": room_name,
@@ -336,6 +685,27 @@ impl WebSocketServer {
                         if let Some(as_) = allow_spectators {
                             payload["allow_spectators"] = serde_json::json!(as_);
                         }
+                        if let Some(ms) = max_spectators {
+                            payload["max_spectators"] = serde_json::json!(ms);
+                        }
+                        if let Some(tt) = turn_timer_seconds {
+                            payload["turn_timer_seconds"] = serde_json::json!(tt);
+                        }
+                        if let Some(preset) = preset {
+                            payload["preset"] = serde_json::json!(preset);
+                        }
+                        if let Some(vs_bot) = vs_bot {
+                            payload["vs_bot"] = serde_json::json!(vs_bot);
+                        }
+                        if let Some(difficulty) = bot_difficulty {
+                            payload["bot_difficulty"] = serde_json::json!(difficulty);
+                        }
+                        if let Some(start_at) = scheduled_start_at {
+                            payload["scheduled_start_at"] = serde_json::json!(start_at);
+                        }
+                        if let Some(rules) = rules {
+                            payload["rules"] = rules;
+                        }
                         self.forward_games_command(connection, "games.command.create_room", payload).await
                     }
                     ClientMessage::GameJoinRoom { room_name, password } => {
@@ -362,6 +732,34 @@ impl WebSocketServer {
                             "room_id": room_id,
                         })).await
                     }
+                    ClientMessage::GamePredictWinner { room_id, predicted_winner_id, stake_cents } => {
+                        self.forward_games_command(connection, "games.command.predict_winner", serde_json::json!({
+                            "room_id": room_id,
+                            "predicted_winner_id": predicted_winner_id,
+                            "stake_cents": stake_cents,
+                        })).await
+                    }
+                    ClientMessage::GameExtendRoom { room_id } => {
+                        self.forward_games_command(connection, "games.command.extend_room", serde_json::json!({
+                            "room_id": room_id,
+                        })).await
+                    }
+                    ClientMessage::GameInviteUser { room_id, user_id } => {
+                        self.forward_games_command(connection, "games.command.invite_user", serde_json::json!({
+                            "room_id": room_id,
+                            "user_id": user_id,
+                        })).await
+                    }
+                    ClientMessage::GameAcceptInvite { invite_id } => {
+                        self.forward_games_command(connection, "games.command.accept_invite", serde_json::json!({
+                            "invite_id": invite_id,
+                        })).await
+                    }
+                    ClientMessage::GameDeclineInvite { invite_id } => {
+                        self.forward_games_command(connection, "games.command.decline_invite", serde_json::json!({
+                            "invite_id": invite_id,
+                        })).await
+                    }
                     ClientMessage::GamePlayerChat { room_id, content } => {
                         self.forward_games_command(connection, "games.command.player_chat", serde_json::json!({
                             "room_id": room_id,
@@ -444,9 +842,27 @@ impl WebSocketServer {
                     }
 
                     // List rooms command - forward to blazing_sun via Kafka
-                    ClientMessage::GameListRooms { game_type } => {
+                    ClientMessage::GameListRooms { game_type, cursor, limit } => {
                         self.forward_games_command(connection, "games.command.list_rooms", serde_json::json!({
                             "game_type": game_type,
+                            "cursor": cursor,
+                            "limit": limit,
+                        })).await
+                    }
+
+                    ClientMessage::GameSearchRooms {
+                        game_type, status, has_password, allow_spectators, host, query, sort, cursor, limit,
+                    } => {
+                        self.forward_games_command(connection, "games.command.search_rooms", serde_json::json!({
+                            "game_type": game_type,
+                            "status": status,
+                            "has_password": has_password,
+                            "allow_spectators": allow_spectators,
+                            "host": host,
+                            "query": query,
+                            "sort": sort,
+                            "cursor": cursor,
+                            "limit": limit,
                         })).await
                     }
 
@@ -457,6 +873,26 @@ impl WebSocketServer {
                         })).await
                     }
 
+                    // The client's state checksum differs from the one it was sent
+                    ClientMessage::GameReportDesync { room_id, state_checksum } => {
+                        self.forward_games_command(connection, "games.command.report_desync", serde_json::json!({
+                            "room_id": room_id,
+                            "state_checksum": state_checksum,
+                        })).await
+                    }
+
+                    // A state diff did not apply, the client wants the full room state
+                    ClientMessage::GameGetRoomState { room_id, room_name } => {
+                        let mut payload = serde_json::json!({});
+                        if let Some(id) = room_id {
+                            payload["room_id"] = serde_json::json!(id);
+                        }
+                        if let Some(name) = room_name {
+                            payload["room_name"] = serde_json::json!(name);
+                        }
+                        self.forward_games_command(connection, "games.command.get_room_state", payload).await
+                    }
+
                     // Rejoin room command
                     ClientMessage::GameRejoinRoom { room_id, room_name } => {
                         let mut payload = serde_json::json!({});
@@ -547,6 +983,23 @@ impl WebSocketServer {
                         })).await
                     }
 
+                    // Moderator chat mutes
+                    ClientMessage::GameChatMute { room_id, target_user_id, channel, duration_seconds } => {
+                        self.forward_games_command(connection, "games.command.chat_mute", serde_json::json!({
+                            "room_id": room_id,
+                            "target_user_id": target_user_id,
+                            "channel": channel,
+                            "duration_seconds": duration_seconds,
+                        })).await
+                    }
+                    ClientMessage::GameChatUnmute { room_id, target_user_id, channel } => {
+                        self.forward_games_command(connection, "games.command.chat_unmute", serde_json::json!({
+                            "room_id": room_id,
+                            "target_user_id": target_user_id,
+                            "channel": channel,
+                        })).await
+                    }
+
                     _ => Ok(())
                 }
             }
@@ -561,19 +1014,49 @@ impl WebSocketServer {
         user_id_opt: Option<String>,
         username_opt: Option<String>,
         _avatar_id: Option<String>,
+        protocol_version: Option<u32>,
     ) -> GatewayResult<()> {
         debug!("Authenticating connection {}", connection.id());
 
+        // Clients older than the minimum version are refused before authenticating
+        let negotiation = negotiate(protocol_version, self.config.min_protocol_version);
+        if let Negotiation::Unsupported { version } = negotiation {
+            warn!(
+                "Connection {} refused: protocol version {} is older than the minimum {}",
+                connection.id(), version, self.config.min_protocol_version
+            );
+            return Err(GatewayError::UnsupportedProtocolVersion(version));
+        }
+
         let (user_id, username, roles): (String, String, Vec<String>);
 
         // Try token-based auth first
         if let Some(token) = token {
             if !token.is_empty() {
                 let user = self.jwt_validator.validate(&token)?;
+
+                // Tokens of a device signed out in blazing_sun stay valid until they expire
+                if let Some(session_id) = &user.session_id {
+                    match self.redis.is_session_revoked(session_id).await {
+                        Ok(true) => {
+                            return Err(GatewayError::AuthFailed("Session has been revoked".to_string()));
+                        }
+                        Ok(false) => {}
+                        Err(e) => warn!("Failed to check session {}: {}", session_id, e),
+                    }
+                }
+
                 user_id = user.user_id.clone();
                 username = user.username.clone();
                 roles = user.roles.clone();
 
+                if let Some(impersonator_id) = &user.impersonator_id {
+                    info!(
+                        "Connection {} authenticated as user {}, impersonated by admin {}",
+                        connection.id(), user_id, impersonator_id
+                    );
+                }
+
                 // Update connection state
                 connection.authenticate(user);
             } else {
@@ -597,29 +1080,77 @@ impl WebSocketServer {
                 email: None,
                 roles: roles.clone(),
                 permission_level: 1,
+                locale: None,
+                impersonator_id: None,
+                session_id: None,
             };
             connection.authenticate(user);
         } else {
             return Err(GatewayError::NotAuthenticated);
         }
 
-        // Update connection manager
-        self.connections.set_user(connection.id(), &user_id);
-
         // Register in Redis
         self.redis
             .register_socket(connection.id(), &user_id, &username, roles.clone())
             .await?;
 
+        // A mute follows the user to new connections
+        match self.redis.get_flood_mute(&user_id).await {
+            Ok(until) => connection.flood.sync_mute(until),
+            Err(e) => warn!("Failed to load flood mute: {}", e),
+        }
+
+        // Everything from here on is written in the negotiated version
+        let version = negotiation.version().unwrap_or(PROTOCOL_VERSION);
+        connection.set_protocol_version(version);
+
+        // Settings let the client apply its theme and language right away
+        let settings = match self.redis.get_user_settings(&user_id).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Failed to load user settings: {}", e);
+                None
+            }
+        };
+
         // Send authenticated response
         let response = ServerMessage::Authenticated {
             user_id: user_id.clone(),
             username: username.clone(),
             roles: roles.clone(),
+            protocol_version: version,
+            settings,
             timestamp: Utc::now(),
         };
         connection.send(response);
 
+        if let Negotiation::Deprecated { version } = negotiation {
+            connection.send(ServerMessage::DeprecationWarning {
+                protocol_version: version,
+                current_version: PROTOCOL_VERSION,
+                min_supported_version: self.config.min_protocol_version,
+                message: "This app version is out of date, please refresh the page".to_string(),
+            });
+        }
+
+        if let Some(notice) = self.maintenance.notice() {
+            connection.send(notice);
+        }
+
+        // Deliver events buffered while the user was offline before any live traffic
+        self.replay_offline_events(connection, &user_id).await;
+
+        // Update connection manager (live events flow from here on)
+        self.connections
+            .set_user(connection.id(), &user_id, connection.impersonator_id());
+        if let Some(session_id) = connection.session_id() {
+            self.connections.set_session(connection.id(), session_id);
+        }
+        tracing::Span::current().record("user_id", user_id.as_str());
+
+        // Pick up anything buffered between the replay and going live
+        self.replay_offline_events(connection, &user_id).await;
+
         // Publish connect event
         let envelope = EventEnvelope::new(
             "system.event.user_connected",
@@ -627,6 +1158,7 @@ impl WebSocketServer {
                 user_id: user_id.clone(),
                 username: Some(username.clone()),
                 roles: roles.clone(),
+                impersonator_id: connection.impersonator_id().map(String::from),
             },
             Audience {
                 audience_type: AudienceType::Broadcast,
@@ -656,6 +1188,8 @@ impl WebSocketServer {
         // In a full implementation, this would query Redis or a game service
         let response = ServerMessage::GameRoomList {
             rooms: vec![],
+            next_cursor: None,
+            prev_cursor: None,
         };
         connection.send(response);
         Ok(())
@@ -683,18 +1217,49 @@ impl WebSocketServer {
             return Err(GatewayError::NotAuthenticated);
         }
 
-        // TODO: Implement full state sync
-        // For now, send empty state
+        // Unread direct messages are mirrored to Redis by blazing_sun; without
+        // Redis the snapshot reports none and clients load counts over REST
+        let unread_conversations = match connection.user.as_ref() {
+            Some(user) => self.redis.get_unread_counts(&user.user_id).await.unwrap_or_else(|e| {
+                warn!("Failed to load unread messages of user {}: {}", user.user_id, e);
+                Default::default()
+            }),
+            None => Default::default(),
+        };
+
+        // TODO: Implement full game state sync
         let response = ServerMessage::StateSnapshot {
             active_rooms: connection.rooms.clone(),
             game_states: serde_json::json!({}),
-            unread_messages: 0,
+            unread_messages: unread_conversations.values().sum(),
+            unread_conversations,
         };
         connection.send(response);
 
         Ok(())
     }
 
+    /// Whether the recipient of a direct chat command blocked the sender.
+    /// Fails open without Redis; blazing_sun checks blocks again.
+    async fn blocked_by_recipient(&self, connection: &Connection, recipient_id: &str) -> bool {
+        let Some(user) = connection.user.as_ref() else {
+            return false;
+        };
+
+        match self.redis.is_blocked(recipient_id, &user.user_id).await {
+            Ok(blocked) => {
+                if blocked {
+                    debug!("Dropped chat command from {} to {}: blocked", user.user_id, recipient_id);
+                }
+                blocked
+            }
+            Err(e) => {
+                warn!("Failed to check blocks of user {}: {}", recipient_id, e);
+                false
+            }
+        }
+    }
+
     /// Forward a chat command to Kafka
     async fn forward_chat_command(
         &self,
@@ -710,6 +1275,7 @@ impl WebSocketServer {
                 user_id: user.user_id.clone(),
                 username: Some(user.username.clone()),
                 roles: user.roles.clone(),
+                impersonator_id: user.impersonator_id.clone(),
             },
             Audience {
                 audience_type: AudienceType::User,
@@ -723,6 +1289,72 @@ impl WebSocketServer {
         self.kafka_producer.publish_chat_command(&user.user_id, &envelope).await
     }
 
+    /// Send buffered offline events to a freshly authenticated connection
+    async fn replay_offline_events(&self, connection: &Connection, user_id: &str) {
+        let offline_buffer = OfflineBuffer::from_config(&self.config);
+        if !offline_buffer.is_enabled() {
+            return;
+        }
+
+        let events = match self.redis.drain_offline_events(user_id, offline_buffer.window_secs).await {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Failed to load offline events for user {}: {}", user_id, e);
+                return;
+            }
+        };
+
+        if events.is_empty() {
+            return;
+        }
+
+        let mut delivered = 0;
+        for json in &events {
+            let envelope = match serde_json::from_str::<EventEnvelope>(json) {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    warn!("Skipping malformed offline event for user {}: {}", user_id, e);
+                    continue;
+                }
+            };
+
+            if let Ok(Some(message)) = Self::envelope_to_server_message(&envelope) {
+                connection.send(message);
+                delivered += 1;
+            }
+        }
+
+        info!("Delivered {} offline event(s) to user {} on {}", delivered, user_id, connection.id());
+    }
+
+    /// Buffer an event for a user that has no active connections
+    async fn buffer_offline_event(
+        redis: &RedisManager,
+        offline_buffer: OfflineBuffer,
+        user_id: &str,
+        envelope: &EventEnvelope,
+    ) {
+        if !offline_buffer.is_enabled() || !OfflineBuffer::should_buffer(&envelope.event_type) {
+            return;
+        }
+
+        let json = match serde_json::to_string(envelope) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to encode {} for offline delivery: {}", envelope.event_type, e);
+                return;
+            }
+        };
+
+        match redis
+            .buffer_offline_event(user_id, &json, offline_buffer.max_events, offline_buffer.window_secs)
+            .await
+        {
+            Ok(()) => debug!("Buffered {} for offline user {}", envelope.event_type, user_id),
+            Err(e) => warn!("Failed to buffer {} for offline user {}: {}", envelope.event_type, user_id, e),
+        }
+    }
+
     /// Forward a games command to Kafka
     async fn forward_games_command(
         &self,
@@ -732,6 +1364,8 @@ impl WebSocketServer {
     ) -> GatewayResult<()> {
         let user = connection.user.as_ref().ok_or(GatewayError::NotAuthenticated)?;
 
+        self.check_command_limit(&user.user_id, command_type).await?;
+
         // Extract room_id from payload for partitioning (clone to avoid borrow issues)
         let room_id = payload.get("room_id")
             .and_then(|v| v.as_str())
@@ -745,6 +1379,7 @@ impl WebSocketServer {
                 user_id: user.user_id.clone(),
                 username: Some(user.username.clone()),
                 roles: user.roles.clone(),
+                impersonator_id: user.impersonator_id.clone(),
             },
             Audience {
                 audience_type: AudienceType::Room,
@@ -758,10 +1393,52 @@ impl WebSocketServer {
         self.kafka_producer.publish_games_command(key, &envelope).await
     }
 
+    /// Refuse a game command the user already sent as often as its limit
+    /// allows in the current window; without Redis commands are not limited
+    async fn check_command_limit(&self, user_id: &str, command_type: &str) -> GatewayResult<()> {
+        let Some(limit) = self.config.game_command_limits.for_command(command_type) else {
+            return Ok(());
+        };
+
+        let command = CommandLimits::command_name(command_type);
+        let window = limit.window(Utc::now().timestamp_millis().max(0) as u64);
+        let uses = match self
+            .redis
+            .count_command_use(user_id, command, window.index, limit.window_ms)
+            .await
+        {
+            Ok(uses) => uses,
+            Err(e) => {
+                debug!("Failed to count {} for user {}: {}", command, user_id, e);
+                return Ok(());
+            }
+        };
+
+        if uses > limit.max {
+            debug!(user_id, command, uses, "Game command over its rate limit");
+            return Err(GatewayError::CommandRateLimited {
+                command: command.to_string(),
+                retry_after_ms: window.remaining_ms,
+            });
+        }
+        Ok(())
+    }
+
     /// Handle an event received from Kafka
+    #[tracing::instrument(
+        name = "kafka_event",
+        skip_all,
+        fields(
+            event_id = %event.envelope.event_id,
+            event_type = %event.envelope.event_type,
+            request_id = event.envelope.correlation_id.as_deref(),
+        )
+    )]
     async fn handle_kafka_event(
         connections: &ConnectionManager,
-        _redis: &RedisManager,
+        redis: &RedisManager,
+        room_roles: &RoomRoles,
+        offline_buffer: OfflineBuffer,
         event: crate::kafka::KafkaEvent,
     ) {
         let envelope = event.envelope;
@@ -771,6 +1448,31 @@ impl WebSocketServer {
             envelope.event_type, envelope.audience.audience_type, envelope.audience.user_ids
         );
 
+        // A device signed out in blazing_sun loses its open connections
+        if envelope.event_type == SESSION_REVOKED_EVENT {
+            let Some(session_id) = envelope.payload.get("session_id").and_then(|v| v.as_str()) else {
+                warn!("{} without a session_id", SESSION_REVOKED_EVENT);
+                return;
+            };
+            for user_id in &envelope.audience.user_ids {
+                let closed = connections.disconnect_session(user_id, session_id, session_revoked_notice());
+                info!("Session {} of user {} revoked: closed {} connection(s)", session_id, user_id, closed);
+            }
+            return;
+        }
+
+        // Hosts and admin spectators of rooms, for host- and admin-only events
+        room_roles.observe(&envelope);
+
+        // Users who blocked the sender of the event do not get it
+        let hidden_from = match blockable_sender(&envelope) {
+            Some(sender_id) => redis.get_blockers(&sender_id).await.unwrap_or_else(|e| {
+                warn!("Failed to load blockers of user {}: {}", sender_id, e);
+                HashSet::new()
+            }),
+            None => HashSet::new(),
+        };
+
         // Route based on audience
         match envelope.audience.audience_type {
             AudienceType::User => {
@@ -779,6 +1481,10 @@ impl WebSocketServer {
                     warn!("User audience but no user_ids specified for event: {}", envelope.event_type);
                 }
                 for user_id in &envelope.audience.user_ids {
+                    if hidden_from.contains(user_id) {
+                        debug!("Skipped {} for user {}: sender blocked", envelope.event_type, user_id);
+                        continue;
+                    }
                     debug!("Attempting to send {} to user {}", envelope.event_type, user_id);
                     if let Ok(Some(message)) = Self::envelope_to_server_message(&envelope) {
                         // Register user connections in room for room_state, room_created, lobby_joined events
@@ -890,8 +1596,34 @@ impl WebSocketServer {
                             }
                         }
 
+                        // Chat channel membership - channel messages are delivered to the
+                        // "channel:{id}" room, so track the user's connections there
+                        if envelope.event_type == "chat.event.channel_joined"
+                            || envelope.event_type == "chat.event.channel_left"
+                        {
+                            let channel_id = envelope.payload.get("channel_id")
+                                .and_then(|v| v.as_i64().map(|id| id.to_string()).or_else(|| v.as_str().map(String::from)));
+
+                            if let Some(channel_id) = channel_id {
+                                let channel_room = format!("channel:{}", channel_id);
+                                for conn_id in connections.get_user_connections(user_id) {
+                                    if envelope.event_type == "chat.event.channel_joined" {
+                                        connections.join_room(&conn_id, &channel_room);
+                                    } else {
+                                        connections.leave_room(&conn_id, &channel_room);
+                                    }
+                                    debug!("Updated connection {} in {} for user {} ({})",
+                                        conn_id, channel_room, user_id, envelope.event_type);
+                                }
+                            }
+                        }
+
                         let sent = connections.send_to_user(user_id, message);
                         debug!("Sent {} to user {}: {} connection(s)", envelope.event_type, user_id, sent);
+
+                        if sent == 0 {
+                            Self::buffer_offline_event(redis, offline_buffer, user_id, &envelope).await;
+                        }
                     } else {
                         warn!("Failed to convert envelope to server message for event: {}", envelope.event_type);
                     }
@@ -941,7 +1673,20 @@ impl WebSocketServer {
                         }
                     } else {
                         if let Ok(Some(message)) = Self::envelope_to_server_message(&envelope) {
-                            connections.send_to_room(room_id, message);
+                            connections.send_to_room_except_users(room_id, message, &hidden_from);
+                        }
+
+                        // A migrated room is served by another region's gateway from now
+                        // on, and a closing room is gone
+                        if envelope.event_type.ends_with(".room_migrated")
+                            || envelope.event_type == "games.event.room_closing"
+                        {
+                            let spectator_room = format!("spectators:{}", room_id);
+                            for conn_id in connections.get_room_connections(room_id) {
+                                connections.leave_room(&conn_id, room_id);
+                                connections.leave_room(&conn_id, &spectator_room);
+                            }
+                            debug!("Released connections of room {} ({})", room_id, envelope.event_type);
                         }
                     }
                 }
@@ -967,7 +1712,30 @@ impl WebSocketServer {
             AudienceType::Broadcast => {
                 // Send to all connected users
                 if let Ok(Some(message)) = Self::envelope_to_server_message(&envelope) {
-                    connections.broadcast(message);
+                    connections.broadcast_except_users(message, &hidden_from);
+                }
+            }
+            AudienceType::Host | AudienceType::Admins => {
+                // Send only to the room's host (and admin spectator); unknown
+                // roles drop the event rather than leak it to the room
+                if let Some(room_id) = &envelope.audience.room_id {
+                    match room_roles.get(room_id) {
+                        Some(roles) => {
+                            let users = if envelope.audience.audience_type == AudienceType::Host {
+                                roles.host()
+                            } else {
+                                roles.admins()
+                            };
+                            if let Ok(Some(message)) = Self::envelope_to_server_message(&envelope) {
+                                let sent = connections.send_to_room_users(room_id, message, &users);
+                                debug!("Sent {} to {} of room {}: {} connection(s)",
+                                    envelope.event_type, users.len(), room_id, sent);
+                            }
+                        }
+                        None => warn!("Dropped {}: roles of room {} are not known yet", envelope.event_type, room_id),
+                    }
+                } else {
+                    warn!("{:?} audience but no room_id for event: {}", envelope.audience.audience_type, envelope.event_type);
                 }
             }
         }
@@ -985,12 +1753,57 @@ impl WebSocketServer {
         // The full implementation would have a comprehensive mapping
         match event_type.as_str() {
             "chat.event.message_sent" | "chat.event.message_received" => {
+                // blazing_sun publishes direct messages as the system actor
+                // with the sender in the payload
+                let sender_id = payload
+                    .get("sender_id")
+                    .and_then(|v| v.as_i64())
+                    .map(|id| id.to_string())
+                    .unwrap_or_else(|| envelope.actor.user_id.clone());
+                let sender_name = payload
+                    .get("sender_username")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| envelope.actor.username.clone().unwrap_or_default());
                 Ok(Some(ServerMessage::ChatMessageReceived {
                     message_id: payload.get("message_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                    sender_id: envelope.actor.user_id.clone(),
-                    sender_name: envelope.actor.username.clone().unwrap_or_default(),
+                    conversation_id: payload.get("conversation_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    sender_id,
+                    sender_name,
+                    recipient_id: payload.get("recipient_id").and_then(|v| v.as_i64()).map(|id| id.to_string()).unwrap_or_default(),
                     content: payload.get("content").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                    sent_at: envelope.timestamp,
+                    sent_at: payload
+                        .get("created_at")
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                        .map(|v| v.with_timezone(&Utc))
+                        .unwrap_or(envelope.timestamp),
+                }))
+            }
+            "chat.event.message_delivered" => {
+                Ok(Some(ServerMessage::ChatMessageDelivered {
+                    conversation_id: payload.get("conversation_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    message_ids: string_list(payload.get("message_ids")),
+                    recipient_id: payload.get("recipient_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                    delivered_at: payload
+                        .get("delivered_at")
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                        .map(|v| v.with_timezone(&Utc))
+                        .unwrap_or(envelope.timestamp),
+                }))
+            }
+            "chat.event.message_read" => {
+                Ok(Some(ServerMessage::ChatMessageRead {
+                    conversation_id: payload.get("conversation_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    message_ids: string_list(payload.get("message_ids")),
+                    reader_id: payload.get("reader_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                    read_at: payload
+                        .get("read_at")
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                        .map(|v| v.with_timezone(&Utc))
+                        .unwrap_or(envelope.timestamp),
                 }))
             }
             "chat.event.lobby_message" => {
@@ -1003,6 +1816,36 @@ impl WebSocketServer {
                     sent_at: envelope.timestamp,
                 }))
             }
+            "chat.event.channel_joined" => {
+                Ok(Some(ServerMessage::ChatChannelJoined {
+                    channel_id: payload.get("channel_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                    channel_name: payload.get("channel_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    last_read_message_id: payload.get("last_read_message_id").and_then(|v| v.as_str()).map(String::from),
+                }))
+            }
+            "chat.event.channel_left" => {
+                Ok(Some(ServerMessage::ChatChannelLeft {
+                    channel_id: payload.get("channel_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                }))
+            }
+            "chat.event.channel_message" => {
+                Ok(Some(ServerMessage::ChatChannelMessage {
+                    channel_id: payload.get("channel_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                    message_id: payload.get("message_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    sender_id: payload.get("sender_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                    sender_name: payload.get("sender_username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    sender_avatar_id: payload.get("sender_avatar_id").and_then(|v| v.as_i64()),
+                    content: payload.get("content").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    sent_at: envelope.timestamp,
+                }))
+            }
+            "chat.event.error" => {
+                Ok(Some(ServerMessage::Error {
+                    code: payload.get("code").and_then(|v| v.as_str()).unwrap_or("chat_error").to_string(),
+                    message: payload.get("message").and_then(|v| v.as_str()).unwrap_or("Unknown error").to_string(),
+                    retry_after_ms: None,
+                }))
+            }
             "presence.event.user_online" => {
                 Ok(Some(ServerMessage::UserOnline {
                     user_id: envelope.actor.user_id.clone(),
@@ -1015,6 +1858,66 @@ impl WebSocketServer {
                     username: envelope.actor.username.clone().unwrap_or_default(),
                 }))
             }
+            "notification.event.received" => {
+                Ok(Some(ServerMessage::Notification {
+                    notification_id: envelope.event_id.to_string(),
+                    category: payload.get("category").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    title: payload.get("title").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    body: payload.get("body").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    data: payload.get("data").cloned().unwrap_or(serde_json::Value::Null),
+                    created_at: envelope.timestamp,
+                }))
+            }
+            "achievement.event.unlocked" => {
+                Ok(Some(ServerMessage::AchievementUnlocked {
+                    achievement_id: payload.get("achievement_id").and_then(|v| v.as_i64()).unwrap_or(0),
+                    code: payload.get("code").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    name: payload.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    description: payload.get("description").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    badge: payload.get("badge").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    unlocked_at: payload
+                        .get("unlocked_at")
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                        .map(|v| v.with_timezone(&Utc))
+                        .unwrap_or(envelope.timestamp),
+                }))
+            }
+            "checkout.event.session_ready" => {
+                Ok(Some(ServerMessage::CheckoutSessionReady {
+                    request_id: payload.get("request_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    session_id: payload.get("session_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    url: payload.get("url").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    expires_at: payload
+                        .get("expires_at")
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                        .map(|v| v.with_timezone(&Utc)),
+                }))
+            }
+            "checkout.event.session_failed" => {
+                Ok(Some(ServerMessage::CheckoutSessionFailed {
+                    request_id: payload.get("request_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    message: payload.get("message").and_then(|v| v.as_str()).unwrap_or("Checkout failed").to_string(),
+                }))
+            }
+            "cache.invalidate" => {
+                Ok(Some(ServerMessage::CacheInvalidate {
+                    entity: payload.get("entity").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    id: payload.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    changed_at: envelope.timestamp,
+                }))
+            }
+            "system.job_progress" => {
+                Ok(Some(ServerMessage::JobProgress {
+                    job_id: payload.get("job_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    job_type: payload.get("job_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    status: payload.get("status").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    percent: payload.get("percent").and_then(|v| v.as_u64()).map(|p| p.min(100) as u8),
+                    message: payload.get("message").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    updated_at: envelope.timestamp,
+                }))
+            }
             // Game events - support both unprefixed and game-prefixed event types
             // room_created - game-specific variants
             "games.event.tic_tac_toe.room_created" => {
@@ -1058,23 +1961,40 @@ impl WebSocketServer {
                 Ok(Some(ServerMessage::Error {
                     code: payload.get("code").and_then(|v| v.as_str()).unwrap_or("game_error").to_string(),
                     message: payload.get("message").and_then(|v| v.as_str()).unwrap_or("Unknown error").to_string(),
+                    retry_after_ms: None,
                 }))
             }
             // room_state - game-specific variants
             "games.event.tic_tac_toe.room_state" => {
                 Ok(Some(ServerMessage::TicTacToeRoomState {
                     room: payload.get("room").cloned().unwrap_or(serde_json::json!({})),
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
+                    version: payload.get("version").and_then(|v| v.as_u64()),
                 }))
             }
             "games.event.bigger_dice.room_state" => {
                 Ok(Some(ServerMessage::BiggerDiceRoomState {
                     room: payload.get("room").cloned().unwrap_or(serde_json::json!({})),
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
+                    version: payload.get("version").and_then(|v| v.as_u64()),
                 }))
             }
             // room_state - generic fallback
             "games.event.room_state" => {
                 Ok(Some(ServerMessage::GameRoomState {
                     room: payload.get("room").cloned().unwrap_or(serde_json::json!({})),
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
+                    version: payload.get("version").and_then(|v| v.as_u64()),
+                }))
+            }
+            // Changes since the room state the user holds (same for every game)
+            "games.event.room_state_diff" => {
+                Ok(Some(ServerMessage::GameRoomStateDiff {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    base_version: payload.get("base_version").and_then(|v| v.as_u64()).unwrap_or(0),
+                    version: payload.get("version").and_then(|v| v.as_u64()).unwrap_or(0),
+                    ops: payload.get("ops").and_then(|v| v.as_array()).cloned().unwrap_or_default(),
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
                 }))
             }
             // player_disconnected - game-specific variants
@@ -1176,6 +2096,161 @@ impl WebSocketServer {
                     username: payload.get("username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                 }))
             }
+            "games.event.room_inactivity_warning" => {
+                Ok(Some(ServerMessage::GameRoomInactivityWarning {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    closes_at: payload.get("closes_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    closes_in_seconds: payload.get("closes_in_seconds").and_then(|v| v.as_i64()).unwrap_or(0),
+                }))
+            }
+            "games.event.room_inactivity_extended" => {
+                Ok(Some(ServerMessage::GameRoomInactivityExtended {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    closes_at: payload.get("closes_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    extended_by: payload.get("extended_by").and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0).to_string(),
+                }))
+            }
+            "games.event.room_closing" => {
+                Ok(Some(ServerMessage::GameRoomClosing {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    reason: payload.get("reason").and_then(|v| v.as_str()).unwrap_or("inactive").to_string(),
+                }))
+            }
+            "games.event.room_gone" => {
+                Ok(Some(ServerMessage::GameRoomGone {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    reason: payload.get("reason").and_then(|v| v.as_str()).unwrap_or("deleted").to_string(),
+                }))
+            }
+            // room_migrated - sent typed to the room and untyped to late joiners
+            "games.event.room_migrated"
+            | "games.event.bigger_dice.room_migrated"
+            | "games.event.tic_tac_toe.room_migrated" => {
+                Ok(Some(ServerMessage::GameRoomMigrated {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    from_region: payload.get("from_region").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    to_region: payload.get("to_region").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    gateway_url: payload.get("gateway_url").and_then(|v| v.as_str()).map(String::from),
+                }))
+            }
+            "games.event.room_occupancy_changed" => {
+                let count = |key: &str| payload.get(key).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+                Ok(Some(ServerMessage::GameRoomOccupancy {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    game_type: payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    status: payload.get("status").and_then(|v| v.as_str()).unwrap_or("waiting").to_string(),
+                    player_count: count("player_count"),
+                    max_players: count("max_players"),
+                    spectator_count: count("spectator_count"),
+                    max_spectators: count("max_spectators"),
+                }))
+            }
+            "games.event.prediction_opened" => {
+                let (min_stake_cents, max_stake_cents) = (
+                    payload.get("min_stake_cents").and_then(|v| v.as_i64()).unwrap_or(0),
+                    payload.get("max_stake_cents").and_then(|v| v.as_i64()).unwrap_or(0),
+                );
+                Ok(Some(ServerMessage::GamePredictionOpened {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    closes_at: payload.get("closes_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    min_stake_cents,
+                    max_stake_cents,
+                    candidates: payload.get("candidates").cloned().unwrap_or(serde_json::json!([])),
+                }))
+            }
+            "games.event.prediction_pool_updated" => {
+                Ok(Some(ServerMessage::GamePredictionPoolUpdated {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    total_pool_cents: payload.get("total_pool_cents").and_then(|v| v.as_i64()).unwrap_or(0),
+                    candidates: payload.get("candidates").cloned().unwrap_or(serde_json::json!([])),
+                }))
+            }
+            "games.event.prediction_closed" => {
+                Ok(Some(ServerMessage::GamePredictionClosed {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    total_pool_cents: payload.get("total_pool_cents").and_then(|v| v.as_i64()).unwrap_or(0),
+                    candidates: payload.get("candidates").cloned().unwrap_or(serde_json::json!([])),
+                }))
+            }
+            "games.event.prediction_settled" => {
+                Ok(Some(ServerMessage::GamePredictionSettled {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    winner_id: payload.get("winner_id").and_then(|v| v.as_i64()).map(|id| id.to_string()),
+                    total_pool_cents: payload.get("total_pool_cents").and_then(|v| v.as_i64()).unwrap_or(0),
+                    payouts: payload.get("payouts").cloned().unwrap_or(serde_json::json!([])),
+                }))
+            }
+            "games.event.tournament_round_started" => {
+                Ok(Some(ServerMessage::GameTournamentRoundStarted {
+                    tournament_id: payload.get("tournament_id").and_then(|v| v.as_i64()).unwrap_or(0),
+                    tournament_name: payload.get("tournament_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    game_type: payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    round: payload.get("round").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                    rounds: payload.get("rounds").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                    matches: payload.get("matches").cloned().unwrap_or(serde_json::json!([])),
+                }))
+            }
+            "games.event.room_scheduled" => {
+                Ok(Some(ServerMessage::GameRoomScheduled {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    game_type: payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    scheduled_start_at: payload.get("scheduled_start_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                }))
+            }
+            "games.event.scheduled_room_reminder" => {
+                Ok(Some(ServerMessage::GameScheduledRoomReminder {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    game_type: payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    scheduled_start_at: payload.get("scheduled_start_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    minutes_before: payload.get("minutes_before").and_then(|v| v.as_i64()).unwrap_or(0),
+                }))
+            }
+            "games.event.scheduled_room_opened" => {
+                Ok(Some(ServerMessage::GameScheduledRoomOpened {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    game_type: payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                }))
+            }
+            "games.event.invite_received" => {
+                Ok(Some(ServerMessage::GameInviteReceived {
+                    invite_id: payload.get("invite_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    game_type: payload.get("game_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    inviter_id: payload.get("inviter_id").and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0).to_string(),
+                    inviter_username: payload.get("inviter_username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    is_password_protected: payload.get("is_password_protected").and_then(|v| v.as_bool()).unwrap_or(false),
+                    player_count: payload.get("player_count").and_then(|v| v.as_i64()).unwrap_or(2) as i32,
+                    joined_count: payload.get("joined_count").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                    expires_at: payload.get("expires_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                }))
+            }
+            "games.event.invite_answered" => {
+                Ok(Some(ServerMessage::GameInviteAnswered {
+                    invite_id: payload.get("invite_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    invitee_id: payload.get("invitee_id").and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0).to_string(),
+                    invitee_username: payload.get("invitee_username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    status: payload.get("status").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                }))
+            }
+            "games.event.tournament_finished" => {
+                Ok(Some(ServerMessage::GameTournamentFinished {
+                    tournament_id: payload.get("tournament_id").and_then(|v| v.as_i64()).unwrap_or(0),
+                    tournament_name: payload.get("tournament_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    winner_id: payload.get("winner_id").and_then(|v| v.as_i64()).unwrap_or(0).to_string(),
+                    prize_pool_cents: payload.get("prize_pool_cents").and_then(|v| v.as_i64()).unwrap_or(0),
+                    payouts: payload.get("payouts").cloned().unwrap_or(serde_json::json!([])),
+                }))
+            }
             // not_in_room - game-specific variants
             "games.event.tic_tac_toe.not_in_room" => {
                 Ok(Some(ServerMessage::TicTacToeNotInRoom {
@@ -1221,6 +2296,7 @@ impl WebSocketServer {
                         .map(|s| s.to_string()),
                     score: 0,
                     is_ready: false,
+                    stats: player_json.get("stats").cloned().filter(|v| !v.is_null()),
                 };
                 Ok(Some(ServerMessage::TicTacToeLobbyJoined {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -1242,6 +2318,7 @@ impl WebSocketServer {
                         .map(|s| s.to_string()),
                     score: 0,
                     is_ready: false,
+                    stats: player_json.get("stats").cloned().filter(|v| !v.is_null()),
                 };
                 Ok(Some(ServerMessage::BiggerDiceLobbyJoined {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -1264,6 +2341,7 @@ impl WebSocketServer {
                         .map(|s| s.to_string()),
                     score: 0,
                     is_ready: false,
+                    stats: player_json.get("stats").cloned().filter(|v| !v.is_null()),
                 };
                 Ok(Some(ServerMessage::GameLobbyJoined {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -1374,6 +2452,7 @@ impl WebSocketServer {
                         .and_then(|v| v.as_i64().map(|n| n.to_string()).or_else(|| v.as_str().map(|s| s.to_string()))),
                     score: player_json.get("score").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
                     is_ready: player_json.get("is_ready").and_then(|v| v.as_bool()).unwrap_or(false),
+                    stats: player_json.get("stats").cloned().filter(|v| !v.is_null()),
                 };
                 Ok(Some(ServerMessage::TicTacToePlayerSelected {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -1394,6 +2473,7 @@ impl WebSocketServer {
                         .and_then(|v| v.as_i64().map(|n| n.to_string()).or_else(|| v.as_str().map(|s| s.to_string()))),
                     score: player_json.get("score").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
                     is_ready: player_json.get("is_ready").and_then(|v| v.as_bool()).unwrap_or(false),
+                    stats: player_json.get("stats").cloned().filter(|v| !v.is_null()),
                 };
                 Ok(Some(ServerMessage::BiggerDicePlayerSelected {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -1415,6 +2495,7 @@ impl WebSocketServer {
                         .and_then(|v| v.as_i64().map(|n| n.to_string()).or_else(|| v.as_str().map(|s| s.to_string()))),
                     score: player_json.get("score").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
                     is_ready: player_json.get("is_ready").and_then(|v| v.as_bool()).unwrap_or(false),
+                    stats: player_json.get("stats").cloned().filter(|v| !v.is_null()),
                 };
                 Ok(Some(ServerMessage::GamePlayerSelected {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -1490,11 +2571,20 @@ impl WebSocketServer {
                     player_name: payload.get("username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                 }))
             }
+            "games.event.room_join_denied" => {
+                Ok(Some(ServerMessage::GameRoomJoinDenied {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    reason: payload.get("reason").and_then(|v| v.as_str()).unwrap_or("too_many_attempts").to_string(),
+                    retry_after_seconds: payload.get("retry_after_seconds").and_then(|v| v.as_u64()).unwrap_or(0),
+                }))
+            }
             "games.event.user_banned" => {
                 // Forward as an error message to the banned user
                 Ok(Some(ServerMessage::Error {
                     code: "user_banned".to_string(),
                     message: "You are banned from this room".to_string(),
+                    retry_after_ms: None,
                 }))
             }
             // turn_changed - game-specific variants
@@ -1503,6 +2593,7 @@ impl WebSocketServer {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                     current_turn: payload.get("current_turn").and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0).to_string(),
                     turn_number: payload.get("turn_number").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
                 }))
             }
             "games.event.bigger_dice.turn_changed" => {
@@ -1510,6 +2601,7 @@ impl WebSocketServer {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                     current_turn: payload.get("current_turn").and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0).to_string(),
                     turn_number: payload.get("turn_number").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
                 }))
             }
             // turn_changed - generic fallback
@@ -1518,6 +2610,7 @@ impl WebSocketServer {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                     current_turn: payload.get("current_turn").and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0).to_string(),
                     turn_number: payload.get("turn_number").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                    state_checksum: payload.get("state_checksum").and_then(|v| v.as_str()).map(String::from),
                 }))
             }
             "games.event.bigger_dice.round_result" => {
@@ -1658,7 +2751,11 @@ impl WebSocketServer {
                         rejoin_role: r.get("rejoin_role").and_then(|v| v.as_str()).map(|s| s.to_string()),
                     }
                 }).collect();
-                Ok(Some(ServerMessage::GameRoomList { rooms }))
+                Ok(Some(ServerMessage::GameRoomList {
+                    rooms,
+                    next_cursor: payload.get("next_cursor").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    prev_cursor: payload.get("prev_cursor").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                }))
             }
             // room_removed - game-specific variants
             "games.event.tic_tac_toe.room_removed" => {
@@ -2100,6 +3197,25 @@ impl WebSocketServer {
                     target_username: payload.get("target_username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                 }))
             }
+            "games.event.chat_muted" => {
+                Ok(Some(ServerMessage::GameChatMuted {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    user_id: payload.get("user_id").and_then(|v| v.as_i64().map(|n| n.to_string()).or_else(|| v.as_str().map(|s| s.to_string()))).unwrap_or_default(),
+                    username: payload.get("username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    channel: payload.get("channel").and_then(|v| v.as_str()).map(String::from),
+                    muted_by: payload.get("muted_by").and_then(|v| v.as_i64().map(|n| n.to_string()).or_else(|| v.as_str().map(|s| s.to_string()))).unwrap_or_default(),
+                    expires_at: payload.get("expires_at").and_then(|v| v.as_str()).map(String::from),
+                }))
+            }
+            "games.event.chat_unmuted" => {
+                Ok(Some(ServerMessage::GameChatUnmuted {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    user_id: payload.get("user_id").and_then(|v| v.as_i64().map(|n| n.to_string()).or_else(|| v.as_str().map(|s| s.to_string()))).unwrap_or_default(),
+                    username: payload.get("username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    channel: payload.get("channel").and_then(|v| v.as_str()).map(String::from),
+                    expired: payload.get("expired").and_then(|v| v.as_bool()).unwrap_or(false),
+                }))
+            }
             "games.event.spectators_updated" => {
                 Ok(Some(ServerMessage::GameSpectatorsUpdated {
                     room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -2175,6 +3291,15 @@ impl WebSocketServer {
                     username: payload.get("username").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                 }))
             }
+            "games.event.spectator_waitlist_position" => {
+                Ok(Some(ServerMessage::GameSpectatorWaitlistPosition {
+                    room_id: payload.get("room_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    room_name: payload.get("room_name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    position: payload.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+                    waitlist_size: payload.get("waitlist_size").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+                    expires_at: payload.get("expires_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                }))
+            }
             // spectator_kicked - game-specific variants
             "games.event.tic_tac_toe.spectator_kicked" => {
                 Ok(Some(ServerMessage::TicTacToeSpectatorKicked {
@@ -2229,6 +3354,26 @@ impl WebSocketServer {
         }
     }
 
+    /// Shared connection manager (used by the health endpoint)
+    pub fn connections(&self) -> SharedConnectionManager {
+        self.connections.clone()
+    }
+
+    /// JWT validator (used by the admin endpoints)
+    pub fn jwt_validator(&self) -> SharedJwtValidator {
+        self.jwt_validator.clone()
+    }
+
+    /// Shared Redis manager (used by the health endpoint)
+    pub fn redis(&self) -> SharedRedisManager {
+        self.redis.clone()
+    }
+
+    /// Shared Kafka producer (used by the health endpoint)
+    pub fn kafka_producer(&self) -> SharedKafkaProducer {
+        self.kafka_producer.clone()
+    }
+
     /// Shutdown the server gracefully
     pub async fn shutdown(&self) {
         info!("Shutting down WebSocket Server...");
@@ -2236,8 +3381,14 @@ impl WebSocketServer {
         // Get stats before shutdown
         let stats = self.connections.stats();
         info!(
-            "Final stats: {} connections, {} users, {} rooms",
-            stats.total_connections, stats.unique_users, stats.active_rooms
+            "Final stats: {} connections, {} users, {} rooms, {} slow, {} dropped messages, {} stall disconnects, {} idle evictions",
+            stats.total_connections,
+            stats.unique_users,
+            stats.active_rooms,
+            stats.slow_connections,
+            stats.dropped_messages,
+            stats.stall_disconnects,
+            stats.idle_evictions
         );
 
         // TODO: Send disconnect messages to all clients
@@ -0,0 +1,404 @@
+//! Client -> Server Messages (Commands)
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::ChatReceipt;
+
+/// Incoming message from client
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    // System messages
+    #[serde(rename = "system.authenticate")]
+    Authenticate {
+        #[serde(default)]
+        token: Option<String>,
+        // Allow credential-based auth for development
+        #[serde(default)]
+        user_id: Option<String>,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        avatar_id: Option<String>,
+        /// Protocol version the client was built for; omitted by builds that
+        /// predate negotiation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        protocol_version: Option<u32>,
+    },
+
+    // List available game rooms
+    #[serde(rename = "games.command.list_rooms")]
+    GameListRooms {
+        #[serde(default)]
+        game_type: Option<String>,
+        /// Opaque cursor from a previous room list's `next_cursor`/`prev_cursor`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cursor: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
+    },
+
+    // Search game rooms; answered with a room list
+    #[serde(rename = "games.command.search_rooms")]
+    GameSearchRooms {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        game_type: Option<String>,
+        /// "waiting" or "in_progress"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        has_password: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        allow_spectators: Option<bool>,
+        /// Host username prefix
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host: Option<String>,
+        /// Text anywhere in the room name
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<String>,
+        /// "newest" (default) or "oldest"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sort: Option<String>,
+        /// Opaque cursor from a previous room list of the same search
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cursor: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
+    },
+
+    // Ready up in a waiting room
+    #[serde(rename = "games.command.ready")]
+    GameReady {
+        room_id: String,
+    },
+
+    // Report that the state the client rendered no longer matches the
+    // `state_checksum` of the last room_state or turn_changed; the server
+    // resends the room state
+    #[serde(rename = "games.command.report_desync")]
+    GameReportDesync {
+        room_id: String,
+        state_checksum: String,
+    },
+
+    // Ask for a full room state, after a room_state_diff that did not apply
+    // to the version the client holds
+    #[serde(rename = "games.command.get_room_state")]
+    GameGetRoomState {
+        #[serde(default)]
+        room_id: Option<String>,
+        #[serde(default)]
+        room_name: Option<String>,
+    },
+
+    // Rejoin a room after reconnection
+    #[serde(rename = "games.command.rejoin_room")]
+    GameRejoinRoom {
+        #[serde(default)]
+        room_id: Option<String>,
+        #[serde(default)]
+        room_name: Option<String>,
+    },
+
+    #[serde(rename = "system.heartbeat")]
+    Heartbeat,
+
+    #[serde(rename = "system.sync_state")]
+    SyncState,
+
+    // Chat commands
+    #[serde(rename = "chat.command.send_message")]
+    ChatSendMessage {
+        recipient_id: String,
+        content: String,
+    },
+
+    #[serde(rename = "chat.command.send_lobby_message")]
+    ChatSendLobbyMessage {
+        lobby_id: String,
+        content: String,
+    },
+
+    #[serde(rename = "chat.command.typing")]
+    ChatTyping {
+        recipient_id: String,
+    },
+
+    /// Acknowledge received direct messages; `receipt` defaults to `read`
+    #[serde(rename = "chat.command.mark_read")]
+    ChatMarkRead {
+        message_ids: Vec<String>,
+        #[serde(default)]
+        receipt: ChatReceipt,
+    },
+
+    #[serde(rename = "chat.command.channel_join")]
+    ChatChannelJoin {
+        channel_id: String,
+    },
+
+    #[serde(rename = "chat.command.channel_leave")]
+    ChatChannelLeave {
+        channel_id: String,
+    },
+
+    #[serde(rename = "chat.command.channel_send")]
+    ChatChannelSend {
+        channel_id: String,
+        content: String,
+    },
+
+    // Game commands
+    #[serde(rename = "games.command.create_room")]
+    GameCreateRoom {
+        game_type: String,
+        room_name: String,
+        #[serde(default)]
+        password: Option<String>,
+        #[serde(default)]
+        max_players: Option<i32>,
+        #[serde(default)]
+        allow_spectators: Option<bool>,
+        #[serde(default)]
+        max_spectators: Option<i32>,
+        /// Seconds per move (timed games only)
+        #[serde(default)]
+        turn_timer_seconds: Option<i64>,
+        /// Room preset key; explicit settings override the preset's values
+        #[serde(default)]
+        preset: Option<String>,
+        /// Practice room against a bot opponent
+        #[serde(default)]
+        vs_bot: Option<bool>,
+        /// Bot difficulty: easy, medium (default) or hard
+        #[serde(default)]
+        bot_difficulty: Option<String>,
+        /// RFC 3339 time the room opens at; the room is scheduled until then
+        #[serde(default)]
+        scheduled_start_at: Option<String>,
+        /// Bigger Dice rules (`rounds_to_win`, `dice_count`, `dice_sides`,
+        /// `tiebreaker`: "sudden_death" or "best_of", `tiebreaker_roll_offs`);
+        /// unset rules keep the defaults
+        #[serde(default)]
+        rules: Option<serde_json::Value>,
+    },
+
+    #[serde(rename = "games.command.join_room")]
+    GameJoinRoom {
+        room_name: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+
+    #[serde(rename = "games.command.leave_room")]
+    GameLeaveRoom {
+        room_id: String,
+    },
+
+    #[serde(rename = "games.command.spectate")]
+    GameSpectate {
+        room_id: String,
+    },
+
+    #[serde(rename = "games.command.stop_spectating")]
+    GameStopSpectating {
+        room_id: String,
+    },
+
+    /// Stake coins on a player winning (spectators only, while predictions are open)
+    #[serde(rename = "games.command.predict_winner")]
+    GamePredictWinner {
+        room_id: String,
+        predicted_winner_id: String,
+        stake_cents: i64,
+    },
+
+    /// Host pushes back the inactivity deadline of their waiting room
+    #[serde(rename = "games.command.extend_room")]
+    GameExtendRoom {
+        room_id: String,
+    },
+
+    /// Invite another user into a room the sender is in
+    #[serde(rename = "games.command.invite_user")]
+    GameInviteUser {
+        room_id: String,
+        user_id: String,
+    },
+
+    /// Accept an invitation and join its room (no password needed)
+    #[serde(rename = "games.command.accept_invite")]
+    GameAcceptInvite {
+        invite_id: String,
+    },
+
+    #[serde(rename = "games.command.decline_invite")]
+    GameDeclineInvite {
+        invite_id: String,
+    },
+
+    #[serde(rename = "games.command.player_chat")]
+    GamePlayerChat {
+        room_id: String,
+        content: String,
+    },
+
+    #[serde(rename = "games.command.spectator_chat")]
+    GameSpectatorChat {
+        room_id: String,
+        content: String,
+    },
+
+    // Admin/host commands for player management
+    #[serde(rename = "games.command.select_player")]
+    GameSelectPlayer {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    #[serde(rename = "games.command.kick_player")]
+    GameKickPlayer {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    #[serde(rename = "games.command.kick_spectator")]
+    GameKickSpectator {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    #[serde(rename = "games.command.vote_kick_disconnected")]
+    GameVoteKickDisconnected {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    #[serde(rename = "games.command.ban_player")]
+    GameBanPlayer {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    #[serde(rename = "games.command.unban_player")]
+    GameUnbanPlayer {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    // Bigger Dice specific commands
+    #[serde(rename = "games.command.bigger_dice.roll")]
+    BiggerDiceRoll {
+        room_id: String,
+    },
+
+    // Tic Tac Toe specific commands
+    #[serde(rename = "games.command.tic_tac_toe.move")]
+    TicTacToeMove {
+        room_id: String,
+        position: u8,
+    },
+
+    /// Auto-roll for a kicked player (frontend fallback when backend auto-roll fails)
+    #[serde(rename = "games.command.bigger_dice.auto_roll")]
+    BiggerDiceAutoRoll {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    /// Voluntarily enable auto-play for self
+    #[serde(rename = "games.command.bigger_dice.enable_auto_play")]
+    BiggerDiceEnableAutoPlay {
+        room_id: String,
+    },
+
+    // ========== Enhanced Game Room Commands ==========
+
+    /// Send a chat message to a channel (lobby, players, spectators)
+    #[serde(rename = "games.command.send_chat")]
+    GameSendChat {
+        room_id: String,
+        channel: String,
+        content: String,
+    },
+
+    /// Get chat history for a channel
+    #[serde(rename = "games.command.get_chat_history")]
+    GameGetChatHistory {
+        room_id: String,
+        channel: String,
+        #[serde(default)]
+        limit: Option<i64>,
+    },
+
+    /// Set ready status (toggle)
+    #[serde(rename = "games.command.set_ready")]
+    GameSetReady {
+        room_id: String,
+        is_ready: bool,
+    },
+
+    /// Host starts the game
+    #[serde(rename = "games.command.start_game")]
+    GameStartGame {
+        room_id: String,
+    },
+
+    /// Admin deselects a player (move back to lobby)
+    #[serde(rename = "games.command.deselect_player")]
+    GameDeselectPlayer {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    /// Host designates an admin spectator
+    #[serde(rename = "games.command.designate_admin_spectator")]
+    GameDesignateAdminSpectator {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    /// Join room as spectator
+    #[serde(rename = "games.command.join_as_spectator")]
+    GameJoinAsSpectator {
+        room_name: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+
+    /// Mute a user in chat
+    #[serde(rename = "games.command.mute_user")]
+    GameMuteUser {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    /// Unmute a user in chat
+    #[serde(rename = "games.command.unmute_user")]
+    GameUnmuteUser {
+        room_id: String,
+        target_user_id: String,
+    },
+
+    /// Host or admin spectator mutes a member in a chat channel ("lobby",
+    /// "players", "spectators"; every channel without one), for
+    /// `duration_seconds` or until unmuted
+    #[serde(rename = "games.command.chat_mute")]
+    GameChatMute {
+        room_id: String,
+        target_user_id: String,
+        #[serde(default)]
+        channel: Option<String>,
+        #[serde(default)]
+        duration_seconds: Option<i64>,
+    },
+
+    /// Host or admin spectator lifts a chat mute
+    #[serde(rename = "games.command.chat_unmute")]
+    GameChatUnmute {
+        room_id: String,
+        target_user_id: String,
+        #[serde(default)]
+        channel: Option<String>,
+    },
+}
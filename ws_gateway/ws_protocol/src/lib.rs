@@ -0,0 +1,163 @@
+//! Blazing Sun WebSocket Protocol
+//!
+//! Typed messages exchanged between clients and the WebSocket gateway. The gateway
+//! depends on this crate directly, so any Rust client (bots, load tests, tools)
+//! that uses it speaks exactly the protocol the gateway parses and emits.
+//!
+//! Every message is a JSON object tagged by its `type` field:
+//!
+//! ```
+//! use ws_protocol::{ClientMessage, ServerMessage};
+//!
+//! let command = ClientMessage::GameListRooms { game_type: None, cursor: None, limit: None };
+//! assert_eq!(command.to_json().unwrap(), r#"{"type":"games.command.list_rooms","game_type":null}"#);
+//!
+//! let event = ServerMessage::from_json(r#"{"type":"system.error","code":"bad","message":"nope"}"#).unwrap();
+//! assert_eq!(event.message_type(), "system.error");
+//! ```
+
+mod client;
+mod server;
+mod types;
+pub mod version;
+
+pub use client::ClientMessage;
+pub use server::ServerMessage;
+pub use types::{
+    BiggerDiceState, ChatReceipt, LobbyPlayer, PlayerInfo, RollResults, RoomInfo, Scores,
+};
+pub use version::{negotiate, Negotiation, LEGACY_VERSION, PROTOCOL_VERSION};
+
+/// Read the `type` tag from an already serialized message
+fn type_tag(value: &serde_json::Value) -> &str {
+    value.get("type").and_then(|t| t.as_str()).unwrap_or_default()
+}
+
+impl ClientMessage {
+    /// Parse a command received as a text frame
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Serialize a command into a text frame
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Wire name of the command (e.g. `games.command.ready`)
+    pub fn message_type(&self) -> String {
+        serde_json::to_value(self)
+            .map(|v| type_tag(&v).to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl ServerMessage {
+    /// Parse an event received as a text frame
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Serialize an event into a text frame
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Wire name of the event (e.g. `games.event.room_created`)
+    pub fn message_type(&self) -> String {
+        serde_json::to_value(self)
+            .map(|v| type_tag(&v).to_string())
+            .unwrap_or_default()
+    }
+
+    /// Build the welcome event a gateway sends right after the handshake
+    pub fn welcome(connection_id: impl Into<String>, min_protocol_version: u32) -> Self {
+        ServerMessage::Welcome {
+            connection_id: connection_id.into(),
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+}
+
+impl TryFrom<&str> for ClientMessage {
+    type Error = serde_json::Error;
+
+    fn try_from(json: &str) -> serde_json::Result<Self> {
+        Self::from_json(json)
+    }
+}
+
+impl TryFrom<&str> for ServerMessage {
+    type Error = serde_json::Error;
+
+    fn try_from(json: &str) -> serde_json::Result<Self> {
+        Self::from_json(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_message_round_trip() {
+        let json = r#"{"type":"system.authenticate","token":"abc"}"#;
+        let message = ClientMessage::from_json(json).unwrap();
+        assert_eq!(message.message_type(), "system.authenticate");
+
+        let reparsed = ClientMessage::from_json(&message.to_json().unwrap()).unwrap();
+        assert!(matches!(
+            reparsed,
+            ClientMessage::Authenticate { token: Some(t), .. } if t == "abc"
+        ));
+    }
+
+    #[test]
+    fn test_search_rooms_filters_are_optional() {
+        let json = r#"{"type":"games.command.search_rooms","has_password":false,"query":"friday"}"#;
+        let message = ClientMessage::from_json(json).unwrap();
+        match &message {
+            ClientMessage::GameSearchRooms { query, has_password, game_type, sort, .. } => {
+                assert_eq!(query.as_deref(), Some("friday"));
+                assert_eq!(*has_password, Some(false));
+                assert!(game_type.is_none() && sort.is_none());
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+        assert_eq!(message.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn test_welcome_carries_protocol_version() {
+        let json = ServerMessage::welcome("conn-1", LEGACY_VERSION).to_json().unwrap();
+        let parsed = ServerMessage::from_json(&json).unwrap();
+        match parsed {
+            ServerMessage::Welcome { connection_id, protocol_version, min_protocol_version, .. } => {
+                assert_eq!(connection_id, "conn-1");
+                assert_eq!(protocol_version, PROTOCOL_VERSION);
+                assert_eq!(min_protocol_version, LEGACY_VERSION);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_type_is_rejected() {
+        assert!(ServerMessage::from_json(r#"{"type":"system.nope"}"#).is_err());
+        assert!(ClientMessage::try_from(r#"{"type":"system.nope"}"#).is_err());
+    }
+
+    #[test]
+    fn test_every_message_is_snake_case() {
+        let violations = api_casing::contract::scan_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+        let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        assert!(violations.is_empty(), "{:#?}", violations);
+
+        api_casing::contract::assert_casing(
+            &ServerMessage::welcome("conn-1", PROTOCOL_VERSION),
+            api_casing::Casing::Snake,
+        );
+    }
+}
@@ -0,0 +1,1517 @@
+//! Server -> Client Messages (Events)
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{BiggerDiceState, LobbyPlayer, PlayerInfo, RoomInfo, RollResults, Scores};
+
+/// Outgoing message to client
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    // System events
+    #[serde(rename = "system.welcome")]
+    Welcome {
+        connection_id: String,
+        /// See [`crate::PROTOCOL_VERSION`]; older gateways omit it
+        #[serde(default)]
+        protocol_version: u32,
+        /// Oldest version the gateway still serves
+        #[serde(default)]
+        min_protocol_version: u32,
+        timestamp: DateTime<Utc>,
+    },
+
+    #[serde(rename = "system.authenticated")]
+    Authenticated {
+        user_id: String,
+        username: String,
+        roles: Vec<String>,
+        /// Version this connection's events are written in
+        #[serde(default)]
+        protocol_version: u32,
+        /// The user's display settings (theme, language, notification
+        /// sounds), when blazing_sun mirrored them; clients apply them
+        /// before any HTTP request
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        settings: Option<serde_json::Value>,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// The client's protocol version is served through compatibility shims
+    /// and will stop being supported; the client should be updated
+    #[serde(rename = "system.deprecation_warning")]
+    DeprecationWarning {
+        protocol_version: u32,
+        current_version: u32,
+        min_supported_version: u32,
+        message: String,
+    },
+
+    #[serde(rename = "system.heartbeat_ack")]
+    HeartbeatAck {
+        timestamp: DateTime<Utc>,
+    },
+
+    /// `retry_after_ms` is set on `RATE_LIMIT` errors of game commands: how
+    /// long until the command is accepted again
+    #[serde(rename = "system.error")]
+    Error {
+        code: String,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
+
+    /// The client sent messages faster than the rate limit; `penalty` is
+    /// "warning", "mute" (messages are dropped until `muted_until`) or
+    /// "disconnect" (the connection is closed after this message)
+    #[serde(rename = "system.rate_limited")]
+    RateLimited {
+        penalty: String,
+        strikes: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cooldown_secs: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        muted_until: Option<DateTime<Utc>>,
+        message: String,
+    },
+
+    /// Progress of a background job the user submitted (an export, an
+    /// erasure); `status` is "pending", "processing", "retrying", "completed"
+    /// or "failed", and `message` what the job is doing or why it failed
+    #[serde(rename = "system.job_progress")]
+    JobProgress {
+        job_id: String,
+        job_type: String,
+        status: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        updated_at: DateTime<Utc>,
+    },
+
+    /// Something the client fetched over REST changed and should be
+    /// re-fetched; `entity` is e.g. "user_profile", "balance" or
+    /// "transactions" and `id` the entity's ID
+    #[serde(rename = "cache.invalidate")]
+    CacheInvalidate {
+        entity: String,
+        id: String,
+        changed_at: DateTime<Utc>,
+    },
+
+    /// Message from the operators to every connected client; `level` is
+    /// "info" or "warning"
+    #[serde(rename = "system.announcement")]
+    Announcement {
+        message: String,
+        level: String,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Read-only maintenance started or ended; while `active`, reads keep
+    /// working but actions that change something are refused. Also sent right
+    /// after `system.authenticated` while maintenance is on.
+    #[serde(rename = "system.maintenance_notice")]
+    MaintenanceNotice {
+        active: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ends_at: Option<DateTime<Utc>>,
+        timestamp: DateTime<Utc>,
+    },
+
+    #[serde(rename = "system.reauth_required")]
+    ReauthRequired {
+        reason: String,
+    },
+
+    #[serde(rename = "system.state_snapshot")]
+    StateSnapshot {
+        active_rooms: Vec<String>,
+        game_states: serde_json::Value,
+        /// Unread direct messages across all conversations
+        unread_messages: u32,
+        /// Unread direct messages per conversation id
+        #[serde(default)]
+        unread_conversations: BTreeMap<String, u32>,
+    },
+
+    /// Consecutive high-frequency updates (state changes, presence, lobby
+    /// updates) coalesced into one frame; clients handle `messages` in order
+    /// as if each had arrived on its own
+    #[serde(rename = "system.batch")]
+    Batch {
+        messages: Vec<ServerMessage>,
+    },
+
+    // Chat events
+    /// A direct message, sent to the recipient and echoed to the sender
+    #[serde(rename = "chat.event.message_received")]
+    ChatMessageReceived {
+        message_id: String,
+        /// `dm:{lower user id}:{higher user id}`
+        #[serde(default)]
+        conversation_id: String,
+        sender_id: String,
+        sender_name: String,
+        #[serde(default)]
+        recipient_id: String,
+        content: String,
+        sent_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "chat.event.lobby_message")]
+    ChatLobbyMessage {
+        lobby_id: String,
+        message_id: String,
+        sender_id: String,
+        sender_name: String,
+        content: String,
+        sent_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "chat.event.message_rejected")]
+    ChatMessageRejected {
+        reason: String,
+        recipient_id: String,
+    },
+
+    #[serde(rename = "chat.event.typing")]
+    ChatTyping {
+        sender_id: String,
+        sender_name: String,
+    },
+
+    /// Sent to the sender when the recipient's client received their messages
+    #[serde(rename = "chat.event.message_delivered")]
+    ChatMessageDelivered {
+        conversation_id: String,
+        message_ids: Vec<String>,
+        recipient_id: String,
+        delivered_at: DateTime<Utc>,
+    },
+
+    /// Sent to the sender when the recipient read their messages
+    #[serde(rename = "chat.event.message_read")]
+    ChatMessageRead {
+        #[serde(default)]
+        conversation_id: String,
+        message_ids: Vec<String>,
+        reader_id: String,
+        #[serde(default = "Utc::now")]
+        read_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "chat.event.channel_joined")]
+    ChatChannelJoined {
+        channel_id: String,
+        channel_name: String,
+        last_read_message_id: Option<String>,
+    },
+
+    #[serde(rename = "chat.event.channel_left")]
+    ChatChannelLeft {
+        channel_id: String,
+    },
+
+    #[serde(rename = "chat.event.channel_message")]
+    ChatChannelMessage {
+        channel_id: String,
+        message_id: String,
+        sender_id: String,
+        sender_name: String,
+        sender_avatar_id: Option<i64>,
+        content: String,
+        sent_at: DateTime<Utc>,
+    },
+
+    // Game events
+    #[serde(rename = "games.event.room_created")]
+    GameRoomCreated {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        host_id: String,
+        host_name: String,
+        #[serde(default)]
+        is_password_protected: bool,
+        #[serde(default)]
+        player_count: i32,
+        #[serde(default)]
+        allow_spectators: bool,
+    },
+
+    #[serde(rename = "games.event.tic_tac_toe.room_created")]
+    TicTacToeRoomCreated {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        host_id: String,
+        host_name: String,
+        #[serde(default)]
+        is_password_protected: bool,
+        #[serde(default)]
+        player_count: i32,
+        #[serde(default)]
+        allow_spectators: bool,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.room_created")]
+    BiggerDiceRoomCreated {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        host_id: String,
+        host_name: String,
+        #[serde(default)]
+        is_password_protected: bool,
+        #[serde(default)]
+        player_count: i32,
+        #[serde(default)]
+        allow_spectators: bool,
+    },
+
+    #[serde(rename = "games.event.room_creation_failed")]
+    GameRoomCreationFailed {
+        reason: String,
+        room_name: String,
+    },
+
+    #[serde(rename = "games.event.player_joined")]
+    GamePlayerJoined {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.player_left")]
+    GamePlayerLeft {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    // Game-specific PlayerLeft variants
+    #[serde(rename = "games.event.tic_tac_toe.player_left")]
+    TicTacToePlayerLeft {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_left")]
+    BiggerDicePlayerLeft {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.player_disconnected")]
+    GamePlayerDisconnected {
+        room_id: String,
+        user_id: String,
+        username: String,
+        timeout_at: DateTime<Utc>,
+    },
+
+    // Game-specific PlayerDisconnected variants
+    #[serde(rename = "games.event.tic_tac_toe.player_disconnected")]
+    TicTacToePlayerDisconnected {
+        room_id: String,
+        user_id: String,
+        username: String,
+        timeout_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_disconnected")]
+    BiggerDicePlayerDisconnected {
+        room_id: String,
+        user_id: String,
+        username: String,
+        timeout_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "games.event.player_rejoined")]
+    GamePlayerRejoined {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    // Game-specific PlayerRejoined variants
+    #[serde(rename = "games.event.tic_tac_toe.player_rejoined")]
+    TicTacToePlayerRejoined {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_rejoined")]
+    BiggerDicePlayerRejoined {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.player_auto_enabled")]
+    GamePlayerAutoEnabled {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.player_auto_disabled")]
+    GamePlayerAutoDisabled {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    // Game-specific PlayerAutoEnabled variants
+    #[serde(rename = "games.event.bigger_dice.player_auto_enabled")]
+    BiggerDicePlayerAutoEnabled {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_auto_disabled")]
+    BiggerDicePlayerAutoDisabled {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.lobby_joined")]
+    GameLobbyJoined {
+        room_id: String,
+        player: LobbyPlayer,
+    },
+
+    // Game-specific LobbyJoined variants
+    #[serde(rename = "games.event.tic_tac_toe.lobby_joined")]
+    TicTacToeLobbyJoined {
+        room_id: String,
+        player: LobbyPlayer,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.lobby_joined")]
+    BiggerDiceLobbyJoined {
+        room_id: String,
+        player: LobbyPlayer,
+    },
+
+    #[serde(rename = "games.event.game_started")]
+    GameStarted {
+        room_id: String,
+        players: Vec<PlayerInfo>,
+        first_turn: String,
+        game_type: String,
+    },
+
+    #[serde(rename = "games.event.tic_tac_toe.game_started")]
+    TicTacToeGameStarted {
+        room_id: String,
+        players: Vec<PlayerInfo>,
+        first_turn: String,
+        game_type: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.game_started")]
+    BiggerDiceGameStarted {
+        room_id: String,
+        players: Vec<PlayerInfo>,
+        first_turn: String,
+        game_type: String,
+    },
+
+    #[serde(rename = "games.event.rooms_updated")]
+    GameRoomsUpdated {
+        game_type: String,
+        rooms: Vec<RoomInfo>,
+    },
+
+    #[serde(rename = "games.event.room_list")]
+    GameRoomList {
+        rooms: Vec<RoomInfo>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_cursor: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prev_cursor: Option<String>,
+    },
+
+    #[serde(rename = "games.event.room_removed")]
+    GameRoomRemoved {
+        room_id: String,
+        room_name: String,
+        reason: String, // "host_left", "game_finished", "abandoned"
+    },
+
+    #[serde(rename = "games.event.tic_tac_toe.room_removed")]
+    TicTacToeRoomRemoved {
+        room_id: String,
+        room_name: String,
+        reason: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.room_removed")]
+    BiggerDiceRoomRemoved {
+        room_id: String,
+        room_name: String,
+        reason: String,
+    },
+
+    #[serde(rename = "games.event.room_state")]
+    GameRoomState {
+        room: serde_json::Value,
+        /// Checksum of the room's canonical state; clients that compute a
+        /// different one send `games.command.report_desync`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+        /// Version of this client's copy of the room, the base of later diffs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<u64>,
+    },
+
+    #[serde(rename = "games.event.tic_tac_toe.room_state")]
+    TicTacToeRoomState {
+        room: serde_json::Value,
+        /// Checksum of the room's canonical state; clients that compute a
+        /// different one send `games.command.report_desync`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+        /// Version of this client's copy of the room, the base of later diffs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<u64>,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.room_state")]
+    BiggerDiceRoomState {
+        room: serde_json::Value,
+        /// Checksum of the room's canonical state; clients that compute a
+        /// different one send `games.command.report_desync`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+        /// Version of this client's copy of the room, the base of later diffs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<u64>,
+    },
+
+    /// Command targeted a room that was recently deleted or finished
+    #[serde(rename = "games.event.room_gone")]
+    GameRoomGone {
+        room_id: String,
+        room_name: String,
+        reason: String,
+    },
+
+    /// Waiting room closes for inactivity at `closes_at` unless someone acts
+    #[serde(rename = "games.event.room_inactivity_warning")]
+    GameRoomInactivityWarning {
+        room_id: String,
+        room_name: String,
+        closes_at: String,
+        closes_in_seconds: i64,
+    },
+
+    /// The host extended the room; it now closes at `closes_at` if left idle
+    #[serde(rename = "games.event.room_inactivity_extended")]
+    GameRoomInactivityExtended {
+        room_id: String,
+        closes_at: String,
+        extended_by: String,
+    },
+
+    /// The room is being closed ("inactive" or "closed_by_admin"); leave it
+    #[serde(rename = "games.event.room_closing")]
+    GameRoomClosing {
+        room_id: String,
+        room_name: String,
+        reason: String,
+    },
+
+    /// Join refused after too many wrong room passwords
+    #[serde(rename = "games.event.room_join_denied")]
+    GameRoomJoinDenied {
+        room_id: String,
+        room_name: String,
+        reason: String,
+        retry_after_seconds: u64,
+    },
+
+    /// Waiting room moved to another region; reconnect to `gateway_url` and rejoin
+    #[serde(rename = "games.event.room_migrated")]
+    GameRoomMigrated {
+        room_id: String,
+        room_name: String,
+        from_region: String,
+        to_region: String,
+        gateway_url: Option<String>,
+    },
+
+    /// Player/spectator counts or status of a room changed (for lobby room lists)
+    #[serde(rename = "games.event.room_occupancy_changed")]
+    GameRoomOccupancy {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        status: String,
+        player_count: i32,
+        max_players: i32,
+        spectator_count: i32,
+        max_spectators: i32,
+    },
+
+    /// Spectators can predict the winner until `closes_at`
+    #[serde(rename = "games.event.prediction_opened")]
+    GamePredictionOpened {
+        room_id: String,
+        closes_at: String,
+        min_stake_cents: i64,
+        max_stake_cents: i64,
+        candidates: serde_json::Value,
+    },
+
+    /// Prediction pools and odds changed
+    #[serde(rename = "games.event.prediction_pool_updated")]
+    GamePredictionPoolUpdated {
+        room_id: String,
+        total_pool_cents: i64,
+        candidates: serde_json::Value,
+    },
+
+    #[serde(rename = "games.event.prediction_closed")]
+    GamePredictionClosed {
+        room_id: String,
+        total_pool_cents: i64,
+        candidates: serde_json::Value,
+    },
+
+    /// Payouts after the game ended; `winner_id` is None when stakes were refunded
+    #[serde(rename = "games.event.prediction_settled")]
+    GamePredictionSettled {
+        room_id: String,
+        winner_id: Option<String>,
+        total_pool_cents: i64,
+        payouts: serde_json::Value,
+    },
+
+    /// A tournament round's match rooms are ready (sent to the round's players)
+    #[serde(rename = "games.event.tournament_round_started")]
+    GameTournamentRoundStarted {
+        tournament_id: i64,
+        tournament_name: String,
+        game_type: String,
+        round: i32,
+        rounds: i32,
+        matches: serde_json::Value,
+    },
+
+    /// The final was played (sent to every entrant)
+    #[serde(rename = "games.event.tournament_finished")]
+    GameTournamentFinished {
+        tournament_id: i64,
+        tournament_name: String,
+        winner_id: String,
+        prize_pool_cents: i64,
+        payouts: serde_json::Value,
+    },
+
+    /// The room was created to open at `scheduled_start_at` (sent to the host)
+    #[serde(rename = "games.event.room_scheduled")]
+    GameRoomScheduled {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        scheduled_start_at: String,
+    },
+
+    /// A scheduled room the user registered for opens in `minutes_before` minutes
+    #[serde(rename = "games.event.scheduled_room_reminder")]
+    GameScheduledRoomReminder {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        scheduled_start_at: String,
+        minutes_before: i64,
+    },
+
+    /// A scheduled room the user registered for can be joined now
+    #[serde(rename = "games.event.scheduled_room_opened")]
+    GameScheduledRoomOpened {
+        room_id: String,
+        room_name: String,
+        game_type: String,
+    },
+
+    /// Another player invited the user into their room until `expires_at`
+    #[serde(rename = "games.event.invite_received")]
+    GameInviteReceived {
+        invite_id: String,
+        room_id: String,
+        room_name: String,
+        game_type: String,
+        inviter_id: String,
+        inviter_username: String,
+        is_password_protected: bool,
+        /// Seats of the game
+        player_count: i32,
+        /// Users already in the lobby or seated
+        joined_count: i32,
+        expires_at: String,
+    },
+
+    /// An invitation the user sent was accepted, declined or expired (sent to the inviter)
+    #[serde(rename = "games.event.invite_answered")]
+    GameInviteAnswered {
+        invite_id: String,
+        room_id: String,
+        invitee_id: String,
+        invitee_username: String,
+        status: String,
+    },
+
+    #[serde(rename = "games.event.not_in_room")]
+    GameNotInRoom {
+        room_id: String,
+        room_name: String,
+        is_password_protected: bool,
+        status: String,
+        #[serde(default)]
+        allow_spectators: bool,
+    },
+
+    // Game-specific NotInRoom variants
+    #[serde(rename = "games.event.tic_tac_toe.not_in_room")]
+    TicTacToeNotInRoom {
+        room_id: String,
+        room_name: String,
+        is_password_protected: bool,
+        status: String,
+        #[serde(default)]
+        allow_spectators: bool,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.not_in_room")]
+    BiggerDiceNotInRoom {
+        room_id: String,
+        room_name: String,
+        is_password_protected: bool,
+        status: String,
+        #[serde(default)]
+        allow_spectators: bool,
+    },
+
+    #[serde(rename = "games.event.spectator_joined")]
+    GameSpectatorJoined {
+        room_id: String,
+        spectator_id: String,
+        spectator_name: String,
+        spectator_count: u32,
+    },
+
+    // Game-specific SpectatorJoined variants
+    #[serde(rename = "games.event.tic_tac_toe.spectator_joined")]
+    TicTacToeSpectatorJoined {
+        room_id: String,
+        spectator_id: String,
+        spectator_name: String,
+        spectator_count: u32,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.spectator_joined")]
+    BiggerDiceSpectatorJoined {
+        room_id: String,
+        spectator_id: String,
+        spectator_name: String,
+        spectator_count: u32,
+    },
+
+    /// Spectator joined with full data (new format)
+    #[serde(rename = "games.event.spectator_data_joined")]
+    GameSpectatorDataJoined {
+        room_id: String,
+        spectator: serde_json::Value,
+    },
+
+    // Game-specific SpectatorDataJoined variants
+    #[serde(rename = "games.event.bigger_dice.spectator_data_joined")]
+    BiggerDiceSpectatorDataJoined {
+        room_id: String,
+        spectator: serde_json::Value,
+    },
+
+    #[serde(rename = "games.event.tic_tac_toe.spectator_data_joined")]
+    TicTacToeSpectatorDataJoined {
+        room_id: String,
+        spectator: serde_json::Value,
+    },
+
+    #[serde(rename = "games.event.spectator_left")]
+    GameSpectatorLeft {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    // Game-specific SpectatorLeft variants
+    #[serde(rename = "games.event.tic_tac_toe.spectator_left")]
+    TicTacToeSpectatorLeft {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.spectator_left")]
+    BiggerDiceSpectatorLeft {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    /// Place in a full room's spectator waiting list (1 = next to be admitted)
+    #[serde(rename = "games.event.spectator_waitlist_position")]
+    GameSpectatorWaitlistPosition {
+        room_id: String,
+        room_name: String,
+        position: u32,
+        waitlist_size: u32,
+        /// The place is given up unless the user asks to spectate again before this
+        expires_at: String,
+    },
+
+    #[serde(rename = "games.event.spectator_kicked")]
+    GameSpectatorKicked {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    // Game-specific SpectatorKicked variants
+    #[serde(rename = "games.event.tic_tac_toe.spectator_kicked")]
+    TicTacToeSpectatorKicked {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.spectator_kicked")]
+    BiggerDiceSpectatorKicked {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.player_chat_message")]
+    GamePlayerChatMessage {
+        room_id: String,
+        sender_id: String,
+        sender_name: String,
+        content: String,
+        sent_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "games.event.spectator_chat_message")]
+    GameSpectatorChatMessage {
+        room_id: String,
+        sender_id: String,
+        sender_name: String,
+        content: String,
+        sent_at: DateTime<Utc>,
+    },
+
+    #[serde(rename = "games.event.player_selected")]
+    GamePlayerSelected {
+        room_id: String,
+        player: LobbyPlayer,
+    },
+
+    // Game-specific PlayerSelected variants
+    #[serde(rename = "games.event.tic_tac_toe.player_selected")]
+    TicTacToePlayerSelected {
+        room_id: String,
+        player: LobbyPlayer,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_selected")]
+    BiggerDicePlayerSelected {
+        room_id: String,
+        player: LobbyPlayer,
+    },
+
+    #[serde(rename = "games.event.turn_changed")]
+    GameTurnChanged {
+        room_id: String,
+        current_turn: String,
+        turn_number: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+    },
+
+    // Game-specific TurnChanged variants
+    #[serde(rename = "games.event.tic_tac_toe.turn_changed")]
+    TicTacToeTurnChanged {
+        room_id: String,
+        current_turn: String,
+        turn_number: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.turn_changed")]
+    BiggerDiceTurnChanged {
+        room_id: String,
+        current_turn: String,
+        turn_number: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+    },
+
+    #[serde(rename = "games.event.player_ready")]
+    GamePlayerReady {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    // Game-specific PlayerReady variants
+    #[serde(rename = "games.event.tic_tac_toe.player_ready")]
+    TicTacToePlayerReady {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_ready")]
+    BiggerDicePlayerReady {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.removed_from_game")]
+    GameRemovedFromGame {
+        room_id: String,
+        reason: String,
+        message: String,
+    },
+
+    // Game-specific RemovedFromGame variants
+    #[serde(rename = "games.event.tic_tac_toe.removed_from_game")]
+    TicTacToeRemovedFromGame {
+        room_id: String,
+        reason: String,
+        message: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.removed_from_game")]
+    BiggerDiceRemovedFromGame {
+        room_id: String,
+        reason: String,
+        message: String,
+    },
+
+    #[serde(rename = "games.event.game_starting")]
+    GameGameStarting {
+        room_id: String,
+        players: serde_json::Value,
+    },
+
+    // Game-specific GameStarting variants
+    #[serde(rename = "games.event.tic_tac_toe.game_starting")]
+    TicTacToeGameStarting {
+        room_id: String,
+        players: serde_json::Value,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.game_starting")]
+    BiggerDiceGameStarting {
+        room_id: String,
+        players: serde_json::Value,
+    },
+
+    /// N-player round result
+    #[serde(rename = "games.event.bigger_dice.round_result")]
+    BiggerDiceRoundResult {
+        room_id: String,
+        /// All rolls for this round: (player_id, roll)
+        rolls: Vec<(String, i32)>,
+        /// Winner if exactly one player had highest roll
+        winner_id: Option<String>,
+        /// True if multiple players tied for highest
+        is_tie: bool,
+        /// True if this was a tiebreaker round
+        is_tiebreaker: bool,
+        /// Players going to tiebreaker (only set when is_tie=true)
+        tiebreaker_players: Vec<String>,
+        /// Authoritative scores for all players after this round: (player_id, score)
+        scores: Vec<(String, i32)>,
+    },
+
+    /// Tiebreaker started event for N-player Bigger Dice
+    #[serde(rename = "games.event.bigger_dice.tiebreaker_started")]
+    BiggerDiceTiebreakerStarted {
+        room_id: String,
+        /// Players participating in the tiebreaker
+        tied_players: Vec<String>,
+        /// The roll value they all tied on
+        tied_roll: i32,
+    },
+
+    /// Current round state for N-player Bigger Dice (for rejoin/sync)
+    #[serde(rename = "games.event.bigger_dice.state")]
+    BiggerDiceState {
+        room_id: String,
+        /// Current round number
+        round_number: i32,
+        /// Rolls so far this round: (player_id, roll)
+        current_rolls: Vec<(String, i32)>,
+        /// Players who still need to roll
+        pending_rollers: Vec<String>,
+        /// Whether we are in a tiebreaker
+        is_tiebreaker: bool,
+    },
+
+    #[serde(rename = "games.event.player_kicked")]
+    GamePlayerKicked {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    // Game-specific PlayerKicked variants
+    #[serde(rename = "games.event.tic_tac_toe.player_kicked")]
+    TicTacToePlayerKicked {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_kicked")]
+    BiggerDicePlayerKicked {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.player_banned")]
+    GamePlayerBanned {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    // Game-specific PlayerBanned variants
+    #[serde(rename = "games.event.tic_tac_toe.player_banned")]
+    TicTacToePlayerBanned {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_banned")]
+    BiggerDicePlayerBanned {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.player_unbanned")]
+    GamePlayerUnbanned {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    // Game-specific PlayerUnbanned variants
+    #[serde(rename = "games.event.tic_tac_toe.player_unbanned")]
+    TicTacToePlayerUnbanned {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_unbanned")]
+    BiggerDicePlayerUnbanned {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+    },
+
+    // Bigger Dice specific events
+    #[serde(rename = "games.event.bigger_dice.rolled")]
+    BiggerDiceRolled {
+        room_id: String,
+        player_id: String,
+        player_name: String,
+        roll: u8,
+        is_first_roll: bool,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.round_complete")]
+    BiggerDiceRoundComplete {
+        room_id: String,
+        round: u8,
+        rolls: RollResults,
+        winner: Option<String>,
+        winner_name: Option<String>,
+        scores: Scores,
+        next_turn: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.game_over")]
+    BiggerDiceGameOver {
+        room_id: String,
+        winner: String,
+        winner_name: String,
+        final_scores: Scores,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.state_sync")]
+    BiggerDiceStateSync {
+        room_id: String,
+        state: BiggerDiceState,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.your_turn")]
+    BiggerDiceYourTurn {
+        room_id: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.waiting_for_opponent")]
+    BiggerDiceWaitingForOpponent {
+        room_id: String,
+        opponent_name: String,
+    },
+
+    // ========== Tic Tac Toe Events ==========
+
+    /// Move made on the board
+    #[serde(rename = "games.event.tic_tac_toe.moved")]
+    TicTacToeMoved {
+        room_id: String,
+        player_id: String,
+        player_username: String,
+        position: u8,
+        mark: char,
+        board: Vec<Option<char>>,
+    },
+
+    /// Single game ended within match
+    #[serde(rename = "games.event.tic_tac_toe.game_result")]
+    TicTacToeGameResult {
+        room_id: String,
+        winner_id: Option<String>,
+        winner_username: Option<String>,
+        winning_line: Option<Vec<u8>>,
+        is_draw: bool,
+        scores: Vec<(String, i32)>,
+        game_number: i32,
+        next_first_player: String,
+    },
+
+    /// Match ended (first to 5 wins)
+    #[serde(rename = "games.event.tic_tac_toe.match_ended")]
+    TicTacToeMatchEnded {
+        room_id: String,
+        winner_id: String,
+        winner_username: String,
+        final_scores: Vec<(String, String, i32)>,
+        prize_amount: i64,
+    },
+
+    /// Full state sync (for rejoin/spectators)
+    #[serde(rename = "games.event.tic_tac_toe.state")]
+    TicTacToeState {
+        room_id: String,
+        board: Vec<Option<char>>,
+        player_x_id: String,
+        player_o_id: String,
+        current_turn: String,
+        scores: Vec<(String, i32)>,
+        game_number: i32,
+        move_deadline: Option<String>,
+        is_paused: bool,
+        disconnected_player: Option<String>,
+    },
+
+    /// Player forfeited game due to turn timeout
+    #[serde(rename = "games.event.tic_tac_toe.turn_timeout")]
+    TicTacToeTurnTimeout {
+        room_id: String,
+        player_id: String,
+        player_username: String,
+        winner_id: String,
+        winner_username: String,
+        scores: Vec<(String, i32)>,
+        game_number: i32,
+    },
+
+    /// Match cancelled (both players disconnected > 10 min)
+    #[serde(rename = "games.event.tic_tac_toe.match_cancelled")]
+    TicTacToeMatchCancelled {
+        room_id: String,
+        reason: String,
+        refund_amount: i64,
+    },
+
+    /// Game paused due to player disconnect
+    #[serde(rename = "games.event.tic_tac_toe.game_paused")]
+    TicTacToeGamePaused {
+        room_id: String,
+        disconnected_player_id: String,
+        disconnected_player_username: String,
+        timeout_at: String,
+    },
+
+    /// Game resumed after player reconnect
+    #[serde(rename = "games.event.tic_tac_toe.game_resumed")]
+    TicTacToeGameResumed {
+        room_id: String,
+        reconnected_player_id: String,
+        reconnected_player_username: String,
+    },
+
+    // User presence events
+    #[serde(rename = "presence.event.user_online")]
+    UserOnline {
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "presence.event.user_offline")]
+    UserOffline {
+        user_id: String,
+        username: String,
+    },
+
+    /// Something the user asked to be told about (a payment, a game invite, a
+    /// chat mention); only sent to users who get that category over WebSocket
+    #[serde(rename = "notification.event.received")]
+    Notification {
+        notification_id: String,
+        category: String,
+        title: String,
+        body: String,
+        #[serde(default)]
+        data: serde_json::Value,
+        created_at: DateTime<Utc>,
+    },
+
+    /// The user unlocked an achievement (first win, games played, money
+    /// spent); `badge` names the icon to show
+    #[serde(rename = "achievement.event.unlocked")]
+    AchievementUnlocked {
+        achievement_id: i64,
+        code: String,
+        name: String,
+        description: String,
+        badge: String,
+        unlocked_at: DateTime<Utc>,
+    },
+
+    /// The Stripe session of a balance top-up requested through the Kafka
+    /// checkout flow is ready; sent when the API request stopped waiting for it
+    #[serde(rename = "checkout.event.session_ready")]
+    CheckoutSessionReady {
+        request_id: String,
+        session_id: String,
+        url: String,
+        #[serde(default)]
+        expires_at: Option<DateTime<Utc>>,
+    },
+
+    /// No Stripe session could be opened for a Kafka checkout request
+    #[serde(rename = "checkout.event.session_failed")]
+    CheckoutSessionFailed { request_id: String, message: String },
+
+    // ========== Enhanced Game Room Events ==========
+
+    /// Chat message received
+    #[serde(rename = "games.event.chat_message")]
+    GameChatMessage {
+        room_id: String,
+        channel: String,
+        user_id: String,
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        avatar_id: Option<String>,
+        content: String,
+        is_system: bool,
+        timestamp: String,
+    },
+
+    // Game-specific ChatMessage variants
+    #[serde(rename = "games.event.tic_tac_toe.chat_message")]
+    TicTacToeChatMessage {
+        room_id: String,
+        channel: String,
+        user_id: String,
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        avatar_id: Option<String>,
+        content: String,
+        is_system: bool,
+        timestamp: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.chat_message")]
+    BiggerDiceChatMessage {
+        room_id: String,
+        channel: String,
+        user_id: String,
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        avatar_id: Option<String>,
+        content: String,
+        is_system: bool,
+        timestamp: String,
+    },
+
+    /// Chat history response
+    #[serde(rename = "games.event.chat_history")]
+    GameChatHistory {
+        room_id: String,
+        channel: String,
+        messages: Vec<serde_json::Value>,
+    },
+
+    // Game-specific ChatHistory variants
+    #[serde(rename = "games.event.tic_tac_toe.chat_history")]
+    TicTacToeChatHistory {
+        room_id: String,
+        channel: String,
+        messages: Vec<serde_json::Value>,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.chat_history")]
+    BiggerDiceChatHistory {
+        room_id: String,
+        channel: String,
+        messages: Vec<serde_json::Value>,
+    },
+
+    // ========== Bigger Dice Channel-Specific Chat Events ==========
+
+    /// Bigger Dice lobby chat message
+    #[serde(rename = "games.event.bigger_dice.lobby_chat")]
+    BiggerDiceLobbyChat {
+        room_id: String,
+        user_id: String,
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        avatar_id: Option<String>,
+        content: String,
+        is_system: bool,
+        timestamp: String,
+    },
+
+    /// Bigger Dice player chat message
+    #[serde(rename = "games.event.bigger_dice.player_chat")]
+    BiggerDicePlayerChat {
+        room_id: String,
+        user_id: String,
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        avatar_id: Option<String>,
+        content: String,
+        is_system: bool,
+        timestamp: String,
+    },
+
+    /// Bigger Dice spectator chat message
+    #[serde(rename = "games.event.bigger_dice.spectator_chat")]
+    BiggerDiceSpectatorChat {
+        room_id: String,
+        user_id: String,
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        avatar_id: Option<String>,
+        content: String,
+        is_system: bool,
+        timestamp: String,
+    },
+
+    /// Bigger Dice lobby chat history
+    #[serde(rename = "games.event.bigger_dice.lobby_chat_history")]
+    BiggerDiceLobbyChatHistory {
+        room_id: String,
+        messages: Vec<serde_json::Value>,
+    },
+
+    /// Bigger Dice player chat history
+    #[serde(rename = "games.event.bigger_dice.player_chat_history")]
+    BiggerDicePlayerChatHistory {
+        room_id: String,
+        messages: Vec<serde_json::Value>,
+    },
+
+    /// Bigger Dice spectator chat history
+    #[serde(rename = "games.event.bigger_dice.spectator_chat_history")]
+    BiggerDiceSpectatorChatHistory {
+        room_id: String,
+        messages: Vec<serde_json::Value>,
+    },
+
+    /// Player ready status changed
+    #[serde(rename = "games.event.player_ready_changed")]
+    GamePlayerReadyChanged {
+        room_id: String,
+        user_id: String,
+        username: String,
+        is_ready: bool,
+    },
+
+    // Game-specific PlayerReadyChanged variants
+    #[serde(rename = "games.event.tic_tac_toe.player_ready_changed")]
+    TicTacToePlayerReadyChanged {
+        room_id: String,
+        user_id: String,
+        username: String,
+        is_ready: bool,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_ready_changed")]
+    BiggerDicePlayerReadyChanged {
+        room_id: String,
+        user_id: String,
+        username: String,
+        is_ready: bool,
+    },
+
+    /// Player was deselected
+    #[serde(rename = "games.event.player_deselected")]
+    GamePlayerDeselected {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    // Game-specific PlayerDeselected variants
+    #[serde(rename = "games.event.tic_tac_toe.player_deselected")]
+    TicTacToePlayerDeselected {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.player_deselected")]
+    BiggerDicePlayerDeselected {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    /// Selected players list updated
+    #[serde(rename = "games.event.selected_players_updated")]
+    GameSelectedPlayersUpdated {
+        room_id: String,
+        selected_players: Vec<String>,
+    },
+
+    // Game-specific SelectedPlayersUpdated variants
+    #[serde(rename = "games.event.tic_tac_toe.selected_players_updated")]
+    TicTacToeSelectedPlayersUpdated {
+        room_id: String,
+        selected_players: Vec<String>,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.selected_players_updated")]
+    BiggerDiceSelectedPlayersUpdated {
+        room_id: String,
+        selected_players: Vec<String>,
+    },
+
+    /// Admin spectator designated
+    #[serde(rename = "games.event.admin_spectator_designated")]
+    GameAdminSpectatorDesignated {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+
+    /// User muted
+    #[serde(rename = "games.event.user_muted")]
+    GameUserMuted {
+        room_id: String,
+        target_user_id: String,
+        target_username: String,
+    },
+
+    /// User unmuted
+    #[serde(rename = "games.event.user_unmuted")]
+    GameUserUnmuted {
+        room_id: String,
+        target_user_id: String,
+        target_username: String,
+    },
+
+    /// Changes of a room since the room state this client holds: JSON-patch
+    /// style `ops` (`add`/`remove`/`replace` with RFC 6901 paths) from
+    /// `base_version` to `version`. A client holding another version sends
+    /// `games.command.get_room_state` instead of applying them
+    #[serde(rename = "games.event.room_state_diff")]
+    GameRoomStateDiff {
+        room_id: String,
+        base_version: u64,
+        version: u64,
+        ops: Vec<serde_json::Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_checksum: Option<String>,
+    },
+
+    /// A member was muted in a chat channel (`channel` null = every channel)
+    /// by the host or admin spectator, until `expires_at` (null = until unmuted)
+    #[serde(rename = "games.event.chat_muted")]
+    GameChatMuted {
+        room_id: String,
+        user_id: String,
+        username: String,
+        channel: Option<String>,
+        muted_by: String,
+        expires_at: Option<String>,
+    },
+
+    /// A chat mute was lifted, or ran out (`expired`)
+    #[serde(rename = "games.event.chat_unmuted")]
+    GameChatUnmuted {
+        room_id: String,
+        user_id: String,
+        username: String,
+        channel: Option<String>,
+        expired: bool,
+    },
+
+    /// Spectators list updated
+    #[serde(rename = "games.event.spectators_updated")]
+    GameSpectatorsUpdated {
+        room_id: String,
+        spectators: Vec<serde_json::Value>,
+    },
+
+    /// Lobby list updated
+    #[serde(rename = "games.event.lobby_updated")]
+    GameLobbyUpdated {
+        room_id: String,
+        lobby: Vec<serde_json::Value>,
+    },
+
+    // Game-specific LobbyUpdated variants
+    #[serde(rename = "games.event.tic_tac_toe.lobby_updated")]
+    TicTacToeLobbyUpdated {
+        room_id: String,
+        lobby: Vec<serde_json::Value>,
+    },
+
+    #[serde(rename = "games.event.bigger_dice.lobby_updated")]
+    BiggerDiceLobbyUpdated {
+        room_id: String,
+        lobby: Vec<serde_json::Value>,
+    },
+}
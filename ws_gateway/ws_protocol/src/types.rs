@@ -0,0 +1,85 @@
+//! Supporting types embedded in commands and events
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Receipt a recipient sends for direct messages
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatReceipt {
+    /// The messages reached one of the recipient's clients
+    Delivered,
+    /// The recipient opened the conversation (implies delivered)
+    #[default]
+    Read,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LobbyPlayer {
+    pub user_id: String,
+    pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_id: Option<String>,
+    #[serde(default)]
+    pub score: u32,
+    #[serde(default)]
+    pub is_ready: bool,
+    /// Player stats for the room's game type (games played, wins, streaks, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoomInfo {
+    pub room_id: String,
+    pub room_name: String,
+    pub game_type: String,
+    pub host_name: String,
+    pub status: String, // "waiting" | "playing" | "finished"
+    pub player_count: u8,
+    pub spectator_count: u32,
+    #[serde(default)]
+    pub is_password_protected: bool,
+    #[serde(default)]
+    pub players: Vec<Value>,
+    #[serde(default)]
+    pub lobby: Vec<Value>,
+    #[serde(default)]
+    pub max_players: u8,
+    #[serde(default)]
+    pub allow_spectators: bool,
+    #[serde(default)]
+    pub can_rejoin: bool,
+    #[serde(default)]
+    pub rejoin_role: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RollResults {
+    pub player1: Option<u8>,
+    pub player2: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Scores {
+    pub player1_id: String,
+    pub player1_score: u8,
+    pub player2_id: String,
+    pub player2_score: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BiggerDiceState {
+    pub players: Vec<PlayerInfo>,
+    pub scores: Scores,
+    pub current_turn: String,
+    pub round: u8,
+    pub last_rolls: RollResults,
+    pub phase: String, // "waiting_for_roll" | "waiting_for_second_roll" | "round_complete"
+}
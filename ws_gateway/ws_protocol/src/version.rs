@@ -0,0 +1,473 @@
+//! Protocol versions and compatibility shims
+//!
+//! The gateway announces [`PROTOCOL_VERSION`] and the oldest version it still
+//! serves in `system.welcome`; clients answer with the version they were built
+//! for in `system.authenticate`. Builds that predate negotiation send no
+//! version and are treated as [`LEGACY_VERSION`].
+//!
+//! Events are always built in the current shape. Before one is written to a
+//! client on an older version, the registry below rewrites it one version at
+//! a time, newest change first: messages introduced after the client's version
+//! are withheld and changed messages get the shape that version expects.
+//!
+//! When changing a message's shape, bump [`PROTOCOL_VERSION`] and add a
+//! [`VersionChange`] whose `downgrade` turns the new shape into the previous one.
+
+use serde_json::{Map, Value};
+
+use crate::ServerMessage;
+
+/// Version announced in `system.welcome`
+pub const PROTOCOL_VERSION: u32 = 9;
+
+/// Version of clients that do not announce one
+pub const LEGACY_VERSION: u32 = 1;
+
+/// What changed in a protocol version
+pub struct VersionChange {
+    pub version: u32,
+    pub summary: &'static str,
+    /// Events added in this version; clients on older versions never get them
+    pub introduced: &'static [&'static str],
+    /// Rewrite an event from this version's shape into the previous version's
+    pub downgrade: fn(&mut Map<String, Value>),
+}
+
+/// Every version after [`LEGACY_VERSION`], oldest first
+pub const CHANGES: &[VersionChange] = &[
+    VersionChange {
+        version: 2,
+        summary: "Room lists are paged with cursors; room states and turn changes carry state checksums; predictions, tournaments, chat channels, room lifecycle events, scheduled rooms, notifications, flood penalties, job progress, spectator waiting lists, operator announcements, cache invalidation hints, unlocked achievements and late checkout sessions",
+        introduced: &[
+            "achievement.event.unlocked",
+            "cache.invalidate",
+            "checkout.event.session_failed",
+            "checkout.event.session_ready",
+            "chat.event.channel_joined",
+            "chat.event.channel_left",
+            "chat.event.channel_message",
+            "games.event.prediction_opened",
+            "games.event.prediction_pool_updated",
+            "games.event.prediction_closed",
+            "games.event.prediction_settled",
+            "games.event.room_closing",
+            "games.event.room_gone",
+            "games.event.room_inactivity_extended",
+            "games.event.room_inactivity_warning",
+            "games.event.room_join_denied",
+            "games.event.room_migrated",
+            "games.event.room_occupancy_changed",
+            "games.event.room_scheduled",
+            "games.event.scheduled_room_opened",
+            "games.event.scheduled_room_reminder",
+            "games.event.spectator_waitlist_position",
+            "games.event.tournament_round_started",
+            "games.event.tournament_finished",
+            "notification.event.received",
+            "system.announcement",
+            "system.job_progress",
+            "system.rate_limited",
+        ],
+        downgrade: downgrade_to_v1,
+    },
+    VersionChange {
+        version: 3,
+        summary: "High-frequency state, presence and lobby updates may arrive coalesced in system.batch",
+        introduced: &["system.batch"],
+        downgrade: downgrade_to_v2,
+    },
+    VersionChange {
+        version: 4,
+        summary: "Direct messages carry conversation ids and delivered/read receipts; state snapshots count unread messages per conversation",
+        introduced: &["chat.event.message_delivered"],
+        downgrade: downgrade_to_v3,
+    },
+    VersionChange {
+        version: 5,
+        summary: "Players can invite each other into rooms and answer invitations",
+        introduced: &["games.event.invite_answered", "games.event.invite_received"],
+        downgrade: downgrade_to_v4,
+    },
+    VersionChange {
+        version: 6,
+        summary: "Clients are told when read-only maintenance starts and ends",
+        introduced: &["system.maintenance_notice"],
+        downgrade: downgrade_to_v5,
+    },
+    VersionChange {
+        version: 7,
+        summary: "Hosts and admin spectators mute members per chat channel, with expiry; room states list the active chat mutes",
+        introduced: &["games.event.chat_muted", "games.event.chat_unmuted"],
+        downgrade: downgrade_to_v6,
+    },
+    VersionChange {
+        version: 8,
+        summary: "Room states are versioned per client and later changes arrive as room_state_diff patches",
+        introduced: &["games.event.room_state_diff"],
+        downgrade: downgrade_to_v7,
+    },
+    VersionChange {
+        version: 9,
+        summary: "Authenticated carries the user's display settings for instant hydration",
+        introduced: &[],
+        downgrade: downgrade_to_v8,
+    },
+];
+
+/// Version 1 room lists were a single, complete list, and room states and
+/// turn changes carried no checksum
+fn downgrade_to_v1(message: &mut Map<String, Value>) {
+    let message_type = message.get("type").and_then(Value::as_str).unwrap_or_default();
+    if message_type == "games.event.room_list" {
+        message.remove("next_cursor");
+        message.remove("prev_cursor");
+    } else if message_type.ends_with("room_state") || message_type.ends_with("turn_changed") {
+        message.remove("state_checksum");
+    }
+}
+
+/// Version 3 only added `system.batch`, which older clients are never sent
+fn downgrade_to_v2(_message: &mut Map<String, Value>) {}
+
+/// Version 3 direct messages had no conversation ids or receipt times, and
+/// state snapshots only had the total unread count
+fn downgrade_to_v3(message: &mut Map<String, Value>) {
+    match message.get("type").and_then(Value::as_str).unwrap_or_default() {
+        "chat.event.message_received" => {
+            message.remove("conversation_id");
+            message.remove("recipient_id");
+        }
+        "chat.event.message_read" => {
+            message.remove("conversation_id");
+            message.remove("read_at");
+        }
+        "system.state_snapshot" => {
+            message.remove("unread_conversations");
+        }
+        _ => {}
+    }
+}
+
+/// Version 5 only added invitation events, which older clients are never sent
+fn downgrade_to_v4(_message: &mut Map<String, Value>) {}
+
+/// Version 6 only added maintenance notices, which older clients are never sent
+fn downgrade_to_v5(_message: &mut Map<String, Value>) {}
+
+/// Version 6 room states had no chat mutes
+fn downgrade_to_v6(message: &mut Map<String, Value>) {
+    let message_type = message.get("type").and_then(Value::as_str).unwrap_or_default();
+    if message_type.ends_with("room_state") {
+        if let Some(Value::Object(room)) = message.get_mut("room") {
+            room.remove("chat_mutes");
+        }
+    }
+}
+
+/// Version 7 room states had no version
+fn downgrade_to_v7(message: &mut Map<String, Value>) {
+    let message_type = message.get("type").and_then(Value::as_str).unwrap_or_default();
+    if message_type.ends_with("room_state") {
+        message.remove("version");
+    }
+}
+
+/// Version 8 authenticated responses had no settings
+fn downgrade_to_v8(message: &mut Map<String, Value>) {
+    if message.get("type").and_then(Value::as_str) == Some("system.authenticated") {
+        message.remove("settings");
+    }
+}
+
+/// Outcome of a client announcing its protocol version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Negotiation {
+    /// The client speaks the current version (newer clients are served the current one)
+    Current,
+    /// Still served through compatibility shims, but the client should upgrade
+    Deprecated { version: u32 },
+    /// Older than the oldest version the gateway serves
+    Unsupported { version: u32 },
+}
+
+impl Negotiation {
+    /// Version the connection's events are written in, if it is served at all
+    pub fn version(self) -> Option<u32> {
+        match self {
+            Negotiation::Current => Some(PROTOCOL_VERSION),
+            Negotiation::Deprecated { version } => Some(version),
+            Negotiation::Unsupported { .. } => None,
+        }
+    }
+}
+
+/// Settle the version a client is served in
+pub fn negotiate(requested: Option<u32>, min_supported: u32) -> Negotiation {
+    let version = requested.unwrap_or(LEGACY_VERSION).max(LEGACY_VERSION);
+
+    if version >= PROTOCOL_VERSION {
+        Negotiation::Current
+    } else if version < min_supported {
+        Negotiation::Unsupported { version }
+    } else {
+        Negotiation::Deprecated { version }
+    }
+}
+
+/// Rewrite a serialized event for a client on `version`; `None` when that
+/// version does not know the event
+pub fn downgrade(mut message: Value, version: u32) -> Option<Value> {
+    let Some(object) = message.as_object_mut() else {
+        return Some(message);
+    };
+
+    if object.get("type").and_then(Value::as_str) == Some("system.batch") {
+        if let Some(Value::Array(messages)) = object.get_mut("messages") {
+            let inner = std::mem::take(messages);
+            *messages = inner
+                .into_iter()
+                .filter_map(|message| downgrade(message, version))
+                .collect();
+        }
+    }
+
+    for change in CHANGES.iter().rev().filter(|change| change.version > version) {
+        let message_type = object.get("type").and_then(Value::as_str).unwrap_or_default();
+        if change.introduced.contains(&message_type) {
+            return None;
+        }
+        (change.downgrade)(object);
+    }
+
+    Some(message)
+}
+
+impl ServerMessage {
+    /// Serialize an event for a client on protocol `version`; `Ok(None)` when
+    /// that version does not know the event
+    pub fn to_json_for(&self, version: u32) -> serde_json::Result<Option<String>> {
+        if version >= PROTOCOL_VERSION {
+            return self.to_json().map(Some);
+        }
+
+        let value = serde_json::to_value(self)?;
+        downgrade(value, version)
+            .map(|value| serde_json::to_string(&value))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RoomInfo;
+
+    fn room_list() -> ServerMessage {
+        ServerMessage::GameRoomList {
+            rooms: Vec::<RoomInfo>::new(),
+            next_cursor: Some("n1".to_string()),
+            prev_cursor: None,
+        }
+    }
+
+    #[test]
+    fn clients_without_a_version_are_legacy() {
+        assert_eq!(negotiate(None, 1), Negotiation::Deprecated { version: LEGACY_VERSION });
+        assert_eq!(negotiate(Some(0), 1), Negotiation::Deprecated { version: LEGACY_VERSION });
+        assert_eq!(negotiate(None, 2), Negotiation::Unsupported { version: LEGACY_VERSION });
+    }
+
+    #[test]
+    fn current_and_newer_clients_get_the_current_version() {
+        assert_eq!(negotiate(Some(PROTOCOL_VERSION), 1), Negotiation::Current);
+        assert_eq!(negotiate(Some(PROTOCOL_VERSION + 3), 1).version(), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate(Some(1), 2).version(), None);
+    }
+
+    #[test]
+    fn changed_messages_are_shimmed_for_old_clients() {
+        let current = room_list().to_json_for(PROTOCOL_VERSION).unwrap().unwrap();
+        assert!(current.contains("next_cursor"));
+
+        let legacy = room_list().to_json_for(1).unwrap().unwrap();
+        let legacy: Value = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(legacy, serde_json::json!({ "type": "games.event.room_list", "rooms": [] }));
+    }
+
+    #[test]
+    fn state_checksums_are_dropped_for_old_clients() {
+        let turn = ServerMessage::BiggerDiceTurnChanged {
+            room_id: "r1".to_string(),
+            current_turn: "42".to_string(),
+            turn_number: 3,
+            state_checksum: Some("0a1b2c3d4e5f6071".to_string()),
+        };
+        assert!(turn.to_json_for(2).unwrap().unwrap().contains("state_checksum"));
+        assert!(!turn.to_json_for(1).unwrap().unwrap().contains("state_checksum"));
+    }
+
+    #[test]
+    fn newer_messages_are_withheld_from_old_clients() {
+        let denied = ServerMessage::GameRoomJoinDenied {
+            room_id: "r1".to_string(),
+            room_name: "Room".to_string(),
+            reason: "too_many_attempts".to_string(),
+            retry_after_seconds: 30,
+        };
+        assert!(denied.to_json_for(1).unwrap().is_none());
+        assert!(denied.to_json_for(2).unwrap().is_some());
+
+        let error = ServerMessage::Error {
+            code: "bad".to_string(),
+            message: "nope".to_string(),
+            retry_after_ms: None,
+        };
+        assert!(error.to_json_for(1).unwrap().is_some());
+    }
+
+    #[test]
+    fn batches_are_only_sent_to_clients_that_know_them() {
+        let batch = ServerMessage::Batch {
+            messages: vec![room_list(), room_list()],
+        };
+        assert!(batch.to_json_for(2).unwrap().is_none());
+
+        let current: Value =
+            serde_json::from_str(&batch.to_json_for(PROTOCOL_VERSION).unwrap().unwrap()).unwrap();
+        assert_eq!(current["type"], "system.batch");
+        assert_eq!(current["messages"][1]["next_cursor"], "n1");
+    }
+
+    #[test]
+    fn direct_message_receipts_are_shimmed_for_v3_clients() {
+        let delivered = ServerMessage::ChatMessageDelivered {
+            conversation_id: "dm:7:9".to_string(),
+            message_ids: vec!["m1".to_string()],
+            recipient_id: "9".to_string(),
+            delivered_at: chrono::Utc::now(),
+        };
+        assert!(delivered.to_json_for(3).unwrap().is_none());
+        assert!(delivered.to_json_for(PROTOCOL_VERSION).unwrap().is_some());
+
+        let read = ServerMessage::ChatMessageRead {
+            conversation_id: "dm:7:9".to_string(),
+            message_ids: vec!["m1".to_string()],
+            reader_id: "9".to_string(),
+            read_at: chrono::Utc::now(),
+        };
+        let v3: Value = serde_json::from_str(&read.to_json_for(3).unwrap().unwrap()).unwrap();
+        assert_eq!(
+            v3,
+            serde_json::json!({
+                "type": "chat.event.message_read",
+                "message_ids": ["m1"],
+                "reader_id": "9",
+            })
+        );
+
+        let snapshot = ServerMessage::StateSnapshot {
+            active_rooms: vec![],
+            game_states: serde_json::json!({}),
+            unread_messages: 3,
+            unread_conversations: [("dm:7:9".to_string(), 3)].into_iter().collect(),
+        };
+        let v3 = snapshot.to_json_for(3).unwrap().unwrap();
+        assert!(v3.contains("\"unread_messages\":3"));
+        assert!(!v3.contains("unread_conversations"));
+    }
+
+    #[test]
+    fn invitations_are_only_sent_to_v5_clients() {
+        let answered = ServerMessage::GameInviteAnswered {
+            invite_id: "i1".to_string(),
+            room_id: "r1".to_string(),
+            invitee_id: "9".to_string(),
+            invitee_username: "bob".to_string(),
+            status: "declined".to_string(),
+        };
+        assert!(answered.to_json_for(4).unwrap().is_none());
+        let current = answered.to_json_for(PROTOCOL_VERSION).unwrap().unwrap();
+        assert!(current.contains("\"type\":\"games.event.invite_answered\""));
+    }
+
+    #[test]
+    fn maintenance_notices_are_only_sent_to_v6_clients() {
+        let notice = ServerMessage::MaintenanceNotice {
+            active: false,
+            message: None,
+            ends_at: None,
+            timestamp: chrono::Utc::now(),
+        };
+        assert!(notice.to_json_for(5).unwrap().is_none());
+        let current = notice.to_json_for(PROTOCOL_VERSION).unwrap().unwrap();
+        assert!(current.contains("\"type\":\"system.maintenance_notice\""));
+        assert!(!current.contains("ends_at"));
+    }
+
+    #[test]
+    fn chat_mutes_are_only_sent_to_v7_clients() {
+        let muted = ServerMessage::GameChatMuted {
+            room_id: "r1".to_string(),
+            user_id: "42".to_string(),
+            username: "player42".to_string(),
+            channel: Some("players".to_string()),
+            muted_by: "7".to_string(),
+            expires_at: None,
+        };
+        assert!(muted.to_json_for(6).unwrap().is_none());
+        assert!(muted.to_json_for(PROTOCOL_VERSION).unwrap().is_some());
+
+        let state = ServerMessage::GameRoomState {
+            room: serde_json::json!({ "room_id": "r1", "chat_mutes": [{ "user_id": 42 }] }),
+            state_checksum: None,
+            version: None,
+        };
+        assert!(state.to_json_for(PROTOCOL_VERSION).unwrap().unwrap().contains("chat_mutes"));
+        let v6: Value = serde_json::from_str(&state.to_json_for(6).unwrap().unwrap()).unwrap();
+        assert_eq!(v6["room"], serde_json::json!({ "room_id": "r1" }));
+    }
+
+    #[test]
+    fn state_diffs_are_only_sent_to_v8_clients() {
+        let diff = ServerMessage::GameRoomStateDiff {
+            room_id: "r1".to_string(),
+            base_version: 3,
+            version: 4,
+            ops: vec![serde_json::json!({ "op": "replace", "path": "/status", "value": "in_progress" })],
+            state_checksum: None,
+        };
+        assert!(diff.to_json_for(7).unwrap().is_none());
+        assert!(diff.to_json_for(PROTOCOL_VERSION).unwrap().is_some());
+
+        let state = ServerMessage::BiggerDiceRoomState {
+            room: serde_json::json!({ "room_id": "r1" }),
+            state_checksum: None,
+            version: Some(4),
+        };
+        assert!(state.to_json_for(PROTOCOL_VERSION).unwrap().unwrap().contains("\"version\":4"));
+        assert!(!state.to_json_for(7).unwrap().unwrap().contains("version"));
+    }
+
+    #[test]
+    fn settings_are_only_sent_to_v9_clients() {
+        let authenticated = ServerMessage::Authenticated {
+            user_id: "42".to_string(),
+            username: "player42".to_string(),
+            roles: vec![],
+            protocol_version: 8,
+            settings: Some(serde_json::json!({ "theme": "dark" })),
+            timestamp: chrono::Utc::now(),
+        };
+        let current: Value =
+            serde_json::from_str(&authenticated.to_json_for(PROTOCOL_VERSION).unwrap().unwrap()).unwrap();
+        assert_eq!(current["settings"]["theme"], "dark");
+        assert!(!authenticated.to_json_for(8).unwrap().unwrap().contains("settings"));
+    }
+
+    #[test]
+    fn registry_is_ordered_and_ends_at_the_current_version() {
+        assert!(CHANGES.windows(2).all(|pair| pair[0].version < pair[1].version));
+        assert_eq!(CHANGES.last().map(|change| change.version), Some(PROTOCOL_VERSION));
+        assert!(CHANGES.iter().all(|change| change.version > LEGACY_VERSION));
+    }
+}